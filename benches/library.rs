@@ -0,0 +1,102 @@
+//! Scan and query timings on a synthetic library, for comparing runs over
+//! time. The ignored `perf_` tests hold the budgets CI fails on; these
+//! show how far from them a change moves things.
+//!
+//! `cargo bench --features test-utils` (`MUSIC_MINDER_PERF_TRACKS=20000`
+//! for a smaller library).
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::time::{Duration, Instant};
+
+use music_minder::tasks::{TaskHandle, TaskKind};
+use music_minder::test_utils::{
+    insert_synthetic_library, perf_library_size, synthetic_library, temp_db, write_synthetic_files,
+};
+use music_minder::{completeness, db, library};
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Runtime::new().expect("Failed to start runtime")
+}
+
+fn queries(c: &mut Criterion) {
+    let rt = runtime();
+    let n = perf_library_size();
+    let (pool, _dir) = rt.block_on(async {
+        let (pool, dir) = temp_db().await;
+        insert_synthetic_library(&pool, &synthetic_library(n, 1)).await;
+        (pool, dir)
+    });
+    let last_page = (n as i64 - 500).max(0);
+    let month_ago = chrono::Utc::now().timestamp() - 30 * 86_400;
+
+    let mut group = c.benchmark_group(format!("queries/{}", n));
+    group.sample_size(10);
+    group.bench_function("load library", |b| {
+        b.to_async(&rt)
+            .iter(|| db::get_all_tracks_with_metadata(&pool))
+    });
+    group.bench_function("count tracks", |b| {
+        b.to_async(&rt).iter(|| db::count_tracks(&pool))
+    });
+    group.bench_function("last page", |b| {
+        b.to_async(&rt)
+            .iter(|| db::get_tracks_paginated(&pool, 500, last_page))
+    });
+    group.bench_function("added in the last month", |b| {
+        b.to_async(&rt)
+            .iter(|| db::get_tracks_added_since(&pool, month_ago))
+    });
+    group.bench_function("file info for rescans", |b| {
+        b.to_async(&rt).iter(|| db::get_all_track_file_info(&pool))
+    });
+    group.bench_function("album listing", |b| {
+        b.to_async(&rt).iter(|| completeness::library_albums(&pool))
+    });
+    group.finish();
+}
+
+fn scan(c: &mut Criterion) {
+    let rt = runtime();
+    // Files are slower to set up than rows: a tenth of the library
+    let n = perf_library_size() / 10;
+    let files = tempfile::tempdir().expect("Failed to create temp directory");
+    write_synthetic_files(files.path(), &synthetic_library(n, 1));
+    let task = TaskHandle::detached(TaskKind::Scan, "Scan");
+
+    let mut group = c.benchmark_group(format!("scan/{}", n));
+    group.sample_size(10);
+    // Each run starts from an empty database; only the scan is timed
+    group.bench_function("first scan", |b| {
+        b.to_async(&rt).iter_custom(|iters| {
+            let (root, task) = (files.path(), &task);
+            async move {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    let (pool, _dir) = temp_db().await;
+                    let started = Instant::now();
+                    library::incremental_scan(&pool, root, task)
+                        .await
+                        .expect("Scan failed");
+                    total += started.elapsed();
+                }
+                total
+            }
+        })
+    });
+
+    let (pool, _dir) = rt.block_on(async {
+        let (pool, dir) = temp_db().await;
+        library::incremental_scan(&pool, files.path(), &task)
+            .await
+            .expect("Scan failed");
+        (pool, dir)
+    });
+    group.bench_function("rescan, nothing changed", |b| {
+        b.to_async(&rt)
+            .iter(|| library::incremental_scan(&pool, files.path(), &task))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, queries, scan);
+criterion_main!(benches);
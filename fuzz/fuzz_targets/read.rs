@@ -0,0 +1,28 @@
+//! Arbitrary bytes through the tag reader, named as each supported format.
+//! The first byte picks the extension; the rest is the file.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use music_minder::metadata;
+
+const EXTENSIONS: [&str; 5] = ["mp3", "flac", "ogg", "m4a", "wav"];
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&pick, bytes)) = data.split_first() else {
+        return;
+    };
+    let extension = EXTENSIONS[pick as usize % EXTENSIONS.len()];
+    let path = std::env::temp_dir().join(format!(
+        "music-minder-fuzz-{}.{}",
+        std::process::id(),
+        extension
+    ));
+    if std::fs::write(&path, bytes).is_err() {
+        return;
+    }
+    // Errors are fine; a panic or hang is what this is looking for
+    let _ = metadata::read(&path);
+    let _ = metadata::read_full(&path);
+    let _ = std::fs::remove_file(&path);
+});
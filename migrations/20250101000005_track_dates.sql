@@ -0,0 +1,9 @@
+-- Add date added / date modified columns to tracks
+-- Both store Unix timestamps; existing rows are backfilled from file
+-- creation/modification times when the database is opened
+
+ALTER TABLE tracks ADD COLUMN added_at INTEGER;
+ALTER TABLE tracks ADD COLUMN updated_at INTEGER;
+
+-- Index for "recently added" filters and smart playlists
+CREATE INDEX IF NOT EXISTS idx_tracks_added_at ON tracks(added_at);
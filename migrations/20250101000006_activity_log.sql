@@ -0,0 +1,59 @@
+-- Library change feed
+-- Append-only record of everything the app changed, so users can answer
+-- "what happened to my library last Tuesday?"
+
+CREATE TABLE IF NOT EXISTS activity_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL,  -- Unix timestamp
+    kind TEXT NOT NULL,  -- 'track_added', 'track_removed', 'tags_written', 'file_organized', 'recording_matched'
+    track_id INTEGER,  -- No foreign key: entries outlive the tracks they describe
+    path TEXT NOT NULL,
+    detail TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_log_timestamp ON activity_log(timestamp);
+
+-- The log is append-only
+CREATE TRIGGER IF NOT EXISTS activity_log_no_update
+BEFORE UPDATE ON activity_log
+BEGIN
+    SELECT RAISE(ABORT, 'activity_log is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS activity_log_no_delete
+BEFORE DELETE ON activity_log
+BEGIN
+    SELECT RAISE(ABORT, 'activity_log is append-only');
+END;
+
+-- Track lifecycle is recorded here so every insert/delete/move path is covered
+CREATE TRIGGER IF NOT EXISTS activity_track_added
+AFTER INSERT ON tracks
+BEGIN
+    INSERT INTO activity_log (timestamp, kind, track_id, path, detail)
+    VALUES (unixepoch(), 'track_added', NEW.id, NEW.path, NEW.title);
+END;
+
+CREATE TRIGGER IF NOT EXISTS activity_track_removed
+AFTER DELETE ON tracks
+BEGIN
+    INSERT INTO activity_log (timestamp, kind, track_id, path, detail)
+    VALUES (unixepoch(), 'track_removed', OLD.id, OLD.path, OLD.title);
+END;
+
+CREATE TRIGGER IF NOT EXISTS activity_file_organized
+AFTER UPDATE OF path ON tracks
+WHEN OLD.path <> NEW.path
+BEGIN
+    INSERT INTO activity_log (timestamp, kind, track_id, path, detail)
+    VALUES (unixepoch(), 'file_organized', NEW.id, NEW.path, 'from ' || OLD.path);
+END;
+
+CREATE TRIGGER IF NOT EXISTS activity_recording_matched
+AFTER UPDATE OF musicbrainz_recording_id ON tracks
+WHEN NEW.musicbrainz_recording_id IS NOT NULL
+    AND NEW.musicbrainz_recording_id IS NOT OLD.musicbrainz_recording_id
+BEGIN
+    INSERT INTO activity_log (timestamp, kind, track_id, path, detail)
+    VALUES (unixepoch(), 'recording_matched', NEW.id, NEW.path, NEW.musicbrainz_recording_id);
+END;
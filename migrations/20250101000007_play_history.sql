@@ -0,0 +1,12 @@
+-- Playback history
+-- One row each time a library track starts playing; backs "recently played"
+-- and the resume card shown on startup
+
+CREATE TABLE IF NOT EXISTS play_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    track_id INTEGER NOT NULL REFERENCES tracks(id) ON DELETE CASCADE,
+    played_at INTEGER NOT NULL  -- Unix timestamp
+);
+
+CREATE INDEX IF NOT EXISTS idx_play_history_played_at ON play_history(played_at);
+CREATE INDEX IF NOT EXISTS idx_play_history_track ON play_history(track_id);
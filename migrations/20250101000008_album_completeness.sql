@@ -0,0 +1,17 @@
+-- Album completeness
+-- Result of comparing an album's tracks against its MusicBrainz release
+-- tracklist, so "9 of 11 tracks" can be shown without a lookup each time
+
+CREATE TABLE IF NOT EXISTS album_completeness (
+    album_id INTEGER PRIMARY KEY REFERENCES albums(id) ON DELETE CASCADE,
+    release_id TEXT NOT NULL,      -- MusicBrainz release compared against
+    release_title TEXT NOT NULL,
+    total_tracks INTEGER NOT NULL, -- Tracks on the release
+    owned_tracks INTEGER NOT NULL, -- Release tracks found in the library
+    missing_tracks TEXT NOT NULL,  -- JSON array of {disc, position, title}
+    checked_at INTEGER NOT NULL    -- Unix timestamp
+);
+
+CREATE INDEX IF NOT EXISTS idx_album_completeness_missing
+ON album_completeness(album_id)
+WHERE owned_tracks < total_tracks;
@@ -0,0 +1,6 @@
+-- Inferred track numbers
+-- Set when a scan guessed the track number from the file name or folder
+-- order because the file's tags had none; cleared once a scan reads the
+-- number from tags
+
+ALTER TABLE tracks ADD COLUMN track_number_inferred BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,14 @@
+-- Scheduled maintenance runs
+-- One row per finished scheduler job (scan, quality re-check, backup, verify);
+-- backs the last-run/next-run status and per-job log in Settings
+
+CREATE TABLE IF NOT EXISTS job_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    job TEXT NOT NULL,              -- Job key: scan, gardener, backup, verify
+    started_at INTEGER NOT NULL,    -- Unix timestamp
+    finished_at INTEGER NOT NULL,   -- Unix timestamp
+    success BOOLEAN NOT NULL,
+    summary TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_runs_job ON job_runs(job, id);
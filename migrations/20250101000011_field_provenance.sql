@@ -0,0 +1,23 @@
+-- Tag field provenance
+-- Which service (or person) last wrote each tag field, how confident the
+-- match was, and when; backs the track detail tooltips and the
+-- "machine-written" library filter. Fields never written by the app have no
+-- row: their values came with the file.
+
+CREATE TABLE IF NOT EXISTS field_provenance (
+    track_id INTEGER NOT NULL,
+    field TEXT NOT NULL,         -- Tag field: title, artist, album, year, ...
+    source TEXT NOT NULL,        -- 'acoustid', 'musicbrainz', 'manual', 'inferred', 'plan'
+    confidence REAL,             -- Match score 0.0-1.0 for identified values
+    written_at INTEGER NOT NULL, -- Unix timestamp
+    PRIMARY KEY (track_id, field)
+);
+
+CREATE INDEX IF NOT EXISTS idx_field_provenance_field ON field_provenance(field, source);
+
+-- Provenance goes with the track
+CREATE TRIGGER IF NOT EXISTS field_provenance_track_removed
+AFTER DELETE ON tracks
+BEGIN
+    DELETE FROM field_provenance WHERE track_id = OLD.id;
+END;
@@ -0,0 +1,25 @@
+-- Tag conflicts
+-- Values re-enrichment wanted to write over a field the user set by hand.
+-- They wait here for review instead of being written. A conflict the user
+-- answered "keep mine" stays, marked kept, so the same suggestion isn't
+-- raised again; a different suggestion reopens it.
+
+CREATE TABLE IF NOT EXISTS tag_conflicts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    track_id INTEGER NOT NULL,
+    field TEXT NOT NULL,            -- Tag field, as in field_provenance
+    current_value TEXT,             -- The hand-set value in the file
+    proposed_value TEXT NOT NULL,   -- What the new match would write
+    source TEXT NOT NULL,           -- 'acoustid', 'musicbrainz'
+    confidence REAL,                -- Match score 0.0-1.0
+    created_at INTEGER NOT NULL,    -- Unix timestamp
+    kept INTEGER NOT NULL DEFAULT 0, -- 1 once the user kept their value
+    UNIQUE (track_id, field)
+);
+
+-- Conflicts go with the track
+CREATE TRIGGER IF NOT EXISTS tag_conflicts_track_removed
+AFTER DELETE ON tracks
+BEGIN
+    DELETE FROM tag_conflicts WHERE track_id = OLD.id;
+END;
@@ -0,0 +1,8 @@
+-- Loudness
+-- ReplayGain values read from the file's tags (R128 converted to the
+-- ReplayGain reference). Gains in dB, peaks linear; NULL when untagged.
+
+ALTER TABLE tracks ADD COLUMN replaygain_track_gain REAL;
+ALTER TABLE tracks ADD COLUMN replaygain_track_peak REAL;
+ALTER TABLE tracks ADD COLUMN replaygain_album_gain REAL;
+ALTER TABLE tracks ADD COLUMN replaygain_album_peak REAL;
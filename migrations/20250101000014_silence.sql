@@ -0,0 +1,9 @@
+-- Silence
+-- Silent stretches at the start and end of each track, measured by the
+-- silence maintenance job. NULL until analyzed; silence_checked_at is set
+-- even when the file couldn't be decoded, so it isn't retried every run.
+-- A changed file is analyzed again.
+
+ALTER TABLE tracks ADD COLUMN leading_silence_ms INTEGER;
+ALTER TABLE tracks ADD COLUMN trailing_silence_ms INTEGER;
+ALTER TABLE tracks ADD COLUMN silence_checked_at INTEGER;
@@ -0,0 +1,8 @@
+-- Ratings and play counts
+-- Imported from the tags other players wrote (POPM, FMPS_RATING,
+-- FMPS_PLAYCOUNT, RATING) during scans. Ratings are 0-100, 20 per star;
+-- NULL when no tag has one. tag_play_count only grows, and is counted on
+-- top of this app's own plays in play_history.
+
+ALTER TABLE tracks ADD COLUMN rating INTEGER;
+ALTER TABLE tracks ADD COLUMN tag_play_count INTEGER;
@@ -0,0 +1,9 @@
+-- Language and explicit content
+-- Lyrics language as an ISO 639-2 code ('zxx' = instrumental), from the
+-- tags or MusicBrainz work data, and the advisory rating: 1 explicit,
+-- 0 clean, NULL when the tags don't say.
+
+ALTER TABLE tracks ADD COLUMN language TEXT;
+ALTER TABLE tracks ADD COLUMN explicit INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_tracks_language ON tracks(language);
@@ -0,0 +1,12 @@
+-- Per-folder enrichment defaults
+-- Applied to tracks under `folder` (the deepest matching folder wins) when
+-- they are added to the Enrich pane or identified by the background agent.
+-- min_confidence NULL means the usual threshold.
+
+CREATE TABLE IF NOT EXISTS folder_enrichment (
+    folder TEXT PRIMARY KEY,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    fill_only INTEGER NOT NULL DEFAULT 0,
+    min_confidence REAL,
+    auto_write INTEGER NOT NULL DEFAULT 0
+);
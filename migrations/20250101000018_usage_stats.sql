@@ -0,0 +1,27 @@
+-- Local usage statistics
+-- How long each play actually lasted, identification outcomes and scan
+-- durations, for the Stats pane. Nothing here is sent anywhere.
+
+-- Seconds listened (seeking doesn't count) and the genre tag at the time;
+-- NULL for plays recorded before this and for the one still playing
+ALTER TABLE play_history ADD COLUMN listened_secs INTEGER;
+ALTER TABLE play_history ADD COLUMN genre TEXT;
+
+-- One row per track identification tried, from the app, CLI or agent
+CREATE TABLE IF NOT EXISTS identification_attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    attempted_at INTEGER NOT NULL,  -- Unix timestamp
+    outcome TEXT NOT NULL           -- 'identified', 'no_match', 'error'
+);
+
+CREATE INDEX IF NOT EXISTS idx_identification_attempts_at ON identification_attempts(attempted_at);
+
+-- One row per finished library scan
+CREATE TABLE IF NOT EXISTS scan_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    started_at INTEGER NOT NULL,    -- Unix timestamp
+    duration_ms INTEGER NOT NULL,
+    files INTEGER NOT NULL          -- Files read (new or changed)
+);
+
+CREATE INDEX IF NOT EXISTS idx_scan_runs_started_at ON scan_runs(started_at);
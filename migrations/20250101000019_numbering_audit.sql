@@ -0,0 +1,6 @@
+-- Album numbering audit
+-- Set on every track of an album whose track totals, track numbering or
+-- disc tags failed the last numbering audit (see library::numbering);
+-- cleared when the album is audited again and passes
+
+ALTER TABLE tracks ADD COLUMN numbering_inconsistent BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,8 @@
+-- Track path keys
+-- The canonical, case-folded form of each track's path (see db::paths), so
+-- D:\Music\x.mp3 and d:\music\X.MP3 are one track. Filled in, and duplicate
+-- rows merged, when the database is next opened.
+
+ALTER TABLE tracks ADD COLUMN path_key TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tracks_path_key ON tracks(path_key);
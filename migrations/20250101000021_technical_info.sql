@@ -0,0 +1,11 @@
+-- Technical info cache
+-- What the track detail's Technical tab shows (codec profile, encoder,
+-- true peak, tag sizes; see metadata::technical), as JSON. Probing decodes
+-- the whole file, so the result is kept until the file's mtime changes.
+
+CREATE TABLE IF NOT EXISTS technical_info (
+    track_id INTEGER PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    mtime INTEGER NOT NULL,         -- File mtime when probed (Unix timestamp)
+    info TEXT NOT NULL,             -- metadata::technical::TechnicalInfo as JSON
+    probed_at INTEGER NOT NULL      -- Unix timestamp
+);
@@ -0,0 +1,28 @@
+-- Tag write audit
+-- What each logged tag write did to the file (see metadata::audit): tag
+-- blocks and size before and after, whether the tags outgrew their padding
+-- so the audio moved, and whether the saved file read back with the same
+-- audio properties. Append-only, like the activity entry it belongs to.
+
+CREATE TABLE IF NOT EXISTS tag_write_audit (
+    activity_id INTEGER PRIMARY KEY REFERENCES activity_log(id),
+    tags_before TEXT NOT NULL,      -- Tag blocks, e.g. "ID3v2.3, ID3v1"
+    tags_after TEXT NOT NULL,
+    size_before INTEGER NOT NULL,   -- Bytes
+    size_after INTEGER NOT NULL,
+    padding_rewritten BOOLEAN NOT NULL,
+    verified BOOLEAN NOT NULL,
+    verify_note TEXT                -- What the read-back found, when it failed
+);
+
+CREATE TRIGGER IF NOT EXISTS tag_write_audit_no_update
+BEFORE UPDATE ON tag_write_audit
+BEGIN
+    SELECT RAISE(ABORT, 'tag_write_audit is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS tag_write_audit_no_delete
+BEFORE DELETE ON tag_write_audit
+BEGIN
+    SELECT RAISE(ABORT, 'tag_write_audit is append-only');
+END;
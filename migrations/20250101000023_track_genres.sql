@@ -0,0 +1,6 @@
+-- Track genres
+-- Every genre in the track's tags, joined with '; ' (see metadata::genres),
+-- after the genre rules (tagging.genre_map). NULL until the file has been
+-- read since this column was added; '' when the tags have no genre.
+
+ALTER TABLE tracks ADD COLUMN genre TEXT;
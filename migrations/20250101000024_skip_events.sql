@@ -0,0 +1,5 @@
+-- Skip events
+-- Whether a play moved on before 30% of the track, for per-track skip
+-- rates in the Stats pane and the "Hide skipped" library filter
+
+ALTER TABLE play_history ADD COLUMN skipped INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,64 @@
+-- Album rollups
+-- Track count, total duration and bitrate totals kept on each album, so
+-- album views don't aggregate over every track. Triggers keep them in step
+-- with every insert, delete and change of a track's album, duration or
+-- bitrate, whichever code path makes it.
+
+-- Audio bitrate in kbps; NULL until the file has been read since this
+-- column was added
+ALTER TABLE tracks ADD COLUMN bitrate INTEGER;
+
+ALTER TABLE albums ADD COLUMN track_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE albums ADD COLUMN total_duration INTEGER NOT NULL DEFAULT 0;  -- Seconds
+-- Average bitrate is bitrate_sum / bitrate_tracks, over the tracks with one
+ALTER TABLE albums ADD COLUMN bitrate_sum INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE albums ADD COLUMN bitrate_tracks INTEGER NOT NULL DEFAULT 0;
+
+UPDATE albums SET
+    track_count = (SELECT COUNT(*) FROM tracks t WHERE t.album_id = albums.id),
+    total_duration = (SELECT COALESCE(SUM(t.duration), 0) FROM tracks t WHERE t.album_id = albums.id);
+
+CREATE TRIGGER IF NOT EXISTS album_rollup_track_added
+AFTER INSERT ON tracks
+WHEN NEW.album_id IS NOT NULL
+BEGIN
+    UPDATE albums SET
+        track_count = track_count + 1,
+        total_duration = total_duration + COALESCE(NEW.duration, 0),
+        bitrate_sum = bitrate_sum + COALESCE(NEW.bitrate, 0),
+        bitrate_tracks = bitrate_tracks + (NEW.bitrate IS NOT NULL)
+    WHERE id = NEW.album_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS album_rollup_track_removed
+AFTER DELETE ON tracks
+WHEN OLD.album_id IS NOT NULL
+BEGIN
+    UPDATE albums SET
+        track_count = track_count - 1,
+        total_duration = total_duration - COALESCE(OLD.duration, 0),
+        bitrate_sum = bitrate_sum - COALESCE(OLD.bitrate, 0),
+        bitrate_tracks = bitrate_tracks - (OLD.bitrate IS NOT NULL)
+    WHERE id = OLD.album_id;
+END;
+
+-- Rescans rewrite these on every upsert; only real changes touch albums
+CREATE TRIGGER IF NOT EXISTS album_rollup_track_changed
+AFTER UPDATE OF album_id, duration, bitrate ON tracks
+WHEN OLD.album_id IS NOT NEW.album_id
+    OR OLD.duration IS NOT NEW.duration
+    OR OLD.bitrate IS NOT NEW.bitrate
+BEGIN
+    UPDATE albums SET
+        track_count = track_count - 1,
+        total_duration = total_duration - COALESCE(OLD.duration, 0),
+        bitrate_sum = bitrate_sum - COALESCE(OLD.bitrate, 0),
+        bitrate_tracks = bitrate_tracks - (OLD.bitrate IS NOT NULL)
+    WHERE id = OLD.album_id;
+    UPDATE albums SET
+        track_count = track_count + 1,
+        total_duration = total_duration + COALESCE(NEW.duration, 0),
+        bitrate_sum = bitrate_sum + COALESCE(NEW.bitrate, 0),
+        bitrate_tracks = bitrate_tracks + (NEW.bitrate IS NOT NULL)
+    WHERE id = NEW.album_id;
+END;
@@ -0,0 +1,3 @@
+-- Shared error class of a failed health check: network, locked,
+-- rate_limited (worth retrying) or unsupported, corrupt, not_found
+ALTER TABLE file_health ADD COLUMN error_class TEXT;
@@ -0,0 +1,6 @@
+-- Track source
+-- Where a track's audio comes from: 'local' (a file), 'stream' (an HTTP
+-- URL) or 'cloud' (a cloud drive share link). Scans, organize and
+-- fingerprinting only look at local tracks.
+
+ALTER TABLE tracks ADD COLUMN source TEXT NOT NULL DEFAULT 'local';
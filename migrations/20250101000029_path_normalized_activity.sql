@@ -0,0 +1,35 @@
+-- Path normalization in the activity log
+-- Canonicalizing stored paths rewrites tracks.path without moving any file,
+-- and merging two spellings of one path deletes a track that was never
+-- really removed. While a row is in activity_path_pass (inside the
+-- normalizing transaction only), those changes are logged as
+-- 'path_normalized' instead of 'file_organized' and 'track_removed'.
+
+CREATE TABLE IF NOT EXISTS activity_path_pass (
+    kind TEXT NOT NULL
+);
+
+DROP TRIGGER IF EXISTS activity_file_organized;
+CREATE TRIGGER activity_file_organized
+AFTER UPDATE OF path ON tracks
+WHEN OLD.path <> NEW.path
+BEGIN
+    INSERT INTO activity_log (timestamp, kind, track_id, path, detail)
+    VALUES (
+        unixepoch(),
+        COALESCE((SELECT kind FROM activity_path_pass LIMIT 1), 'file_organized'),
+        NEW.id, NEW.path, 'from ' || OLD.path
+    );
+END;
+
+DROP TRIGGER IF EXISTS activity_track_removed;
+CREATE TRIGGER activity_track_removed
+AFTER DELETE ON tracks
+BEGIN
+    INSERT INTO activity_log (timestamp, kind, track_id, path, detail)
+    SELECT unixepoch(), 'track_removed', OLD.id, OLD.path, OLD.title
+    WHERE NOT EXISTS (SELECT 1 FROM activity_path_pass)
+    UNION ALL
+    SELECT unixepoch(), kind, OLD.id, OLD.path, 'merged duplicate: ' || OLD.title
+    FROM (SELECT kind FROM activity_path_pass LIMIT 1);
+END;
@@ -0,0 +1,528 @@
+//! Library change feed.
+//!
+//! An append-only log of everything the app changed in the library:
+//! tracks added or removed, tags written, files organized, and recordings
+//! matched. Track lifecycle events are recorded by database triggers so no
+//! code path can forget them; tag writes happen outside the database and are
+//! recorded explicitly with [`record_tags_written`], together with the
+//! [`WriteAudit`] of what the save did to the file.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::activity::{ActivityQuery, query};
+//!
+//! // Everything that changed in the last week
+//! let entries = query(&pool, &ActivityQuery::last_days(7)).await?;
+//! ```
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde::Serialize;
+use sqlx::sqlite::SqlitePool;
+use std::fs;
+use std::path::Path;
+
+use crate::metadata::audit::WriteAudit;
+use crate::plan::push_csv_row;
+
+/// Errors that can occur when exporting the activity log.
+#[derive(Debug, thiserror::Error)]
+pub enum ActivityError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// What kind of change an activity entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    /// A track entered the library
+    TrackAdded,
+    /// A track was removed from the library
+    TrackRemoved,
+    /// Tags were written to a file
+    TagsWritten,
+    /// A file was moved or renamed
+    FileOrganized,
+    /// A track was matched to a MusicBrainz recording
+    RecordingMatched,
+    /// A stored path was rewritten in canonical form, or a track stored
+    /// under a second spelling of it merged away (the file didn't move)
+    PathNormalized,
+}
+
+impl ActivityKind {
+    /// All kinds, in display order.
+    pub const ALL: [ActivityKind; 6] = [
+        ActivityKind::TrackAdded,
+        ActivityKind::TrackRemoved,
+        ActivityKind::TagsWritten,
+        ActivityKind::FileOrganized,
+        ActivityKind::RecordingMatched,
+        ActivityKind::PathNormalized,
+    ];
+
+    /// Convert to string representation for storage.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::TrackAdded => "track_added",
+            ActivityKind::TrackRemoved => "track_removed",
+            ActivityKind::TagsWritten => "tags_written",
+            ActivityKind::FileOrganized => "file_organized",
+            ActivityKind::RecordingMatched => "recording_matched",
+            ActivityKind::PathNormalized => "path_normalized",
+        }
+    }
+
+    /// Human-readable label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActivityKind::TrackAdded => "Added",
+            ActivityKind::TrackRemoved => "Removed",
+            ActivityKind::TagsWritten => "Tags written",
+            ActivityKind::FileOrganized => "Organized",
+            ActivityKind::RecordingMatched => "Matched",
+            ActivityKind::PathNormalized => "Path normalized",
+        }
+    }
+}
+
+impl std::fmt::Display for ActivityKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ActivityKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ActivityKind::ALL
+            .into_iter()
+            .find(|k| k.as_str() == s)
+            .ok_or_else(|| format!("unknown activity kind '{}'", s))
+    }
+}
+
+/// One entry in the activity log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActivityEntry {
+    /// Database ID
+    pub id: i64,
+    /// When the change happened
+    #[serde(serialize_with = "serialize_rfc3339")]
+    pub timestamp: DateTime<Utc>,
+    /// What changed
+    pub kind: ActivityKind,
+    /// Track the change applies to, if it was in the library
+    pub track_id: Option<i64>,
+    /// File path (the new path for moves)
+    pub path: String,
+    /// Extra context: title, previous path, recording ID, or written fields
+    pub detail: Option<String>,
+    /// For tag writes: tag versions, size change and read-back result
+    pub audit: Option<WriteAudit>,
+}
+
+impl ActivityEntry {
+    /// The local calendar day of the entry, used to group the timeline.
+    pub fn local_date(&self) -> NaiveDate {
+        self.timestamp.with_timezone(&Local).date_naive()
+    }
+}
+
+fn serialize_rfc3339<S: serde::Serializer>(ts: &DateTime<Utc>, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&ts.to_rfc3339())
+}
+
+/// Database row for the activity_log table.
+#[derive(Debug, sqlx::FromRow)]
+struct ActivityRow {
+    id: i64,
+    timestamp: i64,
+    kind: String,
+    track_id: Option<i64>,
+    path: String,
+    detail: Option<String>,
+    tags_before: Option<String>,
+    tags_after: Option<String>,
+    size_before: Option<i64>,
+    size_after: Option<i64>,
+    padding_rewritten: Option<bool>,
+    verified: Option<bool>,
+    verify_note: Option<String>,
+}
+
+impl TryFrom<ActivityRow> for ActivityEntry {
+    type Error = String;
+
+    fn try_from(row: ActivityRow) -> Result<Self, Self::Error> {
+        let audit = match (row.size_before, row.size_after) {
+            (Some(size_before), Some(size_after)) => Some(WriteAudit {
+                tags_before: row.tags_before.unwrap_or_default(),
+                tags_after: row.tags_after.unwrap_or_default(),
+                size_before: size_before as u64,
+                size_after: size_after as u64,
+                padding_rewritten: row.padding_rewritten.unwrap_or(false),
+                verified: row.verified.unwrap_or(false),
+                verify_note: row.verify_note,
+            }),
+            _ => None,
+        };
+        Ok(ActivityEntry {
+            id: row.id,
+            timestamp: DateTime::from_timestamp(row.timestamp, 0).unwrap_or_default(),
+            kind: row.kind.parse()?,
+            track_id: row.track_id,
+            path: row.path,
+            detail: row.detail,
+            audit,
+        })
+    }
+}
+
+/// Filter for [`query`]. All bounds are optional; `until` is exclusive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityQuery {
+    /// Only entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only entries of this kind
+    pub kind: Option<ActivityKind>,
+    /// Maximum number of entries (newest first)
+    pub limit: Option<u32>,
+}
+
+impl ActivityQuery {
+    /// Entries from the last `days` days.
+    pub fn last_days(days: u32) -> Self {
+        Self {
+            since: Some(Utc::now() - chrono::Duration::days(i64::from(days))),
+            ..Default::default()
+        }
+    }
+
+    /// Entries on a single local calendar day.
+    pub fn on_day(date: NaiveDate) -> Self {
+        Self {
+            since: local_midnight(date),
+            until: date.succ_opt().and_then(local_midnight),
+            ..Default::default()
+        }
+    }
+}
+
+/// Start of a local calendar day, in UTC.
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Utc>> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Append an entry to the log.
+pub async fn record(
+    pool: &SqlitePool,
+    kind: ActivityKind,
+    track_id: Option<i64>,
+    path: &str,
+    detail: Option<&str>,
+) -> sqlx::Result<i64> {
+    let result = sqlx::query(
+        "INSERT INTO activity_log (timestamp, kind, track_id, path, detail) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(Utc::now().timestamp())
+    .bind(kind.as_str())
+    .bind(track_id)
+    .bind(path)
+    .bind(detail)
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Record a successful tag write, with the audit of the save if
+/// [`crate::metadata::audit`] has one for the file.
+///
+/// Logging is best effort: a failure is traced but never fails the write
+/// that already happened on disk.
+pub async fn record_tags_written(pool: &SqlitePool, path: &Path, fields_updated: usize) {
+    let path = path.to_string_lossy();
+    let track_id: Option<i64> = sqlx::query_scalar("SELECT id FROM tracks WHERE path_key = ?")
+        .bind(crate::db::paths::key(&path))
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten();
+    let detail = format!("{} fields", fields_updated);
+    let id = match record(
+        pool,
+        ActivityKind::TagsWritten,
+        track_id,
+        &path,
+        Some(&detail),
+    )
+    .await
+    {
+        Ok(id) => id,
+        Err(e) => {
+            tracing::warn!("Failed to record tag write for {}: {}", path, e);
+            return;
+        }
+    };
+    if let Some(audit) = crate::metadata::audit::take(Path::new(path.as_ref()))
+        && let Err(e) = record_audit(pool, id, &audit).await
+    {
+        tracing::warn!("Failed to record tag write audit for {}: {}", path, e);
+    }
+}
+
+/// Store the audit of the tag write logged as entry `activity_id`
+async fn record_audit(pool: &SqlitePool, activity_id: i64, audit: &WriteAudit) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO tag_write_audit
+            (activity_id, tags_before, tags_after, size_before, size_after,
+             padding_rewritten, verified, verify_note)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(activity_id)
+    .bind(&audit.tags_before)
+    .bind(&audit.tags_after)
+    .bind(audit.size_before as i64)
+    .bind(audit.size_after as i64)
+    .bind(audit.padding_rewritten)
+    .bind(audit.verified)
+    .bind(&audit.verify_note)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Query the log, newest entries first.
+pub async fn query(pool: &SqlitePool, filter: &ActivityQuery) -> sqlx::Result<Vec<ActivityEntry>> {
+    let rows: Vec<ActivityRow> = sqlx::query_as(
+        r#"
+        SELECT a.id, a.timestamp, a.kind, a.track_id, a.path, a.detail,
+               w.tags_before, w.tags_after, w.size_before, w.size_after,
+               w.padding_rewritten, w.verified, w.verify_note
+        FROM activity_log a
+        LEFT JOIN tag_write_audit w ON w.activity_id = a.id
+        WHERE (?1 IS NULL OR a.timestamp >= ?1)
+          AND (?2 IS NULL OR a.timestamp < ?2)
+          AND (?3 IS NULL OR a.kind = ?3)
+        ORDER BY a.timestamp DESC, a.id DESC
+        LIMIT ?4
+        "#,
+    )
+    .bind(filter.since.map(|t| t.timestamp()))
+    .bind(filter.until.map(|t| t.timestamp()))
+    .bind(filter.kind.map(|k| k.as_str()))
+    .bind(filter.limit.map(i64::from).unwrap_or(-1))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| ActivityEntry::try_from(row).ok())
+        .collect())
+}
+
+/// Render entries as CSV.
+pub fn to_csv(entries: &[ActivityEntry]) -> String {
+    let mut out = String::from(
+        "timestamp,kind,track_id,path,detail,tags_before,tags_after,size_before,size_after,padding_rewritten,verified\n",
+    );
+    for e in entries {
+        let audit = e.audit.as_ref();
+        let size = |f: fn(&WriteAudit) -> u64| audit.map(|a| f(a).to_string()).unwrap_or_default();
+        let flag = |f: fn(&WriteAudit) -> bool| audit.map(|a| f(a).to_string()).unwrap_or_default();
+        push_csv_row(
+            &mut out,
+            &[
+                &e.timestamp.to_rfc3339(),
+                e.kind.as_str(),
+                &e.track_id.map(|id| id.to_string()).unwrap_or_default(),
+                &e.path,
+                e.detail.as_deref().unwrap_or(""),
+                audit.map(|a| a.tags_before.as_str()).unwrap_or(""),
+                audit.map(|a| a.tags_after.as_str()).unwrap_or(""),
+                &size(|a| a.size_before),
+                &size(|a| a.size_after),
+                &flag(|a| a.padding_rewritten),
+                &flag(|a| a.verified),
+            ],
+        );
+    }
+    out
+}
+
+/// Save entries to a file, choosing CSV or JSON from the extension.
+pub fn export(entries: &[ActivityEntry], path: &Path) -> Result<(), ActivityError> {
+    let is_csv = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    let contents = if is_csv {
+        to_csv(entries)
+    } else {
+        serde_json::to_string_pretty(entries)?
+    };
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::metadata::TrackMetadata;
+
+    async fn test_pool(dir: &Path) -> SqlitePool {
+        let db_url = format!("sqlite:{}", dir.join("test.db").display());
+        db::init_db(&db_url).await.unwrap()
+    }
+
+    fn meta(title: &str) -> TrackMetadata {
+        TrackMetadata {
+            title: title.to_string(),
+            artist: "Artist".to_string(),
+            album: "Album".to_string(),
+            duration: 180,
+            track_number: Some(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_track_lifecycle_is_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+
+        let id = db::insert_track(&pool, &meta("Song"), "/a/song.mp3", None, None)
+            .await
+            .unwrap();
+        // Rescanning an existing track is not a new addition
+        db::insert_track(&pool, &meta("Song"), "/a/song.mp3", None, None)
+            .await
+            .unwrap();
+        db::update_track_path(&pool, id, "/b/song.mp3")
+            .await
+            .unwrap();
+        db::delete_track_by_path(&pool, "/b/song.mp3")
+            .await
+            .unwrap();
+
+        let entries = query(&pool, &ActivityQuery::default()).await.unwrap();
+        let kinds: Vec<_> = entries.iter().rev().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ActivityKind::TrackAdded,
+                ActivityKind::FileOrganized,
+                ActivityKind::TrackRemoved
+            ]
+        );
+        let moved = &entries[1];
+        assert_eq!(moved.path, "/b/song.mp3");
+        assert_eq!(moved.detail.as_deref(), Some("from /a/song.mp3"));
+    }
+
+    #[tokio::test]
+    async fn test_log_is_append_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+
+        record(&pool, ActivityKind::TagsWritten, None, "/x.mp3", None)
+            .await
+            .unwrap();
+        assert!(
+            sqlx::query("DELETE FROM activity_log")
+                .execute(&pool)
+                .await
+                .is_err()
+        );
+        assert!(
+            sqlx::query("UPDATE activity_log SET path = 'y'")
+                .execute(&pool)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_date_and_kind() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+
+        record_tags_written(&pool, Path::new("/x.mp3"), 3).await;
+        db::insert_track(&pool, &meta("Song"), "/y.mp3", None, None)
+            .await
+            .unwrap();
+
+        let today = query(&pool, &ActivityQuery::on_day(Local::now().date_naive()))
+            .await
+            .unwrap();
+        assert_eq!(today.len(), 2);
+
+        let last_week = ActivityQuery {
+            until: Some(Utc::now() - chrono::Duration::days(7)),
+            ..Default::default()
+        };
+        assert!(query(&pool, &last_week).await.unwrap().is_empty());
+
+        let tags = ActivityQuery {
+            kind: Some(ActivityKind::TagsWritten),
+            ..Default::default()
+        };
+        let tags = query(&pool, &tags).await.unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].detail.as_deref(), Some("3 fields"));
+    }
+
+    #[tokio::test]
+    async fn test_tag_write_is_logged_with_its_audit() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+        let path = crate::test_utils::write_audio_fixture(
+            dir.path(),
+            crate::test_utils::AudioFixture::Mp3,
+        );
+        let size_before = fs::metadata(&path).unwrap().len();
+
+        let track = crate::enrichment::domain::IdentifiedTrack {
+            title: Some("Song".to_string()),
+            ..Default::default()
+        };
+        let result = crate::metadata::write(&path, &track, &Default::default()).unwrap();
+        record_tags_written(&pool, &path, result.fields_updated).await;
+
+        let entries = query(&pool, &ActivityQuery::default()).await.unwrap();
+        let audit = entries[0].audit.as_ref().expect("write was audited");
+        assert_eq!(audit.tags_before, "");
+        assert!(audit.tags_after.starts_with("ID3v2"), "{:?}", audit);
+        assert_eq!(audit.size_before, size_before);
+        assert!(audit.size_delta() > 0);
+        assert!(audit.padding_rewritten);
+        assert!(audit.verified, "{:?}", audit.verify_note);
+    }
+
+    #[test]
+    fn test_csv_export_quotes_fields() {
+        let entry = ActivityEntry {
+            id: 1,
+            timestamp: DateTime::from_timestamp(0, 0).unwrap(),
+            kind: ActivityKind::FileOrganized,
+            track_id: Some(7),
+            path: "/music/a, b.mp3".to_string(),
+            detail: None,
+            audit: None,
+        };
+        let csv = to_csv(&[entry]);
+        assert!(csv.contains("file_organized,7,\"/music/a, b.mp3\","));
+    }
+}
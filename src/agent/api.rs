@@ -0,0 +1,554 @@
+//! The agent's JSON API.
+//!
+//! A deliberately small HTTP/1.1 server: one request per connection, no
+//! request bodies.
+//!
+//! | Request                          | Response                                        |
+//! |----------------------------------|-------------------------------------------------|
+//! | `GET /api/status`                | version, profile, track count, watched folders  |
+//! | `GET /api/jobs`                  | each job's schedule, next run and recent runs   |
+//! | `POST /api/jobs/{job}/run`       | starts the job (`202`), `409` if one is running |
+//! | `GET /api/tracks?limit=&offset=` | tracks with artist and album names, by id       |
+//! | `GET /api/tasks`                 | running tasks with phase, counts and ETA        |
+//! | `POST /api/tasks/{id}/cancel`    | cancels a running task, `404` if there's none   |
+//!
+//! With `agent.api_token` set, every request needs an
+//! `Authorization: Bearer <token>` header. Without one, requests must name
+//! the agent by `localhost` or an IP address in `Host`, and `POST`s must not
+//! carry an `Origin`, so web pages open in a browser on the same machine
+//! can't reach the API.
+
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::AgentState;
+use crate::db;
+use crate::scheduler::{self, Job, JobRun, Schedule};
+use crate::tasks::TaskId;
+
+/// Longest request head (request line and headers) accepted
+const MAX_HEAD_BYTES: u64 = 16 * 1024;
+/// How long a client gets to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Tracks per page by default, and at most
+const DEFAULT_PAGE: i64 = 100;
+const MAX_PAGE: i64 = 1000;
+
+/// A parsed request
+#[derive(Debug, Default, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub authorization: Option<String>,
+    pub host: Option<String>,
+    pub origin: Option<String>,
+}
+
+/// A JSON response
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }),
+        }
+    }
+}
+
+/// Accept connections until the listener fails.
+pub(super) async fn serve(listener: TcpListener, state: Arc<AgentState>) -> std::io::Result<()> {
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = Arc::clone(&state);
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, &state).await {
+                tracing::debug!(%peer, "Connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, state: &Arc<AgentState>) -> std::io::Result<()> {
+    let (read, mut write) = stream.into_split();
+    let mut reader = BufReader::new(read).take(MAX_HEAD_BYTES);
+    let head = tokio::time::timeout(READ_TIMEOUT, async {
+        let mut head = String::new();
+        loop {
+            let n = reader.read_line(&mut head).await?;
+            if n == 0 || head.ends_with("\r\n\r\n") || head.ends_with("\n\n") {
+                return std::io::Result::Ok(head);
+            }
+        }
+    })
+    .await
+    .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "request timed out"))??;
+
+    let response = match parse_head(&head) {
+        Some(request) => {
+            tracing::debug!(method = %request.method, path = %request.path);
+            respond(state, &request).await
+        }
+        None => Response::error(400, "Malformed request"),
+    };
+
+    let body = response.body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        body.len()
+    );
+    write.write_all(head.as_bytes()).await?;
+    write.write_all(body.as_bytes()).await?;
+    write.shutdown().await
+}
+
+/// Parse the request line and the headers we care about.
+pub fn parse_head(head: &str) -> Option<Request> {
+    let mut lines = head.lines();
+    let mut parts = lines.next()?.split_whitespace();
+    let method = parts.next()?.to_ascii_uppercase();
+    let target = parts.next()?;
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect();
+
+    let headers: Vec<(&str, &str)> = lines.filter_map(|line| line.split_once(':')).collect();
+    let header = |wanted: &str| {
+        headers
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(wanted))
+            .map(|(_, value)| value.trim().to_string())
+    };
+
+    Some(Request {
+        method,
+        path: decode(path),
+        query,
+        authorization: header("authorization"),
+        host: header("host"),
+        origin: header("origin"),
+    })
+}
+
+/// Whether a `Host` header names this machine by `localhost` or an IP
+/// address. Anything else is a DNS name, which a web page can point at
+/// 127.0.0.1 to get past the browser's same-origin checks.
+fn is_local_host(host: &str) -> bool {
+    let is_local = |name: &str| {
+        name.eq_ignore_ascii_case("localhost") || name.parse::<std::net::IpAddr>().is_ok()
+    };
+    let unbracketed = |name: &str| {
+        name.strip_prefix('[')
+            .and_then(|n| n.strip_suffix(']'))
+            .unwrap_or(name)
+            .to_string()
+    };
+    // `::1` and `[::1]` carry colons of their own; try them whole first
+    is_local(&unbracketed(host))
+        || host
+            .rsplit_once(':')
+            .is_some_and(|(name, port)| port.parse::<u16>().is_ok() && is_local(&unbracketed(name)))
+}
+
+fn decode(s: &str) -> String {
+    let s = s.replace('+', " ");
+    urlencoding::decode(&s).map(|d| d.into_owned()).unwrap_or(s)
+}
+
+/// Answer one request.
+pub async fn respond(state: &Arc<AgentState>, request: &Request) -> Response {
+    if let Some(token) = &state.config.agent.api_token
+        && request.authorization.as_deref() != Some(format!("Bearer {}", token).as_str())
+    {
+        return Response::error(401, "Missing or wrong API token");
+    }
+    if state.config.agent.api_token.is_none()
+        && (request.host.as_deref().is_some_and(|h| !is_local_host(h))
+            || (request.method == "POST" && request.origin.is_some()))
+    {
+        return Response::error(403, "Cross-site requests need agent.api_token");
+    }
+
+    let segments: Vec<&str> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+    let result = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["api", "status"]) => status(state).await,
+        ("GET", ["api", "jobs"]) => Ok(jobs(state)),
+        ("POST", ["api", "jobs", name, "run"]) => Ok(run_job(state, name)),
+        ("GET", ["api", "tracks"]) => tracks(state, &request.query).await,
+        ("GET", ["api", "tasks"]) => Ok(tasks(state)),
+        ("POST", ["api", "tasks", id, "cancel"]) => Ok(cancel_task(state, id)),
+        (_, ["api", "status" | "jobs" | "tracks" | "tasks"])
+        | (_, ["api", "jobs", _, "run"])
+        | (_, ["api", "tasks", _, "cancel"]) => Ok(Response::error(405, "Method not allowed")),
+        _ => Ok(Response::error(404, "Not found")),
+    };
+    result.unwrap_or_else(|e: sqlx::Error| {
+        tracing::warn!("Request failed: {}", e);
+        Response::error(500, "Database error")
+    })
+}
+
+async fn status(state: &AgentState) -> sqlx::Result<Response> {
+    let tracks = db::count_tracks(&state.pool).await?;
+    Ok(Response::ok(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "profile": crate::profile::active(),
+        "read_only": crate::readonly::is_enabled(),
+        "tracks": tracks,
+        "watching": state.watching.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+        "running_job": state.running.lock().map(|job| job.as_str()),
+        "started": state.started.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    })))
+}
+
+fn jobs(state: &AgentState) -> Response {
+    let config = &state.config.scheduler;
+    let runs = state.runs.lock();
+    let running = *state.running.lock();
+    let jobs: Vec<Value> = Job::ALL
+        .into_iter()
+        .map(|job| {
+            let last = scheduler::last_run(&runs, job).map(JobRun::started_local);
+            let (next_run, error) = match job.schedule(config) {
+                Ok(Schedule::Idle) => (Some("idle".to_string()), None),
+                Ok(schedule) => (
+                    schedule
+                        .next_run(last, state.started)
+                        .map(|at| at.format("%Y-%m-%dT%H:%M:%S").to_string()),
+                    None,
+                ),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            json!({
+                "job": job.as_str(),
+                "label": job.label(),
+                "enabled": config.enabled && config.job(job).enabled,
+                "schedule": job.schedule_rule(config),
+                "schedule_error": error,
+                "next_run": next_run,
+                "running": running == Some(job),
+                "runs": runs.iter().filter(|r| r.job == job).map(run_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    Response::ok(json!({ "jobs": jobs }))
+}
+
+fn run_json(run: &JobRun) -> Value {
+    json!({
+        "started_at": run.started_at.to_rfc3339(),
+        "finished_at": run.finished_at.to_rfc3339(),
+        "success": run.success,
+        "summary": run.summary,
+    })
+}
+
+fn run_job(state: &Arc<AgentState>, name: &str) -> Response {
+    let Ok(job) = name.parse::<Job>() else {
+        return Response::error(404, "Unknown job");
+    };
+    if state.start_job(job) {
+        Response {
+            status: 202,
+            body: json!({ "started": job.as_str() }),
+        }
+    } else {
+        Response::error(409, "Another job is running")
+    }
+}
+
+async fn tracks(state: &AgentState, query: &HashMap<String, String>) -> sqlx::Result<Response> {
+    let number = |key: &str, default: i64| {
+        query
+            .get(key)
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(default)
+            .max(0)
+    };
+    let limit = number("limit", DEFAULT_PAGE).min(MAX_PAGE);
+    let offset = number("offset", 0);
+    let tracks: Vec<Value> = db::get_tracks_paginated(&state.pool, limit, offset)
+        .await?
+        .into_iter()
+        .map(|t| {
+            json!({
+                "id": t.id,
+                "title": t.title,
+                "artist": t.artist_name,
+                "album": t.album_name,
+                "year": t.year,
+                "track_number": t.track_number,
+                "duration": t.duration,
+                "quality_score": t.quality_score,
+                "path": t.path,
+                "source": t.source.as_str(),
+            })
+        })
+        .collect();
+    Ok(Response::ok(json!({
+        "total": db::count_tracks(&state.pool).await?,
+        "offset": offset,
+        "tracks": tracks,
+    })))
+}
+
+fn tasks(state: &AgentState) -> Response {
+    let tasks: Vec<Value> = state
+        .tasks
+        .running()
+        .into_iter()
+        .map(|task| {
+            json!({
+                "id": task.id,
+                "kind": task.kind.as_str(),
+                "label": task.label,
+                "phase": task.phase,
+                "done": task.done,
+                "total": task.total,
+                "elapsed_secs": task.elapsed.as_secs(),
+                "eta_secs": task.eta().map(|eta| eta.as_secs()),
+                "cancelled": task.cancelled,
+            })
+        })
+        .collect();
+    Response::ok(json!({ "tasks": tasks }))
+}
+
+fn cancel_task(state: &AgentState, id: &str) -> Response {
+    match id.parse::<TaskId>() {
+        Ok(id) if state.tasks.cancel(id) => Response {
+            status: 202,
+            body: json!({ "cancelled": id }),
+        },
+        _ => Response::error(404, "No such task"),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::metadata::TrackMetadata;
+    use crate::tasks::TaskKind;
+
+    async fn state(config: Config) -> (tempfile::TempDir, Arc<AgentState>) {
+        let temp = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", temp.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        let meta = TrackMetadata {
+            title: "Airbag".to_string(),
+            artist: "Radiohead".to_string(),
+            album: "OK Computer".to_string(),
+            duration: 284,
+            track_number: Some(1),
+        };
+        db::insert_track(&pool, &meta, "/m/01 Airbag.mp3", None, None)
+            .await
+            .unwrap();
+        let state = AgentState::new(pool, config, Vec::new()).await.unwrap();
+        (temp, Arc::new(state))
+    }
+
+    fn get(path: &str) -> Request {
+        parse_head(&format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_head() {
+        let request = parse_head(
+            "post /api/jobs/scan/run?limit=5&name=a%20b+c HTTP/1.1\r\nauthorization: Bearer t\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/jobs/scan/run");
+        assert_eq!(request.query["limit"], "5");
+        assert_eq!(request.query["name"], "a b c");
+        assert_eq!(request.authorization.as_deref(), Some("Bearer t"));
+        assert_eq!(request.host, None);
+
+        assert_eq!(parse_head("GET /\r\n\r\n"), None);
+        assert_eq!(parse_head(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_routes() {
+        let (_temp, state) = state(Config::default()).await;
+
+        let status = respond(&state, &get("/api/status")).await;
+        assert_eq!(status.status, 200);
+        assert_eq!(status.body["tracks"], 1);
+
+        let tracks = respond(&state, &get("/api/tracks?limit=10")).await;
+        assert_eq!(tracks.body["total"], 1);
+        assert_eq!(tracks.body["tracks"][0]["title"], "Airbag");
+
+        let jobs = respond(&state, &get("/api/jobs")).await;
+        assert_eq!(jobs.body["jobs"].as_array().unwrap().len(), Job::ALL.len());
+        assert_eq!(jobs.body["jobs"][1]["next_run"], "idle");
+
+        assert_eq!(respond(&state, &get("/api/nothing")).await.status, 404);
+        assert_eq!(
+            respond(&state, &get("/api/jobs/scan/run")).await.status,
+            405
+        );
+
+        // Only one job at a time
+        let post = |job: &str| Request {
+            method: "POST".to_string(),
+            path: format!("/api/jobs/{}/run", job),
+            ..Default::default()
+        };
+        assert_eq!(respond(&state, &post("nope")).await.status, 404);
+        let _running = state.job_lock.lock().await;
+        assert_eq!(respond(&state, &post("verify")).await.status, 409);
+    }
+
+    #[tokio::test]
+    async fn test_tasks() {
+        let (_temp, state) = state(Config::default()).await;
+        let task = state.tasks.start(TaskKind::Maintenance, "Verify library");
+        task.set_total(10);
+        task.advance(5);
+
+        let tasks = respond(&state, &get("/api/tasks")).await;
+        assert_eq!(tasks.body["tasks"][0]["label"], "Verify library");
+        assert_eq!(tasks.body["tasks"][0]["total"], 10);
+
+        let cancel = |id: &str| Request {
+            method: "POST".to_string(),
+            path: format!("/api/tasks/{}/cancel", id),
+            ..Default::default()
+        };
+        assert_eq!(respond(&state, &cancel("99")).await.status, 404);
+        assert_eq!(
+            respond(&state, &cancel(&task.id().to_string()))
+                .await
+                .status,
+            202
+        );
+        assert!(task.is_cancelled());
+
+        task.finish();
+        let tasks = respond(&state, &get("/api/tasks")).await;
+        assert_eq!(tasks.body["tasks"].as_array().unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_api_token() {
+        let mut config = Config::default();
+        config.agent.api_token = Some("secret".to_string());
+        let (_temp, state) = state(config).await;
+
+        assert_eq!(respond(&state, &get("/api/status")).await.status, 401);
+        let mut request = get("/api/status");
+        request.authorization = Some("Bearer secret".to_string());
+        assert_eq!(respond(&state, &request).await.status, 200);
+    }
+
+    #[test]
+    fn test_is_local_host() {
+        for host in [
+            "localhost",
+            "LOCALHOST:7431",
+            "127.0.0.1",
+            "127.0.0.1:7431",
+            "192.168.1.20:7431",
+            "[::1]",
+            "[::1]:7431",
+            "::1",
+        ] {
+            assert!(is_local_host(host), "{}", host);
+        }
+        for host in [
+            "evil.example",
+            "evil.example:7431",
+            "localhost.evil.example",
+        ] {
+            assert!(!is_local_host(host), "{}", host);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cross_site_requests_refused() {
+        let (_temp, state) = state(Config::default()).await;
+        let request = |head: &str| parse_head(head).unwrap();
+
+        // A page on another site posting to the agent
+        let posted = request(
+            "POST /api/tasks/99/cancel HTTP/1.1\r\nHost: 127.0.0.1:7431\r\nOrigin: https://evil.example\r\n\r\n",
+        );
+        assert_eq!(respond(&state, &posted).await.status, 403);
+        // A DNS name rebound to 127.0.0.1
+        let rebound = request("GET /api/status HTTP/1.1\r\nHost: evil.example:7431\r\n\r\n");
+        assert_eq!(respond(&state, &rebound).await.status, 403);
+        // Scripts and curl
+        let local = request("POST /api/tasks/99/cancel HTTP/1.1\r\nHost: 127.0.0.1:7431\r\n\r\n");
+        assert_eq!(respond(&state, &local).await.status, 404);
+
+        // With a token, the token decides
+        let mut config = Config::default();
+        config.agent.api_token = Some("secret".to_string());
+        let (_temp, with_token) = self::state(config).await;
+        let mut posted = posted;
+        posted.authorization = Some("Bearer secret".to_string());
+        assert_eq!(respond(&with_token, &posted).await.status, 404);
+    }
+
+    #[tokio::test]
+    async fn test_serve_over_tcp() {
+        let (_temp, state) = state(Config::default()).await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, state));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /api/status HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).await.unwrap();
+        assert!(reply.starts_with("HTTP/1.1 200 OK\r\n"));
+        let body: Value = serde_json::from_str(reply.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["tracks"], 1);
+    }
+}
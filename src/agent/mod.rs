@@ -0,0 +1,466 @@
+//! Headless background agent.
+//!
+//! `music-minder agent` (or `serve`) keeps the library up to date without a
+//! window, for an always-on machine:
+//! - watches the library folders and indexes new, changed and removed files
+//! - runs the scheduled maintenance jobs from [`crate::scheduler`]; with no
+//!   one at the keyboard, "idle" means no file changes for
+//!   `scheduler.idle_minutes`
+//! - identifies newly added tracks when an AcoustID key is set, storing the
+//!   matches for review in the app instead of writing tags, unless the
+//!   track's folder defaults turn that off or write confident matches
+//!   (see [`crate::enrichment::folders`])
+//! - serves a small JSON API (see [`api`]) for status, tracks, jobs and the
+//!   progress of running ones
+//!
+//! Settings come from the `[agent]` config section and are read once at
+//! startup. [`service`] registers the agent as a Windows service or a
+//! systemd user unit.
+
+pub mod api;
+pub mod service;
+
+use chrono::{Local, NaiveDateTime};
+use parking_lot::Mutex;
+use sqlx::SqlitePool;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::{OwnedMutexGuard, mpsc};
+
+use crate::config::{Config, TaggingConfig};
+use crate::enrichment::{EnrichmentConfig, EnrichmentService, fingerprint, folders};
+use crate::library::{self, ScanEvent};
+use crate::scanner::{FileWatcher, WatchError, WatchEvent};
+use crate::scheduler::{self, Job, JobRun};
+use crate::tasks::{TaskKind, TaskRegistry};
+use crate::write_queue::{self, Outcome};
+use crate::{db, metadata, readonly};
+
+/// How often the scheduler checks for due jobs
+const SCHEDULE_TICK: Duration = Duration::from_secs(60);
+
+/// How often deferred tag writes are checked on
+const DEFERRED_WRITE_TICK: Duration = Duration::from_secs(5);
+/// New tracks waiting to be identified; more are dropped (a later scan or
+/// the app can still identify them)
+const ENRICH_QUEUE: usize = 1000;
+/// How long shutdown waits for a running job
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
+
+/// Agent errors
+#[derive(Debug, thiserror::Error)]
+pub enum AgentError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("File watcher error: {0}")]
+    Watch(#[from] WatchError),
+
+    #[error("Invalid listen address {0:?}")]
+    Listen(String),
+
+    #[error("Service error: {0}")]
+    Service(String),
+}
+
+/// How to run the agent.
+#[derive(Debug, Clone)]
+pub struct AgentOptions {
+    /// Address the JSON API listens on
+    pub listen: SocketAddr,
+    /// Identify newly added tracks
+    pub enrich: bool,
+}
+
+impl AgentOptions {
+    /// Options from the `[agent]` config section.
+    pub fn from_config(config: &Config) -> Result<Self, AgentError> {
+        Ok(Self {
+            listen: parse_listen(&config.agent.listen)?,
+            enrich: config.agent.enrich_new_tracks,
+        })
+    }
+}
+
+/// Parse an API listen address such as `127.0.0.1:7431`.
+pub fn parse_listen(listen: &str) -> Result<SocketAddr, AgentError> {
+    listen
+        .trim()
+        .parse()
+        .map_err(|_| AgentError::Listen(listen.to_string()))
+}
+
+/// What the API reads and the jobs update.
+pub struct AgentState {
+    pool: SqlitePool,
+    config: Config,
+    /// When the agent started (jobs that never ran count from here)
+    started: NaiveDateTime,
+    /// Folders being watched
+    watching: Vec<PathBuf>,
+    /// Recent job runs, newest first
+    runs: Mutex<Vec<JobRun>>,
+    /// Job currently running
+    running: Mutex<Option<Job>>,
+    /// Held while a job runs, so only one runs at a time
+    job_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Last file change seen by the watcher
+    last_activity: Mutex<Instant>,
+    /// Running jobs' progress, for the API to show and cancel
+    tasks: TaskRegistry,
+}
+
+impl AgentState {
+    /// State for an agent using `pool`, with job history loaded from it.
+    pub async fn new(
+        pool: SqlitePool,
+        config: Config,
+        watching: Vec<PathBuf>,
+    ) -> Result<Self, AgentError> {
+        let runs = scheduler::recent_runs(&pool, scheduler::RUNS_KEPT_PER_JOB).await?;
+        Ok(Self {
+            pool,
+            config,
+            started: Local::now().naive_local(),
+            watching,
+            runs: Mutex::new(runs),
+            running: Mutex::new(None),
+            job_lock: Arc::new(tokio::sync::Mutex::new(())),
+            last_activity: Mutex::new(Instant::now()),
+            tasks: TaskRegistry::new(),
+        })
+    }
+
+    fn touch(&self) {
+        *self.last_activity.lock() = Instant::now();
+    }
+
+    fn is_idle(&self) -> bool {
+        let idle_after = Duration::from_secs(u64::from(self.config.scheduler.idle_minutes) * 60);
+        self.last_activity.lock().elapsed() >= idle_after
+    }
+
+    /// Start `job` in the background, unless a job is already running.
+    ///
+    /// Returns whether it was started.
+    fn start_job(self: &Arc<Self>, job: Job) -> bool {
+        let Ok(guard) = self.job_lock.clone().try_lock_owned() else {
+            return false;
+        };
+        let state = Arc::clone(self);
+        tokio::spawn(async move { state.run_job(job, guard).await });
+        true
+    }
+
+    async fn run_job(&self, job: Job, _guard: OwnedMutexGuard<()>) {
+        *self.running.lock() = Some(job);
+        let task = self.tasks.start(TaskKind::Maintenance, job.label());
+        let run = scheduler::run_and_record(&self.pool, job, &self.config, &task).await;
+        task.finish();
+        *self.running.lock() = None;
+        scheduler::push_run(&mut self.runs.lock(), run);
+    }
+}
+
+/// Run the agent until `shutdown` completes.
+///
+/// Uses the active profile's database and config. A job still running at
+/// shutdown is cancelled and gets a short grace period to stop.
+pub async fn run(
+    options: AgentOptions,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), AgentError> {
+    let config = crate::config::load();
+    let pool = db::init_db(&db::db_url(None)).await?;
+
+    let watching = if config.library.watch_for_changes {
+        let unwatched = &config.library.unwatched_paths;
+        let mut paths = config.library.paths.clone();
+        paths.retain(|p| !unwatched.contains(p));
+        paths
+    } else {
+        Vec::new()
+    };
+    let (mut watcher, events) = FileWatcher::new_async(Vec::new(), config.library.watch_settle())?;
+    let mut watched = Vec::new();
+    for path in watching {
+        match watcher.watch(&path) {
+            Ok(()) => watched.push(path),
+            Err(e) => tracing::warn!("Not watching {}: {}", path.display(), e),
+        }
+    }
+    if watched.is_empty() {
+        tracing::info!("No library folders to watch (library.paths)");
+    }
+
+    let enrich = if options.enrich {
+        start_enrichment(pool.clone(), &config)
+    } else {
+        None
+    };
+
+    let listener = TcpListener::bind(options.listen).await?;
+    tracing::info!("API listening on http://{}", listener.local_addr()?);
+    if !options.listen.ip().is_loopback() && config.agent.api_token.is_none() {
+        tracing::warn!("The API is open to the network without a token (agent.api_token)");
+    }
+
+    let state = Arc::new(AgentState::new(pool.clone(), config, watched).await?);
+    tracing::info!(profile = %crate::profile::active(), "Agent started");
+
+    tokio::select! {
+        () = shutdown => tracing::info!("Shutting down"),
+        result = api::serve(listener, Arc::clone(&state)) => result?,
+        () = watch_files(Arc::clone(&state), events, enrich) => {
+            tracing::warn!("File watcher stopped");
+        }
+        () = schedule_jobs(Arc::clone(&state)) => {}
+        () = retry_deferred_writes(state.config.tagging.watch_locked_files) => {}
+    }
+
+    // Jobs stop at their next check and keep what they did
+    state.tasks.cancel_all();
+    if tokio::time::timeout(SHUTDOWN_GRACE, state.job_lock.lock())
+        .await
+        .is_err()
+    {
+        tracing::warn!("Stopping with a job still running");
+    }
+    drop(watcher);
+    pool.close().await;
+    Ok(())
+}
+
+/// Completes on Ctrl+C, or on SIGTERM (how systemd stops a service).
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+                return;
+            }
+            Err(e) => tracing::warn!("Can't listen for SIGTERM: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Index watcher events as they come in
+async fn watch_files(
+    state: Arc<AgentState>,
+    mut events: mpsc::Receiver<WatchEvent>,
+    enrich: Option<mpsc::Sender<PathBuf>>,
+) {
+    while let Some(event) = events.recv().await {
+        state.touch();
+        if readonly::is_enabled() {
+            continue;
+        }
+        match event {
+            WatchEvent::Created(path) | WatchEvent::Modified(path) => {
+                let known = db::get_track_by_path(&state.pool, &path.to_string_lossy())
+                    .await
+                    .ok()
+                    .flatten()
+                    .is_some();
+                match library::index_changed_file(&state.pool, path).await {
+                    ScanEvent::Processed(path) => {
+                        tracing::info!(path = %path.display(), "Indexed");
+                        if !known
+                            && let Some(tx) = &enrich
+                            && tx.try_send(path).is_err()
+                        {
+                            tracing::debug!("Identify queue full");
+                        }
+                    }
+                    ScanEvent::Error(path, e) => {
+                        tracing::warn!(path = %path.display(), "Can't index: {}", e);
+                    }
+                    ScanEvent::CompilationsGrouped(_) => {}
+                }
+            }
+            WatchEvent::Removed(path) => {
+                match db::delete_track_by_path(&state.pool, &path.to_string_lossy()).await {
+                    Ok(true) => {
+                        tracing::info!(path = %path.display(), "Removed");
+                    }
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Failed to remove track: {}", e),
+                }
+            }
+            // Files inside arrive as events of their own
+            WatchEvent::DirCreated(_) => {}
+            WatchEvent::Error(e) => tracing::warn!("Watcher error: {}", e),
+        }
+    }
+}
+
+/// Start due maintenance jobs, one at a time
+async fn schedule_jobs(state: Arc<AgentState>) {
+    let mut tick = tokio::time::interval(SCHEDULE_TICK);
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        tick.tick().await;
+        let now = Local::now().naive_local();
+        let due = {
+            let runs = state.runs.lock();
+            scheduler::due_jobs(
+                &state.config.scheduler,
+                &runs,
+                state.started,
+                now,
+                state.is_idle(),
+            )
+        };
+        if let Some(job) = due.first() {
+            state.start_job(*job);
+        }
+    }
+}
+
+/// Try tag writes again that were put off while their file was in use
+async fn retry_deferred_writes(watch: bool) {
+    let mut tick = tokio::time::interval(DEFERRED_WRITE_TICK);
+    loop {
+        tick.tick().await;
+        if write_queue::len() == 0 {
+            continue;
+        }
+        for settled in write_queue::retry_due(watch).await {
+            if let Ok(fields) = settled.result {
+                tracing::info!(path = %settled.path.display(), fields, "Wrote deferred match");
+            }
+        }
+    }
+}
+
+/// Worker identifying new tracks, when an AcoustID key and fpcalc are there
+fn start_enrichment(pool: SqlitePool, config: &Config) -> Option<mpsc::Sender<PathBuf>> {
+    let Some(api_key) = config
+        .credentials
+        .acoustid_api_key
+        .clone()
+        .filter(|k| !k.trim().is_empty())
+    else {
+        tracing::info!("No AcoustID API key: new tracks won't be identified");
+        return None;
+    };
+    if !fingerprint::is_fpcalc_available() {
+        tracing::info!("fpcalc not found: new tracks won't be identified");
+        return None;
+    }
+
+    let (tx, mut rx) = mpsc::channel::<PathBuf>(ENRICH_QUEUE);
+    let tagging = config.tagging.clone();
+    tokio::spawn(async move {
+        let service = EnrichmentService::new(EnrichmentConfig {
+            acoustid_api_key: api_key,
+            ..Default::default()
+        });
+        while let Some(path) = rx.recv().await {
+            if !readonly::is_enabled() {
+                identify(&pool, &service, &tagging, &path).await;
+            }
+        }
+    });
+    Some(tx)
+}
+
+/// Identify one track and store the match for review, or write it when the
+/// track's folder says to
+async fn identify(
+    pool: &SqlitePool,
+    service: &EnrichmentService,
+    tagging: &TaggingConfig,
+    path: &Path,
+) {
+    let rules = folders::load(pool).await.unwrap_or_else(|e| {
+        tracing::warn!("Failed to load folder defaults: {}", e);
+        folders::FolderRules::default()
+    });
+    let rule = rules.for_path(path).cloned();
+    if rule.as_ref().is_some_and(|rule| !rule.enabled) {
+        tracing::debug!(path = %path.display(), "Enrichment is off for this folder");
+        return;
+    }
+    let Ok(Some(track)) = db::get_track_by_path(pool, &path.to_string_lossy()).await else {
+        return;
+    };
+    let result = service.identify_track(path).await;
+    crate::stats::record_identification(pool, crate::stats::IdentifyOutcome::of(&result)).await;
+    let identification = match result {
+        Ok(identification) => identification,
+        Err(e) => {
+            tracing::debug!(path = %path.display(), "Not identified: {}", e);
+            return;
+        }
+    };
+    let found = &identification.track;
+    let title = found.title.as_deref().unwrap_or_default();
+    match db::upsert_track_match(
+        pool,
+        track.id,
+        "acoustid",
+        identification.score,
+        found.recording_id.as_deref(),
+        title,
+        found.artist.as_deref(),
+        identification.scores.title,
+        identification.scores.artist,
+    )
+    .await
+    {
+        Ok(_) => tracing::info!(
+            path = %path.display(),
+            score = identification.score,
+            "Identified as {:?}",
+            title
+        ),
+        Err(e) => tracing::warn!("Failed to store match: {}", e),
+    }
+
+    if let Some(rule) = rule
+        && rule.auto_write
+        && identification.auto_accepted(rule.min_confidence())
+    {
+        write_match(pool, tagging, &rule, path, &identification).await;
+    }
+}
+
+/// Write an accepted match to the file, keeping hand-edited fields
+async fn write_match(
+    pool: &SqlitePool,
+    tagging: &TaggingConfig,
+    rule: &folders::FolderDefaults,
+    path: &Path,
+    identification: &crate::enrichment::TrackIdentification,
+) {
+    let options = metadata::WriteOptions2 {
+        only_fill_empty: rule.fill_only,
+        write_musicbrainz_ids: true,
+        placeholders: metadata::PlaceholderDetector::from_config(tagging),
+        genre_map: metadata::genres::GenreMap::from_config(tagging),
+    };
+    match write_queue::write_match(pool, path, identification, options, &tagging.manual_edits).await
+    {
+        Ok(Outcome::Written(fields)) => tracing::info!(
+            path = %path.display(),
+            fields,
+            "Wrote match (folder auto-write)"
+        ),
+        Ok(Outcome::Deferred) => {}
+        Err(e) => tracing::warn!(path = %path.display(), "Failed to write match: {}", e),
+    }
+}
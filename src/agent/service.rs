@@ -0,0 +1,432 @@
+//! Installing the agent as a system service.
+//!
+//! - Windows: registered with the Service Control Manager (via `sc.exe`, so
+//!   it needs an elevated prompt), starting automatically with the machine.
+//!   It runs as LocalSystem, so the command line pins the installing user's
+//!   config directory, and logs go to `agent.log` next to the database.
+//! - Linux: a systemd user unit in `~/.config/systemd/user/`, enabled and
+//!   started with `systemctl --user`; logs go to the journal.
+//!
+//! Each profile gets its own service, named after it.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::AgentError;
+use crate::{config, profile, readonly};
+
+/// Display name shown in the service manager
+const DISPLAY_NAME: &str = "Music Minder Agent";
+
+/// Name the active profile's service is registered under.
+pub fn service_name() -> String {
+    if profile::is_default() {
+        "music-minder-agent".to_string()
+    } else {
+        format!("music-minder-agent-{}", profile::active())
+    }
+}
+
+/// Arguments the installed service starts the executable with.
+///
+/// Pins the config directory, profile and working directory (the default
+/// profile's database is relative to it) so the service finds the same
+/// library as the user installing it. `extra` are further `agent` options.
+pub fn service_args(config_dir: &Path, working_dir: &Path, extra: &[String]) -> Vec<String> {
+    let mut args = vec![
+        "--config-dir".to_string(),
+        config_dir.display().to_string(),
+        "--profile".to_string(),
+        profile::active(),
+    ];
+    if readonly::is_enabled() {
+        args.push("--read-only".to_string());
+    }
+    args.extend([
+        "agent".to_string(),
+        "--working-dir".to_string(),
+        working_dir.display().to_string(),
+    ]);
+    args.extend(extra.iter().cloned());
+    args
+}
+
+/// Install and start the service. Returns what was done, for the user.
+pub fn install(extra: &[String]) -> Result<String, AgentError> {
+    let exe = std::env::current_exe()?;
+    let config_dir = config::config_dir()
+        .ok_or_else(|| AgentError::Service("Could not determine config directory".into()))?;
+    let working_dir = std::env::current_dir()?;
+    let name = service_name();
+
+    if cfg!(windows) {
+        let log_file = working_dir.join(profile::data_path("agent.log"));
+        let mut args = service_args(&config_dir, &working_dir, extra);
+        args.extend([
+            "--service".to_string(),
+            "--log-file".to_string(),
+            log_file.display().to_string(),
+        ]);
+        let command_line = std::iter::once(exe.display().to_string())
+            .chain(args)
+            .map(|arg| windows_quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ");
+        run_tool(
+            "sc.exe",
+            &[
+                "create",
+                &name,
+                "binPath=",
+                &command_line,
+                "start=",
+                "auto",
+                "DisplayName=",
+                &format!("{} ({})", DISPLAY_NAME, profile::active()),
+            ],
+        )?;
+        run_tool(
+            "sc.exe",
+            &[
+                "description",
+                &name,
+                "Watches, scans and maintains the Music Minder library",
+            ],
+        )?;
+        run_tool("sc.exe", &["start", &name])?;
+        Ok(format!(
+            "Installed and started Windows service {:?}\nLogs: {}",
+            name,
+            log_file.display()
+        ))
+    } else if cfg!(target_os = "linux") {
+        let unit_path = systemd_unit_path(&name)?;
+        let args = service_args(&config_dir, &working_dir, extra);
+        if let Some(dir) = unit_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&unit_path, systemd_unit(&exe, &args, &working_dir))?;
+        run_tool("systemctl", &["--user", "daemon-reload"])?;
+        run_tool("systemctl", &["--user", "enable", "--now", &name])?;
+        Ok(format!(
+            "Installed and started {}\nLogs: journalctl --user -u {}\n\
+             To keep it running while you're logged out: loginctl enable-linger",
+            unit_path.display(),
+            name
+        ))
+    } else {
+        Err(AgentError::Service(format!(
+            "Service install isn't supported on this system; start `{} {}` at login instead",
+            exe.display(),
+            service_args(&config_dir, &working_dir, extra).join(" ")
+        )))
+    }
+}
+
+/// Stop and remove the service. Returns what was done, for the user.
+pub fn uninstall() -> Result<String, AgentError> {
+    let name = service_name();
+    if cfg!(windows) {
+        // Not running is fine
+        let _ = run_tool("sc.exe", &["stop", &name]);
+        run_tool("sc.exe", &["delete", &name])?;
+        Ok(format!("Removed Windows service {:?}", name))
+    } else if cfg!(target_os = "linux") {
+        let unit_path = systemd_unit_path(&name)?;
+        if !unit_path.exists() {
+            return Err(AgentError::Service(format!(
+                "{} is not installed",
+                unit_path.display()
+            )));
+        }
+        let _ = run_tool("systemctl", &["--user", "disable", "--now", &name]);
+        std::fs::remove_file(&unit_path)?;
+        run_tool("systemctl", &["--user", "daemon-reload"])?;
+        Ok(format!("Removed {}", unit_path.display()))
+    } else {
+        Err(AgentError::Service(
+            "Service install isn't supported on this system".into(),
+        ))
+    }
+}
+
+fn systemd_unit_path(name: &str) -> Result<PathBuf, AgentError> {
+    // The user's own config dir, not a --config-dir override
+    let dir = dirs::config_dir()
+        .ok_or_else(|| AgentError::Service("Could not determine config directory".into()))?;
+    Ok(dir
+        .join("systemd")
+        .join("user")
+        .join(format!("{}.service", name)))
+}
+
+/// Contents of the systemd user unit.
+pub fn systemd_unit(exe: &Path, args: &[String], working_dir: &Path) -> String {
+    let exec = std::iter::once(exe.display().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| systemd_quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "[Unit]\n\
+         Description={} ({})\n\
+         After=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         ExecStart={}\n\
+         WorkingDirectory={}\n\
+         Restart=on-failure\n\
+         RestartSec=10\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        DISPLAY_NAME,
+        profile::active(),
+        exec,
+        working_dir.display().to_string().replace('%', "%%")
+    )
+}
+
+/// Quote one word of a unit file command line
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%");
+    if !escaped.is_empty() && !escaped.contains([' ', '\t', '"', '\\', '\'', ';', '$']) {
+        return escaped;
+    }
+    format!(
+        "\"{}\"",
+        escaped
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('$', "$$")
+    )
+}
+
+/// Quote one argument the way Windows command-line parsing expects
+fn windows_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // Backslashes before a quote are doubled, and the quote escaped
+                quoted.extend(std::iter::repeat_n('\\', backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+                continue;
+            }
+            _ => {}
+        }
+        if c != '\\' {
+            quoted.extend(std::iter::repeat_n('\\', backslashes));
+            backslashes = 0;
+            quoted.push(c);
+        }
+    }
+    // Backslashes before the closing quote are doubled too
+    quoted.extend(std::iter::repeat_n('\\', backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// Run a service manager command, failing with its output if it fails
+fn run_tool(program: &str, args: &[&str]) -> Result<(), AgentError> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| AgentError::Service(format!("Couldn't run {}: {}", program, e)))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let mut message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if message.is_empty() {
+        message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    }
+    Err(AgentError::Service(format!(
+        "{} {} failed: {}",
+        program,
+        args.first().unwrap_or(&""),
+        message
+    )))
+}
+
+/// Running under the Windows Service Control Manager.
+#[cfg(windows)]
+pub mod windows {
+    use std::ptr;
+    use std::sync::OnceLock;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+    use tokio::sync::Notify;
+    use windows_sys::Win32::Foundation::{ERROR_CALL_NOT_IMPLEMENTED, NO_ERROR};
+    use windows_sys::Win32::System::Services::{
+        RegisterServiceCtrlHandlerExW, SERVICE_ACCEPT_SHUTDOWN, SERVICE_ACCEPT_STOP,
+        SERVICE_CONTROL_INTERROGATE, SERVICE_CONTROL_SHUTDOWN, SERVICE_CONTROL_STOP,
+        SERVICE_RUNNING, SERVICE_START_PENDING, SERVICE_STATUS, SERVICE_STATUS_CURRENT_STATE,
+        SERVICE_STOP_PENDING, SERVICE_STOPPED, SERVICE_TABLE_ENTRYW, SERVICE_WIN32_OWN_PROCESS,
+        SetServiceStatus, StartServiceCtrlDispatcherW,
+    };
+
+    use super::super::{AgentError, AgentOptions};
+
+    /// Options for the service thread (the SCM calls it with no context)
+    static OPTIONS: OnceLock<AgentOptions> = OnceLock::new();
+    /// Signalled when the SCM asks the service to stop
+    static STOP: Notify = Notify::const_new();
+    /// Handle for reporting status to the SCM
+    static STATUS_HANDLE: AtomicPtr<core::ffi::c_void> = AtomicPtr::new(ptr::null_mut());
+
+    /// Hand this process to the Service Control Manager and run the agent
+    /// until it's stopped. Blocks until the service stops.
+    pub fn run(options: AgentOptions) -> Result<(), AgentError> {
+        let _ = OPTIONS.set(options);
+        // The name is ignored for SERVICE_WIN32_OWN_PROCESS services
+        let mut name: Vec<u16> = "music-minder-agent\0".encode_utf16().collect();
+        let table = [
+            SERVICE_TABLE_ENTRYW {
+                lpServiceName: name.as_mut_ptr(),
+                lpServiceProc: Some(service_main),
+            },
+            SERVICE_TABLE_ENTRYW {
+                lpServiceName: ptr::null_mut(),
+                lpServiceProc: None,
+            },
+        ];
+        // SAFETY: the table is null-terminated and outlives the call, which
+        // returns only once the service has stopped
+        if unsafe { StartServiceCtrlDispatcherW(table.as_ptr()) } == 0 {
+            return Err(AgentError::Service(format!(
+                "Not started by the service manager: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(())
+    }
+
+    unsafe extern "system" fn service_main(_argc: u32, _argv: *mut windows_sys::core::PWSTR) {
+        let name: Vec<u16> = "music-minder-agent\0".encode_utf16().collect();
+        // SAFETY: the name is null-terminated; the handler is 'static
+        let handle = unsafe {
+            RegisterServiceCtrlHandlerExW(name.as_ptr(), Some(control_handler), ptr::null())
+        };
+        if handle.is_null() {
+            return;
+        }
+        STATUS_HANDLE.store(handle, Ordering::SeqCst);
+        report(SERVICE_START_PENDING, NO_ERROR);
+
+        let Some(options) = OPTIONS.get().cloned() else {
+            report(SERVICE_STOPPED, ERROR_CALL_NOT_IMPLEMENTED);
+            return;
+        };
+        let exit = match tokio::runtime::Runtime::new() {
+            Ok(rt) => {
+                report(SERVICE_RUNNING, NO_ERROR);
+                match rt.block_on(super::super::run(options, STOP.notified())) {
+                    Ok(()) => NO_ERROR,
+                    Err(e) => {
+                        tracing::error!("Agent failed: {}", e);
+                        1
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to start runtime: {}", e);
+                1
+            }
+        };
+        report(SERVICE_STOPPED, exit);
+    }
+
+    unsafe extern "system" fn control_handler(
+        control: u32,
+        _event_type: u32,
+        _event_data: *mut core::ffi::c_void,
+        _context: *mut core::ffi::c_void,
+    ) -> u32 {
+        match control {
+            SERVICE_CONTROL_STOP | SERVICE_CONTROL_SHUTDOWN => {
+                report(SERVICE_STOP_PENDING, NO_ERROR);
+                // Stores a permit if the agent isn't waiting yet
+                STOP.notify_one();
+                NO_ERROR
+            }
+            SERVICE_CONTROL_INTERROGATE => NO_ERROR,
+            _ => ERROR_CALL_NOT_IMPLEMENTED,
+        }
+    }
+
+    fn report(state: SERVICE_STATUS_CURRENT_STATE, exit_code: u32) {
+        let handle = STATUS_HANDLE.load(Ordering::SeqCst);
+        if handle.is_null() {
+            return;
+        }
+        let status = SERVICE_STATUS {
+            dwServiceType: SERVICE_WIN32_OWN_PROCESS,
+            dwCurrentState: state,
+            dwControlsAccepted: if state == SERVICE_RUNNING {
+                SERVICE_ACCEPT_STOP | SERVICE_ACCEPT_SHUTDOWN
+            } else {
+                0
+            },
+            dwWin32ExitCode: exit_code,
+            dwServiceSpecificExitCode: 0,
+            dwCheckPoint: 0,
+            dwWaitHint: if state == SERVICE_RUNNING || state == SERVICE_STOPPED {
+                0
+            } else {
+                super::super::SHUTDOWN_GRACE.as_millis() as u32
+            },
+        };
+        // SAFETY: the handle came from RegisterServiceCtrlHandlerExW
+        unsafe {
+            SetServiceStatus(handle, &status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systemd_unit() {
+        let unit = systemd_unit(
+            Path::new("/opt/music minder/music-minder"),
+            &["--profile".to_string(), "100%".to_string()],
+            Path::new("/home/me/Music"),
+        );
+        assert!(unit.contains("\nExecStart=\"/opt/music minder/music-minder\" --profile 100%%\n"));
+        assert!(unit.contains("\nWorkingDirectory=/home/me/Music\n"));
+        assert!(unit.contains("\nWantedBy=default.target\n"));
+    }
+
+    #[test]
+    fn test_windows_quote() {
+        assert_eq!(windows_quote("agent"), "agent");
+        assert_eq!(
+            windows_quote(r"C:\Program Files\Music Minder\"),
+            r#""C:\Program Files\Music Minder\\""#
+        );
+        assert_eq!(windows_quote(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(windows_quote(""), r#""""#);
+    }
+
+    #[test]
+    fn test_service_args() {
+        let args = service_args(
+            Path::new("/home/me/.config/music-minder"),
+            Path::new("/home/me"),
+            &["--listen".to_string(), "0.0.0.0:7431".to_string()],
+        );
+        let agent = args.iter().position(|a| a == "agent").unwrap();
+        // Global options come before the subcommand
+        assert_eq!(args[..2], ["--config-dir", "/home/me/.config/music-minder"]);
+        assert_eq!(args[agent + 1..agent + 3], ["--working-dir", "/home/me"]);
+        assert_eq!(args.last().unwrap(), "0.0.0.0:7431");
+    }
+}
@@ -0,0 +1,307 @@
+//! AccurateRip verification.
+//!
+//! AccurateRip keeps checksums of each track as ripped by other people. When
+//! ours matches, the rip is bit-identical to theirs, which a read error would
+//! almost certainly have broken. The database is keyed by three IDs computed
+//! from the disc's table of contents; each entry lists the checksums of every
+//! track for one pressing, with how many rips agreed on them.
+//!
+//! Matches need the drive's read offset to be set (`--read-offset`); without
+//! it tracks come out as "not verified" rather than failing.
+
+use std::path::Path;
+
+use super::RipError;
+use crate::enrichment::discid::DiscToc;
+
+/// CD audio samples (stereo frames) per sector
+const SAMPLES_PER_SECTOR: usize = 588;
+
+/// Samples left out of the checksums at the start of the first track and
+/// the end of the last, where drive offsets make rips differ
+const SKIPPED_SAMPLES: usize = 5 * SAMPLES_PER_SECTOR;
+
+/// The IDs a disc is filed under
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiscIds {
+    pub tracks: usize,
+    pub id1: u32,
+    pub id2: u32,
+    pub cddb: u32,
+}
+
+impl DiscIds {
+    pub fn from_toc(toc: &DiscToc) -> Self {
+        // AccurateRip counts sectors from the first track, not the lead-in
+        let lba = |sector: u32| sector.saturating_sub(150);
+        let tracks = toc.offsets.len();
+
+        let mut id1 = lba(toc.lead_out);
+        let mut id2 = lba(toc.lead_out).max(1).wrapping_mul(tracks as u32 + 1);
+        let mut digits = 0;
+        for (i, &offset) in toc.offsets.iter().enumerate() {
+            id1 = id1.wrapping_add(lba(offset));
+            id2 = id2.wrapping_add(lba(offset).max(1).wrapping_mul(i as u32 + 1));
+            digits += digit_sum(offset / 75);
+        }
+        let first = toc.offsets.first().copied().unwrap_or(150);
+        let seconds = toc.lead_out / 75 - first / 75;
+        let cddb = ((digits % 255) << 24) | (seconds << 8) | tracks as u32;
+
+        Self {
+            tracks,
+            id1,
+            id2,
+            cddb,
+        }
+    }
+
+    /// Where the disc's entries are published
+    pub fn url(&self) -> String {
+        format!(
+            "http://www.accuraterip.com/accuraterip/{:x}/{:x}/{:x}/dBAR-{:03}-{:08x}-{:08x}-{:08x}.bin",
+            self.id1 & 0xF,
+            (self.id1 >> 4) & 0xF,
+            (self.id1 >> 8) & 0xF,
+            self.tracks,
+            self.id1,
+            self.id2,
+            self.cddb
+        )
+    }
+}
+
+fn digit_sum(mut n: u32) -> u32 {
+    let mut sum = 0;
+    while n > 0 {
+        sum += n % 10;
+        n /= 10;
+    }
+    sum
+}
+
+/// One track's checksum in one pressing's entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackEntry {
+    /// Rips that agreed on the checksum
+    pub confidence: u8,
+    pub crc: u32,
+}
+
+/// Parse a database response: one list of track entries per pressing
+pub fn parse_response(mut bytes: &[u8]) -> Vec<Vec<TrackEntry>> {
+    let mut pressings = Vec::new();
+    // Header: track count, then the three disc IDs; 9 bytes per track
+    while let Some((&count, rest)) = bytes.split_first() {
+        let rest = rest.get(12..).unwrap_or_default();
+        let Some(body) = rest.get(..count as usize * 9) else {
+            break;
+        };
+        pressings.push(
+            body.chunks_exact(9)
+                .map(|t| TrackEntry {
+                    confidence: t[0],
+                    crc: u32::from_le_bytes([t[1], t[2], t[3], t[4]]),
+                })
+                .collect(),
+        );
+        bytes = &rest[body.len()..];
+    }
+    pressings
+}
+
+/// Fetch the disc's entries; none when the disc isn't in the database
+pub async fn lookup(toc: &DiscToc) -> Result<Vec<Vec<TrackEntry>>, RipError> {
+    let url = DiscIds::from_toc(toc).url();
+    crate::enrichment::http::guard(&url).map_err(|e| RipError::Verify(e.to_string()))?;
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| RipError::Verify(e.to_string()))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    let bytes = response
+        .error_for_status()
+        .map_err(|e| RipError::Verify(e.to_string()))?
+        .bytes()
+        .await
+        .map_err(|e| RipError::Verify(e.to_string()))?;
+    Ok(parse_response(&bytes))
+}
+
+/// A track's AccurateRip checksums (the database holds either version)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checksums {
+    pub v1: u32,
+    pub v2: u32,
+}
+
+/// Checksums of a track's samples, each a stereo frame as a little-endian
+/// `u32` (left in the low half)
+pub fn checksums(samples: &[u32], first_track: bool, last_track: bool) -> Checksums {
+    let from = if first_track { SKIPPED_SAMPLES - 1 } else { 0 };
+    let to = if last_track {
+        samples.len().saturating_sub(SKIPPED_SAMPLES)
+    } else {
+        samples.len()
+    };
+    let (mut v1, mut v2) = (0u32, 0u32);
+    for (i, &sample) in samples.iter().enumerate() {
+        let multiplier = i + 1;
+        if multiplier < from || multiplier > to {
+            continue;
+        }
+        let product = sample as u64 * multiplier as u64;
+        v1 = v1.wrapping_add(product as u32);
+        v2 = v2.wrapping_add((product as u32).wrapping_add((product >> 32) as u32));
+    }
+    Checksums { v1, v2 }
+}
+
+/// The samples of a 16-bit stereo WAV file, as [`checksums`] takes them
+pub fn wav_samples(path: &Path) -> std::io::Result<Vec<u32>> {
+    let data = std::fs::read(path)?;
+    let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, "not a WAV file");
+    if data.get(..4) != Some(b"RIFF") || data.get(8..12) != Some(b"WAVE") {
+        return Err(invalid());
+    }
+    let mut chunks = data.get(12..).ok_or_else(invalid)?;
+    while chunks.len() >= 8 {
+        let size = u32::from_le_bytes([chunks[4], chunks[5], chunks[6], chunks[7]]) as usize;
+        let body = chunks.get(8..).ok_or_else(invalid)?;
+        if &chunks[..4] == b"data" {
+            let body = &body[..size.min(body.len())];
+            return Ok(body
+                .chunks_exact(4)
+                .map(|s| u32::from_le_bytes([s[0], s[1], s[2], s[3]]))
+                .collect());
+        }
+        // Chunks are padded to an even size
+        chunks = body.get(size + size % 2..).unwrap_or_default();
+    }
+    Err(invalid())
+}
+
+/// How a ripped track compares with the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    /// Matches rips by `confidence` others
+    Accurate { confidence: u32 },
+    /// The disc is known but no pressing has this checksum
+    Mismatch,
+    /// The disc isn't in the database (or couldn't be fetched)
+    Unknown,
+}
+
+impl Verification {
+    pub fn describe(&self) -> String {
+        match self {
+            Verification::Accurate { confidence } => {
+                format!("accurately ripped (confidence {})", confidence)
+            }
+            Verification::Mismatch => "not verified: no matching AccurateRip checksum".to_string(),
+            Verification::Unknown => "not verified: disc not in AccurateRip".to_string(),
+        }
+    }
+}
+
+/// Compare track `index` (0-based) with every pressing's entry
+pub fn verify(index: usize, sums: Checksums, pressings: &[Vec<TrackEntry>]) -> Verification {
+    if pressings.is_empty() {
+        return Verification::Unknown;
+    }
+    let confidence: u32 = pressings
+        .iter()
+        .filter_map(|p| p.get(index))
+        .filter(|t| t.crc == sums.v1 || t.crc == sums.v2)
+        .map(|t| t.confidence as u32)
+        .sum();
+    if confidence > 0 {
+        Verification::Accurate { confidence }
+    } else {
+        Verification::Mismatch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn toc() -> DiscToc {
+        DiscToc {
+            first_track: 1,
+            lead_out: 267257,
+            offsets: vec![
+                150, 22767, 41887, 58317, 72102, 91375, 104652, 115380, 132165, 143932, 159870,
+                174597,
+            ],
+        }
+    }
+
+    #[test]
+    fn test_disc_ids() {
+        let ids = DiscIds::from_toc(&toc());
+        assert_eq!(ids.tracks, 12);
+        // Sum of the offsets and lead-out, counted from the first track
+        let id1: u32 = toc().offsets.iter().map(|o| o - 150).sum::<u32>() + 267257 - 150;
+        assert_eq!(ids.id1, id1);
+        // Disc length in seconds and track count are the last bytes of the CDDB ID
+        assert_eq!(ids.cddb & 0xFF, 12);
+        assert_eq!((ids.cddb >> 8) & 0xFFFF, 267257 / 75 - 150 / 75);
+
+        let url = ids.url();
+        assert!(url.starts_with("http://www.accuraterip.com/accuraterip/"));
+        assert!(url.ends_with(&format!(
+            "dBAR-012-{:08x}-{:08x}-{:08x}.bin",
+            ids.id1, ids.id2, ids.cddb
+        )));
+    }
+
+    #[test]
+    fn test_parse_and_verify() {
+        // Two pressings of a two-track disc
+        let mut bytes = Vec::new();
+        for (conf, crcs) in [(3u8, [0x11u32, 0x22]), (5, [0x11, 0x99])] {
+            bytes.push(2);
+            bytes.extend_from_slice(&[0; 12]);
+            for crc in crcs {
+                bytes.push(conf);
+                bytes.extend_from_slice(&crc.to_le_bytes());
+                bytes.extend_from_slice(&[0; 4]);
+            }
+        }
+        let pressings = parse_response(&bytes);
+        assert_eq!(pressings.len(), 2);
+
+        let sums = |crc| Checksums { v1: crc, v2: 0 };
+        assert_eq!(
+            verify(0, sums(0x11), &pressings),
+            Verification::Accurate { confidence: 8 }
+        );
+        assert_eq!(
+            verify(1, sums(0x22), &pressings),
+            Verification::Accurate { confidence: 3 }
+        );
+        assert_eq!(verify(1, sums(0x33), &pressings), Verification::Mismatch);
+        assert_eq!(verify(0, sums(0x11), &[]), Verification::Unknown);
+    }
+
+    #[test]
+    fn test_checksums() {
+        // A middle track: every sample weighted by its position
+        let sums = checksums(&[1, 2, 3], false, false);
+        assert_eq!(sums.v1, 1 + 2 * 2 + 3 * 3);
+        assert_eq!(sums.v2, sums.v1);
+
+        // v2 folds the carry of large products back in
+        let sums = checksums(&[0, u32::MAX], false, false);
+        assert_eq!(sums.v1, u32::MAX.wrapping_mul(2));
+        assert_eq!(sums.v2, u32::MAX.wrapping_mul(2).wrapping_add(1));
+
+        // The edges of the disc are left out
+        let samples = vec![1; SKIPPED_SAMPLES * 3];
+        let middle = checksums(&samples, false, false);
+        assert_ne!(checksums(&samples, true, false), middle);
+        assert_ne!(checksums(&samples, false, true), middle);
+    }
+}
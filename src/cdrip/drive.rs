@@ -0,0 +1,191 @@
+//! Reading the drive and encoding, by shelling out to `cdparanoia` and `flac`.
+//!
+//! cdparanoia re-reads sectors until consecutive reads agree, which catches
+//! most scratches before AccurateRip gets a say. Install both tools:
+//! - Debian/Ubuntu: `apt install cdparanoia flac`
+//! - macOS: `brew install cdparanoia flac`
+
+use std::path::Path;
+use std::process::Command;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use super::RipError;
+use crate::enrichment::discid::DiscToc;
+
+/// Windows: CREATE_NO_WINDOW flag to prevent console popup
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Names cdparanoia is installed under (libcdio ships it as `cd-paranoia`)
+const CDPARANOIA_PATHS: &[&str] = &[
+    "cdparanoia",
+    "cd-paranoia",
+    "/usr/bin/cdparanoia",
+    "/usr/local/bin/cdparanoia",
+    "/opt/homebrew/bin/cdparanoia",
+];
+
+const FLAC_PATHS: &[&str] = &[
+    "flac",
+    "/usr/bin/flac",
+    "/usr/local/bin/flac",
+    "/opt/homebrew/bin/flac",
+];
+
+/// Sectors before the first track (AccurateRip and cdparanoia count from
+/// the first track, disc IDs from the start of the disc)
+const LEAD_IN: u32 = 150;
+
+fn command(program: &str) -> Command {
+    #[allow(unused_mut)]
+    let mut cmd = Command::new(program);
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+/// First of `paths` that runs with `version_arg`
+fn find_tool(paths: &'static [&'static str], version_arg: &str) -> Option<&'static str> {
+    paths.iter().copied().find(|path| {
+        // cdparanoia exits with an error after printing its version, so
+        // just being able to start it counts
+        command(path).arg(version_arg).output().is_ok()
+    })
+}
+
+fn cdparanoia() -> Result<&'static str, RipError> {
+    find_tool(CDPARANOIA_PATHS, "-V").ok_or(RipError::ToolMissing("cdparanoia"))
+}
+
+fn flac() -> Result<&'static str, RipError> {
+    find_tool(FLAC_PATHS, "--version").ok_or(RipError::ToolMissing("flac"))
+}
+
+/// Check that both tools are installed, naming the first that isn't
+pub fn check_tools() -> Result<(), RipError> {
+    cdparanoia()?;
+    flac()?;
+    Ok(())
+}
+
+fn paranoia(device: Option<&str>) -> Result<Command, RipError> {
+    let mut cmd = command(cdparanoia()?);
+    if let Some(device) = device {
+        cmd.args(["-d", device]);
+    }
+    Ok(cmd)
+}
+
+/// Read the table of contents of the disc in the drive
+pub fn read_toc(device: Option<&str>) -> Result<DiscToc, RipError> {
+    let output = paranoia(device)?.arg("-Q").output()?;
+    // The table goes to stderr, along with any error
+    let text = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(RipError::NoDisc);
+    }
+    parse_toc(&text).ok_or(RipError::NoDisc)
+}
+
+/// Parse the table `cdparanoia -Q` prints:
+///
+/// ```text
+/// track        length               begin        copy pre ch
+/// ===========================================================
+///   1.    16503 [03:40.03]        0 [00:00.00]    no   no  2
+///   2.    14530 [03:13.55]    16503 [03:40.03]    no   no  2
+/// ```
+pub fn parse_toc(output: &str) -> Option<DiscToc> {
+    let mut first_track = None;
+    let mut offsets = Vec::new();
+    let mut lead_out = 0;
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(number) = fields
+            .next()
+            .and_then(|n| n.strip_suffix('.'))
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let length = fields.next().and_then(|n| n.parse::<u32>().ok())?;
+        let begin = fields.nth(1).and_then(|n| n.parse::<u32>().ok())?;
+        first_track.get_or_insert(number);
+        offsets.push(begin + LEAD_IN);
+        lead_out = begin + length + LEAD_IN;
+    }
+    Some(DiscToc {
+        first_track: first_track?,
+        lead_out,
+        offsets,
+    })
+}
+
+/// Rip track `number` to a WAV file, correcting by the drive's read offset
+/// (in samples)
+pub fn rip_track(
+    device: Option<&str>,
+    number: u32,
+    read_offset: i32,
+    wav: &Path,
+) -> Result<(), RipError> {
+    let mut cmd = paranoia(device)?;
+    if read_offset != 0 {
+        cmd.args(["-O", &read_offset.to_string()]);
+    }
+    let output = cmd
+        .args(["-q", "-w", &number.to_string()])
+        .arg(wav)
+        .output()?;
+    if !output.status.success() {
+        return Err(RipError::Read {
+            track: number,
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Encode a WAV file to FLAC
+pub fn encode_flac(number: u32, wav: &Path, flac_path: &Path) -> Result<(), RipError> {
+    let output = command(flac()?)
+        .args(["--best", "--silent", "--force", "-o"])
+        .arg(flac_path)
+        .arg(wav)
+        .output()?;
+    if !output.status.success() {
+        return Err(RipError::Encode {
+            track: number,
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toc() {
+        let output = "\
+cdparanoia III release 10.2 (September 11, 2008)
+
+Table of contents (audio tracks only):
+track        length               begin        copy pre ch
+===========================================================
+  1.    16503 [03:40.03]        0 [00:00.00]    no   no  2
+  2.    14530 [03:13.55]    16503 [03:40.03]    no   no  2
+  3.    20112 [04:28.12]    31033 [06:53.58]    no   no  2
+TOTAL   51145 [11:21.70]    (audio only)
+";
+        let toc = parse_toc(output).unwrap();
+        assert_eq!(toc.first_track, 1);
+        assert_eq!(toc.offsets, vec![150, 16653, 31183]);
+        assert_eq!(toc.lead_out, 31033 + 20112 + 150);
+
+        assert_eq!(parse_toc("Unable to open disc."), None);
+    }
+}
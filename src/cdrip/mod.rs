@@ -0,0 +1,280 @@
+//! Ripping audio CDs straight into the organized library (the `cd-rip`
+//! feature).
+//!
+//! A rip reads the disc's table of contents and looks it up by MusicBrainz
+//! disc ID, then for each track:
+//! 1. reads it with cdparanoia into a staging folder under the destination,
+//! 2. checks it against AccurateRip,
+//! 3. encodes it to FLAC, writes the release's tags and front cover,
+//! 4. moves it into place with the organize pattern, and indexes it.
+//!
+//! Discs MusicBrainz doesn't know are still ripped, tagged with their track
+//! numbers only, ready for fingerprinting. A track AccurateRip disagrees
+//! with is kept and reported; scratched discs often rip fine and a second
+//! attempt can be compared by hand.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::cdrip::{self, RipOptions};
+//!
+//! let report = cdrip::rip_disc(None, &options, |event| println!("{:?}", event)).await?;
+//! ```
+
+pub mod accuraterip;
+pub mod drive;
+
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+use crate::enrichment::coverart::{CoverArtClient, CoverSize};
+use crate::enrichment::discid::DiscToc;
+use crate::enrichment::domain::{DiscMatch, IdentifiedTrack};
+use crate::enrichment::musicbrainz::MusicBrainzClient;
+use crate::metadata::{self, WriteOptions2};
+use crate::provenance::{self, FieldSource};
+use accuraterip::Verification;
+
+/// Errors that stop a rip
+#[derive(Debug, thiserror::Error)]
+pub enum RipError {
+    #[error("{0} not found; install it to rip CDs")]
+    ToolMissing(&'static str),
+
+    #[error("No audio CD in the drive")]
+    NoDisc,
+
+    #[error("Reading track {track} failed: {message}")]
+    Read { track: u32, message: String },
+
+    #[error("Encoding track {track} failed: {message}")]
+    Encode { track: u32, message: String },
+
+    #[error("AccurateRip lookup failed: {0}")]
+    Verify(String),
+
+    #[error("Tagging failed: {0}")]
+    Tag(String),
+
+    #[error("Moving into the library failed: {0}")]
+    Organize(String),
+
+    #[error(transparent)]
+    ReadOnly(#[from] crate::readonly::ReadOnlyError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// What to rip and where to put it
+#[derive(Debug, Clone)]
+pub struct RipOptions {
+    /// Drive to read (default: cdparanoia's pick)
+    pub device: Option<String>,
+    /// The drive's read offset in samples, as listed by AccurateRip
+    pub read_offset: i32,
+    /// Library root the tracks are organized into
+    pub destination: PathBuf,
+    /// Organize pattern, e.g. "{Artist}/{Album}/{TrackNum} - {Title}.{ext}"
+    pub pattern: String,
+}
+
+/// Progress of a rip
+#[derive(Debug, Clone)]
+pub enum RipEvent {
+    /// The disc was looked up; `release` is "Artist - Album" when found
+    Identified {
+        tracks: usize,
+        release: Option<String>,
+    },
+    /// Reading a track started
+    Ripping { track: u32, total: usize },
+    /// A track is in the library
+    TrackDone(RippedTrack),
+}
+
+/// A ripped track
+#[derive(Debug, Clone)]
+pub struct RippedTrack {
+    pub number: u32,
+    /// Where it was organized to
+    pub path: PathBuf,
+    pub verification: Verification,
+}
+
+/// Result of a rip
+#[derive(Debug, Clone)]
+pub struct RipReport {
+    pub disc_id: String,
+    /// Whether MusicBrainz knew the disc
+    pub identified: bool,
+    pub tracks: Vec<RippedTrack>,
+}
+
+impl RipReport {
+    /// Tracks AccurateRip confirmed
+    pub fn accurate(&self) -> usize {
+        self.tracks
+            .iter()
+            .filter(|t| matches!(t.verification, Verification::Accurate { .. }))
+            .count()
+    }
+}
+
+/// Rip the disc in the drive into `options.destination`, indexing the
+/// tracks into `pool` when given
+pub async fn rip_disc(
+    pool: Option<&SqlitePool>,
+    options: &RipOptions,
+    mut on_event: impl FnMut(RipEvent),
+) -> Result<RipReport, RipError> {
+    crate::readonly::ensure_writable("Ripping a CD")?;
+    drive::check_tools()?;
+
+    let device = options.device.clone();
+    let toc = tokio::task::spawn_blocking(move || drive::read_toc(device.as_deref()))
+        .await
+        .map_err(std::io::Error::other)??;
+    let disc_id = toc.disc_id();
+
+    let release = identify(&toc).await;
+    on_event(RipEvent::Identified {
+        tracks: toc.offsets.len(),
+        release: release.as_ref().and_then(|m| m.tracks.first()).map(|t| {
+            format!(
+                "{} - {}",
+                t.album_artist
+                    .as_deref()
+                    .or(t.artist.as_deref())
+                    .unwrap_or("?"),
+                t.album.as_deref().unwrap_or("?")
+            )
+        }),
+    });
+
+    // A failed lookup only costs the verification
+    let pressings = accuraterip::lookup(&toc).await.unwrap_or_else(|e| {
+        tracing::warn!("{}", e);
+        Vec::new()
+    });
+    let cover = match release
+        .as_ref()
+        .and_then(|m| m.tracks.first())
+        .and_then(|t| t.release_id.clone())
+    {
+        Some(id) => CoverArtClient::new()
+            .get_front_cover(&id, CoverSize::Large)
+            .await
+            .ok(),
+        None => None,
+    };
+
+    let staging = options
+        .destination
+        .join(format!(".music-minder-rip-{}", disc_id));
+    std::fs::create_dir_all(&staging)?;
+
+    let total = toc.offsets.len();
+    let mut tracks = Vec::with_capacity(total);
+    for index in 0..total {
+        let number = toc.first_track + index as u32;
+        on_event(RipEvent::Ripping {
+            track: number,
+            total,
+        });
+
+        let tags = release
+            .as_ref()
+            .and_then(|m| m.tracks.get(index).cloned())
+            .unwrap_or_else(|| IdentifiedTrack {
+                track_number: Some(number),
+                total_tracks: Some(toc.last_track()),
+                ..Default::default()
+            });
+        let wav = staging.join(format!("{:02}.wav", number));
+        let flac = staging.join(format!("{:02}.flac", number));
+
+        let device = options.device.clone();
+        let read_offset = options.read_offset;
+        let encoded = flac.clone();
+        let last = index + 1 == total;
+        let pressings_for_track = pressings.clone();
+        let verification = tokio::task::spawn_blocking(move || {
+            drive::rip_track(device.as_deref(), number, read_offset, &wav)?;
+            let samples = accuraterip::wav_samples(&wav)?;
+            let sums = accuraterip::checksums(&samples, index == 0, last);
+            drive::encode_flac(number, &wav, &encoded)?;
+            std::fs::remove_file(&wav)?;
+            Ok::<_, RipError>(accuraterip::verify(index, sums, &pressings_for_track))
+        })
+        .await
+        .map_err(std::io::Error::other)??;
+
+        let written = metadata::write(
+            &flac,
+            &tags,
+            &WriteOptions2 {
+                only_fill_empty: false,
+                write_musicbrainz_ids: true,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| RipError::Tag(e.to_string()))?;
+        if let Some(cover) = &cover {
+            metadata::write_cover_art(&flac, &cover.data, &cover.mime_type, false)
+                .map_err(|e| RipError::Tag(e.to_string()))?;
+        }
+
+        let path = organize(&flac, &options.pattern, &options.destination)?;
+        if let Some(pool) = pool {
+            crate::library::index_changed_file(pool, path.clone()).await;
+            if release.is_some() {
+                let score = release.as_ref().map(|m| if m.exact { 1.0 } else { 0.9 });
+                provenance::record_written(
+                    pool,
+                    &path,
+                    &written.fields_written,
+                    FieldSource::MusicBrainz,
+                    score,
+                )
+                .await;
+            }
+        }
+
+        let track = RippedTrack {
+            number,
+            path,
+            verification,
+        };
+        on_event(RipEvent::TrackDone(track.clone()));
+        tracks.push(track);
+    }
+
+    std::fs::remove_dir_all(&staging)?;
+    Ok(RipReport {
+        disc_id,
+        identified: release.is_some(),
+        tracks,
+    })
+}
+
+/// The disc's best MusicBrainz release, if any: an exact disc ID match
+/// before a similar table of contents
+async fn identify(toc: &DiscToc) -> Option<DiscMatch> {
+    match MusicBrainzClient::new().lookup_discid(toc).await {
+        Ok(matches) => matches
+            .into_iter()
+            .find(|m| m.tracks.len() == toc.offsets.len()),
+        Err(e) => {
+            tracing::info!("Disc {} not identified: {}", toc.disc_id(), e);
+            None
+        }
+    }
+}
+
+/// Move a tagged track from staging into the library
+fn organize(flac: &Path, pattern: &str, destination: &Path) -> Result<PathBuf, RipError> {
+    let tags = metadata::read(flac).map_err(|e| RipError::Tag(e.to_string()))?;
+    crate::organizer::organize_track(flac, &tags, pattern, destination)
+        .map_err(|e| RipError::Organize(e.to_string()))
+}
@@ -0,0 +1,70 @@
+//! Library change feed command.
+
+use chrono::{Local, NaiveDate};
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+use crate::activity::{self, ActivityKind, ActivityQuery};
+use crate::db;
+
+/// Show or export the activity log
+pub fn cmd_activity(
+    rt: &Runtime,
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    on: Option<NaiveDate>,
+    kind: Option<ActivityKind>,
+    limit: u32,
+    export: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mut filter = match on {
+        Some(day) => ActivityQuery::on_day(day),
+        None => ActivityQuery {
+            since: since.and_then(|d| ActivityQuery::on_day(d).since),
+            until: until.and_then(|d| ActivityQuery::on_day(d).since),
+            ..Default::default()
+        },
+    };
+    filter.kind = kind;
+    filter.limit = Some(limit);
+
+    let entries = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(None)).await?;
+        anyhow::Ok(activity::query(&pool, &filter).await?)
+    })?;
+
+    if let Some(path) = export {
+        activity::export(&entries, path)?;
+        println!("Exported {} entries to {:?}", entries.len(), path);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No activity recorded for that period.");
+        return Ok(());
+    }
+
+    let mut current_day = None;
+    for entry in &entries {
+        let day = entry.local_date();
+        if current_day != Some(day) {
+            println!("\n{}", day.format("%A %Y-%m-%d"));
+            current_day = Some(day);
+        }
+        let time = entry.timestamp.with_timezone(&Local).format("%H:%M:%S");
+        match &entry.detail {
+            Some(detail) => println!(
+                "  {} {:<13} {} ({})",
+                time,
+                entry.kind.label(),
+                entry.path,
+                detail
+            ),
+            None => println!("  {} {:<13} {}", time, entry.kind.label(), entry.path),
+        }
+        if let Some(audit) = &entry.audit {
+            println!("  {:8} {:<13} {}", "", "", audit.summary());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Headless agent command and service install helpers.
+
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+use super::AgentAction;
+use crate::agent::{self, AgentOptions, service};
+
+/// Options given to `agent` on the command line
+pub struct AgentArgs<'a> {
+    pub listen: Option<&'a str>,
+    pub no_enrich: bool,
+    pub working_dir: Option<&'a Path>,
+    pub service: bool,
+}
+
+/// Run the agent, or install/uninstall it as a service
+pub fn cmd_agent(
+    rt: &Runtime,
+    args: &AgentArgs<'_>,
+    action: Option<&AgentAction>,
+) -> anyhow::Result<()> {
+    match action {
+        Some(AgentAction::Install) => {
+            // Options given here are baked into the service's command line
+            let mut extra = Vec::new();
+            if let Some(listen) = args.listen {
+                agent::parse_listen(listen)?;
+                extra.extend(["--listen".to_string(), listen.to_string()]);
+            }
+            if args.no_enrich {
+                extra.push("--no-enrich".to_string());
+            }
+            println!("{}", service::install(&extra)?);
+            return Ok(());
+        }
+        Some(AgentAction::Uninstall) => {
+            println!("{}", service::uninstall()?);
+            return Ok(());
+        }
+        None => {}
+    }
+
+    if let Some(dir) = args.working_dir {
+        std::env::set_current_dir(dir)?;
+    }
+    let mut options = AgentOptions::from_config(&crate::config::load())?;
+    if let Some(listen) = args.listen {
+        options.listen = agent::parse_listen(listen)?;
+    }
+    if args.no_enrich {
+        options.enrich = false;
+    }
+
+    if args.service {
+        #[cfg(windows)]
+        return Ok(service::windows::run(options)?);
+        #[cfg(not(windows))]
+        anyhow::bail!("--service is for the Windows service manager; use `agent install` instead");
+    }
+
+    rt.block_on(agent::run(options, agent::shutdown_signal()))?;
+    Ok(())
+}
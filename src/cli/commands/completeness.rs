@@ -0,0 +1,113 @@
+//! Missing-from-album report command.
+
+use std::path::Path;
+#[cfg(feature = "enrichment")]
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use crate::completeness::{self, AlbumCompleteness};
+use crate::db;
+#[cfg(feature = "enrichment")]
+use crate::enrichment::musicbrainz::MusicBrainzClient;
+
+/// Show which tracks of identified albums are missing, optionally checking
+/// every album against MusicBrainz first
+pub fn cmd_completeness(
+    rt: &Runtime,
+    db_path: Option<&Path>,
+    check: bool,
+    all: bool,
+) -> anyhow::Result<()> {
+    let results = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+
+        if check {
+            #[cfg(feature = "enrichment")]
+            check_library(&pool).await?;
+            #[cfg(not(feature = "enrichment"))]
+            anyhow::bail!("--check needs a build with the enrichment feature");
+        }
+        anyhow::Ok(completeness::report(&pool, all).await?)
+    })?;
+
+    if results.is_empty() {
+        if all {
+            println!("No albums checked yet. Run with --check to compare against MusicBrainz.");
+        } else {
+            println!("No incomplete albums found.");
+        }
+        return Ok(());
+    }
+
+    for result in &results {
+        print_album(result);
+    }
+
+    let incomplete = results.iter().filter(|r| !r.is_complete()).count();
+    let missing: usize = results.iter().map(|r| r.missing.len()).sum();
+    println!(
+        "\n{} album(s), {} incomplete, {} track(s) missing",
+        results.len(),
+        incomplete,
+        missing
+    );
+    Ok(())
+}
+
+/// Compare every identified album with its release tracklist
+#[cfg(feature = "enrichment")]
+async fn check_library(pool: &sqlx::SqlitePool) -> anyhow::Result<()> {
+    let client = MusicBrainzClient::new();
+    let albums = completeness::library_albums(pool).await?;
+    println!("Checking {} album(s) against MusicBrainz...", albums.len());
+
+    let mut looked_up = false;
+    let (mut checked, mut unidentified, mut failed) = (0, 0, 0);
+    for album in albums {
+        let name = format!("{} - {}", album.artist, album.album);
+        // Respect MusicBrainz rate limits (1 req/sec)
+        if looked_up {
+            tokio::time::sleep(Duration::from_millis(1100)).await;
+        }
+        looked_up = false;
+        match completeness::check_album(pool, &client, album).await {
+            Ok(Some(_)) => {
+                checked += 1;
+                looked_up = true;
+            }
+            Ok(None) => unidentified += 1,
+            Err(completeness::CompletenessError::Database(e)) => return Err(e.into()),
+            Err(e) => {
+                eprintln!("  {}: {}", name, e);
+                failed += 1;
+                looked_up = true;
+            }
+        }
+    }
+
+    println!(
+        "Checked {} album(s); {} not identified; {} failed\n",
+        checked, unidentified, failed
+    );
+    Ok(())
+}
+
+/// One album: "have/total" plus its missing tracks
+fn print_album(result: &AlbumCompleteness) {
+    let mark = if result.is_complete() { "✓" } else { "✗" };
+    println!(
+        "{} {} - {} ({}/{})",
+        mark, result.artist, result.album, result.owned_tracks, result.total_tracks
+    );
+    let multi_disc = result.multi_disc();
+    for track in &result.missing {
+        if multi_disc {
+            println!(
+                "    missing {}-{:02} {}",
+                track.disc, track.position, track.title
+            );
+        } else {
+            println!("    missing {:02} {}", track.position, track.title);
+        }
+    }
+}
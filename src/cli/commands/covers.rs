@@ -0,0 +1,51 @@
+//! Album cover commands.
+
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+use crate::cover::{self, CoverOutcome};
+use crate::db;
+use crate::tasks::{TaskHandle, TaskKind};
+
+/// Fetch a cover for every album and write it as `[covers]` says
+pub fn cmd_covers_fetch(
+    rt: &Runtime,
+    missing_only: bool,
+    db_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    let config = crate::config::load().covers;
+    if !config.folder_file && !config.embed {
+        anyhow::bail!("Nowhere to write covers: set covers.folder_file or covers.embed");
+    }
+    let report = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        let task = TaskHandle::detached(TaskKind::Maintenance, "Fetch album covers");
+        anyhow::Ok(cover::fetch_album_covers(&pool, &config, missing_only, &task).await?)
+    })?;
+
+    for album in &report.albums {
+        let name = format!("{} - {}", album.artist, album.album);
+        match &album.outcome {
+            CoverOutcome::Written {
+                source,
+                folder,
+                embedded,
+            } => {
+                let mut written = Vec::new();
+                if *folder {
+                    written.push("folder file".to_string());
+                }
+                if *embedded > 0 {
+                    written.push(format!("embedded in {} tracks", embedded));
+                }
+                println!("WROTE: {} ({}; from {})", name, written.join(", "), source);
+            }
+            CoverOutcome::HasCover => println!("HAS COVER: {}", name),
+            CoverOutcome::NoRelease => println!("NO RELEASE: {}", name),
+            CoverOutcome::Unavailable(reason) => println!("NO COVER: {} ({})", name, reason),
+            CoverOutcome::Failed(e) => eprintln!("ERROR: {}: {}", name, e),
+        }
+    }
+    println!("\n{}", report.summary());
+    Ok(())
+}
@@ -0,0 +1,130 @@
+//! Database inspection, merge and split commands.
+
+use std::io::Write;
+use std::path::Path;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use tokio::runtime::Runtime;
+
+use crate::db::{self, SchemaInfo, TrackSelection, TransferProgress, TransferReport};
+
+/// Print the database's schema version and migrations, without upgrading it
+pub fn cmd_db_info(rt: &Runtime, db_path: Option<&Path>) -> anyhow::Result<()> {
+    let url = db::db_url(db_path);
+    let file = db::db_file(&url).ok_or_else(|| anyhow::anyhow!("not a database file: {}", url))?;
+    if !file.exists() {
+        anyhow::bail!("no database at {}", file.display());
+    }
+    let info = rt.block_on(async {
+        let options = SqliteConnectOptions::new().filename(&file).read_only(true);
+        let pool = SqlitePool::connect_with(options).await?;
+        anyhow::Ok(db::schema_info(&pool).await?)
+    })?;
+
+    let supported = SchemaInfo::supported();
+    let status = match info.version() {
+        _ if info.unknown().next().is_some() => {
+            "newer than this version of Music Minder".to_string()
+        }
+        None => "empty (set up on first use)".to_string(),
+        Some(_) if info.pending.is_empty() => "up to date".to_string(),
+        Some(_) => format!("{} migrations pending", info.pending.len()),
+    };
+    println!("Database: {}", file.display());
+    println!(
+        "Schema:   {} ({})",
+        info.version()
+            .map(|v| v.to_string())
+            .unwrap_or("none".into()),
+        status
+    );
+    println!("Supports: {}", supported);
+
+    if !info.applied.is_empty() {
+        println!("\nApplied:");
+        for m in &info.applied {
+            let note = match (m.known, m.success) {
+                (false, _) => "  (unknown to this version)",
+                (true, false) => "  (failed)",
+                _ => "",
+            };
+            println!(
+                "  {}  {}  {}{}",
+                m.version, m.installed_on, m.description, note
+            );
+        }
+    }
+    if !info.pending.is_empty() {
+        println!("\nPending (applied when the app or a command next opens it):");
+        for (version, description) in &info.pending {
+            println!("  {}  {}", version, description);
+        }
+    }
+    let backups = db::backups(&file);
+    if !backups.is_empty() {
+        println!("\nBackups made before upgrading:");
+        for (version, path) in backups {
+            println!("  schema {}  {}", version, path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Canonicalize every stored track path, merging tracks stored twice
+pub fn cmd_db_normalize_paths(rt: &Runtime, db_path: Option<&Path>) -> anyhow::Result<()> {
+    let merged = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        anyhow::Ok(db::paths::normalize_track_paths(&pool, &db::paths::policy(), true).await?)
+    })?;
+    println!("Merged {} duplicate track(s)", merged);
+    Ok(())
+}
+
+/// Merge the library in `from` into this one
+pub fn cmd_db_merge(rt: &Runtime, from: &Path, db_path: Option<&Path>) -> anyhow::Result<()> {
+    let report = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        println!("Merging {}...", from.display());
+        anyhow::Ok(db::merge(&pool, from, print_progress).await?)
+    })?;
+    print_report(&report);
+    Ok(())
+}
+
+/// Copy the selected tracks into a new database at `to`
+pub fn cmd_db_split(
+    rt: &Runtime,
+    to: &Path,
+    selection: &TrackSelection,
+    remove: bool,
+    db_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    if selection.is_empty() {
+        anyhow::bail!("choose the tracks to split off with --folder, --artist or --format");
+    }
+    let report = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        println!("Splitting into {}...", to.display());
+        anyhow::Ok(db::split(&pool, to, selection, remove, print_progress).await?)
+    })?;
+    print_report(&report);
+    if remove {
+        println!("Removed {} track(s) from this database", report.removed);
+    }
+    Ok(())
+}
+
+fn print_progress(progress: TransferProgress) {
+    print!("\r{}/{} tracks...", progress.done, progress.total);
+    std::io::stdout().flush().ok();
+}
+
+fn print_report(report: &TransferReport) {
+    println!();
+    println!("Added:             {}", report.added);
+    println!("Already there:     {}", report.matched_by_path);
+    println!("Same recording:    {}", report.matched_by_recording);
+    println!("Ratings taken:     {}", report.ratings_filled);
+    println!("Plays added:       {}", report.plays_added);
+    println!("Playlist entries:  {}", report.playlist_entries_added);
+}
@@ -1,13 +1,22 @@
 //! Audio fingerprinting and metadata enrichment commands.
 
+#[cfg(feature = "enrichment")]
 use std::path::PathBuf;
 use tokio::runtime::Runtime;
 
-use crate::{db, enrichment, health, metadata};
+#[cfg(feature = "enrichment")]
+use crate::error::Classify;
+use crate::provenance::{self, FieldSource};
+#[cfg(feature = "enrichment")]
+use crate::{activity, health, stats};
+use crate::{config, db, enrichment, metadata};
 
-use super::{collect_audio_files, print_fpcalc_install_instructions};
+#[cfg(feature = "enrichment")]
+use super::collect_audio_files;
+use super::print_fpcalc_install_instructions;
 
 /// Identify a track using audio fingerprinting
+#[cfg(feature = "enrichment")]
 pub fn cmd_identify(
     rt: &Runtime,
     path: &PathBuf,
@@ -47,6 +56,9 @@ pub fn cmd_identify(
         match service.identify_track(path).await {
             Ok(result) => {
                 println!("✓ Match found! (confidence: {:.0}%)", result.score * 100.0);
+                if result.scores != Default::default() {
+                    println!("  Against tags: {}", result.scores);
+                }
                 println!();
                 if let Some(title) = &result.track.title {
                     println!("  Title:  {}", title);
@@ -78,9 +90,12 @@ pub fn cmd_identify(
                 // Write tags if requested
                 if write {
                     println!();
+                    let tagging = config::load().tagging;
                     let options = metadata::WriteOptions2 {
                         only_fill_empty: fill_only,
                         write_musicbrainz_ids: true,
+                        placeholders: metadata::PlaceholderDetector::from_config(&tagging),
+                        genre_map: metadata::genres::GenreMap::from_config(&tagging),
                     };
                     match metadata::write(path, &result.track, &options) {
                         Ok(write_result) => {
@@ -138,6 +153,7 @@ pub fn cmd_check_tools() -> anyhow::Result<()> {
 /// Write metadata to an audio file
 #[allow(clippy::too_many_arguments)]
 pub fn cmd_write_tags(
+    rt: &Runtime,
     path: &std::path::Path,
     title: Option<&str>,
     artist: Option<&str>,
@@ -157,9 +173,12 @@ pub fn cmd_write_tags(
         ..Default::default()
     };
 
+    let tagging = config::load().tagging;
     let options = metadata::WriteOptions2 {
         only_fill_empty: fill_only,
         write_musicbrainz_ids: false,
+        placeholders: metadata::PlaceholderDetector::from_config(&tagging),
+        genre_map: metadata::genres::GenreMap::from_config(&tagging),
     };
 
     if preview {
@@ -195,6 +214,20 @@ pub fn cmd_write_tags(
                 if !result.fields_skipped.is_empty() {
                     println!("  Skipped: {}", result.fields_skipped.join(", "));
                 }
+                // Credit the values to the user if the file is in the library
+                let library_path = std::fs::canonicalize(path).unwrap_or_else(|_| path.into());
+                rt.block_on(async {
+                    if let Ok(pool) = db::init_db(&db::db_url(None)).await {
+                        provenance::record_written(
+                            &pool,
+                            &library_path,
+                            &result.fields_written,
+                            FieldSource::Manual,
+                            None,
+                        )
+                        .await;
+                    }
+                });
             }
             Err(e) => {
                 eprintln!("Error writing tags: {}", e);
@@ -206,6 +239,7 @@ pub fn cmd_write_tags(
 }
 
 /// Batch enrich multiple audio files
+#[cfg(feature = "enrichment")]
 #[allow(clippy::too_many_arguments)]
 pub fn cmd_enrich(
     rt: &Runtime,
@@ -217,6 +251,9 @@ pub fn cmd_enrich(
     min_confidence: f32,
     dry_run: bool,
     db_path: Option<&PathBuf>,
+    use_disc_ids: bool,
+    use_albums: bool,
+    report_path: Option<&PathBuf>,
 ) -> anyhow::Result<()> {
     let api_key = match api_key {
         Some(key) => key.to_string(),
@@ -234,152 +271,501 @@ pub fn cmd_enrich(
         std::process::exit(1);
     }
 
-    rt.block_on(async {
-        // Initialize database if --db is provided
-        let pool = if let Some(db_path) = db_path {
-            let db_url = format!("sqlite:{}", db_path.display());
-            match db::init_db(&db_url).await {
-                Ok(p) => Some(p),
-                Err(e) => {
-                    eprintln!("Warning: Failed to initialize database: {}", e);
-                    None
-                }
-            }
-        } else {
-            None
-        };
+    let config = enrichment::EnrichmentConfig {
+        acoustid_api_key: api_key,
+        min_confidence,
+        use_musicbrainz: true,
+        ..Default::default()
+    };
+    rt.block_on(run_enrich(
+        config,
+        path,
+        write,
+        fill_only,
+        recursive,
+        dry_run,
+        db_path,
+        use_disc_ids,
+        use_albums,
+        report_path,
+    ));
+    Ok(())
+}
 
-        // Collect files to process
-        let files = collect_audio_files(path, recursive);
+/// Fingerprint tagged library tracks and flag those whose audio is another
+/// recording than their tags name
+#[cfg(feature = "enrichment")]
+pub fn cmd_verify_tags(
+    rt: &Runtime,
+    api_key: Option<&str>,
+    db_path: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    use crate::library::verify_tags;
+    use crate::tasks::{TaskHandle, TaskKind};
 
-        if files.is_empty() {
-            println!("No audio files found.");
-            return;
-        }
+    let Some(api_key) = api_key else {
+        eprintln!("Error: AcoustID API key required.");
+        eprintln!("Get one at: https://acoustid.org/new-application");
+        eprintln!("Then use: --api-key YOUR_KEY or set ACOUSTID_API_KEY env var");
+        std::process::exit(1);
+    };
+    if !enrichment::fingerprint::is_fpcalc_available() {
+        print_fpcalc_install_instructions();
+        std::process::exit(1);
+    }
 
-        if dry_run {
-            println!("DRY RUN - no changes will be made\n");
+    let service = enrichment::EnrichmentService::new(enrichment::EnrichmentConfig {
+        acoustid_api_key: api_key.to_string(),
+        min_confidence: 0.5,
+        use_musicbrainz: true,
+        ..Default::default()
+    });
+    rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        let task = TaskHandle::detached(TaskKind::Enrichment, "Verify tags");
+        let report = verify_tags::verify(&pool, &service, None, &task).await?;
+        for suspect in &report.suspects {
+            let found = &suspect.identification.track;
+            println!("⚠ {}", suspect.path);
+            println!(
+                "    Tagged: {} - {}",
+                suspect.tagged_artist, suspect.tagged_title
+            );
+            println!(
+                "    Audio:  {} - {} ({:.0}%)",
+                found.artist.as_deref().unwrap_or("?"),
+                found.title.as_deref().unwrap_or("?"),
+                suspect.identification.score * 100.0
+            );
+        }
+        for (path, error) in &report.failed {
+            println!("  Failed: {}: {}", path, error);
         }
-        if pool.is_some() {
-            println!("Health tracking enabled\n");
+        println!("{}", report.summary());
+        if !report.suspects.is_empty() {
+            println!("Review them with `music-minder identify <file> --write`, or in the app.");
         }
-        println!("Enriching {} file(s)...\n", files.len());
+        Ok(())
+    })
+}
 
-        let config = enrichment::EnrichmentConfig {
-            acoustid_api_key: api_key,
-            min_confidence,
-            use_musicbrainz: true,
-            ..Default::default()
-        };
-        let service = enrichment::EnrichmentService::new(config);
+/// Files an `enrich` run identified, found no match for, and failed on
+#[cfg(feature = "enrichment")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct EnrichTotals {
+    identified: usize,
+    no_match: usize,
+    errors: usize,
+}
+
+/// The work of `enrich`, with the services `config` points at
+#[cfg(feature = "enrichment")]
+#[allow(clippy::too_many_arguments)]
+async fn run_enrich(
+    config: enrichment::EnrichmentConfig,
+    path: &PathBuf,
+    write: bool,
+    fill_only: bool,
+    recursive: bool,
+    dry_run: bool,
+    db_path: Option<&PathBuf>,
+    use_disc_ids: bool,
+    use_albums: bool,
+    report_path: Option<&PathBuf>,
+) -> EnrichTotals {
+    let tagging = config::load().tagging;
+    let options = metadata::WriteOptions2 {
+        only_fill_empty: fill_only,
+        write_musicbrainz_ids: true,
+        placeholders: metadata::PlaceholderDetector::from_config(&tagging),
+        genre_map: metadata::genres::GenreMap::from_config(&tagging),
+    };
+
+    // Initialize database if --db is provided
+    let pool = if let Some(db_path) = db_path {
+        let db_url = format!("sqlite:{}", db_path.display());
+        match db::init_db(&db_url).await {
+            Ok(p) => Some(p),
+            Err(e) => {
+                eprintln!("Warning: Failed to initialize database: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Collect files to process
+    let files = collect_audio_files(path, recursive);
 
-        let mut success_count = 0;
-        let mut skip_count = 0;
-        let mut fail_count = 0;
+    if files.is_empty() {
+        println!("No audio files found.");
+        return EnrichTotals::default();
+    }
+
+    if dry_run {
+        println!("DRY RUN - no changes will be made\n");
+    }
+    if pool.is_some() {
+        println!("Health tracking enabled\n");
+    }
+    println!("Enriching {} file(s)...\n", files.len());
 
-        for (i, file_path) in files.iter().enumerate() {
-            let filename = file_path
-                .file_name()
-                .and_then(|s| s.to_str())
-                .unwrap_or("?");
+    let service = enrichment::EnrichmentService::new(config);
 
-            print!("[{}/{}] {}... ", i + 1, files.len(), filename);
-            use std::io::Write;
-            std::io::stdout().flush().unwrap();
+    // Whole ripped CDs first: one disc ID lookup instead of a fingerprint per track
+    let mut by_disc_id = if use_disc_ids {
+        service.identify_discs(&files).await
+    } else {
+        Default::default()
+    };
+    if !by_disc_id.is_empty() {
+        println!("{} file(s) matched by disc ID\n", by_disc_id.len());
+    }
+    // Then full-album folders, voting on one release
+    let mut by_album = if use_albums {
+        let rest: Vec<PathBuf> = files
+            .iter()
+            .filter(|f| !by_disc_id.contains_key(*f))
+            .cloned()
+            .collect();
+        service.identify_albums(&rest).await
+    } else {
+        Default::default()
+    };
+    if !by_album.is_empty() {
+        println!("{} file(s) matched as part of an album\n", by_album.len());
+    }
 
-            let path_str = file_path.to_string_lossy().to_string();
+    let mut totals = EnrichTotals::default();
+    let mut conflict_count = 0;
+    let mut report = Vec::new();
+
+    for (i, file_path) in files.iter().enumerate() {
+        let filename = file_path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("?");
+
+        print!("[{}/{}] {}... ", i + 1, files.len(), filename);
+        use std::io::Write;
+        std::io::stdout().flush().unwrap();
+
+        let path_str = file_path.to_string_lossy().to_string();
+
+        let disc_match = by_disc_id.remove(file_path);
+        let matched_by_disc = disc_match.is_some();
+        let album_match = by_album.remove(file_path);
+        let matched_by_album = album_match.is_some();
+        let identified = match disc_match.or(album_match) {
+            Some(identification) => Ok(identification),
+            None => service.identify_track(file_path).await,
+        };
+        if let Some(ref p) = pool {
+            stats::record_identification(p, stats::IdentifyOutcome::of(&identified)).await;
+        }
 
-            match service.identify_track(file_path).await {
-                Ok(result) => {
-                    let album = result.track.album.as_deref().unwrap_or("?");
+        match identified {
+            Ok(result) => {
+                let album = result.track.album.as_deref().unwrap_or("?");
+                if matched_by_disc {
+                    print!("✓ {} (disc ID) ", album);
+                } else if matched_by_album {
+                    print!("✓ {} (album) ", album);
+                } else {
                     print!("✓ {} ", album);
+                }
 
-                    // Track health: OK
-                    if let Some(ref p) = pool {
-                        let health_record = health::FileHealth::ok(
-                            &path_str,
-                            result.score as f64,
-                            result.track.recording_id.clone(),
-                        )
-                        .with_file_info(file_path);
-                        let _ = health::upsert_health(p, &health_record).await;
-                    }
+                // Track health: OK
+                if let Some(ref p) = pool {
+                    let health_record = health::FileHealth::ok(
+                        &path_str,
+                        result.score as f64,
+                        result.track.recording_id.clone(),
+                    )
+                    .with_file_info(file_path);
+                    let _ = health::upsert_health(p, &health_record).await;
+                }
 
-                    if write && !dry_run {
-                        let options = metadata::WriteOptions2 {
-                            only_fill_empty: fill_only,
-                            write_musicbrainz_ids: true,
-                        };
-                        match metadata::write(file_path, &result.track, &options) {
-                            Ok(write_result) => {
+                let matched_by = if matched_by_disc {
+                    "disc ID"
+                } else if matched_by_album {
+                    "album"
+                } else {
+                    "fingerprint"
+                };
+                let mut file_report = report_path.map(|_| {
+                    let preview = metadata::preview_write(file_path, &result.track, &options);
+                    enrichment::report::FileReport::identified(
+                        file_path,
+                        &result,
+                        matched_by,
+                        preview.ok(),
+                    )
+                });
+
+                if write && result.scores.contradicts_tags() {
+                    // Needs a look first, like in the Enrich pane
+                    println!("(not written: disagrees with tags, {})", result.scores);
+                } else if write && !dry_run {
+                    // Hand-edited fields are left alone (see provenance::conflicts)
+                    let (track, conflicts) = match pool {
+                        Some(ref p) => {
+                            provenance::guard_manual_edits(
+                                p,
+                                file_path,
+                                &result,
+                                &tagging.manual_edits,
+                            )
+                            .await
+                        }
+                        None => (result.track.clone(), 0),
+                    };
+                    conflict_count += conflicts;
+                    if conflicts > 0
+                        && let Some(ref mut file_report) = file_report
+                    {
+                        // Report what is written, not what was suggested
+                        let preview = metadata::preview_write(file_path, &track, &options);
+                        *file_report = enrichment::report::FileReport::identified(
+                            file_path,
+                            &result,
+                            matched_by,
+                            preview.ok(),
+                        );
+                    }
+                    let written = metadata::write(file_path, &track, &options);
+                    if let Some(file_report) = file_report.take() {
+                        report.push(file_report.with_write_result(
+                            written.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+                        ));
+                    }
+                    match written {
+                        Ok(write_result) => {
+                            if conflicts > 0 {
+                                println!(
+                                    "({} tags written, {} kept for review)",
+                                    write_result.fields_updated, conflicts
+                                );
+                            } else {
                                 println!("({} tags written)", write_result.fields_updated);
                             }
-                            Err(e) => {
-                                println!("(write failed: {})", e);
+                            if let Some(ref p) = pool {
+                                activity::record_tags_written(
+                                    p,
+                                    file_path,
+                                    write_result.fields_updated,
+                                )
+                                .await;
+                                provenance::record_identification(
+                                    p,
+                                    file_path,
+                                    &write_result.fields_written,
+                                    &result,
+                                )
+                                .await;
                             }
                         }
-                    } else if write && dry_run {
-                        println!("(would write tags)");
-                    } else {
-                        println!();
+                        Err(e) => {
+                            println!("(write failed: {})", e);
+                        }
                     }
-                    success_count += 1;
+                } else if write && dry_run {
+                    println!("(would write tags)");
+                } else {
+                    println!();
                 }
-                Err(enrichment::EnrichmentError::NoMatches) => {
-                    println!("✗ No match");
-                    // Track health: No match
-                    if let Some(ref p) = pool {
-                        let health_record =
-                            health::FileHealth::no_match(&path_str).with_file_info(file_path);
-                        let _ = health::upsert_health(p, &health_record).await;
-                    }
-                    skip_count += 1;
+                report.extend(file_report);
+                totals.identified += 1;
+            }
+            Err(enrichment::EnrichmentError::NoMatches) => {
+                println!("✗ No match");
+                // Track health: No match
+                if let Some(ref p) = pool {
+                    let health_record =
+                        health::FileHealth::no_match(&path_str).with_file_info(file_path);
+                    let _ = health::upsert_health(p, &health_record).await;
                 }
-                Err(e) => {
-                    println!("✗ Error: {}", e);
-                    // Track health: Error
-                    if let Some(ref p) = pool {
-                        let error_type = if e.to_string().contains("fingerprint") {
-                            health::ErrorType::EmptyFingerprint
-                        } else if e.to_string().contains("decode") {
-                            health::ErrorType::DecodeError
-                        } else {
-                            health::ErrorType::Other("enrichment_error".to_string())
-                        };
-                        let health_record =
-                            health::FileHealth::error(&path_str, error_type, e.to_string())
-                                .with_file_info(file_path);
-                        let _ = health::upsert_health(p, &health_record).await;
-                    }
-                    fail_count += 1;
+                if report_path.is_some() {
+                    report.push(enrichment::report::FileReport::no_match(file_path));
                 }
+                totals.no_match += 1;
             }
-
-            // Small delay between files to be nice to APIs
-            if i < files.len() - 1 {
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            Err(e) => {
+                println!("✗ Error: {}", e);
+                // Track health: Error
+                if let Some(ref p) = pool {
+                    let error_type = if e.to_string().contains("fingerprint") {
+                        health::ErrorType::EmptyFingerprint
+                    } else if e.to_string().contains("decode") {
+                        health::ErrorType::DecodeError
+                    } else {
+                        health::ErrorType::Other("enrichment_error".to_string())
+                    };
+                    let health_record =
+                        health::FileHealth::error(&path_str, error_type, e.to_string())
+                            .with_class(e.class())
+                            .with_file_info(file_path);
+                    let _ = health::upsert_health(p, &health_record).await;
+                }
+                if report_path.is_some() {
+                    report.push(enrichment::report::FileReport::error(
+                        file_path,
+                        e.to_string(),
+                    ));
+                }
+                totals.errors += 1;
             }
         }
 
-        println!();
+        // Small delay between files to be nice to APIs
+        if !matched_by_disc && !matched_by_album && i < files.len() - 1 {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+    }
+
+    println!();
+    println!(
+        "Done! {} identified, {} no match, {} errors",
+        totals.identified, totals.no_match, totals.errors
+    );
+    let lookups = service.lookup_stats();
+    if lookups.saved > 0 {
+        println!(
+            "MusicBrainz: {} requests, {} saved by reusing album lookups",
+            lookups.requests, lookups.saved
+        );
+    }
+    if conflict_count > 0 {
         println!(
-            "Done! {} identified, {} no match, {} errors",
-            success_count, skip_count, fail_count
+            "{} new match(es) disagreed with values you set by hand; \
+             review them in the Enrich pane.",
+            conflict_count
         );
+    }
 
-        // Show health summary if tracking
-        if let Some(ref p) = pool
-            && let Ok(summary) = health::get_summary(p).await
-        {
-            println!(
-                "\nHealth Summary: {} ok, {} errors, {} no match",
-                summary.ok, summary.errors, summary.no_match
-            );
+    // Show health summary if tracking
+    if let Some(ref p) = pool
+        && let Ok(summary) = health::get_summary(p).await
+    {
+        println!(
+            "\nHealth Summary: {} ok, {} errors, {} no match",
+            summary.ok, summary.errors, summary.no_match
+        );
+    }
+
+    if let Some(report_path) = report_path {
+        let report = enrichment::report::EnrichmentReport::new(report, fill_only);
+        match report.save(report_path) {
+            Ok(()) => println!("\nReport saved to {}", report_path.display()),
+            Err(e) => eprintln!("\nFailed to save report: {}", e),
         }
+    }
 
-        if dry_run && write {
-            println!("\nRun without --dry-run to write tags.");
+    if dry_run && write {
+        println!("\nRun without --dry-run to write tags.");
+    }
+    totals
+}
+
+#[cfg(all(test, unix, feature = "enrichment"))]
+mod tests {
+    use super::*;
+    use crate::enrichment::service::Endpoints;
+    use crate::test_utils::{
+        AudioFixture, FixtureResponse, FixtureServer, install_fake_fpcalc, write_audio_fixture,
+    };
+
+    /// A file per outcome: `teen` is identified once AcoustID is answered
+    /// on the second try, `silence` isn't known and `corrupt` can't be
+    /// fingerprinted
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_enrich_end_to_end() {
+        install_fake_fpcalc();
+        let server = FixtureServer::start(vec![
+            (
+                "/v2/lookup?fingerprint=FAKE-silence",
+                FixtureResponse::json(r#"{"results": [], "status": "ok"}"#),
+            ),
+            (
+                "/v2/lookup",
+                FixtureResponse::status(503, "Service Unavailable").times(1),
+            ),
+            (
+                "/v2/lookup",
+                FixtureResponse::json(include_str!(
+                    "../../enrichment/fixtures/acoustid_lookup.json"
+                )),
+            ),
+            (
+                "/ws/2/recording/",
+                FixtureResponse::json(include_str!(
+                    "../../enrichment/fixtures/musicbrainz_recording.json"
+                )),
+            ),
+        ])
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let music = dir.path().join("music");
+        std::fs::create_dir(&music).unwrap();
+        for name in ["teen", "silence", "corrupt"] {
+            let fixture = write_audio_fixture(&music, AudioFixture::Mp3);
+            std::fs::rename(fixture, music.join(format!("{}.mp3", name))).unwrap();
         }
-    });
-    Ok(())
+        let db_path = dir.path().join("library.db");
+        let report_path = dir.path().join("report.json");
+
+        let config = enrichment::EnrichmentConfig {
+            acoustid_api_key: "test".to_string(),
+            min_confidence: 0.5,
+            endpoints: Endpoints::all_at(server.url()),
+            ..Default::default()
+        };
+        let totals = run_enrich(
+            config,
+            &music,
+            true,
+            false,
+            false,
+            false,
+            Some(&db_path),
+            false,
+            false,
+            Some(&report_path),
+        )
+        .await;
+        assert_eq!(
+            totals,
+            EnrichTotals {
+                identified: 1,
+                no_match: 1,
+                errors: 1,
+            }
+        );
+
+        // The 503 was retried
+        let lookups = server
+            .requests()
+            .iter()
+            .filter(|r| r.contains("fingerprint=FAKE-teen"))
+            .count();
+        assert_eq!(lookups, 2);
+
+        let tags = metadata::read(&music.join("teen.mp3")).unwrap();
+        assert_eq!(tags.title, "Smells Like Teen Spirit");
+        assert_eq!(tags.artist, "Nirvana");
+
+        let pool = db::init_db(&format!("sqlite:{}", db_path.display()))
+            .await
+            .unwrap();
+        let summary = health::get_summary(&pool).await.unwrap();
+        assert_eq!((summary.ok, summary.errors, summary.no_match), (1, 1, 1));
+
+        let report: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&report_path).unwrap()).unwrap();
+        assert_eq!(report["summary"]["files"], 3);
+        assert_eq!(report["summary"]["identified"], 1);
+        assert_eq!(report["summary"]["written"], 1);
+    }
 }
@@ -0,0 +1,106 @@
+//! Gapless verification report command.
+
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+use crate::completeness::{self, AlbumTracks, OwnedTrack};
+use crate::db;
+use crate::player::gapless::{self, Boundary};
+
+/// Check that consecutive tracks of each album join without a gap and list
+/// the pairs that don't
+pub fn cmd_gapless(
+    rt: &Runtime,
+    path: Option<&Path>,
+    db_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    let albums = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        anyhow::Ok(completeness::library_albums(&pool).await?)
+    })?;
+
+    let (mut checked, mut seamless, mut pauses, mut gaps, mut failed) = (0, 0, 0, 0, 0);
+    for mut album in albums {
+        if let Some(root) = path
+            && !album
+                .tracks
+                .iter()
+                .all(|t| Path::new(&t.path).starts_with(root))
+        {
+            continue;
+        }
+        if album.tracks.len() < 2 {
+            continue;
+        }
+        completeness::read_tag_ids(&mut album);
+
+        let mut problems = Vec::new();
+        for (first, second) in consecutive_pairs(&album) {
+            checked += 1;
+            match gapless::check_boundary(Path::new(&first.path), Path::new(&second.path)) {
+                Ok(check) => match check.boundary {
+                    Boundary::Seamless => seamless += 1,
+                    Boundary::Pause => pauses += 1,
+                    Boundary::Gap(gap) => {
+                        gaps += 1;
+                        let cause = check
+                            .cause()
+                            .map(|c| format!(" ({})", c))
+                            .unwrap_or_default();
+                        problems.push(format!(
+                            "    {} → {}  {} ms gap{}",
+                            position(first),
+                            position(second),
+                            gap.as_millis(),
+                            cause
+                        ));
+                    }
+                },
+                Err(e) => {
+                    failed += 1;
+                    eprintln!("  {}: {}", second.path, e);
+                }
+            }
+        }
+
+        if !problems.is_empty() {
+            println!("✗ {} - {}", album.artist, album.album);
+            for problem in problems {
+                println!("{}", problem);
+            }
+        }
+    }
+
+    println!(
+        "\nChecked {} track boundaries: {} seamless, {} pauses, {} with gaps, {} unreadable",
+        checked, seamless, pauses, gaps, failed
+    );
+    Ok(())
+}
+
+/// Tracks followed by the next one on the same disc
+fn consecutive_pairs(album: &AlbumTracks) -> Vec<(&OwnedTrack, &OwnedTrack)> {
+    let mut tracks: Vec<&OwnedTrack> = album
+        .tracks
+        .iter()
+        .filter(|t| t.track_number.is_some())
+        .collect();
+    tracks.sort_by_key(|t| (t.disc_number.unwrap_or(1), t.track_number));
+    tracks
+        .windows(2)
+        .filter(|w| {
+            w[0].disc_number.unwrap_or(1) == w[1].disc_number.unwrap_or(1)
+                && w[0].track_number.map(|n| n + 1) == w[1].track_number
+        })
+        .map(|w| (w[0], w[1]))
+        .collect()
+}
+
+/// "03", or "2-03" on later discs
+fn position(track: &OwnedTrack) -> String {
+    let number = track.track_number.unwrap_or(0);
+    match track.disc_number {
+        Some(disc) if disc > 1 => format!("{}-{:02}", disc, number),
+        _ => format!("{:02}", number),
+    }
+}
@@ -0,0 +1,100 @@
+//! Genre manager commands: list genres, merge them, and edit the rules.
+
+use std::path::Path;
+
+use tokio::runtime::Runtime;
+
+use crate::config;
+use crate::db;
+use crate::library::genres;
+use crate::metadata::genres::GenreMap;
+
+/// Print every genre with its track count, most used first
+pub fn cmd_genres_list(rt: &Runtime, db_path: Option<&Path>) -> anyhow::Result<()> {
+    let counts = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        let map = GenreMap::from_config(&config::load().tagging);
+        let read = genres::read_missing(&pool, &map).await?;
+        if read > 0 {
+            println!("Read the genres of {} track(s).\n", read);
+        }
+        anyhow::Ok(genres::counts(&pool).await?)
+    })?;
+    if counts.is_empty() {
+        println!("No genres tagged.");
+        return Ok(());
+    }
+    for genre in &counts {
+        println!("{:>6}  {}", genre.tracks, genre.name);
+    }
+    println!("\n{} genre(s).", counts.len());
+    Ok(())
+}
+
+/// Merge genres into one, in the tags and the database, and optionally
+/// remember the merge as rules
+pub fn cmd_genres_merge(
+    rt: &Runtime,
+    from: &[String],
+    into: &str,
+    remember: bool,
+    db_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    if into.trim().is_empty() {
+        anyhow::bail!("the genre to merge into is empty");
+    }
+    let report = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        let map = GenreMap::from_config(&config::load().tagging);
+        genres::read_missing(&pool, &map).await?;
+        anyhow::Ok(genres::merge(&pool, from, into).await?)
+    })?;
+
+    for (path, error) in &report.failed {
+        println!("  Failed: {}: {}", path, error);
+    }
+    println!(
+        "Retagged {} track(s) and {} play(s) as {}.",
+        report.tracks,
+        report.plays,
+        into.trim()
+    );
+    if !report.failed.is_empty() {
+        println!(
+            "{} file(s) couldn't be written and keep their genres.",
+            report.failed.len()
+        );
+    }
+
+    if remember {
+        let mut cfg = config::load();
+        genres::add_rules(&mut cfg.tagging.genre_map, from, into);
+        config::save(&cfg)?;
+        println!("Future scans and tag writes will use {}.", into.trim());
+    }
+    Ok(())
+}
+
+/// Print the genre rules
+pub fn cmd_genres_rules() -> anyhow::Result<()> {
+    let rules = config::load().tagging.genre_map;
+    if rules.is_empty() {
+        println!("No genre rules. Add some with `genres merge --remember`.");
+        return Ok(());
+    }
+    for (from, to) in &rules {
+        println!("{} -> {}", from, to);
+    }
+    Ok(())
+}
+
+/// Remove the rule for a spelling
+pub fn cmd_genres_unmap(spelling: &str) -> anyhow::Result<()> {
+    let mut cfg = config::load();
+    if !genres::remove_rule(&mut cfg.tagging.genre_map, spelling) {
+        anyhow::bail!("no genre rule for {}", spelling);
+    }
+    config::save(&cfg)?;
+    println!("Removed the rule for {}.", spelling);
+    Ok(())
+}
@@ -6,9 +6,13 @@ use tokio::runtime::Runtime;
 use crate::{db, diagnostics, health};
 
 /// Check file health status
-pub fn cmd_check(rt: &Runtime, db_path: &Path, path: Option<&PathBuf>) -> anyhow::Result<()> {
+pub fn cmd_check(
+    rt: &Runtime,
+    db_path: Option<&Path>,
+    path: Option<&PathBuf>,
+) -> anyhow::Result<()> {
     rt.block_on(async {
-        let db_url = format!("sqlite:{}", db_path.display());
+        let db_url = db::db_url(db_path);
         let pool = match db::init_db(&db_url).await {
             Ok(p) => p,
             Err(e) => {
@@ -34,6 +38,14 @@ pub fn cmd_check(rt: &Runtime, db_path: &Path, path: Option<&PathBuf>) -> anyhow
                     if let Some(ref err) = record.error_message {
                         println!("Error: {}", err);
                     }
+                    if let Some(class) = record.error_class {
+                        let retry = if class.is_recoverable() {
+                            "worth checking again"
+                        } else {
+                            "won't change on a retry"
+                        };
+                        println!("Class: {} ({})", class.label(), retry);
+                    }
                 }
                 Ok(None) => {
                     println!("No health record found for {:?}", file_path);
@@ -54,6 +66,9 @@ pub fn cmd_check(rt: &Runtime, db_path: &Path, path: Option<&PathBuf>) -> anyhow
                     println!("  ✓ OK:        {}", summary.ok);
                     println!("  ? No match:  {}", summary.no_match);
                     println!("  ✗ Errors:    {}", summary.errors);
+                    if summary.retryable > 0 {
+                        println!("    retryable: {}", summary.retryable);
+                    }
                     println!();
 
                     if summary.errors > 0 {
@@ -67,7 +82,12 @@ pub fn cmd_check(rt: &Runtime, db_path: &Path, path: Option<&PathBuf>) -> anyhow
                                     .and_then(|s| s.to_str())
                                     .unwrap_or("?");
                                 let err_msg = record.error_message.as_deref().unwrap_or("unknown");
-                                println!("  {} - {}", filename, err_msg);
+                                match record.error_class {
+                                    Some(class) => {
+                                        println!("  {} - [{}] {}", filename, class.label(), err_msg)
+                                    }
+                                    None => println!("  {} - {}", filename, err_msg),
+                                }
                             }
                             if errors.len() > 10 {
                                 println!("  ... and {} more", errors.len() - 10);
@@ -86,9 +106,37 @@ pub fn cmd_check(rt: &Runtime, db_path: &Path, path: Option<&PathBuf>) -> anyhow
 }
 
 /// Run system diagnostics
-pub fn cmd_diagnose() -> anyhow::Result<()> {
+pub fn cmd_diagnose(rt: &Runtime, format: &str, db_path: Option<&Path>) -> anyhow::Result<()> {
+    if !matches!(format, "text" | "json") {
+        anyhow::bail!("unknown format {:?} (expected text or json)", format);
+    }
     let report = diagnostics::DiagnosticReport::generate();
 
+    // Compare the library's sample rates with the output device and check
+    // the database, if there's a library to check
+    let db_url = db::db_url(db_path);
+    let report = match db::db_file(&db_url).filter(|f| f.exists()) {
+        Some(_) => {
+            let (tracks, health) = rt.block_on(async {
+                let pool = db::init_db(&db_url).await?;
+                let tracks = db::get_all_tracks(&pool).await?;
+                let health = diagnostics::LibraryHealth::check(&pool).await?;
+                anyhow::Ok((tracks, health))
+            })?;
+            report
+                .with_library(&diagnostics::sample_paths(
+                    tracks.iter().map(|t| t.path.as_str()),
+                ))
+                .with_library_health(&health)
+        }
+        None => report,
+    };
+
+    if format == "json" {
+        println!("{}", report.to_json());
+        return Ok(());
+    }
+
     println!("System Diagnostics Report");
     println!("=========================\n");
     println!(
@@ -107,6 +155,14 @@ pub fn cmd_diagnose() -> anyhow::Result<()> {
         if let Some(ref rec) = check.recommendation {
             println!("    → {}", rec);
         }
+        if let Some(fix) = check.fix
+            && matches!(
+                check.status,
+                diagnostics::CheckStatus::Warning | diagnostics::CheckStatus::Fail
+            )
+        {
+            println!("    → Fix in the Diagnostics pane: {}", fix.label());
+        }
     }
 
     println!();
@@ -114,9 +170,9 @@ pub fn cmd_diagnose() -> anyhow::Result<()> {
 }
 
 /// Assess metadata quality for tracks in the library
-pub fn cmd_quality(rt: &Runtime, db_path: &Path, verbose: bool) -> anyhow::Result<()> {
+pub fn cmd_quality(rt: &Runtime, db_path: Option<&Path>, verbose: bool) -> anyhow::Result<()> {
     rt.block_on(async {
-        let db_url = format!("sqlite:{}", db_path.display());
+        let db_url = db::db_url(db_path);
         let pool = match db::init_db(&db_url).await {
             Ok(p) => p,
             Err(e) => {
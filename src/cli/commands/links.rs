@@ -0,0 +1,28 @@
+//! `musicminder://` link commands: opening a link and registering the scheme.
+
+use crate::deeplink::{self, DeepLink};
+use crate::instance;
+
+/// Hand a link to the running app. Returns `false` when none is running:
+/// the link is kept and the GUI should launch to handle it.
+pub fn cmd_open(uri: &str) -> anyhow::Result<bool> {
+    DeepLink::parse(uri)?;
+    if instance::forward(uri) {
+        println!("Sent to the running Music Minder.");
+        return Ok(true);
+    }
+    deeplink::set_pending(uri.to_string());
+    Ok(false)
+}
+
+/// Make the desktop open `musicminder://` links with this executable
+pub fn cmd_links_register() -> anyhow::Result<()> {
+    println!("{}", deeplink::register()?);
+    Ok(())
+}
+
+/// Stop the desktop opening `musicminder://` links here
+pub fn cmd_links_unregister() -> anyhow::Result<()> {
+    println!("{}", deeplink::unregister()?);
+    Ok(())
+}
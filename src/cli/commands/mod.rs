@@ -2,27 +2,76 @@
 //!
 //! This module provides the command-line interface for Music Minder.
 //! Each subcommand is implemented in its own submodule for maintainability:
-//! - `scan`: Library scanning and file watching
-//! - `organize`: File organization by metadata
+//! - `scan`: Library scanning, file watching and adding stream URLs
+//! - `organize`: File organization by metadata, plan execution and NFO export
 //! - `enrich`: Audio fingerprinting and metadata enrichment
 //! - `health`: File health checking and diagnostics
+//! - `links`: Opening `musicminder://` links and registering the scheme
+//! - `activity`: Library change feed
+//! - `agent`: Headless agent and its service install helpers (`serve` feature)
+//! - `completeness`: Missing-from-album report
+//! - `covers`: Album cover art in a batch
+//! - `db`: Database schema version and migrations, merging and splitting
+//! - `gapless`: Gapless verification of album track boundaries
+//! - `genres`: Genre counts, merges and rules
+//! - `profile`: Library profiles
+//! - `replaygain`: Loudness measurement and ReplayGain tags
+//! - `rip`: Ripping a CD into the library (`cd-rip` feature)
+//! - `secrets`: Passphrase encryption of the config's credentials
+//! - `settings`: Exporting and importing settings bundles
 
+mod activity;
+#[cfg(feature = "serve")]
+mod agent;
+mod completeness;
+mod covers;
+mod db;
 mod enrich;
+mod gapless;
+mod genres;
 mod health;
+mod links;
 mod organize;
+mod profile;
+mod replaygain;
+#[cfg(feature = "cd-rip")]
+mod rip;
 mod scan;
+mod secrets;
+mod settings;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use std::path::PathBuf;
 use tokio::runtime::Runtime;
 
 // Shared audio file detection
+#[cfg(feature = "enrichment")]
 use crate::scanner::is_audio_file;
 
-pub use enrich::{cmd_check_tools, cmd_enrich, cmd_identify, cmd_write_tags};
+pub use activity::cmd_activity;
+#[cfg(feature = "serve")]
+pub use agent::{AgentArgs, cmd_agent};
+pub use completeness::cmd_completeness;
+pub use covers::cmd_covers_fetch;
+pub use db::{cmd_db_info, cmd_db_merge, cmd_db_normalize_paths, cmd_db_split};
+pub use enrich::{cmd_check_tools, cmd_write_tags};
+#[cfg(feature = "enrichment")]
+pub use enrich::{cmd_enrich, cmd_identify, cmd_verify_tags};
+pub use gapless::cmd_gapless;
+pub use genres::{cmd_genres_list, cmd_genres_merge, cmd_genres_rules, cmd_genres_unmap};
 pub use health::{cmd_check, cmd_diagnose, cmd_quality};
-pub use organize::cmd_organize;
-pub use scan::{cmd_list, cmd_scan, cmd_watch};
+pub use links::{cmd_links_register, cmd_links_unregister, cmd_open};
+pub use organize::{cmd_apply_plan, cmd_export_nfo, cmd_organize, cmd_recover_organize};
+pub use profile::cmd_profiles;
+pub use replaygain::cmd_replaygain;
+#[cfg(feature = "cd-rip")]
+pub use rip::cmd_rip;
+pub use scan::{
+    cmd_add_url, cmd_album_artists, cmd_compilations, cmd_import_ratings, cmd_list, cmd_scan,
+    cmd_watch,
+};
+pub use secrets::{cmd_secrets_decrypt, cmd_secrets_encrypt, cmd_secrets_status};
+pub use settings::{cmd_settings_export, cmd_settings_import};
 
 /// Music Minder CLI
 #[derive(Parser)]
@@ -30,6 +79,66 @@ pub use scan::{cmd_list, cmd_scan, cmd_watch};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+
+    /// Lock the library: refuse tag writes, moves, deletes, and stored
+    /// enrichment results (also `library.read_only` in the config file)
+    #[arg(long, global = true)]
+    pub read_only: bool,
+
+    /// Library profile to use (created if missing; default: the one last
+    /// selected in the app)
+    #[arg(long, global = true, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Directory holding the config file and profiles (default: the OS
+    /// config directory)
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        env = "MUSIC_MINDER_CONFIG_DIR"
+    )]
+    pub config_dir: Option<PathBuf>,
+
+    /// Keep the database, config and caches next to the executable and
+    /// store library paths on its drive relative to the drive (also on when
+    /// a `portable.txt` file is next to the executable)
+    #[arg(long, global = true)]
+    pub portable: bool,
+}
+
+impl Cli {
+    /// Parse the command line with every path made absolute, so paths keep
+    /// their meaning when portable mode moves the working directory
+    pub fn parse_absolute() -> Self {
+        let matches = absolute_paths(Self::command()).get_matches();
+        Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Log file to write to instead of the console, if any
+    pub fn log_file(&self) -> Option<&std::path::Path> {
+        match &self.command {
+            #[cfg(feature = "serve")]
+            Some(Commands::Agent { log_file, .. }) => log_file.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// Make every `PathBuf` argument of a command and its subcommands absolute
+/// as it is parsed
+fn absolute_paths(command: clap::Command) -> clap::Command {
+    command
+        .mut_args(|arg| {
+            if arg.get_value_parser().type_id() == std::any::TypeId::of::<PathBuf>() {
+                arg.value_parser(|value: &str| {
+                    std::path::absolute(value).map_err(|e| e.to_string())
+                })
+            } else {
+                arg
+            }
+        })
+        .mut_subcommands(absolute_paths)
 }
 
 /// Available subcommands
@@ -41,7 +150,29 @@ pub enum Commands {
         path: PathBuf,
     },
     /// List all tracks in the database
-    List,
+    List {
+        /// Only list tracks added in the last N days, newest first
+        #[arg(long)]
+        added_within: Option<u32>,
+    },
+    /// Add a stream or cloud drive link to the library (played from the
+    /// network; not scanned, organized or fingerprinted)
+    AddUrl {
+        /// The http:// or https:// URL
+        url: String,
+        /// Track title (default: the last part of the URL)
+        #[arg(long)]
+        title: Option<String>,
+        /// Artist name
+        #[arg(long, default_value = "")]
+        artist: String,
+        /// Album name
+        #[arg(long, default_value = "")]
+        album: String,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
     /// Organize music files based on metadata
     Organize {
         /// Destination root directory
@@ -57,8 +188,45 @@ pub enum Commands {
         /// Dry run - show what would be done without actually moving files
         #[arg(long)]
         dry_run: bool,
+        /// Save the dry-run plan to a file (.json to apply later, .csv for review)
+        #[arg(long, requires = "dry_run")]
+        plan: Option<PathBuf>,
+        /// Leave out files whose move would overwrite another file
+        /// (default: refuse to organize while there are conflicts)
+        #[arg(long)]
+        skip_conflicts: bool,
+    },
+    /// Execute a plan saved by a dry run, exactly as previewed
+    ApplyPlan {
+        /// Path to the JSON plan file
+        plan: PathBuf,
+        /// Skip entries whose files changed since the plan was made
+        /// (default: abort if anything drifted)
+        #[arg(long)]
+        skip_drifted: bool,
+    },
+    /// Resume or roll back an organize that was interrupted
+    RecoverOrganize {
+        /// Finish the interrupted moves
+        #[arg(long, conflicts_with = "rollback")]
+        resume: bool,
+        /// Move every file of the interrupted organize back
+        #[arg(long)]
+        rollback: bool,
+    },
+    /// Write folder art and Kodi/Jellyfin album.nfo and artist.nfo files
+    ExportNfo {
+        /// Only albums under this folder (default: the whole library)
+        path: Option<PathBuf>,
+        /// Replace existing files (default: `nfo.overwrite`)
+        #[arg(long)]
+        overwrite: bool,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
     },
     /// Identify a track using audio fingerprinting
+    #[cfg(feature = "enrichment")]
     Identify {
         /// Path to the audio file
         path: PathBuf,
@@ -101,6 +269,7 @@ pub enum Commands {
         preview: bool,
     },
     /// Batch enrich multiple audio files
+    #[cfg(feature = "enrichment")]
     Enrich {
         /// Path to file or directory to enrich
         path: PathBuf,
@@ -125,14 +294,37 @@ pub enum Commands {
         /// Database path for tracking file health (enables health tracking)
         #[arg(long)]
         db: Option<PathBuf>,
+        /// Fingerprint every file, even whole ripped CDs (which are matched
+        /// by disc ID by default)
+        #[arg(long)]
+        no_disc_id: bool,
+        /// Identify every file on its own, even full-album folders (whose
+        /// files vote on one release by default)
+        #[arg(long)]
+        no_album: bool,
+        /// Save a report of every file's match and tag changes (.json, .csv
+        /// or .html)
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
+    /// Fingerprint tagged library tracks and flag those whose audio is
+    /// another recording than their title and artist say
+    #[cfg(feature = "enrichment")]
+    VerifyTags {
+        /// AcoustID API key (or set ACOUSTID_API_KEY env var)
+        #[arg(short, long, env = "ACOUSTID_API_KEY")]
+        api_key: Option<String>,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
     },
     /// Check file health status
     Check {
         /// Path to file or directory to check
         path: Option<PathBuf>,
-        /// Database path
-        #[arg(long, default_value = "music_minder.db")]
-        db: PathBuf,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
         /// Show only files with errors
         #[arg(long)]
         errors_only: bool,
@@ -148,16 +340,198 @@ pub enum Commands {
         /// Run quick check (skip slow measurements)
         #[arg(long)]
         quick: bool,
+        /// Database whose tracks' sample rates are compared with the output
+        /// device (default: the active profile's database, if there is one)
+        #[arg(long)]
+        db: Option<PathBuf>,
     },
     /// Assess metadata quality for library tracks
     Quality {
-        /// Database path
-        #[arg(long, default_value = "music_minder.db")]
-        db: PathBuf,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
         /// Show detailed output for each track
         #[arg(short, long)]
         verbose: bool,
     },
+    /// Find compilation folders that were split into one album per artist
+    Compilations {
+        /// Group them under "Various Artists" (default: just list them)
+        #[arg(long)]
+        apply: bool,
+        /// Confidence needed, 0.0-1.0 (default: `library.compilation_threshold`)
+        #[arg(long)]
+        threshold: Option<f32>,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Find albums an older scan split by track artist, and regroup them
+    /// under their album artist
+    AlbumArtists {
+        /// Write the album artist tags and regroup (default: just list them)
+        #[arg(long)]
+        apply: bool,
+        /// Put back what the last --apply changed
+        #[arg(long, conflicts_with = "apply")]
+        undo: bool,
+        /// Confidence a proposal needs, 0.0-1.0
+        #[arg(long, default_value_t = crate::library::album_artists::DEFAULT_MIN_CONFIDENCE)]
+        min_confidence: f32,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Import ratings and play counts other players wrote to the tags
+    /// (POPM, FMPS_RATING, FMPS_PLAYCOUNT, RATING)
+    ImportRatings {
+        /// Only tracks under this folder (default: the whole library)
+        path: Option<PathBuf>,
+        /// Import them (default: just show what would change)
+        #[arg(long)]
+        apply: bool,
+        /// Take POPM ratings from this player's frame only, e.g. "MusicBee"
+        /// (default: `library.popm_email`; empty takes the highest)
+        #[arg(long)]
+        popm_email: Option<String>,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Report tracks missing from identified albums
+    Completeness {
+        /// Compare every identified album with its MusicBrainz release first
+        /// (about one album per second)
+        #[arg(long)]
+        check: bool,
+        /// Include complete albums in the report
+        #[arg(long)]
+        all: bool,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Album cover art
+    Covers {
+        #[command(subcommand)]
+        action: CoversAction,
+    },
+    /// Check that consecutive album tracks play without gaps between them
+    Gapless {
+        /// Only albums under this folder (default: the whole library)
+        path: Option<PathBuf>,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Measure loudness and write ReplayGain tags (EBU R128)
+    #[command(name = "replaygain")]
+    ReplayGain {
+        /// Only tracks under this folder (default: the whole library)
+        path: Option<PathBuf>,
+        /// Print the values without writing tags or storing them
+        #[arg(long)]
+        dry_run: bool,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Genres in the library, merging them, and the spelling rules
+    Genres {
+        #[command(subcommand)]
+        action: GenresAction,
+    },
+    /// Show what the app changed in the library, newest first
+    Activity {
+        /// Only changes on or after this date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<chrono::NaiveDate>,
+        /// Only changes before this date (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<chrono::NaiveDate>,
+        /// Only changes on this day (YYYY-MM-DD)
+        #[arg(long, conflicts_with_all = ["since", "until"])]
+        on: Option<chrono::NaiveDate>,
+        /// Only this kind of change (track_added, track_removed, tags_written,
+        /// file_organized, recording_matched, path_normalized)
+        #[arg(long)]
+        kind: Option<crate::activity::ActivityKind>,
+        /// Maximum number of entries
+        #[arg(long, default_value = "200")]
+        limit: u32,
+        /// Save the entries to a file (.json or .csv) instead of printing
+        #[arg(long)]
+        export: Option<PathBuf>,
+    },
+    /// List library profiles and show which one is active
+    Profiles,
+    /// Encrypt the API keys in the config with a passphrase
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsAction,
+    },
+    /// Carry settings to another machine in one JSON file
+    Settings {
+        #[command(subcommand)]
+        action: SettingsAction,
+    },
+    /// Open a musicminder:// link in the running app (or start it)
+    Open {
+        /// The link, e.g. musicminder://search?q=blue+train
+        uri: String,
+    },
+    /// Register the musicminder:// scheme with the desktop
+    Links {
+        #[command(subcommand)]
+        action: LinksAction,
+    },
+    /// Inspect the library database
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    /// Rip the CD in the drive to FLAC, tagged and organized into the library
+    #[cfg(feature = "cd-rip")]
+    Rip {
+        /// CD drive to read (default: the first one found)
+        #[arg(long)]
+        device: Option<String>,
+        /// The drive's read offset in samples, for AccurateRip verification
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        read_offset: i32,
+        /// Library root to organize into (default: the last organize destination)
+        #[arg(short, long)]
+        destination: Option<PathBuf>,
+        /// Organize pattern (default: the last one used)
+        #[arg(short, long)]
+        pattern: Option<String>,
+        /// Database to index the tracks into (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Run headless: watch and scan the library, run scheduled jobs,
+    /// identify new tracks and serve the JSON API
+    #[cfg(feature = "serve")]
+    #[command(alias = "serve")]
+    Agent {
+        /// API listen address (default: `agent.listen`, 127.0.0.1:7431)
+        #[arg(long, value_name = "ADDR")]
+        listen: Option<String>,
+        /// Don't identify new tracks
+        #[arg(long)]
+        no_enrich: bool,
+        /// Directory to run in (the default profile's database is relative to it)
+        #[arg(long, value_name = "DIR")]
+        working_dir: Option<PathBuf>,
+        /// Write logs to this file instead of the console
+        #[arg(long, value_name = "PATH")]
+        log_file: Option<PathBuf>,
+        /// Run under the Windows service manager (set by `agent install`)
+        #[arg(long, hide = true)]
+        service: bool,
+        #[command(subcommand)]
+        action: Option<AgentAction>,
+    },
     /// Watch a directory for file changes (for debugging/testing)
     Watch {
         /// Path to the directory to watch
@@ -174,6 +548,165 @@ pub enum Commands {
     },
 }
 
+/// `agent` subcommands
+#[cfg(feature = "serve")]
+#[derive(Subcommand)]
+pub enum AgentAction {
+    /// Install and start the agent as a service for the active profile
+    /// (Windows service or systemd user unit), with the options given
+    Install,
+    /// Stop and remove the active profile's agent service
+    Uninstall,
+}
+
+/// `covers` subcommands
+#[derive(Subcommand)]
+pub enum CoversAction {
+    /// Fetch one cover per album (the album's own art, the cache, then the
+    /// Cover Art Archive) and write it as folder.jpg or embed it, per
+    /// `[covers]` in the config file
+    Fetch {
+        /// Only albums without a cover where it would be written; existing
+        /// covers are replaced otherwise
+        #[arg(long)]
+        missing_only: bool,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+}
+
+/// `genres` subcommands
+#[derive(Subcommand)]
+pub enum GenresAction {
+    /// List every genre with its track count, most used first
+    List {
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Merge genres into one (or rename one), in the tags and the database
+    Merge {
+        /// The genres to merge, as listed
+        #[arg(required = true)]
+        from: Vec<String>,
+        /// The genre they become
+        #[arg(long)]
+        into: String,
+        /// Also map these spellings on future scans and tag writes
+        /// (`tagging.genre_map`)
+        #[arg(long)]
+        remember: bool,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// List the genre rules
+    Rules,
+    /// Remove the rule for a spelling
+    Unmap {
+        /// The spelling (any spelling with the same letters and digits)
+        spelling: String,
+    },
+}
+
+/// `secrets` subcommands
+///
+/// The passphrase is read from `MUSIC_MINDER_PASSPHRASE`, or asked for.
+#[derive(Subcommand)]
+pub enum SecretsAction {
+    /// Encrypt the credentials from now on
+    Encrypt,
+    /// Store the credentials in plain text again
+    Decrypt,
+    /// Show whether the credentials are encrypted
+    Status,
+}
+
+/// `settings` subcommands
+#[derive(Subcommand)]
+pub enum SettingsAction {
+    /// Save the active profile's settings to a bundle
+    Export {
+        /// The bundle to write (.json)
+        file: PathBuf,
+        /// Only these sections, comma-separated (default: all but credentials)
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<crate::settings_bundle::Section>,
+        /// Include the API keys (still encrypted if they are)
+        #[arg(long)]
+        with_credentials: bool,
+    },
+    /// Load settings from a bundle into the active profile
+    Import {
+        /// The bundle to read
+        file: PathBuf,
+        /// Only these sections, comma-separated (default: all in the bundle)
+        #[arg(long, value_delimiter = ',')]
+        only: Vec<crate::settings_bundle::Section>,
+        /// List the bundle's sections without importing
+        #[arg(long)]
+        list: bool,
+    },
+}
+
+/// `links` subcommands
+#[derive(Subcommand)]
+pub enum LinksAction {
+    /// Open musicminder:// links with this executable (for the current user)
+    Register,
+    /// Stop opening musicminder:// links with this executable
+    Unregister,
+}
+
+/// `db` subcommands
+#[derive(Subcommand)]
+pub enum DbAction {
+    /// Show the schema version, applied and pending migrations, and the
+    /// backups made before upgrades (doesn't upgrade the database)
+    Info {
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Store every track path in canonical form and merge tracks stored
+    /// twice under different spellings (run after changing the path settings)
+    NormalizePaths {
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Merge another library database into this one (tracks matched by path
+    /// or MusicBrainz recording; ratings and play history kept)
+    Merge {
+        /// The database to merge in
+        from: PathBuf,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+    /// Copy part of the library into a new database
+    Split {
+        /// The new database file
+        to: PathBuf,
+        /// Take tracks under this folder (repeatable)
+        #[arg(long)]
+        folder: Vec<PathBuf>,
+        /// Take tracks by this artist
+        #[arg(long)]
+        artist: Option<String>,
+        /// Take tracks with this file extension (e.g. flac)
+        #[arg(long)]
+        format: Option<String>,
+        /// Remove the copied tracks from this database
+        #[arg(long)]
+        remove: bool,
+        /// Database path (default: the active profile's database)
+        #[arg(long)]
+        db: Option<PathBuf>,
+    },
+}
+
 /// Run the specified CLI command.
 ///
 /// Returns `Ok(true)` if a command was run, `Ok(false)` if no command was specified
@@ -186,18 +719,54 @@ pub fn run_command(cli: &Cli) -> anyhow::Result<bool> {
             cmd_scan(&rt, path)?;
             Ok(true)
         }
-        Some(Commands::List) => {
-            cmd_list(&rt)?;
+        Some(Commands::List { added_within }) => {
+            cmd_list(&rt, *added_within)?;
+            Ok(true)
+        }
+        Some(Commands::AddUrl {
+            url,
+            title,
+            artist,
+            album,
+            db,
+        }) => {
+            cmd_add_url(&rt, url, title.as_deref(), artist, album, db.as_deref())?;
             Ok(true)
         }
         Some(Commands::Organize {
             destination,
             pattern,
             dry_run,
+            plan,
+            skip_conflicts,
+        }) => {
+            cmd_organize(
+                &rt,
+                destination,
+                pattern,
+                *dry_run,
+                plan.as_ref(),
+                *skip_conflicts,
+            )?;
+            Ok(true)
+        }
+        Some(Commands::ApplyPlan { plan, skip_drifted }) => {
+            cmd_apply_plan(&rt, plan, *skip_drifted)?;
+            Ok(true)
+        }
+        Some(Commands::RecoverOrganize { resume, rollback }) => {
+            cmd_recover_organize(&rt, *resume, *rollback)?;
+            Ok(true)
+        }
+        Some(Commands::ExportNfo {
+            path,
+            overwrite,
+            db,
         }) => {
-            cmd_organize(&rt, destination, pattern, *dry_run)?;
+            cmd_export_nfo(&rt, path.as_deref(), *overwrite, db.as_deref())?;
             Ok(true)
         }
+        #[cfg(feature = "enrichment")]
         Some(Commands::Identify {
             path,
             api_key,
@@ -222,6 +791,7 @@ pub fn run_command(cli: &Cli) -> anyhow::Result<bool> {
             preview,
         }) => {
             cmd_write_tags(
+                &rt,
                 path,
                 title.as_deref(),
                 artist.as_deref(),
@@ -233,6 +803,7 @@ pub fn run_command(cli: &Cli) -> anyhow::Result<bool> {
             )?;
             Ok(true)
         }
+        #[cfg(feature = "enrichment")]
         Some(Commands::Enrich {
             path,
             api_key,
@@ -242,6 +813,9 @@ pub fn run_command(cli: &Cli) -> anyhow::Result<bool> {
             min_confidence,
             dry_run,
             db,
+            no_disc_id,
+            no_album,
+            report,
         }) => {
             cmd_enrich(
                 &rt,
@@ -253,27 +827,36 @@ pub fn run_command(cli: &Cli) -> anyhow::Result<bool> {
                 *min_confidence,
                 *dry_run,
                 db.as_ref(),
+                !*no_disc_id,
+                !*no_album,
+                report.as_ref(),
             )?;
             Ok(true)
         }
+        #[cfg(feature = "enrichment")]
+        Some(Commands::VerifyTags { api_key, db }) => {
+            cmd_verify_tags(&rt, api_key.as_deref(), db.as_deref())?;
+            Ok(true)
+        }
         Some(Commands::Check {
             path,
             db,
             errors_only: _,
             verbose: _,
         }) => {
-            cmd_check(&rt, db, path.as_ref())?;
+            cmd_check(&rt, db.as_deref(), path.as_ref())?;
             Ok(true)
         }
         Some(Commands::Diagnose {
-            format: _,
+            format,
             quick: _,
+            db,
         }) => {
-            cmd_diagnose()?;
+            cmd_diagnose(&rt, format, db.as_deref())?;
             Ok(true)
         }
         Some(Commands::Quality { db, verbose }) => {
-            cmd_quality(&rt, db, *verbose)?;
+            cmd_quality(&rt, db.as_deref(), *verbose)?;
             Ok(true)
         }
         Some(Commands::Watch {
@@ -285,6 +868,211 @@ pub fn run_command(cli: &Cli) -> anyhow::Result<bool> {
             cmd_watch(&rt, path, *verbose, db.as_ref(), *scan_first)?;
             Ok(true)
         }
+        Some(Commands::Activity {
+            since,
+            until,
+            on,
+            kind,
+            limit,
+            export,
+        }) => {
+            cmd_activity(&rt, *since, *until, *on, *kind, *limit, export.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::Compilations {
+            apply,
+            threshold,
+            db,
+        }) => {
+            let threshold =
+                threshold.unwrap_or_else(|| crate::config::load().library.compilation_threshold);
+            cmd_compilations(&rt, db.as_deref(), threshold, *apply)?;
+            Ok(true)
+        }
+        Some(Commands::AlbumArtists {
+            apply,
+            undo,
+            min_confidence,
+            db,
+        }) => {
+            cmd_album_artists(&rt, db.as_deref(), *min_confidence, *apply, *undo)?;
+            Ok(true)
+        }
+        Some(Commands::ImportRatings {
+            path,
+            apply,
+            popm_email,
+            db,
+        }) => {
+            let popm_email = popm_email
+                .clone()
+                .unwrap_or_else(|| crate::config::load().library.popm_email);
+            cmd_import_ratings(&rt, db.as_deref(), path.as_deref(), &popm_email, *apply)?;
+            Ok(true)
+        }
+        Some(Commands::Completeness { check, all, db }) => {
+            cmd_completeness(&rt, db.as_deref(), *check, *all)?;
+            Ok(true)
+        }
+        Some(Commands::Covers {
+            action: CoversAction::Fetch { missing_only, db },
+        }) => {
+            cmd_covers_fetch(&rt, *missing_only, db.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::Gapless { path, db }) => {
+            cmd_gapless(&rt, path.as_deref(), db.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::ReplayGain { path, dry_run, db }) => {
+            cmd_replaygain(&rt, path.as_deref(), *dry_run, db.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::Profiles) => {
+            cmd_profiles()?;
+            Ok(true)
+        }
+        Some(Commands::Settings {
+            action:
+                SettingsAction::Export {
+                    file,
+                    only,
+                    with_credentials,
+                },
+        }) => {
+            cmd_settings_export(file, only, *with_credentials)?;
+            Ok(true)
+        }
+        Some(Commands::Settings {
+            action: SettingsAction::Import { file, only, list },
+        }) => {
+            cmd_settings_import(file, only, *list)?;
+            Ok(true)
+        }
+        Some(Commands::Secrets { action }) => {
+            match action {
+                SecretsAction::Encrypt => cmd_secrets_encrypt()?,
+                SecretsAction::Decrypt => cmd_secrets_decrypt()?,
+                SecretsAction::Status => cmd_secrets_status()?,
+            }
+            Ok(true)
+        }
+        Some(Commands::Open { uri }) => cmd_open(uri),
+        Some(Commands::Links {
+            action: LinksAction::Register,
+        }) => {
+            cmd_links_register()?;
+            Ok(true)
+        }
+        Some(Commands::Links {
+            action: LinksAction::Unregister,
+        }) => {
+            cmd_links_unregister()?;
+            Ok(true)
+        }
+        Some(Commands::Genres {
+            action: GenresAction::List { db },
+        }) => {
+            cmd_genres_list(&rt, db.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::Genres {
+            action:
+                GenresAction::Merge {
+                    from,
+                    into,
+                    remember,
+                    db,
+                },
+        }) => {
+            cmd_genres_merge(&rt, from, into, *remember, db.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::Genres {
+            action: GenresAction::Rules,
+        }) => {
+            cmd_genres_rules()?;
+            Ok(true)
+        }
+        Some(Commands::Genres {
+            action: GenresAction::Unmap { spelling },
+        }) => {
+            cmd_genres_unmap(spelling)?;
+            Ok(true)
+        }
+        Some(Commands::Db {
+            action: DbAction::Info { db },
+        }) => {
+            cmd_db_info(&rt, db.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::Db {
+            action: DbAction::NormalizePaths { db },
+        }) => {
+            cmd_db_normalize_paths(&rt, db.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::Db {
+            action: DbAction::Merge { from, db },
+        }) => {
+            cmd_db_merge(&rt, from, db.as_deref())?;
+            Ok(true)
+        }
+        Some(Commands::Db {
+            action:
+                DbAction::Split {
+                    to,
+                    folder,
+                    artist,
+                    format,
+                    remove,
+                    db,
+                },
+        }) => {
+            let selection = crate::db::TrackSelection {
+                folders: folder.clone(),
+                artist: artist.clone(),
+                format: format.clone(),
+            };
+            cmd_db_split(&rt, to, &selection, *remove, db.as_deref())?;
+            Ok(true)
+        }
+        #[cfg(feature = "cd-rip")]
+        Some(Commands::Rip {
+            device,
+            read_offset,
+            destination,
+            pattern,
+            db,
+        }) => {
+            cmd_rip(
+                &rt,
+                device.clone(),
+                *read_offset,
+                destination.clone(),
+                pattern.clone(),
+                db.as_deref(),
+            )?;
+            Ok(true)
+        }
+        #[cfg(feature = "serve")]
+        Some(Commands::Agent {
+            listen,
+            no_enrich,
+            working_dir,
+            log_file: _,
+            service,
+            action,
+        }) => {
+            let args = AgentArgs {
+                listen: listen.as_deref(),
+                no_enrich: *no_enrich,
+                working_dir: working_dir.as_deref(),
+                service: *service,
+            };
+            cmd_agent(&rt, &args, action.as_ref())?;
+            Ok(true)
+        }
         None => Ok(false),
     }
 }
@@ -303,6 +1091,7 @@ pub(crate) fn print_fpcalc_install_instructions() {
 }
 
 /// Collect audio files from a path (file or directory)
+#[cfg(feature = "enrichment")]
 pub(crate) fn collect_audio_files(path: &PathBuf, recursive: bool) -> Vec<PathBuf> {
     if path.is_dir() {
         if recursive {
@@ -1,9 +1,12 @@
 //! File organization command.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::runtime::Runtime;
 
-use crate::{db, metadata, organizer};
+use crate::model::TrackSource;
+use crate::plan::{OperationPlan, PlanKind};
+use crate::provenance::{self, FieldSource};
+use crate::{activity, db, metadata, nfo, organizer};
 
 /// Organize music files based on metadata
 pub fn cmd_organize(
@@ -11,10 +14,12 @@ pub fn cmd_organize(
     destination: &PathBuf,
     pattern: &str,
     dry_run: bool,
+    plan_path: Option<&PathBuf>,
+    skip_conflicts: bool,
 ) -> anyhow::Result<()> {
     rt.block_on(async {
-        let db_url = "sqlite:music_minder.db";
-        let pool = db::init_db(db_url).await.expect("Failed to init DB");
+        let db_url = db::db_url(None);
+        let pool = db::init_db(&db_url).await?;
         let tracks = db::get_all_tracks(&pool)
             .await
             .expect("Failed to get tracks");
@@ -27,44 +32,354 @@ pub fn cmd_organize(
             println!("\n[DRY RUN MODE - No files will be moved]\n");
         }
 
-        let mut success_count = 0;
-        let mut error_count = 0;
-
+        // Work out every destination first, so the whole batch can be
+        // checked for moves that would overwrite a file
+        let mut planned = Vec::new();
         for track in tracks {
+            // Streams and cloud links have no file to move
+            if !TrackSource::detect(&track.path).is_local() {
+                continue;
+            }
             let source_path = PathBuf::from(&track.path);
 
             // Read metadata from file
-            if let Ok(meta) = metadata::read(&source_path) {
-                match organizer::organize_track(&source_path, &meta, pattern, destination) {
-                    Ok(new_path) => {
-                        if dry_run {
-                            println!("WOULD MOVE: {} -> {:?}", track.path, new_path);
-                        } else {
-                            println!("MOVED: {} -> {:?}", track.path, new_path);
-                            // Update database with new path
-                            let _ = db::insert_track(
-                                &pool,
-                                &meta,
-                                new_path.to_str().unwrap_or(""),
-                                track.artist_id,
-                                track.album_id,
-                            )
+            let Ok(meta) = metadata::read(&source_path) else {
+                continue;
+            };
+
+            let preview =
+                organizer::preview_organize(&source_path, &meta, pattern, destination, track.id);
+            planned.push((track, preview));
+        }
+
+        let simulation = organizer::simulate(
+            &planned
+                .iter()
+                .map(|(_, preview)| preview.clone())
+                .collect::<Vec<_>>(),
+        );
+        for conflict in &simulation.conflicts {
+            eprintln!("CONFLICT: {}", conflict);
+        }
+        if !simulation.is_clear() {
+            if skip_conflicts {
+                println!(
+                    "Skipping {} conflicting moves ({})",
+                    simulation.conflicts.len(),
+                    simulation.summary()
+                );
+                planned.retain(|(_, preview)| !simulation.is_conflicted(&preview.source));
+            } else if dry_run {
+                println!(
+                    "{} of {} moves conflict ({}); organizing will refuse until they're resolved",
+                    simulation.conflicts.len(),
+                    simulation.moves,
+                    simulation.summary()
+                );
+            } else {
+                anyhow::bail!(
+                    "{} of {} moves would overwrite files ({}); change the pattern or pass --skip-conflicts",
+                    simulation.conflicts.len(),
+                    simulation.moves,
+                    simulation.summary()
+                );
+            }
+        }
+
+        let mut success_count = 0;
+        let mut error_count = 0;
+        let mut previews = Vec::new();
+        let mut journal = if dry_run {
+            None
+        } else {
+            Some(organizer::OrganizeJournal::begin()?)
+        };
+        let mut sync = organizer::PathSync::new();
+
+        for (track, preview) in planned {
+            let source_path = preview.source.clone();
+
+            let Some(journal) = journal.as_mut() else {
+                println!("WOULD MOVE: {} -> {:?}", track.path, preview.destination);
+                previews.push(preview);
+                success_count += 1;
+                continue;
+            };
+
+            let new_path = preview.destination;
+            match journal.journaled_move(track.id, &source_path, &new_path) {
+                Ok(seq) => {
+                    println!("MOVED: {} -> {:?}", track.path, new_path);
+                    match sync.record(&pool, track.id, &new_path).await {
+                        Ok(()) => journal.record_commit(seq)?,
+                        Err(e) => eprintln!("ERROR updating {}: {}", track.path, e),
+                    }
+                    success_count += 1;
+                }
+                Err(e) => {
+                    eprintln!("ERROR organizing {}: {}", track.path, e);
+                    error_count += 1;
+                }
+            }
+        }
+
+        println!(
+            "\nCompleted: {} successful, {} errors",
+            success_count, error_count
+        );
+        print_sync_report(&sync.finish());
+        finish_journal(journal)?;
+
+        let nfo_config = crate::config::load().nfo;
+        if !dry_run && success_count > 0 && nfo_config.after_organize {
+            let scope = nfo::Scope::Under(destination.clone());
+            let report = nfo::export(&pool, &scope, &nfo_config).await?;
+            print_nfo_report(&report);
+        }
+
+        if let Some(path) = plan_path {
+            let plan = OperationPlan::organize(&previews);
+            plan.save(path)?;
+            println!(
+                "Plan with {} moves saved to {:?} (hash {})",
+                plan.moves.len(),
+                path,
+                &plan.hash[..12]
+            );
+        }
+        anyhow::Ok(())
+    })
+}
+
+/// Write folder art and Kodi/Jellyfin NFO files for the library, or the
+/// albums under `path`
+pub fn cmd_export_nfo(
+    rt: &Runtime,
+    path: Option<&Path>,
+    overwrite: bool,
+    db_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mut config = crate::config::load().nfo;
+    config.overwrite |= overwrite;
+    let scope = match path {
+        Some(path) => nfo::Scope::Under(path.to_path_buf()),
+        None => nfo::Scope::Library,
+    };
+    let report = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        anyhow::Ok(nfo::export(&pool, &scope, &config).await?)
+    })?;
+    print_nfo_report(&report);
+    Ok(())
+}
+
+fn print_nfo_report(report: &nfo::ExportReport) {
+    for error in &report.errors {
+        eprintln!("ERROR: {}", error);
+    }
+    println!("{}", report.summary());
+}
+
+/// Execute a previously saved dry-run plan
+pub fn cmd_apply_plan(rt: &Runtime, plan_path: &Path, skip_drifted: bool) -> anyhow::Result<()> {
+    let plan = OperationPlan::load(plan_path)?;
+    println!(
+        "Loaded {} plan from {} ({} entries, created {})",
+        plan.kind,
+        plan_path.display(),
+        plan.len(),
+        plan.created_at
+    );
+
+    let drift = plan.detect_drift();
+    if !drift.is_empty() {
+        for d in &drift {
+            eprintln!("DRIFT: {}", d);
+        }
+        if !skip_drifted {
+            anyhow::bail!(
+                "{} entries changed since the plan was made; re-run the dry run or pass --skip-drifted",
+                drift.len()
+            );
+        }
+        println!("Skipping {} drifted entries", drift.len());
+    }
+    let drifted = |path: &Path| drift.iter().any(|d| d.path == path);
+
+    // A plan can be applied long after it was made; check the moves it
+    // still has against the disk as it is now
+    let remaining: Vec<_> = plan
+        .to_previews()
+        .into_iter()
+        .filter(|p| !drifted(&p.source))
+        .collect();
+    let simulation = organizer::simulate(&remaining);
+    if !simulation.is_clear() {
+        for conflict in &simulation.conflicts {
+            eprintln!("CONFLICT: {}", conflict);
+        }
+        anyhow::bail!(
+            "{} moves would overwrite files ({}); re-run the dry run",
+            simulation.conflicts.len(),
+            simulation.summary()
+        );
+    }
+
+    let mut success_count = 0;
+    let mut error_count = 0;
+
+    match plan.kind {
+        PlanKind::Organize => rt.block_on(async {
+            let pool = db::init_db(&db::db_url(None)).await?;
+            let mut journal = organizer::OrganizeJournal::begin()?;
+            let mut sync = organizer::PathSync::new();
+            let mut undo_log = organizer::UndoLog {
+                moves: vec![],
+                timestamp: Some(chrono::Utc::now().to_rfc3339()),
+            };
+
+            for m in plan.moves.iter().filter(|m| !drifted(&m.source)) {
+                match journal.journaled_move(m.track_id, &m.source, &m.destination) {
+                    Ok(seq) => {
+                        println!("MOVED: {:?} -> {:?}", m.source, m.destination);
+                        sync.record(&pool, m.track_id, &m.destination).await?;
+                        journal.record_commit(seq)?;
+                        undo_log.moves.push(organizer::MoveRecord {
+                            source: m.source.clone(),
+                            destination: m.destination.clone(),
+                            track_id: m.track_id,
+                        });
+                        success_count += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("ERROR moving {:?}: {}", m.source, e);
+                        error_count += 1;
+                    }
+                }
+            }
+
+            // Before this batch's undo log replaces the earlier one
+            print_sync_report(&sync.finish());
+            undo_log.save()?;
+            finish_journal(Some(journal))?;
+            anyhow::Ok(())
+        })?,
+        PlanKind::Enrich => rt.block_on(async {
+            let pool = db::init_db(&db::db_url(None)).await?;
+            for edit in plan.tag_edits.iter().filter(|e| !drifted(&e.path)) {
+                match edit.apply() {
+                    Ok(result) => {
+                        println!("WROTE: {:?} ({} fields)", edit.path, result.fields_updated);
+                        activity::record_tags_written(&pool, &edit.path, result.fields_updated)
                             .await;
-                        }
+                        provenance::record_written(
+                            &pool,
+                            &edit.path,
+                            &result.fields_written,
+                            FieldSource::Plan,
+                            None,
+                        )
+                        .await;
                         success_count += 1;
                     }
                     Err(e) => {
-                        eprintln!("ERROR organizing {}: {}", track.path, e);
+                        eprintln!("ERROR writing {:?}: {}", edit.path, e);
                         error_count += 1;
                     }
                 }
             }
-        }
+            anyhow::Ok(())
+        })?,
+    }
 
+    println!(
+        "\nCompleted: {} successful, {} errors",
+        success_count, error_count
+    );
+    Ok(())
+}
+
+/// Resume or roll back an organize that was interrupted by a crash
+pub fn cmd_recover_organize(rt: &Runtime, resume: bool, rollback: bool) -> anyhow::Result<()> {
+    let Some(incomplete) = organizer::OrganizeJournal::load_incomplete() else {
+        println!("No interrupted organize found.");
+        return Ok(());
+    };
+
+    println!(
+        "Interrupted organize (started {}): {} of {} moves unfinished",
+        incomplete.started.as_deref().unwrap_or("unknown"),
+        incomplete.pending_count(),
+        incomplete.entries.len()
+    );
+    for entry in incomplete.entries.iter().filter(|e| !e.committed) {
         println!(
-            "\nCompleted: {} successful, {} errors",
-            success_count, error_count
+            "  [{:?}] {:?} -> {:?}",
+            entry.location(),
+            entry.source,
+            entry.destination
         );
-    });
+    }
+
+    let mode = match (resume, rollback) {
+        (true, _) => organizer::RecoveryMode::Resume,
+        (_, true) => organizer::RecoveryMode::Rollback,
+        _ => {
+            println!("\nRun again with --resume or --rollback.");
+            return Ok(());
+        }
+    };
+
+    let report = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(None)).await?;
+        anyhow::Ok(organizer::recover(&pool, &incomplete, mode).await)
+    })?;
+
+    for e in &report.errors {
+        eprintln!("ERROR: {}", e);
+    }
+    print_sync_report(&report.sync);
+    println!(
+        "\nRecovery: {} resumed, {} rolled back, {} errors",
+        report.resumed,
+        report.rolled_back,
+        report.errors.len()
+    );
+    if !report.errors.is_empty() {
+        anyhow::bail!("recovery incomplete; the journal was kept so it can be retried");
+    }
     Ok(())
 }
+
+/// Say which saved state followed the moved files
+fn print_sync_report(report: &organizer::SyncReport) {
+    for e in &report.errors {
+        eprintln!("ERROR updating {}", e);
+    }
+    if report.session_entries > 0 {
+        println!(
+            "Updated {} entries of the saved queue",
+            report.session_entries
+        );
+    }
+    if report.undo_records > 0 {
+        println!(
+            "Updated {} records of the previous undo log",
+            report.undo_records
+        );
+    }
+}
+
+/// Remove the journal if every move was committed, otherwise point at recovery
+fn finish_journal(journal: Option<organizer::OrganizeJournal>) -> anyhow::Result<()> {
+    match journal {
+        Some(journal) if journal.is_complete() => journal.finish(),
+        Some(_) => {
+            eprintln!(
+                "Some moves were not recorded in the database; run `music-minder recover-organize`"
+            );
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,18 @@
+//! Library profile command.
+
+use crate::profile;
+
+/// List profiles, marking the active one
+pub fn cmd_profiles() -> anyhow::Result<()> {
+    let active = profile::active();
+    for name in profile::list() {
+        let marker = if name == active { "*" } else { " " };
+        println!("{} {}", marker, name);
+    }
+    println!();
+    println!("Database: {}", profile::db_path().display());
+    if let Some(path) = profile::config_path() {
+        println!("Config:   {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! ReplayGain scanning command.
+
+use std::path::Path;
+use tokio::runtime::Runtime;
+
+use crate::db;
+use crate::library::replaygain::{self, Scope};
+use crate::metadata::loudness::{format_gain, format_peak};
+use crate::tasks::{TaskHandle, TaskKind};
+
+/// Measure the loudness of the library's tracks (or those under `path`),
+/// and write ReplayGain tags unless `dry_run`
+pub fn cmd_replaygain(
+    rt: &Runtime,
+    path: Option<&Path>,
+    dry_run: bool,
+    db_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    let scope = match path {
+        Some(path) => Scope::Under(path.to_path_buf()),
+        None => Scope::Library,
+    };
+    let report = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        let task = TaskHandle::detached(TaskKind::Maintenance, "ReplayGain");
+        anyhow::Ok(replaygain::scan(&pool, &scope, dry_run, &task).await?)
+    })?;
+
+    for (path, loudness) in &report.tracks {
+        println!(
+            "{}  track {} peak {}  album {} peak {}",
+            path,
+            format_gain(loudness.track_gain),
+            format_peak(loudness.track_peak),
+            format_gain(loudness.album_gain),
+            format_peak(loudness.album_peak)
+        );
+    }
+    for (path, error) in &report.failed {
+        eprintln!("  Failed: {}: {}", path, error);
+    }
+    println!("\n{}", report.summary());
+    if dry_run && !report.tracks.is_empty() {
+        println!("Dry run: no tags written. Run without --dry-run to write them.");
+    }
+    Ok(())
+}
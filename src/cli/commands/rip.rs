@@ -0,0 +1,78 @@
+//! CD ripping command.
+
+use std::path::{Path, PathBuf};
+use tokio::runtime::Runtime;
+
+use crate::cdrip::{self, RipEvent, RipOptions};
+use crate::{config, db, nfo};
+
+/// Pattern used when none was given and none was used before
+const DEFAULT_PATTERN: &str = "{Artist}/{Album}/{TrackNum} - {Title}.{ext}";
+
+/// Rip the CD in the drive into the library
+pub fn cmd_rip(
+    rt: &Runtime,
+    device: Option<String>,
+    read_offset: i32,
+    destination: Option<PathBuf>,
+    pattern: Option<String>,
+    db_path: Option<&Path>,
+) -> anyhow::Result<()> {
+    let config = config::load();
+    let history = config.history;
+    let destination = destination
+        .or_else(|| history.organize_destinations.first().cloned())
+        .ok_or_else(|| {
+            anyhow::anyhow!("No destination given and no organize destination used before")
+        })?;
+    let pattern = pattern
+        .or_else(|| history.organize_patterns.first().cloned())
+        .unwrap_or_else(|| DEFAULT_PATTERN.to_string());
+    let options = RipOptions {
+        device,
+        read_offset,
+        destination,
+        pattern,
+    };
+
+    let report = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        let report = cdrip::rip_disc(Some(&pool), &options, print_event).await?;
+        if config.nfo.after_organize && !report.tracks.is_empty() {
+            let paths = report
+                .tracks
+                .iter()
+                .map(|t| t.path.to_string_lossy().to_string())
+                .collect();
+            let written = nfo::export(&pool, &nfo::Scope::Containing(paths), &config.nfo).await?;
+            println!("{}", written.summary());
+        }
+        anyhow::Ok(report)
+    })?;
+
+    println!(
+        "\nRipped {} track(s), {} accurately (disc ID {})",
+        report.tracks.len(),
+        report.accurate(),
+        report.disc_id
+    );
+    if !report.identified {
+        println!("Disc not found on MusicBrainz; run `enrich` on the tracks to identify them.");
+    }
+    Ok(())
+}
+
+fn print_event(event: RipEvent) {
+    match event {
+        RipEvent::Identified { tracks, release } => match release {
+            Some(release) => println!("{} ({} tracks)", release, tracks),
+            None => println!("Unknown disc ({} tracks)", tracks),
+        },
+        RipEvent::Ripping { track, total } => println!("Ripping track {}/{}...", track, total),
+        RipEvent::TrackDone(track) => println!(
+            "  {} - {}",
+            track.path.display(),
+            track.verification.describe()
+        ),
+    }
+}
@@ -1,4 +1,4 @@
-//! Library scanning and file watching commands.
+//! Library scanning, file watching and stream URL commands.
 
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -8,19 +8,26 @@ use tracing::{debug, info, warn};
 
 use crate::db;
 use crate::library;
+use crate::metadata;
+use crate::model::TrackSource;
 use crate::scanner;
+use crate::tasks::{TaskHandle, TaskKind};
 
 use crate::scanner::is_audio_file;
 
 /// Scan a directory for music files
 pub fn cmd_scan(rt: &Runtime, path: &PathBuf) -> anyhow::Result<()> {
     rt.block_on(async {
-        let db_url = "sqlite:music_minder.db";
-        let pool = db::init_db(db_url).await.expect("Failed to init DB");
+        let db_url = db::db_url(None);
+        let pool = db::init_db(&db_url).await?;
         println!("Scanning directory: {:?}", path);
 
         use futures::StreamExt;
-        let stream = library::scan_library(pool, path.clone());
+        let stream = library::scan_library(
+            pool,
+            path.clone(),
+            TaskHandle::detached(TaskKind::Scan, "Scan"),
+        );
         let mut stream = std::pin::pin!(stream);
         let mut count = 0;
 
@@ -37,25 +44,306 @@ pub fn cmd_scan(rt: &Runtime, path: &PathBuf) -> anyhow::Result<()> {
                 library::ScanEvent::Error(p, e) => {
                     eprintln!("\nError processing {:?}: {}", p, e);
                 }
+                library::ScanEvent::CompilationsGrouped(n) => {
+                    println!(
+                        "\nGrouped {} compilation album(s) under {}",
+                        n,
+                        library::VARIOUS_ARTISTS
+                    );
+                }
             }
         }
         println!("\nScan complete. Total scanned: {} tracks.", count);
-    });
-    Ok(())
+        anyhow::Ok(())
+    })
+}
+
+/// Find compilation folders split into one album per artist, and optionally
+/// group them under "Various Artists"
+pub fn cmd_compilations(
+    rt: &Runtime,
+    db_path: Option<&std::path::Path>,
+    threshold: f32,
+    apply: bool,
+) -> anyhow::Result<()> {
+    rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        let candidates = library::find_compilations(&pool, None, threshold).await?;
+        if candidates.is_empty() {
+            println!("No ungrouped compilations found.");
+            return Ok(());
+        }
+
+        for candidate in &candidates {
+            println!(
+                "{:.2}  {} ({} tracks, {} artists)\n      {}",
+                candidate.confidence,
+                candidate.album,
+                candidate.track_ids.len(),
+                candidate.artists,
+                candidate.folder.display()
+            );
+            if apply {
+                library::merge_compilation(&pool, candidate).await?;
+            }
+        }
+
+        if apply {
+            println!(
+                "\nGrouped {} album(s) under {}.",
+                candidates.len(),
+                library::VARIOUS_ARTISTS
+            );
+        } else {
+            println!(
+                "\n{} album(s) would be grouped under {}. Run with --apply to group them.",
+                candidates.len(),
+                library::VARIOUS_ARTISTS
+            );
+        }
+        anyhow::Ok(())
+    })
+}
+
+/// Find albums split by track artist or scattered into one-track albums,
+/// and optionally regroup them under their album artist (or undo that)
+pub fn cmd_album_artists(
+    rt: &Runtime,
+    db_path: Option<&std::path::Path>,
+    min_confidence: f32,
+    apply: bool,
+    undo: bool,
+) -> anyhow::Result<()> {
+    use crate::library::album_artists::{self, AlbumArtistUndo};
+
+    rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        if undo {
+            let Some(log) = AlbumArtistUndo::load() else {
+                println!("No album artist fixes to undo.");
+                return Ok(());
+            };
+            let report = album_artists::undo_fixes(&pool, &log).await?;
+            for (path, error) in &report.failed {
+                println!("  Failed: {}: {}", path, error);
+            }
+            AlbumArtistUndo::clear()?;
+            println!("Put back {} track(s).", report.tracks);
+            return Ok(());
+        }
+
+        let fixes = album_artists::find_fixes(&pool, min_confidence).await?;
+        if fixes.is_empty() {
+            println!("No misfiled albums found.");
+            return Ok(());
+        }
+        for fix in &fixes {
+            println!(
+                "{:.2}  {} ({})\n      {}",
+                fix.confidence,
+                fix.kind,
+                fix.folder.display(),
+                fix.after.title
+            );
+            for before in &fix.before {
+                println!(
+                    "        - {} / {} ({} tracks)",
+                    before.artist, before.title, before.tracks
+                );
+            }
+            println!(
+                "        + {} / {} ({} tracks)",
+                fix.after.artist, fix.after.title, fix.after.tracks
+            );
+        }
+
+        if apply {
+            let report = album_artists::apply_fixes(&pool, &fixes).await?;
+            for (path, error) in &report.failed {
+                println!("  Failed: {}: {}", path, error);
+            }
+            report.undo.save()?;
+            println!(
+                "\nRetagged and regrouped {} track(s). Run with --undo to put them back.",
+                report.tracks
+            );
+        } else {
+            println!(
+                "\n{} album(s) would be regrouped. Run with --apply to write the tags.",
+                fixes.len()
+            );
+        }
+        anyhow::Ok(())
+    })
+}
+
+/// Show the ratings and play counts the files' tags would import, and
+/// optionally import them
+pub fn cmd_import_ratings(
+    rt: &Runtime,
+    db_path: Option<&std::path::Path>,
+    under: Option<&std::path::Path>,
+    popm_email: &str,
+    apply: bool,
+) -> anyhow::Result<()> {
+    use crate::metadata::{self, ratings};
+
+    rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        let tracks: Vec<db::TrackRatings> = db::get_track_ratings(&pool)
+            .await?
+            .into_iter()
+            .filter(|t| under.is_none_or(|root| std::path::Path::new(&t.path).starts_with(root)))
+            .collect();
+
+        let stars = |rating: Option<i64>| match rating {
+            Some(r) => format!("{}/5", r as f64 / 20.0),
+            None => "unrated".to_string(),
+        };
+        let mut changed = 0;
+        let mut unreadable = 0;
+        let mut players: HashMap<String, usize> = HashMap::new();
+        for track in &tracks {
+            let Ok(tags) = metadata::read_for_index(std::path::Path::new(&track.path)) else {
+                unreadable += 1;
+                continue;
+            };
+            for frame in &tags.ratings.popm {
+                *players.entry(frame.email.clone()).or_default() += 1;
+            }
+            let imported = tags.ratings.resolve(popm_email);
+            let rating = imported
+                .rating
+                .map(i64::from)
+                .filter(|r| track.rating != Some(*r));
+            let plays = imported
+                .play_count
+                .map(i64::from)
+                .filter(|c| track.tag_play_count.is_none_or(|old| *c > old));
+            if rating.is_none() && plays.is_none() {
+                continue;
+            }
+
+            changed += 1;
+            let mut changes = Vec::new();
+            if let Some(rating) = rating {
+                changes.push(format!(
+                    "rating {} → {} (from {})",
+                    stars(track.rating),
+                    stars(Some(rating)),
+                    imported.source.as_deref().unwrap_or("tags")
+                ));
+            }
+            if let Some(plays) = plays {
+                changes.push(format!(
+                    "plays {} → {}",
+                    track.tag_play_count.unwrap_or(0),
+                    plays
+                ));
+            }
+            println!("{}\n      {}", track.path, changes.join(", "));
+            if apply {
+                db::update_track_ratings(&pool, track.id, &imported).await?;
+            }
+        }
+
+        if !players.is_empty() {
+            let mut players: Vec<_> = players.into_iter().collect();
+            players.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            println!("\nPOPM frames found (set one with --popm-email or library.popm_email):");
+            for (email, count) in players {
+                println!(
+                    "  {:>6}  {} ({:?})",
+                    count,
+                    ratings::player_name(&email),
+                    email
+                );
+            }
+        }
+        if unreadable > 0 {
+            println!("\n{} file(s) couldn't be read.", unreadable);
+        }
+        if apply {
+            println!(
+                "\nImported ratings and play counts for {} track(s).",
+                changed
+            );
+        } else {
+            println!(
+                "\n{} track(s) would change. Run with --apply to import them.",
+                changed
+            );
+        }
+        anyhow::Ok(())
+    })
 }
 
 /// List all tracks in the database
-pub fn cmd_list(rt: &Runtime) -> anyhow::Result<()> {
+pub fn cmd_list(rt: &Runtime, added_within: Option<u32>) -> anyhow::Result<()> {
     rt.block_on(async {
-        let db_url = "sqlite:music_minder.db";
-        let pool = db::init_db(db_url).await.expect("Failed to init DB");
+        let db_url = db::db_url(None);
+        let pool = db::init_db(&db_url).await?;
+        if let Some(days) = added_within {
+            let since = chrono::Utc::now().timestamp() - i64::from(days) * 86_400;
+            let tracks = db::get_tracks_added_since(&pool, since)
+                .await
+                .expect("Failed to get tracks");
+            for track in tracks {
+                let added = chrono::DateTime::from_timestamp(track.added_at.unwrap_or(0), 0)
+                    .map(|d| d.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default();
+                println!("{} {} - {}", added, track.title, track.path);
+            }
+            return anyhow::Ok(());
+        }
         let tracks = db::get_all_tracks(&pool)
             .await
             .expect("Failed to get tracks");
         for track in tracks {
             println!("{} - {}", track.title, track.path);
         }
+        anyhow::Ok(())
+    })
+}
+
+/// Add a stream or cloud drive URL to the library
+pub fn cmd_add_url(
+    rt: &Runtime,
+    url: &str,
+    title: Option<&str>,
+    artist: &str,
+    album: &str,
+    db_path: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    let title = title.map(str::to_string).unwrap_or_else(|| {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let name = path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(path);
+        urlencoding::decode(name)
+            .map(|name| name.into_owned())
+            .unwrap_or_else(|_| name.to_string())
     });
+    let meta = metadata::TrackMetadata {
+        title,
+        artist: artist.to_string(),
+        album: album.to_string(),
+        duration: 0,
+        track_number: None,
+    };
+    let id = rt.block_on(async {
+        let pool = db::init_db(&db::db_url(db_path)).await?;
+        anyhow::Ok(library::remote::add_remote(&pool, url, &meta).await?)
+    })?;
+    println!(
+        "Added {} track {}: {} ({})",
+        TrackSource::detect(url).as_str(),
+        id,
+        meta.title,
+        url
+    );
     Ok(())
 }
 
@@ -98,9 +386,16 @@ pub fn cmd_watch(
 
         // Start file watcher
         println!("Watching for changes in: {}", path.display());
+        let settle = crate::config::load().library.watch_settle();
+        if !settle.is_zero() {
+            println!(
+                "New files are imported once unchanged for {}s.",
+                settle.as_secs()
+            );
+        }
         println!("Press Ctrl+C to stop.\n");
 
-        let (mut watcher, rx) = match scanner::FileWatcher::new(vec![]) {
+        let (mut watcher, rx) = match scanner::FileWatcher::new(vec![], settle) {
             Ok(w) => w,
             Err(e) => {
                 eprintln!("Failed to create file watcher: {}", e);
@@ -209,10 +504,13 @@ async fn run_incremental_scan(pool: &sqlx::SqlitePool, root: &PathBuf, verbose:
         }
     };
 
-    // Build map of path -> (id, mtime)
-    let mut db_map: HashMap<String, (i64, Option<i64>)> = HashMap::new();
+    // Build map of path key -> (id, mtime, path)
+    let mut db_map: HashMap<String, (i64, Option<i64>, String)> = HashMap::new();
     for track in db_tracks {
-        db_map.insert(track.path.clone(), (track.id, track.mtime));
+        db_map.insert(
+            db::paths::key(&track.path),
+            (track.id, track.mtime, track.path),
+        );
     }
 
     debug!(target: "scanner::incremental", count = db_map.len(), "Loaded tracks from database");
@@ -230,8 +528,8 @@ async fn run_incremental_scan(pool: &sqlx::SqlitePool, root: &PathBuf, verbose:
         .filter(|e| is_audio_file(e.path()))
     {
         let path = entry.path();
-        let path_str = path.to_string_lossy().to_string();
-        seen_paths.insert(path_str.clone());
+        let path_key = db::paths::key(&path.to_string_lossy());
+        seen_paths.insert(path_key.clone());
 
         let fs_mtime = path
             .metadata()
@@ -240,7 +538,7 @@ async fn run_incremental_scan(pool: &sqlx::SqlitePool, root: &PathBuf, verbose:
             .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
             .map(|d| d.as_secs() as i64);
 
-        if let Some((id, db_mtime)) = db_map.get(&path_str) {
+        if let Some((id, db_mtime, _)) = db_map.get(&path_key) {
             // File exists in DB - check if modified
             if db_mtime.is_none() || fs_mtime != *db_mtime {
                 modified_files += 1;
@@ -266,8 +564,8 @@ async fn run_incremental_scan(pool: &sqlx::SqlitePool, root: &PathBuf, verbose:
 
     // Check for removed files
     let mut removed_files = 0;
-    for path_str in db_map.keys() {
-        if !seen_paths.contains(path_str) {
+    for (path_key, (_, _, path_str)) in &db_map {
+        if !seen_paths.contains(path_key) {
             removed_files += 1;
             if verbose {
                 println!("- REMOVED: {}", path_str);
@@ -0,0 +1,72 @@
+//! Credential encryption commands.
+
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::config;
+use crate::secrets::{self, Status};
+
+/// The passphrase from `MUSIC_MINDER_PASSPHRASE`, or typed in (without
+/// echo on a Unix terminal)
+fn passphrase(prompt: &str) -> anyhow::Result<String> {
+    if let Ok(passphrase) = std::env::var(secrets::PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    let stdin = std::io::stdin();
+    let hide = cfg!(unix) && stdin.is_terminal();
+    eprint!("{}: ", prompt);
+    std::io::stderr().flush()?;
+    if hide {
+        set_echo(false);
+    }
+    let mut line = String::new();
+    let read = stdin.lock().read_line(&mut line);
+    if hide {
+        set_echo(true);
+        eprintln!();
+    }
+    read?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn set_echo(on: bool) {
+    let _ = std::process::Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(std::process::Stdio::inherit())
+        .status();
+}
+
+/// Start encrypting the credentials
+pub fn cmd_secrets_encrypt() -> anyhow::Result<()> {
+    let new = passphrase("New passphrase")?;
+    if std::env::var(secrets::PASSPHRASE_ENV).is_err() && passphrase("Repeat it")? != new {
+        anyhow::bail!("The passphrases don't match");
+    }
+    secrets::encrypt(&new)?;
+    println!("Credentials are encrypted.");
+    println!(
+        "The app asks for the passphrase once per session; services can set {}.",
+        secrets::PASSPHRASE_ENV
+    );
+    Ok(())
+}
+
+/// Store the credentials in plain text again
+pub fn cmd_secrets_decrypt() -> anyhow::Result<()> {
+    if secrets::status(&config::load()) == Status::Locked {
+        secrets::unlock(&passphrase("Passphrase")?)?;
+    }
+    secrets::decrypt()?;
+    println!("Credentials are stored in plain text again.");
+    Ok(())
+}
+
+/// Show whether the credentials are encrypted
+pub fn cmd_secrets_status() -> anyhow::Result<()> {
+    let status = match secrets::status(&config::load()) {
+        Status::Plain => "not encrypted",
+        Status::Locked => "encrypted (locked)",
+        Status::Unlocked => "encrypted (unlocked)",
+    };
+    println!("Credentials: {}", status);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+//! Settings bundle commands.
+
+use std::path::Path;
+
+use crate::settings_bundle::{self, Section};
+
+/// Export settings to a bundle
+pub fn cmd_settings_export(
+    file: &Path,
+    only: &[Section],
+    with_credentials: bool,
+) -> anyhow::Result<()> {
+    let mut sections: Vec<Section> = if only.is_empty() {
+        Section::ALL
+            .into_iter()
+            .filter(|s| s.exported_by_default())
+            .collect()
+    } else {
+        only.to_vec()
+    };
+    if with_credentials && !sections.contains(&Section::Credentials) {
+        sections.push(Section::Credentials);
+    }
+    let bundle = settings_bundle::export(file, &sections)?;
+    println!("Exported to {}:", file.display());
+    for section in bundle.sections() {
+        println!("  {:<12} {}", section.as_str(), section.label());
+    }
+    Ok(())
+}
+
+/// Import settings from a bundle
+pub fn cmd_settings_import(file: &Path, only: &[Section], list: bool) -> anyhow::Result<()> {
+    let bundle = settings_bundle::read(file)?;
+    let present = bundle.sections();
+    if list {
+        println!(
+            "Exported {} by version {}:",
+            bundle.exported_at, bundle.app_version
+        );
+        for section in &present {
+            println!("  {:<12} {}", section.as_str(), section.label());
+        }
+        return Ok(());
+    }
+
+    let sections: Vec<Section> = if only.is_empty() {
+        present.clone()
+    } else {
+        if let Some(missing) = only.iter().find(|s| !present.contains(s)) {
+            anyhow::bail!("The bundle has no {} settings", missing.as_str());
+        }
+        only.to_vec()
+    };
+    settings_bundle::import(&bundle, &sections)?;
+    println!("Imported from {}:", file.display());
+    for section in &sections {
+        println!("  {:<12} {}", section.as_str(), section.label());
+    }
+    Ok(())
+}
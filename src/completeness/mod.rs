@@ -0,0 +1,725 @@
+//! Missing-from-album detection.
+//!
+//! An album is *identified* when its tracks carry a MusicBrainz release ID,
+//! either from a selected match in the review queue or from the files' own
+//! tags. For identified albums the release tracklist is fetched and compared
+//! with the tracks in the library, giving "you have 9 of 11 tracks of OK
+//! Computer" plus the titles of the missing ones. Results are stored in
+//! `album_completeness` so they can be shown per album and as a library-wide
+//! report without another lookup.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::completeness;
+//! use music_minder::enrichment::musicbrainz::MusicBrainzClient;
+//!
+//! let client = MusicBrainzClient::new();
+//! for album in completeness::library_albums(&pool).await? {
+//!     if let Some(result) = completeness::check_album(&pool, &client, album).await? {
+//!         println!("{}", result.summary());
+//!     }
+//! }
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[cfg(feature = "enrichment")]
+use crate::enrichment::traits::MusicBrainzApi;
+use crate::enrichment::{EnrichmentError, ReleaseTrack, ReleaseTracklist};
+
+/// Minimum title similarity for a track to count as a release track
+const TITLE_MATCH_THRESHOLD: f32 = 0.9;
+
+/// Completeness check errors
+#[derive(Debug, thiserror::Error)]
+pub enum CompletenessError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Release lookup failed: {0}")]
+    Lookup(#[from] EnrichmentError),
+}
+
+// ============================================================================
+// Albums in the Library
+// ============================================================================
+
+/// A library track, as far as matching it against a release goes.
+#[derive(Debug, Clone, Default)]
+pub struct OwnedTrack {
+    /// File path
+    pub path: String,
+    /// Track title
+    pub title: String,
+    /// Track number on the album
+    pub track_number: Option<u32>,
+    /// Disc number (from tags, if read)
+    pub disc_number: Option<u32>,
+    /// MusicBrainz recording ID
+    pub recording_id: Option<String>,
+}
+
+/// An album in the library with its tracks.
+#[derive(Debug, Clone, Default)]
+pub struct AlbumTracks {
+    /// Database album ID
+    pub album_id: i64,
+    /// Album title
+    pub album: String,
+    /// Album artist (or "Unknown Artist")
+    pub artist: String,
+    /// MusicBrainz release the album was identified as
+    pub release_id: Option<String>,
+    /// Tracks in the library
+    pub tracks: Vec<OwnedTrack>,
+}
+
+/// Database row for [`AlbumTracks`] queries, one per track.
+#[derive(Debug, sqlx::FromRow)]
+struct AlbumTrackRow {
+    album_id: i64,
+    album: String,
+    artist: String,
+    path: String,
+    title: String,
+    track_number: Option<i64>,
+    musicbrainz_recording_id: Option<String>,
+}
+
+/// Every album in the library, with release IDs from selected matches.
+///
+/// Albums whose release isn't known from the database can still be
+/// identified from file tags by [`check_album`].
+pub async fn library_albums(pool: &SqlitePool) -> sqlx::Result<Vec<AlbumTracks>> {
+    let rows: Vec<AlbumTrackRow> = sqlx::query_as(
+        r#"
+        SELECT
+            al.id AS album_id,
+            al.title AS album,
+            COALESCE(a.name, 'Unknown Artist') AS artist,
+            t.path, t.title, t.track_number, t.musicbrainz_recording_id
+        FROM tracks t
+        JOIN albums al ON t.album_id = al.id
+        LEFT JOIN artists a ON al.artist_id = a.id
+        ORDER BY artist, al.title, al.id, t.track_number, t.path
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let release_ids = selected_release_ids(pool, None).await?;
+    Ok(group_rows(rows, &release_ids))
+}
+
+/// The album a track belongs to, with its other tracks.
+pub async fn album_of_track(pool: &SqlitePool, track_id: i64) -> sqlx::Result<Option<AlbumTracks>> {
+    let rows: Vec<AlbumTrackRow> = sqlx::query_as(
+        r#"
+        SELECT
+            al.id AS album_id,
+            al.title AS album,
+            COALESCE(a.name, 'Unknown Artist') AS artist,
+            t.path, t.title, t.track_number, t.musicbrainz_recording_id
+        FROM tracks t
+        JOIN albums al ON t.album_id = al.id
+        LEFT JOIN artists a ON al.artist_id = a.id
+        WHERE t.album_id = (SELECT album_id FROM tracks WHERE id = ?)
+        ORDER BY t.track_number, t.path
+        "#,
+    )
+    .bind(track_id)
+    .fetch_all(pool)
+    .await?;
+
+    let Some(album_id) = rows.first().map(|r| r.album_id) else {
+        return Ok(None);
+    };
+    let release_ids = selected_release_ids(pool, Some(album_id)).await?;
+    Ok(group_rows(rows, &release_ids).pop())
+}
+
+/// The library album identified as MusicBrainz release `release_id`: by
+/// its selected matches, or else the release it was checked against.
+pub async fn album_of_release(
+    pool: &SqlitePool,
+    release_id: &str,
+) -> sqlx::Result<Option<AlbumTracks>> {
+    let matched = selected_release_ids(pool, None)
+        .await?
+        .into_iter()
+        .filter(|(_, id)| id == release_id)
+        .map(|(album_id, _)| album_id)
+        .min();
+    let album_id =
+        match matched {
+            Some(album_id) => Some(album_id),
+            None => sqlx::query_scalar(
+                "SELECT album_id FROM album_completeness WHERE release_id = ? ORDER BY album_id",
+            )
+            .bind(release_id)
+            .fetch_optional(pool)
+            .await?,
+        };
+    let Some(album_id) = album_id else {
+        return Ok(None);
+    };
+    let track_id: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM tracks WHERE album_id = ? ORDER BY id LIMIT 1")
+            .bind(album_id)
+            .fetch_optional(pool)
+            .await?;
+    match track_id {
+        Some(track_id) => album_of_track(pool, track_id).await,
+        None => Ok(None),
+    }
+}
+
+/// Most common release among each album's selected matches.
+///
+/// A preferred release wins over the others offered for the same match.
+async fn selected_release_ids(
+    pool: &SqlitePool,
+    album_id: Option<i64>,
+) -> sqlx::Result<HashMap<i64, String>> {
+    let rows: Vec<(i64, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT t.album_id, mr.release_id, COUNT(*) AS n
+        FROM match_releases mr
+        JOIN track_matches tm ON mr.match_id = tm.id
+        JOIN tracks t ON tm.track_id = t.id
+        WHERE tm.is_selected
+          AND t.album_id IS NOT NULL
+          AND (? IS NULL OR t.album_id = ?)
+          AND (mr.is_preferred OR NOT EXISTS (
+              SELECT 1 FROM match_releases p WHERE p.match_id = mr.match_id AND p.is_preferred
+          ))
+        GROUP BY t.album_id, mr.release_id
+        ORDER BY t.album_id, n DESC, mr.release_id
+        "#,
+    )
+    .bind(album_id)
+    .bind(album_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut ids = HashMap::new();
+    for (album_id, release_id, _) in rows {
+        ids.entry(album_id).or_insert(release_id);
+    }
+    Ok(ids)
+}
+
+fn group_rows(rows: Vec<AlbumTrackRow>, release_ids: &HashMap<i64, String>) -> Vec<AlbumTracks> {
+    let mut albums: Vec<AlbumTracks> = Vec::new();
+    for row in rows {
+        if albums.last().is_none_or(|a| a.album_id != row.album_id) {
+            albums.push(AlbumTracks {
+                album_id: row.album_id,
+                album: row.album,
+                artist: row.artist,
+                release_id: release_ids.get(&row.album_id).cloned(),
+                tracks: Vec::new(),
+            });
+        }
+        if let Some(album) = albums.last_mut() {
+            album.tracks.push(OwnedTrack {
+                path: row.path,
+                title: row.title,
+                track_number: row.track_number.and_then(|n| u32::try_from(n).ok()),
+                disc_number: None,
+                recording_id: row.musicbrainz_recording_id,
+            });
+        }
+    }
+    albums
+}
+
+/// Fill in disc numbers, recording IDs and (if still unknown) the release ID
+/// from the files' tags. Blocking; unreadable files are skipped.
+pub fn read_tag_ids(album: &mut AlbumTracks) {
+    let mut tagged_releases: HashMap<String, usize> = HashMap::new();
+    for track in &mut album.tracks {
+        let Ok(meta) = crate::metadata::read_full(Path::new(&track.path)) else {
+            continue;
+        };
+        track.disc_number = track.disc_number.or(meta.disc_number);
+        if track.recording_id.is_none() {
+            track.recording_id = meta.musicbrainz_recording_id;
+        }
+        if let Some(release_id) = meta.musicbrainz_release_id {
+            *tagged_releases.entry(release_id).or_default() += 1;
+        }
+    }
+    if album.release_id.is_none() {
+        album.release_id = tagged_releases
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+            .map(|(id, _)| id);
+    }
+}
+
+// ============================================================================
+// Comparison
+// ============================================================================
+
+/// A release track that isn't in the library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingTrack {
+    /// Disc number
+    pub disc: u32,
+    /// Position on the disc
+    pub position: u32,
+    /// Track title
+    pub title: String,
+}
+
+/// How much of an identified album is in the library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumCompleteness {
+    /// Database album ID
+    pub album_id: i64,
+    /// Album title
+    pub album: String,
+    /// Album artist
+    pub artist: String,
+    /// MusicBrainz release compared against
+    pub release_id: String,
+    /// Release title
+    pub release_title: String,
+    /// Tracks on the release
+    pub total_tracks: u32,
+    /// Release tracks found in the library
+    pub owned_tracks: u32,
+    /// Release tracks not found, in release order
+    pub missing: Vec<MissingTrack>,
+    /// When the comparison was made
+    pub checked_at: DateTime<Utc>,
+}
+
+impl AlbumCompleteness {
+    /// Whether every release track is in the library
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// "You have 9 of 11 tracks of OK Computer"
+    pub fn summary(&self) -> String {
+        format!(
+            "You have {} of {} tracks of {}",
+            self.owned_tracks, self.total_tracks, self.album
+        )
+    }
+
+    /// Whether any missing track is on a disc other than the first
+    pub fn multi_disc(&self) -> bool {
+        self.missing.iter().any(|t| t.disc != 1)
+    }
+}
+
+/// Compare an album's tracks with a release tracklist.
+///
+/// Each library track can account for one release track, matched by recording
+/// ID first, then by title, then by position (track number plus disc number
+/// where that's known or the release has a single disc). Library tracks that
+/// aren't on the release, such as bonus tracks, are ignored.
+pub fn compare(album: &AlbumTracks, tracklist: &ReleaseTracklist) -> AlbumCompleteness {
+    let single_disc = tracklist.disc_count() <= 1;
+    let mut unclaimed: Vec<&OwnedTrack> = album.tracks.iter().collect();
+    let mut found = vec![false; tracklist.tracks.len()];
+
+    let mut claim = |matches: &dyn Fn(&ReleaseTrack, &OwnedTrack) -> bool| {
+        for (i, release_track) in tracklist.tracks.iter().enumerate() {
+            if found[i] {
+                continue;
+            }
+            if let Some(pos) = unclaimed.iter().position(|t| matches(release_track, t)) {
+                unclaimed.swap_remove(pos);
+                found[i] = true;
+            }
+        }
+    };
+
+    claim(&|r, t| {
+        r.recording_id.is_some() && t.recording_id.as_deref() == r.recording_id.as_deref()
+    });
+    claim(&|r, t| crate::health::string_similarity(&r.title, &t.title) >= TITLE_MATCH_THRESHOLD);
+    claim(&|r, t| {
+        t.track_number == Some(r.position)
+            && match t.disc_number {
+                Some(disc) => disc == r.disc,
+                None => single_disc,
+            }
+    });
+
+    let missing: Vec<MissingTrack> = tracklist
+        .tracks
+        .iter()
+        .zip(&found)
+        .filter(|(_, found)| !**found)
+        .map(|(t, _)| MissingTrack {
+            disc: t.disc,
+            position: t.position,
+            title: t.title.clone(),
+        })
+        .collect();
+
+    AlbumCompleteness {
+        album_id: album.album_id,
+        album: album.album.clone(),
+        artist: album.artist.clone(),
+        release_id: tracklist.release_id.clone(),
+        release_title: tracklist.title.clone(),
+        total_tracks: tracklist.tracks.len() as u32,
+        owned_tracks: (tracklist.tracks.len() - missing.len()) as u32,
+        missing,
+        checked_at: Utc::now(),
+    }
+}
+
+/// Fetch the album's release tracklist, compare, and store the result.
+///
+/// Reads tags to find the release if the database doesn't know it. Returns
+/// `None` for albums that aren't identified. Callers checking many albums
+/// must pace the calls to MusicBrainz's rate limit (1 request/second).
+#[cfg(feature = "enrichment")]
+pub async fn check_album<M: MusicBrainzApi>(
+    pool: &SqlitePool,
+    api: &M,
+    album: AlbumTracks,
+) -> Result<Option<AlbumCompleteness>, CompletenessError> {
+    let album = tokio::task::spawn_blocking(move || {
+        let mut album = album;
+        read_tag_ids(&mut album);
+        album
+    })
+    .await
+    .map_err(|e| EnrichmentError::InvalidResponse(e.to_string()))?;
+
+    let Some(release_id) = album.release_id.as_deref() else {
+        return Ok(None);
+    };
+    let tracklist = api.lookup_release(release_id).await?;
+    let result = compare(&album, &tracklist);
+    save(pool, &result).await?;
+    Ok(Some(result))
+}
+
+// ============================================================================
+// Storage
+// ============================================================================
+
+/// Database row for `album_completeness` joined with the album.
+#[derive(Debug, sqlx::FromRow)]
+struct CompletenessRow {
+    album_id: i64,
+    album: String,
+    artist: String,
+    release_id: String,
+    release_title: String,
+    total_tracks: i64,
+    owned_tracks: i64,
+    missing_tracks: String,
+    checked_at: i64,
+}
+
+impl From<CompletenessRow> for AlbumCompleteness {
+    fn from(row: CompletenessRow) -> Self {
+        Self {
+            album_id: row.album_id,
+            album: row.album,
+            artist: row.artist,
+            release_id: row.release_id,
+            release_title: row.release_title,
+            total_tracks: row.total_tracks as u32,
+            owned_tracks: row.owned_tracks as u32,
+            missing: serde_json::from_str(&row.missing_tracks).unwrap_or_default(),
+            checked_at: DateTime::from_timestamp(row.checked_at, 0).unwrap_or_default(),
+        }
+    }
+}
+
+const SELECT_COMPLETENESS: &str = r#"
+    SELECT
+        c.album_id,
+        al.title AS album,
+        COALESCE(a.name, 'Unknown Artist') AS artist,
+        c.release_id, c.release_title, c.total_tracks, c.owned_tracks,
+        c.missing_tracks, c.checked_at
+    FROM album_completeness c
+    JOIN albums al ON c.album_id = al.id
+    LEFT JOIN artists a ON al.artist_id = a.id
+"#;
+
+/// Store (or replace) an album's completeness.
+pub async fn save(pool: &SqlitePool, result: &AlbumCompleteness) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Storing album completeness")?;
+    let missing =
+        serde_json::to_string(&result.missing).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    sqlx::query(
+        r#"INSERT INTO album_completeness
+           (album_id, release_id, release_title, total_tracks, owned_tracks,
+            missing_tracks, checked_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?)
+           ON CONFLICT(album_id) DO UPDATE SET
+             release_id = excluded.release_id,
+             release_title = excluded.release_title,
+             total_tracks = excluded.total_tracks,
+             owned_tracks = excluded.owned_tracks,
+             missing_tracks = excluded.missing_tracks,
+             checked_at = excluded.checked_at"#,
+    )
+    .bind(result.album_id)
+    .bind(&result.release_id)
+    .bind(&result.release_title)
+    .bind(i64::from(result.total_tracks))
+    .bind(i64::from(result.owned_tracks))
+    .bind(missing)
+    .bind(result.checked_at.timestamp())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Stored completeness of the album a track belongs to.
+pub async fn for_track(
+    pool: &SqlitePool,
+    track_id: i64,
+) -> sqlx::Result<Option<AlbumCompleteness>> {
+    let sql = format!(
+        "{} WHERE c.album_id = (SELECT album_id FROM tracks WHERE id = ?)",
+        SELECT_COMPLETENESS
+    );
+    let row: Option<CompletenessRow> = sqlx::query_as(&sql)
+        .bind(track_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.map(Into::into))
+}
+
+/// Library-wide report: every checked album, or only incomplete ones,
+/// by artist then album.
+pub async fn report(
+    pool: &SqlitePool,
+    include_complete: bool,
+) -> sqlx::Result<Vec<AlbumCompleteness>> {
+    let sql = format!(
+        "{} WHERE ? OR c.owned_tracks < c.total_tracks ORDER BY artist, album",
+        SELECT_COMPLETENESS
+    );
+    let rows: Vec<CompletenessRow> = sqlx::query_as(&sql)
+        .bind(include_complete)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(Into::into).collect())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "enrichment")]
+    use crate::{db, enrichment::traits::mocks::MockMusicBrainz, metadata::TrackMetadata};
+
+    #[cfg(feature = "enrichment")]
+    async fn test_pool(dir: &Path) -> SqlitePool {
+        let db_url = format!("sqlite:{}", dir.join("test.db").display());
+        db::init_db(&db_url).await.unwrap()
+    }
+
+    fn owned(title: &str, number: Option<u32>, recording: Option<&str>) -> OwnedTrack {
+        OwnedTrack {
+            path: format!("/m/{}.mp3", title),
+            title: title.to_string(),
+            track_number: number,
+            disc_number: None,
+            recording_id: recording.map(String::from),
+        }
+    }
+
+    fn tracklist(tracks: &[(u32, u32, &str, Option<&str>)]) -> ReleaseTracklist {
+        ReleaseTracklist {
+            release_id: "rel-okc".to_string(),
+            title: "OK Computer".to_string(),
+            tracks: tracks
+                .iter()
+                .map(|&(disc, position, title, recording)| ReleaseTrack {
+                    disc,
+                    position,
+                    title: title.to_string(),
+                    recording_id: recording.map(String::from),
+                    duration: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn album(tracks: Vec<OwnedTrack>) -> AlbumTracks {
+        AlbumTracks {
+            album_id: 1,
+            album: "OK Computer".to_string(),
+            artist: "Radiohead".to_string(),
+            release_id: Some("rel-okc".to_string()),
+            tracks,
+        }
+    }
+
+    fn missing_titles(result: &AlbumCompleteness) -> Vec<&str> {
+        result.missing.iter().map(|t| t.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_compare_by_recording_title_and_position() {
+        let release = tracklist(&[
+            (1, 1, "Airbag", Some("rec-1")),
+            (1, 2, "Paranoid Android", Some("rec-2")),
+            (1, 3, "Subterranean Homesick Alien", Some("rec-3")),
+            (1, 4, "Exit Music (For a Film)", Some("rec-4")),
+        ]);
+        let result = compare(
+            &album(vec![
+                // Recording ID wins even with a mistyped title
+                owned("Airbg", Some(1), Some("rec-1")),
+                // Title match despite a wrong number
+                owned("paranoid android", Some(9), None),
+                // Position match with an unrecognisable title
+                owned("Track 04", Some(4), None),
+                // Not on the release at all
+                owned("Bonus Demo", None, None),
+            ]),
+            &release,
+        );
+
+        assert_eq!(result.total_tracks, 4);
+        assert_eq!(result.owned_tracks, 3);
+        assert_eq!(missing_titles(&result), ["Subterranean Homesick Alien"]);
+        assert_eq!(result.summary(), "You have 3 of 4 tracks of OK Computer");
+        assert!(!result.is_complete());
+    }
+
+    #[test]
+    fn test_compare_each_track_counts_once() {
+        let release = tracklist(&[(1, 1, "Intro", None), (1, 2, "Intro", None)]);
+        let result = compare(&album(vec![owned("Intro", None, None)]), &release);
+        assert_eq!(result.owned_tracks, 1);
+        assert_eq!(result.missing[0].position, 2);
+    }
+
+    #[test]
+    fn test_compare_multi_disc_positions_need_disc() {
+        let release = tracklist(&[(1, 1, "One", None), (2, 1, "Two", None)]);
+        let mut on_disc_two = owned("Track 1", Some(1), None);
+        on_disc_two.disc_number = Some(2);
+
+        let result = compare(
+            &album(vec![owned("Unknown", Some(1), None), on_disc_two]),
+            &release,
+        );
+
+        // The track without a disc number can't be placed on a two-disc release
+        assert_eq!(result.owned_tracks, 1);
+        assert_eq!(missing_titles(&result), ["One"]);
+        assert!(!result.multi_disc());
+    }
+
+    #[cfg(feature = "enrichment")]
+    #[tokio::test]
+    async fn test_check_album_stores_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+        let artist_id = db::get_or_create_artist(&pool, "Radiohead").await.unwrap();
+        let album_id = db::get_or_create_album(&pool, "OK Computer", Some(artist_id))
+            .await
+            .unwrap();
+        let track_id = db::insert_track(
+            &pool,
+            &TrackMetadata {
+                title: "Airbag".to_string(),
+                artist: "Radiohead".to_string(),
+                album: "OK Computer".to_string(),
+                duration: 284,
+                track_number: Some(1),
+            },
+            "/m/airbag.mp3",
+            Some(artist_id),
+            Some(album_id),
+        )
+        .await
+        .unwrap();
+
+        // Not identified yet: nothing to check
+        let albums = library_albums(&pool).await.unwrap();
+        assert_eq!(albums.len(), 1);
+        assert_eq!(albums[0].release_id, None);
+        let api = MockMusicBrainz::with_tracklist(tracklist(&[
+            (1, 1, "Airbag", None),
+            (1, 2, "Paranoid Android", None),
+        ]));
+        let unidentified = check_album(&pool, &api, albums[0].clone()).await.unwrap();
+        assert_eq!(unidentified, None);
+
+        // A selected match identifies the release
+        let match_id = db::upsert_track_match(
+            &pool,
+            track_id,
+            "acoustid",
+            0.95,
+            Some("rec-1"),
+            "Airbag",
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        db::upsert_match_release(
+            &pool,
+            match_id,
+            "rel-okc",
+            "OK Computer",
+            None,
+            Some(1997),
+            None,
+            Some(1),
+            true,
+            false,
+        )
+        .await
+        .unwrap();
+        db::select_track_match(&pool, match_id).await.unwrap();
+
+        let album = album_of_track(&pool, track_id).await.unwrap().unwrap();
+        assert_eq!(album.release_id.as_deref(), Some("rel-okc"));
+        let by_release = album_of_release(&pool, "rel-okc").await.unwrap().unwrap();
+        assert_eq!(by_release.album_id, album_id);
+        assert!(
+            album_of_release(&pool, "rel-other")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let result = check_album(&pool, &api, album).await.unwrap().unwrap();
+        assert_eq!(result.summary(), "You have 1 of 2 tracks of OK Computer");
+
+        let stored = for_track(&pool, track_id).await.unwrap().unwrap();
+        assert_eq!(stored.artist, "Radiohead");
+        assert_eq!(stored.missing, result.missing);
+        assert_eq!(report(&pool, false).await.unwrap().len(), 1);
+        assert_eq!(report(&pool, true).await.unwrap().len(), 1);
+
+        // Filling the gap makes it complete, and drops it from the default report
+        let mut complete = stored.clone();
+        complete.owned_tracks = 2;
+        complete.missing.clear();
+        save(&pool, &complete).await.unwrap();
+        assert!(report(&pool, false).await.unwrap().is_empty());
+        assert!(report(&pool, true).await.unwrap()[0].is_complete());
+    }
+}
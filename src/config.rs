@@ -5,19 +5,30 @@
 //! - macOS: ~/Library/Application Support/music-minder/config.toml
 //! - Linux: ~/.config/music-minder/config.toml
 //!
+//! Profiles other than the default keep their own config file under
+//! `profiles/<name>/` in the same directory (see [`crate::profile`]).
+//!
 //! The config file is human-readable and editable. Settings are
-//! loaded at startup and saved when changed through the UI.
+//! loaded at startup and saved when changed through the UI. The window
+//! also picks up edits made to the file while it runs (by hand, by the CLI
+//! or by another instance): [`read_for_reload`] reads it again and
+//! [`changed_settings`] lists what differs, some of which only takes
+//! effect after a restart ([`needs_restart`]).
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Application configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
-    /// API credentials (keep separate for potential future encryption)
+    /// API credentials (empty on disk while they're encrypted)
     pub credentials: Credentials,
 
+    /// The credentials sealed with a passphrase, see [`crate::secrets`]
+    pub secrets: SecretsConfig,
+
     /// Appearance settings
     pub appearance: AppearanceConfig,
 
@@ -26,16 +37,48 @@ pub struct Config {
 
     /// Library settings
     pub library: LibraryConfig,
+
+    /// Tag writing settings
+    pub tagging: TaggingConfig,
+
+    /// Recently used input values
+    pub history: InputHistory,
+
+    /// Background maintenance jobs
+    pub scheduler: SchedulerConfig,
+
+    /// Headless agent (`music-minder agent`)
+    pub agent: AgentConfig,
+
+    /// CPU use of fingerprinting
+    pub analysis: AnalysisConfig,
+
+    /// Kodi/Jellyfin sidecar files
+    pub nfo: NfoConfig,
+
+    /// Where fetched album covers go
+    pub covers: CoversConfig,
+
+    /// Update checks and other outgoing requests
+    pub network: NetworkConfig,
 }
 
 /// API credentials
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Credentials {
     /// AcoustID API key for fingerprint lookups
     pub acoustid_api_key: Option<String>,
 }
 
+/// Passphrase encryption of the credentials
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecretsConfig {
+    /// The sealed credentials; `None` keeps them in plain text
+    pub encrypted: Option<String>,
+}
+
 /// Appearance/theme settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -45,6 +88,12 @@ pub struct AppearanceConfig {
 
     /// Whether the sidebar is collapsed
     pub sidebar_collapsed: bool,
+
+    /// Start in the compact mini-player window
+    pub mini_player: bool,
+
+    /// Keep the mini-player above other windows
+    pub mini_player_always_on_top: bool,
 }
 
 impl Default for AppearanceConfig {
@@ -52,6 +101,8 @@ impl Default for AppearanceConfig {
         Self {
             theme: "dark".to_string(),
             sidebar_collapsed: false,
+            mini_player: false,
+            mini_player_always_on_top: true,
         }
     }
 }
@@ -68,6 +119,43 @@ pub struct AudioConfig {
 
     /// Last volume level (0.0 - 1.0)
     pub volume: f32,
+
+    /// Skip long silence at the start and end of tracks
+    pub trim_silence: bool,
+
+    /// How much audio to decode ahead, in ms (0 = tune automatically)
+    pub buffer_ms: u32,
+
+    /// Fade on pause, stop and seek, in ms (0 = instant)
+    pub fade_ms: u32,
+
+    /// Crossfade between tracks, in seconds (0 = off, up to 12)
+    pub crossfade_secs: u32,
+
+    /// Loudness normalization during playback: "off", "track" or "album"
+    pub normalization: crate::player::normalization::NormalizationMode,
+
+    /// Gain added to the normalization gain, in dB (-12 to +12)
+    pub preamp_db: f32,
+
+    /// Run the equalizer
+    pub eq_enabled: bool,
+
+    /// Equalizer preset the gains came from ("custom" once edited)
+    pub eq_preset: crate::player::equalizer::EqPreset,
+
+    /// Equalizer band gains in dB (-12 to +12), 31 Hz to 16 kHz
+    pub eq_gains_db: crate::player::equalizer::EqGains,
+
+    /// What plays once the queue runs out
+    pub end_of_queue: crate::player::EndOfQueue,
+
+    /// How far the keyboard and mouse wheel move the volume, in dB
+    pub volume_step_db: f32,
+
+    /// Also listen for media keys directly, for sessions where the OS
+    /// media controls never deliver them (needs the `media-keys` feature)
+    pub media_key_fallback: bool,
 }
 
 impl Default for AudioConfig {
@@ -76,6 +164,18 @@ impl Default for AudioConfig {
             output_device: String::new(),
             visualization_mode: "spectrum".to_string(),
             volume: 1.0,
+            trim_silence: false,
+            buffer_ms: crate::player::buffering::AUTO,
+            fade_ms: crate::player::fade::DEFAULT_FADE_MS,
+            crossfade_secs: crate::player::crossfade::DEFAULT_CROSSFADE_SECS,
+            normalization: crate::player::normalization::NormalizationMode::Off,
+            preamp_db: crate::player::normalization::DEFAULT_PREAMP_DB,
+            eq_enabled: false,
+            eq_preset: crate::player::equalizer::EqPreset::Flat,
+            eq_gains_db: [0.0; crate::player::equalizer::BANDS],
+            end_of_queue: crate::player::EndOfQueue::Stop,
+            volume_step_db: crate::player::volume::DEFAULT_STEP_DB,
+            media_key_fallback: false,
         }
     }
 }
@@ -93,8 +193,44 @@ pub struct LibraryConfig {
     /// Whether to watch for file changes
     pub watch_for_changes: bool,
 
+    /// Library paths the file watcher leaves alone (still scanned by hand
+    /// and by scheduled jobs)
+    pub unwatched_paths: Vec<PathBuf>,
+
     /// Auto-queue tracks from same album when starting playback
     pub auto_queue: bool,
+
+    /// Refuse every operation that modifies files or stored results
+    /// (see [`crate::readonly`])
+    pub read_only: bool,
+
+    /// Confidence (0.0-1.0) needed for a scanned folder of one album by many
+    /// artists to be grouped under "Various Artists"; above 1.0 disables it
+    pub compilation_threshold: f32,
+
+    /// Player whose POPM frame ratings and play counts are imported from
+    /// (its frame email, e.g. "MusicBee"); empty takes the highest of any
+    pub popm_email: String,
+
+    /// Seconds a new or changed file must keep the same size and mtime
+    /// before the watcher imports it (0 = right away)
+    pub watch_settle_secs: u64,
+
+    /// Path prefixes stored as another, e.g. `"\\\\NAS\\music" = "M:\\"`
+    /// for a share mapped to a drive letter, so both spellings are one track
+    /// (see [`crate::db::paths`])
+    pub path_aliases: BTreeMap<String, String>,
+
+    /// Store tracks reached through a symlink under their real location
+    pub resolve_symlinks: bool,
+
+    /// Match track paths ignoring case for every path, not only Windows ones
+    /// (the default on macOS)
+    pub case_insensitive_paths: bool,
+
+    /// Size limit of the downloaded cover art cache in MB (0 = unlimited);
+    /// past it the least recently shown covers are dropped
+    pub cover_cache_mb: u64,
 }
 
 impl Default for LibraryConfig {
@@ -103,23 +239,316 @@ impl Default for LibraryConfig {
             paths: Vec::new(),
             last_scan_path: None,
             watch_for_changes: true,
+            unwatched_paths: Vec::new(),
             auto_queue: true,
+            read_only: false,
+            compilation_threshold: crate::library::DEFAULT_COMPILATION_THRESHOLD,
+            popm_email: String::new(),
+            watch_settle_secs: 5,
+            path_aliases: BTreeMap::new(),
+            resolve_symlinks: false,
+            case_insensitive_paths: cfg!(target_os = "macos"),
+            cover_cache_mb: crate::cover::DEFAULT_CACHE_LIMIT_MB,
+        }
+    }
+}
+
+impl LibraryConfig {
+    /// How long the watcher holds a changed file before importing it
+    pub fn watch_settle(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.watch_settle_secs)
+    }
+}
+
+/// Tag writing settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaggingConfig {
+    /// Extra tag values to treat as empty in "fill missing only" mode,
+    /// on top of the built-in placeholders ("Unknown Artist", "Track 01", ...)
+    pub extra_placeholders: Vec<String>,
+
+    /// Whether re-enrichment may overwrite fields you set by hand, per field
+    /// name ("album", "genre", ...); unlisted fields are protected
+    pub manual_edits: crate::provenance::ManualEditRules,
+
+    /// Genre spellings written and stored as another, e.g.
+    /// `"hip-hop" = "Hip Hop"`; matched ignoring case, spaces and
+    /// punctuation (see [`crate::metadata::genres::GenreMap`])
+    pub genre_map: BTreeMap<String, String>,
+
+    /// Write tags put off while a file was in use as soon as it's let go
+    /// of, rather than at the next scheduled retry (see
+    /// [`crate::write_queue`])
+    pub watch_locked_files: bool,
+
+    /// ID3 version, text encoding and padding tags are saved with
+    /// (see [`crate::metadata::strategy`])
+    pub write: crate::metadata::WriteStrategy,
+}
+
+/// Background maintenance settings (see [`crate::scheduler`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// Run jobs at all while the app is open
+    pub enabled: bool,
+
+    /// Minutes without input before the app counts as idle (`@idle` jobs)
+    pub idle_minutes: u32,
+
+    /// Database backups to keep; older ones are deleted
+    pub backups_kept: usize,
+
+    /// Incremental scan of the library folders
+    pub scan: JobConfig,
+
+    /// Quality re-check of tracks not assessed in a while
+    pub gardener: JobConfig,
+
+    /// Database backup
+    pub backup: JobConfig,
+
+    /// Database integrity and missing-file check
+    pub verify: JobConfig,
+
+    /// Silence analysis of new tracks
+    pub silence: JobConfig,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_minutes: 10,
+            backups_kept: 4,
+            scan: JobConfig::default(),
+            gardener: JobConfig::default(),
+            backup: JobConfig::default(),
+            verify: JobConfig::default(),
+            silence: JobConfig::default(),
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Settings of one job
+    pub fn job(&self, job: crate::scheduler::Job) -> &JobConfig {
+        use crate::scheduler::Job;
+        match job {
+            Job::Scan => &self.scan,
+            Job::Gardener => &self.gardener,
+            Job::Backup => &self.backup,
+            Job::Verify => &self.verify,
+            Job::Silence => &self.silence,
+        }
+    }
+
+    /// Mutable settings of one job
+    pub fn job_mut(&mut self, job: crate::scheduler::Job) -> &mut JobConfig {
+        use crate::scheduler::Job;
+        match job {
+            Job::Scan => &mut self.scan,
+            Job::Gardener => &mut self.gardener,
+            Job::Backup => &mut self.backup,
+            Job::Verify => &mut self.verify,
+            Job::Silence => &mut self.silence,
+        }
+    }
+}
+
+/// One scheduled job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobConfig {
+    /// Whether the job runs on its schedule
+    pub enabled: bool,
+
+    /// Cron rule or shortcut (see [`crate::scheduler::Schedule`]);
+    /// unset means the job's built-in default
+    pub schedule: Option<String>,
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            schedule: None,
+        }
+    }
+}
+
+/// Headless agent settings (see [`crate::agent`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Address the JSON API listens on
+    pub listen: String,
+
+    /// Token API clients must send as `Authorization: Bearer <token>`;
+    /// unset allows anyone who can reach `listen`, except web pages
+    pub api_token: Option<String>,
+
+    /// Identify newly added tracks (needs an AcoustID key); matches are
+    /// stored for review, tags aren't written
+    pub enrich_new_tracks: bool,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:7431".to_string(),
+            api_token: None,
+            enrich_new_tracks: true,
+        }
+    }
+}
+
+/// CPU budget for fingerprinting (see [`crate::enrichment::budget`])
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalysisConfig {
+    /// Most fingerprints computed at once; 0 uses half the CPU cores
+    pub max_threads: usize,
+
+    /// Hold new fingerprints back while the laptop runs on battery
+    pub pause_on_battery: bool,
+
+    /// Hold new fingerprints back while music is playing
+    pub pause_while_playing: bool,
+}
+
+/// Sidecar files for media centers (see [`crate::nfo`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NfoConfig {
+    /// Write them for the albums just organized
+    pub after_organize: bool,
+
+    /// Write the album cover as `folder.jpg`
+    pub folder_art: bool,
+
+    /// Replace files that already exist (off keeps hand-edited ones)
+    pub overwrite: bool,
+
+    /// `album.nfo` contents: {Album}, {Artist}, {Year}, {Genre}, {ReleaseId},
+    /// {ReleaseGroupId}, {ArtistId} and {Tracks}
+    pub album_template: String,
+
+    /// Each track in {Tracks}: {Position}, {Title}, {Duration} (m:ss) and
+    /// {Seconds}
+    pub track_template: String,
+
+    /// `artist.nfo` contents: {Artist}, {ArtistId} and {Albums}
+    pub artist_template: String,
+
+    /// Each album in {Albums}: {Album} and {Year}
+    pub artist_album_template: String,
+}
+
+impl Default for NfoConfig {
+    fn default() -> Self {
+        Self {
+            after_organize: false,
+            folder_art: true,
+            overwrite: false,
+            album_template: crate::nfo::DEFAULT_ALBUM_TEMPLATE.to_string(),
+            track_template: crate::nfo::DEFAULT_TRACK_TEMPLATE.to_string(),
+            artist_template: crate::nfo::DEFAULT_ARTIST_TEMPLATE.to_string(),
+            artist_album_template: crate::nfo::DEFAULT_ARTIST_ALBUM_TEMPLATE.to_string(),
         }
     }
 }
 
+/// Album covers fetched in a batch (see [`crate::cover::fetch_album_covers`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoversConfig {
+    /// Write the cover as `folder.jpg` (or `.png`) in the album's folder
+    pub folder_file: bool,
+
+    /// Embed the cover in each of the album's tracks
+    pub embed: bool,
+}
+
+impl Default for CoversConfig {
+    fn default() -> Self {
+        Self {
+            folder_file: true,
+            embed: false,
+        }
+    }
+}
+
+/// Outgoing requests (see [`crate::updates`] and [`crate::enrichment::http`])
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Never contact anything outside this machine: no identification,
+    /// cover art, AccurateRip or update checks
+    pub offline: bool,
+
+    /// Look for a newer release on GitHub at startup
+    pub check_for_updates: bool,
+}
+
+/// Entries kept per input history
+pub const HISTORY_LEN: usize = 8;
+
+/// Recently used values of the scan and organize inputs, most recent first.
+///
+/// The first entry of each list is what the input starts with next time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputHistory {
+    /// Folders scanned
+    pub scan_paths: Vec<PathBuf>,
+
+    /// Organize destination folders
+    pub organize_destinations: Vec<PathBuf>,
+
+    /// Organize naming patterns
+    pub organize_patterns: Vec<String>,
+}
+
+/// Move `value` to the front of a history list, dropping duplicates and
+/// anything past [`HISTORY_LEN`]. Empty values aren't recorded.
+pub fn remember<T: PartialEq + AsRef<std::ffi::OsStr>>(list: &mut Vec<T>, value: T) {
+    if value.as_ref().is_empty() {
+        return;
+    }
+    list.retain(|v| *v != value);
+    list.insert(0, value);
+    list.truncate(HISTORY_LEN);
+}
+
 // ============================================================================
 // Config File Operations
 // ============================================================================
 
+/// Config directory set with `--config-dir`, overriding the OS default
+static CONFIG_DIR: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+
+/// Use `dir` instead of the OS config directory for the rest of this process.
+///
+/// Lets a service running as another account (LocalSystem on Windows) use
+/// the settings and profiles of the user who installed it. Only the first
+/// call has an effect.
+pub fn set_config_dir(dir: PathBuf) {
+    let _ = CONFIG_DIR.set(dir);
+}
+
 /// Get the config directory path
 pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = CONFIG_DIR.get() {
+        return Some(dir.clone());
+    }
     dirs::config_dir().map(|d| d.join("music-minder"))
 }
 
-/// Get the full path to the config file
+/// Get the full path to the active profile's config file
 pub fn config_path() -> Option<PathBuf> {
-    config_dir().map(|d| d.join("config.toml"))
+    crate::profile::config_path()
 }
 
 /// Load configuration from disk
@@ -138,7 +567,7 @@ pub fn load() -> Config {
     }
 
     match std::fs::read_to_string(&path) {
-        Ok(contents) => match toml::from_str(&contents) {
+        Ok(contents) => match parse(&contents) {
             Ok(config) => {
                 tracing::info!("Loaded config from {:?}", path);
                 config
@@ -156,12 +585,28 @@ pub fn load() -> Config {
     }
 }
 
+/// Parse a config file's contents, opening its encrypted credentials if
+/// they're unlocked
+fn parse(contents: &str) -> Result<Config, toml::de::Error> {
+    let mut config = toml::from_str(contents)?;
+    crate::secrets::open_loaded(&mut config);
+    Ok(config)
+}
+
 /// Save configuration to disk
 ///
-/// Creates the config directory if it doesn't exist.
+/// Creates the config directory if it doesn't exist. Encrypted credentials
+/// are sealed again; while they're locked, the sealed copy is kept and
+/// saving new ones fails.
 pub fn save(config: &Config) -> Result<(), ConfigError> {
-    let dir = config_dir().ok_or(ConfigError::NoConfigDir)?;
-    let path = dir.join("config.toml");
+    let sealed = crate::secrets::for_disk(config).map_err(|e| match e {
+        crate::secrets::SecretsError::Locked => ConfigError::CredentialsLocked,
+        e => ConfigError::Secrets(e.to_string()),
+    })?;
+    let config = sealed.as_ref().unwrap_or(config);
+
+    let path = config_path().ok_or(ConfigError::NoConfigDir)?;
+    let dir = path.parent().ok_or(ConfigError::NoConfigDir)?.to_path_buf();
 
     // Ensure directory exists
     std::fs::create_dir_all(&dir).map_err(|e| ConfigError::CreateDir(dir.clone(), e))?;
@@ -174,6 +619,7 @@ pub fn save(config: &Config) -> Result<(), ConfigError> {
     std::fs::write(&temp_path, &contents).map_err(|e| ConfigError::Write(temp_path.clone(), e))?;
     std::fs::rename(&temp_path, &path)
         .map_err(|e| ConfigError::Rename(temp_path, path.clone(), e))?;
+    *WRITTEN.lock().unwrap_or_else(|e| e.into_inner()) = Some(contents);
 
     tracing::info!("Saved config to {:?}", path);
     Ok(())
@@ -187,6 +633,119 @@ pub async fn save_async(config: Config) -> Result<(), ConfigError> {
         .map_err(|e| ConfigError::TaskJoin(e.to_string()))?
 }
 
+// ============================================================================
+// Reloading
+// ============================================================================
+
+/// What this process last saved, so a reload can tell its own saves from
+/// edits made elsewhere
+static WRITTEN: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+/// Sections and settings that are state rather than settings: the app
+/// keeps them up to date itself, so edits to them aren't reloaded
+const NOT_RELOADED: &[&str] = &[
+    "history",
+    "secrets",
+    "audio.volume",
+    "library.last_scan_path",
+    "appearance.mini_player",
+    "appearance.mini_player_always_on_top",
+];
+
+/// Sections and settings only read at startup
+const RESTART_NEEDED: &[&str] = &[
+    "agent",
+    "audio.output_device",
+    "audio.media_key_fallback",
+    "library.read_only",
+    "library.path_aliases",
+    "library.resolve_symlinks",
+    "library.case_insensitive_paths",
+    "library.watch_for_changes",
+    "library.watch_settle_secs",
+];
+
+/// When the config file was last changed, to notice edits without reading it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    modified: std::time::SystemTime,
+    len: u64,
+}
+
+/// The active config file's stamp, or `None` while there's no file
+pub fn file_stamp() -> Option<FileStamp> {
+    let meta = std::fs::metadata(config_path()?).ok()?;
+    Some(FileStamp {
+        modified: meta.modified().ok()?,
+        len: meta.len(),
+    })
+}
+
+/// The config file as read again while running
+#[derive(Debug, Clone)]
+pub struct Reloaded {
+    pub config: Config,
+    /// It's what this process last saved
+    pub own_save: bool,
+}
+
+/// Read the active config file again. Unlike [`load`], a file that's
+/// missing or doesn't parse is an error, so a half-finished edit doesn't
+/// reset every setting to its default.
+pub fn read_for_reload() -> Result<Reloaded, ConfigError> {
+    let path = config_path().ok_or(ConfigError::NoConfigDir)?;
+    let contents =
+        std::fs::read_to_string(&path).map_err(|e| ConfigError::Read(path.clone(), e))?;
+    let config = parse(&contents).map_err(|e| ConfigError::Parse(path, Box::new(e)))?;
+    let own_save =
+        WRITTEN.lock().unwrap_or_else(|e| e.into_inner()).as_deref() == Some(contents.as_str());
+    Ok(Reloaded { config, own_save })
+}
+
+/// Settings that differ between `old` and `new`, as `section.setting`
+/// (e.g. `audio.fade_ms`), leaving out the ones the app keeps itself
+pub fn changed_settings(old: &Config, new: &Config) -> Vec<String> {
+    let (Ok(toml::Value::Table(old)), Ok(toml::Value::Table(new))) =
+        (toml::Value::try_from(old), toml::Value::try_from(new))
+    else {
+        return Vec::new();
+    };
+    let empty = toml::map::Map::new();
+    let mut changed = Vec::new();
+    let sections: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for section in sections {
+        let table = |config: &toml::map::Map<String, toml::Value>| match config.get(section) {
+            Some(toml::Value::Table(table)) => table.clone(),
+            _ => empty.clone(),
+        };
+        let (old, new) = (table(&old), table(&new));
+        let keys: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for key in keys {
+            let setting = format!("{}.{}", section, key);
+            if old.get(key) != new.get(key) && !matches_any(&setting, NOT_RELOADED) {
+                changed.push(setting);
+            }
+        }
+    }
+    changed
+}
+
+/// Whether a change to `setting` (as from [`changed_settings`]) only takes
+/// effect after a restart
+pub fn needs_restart(setting: &str) -> bool {
+    matches_any(setting, RESTART_NEEDED)
+}
+
+/// Whether `setting` is one of `list`, or in a section of it
+fn matches_any(setting: &str, list: &[&str]) -> bool {
+    list.iter().any(|entry| {
+        setting == *entry
+            || setting
+                .strip_prefix(entry)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
 // ============================================================================
 // Error Types
 // ============================================================================
@@ -209,8 +768,20 @@ pub enum ConfigError {
     #[error("Failed to rename temp file {0} to {1}: {2}")]
     Rename(PathBuf, PathBuf, std::io::Error),
 
+    #[error("Failed to read config from {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+
+    #[error("Failed to parse config file {0}: {1}")]
+    Parse(PathBuf, Box<toml::de::Error>),
+
     #[error("Task join error: {0}")]
     TaskJoin(String),
+
+    #[error("Credentials are encrypted and locked: unlock them to change them")]
+    CredentialsLocked,
+
+    #[error("Failed to encrypt credentials: {0}")]
+    Secrets(String),
 }
 
 // ============================================================================
@@ -229,6 +800,8 @@ mod tests {
         assert!(toml.contains("[appearance]"));
         assert!(toml.contains("[audio]"));
         assert!(toml.contains("[library]"));
+        assert!(toml.contains("[tagging]"));
+        assert!(toml.contains("[history]"));
     }
 
     #[test]
@@ -269,4 +842,68 @@ acoustid_api_key = "my-key"
         assert_eq!(config.audio.volume, 1.0);
         assert!(config.library.paths.is_empty());
     }
+
+    #[test]
+    fn test_manual_edit_policies() {
+        let toml = r#"
+[tagging.manual_edits]
+genre = "overwrite"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let rules = &config.tagging.manual_edits;
+        assert_eq!(
+            rules.policy("genre"),
+            crate::provenance::ManualEditPolicy::Overwrite
+        );
+        assert_eq!(
+            rules.policy("album"),
+            crate::provenance::ManualEditPolicy::Protect
+        );
+    }
+
+    #[test]
+    fn test_changed_settings() {
+        let old = Config::default();
+        let mut new = old.clone();
+        new.audio.fade_ms = 50;
+        new.audio.volume = 0.2;
+        new.audio.output_device = "USB DAC".to_string();
+        new.tagging
+            .genre_map
+            .insert("hip hop".into(), "Hip-Hop".into());
+        new.scheduler.scan.enabled = !new.scheduler.scan.enabled;
+        new.history.scan_paths.push(PathBuf::from("/music"));
+
+        let changed = changed_settings(&old, &new);
+        assert_eq!(
+            changed,
+            [
+                "audio.fade_ms",
+                "audio.output_device",
+                "scheduler.scan",
+                "tagging.genre_map"
+            ]
+        );
+        let restart: Vec<_> = changed.iter().filter(|s| needs_restart(s)).collect();
+        assert_eq!(restart, ["audio.output_device"]);
+
+        assert!(needs_restart("agent.listen"));
+        assert!(!needs_restart("library.read_only_extra"));
+        assert!(changed_settings(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_remember_moves_to_front() {
+        let mut list: Vec<String> = Vec::new();
+        for pattern in ["a", "b", "c", "a", ""] {
+            remember(&mut list, pattern.to_string());
+        }
+        assert_eq!(list, ["a", "c", "b"]);
+
+        for i in 0..HISTORY_LEN + 2 {
+            remember(&mut list, i.to_string());
+        }
+        assert_eq!(list.len(), HISTORY_LEN);
+        assert_eq!(list[0], (HISTORY_LEN + 1).to_string());
+    }
 }
@@ -0,0 +1,409 @@
+//! Album covers in a batch: one cover per album, not per track.
+//!
+//! [`fetch_album_covers`] walks the library's albums, works out each one's
+//! release (the most common among its selected matches, otherwise among its
+//! tags), and gets its cover once from the usual sources: the album's own
+//! embedded or folder art, the cover cache, then the Cover Art Archive. The
+//! cover is then written where `[covers]` in the config file says: a
+//! `folder.jpg` (or `.png`) in the album's folder, embedded in every track,
+//! or both.
+//!
+//! With `missing_only`, albums that already have a cover where it would go
+//! are left alone, and art the album already has is used before anything is
+//! downloaded. Without it every album is fetched again from its release and
+//! existing covers are replaced.
+
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+use super::embedded::extract_embedded_cover;
+use super::sidecar::{find_folder_cover, find_sidecar_cover};
+use super::{CoverArt, CoverResolver, CoverSource};
+use crate::completeness::{self, AlbumTracks};
+use crate::config::CoversConfig;
+use crate::error::{Classify, ErrorClass, Failure};
+use crate::tasks::TaskHandle;
+
+/// Batch errors (per-album problems are reported in the results instead)
+#[derive(Debug, thiserror::Error)]
+pub enum CoverBatchError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error(transparent)]
+    ReadOnly(#[from] crate::readonly::ReadOnlyError),
+}
+
+/// What happened to one album
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoverOutcome {
+    /// The cover was written: as the folder file, and embedded in this many tracks
+    Written {
+        source: CoverSource,
+        folder: bool,
+        embedded: usize,
+    },
+    /// The album already has a cover everywhere it would be written
+    HasCover,
+    /// No release to look the cover up by, and no art of its own
+    NoRelease,
+    /// No source had a cover (the reason the last one gave)
+    Unavailable(Failure),
+    /// The cover was found but couldn't be written
+    Failed(Failure),
+}
+
+/// One album's result
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumCoverResult {
+    pub album_id: i64,
+    pub artist: String,
+    pub album: String,
+    pub release_id: Option<String>,
+    pub outcome: CoverOutcome,
+}
+
+/// Every album's result, in library order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverBatchReport {
+    pub albums: Vec<AlbumCoverResult>,
+}
+
+impl CoverBatchReport {
+    fn count(&self, f: impl Fn(&CoverOutcome) -> bool) -> usize {
+        self.albums.iter().filter(|a| f(&a.outcome)).count()
+    }
+
+    pub fn written(&self) -> usize {
+        self.count(|o| matches!(o, CoverOutcome::Written { .. }))
+    }
+
+    /// Albums that ended without a cover written (not counting those that had one)
+    pub fn failed(&self) -> usize {
+        self.count(|o| {
+            matches!(
+                o,
+                CoverOutcome::NoRelease | CoverOutcome::Unavailable(_) | CoverOutcome::Failed(_)
+            )
+        })
+    }
+
+    /// One line for the status bar or console
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Wrote covers for {} albums", self.written());
+        let had = self.count(|o| *o == CoverOutcome::HasCover);
+        if had > 0 {
+            summary.push_str(&format!(", {} already had one", had));
+        }
+        let no_release = self.count(|o| *o == CoverOutcome::NoRelease);
+        if no_release > 0 {
+            summary.push_str(&format!(", {} not identified", no_release));
+        }
+        let unavailable = self.count(|o| matches!(o, CoverOutcome::Unavailable(_)));
+        if unavailable > 0 {
+            summary.push_str(&format!(", {} without a cover", unavailable));
+        }
+        let failed = self.count(|o| matches!(o, CoverOutcome::Failed(_)));
+        if failed > 0 {
+            summary.push_str(&format!(", {} failed", failed));
+        }
+        let retryable = self.count(|o| o.failure().is_some_and(Failure::is_recoverable));
+        if retryable > 0 {
+            summary.push_str(&format!(" ({} worth trying again later)", retryable));
+        }
+        summary
+    }
+
+    /// How many albums failed with each class, most first
+    pub fn failure_classes(&self) -> Vec<(ErrorClass, usize)> {
+        let mut classes: Vec<(ErrorClass, usize)> = ErrorClass::ALL
+            .into_iter()
+            .map(|class| {
+                let n = self.count(|o| o.failure().is_some_and(|f| f.class == Some(class)));
+                (class, n)
+            })
+            .filter(|(_, n)| *n > 0)
+            .collect();
+        classes.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        classes
+    }
+}
+
+impl CoverOutcome {
+    /// Why no cover was written, for outcomes that failed with a reason
+    pub fn failure(&self) -> Option<&Failure> {
+        match self {
+            CoverOutcome::Unavailable(f) | CoverOutcome::Failed(f) => Some(f),
+            _ => None,
+        }
+    }
+}
+
+/// Where an album's cover is missing
+#[derive(Debug, Default)]
+struct Needs {
+    /// Folder to write the folder file in
+    folder: Option<PathBuf>,
+    /// Tracks to embed the cover in
+    embed: Vec<PathBuf>,
+}
+
+impl Needs {
+    fn is_empty(&self) -> bool {
+        self.folder.is_none() && self.embed.is_empty()
+    }
+}
+
+/// Fetch and write a cover for every album of the library, reporting
+/// progress and stopping early through `task`
+pub async fn fetch_album_covers(
+    pool: &SqlitePool,
+    config: &CoversConfig,
+    missing_only: bool,
+    task: &TaskHandle,
+) -> Result<CoverBatchReport, CoverBatchError> {
+    crate::readonly::ensure_writable("Writing album covers")?;
+    let albums = completeness::library_albums(pool).await?;
+    task.set_total(albums.len() as u64);
+    let resolver = CoverResolver::new();
+    let mut report = CoverBatchReport::default();
+
+    for album in albums {
+        if task.is_cancelled() {
+            break;
+        }
+        task.set_phase(format!("{} - {}", album.artist, album.album));
+        let config = config.clone();
+        let prepared = tokio::task::spawn_blocking(move || {
+            let mut album = album;
+            completeness::read_tag_ids(&mut album);
+            let needs = needs(&album, &config, missing_only);
+            let local = if missing_only {
+                local_cover(&album)
+            } else {
+                None
+            };
+            (album, needs, local)
+        })
+        .await;
+        let Ok((album, needs, local)) = prepared else {
+            task.advance(1);
+            continue;
+        };
+
+        let outcome = if needs.is_empty() {
+            CoverOutcome::HasCover
+        } else {
+            match find_cover(&resolver, local, album.release_id.as_deref()).await {
+                Ok(cover) => tokio::task::spawn_blocking(move || write(cover, needs, missing_only))
+                    .await
+                    .unwrap_or_else(|e| CoverOutcome::Failed(Failure::message(e.to_string()))),
+                Err(outcome) => outcome,
+            }
+        };
+        report.albums.push(AlbumCoverResult {
+            album_id: album.album_id,
+            artist: album.artist,
+            album: album.album,
+            release_id: album.release_id,
+            outcome,
+        });
+        task.advance(1);
+    }
+    Ok(report)
+}
+
+/// Where `album` needs its cover written. Blocking.
+fn needs(album: &AlbumTracks, config: &CoversConfig, missing_only: bool) -> Needs {
+    let paths: Vec<&Path> = album.tracks.iter().map(|t| Path::new(&t.path)).collect();
+    let folder = config
+        .folder_file
+        .then(|| crate::nfo::album_folder(paths.iter().copied()))
+        .flatten()
+        .filter(|folder| !missing_only || find_folder_cover(folder).is_none());
+    let embed = if config.embed {
+        paths
+            .iter()
+            .filter(|p| !missing_only || extract_embedded_cover(p).is_none())
+            .map(|p| p.to_path_buf())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Needs { folder, embed }
+}
+
+/// Art the album already has: embedded in a track, or next to one. Blocking.
+fn local_cover(album: &AlbumTracks) -> Option<CoverArt> {
+    album.tracks.iter().find_map(|t| {
+        let path = Path::new(&t.path);
+        extract_embedded_cover(path).or_else(|| find_sidecar_cover(path))
+    })
+}
+
+/// The album's own art, else its release's cover from the cache or the
+/// Cover Art Archive (cached once downloaded)
+async fn find_cover(
+    resolver: &CoverResolver,
+    local: Option<CoverArt>,
+    release_id: Option<&str>,
+) -> Result<CoverArt, CoverOutcome> {
+    if let Some(cover) = local {
+        return Ok(cover);
+    }
+    let Some(release_id) = release_id else {
+        return Err(CoverOutcome::NoRelease);
+    };
+    if let Some(cover) = resolver.resolve_cached(release_id) {
+        return Ok(cover);
+    }
+    let cover = resolver
+        .fetch_remote(release_id)
+        .await
+        .map_err(CoverOutcome::Unavailable)?;
+    if let Err(e) = resolver.cache_cover(release_id, &cover) {
+        tracing::warn!(target: "cover::batch", "Failed to cache cover of {}: {}", release_id, e);
+    }
+    Ok(cover)
+}
+
+/// Write `cover` where `needs` says. Blocking.
+fn write(cover: CoverArt, needs: Needs, missing_only: bool) -> CoverOutcome {
+    let mut errors = Vec::new();
+    let mut folder = false;
+    if let Some(dir) = &needs.folder {
+        match write_folder_file(dir, &cover) {
+            Ok(()) => folder = true,
+            Err(e) => errors.push(e),
+        }
+    }
+    let mut embedded = 0;
+    for path in &needs.embed {
+        match crate::metadata::write_cover_art(path, &cover.data, &cover.mime_type, missing_only) {
+            Ok(true) => embedded += 1,
+            Ok(false) => {}
+            Err(e) => errors.push(Failure::new(
+                e.class(),
+                format!("{}: {}", path.display(), e),
+            )),
+        }
+    }
+    match errors.into_iter().next() {
+        Some(e) if !folder && embedded == 0 => CoverOutcome::Failed(e),
+        Some(e) => {
+            tracing::warn!(target: "cover::batch", "Cover only partly written: {}", e);
+            CoverOutcome::Written {
+                source: cover.source,
+                folder,
+                embedded,
+            }
+        }
+        None => CoverOutcome::Written {
+            source: cover.source,
+            folder,
+            embedded,
+        },
+    }
+}
+
+/// Write `folder.jpg` (or `folder.png`) in `dir`, replacing the other format
+pub(super) fn write_folder_file(dir: &Path, cover: &CoverArt) -> Result<(), Failure> {
+    let (name, other) = if cover.mime_type == "image/png" {
+        ("folder.png", "folder.jpg")
+    } else {
+        ("folder.jpg", "folder.png")
+    };
+    let path = dir.join(name);
+    let failure = |path: &Path, e: std::io::Error| {
+        Failure::new(e.class(), format!("{}: {}", path.display(), e))
+    };
+    std::fs::write(&path, &cover.data).map_err(|e| failure(&path, e))?;
+    let other = dir.join(other);
+    if other.exists() {
+        std::fs::remove_file(&other).map_err(|e| failure(&other, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::completeness::OwnedTrack;
+    use tempfile::tempdir;
+
+    fn album(paths: &[PathBuf]) -> AlbumTracks {
+        AlbumTracks {
+            album_id: 1,
+            album: "Album".to_string(),
+            artist: "Artist".to_string(),
+            release_id: None,
+            tracks: paths
+                .iter()
+                .map(|p| OwnedTrack {
+                    path: p.to_string_lossy().to_string(),
+                    title: String::new(),
+                    track_number: None,
+                    disc_number: None,
+                    recording_id: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_needs_skips_albums_with_folder_art_when_missing_only() {
+        let dir = tempdir().unwrap();
+        let tracks = [dir.path().join("CD1/01.mp3"), dir.path().join("CD2/01.mp3")];
+        let album = album(&tracks);
+        let config = CoversConfig::default();
+
+        // Multi-disc: the folder file goes in the folder above the discs
+        assert_eq!(
+            needs(&album, &config, true).folder.as_deref(),
+            Some(dir.path())
+        );
+
+        std::fs::write(dir.path().join("cover.jpg"), b"jpeg").unwrap();
+        assert!(needs(&album, &config, true).is_empty());
+        assert_eq!(
+            needs(&album, &config, false).folder.as_deref(),
+            Some(dir.path())
+        );
+    }
+
+    #[test]
+    fn test_write_folder_file_replaces_other_format() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("folder.jpg"), b"old jpeg").unwrap();
+        let cover = CoverArt {
+            data: b"png".to_vec(),
+            mime_type: "image/png".to_string(),
+            source: CoverSource::Remote,
+            album: None,
+            artist: None,
+        };
+
+        let outcome = write(
+            cover,
+            Needs {
+                folder: Some(dir.path().to_path_buf()),
+                embed: Vec::new(),
+            },
+            false,
+        );
+
+        assert_eq!(
+            outcome,
+            CoverOutcome::Written {
+                source: CoverSource::Remote,
+                folder: true,
+                embedded: 0,
+            }
+        );
+        assert_eq!(
+            std::fs::read(dir.path().join("folder.png")).unwrap(),
+            b"png"
+        );
+        assert!(!dir.path().join("folder.jpg").exists());
+    }
+}
@@ -2,15 +2,80 @@
 //!
 //! Caches fetched cover art to avoid repeated network requests.
 //! Uses the album's MusicBrainz release ID as the cache key.
-
+//!
+//! Releases often share artwork byte for byte (every pressing of an album,
+//! a label's compilation series), so images are stored once under the
+//! SHA-256 of their bytes, in `blobs/`, and `index.json` maps release IDs
+//! to them. Each image counts the releases pointing at it and is deleted
+//! when the last one goes. Past the size limit ([`set_cache_limit`], from
+//! `library.cover_cache_mb`), the least recently used images are evicted.
+
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use super::{CoverArt, CoverSource};
 
+/// Default size limit of the cache
+pub const DEFAULT_CACHE_LIMIT_MB: u64 = 500;
+
+/// Size limit of caches opened from now on, in bytes (0 = unlimited)
+static LIMIT: AtomicU64 = AtomicU64::new(DEFAULT_CACHE_LIMIT_MB * 1_000_000);
+
+/// Resolvers are short-lived and run in parallel tasks; this serializes
+/// their read-modify-write of the index
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+const INDEX_FILE: &str = "index.json";
+const BLOB_DIR: &str = "blobs";
+
+/// Set the size limit of the cover cache in bytes (0 = unlimited)
+pub fn set_cache_limit(bytes: u64) {
+    LIMIT.store(bytes, Ordering::Relaxed);
+}
+
+/// What the cache holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Bytes of image data stored
+    pub bytes: u64,
+    /// Distinct images
+    pub images: usize,
+    /// Releases with a cached cover
+    pub releases: usize,
+    /// Size limit in bytes (0 = unlimited)
+    pub limit: u64,
+}
+
+/// `index.json`: which image each release uses
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Index {
+    /// Release ID → image hash
+    releases: BTreeMap<String, String>,
+    /// Image hash → image
+    images: BTreeMap<String, ImageEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ImageEntry {
+    /// File extension ("jpg" or "png")
+    ext: String,
+    bytes: u64,
+    /// Releases pointing at this image
+    refs: u32,
+    /// Unix time of the last read or write, for eviction
+    last_used: u64,
+}
+
 /// Cover art disk cache.
 pub struct CoverCache {
     cache_dir: PathBuf,
+    limit: u64,
 }
 
 impl CoverCache {
@@ -18,44 +83,67 @@ impl CoverCache {
     pub fn new(cache_dir: impl Into<PathBuf>) -> Self {
         let cache_dir = cache_dir.into();
         // Ensure cache directory exists
-        let _ = fs::create_dir_all(&cache_dir);
-        Self { cache_dir }
+        let _ = fs::create_dir_all(cache_dir.join(BLOB_DIR));
+        let cache = Self {
+            cache_dir,
+            limit: LIMIT.load(Ordering::Relaxed),
+        };
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = cache.migrate_flat_files() {
+            tracing::warn!("Failed to migrate cached covers: {}", e);
+        }
+        cache
     }
 
-    /// Create a cache in the default location (user cache directory).
+    /// Create a cache in the default location (user cache directory, or
+    /// the data directory of a portable install).
     pub fn default_location() -> Self {
-        let cache_dir = dirs::cache_dir()
-            .unwrap_or_else(|| PathBuf::from(".cache"))
-            .join("music-minder")
-            .join("covers");
+        let cache_dir = match crate::portable::get() {
+            Some(portable) => portable.cache_dir("covers"),
+            None => dirs::cache_dir()
+                .unwrap_or_else(|| PathBuf::from(".cache"))
+                .join("music-minder")
+                .join("covers"),
+        };
         Self::new(cache_dir)
     }
 
+    /// Use a size limit other than the one set with [`set_cache_limit`]
+    pub fn with_limit(mut self, bytes: u64) -> Self {
+        self.limit = bytes;
+        self
+    }
+
     /// Get cached cover art for a release ID.
     pub fn get(&self, release_id: &str) -> Option<CoverArt> {
-        let path = self.cache_path(release_id);
-        if !path.exists() {
-            return None;
-        }
-
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.load_index();
+        let hash = index.releases.get(release_id)?.clone();
+        let entry = index.images.get_mut(&hash)?;
+        let path = self.blob_path(&hash, &entry.ext);
         let data = fs::read(&path).ok()?;
 
-        // Determine MIME type from file extension
-        let mime_type = match path.extension().and_then(|s| s.to_str()) {
-            Some("png") => "image/png",
+        entry.last_used = now();
+        let mime_type = match entry.ext.as_str() {
+            "png" => "image/png",
             _ => "image/jpeg",
         };
+        // Recency only orders eviction; losing an update is harmless
+        let _ = self.save_index(&index);
 
         Some(CoverArt {
             data,
             mime_type: mime_type.to_string(),
-            source: CoverSource::Cached(path.clone()),
+            source: CoverSource::Cached(path),
             album: None,
             artist: None,
         })
     }
 
     /// Store cover art in the cache.
+    ///
+    /// Returns where the image is stored: shared with every other release
+    /// whose cover has the same bytes.
     pub fn put(&self, release_id: &str, cover: &CoverArt) -> Result<PathBuf, std::io::Error> {
         // Determine extension from MIME type
         let ext = if cover.mime_type.contains("png") {
@@ -63,38 +151,41 @@ impl CoverCache {
         } else {
             "jpg"
         };
-        let path = self.cache_dir.join(format!("{}.{}", release_id, ext));
-
-        fs::write(&path, &cover.data)?;
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.load_index();
+        let path = self.insert(&mut index, release_id, &cover.data, ext)?;
+        self.evict(&mut index, &path)?;
+        self.save_index(&index)?;
         Ok(path)
     }
 
     /// Check if a release is cached.
     pub fn contains(&self, release_id: &str) -> bool {
-        self.cache_path(release_id).exists()
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        self.load_index().releases.contains_key(release_id)
     }
 
-    /// Get the cache path for a release ID.
-    fn cache_path(&self, release_id: &str) -> PathBuf {
-        // Check for both jpg and png
-        let jpg_path = self.cache_dir.join(format!("{}.jpg", release_id));
-        if jpg_path.exists() {
-            return jpg_path;
-        }
-
-        let png_path = self.cache_dir.join(format!("{}.png", release_id));
-        if png_path.exists() {
-            return png_path;
+    /// Forget the cover cached for a release, deleting the image once no
+    /// other release uses it.
+    #[cfg(feature = "gui")]
+    pub fn remove(&self, release_id: &str) -> Result<(), std::io::Error> {
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut index = self.load_index();
+        if let Some(hash) = index.releases.remove(release_id) {
+            self.release_image(&mut index, &hash)?;
+            self.save_index(&index)?;
         }
-
-        // Default to jpg for new entries
-        jpg_path
+        Ok(())
     }
 
     /// Clear all cached covers.
     pub fn clear(&self) -> Result<(), std::io::Error> {
-        if self.cache_dir.exists() {
-            for entry in fs::read_dir(&self.cache_dir)? {
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for dir in [self.cache_dir.clone(), self.cache_dir.join(BLOB_DIR)] {
+            if !dir.exists() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir)? {
                 let entry = entry?;
                 if entry.file_type()?.is_file() {
                     fs::remove_file(entry.path())?;
@@ -106,22 +197,173 @@ impl CoverCache {
 
     /// Get the total size of the cache in bytes.
     pub fn size_bytes(&self) -> u64 {
-        if !self.cache_dir.exists() {
-            return 0;
+        self.stats().bytes
+    }
+
+    /// Size, image and release counts, and the limit
+    pub fn stats(&self) -> CacheStats {
+        let _guard = INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let index = self.load_index();
+        CacheStats {
+            bytes: index.images.values().map(|e| e.bytes).sum(),
+            images: index.images.len(),
+            releases: index.releases.len(),
+            limit: self.limit,
+        }
+    }
+
+    /// Point `release_id` at the image with these bytes, storing it if new
+    fn insert(
+        &self,
+        index: &mut Index,
+        release_id: &str,
+        data: &[u8],
+        ext: &str,
+    ) -> Result<PathBuf, std::io::Error> {
+        let hash = format!("{:x}", Sha256::digest(data));
+        // The same bytes under another MIME type are still the same image
+        let ext = index
+            .images
+            .get(&hash)
+            .map_or(ext, |e| e.ext.as_str())
+            .to_string();
+        let ext = ext.as_str();
+        let path = self.blob_path(&hash, ext);
+        if !path.exists() {
+            fs::write(&path, data)?;
         }
 
-        fs::read_dir(&self.cache_dir)
-            .map(|entries| {
-                entries
-                    .filter_map(|e| e.ok())
-                    .filter_map(|e| e.metadata().ok())
-                    .map(|m| m.len())
-                    .sum()
+        let previous = index.releases.insert(release_id.to_string(), hash.clone());
+        if previous.as_deref() != Some(hash.as_str()) {
+            if let Some(previous) = previous {
+                self.release_image(index, &previous)?;
+            }
+            let entry = index.images.entry(hash).or_insert_with(|| ImageEntry {
+                ext: ext.to_string(),
+                bytes: data.len() as u64,
+                refs: 0,
+                last_used: 0,
+            });
+            entry.refs += 1;
+            entry.last_used = now();
+        } else if let Some(entry) = index.images.get_mut(&hash) {
+            entry.last_used = now();
+        }
+        Ok(path)
+    }
+
+    /// Drop one reference to an image, deleting it once unused
+    fn release_image(&self, index: &mut Index, hash: &str) -> Result<(), std::io::Error> {
+        let Some(entry) = index.images.get_mut(hash) else {
+            return Ok(());
+        };
+        entry.refs = entry.refs.saturating_sub(1);
+        if entry.refs == 0 {
+            let path = self.blob_path(hash, &entry.ext);
+            index.images.remove(hash);
+            remove_if_exists(&path)?;
+        }
+        Ok(())
+    }
+
+    /// Delete least recently used images, and the releases using them,
+    /// until the cache fits its limit; `keep` (just stored) stays
+    fn evict(&self, index: &mut Index, keep: &Path) -> Result<(), std::io::Error> {
+        if self.limit == 0 {
+            return Ok(());
+        }
+        let mut total: u64 = index.images.values().map(|e| e.bytes).sum();
+        let mut by_age: Vec<(u64, String)> = index
+            .images
+            .iter()
+            .filter(|(hash, entry)| self.blob_path(hash, &entry.ext) != keep)
+            .map(|(hash, entry)| (entry.last_used, hash.clone()))
+            .collect();
+        by_age.sort();
+
+        for (_, hash) in by_age {
+            if total <= self.limit {
+                break;
+            }
+            let Some(entry) = index.images.remove(&hash) else {
+                continue;
+            };
+            remove_if_exists(&self.blob_path(&hash, &entry.ext))?;
+            index.releases.retain(|_, h| *h != hash);
+            total -= entry.bytes;
+            tracing::debug!("Evicted cached cover {} ({} bytes)", hash, entry.bytes);
+        }
+        Ok(())
+    }
+
+    /// Move covers cached one file per release (`<release>.jpg`) by
+    /// earlier versions into the shared store
+    fn migrate_flat_files(&self) -> Result<(), std::io::Error> {
+        let flat: Vec<PathBuf> = fs::read_dir(&self.cache_dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_file() && matches!(p.extension().and_then(|e| e.to_str()), Some("jpg" | "png"))
             })
-            .unwrap_or(0)
+            .collect();
+        if flat.is_empty() {
+            return Ok(());
+        }
+
+        let mut index = self.load_index();
+        for path in &flat {
+            let (Some(release_id), Some(ext)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|e| e.to_str()),
+            ) else {
+                continue;
+            };
+            let data = fs::read(path)?;
+            self.insert(&mut index, release_id, &data, ext)?;
+            fs::remove_file(path)?;
+        }
+        self.save_index(&index)?;
+        tracing::info!("Moved {} cached covers into the shared store", flat.len());
+        Ok(())
+    }
+
+    fn blob_path(&self, hash: &str, ext: &str) -> PathBuf {
+        self.cache_dir
+            .join(BLOB_DIR)
+            .join(format!("{}.{}", hash, ext))
+    }
+
+    /// The index; missing or unreadable is an empty cache
+    fn load_index(&self) -> Index {
+        fs::read_to_string(self.cache_dir.join(INDEX_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_index(&self, index: &Index) -> Result<(), std::io::Error> {
+        let json = serde_json::to_string(index)?;
+        // Written aside and renamed, so a crash can't leave half an index
+        let tmp = self.cache_dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, self.cache_dir.join(INDEX_FILE))
+    }
+}
+
+fn remove_if_exists(path: &Path) -> Result<(), std::io::Error> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
     }
 }
 
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,4 +464,75 @@ mod tests {
         cache.put("test", &cover).unwrap();
         assert_eq!(cache.size_bytes(), 1000);
     }
+
+    fn jpeg(data: &[u8]) -> CoverArt {
+        CoverArt {
+            data: data.to_vec(),
+            mime_type: "image/jpeg".to_string(),
+            source: CoverSource::Remote,
+            album: None,
+            artist: None,
+        }
+    }
+
+    #[test]
+    fn test_identical_covers_stored_once() {
+        let temp = TempDir::new().unwrap();
+        let cache = CoverCache::new(temp.path()).with_limit(0);
+
+        let shared = jpeg(&[7; 500]);
+        let first = cache.put("pressing-1", &shared).unwrap();
+        for i in 2..=200 {
+            assert_eq!(cache.put(&format!("pressing-{i}"), &shared).unwrap(), first);
+        }
+        cache.put("other", &jpeg(&[9; 300])).unwrap();
+
+        let stats = cache.stats();
+        assert_eq!(stats.images, 2);
+        assert_eq!(stats.releases, 201);
+        assert_eq!(stats.bytes, 800);
+        assert_eq!(fs::read_dir(temp.path().join(BLOB_DIR)).unwrap().count(), 2);
+
+        // Repointing the only release using an image deletes the image
+        cache.put("other", &shared).unwrap();
+        assert_eq!(cache.stats().images, 1);
+        assert_eq!(cache.get("other").unwrap().data, vec![7; 500]);
+    }
+
+    #[test]
+    fn test_least_recently_used_evicted_over_limit() {
+        let temp = TempDir::new().unwrap();
+        let cache = CoverCache::new(temp.path()).with_limit(250);
+
+        cache.put("old", &jpeg(&[1; 100])).unwrap();
+        cache.put("older", &jpeg(&[2; 100])).unwrap();
+        // Make "older" the least recent and "old" recently read
+        {
+            let mut index = cache.load_index();
+            index.images.values_mut().for_each(|e| e.last_used = 10);
+            let old = index.releases["old"].clone();
+            index.images.get_mut(&old).unwrap().last_used = 20;
+            cache.save_index(&index).unwrap();
+        }
+
+        cache.put("new", &jpeg(&[3; 100])).unwrap();
+        assert!(cache.contains("old"));
+        assert!(!cache.contains("older"));
+        assert!(cache.contains("new"));
+        assert_eq!(cache.size_bytes(), 200);
+    }
+
+    #[test]
+    fn test_flat_files_migrated() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("release-a.jpg"), [5; 64]).unwrap();
+        fs::write(temp.path().join("release-b.jpg"), [5; 64]).unwrap();
+        fs::write(temp.path().join("release-c.png"), [6; 32]).unwrap();
+
+        let cache = CoverCache::new(temp.path());
+        assert!(!temp.path().join("release-a.jpg").exists());
+        assert_eq!(cache.get("release-b").unwrap().data, vec![5; 64]);
+        assert_eq!(cache.get("release-c").unwrap().mime_type, "image/png");
+        assert_eq!(cache.stats().images, 2);
+    }
 }
@@ -17,6 +17,16 @@ use super::{CoverArt, CoverSource};
 /// This is a fast, synchronous operation that only reads the tag data.
 /// Returns None if no cover art is embedded or the file can't be read.
 pub fn extract_embedded_cover(path: &Path) -> Option<CoverArt> {
+    read_cover(path, false)
+}
+
+/// The embedded front cover only, not another picture standing in for it
+#[cfg(feature = "gui")]
+pub(super) fn extract_front_cover(path: &Path) -> Option<CoverArt> {
+    read_cover(path, true)
+}
+
+fn read_cover(path: &Path, front_only: bool) -> Option<CoverArt> {
     // Open and probe the file
     let tagged_file = Probe::open(path).ok()?.read().ok()?;
 
@@ -36,7 +46,7 @@ pub fn extract_embedded_cover(path: &Path) -> Option<CoverArt> {
     let picture = pictures
         .iter()
         .find(|p| p.pic_type() == lofty::picture::PictureType::CoverFront)
-        .or_else(|| pictures.first())?;
+        .or_else(|| pictures.first().filter(|_| !front_only))?;
 
     let mime_type = match picture.mime_type() {
         Some(lofty::picture::MimeType::Jpeg) => "image/jpeg",
@@ -0,0 +1,398 @@
+//! Covers set by hand: an image file dropped on (or pasted into) a track's
+//! details or an album's view.
+//!
+//! [`prepare`] checks the file is an image big enough to be a cover and
+//! scales anything longer than [`MAX_SIDE`] down. JPEG and PNG that fit are
+//! kept byte for byte; anything else is re-encoded as JPEG. [`apply`] then
+//! writes it to the album where `[covers]` in the config file says, as the
+//! other covers are written, replacing what is there, and caches it under
+//! the album's release. What it replaced comes back as a [`CoverChange`],
+//! which [`undo`] puts back.
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{GenericImageView, ImageFormat};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::batch::write_folder_file;
+use super::embedded::extract_front_cover;
+use super::{CoverArt, CoverCache, CoverSource};
+use crate::config::CoversConfig;
+use crate::error::{Classify, Failure};
+
+/// Longer sides are scaled down to this many pixels
+pub const MAX_SIDE: u32 = 1500;
+
+/// Images with a side shorter than this are too small for a cover
+pub const MIN_SIDE: u32 = 100;
+
+/// JPEG quality of scaled and converted covers
+const JPEG_QUALITY: u8 = 90;
+
+/// The folder files a cover replaces ([`write_folder_file`] writes one)
+const FOLDER_FILES: [&str; 2] = ["folder.jpg", "folder.png"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum CoverSetError {
+    #[error("Couldn't read {}: {}", .0.display(), .1)]
+    Read(PathBuf, std::io::Error),
+
+    #[error("Not an image: {0}")]
+    NotAnImage(String),
+
+    #[error("The image is only {0}×{1} pixels; a cover needs {MIN_SIDE} a side")]
+    TooSmall(u32, u32),
+
+    #[error("Set covers.folder_file or covers.embed in the config file")]
+    NowhereToWrite,
+
+    #[error(transparent)]
+    ReadOnly(#[from] crate::readonly::ReadOnlyError),
+
+    #[error("{0}")]
+    Write(Failure),
+}
+
+/// What setting an album's cover replaced, to put it back
+#[derive(Debug, Clone)]
+pub struct CoverChange {
+    /// The folder file written
+    folder: Option<FolderChange>,
+    /// Tracks the cover was embedded in, with the front cover each had
+    embedded: Vec<(PathBuf, Option<CoverArt>)>,
+    /// Release the cover was cached under, with the cover cached before
+    cached: Option<(String, Option<CoverArt>)>,
+    /// Writes that failed; the others went ahead
+    pub failures: Vec<Failure>,
+}
+
+/// A folder file written, and the folder files it replaced
+#[derive(Debug, Clone)]
+struct FolderChange {
+    dir: PathBuf,
+    /// Path and bytes
+    replaced: Vec<(PathBuf, Vec<u8>)>,
+}
+
+impl CoverChange {
+    /// Tracks the cover was embedded in
+    pub fn embedded_paths(&self) -> impl Iterator<Item = &Path> {
+        self.embedded.iter().map(|(path, _)| path.as_path())
+    }
+
+    /// Whether `path` is one of the album's files this changed, or in its
+    /// folder
+    pub fn touches(&self, path: &Path) -> bool {
+        self.embedded_paths().any(|p| p == path)
+            || self
+                .folder
+                .as_ref()
+                .is_some_and(|folder| path.starts_with(&folder.dir))
+    }
+
+    /// e.g. "folder file and embedded in 12 tracks"
+    pub fn summary(&self) -> String {
+        let embedded = match self.embedded.len() {
+            0 => None,
+            1 => Some("embedded in 1 track".to_string()),
+            n => Some(format!("embedded in {} tracks", n)),
+        };
+        let folder = self.folder.as_ref().map(|_| "folder file".to_string());
+        let mut summary = match (folder, embedded) {
+            (Some(folder), Some(embedded)) => format!("{} and {}", folder, embedded),
+            (Some(one), None) | (None, Some(one)) => one,
+            (None, None) => "nothing written".to_string(),
+        };
+        if !self.failures.is_empty() {
+            summary.push_str(&format!(" ({} failed)", self.failures.len()));
+        }
+        summary
+    }
+}
+
+/// Read, check and (if needed) scale the image at `path`. Blocking.
+pub fn prepare(path: &Path) -> Result<CoverArt, CoverSetError> {
+    let data = fs::read(path).map_err(|e| CoverSetError::Read(path.to_path_buf(), e))?;
+    prepare_bytes(data, CoverSource::Sidecar(path.to_path_buf()))
+}
+
+fn prepare_bytes(data: Vec<u8>, source: CoverSource) -> Result<CoverArt, CoverSetError> {
+    let not_an_image = |e: image::ImageError| CoverSetError::NotAnImage(e.to_string());
+    let format = image::guess_format(&data).map_err(not_an_image)?;
+    let decoded = image::load_from_memory_with_format(&data, format).map_err(not_an_image)?;
+    let (width, height) = decoded.dimensions();
+    if width.min(height) < MIN_SIDE {
+        return Err(CoverSetError::TooSmall(width, height));
+    }
+
+    let fits = width.max(height) <= MAX_SIDE;
+    let (data, mime_type) = match format {
+        ImageFormat::Jpeg if fits => (data, "image/jpeg"),
+        ImageFormat::Png if fits => (data, "image/png"),
+        _ => {
+            let scaled = if fits {
+                decoded
+            } else {
+                decoded.resize(MAX_SIDE, MAX_SIDE, FilterType::Lanczos3)
+            };
+            let mut jpeg = Vec::new();
+            image::DynamicImage::from(scaled.into_rgb8())
+                .write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY))
+                .map_err(not_an_image)?;
+            (jpeg, "image/jpeg")
+        }
+    };
+    Ok(CoverArt {
+        data,
+        mime_type: mime_type.to_string(),
+        source,
+        album: None,
+        artist: None,
+    })
+}
+
+/// The image file named by pasted text: a path or a `file://` URI, the
+/// first of several lines (file managers copy one per file)
+pub fn path_from_clipboard(text: &str) -> Option<PathBuf> {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    let path = match line.strip_prefix("file://") {
+        Some(uri) => {
+            let decoded = urlencoding::decode(uri).ok()?;
+            // file:///C:/Music/cover.jpg
+            match decoded.strip_prefix('/') {
+                Some(rest) if rest.get(1..2) == Some(":") => PathBuf::from(rest),
+                _ => PathBuf::from(decoded.as_ref()),
+            }
+        }
+        None => PathBuf::from(line.trim_matches('"')),
+    };
+    path.is_absolute().then_some(path)
+}
+
+/// Set `cover` as the cover of the album with these `tracks`: as the
+/// folder file and embedded in each track, as `config` says, replacing
+/// what's there, and cached under the album's release. Blocking.
+pub fn apply(
+    cover: &CoverArt,
+    tracks: &[PathBuf],
+    config: &CoversConfig,
+    cache: &CoverCache,
+) -> Result<CoverChange, CoverSetError> {
+    crate::readonly::ensure_writable("Setting an album cover")?;
+    if !config.folder_file && !config.embed {
+        return Err(CoverSetError::NowhereToWrite);
+    }
+    let mut change = CoverChange {
+        folder: None,
+        embedded: Vec::new(),
+        cached: None,
+        failures: Vec::new(),
+    };
+
+    if config.folder_file {
+        match crate::nfo::album_folder(tracks.iter().map(PathBuf::as_path)) {
+            Some(dir) => {
+                let replaced = FOLDER_FILES
+                    .iter()
+                    .map(|name| dir.join(name))
+                    .filter_map(|path| Some((path.clone(), fs::read(&path).ok()?)))
+                    .collect();
+                match write_folder_file(&dir, cover) {
+                    Ok(()) => change.folder = Some(FolderChange { dir, replaced }),
+                    Err(e) => change.failures.push(e),
+                }
+            }
+            None => change.failures.push(Failure::new(
+                None,
+                "The album's tracks are in more than one folder, so it has no folder file",
+            )),
+        }
+    }
+    if config.embed {
+        for path in tracks {
+            let before = extract_front_cover(path);
+            match crate::metadata::write_cover_art(path, &cover.data, &cover.mime_type, false) {
+                Ok(_) => change.embedded.push((path.clone(), before)),
+                Err(e) => change.failures.push(Failure::new(
+                    e.class(),
+                    format!("{}: {}", path.display(), e),
+                )),
+            }
+        }
+    }
+    if change.folder.is_none() && change.embedded.is_empty() {
+        let failure = change.failures.into_iter().next();
+        return Err(CoverSetError::Write(failure.unwrap_or_else(|| {
+            Failure::new(None, "The album has no tracks to write the cover to")
+        })));
+    }
+
+    let release_id = tracks.iter().find_map(|path| {
+        crate::metadata::read_full(path)
+            .ok()?
+            .musicbrainz_release_id
+    });
+    if let Some(release_id) = release_id {
+        let before = cache.get(&release_id);
+        match cache.put(&release_id, cover) {
+            Ok(_) => change.cached = Some((release_id, before)),
+            Err(e) => {
+                tracing::warn!(target: "cover::manual", "Failed to cache cover of {}: {}", release_id, e)
+            }
+        }
+    }
+    Ok(change)
+}
+
+/// Put back what setting a cover replaced. Blocking.
+///
+/// Carries on past files it can't restore, and fails with the first.
+pub fn undo(change: &CoverChange, cache: &CoverCache) -> Result<(), CoverSetError> {
+    crate::readonly::ensure_writable("Undoing an album cover")?;
+    let mut failures = Vec::new();
+    let failure = |path: &Path, e: &dyn std::fmt::Display, class| {
+        Failure::new(class, format!("{}: {}", path.display(), e))
+    };
+
+    if let Some(folder) = &change.folder {
+        for path in FOLDER_FILES.iter().map(|name| folder.dir.join(name)) {
+            if let Err(e) = fs::remove_file(&path)
+                && e.kind() != std::io::ErrorKind::NotFound
+            {
+                failures.push(failure(&path, &e, e.class()));
+            }
+        }
+        for (path, data) in &folder.replaced {
+            if let Err(e) = fs::write(path, data) {
+                failures.push(failure(path, &e, e.class()));
+            }
+        }
+    }
+    for (path, before) in &change.embedded {
+        let restored = match before {
+            Some(cover) => {
+                crate::metadata::write_cover_art(path, &cover.data, &cover.mime_type, false)
+            }
+            None => crate::metadata::remove_cover_art(path),
+        };
+        if let Err(e) = restored {
+            failures.push(failure(path, &e, e.class()));
+        }
+    }
+    if let Some((release_id, before)) = &change.cached {
+        let restored = match before {
+            Some(cover) => cache.put(release_id, cover).map(drop),
+            None => cache.remove(release_id),
+        };
+        if let Err(e) = restored {
+            tracing::warn!(target: "cover::manual", "Failed to restore cached cover of {}: {}", release_id, e);
+        }
+    }
+
+    match failures.into_iter().next() {
+        Some(e) => Err(CoverSetError::Write(e)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{AudioFixture, write_audio_fixture};
+    use tempfile::tempdir;
+
+    fn image_bytes(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(width, height)
+            .write_to(&mut bytes, format)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn test_prepare_keeps_fitting_covers_and_scales_others() {
+        let png = image_bytes(300, 300, ImageFormat::Png);
+        let kept = prepare_bytes(png.clone(), CoverSource::Remote).unwrap();
+        assert_eq!((kept.data, kept.mime_type.as_str()), (png, "image/png"));
+
+        let large = prepare_bytes(
+            image_bytes(3000, 2000, ImageFormat::Png),
+            CoverSource::Remote,
+        )
+        .unwrap();
+        assert_eq!(large.mime_type, "image/jpeg");
+        let scaled = image::load_from_memory(&large.data).unwrap();
+        assert_eq!(scaled.dimensions(), (MAX_SIDE, 1000));
+
+        let bmp =
+            prepare_bytes(image_bytes(200, 200, ImageFormat::Bmp), CoverSource::Remote).unwrap();
+        assert_eq!(bmp.mime_type, "image/jpeg");
+
+        assert!(matches!(
+            prepare_bytes(image_bytes(64, 64, ImageFormat::Png), CoverSource::Remote),
+            Err(CoverSetError::TooSmall(64, 64))
+        ));
+        assert!(matches!(
+            prepare_bytes(b"not an image".to_vec(), CoverSource::Remote),
+            Err(CoverSetError::NotAnImage(_))
+        ));
+    }
+
+    #[test]
+    fn test_path_from_clipboard() {
+        #[cfg(not(windows))]
+        {
+            assert_eq!(
+                path_from_clipboard("file:///music/My%20Album/cover.jpg\nfile:///x.png"),
+                Some(PathBuf::from("/music/My Album/cover.jpg"))
+            );
+            assert_eq!(
+                path_from_clipboard("  \"/music/front.png\"  "),
+                Some(PathBuf::from("/music/front.png"))
+            );
+        }
+        assert_eq!(path_from_clipboard("cover.jpg"), None);
+        assert_eq!(path_from_clipboard(""), None);
+    }
+
+    #[test]
+    fn test_apply_and_undo() {
+        let dir = tempdir().unwrap();
+        let track = write_audio_fixture(dir.path(), AudioFixture::Mp3);
+        let old_folder = image_bytes(200, 200, ImageFormat::Png);
+        fs::write(dir.path().join("folder.png"), &old_folder).unwrap();
+        let cache = CoverCache::new(dir.path().join("cache"));
+        let config = CoversConfig {
+            folder_file: true,
+            embed: true,
+        };
+
+        let cover = prepare_bytes(
+            image_bytes(400, 400, ImageFormat::Jpeg),
+            CoverSource::Remote,
+        )
+        .unwrap();
+        let change = apply(&cover, std::slice::from_ref(&track), &config, &cache).unwrap();
+        assert!(change.failures.is_empty());
+        assert_eq!(change.summary(), "folder file and embedded in 1 track");
+        assert!(change.touches(&track));
+        assert_eq!(fs::read(dir.path().join("folder.jpg")).unwrap(), cover.data);
+        assert!(!dir.path().join("folder.png").exists());
+        assert_eq!(extract_front_cover(&track).unwrap().data, cover.data);
+
+        undo(&change, &cache).unwrap();
+        assert!(!dir.path().join("folder.jpg").exists());
+        assert_eq!(fs::read(dir.path().join("folder.png")).unwrap(), old_folder);
+        assert!(extract_front_cover(&track).is_none());
+
+        let nowhere = CoversConfig {
+            folder_file: false,
+            embed: false,
+        };
+        assert!(matches!(
+            apply(&cover, &[track], &nowhere, &cache),
+            Err(CoverSetError::NowhereToWrite)
+        ));
+    }
+}
@@ -12,14 +12,24 @@
 //! - **Non-blocking**: All operations are async and never block audio playback
 //! - **Graceful degradation**: Missing art is fine, just returns None
 //! - **Consistency**: Cover art must match the album in tags
-//! - **Caching**: Fetched art is cached to disk to avoid repeated network calls
+//! - **Caching**: Fetched art is cached to disk to avoid repeated network calls,
+//!   each distinct image once, within a size limit
+//!
+//! [`fetch_album_covers`] fetches and writes covers for whole albums in a batch.
+//! [`manual`] sets an album's cover from an image the user drops or pastes.
 
+mod batch;
 mod cache;
 mod embedded;
+#[cfg(feature = "gui")]
+pub mod manual;
 mod resolver;
 mod sidecar;
 
-pub use cache::CoverCache;
+pub use batch::{
+    AlbumCoverResult, CoverBatchError, CoverBatchReport, CoverOutcome, fetch_album_covers,
+};
+pub use cache::{CacheStats, CoverCache, DEFAULT_CACHE_LIMIT_MB, set_cache_limit};
 pub use resolver::{CoverArtResult, CoverResolver, CoverSource};
 
 /// Cover art data ready for display
@@ -10,11 +10,14 @@
 //!
 //! The resolver is designed to never block. Local sources (embedded, sidecar, cache)
 //! are checked synchronously but fast. Remote fetching is always async and can be
-//! triggered in the background.
+//! triggered in the background. Builds without the `enrichment` feature
+//! only use the local sources.
 
 use std::path::{Path, PathBuf};
 
+#[cfg(feature = "enrichment")]
 use crate::enrichment::coverart::{CoverArtClient, CoverSize};
+use crate::error::Failure;
 
 use super::CoverArt;
 use super::cache::CoverCache;
@@ -34,6 +37,17 @@ pub enum CoverSource {
     Remote,
 }
 
+impl std::fmt::Display for CoverSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoverSource::Embedded => f.write_str("embedded art"),
+            CoverSource::Sidecar(path) => write!(f, "{}", path.display()),
+            CoverSource::Cached(_) => f.write_str("cover cache"),
+            CoverSource::Remote => f.write_str("Cover Art Archive"),
+        }
+    }
+}
+
 /// Result of a cover art resolution
 #[derive(Debug, Clone)]
 pub struct CoverArtResult {
@@ -46,6 +60,7 @@ pub struct CoverArtResult {
 /// Cover art resolver with caching and background fetching.
 pub struct CoverResolver {
     cache: CoverCache,
+    #[cfg(feature = "enrichment")]
     client: CoverArtClient,
 }
 
@@ -54,6 +69,7 @@ impl CoverResolver {
     pub fn new() -> Self {
         Self {
             cache: CoverCache::default_location(),
+            #[cfg(feature = "enrichment")]
             client: CoverArtClient::new(),
         }
     }
@@ -62,6 +78,7 @@ impl CoverResolver {
     pub fn with_cache_dir(cache_dir: impl Into<PathBuf>) -> Self {
         Self {
             cache: CoverCache::new(cache_dir),
+            #[cfg(feature = "enrichment")]
             client: CoverArtClient::new(),
         }
     }
@@ -136,12 +153,12 @@ impl CoverResolver {
     /// Fetch cover art from Cover Art Archive.
     ///
     /// This is a network operation and should be called from a background task.
-    pub async fn fetch_remote(&self, release_id: &str) -> Result<CoverArt, String> {
-        let result = self
-            .client
-            .get_front_cover(release_id, CoverSize::Medium)
+    #[cfg(feature = "enrichment")]
+    pub async fn fetch_remote(&self, release_id: &str) -> Result<CoverArt, Failure> {
+        let result = crate::error::Retry::NETWORK
+            .run_async(|| self.client.get_front_cover(release_id, CoverSize::Medium))
             .await
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| Failure::of(&e))?;
 
         Ok(CoverArt {
             data: result.data,
@@ -152,9 +169,19 @@ impl CoverResolver {
         })
     }
 
+    /// Without the `enrichment` feature there's nothing to fetch from.
+    #[cfg(not(feature = "enrichment"))]
+    pub async fn fetch_remote(&self, _release_id: &str) -> Result<CoverArt, Failure> {
+        Err(Failure::new(
+            Some(crate::error::ErrorClass::UNSUPPORTED),
+            "built without the enrichment feature",
+        ))
+    }
+
     /// Pre-fetch cover art for a release in the background.
     ///
     /// This is fire-and-forget - it caches the result but doesn't block.
+    #[cfg(feature = "enrichment")]
     pub fn prefetch_background(&self, release_id: String) -> tokio::task::JoinHandle<()> {
         let cache = CoverCache::default_location();
         let client = CoverArtClient::new();
@@ -179,6 +206,11 @@ impl CoverResolver {
         })
     }
 
+    /// Keep a fetched cover in the cache for its release.
+    pub fn cache_cover(&self, release_id: &str, cover: &CoverArt) -> Result<(), std::io::Error> {
+        self.cache.put(release_id, cover).map(|_| ())
+    }
+
     /// Get cache statistics.
     pub fn cache_size_bytes(&self) -> u64 {
         self.cache.size_bytes()
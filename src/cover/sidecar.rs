@@ -29,8 +29,11 @@ const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "gif", "webp"];
 ///
 /// Returns None if no cover art is found.
 pub fn find_sidecar_cover(audio_path: &Path) -> Option<CoverArt> {
-    let parent = audio_path.parent()?;
+    find_folder_cover(audio_path.parent()?)
+}
 
+/// Find a cover art file in `parent`.
+pub fn find_folder_cover(parent: &Path) -> Option<CoverArt> {
     // Try each known cover filename
     for name in COVER_FILENAMES {
         for ext in IMAGE_EXTENSIONS {
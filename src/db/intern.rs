@@ -0,0 +1,188 @@
+//! Interned strings for the columns that repeat across tracks.
+//!
+//! A library of 300k tracks has a few thousand artists and albums and a
+//! handful of languages. Decoding those columns as [`SharedStr`] makes every
+//! track by the same artist point at one allocation instead of holding its
+//! own copy of the name.
+//!
+//! The pool holds a reference to each distinct string. Strings no track
+//! uses any more (only the pool holds them) are dropped whenever the pool
+//! has doubled in size since the last sweep.
+
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, LazyLock};
+
+use sqlx::sqlite::{Sqlite, SqliteTypeInfo, SqliteValueRef};
+
+/// Distinct strings currently in use, and the pool size after the last sweep
+static POOL: LazyLock<Mutex<(HashSet<Arc<str>>, usize)>> =
+    LazyLock::new(|| Mutex::new((HashSet::new(), 0)));
+
+/// Pool size below which unused strings aren't worth sweeping
+const MIN_SWEEP: usize = 1024;
+
+/// A cheaply cloned, shared, immutable string.
+///
+/// Derefs to `str`, so it reads like a `String` field almost everywhere.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SharedStr(Arc<str>);
+
+impl SharedStr {
+    /// The pooled copy of `s`
+    pub fn intern(s: &str) -> Self {
+        let mut guard = POOL.lock();
+        let (pool, swept) = &mut *guard;
+        if let Some(existing) = pool.get(s) {
+            return Self(Arc::clone(existing));
+        }
+        if pool.len() >= MIN_SWEEP && pool.len() >= 2 * *swept {
+            pool.retain(|s| Arc::strong_count(s) > 1);
+            *swept = pool.len();
+        }
+        let shared: Arc<str> = Arc::from(s);
+        pool.insert(Arc::clone(&shared));
+        Self(shared)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Bytes of string data, counted once however many tracks share it
+    pub fn heap_bytes(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether `self` and `other` are the same allocation
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Address of the shared allocation, for counting distinct strings
+    pub fn addr(&self) -> usize {
+        Arc::as_ptr(&self.0) as *const u8 as usize
+    }
+}
+
+impl Default for SharedStr {
+    fn default() -> Self {
+        Self::intern("")
+    }
+}
+
+impl Deref for SharedStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for SharedStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::borrow::Borrow<str> for SharedStr {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for SharedStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl From<&str> for SharedStr {
+    fn from(s: &str) -> Self {
+        Self::intern(s)
+    }
+}
+
+impl From<String> for SharedStr {
+    fn from(s: String) -> Self {
+        Self::intern(&s)
+    }
+}
+
+impl From<SharedStr> for String {
+    fn from(s: SharedStr) -> Self {
+        s.0.to_string()
+    }
+}
+
+impl PartialEq<str> for SharedStr {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for SharedStr {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for SharedStr {
+    fn eq(&self, other: &String) -> bool {
+        *self.0 == **other
+    }
+}
+
+impl serde::Serialize for SharedStr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl sqlx::Type<Sqlite> for SharedStr {
+    fn type_info() -> SqliteTypeInfo {
+        <&str as sqlx::Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <&str as sqlx::Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'q> sqlx::Encode<'q, Sqlite> for SharedStr {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Sqlite as sqlx::Database>::ArgumentBuffer<'q>,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <String as sqlx::Encode<Sqlite>>::encode(self.0.to_string(), buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Sqlite> for SharedStr {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        <&str as sqlx::Decode<Sqlite>>::decode(value).map(Self::intern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_strings_share_one_allocation() {
+        let a = SharedStr::intern("Boards of Canada");
+        let b = SharedStr::from("Boards of Canada".to_string());
+        assert!(a.ptr_eq(&b));
+        assert_eq!(a, "Boards of Canada");
+        assert_eq!(a.to_lowercase(), "boards of canada");
+        assert!(!a.ptr_eq(&SharedStr::intern("Autechre")));
+    }
+}
@@ -0,0 +1,204 @@
+//! Database upkeep: integrity, orphan rows and the write-ahead log.
+//!
+//! Backs the library checks of the Diagnostics pane and the fixes they
+//! link to. Orphans are rows whose track, album or artist is gone: tables
+//! without a foreign key to `tracks` miss its cascades, and removing tracks
+//! leaves their albums and artists behind.
+
+use std::path::PathBuf;
+
+use sqlx::sqlite::SqlitePool;
+
+use super::paths;
+
+/// Each table that can hold orphans, and which of its rows are. Children
+/// come before their parents, so removing in this order leaves none.
+const ORPHANS: &[(&str, &str)] = &[
+    ("track_matches", "track_id NOT IN (SELECT id FROM tracks)"),
+    (
+        "match_releases",
+        "match_id NOT IN (SELECT id FROM track_matches)",
+    ),
+    ("play_history", "track_id NOT IN (SELECT id FROM tracks)"),
+    ("technical_info", "track_id NOT IN (SELECT id FROM tracks)"),
+    (
+        "field_provenance",
+        "track_id NOT IN (SELECT id FROM tracks)",
+    ),
+    ("tag_conflicts", "track_id NOT IN (SELECT id FROM tracks)"),
+    (
+        "playlist_tracks",
+        "track_id NOT IN (SELECT id FROM tracks) OR playlist_id NOT IN (SELECT id FROM playlists)",
+    ),
+    (
+        "albums",
+        "id NOT IN (SELECT album_id FROM tracks WHERE album_id IS NOT NULL)",
+    ),
+    (
+        "album_completeness",
+        "album_id NOT IN (SELECT id FROM albums)",
+    ),
+    (
+        "artists",
+        "id NOT IN (SELECT artist_id FROM tracks WHERE artist_id IS NOT NULL) \
+         AND id NOT IN (SELECT artist_id FROM albums WHERE artist_id IS NOT NULL)",
+    ),
+];
+
+/// What SQLite's integrity check found; empty if the database is sound
+pub async fn integrity_problems(pool: &SqlitePool) -> sqlx::Result<Vec<String>> {
+    let rows: Vec<(String,)> = sqlx::query_as("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(problem,)| problem)
+        .filter(|problem| problem != "ok")
+        .collect())
+}
+
+/// Rebuild every index, which fixes the index problems the integrity
+/// check reports
+pub async fn reindex(pool: &SqlitePool) -> sqlx::Result<()> {
+    sqlx::query("REINDEX").execute(pool).await?;
+    Ok(())
+}
+
+/// Orphan rows per table, for the tables that have any
+pub async fn orphan_counts(pool: &SqlitePool) -> sqlx::Result<Vec<(&'static str, i64)>> {
+    let mut counts = Vec::new();
+    for (table, orphaned) in ORPHANS {
+        let (count,): (i64,) = sqlx::query_as(&format!(
+            "SELECT COUNT(*) FROM {} WHERE {}",
+            table, orphaned
+        ))
+        .fetch_one(pool)
+        .await?;
+        if count > 0 {
+            counts.push((*table, count));
+        }
+    }
+    Ok(counts)
+}
+
+/// Delete every orphan row; returns how many
+pub async fn remove_orphans(pool: &SqlitePool) -> sqlx::Result<u64> {
+    let mut removed = 0;
+    let mut tx = pool.begin().await?;
+    for (table, orphaned) in ORPHANS {
+        removed += sqlx::query(&format!("DELETE FROM {} WHERE {}", table, orphaned))
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+    tx.commit().await?;
+    Ok(removed)
+}
+
+/// Tracks stored under another spelling of a path already in the library,
+/// which [`paths::normalize_track_paths`] would merge
+pub async fn duplicate_paths(pool: &SqlitePool) -> sqlx::Result<usize> {
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT path FROM tracks")
+        .fetch_all(pool)
+        .await?;
+    let policy = paths::policy();
+    let mut keys: Vec<String> = rows.iter().map(|(path,)| policy.key(path)).collect();
+    let total = keys.len();
+    keys.sort_unstable();
+    keys.dedup();
+    Ok(total - keys.len())
+}
+
+/// The database file, `None` for in-memory databases
+pub async fn database_file(pool: &SqlitePool) -> sqlx::Result<Option<PathBuf>> {
+    let file: Option<(String,)> =
+        sqlx::query_as("SELECT file FROM pragma_database_list WHERE name = 'main'")
+            .fetch_optional(pool)
+            .await?;
+    Ok(file
+        .map(|(file,)| file)
+        .filter(|file| !file.is_empty())
+        .map(PathBuf::from))
+}
+
+/// Size of the write-ahead log next to the database file, if there is one
+pub async fn wal_bytes(pool: &SqlitePool) -> sqlx::Result<Option<u64>> {
+    let Some(file) = database_file(pool).await? else {
+        return Ok(None);
+    };
+    let mut wal = file.into_os_string();
+    wal.push("-wal");
+    Ok(std::fs::metadata(wal).ok().map(|m| m.len()))
+}
+
+/// Write the log back into the database and truncate it
+pub async fn checkpoint(pool: &SqlitePool) -> sqlx::Result<()> {
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{insert_mock_track, temp_db};
+
+    #[tokio::test]
+    async fn test_orphans_counted_and_removed() {
+        let (pool, _dir) = temp_db().await;
+        let kept = insert_mock_track(&pool, "/music/kept.mp3").await;
+        let gone = insert_mock_track(&pool, "/music/gone.mp3").await;
+        for track in [kept, gone] {
+            sqlx::query(
+                "INSERT INTO field_provenance (track_id, field, source, written_at) \
+                 VALUES (?, 'title', 'manual', 0)",
+            )
+            .bind(track)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        sqlx::query("DROP TRIGGER IF EXISTS field_provenance_track_removed")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM tracks WHERE id = ?")
+            .bind(gone)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO artists (name) VALUES ('Nobody')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            orphan_counts(&pool).await.unwrap(),
+            [("field_provenance", 1), ("artists", 1)]
+        );
+        assert_eq!(remove_orphans(&pool).await.unwrap(), 2);
+        assert!(orphan_counts(&pool).await.unwrap().is_empty());
+        assert_eq!(
+            crate::provenance::for_track(&pool, kept)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_integrity_duplicates_and_wal() {
+        let (pool, dir) = temp_db().await;
+        insert_mock_track(&pool, "/music/a.mp3").await;
+        assert!(integrity_problems(&pool).await.unwrap().is_empty());
+        assert_eq!(duplicate_paths(&pool).await.unwrap(), 0);
+        assert_eq!(
+            database_file(&pool).await.unwrap().unwrap().file_name(),
+            dir.path().join("test.db").file_name()
+        );
+        checkpoint(&pool).await.unwrap();
+        assert_eq!(wal_bytes(&pool).await.unwrap().unwrap_or(0), 0);
+    }
+}
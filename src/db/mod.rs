@@ -5,6 +5,12 @@
 //! - Track CRUD operations
 //! - Artist and album management  
 //! - Batch updates for file organization
+//! - Merging and splitting libraries ([`merge`], [`split`])
+//! - Canonical track paths, so one file is one track ([`paths`])
+//! - Remote tracks, whose path is a stream or cloud drive URL
+//! - Caching the technical info the track detail shows
+//! - Upkeep: integrity, orphan rows, the write-ahead log ([`maintenance`])
+//! - Playlists and their ordered tracks ([`playlists`])
 //!
 //! # Example
 //!
@@ -15,10 +21,21 @@
 //! let tracks = get_all_tracks_with_metadata(&pool).await?;
 //! ```
 
+mod intern;
+pub mod maintenance;
+pub mod paths;
+pub mod playlists;
+mod schema;
+mod transfer;
+
+pub use intern::SharedStr;
+pub use schema::{AppliedMigration, NewerSchemaError, SchemaInfo, backups, db_file, schema_info};
+pub use transfer::{TrackSelection, TransferError, TransferProgress, TransferReport, merge, split};
+
 use std::path::PathBuf;
 
 use crate::metadata::TrackMetadata;
-use crate::model::Track;
+use crate::model::{Track, TrackSource};
 use sqlx::migrate::MigrateDatabase;
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use std::time::Instant;
@@ -28,7 +45,8 @@ pub const DEFAULT_DB_NAME: &str = "music_minder.db";
 
 /// Build a SQLite database URL from an optional path.
 ///
-/// If no path is provided, uses [`DEFAULT_DB_NAME`] in the current directory.
+/// If no path is provided, uses the active profile's database
+/// ([`DEFAULT_DB_NAME`] in the current directory for the default profile).
 ///
 /// # Arguments
 ///
@@ -40,14 +58,15 @@ pub const DEFAULT_DB_NAME: &str = "music_minder.db";
 pub fn db_url(path: Option<&std::path::Path>) -> String {
     match path {
         Some(p) => format!("sqlite:{}", p.display()),
-        None => format!("sqlite:{}", DEFAULT_DB_NAME),
+        None => crate::profile::db_url(),
     }
 }
 
 /// Initialize the database connection pool and run migrations.
 ///
 /// Creates the database file if it doesn't exist, establishes a connection
-/// pool with up to 5 connections, and runs all pending migrations.
+/// pool with up to 5 connections, and runs all pending migrations. A
+/// database being upgraded is copied to `<file>.v<version>.bak` first.
 ///
 /// # Arguments
 ///
@@ -58,7 +77,8 @@ pub fn db_url(path: Option<&std::path::Path>) -> String {
 /// Returns an error if:
 /// - Database creation fails
 /// - Connection cannot be established
-/// - Migration fails
+/// - The database was opened by a newer version ([`NewerSchemaError`])
+/// - The pre-upgrade backup or a migration fails
 pub async fn init_db(db_url: &str) -> Result<SqlitePool, sqlx::Error> {
     let total_start = Instant::now();
 
@@ -83,12 +103,31 @@ pub async fn init_db(db_url: &str) -> Result<SqlitePool, sqlx::Error> {
     );
 
     let migrate_start = Instant::now();
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    if let Some(backup) = schema::prepare_migration(&pool, db_url).await? {
+        tracing::info!(
+            "Backed up database to {} before upgrading",
+            backup.display()
+        );
+    }
+    schema::MIGRATOR.run(&pool).await?;
     tracing::debug!(
         "Migrations completed in {:.1}ms",
         migrate_start.elapsed().as_secs_f64() * 1000.0
     );
 
+    let backfilled = backfill_track_dates(&pool).await?;
+    if backfilled > 0 {
+        tracing::info!("Backfilled date added for {} tracks", backfilled);
+    }
+
+    let merged = paths::normalize_track_paths(&pool, &paths::policy(), false).await?;
+    if merged > 0 {
+        tracing::info!(
+            "Merged {} tracks stored under two spellings of one path",
+            merged
+        );
+    }
+
     tracing::info!(
         "Total database init: {:.1}ms",
         total_start.elapsed().as_secs_f64() * 1000.0
@@ -97,6 +136,49 @@ pub async fn init_db(db_url: &str) -> Result<SqlitePool, sqlx::Error> {
     Ok(pool)
 }
 
+/// Fill in `added_at`/`updated_at` for tracks that predate those columns.
+///
+/// `added_at` comes from the file's creation time (falling back to its
+/// modification time, then the stored mtime, then now). `updated_at` comes
+/// from the modification time. Only rows with a NULL `added_at` are touched,
+/// so this is a no-op once the library has been backfilled.
+async fn backfill_track_dates(pool: &SqlitePool) -> sqlx::Result<usize> {
+    let rows: Vec<(i64, String, Option<i64>)> =
+        sqlx::query_as("SELECT id, path, mtime FROM tracks WHERE added_at IS NULL")
+            .fetch_all(pool)
+            .await?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let to_unix = |t: std::io::Result<std::time::SystemTime>| {
+        t.ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+    };
+
+    let mut tx = pool.begin().await?;
+    for (id, path, mtime) in &rows {
+        let meta = std::fs::metadata(path).ok();
+        let modified = meta.as_ref().and_then(|m| to_unix(m.modified())).or(*mtime);
+        let created = meta.as_ref().and_then(|m| to_unix(m.created()));
+        let added = created.or(modified).unwrap_or(now);
+
+        sqlx::query(
+            "UPDATE tracks SET added_at = ?, updated_at = COALESCE(updated_at, ?) WHERE id = ?",
+        )
+        .bind(added)
+        .bind(modified.unwrap_or(added))
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(rows.len())
+}
+
 /// Get or create an artist by name.
 ///
 /// Looks up an artist by exact name match. If not found, creates a new
@@ -166,6 +248,68 @@ pub async fn get_or_create_album(
     }
 }
 
+/// An album with the rollups kept on it: track count, total duration and
+/// average bitrate, maintained by triggers as tracks come, go and change.
+#[derive(Debug, Clone, PartialEq, Eq, sqlx::FromRow)]
+pub struct AlbumSummary {
+    /// Database ID
+    pub id: i64,
+    /// Album title
+    pub title: String,
+    /// Album artist name (or "Unknown Artist")
+    pub artist: String,
+    /// Release year
+    pub year: Option<i32>,
+    /// Tracks on the album
+    pub track_count: i64,
+    /// Total duration in seconds
+    pub total_duration: i64,
+    /// Average bitrate in kbps over the tracks with one (None if none has)
+    pub avg_bitrate: Option<i64>,
+}
+
+const ALBUM_SUMMARY_SELECT: &str = r#"
+    SELECT
+        al.id, al.title,
+        COALESCE(ar.name, 'Unknown Artist') AS artist,
+        al.year, al.track_count, al.total_duration,
+        CASE WHEN al.bitrate_tracks > 0 THEN al.bitrate_sum / al.bitrate_tracks END
+            AS avg_bitrate
+    FROM albums al
+    LEFT JOIN artists ar ON al.artist_id = ar.id
+    WHERE al.track_count > 0
+"#;
+
+/// Every album with tracks, with its rollups, by artist then title.
+///
+/// Reads the rollup columns only, so it stays fast however many tracks the
+/// library has.
+pub async fn get_album_summaries(pool: &SqlitePool) -> sqlx::Result<Vec<AlbumSummary>> {
+    sqlx::query_as::<_, AlbumSummary>(&format!(
+        "{ALBUM_SUMMARY_SELECT} ORDER BY artist COLLATE NOCASE, al.title COLLATE NOCASE"
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+/// The rollups of the album `title` by `artist`, as the library groups
+/// tracks. An album stored without an artist (added by the folder watcher)
+/// counts when no album has the artist.
+pub async fn get_album_summary(
+    pool: &SqlitePool,
+    title: &str,
+    artist: &str,
+) -> sqlx::Result<Option<AlbumSummary>> {
+    sqlx::query_as::<_, AlbumSummary>(&format!(
+        "{ALBUM_SUMMARY_SELECT} AND al.title = ? AND (ar.name = ? OR al.artist_id IS NULL) \
+         ORDER BY al.artist_id IS NULL LIMIT 1"
+    ))
+    .bind(title)
+    .bind(artist)
+    .fetch_optional(pool)
+    .await
+}
+
 /// Insert or update a track record.
 ///
 /// Uses SQLite's UPSERT to either insert a new track or update an existing
@@ -195,23 +339,36 @@ pub async fn insert_track(
 
     let row: (i64,) = sqlx::query_as(
         r#"
-        INSERT INTO tracks (title, artist_id, album_id, path, duration, track_number)
-        VALUES (?, ?, ?, ?, ?, ?)
-        ON CONFLICT(path) DO UPDATE SET
+        INSERT INTO tracks (title, artist_id, album_id, path, path_key, duration, track_number,
+                            source, added_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, unixepoch(), unixepoch())
+        ON CONFLICT(path_key) DO UPDATE SET
             title = excluded.title,
             artist_id = excluded.artist_id,
             album_id = excluded.album_id,
             duration = excluded.duration,
-            track_number = excluded.track_number
+            track_number = excluded.track_number,
+            track_number_inferred = FALSE,
+            quality_flags = CASE WHEN tracks.title IS excluded.title
+                                  AND tracks.artist_id IS excluded.artist_id
+                                 THEN tracks.quality_flags ELSE tracks.quality_flags & ~? END,
+            updated_at = CASE WHEN tracks.title IS excluded.title
+                               AND tracks.artist_id IS excluded.artist_id
+                               AND tracks.album_id IS excluded.album_id
+                               AND tracks.track_number IS excluded.track_number
+                              THEN tracks.updated_at ELSE excluded.updated_at END
         RETURNING id
         "#,
     )
     .bind(&meta.title)
     .bind(artist_id)
     .bind(album_id)
-    .bind(path)
+    .bind(paths::canonical(path))
+    .bind(paths::key(path))
     .bind(duration)
     .bind(track_number)
+    .bind(TrackSource::detect(path))
+    .bind(crate::health::QualityFlags::MISLABELED_SUSPECT.to_bits_i64())
     .fetch_one(pool)
     .await?;
 
@@ -245,8 +402,10 @@ pub async fn update_track_path(
     track_id: i64,
     new_path: &str,
 ) -> sqlx::Result<()> {
-    sqlx::query("UPDATE tracks SET path = ? WHERE id = ?")
-        .bind(new_path)
+    crate::readonly::ensure_writable("Updating track paths")?;
+    sqlx::query("UPDATE tracks SET path = ?, path_key = ?, updated_at = unixepoch() WHERE id = ?")
+        .bind(paths::canonical(new_path))
+        .bind(paths::key(new_path))
         .bind(track_id)
         .execute(pool)
         .await?;
@@ -259,15 +418,19 @@ pub async fn batch_update_track_paths(
     pool: &SqlitePool,
     updates: &[(i64, String)],
 ) -> sqlx::Result<usize> {
+    crate::readonly::ensure_writable("Updating track paths")?;
     let mut tx = pool.begin().await?;
     let mut success_count = 0;
 
     for (track_id, new_path) in updates {
-        let result = sqlx::query("UPDATE tracks SET path = ? WHERE id = ?")
-            .bind(new_path)
-            .bind(track_id)
-            .execute(&mut *tx)
-            .await;
+        let result = sqlx::query(
+            "UPDATE tracks SET path = ?, path_key = ?, updated_at = unixepoch() WHERE id = ?",
+        )
+        .bind(paths::canonical(new_path))
+        .bind(paths::key(new_path))
+        .bind(track_id)
+        .execute(&mut *tx)
+        .await;
 
         if result.is_ok() {
             success_count += 1;
@@ -313,19 +476,41 @@ pub struct TrackWithMetadata {
     /// File path
     pub path: String,
     /// Duration in seconds
-    pub duration: Option<i64>,
+    pub duration: Option<u32>,
     /// Track number on album
-    pub track_number: Option<i64>,
-    /// Artist name (or "Unknown Artist")
-    pub artist_name: String,
-    /// Album name (or "Unknown Album")
-    pub album_name: String,
+    pub track_number: Option<u32>,
+    /// Artist name (or "Unknown Artist"), shared by the artist's tracks
+    pub artist_name: SharedStr,
+    /// Album name (or "Unknown Album"), shared by the album's tracks
+    pub album_name: SharedStr,
     /// Release year (from album)
-    pub year: Option<i64>,
+    pub year: Option<i32>,
     /// Quality score (0-100, None if never assessed)
-    pub quality_score: Option<i64>,
+    pub quality_score: Option<u8>,
     /// Quality flags as bitfield
-    pub quality_flags: Option<i64>,
+    pub quality_flags: Option<u32>,
+    /// When the track entered the library (Unix timestamp)
+    pub added_at: Option<i64>,
+    /// When the track record last changed (Unix timestamp)
+    pub updated_at: Option<i64>,
+    /// Track number was guessed from the file name or folder order
+    pub track_number_inferred: bool,
+    /// ReplayGain track gain in dB (None if untagged)
+    pub track_gain: Option<f32>,
+    /// ReplayGain track peak, linear
+    pub track_peak: Option<f32>,
+    /// Silence before the first sound in ms (None until analyzed)
+    pub leading_silence_ms: Option<u32>,
+    /// Silence after the last sound in ms (None until analyzed)
+    pub trailing_silence_ms: Option<u32>,
+    /// Lyrics language, ISO 639-2 ("zxx" = instrumental)
+    pub language: Option<SharedStr>,
+    /// Advisory rating: explicit (true), clean (false), or untagged
+    pub explicit: Option<bool>,
+    /// The album's track or disc numbering failed the last numbering audit
+    pub numbering_inconsistent: bool,
+    /// A file, or a stream or cloud drive URL
+    pub source: TrackSource,
 }
 
 /// Lightweight track info for incremental scanning.
@@ -359,11 +544,32 @@ impl TrackWithMetadata {
     /// Get quality flags as the typed bitflags.
     pub fn quality_flags(&self) -> crate::health::QualityFlags {
         self.quality_flags
-            .map(crate::health::QualityFlags::from_bits_i64)
+            .map(crate::health::QualityFlags::from_bits_truncate)
             .unwrap_or_default()
     }
 }
 
+/// Heap bookkeeping per allocation on top of its bytes (typical malloc)
+const ALLOC_OVERHEAD: usize = 16;
+
+/// Approximate memory held by a loaded track list: the rows, the strings
+/// each track owns, and each distinct shared name once.
+pub fn track_list_bytes(tracks: &[TrackWithMetadata]) -> usize {
+    let mut shared = std::collections::HashSet::new();
+    let mut bytes = std::mem::size_of_val(tracks);
+    for track in tracks {
+        bytes += track.title.capacity() + track.path.capacity() + 2 * ALLOC_OVERHEAD;
+        let names = [Some(&track.artist_name), Some(&track.album_name)];
+        for name in names.into_iter().chain([track.language.as_ref()]).flatten() {
+            if shared.insert(name.addr()) {
+                // Plus the Arc's two reference counts
+                bytes += name.heap_bytes() + 2 * std::mem::size_of::<usize>() + ALLOC_OVERHEAD;
+            }
+        }
+    }
+    bytes
+}
+
 /// Get all tracks with artist and album names.
 ///
 /// Performs a LEFT JOIN to include tracks even if they have no artist
@@ -381,7 +587,11 @@ pub async fn get_all_tracks_with_metadata(
             COALESCE(a.name, 'Unknown Artist') as artist_name,
             COALESCE(al.title, 'Unknown Album') as album_name,
             al.year,
-            t.quality_score, t.quality_flags
+            t.quality_score, t.quality_flags,
+            t.added_at, t.updated_at, t.track_number_inferred,
+            t.replaygain_track_gain AS track_gain, t.replaygain_track_peak AS track_peak,
+            t.leading_silence_ms, t.trailing_silence_ms,
+            t.language, t.explicit, t.numbering_inconsistent, t.source
         FROM tracks t
         LEFT JOIN artists a ON t.artist_id = a.id
         LEFT JOIN albums al ON t.album_id = al.id
@@ -418,7 +628,11 @@ pub async fn get_tracks_paginated(
             COALESCE(a.name, 'Unknown Artist') as artist_name,
             COALESCE(al.title, 'Unknown Album') as album_name,
             al.year,
-            t.quality_score, t.quality_flags
+            t.quality_score, t.quality_flags,
+            t.added_at, t.updated_at, t.track_number_inferred,
+            t.replaygain_track_gain AS track_gain, t.replaygain_track_peak AS track_peak,
+            t.leading_silence_ms, t.trailing_silence_ms,
+            t.language, t.explicit, t.numbering_inconsistent, t.source
         FROM tracks t
         LEFT JOIN artists a ON t.artist_id = a.id
         LEFT JOIN albums al ON t.album_id = al.id
@@ -432,6 +646,37 @@ pub async fn get_tracks_paginated(
     .await
 }
 
+/// Get tracks added to the library at or after a Unix timestamp.
+///
+/// Backs "recently added" smart playlists; newest additions come first.
+pub async fn get_tracks_added_since(
+    pool: &SqlitePool,
+    since: i64,
+) -> sqlx::Result<Vec<TrackWithMetadata>> {
+    sqlx::query_as::<_, TrackWithMetadata>(
+        r#"
+        SELECT 
+            t.id, t.title, t.path, t.duration, t.track_number,
+            COALESCE(a.name, 'Unknown Artist') as artist_name,
+            COALESCE(al.title, 'Unknown Album') as album_name,
+            al.year,
+            t.quality_score, t.quality_flags,
+            t.added_at, t.updated_at, t.track_number_inferred,
+            t.replaygain_track_gain AS track_gain, t.replaygain_track_peak AS track_peak,
+            t.leading_silence_ms, t.trailing_silence_ms,
+            t.language, t.explicit, t.numbering_inconsistent, t.source
+        FROM tracks t
+        LEFT JOIN artists a ON t.artist_id = a.id
+        LEFT JOIN albums al ON t.album_id = al.id
+        WHERE t.added_at >= ?
+        ORDER BY t.added_at DESC, t.id
+        "#,
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
 /// Count total number of tracks in the database.
 ///
 /// Fast query to determine library size for pagination/progress.
@@ -449,8 +694,9 @@ pub async fn count_tracks(pool: &SqlitePool) -> sqlx::Result<i64> {
 /// Get all track paths and mtimes for incremental scanning.
 ///
 /// Returns lightweight records for efficient comparison with filesystem.
+/// Remote tracks have no file and are left out, so scans don't remove them.
 pub async fn get_all_track_file_info(pool: &SqlitePool) -> sqlx::Result<Vec<TrackFileInfo>> {
-    sqlx::query_as::<_, TrackFileInfo>("SELECT id, path, mtime FROM tracks")
+    sqlx::query_as::<_, TrackFileInfo>("SELECT id, path, mtime FROM tracks WHERE source = 'local'")
         .fetch_all(pool)
         .await
 }
@@ -481,37 +727,285 @@ pub async fn insert_track_with_mtime(
 
     let row: (i64,) = sqlx::query_as(
         r#"
-        INSERT INTO tracks (title, artist_id, album_id, path, duration, track_number, mtime)
-        VALUES (?, ?, ?, ?, ?, ?, ?)
-        ON CONFLICT(path) DO UPDATE SET
+        INSERT INTO tracks (title, artist_id, album_id, path, path_key, duration, track_number,
+                            mtime, added_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, unixepoch(), unixepoch())
+        ON CONFLICT(path_key) DO UPDATE SET
             title = excluded.title,
             artist_id = excluded.artist_id,
             album_id = excluded.album_id,
             duration = excluded.duration,
             track_number = excluded.track_number,
-            mtime = excluded.mtime
+            track_number_inferred = FALSE,
+            silence_checked_at = CASE WHEN tracks.mtime IS excluded.mtime
+                                      THEN tracks.silence_checked_at END,
+            quality_flags = CASE WHEN tracks.title IS excluded.title
+                                  AND tracks.artist_id IS excluded.artist_id
+                                 THEN tracks.quality_flags ELSE tracks.quality_flags & ~? END,
+            mtime = excluded.mtime,
+            updated_at = CASE WHEN tracks.title IS excluded.title
+                               AND tracks.artist_id IS excluded.artist_id
+                               AND tracks.album_id IS excluded.album_id
+                               AND tracks.track_number IS excluded.track_number
+                               AND tracks.mtime IS excluded.mtime
+                              THEN tracks.updated_at ELSE excluded.updated_at END
         RETURNING id
         "#,
     )
     .bind(&meta.title)
     .bind(artist_id)
     .bind(album_id)
-    .bind(path)
+    .bind(paths::canonical(path))
+    .bind(paths::key(path))
     .bind(duration)
     .bind(track_number)
     .bind(mtime)
+    .bind(crate::health::QualityFlags::MISLABELED_SUSPECT.to_bits_i64())
     .fetch_one(pool)
     .await?;
 
     Ok(row.0)
 }
 
+/// Store the ReplayGain values read from a track's tags.
+///
+/// Called after the track is inserted; untagged values are stored as NULL.
+pub async fn update_track_loudness(
+    pool: &SqlitePool,
+    track_id: i64,
+    loudness: &crate::metadata::Loudness,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE tracks SET
+            replaygain_track_gain = ?,
+            replaygain_track_peak = ?,
+            replaygain_album_gain = ?,
+            replaygain_album_peak = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(loudness.track_gain)
+    .bind(loudness.track_peak)
+    .bind(loudness.album_gain)
+    .bind(loudness.album_peak)
+    .bind(track_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Store the language and explicit flag read from a track's tags.
+pub async fn update_track_content(
+    pool: &SqlitePool,
+    track_id: i64,
+    content: &crate::metadata::ContentTags,
+) -> sqlx::Result<()> {
+    sqlx::query("UPDATE tracks SET language = ?, explicit = ? WHERE id = ?")
+        .bind(content.language.as_deref())
+        .bind(content.explicit)
+        .bind(track_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Store the genres read from a track's tags, after the genre rules
+pub async fn update_track_genres(
+    pool: &SqlitePool,
+    track_id: i64,
+    genres: &[String],
+) -> sqlx::Result<()> {
+    sqlx::query("UPDATE tracks SET genre = ? WHERE id = ?")
+        .bind(genres.join(crate::metadata::genres::SEPARATOR))
+        .bind(track_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Store a track's audio bitrate (kbps), which the album rollups add up
+pub async fn update_track_bitrate(
+    pool: &SqlitePool,
+    track_id: i64,
+    bitrate: Option<u32>,
+) -> sqlx::Result<()> {
+    sqlx::query("UPDATE tracks SET bitrate = ? WHERE id = ?")
+        .bind(bitrate)
+        .bind(track_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Store the rating and play count imported from a track's tags.
+///
+/// A rating replaces the stored one; a play count only ever raises it.
+/// Values the tags don't have leave the stored ones alone.
+pub async fn update_track_ratings(
+    pool: &SqlitePool,
+    track_id: i64,
+    ratings: &crate::metadata::ratings::ImportedRatings,
+) -> sqlx::Result<()> {
+    if ratings.is_empty() {
+        return Ok(());
+    }
+    let play_count = ratings.play_count.map(i64::from);
+    sqlx::query(
+        r#"
+        UPDATE tracks SET
+            rating = COALESCE(?, rating),
+            tag_play_count = COALESCE(MAX(tag_play_count, ?), ?, tag_play_count)
+        WHERE id = ?
+        "#,
+    )
+    .bind(ratings.rating.map(i64::from))
+    .bind(play_count)
+    .bind(play_count)
+    .bind(track_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A track's stored rating and imported play count
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TrackRatings {
+    pub id: i64,
+    pub path: String,
+    /// 0-100, None if unrated
+    pub rating: Option<i64>,
+    pub tag_play_count: Option<i64>,
+}
+
+/// Ratings and imported play counts of every track, by path.
+pub async fn get_track_ratings(pool: &SqlitePool) -> sqlx::Result<Vec<TrackRatings>> {
+    sqlx::query_as("SELECT id, path, rating, tag_play_count FROM tracks ORDER BY path")
+        .fetch_all(pool)
+        .await
+}
+
+/// Tracks whose silence hasn't been measured, oldest first.
+pub async fn get_tracks_needing_silence_check(
+    pool: &SqlitePool,
+    limit: u32,
+) -> sqlx::Result<Vec<(i64, String)>> {
+    sqlx::query_as(
+        "SELECT id, path FROM tracks WHERE silence_checked_at IS NULL AND source = 'local' \
+         ORDER BY id LIMIT ?",
+    )
+    .bind(i64::from(limit))
+    .fetch_all(pool)
+    .await
+}
+
+/// Store a track's measured silence (`None` if the file couldn't be
+/// decoded), and set or clear its long-silence quality flag.
+pub async fn update_track_silence(
+    pool: &SqlitePool,
+    track_id: i64,
+    silence: Option<&crate::player::silence::Silence>,
+) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Storing silence analysis")?;
+    let flag = crate::health::QualityFlags::LONG_SILENCE.to_bits_i64();
+    let long = silence.is_some_and(|s| s.is_long());
+    sqlx::query(
+        r#"
+        UPDATE tracks SET
+            leading_silence_ms = ?,
+            trailing_silence_ms = ?,
+            silence_checked_at = unixepoch(),
+            quality_flags = CASE WHEN quality_flags IS NULL THEN NULL
+                                 ELSE (quality_flags & ~?) | ? END
+        WHERE id = ?
+        "#,
+    )
+    .bind(silence.map(|s| s.leading.as_millis() as i64))
+    .bind(silence.map(|s| s.trailing.as_millis() as i64))
+    .bind(flag)
+    .bind(if long { flag } else { 0 })
+    .bind(track_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The measured silence of the track at `path`, if analyzed.
+pub async fn get_track_silence(
+    pool: &SqlitePool,
+    path: &str,
+) -> sqlx::Result<Option<crate::player::silence::Silence>> {
+    let row: Option<(Option<i64>, Option<i64>)> = sqlx::query_as(
+        "SELECT leading_silence_ms, trailing_silence_ms FROM tracks WHERE path_key = ?",
+    )
+    .bind(paths::key(path))
+    .fetch_optional(pool)
+    .await?;
+    Ok(match row {
+        Some((Some(leading), Some(trailing))) => Some(
+            crate::player::silence::Silence::from_millis(leading, trailing),
+        ),
+        _ => None,
+    })
+}
+
+/// The cached technical info of the track at `path`, if it was probed at
+/// file modification time `mtime`.
+pub async fn get_technical_info(
+    pool: &SqlitePool,
+    path: &str,
+    mtime: i64,
+) -> sqlx::Result<Option<crate::metadata::technical::TechnicalInfo>> {
+    let row: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT ti.info FROM technical_info ti
+        JOIN tracks t ON t.id = ti.track_id
+        WHERE t.path_key = ? AND ti.mtime = ?
+        "#,
+    )
+    .bind(paths::key(path))
+    .bind(mtime)
+    .fetch_optional(pool)
+    .await?;
+    // Info stored by an older version that no longer parses is probed again
+    Ok(row.and_then(|(json,)| serde_json::from_str(&json).ok()))
+}
+
+/// Cache the technical info of the track at `path`, probed at file
+/// modification time `mtime`.
+pub async fn store_technical_info(
+    pool: &SqlitePool,
+    path: &str,
+    mtime: i64,
+    info: &crate::metadata::technical::TechnicalInfo,
+) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Caching technical info")?;
+    let json = serde_json::to_string(info).map_err(|e| sqlx::Error::Encode(e.into()))?;
+    sqlx::query(
+        r#"
+        INSERT INTO technical_info (track_id, mtime, info, probed_at)
+        SELECT id, ?, ?, unixepoch() FROM tracks WHERE path_key = ?
+        ON CONFLICT(track_id) DO UPDATE SET
+            mtime = excluded.mtime,
+            info = excluded.info,
+            probed_at = excluded.probed_at
+        "#,
+    )
+    .bind(mtime)
+    .bind(json)
+    .bind(paths::key(path))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 /// Delete a track by path.
 ///
 /// Used when a file is detected as removed from the filesystem.
 pub async fn delete_track_by_path(pool: &SqlitePool, path: &str) -> sqlx::Result<bool> {
-    let result = sqlx::query("DELETE FROM tracks WHERE path = ?")
-        .bind(path)
+    crate::readonly::ensure_writable("Removing tracks")?;
+    let result = sqlx::query("DELETE FROM tracks WHERE path_key = ?")
+        .bind(paths::key(path))
         .execute(pool)
         .await?;
     Ok(result.rows_affected() > 0)
@@ -522,8 +1016,8 @@ pub async fn get_track_by_path(
     pool: &SqlitePool,
     path: &str,
 ) -> sqlx::Result<Option<TrackFileInfo>> {
-    sqlx::query_as::<_, TrackFileInfo>("SELECT id, path, mtime FROM tracks WHERE path = ?")
-        .bind(path)
+    sqlx::query_as::<_, TrackFileInfo>("SELECT id, path, mtime FROM tracks WHERE path_key = ?")
+        .bind(paths::key(path))
         .fetch_optional(pool)
         .await
 }
@@ -542,6 +1036,7 @@ pub async fn update_track_quality(
     track_id: i64,
     quality: &TrackQuality,
 ) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Storing quality results")?;
     let now = chrono::Utc::now().to_rfc3339();
     sqlx::query(
         r#"UPDATE tracks SET 
@@ -563,6 +1058,27 @@ pub async fn update_track_quality(
     Ok(())
 }
 
+/// Set or clear a track's mislabeled-suspect quality flag (see
+/// [`crate::library::verify_tags`]). Tracks never assessed get it too, so the
+/// gardener keeps it when it gets to them.
+pub async fn set_mislabeled_suspect(
+    pool: &SqlitePool,
+    track_id: i64,
+    suspect: bool,
+) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Storing tag verification")?;
+    let flag = crate::health::QualityFlags::MISLABELED_SUSPECT.to_bits_i64();
+    sqlx::query(
+        "UPDATE tracks SET quality_flags = (COALESCE(quality_flags, 0) & ~?) | ? WHERE id = ?",
+    )
+    .bind(flag)
+    .bind(if suspect { flag } else { 0 })
+    .bind(track_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 /// Get tracks that need quality assessment.
 ///
 /// Returns tracks that have never been checked or have changed since last check.
@@ -578,7 +1094,11 @@ pub async fn get_tracks_needing_quality_check(
             COALESCE(a.name, 'Unknown Artist') as artist_name,
             COALESCE(al.title, 'Unknown Album') as album_name,
             al.year,
-            t.quality_score, t.quality_flags
+            t.quality_score, t.quality_flags,
+            t.added_at, t.updated_at, t.track_number_inferred,
+            t.replaygain_track_gain AS track_gain, t.replaygain_track_peak AS track_peak,
+            t.leading_silence_ms, t.trailing_silence_ms,
+            t.language, t.explicit, t.numbering_inconsistent, t.source
         FROM tracks t
         LEFT JOIN artists a ON t.artist_id = a.id
         LEFT JOIN albums al ON t.album_id = al.id
@@ -593,6 +1113,41 @@ pub async fn get_tracks_needing_quality_check(
     .await
 }
 
+/// Get tracks whose quality was last assessed before `before`.
+///
+/// Oldest assessments first, up to `limit`. `before` is an RFC 3339 UTC
+/// timestamp, the format [`update_track_quality`] stores.
+pub async fn get_tracks_quality_checked_before(
+    pool: &SqlitePool,
+    before: &str,
+    limit: u32,
+) -> sqlx::Result<Vec<TrackWithMetadata>> {
+    sqlx::query_as::<_, TrackWithMetadata>(
+        r#"
+        SELECT 
+            t.id, t.title, t.path, t.duration, t.track_number,
+            COALESCE(a.name, 'Unknown Artist') as artist_name,
+            COALESCE(al.title, 'Unknown Album') as album_name,
+            al.year,
+            t.quality_score, t.quality_flags,
+            t.added_at, t.updated_at, t.track_number_inferred,
+            t.replaygain_track_gain AS track_gain, t.replaygain_track_peak AS track_peak,
+            t.leading_silence_ms, t.trailing_silence_ms,
+            t.language, t.explicit, t.numbering_inconsistent, t.source
+        FROM tracks t
+        LEFT JOIN artists a ON t.artist_id = a.id
+        LEFT JOIN albums al ON t.album_id = al.id
+        WHERE t.quality_checked_at < ?
+        ORDER BY t.quality_checked_at
+        LIMIT ?
+        "#,
+    )
+    .bind(before)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
 /// Get tracks needing attention (quality score < threshold).
 ///
 /// Returns tracks that would benefit from enrichment.
@@ -607,7 +1162,11 @@ pub async fn get_tracks_needing_attention(
             COALESCE(a.name, 'Unknown Artist') as artist_name,
             COALESCE(al.title, 'Unknown Album') as album_name,
             al.year,
-            t.quality_score, t.quality_flags
+            t.quality_score, t.quality_flags,
+            t.added_at, t.updated_at, t.track_number_inferred,
+            t.replaygain_track_gain AS track_gain, t.replaygain_track_peak AS track_peak,
+            t.leading_silence_ms, t.trailing_silence_ms,
+            t.language, t.explicit, t.numbering_inconsistent, t.source
         FROM tracks t
         LEFT JOIN artists a ON t.artist_id = a.id
         LEFT JOIN albums al ON t.album_id = al.id
@@ -716,6 +1275,7 @@ pub async fn upsert_track_match(
     title_similarity: Option<f32>,
     artist_similarity: Option<f32>,
 ) -> sqlx::Result<i64> {
+    crate::readonly::ensure_writable("Storing matches")?;
     let result = sqlx::query(
         r#"INSERT INTO track_matches 
            (track_id, source, confidence, recording_id, recording_title, 
@@ -756,6 +1316,7 @@ pub async fn upsert_match_release(
     is_original_release: bool,
     is_compilation: bool,
 ) -> sqlx::Result<i64> {
+    crate::readonly::ensure_writable("Storing matches")?;
     let result = sqlx::query(
         r#"INSERT INTO match_releases
            (match_id, release_id, release_title, release_artist, release_year,
@@ -782,7 +1343,9 @@ pub async fn upsert_match_release(
     Ok(sqlx::Row::get(&result, 0))
 }
 
-/// Get all matches for a track, ordered by confidence.
+/// Get all matches for a track, most likely first: confidence weighed by
+/// how well the title and artist agree with the tags (as
+/// `TrackIdentification::review_score` does).
 pub async fn get_track_matches(pool: &SqlitePool, track_id: i64) -> sqlx::Result<Vec<TrackMatch>> {
     sqlx::query_as::<_, TrackMatch>(
         r#"SELECT id, track_id, source, confidence, recording_id,
@@ -790,7 +1353,8 @@ pub async fn get_track_matches(pool: &SqlitePool, track_id: i64) -> sqlx::Result
                   artist_similarity, is_selected, is_rejected
            FROM track_matches
            WHERE track_id = ?
-           ORDER BY confidence DESC"#,
+           ORDER BY confidence * (0.5 + 0.25 * COALESCE(title_similarity, 1.0)
+                                      + 0.25 * COALESCE(artist_similarity, 1.0)) DESC"#,
     )
     .bind(track_id)
     .fetch_all(pool)
@@ -817,6 +1381,7 @@ pub async fn get_match_releases(
 
 /// Mark a match as selected (user chose this one).
 pub async fn select_track_match(pool: &SqlitePool, match_id: i64) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Selecting matches")?;
     // First, get the track_id for this match
     let track_id: (i64,) = sqlx::query_as("SELECT track_id FROM track_matches WHERE id = ?")
         .bind(match_id)
@@ -842,6 +1407,7 @@ pub async fn select_track_match(pool: &SqlitePool, match_id: i64) -> sqlx::Resul
 
 /// Mark a match as rejected (user doesn't want this one).
 pub async fn reject_track_match(pool: &SqlitePool, match_id: i64) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Rejecting matches")?;
     sqlx::query(
         "UPDATE track_matches SET is_rejected = TRUE, reviewed_at = CURRENT_TIMESTAMP WHERE id = ?",
     )
@@ -853,6 +1419,7 @@ pub async fn reject_track_match(pool: &SqlitePool, match_id: i64) -> sqlx::Resul
 
 /// Mark a release as preferred for a match.
 pub async fn prefer_release(pool: &SqlitePool, release_id: i64) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Choosing releases")?;
     // Get the match_id for this release
     let match_id: (i64,) = sqlx::query_as("SELECT match_id FROM match_releases WHERE id = ?")
         .bind(release_id)
@@ -895,6 +1462,7 @@ pub async fn get_tracks_with_pending_matches(
 
 /// Delete all matches for a track (e.g., before re-fingerprinting).
 pub async fn clear_track_matches(pool: &SqlitePool, track_id: i64) -> sqlx::Result<u64> {
+    crate::readonly::ensure_writable("Clearing matches")?;
     let result = sqlx::query("DELETE FROM track_matches WHERE track_id = ?")
         .bind(track_id)
         .execute(pool)
@@ -1006,6 +1574,82 @@ mod tests {
         assert_eq!(album_id1, album_id2);
     }
 
+    #[tokio::test]
+    async fn test_album_rollups_follow_tracks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", temp_dir.path().join("test.db").display());
+        let pool = init_db(&db_url).await.unwrap();
+
+        let artist_id = get_or_create_artist(&pool, "Artist").await.unwrap();
+        let album = get_or_create_album(&pool, "Album", Some(artist_id))
+            .await
+            .unwrap();
+        let other = get_or_create_album(&pool, "Other", Some(artist_id))
+            .await
+            .unwrap();
+        let track = |title: &str, duration| TrackMetadata {
+            title: title.to_string(),
+            artist: "Artist".to_string(),
+            album: "Album".to_string(),
+            duration,
+            track_number: None,
+        };
+        let one = insert_track(&pool, &track("One", 200), "/m/1.flac", None, Some(album))
+            .await
+            .unwrap();
+        let two = insert_track(&pool, &track("Two", 100), "/m/2.flac", None, Some(album))
+            .await
+            .unwrap();
+        update_track_bitrate(&pool, one, Some(1000)).await.unwrap();
+        // A rescan rewriting the same values changes nothing
+        insert_track(&pool, &track("One", 200), "/m/1.flac", None, Some(album))
+            .await
+            .unwrap();
+
+        let summary = get_album_summary(&pool, "Album", "Artist")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(summary.track_count, 2);
+        assert_eq!(summary.total_duration, 300);
+        assert_eq!(summary.avg_bitrate, Some(1000));
+
+        update_track_bitrate(&pool, two, Some(500)).await.unwrap();
+        sqlx::query("UPDATE tracks SET album_id = ? WHERE id = ?")
+            .bind(other)
+            .bind(one)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let summaries = get_album_summaries(&pool).await.unwrap();
+        let counts: Vec<_> = summaries
+            .iter()
+            .map(|a| {
+                (
+                    a.title.as_str(),
+                    a.track_count,
+                    a.total_duration,
+                    a.avg_bitrate,
+                )
+            })
+            .collect();
+        assert_eq!(
+            counts,
+            [("Album", 1, 100, Some(500)), ("Other", 1, 200, Some(1000))]
+        );
+
+        delete_track_by_path(&pool, "/m/2.flac").await.unwrap();
+        let summaries = get_album_summaries(&pool).await.unwrap();
+        assert_eq!(summaries.len(), 1, "empty albums are left out");
+        assert_eq!(summaries[0].title, "Other");
+        assert!(
+            get_album_summary(&pool, "Album", "Artist")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
     #[tokio::test]
     async fn test_track_insertion_and_update() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -1148,4 +1792,396 @@ mod tests {
         assert_eq!(track1.path, "/new/path1.mp3");
         assert_eq!(track2.path, "/new/path2.mp3");
     }
+
+    #[tokio::test]
+    async fn test_track_dates_set_on_insert_and_kept_on_rescan() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite:{}", db_path.display());
+        let pool = init_db(&db_url).await.unwrap();
+
+        let meta = TrackMetadata {
+            title: "Test Song".to_string(),
+            artist: "Test Artist".to_string(),
+            album: "Test Album".to_string(),
+            duration: 180,
+            track_number: Some(1),
+        };
+        insert_track(&pool, &meta, "/test/dated.mp3", None, None)
+            .await
+            .unwrap();
+
+        // Pretend the track was added long ago, then rescan it
+        sqlx::query("UPDATE tracks SET added_at = 1000, updated_at = 1000")
+            .execute(&pool)
+            .await
+            .unwrap();
+        insert_track(&pool, &meta, "/test/dated.mp3", None, None)
+            .await
+            .unwrap();
+
+        let tracks = get_all_tracks_with_metadata(&pool).await.unwrap();
+        assert_eq!(tracks[0].added_at, Some(1000));
+        // Nothing changed, so it wasn't modified
+        assert_eq!(tracks[0].updated_at, Some(1000));
+
+        let retitled = TrackMetadata {
+            title: "Retitled".to_string(),
+            ..meta.clone()
+        };
+        insert_track(&pool, &retitled, "/test/dated.mp3", None, None)
+            .await
+            .unwrap();
+        let tracks = get_all_tracks_with_metadata(&pool).await.unwrap();
+        assert_eq!(tracks[0].added_at, Some(1000));
+        assert!(tracks[0].updated_at.unwrap() > 1000);
+
+        assert!(
+            get_tracks_added_since(&pool, 2000)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(get_tracks_added_since(&pool, 1000).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rescan_with_mtime_bumps_updated_only_on_change() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite:{}", db_path.display());
+        let pool = init_db(&db_url).await.unwrap();
+
+        let meta = TrackMetadata {
+            title: "Test Song".to_string(),
+            artist: "Test Artist".to_string(),
+            album: "Test Album".to_string(),
+            duration: 180,
+            track_number: Some(1),
+        };
+        let path = "/test/mtime.mp3";
+        insert_track_with_mtime(&pool, &meta, path, None, None, 10)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE tracks SET updated_at = 1000")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        insert_track_with_mtime(&pool, &meta, path, None, None, 10)
+            .await
+            .unwrap();
+        let tracks = get_all_tracks_with_metadata(&pool).await.unwrap();
+        assert_eq!(tracks[0].updated_at, Some(1000));
+
+        // The file was rewritten
+        insert_track_with_mtime(&pool, &meta, path, None, None, 20)
+            .await
+            .unwrap();
+        let tracks = get_all_tracks_with_metadata(&pool).await.unwrap();
+        assert!(tracks[0].updated_at.unwrap() > 1000);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_track_dates_from_file_times() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite:{}", db_path.display());
+        let pool = init_db(&db_url).await.unwrap();
+
+        let file = temp_dir.path().join("old.mp3");
+        std::fs::write(&file, b"audio").unwrap();
+        sqlx::query("INSERT INTO tracks (title, path, mtime) VALUES ('Old', ?, 42)")
+            .bind(file.to_string_lossy().to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO tracks (title, path, mtime) VALUES ('Gone', '/missing.mp3', 42)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(backfill_track_dates(&pool).await.unwrap(), 2);
+        // Second run finds nothing left to fill
+        assert_eq!(backfill_track_dates(&pool).await.unwrap(), 0);
+
+        let tracks = get_all_tracks_with_metadata(&pool).await.unwrap();
+        let old = tracks.iter().find(|t| t.title == "Old").unwrap();
+        let gone = tracks.iter().find(|t| t.title == "Gone").unwrap();
+        assert!(old.added_at.unwrap() > 42);
+        // Missing files fall back to the stored mtime
+        assert_eq!(gone.added_at, Some(42));
+        assert_eq!(gone.updated_at, Some(42));
+    }
+
+    #[tokio::test]
+    async fn test_silence_stored_and_reset_on_change() {
+        use crate::player::silence::Silence;
+        use std::time::Duration;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite:{}", db_path.display());
+        let pool = init_db(&db_url).await.unwrap();
+
+        let meta = TrackMetadata {
+            title: "Hidden Track".to_string(),
+            artist: "Test Artist".to_string(),
+            album: "Test Album".to_string(),
+            duration: 600,
+            track_number: Some(12),
+        };
+        let id = insert_track_with_mtime(&pool, &meta, "/test/hidden.flac", None, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_tracks_needing_silence_check(&pool, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let silence = Silence {
+            leading: Duration::from_millis(1500),
+            trailing: Duration::from_secs(240),
+        };
+        update_track_silence(&pool, id, Some(&silence))
+            .await
+            .unwrap();
+        assert!(
+            get_tracks_needing_silence_check(&pool, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            get_track_silence(&pool, "/test/hidden.flac").await.unwrap(),
+            Some(silence)
+        );
+
+        // Rescanning an unchanged file keeps the analysis, a changed one redoes it
+        insert_track_with_mtime(&pool, &meta, "/test/hidden.flac", None, None, 1)
+            .await
+            .unwrap();
+        assert!(
+            get_tracks_needing_silence_check(&pool, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        insert_track_with_mtime(&pool, &meta, "/test/hidden.flac", None, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_tracks_needing_silence_check(&pool, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_language_and_explicit_stored() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite:{}", db_path.display());
+        let pool = init_db(&db_url).await.unwrap();
+
+        let meta = TrackMetadata {
+            title: "Lyrics".to_string(),
+            artist: "Test Artist".to_string(),
+            album: "Test Album".to_string(),
+            duration: 200,
+            track_number: Some(1),
+        };
+        let id = insert_track(&pool, &meta, "/test/lyrics.m4a", None, None)
+            .await
+            .unwrap();
+        let content = crate::metadata::ContentTags {
+            language: Some("fra".to_string()),
+            explicit: Some(false),
+        };
+        update_track_content(&pool, id, &content).await.unwrap();
+
+        let track = get_all_tracks_with_metadata(&pool).await.unwrap().remove(0);
+        assert_eq!(track.language.as_deref(), Some("fra"));
+        assert_eq!(track.explicit, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_imported_ratings_keep_the_highest_play_count() {
+        use crate::metadata::ratings::ImportedRatings;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite:{}", db_path.display());
+        let pool = init_db(&db_url).await.unwrap();
+
+        let meta = TrackMetadata {
+            title: "Rated".to_string(),
+            artist: "Test Artist".to_string(),
+            album: "Test Album".to_string(),
+            duration: 200,
+            track_number: Some(1),
+        };
+        let id = insert_track(&pool, &meta, "/test/rated.mp3", None, None)
+            .await
+            .unwrap();
+        let stored = async || {
+            let track = get_track_ratings(&pool).await.unwrap().remove(0);
+            (track.rating, track.tag_play_count)
+        };
+        assert_eq!(stored().await, (None, None));
+
+        let imported = |rating, play_count| ImportedRatings {
+            rating,
+            play_count,
+            source: None,
+        };
+        update_track_ratings(&pool, id, &imported(Some(80), Some(12)))
+            .await
+            .unwrap();
+        assert_eq!(stored().await, (Some(80), Some(12)));
+
+        // A lower count doesn't replace a higher one; a missing rating keeps it
+        update_track_ratings(&pool, id, &imported(None, Some(5)))
+            .await
+            .unwrap();
+        assert_eq!(stored().await, (Some(80), Some(12)));
+        update_track_ratings(&pool, id, &imported(Some(40), Some(30)))
+            .await
+            .unwrap();
+        assert_eq!(stored().await, (Some(40), Some(30)));
+    }
+
+    #[test]
+    fn test_track_list_memory_budget() {
+        // Under 100 MB for 300k tracks, extrapolated from 30k
+        let tracks = crate::test_utils::synthetic_library(30_000, 1);
+        let bytes = track_list_bytes(&tracks);
+        assert!(bytes * 10 < 100_000_000, "{} bytes for 30k tracks", bytes);
+        assert!(
+            tracks[0].artist_name.ptr_eq(
+                &tracks
+                    .iter()
+                    .rfind(|t| t.artist_name == tracks[0].artist_name)
+                    .unwrap()
+                    .artist_name
+            )
+        );
+    }
+
+    #[tokio::test]
+    #[ignore] // Performance budget - run with `cargo test --release perf_ -- --ignored`
+    async fn perf_large_library_queries() {
+        use crate::test_utils::{insert_synthetic_library, synthetic_library, within_budget_async};
+        use std::time::Duration;
+
+        let n = crate::test_utils::perf_library_size();
+        let (pool, _dir) = crate::test_utils::temp_db().await;
+        insert_synthetic_library(&pool, &synthetic_library(n, 1)).await;
+
+        let tracks = within_budget_async(
+            "load library",
+            n,
+            Duration::from_millis(2500),
+            get_all_tracks_with_metadata(&pool),
+        )
+        .await
+        .unwrap();
+        assert_eq!(tracks.len(), n);
+
+        within_budget_async(
+            "count tracks",
+            n,
+            Duration::from_millis(50),
+            count_tracks(&pool),
+        )
+        .await
+        .unwrap();
+        let last_page = (n as i64 - 500).max(0);
+        within_budget_async(
+            "last page",
+            n,
+            Duration::from_millis(100),
+            get_tracks_paginated(&pool, 500, last_page),
+        )
+        .await
+        .unwrap();
+        let month_ago = chrono::Utc::now().timestamp() - 30 * 86_400;
+        within_budget_async(
+            "added in the last month",
+            n,
+            Duration::from_millis(500),
+            get_tracks_added_since(&pool, month_ago),
+        )
+        .await
+        .unwrap();
+        within_budget_async(
+            "file info for rescans",
+            n,
+            Duration::from_millis(600),
+            get_all_track_file_info(&pool),
+        )
+        .await
+        .unwrap();
+        within_budget_async(
+            "album listing",
+            n,
+            Duration::from_millis(1500),
+            crate::completeness::library_albums(&pool),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_technical_info_cached_until_file_changes() {
+        use crate::metadata::technical::TechnicalInfo;
+
+        let (pool, _dir) = crate::test_utils::temp_db().await;
+        let meta = crate::test_utils::mock_track_metadata();
+        insert_track_with_mtime(&pool, &meta, "/test/probed.mp3", None, None, 1)
+            .await
+            .unwrap();
+        let info = TechnicalInfo {
+            codec: "MP3".to_string(),
+            encoder: Some("LAME 3.100".to_string()),
+            true_peak_db: Some(0.4),
+            ..Default::default()
+        };
+
+        store_technical_info(&pool, "/test/probed.mp3", 1, &info)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_technical_info(&pool, "/test/probed.mp3", 1)
+                .await
+                .unwrap(),
+            Some(info.clone())
+        );
+        assert_eq!(
+            get_technical_info(&pool, "/test/probed.mp3", 2)
+                .await
+                .unwrap(),
+            None
+        );
+
+        // Probing again replaces the cached info
+        let reprobed = TechnicalInfo {
+            true_peak_db: Some(-1.0),
+            ..info
+        };
+        store_technical_info(&pool, "/test/probed.mp3", 2, &reprobed)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_technical_info(&pool, "/test/probed.mp3", 2)
+                .await
+                .unwrap(),
+            Some(reprobed)
+        );
+    }
 }
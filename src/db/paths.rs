@@ -0,0 +1,547 @@
+//! Canonical track paths.
+//!
+//! One file can reach the library under several spellings: `D:\Music\x.mp3`
+//! and `d:\music\X.MP3` on Windows, `\\NAS\music\x.mp3` and the drive letter
+//! that share is mapped to, or a symlinked folder and the folder it points
+//! at. Tracks are stored under their [`canonical`] path and matched by its
+//! [`key`]:
+//! - separators, `.` and `..` and the `\\?\` prefix are tidied up, and the
+//!   drive letter is upper-cased;
+//! - `library.path_aliases` rewrites one prefix to another (a share to the
+//!   drive it's mapped to, or one mount point to another);
+//! - with `library.resolve_symlinks`, files that exist are stored under
+//!   their real location;
+//! - in portable mode, paths on the executable's drive are stored relative
+//!   to its root, so they survive the drive getting another letter;
+//! - the key is lower-cased for Windows paths, and for all paths when
+//!   `library.case_insensitive_paths` is set (the default on macOS).
+//!
+//! Rows stored before keys existed are merged by [`normalize_track_paths`]
+//! when the database is opened.
+//!
+//! Stream and cloud drive URLs are stored as given: they aren't paths.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::RwLock;
+
+use sqlx::SqlitePool;
+
+use crate::config::LibraryConfig;
+use crate::model::TrackSource;
+
+/// How paths are canonicalized (from the `[library]` config)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathPolicy {
+    /// Prefix -> the prefix it's stored as, both canonicalized
+    aliases: Vec<(String, String)>,
+    resolve_symlinks: bool,
+    case_insensitive: bool,
+    /// Drive root that paths under it are stored relative to (portable mode)
+    relative_root: Option<String>,
+}
+
+static POLICY: RwLock<Option<PathPolicy>> = RwLock::new(None);
+
+impl PathPolicy {
+    pub fn new(
+        aliases: &BTreeMap<String, String>,
+        resolve_symlinks: bool,
+        case_insensitive: bool,
+    ) -> Self {
+        let mut aliases: Vec<(String, String)> = aliases
+            .iter()
+            .map(|(from, to)| (clean(from), clean(to)))
+            .filter(|(from, to)| !from.is_empty() && from != to)
+            .collect();
+        // Longest prefix first, so a nested alias wins over its parent
+        aliases.sort_by_key(|(from, _)| std::cmp::Reverse(from.len()));
+        Self {
+            aliases,
+            resolve_symlinks,
+            case_insensitive,
+            relative_root: None,
+        }
+    }
+
+    /// Store paths under `root` relative to it. Relative paths are opened
+    /// from the working directory, which portable mode sets to `root`.
+    pub fn relative_to(mut self, root: &Path) -> Self {
+        self.relative_root = Some(clean(&root.to_string_lossy()));
+        self
+    }
+
+    pub fn from_config(library: &LibraryConfig) -> Self {
+        Self::new(
+            &library.path_aliases,
+            library.resolve_symlinks,
+            library.case_insensitive_paths,
+        )
+    }
+
+    /// The form a path is stored in
+    pub fn canonical(&self, path: &str) -> String {
+        if !TrackSource::detect(path).is_local() {
+            return path.trim().to_string();
+        }
+        let mut path = clean(path);
+        if self.resolve_symlinks
+            && let Ok(real) = std::fs::canonicalize(&path)
+        {
+            path = clean(&real.to_string_lossy());
+        }
+        for (from, to) in &self.aliases {
+            if let Some(rest) = self.strip_prefix(&path, from) {
+                path = format!("{}{}", to, rest);
+                break;
+            }
+        }
+        if let Some(root) = &self.relative_root
+            && let Some(rest) = self.strip_prefix(&path, root)
+        {
+            let rest = rest.trim_start_matches(['/', '\\']);
+            path = if rest.is_empty() { "." } else { rest }.to_string();
+        }
+        path
+    }
+
+    /// What paths are matched by: two paths with the same key are one file
+    pub fn key(&self, path: &str) -> String {
+        let path = self.canonical(path);
+        if TrackSource::detect(&path).is_local() && self.folds_case(&path) {
+            path.to_lowercase()
+        } else {
+            path
+        }
+    }
+
+    fn folds_case(&self, path: &str) -> bool {
+        self.case_insensitive
+            || is_windows_path(path)
+            || (!path.starts_with('/')
+                && self.relative_root.as_deref().is_some_and(is_windows_path))
+    }
+
+    /// `path` minus `prefix`, if `prefix` is whole leading components of it
+    fn strip_prefix<'a>(&self, path: &'a str, prefix: &str) -> Option<&'a str> {
+        let head = path.get(..prefix.len())?;
+        let same = if self.folds_case(path) {
+            head.to_lowercase() == prefix.to_lowercase()
+        } else {
+            head == prefix
+        };
+        let rest = &path[prefix.len()..];
+        let boundary =
+            rest.is_empty() || rest.starts_with(['/', '\\']) || prefix.ends_with(['/', '\\']);
+        (same && boundary).then_some(rest)
+    }
+}
+
+/// Use `policy` for every path from now on (set at startup from the config)
+pub fn set_policy(policy: PathPolicy) {
+    *POLICY.write().unwrap_or_else(|e| e.into_inner()) = Some(policy);
+}
+
+/// The policy in use: the one set at startup, or the config's defaults
+pub fn policy() -> PathPolicy {
+    POLICY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| PathPolicy::from_config(&LibraryConfig::default()))
+}
+
+/// The stored form of `path` under the current policy
+pub fn canonical(path: &str) -> String {
+    policy().canonical(path)
+}
+
+/// The matching key of `path` under the current policy
+pub fn key(path: &str) -> String {
+    policy().key(path)
+}
+
+/// `X:\...`, `X:/...` or `\\server\share\...`
+fn is_windows_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with("\\\\")
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'\\' | b'/'))
+}
+
+/// Tidy a path without touching the file system
+fn clean(path: &str) -> String {
+    let path = path.trim();
+    // Verbatim prefixes: \\?\C:\x and \\?\UNC\server\share\x
+    let path = if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", rest)
+    } else {
+        path.strip_prefix(r"\\?\").unwrap_or(path).to_string()
+    };
+
+    if is_windows_path(&path) {
+        let unified = path.replace('/', "\\");
+        let (root, rest) = if let Some(rest) = unified.strip_prefix("\\\\") {
+            // \\server\share stays the root; .. can't climb above it
+            let mut parts = rest.splitn(3, '\\');
+            let server = parts.next().unwrap_or_default();
+            let share = parts.next().unwrap_or_default();
+            (
+                format!(r"\\{}\{}", server, share),
+                parts.next().unwrap_or_default().to_string(),
+            )
+        } else {
+            let drive = unified[..1].to_ascii_uppercase();
+            (format!("{}:", drive), unified[2..].to_string())
+        };
+        let parts = components(&rest, '\\', true);
+        if parts.is_empty() {
+            format!("{}\\", root)
+        } else {
+            format!("{}\\{}", root, parts.join("\\"))
+        }
+    } else {
+        let absolute = path.starts_with('/');
+        let parts = components(&path, '/', absolute);
+        match (absolute, parts.is_empty()) {
+            (true, _) => format!("/{}", parts.join("/")),
+            (false, true) => ".".to_string(),
+            (false, false) => parts.join("/"),
+        }
+    }
+}
+
+/// Path components with empty and `.` parts dropped and `..` applied (an
+/// absolute path can't climb above its root)
+fn components(path: &str, separator: char, absolute: bool) -> Vec<&str> {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split(separator) {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|p| *p != "..") => {
+                parts.pop();
+            }
+            ".." if absolute => {}
+            _ => parts.push(part),
+        }
+    }
+    parts
+}
+
+/// Canonicalize stored track paths and merge tracks that turn out to be the
+/// same file.
+///
+/// With `all` false only tracks without a key yet are looked at (fast enough
+/// to run whenever the database opens); `all` redoes every track, after the
+/// path settings change. Of the duplicates, the track whose file exists is
+/// kept (the oldest if several do). It takes the others' plays, playlist
+/// entries, rating and play count; they are then removed. Returns how many
+/// were removed.
+///
+/// In read-only mode nothing is changed: the duplicates found are only
+/// logged, and none are reported removed.
+pub async fn normalize_track_paths(
+    pool: &SqlitePool,
+    policy: &PathPolicy,
+    all: bool,
+) -> sqlx::Result<usize> {
+    if !all {
+        let (unkeyed,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM tracks WHERE path_key IS NULL")
+                .fetch_one(pool)
+                .await?;
+        if unkeyed == 0 {
+            return Ok(0);
+        }
+    }
+
+    let rows: Vec<(i64, String, Option<String>)> =
+        sqlx::query_as("SELECT id, path, path_key FROM tracks ORDER BY id")
+            .fetch_all(pool)
+            .await?;
+    let mut groups: HashMap<String, Vec<(i64, String, Option<String>)>> = HashMap::new();
+    for row in rows {
+        groups.entry(policy.key(&row.1)).or_default().push(row);
+    }
+
+    if crate::readonly::is_enabled() {
+        let duplicates: usize = groups.values().map(|g| g.len() - 1).sum();
+        if duplicates > 0 {
+            tracing::warn!(
+                "{} tracks are stored under two spellings of one path; not merging them in read-only mode",
+                duplicates
+            );
+        }
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    let mut tx = pool.begin().await?;
+    // No file moves or leaves the library: log the pass as path_normalized
+    sqlx::query("INSERT INTO activity_path_pass (kind) VALUES ('path_normalized')")
+        .execute(&mut *tx)
+        .await?;
+    // Keys are unique: clear the ones about to change before setting any
+    for (key, group) in &groups {
+        for (id, _, old_key) in group {
+            if old_key.as_deref() != Some(key.as_str()) {
+                sqlx::query("UPDATE tracks SET path_key = NULL WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+    }
+    for (key, mut group) in groups {
+        let keep = group
+            .iter()
+            .position(|(_, path, _)| Path::new(path).exists())
+            .unwrap_or(0);
+        let (keep_id, keep_path, keep_key) = group.swap_remove(keep);
+
+        for (id, path, _) in group {
+            tracing::info!("Merging duplicate track {} into {}", path, keep_path);
+            for table in ["play_history", "playlist_tracks"] {
+                sqlx::query(&format!(
+                    "UPDATE {table} SET track_id = ? WHERE track_id = ?"
+                ))
+                .bind(keep_id)
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            sqlx::query(
+                "UPDATE tracks SET \
+                   rating = COALESCE(rating, (SELECT rating FROM tracks WHERE id = ?2)), \
+                   tag_play_count = MAX(COALESCE(tag_play_count, 0), \
+                     COALESCE((SELECT tag_play_count FROM tracks WHERE id = ?2), 0)) \
+                 WHERE id = ?1",
+            )
+            .bind(keep_id)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("DELETE FROM tracks WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            removed += 1;
+        }
+
+        let canonical = policy.canonical(&keep_path);
+        if canonical != keep_path || keep_key.as_deref() != Some(key.as_str()) {
+            sqlx::query("UPDATE tracks SET path = ?, path_key = ? WHERE id = ?")
+                .bind(&canonical)
+                .bind(&key)
+                .bind(keep_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    sqlx::query("DELETE FROM activity_path_pass")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::playlists;
+    use crate::test_utils::temp_db;
+
+    fn folding() -> PathPolicy {
+        PathPolicy::new(&BTreeMap::new(), false, true)
+    }
+
+    #[test]
+    fn test_clean_windows_paths() {
+        assert_eq!(clean(r"d:\Music\.\Rock\..\x.mp3"), r"D:\Music\x.mp3");
+        assert_eq!(clean("D:/Music//x.mp3"), r"D:\Music\x.mp3");
+        assert_eq!(clean(r"\\?\D:\Music\x.mp3"), r"D:\Music\x.mp3");
+        assert_eq!(clean(r"\\?\UNC\NAS\music\x.mp3"), r"\\NAS\music\x.mp3");
+        assert_eq!(clean(r"\\NAS\music\..\..\x.mp3"), r"\\NAS\music\x.mp3");
+        assert_eq!(clean(r"D:\"), r"D:\");
+    }
+
+    #[test]
+    fn test_clean_unix_paths() {
+        assert_eq!(clean("/music//rock/./x.mp3"), "/music/rock/x.mp3");
+        assert_eq!(clean("/music/rock/../x.mp3"), "/music/x.mp3");
+        assert_eq!(clean("/music/"), "/music");
+        assert_eq!(clean("../x.mp3"), "../x.mp3");
+    }
+
+    #[test]
+    fn test_windows_keys_ignore_case() {
+        let policy = PathPolicy::default();
+        assert_eq!(policy.key(r"D:\Music\x.mp3"), policy.key(r"d:\music\X.MP3"));
+        // Keys fold, stored paths keep their case
+        assert_eq!(policy.canonical(r"d:\music\X.MP3"), r"D:\music\X.MP3");
+        // Unix paths only fold when asked to
+        assert_ne!(policy.key("/Music/x.mp3"), policy.key("/music/x.mp3"));
+        assert_eq!(folding().key("/Music/x.mp3"), folding().key("/music/x.mp3"));
+    }
+
+    #[test]
+    fn test_urls_kept_as_given() {
+        let url = "https://radio.example//Live/./Stream.mp3?x=1";
+        assert_eq!(folding().canonical(url), url);
+        assert_eq!(folding().key(url), url);
+    }
+
+    #[test]
+    fn test_aliases_map_shares_to_drives() {
+        let aliases = BTreeMap::from([
+            (r"\\NAS\music".to_string(), r"M:\".to_string()),
+            ("/mnt/nas".to_string(), "/media/music".to_string()),
+        ]);
+        let policy = PathPolicy::new(&aliases, false, false);
+        assert_eq!(
+            policy.canonical(r"\\nas\Music\Rock\x.mp3"),
+            r"M:\Rock\x.mp3"
+        );
+        assert_eq!(policy.key(r"\\NAS\music\x.mp3"), policy.key(r"m:\X.mp3"));
+        assert_eq!(policy.canonical("/mnt/nas/x.mp3"), "/media/music/x.mp3");
+        // Only whole components match
+        assert_eq!(policy.canonical("/mnt/nas2/x.mp3"), "/mnt/nas2/x.mp3");
+    }
+
+    #[test]
+    fn test_relative_to_drive_root() {
+        let windows = PathPolicy::default().relative_to(Path::new(r"e:\"));
+        assert_eq!(windows.canonical(r"E:\Music\x.mp3"), r"Music\x.mp3");
+        assert_eq!(windows.key(r"e:\music\X.mp3"), windows.key(r"Music\x.mp3"));
+        // Other drives stay absolute
+        assert_eq!(windows.canonical(r"C:\Music\x.mp3"), r"C:\Music\x.mp3");
+        // Stored relative paths are left as they are
+        assert_eq!(windows.canonical(r"Music\x.mp3"), r"Music\x.mp3");
+
+        let unix = PathPolicy::default().relative_to(Path::new("/media/stick"));
+        assert_eq!(unix.canonical("/media/stick/Music/x.mp3"), "Music/x.mp3");
+        assert_eq!(unix.canonical("/media/stick2/x.mp3"), "/media/stick2/x.mp3");
+        assert_ne!(unix.key("Music/x.mp3"), unix.key("music/x.mp3"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real");
+        std::fs::create_dir(&real).unwrap();
+        std::fs::write(real.join("x.mp3"), b"").unwrap();
+        std::os::unix::fs::symlink(&real, dir.path().join("link")).unwrap();
+        let linked = dir.path().join("link/x.mp3").to_string_lossy().to_string();
+
+        let kept = PathPolicy::new(&BTreeMap::new(), false, false);
+        assert_eq!(kept.canonical(&linked), linked);
+        let resolved = PathPolicy::new(&BTreeMap::new(), true, false);
+        let expected = std::fs::canonicalize(real.join("x.mp3")).unwrap();
+        assert_eq!(resolved.canonical(&linked), expected.to_string_lossy());
+    }
+
+    #[tokio::test]
+    async fn test_normalize_merges_duplicates() {
+        let (pool, _dir) = temp_db().await;
+        // As stored before paths had keys
+        for (id, path, rating) in [
+            (1, r"D:\Music\x.mp3", None),
+            (2, r"d:\music\X.MP3", Some(80)),
+            (3, r"D:/Music/y.mp3", None),
+        ] {
+            sqlx::query("INSERT INTO tracks (id, title, path, rating) VALUES (?, 't', ?, ?)")
+                .bind(id)
+                .bind(path)
+                .bind(rating)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("INSERT INTO play_history (track_id, played_at) VALUES (2, 100)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let removed = normalize_track_paths(&pool, &PathPolicy::default(), false)
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+
+        let rows: Vec<(i64, String, Option<i64>)> =
+            sqlx::query_as("SELECT id, path, rating FROM tracks ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (1, r"D:\Music\x.mp3".to_string(), Some(80)),
+                (3, r"D:\Music\y.mp3".to_string(), None),
+            ]
+        );
+        let (plays,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM play_history WHERE track_id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(plays, 1);
+
+        // Nothing moved or left the library, so neither is logged as such
+        let kinds: Vec<(String, String)> = sqlx::query_as(
+            "SELECT kind, path FROM activity_log WHERE kind != 'track_added' ORDER BY path",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            kinds,
+            vec![
+                ("path_normalized".to_string(), r"D:\Music\y.mp3".to_string()),
+                ("path_normalized".to_string(), r"d:\music\X.MP3".to_string()),
+            ]
+        );
+
+        // Nothing left to do the next time
+        let again = normalize_track_paths(&pool, &PathPolicy::default(), false)
+            .await
+            .unwrap();
+        assert_eq!(again, 0);
+    }
+
+    #[tokio::test]
+    async fn test_normalize_keeps_playlist_entries() {
+        let (pool, _dir) = temp_db().await;
+        for (id, path) in [
+            (1, r"D:\Music\x.mp3"),
+            (2, r"d:\music\X.MP3"),
+            (3, r"D:\Music\y.mp3"),
+        ] {
+            sqlx::query("INSERT INTO tracks (id, title, path) VALUES (?, 't', ?)")
+                .bind(id)
+                .bind(path)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        let playlist = playlists::create(&pool, "Mix").await.unwrap();
+        playlists::add_tracks(&pool, playlist, &[2, 3, 1])
+            .await
+            .unwrap();
+
+        let removed = normalize_track_paths(&pool, &PathPolicy::default(), false)
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+
+        let ids: Vec<i64> = playlists::tracks(&pool, playlist)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.track_id)
+            .collect();
+        assert_eq!(ids, vec![1, 3, 1]);
+    }
+}
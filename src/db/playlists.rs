@@ -0,0 +1,279 @@
+//! Playlists: named, ordered sets of library tracks.
+//!
+//! Entries are addressed by their position in the playlist, as shown.
+//! Every change rewrites the playlist's positions as 0..n, so gaps left by
+//! tracks removed from the library close on the next edit.
+
+use sqlx::sqlite::SqlitePool;
+use sqlx::{Sqlite, Transaction};
+
+/// A playlist with its length, as listed in the Playlists pane
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct Playlist {
+    pub id: i64,
+    pub name: String,
+    /// Entries, counting a track once per time it appears
+    pub track_count: i64,
+    /// Seconds
+    pub total_duration: i64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// One entry of a playlist, with what the pane shows of its track
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct PlaylistTrack {
+    pub track_id: i64,
+    pub title: String,
+    pub artist_name: String,
+    pub album_title: String,
+    pub path: String,
+    /// Seconds
+    pub duration: Option<i64>,
+}
+
+/// Every playlist, by name
+pub async fn list(pool: &SqlitePool) -> sqlx::Result<Vec<Playlist>> {
+    sqlx::query_as::<_, Playlist>(
+        r#"SELECT p.id, p.name, p.created_at, p.updated_at,
+                  COUNT(t.id) AS track_count,
+                  COALESCE(SUM(t.duration), 0) AS total_duration
+           FROM playlists p
+           LEFT JOIN playlist_tracks pt ON pt.playlist_id = p.id
+           LEFT JOIN tracks t ON t.id = pt.track_id
+           GROUP BY p.id
+           ORDER BY p.name COLLATE NOCASE"#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Create an empty playlist; returns its ID. Names are unique, ignoring case.
+pub async fn create(pool: &SqlitePool, name: &str) -> sqlx::Result<i64> {
+    crate::readonly::ensure_writable("Creating playlists")?;
+    let result = sqlx::query(
+        "INSERT INTO playlists (name, created_at, updated_at) VALUES (?, unixepoch(), unixepoch())",
+    )
+    .bind(name.trim())
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// Rename a playlist
+pub async fn rename(pool: &SqlitePool, playlist_id: i64, name: &str) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Renaming playlists")?;
+    sqlx::query("UPDATE playlists SET name = ?, updated_at = unixepoch() WHERE id = ?")
+        .bind(name.trim())
+        .bind(playlist_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Delete a playlist and its entries (the tracks stay in the library)
+pub async fn delete(pool: &SqlitePool, playlist_id: i64) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Deleting playlists")?;
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM playlist_tracks WHERE playlist_id = ?")
+        .bind(playlist_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM playlists WHERE id = ?")
+        .bind(playlist_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+/// The tracks of a playlist, in order
+pub async fn tracks(pool: &SqlitePool, playlist_id: i64) -> sqlx::Result<Vec<PlaylistTrack>> {
+    sqlx::query_as::<_, PlaylistTrack>(
+        r#"SELECT t.id AS track_id, t.title, t.path, t.duration,
+                  COALESCE(ar.name, 'Unknown Artist') AS artist_name,
+                  COALESCE(al.title, 'Unknown Album') AS album_title
+           FROM playlist_tracks pt
+           JOIN tracks t ON t.id = pt.track_id
+           LEFT JOIN artists ar ON ar.id = t.artist_id
+           LEFT JOIN albums al ON al.id = t.album_id
+           WHERE pt.playlist_id = ?
+           ORDER BY pt.position, pt.id"#,
+    )
+    .bind(playlist_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Append tracks to the end of a playlist; returns how many were added
+pub async fn add_tracks(
+    pool: &SqlitePool,
+    playlist_id: i64,
+    track_ids: &[i64],
+) -> sqlx::Result<usize> {
+    crate::readonly::ensure_writable("Adding to playlists")?;
+    let mut tx = pool.begin().await?;
+    let mut entries = entry_ids(&mut tx, playlist_id).await?;
+    for track_id in track_ids {
+        let result = sqlx::query(
+            "INSERT INTO playlist_tracks (playlist_id, track_id, position) VALUES (?, ?, ?)",
+        )
+        .bind(playlist_id)
+        .bind(track_id)
+        .bind(entries.len() as i64)
+        .execute(&mut *tx)
+        .await?;
+        entries.push(result.last_insert_rowid());
+    }
+    renumber(&mut tx, playlist_id, &entries).await?;
+    tx.commit().await?;
+    Ok(track_ids.len())
+}
+
+/// Remove the entries at `positions` from a playlist
+pub async fn remove(pool: &SqlitePool, playlist_id: i64, positions: &[usize]) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Editing playlists")?;
+    let mut tx = pool.begin().await?;
+    let entries = entry_ids(&mut tx, playlist_id).await?;
+    let (removed, kept): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .enumerate()
+        .partition(|(pos, _)| positions.contains(pos));
+    for (_, id) in removed {
+        sqlx::query("DELETE FROM playlist_tracks WHERE id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let kept: Vec<i64> = kept.into_iter().map(|(_, id)| id).collect();
+    renumber(&mut tx, playlist_id, &kept).await?;
+    tx.commit().await
+}
+
+/// Move the entry at `from` so it ends up at `to`, shifting those between
+pub async fn move_track(
+    pool: &SqlitePool,
+    playlist_id: i64,
+    from: usize,
+    to: usize,
+) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Editing playlists")?;
+    let mut tx = pool.begin().await?;
+    let mut entries = entry_ids(&mut tx, playlist_id).await?;
+    if from >= entries.len() {
+        return Ok(());
+    }
+    let entry = entries.remove(from);
+    entries.insert(to.min(entries.len()), entry);
+    renumber(&mut tx, playlist_id, &entries).await?;
+    tx.commit().await
+}
+
+/// Entry IDs of a playlist, in order
+async fn entry_ids(tx: &mut Transaction<'_, Sqlite>, playlist_id: i64) -> sqlx::Result<Vec<i64>> {
+    sqlx::query_scalar("SELECT id FROM playlist_tracks WHERE playlist_id = ? ORDER BY position, id")
+        .bind(playlist_id)
+        .fetch_all(&mut **tx)
+        .await
+}
+
+/// Give `entries` the positions 0..n, and mark the playlist changed
+async fn renumber(
+    tx: &mut Transaction<'_, Sqlite>,
+    playlist_id: i64,
+    entries: &[i64],
+) -> sqlx::Result<()> {
+    for (pos, id) in entries.iter().enumerate() {
+        sqlx::query("UPDATE playlist_tracks SET position = ? WHERE id = ? AND position IS NOT ?")
+            .bind(pos as i64)
+            .bind(id)
+            .bind(pos as i64)
+            .execute(&mut **tx)
+            .await?;
+    }
+    sqlx::query("UPDATE playlists SET updated_at = unixepoch() WHERE id = ?")
+        .bind(playlist_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{insert_mock_track, temp_db};
+
+    async fn ids(pool: &SqlitePool, playlist_id: i64) -> Vec<i64> {
+        tracks(pool, playlist_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.track_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_create_rename_delete() {
+        let (pool, _dir) = temp_db().await;
+        let id = create(&pool, " Road Trip ").await.unwrap();
+        assert!(
+            create(&pool, "road trip").await.is_err(),
+            "names are unique"
+        );
+
+        rename(&pool, id, "Summer").await.unwrap();
+        let playlists = list(&pool).await.unwrap();
+        assert_eq!(playlists.len(), 1);
+        assert_eq!(playlists[0].name, "Summer");
+        assert_eq!(playlists[0].track_count, 0);
+
+        let track = insert_mock_track(&pool, "/music/a.mp3").await;
+        add_tracks(&pool, id, &[track]).await.unwrap();
+        delete(&pool, id).await.unwrap();
+        assert!(list(&pool).await.unwrap().is_empty());
+        let entries: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM playlist_tracks")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_add_move_remove() {
+        let (pool, _dir) = temp_db().await;
+        let id = create(&pool, "Mix").await.unwrap();
+        let a = insert_mock_track(&pool, "/music/a.mp3").await;
+        let b = insert_mock_track(&pool, "/music/b.mp3").await;
+        let c = insert_mock_track(&pool, "/music/c.mp3").await;
+
+        assert_eq!(add_tracks(&pool, id, &[a, b]).await.unwrap(), 2);
+        add_tracks(&pool, id, &[c, a]).await.unwrap();
+        assert_eq!(ids(&pool, id).await, vec![a, b, c, a]);
+        assert_eq!(list(&pool).await.unwrap()[0].track_count, 4);
+
+        move_track(&pool, id, 2, 0).await.unwrap();
+        assert_eq!(ids(&pool, id).await, vec![c, a, b, a]);
+        move_track(&pool, id, 0, 99).await.unwrap();
+        assert_eq!(ids(&pool, id).await, vec![a, b, a, c]);
+
+        remove(&pool, id, &[0, 2]).await.unwrap();
+        assert_eq!(ids(&pool, id).await, vec![b, c]);
+    }
+
+    #[tokio::test]
+    async fn test_removed_track_leaves_playlist() {
+        let (pool, _dir) = temp_db().await;
+        let id = create(&pool, "Mix").await.unwrap();
+        let a = insert_mock_track(&pool, "/music/a.mp3").await;
+        let b = insert_mock_track(&pool, "/music/b.mp3").await;
+        let c = insert_mock_track(&pool, "/music/c.mp3").await;
+        add_tracks(&pool, id, &[a, b, c]).await.unwrap();
+
+        crate::db::delete_track_by_path(&pool, "/music/b.mp3")
+            .await
+            .unwrap();
+        assert_eq!(ids(&pool, id).await, vec![a, c]);
+        // The gap closes with the next change
+        move_track(&pool, id, 1, 0).await.unwrap();
+        assert_eq!(ids(&pool, id).await, vec![c, a]);
+    }
+}
@@ -0,0 +1,253 @@
+//! Schema versions and the guards around upgrading a database.
+//!
+//! A database's schema version is the newest migration applied to it (sqlx
+//! records them in `_sqlx_migrations`). Before migrating, [`prepare_migration`]:
+//! - refuses a database with migrations this build doesn't have: a newer
+//!   Music Minder opened it, and this one can't read what it changed;
+//! - copies a database with migrations pending to `<name>.v<version>.bak`
+//!   beside it, so a bad upgrade can be undone by putting the copy back.
+
+use std::path::{Path, PathBuf};
+
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::SqlitePool;
+
+/// The migrations built into this version, from `migrations/`
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// A migration recorded in a database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    /// When it was applied (UTC, `YYYY-MM-DD HH:MM:SS`)
+    pub installed_on: String,
+    /// False if it failed part-way (sqlx refuses to continue)
+    pub success: bool,
+    /// Whether this build has the migration
+    pub known: bool,
+}
+
+/// A database's migrations, applied and pending
+#[derive(Debug, Clone, Default)]
+pub struct SchemaInfo {
+    pub applied: Vec<AppliedMigration>,
+    /// Migrations of this build not yet applied: (version, description)
+    pub pending: Vec<(i64, String)>,
+}
+
+impl SchemaInfo {
+    /// Newest migration applied, `None` for a new database
+    pub fn version(&self) -> Option<i64> {
+        self.applied.iter().map(|m| m.version).max()
+    }
+
+    /// Newest migration this build has
+    pub fn supported() -> i64 {
+        MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0)
+    }
+
+    /// Applied migrations this build doesn't have
+    pub fn unknown(&self) -> impl Iterator<Item = &AppliedMigration> {
+        self.applied.iter().filter(|m| !m.known)
+    }
+}
+
+/// Opening a database written by a newer version
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "{path} was opened by a newer version of Music Minder (schema {found}; this version supports up to {supported}). Update Music Minder to use it{}",
+    .rollback.as_ref().map(|p| format!(", or put back the copy made before it was upgraded: {}", p.display())).unwrap_or_default()
+)]
+pub struct NewerSchemaError {
+    pub path: String,
+    pub found: i64,
+    pub supported: i64,
+    /// A pre-upgrade copy this version can open
+    pub rollback: Option<PathBuf>,
+}
+
+/// Read which migrations a database has applied, without changing it
+pub async fn schema_info(pool: &SqlitePool) -> sqlx::Result<SchemaInfo> {
+    let has_table: Option<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_optional(pool)
+    .await?;
+    let rows: Vec<(i64, String, String, bool)> = if has_table.is_some() {
+        sqlx::query_as(
+            "SELECT version, description, CAST(installed_on AS TEXT), success
+             FROM _sqlx_migrations ORDER BY version",
+        )
+        .fetch_all(pool)
+        .await?
+    } else {
+        Vec::new()
+    };
+
+    let known = |version: i64| MIGRATOR.iter().any(|m| m.version == version);
+    let pending = MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .filter(|m| !rows.iter().any(|(version, ..)| *version == m.version))
+        .map(|m| (m.version, m.description.to_string()))
+        .collect();
+    Ok(SchemaInfo {
+        applied: rows
+            .into_iter()
+            .map(
+                |(version, description, installed_on, success)| AppliedMigration {
+                    version,
+                    description,
+                    installed_on,
+                    success,
+                    known: known(version),
+                },
+            )
+            .collect(),
+        pending,
+    })
+}
+
+/// The file behind a SQLite URL; `None` for in-memory databases
+pub fn db_file(db_url: &str) -> Option<PathBuf> {
+    let path = db_url
+        .strip_prefix("sqlite://")
+        .or_else(|| db_url.strip_prefix("sqlite:"))
+        .unwrap_or(db_url);
+    let path = path.split('?').next().unwrap_or_default();
+    (!path.is_empty() && !path.contains(":memory:")).then(|| PathBuf::from(path))
+}
+
+/// Where the copy made before upgrading `db` from `version` goes
+pub fn backup_path(db: &Path, version: i64) -> PathBuf {
+    let mut name = db.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+    db.with_file_name(name)
+}
+
+/// Pre-upgrade copies of `db`, oldest schema first
+pub fn backups(db: &Path) -> Vec<(i64, PathBuf)> {
+    let Some(name) = db.file_name().and_then(|n| n.to_str()) else {
+        return Vec::new();
+    };
+    let prefix = format!("{}.v", name);
+    let dir = db.parent().filter(|d| !d.as_os_str().is_empty());
+    let mut found: Vec<(i64, PathBuf)> = std::fs::read_dir(dir.unwrap_or(Path::new(".")))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let version = e
+                .file_name()
+                .to_str()?
+                .strip_prefix(&prefix)?
+                .strip_suffix(".bak")?
+                .parse()
+                .ok()?;
+            Some((version, e.path()))
+        })
+        .collect();
+    found.sort();
+    found
+}
+
+/// Check a database before migrating it; back it up if it's about to be
+/// upgraded. Returns the backup's path, if one was made.
+pub async fn prepare_migration(pool: &SqlitePool, db_url: &str) -> sqlx::Result<Option<PathBuf>> {
+    let info = schema_info(pool).await?;
+    let db = db_file(db_url);
+    let supported = SchemaInfo::supported();
+
+    if let Some(newest) = info.unknown().map(|m| m.version).max() {
+        let rollback = db.as_deref().and_then(|db| {
+            backups(db)
+                .into_iter()
+                .rev()
+                .find(|(version, _)| *version <= supported)
+                .map(|(_, path)| path)
+        });
+        return Err(sqlx::Error::Configuration(Box::new(NewerSchemaError {
+            path: db
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| db_url.to_string()),
+            found: newest,
+            supported,
+            rollback,
+        })));
+    }
+
+    let (Some(version), Some(db)) = (info.version(), db) else {
+        return Ok(None);
+    };
+    if info.pending.is_empty() {
+        return Ok(None);
+    }
+    let backup = backup_path(&db, version);
+    // VACUUM INTO won't overwrite; a copy from an earlier attempt is stale
+    if backup.exists() {
+        std::fs::remove_file(&backup).map_err(sqlx::Error::Io)?;
+    }
+    sqlx::query("VACUUM INTO ?")
+        .bind(backup.to_string_lossy().as_ref())
+        .execute(pool)
+        .await?;
+    Ok(Some(backup))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_db_file_from_url() {
+        assert_eq!(db_file("sqlite:music.db"), Some(PathBuf::from("music.db")));
+        assert_eq!(
+            db_file("sqlite:///data/music.db?mode=rwc"),
+            Some(PathBuf::from("/data/music.db"))
+        );
+        assert_eq!(db_file("sqlite::memory:"), None);
+        assert_eq!(
+            backup_path(Path::new("/data/music.db"), 20250101000014),
+            Path::new("/data/music.db.v20250101000014.bak")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_backup_and_newer_schema_guard() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("library.db");
+        let url = format!("sqlite:{}", path.display());
+
+        // A new database isn't backed up, and ends up at the latest version
+        let pool = crate::db::init_db(&url).await.unwrap();
+        let info = schema_info(&pool).await.unwrap();
+        assert_eq!(info.version(), Some(SchemaInfo::supported()));
+        assert!(info.pending.is_empty());
+        assert!(backups(&path).is_empty());
+
+        // Pretend the last migration is new: the upgrade backs up first
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = ?")
+            .bind(SchemaInfo::supported())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let previous = schema_info(&pool).await.unwrap().version().unwrap();
+        let backup = prepare_migration(&pool, &url).await.unwrap().unwrap();
+        assert_eq!(backups(&path), [(previous, backup.clone())]);
+
+        // A migration from the future is refused, pointing at the backup
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+             VALUES (29991231000000, 'from the future', TRUE, x'00', 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+        let err = crate::db::init_db(&url).await.unwrap_err().to_string();
+        assert!(err.contains("newer version of Music Minder"), "{}", err);
+        assert!(err.contains("schema 29991231000000"), "{}", err);
+        assert!(err.contains(&backup.display().to_string()), "{}", err);
+    }
+}
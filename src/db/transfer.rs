@@ -0,0 +1,707 @@
+//! Moving tracks between library databases.
+//!
+//! [`merge`] brings another database's tracks into this one, for
+//! consolidating the libraries of two PCs; [`split`] copies part of the
+//! library (by folder, artist or format) into a new database. Both attach
+//! the other file to one connection and copy inside a single transaction, so
+//! a failure leaves both databases as they were.
+//!
+//! A track already in the destination is matched by its path, or else by its
+//! MusicBrainz recording on the same album (the same file on another PC).
+//! Matched tracks keep their own tags; they only gain the other side's
+//! rating if they had none, the higher imported play count, and any plays
+//! not already in their history. Unmatched tracks are added with everything
+//! the library knows about them: ratings, play history, loudness, quality,
+//! tag provenance and pending tag conflicts. Identification results, health
+//! records and album checks are left behind; they are rebuilt by scanning.
+//!
+//! Playlists holding any of the tracks come along with those of their
+//! entries. A playlist whose name the destination already has gains the
+//! entries for tracks it doesn't hold yet, after its own.
+
+use std::path::{Path, PathBuf};
+
+use sqlx::sqlite::{SqliteConnection, SqlitePool};
+
+/// Tracks copied per step (progress is reported after each)
+const CHUNK: usize = 500;
+
+/// Name the other database is attached under
+const OTHER: &str = "other";
+
+/// Errors merging or splitting a library
+#[derive(Debug, thiserror::Error)]
+pub enum TransferError {
+    #[error("no database at {0}")]
+    Missing(PathBuf),
+    #[error("{0} already exists; split into a new file")]
+    Exists(PathBuf),
+    #[error("{0} is the database being used")]
+    SameDatabase(PathBuf),
+    #[error(transparent)]
+    ReadOnly(#[from] crate::readonly::ReadOnlyError),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+}
+
+/// Which tracks a split takes; each set criterion must match
+#[derive(Debug, Clone, Default)]
+pub struct TrackSelection {
+    /// Tracks under any of these folders
+    pub folders: Vec<PathBuf>,
+    /// Tracks by this artist (ignoring case)
+    pub artist: Option<String>,
+    /// Tracks with this file extension (ignoring case)
+    pub format: Option<String>,
+}
+
+impl TrackSelection {
+    /// Nothing set: the selection would take the whole library
+    pub fn is_empty(&self) -> bool {
+        self.folders.is_empty() && self.artist.is_none() && self.format.is_none()
+    }
+
+    pub fn matches(&self, path: &str, artist: Option<&str>) -> bool {
+        let path = Path::new(path);
+        (self.folders.is_empty() || self.folders.iter().any(|f| path.starts_with(f)))
+            && self
+                .artist
+                .as_ref()
+                .is_none_or(|want| artist.is_some_and(|a| a.eq_ignore_ascii_case(want)))
+            && self.format.as_ref().is_none_or(|want| {
+                path.extension()
+                    .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(want))
+            })
+    }
+}
+
+/// How far a transfer has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferProgress {
+    /// Tracks matched or copied so far
+    pub done: usize,
+    pub total: usize,
+}
+
+/// What a merge or split did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferReport {
+    /// Tracks copied as new
+    pub added: usize,
+    /// Tracks already at the same path
+    pub matched_by_path: usize,
+    /// Tracks already there under another path (same recording and album)
+    pub matched_by_recording: usize,
+    /// Matched tracks that took the other side's rating
+    pub ratings_filled: usize,
+    /// Plays added to the history
+    pub plays_added: usize,
+    /// Entries added to playlists
+    pub playlist_entries_added: usize,
+    /// Tracks removed from the source after a split
+    pub removed: usize,
+}
+
+/// Merge the library in `other` into this one.
+///
+/// `other` is upgraded to this version's schema first (with the usual
+/// backup) but not otherwise changed. Merging the same file twice adds
+/// nothing the second time.
+pub async fn merge(
+    pool: &SqlitePool,
+    other: &Path,
+    mut progress: impl FnMut(TransferProgress),
+) -> Result<TransferReport, TransferError> {
+    crate::readonly::ensure_writable("Merging libraries")?;
+    if !other.is_file() {
+        return Err(TransferError::Missing(other.to_path_buf()));
+    }
+    check_not_same(pool, other).await?;
+    super::init_db(&super::db_url(Some(other)))
+        .await?
+        .close()
+        .await;
+
+    let mut conn = pool.acquire().await?;
+    attach(&mut conn, other).await?;
+    let result = async {
+        let ids: Vec<(i64,)> = sqlx::query_as(&format!("SELECT id FROM {OTHER}.tracks"))
+            .fetch_all(&mut *conn)
+            .await?;
+        let ids: Vec<i64> = ids.into_iter().map(|(id,)| id).collect();
+
+        let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+        let report = copy_tracks(&mut tx, OTHER, "main", &ids, &mut progress).await?;
+        tx.commit().await?;
+        Ok(report)
+    }
+    .await;
+    detach(&mut conn).await;
+    result
+}
+
+/// Copy the tracks picked by `selection` into a new database at `to`,
+/// removing them from this one if `remove` is set.
+pub async fn split(
+    pool: &SqlitePool,
+    to: &Path,
+    selection: &TrackSelection,
+    remove: bool,
+    mut progress: impl FnMut(TransferProgress),
+) -> Result<TransferReport, TransferError> {
+    crate::readonly::ensure_writable("Splitting libraries")?;
+    if to.exists() {
+        return Err(TransferError::Exists(to.to_path_buf()));
+    }
+    let rows: Vec<(i64, String, Option<String>)> = sqlx::query_as(
+        "SELECT t.id, t.path, a.name FROM tracks t LEFT JOIN artists a ON a.id = t.artist_id",
+    )
+    .fetch_all(pool)
+    .await?;
+    let ids: Vec<i64> = rows
+        .into_iter()
+        .filter(|(_, path, artist)| selection.matches(path, artist.as_deref()))
+        .map(|(id, _, _)| id)
+        .collect();
+
+    super::init_db(&super::db_url(Some(to)))
+        .await?
+        .close()
+        .await;
+
+    let mut conn = pool.acquire().await?;
+    attach(&mut conn, to).await?;
+    let result = async {
+        let mut tx = sqlx::Connection::begin(&mut *conn).await?;
+        let mut report = copy_tracks(&mut tx, "main", OTHER, &ids, &mut progress).await?;
+        if remove {
+            for chunk in ids.chunks(CHUNK) {
+                let removed = sqlx::query(
+                    "DELETE FROM main.tracks WHERE id IN (SELECT value FROM json_each(?))",
+                )
+                .bind(json_ids(chunk))
+                .execute(&mut *tx)
+                .await?;
+                report.removed += removed.rows_affected() as usize;
+            }
+            sqlx::query(
+                "DELETE FROM main.albums WHERE NOT EXISTS \
+                 (SELECT 1 FROM main.tracks t WHERE t.album_id = albums.id)",
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(report)
+    }
+    .await;
+    detach(&mut conn).await;
+    result
+}
+
+/// Refuse to merge a database into itself
+async fn check_not_same(pool: &SqlitePool, other: &Path) -> Result<(), TransferError> {
+    let (main,): (String,) =
+        sqlx::query_as("SELECT file FROM pragma_database_list WHERE name = 'main'")
+            .fetch_one(pool)
+            .await?;
+    let same = match (Path::new(&main).canonicalize(), other.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    };
+    if same {
+        return Err(TransferError::SameDatabase(other.to_path_buf()));
+    }
+    Ok(())
+}
+
+async fn attach(conn: &mut SqliteConnection, file: &Path) -> sqlx::Result<()> {
+    sqlx::query(&format!("ATTACH DATABASE ? AS {OTHER}"))
+        .bind(file.to_string_lossy().as_ref())
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+/// Detach the other database so the connection goes back to the pool clean
+async fn detach(conn: &mut SqliteConnection) {
+    if let Err(e) = sqlx::query(&format!("DETACH DATABASE {OTHER}"))
+        .execute(conn)
+        .await
+    {
+        tracing::warn!("Couldn't detach the merged database: {}", e);
+    }
+}
+
+fn json_ids(ids: &[i64]) -> String {
+    serde_json::to_string(ids).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Copy tracks `ids` of schema `src` into schema `dst` on one connection
+async fn copy_tracks(
+    conn: &mut SqliteConnection,
+    src: &str,
+    dst: &str,
+    ids: &[i64],
+    progress: &mut impl FnMut(TransferProgress),
+) -> sqlx::Result<TransferReport> {
+    let mut report = TransferReport::default();
+    let total = ids.len();
+
+    // Source track -> destination track, and how it was found
+    sqlx::query(
+        "CREATE TEMP TABLE transfer_tracks \
+         (src_id INTEGER PRIMARY KEY, dst_id INTEGER, matched TEXT)",
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query("CREATE TEMP TABLE transfer_artists (src_id INTEGER PRIMARY KEY, dst_id INTEGER)")
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("CREATE TEMP TABLE transfer_albums (src_id INTEGER PRIMARY KEY, dst_id INTEGER)")
+        .execute(&mut *conn)
+        .await?;
+    for chunk in ids.chunks(CHUNK) {
+        sqlx::query("INSERT INTO temp.transfer_tracks (src_id) SELECT value FROM json_each(?)")
+            .bind(json_ids(chunk))
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    report.matched_by_path = sqlx::query(&format!(
+        "UPDATE temp.transfer_tracks SET matched = 'path', dst_id = \
+           (SELECT d.id FROM {src}.tracks s JOIN {dst}.tracks d ON d.path_key = s.path_key \
+            WHERE s.id = transfer_tracks.src_id) \
+         WHERE EXISTS (SELECT 1 FROM {src}.tracks s JOIN {dst}.tracks d ON d.path_key = s.path_key \
+                       WHERE s.id = transfer_tracks.src_id)"
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected() as usize;
+
+    let same_recording = format!(
+        "SELECT MIN(d.id) FROM {src}.tracks s \
+         JOIN {dst}.tracks d ON d.musicbrainz_recording_id = s.musicbrainz_recording_id \
+         LEFT JOIN {src}.albums sa ON sa.id = s.album_id \
+         LEFT JOIN {dst}.albums da ON da.id = d.album_id \
+         WHERE s.id = transfer_tracks.src_id AND lower(sa.title) IS lower(da.title) \
+           AND d.id NOT IN (SELECT dst_id FROM temp.transfer_tracks WHERE dst_id IS NOT NULL)"
+    );
+    report.matched_by_recording = sqlx::query(&format!(
+        "UPDATE temp.transfer_tracks SET matched = 'recording', dst_id = ({same_recording}) \
+         WHERE dst_id IS NULL AND ({same_recording}) IS NOT NULL"
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected() as usize;
+    let mut done = report.matched_by_path + report.matched_by_recording;
+    progress(TransferProgress { done, total });
+
+    // Artists and albums of the tracks to add
+    let new_tracks = format!(
+        "SELECT s.* FROM {src}.tracks s \
+         JOIN temp.transfer_tracks t ON t.src_id = s.id WHERE t.dst_id IS NULL"
+    );
+    sqlx::query(&format!(
+        "INSERT OR IGNORE INTO {dst}.artists (name) \
+         SELECT a.name FROM {src}.artists a WHERE a.id IN \
+           (SELECT artist_id FROM ({new_tracks}) \
+            UNION SELECT al.artist_id FROM {src}.albums al \
+              WHERE al.id IN (SELECT album_id FROM ({new_tracks})))"
+    ))
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO temp.transfer_artists (src_id, dst_id) \
+         SELECT a.id, d.id FROM {src}.artists a JOIN {dst}.artists d ON d.name = a.name"
+    ))
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO {dst}.albums (title, artist_id, year) \
+         SELECT sa.title, ta.dst_id, MAX(sa.year) FROM {src}.albums sa \
+         LEFT JOIN temp.transfer_artists ta ON ta.src_id = sa.artist_id \
+         WHERE sa.id IN (SELECT album_id FROM ({new_tracks})) \
+           AND NOT EXISTS (SELECT 1 FROM {dst}.albums da \
+                           WHERE da.title = sa.title AND da.artist_id IS ta.dst_id) \
+         GROUP BY sa.title, ta.dst_id"
+    ))
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT INTO temp.transfer_albums (src_id, dst_id) \
+         SELECT sa.id, (SELECT MIN(da.id) FROM {dst}.albums da \
+                        WHERE da.title = sa.title AND da.artist_id IS ta.dst_id) \
+         FROM {src}.albums sa LEFT JOIN temp.transfer_artists ta ON ta.src_id = sa.artist_id"
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    // Every other column of the track comes across as it is
+    let columns: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM pragma_table_info('tracks', ?) \
+         WHERE name NOT IN ('id', 'artist_id', 'album_id')",
+    )
+    .bind(dst)
+    .fetch_all(&mut *conn)
+    .await?;
+    let columns: Vec<String> = columns.into_iter().map(|(c,)| c).collect();
+    let source_columns: Vec<String> = columns.iter().map(|c| format!("s.{c}")).collect();
+    let insert = format!(
+        "INSERT INTO {dst}.tracks ({}, artist_id, album_id) \
+         SELECT {}, ta.dst_id, tal.dst_id FROM {src}.tracks s \
+         JOIN temp.transfer_tracks t ON t.src_id = s.id \
+         LEFT JOIN temp.transfer_artists ta ON ta.src_id = s.artist_id \
+         LEFT JOIN temp.transfer_albums tal ON tal.src_id = s.album_id \
+         WHERE t.dst_id IS NULL AND s.id IN (SELECT value FROM json_each(?))",
+        columns.join(", "),
+        source_columns.join(", ")
+    );
+    let map_new = format!(
+        "UPDATE temp.transfer_tracks SET matched = 'new', dst_id = \
+           (SELECT d.id FROM {src}.tracks s JOIN {dst}.tracks d ON d.path = s.path \
+            WHERE s.id = transfer_tracks.src_id) \
+         WHERE dst_id IS NULL AND src_id IN (SELECT value FROM json_each(?))"
+    );
+    let unmatched: Vec<(i64,)> =
+        sqlx::query_as("SELECT src_id FROM temp.transfer_tracks WHERE dst_id IS NULL")
+            .fetch_all(&mut *conn)
+            .await?;
+    let unmatched: Vec<i64> = unmatched.into_iter().map(|(id,)| id).collect();
+    for chunk in unmatched.chunks(CHUNK) {
+        let chunk = json_ids(chunk);
+        report.added += sqlx::query(&insert)
+            .bind(&chunk)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected() as usize;
+        sqlx::query(&map_new)
+            .bind(&chunk)
+            .execute(&mut *conn)
+            .await?;
+        done = (done + CHUNK).min(total);
+        progress(TransferProgress { done, total });
+    }
+
+    // Matched tracks keep their own rating, but take one if they had none
+    report.ratings_filled = sqlx::query(&format!(
+        "UPDATE {dst}.tracks AS d SET rating = s.rating \
+         FROM temp.transfer_tracks t JOIN {src}.tracks s ON s.id = t.src_id \
+         WHERE d.id = t.dst_id AND t.matched != 'new' \
+           AND d.rating IS NULL AND s.rating IS NOT NULL"
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected() as usize;
+    sqlx::query(&format!(
+        "UPDATE {dst}.tracks AS d SET tag_play_count = s.tag_play_count \
+         FROM temp.transfer_tracks t JOIN {src}.tracks s ON s.id = t.src_id \
+         WHERE d.id = t.dst_id AND t.matched != 'new' \
+           AND s.tag_play_count > COALESCE(d.tag_play_count, 0)"
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    // Plays the destination doesn't have yet (so merging twice adds none)
+    report.plays_added = sqlx::query(&format!(
+        "INSERT INTO {dst}.play_history (track_id, played_at, listened_secs, genre, skipped) \
+         SELECT t.dst_id, h.played_at, h.listened_secs, h.genre, h.skipped \
+         FROM {src}.play_history h \
+         JOIN temp.transfer_tracks t ON t.src_id = h.track_id \
+         WHERE t.dst_id IS NOT NULL AND NOT EXISTS \
+           (SELECT 1 FROM {dst}.play_history p \
+            WHERE p.track_id = t.dst_id AND p.played_at = h.played_at)"
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected() as usize;
+
+    // Who wrote each tag goes with the tracks that brought their tags
+    sqlx::query(&format!(
+        "INSERT OR IGNORE INTO {dst}.field_provenance \
+           (track_id, field, source, confidence, written_at) \
+         SELECT t.dst_id, p.field, p.source, p.confidence, p.written_at \
+         FROM {src}.field_provenance p JOIN temp.transfer_tracks t ON t.src_id = p.track_id \
+         WHERE t.matched = 'new'"
+    ))
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(&format!(
+        "INSERT OR IGNORE INTO {dst}.tag_conflicts \
+           (track_id, field, current_value, proposed_value, source, confidence, created_at, kept) \
+         SELECT t.dst_id, c.field, c.current_value, c.proposed_value, c.source, c.confidence, \
+                c.created_at, c.kept \
+         FROM {src}.tag_conflicts c JOIN temp.transfer_tracks t ON t.src_id = c.track_id \
+         WHERE t.matched = 'new'"
+    ))
+    .execute(&mut *conn)
+    .await?;
+
+    // Playlists with any of the tracks, then their entries for them
+    sqlx::query(&format!(
+        "INSERT OR IGNORE INTO {dst}.playlists (name, created_at, updated_at) \
+         SELECT p.name, p.created_at, p.updated_at FROM {src}.playlists p \
+         WHERE p.id IN (SELECT pt.playlist_id FROM {src}.playlist_tracks pt \
+                        JOIN temp.transfer_tracks t ON t.src_id = pt.track_id)"
+    ))
+    .execute(&mut *conn)
+    .await?;
+    report.playlist_entries_added = sqlx::query(&format!(
+        "INSERT INTO {dst}.playlist_tracks (playlist_id, track_id, position) \
+         SELECT dp.id, t.dst_id, \
+                COALESCE((SELECT MAX(e.position) + 1 FROM {dst}.playlist_tracks e \
+                          WHERE e.playlist_id = dp.id), 0) \
+                + ROW_NUMBER() OVER (PARTITION BY dp.id ORDER BY pt.position, pt.id) - 1 \
+         FROM {src}.playlist_tracks pt \
+         JOIN {src}.playlists sp ON sp.id = pt.playlist_id \
+         JOIN {dst}.playlists dp ON dp.name = sp.name \
+         JOIN temp.transfer_tracks t ON t.src_id = pt.track_id \
+         WHERE t.dst_id IS NOT NULL AND NOT EXISTS \
+           (SELECT 1 FROM {dst}.playlist_tracks e \
+            WHERE e.playlist_id = dp.id AND e.track_id = t.dst_id)"
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected() as usize;
+
+    for table in ["transfer_tracks", "transfer_artists", "transfer_albums"] {
+        sqlx::query(&format!("DROP TABLE temp.{table}"))
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{self, playlists};
+    use crate::test_utils::{mock_track_metadata, temp_db};
+
+    async fn add(pool: &SqlitePool, path: &str, artist: &str, album: &str) -> i64 {
+        let artist_id = db::get_or_create_artist(pool, artist).await.unwrap();
+        let album_id = db::get_or_create_album(pool, album, Some(artist_id))
+            .await
+            .unwrap();
+        let meta = crate::metadata::TrackMetadata {
+            title: path.to_string(),
+            ..mock_track_metadata()
+        };
+        db::insert_track(pool, &meta, path, Some(artist_id), Some(album_id))
+            .await
+            .unwrap()
+    }
+
+    async fn other_db(dir: &Path) -> (SqlitePool, PathBuf) {
+        let path = dir.join("other.db");
+        let pool = db::init_db(&db::db_url(Some(&path))).await.unwrap();
+        (pool, path)
+    }
+
+    async fn count(pool: &SqlitePool, sql: &str) -> i64 {
+        sqlx::query_as::<_, (i64,)>(sql)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_selection_matches() {
+        let selection = TrackSelection {
+            folders: vec![PathBuf::from("/music/rock")],
+            format: Some("flac".into()),
+            ..Default::default()
+        };
+        assert!(selection.matches("/music/rock/a/01.FLAC", None));
+        assert!(!selection.matches("/music/rock/a/01.mp3", None));
+        assert!(!selection.matches("/music/rockabilly/01.flac", None));
+
+        let by_artist = TrackSelection {
+            artist: Some("Björk".into()),
+            ..Default::default()
+        };
+        assert!(by_artist.matches("/x.flac", Some("Björk")));
+        assert!(!by_artist.matches("/x.flac", None));
+    }
+
+    #[tokio::test]
+    async fn test_merge_dedupes_and_keeps_history() {
+        let (pool, dir) = temp_db().await;
+        let (other, other_path) = other_db(dir.path()).await;
+
+        let ours = add(&pool, "/music/a.flac", "Artist", "Album").await;
+        let theirs = add(&other, "/music/a.flac", "Artist", "Album").await;
+        add(&other, "/music/b.flac", "New Artist", "New Album").await;
+        sqlx::query("UPDATE tracks SET rating = 80 WHERE id = ?")
+            .bind(theirs)
+            .execute(&other)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO play_history (track_id, played_at) VALUES (?, 100), (?, 200)")
+            .bind(theirs)
+            .bind(theirs)
+            .execute(&other)
+            .await
+            .unwrap();
+        other.close().await;
+
+        let mut updates = Vec::new();
+        let report = merge(&pool, &other_path, |p| updates.push(p))
+            .await
+            .unwrap();
+        assert_eq!(report.matched_by_path, 1);
+        assert_eq!(report.added, 1);
+        assert_eq!(report.ratings_filled, 1);
+        assert_eq!(report.plays_added, 2);
+        assert_eq!(
+            updates.last(),
+            Some(&TransferProgress { done: 2, total: 2 })
+        );
+
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM tracks").await, 2);
+        let rating = format!("SELECT rating FROM tracks WHERE id = {ours}");
+        assert_eq!(count(&pool, &rating).await, 80);
+        let new_album = "SELECT COUNT(*) FROM tracks t JOIN albums al ON al.id = t.album_id \
+                         JOIN artists a ON a.id = al.artist_id \
+                         WHERE t.path = '/music/b.flac' AND al.title = 'New Album' \
+                           AND a.name = 'New Artist'";
+        assert_eq!(count(&pool, new_album).await, 1);
+
+        // A second merge finds everything already there
+        let again = merge(&pool, &other_path, |_| {}).await.unwrap();
+        assert_eq!(again.added, 0);
+        assert_eq!(again.plays_added, 0);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM play_history").await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_merge_matches_recording_on_same_album() {
+        let (pool, dir) = temp_db().await;
+        let (other, other_path) = other_db(dir.path()).await;
+
+        let ours = add(&pool, "/home/me/music/song.flac", "Artist", "Album").await;
+        let theirs = add(&other, "D:/Music/song.flac", "Artist", "album").await;
+        let compilation = add(&other, "D:/Music/best-of/song.flac", "Artist", "Best Of").await;
+        for (p, id) in [(&pool, ours), (&other, theirs), (&other, compilation)] {
+            sqlx::query("UPDATE tracks SET musicbrainz_recording_id = 'rec-1' WHERE id = ?")
+                .bind(id)
+                .execute(p)
+                .await
+                .unwrap();
+        }
+        other.close().await;
+
+        let report = merge(&pool, &other_path, |_| {}).await.unwrap();
+        assert_eq!(report.matched_by_recording, 1);
+        assert_eq!(report.added, 1, "the compilation copy is its own track");
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM tracks").await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_merge_refuses_itself() {
+        let (pool, dir) = temp_db().await;
+        let result = merge(&pool, &dir.path().join("test.db"), |_| {}).await;
+        assert!(matches!(result, Err(TransferError::SameDatabase(_))));
+    }
+
+    #[tokio::test]
+    async fn test_split_by_folder_and_remove() {
+        let (pool, dir) = temp_db().await;
+        let kept = add(&pool, "/music/jazz/a.flac", "Jazz", "Blue").await;
+        let moved = add(&pool, "/music/rock/b.flac", "Rock", "Loud").await;
+        sqlx::query("INSERT INTO play_history (track_id, played_at) VALUES (?, 100)")
+            .bind(moved)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let to = dir.path().join("rock.db");
+        let selection = TrackSelection {
+            folders: vec![PathBuf::from("/music/rock")],
+            ..Default::default()
+        };
+        let report = split(&pool, &to, &selection, true, |_| {}).await.unwrap();
+        assert_eq!(report.added, 1);
+        assert_eq!(report.plays_added, 1);
+        assert_eq!(report.removed, 1);
+
+        let split_pool = db::init_db(&db::db_url(Some(&to))).await.unwrap();
+        assert_eq!(count(&split_pool, "SELECT COUNT(*) FROM tracks").await, 1);
+        assert_eq!(
+            count(&split_pool, "SELECT COUNT(*) FROM play_history").await,
+            1
+        );
+        assert_eq!(count(&split_pool, "SELECT COUNT(*) FROM artists").await, 1);
+
+        let left = format!("SELECT COUNT(*) FROM tracks WHERE id = {kept}");
+        assert_eq!(count(&pool, &left).await, 1);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM tracks").await, 1);
+        assert_eq!(count(&pool, "SELECT COUNT(*) FROM albums").await, 1);
+
+        let again = split(&pool, &to, &selection, false, |_| {}).await;
+        assert!(matches!(again, Err(TransferError::Exists(_))));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_carries_playlists() {
+        let (pool, dir) = temp_db().await;
+        let (other, other_path) = other_db(dir.path()).await;
+
+        let ours = add(&pool, "/music/a.flac", "Artist", "Album").await;
+        let mine = playlists::create(&pool, "mix").await.unwrap();
+        playlists::add_tracks(&pool, mine, &[ours]).await.unwrap();
+
+        let a = add(&other, "/music/a.flac", "Artist", "Album").await;
+        let b = add(&other, "/music/b.flac", "Artist", "Album").await;
+        let theirs = playlists::create(&other, "Mix").await.unwrap();
+        playlists::add_tracks(&other, theirs, &[b, a])
+            .await
+            .unwrap();
+        let road = playlists::create(&other, "Road").await.unwrap();
+        playlists::add_tracks(&other, road, &[b]).await.unwrap();
+        other.close().await;
+
+        let paths = |tracks: Vec<playlists::PlaylistTrack>| -> Vec<String> {
+            tracks.into_iter().map(|t| t.path).collect()
+        };
+        let report = merge(&pool, &other_path, |_| {}).await.unwrap();
+        assert_eq!(report.playlist_entries_added, 2);
+        let names: Vec<String> = playlists::list(&pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["mix", "Road"]);
+        assert_eq!(
+            paths(playlists::tracks(&pool, mine).await.unwrap()),
+            vec!["/music/a.flac", "/music/b.flac"]
+        );
+        let again = merge(&pool, &other_path, |_| {}).await.unwrap();
+        assert_eq!(again.playlist_entries_added, 0);
+
+        // Split off one track: its entries go with it, the rest stay
+        let to = dir.path().join("b.db");
+        let selection = TrackSelection {
+            folders: vec![PathBuf::from("/music/b.flac")],
+            ..Default::default()
+        };
+        let report = split(&pool, &to, &selection, true, |_| {}).await.unwrap();
+        assert_eq!(report.playlist_entries_added, 2);
+        assert_eq!(
+            paths(playlists::tracks(&pool, mine).await.unwrap()),
+            vec!["/music/a.flac"]
+        );
+
+        let split_pool = db::init_db(&db::db_url(Some(&to))).await.unwrap();
+        let split_lists = playlists::list(&split_pool).await.unwrap();
+        assert_eq!(split_lists.len(), 2);
+        for playlist in split_lists {
+            assert_eq!(
+                paths(playlists::tracks(&split_pool, playlist.id).await.unwrap()),
+                vec!["/music/b.flac"]
+            );
+        }
+    }
+}
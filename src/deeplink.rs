@@ -0,0 +1,352 @@
+//! `musicminder://` links.
+//!
+//! Links from notes or a web page open the app (or focus the running one,
+//! see [`crate::instance`]) and do something there:
+//!
+//! - `musicminder://` or `musicminder://focus`: bring the window up
+//! - `musicminder://play?path=<file>`: play a library track
+//! - `musicminder://queue?path=<file>`: add a library track to the queue
+//! - `musicminder://album/<release MBID>`: show an album
+//! - `musicminder://search?q=<text>`: search the library
+//!
+//! Parameters are percent-encoded; in `q`, `+` is a space as in web forms.
+//! The scheme is registered with the desktop by [`register`]: in the
+//! current user's registry classes on Windows, a `.desktop` handler on
+//! Linux. macOS only routes schemes declared in an app bundle's
+//! `Info.plist`, so there's nothing to register from here.
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Mutex;
+
+/// The URI scheme
+pub const SCHEME: &str = "musicminder";
+
+/// The link that just brings the window up
+pub const FOCUS: &str = "musicminder://focus";
+
+/// Link given on the command line before the window opened, handled once
+/// the library is loaded
+static PENDING: Mutex<Option<String>> = Mutex::new(None);
+
+/// Deep link errors
+#[derive(Debug, thiserror::Error)]
+pub enum DeepLinkError {
+    #[error("Not a {SCHEME}:// link: {0}")]
+    NotDeepLink(String),
+
+    #[error("Unknown link action {0:?}")]
+    UnknownAction(String),
+
+    #[error("The {0} link needs a {1} parameter")]
+    MissingParam(&'static str, &'static str),
+
+    #[error("Invalid MusicBrainz release ID {0:?}")]
+    InvalidMbid(String),
+
+    #[error("Link registration failed: {0}")]
+    Register(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// What a link asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLink {
+    /// Bring the window up
+    Focus,
+    /// Play a library track
+    Play { path: PathBuf },
+    /// Add a library track to the queue
+    Queue { path: PathBuf },
+    /// Show the album of a MusicBrainz release
+    Album { mbid: String },
+    /// Search the library
+    Search { query: String },
+}
+
+impl DeepLink {
+    /// Parse a `musicminder://` link
+    pub fn parse(uri: &str) -> Result<Self, DeepLinkError> {
+        let uri = uri.trim();
+        let rest = uri
+            .split_once(':')
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(SCHEME))
+            .map(|(_, rest)| rest.trim_start_matches('/'))
+            .ok_or_else(|| DeepLinkError::NotDeepLink(uri.to_string()))?;
+        let (route, query) = rest.split_once('?').unwrap_or((rest, ""));
+        // Browsers and shells may add a trailing slash
+        let route = route.trim_end_matches('/');
+        let (action, target) = route.split_once('/').unwrap_or((route, ""));
+
+        match action.to_ascii_lowercase().as_str() {
+            "" | "focus" => Ok(Self::Focus),
+            "play" => Ok(Self::Play {
+                path: PathBuf::from(
+                    param(query, "path", false)
+                        .ok_or(DeepLinkError::MissingParam("play", "path"))?,
+                ),
+            }),
+            "queue" => Ok(Self::Queue {
+                path: PathBuf::from(
+                    param(query, "path", false)
+                        .ok_or(DeepLinkError::MissingParam("queue", "path"))?,
+                ),
+            }),
+            "album" => {
+                let mbid = decode(target, false).to_ascii_lowercase();
+                if mbid.is_empty() {
+                    return Err(DeepLinkError::MissingParam("album", "release ID"));
+                }
+                if !is_mbid(&mbid) {
+                    return Err(DeepLinkError::InvalidMbid(mbid));
+                }
+                Ok(Self::Album { mbid })
+            }
+            "search" => Ok(Self::Search {
+                query: param(query, "q", true)
+                    .filter(|q| !q.trim().is_empty())
+                    .ok_or(DeepLinkError::MissingParam("search", "q"))?,
+            }),
+            other => Err(DeepLinkError::UnknownAction(other.to_string())),
+        }
+    }
+}
+
+/// The decoded value of `name` in a query string
+fn param(query: &str, name: &str, plus_is_space: bool) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| decode(value, plus_is_space))
+        .filter(|value| !value.is_empty())
+}
+
+fn decode(value: &str, plus_is_space: bool) -> String {
+    let value = if plus_is_space {
+        value.replace('+', " ")
+    } else {
+        value.to_string()
+    };
+    match urlencoding::decode(&value) {
+        Ok(decoded) => decoded.into_owned(),
+        Err(_) => value,
+    }
+}
+
+/// Whether `id` looks like a MusicBrainz ID (a lowercase UUID)
+fn is_mbid(id: &str) -> bool {
+    id.len() == 36
+        && id.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// Keep a link for the window about to open
+pub fn set_pending(uri: String) {
+    *PENDING.lock().unwrap_or_else(|e| e.into_inner()) = Some(uri);
+}
+
+/// The link given on the command line, if it hasn't been handled yet
+pub fn take_pending() -> Option<String> {
+    PENDING.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Registry key of the scheme on Windows
+const WINDOWS_KEY: &str = r"HKCU\Software\Classes\musicminder";
+
+/// Desktop entry handling the scheme on Linux
+const DESKTOP_FILE: &str = "music-minder-url.desktop";
+
+/// Make the desktop open `musicminder://` links with this executable.
+/// Returns what was done, for the user.
+pub fn register() -> Result<String, DeepLinkError> {
+    let exe = std::env::current_exe()?;
+    if cfg!(windows) {
+        let command = format!("\"{}\" open \"%1\"", exe.display());
+        run_tool(
+            "reg",
+            &["add", WINDOWS_KEY, "/ve", "/d", "URL:Music Minder", "/f"],
+        )?;
+        run_tool(
+            "reg",
+            &["add", WINDOWS_KEY, "/v", "URL Protocol", "/d", "", "/f"],
+        )?;
+        run_tool(
+            "reg",
+            &[
+                "add",
+                &format!(r"{}\shell\open\command", WINDOWS_KEY),
+                "/ve",
+                "/d",
+                &command,
+                "/f",
+            ],
+        )?;
+        Ok(format!("Registered {}:// in {}", SCHEME, WINDOWS_KEY))
+    } else if cfg!(target_os = "linux") {
+        let path = desktop_file_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, desktop_entry(&exe))?;
+        run_tool(
+            "xdg-mime",
+            &[
+                "default",
+                DESKTOP_FILE,
+                &format!("x-scheme-handler/{}", SCHEME),
+            ],
+        )?;
+        Ok(format!("Registered {}:// with {}", SCHEME, path.display()))
+    } else {
+        Err(DeepLinkError::Register(format!(
+            "{}:// links can't be registered on this system; declare the scheme in the \
+             app bundle's Info.plist (CFBundleURLTypes) instead",
+            SCHEME
+        )))
+    }
+}
+
+/// Stop the desktop sending `musicminder://` links here. Returns what was
+/// done, for the user.
+pub fn unregister() -> Result<String, DeepLinkError> {
+    if cfg!(windows) {
+        run_tool("reg", &["delete", WINDOWS_KEY, "/f"])?;
+        Ok(format!("Removed {}", WINDOWS_KEY))
+    } else if cfg!(target_os = "linux") {
+        let path = desktop_file_path()?;
+        if !path.exists() {
+            return Err(DeepLinkError::Register(format!(
+                "{} is not registered",
+                path.display()
+            )));
+        }
+        std::fs::remove_file(&path)?;
+        Ok(format!("Removed {}", path.display()))
+    } else {
+        Err(DeepLinkError::Register(
+            "Link registration isn't supported on this system".into(),
+        ))
+    }
+}
+
+fn desktop_file_path() -> Result<PathBuf, DeepLinkError> {
+    let dir = dirs::data_dir()
+        .ok_or_else(|| DeepLinkError::Register("Could not determine data directory".into()))?;
+    Ok(dir.join("applications").join(DESKTOP_FILE))
+}
+
+/// Desktop entry opening links with `exe`
+fn desktop_entry(exe: &std::path::Path) -> String {
+    // Quoted per the desktop entry spec: `"`, `` ` ``, `$` and `\` take a
+    // backslash, itself escaped as the value is a string too
+    let mut quoted = String::from('"');
+    for c in exe.display().to_string().chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push_str("\\\\");
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Music Minder\n\
+         Exec={} open %u\n\
+         MimeType=x-scheme-handler/{};\n\
+         NoDisplay=true\n\
+         Terminal=false\n",
+        quoted.replace('%', "%%"),
+        SCHEME
+    )
+}
+
+/// Run a registration command, failing with its output if it fails
+fn run_tool(program: &str, args: &[&str]) -> Result<(), DeepLinkError> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| DeepLinkError::Register(format!("Couldn't run {}: {}", program, e)))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let mut message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    if message.is_empty() {
+        message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    }
+    Err(DeepLinkError::Register(format!(
+        "{} {} failed: {}",
+        program,
+        args.first().unwrap_or(&""),
+        message
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_links() {
+        assert_eq!(DeepLink::parse("musicminder://").unwrap(), DeepLink::Focus);
+        assert_eq!(
+            DeepLink::parse("MusicMinder://focus/").unwrap(),
+            DeepLink::Focus
+        );
+        assert_eq!(
+            DeepLink::parse("musicminder://play?path=%2Fmusic%2FA%20%2B%20B.flac").unwrap(),
+            DeepLink::Play {
+                path: PathBuf::from("/music/A + B.flac")
+            }
+        );
+        assert_eq!(
+            DeepLink::parse("musicminder://queue/?x=1&path=C:%5CMusic%5Ca.mp3").unwrap(),
+            DeepLink::Queue {
+                path: PathBuf::from(r"C:\Music\a.mp3")
+            }
+        );
+        assert_eq!(
+            DeepLink::parse("musicminder://album/3F8A2C44-1B2E-4F7A-9C11-0D5E6A7B8C9D").unwrap(),
+            DeepLink::Album {
+                mbid: "3f8a2c44-1b2e-4f7a-9c11-0d5e6a7b8c9d".to_string()
+            }
+        );
+        assert_eq!(
+            DeepLink::parse("musicminder://search?q=daft+punk%21").unwrap(),
+            DeepLink::Search {
+                query: "daft punk!".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_links() {
+        assert!(matches!(
+            DeepLink::parse("https://example.com"),
+            Err(DeepLinkError::NotDeepLink(_))
+        ));
+        assert!(matches!(
+            DeepLink::parse("musicminder://play"),
+            Err(DeepLinkError::MissingParam("play", "path"))
+        ));
+        assert!(matches!(
+            DeepLink::parse("musicminder://album/not-an-id"),
+            Err(DeepLinkError::InvalidMbid(_))
+        ));
+        assert!(matches!(
+            DeepLink::parse("musicminder://delete?path=/x"),
+            Err(DeepLinkError::UnknownAction(_))
+        ));
+    }
+
+    #[test]
+    fn test_desktop_entry_quotes_exec() {
+        let entry = desktop_entry(std::path::Path::new("/opt/My $Apps/music-minder"));
+        assert!(entry.contains("Exec=\"/opt/My \\\\$Apps/music-minder\" open %u\n"));
+        assert!(entry.contains("MimeType=x-scheme-handler/musicminder;\n"));
+    }
+}
@@ -1,7 +1,30 @@
 //! Audio Device Diagnostics
 //!
-//! Enumerates audio devices and their capabilities.
-//! Uses Windows Multimedia APIs for device enumeration.
+//! Enumerates audio devices and their capabilities through cpal (WASAPI,
+//! CoreAudio or ALSA): sample rates, sample formats, buffer sizes and
+//! whether a device can be opened exclusively.
+//!
+//! The player opens the default output at its current sample rate and
+//! resamples anything else, so [`sample_rate_check`] compares that rate with
+//! the rate most of the library is at.
+//!
+//! Builds without the `player` feature have no cpal and find no devices.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "player")]
+use cpal::traits::{DeviceTrait, HostTrait};
+
+use super::{CheckStatus, DiagnosticCheck};
+
+/// Rates reported as supported when a device's ranges cover them
+const STANDARD_RATES: [u32; 13] = [
+    8000, 11025, 16000, 22050, 32000, 44100, 48000, 88200, 96000, 176400, 192000, 352800, 384000,
+];
+
+/// Tracks whose headers are read to find the library's sample rates
+pub const RATE_SAMPLE_SIZE: usize = 200;
 
 /// Audio device information
 #[derive(Debug, Clone)]
@@ -16,6 +39,14 @@ pub struct AudioDeviceInfo {
     pub sample_rates: Vec<u32>,
     /// Number of channels
     pub channels: Option<u32>,
+    /// The rate the device runs at in shared mode (what playback uses)
+    pub default_sample_rate: Option<u32>,
+    /// Supported sample formats ("i16", "f32", ...)
+    pub sample_formats: Vec<String>,
+    /// Smallest and largest buffer, in frames (if the driver reports them)
+    pub buffer_size: Option<(u32, u32)>,
+    /// Whether the device can be opened exclusively
+    pub exclusive: ExclusiveMode,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -24,198 +55,162 @@ pub enum AudioDeviceType {
     Input,
 }
 
-impl AudioDeviceInfo {
-    /// Enumerate all audio devices
-    #[cfg(windows)]
-    pub fn enumerate() -> Vec<Self> {
-        let mut devices = Vec::new();
-
-        // Enumerate output devices using waveOut
-        devices.extend(enumerate_wave_out_devices());
-
-        // Enumerate input devices using waveIn
-        devices.extend(enumerate_wave_in_devices());
+/// Whether a device can be opened for exclusive (bit-perfect) access
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExclusiveMode {
+    /// Direct hardware access that bypasses the system mixer
+    Available,
+    /// Goes through the system mixer (dmix, PulseAudio, PipeWire)
+    SharedOnly,
+    /// The audio API doesn't say
+    Unknown,
+}
 
-        devices
+impl ExclusiveMode {
+    /// From an ALSA device name: `hw:`, `plughw:` and `front:` open the
+    /// card directly; everything else goes through a mixer.
+    #[cfg_attr(not(feature = "player"), allow(dead_code))]
+    fn from_alsa_name(name: &str) -> Self {
+        if ["hw:", "plughw:", "front:"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            Self::Available
+        } else {
+            Self::SharedOnly
+        }
     }
 
-    #[cfg(not(windows))]
-    pub fn enumerate() -> Vec<Self> {
-        Vec::new()
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Available => "available",
+            Self::SharedOnly => "shared only",
+            Self::Unknown => "unknown",
+        }
     }
 }
 
-#[cfg(windows)]
-fn enumerate_wave_out_devices() -> Vec<AudioDeviceInfo> {
-    use std::mem::MaybeUninit;
-
-    #[repr(C)]
-    #[allow(non_snake_case, clippy::upper_case_acronyms)]
-    struct WAVEOUTCAPSW {
-        wMid: u16,
-        wPid: u16,
-        vDriverVersion: u32,
-        szPname: [u16; 32],
-        dwFormats: u32,
-        wChannels: u16,
-        wReserved1: u16,
-        dwSupport: u32,
-    }
-
-    #[link(name = "winmm")]
-    unsafe extern "system" {
-        fn waveOutGetNumDevs() -> u32;
-        fn waveOutGetDevCapsW(uDeviceID: usize, pwoc: *mut WAVEOUTCAPSW, cbwoc: u32) -> u32;
+impl AudioDeviceInfo {
+    /// Enumerate all audio devices
+    #[cfg(not(feature = "player"))]
+    pub fn enumerate() -> Vec<Self> {
+        Vec::new()
     }
 
-    let mut devices = Vec::new();
-
-    unsafe {
-        let num_devs = waveOutGetNumDevs();
-
-        for i in 0..num_devs {
-            let mut caps = MaybeUninit::<WAVEOUTCAPSW>::uninit();
-
-            if waveOutGetDevCapsW(
-                i as usize,
-                caps.as_mut_ptr(),
-                std::mem::size_of::<WAVEOUTCAPSW>() as u32,
-            ) == 0
-            {
-                let caps = caps.assume_init();
-
-                // Convert name from UTF-16
-                let name_len = caps.szPname.iter().position(|&c| c == 0).unwrap_or(32);
-                let name = String::from_utf16_lossy(&caps.szPname[..name_len]);
-
-                // Parse supported sample rates from dwFormats
-                let sample_rates = parse_wave_formats(caps.dwFormats);
+    /// Enumerate all audio devices
+    #[cfg(feature = "player")]
+    pub fn enumerate() -> Vec<Self> {
+        let host = cpal::default_host();
+        let default_output = host.default_output_device().and_then(|d| d.name().ok());
+        let default_input = host.default_input_device().and_then(|d| d.name().ok());
 
-                devices.push(AudioDeviceInfo {
+        let mut devices = Vec::new();
+        if let Ok(outputs) = host.output_devices() {
+            for device in outputs {
+                let Ok(name) = device.name() else { continue };
+                let is_default = default_output.as_deref() == Some(name.as_str());
+                let configs: Vec<_> = device
+                    .supported_output_configs()
+                    .map(|c| c.collect())
+                    .unwrap_or_default();
+                let default_rate = device
+                    .default_output_config()
+                    .ok()
+                    .map(|c| c.sample_rate().0);
+                devices.push(Self::from_configs(
                     name,
-                    device_type: AudioDeviceType::Output,
-                    is_default: i == 0, // First device is typically default
-                    sample_rates,
-                    channels: Some(caps.wChannels as u32),
-                });
+                    AudioDeviceType::Output,
+                    is_default,
+                    &configs,
+                    default_rate,
+                ));
             }
         }
-    }
-
-    devices
-}
-
-#[cfg(windows)]
-fn enumerate_wave_in_devices() -> Vec<AudioDeviceInfo> {
-    use std::mem::MaybeUninit;
-
-    #[repr(C)]
-    #[allow(non_snake_case, clippy::upper_case_acronyms)]
-    struct WAVEINCAPSW {
-        wMid: u16,
-        wPid: u16,
-        vDriverVersion: u32,
-        szPname: [u16; 32],
-        dwFormats: u32,
-        wChannels: u16,
-    }
-
-    #[link(name = "winmm")]
-    unsafe extern "system" {
-        fn waveInGetNumDevs() -> u32;
-        fn waveInGetDevCapsW(uDeviceID: usize, pwic: *mut WAVEINCAPSW, cbwic: u32) -> u32;
-    }
-
-    let mut devices = Vec::new();
-
-    unsafe {
-        let num_devs = waveInGetNumDevs();
-
-        for i in 0..num_devs {
-            let mut caps = MaybeUninit::<WAVEINCAPSW>::uninit();
-
-            if waveInGetDevCapsW(
-                i as usize,
-                caps.as_mut_ptr(),
-                std::mem::size_of::<WAVEINCAPSW>() as u32,
-            ) == 0
-            {
-                let caps = caps.assume_init();
-
-                let name_len = caps.szPname.iter().position(|&c| c == 0).unwrap_or(32);
-                let name = String::from_utf16_lossy(&caps.szPname[..name_len]);
-
-                let sample_rates = parse_wave_formats(caps.dwFormats);
-
-                devices.push(AudioDeviceInfo {
+        if let Ok(inputs) = host.input_devices() {
+            for device in inputs {
+                let Ok(name) = device.name() else { continue };
+                let is_default = default_input.as_deref() == Some(name.as_str());
+                let configs: Vec<_> = device
+                    .supported_input_configs()
+                    .map(|c| c.collect())
+                    .unwrap_or_default();
+                let default_rate = device
+                    .default_input_config()
+                    .ok()
+                    .map(|c| c.sample_rate().0);
+                devices.push(Self::from_configs(
                     name,
-                    device_type: AudioDeviceType::Input,
-                    is_default: i == 0,
-                    sample_rates,
-                    channels: Some(caps.wChannels as u32),
-                });
+                    AudioDeviceType::Input,
+                    is_default,
+                    &configs,
+                    default_rate,
+                ));
             }
         }
+        devices
     }
 
-    devices
-}
-
-#[cfg(windows)]
-fn parse_wave_formats(formats: u32) -> Vec<u32> {
-    // WAVE_FORMAT_* flags indicate supported sample rates
-    let mut rates = Vec::new();
-
-    // 11.025 kHz
-    if formats & 0x00000001 != 0
-        || formats & 0x00000002 != 0
-        || formats & 0x00000004 != 0
-        || formats & 0x00000008 != 0
-    {
-        rates.push(11025);
-    }
-
-    // 22.05 kHz
-    if formats & 0x00000010 != 0
-        || formats & 0x00000020 != 0
-        || formats & 0x00000040 != 0
-        || formats & 0x00000080 != 0
-    {
-        rates.push(22050);
-    }
-
-    // 44.1 kHz
-    if formats & 0x00000100 != 0
-        || formats & 0x00000200 != 0
-        || formats & 0x00000400 != 0
-        || formats & 0x00000800 != 0
-    {
-        rates.push(44100);
-    }
-
-    // 48 kHz
-    if formats & 0x00001000 != 0
-        || formats & 0x00002000 != 0
-        || formats & 0x00004000 != 0
-        || formats & 0x00008000 != 0
-    {
-        rates.push(48000);
+    #[cfg(feature = "player")]
+    fn from_configs(
+        name: String,
+        device_type: AudioDeviceType,
+        is_default: bool,
+        configs: &[cpal::SupportedStreamConfigRange],
+        default_sample_rate: Option<u32>,
+    ) -> Self {
+        let ranges: Vec<(u32, u32)> = configs
+            .iter()
+            .map(|c| (c.min_sample_rate().0, c.max_sample_rate().0))
+            .collect();
+        let mut sample_formats: Vec<String> = Vec::new();
+        for config in configs {
+            let format = config.sample_format().to_string();
+            if !sample_formats.contains(&format) {
+                sample_formats.push(format);
+            }
+        }
+        let buffer_size = configs
+            .iter()
+            .filter_map(|c| match *c.buffer_size() {
+                cpal::SupportedBufferSize::Range { min, max } => Some((min, max)),
+                cpal::SupportedBufferSize::Unknown => None,
+            })
+            .reduce(|(a_min, a_max), (b_min, b_max)| (a_min.min(b_min), a_max.max(b_max)));
+        let exclusive = if cfg!(target_os = "linux") {
+            ExclusiveMode::from_alsa_name(&name)
+        } else {
+            ExclusiveMode::Unknown
+        };
+
+        Self {
+            sample_rates: rates_in_ranges(&ranges),
+            channels: configs.iter().map(|c| u32::from(c.channels())).max(),
+            default_sample_rate,
+            sample_formats,
+            buffer_size,
+            exclusive,
+            name,
+            device_type,
+            is_default,
+        }
     }
 
-    // 96 kHz
-    if formats & 0x00010000 != 0
-        || formats & 0x00020000 != 0
-        || formats & 0x00040000 != 0
-        || formats & 0x00080000 != 0
-    {
-        rates.push(96000);
+    /// Whether the device can run at `rate` without resampling
+    pub fn supports_rate(&self, rate: u32) -> bool {
+        self.sample_rates.contains(&rate) || self.default_sample_rate == Some(rate)
     }
+}
 
-    rates
+/// The standard rates that fall within any of the (min, max) ranges
+#[cfg_attr(not(feature = "player"), allow(dead_code))]
+fn rates_in_ranges(ranges: &[(u32, u32)]) -> Vec<u32> {
+    STANDARD_RATES
+        .into_iter()
+        .filter(|rate| ranges.iter().any(|(min, max)| (min..=max).contains(&rate)))
+        .collect()
 }
 
 /// Get the default audio output device name
-#[cfg(windows)]
 pub fn get_default_output_device() -> Option<String> {
     AudioDeviceInfo::enumerate()
         .into_iter()
@@ -223,40 +218,160 @@ pub fn get_default_output_device() -> Option<String> {
         .map(|d| d.name)
 }
 
+/// Up to [`RATE_SAMPLE_SIZE`] paths spread evenly over the library
+pub fn sample_paths<'a>(paths: impl ExactSizeIterator<Item = &'a str>) -> Vec<PathBuf> {
+    let step = paths.len().div_ceil(RATE_SAMPLE_SIZE).max(1);
+    paths.step_by(step).map(PathBuf::from).collect()
+}
+
+/// Sample rates of the given files and how many are at each, most common
+/// first. Reads only the headers; unreadable files are skipped.
+pub fn library_sample_rates(paths: &[PathBuf]) -> Vec<(u32, usize)> {
+    let mut counts: HashMap<u32, usize> = HashMap::new();
+    for rate in paths.iter().filter_map(|p| file_sample_rate(p)) {
+        *counts.entry(rate).or_default() += 1;
+    }
+    let mut rates: Vec<(u32, usize)> = counts.into_iter().collect();
+    rates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    rates
+}
+
+fn file_sample_rate(path: &Path) -> Option<u32> {
+    use lofty::file::AudioFile;
+    let options = lofty::config::ParseOptions::new().read_tags(false);
+    let file = lofty::probe::Probe::open(path)
+        .ok()?
+        .options(options)
+        .read()
+        .ok()?;
+    file.properties().sample_rate()
+}
+
+/// Whether the library's most common sample rate plays on `device` without
+/// resampling. `None` without a device or rates to compare.
+pub fn sample_rate_check(
+    device: Option<&AudioDeviceInfo>,
+    rates: &[(u32, usize)],
+) -> Option<DiagnosticCheck> {
+    let device = device?;
+    let output_rate = device.default_sample_rate?;
+    let &(dominant, count) = rates.first()?;
+    let total: usize = rates.iter().map(|(_, n)| n).sum();
+    let share = count * 100 / total.max(1);
+    let khz = |rate: u32| format!("{} kHz", rate as f64 / 1000.0);
+
+    let (status, value, recommendation) = if dominant == output_rate {
+        (
+            CheckStatus::Pass,
+            format!("{}% of tracks at {}, played natively", share, khz(dominant)),
+            None,
+        )
+    } else if device.supports_rate(dominant) {
+        (
+            CheckStatus::Warning,
+            format!(
+                "{}% of tracks at {}, resampled to {}",
+                share,
+                khz(dominant),
+                khz(output_rate)
+            ),
+            Some(format!(
+                "{} supports {}: set it as the device's default format in the system sound settings to play them without resampling",
+                device.name,
+                khz(dominant)
+            )),
+        )
+    } else {
+        (
+            CheckStatus::Warning,
+            format!(
+                "{}% of tracks at {}, which {} doesn't support",
+                share,
+                khz(dominant),
+                device.name
+            ),
+            Some(format!(
+                "They're resampled to {}. Use an output that supports {} to play them bit-perfect",
+                khz(output_rate),
+                khz(dominant)
+            )),
+        )
+    };
+    Some(DiagnosticCheck {
+        name: "Library Sample Rate".to_string(),
+        category: "Audio".to_string(),
+        status,
+        value,
+        recommendation,
+        fix: None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    #[allow(unused_imports)]
     use super::*;
 
-    #[test]
-    #[cfg(windows)]
-    fn test_enumerate_audio_devices() {
-        let devices = AudioDeviceInfo::enumerate();
-
-        println!("Found {} audio devices:", devices.len());
-        for device in &devices {
-            let type_str = match device.device_type {
-                AudioDeviceType::Output => "Output",
-                AudioDeviceType::Input => "Input",
-            };
-            let default_str = if device.is_default { " [DEFAULT]" } else { "" };
-
-            println!("  {} ({}){}", device.name, type_str, default_str);
-            if !device.sample_rates.is_empty() {
-                println!("    Sample rates: {:?}", device.sample_rates);
-            }
-            if let Some(ch) = device.channels {
-                println!("    Channels: {}", ch);
-            }
+    fn output(default_rate: u32, ranges: &[(u32, u32)]) -> AudioDeviceInfo {
+        AudioDeviceInfo {
+            name: "Speakers".to_string(),
+            device_type: AudioDeviceType::Output,
+            is_default: true,
+            sample_rates: rates_in_ranges(ranges),
+            channels: Some(2),
+            default_sample_rate: Some(default_rate),
+            sample_formats: vec!["f32".to_string()],
+            buffer_size: None,
+            exclusive: ExclusiveMode::Unknown,
         }
     }
 
+    #[test]
+    fn test_device_capabilities() {
+        assert_eq!(
+            rates_in_ranges(&[(44100, 48000), (96000, 96000)]),
+            [44100, 48000, 96000]
+        );
+        assert_eq!(
+            ExclusiveMode::from_alsa_name("hw:CARD=PCH,DEV=0"),
+            ExclusiveMode::Available
+        );
+        assert_eq!(
+            ExclusiveMode::from_alsa_name("pipewire"),
+            ExclusiveMode::SharedOnly
+        );
+
+        let paths: Vec<String> = (0..1000).map(|i| format!("/music/{}.flac", i)).collect();
+        let sample = sample_paths(paths.iter().map(String::as_str));
+        assert_eq!(sample.len(), RATE_SAMPLE_SIZE);
+        assert_eq!(sample[1], Path::new("/music/5.flac"));
+    }
+
+    #[test]
+    fn test_sample_rate_check() {
+        let rates = [(44100, 150), (96000, 50)];
+
+        let native = sample_rate_check(Some(&output(44100, &[(44100, 96000)])), &rates).unwrap();
+        assert_eq!(native.status, CheckStatus::Pass);
+        assert_eq!(native.value, "75% of tracks at 44.1 kHz, played natively");
+
+        // Supported, but the device runs at 48 kHz
+        let shared = sample_rate_check(Some(&output(48000, &[(44100, 96000)])), &rates).unwrap();
+        assert_eq!(shared.status, CheckStatus::Warning);
+        assert!(shared.recommendation.unwrap().contains("default format"));
+
+        let unsupported = sample_rate_check(Some(&output(48000, &[(48000, 48000)])), &rates);
+        assert!(unsupported.unwrap().value.contains("doesn't support"));
+
+        assert!(sample_rate_check(None, &rates).is_none());
+        assert!(sample_rate_check(Some(&output(48000, &[])), &[]).is_none());
+    }
+
     #[test]
     #[ignore] // Requires audio hardware - run with `cargo test -- --ignored`
-    #[cfg(windows)]
-    fn test_get_default_output() {
-        let default = get_default_output_device();
-        assert!(default.is_some());
-        println!("Default output: {}", default.unwrap());
+    fn test_enumerate_audio_devices() {
+        for device in AudioDeviceInfo::enumerate() {
+            println!("{:?}", device);
+        }
+        assert!(get_default_output_device().is_some());
     }
 }
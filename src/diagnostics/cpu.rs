@@ -106,6 +106,7 @@ impl CpuInfo {
                 self.name, self.physical_cores, self.logical_cores
             ),
             recommendation: None,
+            fix: None,
         });
 
         // Frequency check
@@ -131,6 +132,7 @@ impl CpuInfo {
                 status,
                 value: format!("{} MHz", self.max_frequency_mhz),
                 recommendation,
+                fix: None,
             });
         }
 
@@ -158,6 +160,7 @@ impl CpuInfo {
                 status,
                 value: format!("{:.1}%", usage),
                 recommendation,
+                fix: None,
             });
         }
 
@@ -188,6 +191,7 @@ impl CpuInfo {
             status,
             value: format!("{} logical cores", self.logical_cores),
             recommendation,
+            fix: None,
         });
 
         checks
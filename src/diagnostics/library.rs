@@ -0,0 +1,269 @@
+//! Library database health
+//!
+//! Checks the database and what it refers to: SQLite's integrity check,
+//! orphan rows, tracks whose files are gone, tracks stored twice under two
+//! spellings of a path, and the size of the cover cache and of the
+//! write-ahead log. Each check links to the maintenance tool that fixes it.
+
+use std::path::Path;
+
+use sqlx::SqlitePool;
+
+use super::{CheckStatus, DiagnosticCheck, FixAction};
+use crate::{cover, db};
+
+/// Write-ahead log size past which a checkpoint is suggested
+const WAL_WARN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Share of the cover cache limit past which clearing it is suggested
+const COVER_CACHE_WARN_SHARE: f64 = 0.9;
+
+/// What the library checks found
+#[derive(Debug, Clone, Default)]
+pub struct LibraryHealth {
+    /// Integrity check problems (empty if sound)
+    pub integrity: Vec<String>,
+    /// Orphan rows per table
+    pub orphans: Vec<(&'static str, i64)>,
+    pub tracks: usize,
+    /// Tracks whose files don't exist
+    pub missing_files: usize,
+    /// Tracks stored under another spelling of a path in the library
+    pub duplicate_paths: usize,
+    pub cover_cache: cover::CacheStats,
+    /// Size of the write-ahead log, if there is one
+    pub wal_bytes: Option<u64>,
+}
+
+impl LibraryHealth {
+    /// Run every check. Reads the database and looks for each track's file.
+    pub async fn check(pool: &SqlitePool) -> sqlx::Result<Self> {
+        let integrity = db::maintenance::integrity_problems(pool).await?;
+        let orphans = db::maintenance::orphan_counts(pool).await?;
+        let duplicate_paths = db::maintenance::duplicate_paths(pool).await?;
+        let wal_bytes = db::maintenance::wal_bytes(pool).await?;
+        let files = db::get_all_track_file_info(pool).await?;
+        let tracks = files.len();
+        let (missing_files, cover_cache) = tokio::task::spawn_blocking(move || {
+            let missing = files
+                .iter()
+                .filter(|t| !Path::new(&t.path).exists())
+                .count();
+            (missing, cover::CoverCache::default_location().stats())
+        })
+        .await
+        .unwrap_or_default();
+        Ok(Self {
+            integrity,
+            orphans,
+            tracks,
+            missing_files,
+            duplicate_paths,
+            cover_cache,
+            wal_bytes,
+        })
+    }
+
+    /// One check per finding, in the "Library" category
+    pub fn to_checks(&self) -> Vec<DiagnosticCheck> {
+        let mut checks = Vec::new();
+
+        checks.push(match self.integrity.first() {
+            None => check("Database Integrity", CheckStatus::Pass, "OK", None, None),
+            Some(first) => check(
+                "Database Integrity",
+                CheckStatus::Fail,
+                &format!("{} problem(s): {}", self.integrity.len(), first),
+                Some(
+                    "Rebuild the indexes; if problems remain, restore a backup from the backups folder",
+                ),
+                Some(FixAction::Reindex),
+            ),
+        });
+
+        let orphans: i64 = self.orphans.iter().map(|(_, n)| n).sum();
+        checks.push(if orphans == 0 {
+            check("Orphan Rows", CheckStatus::Pass, "None", None, None)
+        } else {
+            let tables: Vec<String> = self
+                .orphans
+                .iter()
+                .map(|(table, n)| format!("{} {}", n, table.replace('_', " ")))
+                .collect();
+            check(
+                "Orphan Rows",
+                CheckStatus::Warning,
+                &tables.join(", "),
+                Some("Remove the rows left behind by removed tracks"),
+                Some(FixAction::RemoveOrphans),
+            )
+        });
+
+        checks.push(match self.missing_files {
+            0 => check(
+                "Missing Files",
+                CheckStatus::Pass,
+                &format!("All {} files present", self.tracks),
+                None,
+                None,
+            ),
+            n => check(
+                "Missing Files",
+                CheckStatus::Warning,
+                &format!("{} of {} tracks", n, self.tracks),
+                Some(
+                    "Reconnect the drive they're on, or scan the library to drop tracks that were deleted",
+                ),
+                Some(FixAction::Rescan),
+            ),
+        });
+
+        checks.push(match self.duplicate_paths {
+            0 => check("Duplicate Paths", CheckStatus::Pass, "None", None, None),
+            n => check(
+                "Duplicate Paths",
+                CheckStatus::Warning,
+                &format!("{} track(s) stored twice", n),
+                Some("Merge each into the track for the same file, keeping plays and ratings"),
+                Some(FixAction::MergeDuplicatePaths),
+            ),
+        });
+
+        let cache = &self.cover_cache;
+        let near_limit =
+            cache.limit > 0 && cache.bytes as f64 >= cache.limit as f64 * COVER_CACHE_WARN_SHARE;
+        let size = format!("{} ({} covers)", megabytes(cache.bytes), cache.images);
+        checks.push(if near_limit {
+            check(
+                "Cover Cache",
+                CheckStatus::Warning,
+                &format!("{} of {}", size, megabytes(cache.limit)),
+                Some("Older covers are being evicted; clear the cache or raise its limit"),
+                Some(FixAction::ClearCoverCache),
+            )
+        } else {
+            check("Cover Cache", CheckStatus::Info, &size, None, None)
+        });
+
+        if let Some(wal) = self.wal_bytes {
+            checks.push(if wal >= WAL_WARN_BYTES {
+                check(
+                    "Write-Ahead Log",
+                    CheckStatus::Warning,
+                    &megabytes(wal),
+                    Some("Fold the log back into the database file"),
+                    Some(FixAction::Checkpoint),
+                )
+            } else {
+                check(
+                    "Write-Ahead Log",
+                    CheckStatus::Pass,
+                    &megabytes(wal),
+                    None,
+                    None,
+                )
+            });
+        }
+
+        checks
+    }
+}
+
+/// Run a fix that works on the database alone; returns what it did.
+/// [`FixAction::Rescan`] and [`FixAction::ClearCoverCache`] belong to the
+/// scan job and the cover cache, and do nothing here.
+pub async fn apply_fix(pool: &SqlitePool, fix: FixAction) -> sqlx::Result<String> {
+    Ok(match fix {
+        FixAction::Reindex => {
+            db::maintenance::reindex(pool).await?;
+            "Indexes rebuilt".to_string()
+        }
+        FixAction::RemoveOrphans => {
+            let removed = db::maintenance::remove_orphans(pool).await?;
+            format!("Removed {} orphan row(s)", removed)
+        }
+        FixAction::MergeDuplicatePaths => {
+            let merged = db::paths::normalize_track_paths(pool, &db::paths::policy(), true).await?;
+            format!("Merged {} duplicate track(s)", merged)
+        }
+        FixAction::Checkpoint => {
+            db::maintenance::checkpoint(pool).await?;
+            "Database checkpointed".to_string()
+        }
+        FixAction::Rescan | FixAction::ClearCoverCache => String::new(),
+    })
+}
+
+fn check(
+    name: &str,
+    status: CheckStatus,
+    value: &str,
+    recommendation: Option<&str>,
+    fix: Option<FixAction>,
+) -> DiagnosticCheck {
+    DiagnosticCheck {
+        name: name.to_string(),
+        category: "Library".to_string(),
+        status,
+        value: value.to_string(),
+        recommendation: recommendation.map(String::from),
+        fix,
+    }
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / 1_000_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{insert_mock_track, temp_db};
+
+    #[test]
+    fn test_findings_link_to_their_fixes() {
+        let health = LibraryHealth {
+            integrity: vec!["wrong # of entries in index idx_tracks_path_key".to_string()],
+            orphans: vec![("play_history", 3), ("albums", 1)],
+            tracks: 10,
+            missing_files: 2,
+            duplicate_paths: 0,
+            cover_cache: cover::CacheStats {
+                bytes: 95_000_000,
+                images: 400,
+                releases: 380,
+                limit: 100_000_000,
+            },
+            wal_bytes: Some(1024),
+        };
+        let checks = health.to_checks();
+        let find = |name: &str| checks.iter().find(|c| c.name == name).unwrap();
+
+        assert_eq!(find("Database Integrity").status, CheckStatus::Fail);
+        assert_eq!(find("Database Integrity").fix, Some(FixAction::Reindex));
+        assert_eq!(find("Orphan Rows").value, "3 play history, 1 albums");
+        assert_eq!(find("Orphan Rows").fix, Some(FixAction::RemoveOrphans));
+        assert_eq!(find("Missing Files").value, "2 of 10 tracks");
+        assert_eq!(find("Missing Files").fix, Some(FixAction::Rescan));
+        assert_eq!(find("Duplicate Paths").status, CheckStatus::Pass);
+        assert_eq!(find("Duplicate Paths").fix, None);
+        assert_eq!(find("Cover Cache").fix, Some(FixAction::ClearCoverCache));
+        assert_eq!(find("Write-Ahead Log").status, CheckStatus::Pass);
+        assert!(checks.iter().all(|c| c.category == "Library"));
+    }
+
+    #[tokio::test]
+    async fn test_check_finds_missing_files() {
+        let (pool, dir) = temp_db().await;
+        let present = dir.path().join("present.mp3");
+        std::fs::write(&present, b"").unwrap();
+        insert_mock_track(&pool, &present.to_string_lossy()).await;
+        insert_mock_track(&pool, &dir.path().join("deleted.mp3").to_string_lossy()).await;
+
+        let health = LibraryHealth::check(&pool).await.unwrap();
+        assert!(health.integrity.is_empty());
+        assert_eq!(health.tracks, 2);
+        assert_eq!(health.missing_files, 1);
+        assert_eq!(health.duplicate_paths, 0);
+    }
+}
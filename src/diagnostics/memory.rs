@@ -109,6 +109,7 @@ impl MemoryInfo {
             status,
             value: format!("{:.1} GB", total_gb),
             recommendation,
+            fix: None,
         });
 
         // Available RAM check
@@ -136,6 +137,7 @@ impl MemoryInfo {
                 (1.0 - self.memory_load as f64 / 100.0) * 100.0
             ),
             recommendation,
+            fix: None,
         });
 
         // Memory pressure check
@@ -159,17 +161,62 @@ impl MemoryInfo {
             status,
             value: format!("{}% in use", self.memory_load),
             recommendation,
+            fix: None,
         });
 
         checks
     }
 }
 
+/// Memory the loaded track list may use per track: 100 MB for 300k tracks
+pub const TRACK_LIST_BYTES_PER_TRACK: usize = 333;
+
+/// How much memory the app's in-memory track list takes, against its budget
+pub fn track_list_check(tracks: usize, bytes: usize) -> DiagnosticCheck {
+    let per_track = bytes.checked_div(tracks).unwrap_or(0);
+    let (status, recommendation) = if per_track <= TRACK_LIST_BYTES_PER_TRACK {
+        (CheckStatus::Pass, None)
+    } else {
+        (
+            CheckStatus::Warning,
+            Some(format!(
+                "Tracks take more than the {} bytes each budgeted; very long titles or paths add up in a large library.",
+                TRACK_LIST_BYTES_PER_TRACK
+            )),
+        )
+    };
+    DiagnosticCheck {
+        name: "Track List".to_string(),
+        category: "Memory".to_string(),
+        status,
+        value: format!(
+            "{:.1} MB for {} tracks ({} bytes each)",
+            bytes as f64 / 1_000_000.0,
+            tracks,
+            per_track
+        ),
+        recommendation,
+        fix: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
     use super::*;
 
+    #[test]
+    fn test_track_list_check() {
+        let check = track_list_check(300_000, 90_000_000);
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert_eq!(check.value, "90.0 MB for 300000 tracks (300 bytes each)");
+        assert_eq!(
+            track_list_check(1000, 1_000_000).status,
+            CheckStatus::Warning
+        );
+        assert_eq!(track_list_check(0, 0).status, CheckStatus::Pass);
+    }
+
     #[test]
     #[cfg(windows)]
     fn test_query_memory_info() {
@@ -8,8 +8,11 @@
 //! - CPU information and frequency
 //! - Memory availability and pressure
 //! - Power plan (affects CPU throttling)
-//! - Audio device enumeration
+//! - Audio device capabilities, and whether the library's sample rate
+//!   plays without resampling
 //! - Interrupt latency estimation
+//! - Library database health: integrity, orphan rows, missing files,
+//!   duplicate paths, cover cache and write-ahead log size
 //!
 //! ## Architecture Note
 //! True DPC/ISR latency measurement requires kernel-mode access (ETW tracing
@@ -18,6 +21,7 @@
 
 mod audio;
 mod cpu;
+mod library;
 mod memory;
 mod power;
 mod report;
@@ -25,6 +29,7 @@ mod timer;
 
 pub use audio::*;
 pub use cpu::*;
+pub use library::*;
 pub use memory::*;
 pub use power::*;
 pub use timer::*;
@@ -81,6 +86,50 @@ pub struct DiagnosticCheck {
     pub status: CheckStatus,
     pub value: String,
     pub recommendation: Option<String>,
+    /// The maintenance tool that fixes what the check found
+    pub fix: Option<FixAction>,
+}
+
+/// A maintenance tool a check links to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixAction {
+    /// Rebuild the database's indexes
+    Reindex,
+    /// Delete rows left pointing at removed tracks, albums or artists
+    RemoveOrphans,
+    /// Run the library scan job, which drops tracks whose files are gone
+    Rescan,
+    /// Merge tracks stored twice under different spellings of one path
+    MergeDuplicatePaths,
+    /// Delete the downloaded cover cache
+    ClearCoverCache,
+    /// Fold the write-ahead log back into the database file
+    Checkpoint,
+}
+
+impl FixAction {
+    /// Button text
+    pub fn label(&self) -> &'static str {
+        match self {
+            FixAction::Reindex => "Rebuild indexes",
+            FixAction::RemoveOrphans => "Remove orphan rows",
+            FixAction::Rescan => "Scan library now",
+            FixAction::MergeDuplicatePaths => "Merge duplicates",
+            FixAction::ClearCoverCache => "Clear cover cache",
+            FixAction::Checkpoint => "Checkpoint database",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FixAction::Reindex => "reindex",
+            FixAction::RemoveOrphans => "remove_orphans",
+            FixAction::Rescan => "rescan",
+            FixAction::MergeDuplicatePaths => "merge_duplicate_paths",
+            FixAction::ClearCoverCache => "clear_cover_cache",
+            FixAction::Checkpoint => "checkpoint",
+        }
+    }
 }
 
 /// Status of a diagnostic check
@@ -114,6 +163,9 @@ pub struct DiagnosticReport {
     pub memory_info: Option<MemoryInfo>,
     pub power_info: Option<PowerInfo>,
     pub audio_devices: Vec<AudioDeviceInfo>,
+    /// Sample rates of a sample of the library, most common first (empty
+    /// unless [`DiagnosticReport::with_library`] was called)
+    pub library_sample_rates: Vec<(u32, usize)>,
     /// SIMD benchmark results (if run)
     pub simd_benchmark: Option<crate::player::simd::SimdBenchmarkResults>,
 }
@@ -129,12 +181,14 @@ impl Default for DiagnosticReport {
                 status: CheckStatus::Warning,
                 value: "Failed to generate report".to_string(),
                 recommendation: Some("Try running diagnostics again".to_string()),
+                fix: None,
             }],
             timer_info: None,
             cpu_info: None,
             memory_info: None,
             power_info: None,
             audio_devices: vec![],
+            library_sample_rates: vec![],
             simd_benchmark: None,
         }
     }
@@ -156,6 +210,7 @@ impl DiagnosticReport {
                 "Run with 'cargo run --release' for accurate benchmarks and best performance"
                     .to_string(),
             ),
+            fix: None,
         });
 
         #[cfg(not(debug_assertions))]
@@ -165,6 +220,7 @@ impl DiagnosticReport {
             status: CheckStatus::Pass,
             value: "Release build (optimized)".to_string(),
             recommendation: None,
+            fix: None,
         });
 
         // SIMD capabilities (for audio processing acceleration)
@@ -185,6 +241,7 @@ impl DiagnosticReport {
             } else {
                 None
             },
+            fix: None,
         });
 
         // Run SIMD benchmark
@@ -205,6 +262,7 @@ impl DiagnosticReport {
                     bench.volume_speedup, bench.volume_scalar_ns, bench.volume_simd_ns
                 ),
                 recommendation: None,
+                fix: None,
             });
 
             checks.push(DiagnosticCheck {
@@ -222,6 +280,7 @@ impl DiagnosticReport {
                     bench.convert_speedup, bench.convert_scalar_ns, bench.convert_simd_ns
                 ),
                 recommendation: None,
+                fix: None,
             });
         }
 
@@ -258,6 +317,7 @@ impl DiagnosticReport {
                 status: CheckStatus::Info,
                 value: format!("{} device(s) found", audio_devices.len()),
                 recommendation: None,
+                fix: None,
             });
         }
 
@@ -273,10 +333,40 @@ impl DiagnosticReport {
             memory_info,
             power_info,
             audio_devices,
+            library_sample_rates: Vec::new(),
             simd_benchmark,
         }
     }
 
+    /// Add the library sample rate check, from a sample of the library's
+    /// files (see [`sample_paths`]). Blocking: reads the file headers.
+    pub fn with_library(mut self, sample: &[std::path::PathBuf]) -> Self {
+        self.library_sample_rates = library_sample_rates(sample);
+        let output = self
+            .audio_devices
+            .iter()
+            .find(|d| d.device_type == AudioDeviceType::Output && d.is_default);
+        if let Some(check) = sample_rate_check(output, &self.library_sample_rates) {
+            self.checks.push(check);
+            self.overall_rating = Self::calculate_rating(&self.checks);
+        }
+        self
+    }
+
+    /// Add the library database checks (see [`LibraryHealth`])
+    pub fn with_library_health(mut self, health: &LibraryHealth) -> Self {
+        self.checks.extend(health.to_checks());
+        self.overall_rating = Self::calculate_rating(&self.checks);
+        self
+    }
+
+    /// Add the memory the app's track list uses (see [`track_list_check`])
+    pub fn with_track_list(mut self, tracks: usize, bytes: usize) -> Self {
+        self.checks.push(track_list_check(tracks, bytes));
+        self.overall_rating = Self::calculate_rating(&self.checks);
+        self
+    }
+
     fn calculate_rating(checks: &[DiagnosticCheck]) -> AudioReadiness {
         let fail_count = checks
             .iter()
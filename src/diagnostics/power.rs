@@ -97,6 +97,7 @@ impl PowerInfo {
             status,
             value: self.scheme_name.clone(),
             recommendation,
+            fix: None,
         }
     }
 }
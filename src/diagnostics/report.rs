@@ -30,7 +30,7 @@ impl DiagnosticReport {
         }
 
         // Print each category
-        for category in ["System", "CPU", "Memory", "Audio"] {
+        for category in ["System", "CPU", "Memory", "Audio", "Library"] {
             if let Some(checks) = categories.get(category) {
                 println!(
                     "┌─ {} ─────────────────────────────────────────────────",
@@ -48,6 +48,9 @@ impl DiagnosticReport {
                             println!("│     └─ {}", line);
                         }
                     }
+                    if let Some(fix) = check.fix {
+                        println!("│     └─ Fix: {}", fix.label());
+                    }
                 }
                 println!("└────────────────────────────────────────────────────────────");
                 println!();
@@ -69,10 +72,22 @@ impl DiagnosticReport {
                     let rates: Vec<String> = device
                         .sample_rates
                         .iter()
-                        .map(|r| format!("{}kHz", r / 1000))
+                        .map(|r| format!("{}kHz", *r as f64 / 1000.0))
                         .collect();
                     println!("│       Rates: {}", rates.join(", "));
                 }
+                if let Some(rate) = device.default_sample_rate {
+                    println!("│       Running at: {}kHz", rate as f64 / 1000.0);
+                }
+                if !device.sample_formats.is_empty() {
+                    println!("│       Formats: {}", device.sample_formats.join(", "));
+                }
+                if let Some((min, max)) = device.buffer_size {
+                    println!("│       Buffer: {}-{} frames", min, max);
+                }
+                if device.exclusive != ExclusiveMode::Unknown {
+                    println!("│       Exclusive mode: {}", device.exclusive.as_str());
+                }
             }
             println!("└────────────────────────────────────────────────────────────");
             println!();
@@ -137,9 +152,18 @@ impl DiagnosticReport {
                 CheckStatus::Fail => "fail",
                 CheckStatus::Info => "info",
             };
+            let recommendation = check
+                .recommendation
+                .as_ref()
+                .map_or("null".to_string(), |r| {
+                    format!("\"{}\"", r.replace('"', "\\\""))
+                });
+            let fix = check
+                .fix
+                .map_or("null".to_string(), |f| format!("\"{}\"", f.as_str()));
             json.push_str(&format!(
-                "    {{\"name\": \"{}\", \"category\": \"{}\", \"status\": \"{}\", \"value\": \"{}\"}}",
-                check.name, check.category, status, check.value.replace('"', "\\\"")
+                "    {{\"name\": \"{}\", \"category\": \"{}\", \"status\": \"{}\", \"value\": \"{}\", \"recommendation\": {}, \"fix\": {}}}",
+                check.name, check.category, status, check.value.replace('"', "\\\""), recommendation, fix
             ));
             if i < self.checks.len() - 1 {
                 json.push(',');
@@ -155,18 +179,51 @@ impl DiagnosticReport {
                 AudioDeviceType::Output => "output",
                 AudioDeviceType::Input => "input",
             };
+            let join = |values: &[u32]| {
+                values
+                    .iter()
+                    .map(u32::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            let optional = |value: Option<u32>| value.map_or("null".to_string(), |v| v.to_string());
+            let formats: Vec<String> = device
+                .sample_formats
+                .iter()
+                .map(|f| format!("\"{}\"", f))
+                .collect();
             json.push_str(&format!(
-                "    {{\"name\": \"{}\", \"type\": \"{}\", \"default\": {}}}",
+                "    {{\"name\": \"{}\", \"type\": \"{}\", \"default\": {}, \"sample_rates\": [{}], \"default_sample_rate\": {}, \"channels\": {}, \"sample_formats\": [{}], \"buffer_size\": {}, \"exclusive\": \"{}\"}}",
                 device.name.replace('"', "\\\""),
                 dev_type,
-                device.is_default
+                device.is_default,
+                join(&device.sample_rates),
+                optional(device.default_sample_rate),
+                optional(device.channels),
+                formats.join(", "),
+                device.buffer_size.map_or("null".to_string(), |(min, max)| format!(
+                    "{{\"min\": {}, \"max\": {}}}",
+                    min, max
+                )),
+                device.exclusive.as_str()
             ));
             if i < self.audio_devices.len() - 1 {
                 json.push(',');
             }
             json.push('\n');
         }
-        json.push_str("  ]\n");
+        json.push_str("  ],\n");
+
+        // Library sample rates
+        let rates: Vec<String> = self
+            .library_sample_rates
+            .iter()
+            .map(|(rate, count)| format!("{{\"rate\": {}, \"tracks\": {}}}", rate, count))
+            .collect();
+        json.push_str(&format!(
+            "  \"library_sample_rates\": [{}]\n",
+            rates.join(", ")
+        ));
 
         json.push('}');
         json
@@ -197,6 +254,8 @@ mod tests {
         assert!(json.contains("\"timestamp\""));
         assert!(json.contains("\"rating\""));
         assert!(json.contains("\"checks\""));
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(parsed["audio_devices"].is_array());
 
         println!("{}", json);
     }
@@ -104,6 +104,7 @@ impl TimerInfo {
                 self.worst_resolution_us as f64 / 1000.0
             ),
             recommendation,
+            fix: None,
         }
     }
 }
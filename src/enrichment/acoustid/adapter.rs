@@ -32,11 +32,12 @@ pub fn to_identifications(
 /// Each recording is expanded with all its release groups to enable better matching
 fn convert_result_to_identifications(result: dto::LookupResult) -> Vec<TrackIdentification> {
     let score = result.score;
+    let acoustid_id = result.id;
 
     result
         .recordings
         .into_iter()
-        .flat_map(|recording| convert_recording_to_identifications(recording, score))
+        .flat_map(|recording| convert_recording_to_identifications(recording, score, &acoustid_id))
         .collect()
 }
 
@@ -44,6 +45,7 @@ fn convert_result_to_identifications(result: dto::LookupResult) -> Vec<TrackIden
 fn convert_recording_to_identifications(
     recording: dto::Recording,
     acoustid_score: f32,
+    acoustid_id: &str,
 ) -> Vec<TrackIdentification> {
     // Get artist info from first artist
     let (artist_name, artist_id) = recording
@@ -89,12 +91,18 @@ fn convert_recording_to_identifications(
                     release_type: rg.release_type,
                     secondary_types: rg.secondarytypes,
                     genres: vec![], // Will be populated by MusicBrainz lookup
+                    language: None,
+                    explicit: None,
+                    acoustid_id: Some(acoustid_id.to_string()),
+                    fingerprint: None,
                 };
 
                 TrackIdentification {
                     score: acoustid_score,
                     track,
                     source: EnrichmentSource::AcoustId,
+                    musicbrainz_fields: Vec::new(),
+                    scores: Default::default(),
                 }
             })
             .collect()
@@ -120,8 +128,14 @@ fn convert_recording_to_identifications(
                 release_type: None,
                 secondary_types: vec![],
                 genres: vec![],
+                language: None,
+                explicit: None,
+                acoustid_id: Some(acoustid_id.to_string()),
+                fingerprint: None,
             },
             source: EnrichmentSource::AcoustId,
+            musicbrainz_fields: Vec::new(),
+            scores: Default::default(),
         }]
     }
 }
@@ -187,6 +201,10 @@ mod tests {
         );
         assert_eq!(identifications[0].score, 0.9);
         assert_eq!(identifications[0].source, EnrichmentSource::AcoustId);
+        assert_eq!(
+            identifications[0].track.acoustid_id.as_deref(),
+            Some("aid-1")
+        );
     }
 
     #[test]
@@ -228,6 +246,8 @@ mod tests {
                     ..Default::default()
                 },
                 source: EnrichmentSource::AcoustId,
+                musicbrainz_fields: Vec::new(),
+                scores: Default::default(),
             },
             TrackIdentification {
                 score: 0.9,
@@ -236,6 +256,8 @@ mod tests {
                     ..Default::default()
                 },
                 source: EnrichmentSource::AcoustId,
+                musicbrainz_fields: Vec::new(),
+                scores: Default::default(),
             },
         ];
 
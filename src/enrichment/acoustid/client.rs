@@ -26,6 +26,7 @@
 
 use super::{adapter, dto};
 use crate::enrichment::domain::{AudioFingerprint, EnrichmentError, TrackIdentification};
+use crate::enrichment::http;
 
 /// AcoustID API client
 pub struct AcoustIdClient {
@@ -58,13 +59,11 @@ impl AcoustIdClient {
         }
     }
 
-    /// Create a client for testing with custom base URL
-    #[cfg(test)]
+    /// Create a client for another server, such as a local fixture server
     pub fn with_base_url(api_key: impl Into<String>, base_url: impl Into<String>) -> Self {
         Self {
-            api_key: api_key.into(),
-            http_client: reqwest::Client::new(),
             base_url: base_url.into(),
+            ..Self::new(api_key)
         }
     }
 
@@ -102,6 +101,7 @@ impl AcoustIdClient {
             urlencoding::encode(&fingerprint.fingerprint)
         );
 
+        http::guard(&url)?;
         let response = self
             .http_client
             .get(&url)
@@ -113,6 +113,13 @@ impl AcoustIdClient {
             // Try to get the response body for more details
             let status = response.status();
             let body = response.text().await.unwrap_or_default();
+            // Invalid keys and fingerprints come back as 400 with an error body
+            if let Ok(dto::LookupResponse {
+                error: Some(error), ..
+            }) = serde_json::from_str(&body)
+            {
+                return Err(EnrichmentError::ApiError(error.message));
+            }
             return Err(EnrichmentError::Network(format!(
                 "HTTP {}: {} - {}",
                 status,
@@ -131,9 +138,14 @@ impl AcoustIdClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{FixtureResponse, FixtureServer};
 
-    // Note: Real integration tests would use wiremock or similar
-    // to mock the HTTP server. These are unit tests for the client structure.
+    fn fingerprint() -> AudioFingerprint {
+        AudioFingerprint {
+            fingerprint: "AQADtE mock+fingerprint/==".to_string(),
+            duration_secs: 301,
+        }
+    }
 
     #[test]
     fn test_client_creation() {
@@ -147,4 +159,69 @@ mod tests {
         let client = AcoustIdClient::with_base_url("key", "http://localhost:8080");
         assert_eq!(client.base_url, "http://localhost:8080");
     }
+
+    #[tokio::test]
+    async fn test_lookup_recorded_response() {
+        let server = FixtureServer::start(vec![(
+            "/v2/lookup",
+            FixtureResponse::json(include_str!("../fixtures/acoustid_lookup.json")),
+        )])
+        .await;
+        let client =
+            AcoustIdClient::with_base_url("test key", format!("{}/v2/lookup", server.url()));
+
+        let ids = client.lookup(&fingerprint()).await.unwrap();
+        // One per release group; the result without recordings adds none
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0].track.album.as_deref(), Some("Nevermind"));
+        assert_eq!(ids[0].track.artist.as_deref(), Some("Nirvana"));
+        assert_eq!(ids[1].track.secondary_types, ["Compilation"]);
+
+        let request = &server.requests()[0];
+        assert!(request.contains("client=test%20key"));
+        assert!(request.contains("duration=301"));
+        assert!(request.contains("fingerprint=AQADtE%20mock%2Bfingerprint%2F%3D%3D"));
+        // Literal + separators, or the API leaves the metadata out
+        assert!(request.ends_with("&meta=recordings+releasegroups+compress"));
+    }
+
+    #[tokio::test]
+    async fn test_lookup_errors() {
+        let server = FixtureServer::start(vec![
+            (
+                "/bad-key",
+                FixtureResponse::status(400, include_str!("../fixtures/acoustid_error.json")),
+            ),
+            (
+                "/down",
+                FixtureResponse::status(503, "<html>Bad Gateway</html>"),
+            ),
+            ("/garbled", FixtureResponse::json("{\"status\": ")),
+        ])
+        .await;
+        let lookup = |path: &str| {
+            let client = AcoustIdClient::with_base_url("key", format!("{}{}", server.url(), path));
+            async move { client.lookup(&fingerprint()).await.unwrap_err() }
+        };
+
+        assert!(
+            matches!(lookup("/bad-key").await, EnrichmentError::ApiError(m) if m == "invalid API key")
+        );
+        assert!(
+            matches!(lookup("/down").await, EnrichmentError::Network(m) if m.starts_with("HTTP 503"))
+        );
+        assert!(matches!(
+            lookup("/garbled").await,
+            EnrichmentError::Parse(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_default_client_stays_offline_in_tests() {
+        let err = AcoustIdClient::new("key")
+            .lookup(&fingerprint())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EnrichmentError::Network(m) if m.starts_with("offline mode")));
+    }
 }
@@ -138,6 +138,28 @@ pub struct Track {
 mod contract_tests {
     use super::*;
 
+    /// Test we can parse a recorded lookup (`fixtures/acoustid_lookup.json`)
+    #[test]
+    fn test_parse_recorded_lookup() {
+        let response: LookupResponse =
+            serde_json::from_str(include_str!("../fixtures/acoustid_lookup.json"))
+                .expect("Should parse recorded lookup");
+
+        assert_eq!(response.status, "ok");
+        assert_eq!(response.results.len(), 2);
+        let recording = &response.results[0].recordings[0];
+        assert_eq!(recording.duration, Some(301.0));
+        assert_eq!(recording.releasegroups.len(), 2);
+        assert_eq!(recording.releasegroups[1].secondarytypes, ["Compilation"]);
+        // Fingerprints nobody linked to a recording come back bare
+        assert!(response.results[1].recordings.is_empty());
+
+        let error: LookupResponse =
+            serde_json::from_str(include_str!("../fixtures/acoustid_error.json"))
+                .expect("Should parse recorded error");
+        assert_eq!(error.error.unwrap().code, 4);
+    }
+
     /// Test we can parse a minimal successful response
     #[test]
     fn test_parse_minimal_success_response() {
@@ -4,8 +4,10 @@
 //! API docs: https://acoustid.org/webservice
 
 mod adapter;
+#[cfg(feature = "enrichment")]
 mod client;
 pub mod dto;
 
 pub use adapter::{best_identification, to_identifications};
+#[cfg(feature = "enrichment")]
 pub use client::AcoustIdClient;
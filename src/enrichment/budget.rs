@@ -0,0 +1,330 @@
+//! CPU budget for fingerprinting.
+//!
+//! `fpcalc` decodes the whole file and keeps one core busy while it does, so
+//! a batch identification can load every core of a laptop. Every fingerprint
+//! runs through a [`CpuBudget`], which caps how many run at once and holds
+//! new ones back while the machine is on battery or music is playing (each
+//! optional, see [`AnalysisConfig`]). Fingerprints already running are never
+//! interrupted.
+//!
+//! The app, CLI and agent share one budget per process, [`CpuBudget::global`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::enrichment::budget::CpuBudget;
+//!
+//! let fp = CpuBudget::global().fingerprint(&path).await?;
+//! let status = CpuBudget::global().status(); // "2 of 4 threads busy"
+//! ```
+
+use parking_lot::Mutex;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+use crate::config::AnalysisConfig;
+use crate::enrichment::domain::{AudioFingerprint, EnrichmentError};
+use crate::enrichment::fingerprint;
+
+/// How long a battery reading stays valid
+const BATTERY_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often a paused worker looks again (the battery isn't watched)
+const PAUSED_RECHECK: Duration = Duration::from_secs(5);
+
+/// Why new fingerprints are being held back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseReason {
+    OnBattery,
+    Playing,
+}
+
+impl PauseReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseReason::OnBattery => "on battery",
+            PauseReason::Playing => "music is playing",
+        }
+    }
+}
+
+/// Snapshot of the budget for display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetStatus {
+    /// Fingerprints running right now
+    pub busy: usize,
+    /// Most that may run at once
+    pub threads: usize,
+    /// Logical CPU cores
+    pub cores: usize,
+    pub paused: Option<PauseReason>,
+}
+
+impl BudgetStatus {
+    /// Share of the machine's CPU the running fingerprints use, 0.0-1.0
+    /// (each `fpcalc` keeps one core busy)
+    pub fn cpu_share(&self) -> f32 {
+        (self.busy as f32 / self.cores.max(1) as f32).min(1.0)
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    config: AnalysisConfig,
+    busy: usize,
+    playing: bool,
+    /// Last battery reading and when it was taken
+    battery: Option<(Instant, bool)>,
+}
+
+impl State {
+    fn threads(&self) -> usize {
+        threads_for(&self.config, cores())
+    }
+
+    fn paused(&self) -> Option<PauseReason> {
+        if self.config.pause_on_battery && self.battery.is_some_and(|(_, on)| on) {
+            Some(PauseReason::OnBattery)
+        } else if self.config.pause_while_playing && self.playing {
+            Some(PauseReason::Playing)
+        } else {
+            None
+        }
+    }
+}
+
+/// Limits concurrent fingerprinting. See the module docs.
+#[derive(Debug)]
+pub struct CpuBudget {
+    state: Mutex<State>,
+    changed: Notify,
+}
+
+/// A running fingerprint's slot; frees it when dropped
+pub struct BudgetPermit<'a>(&'a CpuBudget);
+
+impl Drop for BudgetPermit<'_> {
+    fn drop(&mut self) {
+        self.0.state.lock().busy -= 1;
+        self.0.changed.notify_waiters();
+    }
+}
+
+impl CpuBudget {
+    pub fn new(config: &AnalysisConfig) -> Self {
+        Self {
+            state: Mutex::new(State {
+                config: config.clone(),
+                busy: 0,
+                playing: false,
+                battery: None,
+            }),
+            changed: Notify::new(),
+        }
+    }
+
+    /// The process-wide budget, set up from the config file on first use
+    pub fn global() -> &'static CpuBudget {
+        static GLOBAL: OnceLock<CpuBudget> = OnceLock::new();
+        GLOBAL.get_or_init(|| CpuBudget::new(&crate::config::load().analysis))
+    }
+
+    /// Apply changed settings; waiting fingerprints start if now allowed
+    pub fn configure(&self, config: &AnalysisConfig) {
+        self.state.lock().config = config.clone();
+        self.changed.notify_waiters();
+    }
+
+    /// Tell the budget whether music is playing
+    pub fn set_playing(&self, playing: bool) {
+        let mut state = self.state.lock();
+        if state.playing != playing {
+            state.playing = playing;
+            drop(state);
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// Most fingerprints that may run at once
+    pub fn threads(&self) -> usize {
+        self.state.lock().threads()
+    }
+
+    /// Current usage. Cheap enough to call every frame: the battery reading
+    /// is only refreshed by waiting work.
+    pub fn status(&self) -> BudgetStatus {
+        let state = self.state.lock();
+        BudgetStatus {
+            busy: state.busy,
+            threads: state.threads(),
+            cores: cores(),
+            paused: state.paused(),
+        }
+    }
+
+    /// Wait for a free slot that isn't paused
+    pub async fn acquire(&self) -> BudgetPermit<'_> {
+        loop {
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            self.refresh_battery();
+            {
+                let mut state = self.state.lock();
+                if state.paused().is_none() && state.busy < state.threads() {
+                    state.busy += 1;
+                    return BudgetPermit(self);
+                }
+            }
+            let _ = tokio::time::timeout(PAUSED_RECHECK, notified).await;
+        }
+    }
+
+    /// Fingerprint `path` within the budget
+    pub async fn fingerprint(&self, path: &Path) -> Result<AudioFingerprint, EnrichmentError> {
+        let _permit = self.acquire().await;
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || fingerprint::generate_fingerprint(&path))
+            .await
+            .map_err(|e| EnrichmentError::FingerprintError(e.to_string()))?
+    }
+
+    /// Re-read the battery if pausing on it is enabled and the last reading
+    /// is stale
+    fn refresh_battery(&self) {
+        {
+            let state = self.state.lock();
+            if !state.config.pause_on_battery
+                || state
+                    .battery
+                    .is_some_and(|(at, _)| at.elapsed() < BATTERY_CHECK_INTERVAL)
+            {
+                return;
+            }
+        }
+        let on = on_battery();
+        self.state.lock().battery = Some((Instant::now(), on));
+    }
+}
+
+/// Thread limit for `config` on a machine with `cores` logical cores:
+/// the configured number, or half the cores when it's 0
+fn threads_for(config: &AnalysisConfig, cores: usize) -> usize {
+    match config.max_threads {
+        0 => (cores / 2).max(1),
+        n => n,
+    }
+}
+
+/// Logical CPU cores
+pub fn cores() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Whether the machine is running on battery power
+#[cfg(target_os = "linux")]
+fn on_battery() -> bool {
+    let Ok(supplies) = std::fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+    supplies.flatten().any(|supply| {
+        let read = |name: &str| {
+            std::fs::read_to_string(supply.path().join(name))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default()
+        };
+        read("type") == "Battery" && read("status") == "Discharging"
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn on_battery() -> bool {
+    std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).contains("'Battery Power'"))
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn on_battery() -> bool {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // SAFETY: GetSystemPowerStatus only writes the struct it is given
+    unsafe {
+        let mut status: SYSTEM_POWER_STATUS = std::mem::zeroed();
+        GetSystemPowerStatus(&mut status) != 0 && status.ACLineStatus == 0
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn on_battery() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_thread_limit() {
+        let auto = AnalysisConfig::default();
+        assert_eq!(threads_for(&auto, 8), 4);
+        assert_eq!(threads_for(&auto, 1), 1);
+        let fixed = AnalysisConfig {
+            max_threads: 3,
+            ..Default::default()
+        };
+        assert_eq!(threads_for(&fixed, 8), 3);
+
+        let status = BudgetStatus {
+            busy: 2,
+            threads: 4,
+            cores: 8,
+            paused: None,
+        };
+        assert_eq!(status.cpu_share(), 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_limit_and_pause_while_playing() {
+        let budget = Arc::new(CpuBudget::new(&AnalysisConfig {
+            max_threads: 1,
+            pause_on_battery: false,
+            pause_while_playing: true,
+        }));
+
+        let first = budget.acquire().await;
+        assert_eq!(budget.status().busy, 1);
+
+        // A second slot opens only once the first is freed
+        let waiting = {
+            let budget = budget.clone();
+            tokio::spawn(async move {
+                let _permit = budget.acquire().await;
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        // Playing pauses new work even with a slot free
+        budget.set_playing(true);
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+        assert_eq!(budget.status().paused, Some(PauseReason::Playing));
+
+        budget.set_playing(false);
+        tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(budget.status().busy, 0);
+    }
+}
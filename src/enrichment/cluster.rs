@@ -0,0 +1,439 @@
+//! Album clustering - identifying a folder of files as one release.
+//!
+//! Fingerprinted one at a time, an album's tracks each pick their own
+//! release, so they end up spread over the compilations and singles they
+//! also appear on, and tracks AcoustID doesn't know stay unmatched. Most
+//! folders hold one release, so the folder votes instead:
+//! 1. Every file votes for each release group among its AcoustID
+//!    candidates, weighted by the candidate's score, and by half when the
+//!    candidate's length is off the file's ([`vote`])
+//! 2. The winning group needs at least half of the folder behind it
+//! 3. The service looks the group's releases up and keeps the one whose
+//!    tracklist the files line up with best ([`assign`])
+//! 4. Each file is tagged from that release: by its own recording when
+//!    that is on it, otherwise by the track it lines up with, if the
+//!    lengths agree
+//!
+//! Lining up takes the files in track number order (file name order when
+//! some aren't numbered) as consecutive tracks of the release, starting
+//! wherever most of them agree, so a partial album still lines up.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::domain::{ReleaseTracklist, TrackIdentification};
+
+/// Files a folder needs before it is identified as an album
+pub const MIN_FILES: usize = 3;
+
+/// Lengths this close are taken for the same track
+const LENGTH_TOLERANCE: Duration = Duration::from_secs(3);
+
+/// Vote weight of a candidate whose length is off the file's
+const LENGTH_MISMATCH_WEIGHT: f32 = 0.5;
+
+/// Confidence of a file tagged from the track it lines up with rather than
+/// by its fingerprint; below the default minimum, so it is reviewed
+const ALIGNED_SCORE: f32 = 0.6;
+
+/// A file of a folder being identified as an album
+#[derive(Debug, Clone)]
+pub struct ClusterFile {
+    pub path: PathBuf,
+    /// Length of the audio
+    pub length: Option<Duration>,
+    /// Disc and track number from the tags
+    pub position: Option<(u32, u32)>,
+    /// What AcoustID found for the fingerprint, at least the minimum
+    /// confidence
+    pub candidates: Vec<TrackIdentification>,
+}
+
+impl ClusterFile {
+    /// The file's vote for each release group: its best candidate there
+    fn votes(&self) -> HashMap<&str, f32> {
+        let mut votes: HashMap<&str, f32> = HashMap::new();
+        for candidate in &self.candidates {
+            let Some(group) = candidate.track.release_group_id.as_deref() else {
+                continue;
+            };
+            let weight = match (self.length, candidate.track.duration) {
+                (Some(a), Some(b)) if !lengths_match(a, b) => {
+                    candidate.score * LENGTH_MISMATCH_WEIGHT
+                }
+                _ => candidate.score,
+            };
+            let vote = votes.entry(group).or_default();
+            *vote = vote.max(weight);
+        }
+        votes
+    }
+
+    fn has_recording(&self, recording_id: &str) -> Option<f32> {
+        self.candidates
+            .iter()
+            .filter(|c| c.track.recording_id.as_deref() == Some(recording_id))
+            .map(|c| c.score)
+            .max_by(f32::total_cmp)
+    }
+}
+
+/// The release group a folder voted for
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupVote {
+    pub release_group_id: String,
+    /// Files with a candidate in the group
+    pub voters: usize,
+    /// Recordings of the group's candidates, the strongest votes first: the
+    /// way to the group's releases
+    pub recordings: Vec<String>,
+}
+
+/// A file tagged from the release the folder was identified as
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub path: PathBuf,
+    /// The recording on the release
+    pub recording_id: String,
+    pub score: f32,
+    /// Tagged from the track it lines up with, not by its fingerprint
+    pub by_position: bool,
+}
+
+/// Put `files` in album order: by disc and track number when they are all
+/// numbered, by file name otherwise
+pub fn order(files: &mut [ClusterFile]) {
+    if files.iter().all(|f| f.position.is_some()) {
+        files.sort_by(|a, b| a.position.cmp(&b.position).then(a.path.cmp(&b.path)));
+    } else {
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+}
+
+/// The release group most of `files` agree on, if at least half of them
+/// (and two or more) have a candidate there
+pub fn vote(files: &[ClusterFile]) -> Option<GroupVote> {
+    if files.len() < MIN_FILES {
+        return None;
+    }
+    let ballots: Vec<HashMap<&str, f32>> = files.iter().map(ClusterFile::votes).collect();
+    let mut tally: HashMap<&str, (f32, usize)> = HashMap::new();
+    for ballot in &ballots {
+        for (group, weight) in ballot {
+            let entry = tally.entry(group).or_default();
+            entry.0 += weight;
+            entry.1 += 1;
+        }
+    }
+    let (group, (_, voters)) = tally
+        .into_iter()
+        .max_by(|a, b| a.1.0.total_cmp(&b.1.0).then(b.0.cmp(a.0)))?;
+    if voters < 2 || voters * 2 < files.len() {
+        return None;
+    }
+
+    let mut strongest: Vec<(f32, &TrackIdentification)> = files
+        .iter()
+        .zip(&ballots)
+        .filter_map(|(file, ballot)| {
+            let weight = *ballot.get(group)?;
+            let candidate = file
+                .candidates
+                .iter()
+                .filter(|c| c.track.release_group_id.as_deref() == Some(group))
+                .max_by(|a, b| a.score.total_cmp(&b.score))?;
+            Some((weight, candidate))
+        })
+        .collect();
+    strongest.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut recordings = Vec::new();
+    for (_, candidate) in strongest {
+        if let Some(id) = &candidate.track.recording_id
+            && !recordings.contains(id)
+        {
+            recordings.push(id.clone());
+        }
+    }
+
+    Some(GroupVote {
+        release_group_id: group.to_string(),
+        voters,
+        recordings,
+    })
+}
+
+/// Tag `files` (in album order) from `tracklist`. Files whose recording
+/// is on it get that track; the others the track they line up with, when
+/// the lengths agree. Each track goes to one file at most.
+pub fn assign(files: &[ClusterFile], tracklist: &ReleaseTracklist) -> Vec<Assignment> {
+    let tracks = &tracklist.tracks;
+    let mut taken: HashSet<&str> = HashSet::new();
+    let mut assigned: Vec<Option<Assignment>> = vec![None; files.len()];
+
+    for (file, slot) in files.iter().zip(assigned.iter_mut()) {
+        let best = tracks
+            .iter()
+            .filter_map(|t| {
+                let id = t.recording_id.as_deref()?;
+                Some((id, file.has_recording(id)?))
+            })
+            .filter(|(id, _)| !taken.contains(id))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((id, score)) = best {
+            taken.insert(id);
+            *slot = Some(Assignment {
+                path: file.path.clone(),
+                recording_id: id.to_string(),
+                score,
+                by_position: false,
+            });
+        }
+    }
+
+    if let Some(offset) = alignment(files, tracklist) {
+        for (i, (file, slot)) in files.iter().zip(assigned.iter_mut()).enumerate() {
+            if slot.is_some() {
+                continue;
+            }
+            let track = &tracks[offset + i];
+            let Some(id) = track.recording_id.as_deref() else {
+                continue;
+            };
+            let agree = matches!(
+                (file.length, track.duration),
+                (Some(a), Some(b)) if lengths_match(a, b)
+            );
+            if agree && !taken.contains(id) {
+                taken.insert(id);
+                *slot = Some(Assignment {
+                    path: file.path.clone(),
+                    recording_id: id.to_string(),
+                    score: ALIGNED_SCORE,
+                    by_position: true,
+                });
+            }
+        }
+    }
+
+    assigned.into_iter().flatten().collect()
+}
+
+/// Whether `assigned` tags enough of a folder of `files` to go by: at
+/// least half of it, two or more by their own fingerprints
+pub fn accepted(assigned: &[Assignment], files: usize) -> bool {
+    let fingerprinted = assigned.iter().filter(|a| !a.by_position).count();
+    fingerprinted >= 2 && assigned.len() * 2 >= files
+}
+
+/// Where on the release the files line up best taken as consecutive
+/// tracks: the first track's index, or `None` if there are more files
+/// than tracks or none agree anywhere
+fn alignment(files: &[ClusterFile], tracklist: &ReleaseTracklist) -> Option<usize> {
+    let tracks = &tracklist.tracks;
+    if files.len() > tracks.len() {
+        return None;
+    }
+    (0..=tracks.len() - files.len())
+        .map(|offset| {
+            let support = files
+                .iter()
+                .zip(&tracks[offset..])
+                .filter(|(file, track)| {
+                    let same_recording = track
+                        .recording_id
+                        .as_deref()
+                        .is_some_and(|id| file.has_recording(id).is_some());
+                    let same_length = matches!(
+                        (file.length, track.duration),
+                        (Some(a), Some(b)) if lengths_match(a, b)
+                    );
+                    same_recording || same_length
+                })
+                .count();
+            (offset, support)
+        })
+        .filter(|(_, support)| *support > 0)
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(offset, _)| offset)
+}
+
+fn lengths_match(a: Duration, b: Duration) -> bool {
+    a.abs_diff(b) <= LENGTH_TOLERANCE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enrichment::domain::{EnrichmentSource, IdentifiedTrack, ReleaseTrack};
+
+    fn candidate(recording: &str, group: &str, secs: u64, score: f32) -> TrackIdentification {
+        TrackIdentification {
+            score,
+            track: IdentifiedTrack {
+                recording_id: Some(recording.to_string()),
+                release_group_id: Some(group.to_string()),
+                duration: Some(Duration::from_secs(secs)),
+                ..Default::default()
+            },
+            source: EnrichmentSource::AcoustId,
+            musicbrainz_fields: Vec::new(),
+            scores: Default::default(),
+        }
+    }
+
+    fn file(name: &str, secs: u64, candidates: Vec<TrackIdentification>) -> ClusterFile {
+        ClusterFile {
+            path: PathBuf::from("/album").join(name),
+            length: Some(Duration::from_secs(secs)),
+            position: None,
+            candidates,
+        }
+    }
+
+    /// An album of four tracks, 200 s, 210 s, ... long
+    fn tracklist() -> ReleaseTracklist {
+        ReleaseTracklist {
+            release_id: "release".to_string(),
+            title: "Album".to_string(),
+            tracks: (1..=4)
+                .map(|n| ReleaseTrack {
+                    disc: 1,
+                    position: n,
+                    title: format!("Track {}", n),
+                    recording_id: Some(format!("rec{}", n)),
+                    duration: Some(Duration::from_secs(190 + 10 * n as u64)),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_folder_outvotes_compilations() {
+        // Each track is also on its own compilation, with a higher score
+        let files = vec![
+            file(
+                "01.flac",
+                200,
+                vec![
+                    candidate("rec1", "album", 200, 0.9),
+                    candidate("rec1", "hits", 200, 0.95),
+                ],
+            ),
+            file(
+                "02.flac",
+                210,
+                vec![
+                    candidate("rec2", "album", 210, 0.9),
+                    candidate("rec2", "best-of", 210, 0.95),
+                ],
+            ),
+            file("03.flac", 220, vec![candidate("rec3", "album", 220, 0.85)]),
+            file("04.flac", 230, Vec::new()),
+        ];
+        let vote = vote(&files).unwrap();
+        assert_eq!(vote.release_group_id, "album");
+        assert_eq!(vote.voters, 3);
+        assert_eq!(vote.recordings, ["rec1", "rec2", "rec3"]);
+    }
+
+    #[test]
+    fn test_vote_needs_half_the_folder() {
+        let files = vec![
+            file("01.flac", 200, vec![candidate("rec1", "album", 200, 0.9)]),
+            file("02.flac", 210, Vec::new()),
+            file("03.flac", 220, Vec::new()),
+            file("04.flac", 230, vec![candidate("x", "other", 230, 0.9)]),
+        ];
+        assert_eq!(vote(&files), None);
+        assert_eq!(vote(&files[..2]), None);
+    }
+
+    #[test]
+    fn test_wrong_lengths_weigh_less() {
+        let files = vec![
+            file(
+                "01.flac",
+                200,
+                vec![
+                    candidate("rec1", "album", 200, 0.8),
+                    candidate("live1", "live", 320, 0.9),
+                ],
+            ),
+            file(
+                "02.flac",
+                210,
+                vec![
+                    candidate("rec2", "album", 211, 0.8),
+                    candidate("live2", "live", 300, 0.9),
+                ],
+            ),
+            file("03.flac", 220, Vec::new()),
+        ];
+        assert_eq!(vote(&files).unwrap().release_group_id, "album");
+    }
+
+    #[test]
+    fn test_assign_fills_gaps_by_position() {
+        let mut files = vec![
+            file("02.flac", 211, Vec::new()),
+            file("01.flac", 200, vec![candidate("rec1", "album", 200, 0.9)]),
+            file("03.flac", 221, vec![candidate("rec3", "album", 220, 0.85)]),
+            // Not the length of track 4: left for fingerprinting on its own
+            file("04.flac", 300, Vec::new()),
+        ];
+        order(&mut files);
+        let assigned = assign(&files, &tracklist());
+        let tagged: Vec<(&str, &str, bool)> = assigned
+            .iter()
+            .map(|a| {
+                let name = a.path.file_name().unwrap().to_str().unwrap();
+                (name, a.recording_id.as_str(), a.by_position)
+            })
+            .collect();
+        assert_eq!(
+            tagged,
+            [
+                ("01.flac", "rec1", false),
+                ("02.flac", "rec2", true),
+                ("03.flac", "rec3", false),
+            ]
+        );
+        assert_eq!(assigned[1].score, ALIGNED_SCORE);
+        assert!(accepted(&assigned, files.len()));
+        assert!(!accepted(&assigned[1..2], files.len()));
+    }
+
+    #[test]
+    fn test_partial_album_lines_up_where_it_starts() {
+        // Tracks 3 and 4, numbered in the tags
+        let mut files = vec![
+            file("b.flac", 230, Vec::new()),
+            file("a.flac", 220, Vec::new()),
+        ];
+        files[0].position = Some((1, 4));
+        files[1].position = Some((1, 3));
+        order(&mut files);
+        let assigned = assign(&files, &tracklist());
+        let recordings: Vec<&str> = assigned.iter().map(|a| a.recording_id.as_str()).collect();
+        assert_eq!(recordings, ["rec3", "rec4"]);
+        assert!(assigned.iter().all(|a| a.by_position));
+    }
+
+    #[test]
+    fn test_more_files_than_tracks_only_match_by_recording() {
+        let files: Vec<ClusterFile> = (1..=5)
+            .map(|n| {
+                let candidates = if n == 1 {
+                    vec![candidate("rec1", "album", 200, 0.9)]
+                } else {
+                    Vec::new()
+                };
+                file(&format!("0{}.flac", n), 190 + 10 * n, candidates)
+            })
+            .collect();
+        let assigned = assign(&files, &tracklist());
+        assert_eq!(assigned.len(), 1);
+        assert_eq!(assigned[0].recording_id, "rec1");
+    }
+}
@@ -7,6 +7,7 @@
 
 use super::dto;
 use crate::enrichment::domain::EnrichmentError;
+use crate::enrichment::http;
 
 /// Desired cover art size
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -48,12 +49,11 @@ impl CoverArtClient {
         }
     }
 
-    /// Create a client for testing with custom base URL
-    #[cfg(test)]
+    /// Create a client for another server, such as a local fixture server
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
         Self {
-            http_client: reqwest::Client::new(),
             base_url: base_url.into(),
+            ..Self::new()
         }
     }
 
@@ -86,6 +86,7 @@ impl CoverArtClient {
     ) -> Result<dto::CoverArtResponse, EnrichmentError> {
         let url = format!("{}/release/{}", self.base_url, release_id);
 
+        http::guard(&url)?;
         let response = self
             .http_client
             .get(&url)
@@ -116,6 +117,7 @@ impl CoverArtClient {
 
     /// Download an image from a URL
     async fn download_image(&self, url: &str) -> Result<CoverArt, EnrichmentError> {
+        http::guard(url)?;
         let response = self
             .http_client
             .get(url)
@@ -168,6 +170,7 @@ impl Default for CoverArtClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{FixtureResponse, FixtureServer};
 
     #[test]
     fn test_client_creation() {
@@ -180,4 +183,45 @@ mod tests {
         let size = CoverSize::default();
         assert_eq!(size, CoverSize::Medium);
     }
+
+    #[tokio::test]
+    async fn test_fetch_recorded_responses() {
+        let png = b"\x89PNG\r\n\x1a\nnot really";
+        let server = FixtureServer::start(vec![
+            (
+                "/release/b52a8f31/front",
+                FixtureResponse::bytes("image/png", png),
+            ),
+            (
+                "/release/b52a8f31",
+                FixtureResponse::json(include_str!("../fixtures/coverart_release.json")),
+            ),
+        ])
+        .await;
+        let client = CoverArtClient::with_base_url(server.url());
+
+        let cover = client
+            .get_front_cover("b52a8f31", CoverSize::Large)
+            .await
+            .unwrap();
+        assert_eq!(cover.data, png);
+        assert_eq!(cover.mime_type, "image/png");
+        assert!(cover.url.ends_with("/release/b52a8f31/front-1200"));
+
+        let listing = client.list_cover_art("b52a8f31").await.unwrap();
+        assert_eq!(listing.images.len(), 2);
+
+        // Releases without art are 404s
+        let missing = client.get_front_cover("none", CoverSize::Small).await;
+        assert!(matches!(missing, Err(EnrichmentError::NoMatches)));
+    }
+
+    #[tokio::test]
+    async fn test_default_client_stays_offline_in_tests() {
+        let err = CoverArtClient::new()
+            .get_front_cover("x", CoverSize::Medium)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EnrichmentError::Network(m) if m.starts_with("offline mode")));
+    }
 }
@@ -33,7 +33,8 @@ pub struct Image {
     pub approved: bool,
     /// Edit ID on MusicBrainz
     pub edit: Option<i64>,
-    /// Image ID
+    /// Image ID (a number in current responses, a string in older ones)
+    #[serde(deserialize_with = "string_or_number")]
     pub id: String,
     /// Comment about the image
     pub comment: Option<String>,
@@ -53,6 +54,19 @@ pub struct Thumbnails {
     pub xlarge: Option<String>,
 }
 
+fn string_or_number<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        String(String),
+        Number(u64),
+    }
+    Ok(match Id::deserialize(deserializer)? {
+        Id::String(id) => id,
+        Id::Number(id) => id.to_string(),
+    })
+}
+
 #[cfg(test)]
 mod contract_tests {
     use super::*;
@@ -85,6 +99,27 @@ mod contract_tests {
         assert_eq!(response.images[0].types, vec!["Front"]);
     }
 
+    #[test]
+    fn test_parse_recorded_response() {
+        let response: CoverArtResponse =
+            serde_json::from_str(include_str!("../fixtures/coverart_release.json"))
+                .expect("Should parse recorded response");
+
+        let front = &response.images[0];
+        assert!(front.front);
+        assert_eq!(front.id, "4587544083");
+        assert!(
+            front
+                .thumbnails
+                .xlarge
+                .as_deref()
+                .unwrap()
+                .ends_with("-1200.jpg")
+        );
+        assert_eq!(response.images[1].types, vec!["Back", "Spine"]);
+        assert!(response.images[1].thumbnails.xlarge.is_none());
+    }
+
     #[test]
     fn test_parse_minimal_response() {
         let json = r#"{
@@ -0,0 +1,486 @@
+//! MusicBrainz disc IDs for ripped CDs.
+//!
+//! A CD's table of contents (where each track starts, in 1/75 s sectors)
+//! identifies the pressing. MusicBrainz hashes it into a disc ID, and a
+//! disc ID lookup returns the exact release in one request, where
+//! fingerprinting costs an `fpcalc` run plus two lookups per track.
+//!
+//! The table of contents is rebuilt from a folder of one-file-per-track rips:
+//! from a cue sheet when there is one, otherwise from the track lengths. The
+//! lengths of lossless rips are exact; lossy ones can be a sector or so off,
+//! so lookups also send the table of contents, letting MusicBrainz match a
+//! disc whose ID differs slightly. Single-file image rips aren't handled:
+//! there are no per-track files to tag. Their cue sheets still give the
+//! player chapter marks ([`cue_chapters`]).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::enrichment::discid;
+//!
+//! for disc in discid::ripped_discs(Path::new("/music/Radiohead/OK Computer")) {
+//!     println!("{} tracks, disc ID {}", disc.files.len(), disc.toc.disc_id());
+//! }
+//! ```
+
+use base64::Engine;
+use sha1::{Digest, Sha1};
+use std::collections::BTreeMap;
+#[cfg(feature = "enrichment")]
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[cfg(feature = "enrichment")]
+use crate::enrichment::domain::{EnrichmentError, EnrichmentSource, TrackIdentification};
+#[cfg(feature = "enrichment")]
+use crate::enrichment::traits::MusicBrainzApi;
+
+/// Sectors before the first track on every CD (the 2 second lead-in)
+const LEAD_IN: u32 = 150;
+
+/// CD sectors per second
+const SECTORS_PER_SECOND: f64 = 75.0;
+
+/// Most tracks a CD can hold
+pub const MAX_TRACKS: usize = 99;
+
+/// Score of a release found by a similar table of contents rather than the
+/// disc ID itself
+#[cfg(feature = "enrichment")]
+const SIMILAR_TOC_SCORE: f32 = 0.9;
+
+/// Pause between lookups (MusicBrainz allows one request per second)
+#[cfg(feature = "enrichment")]
+const LOOKUP_INTERVAL: Duration = Duration::from_millis(1100);
+
+/// A CD's table of contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscToc {
+    /// Number of the first track (1 for nearly every disc)
+    pub first_track: u32,
+    /// Sector where the disc ends
+    pub lead_out: u32,
+    /// Sector where each track starts
+    pub offsets: Vec<u32>,
+}
+
+impl DiscToc {
+    /// Table of contents of a disc whose tracks are `lengths` long, with no
+    /// gaps between them. `None` for no tracks or more than a CD holds.
+    pub fn from_lengths(lengths: &[Duration]) -> Option<Self> {
+        if lengths.is_empty() || lengths.len() > MAX_TRACKS {
+            return None;
+        }
+        let mut offsets = Vec::with_capacity(lengths.len());
+        let mut position = LEAD_IN;
+        for length in lengths {
+            offsets.push(position);
+            position += sectors(*length);
+        }
+        Some(Self {
+            first_track: 1,
+            lead_out: position,
+            offsets,
+        })
+    }
+
+    /// Table of contents from a cue sheet. `file_length` gives the length of
+    /// each `FILE` the sheet names; `INDEX 01` of each audio track is where
+    /// it starts. `None` if the sheet has no audio tracks or a file's length
+    /// is unknown.
+    pub fn from_cue(cue: &str, file_length: impl Fn(&str) -> Option<Duration>) -> Option<Self> {
+        let mut offsets = Vec::new();
+        let mut first_track = None;
+        let mut file_start = LEAD_IN;
+        let mut file_end = LEAD_IN;
+        let mut audio_track = false;
+
+        for line in cue.lines() {
+            let line = line.trim();
+            let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match command.to_ascii_uppercase().as_str() {
+                "FILE" => {
+                    let name = cue_file_name(rest)?;
+                    file_start = file_end;
+                    file_end = file_start + sectors(file_length(name)?);
+                }
+                "TRACK" => {
+                    let mut parts = rest.split_whitespace();
+                    let number: u32 = parts.next()?.parse().ok()?;
+                    audio_track = parts
+                        .next()
+                        .is_some_and(|t| t.eq_ignore_ascii_case("AUDIO"));
+                    if audio_track {
+                        first_track.get_or_insert(number);
+                    }
+                }
+                "INDEX" if audio_track => {
+                    let mut parts = rest.split_whitespace();
+                    if parts.next() == Some("01") {
+                        offsets.push(file_start + cue_time(parts.next()?)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if offsets.is_empty() || offsets.len() > MAX_TRACKS {
+            return None;
+        }
+        Some(Self {
+            first_track: first_track?,
+            lead_out: file_end,
+            offsets,
+        })
+    }
+
+    /// Number of the last track
+    pub fn last_track(&self) -> u32 {
+        self.first_track + self.offsets.len() as u32 - 1
+    }
+
+    /// The MusicBrainz disc ID: SHA-1 of the table of contents as hex, in
+    /// base64 with `.`, `_` and `-` for the characters URLs don't like
+    pub fn disc_id(&self) -> String {
+        let mut hasher = Sha1::new();
+        hasher.update(format!("{:02X}", self.first_track));
+        hasher.update(format!("{:02X}", self.last_track()));
+        hasher.update(format!("{:08X}", self.lead_out));
+        for i in 0..MAX_TRACKS {
+            hasher.update(format!("{:08X}", self.offsets.get(i).copied().unwrap_or(0)));
+        }
+        base64::engine::general_purpose::STANDARD
+            .encode(hasher.finalize())
+            .replace('+', ".")
+            .replace('/', "_")
+            .replace('=', "-")
+    }
+
+    /// The `toc` lookup parameter: first and last track, lead-out, offsets
+    pub fn toc_param(&self) -> String {
+        [self.first_track, self.last_track(), self.lead_out]
+            .iter()
+            .chain(&self.offsets)
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
+
+/// A track of a single-file image as its cue sheet describes it
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueChapter {
+    pub number: u32,
+    pub title: Option<String>,
+    /// Where `INDEX 01` puts it within the file
+    pub start: Duration,
+}
+
+/// The tracks a cue sheet places inside `file_name`, for image rips (one
+/// file holding the whole disc). Empty unless the file has two or more.
+pub fn cue_chapters(cue: &str, file_name: &str) -> Vec<CueChapter> {
+    let mut chapters = Vec::new();
+    let mut in_file = false;
+    let mut current: Option<CueChapter> = None;
+
+    for line in cue.lines() {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match command.to_ascii_uppercase().as_str() {
+            "FILE" => {
+                in_file = cue_file_name(rest).is_some_and(|n| n.eq_ignore_ascii_case(file_name));
+                current = None;
+            }
+            "TRACK" if in_file => {
+                current = rest
+                    .split_whitespace()
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .map(|number| CueChapter {
+                        number,
+                        title: None,
+                        start: Duration::ZERO,
+                    });
+            }
+            "TITLE" => {
+                if let Some(chapter) = current.as_mut() {
+                    chapter.title = Some(rest.trim().trim_matches('"').to_string());
+                }
+            }
+            "INDEX" => {
+                let mut parts = rest.split_whitespace();
+                if parts.next() == Some("01")
+                    && let Some(sectors) = parts.next().and_then(cue_time)
+                    && let Some(mut chapter) = current.take()
+                {
+                    chapter.start = Duration::from_secs_f64(sectors as f64 / SECTORS_PER_SECOND);
+                    chapters.push(chapter);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if chapters.len() < 2 {
+        chapters.clear();
+    }
+    chapters
+}
+
+/// Whole sectors in `length`, rounded
+fn sectors(length: Duration) -> u32 {
+    (length.as_secs_f64() * SECTORS_PER_SECOND).round() as u32
+}
+
+/// `mm:ss:ff` (ff in sectors) as sectors
+fn cue_time(time: &str) -> Option<u32> {
+    let mut parts = time.split(':').map(|p| p.parse::<u32>().ok());
+    let (minutes, seconds, frames) = (parts.next()??, parts.next()??, parts.next()??);
+    Some((minutes * 60 + seconds) * 75 + frames)
+}
+
+/// File name of a `FILE "name" TYPE` line
+fn cue_file_name(rest: &str) -> Option<&str> {
+    match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next(),
+        None => rest.split_whitespace().next(),
+    }
+}
+
+/// One CD's worth of ripped tracks
+#[derive(Debug, Clone)]
+pub struct RippedDisc {
+    /// The files, in track order
+    pub files: Vec<PathBuf>,
+    pub toc: DiscToc,
+}
+
+/// The complete discs ripped into `folder`: files grouped by disc number
+/// whose track numbers run 1..=n with nothing missing. The table of contents
+/// comes from the folder's cue sheet when it lists the same number of tracks,
+/// otherwise from the files' lengths.
+///
+/// Reads every audio file in the folder, so call it off the async runtime.
+pub fn ripped_discs(folder: &Path) -> Vec<RippedDisc> {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    let mut cues = Vec::new();
+    for path in entries.flatten().map(|e| e.path()) {
+        if crate::scanner::is_audio_file(&path) {
+            files.push(path);
+        } else if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("cue"))
+        {
+            cues.push(path);
+        }
+    }
+
+    // disc number -> (track number, file, total tracks tag)
+    let mut discs: BTreeMap<u32, Vec<(u32, PathBuf, Option<u32>)>> = BTreeMap::new();
+    for path in files {
+        let Ok(meta) = crate::metadata::read_full(&path) else {
+            return Vec::new();
+        };
+        let Some(track) = meta.track_number else {
+            return Vec::new();
+        };
+        discs
+            .entry(meta.disc_number.unwrap_or(1))
+            .or_default()
+            .push((track, path, meta.total_tracks));
+    }
+
+    let single_disc = discs.len() == 1;
+    discs
+        .into_values()
+        .filter_map(|mut tracks| {
+            tracks.sort_by_key(|(n, _, _)| *n);
+            let n = tracks.len() as u32;
+            let complete = tracks.iter().zip(1..).all(|((t, _, _), want)| *t == want)
+                && tracks
+                    .iter()
+                    .all(|(_, _, total)| total.is_none_or(|t| t == n));
+            if !complete {
+                return None;
+            }
+            let files: Vec<PathBuf> = tracks.into_iter().map(|(_, path, _)| path).collect();
+            let lengths: Vec<Duration> = files
+                .iter()
+                .map(|f| track_length(f).filter(|d| !d.is_zero()))
+                .collect::<Option<_>>()?;
+
+            // A cue sheet is only unambiguous for a folder holding one disc
+            let from_cue = match cues.as_slice() {
+                [cue] if single_disc => std::fs::read_to_string(cue).ok().and_then(|text| {
+                    DiscToc::from_cue(&text, |name| {
+                        let pos = files.iter().position(|f| {
+                            f.file_name().is_some_and(|f| f.eq_ignore_ascii_case(name))
+                        })?;
+                        Some(lengths[pos])
+                    })
+                }),
+                _ => None,
+            };
+            let toc = from_cue
+                .filter(|toc| toc.offsets.len() == files.len())
+                .or_else(|| DiscToc::from_lengths(&lengths))?;
+            Some(RippedDisc { files, toc })
+        })
+        .collect()
+}
+
+/// Identify the ripped CDs among `files` by disc ID.
+///
+/// Checks every folder holding one of `files` for complete discs
+/// ([`ripped_discs`]) and looks each up. Returns tags for the files of the
+/// discs MusicBrainz knows; the rest are left for fingerprinting.
+#[cfg(feature = "enrichment")]
+pub async fn identify_discs<M: MusicBrainzApi>(
+    api: &M,
+    files: &[PathBuf],
+) -> HashMap<PathBuf, TrackIdentification> {
+    let folders: BTreeSet<PathBuf> = files
+        .iter()
+        .filter_map(|f| f.parent().map(Path::to_path_buf))
+        .collect();
+    let mut identified = HashMap::new();
+    let mut first_lookup = true;
+
+    for folder in folders {
+        let discs = tokio::task::spawn_blocking(move || ripped_discs(&folder))
+            .await
+            .unwrap_or_default();
+        for disc in discs {
+            if !disc.files.iter().any(|f| files.contains(f)) {
+                continue;
+            }
+            if !first_lookup {
+                tokio::time::sleep(LOOKUP_INTERVAL).await;
+            }
+            first_lookup = false;
+
+            let best = match api.lookup_discid(&disc.toc).await {
+                Ok(mut matches) if !matches.is_empty() => matches.swap_remove(0),
+                Ok(_) | Err(EnrichmentError::NoMatches) => continue,
+                Err(e) => {
+                    tracing::warn!("Disc ID lookup failed: {}", e);
+                    continue;
+                }
+            };
+            let score = if best.exact { 1.0 } else { SIMILAR_TOC_SCORE };
+            for (file, track) in disc.files.into_iter().zip(best.tracks) {
+                if files.contains(&file) {
+                    let identification = TrackIdentification {
+                        score,
+                        track,
+                        source: EnrichmentSource::MusicBrainz,
+                        musicbrainz_fields: Vec::new(),
+                        scores: Default::default(),
+                    };
+                    identified.insert(file, identification);
+                }
+            }
+        }
+    }
+    identified
+}
+
+/// Exact length of an audio file, from its sample count
+fn track_length(path: &Path) -> Option<Duration> {
+    crate::player::AudioDecoder::open(path)
+        .ok()
+        .map(|decoder| decoder.duration())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example disc of the MusicBrainz web service documentation
+    fn example_toc() -> DiscToc {
+        DiscToc {
+            first_track: 1,
+            lead_out: 267257,
+            offsets: vec![
+                150, 22767, 41887, 58317, 72102, 91375, 104652, 115380, 132165, 143932, 159870,
+                174597,
+            ],
+        }
+    }
+
+    #[test]
+    fn test_disc_id() {
+        let toc = example_toc();
+        assert_eq!(toc.last_track(), 12);
+        assert_eq!(toc.disc_id(), "I5l9cCSFccLKFEKS.7wqSZAorPU-");
+        assert!(toc.toc_param().starts_with("1+12+267257+150+22767+"));
+    }
+
+    #[test]
+    fn test_toc_from_lengths() {
+        let toc = DiscToc::from_lengths(&[
+            Duration::from_secs_f64(22617.0 / 75.0),
+            Duration::from_secs_f64(19120.0 / 75.0),
+        ])
+        .unwrap();
+        assert_eq!(toc.offsets, [150, 22767]);
+        assert_eq!(toc.lead_out, 41887);
+        assert!(DiscToc::from_lengths(&[]).is_none());
+    }
+
+    #[test]
+    fn test_toc_from_cue() {
+        // One file per track, the second with a 2 second pregap
+        let cue = r#"
+            PERFORMER "Someone"
+            FILE "01 One.flac" WAVE
+              TRACK 01 AUDIO
+                INDEX 01 00:00:00
+            FILE "02 Two.flac" WAVE
+              TRACK 02 AUDIO
+                INDEX 00 00:00:00
+                INDEX 01 00:02:00
+        "#;
+        let toc = DiscToc::from_cue(cue, |name| match name {
+            "01 One.flac" => Some(Duration::from_secs(300)),
+            "02 Two.flac" => Some(Duration::from_secs(200)),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(toc.first_track, 1);
+        assert_eq!(toc.offsets, [150, 150 + 300 * 75 + 150]);
+        assert_eq!(toc.lead_out, 150 + 500 * 75);
+
+        // A file of unknown length leaves the table of contents unknown
+        assert!(DiscToc::from_cue(cue, |_| None).is_none());
+    }
+
+    #[test]
+    fn test_cue_chapters() {
+        let cue = r#"
+            TITLE "The Album"
+            FILE "Album.flac" WAVE
+              TRACK 01 AUDIO
+                TITLE "One"
+                INDEX 01 00:00:00
+              TRACK 02 AUDIO
+                TITLE "Two"
+                INDEX 00 03:58:40
+                INDEX 01 04:00:00
+        "#;
+        let chapters = cue_chapters(cue, "album.FLAC");
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].title.as_deref(), Some("One"));
+        assert_eq!(chapters[1].number, 2);
+        assert_eq!(chapters[1].start, Duration::from_secs(240));
+
+        // Sheets for other files, or one file per track, give nothing
+        assert!(cue_chapters(cue, "other.flac").is_empty());
+        let per_track = "FILE \"01.flac\" WAVE\n TRACK 01 AUDIO\n INDEX 01 00:00:00";
+        assert!(cue_chapters(per_track, "01.flac").is_empty());
+    }
+}
@@ -5,6 +5,8 @@
 
 use std::time::Duration;
 
+use super::similarity::MatchScores;
+
 /// Result of attempting to identify a track via audio fingerprint
 #[derive(Debug, Clone)]
 pub struct TrackIdentification {
@@ -14,6 +16,48 @@ pub struct TrackIdentification {
     pub track: IdentifiedTrack,
     /// Where this identification came from
     pub source: EnrichmentSource,
+    /// Fields filled in by a follow-up MusicBrainz lookup (tag field names,
+    /// as in [`crate::metadata::WriteResult::fields_written`])
+    pub musicbrainz_fields: Vec<&'static str>,
+    /// How the match compares with the file's existing tags
+    pub scores: MatchScores,
+}
+
+impl TrackIdentification {
+    /// Fill missing fields from a MusicBrainz lookup, remembering which
+    pub fn merge_musicbrainz(&mut self, other: &IdentifiedTrack) {
+        for field in self.track.merge(other) {
+            if !self.musicbrainz_fields.contains(&field) {
+                self.musicbrainz_fields.push(field);
+            }
+        }
+    }
+
+    /// Score the match against the file's existing tags
+    pub fn score_against(&mut self, tags: &crate::metadata::TrackMetadata) {
+        self.scores = MatchScores::compare(tags, &self.track);
+    }
+
+    /// Whether the match can be accepted without review: confident enough,
+    /// and not contradicting the title or artist already tagged
+    pub fn auto_accepted(&self, min_confidence: f32) -> bool {
+        self.score >= min_confidence && !self.scores.contradicts_tags()
+    }
+
+    /// How sure the match is, confidence weighed by how well it agrees with
+    /// the tags; the least sure are reviewed first
+    pub fn review_score(&self) -> f32 {
+        self.score * (0.5 + 0.5 * self.scores.agreement())
+    }
+
+    /// Which service a tag field's value came from
+    pub fn field_source(&self, field: &str) -> EnrichmentSource {
+        if self.musicbrainz_fields.contains(&field) {
+            EnrichmentSource::MusicBrainz
+        } else {
+            self.source
+        }
+    }
 }
 
 /// Track metadata obtained from external services
@@ -53,6 +97,15 @@ pub struct IdentifiedTrack {
     pub secondary_types: Vec<String>,
     /// Genres/tags from MusicBrainz
     pub genres: Vec<String>,
+    /// Language of the lyrics (ISO 639-2, "zxx" = instrumental), from the
+    /// recorded work
+    pub language: Option<String>,
+    /// Explicit (true) or clean (false) version
+    pub explicit: Option<bool>,
+    /// AcoustID track ID the fingerprint matched
+    pub acoustid_id: Option<String>,
+    /// Chromaprint fingerprint of the file it was identified from
+    pub fingerprint: Option<String>,
 }
 
 /// Source of enrichment data
@@ -63,6 +116,49 @@ pub enum EnrichmentSource {
     Manual,
 }
 
+/// A release's full tracklist, for comparing against the tracks we own
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseTracklist {
+    /// MusicBrainz release ID
+    pub release_id: String,
+    /// Release title
+    pub title: String,
+    /// Every track on the release, in disc then position order
+    pub tracks: Vec<ReleaseTrack>,
+}
+
+/// One track of a [`ReleaseTracklist`]
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseTrack {
+    /// Disc number (1 for single-disc releases)
+    pub disc: u32,
+    /// Position on the disc
+    pub position: u32,
+    /// Track title
+    pub title: String,
+    /// MusicBrainz recording ID
+    pub recording_id: Option<String>,
+    /// Track length
+    pub duration: Option<Duration>,
+}
+
+impl ReleaseTracklist {
+    /// Number of discs on the release
+    pub fn disc_count(&self) -> u32 {
+        self.tracks.iter().map(|t| t.disc).max().unwrap_or(0)
+    }
+}
+
+/// A release found by disc ID, with tags for each track of the matched disc
+#[derive(Debug, Clone)]
+pub struct DiscMatch {
+    /// Whether the disc ID itself matched; false for a release found by a
+    /// similar table of contents
+    pub exact: bool,
+    /// Tags for each track of the disc, in track order
+    pub tracks: Vec<IdentifiedTrack>,
+}
+
 /// Audio fingerprint for a track
 #[derive(Debug, Clone)]
 pub struct AudioFingerprint {
@@ -101,8 +197,11 @@ pub enum EnrichmentError {
 }
 
 impl IdentifiedTrack {
-    /// Merge another identification into this one, preferring non-None values
-    pub fn merge(&mut self, other: &IdentifiedTrack) {
+    /// Merge another identification into this one, preferring non-None values.
+    ///
+    /// Returns the tag fields that were filled in.
+    pub fn merge(&mut self, other: &IdentifiedTrack) -> Vec<&'static str> {
+        let before = self.tag_fields();
         if self.title.is_none() {
             self.title = other.title.clone();
         }
@@ -148,6 +247,133 @@ impl IdentifiedTrack {
         if self.genres.is_empty() {
             self.genres = other.genres.clone();
         }
+        if self.language.is_none() {
+            self.language = other.language.clone();
+        }
+        if self.explicit.is_none() {
+            self.explicit = other.explicit;
+        }
+        if self.acoustid_id.is_none() {
+            self.acoustid_id = other.acoustid_id.clone();
+        }
+        if self.fingerprint.is_none() {
+            self.fingerprint = other.fingerprint.clone();
+        }
+        self.tag_fields()
+            .into_iter()
+            .filter(|field| !before.contains(field))
+            .collect()
+    }
+
+    /// A tag field's value as it would be written, e.g. `field_value("year")`
+    pub fn field_value(&self, field: &str) -> Option<String> {
+        let number = |n: Option<u32>| n.map(|n| n.to_string());
+        match field {
+            "title" => self.title.clone(),
+            "artist" => self.artist.clone(),
+            "album_artist" => self.album_artist.clone(),
+            "album" => self.album.clone(),
+            "track_number" => number(self.track_number),
+            "total_tracks" => number(self.total_tracks),
+            "disc_number" => number(self.disc_number),
+            "total_discs" => number(self.total_discs),
+            "year" => self.year.map(|y| y.to_string()),
+            "genre" => (!self.genres.is_empty()).then(|| self.genres.join("; ")),
+            "language" => self.language.clone(),
+            "explicit" => self
+                .explicit
+                .map(|e| if e { "explicit" } else { "clean" }.to_string()),
+            "musicbrainz_recording_id" => self.recording_id.clone(),
+            "musicbrainz_artist_id" => self.artist_id.clone(),
+            "musicbrainz_release_id" => self.release_id.clone(),
+            "musicbrainz_release_group_id" => self.release_group_id.clone(),
+            "acoustid_id" => self.acoustid_id.clone(),
+            "acoustid_fingerprint" => self.fingerprint.clone(),
+            _ => None,
+        }
+    }
+
+    /// Set a tag field from its written form, or clear it with `None`.
+    ///
+    /// Returns false for unknown fields and unparsable numbers.
+    pub fn set_field(&mut self, field: &str, value: Option<&str>) -> bool {
+        fn number<T: std::str::FromStr>(slot: &mut Option<T>, value: Option<&str>) -> bool {
+            match value.map(str::parse) {
+                Some(Ok(n)) => *slot = Some(n),
+                Some(Err(_)) => return false,
+                None => *slot = None,
+            }
+            true
+        }
+        let text = value.map(String::from);
+        match field {
+            "title" => self.title = text,
+            "artist" => self.artist = text,
+            "album_artist" => self.album_artist = text,
+            "album" => self.album = text,
+            "track_number" => return number(&mut self.track_number, value),
+            "total_tracks" => return number(&mut self.total_tracks, value),
+            "disc_number" => return number(&mut self.disc_number, value),
+            "total_discs" => return number(&mut self.total_discs, value),
+            "year" => return number(&mut self.year, value),
+            "genre" => {
+                self.genres = value
+                    .map(crate::metadata::genres::split)
+                    .unwrap_or_default()
+            }
+            "language" => match value {
+                Some(value) => match crate::metadata::content::normalize_language(value) {
+                    Some(code) => self.language = Some(code),
+                    None => return false,
+                },
+                None => self.language = None,
+            },
+            "explicit" => match value {
+                Some(value) => match crate::metadata::content::parse_advisory(value) {
+                    Some(explicit) => self.explicit = Some(explicit),
+                    None => return false,
+                },
+                None => self.explicit = None,
+            },
+            "musicbrainz_recording_id" => self.recording_id = text,
+            "musicbrainz_artist_id" => self.artist_id = text,
+            "musicbrainz_release_id" => self.release_id = text,
+            "musicbrainz_release_group_id" => self.release_group_id = text,
+            "acoustid_id" => self.acoustid_id = text,
+            "acoustid_fingerprint" => self.fingerprint = text,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Tag fields this identification has a value for
+    fn tag_fields(&self) -> Vec<&'static str> {
+        [
+            ("title", self.title.is_some()),
+            ("artist", self.artist.is_some()),
+            ("album_artist", self.album_artist.is_some()),
+            ("album", self.album.is_some()),
+            ("track_number", self.track_number.is_some()),
+            ("total_tracks", self.total_tracks.is_some()),
+            ("disc_number", self.disc_number.is_some()),
+            ("total_discs", self.total_discs.is_some()),
+            ("year", self.year.is_some()),
+            ("genre", !self.genres.is_empty()),
+            ("language", self.language.is_some()),
+            ("explicit", self.explicit.is_some()),
+            ("musicbrainz_recording_id", self.recording_id.is_some()),
+            ("musicbrainz_artist_id", self.artist_id.is_some()),
+            ("musicbrainz_release_id", self.release_id.is_some()),
+            (
+                "musicbrainz_release_group_id",
+                self.release_group_id.is_some(),
+            ),
+            ("acoustid_id", self.acoustid_id.is_some()),
+            ("acoustid_fingerprint", self.fingerprint.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, set)| set.then_some(field))
+        .collect()
     }
 }
 
@@ -170,10 +396,31 @@ mod tests {
             ..Default::default()
         };
 
-        track.merge(&other);
+        // Only the filled-in fields are reported
+        assert_eq!(track.merge(&other), ["artist", "album"]);
 
         assert_eq!(track.title, Some("Song".to_string())); // Kept original
         assert_eq!(track.artist, Some("Artist".to_string())); // Filled in
         assert_eq!(track.album, Some("Album".to_string())); // Filled in
     }
+
+    #[test]
+    fn test_field_values_round_trip() {
+        let mut track = IdentifiedTrack::default();
+        assert!(track.set_field("album", Some("OK Computer")));
+        assert!(track.set_field("year", Some("1997")));
+        assert!(!track.set_field("year", Some("soon")));
+        assert!(!track.set_field("mood", Some("grey")));
+        assert!(track.set_field("genre", Some("Rock; Trip Hop")));
+        assert_eq!(track.genres, ["Rock", "Trip Hop"]);
+        assert!(track.set_field("explicit", Some("clean")));
+        assert_eq!(track.field_value("explicit").as_deref(), Some("clean"));
+        assert!(track.set_field("explicit", None));
+        assert_eq!(track.field_value("album").as_deref(), Some("OK Computer"));
+        assert_eq!(track.field_value("year").as_deref(), Some("1997"));
+
+        assert!(track.set_field("album", None));
+        assert_eq!(track.field_value("album"), None);
+        assert_eq!(track.tag_fields(), ["year", "genre"]);
+    }
 }
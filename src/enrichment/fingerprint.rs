@@ -8,14 +8,20 @@
 //! - Windows: `winget install AcoustID.Chromaprint` or download from https://acoustid.org/chromaprint
 //! - macOS: `brew install chromaprint`
 //! - Linux: `apt install libchromaprint-tools` or equivalent
+//!
+//! [`FPCALC_ENV`] (or [`set_fpcalc`]) points at another fpcalc, such as a
+//! fake one that prints canned fingerprints, so enrichment can run end to
+//! end in CI without Chromaprint installed.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::RwLock;
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
 use crate::enrichment::domain::{AudioFingerprint, EnrichmentError};
+use crate::model::TrackSource;
 
 /// Windows: CREATE_NO_WINDOW flag to prevent console popup
 #[cfg(windows)]
@@ -39,22 +45,55 @@ const FPCALC_PATHS: &[&str] = &[
     "/opt/homebrew/bin/fpcalc",
 ];
 
-/// Find the fpcalc executable, checking common installation paths
-fn find_fpcalc() -> Option<&'static str> {
+/// Environment variable naming the fpcalc to run instead of searching for one
+pub const FPCALC_ENV: &str = "MUSIC_MINDER_FPCALC";
+
+/// The fpcalc set with [`set_fpcalc`]
+static FPCALC: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Run `fpcalc` instead of searching for one (`None` to search again);
+/// takes precedence over [`FPCALC_ENV`]
+pub fn set_fpcalc(fpcalc: Option<PathBuf>) {
+    *FPCALC.write().unwrap_or_else(|e| e.into_inner()) = fpcalc;
+}
+
+/// Find the fpcalc executable: the one set or named in [`FPCALC_ENV`],
+/// else the first of the common installation paths that runs
+fn find_fpcalc() -> Option<PathBuf> {
+    let chosen = FPCALC
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .or_else(|| std::env::var_os(FPCALC_ENV).map(PathBuf::from));
+    if let Some(fpcalc) = chosen {
+        return runs(&fpcalc).then_some(fpcalc);
+    }
     FPCALC_PATHS
         .iter()
-        .find(|&path| {
-            let mut cmd = Command::new(path);
-            cmd.arg("-version");
-            #[cfg(windows)]
-            cmd.creation_flags(CREATE_NO_WINDOW);
-            cmd.output().map(|o| o.status.success()).unwrap_or(false)
-        })
-        .map(|v| v as _)
+        .map(PathBuf::from)
+        .find(|path| runs(path))
+}
+
+/// Whether `fpcalc -version` succeeds
+fn runs(fpcalc: &Path) -> bool {
+    let mut cmd = Command::new(fpcalc);
+    cmd.arg("-version");
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd.output().map(|o| o.status.success()).unwrap_or(false)
 }
 
 /// Generate an audio fingerprint for the given file
+///
+/// Streams and cloud drive links aren't downloaded to be fingerprinted.
 pub fn generate_fingerprint(path: &Path) -> Result<AudioFingerprint, EnrichmentError> {
+    let source = TrackSource::detect(&path.to_string_lossy());
+    if !source.is_local() {
+        return Err(EnrichmentError::FingerprintError(format!(
+            "{} tracks can't be fingerprinted, only files",
+            source.label()
+        )));
+    }
     let fpcalc = find_fpcalc().ok_or_else(|| {
         EnrichmentError::FingerprintError(
             "fpcalc not found. Please install Chromaprint: https://acoustid.org/chromaprint"
@@ -124,6 +163,15 @@ pub fn get_fpcalc_version() -> Option<String> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_streams_not_fingerprinted() {
+        let err = generate_fingerprint(Path::new("http://radio.example/live.mp3")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Stream tracks can't be fingerprinted")
+        );
+    }
+
     #[test]
     fn test_parse_fpcalc_json() {
         let json = r#"{"duration": 180.5, "fingerprint": "AQADtNIyRUkkZUqS"}"#;
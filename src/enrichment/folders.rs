@@ -0,0 +1,165 @@
+//! Per-folder enrichment defaults.
+//!
+//! Folders can be treated differently: audiobooks never identified,
+//! "Incoming" only filling empty tags, a trusted rip folder written without
+//! review. A rule applies to every track under its folder; the deepest
+//! matching folder wins. Rules live in the `folder_enrichment` table and are
+//! applied when tracks are added to the Enrich pane and when the background
+//! agent identifies new tracks.
+
+use std::path::Path;
+
+use sqlx::sqlite::SqlitePool;
+
+/// Confidence at which a match is accepted without review, unless a folder
+/// sets its own
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.7;
+
+/// How tracks under one folder are enriched
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct FolderDefaults {
+    pub folder: String,
+    /// Whether tracks here are identified at all
+    pub enabled: bool,
+    /// Only fill empty (or placeholder) tags
+    pub fill_only: bool,
+    /// Accept matches at or above this confidence; `None` for the default
+    pub min_confidence: Option<f32>,
+    /// Write accepted matches without review
+    pub auto_write: bool,
+}
+
+impl FolderDefaults {
+    /// A rule that changes nothing yet
+    pub fn new(folder: impl Into<String>) -> Self {
+        Self {
+            folder: folder.into(),
+            enabled: true,
+            fill_only: false,
+            min_confidence: None,
+            auto_write: false,
+        }
+    }
+
+    pub fn min_confidence(&self) -> f32 {
+        self.min_confidence.unwrap_or(DEFAULT_MIN_CONFIDENCE)
+    }
+}
+
+/// Every folder's rule
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FolderRules(pub Vec<FolderDefaults>);
+
+impl FolderRules {
+    /// The rule of the deepest folder holding `path`
+    pub fn for_path(&self, path: &Path) -> Option<&FolderDefaults> {
+        self.0
+            .iter()
+            .filter(|rule| path.starts_with(&rule.folder))
+            .max_by_key(|rule| Path::new(&rule.folder).components().count())
+    }
+
+    pub fn is_enabled(&self, path: &Path) -> bool {
+        self.for_path(path).is_none_or(|rule| rule.enabled)
+    }
+
+    pub fn fill_only(&self, path: &Path) -> bool {
+        self.for_path(path).is_some_and(|rule| rule.fill_only)
+    }
+
+    pub fn min_confidence(&self, path: &Path) -> f32 {
+        self.for_path(path)
+            .map_or(DEFAULT_MIN_CONFIDENCE, FolderDefaults::min_confidence)
+    }
+
+    pub fn auto_write(&self, path: &Path) -> bool {
+        self.for_path(path).is_some_and(|rule| rule.auto_write)
+    }
+}
+
+/// Load every rule, by folder
+pub async fn load(pool: &SqlitePool) -> sqlx::Result<FolderRules> {
+    let rules = sqlx::query_as(
+        "SELECT folder, enabled, fill_only, min_confidence, auto_write
+         FROM folder_enrichment ORDER BY folder",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(FolderRules(rules))
+}
+
+/// Add a folder's rule, or replace it
+pub async fn save(pool: &SqlitePool, rule: &FolderDefaults) -> sqlx::Result<()> {
+    sqlx::query(
+        "INSERT INTO folder_enrichment (folder, enabled, fill_only, min_confidence, auto_write)
+         VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(folder) DO UPDATE SET
+             enabled = excluded.enabled,
+             fill_only = excluded.fill_only,
+             min_confidence = excluded.min_confidence,
+             auto_write = excluded.auto_write",
+    )
+    .bind(&rule.folder)
+    .bind(rule.enabled)
+    .bind(rule.fill_only)
+    .bind(rule.min_confidence)
+    .bind(rule.auto_write)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Remove a folder's rule. Returns whether there was one.
+pub async fn remove(pool: &SqlitePool, folder: &str) -> sqlx::Result<bool> {
+    let result = sqlx::query("DELETE FROM folder_enrichment WHERE folder = ?")
+        .bind(folder)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_db;
+
+    #[tokio::test]
+    async fn test_deepest_folder_wins() {
+        let (pool, _dir) = temp_db().await;
+
+        let music = FolderDefaults {
+            min_confidence: Some(0.9),
+            ..FolderDefaults::new("/music")
+        };
+        let incoming = FolderDefaults {
+            fill_only: true,
+            auto_write: true,
+            ..FolderDefaults::new("/music/Incoming")
+        };
+        let audiobooks = FolderDefaults {
+            enabled: false,
+            ..FolderDefaults::new("/music/Audiobooks")
+        };
+        for rule in [&music, &incoming, &audiobooks] {
+            save(&pool, rule).await.unwrap();
+        }
+        // Saving again replaces the rule
+        save(&pool, &incoming).await.unwrap();
+
+        let rules = load(&pool).await.unwrap();
+        assert_eq!(rules.0.len(), 3);
+
+        let new = Path::new("/music/Incoming/Album/01.flac");
+        assert!(rules.fill_only(new));
+        assert!(rules.auto_write(new));
+        assert_eq!(rules.min_confidence(new), DEFAULT_MIN_CONFIDENCE);
+        assert!(!rules.is_enabled(Path::new("/music/Audiobooks/Book/1.mp3")));
+        assert_eq!(rules.min_confidence(Path::new("/music/Rock/a.mp3")), 0.9);
+        // A folder name that is only a prefix doesn't count
+        assert!(rules.is_enabled(Path::new("/music/Audiobooks2/a.mp3")));
+        assert!(rules.for_path(Path::new("/other/a.mp3")).is_none());
+
+        assert!(remove(&pool, "/music/Audiobooks").await.unwrap());
+        assert!(!remove(&pool, "/music/Audiobooks").await.unwrap());
+    }
+}
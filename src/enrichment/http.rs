@@ -0,0 +1,70 @@
+//! Offline mode for the enrichment clients
+//!
+//! Every client checks its request URL with [`guard`] before sending. In
+//! tests, when `MUSIC_MINDER_OFFLINE` is set, and with `network.offline`
+//! in the config file, only loopback hosts are allowed: tests point the clients at a local fixture server (see
+//! `test_utils::FixtureServer`) and CI never reaches AcoustID, MusicBrainz
+//! or the Cover Art Archive.
+//!
+//! The fixture server answers with the payloads in `enrichment/fixtures/`:
+//! full responses as the services return them, trimmed to a few entries.
+//! The DTO contract tests parse them and the client tests check what the
+//! adapters make of them. To refresh one, save the live response to the
+//! URL the client builds (the client tests assert it) and update the
+//! assertions.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::enrichment::domain::EnrichmentError;
+
+/// Environment variable that turns offline mode on outside tests
+pub const OFFLINE_ENV: &str = "MUSIC_MINDER_OFFLINE";
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn offline mode on or off; set at startup from `network.offline`
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::SeqCst);
+}
+
+/// Whether requests to external hosts are refused
+pub fn is_offline() -> bool {
+    cfg!(test)
+        || OFFLINE.load(Ordering::SeqCst)
+        || std::env::var_os(OFFLINE_ENV).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Refuse a request to an external host in offline mode
+pub fn guard(url: &str) -> Result<(), EnrichmentError> {
+    if !is_offline() || is_loopback(url) {
+        return Ok(());
+    }
+    Err(EnrichmentError::Network(format!(
+        "offline mode: not requesting {}",
+        url.split('?').next().unwrap_or(url)
+    )))
+}
+
+fn is_loopback(url: &str) -> bool {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .is_some_and(|host| matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tests_run_offline() {
+        assert!(is_offline());
+        assert!(guard("http://127.0.0.1:4000/ws/2/recording/x?fmt=json").is_ok());
+        assert!(guard("http://localhost/release/x").is_ok());
+
+        let err = guard("https://musicbrainz.org/ws/2/recording/x?fmt=json").unwrap_err();
+        assert!(matches!(err, EnrichmentError::Network(_)));
+        // The query (API key, fingerprint) isn't repeated in the error
+        assert!(!err.to_string().contains("fmt=json"));
+    }
+}
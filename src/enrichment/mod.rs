@@ -7,8 +7,18 @@
 //! - **API DTOs** (`acoustid/dto.rs`, `musicbrainz/dto.rs`) - Exact API response shapes
 //! - **Adapters** - Convert DTOs to domain models
 //! - **Clients** - HTTP clients for external APIs
+//! - **HTTP** - Offline mode, which keeps tests off the network
 //! - **Fingerprint** - Audio fingerprint generation via fpcalc
+//! - **Budget** - Limits how many fingerprints run at once
+//! - **Cluster** - Votes a folder of files onto one release (album clustering)
+//! - **Folders** - Per-folder defaults (enabled, fill-only, confidence, auto-write)
 //! - **Service** - High-level orchestration of the enrichment flow
+//! - **Similarity** - Fuzzy title/artist/album scores against a file's tags
+//! - **Report** - What a run changed, exported as JSON, CSV or HTML
+//!
+//! The clients, offline mode and the service need the `enrichment` feature;
+//! without it the domain types, DTOs, fingerprinting and folder rules are
+//! still built for the rest of the crate.
 //!
 //! This decoupling means:
 //! 1. API changes don't ripple through our codebase
@@ -36,16 +46,31 @@
 pub const DEFAULT_ACOUSTID_API_KEY: &str = "SIwKdLgXuH";
 
 pub mod acoustid;
+pub mod budget;
+pub mod cluster;
+#[cfg(feature = "enrichment")]
 pub mod coverart;
+pub mod discid;
 pub mod domain;
 pub mod fingerprint;
+pub mod folders;
+#[cfg(feature = "enrichment")]
+pub mod http;
 pub mod musicbrainz;
+pub mod report;
+#[cfg(feature = "enrichment")]
 pub mod service;
+pub mod similarity;
+#[cfg(feature = "enrichment")]
 pub mod traits;
 
+#[cfg(feature = "enrichment")]
 pub use coverart::{CoverArt, CoverArtClient, CoverSize};
 pub use domain::{
-    AudioFingerprint, EnrichmentError, EnrichmentSource, IdentifiedTrack, TrackIdentification,
+    AudioFingerprint, EnrichmentError, EnrichmentSource, IdentifiedTrack, ReleaseTrack,
+    ReleaseTracklist, TrackIdentification,
 };
+#[cfg(feature = "enrichment")]
 pub use service::{EnrichmentConfig, EnrichmentService, identify_track};
+#[cfg(feature = "enrichment")]
 pub use traits::{AcoustIdApi, CoverArtApi, MusicBrainzApi};
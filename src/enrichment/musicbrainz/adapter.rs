@@ -5,7 +5,11 @@
 //! only this file and dto.rs need to change.
 
 use super::dto;
-use crate::enrichment::domain::{EnrichmentSource, IdentifiedTrack, TrackIdentification};
+use crate::enrichment::domain::{
+    DiscMatch, EnrichmentSource, IdentifiedTrack, ReleaseTrack, ReleaseTracklist,
+    TrackIdentification,
+};
+use crate::metadata::content::{INSTRUMENTAL, normalize_language};
 
 /// Release info extracted from MusicBrainz
 struct ReleaseInfo {
@@ -34,6 +38,12 @@ pub fn to_identification(response: dto::RecordingResponse) -> TrackIdentificatio
     // Extract genres from tags, sorted by vote count
     let genres = extract_genres(&response.tags);
 
+    let language = extract_language(&response.relations);
+    let explicit = response
+        .disambiguation
+        .as_deref()
+        .and_then(explicit_from_disambiguation);
+
     let track = IdentifiedTrack {
         recording_id: Some(response.id),
         title: Some(response.title),
@@ -52,15 +62,203 @@ pub fn to_identification(response: dto::RecordingResponse) -> TrackIdentificatio
         release_type,
         secondary_types: secondary_types.unwrap_or_default(),
         genres,
+        language,
+        explicit,
+        ..Default::default()
     };
 
     TrackIdentification {
         score: 1.0, // MusicBrainz lookups by ID are exact matches
         track,
         source: EnrichmentSource::MusicBrainz,
+        musicbrainz_fields: Vec::new(),
+        scores: Default::default(),
+    }
+}
+
+/// Convert a MusicBrainz release lookup to its tracklist
+pub fn to_tracklist(release: dto::Release) -> ReleaseTracklist {
+    let mut tracks: Vec<ReleaseTrack> = release
+        .media
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, medium)| {
+            let disc = medium.position.unwrap_or(i as u32 + 1);
+            medium
+                .tracks
+                .into_iter()
+                .enumerate()
+                .map(move |(j, track)| ReleaseTrack {
+                    disc,
+                    position: track.position.unwrap_or(j as u32 + 1),
+                    title: track
+                        .title
+                        .or_else(|| track.recording.as_ref().and_then(|r| r.title.clone()))
+                        .unwrap_or_default(),
+                    recording_id: track.recording.map(|r| r.id),
+                    duration: track.length.map(std::time::Duration::from_millis),
+                })
+        })
+        .collect();
+    tracks.sort_by_key(|t| (t.disc, t.position));
+
+    ReleaseTracklist {
+        release_id: release.id,
+        title: release.title,
+        tracks,
     }
 }
 
+/// The identification of one recording from its release's lookup, as
+/// [`to_identification`] would give it with that release chosen. Genres are
+/// the release's, not the recording's. `None` if the recording isn't on it.
+pub fn to_identification_on_release(
+    release: &dto::Release,
+    recording_id: &str,
+) -> Option<TrackIdentification> {
+    let (medium, track) = release.media.iter().find_map(|medium| {
+        medium
+            .tracks
+            .iter()
+            .find(|t| t.recording.as_ref().is_some_and(|r| r.id == recording_id))
+            .map(|track| (medium, track))
+    })?;
+    let recording = track.recording.as_ref()?;
+
+    let credits = release.artist_credit.as_deref().unwrap_or_default();
+    let album_artist = build_artist_string(credits);
+    let multi_disc = release.media.len() > 1;
+    let group = release.release_group.as_ref();
+
+    let track = IdentifiedTrack {
+        recording_id: Some(recording.id.clone()),
+        title: recording.title.clone().or_else(|| track.title.clone()),
+        artist: build_artist_string(&track.artist_credit).or_else(|| album_artist.clone()),
+        album_artist,
+        album: Some(release.title.clone()),
+        track_number: track.position,
+        total_tracks: medium.track_count.or(Some(medium.tracks.len() as u32)),
+        disc_number: if multi_disc { medium.position } else { None },
+        total_discs: multi_disc.then_some(release.media.len() as u32),
+        year: release
+            .date
+            .as_ref()
+            .and_then(|d| d.split('-').next())
+            .and_then(|y| y.parse().ok()),
+        duration: recording
+            .length
+            .or(track.length)
+            .map(std::time::Duration::from_millis),
+        artist_id: track
+            .artist_credit
+            .first()
+            .or(credits.first())
+            .map(|c| c.artist.id.clone()),
+        release_id: Some(release.id.clone()),
+        release_group_id: group.map(|rg| rg.id.clone()),
+        release_type: group.and_then(|rg| rg.primary_type.clone()),
+        secondary_types: group
+            .map(|rg| rg.secondary_types.clone())
+            .unwrap_or_default(),
+        genres: extract_genres(&release.tags),
+        language: extract_language(&recording.relations),
+        explicit: recording
+            .disambiguation
+            .as_deref()
+            .and_then(explicit_from_disambiguation),
+        ..Default::default()
+    };
+
+    Some(TrackIdentification {
+        score: 1.0,
+        track,
+        source: EnrichmentSource::MusicBrainz,
+        musicbrainz_fields: Vec::new(),
+        scores: Default::default(),
+    })
+}
+
+/// Convert a disc ID lookup to the releases with a disc of `track_count`
+/// tracks, exact disc ID matches first
+pub fn to_disc_matches(
+    response: dto::DiscIdResponse,
+    disc_id: &str,
+    track_count: usize,
+) -> Vec<DiscMatch> {
+    let mut matches: Vec<DiscMatch> = response
+        .releases
+        .iter()
+        .filter_map(|release| {
+            let exact = release
+                .media
+                .iter()
+                .find(|m| m.discs.iter().any(|d| d.id == disc_id));
+            let medium =
+                exact.or_else(|| release.media.iter().find(|m| m.tracks.len() == track_count))?;
+            if medium.tracks.len() != track_count {
+                return None;
+            }
+
+            let credits = release.artist_credit.as_deref().unwrap_or_default();
+            let album_artist = build_artist_string(credits);
+            let album_artist_id = credits.first().map(|c| c.artist.id.clone());
+            let multi_disc = release.media.len() > 1;
+            let year = release
+                .date
+                .as_ref()
+                .and_then(|d| d.split('-').next())
+                .and_then(|y| y.parse().ok());
+
+            let tracks = medium
+                .tracks
+                .iter()
+                .enumerate()
+                .map(|(i, track)| IdentifiedTrack {
+                    recording_id: track.recording.as_ref().map(|r| r.id.clone()),
+                    title: track
+                        .title
+                        .clone()
+                        .or_else(|| track.recording.as_ref().and_then(|r| r.title.clone())),
+                    artist: build_artist_string(&track.artist_credit)
+                        .or_else(|| album_artist.clone()),
+                    album_artist: album_artist.clone(),
+                    album: Some(release.title.clone()),
+                    track_number: Some(track.position.unwrap_or(i as u32 + 1)),
+                    total_tracks: Some(track_count as u32),
+                    // Like recording lookups, no disc numbers for single discs
+                    disc_number: if multi_disc { medium.position } else { None },
+                    total_discs: multi_disc.then_some(release.media.len() as u32),
+                    year,
+                    duration: track.length.map(std::time::Duration::from_millis),
+                    artist_id: track
+                        .artist_credit
+                        .first()
+                        .map(|c| c.artist.id.clone())
+                        .or_else(|| album_artist_id.clone()),
+                    release_id: Some(release.id.clone()),
+                    release_group_id: release.release_group.as_ref().map(|rg| rg.id.clone()),
+                    release_type: release
+                        .release_group
+                        .as_ref()
+                        .and_then(|rg| rg.primary_type.clone()),
+                    secondary_types: Vec::new(),
+                    genres: Vec::new(),
+                    language: None,
+                    explicit: None,
+                    ..Default::default()
+                })
+                .collect();
+
+            Some(DiscMatch {
+                exact: exact.is_some(),
+                tracks,
+            })
+        })
+        .collect();
+    matches.sort_by_key(|m| !m.exact);
+    matches
+}
+
 /// Build a combined artist string from artist credits
 fn build_artist_string(credits: &[dto::ArtistCredit]) -> Option<String> {
     if credits.is_empty() {
@@ -82,27 +280,27 @@ fn build_artist_string(credits: &[dto::ArtistCredit]) -> Option<String> {
     Some(result)
 }
 
-/// Extract release type and secondary types from releases
+/// Extract release type and secondary types from the release the album
+/// info comes from
 fn extract_release_types(releases: &[dto::Release]) -> (Option<String>, Option<Vec<String>>) {
-    let release = match releases.first() {
+    let release = match best_release(releases) {
         Some(r) => r,
         None => return (None, None),
     };
 
-    let release_type = release
-        .release_group
-        .as_ref()
-        .and_then(|rg| rg.primary_type.clone());
-
-    let secondary_types = None; // MusicBrainz DTO doesn't currently include secondary types
-
-    (release_type, secondary_types)
+    let Some(group) = &release.release_group else {
+        return (None, None);
+    };
+    (
+        group.primary_type.clone(),
+        Some(group.secondary_types.clone()),
+    )
 }
 
-/// Extract the best release info from available releases
-fn extract_release_info(releases: &[dto::Release]) -> ReleaseInfo {
-    // Prefer official album releases over singles/bootlegs
-    let release = releases
+/// The release to take album info from: official albums over singles and
+/// bootlegs
+fn best_release(releases: &[dto::Release]) -> Option<&dto::Release> {
+    releases
         .iter()
         .find(|r| {
             r.status.as_deref() == Some("Official")
@@ -117,9 +315,12 @@ fn extract_release_info(releases: &[dto::Release]) -> ReleaseInfo {
                 .iter()
                 .find(|r| r.status.as_deref() == Some("Official"))
         })
-        .or_else(|| releases.first());
+        .or_else(|| releases.first())
+}
 
-    let Some(release) = release else {
+/// Extract the best release info from available releases
+fn extract_release_info(releases: &[dto::Release]) -> ReleaseInfo {
+    let Some(release) = best_release(releases) else {
         return ReleaseInfo {
             album: None,
             album_artist: None,
@@ -197,7 +398,7 @@ fn extract_release_info(releases: &[dto::Release]) -> ReleaseInfo {
 /// Takes the top 5 most-voted tags to avoid noise from low-confidence tags
 fn extract_genres(tags: &[dto::Tag]) -> Vec<String> {
     let mut sorted_tags: Vec<_> = tags.iter().collect();
-    sorted_tags.sort_by(|a, b| b.count.cmp(&a.count));
+    sorted_tags.sort_by_key(|t| std::cmp::Reverse(t.count));
 
     sorted_tags
         .into_iter()
@@ -220,6 +421,35 @@ fn extract_genres(tags: &[dto::Tag]) -> Vec<String> {
         .collect()
 }
 
+/// The lyrics language of the performed work: "zxx" for an instrumental
+/// performance, the first language of a multi-language ("mul") work
+fn extract_language(relations: &[dto::Relation]) -> Option<String> {
+    let performance = relations
+        .iter()
+        .find(|r| r.relation_type == "performance" && r.work.is_some())?;
+    if performance.attributes.iter().any(|a| a == "instrumental") {
+        return Some(INSTRUMENTAL.to_string());
+    }
+    let work = performance.work.as_ref()?;
+    work.language
+        .iter()
+        .chain(&work.languages)
+        .find(|code| code.as_str() != "mul")
+        .and_then(|code| normalize_language(code))
+}
+
+/// Explicit and clean versions are told apart by their disambiguation
+fn explicit_from_disambiguation(disambiguation: &str) -> Option<bool> {
+    let lower = disambiguation.to_lowercase();
+    if lower.contains("explicit") {
+        Some(true)
+    } else if lower.contains("clean") || lower.contains("edited") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,6 +463,7 @@ mod tests {
             artist_credit: vec![],
             releases: vec![],
             tags: vec![],
+            relations: vec![],
         }
     }
 
@@ -264,6 +495,141 @@ mod tests {
         assert_eq!(identification.score, 1.0);
     }
 
+    #[test]
+    fn test_convert_language_and_explicit() {
+        let performance =
+            |attributes: &[&str], language: Option<&str>, languages: &[&str]| dto::Relation {
+                relation_type: "performance".to_string(),
+                attributes: attributes.iter().map(|a| a.to_string()).collect(),
+                work: Some(dto::Work {
+                    id: "work-1".to_string(),
+                    title: "Song".to_string(),
+                    language: language.map(String::from),
+                    languages: languages.iter().map(|l| l.to_string()).collect(),
+                }),
+            };
+
+        let mut recording = make_recording("rec-1", "Song");
+        recording.disambiguation = Some("explicit".to_string());
+        recording.relations = vec![performance(&[], Some("jpn"), &["jpn"])];
+        let track = to_identification(recording).track;
+        assert_eq!(track.language.as_deref(), Some("jpn"));
+        assert_eq!(track.explicit, Some(true));
+
+        let instrumental = [performance(&["instrumental"], Some("eng"), &["eng"])];
+        assert_eq!(extract_language(&instrumental).as_deref(), Some("zxx"));
+        let multi = [performance(&[], Some("mul"), &["mul", "fra", "eng"])];
+        assert_eq!(extract_language(&multi).as_deref(), Some("fra"));
+        assert_eq!(extract_language(&[]), None);
+        assert_eq!(explicit_from_disambiguation("clean version"), Some(false));
+        assert_eq!(explicit_from_disambiguation("live"), None);
+    }
+
+    #[test]
+    fn test_convert_release_tracklist() {
+        let track = |position: u32, title: &str, recording: Option<&str>| dto::Track {
+            position: Some(position),
+            number: Some(position.to_string()),
+            title: Some(title.to_string()),
+            length: Some(1000),
+            recording: recording.map(|id| dto::TrackRecording {
+                id: id.to_string(),
+                title: None,
+                length: None,
+                disambiguation: None,
+                relations: Vec::new(),
+            }),
+            artist_credit: Vec::new(),
+        };
+        let medium = |position, tracks| dto::Medium {
+            position: Some(position),
+            format: None,
+            track_count: None,
+            tracks,
+            discs: Vec::new(),
+        };
+        let release = dto::Release {
+            id: "rel-1".to_string(),
+            title: "Double".to_string(),
+            status: None,
+            date: None,
+            country: None,
+            release_group: None,
+            media: vec![
+                medium(2, vec![track(1, "Side C", None)]),
+                medium(
+                    1,
+                    vec![track(2, "Second", Some("rec-2")), track(1, "First", None)],
+                ),
+            ],
+            artist_credit: None,
+            tags: vec![],
+        };
+
+        let tracklist = to_tracklist(release);
+
+        let order: Vec<(u32, u32, &str)> = tracklist
+            .tracks
+            .iter()
+            .map(|t| (t.disc, t.position, t.title.as_str()))
+            .collect();
+        assert_eq!(order, [(1, 1, "First"), (1, 2, "Second"), (2, 1, "Side C")]);
+        assert_eq!(tracklist.tracks[1].recording_id.as_deref(), Some("rec-2"));
+        assert_eq!(tracklist.disc_count(), 2);
+    }
+
+    #[test]
+    fn test_convert_disc_matches() {
+        let json = r#"{
+            "releases": [
+                {
+                    "id": "rel-similar",
+                    "title": "Album (Remaster)",
+                    "media": [{"position": 1, "tracks": [
+                        {"position": 1, "title": "One"}, {"position": 2, "title": "Two"}
+                    ]}]
+                },
+                {
+                    "id": "rel-exact",
+                    "title": "Album",
+                    "date": "1997-05-21",
+                    "artist-credit": [{"artist": {"id": "art-1", "name": "Band"}}],
+                    "media": [
+                        {"position": 1, "discs": [{"id": "other"}], "tracks": [
+                            {"position": 1, "title": "One"}, {"position": 2, "title": "Two"}
+                        ]},
+                        {"position": 2, "discs": [{"id": "disc-2"}], "tracks": [
+                            {"position": 1, "title": "Three",
+                             "artist-credit": [{"artist": {"id": "art-2", "name": "Guest"}}]},
+                            {"position": 2, "title": "Four",
+                             "recording": {"id": "rec-4"}}
+                        ]}
+                    ]
+                },
+                {"id": "rel-other", "title": "Single", "media": [{"tracks": [{"title": "One"}]}]}
+            ]
+        }"#;
+        let response: dto::DiscIdResponse = serde_json::from_str(json).unwrap();
+
+        let matches = to_disc_matches(response, "disc-2", 2);
+        assert_eq!(matches.len(), 2);
+
+        // The exact match comes first, with the disc whose ID matched
+        assert!(matches[0].exact);
+        let tracks = &matches[0].tracks;
+        assert_eq!(tracks[0].title.as_deref(), Some("Three"));
+        assert_eq!(tracks[0].artist.as_deref(), Some("Guest"));
+        assert_eq!(tracks[1].artist.as_deref(), Some("Band"));
+        assert_eq!(tracks[1].album_artist.as_deref(), Some("Band"));
+        assert_eq!(tracks[1].recording_id.as_deref(), Some("rec-4"));
+        assert_eq!(tracks[1].disc_number, Some(2));
+        assert_eq!(tracks[1].total_discs, Some(2));
+        assert_eq!(tracks[1].year, Some(1997));
+
+        assert!(!matches[1].exact);
+        assert_eq!(matches[1].tracks[0].disc_number, None);
+    }
+
     #[test]
     fn test_build_single_artist() {
         let credits = vec![make_artist_credit("Queen", None)];
@@ -296,6 +662,7 @@ mod tests {
             release_group: None,
             media: vec![],
             artist_credit: None,
+            tags: vec![],
         }];
 
         let info = extract_release_info(&releases);
@@ -316,10 +683,12 @@ mod tests {
                     id: "rg-single".to_string(),
                     title: "Single".to_string(),
                     primary_type: Some("Single".to_string()),
+                    secondary_types: vec![],
                     first_release_date: None,
                 }),
                 media: vec![],
                 artist_credit: None,
+                tags: vec![],
             },
             dto::Release {
                 id: "album".to_string(),
@@ -331,10 +700,12 @@ mod tests {
                     id: "rg-album".to_string(),
                     title: "Album".to_string(),
                     primary_type: Some("Album".to_string()),
+                    secondary_types: vec![],
                     first_release_date: None,
                 }),
                 media: vec![],
                 artist_credit: None,
+                tags: vec![],
             },
         ];
 
@@ -4,14 +4,63 @@
 //! See: https://musicbrainz.org/doc/MusicBrainz_API
 //!
 //! IMPORTANT: MusicBrainz requires a User-Agent header and rate limits to 1 req/sec.
+//!
+//! # Coalescing
+//!
+//! A client lives for one enrichment run and remembers what it fetched:
+//! - Recordings are memoized by MBID, so alternatives and repeated tracks
+//!   cost nothing.
+//! - [`MusicBrainzClient::lookup_recording_on`] answers for a recording on
+//!   a release the run is already tagging from (an album's tracks), with
+//!   one release lookup instead of a recording lookup per track.
+//!
+//! [`MusicBrainzClient::stats`] counts the requests sent and saved.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use super::{adapter, dto};
-use crate::enrichment::domain::{EnrichmentError, TrackIdentification};
+use crate::enrichment::discid::DiscToc;
+use crate::enrichment::domain::{
+    DiscMatch, EnrichmentError, ReleaseTracklist, TrackIdentification,
+};
+use crate::enrichment::http;
+
+/// Lookups remembered before the memo starts over (long-running agents)
+const MEMO_LIMIT: usize = 5000;
 
 /// MusicBrainz API client
 pub struct MusicBrainzClient {
     http_client: reqwest::Client,
     base_url: String,
+    memo: Mutex<Memo>,
+    requests: AtomicUsize,
+    saved: AtomicUsize,
+}
+
+/// Lookups made so far in this run
+#[derive(Default)]
+struct Memo {
+    recordings: HashMap<String, TrackIdentification>,
+    /// Releases with their full tracklists; `None` if the lookup failed
+    releases: HashMap<String, Option<dto::Release>>,
+}
+
+impl Memo {
+    fn make_room(&mut self) {
+        if self.recordings.len() + self.releases.len() >= MEMO_LIMIT {
+            self.recordings.clear();
+            self.releases.clear();
+        }
+    }
+}
+
+/// MusicBrainz requests a run sent, and lookups answered without one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LookupStats {
+    pub requests: usize,
+    pub saved: usize,
 }
 
 /// User agent string - MusicBrainz requires this
@@ -32,30 +81,178 @@ impl MusicBrainzClient {
         Self {
             http_client,
             base_url: "https://musicbrainz.org/ws/2".to_string(),
+            memo: Mutex::default(),
+            requests: AtomicUsize::new(0),
+            saved: AtomicUsize::new(0),
         }
     }
 
-    /// Create a client for testing with custom base URL
-    #[cfg(test)]
+    /// Create a client for another server, such as a local fixture server
     pub fn with_base_url(base_url: impl Into<String>) -> Self {
-        let http_client = reqwest::Client::builder()
-            .user_agent(USER_AGENT)
-            .build()
-            .expect("Failed to build HTTP client");
-
         Self {
-            http_client,
             base_url: base_url.into(),
+            ..Self::new()
         }
     }
 
+    /// Requests sent and saved so far
+    pub fn stats(&self) -> LookupStats {
+        LookupStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            saved: self.saved.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Whether a lookup of the recording (on `release_id`, if given) would
+    /// be answered without a request
+    pub fn is_memoized(&self, recording_id: &str, release_id: Option<&str>) -> bool {
+        let memo = self.memo.lock().unwrap_or_else(|e| e.into_inner());
+        let recording = memo.recordings.get(recording_id).is_some_and(|hit| {
+            release_id.is_none_or(|id| hit.track.release_id.as_deref() == Some(id))
+        });
+        recording || release_id.is_some_and(|id| memo.releases.contains_key(id))
+    }
+
+    /// Whether [`Self::lookup_release`] would be answered without a request
+    pub fn has_release(&self, release_id: &str) -> bool {
+        let memo = self.memo.lock().unwrap_or_else(|e| e.into_inner());
+        memo.releases
+            .get(release_id)
+            .is_some_and(|release| release.is_some())
+    }
+
     /// Look up a recording by MusicBrainz ID and return enriched track info
     pub async fn lookup_recording(
         &self,
         recording_id: &str,
     ) -> Result<TrackIdentification, EnrichmentError> {
+        if let Some(hit) = self.memoized_recording(recording_id) {
+            self.saved.fetch_add(1, Ordering::Relaxed);
+            return Ok(hit);
+        }
         let response = self.send_recording_request(recording_id).await?;
-        Ok(adapter::to_identification(response))
+        let identification = adapter::to_identification(response);
+        self.remember(recording_id, &identification);
+        Ok(identification)
+    }
+
+    /// Look a recording up from the whole release it is on, fetching the
+    /// release the first time. `None` (look the recording up instead) if
+    /// the release can't be fetched or the recording isn't on it.
+    pub async fn lookup_recording_on(
+        &self,
+        recording_id: &str,
+        release_id: &str,
+    ) -> Option<TrackIdentification> {
+        if let Some(hit) = self.memoized_recording(recording_id)
+            && hit.track.release_id.as_deref() == Some(release_id)
+        {
+            self.saved.fetch_add(1, Ordering::Relaxed);
+            return Some(hit);
+        }
+        let fetched = self
+            .memo
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .releases
+            .contains_key(release_id);
+        if !fetched {
+            let release = match self.fetch_release(release_id).await {
+                Ok(release) => Some(release),
+                Err(e) => {
+                    tracing::debug!("MusicBrainz release lookup failed: {}", e);
+                    None
+                }
+            };
+            let mut memo = self.memo.lock().unwrap_or_else(|e| e.into_inner());
+            memo.make_room();
+            memo.releases.insert(release_id.to_string(), release);
+        }
+
+        let identification = {
+            let memo = self.memo.lock().unwrap_or_else(|e| e.into_inner());
+            let release = memo.releases.get(release_id)?.as_ref()?;
+            adapter::to_identification_on_release(release, recording_id)?
+        };
+        // The release lookup stands in for this recording's (when it was
+        // just fetched) or saves it outright
+        if fetched {
+            self.saved.fetch_add(1, Ordering::Relaxed);
+        }
+        self.remember(recording_id, &identification);
+        Some(identification)
+    }
+
+    /// Look up a release by MusicBrainz ID and return its full tracklist
+    pub async fn lookup_release(
+        &self,
+        release_id: &str,
+    ) -> Result<ReleaseTracklist, EnrichmentError> {
+        let cached = self
+            .memo
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .releases
+            .get(release_id)
+            .cloned()
+            .flatten();
+        let release = match cached {
+            Some(release) => {
+                self.saved.fetch_add(1, Ordering::Relaxed);
+                release
+            }
+            None => {
+                let release = self.fetch_release(release_id).await?;
+                let mut memo = self.memo.lock().unwrap_or_else(|e| e.into_inner());
+                memo.make_room();
+                memo.releases
+                    .insert(release_id.to_string(), Some(release.clone()));
+                release
+            }
+        };
+        Ok(adapter::to_tracklist(release))
+    }
+
+    /// Look up a ripped CD by its table of contents.
+    ///
+    /// Sends the disc ID along with the table of contents, so releases
+    /// with a slightly different disc are found when the ID isn't known.
+    pub async fn lookup_discid(&self, toc: &DiscToc) -> Result<Vec<DiscMatch>, EnrichmentError> {
+        let disc_id = toc.disc_id();
+        let url = format!(
+            "{}/discid/{}?toc={}&inc=recordings+artist-credits+release-groups&fmt=json",
+            self.base_url,
+            disc_id,
+            toc.toc_param()
+        );
+        let response: dto::DiscIdResponse = self.send_request(&url).await?;
+        Ok(adapter::to_disc_matches(
+            response,
+            &disc_id,
+            toc.offsets.len(),
+        ))
+    }
+
+    fn memoized_recording(&self, recording_id: &str) -> Option<TrackIdentification> {
+        let memo = self.memo.lock().unwrap_or_else(|e| e.into_inner());
+        memo.recordings.get(recording_id).cloned()
+    }
+
+    fn remember(&self, recording_id: &str, identification: &TrackIdentification) {
+        let mut memo = self.memo.lock().unwrap_or_else(|e| e.into_inner());
+        memo.make_room();
+        memo.recordings
+            .insert(recording_id.to_string(), identification.clone());
+    }
+
+    /// A release with its tracks, their artists and recordings, and the
+    /// works the recordings perform
+    async fn fetch_release(&self, release_id: &str) -> Result<dto::Release, EnrichmentError> {
+        let url = format!(
+            "{}/release/{}?fmt=json&inc=recordings+artist-credits+release-groups+tags+recording-level-rels+work-rels",
+            self.base_url, release_id
+        );
+        self.send_request(&url).await
     }
 
     /// Send the HTTP request and parse the response
@@ -64,13 +261,22 @@ impl MusicBrainzClient {
         recording_id: &str,
     ) -> Result<dto::RecordingResponse, EnrichmentError> {
         let url = format!(
-            "{}/recording/{}?fmt=json&inc=artists+releases+media+tags",
+            "{}/recording/{}?fmt=json&inc=artists+releases+release-groups+media+tags+work-rels",
             self.base_url, recording_id
         );
+        self.send_request(&url).await
+    }
 
+    /// GET a lookup URL and parse the JSON body, mapping HTTP failures
+    async fn send_request<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<T, EnrichmentError> {
+        http::guard(url)?;
+        self.requests.fetch_add(1, Ordering::Relaxed);
         let response = self
             .http_client
-            .get(&url)
+            .get(url)
             .send()
             .await
             .map_err(|e| EnrichmentError::Network(e.to_string()))?;
@@ -98,7 +304,7 @@ impl MusicBrainzClient {
         }
 
         response
-            .json::<dto::RecordingResponse>()
+            .json::<T>()
             .await
             .map_err(|e| EnrichmentError::Parse(e.to_string()))
     }
@@ -113,6 +319,7 @@ impl Default for MusicBrainzClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{FixtureResponse, FixtureServer};
 
     #[test]
     fn test_client_creation() {
@@ -130,4 +337,168 @@ mod tests {
     fn test_user_agent_format() {
         assert!(USER_AGENT.starts_with("MusicMinder/"));
     }
+
+    #[tokio::test]
+    async fn test_lookup_recorded_responses() {
+        let toc = DiscToc {
+            first_track: 1,
+            lead_out: 27536,
+            offsets: vec![150, 13808],
+        };
+        let discid = include_str!("../fixtures/musicbrainz_discid.json")
+            .replace("lwHl8fGzJyLXQR33ug60E8jhf4k-", &toc.disc_id());
+        let server = FixtureServer::start(vec![
+            (
+                "/recording/",
+                FixtureResponse::json(include_str!("../fixtures/musicbrainz_recording.json")),
+            ),
+            (
+                "/release/",
+                FixtureResponse::json(include_str!("../fixtures/musicbrainz_release.json")),
+            ),
+            ("/discid/", FixtureResponse::json(&discid)),
+        ])
+        .await;
+        let client = MusicBrainzClient::with_base_url(server.url());
+
+        let recording = client
+            .lookup_recording("5fb524f1-8cc8-4c04-a921-e34c0a911ea7")
+            .await
+            .unwrap();
+        let track = recording.track;
+        // The album, not the single released first
+        assert_eq!(track.album.as_deref(), Some("Nevermind"));
+        assert_eq!(track.release_type.as_deref(), Some("Album"));
+        assert_eq!(track.year, Some(1991));
+        assert_eq!(track.total_tracks, Some(13));
+        assert_eq!(track.genres, ["Grunge", "Alternative Rock"]);
+        assert_eq!(track.language.as_deref(), Some("eng"));
+
+        let tracklist = client.lookup_release("7f3c8e2d").await.unwrap();
+        assert_eq!(tracklist.tracks.len(), 3);
+        assert_eq!(tracklist.tracks[2].title, "Drain You");
+
+        let matches = client.lookup_discid(&toc).await.unwrap();
+        assert!(matches[0].exact);
+        assert_eq!(matches[0].tracks[1].title.as_deref(), Some("Dive"));
+
+        let requests = server.requests();
+        assert!(requests[0].ends_with("inc=artists+releases+release-groups+media+tags+work-rels"));
+        assert!(requests[2].contains(&format!("toc={}", toc.toc_param())));
+    }
+
+    #[tokio::test]
+    async fn test_lookups_are_memoized_and_coalesced() {
+        let server = FixtureServer::start(vec![
+            (
+                "/recording/",
+                FixtureResponse::json(include_str!("../fixtures/musicbrainz_recording.json")),
+            ),
+            (
+                "/release/",
+                FixtureResponse::json(include_str!("../fixtures/musicbrainz_release.json")),
+            ),
+        ])
+        .await;
+        let client = MusicBrainzClient::with_base_url(server.url());
+        let release_id = "7f3c8e2d-1a4b-4c5d-9e6f-0a1b2c3d4e5f";
+
+        // The same recording twice: one request
+        for _ in 0..2 {
+            client
+                .lookup_recording("5fb524f1-8cc8-4c04-a921-e34c0a911ea7")
+                .await
+                .unwrap();
+        }
+
+        // An album's tracks from a single release lookup
+        let school = client
+            .lookup_recording_on("3e4f5a6b-7c8d-4e9f-8a0b-1c2d3e4f5a6b", release_id)
+            .await
+            .unwrap()
+            .track;
+        assert_eq!(school.title.as_deref(), Some("School"));
+        assert_eq!(
+            school.album.as_deref(),
+            Some("From the Muddy Banks of the Wishkah")
+        );
+        assert_eq!(school.track_number, Some(2));
+        assert_eq!(school.total_tracks, Some(3));
+        assert_eq!(school.year, Some(1996));
+        assert_eq!(school.release_id.as_deref(), Some(release_id));
+        let drain_you = client
+            .lookup_recording_on("5a6b7c8d-9e0f-4a1b-8c2d-3e4f5a6b7c8d", release_id)
+            .await
+            .unwrap();
+        assert_eq!(drain_you.track.title.as_deref(), Some("Drain You"));
+        assert!(client.is_memoized("anything", Some(release_id)));
+
+        // Not on the release: looked up on its own instead
+        assert!(
+            client
+                .lookup_recording_on("0000", release_id)
+                .await
+                .is_none()
+        );
+        assert_eq!(
+            client
+                .lookup_release(release_id)
+                .await
+                .unwrap()
+                .tracks
+                .len(),
+            3
+        );
+
+        assert_eq!(
+            client.stats(),
+            LookupStats {
+                requests: 2,
+                saved: 3
+            }
+        );
+        assert!(server.requests()[1].ends_with(
+            "inc=recordings+artist-credits+release-groups+tags+recording-level-rels+work-rels"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_lookup_errors() {
+        let server = FixtureServer::start(vec![
+            (
+                "/bad/recording/",
+                FixtureResponse::status(400, include_str!("../fixtures/musicbrainz_error.json")),
+            ),
+            ("/busy/", FixtureResponse::status(429, "")),
+            (
+                "/down/",
+                FixtureResponse::status(502, "<html>Bad Gateway</html>"),
+            ),
+        ])
+        .await;
+        let lookup = |prefix: &str| {
+            let client = MusicBrainzClient::with_base_url(format!("{}{}", server.url(), prefix));
+            async move { client.lookup_recording("not-an-mbid").await.unwrap_err() }
+        };
+
+        assert!(
+            matches!(lookup("/bad").await, EnrichmentError::ApiError(m) if m == "Invalid mbid.")
+        );
+        assert!(matches!(
+            lookup("/busy").await,
+            EnrichmentError::RateLimited
+        ));
+        assert!(matches!(lookup("/down").await, EnrichmentError::Network(_)));
+        // Unrouted paths are 404s: nothing on MusicBrainz with that ID
+        assert!(matches!(lookup("/gone").await, EnrichmentError::NoMatches));
+    }
+
+    #[tokio::test]
+    async fn test_default_client_stays_offline_in_tests() {
+        let err = MusicBrainzClient::new()
+            .lookup_release("x")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EnrichmentError::Network(m) if m.starts_with("offline mode")));
+    }
 }
@@ -7,7 +7,9 @@
 //! API Reference: https://musicbrainz.org/doc/MusicBrainz_API
 //!
 //! We primarily use the /recording endpoint to look up recordings by MBID
-//! (obtained from AcoustID) and get full metadata.
+//! (obtained from AcoustID) and get full metadata. The /release endpoint
+//! (which returns a [`Release`]) supplies full tracklists, and the /discid
+//! endpoint ([`DiscIdResponse`]) the releases of a ripped CD.
 
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +34,9 @@ pub struct RecordingResponse {
     /// Tags/genres (when inc=tags is used)
     #[serde(default)]
     pub tags: Vec<Tag>,
+    /// Relationships (works performed, when inc=work-rels is used)
+    #[serde(default)]
+    pub relations: Vec<Relation>,
 }
 
 /// Artist credit (can be multiple for collaborations)
@@ -82,6 +87,9 @@ pub struct Release {
     /// Media (discs) in this release
     #[serde(default)]
     pub media: Vec<Medium>,
+    /// Tags/genres of the release (in release lookups with inc=tags)
+    #[serde(default)]
+    pub tags: Vec<Tag>,
 }
 
 /// Release group (e.g., "Abbey Road" across all editions)
@@ -94,6 +102,9 @@ pub struct ReleaseGroup {
     pub title: String,
     /// Primary type (Album, Single, EP, etc.)
     pub primary_type: Option<String>,
+    /// Secondary types (Compilation, Live, Soundtrack, etc.)
+    #[serde(default)]
+    pub secondary_types: Vec<String>,
     /// First release date
     pub first_release_date: Option<String>,
 }
@@ -111,6 +122,26 @@ pub struct Medium {
     /// Tracks on this medium
     #[serde(default)]
     pub tracks: Vec<Track>,
+    /// Disc IDs of CDs of this medium (only in disc ID lookups)
+    #[serde(default)]
+    pub discs: Vec<Disc>,
+}
+
+/// A CD pressing of a medium
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Disc {
+    /// MusicBrainz disc ID
+    pub id: String,
+    /// Length in sectors (1/75 s)
+    pub sectors: Option<u32>,
+}
+
+/// Disc ID lookup response: the releases with that disc, or with a similar
+/// table of contents when the ID itself is unknown
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiscIdResponse {
+    #[serde(default)]
+    pub releases: Vec<Release>,
 }
 
 /// Track on a medium
@@ -124,6 +155,28 @@ pub struct Track {
     pub title: Option<String>,
     /// Track length in milliseconds
     pub length: Option<u64>,
+    /// Recording on this track (only with `inc=recordings`)
+    #[serde(default)]
+    pub recording: Option<TrackRecording>,
+    /// Track artists (only with `inc=artist-credits`)
+    #[serde(default, rename = "artist-credit")]
+    pub artist_credit: Vec<ArtistCredit>,
+}
+
+/// Recording reference on a release track
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrackRecording {
+    /// MusicBrainz recording ID
+    pub id: String,
+    /// Recording title
+    pub title: Option<String>,
+    /// Duration in milliseconds
+    pub length: Option<u64>,
+    /// Disambiguation comment
+    pub disambiguation: Option<String>,
+    /// Relationships (with `inc=recording-level-rels+work-rels`)
+    #[serde(default)]
+    pub relations: Vec<Relation>,
 }
 
 /// Tag (genre) from MusicBrainz
@@ -135,6 +188,34 @@ pub struct Tag {
     pub count: i32,
 }
 
+/// Relationship from a recording to another entity
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Relation {
+    /// Relationship type ("performance" for the work recorded)
+    #[serde(rename = "type")]
+    pub relation_type: String,
+    /// Attributes ("instrumental", "live", "cover", ...)
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    /// The work (for relationships to works)
+    pub work: Option<Work>,
+}
+
+/// Work (the composition a recording performs)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Work {
+    /// MusicBrainz work ID
+    pub id: String,
+    /// Work title
+    pub title: String,
+    /// Lyrics language (ISO 639-3; "zxx" = no lyrics, "mul" = several)
+    pub language: Option<String>,
+    /// Every lyrics language
+    #[serde(default)]
+    pub languages: Vec<String>,
+}
+
 /// Error response from MusicBrainz API
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ApiError {
@@ -152,6 +233,41 @@ pub struct ApiError {
 mod contract_tests {
     use super::*;
 
+    /// Test parsing the recorded lookups in `fixtures/`
+    #[test]
+    fn test_parse_recorded_responses() {
+        let recording: RecordingResponse =
+            serde_json::from_str(include_str!("../fixtures/musicbrainz_recording.json"))
+                .expect("Should parse recorded recording");
+        assert_eq!(recording.length, Some(301920));
+        assert_eq!(
+            recording.artist_credit[0].artist.sort_name.as_deref(),
+            Some("Nirvana")
+        );
+        let group = recording.releases[1].release_group.as_ref().unwrap();
+        assert_eq!(group.primary_type.as_deref(), Some("Album"));
+        assert_eq!(recording.releases[1].media[0].track_count, Some(13));
+        assert_eq!(recording.tags.len(), 3);
+        let work = recording.relations[0].work.as_ref().unwrap();
+        assert_eq!(work.language.as_deref(), Some("eng"));
+
+        let release: Release =
+            serde_json::from_str(include_str!("../fixtures/musicbrainz_release.json"))
+                .expect("Should parse recorded release");
+        assert_eq!(release.media[0].tracks.len(), 3);
+        assert!(release.media[0].tracks[2].title.is_none());
+
+        let discid: DiscIdResponse =
+            serde_json::from_str(include_str!("../fixtures/musicbrainz_discid.json"))
+                .expect("Should parse recorded disc ID lookup");
+        assert_eq!(discid.releases[0].media[0].discs[0].sectors, Some(27536));
+
+        let error: ApiError =
+            serde_json::from_str(include_str!("../fixtures/musicbrainz_error.json"))
+                .expect("Should parse recorded error");
+        assert_eq!(error.error, "Invalid mbid.");
+    }
+
     /// Test parsing a minimal recording response
     #[test]
     fn test_parse_minimal_recording() {
@@ -280,6 +396,72 @@ mod contract_tests {
         assert_eq!(recording.artist_credit[1].artist.name, "David Bowie");
     }
 
+    /// Test parsing a release lookup with recordings
+    #[test]
+    fn test_parse_release_tracklist() {
+        let json = r#"{
+            "id": "rel-okc",
+            "title": "OK Computer",
+            "media": [{
+                "position": 1,
+                "format": "CD",
+                "track-count": 2,
+                "tracks": [
+                    {
+                        "position": 1,
+                        "number": "1",
+                        "title": "Airbag",
+                        "length": 284000,
+                        "recording": {"id": "rec-airbag", "title": "Airbag"}
+                    },
+                    {"position": 2, "number": "2", "title": "Paranoid Android"}
+                ]
+            }]
+        }"#;
+
+        let release: Release = serde_json::from_str(json).expect("Should parse release");
+
+        let tracks = &release.media[0].tracks;
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(
+            tracks[0].recording.as_ref().map(|r| r.id.as_str()),
+            Some("rec-airbag")
+        );
+        assert!(tracks[1].recording.is_none());
+    }
+
+    /// Test parsing a disc ID lookup
+    #[test]
+    fn test_parse_discid_response() {
+        let json = r#"{
+            "id": "I5l9cCSFccLKFEKS.7wqSZAorPU-",
+            "sectors": 267257,
+            "offset-count": 12,
+            "releases": [{
+                "id": "rel-1",
+                "title": "Some Album",
+                "artist-credit": [{"artist": {"id": "art-1", "name": "Band"}, "name": "Band"}],
+                "media": [{
+                    "position": 1,
+                    "track-count": 1,
+                    "discs": [{"id": "I5l9cCSFccLKFEKS.7wqSZAorPU-", "sectors": 267257}],
+                    "tracks": [{
+                        "position": 1,
+                        "title": "Intro",
+                        "artist-credit": [{"artist": {"id": "art-2", "name": "Guest"}}],
+                        "recording": {"id": "rec-1", "title": "Intro"}
+                    }]
+                }]
+            }]
+        }"#;
+
+        let response: DiscIdResponse = serde_json::from_str(json).expect("Should parse disc ID");
+
+        let medium = &response.releases[0].media[0];
+        assert_eq!(medium.discs[0].id, "I5l9cCSFccLKFEKS.7wqSZAorPU-");
+        assert_eq!(medium.tracks[0].artist_credit[0].artist.name, "Guest");
+    }
+
     /// Test parsing error response
     #[test]
     fn test_parse_error_response() {
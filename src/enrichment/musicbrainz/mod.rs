@@ -1,13 +1,16 @@
 //! MusicBrainz API integration
 //!
 //! Provides detailed metadata enrichment by looking up recordings from MusicBrainz.
-//! Typically used after AcoustID identifies a recording by its MusicBrainz ID.
+//! Typically used after AcoustID identifies a recording by its MusicBrainz ID,
+//! or with the disc ID of a ripped CD.
 //!
 //! API docs: https://musicbrainz.org/doc/MusicBrainz_API
 
 mod adapter;
+#[cfg(feature = "enrichment")]
 mod client;
 pub mod dto;
 
-pub use adapter::to_identification;
-pub use client::MusicBrainzClient;
+pub use adapter::{to_disc_matches, to_identification, to_identification_on_release, to_tracklist};
+#[cfg(feature = "enrichment")]
+pub use client::{LookupStats, MusicBrainzClient};
@@ -0,0 +1,516 @@
+//! Enrichment reports.
+//!
+//! A report records what an enrichment run did to each file: the match and
+//! its confidence, the release chosen, every field's original and new value,
+//! the fields left alone, and errors. It is saved as JSON, CSV or HTML
+//! (chosen from the file extension), each starting with a summary.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::domain::{IdentifiedTrack, TrackIdentification};
+use crate::metadata::{FieldChange, WritePreview};
+use crate::plan::push_csv_row;
+
+/// Errors that can occur when saving a report.
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// File format of a saved report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    Csv,
+    Html,
+}
+
+impl ReportFormat {
+    /// From a file's extension; JSON unless it ends in .csv, .html or .htm
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("csv") => Self::Csv,
+            Some("html" | "htm") => Self::Html,
+            _ => Self::Json,
+        }
+    }
+}
+
+/// What happened to one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileOutcome {
+    Identified,
+    NoMatch,
+    Error,
+    /// Not identified yet (the run was cancelled)
+    Pending,
+}
+
+impl FileOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Identified => "identified",
+            Self::NoMatch => "no_match",
+            Self::Error => "error",
+            Self::Pending => "pending",
+        }
+    }
+}
+
+/// The release a match was tagged from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReleaseChoice {
+    pub release_id: Option<String>,
+    pub recording_id: Option<String>,
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+    pub year: Option<i32>,
+}
+
+impl ReleaseChoice {
+    fn of(track: &IdentifiedTrack) -> Self {
+        Self {
+            release_id: track.release_id.clone(),
+            recording_id: track.recording_id.clone(),
+            album: track.album.clone(),
+            album_artist: track.album_artist.clone().or_else(|| track.artist.clone()),
+            year: track.year,
+        }
+    }
+
+    /// "Album - Artist (Year)"
+    fn label(&self) -> String {
+        let mut label = self.album.clone().unwrap_or_else(|| "?".to_string());
+        if let Some(ref artist) = self.album_artist {
+            let _ = write!(label, " - {}", artist);
+        }
+        if let Some(year) = self.year {
+            let _ = write!(label, " ({})", year);
+        }
+        label
+    }
+}
+
+/// One file's entry in a report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub outcome: FileOutcome,
+    /// Match confidence (0.0 - 1.0)
+    pub confidence: Option<f32>,
+    /// How the match was found ("fingerprint", "disc ID")
+    pub matched_by: Option<String>,
+    pub release: Option<ReleaseChoice>,
+    /// Original and new value of each field changed (or to be changed)
+    pub changes: Vec<FieldChange>,
+    /// Fields left alone because they already had a value
+    pub skipped: Vec<String>,
+    /// Whether the changes were written to the file
+    pub written: bool,
+    pub error: Option<String>,
+}
+
+impl FileReport {
+    /// A matched file, with the changes previewed for it (if any)
+    pub fn identified(
+        path: impl Into<PathBuf>,
+        identification: &TrackIdentification,
+        matched_by: &str,
+        preview: Option<WritePreview>,
+    ) -> Self {
+        let (changes, skipped) = preview.map(|p| (p.changes, p.skipped)).unwrap_or_default();
+        Self {
+            path: path.into(),
+            outcome: FileOutcome::Identified,
+            confidence: Some(identification.score),
+            matched_by: Some(matched_by.to_string()),
+            release: Some(ReleaseChoice::of(&identification.track)),
+            changes,
+            skipped,
+            written: false,
+            error: None,
+        }
+    }
+
+    pub fn no_match(path: impl Into<PathBuf>) -> Self {
+        Self::unmatched(path.into(), FileOutcome::NoMatch, None)
+    }
+
+    pub fn error(path: impl Into<PathBuf>, error: impl Into<String>) -> Self {
+        Self::unmatched(path.into(), FileOutcome::Error, Some(error.into()))
+    }
+
+    pub fn pending(path: impl Into<PathBuf>) -> Self {
+        Self::unmatched(path.into(), FileOutcome::Pending, None)
+    }
+
+    fn unmatched(path: PathBuf, outcome: FileOutcome, error: Option<String>) -> Self {
+        Self {
+            path,
+            outcome,
+            confidence: None,
+            matched_by: None,
+            release: None,
+            changes: Vec::new(),
+            skipped: Vec::new(),
+            written: false,
+            error,
+        }
+    }
+
+    /// Mark the changes written, or record why writing failed
+    pub fn with_write_result(mut self, result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => self.written = true,
+            Err(e) => self.error = Some(format!("Write failed: {}", e)),
+        }
+        self
+    }
+}
+
+/// Totals at the top of a report
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReportSummary {
+    pub files: usize,
+    pub identified: usize,
+    pub no_match: usize,
+    pub errors: usize,
+    pub pending: usize,
+    /// Files whose changes were written
+    pub written: usize,
+    /// Field changes across all files
+    pub fields_changed: usize,
+    pub fields_skipped: usize,
+    /// Mean confidence of the matches
+    pub average_confidence: Option<f32>,
+}
+
+impl ReportSummary {
+    fn of(files: &[FileReport]) -> Self {
+        let count = |outcome| files.iter().filter(|f| f.outcome == outcome).count();
+        let confidences: Vec<f32> = files.iter().filter_map(|f| f.confidence).collect();
+        Self {
+            files: files.len(),
+            identified: count(FileOutcome::Identified),
+            no_match: count(FileOutcome::NoMatch),
+            errors: files.iter().filter(|f| f.error.is_some()).count(),
+            pending: count(FileOutcome::Pending),
+            written: files.iter().filter(|f| f.written).count(),
+            fields_changed: files.iter().map(|f| f.changes.len()).sum(),
+            fields_skipped: files.iter().map(|f| f.skipped.len()).sum(),
+            average_confidence: (!confidences.is_empty())
+                .then(|| confidences.iter().sum::<f32>() / confidences.len() as f32),
+        }
+    }
+}
+
+/// A whole run's report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnrichmentReport {
+    /// RFC 3339 timestamp
+    pub generated_at: String,
+    /// Whether only empty fields were filled
+    pub fill_only: bool,
+    pub summary: ReportSummary,
+    pub files: Vec<FileReport>,
+}
+
+impl EnrichmentReport {
+    pub fn new(files: Vec<FileReport>, fill_only: bool) -> Self {
+        Self {
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            fill_only,
+            summary: ReportSummary::of(&files),
+            files,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, ReportError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// One row per field (changed or skipped), and one for each file with
+    /// neither. The summary comes first as `#` comment lines.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        for line in self.summary_lines() {
+            let _ = writeln!(out, "# {}", line);
+        }
+        out.push_str(
+            "path,outcome,confidence,release_id,release,field,original_value,new_value,action,error\n",
+        );
+        for file in &self.files {
+            let path = file.path.to_string_lossy();
+            let confidence = file
+                .confidence
+                .map(|c| format!("{:.2}", c))
+                .unwrap_or_default();
+            let release_id = file
+                .release
+                .as_ref()
+                .and_then(|r| r.release_id.clone())
+                .unwrap_or_default();
+            let release = file
+                .release
+                .as_ref()
+                .map(ReleaseChoice::label)
+                .unwrap_or_default();
+            let error = file.error.as_deref().unwrap_or("");
+            let action = if file.written { "written" } else { "planned" };
+            let row = |out: &mut String, field: &str, original: &str, new: &str, action: &str| {
+                push_csv_row(
+                    out,
+                    &[
+                        &path,
+                        file.outcome.as_str(),
+                        &confidence,
+                        &release_id,
+                        &release,
+                        field,
+                        original,
+                        new,
+                        action,
+                        error,
+                    ],
+                );
+            };
+            for change in &file.changes {
+                row(
+                    &mut out,
+                    &change.field,
+                    &change.current_value,
+                    &change.new_value,
+                    action,
+                );
+            }
+            for field in &file.skipped {
+                row(&mut out, field, "", "", "skipped");
+            }
+            if file.changes.is_empty() && file.skipped.is_empty() {
+                row(&mut out, "", "", "", "");
+            }
+        }
+        out
+    }
+
+    /// A standalone page: the summary, then a table per file
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Music Minder Enrichment Report</title>\n<style>\n\
+             body { font-family: sans-serif; margin: 2em; color: #222; }\n\
+             table { border-collapse: collapse; margin-bottom: 1.5em; }\n\
+             th, td { border: 1px solid #ccc; padding: 0.25em 0.6em; text-align: left; }\n\
+             th { background: #f0f0f0; }\n\
+             .identified { color: #2a7a2a; } .no_match { color: #a06a00; }\n\
+             .error, .pending { color: #b02020; } .muted { color: #888; }\n\
+             </style>\n</head>\n<body>\n<h1>Music Minder Enrichment Report</h1>\n<ul>\n",
+        );
+        for line in self.summary_lines() {
+            let _ = writeln!(out, "<li>{}</li>", escape_html(&line));
+        }
+        out.push_str("</ul>\n");
+
+        for file in &self.files {
+            let _ = writeln!(
+                out,
+                "<h3 class=\"{}\">{}</h3>",
+                file.outcome.as_str(),
+                escape_html(&file.path.to_string_lossy())
+            );
+            let mut details = vec![file.outcome.as_str().replace('_', " ")];
+            if let Some(confidence) = file.confidence {
+                details.push(format!("{:.0}% confidence", confidence * 100.0));
+            }
+            if let Some(ref matched_by) = file.matched_by {
+                details.push(format!("by {}", matched_by));
+            }
+            if let Some(ref release) = file.release {
+                details.push(release.label());
+                if let Some(ref id) = release.release_id {
+                    details.push(format!("release {}", id));
+                }
+            }
+            let _ = writeln!(out, "<p>{}</p>", escape_html(&details.join(" · ")));
+            if let Some(ref error) = file.error {
+                let _ = writeln!(out, "<p class=\"error\">{}</p>", escape_html(error));
+            }
+            if file.changes.is_empty() && file.skipped.is_empty() {
+                continue;
+            }
+            out.push_str("<table>\n<tr><th>Field</th><th>Original</th><th>New</th></tr>\n");
+            for change in &file.changes {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    escape_html(&change.field),
+                    escape_html(&change.current_value),
+                    escape_html(&change.new_value)
+                );
+            }
+            for field in &file.skipped {
+                let _ = writeln!(
+                    out,
+                    "<tr class=\"muted\"><td>{}</td><td colspan=\"2\">kept (already set)</td></tr>",
+                    escape_html(field)
+                );
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Save the report, choosing the format from the file extension
+    pub fn save(&self, path: &Path) -> Result<(), ReportError> {
+        let contents = match ReportFormat::from_path(path) {
+            ReportFormat::Json => self.to_json()?,
+            ReportFormat::Csv => self.to_csv(),
+            ReportFormat::Html => self.to_html(),
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    fn summary_lines(&self) -> Vec<String> {
+        let s = &self.summary;
+        let mut lines = vec![
+            format!("Generated {}", self.generated_at),
+            format!(
+                "{} files: {} identified, {} no match, {} errors",
+                s.files, s.identified, s.no_match, s.errors
+            ),
+            format!(
+                "{} fields changed in {} files written, {} skipped{}",
+                s.fields_changed,
+                s.written,
+                s.fields_skipped,
+                if self.fill_only { " (fill only)" } else { "" }
+            ),
+        ];
+        if s.pending > 0 {
+            lines.push(format!("{} files not identified yet", s.pending));
+        }
+        if let Some(confidence) = s.average_confidence {
+            lines.push(format!("Average confidence {:.0}%", confidence * 100.0));
+        }
+        lines
+    }
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enrichment::EnrichmentSource;
+
+    fn report() -> EnrichmentReport {
+        let identification = TrackIdentification {
+            score: 0.9,
+            track: IdentifiedTrack {
+                title: Some("Song".to_string()),
+                artist: Some("Band".to_string()),
+                album: Some("Rock & Roll".to_string()),
+                release_id: Some("rel-1".to_string()),
+                year: Some(1999),
+                ..Default::default()
+            },
+            source: EnrichmentSource::AcoustId,
+            musicbrainz_fields: Vec::new(),
+            scores: Default::default(),
+        };
+        let preview = WritePreview {
+            changes: vec![FieldChange {
+                field: "album".to_string(),
+                current_value: "Unknown Album".to_string(),
+                new_value: "Rock & Roll".to_string(),
+            }],
+            skipped: vec!["title".to_string()],
+        };
+        EnrichmentReport::new(
+            vec![
+                FileReport::identified(
+                    "/music/a, b.flac",
+                    &identification,
+                    "fingerprint",
+                    Some(preview),
+                )
+                .with_write_result(Ok(())),
+                FileReport::no_match("/music/c.mp3"),
+                FileReport::error("/music/d.mp3", "fpcalc failed"),
+            ],
+            true,
+        )
+    }
+
+    #[test]
+    fn test_summary() {
+        let summary = report().summary;
+        assert_eq!(summary.files, 3);
+        assert_eq!(summary.identified, 1);
+        assert_eq!(summary.no_match, 1);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.written, 1);
+        assert_eq!(summary.fields_changed, 1);
+        assert_eq!(summary.fields_skipped, 1);
+        assert_eq!(summary.average_confidence, Some(0.9));
+    }
+
+    #[test]
+    fn test_formats() {
+        let report = report();
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["summary"]["identified"], 1);
+        assert_eq!(
+            json["files"][0]["changes"][0]["current_value"],
+            "Unknown Album"
+        );
+        assert_eq!(json["files"][1]["outcome"], "no_match");
+
+        let csv = report.to_csv();
+        assert!(csv.starts_with("# Generated "));
+        assert!(csv.contains(
+            "\"/music/a, b.flac\",identified,0.90,rel-1,Rock & Roll - Band (1999),album,Unknown Album,Rock & Roll,written,\n"
+        ));
+        assert!(csv.contains(",title,,,skipped,"));
+        assert!(csv.contains("/music/d.mp3,error,,,,,,,,fpcalc failed\n"));
+
+        let html = report.to_html();
+        assert!(html.contains("<td>Rock &amp; Roll</td>"));
+        assert!(html.contains("fpcalc failed"));
+
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.HTM")),
+            ReportFormat::Html
+        );
+        assert_eq!(ReportFormat::from_path(Path::new("r")), ReportFormat::Json);
+    }
+}
@@ -1,21 +1,49 @@
 //! Enrichment service - orchestrates track identification and metadata lookup
 //!
 //! This is the high-level API for enriching tracks:
-//! 1. Generate audio fingerprint (via fpcalc)
+//! 0. For whole ripped CDs, look the disc ID up instead ([`EnrichmentService::identify_discs`]),
+//!    and identify full-album folders as one release ([`EnrichmentService::identify_albums`])
+//! 1. Generate audio fingerprint (via fpcalc), unless the file's tags carry one
 //! 2. Look up fingerprint on AcoustID (returns MusicBrainz IDs)
 //! 3. Fetch detailed metadata from MusicBrainz
 //! 4. Optionally fetch cover art
+//!
+//! A service is meant to last a run: once two tracks of a folder were
+//! tagged from the same release, the folder's other tracks are read from
+//! that release's lookup (see [`crate::enrichment::musicbrainz`]).
 
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::time::Duration;
 
 use crate::enrichment::{
     acoustid::AcoustIdClient,
+    budget::CpuBudget,
+    cluster::{self, Assignment, ClusterFile, GroupVote},
     coverart::{CoverArt, CoverArtClient, CoverSize},
-    domain::{EnrichmentError, TrackIdentification},
+    discid,
+    domain::{AudioFingerprint, EnrichmentError, TrackIdentification},
     fingerprint,
-    musicbrainz::MusicBrainzClient,
+    musicbrainz::{LookupStats, MusicBrainzClient},
+    similarity::{self, MatchScores},
 };
+use crate::error::Retry;
+
+/// Tracks of a folder tagged from one release before the rest of the folder
+/// is read from that release's lookup
+const COALESCE_AFTER: u32 = 2;
+
+/// Recordings of the winning release group looked up to find its releases
+const MAX_RELEASE_LOOKUPS: usize = 3;
+
+/// Pause before a MusicBrainz request (it allows one per second)
+const MUSICBRAINZ_INTERVAL: Duration = Duration::from_millis(1100);
+
+/// Held across a track's AcoustID and MusicBrainz lookups, so tracks
+/// fingerprinted in parallel still query the services one at a time and
+/// the rate-limit delays below hold
+static LOOKUPS: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
 
 /// Configuration for the enrichment service
 pub struct EnrichmentConfig {
@@ -27,6 +55,33 @@ pub struct EnrichmentConfig {
     pub use_musicbrainz: bool,
     /// Preferred cover art size
     pub cover_size: CoverSize,
+    /// Where the services are reached
+    pub endpoints: Endpoints,
+}
+
+/// Servers standing in for AcoustID, MusicBrainz and the Cover Art Archive;
+/// `None` for the services themselves
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Endpoints {
+    /// The lookup URL (`.../v2/lookup`)
+    pub acoustid: Option<String>,
+    /// The web service root (`.../ws/2`)
+    pub musicbrainz: Option<String>,
+    pub coverart: Option<String>,
+}
+
+impl Endpoints {
+    /// All three on one server, e.g. a `test_utils::FixtureServer`: AcoustID
+    /// under `/v2/lookup`, MusicBrainz under `/ws/2` and the Cover Art
+    /// Archive under `/coverart`
+    #[cfg(test)]
+    pub fn all_at(base: &str) -> Self {
+        Self {
+            acoustid: Some(format!("{}/v2/lookup", base)),
+            musicbrainz: Some(format!("{}/ws/2", base)),
+            coverart: Some(format!("{}/coverart", base)),
+        }
+    }
 }
 
 impl Default for EnrichmentConfig {
@@ -36,6 +91,7 @@ impl Default for EnrichmentConfig {
             min_confidence: 0.8,
             use_musicbrainz: true,
             cover_size: CoverSize::Medium,
+            endpoints: Endpoints::default(),
         }
     }
 }
@@ -46,19 +102,40 @@ pub struct EnrichmentService {
     acoustid: AcoustIdClient,
     musicbrainz: MusicBrainzClient,
     coverart: CoverArtClient,
+    /// Per folder, how many tracks were tagged from each release
+    folder_releases: Mutex<HashMap<PathBuf, HashMap<String, u32>>>,
+    /// AcoustID results of files album clustering left out, so identifying
+    /// them on their own doesn't fingerprint them again
+    candidates: Mutex<HashMap<PathBuf, Vec<TrackIdentification>>>,
 }
 
 impl EnrichmentService {
     /// Create a new enrichment service with the given config
     pub fn new(config: EnrichmentConfig) -> Self {
         Self {
-            acoustid: AcoustIdClient::new(&config.acoustid_api_key),
-            musicbrainz: MusicBrainzClient::new(),
-            coverart: CoverArtClient::new(),
+            acoustid: match &config.endpoints.acoustid {
+                Some(url) => AcoustIdClient::with_base_url(&config.acoustid_api_key, url),
+                None => AcoustIdClient::new(&config.acoustid_api_key),
+            },
+            musicbrainz: match &config.endpoints.musicbrainz {
+                Some(url) => MusicBrainzClient::with_base_url(url),
+                None => MusicBrainzClient::new(),
+            },
+            coverart: match &config.endpoints.coverart {
+                Some(url) => CoverArtClient::with_base_url(url),
+                None => CoverArtClient::new(),
+            },
+            folder_releases: Mutex::default(),
+            candidates: Mutex::default(),
             config,
         }
     }
 
+    /// MusicBrainz requests sent so far, and lookups answered without one
+    pub fn lookup_stats(&self) -> LookupStats {
+        self.musicbrainz.stats()
+    }
+
     /// Check if fingerprinting is available (fpcalc installed)
     pub fn is_fingerprinting_available(&self) -> bool {
         fingerprint::is_fpcalc_available()
@@ -69,6 +146,225 @@ impl EnrichmentService {
         fingerprint::get_fpcalc_version()
     }
 
+    /// Identify whole ripped CDs among `files` by disc ID, the first strategy
+    /// for album-level enrichment. One lookup per disc gives exact release
+    /// matches; files it leaves out need [`Self::identify_track`].
+    pub async fn identify_discs(&self, files: &[PathBuf]) -> HashMap<PathBuf, TrackIdentification> {
+        if !self.config.use_musicbrainz {
+            return HashMap::new();
+        }
+        let _lookups = LOOKUPS.lock().await;
+        discid::identify_discs(&self.musicbrainz, files).await
+    }
+
+    /// Identify full-album folders among `files` as one release each, the
+    /// second strategy for album-level enrichment: the files of a folder
+    /// vote on the release group their AcoustID candidates share, and the
+    /// group's release their lengths line up with tags them all (see
+    /// [`cluster`]). Files it leaves out need [`Self::identify_track`],
+    /// which reuses their AcoustID lookups.
+    pub async fn identify_albums(
+        &self,
+        files: &[PathBuf],
+    ) -> HashMap<PathBuf, TrackIdentification> {
+        let mut identified = HashMap::new();
+        if !self.config.use_musicbrainz {
+            return identified;
+        }
+        let mut folders: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for file in files {
+            let folder = file.parent().map(Path::to_path_buf).unwrap_or_default();
+            folders.entry(folder).or_default().push(file.clone());
+        }
+
+        for (folder, paths) in folders {
+            if paths.len() < cluster::MIN_FILES {
+                continue;
+            }
+            let mut cluster_files = self.cluster_files(&paths).await;
+            cluster::order(&mut cluster_files);
+            let Some(vote) = cluster::vote(&cluster_files) else {
+                continue;
+            };
+            let _lookups = LOOKUPS.lock().await;
+            let Some((release_id, assigned)) = self.album_release(&vote, &cluster_files).await
+            else {
+                continue;
+            };
+            tracing::info!(
+                "{}: {} of {} files identified as release {}",
+                folder.display(),
+                assigned.len(),
+                cluster_files.len(),
+                release_id
+            );
+            // The rest of the folder is looked up on the same release
+            self.folder_releases
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .entry(folder)
+                .or_default()
+                .insert(release_id.clone(), COALESCE_AFTER);
+
+            for assignment in assigned {
+                let Some(on_release) = self
+                    .musicbrainz
+                    .lookup_recording_on(&assignment.recording_id, &release_id)
+                    .await
+                else {
+                    continue;
+                };
+                let fingerprinted = cluster_files
+                    .iter()
+                    .find(|f| f.path == assignment.path)
+                    .and_then(|f| {
+                        f.candidates.iter().find(|c| {
+                            c.track.recording_id.as_deref() == Some(&assignment.recording_id)
+                                && c.track.release_group_id.as_deref()
+                                    == Some(&vote.release_group_id)
+                        })
+                    });
+                let mut identification = match fingerprinted {
+                    Some(candidate) if !assignment.by_position => {
+                        let mut identification = candidate.clone();
+                        identification.merge_musicbrainz(&on_release.track);
+                        identification
+                    }
+                    _ => on_release,
+                };
+                identification.score = assignment.score;
+                if let Ok(meta) = crate::metadata::read(&assignment.path) {
+                    identification.score_against(&meta);
+                }
+                self.candidates
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(&assignment.path);
+                identified.insert(assignment.path, identification);
+            }
+        }
+        identified
+    }
+
+    /// Fingerprint a folder's files (in parallel, within the CPU budget)
+    /// and look them up on AcoustID, keeping the results for
+    /// [`Self::acoustid_lookup`]
+    async fn cluster_files(&self, paths: &[PathBuf]) -> Vec<ClusterFile> {
+        let fingerprints =
+            futures::future::join_all(paths.iter().map(|path| fingerprint_of(path))).await;
+
+        let mut files = Vec::with_capacity(paths.len());
+        for (path, fingerprint) in paths.iter().zip(fingerprints) {
+            let position = crate::metadata::read_full(path)
+                .ok()
+                .and_then(|meta| Some((meta.disc_number.unwrap_or(1), meta.track_number?)));
+            let mut file = ClusterFile {
+                path: path.clone(),
+                length: None,
+                position,
+                candidates: Vec::new(),
+            };
+            if let Ok(fp) = fingerprint {
+                file.length = Some(Duration::from_secs(fp.duration_secs.into()));
+                let _lookups = LOOKUPS.lock().await;
+                let found = match self.acoustid.lookup(&fp).await {
+                    Ok(found) => Some(found),
+                    Err(EnrichmentError::NoMatches) => Some(Vec::new()),
+                    Err(e) => {
+                        tracing::debug!("AcoustID lookup failed: {}", e);
+                        None
+                    }
+                };
+                if let Some(mut found) = found {
+                    with_fingerprint(&mut found, &fp);
+                    file.candidates = found
+                        .iter()
+                        .filter(|id| id.score >= self.config.min_confidence)
+                        .cloned()
+                        .collect();
+                    self.candidates
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(path.clone(), found);
+                }
+            }
+            files.push(file);
+        }
+        files
+    }
+
+    /// The release of the voted group the folder's files line up with best,
+    /// and how they're tagged from it; `None` unless most of them are
+    async fn album_release(
+        &self,
+        vote: &GroupVote,
+        files: &[ClusterFile],
+    ) -> Option<(String, Vec<Assignment>)> {
+        let mut tried = HashSet::new();
+        let mut best: Option<(String, Vec<Assignment>)> = None;
+        for recording_id in vote.recordings.iter().take(MAX_RELEASE_LOOKUPS) {
+            if !self.musicbrainz.is_memoized(recording_id, None) {
+                tokio::time::sleep(MUSICBRAINZ_INTERVAL).await;
+            }
+            let Ok(found) = self.musicbrainz.lookup_recording(recording_id).await else {
+                continue;
+            };
+            if found.track.release_group_id.as_deref() != Some(&vote.release_group_id) {
+                continue;
+            }
+            let Some(release_id) = found.track.release_id else {
+                continue;
+            };
+            if !tried.insert(release_id.clone()) {
+                continue;
+            }
+            if !self.musicbrainz.has_release(&release_id) {
+                tokio::time::sleep(MUSICBRAINZ_INTERVAL).await;
+            }
+            let Ok(tracklist) = self.musicbrainz.lookup_release(&release_id).await else {
+                continue;
+            };
+            let assigned = cluster::assign(files, &tracklist);
+            if best.as_ref().is_none_or(|(_, b)| assigned.len() > b.len()) {
+                best = Some((release_id, assigned));
+            }
+            if best.as_ref().is_some_and(|(_, b)| b.len() == files.len()) {
+                break;
+            }
+        }
+        best.filter(|(_, assigned)| cluster::accepted(assigned, files.len()))
+    }
+
+    /// A track's AcoustID results: kept from album clustering, or looked
+    /// up now from its fingerprint. Comes with the lookup lock, for the
+    /// MusicBrainz lookups that follow.
+    async fn acoustid_lookup(
+        &self,
+        path: &Path,
+    ) -> Result<
+        (
+            Vec<TrackIdentification>,
+            tokio::sync::MutexGuard<'static, ()>,
+        ),
+        EnrichmentError,
+    > {
+        let kept = self
+            .candidates
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(path);
+        if let Some(identifications) = kept {
+            return Ok((identifications, LOOKUPS.lock().await));
+        }
+        let fp = fingerprint_of(path).await?;
+        let lookups = LOOKUPS.lock().await;
+        let mut identifications = Retry::NETWORK
+            .run_async(|| self.acoustid.lookup(&fp))
+            .await?;
+        with_fingerprint(&mut identifications, &fp);
+        Ok((identifications, lookups))
+    }
+
     /// Identify a track by its audio fingerprint
     ///
     /// Returns the best match with confidence >= min_confidence, or NoMatches error.
@@ -77,11 +373,8 @@ impl EnrichmentService {
         &self,
         path: &Path,
     ) -> Result<TrackIdentification, EnrichmentError> {
-        // Step 1: Generate fingerprint
-        let fp = fingerprint::generate_fingerprint(path)?;
-
-        // Step 2: Look up on AcoustID
-        let identifications = self.acoustid.lookup(&fp).await?;
+        // Steps 1-2: Fingerprint and look up on AcoustID
+        let (identifications, _lookups) = self.acoustid_lookup(path).await?;
 
         // Step 3: Read existing metadata from file for matching hints
         let existing_meta = crate::metadata::read(path).ok();
@@ -104,15 +397,12 @@ impl EnrichmentService {
 
         // Step 4: Optionally enrich with MusicBrainz
         if self.config.use_musicbrainz
-            && let Some(ref recording_id) = identification.track.recording_id
+            && let Some(recording_id) = identification.track.recording_id.clone()
         {
-            // Add a small delay to respect MusicBrainz rate limits (1 req/sec)
-            tokio::time::sleep(Duration::from_millis(1100)).await;
-
-            match self.musicbrainz.lookup_recording(recording_id).await {
+            match self.lookup_musicbrainz(path, &recording_id).await {
                 Ok(mb_result) => {
                     // Merge MusicBrainz data into our identification
-                    identification.track.merge(&mb_result.track);
+                    identification.merge_musicbrainz(&mb_result.track);
                 }
                 Err(e) => {
                     // Log but don't fail - AcoustID data is still useful
@@ -121,6 +411,9 @@ impl EnrichmentService {
             }
         }
 
+        if let Some(meta) = &existing_meta {
+            identification.score_against(meta);
+        }
         Ok(identification)
     }
 
@@ -135,11 +428,8 @@ impl EnrichmentService {
         &self,
         path: &Path,
     ) -> Result<(TrackIdentification, Vec<TrackIdentification>), EnrichmentError> {
-        // Step 1: Generate fingerprint
-        let fp = fingerprint::generate_fingerprint(path)?;
-
-        // Step 2: Look up on AcoustID
-        let identifications = self.acoustid.lookup(&fp).await?;
+        // Steps 1-2: Fingerprint and look up on AcoustID
+        let (identifications, _lookups) = self.acoustid_lookup(path).await?;
 
         // Step 3: Read existing metadata from file for matching hints
         let existing_meta = crate::metadata::read(path).ok();
@@ -180,10 +470,9 @@ impl EnrichmentService {
 
         // Enrich best match with MusicBrainz
         if self.config.use_musicbrainz && !recording_id.is_empty() {
-            tokio::time::sleep(Duration::from_millis(1100)).await;
-            match self.musicbrainz.lookup_recording(&recording_id).await {
+            match self.lookup_musicbrainz(path, &recording_id).await {
                 Ok(mb_result) => {
-                    best.track.merge(&mb_result.track);
+                    best.merge_musicbrainz(&mb_result.track);
                 }
                 Err(e) => {
                     tracing::warn!("MusicBrainz lookup failed for best match: {}", e);
@@ -191,36 +480,108 @@ impl EnrichmentService {
             }
         }
 
-        // Enrich alternatives (respecting rate limits)
+        // Enrich alternatives (the same recording: answered from the memo)
         let mut enriched_alts = Vec::new();
         for alt in alternatives {
-            // Small delay between MusicBrainz requests
-            tokio::time::sleep(Duration::from_millis(100)).await;
-
             let mut enriched = alt;
             if self.config.use_musicbrainz && !recording_id.is_empty() {
-                tokio::time::sleep(Duration::from_millis(1100)).await;
-                match self.musicbrainz.lookup_recording(&recording_id).await {
+                match self.lookup_musicbrainz(path, &recording_id).await {
                     Ok(mb_result) => {
-                        enriched.track.merge(&mb_result.track);
+                        enriched.merge_musicbrainz(&mb_result.track);
                     }
                     Err(e) => {
                         tracing::debug!("MusicBrainz lookup failed for alternative: {}", e);
                     }
                 }
             }
+            if let Some(meta) = &existing_meta {
+                enriched.score_against(meta);
+            }
             enriched_alts.push(enriched);
         }
 
+        if let Some(meta) = &existing_meta {
+            best.score_against(meta);
+        }
         Ok((best, enriched_alts))
     }
 
+    /// Look a recording up on MusicBrainz, from the release its folder is
+    /// being tagged from when there is one. Pauses for the rate limit only
+    /// when a request will be sent.
+    async fn lookup_musicbrainz(
+        &self,
+        path: &Path,
+        recording_id: &str,
+    ) -> Result<TrackIdentification, EnrichmentError> {
+        let folder = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let album_release = self.folder_release(&folder);
+
+        if !self
+            .musicbrainz
+            .is_memoized(recording_id, album_release.as_deref())
+        {
+            tokio::time::sleep(MUSICBRAINZ_INTERVAL).await;
+        }
+        let mut found = None;
+        if let Some(ref release_id) = album_release {
+            found = self
+                .musicbrainz
+                .lookup_recording_on(recording_id, release_id)
+                .await;
+        }
+        let result = match found {
+            Some(identification) => identification,
+            None => {
+                if album_release.is_some() && !self.musicbrainz.is_memoized(recording_id, None) {
+                    // Not on the folder's release: the release lookup used
+                    // this request's slot
+                    tokio::time::sleep(MUSICBRAINZ_INTERVAL).await;
+                }
+                Retry::NETWORK
+                    .run_async(|| self.musicbrainz.lookup_recording(recording_id))
+                    .await?
+            }
+        };
+
+        if let Some(ref release_id) = result.track.release_id {
+            let mut folders = self
+                .folder_releases
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            *folders
+                .entry(folder)
+                .or_default()
+                .entry(release_id.clone())
+                .or_default() += 1;
+        }
+        Ok(result)
+    }
+
+    /// The release most of a folder's tracks were tagged from, once
+    /// [`COALESCE_AFTER`] were
+    fn folder_release(&self, folder: &Path) -> Option<String> {
+        let folders = self
+            .folder_releases
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        folders
+            .get(folder)?
+            .iter()
+            .filter(|(_, count)| **count >= COALESCE_AFTER)
+            .max_by_key(|(_, count)| **count)
+            .map(|(release_id, _)| release_id.clone())
+    }
+
     /// Fetch cover art for a release
     ///
     /// Requires a MusicBrainz release ID (from identify_track result).
     pub async fn get_cover_art(&self, release_id: &str) -> Result<CoverArt, EnrichmentError> {
-        self.coverart
-            .get_front_cover(release_id, self.config.cover_size)
+        Retry::NETWORK
+            .run_async(|| {
+                self.coverart
+                    .get_front_cover(release_id, self.config.cover_size)
+            })
             .await
     }
 
@@ -230,7 +591,9 @@ impl EnrichmentService {
         release_id: &str,
         size: CoverSize,
     ) -> Result<CoverArt, EnrichmentError> {
-        self.coverart.get_front_cover(release_id, size).await
+        Retry::NETWORK
+            .run_async(|| self.coverart.get_front_cover(release_id, size))
+            .await
     }
 
     /// Identify multiple tracks, respecting rate limits
@@ -275,6 +638,24 @@ pub async fn identify_track(
     service.identify_track(path).await
 }
 
+/// A file's fingerprint: the one in its tags if it carries one, otherwise
+/// generated with `fpcalc` within the CPU budget
+async fn fingerprint_of(path: &Path) -> Result<AudioFingerprint, EnrichmentError> {
+    if let Some(fp) = crate::metadata::acoustid::read_fingerprint(path) {
+        tracing::debug!("Using the fingerprint tagged in {}", path.display());
+        return Ok(fp);
+    }
+    CpuBudget::global().fingerprint(path).await
+}
+
+/// Remember the fingerprint on each identification, so it's written to the
+/// tags with the IDs
+fn with_fingerprint(identifications: &mut [TrackIdentification], fp: &AudioFingerprint) {
+    for identification in identifications {
+        identification.track.fingerprint = Some(fp.fingerprint.clone());
+    }
+}
+
 /// Calculate a combined match score based on AcoustID confidence + metadata matching
 ///
 /// This helps pick the "right" release when a track appears on multiple albums.
@@ -289,37 +670,17 @@ fn calculate_match_score(
     // Extract hints from file path
     let path_str = file_path.to_string_lossy().to_lowercase();
 
-    // Boost score if album name matches path or existing metadata
+    // Boost score if album name appears in the file path
     if let Some(ref album) = identification.track.album {
-        let album_lower = album.to_lowercase();
-
-        // Check if album appears in file path
-        if path_str.contains(&album_lower) {
+        let album = similarity::normalize(album);
+        if !album.is_empty() && similarity::normalize(&path_str).contains(&album) {
             score += 0.15; // Significant boost for path match
         }
-
-        // Check if album matches existing embedded metadata
-        if let Some(meta) = existing_meta {
-            let existing_lower = meta.album.to_lowercase();
-            if !meta.album.is_empty()
-                && (album_lower.contains(&existing_lower) || existing_lower.contains(&album_lower))
-            {
-                score += 0.20; // Even bigger boost for embedded tag match
-            }
-        }
     }
 
-    // Check if artist matches embedded metadata
-    if let Some(ref artist) = identification.track.artist
-        && let Some(meta) = existing_meta
-    {
-        let artist_lower = artist.to_lowercase();
-        let existing_lower = meta.artist.to_lowercase();
-        if !meta.artist.is_empty()
-            && (artist_lower.contains(&existing_lower) || existing_lower.contains(&artist_lower))
-        {
-            score += 0.10; // Boost for artist match
-        }
+    // Boost score as the album and artist agree with embedded metadata
+    if let Some(meta) = existing_meta {
+        score += MatchScores::compare(meta, &identification.track).release_boost();
     }
 
     // Penalize undesirable release types based on secondary types
@@ -339,16 +700,12 @@ fn calculate_match_score(
                     score -= 0.05; // Mild penalty otherwise
                 }
             }
-            "live" => {
-                // Penalize live unless path indicates it's expected
-                if !path_str.contains("live") && !path_str.contains("concert") {
-                    score -= 0.10;
-                }
+            // Penalize live unless path indicates it's expected
+            "live" if !path_str.contains("live") && !path_str.contains("concert") => {
+                score -= 0.10;
             }
-            "remix" => {
-                if !path_str.contains("remix") {
-                    score -= 0.15;
-                }
+            "remix" if !path_str.contains("remix") => {
+                score -= 0.15;
             }
             _ => {}
         }
@@ -388,4 +745,48 @@ mod tests {
         // Just verify it doesn't panic
         let _ = service.is_fingerprinting_available();
     }
+
+    /// What the Enrich pane does for one file, against fixture servers
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_identify_with_alternatives_from_fixtures() {
+        use crate::test_utils::{
+            AudioFixture, FixtureResponse, FixtureServer, install_fake_fpcalc, write_audio_fixture,
+        };
+
+        install_fake_fpcalc();
+        let server = FixtureServer::start(vec![
+            (
+                "/v2/lookup",
+                FixtureResponse::json(include_str!("fixtures/acoustid_lookup.json")),
+            ),
+            (
+                "/ws/2/recording/",
+                FixtureResponse::json(include_str!("fixtures/musicbrainz_recording.json")),
+            ),
+        ])
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_audio_fixture(dir.path(), AudioFixture::Mp3);
+
+        let service = EnrichmentService::new(EnrichmentConfig {
+            acoustid_api_key: "test".to_string(),
+            min_confidence: 0.5,
+            endpoints: Endpoints::all_at(server.url()),
+            ..Default::default()
+        });
+        assert!(service.is_fingerprinting_available());
+        let (best, _alternatives) = service
+            .identify_track_with_alternatives(&path)
+            .await
+            .unwrap();
+        assert_eq!(best.track.title.as_deref(), Some("Smells Like Teen Spirit"));
+
+        let corrupt = dir.path().join("corrupt.mp3");
+        std::fs::rename(&path, &corrupt).unwrap();
+        assert!(matches!(
+            service.identify_track_with_alternatives(&corrupt).await,
+            Err(EnrichmentError::FingerprintError(_))
+        ));
+    }
 }
@@ -0,0 +1,382 @@
+//! Fuzzy matching of titles, artists and albums against a file's tags.
+//!
+//! Both sides are normalized first, so differences no tagger cares about
+//! disappear: case, diacritics ("Sigur Rós"), punctuation, "&" against
+//! "and", a leading "The" (or a trailing ", The"), featured artists
+//! ("Song (feat. X)", "Artist ft. X") and remaster notes ("Song - 2011
+//! Remaster"). What's left is scored three ways, keeping the best:
+//!
+//! - the edit distance between the whole strings, for typos;
+//! - the same with the words lined up, for reordering ("Smith John");
+//! - the words both sides share, for extra words on one side ("Song
+//!   (Live)"), scaled down by how little of the longer side they cover.
+//!
+//! Words line up when their Jaro-Winkler similarity is high, so a misspelt
+//! word ("Beatels") still pairs with the right one.
+//!
+//! [`MatchScores`] holds a match's title, artist and album scores against
+//! the file's tags. They decide which release of a recording is picked,
+//! whether a match is accepted without review, and which matches are
+//! shown for review first.
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+use super::domain::IdentifiedTrack;
+use crate::metadata::{PlaceholderDetector, TrackMetadata};
+
+/// Jaro-Winkler similarity at which two words count as the same word
+const SAME_WORD: f64 = 0.9;
+
+/// Title or artist scores below this say the match is some other track
+/// than the tags describe
+pub const CONTRADICTS_TAGS: f32 = 0.5;
+
+/// How close two strings are once normalized, from 0.0 to 1.0
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let a = normalize(a);
+    let b = normalize(b);
+    if a == b {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    score(&a, &b) as f32
+}
+
+/// The form strings are compared in: lowercase ASCII-ish words separated
+/// by single spaces, without featured artists, remaster notes or a leading
+/// article
+pub fn normalize(s: &str) -> String {
+    let mut s = fold(s);
+    s = strip_featuring(&s);
+    s = strip_remaster(&s);
+    if let Some(stripped) = s.trim_end().strip_suffix(", the") {
+        s = stripped.to_string();
+    }
+
+    let cleaned: String = s
+        .replace('&', " and ")
+        .chars()
+        .filter_map(|c| match c {
+            '\'' | '’' | '`' => None,
+            c if c.is_alphanumeric() => Some(c),
+            _ => Some(' '),
+        })
+        .collect();
+    let mut words: Vec<&str> = cleaned.split_whitespace().collect();
+    if words.len() > 1 && matches!(words[0], "the" | "a" | "an") {
+        words.remove(0);
+    }
+    words.join(" ")
+}
+
+/// Lowercase without diacritics ("Motörhead" -> "motorhead")
+fn fold(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.nfkd().filter(|c| !is_combining_mark(*c)) {
+        // Letters that don't decompose into a base letter and a mark
+        match c {
+            'ø' | 'Ø' => out.push('o'),
+            'ł' | 'Ł' => out.push('l'),
+            'đ' | 'Đ' => out.push('d'),
+            'ß' => out.push_str("ss"),
+            'æ' | 'Æ' => out.push_str("ae"),
+            'œ' | 'Œ' => out.push_str("oe"),
+            c => out.extend(c.to_lowercase()),
+        }
+    }
+    out
+}
+
+/// Cut a featured-artist credit and everything after it
+fn strip_featuring(s: &str) -> String {
+    const CREDITS: [&str; 8] = [
+        " (feat",
+        " [feat",
+        " (ft.",
+        " [ft.",
+        " feat.",
+        " ft. ",
+        " featuring ",
+        " (with ",
+    ];
+    let cut = CREDITS.iter().filter_map(|c| s.find(c)).min();
+    match cut {
+        Some(pos) => s[..pos].to_string(),
+        None => s.to_string(),
+    }
+}
+
+/// Drop remaster notes: "(Remastered 2009)", "[2011 Remaster]", "- Remastered"
+fn strip_remaster(s: &str) -> String {
+    let mut s = match s.find(" - ") {
+        Some(pos) if s[pos..].contains("remaster") => s[..pos].to_string(),
+        _ => s.to_string(),
+    };
+    while let Some((start, end)) = remaster_group(&s) {
+        s.replace_range(start..end, "");
+    }
+    s
+}
+
+/// Byte range of the first bracketed group mentioning a remaster
+fn remaster_group(s: &str) -> Option<(usize, usize)> {
+    let mut from = 0;
+    while let Some(open) = s[from..].find(['(', '[']).map(|i| i + from) {
+        let close = if s[open..].starts_with('(') { ')' } else { ']' };
+        let end = s[open..].find(close).map_or(s.len(), |i| open + i + 1);
+        if s[open..end].contains("remaster") {
+            return Some((open, end));
+        }
+        from = end;
+    }
+    None
+}
+
+/// Best of the three scores, for normalized, non-empty, different strings
+fn score(a: &str, b: &str) -> f64 {
+    let whole = strsim::normalized_levenshtein(a, b);
+
+    let words_a: Vec<&str> = a.split(' ').collect();
+    let words_b: Vec<&str> = b.split(' ').collect();
+    let (pairs, mut rest_a, mut rest_b) = align(&words_a, &words_b);
+    if pairs.is_empty() {
+        return whole;
+    }
+    rest_a.sort_unstable();
+    rest_b.sort_unstable();
+    let join = |words: &mut dyn Iterator<Item = &str>| words.collect::<Vec<_>>().join(" ");
+
+    let shared_a = join(&mut pairs.iter().map(|p| p.0));
+    let shared_b = join(&mut pairs.iter().map(|p| p.1));
+    let lined_up = strsim::normalized_levenshtein(
+        &join(&mut pairs.iter().map(|p| p.0).chain(rest_a.iter().copied())),
+        &join(&mut pairs.iter().map(|p| p.1).chain(rest_b.iter().copied())),
+    );
+
+    let letters = |words: &[&str]| words.iter().map(|w| w.chars().count()).sum::<usize>();
+    let shared_letters: usize = pairs
+        .iter()
+        .map(|(x, y)| x.chars().count().max(y.chars().count()))
+        .sum();
+    let longer = letters(&words_a).max(letters(&words_b));
+    let coverage = (shared_letters as f64 / longer as f64).min(1.0).sqrt();
+    let shared = strsim::normalized_levenshtein(&shared_a, &shared_b) * coverage;
+
+    whole.max(lined_up).max(shared)
+}
+
+/// Pair each word of `a` with its closest unpaired word of `b`, if close
+/// enough; returns the pairs and the words of each side left over
+fn align<'a>(
+    a: &[&'a str],
+    b: &[&'a str],
+) -> (Vec<(&'a str, &'a str)>, Vec<&'a str>, Vec<&'a str>) {
+    let mut used = vec![false; b.len()];
+    let mut pairs = Vec::new();
+    let mut rest_a = Vec::new();
+    for word in a {
+        let best = b
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !used[*i])
+            .map(|(i, other)| (i, strsim::jaro_winkler(word, other)))
+            .filter(|(_, s)| *s >= SAME_WORD)
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+        match best {
+            Some((i, _)) => {
+                used[i] = true;
+                pairs.push((*word, b[i]));
+            }
+            None => rest_a.push(*word),
+        }
+    }
+    let rest_b = b
+        .iter()
+        .zip(used)
+        .filter(|(_, used)| !used)
+        .map(|(w, _)| *w)
+        .collect();
+    (pairs, rest_a, rest_b)
+}
+
+/// How a match compares with the tags already in the file. A score is
+/// `None` when either side has nothing to compare (no value, or a
+/// placeholder like "Unknown Artist").
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MatchScores {
+    pub title: Option<f32>,
+    pub artist: Option<f32>,
+    pub album: Option<f32>,
+}
+
+impl MatchScores {
+    /// Score `found` against the file's `tags`
+    pub fn compare(tags: &TrackMetadata, found: &IdentifiedTrack) -> Self {
+        let placeholders = PlaceholderDetector::new();
+        let score = |existing: &str, found: Option<&str>| {
+            if placeholders.is_placeholder(existing) {
+                return None;
+            }
+            found
+                .filter(|f| !f.trim().is_empty())
+                .map(|f| similarity(existing, f))
+        };
+        Self {
+            title: score(&tags.title, found.title.as_deref()),
+            artist: score(&tags.artist, found.artist.as_deref()),
+            album: score(&tags.album, found.album.as_deref()),
+        }
+    }
+
+    /// Whether the title or artist is so far off that the match is likely
+    /// a different track than the tags say
+    pub fn contradicts_tags(&self) -> bool {
+        [self.title, self.artist]
+            .into_iter()
+            .flatten()
+            .any(|s| s < CONTRADICTS_TAGS)
+    }
+
+    /// How well the title and artist agree with the tags, from 0.0 to 1.0
+    /// (1.0 when there's nothing to compare)
+    pub fn agreement(&self) -> f32 {
+        (self.title.unwrap_or(1.0) + self.artist.unwrap_or(1.0)) / 2.0
+    }
+
+    /// Ranking bonus for releases whose album and artist agree with the
+    /// tags: nothing below half similar, up to 0.20 for the album and 0.10
+    /// for the artist
+    pub fn release_boost(&self) -> f32 {
+        let boost = |score: Option<f32>, weight: f32| {
+            score.map_or(0.0, |s| ((s - 0.5) * 2.0).max(0.0) * weight)
+        };
+        boost(self.album, 0.20) + boost(self.artist, 0.10)
+    }
+}
+
+impl std::fmt::Display for MatchScores {
+    /// e.g. "title 100%, artist 42%"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            ("title", self.title),
+            ("artist", self.artist),
+            ("album", self.album),
+        ]
+        .into_iter()
+        .filter_map(|(name, score)| Some(format!("{} {:.0}%", name, score? * 100.0)))
+        .collect();
+        f.write_str(&parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pairs from real libraries, with the range their similarity must fall in
+    const CORPUS: &[(&str, &str, f32, f32)] = &[
+        // Spelled differently, the same name
+        ("The Beatles", "Beatles", 1.0, 1.0),
+        ("Beatles, The", "The Beatles", 1.0, 1.0),
+        ("Sigur Rós", "Sigur Ros", 1.0, 1.0),
+        ("Motörhead", "MOTORHEAD", 1.0, 1.0),
+        ("Björk", "Bjork", 1.0, 1.0),
+        ("Mø", "MO", 1.0, 1.0),
+        ("Guns N' Roses", "Guns N Roses", 1.0, 1.0),
+        ("Simon & Garfunkel", "Simon and Garfunkel", 1.0, 1.0),
+        ("Don't Stop Me Now", "Dont Stop Me Now", 1.0, 1.0),
+        ("Santana feat. Rob Thomas", "Santana", 1.0, 1.0),
+        ("Smooth (feat. Rob Thomas)", "Smooth", 1.0, 1.0),
+        (
+            "Empire State of Mind [feat. Alicia Keys]",
+            "Empire State Of Mind",
+            1.0,
+            1.0,
+        ),
+        (
+            "Here Comes the Sun - Remastered 2009",
+            "Here Comes The Sun",
+            1.0,
+            1.0,
+        ),
+        (
+            "Paranoid Android (2017 Remaster)",
+            "Paranoid Android",
+            1.0,
+            1.0,
+        ),
+        // Close: typos, reordering, punctuation
+        ("Beatels", "Beatles", 0.7, 0.99),
+        ("John Smith", "Smith John", 0.95, 1.0),
+        ("AC/DC", "ACDC", 0.75, 0.99),
+        (
+            "Sgt. Pepper's Lonely Hearts Club Band",
+            "Sgt Peppers Lonely Heart Club Band",
+            0.9,
+            0.99,
+        ),
+        // The same song, another version
+        ("Yesterday (Live)", "Yesterday", 0.6, 0.9),
+        ("Hurt - Acoustic", "Hurt", 0.5, 0.9),
+        // Different songs
+        ("Love", "Love Will Tear Us Apart", 0.0, 0.5),
+        ("Yesterday", "Let It Be", 0.0, 0.3),
+        ("Creep", "Karma Police", 0.0, 0.3),
+        ("completely different", "nothing alike", 0.0, 0.3),
+    ];
+
+    #[test]
+    fn test_corpus_scores_in_range() {
+        for (a, b, min, max) in CORPUS {
+            let s = similarity(a, b);
+            assert!(
+                (*min..=*max).contains(&s),
+                "{:?} vs {:?}: {} not in {}..={}",
+                a,
+                b,
+                s,
+                min,
+                max
+            );
+            // Symmetric
+            assert!((similarity(b, a) - s).abs() < 0.05, "{:?} vs {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("The Beatles"), "beatles");
+        assert_eq!(normalize("Song (feat. Guest)"), "song");
+        assert_eq!(normalize("  Multiple   Spaces  "), "multiple spaces");
+        // A lone article is the whole name
+        assert_eq!(normalize("The"), "the");
+        assert_eq!(normalize("  Hey   Jude!! "), "hey jude");
+        assert_eq!(normalize("Jay-Z"), "jay z");
+    }
+
+    #[test]
+    fn test_match_scores_ignore_placeholders() {
+        let tags = TrackMetadata {
+            title: "Track 01".to_string(),
+            artist: "Radiohead".to_string(),
+            album: "OK Computer".to_string(),
+            duration: 0,
+            track_number: None,
+        };
+        let found = IdentifiedTrack {
+            title: Some("Airbag".to_string()),
+            artist: Some("Coldplay".to_string()),
+            album: Some("OK Computer OKNOTOK 1997 2017".to_string()),
+            ..Default::default()
+        };
+        let scores = MatchScores::compare(&tags, &found);
+        assert_eq!(scores.title, None);
+        assert!(scores.artist.unwrap() < CONTRADICTS_TAGS);
+        assert!(scores.contradicts_tags());
+        assert!(scores.album.unwrap() > 0.5);
+        assert!(scores.release_boost() > 0.0);
+    }
+}
@@ -22,7 +22,10 @@
 use async_trait::async_trait;
 
 use super::coverart::{CoverArt, CoverSize};
-use super::domain::{AudioFingerprint, EnrichmentError, TrackIdentification};
+use super::discid::DiscToc;
+use super::domain::{
+    AudioFingerprint, DiscMatch, EnrichmentError, ReleaseTracklist, TrackIdentification,
+};
 
 /// Trait for AcoustID fingerprint lookup.
 ///
@@ -46,6 +49,12 @@ pub trait MusicBrainzApi: Send + Sync {
         &self,
         recording_id: &str,
     ) -> Result<TrackIdentification, EnrichmentError>;
+
+    /// Look up a release's full tracklist by its MusicBrainz ID.
+    async fn lookup_release(&self, release_id: &str) -> Result<ReleaseTracklist, EnrichmentError>;
+
+    /// Look up the releases of a ripped CD by its table of contents.
+    async fn lookup_discid(&self, toc: &DiscToc) -> Result<Vec<DiscMatch>, EnrichmentError>;
 }
 
 /// Trait for Cover Art Archive lookup.
@@ -81,6 +90,14 @@ impl MusicBrainzApi for super::musicbrainz::MusicBrainzClient {
     ) -> Result<TrackIdentification, EnrichmentError> {
         self.lookup_recording(recording_id).await
     }
+
+    async fn lookup_release(&self, release_id: &str) -> Result<ReleaseTracklist, EnrichmentError> {
+        self.lookup_release(release_id).await
+    }
+
+    async fn lookup_discid(&self, toc: &DiscToc) -> Result<Vec<DiscMatch>, EnrichmentError> {
+        self.lookup_discid(toc).await
+    }
 }
 
 #[async_trait]
@@ -137,6 +154,8 @@ pub mod mocks {
                         ..Default::default()
                     },
                     source: crate::enrichment::domain::EnrichmentSource::AcoustId,
+                    musicbrainz_fields: Vec::new(),
+                    scores: Default::default(),
                 }],
                 error: None,
             }
@@ -168,6 +187,10 @@ pub mod mocks {
     pub struct MockMusicBrainz {
         /// Result to return from lookup
         pub result: Option<TrackIdentification>,
+        /// Tracklist to return from release lookups
+        pub tracklist: Option<ReleaseTracklist>,
+        /// Releases to return from disc ID lookups
+        pub discs: Vec<DiscMatch>,
         /// Error to return (takes precedence over result)
         pub error: Option<EnrichmentError>,
     }
@@ -191,7 +214,11 @@ pub mod mocks {
                         ..Default::default()
                     },
                     source: crate::enrichment::domain::EnrichmentSource::MusicBrainz,
+                    musicbrainz_fields: Vec::new(),
+                    scores: Default::default(),
                 }),
+                tracklist: None,
+                discs: Vec::new(),
                 error: None,
             }
         }
@@ -200,9 +227,31 @@ pub mod mocks {
         pub fn with_error(error: EnrichmentError) -> Self {
             Self {
                 result: None,
+                tracklist: None,
+                discs: Vec::new(),
                 error: Some(error),
             }
         }
+
+        /// Create a mock whose disc ID lookups return `discs`.
+        pub fn with_discs(discs: Vec<DiscMatch>) -> Self {
+            Self {
+                result: None,
+                tracklist: None,
+                discs,
+                error: None,
+            }
+        }
+
+        /// Create a mock whose release lookups return `tracklist`.
+        pub fn with_tracklist(tracklist: ReleaseTracklist) -> Self {
+            Self {
+                result: None,
+                tracklist: Some(tracklist),
+                discs: Vec::new(),
+                error: None,
+            }
+        }
     }
 
     #[async_trait]
@@ -216,6 +265,23 @@ pub mod mocks {
             }
             self.result.clone().ok_or(EnrichmentError::NoMatches)
         }
+
+        async fn lookup_release(
+            &self,
+            _release_id: &str,
+        ) -> Result<ReleaseTracklist, EnrichmentError> {
+            if let Some(ref err) = self.error {
+                return Err(err.clone());
+            }
+            self.tracklist.clone().ok_or(EnrichmentError::NoMatches)
+        }
+
+        async fn lookup_discid(&self, _toc: &DiscToc) -> Result<Vec<DiscMatch>, EnrichmentError> {
+            if let Some(ref err) = self.error {
+                return Err(err.clone());
+            }
+            Ok(self.discs.clone())
+        }
     }
 
     /// Mock Cover Art client.
@@ -9,6 +9,19 @@
 //! - [`Error`]: Top-level application error enum
 //! - Module-specific errors (e.g., [`EnrichmentError`]) for detailed handling
 //! - All errors implement `std::error::Error` for compatibility
+//! - [`ErrorClass`]: what kind of failure an error is, shared by every
+//!   subsystem so retries, health records and badges agree
+//!
+//! # Classification
+//!
+//! A failure is either [`Recoverable`] (a network hiccup, a file or the
+//! database held by someone else, a rate limit), worth trying again after a
+//! pause, or [`Permanent`] (an unsupported or corrupt file, something that
+//! doesn't exist), which won't change by retrying. [`Classify`] tells which
+//! for the error types the subsystems produce; errors it can't place are
+//! left unclassified and aren't retried. [`Retry`] runs an operation again
+//! while its failures are recoverable, and [`Failure`] carries a classified
+//! error as a value, for messages and reports.
 //!
 //! # Example
 //!
@@ -22,7 +35,10 @@
 //! }
 //! ```
 
+use std::fmt;
+use std::future::Future;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Application-wide result type.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -139,6 +155,392 @@ impl<T> ResultExt<T> for std::result::Result<T, sqlx::Error> {
     }
 }
 
+/// Failures that may go away by trying again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Recoverable {
+    /// Connection, DNS or timeout trouble
+    Network,
+    /// The file or database is held by another process
+    Locked,
+    /// The service asked us to slow down
+    RateLimited,
+}
+
+/// Failures that stay the same however often they're retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permanent {
+    /// A format, tag or feature we can't handle
+    Unsupported,
+    /// Damaged or undecodable data
+    Corrupt,
+    /// The file, record or match doesn't exist
+    NotFound,
+}
+
+/// What kind of failure an error is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    Recoverable(Recoverable),
+    Permanent(Permanent),
+}
+
+impl ErrorClass {
+    pub const NETWORK: Self = Self::Recoverable(Recoverable::Network);
+    pub const LOCKED: Self = Self::Recoverable(Recoverable::Locked);
+    pub const RATE_LIMITED: Self = Self::Recoverable(Recoverable::RateLimited);
+    pub const UNSUPPORTED: Self = Self::Permanent(Permanent::Unsupported);
+    pub const CORRUPT: Self = Self::Permanent(Permanent::Corrupt);
+    pub const NOT_FOUND: Self = Self::Permanent(Permanent::NotFound);
+
+    /// All classes, recoverable first.
+    pub const ALL: [ErrorClass; 6] = [
+        Self::NETWORK,
+        Self::LOCKED,
+        Self::RATE_LIMITED,
+        Self::UNSUPPORTED,
+        Self::CORRUPT,
+        Self::NOT_FOUND,
+    ];
+
+    /// Convert to string representation for storage.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Recoverable(Recoverable::Network) => "network",
+            Self::Recoverable(Recoverable::Locked) => "locked",
+            Self::Recoverable(Recoverable::RateLimited) => "rate_limited",
+            Self::Permanent(Permanent::Unsupported) => "unsupported",
+            Self::Permanent(Permanent::Corrupt) => "corrupt",
+            Self::Permanent(Permanent::NotFound) => "not_found",
+        }
+    }
+
+    /// Human-readable label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Recoverable(Recoverable::Network) => "Network",
+            Self::Recoverable(Recoverable::Locked) => "File in use",
+            Self::Recoverable(Recoverable::RateLimited) => "Rate limited",
+            Self::Permanent(Permanent::Unsupported) => "Unsupported",
+            Self::Permanent(Permanent::Corrupt) => "Corrupt",
+            Self::Permanent(Permanent::NotFound) => "Not found",
+        }
+    }
+
+    /// Whether trying again later might succeed
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, Self::Recoverable(_))
+    }
+
+    /// Best guess from an error message, for errors that only arrive as
+    /// text (from another process, or flattened to a string on the way)
+    pub fn of_message(message: &str) -> Option<Self> {
+        let message = message.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| message.contains(w));
+        if has(&["offline mode"]) {
+            None
+        } else if has(&["rate limit", "too many requests"]) {
+            Some(Self::RATE_LIMITED)
+        } else if has(&[
+            "locked",
+            "resource busy",
+            "being used by another process",
+            "sharing violation",
+        ]) {
+            Some(Self::LOCKED)
+        } else if has(&["timed out", "timeout", "connection", "network", "dns"]) {
+            Some(Self::NETWORK)
+        } else if has(&["unsupported", "unknown format", "not supported"]) {
+            Some(Self::UNSUPPORTED)
+        } else if has(&[
+            "corrupt",
+            "invalid data",
+            "decode",
+            "malformed",
+            "unexpected end",
+        ]) {
+            Some(Self::CORRUPT)
+        } else if has(&["not found", "no such file", "no matches"]) {
+            Some(Self::NOT_FOUND)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ErrorClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        ErrorClass::ALL
+            .into_iter()
+            .find(|c| c.as_str() == s)
+            .ok_or_else(|| format!("unknown error class '{}'", s))
+    }
+}
+
+/// Errors that can tell what kind of failure they are.
+pub trait Classify {
+    /// The failure's class, `None` when it doesn't fit one
+    fn class(&self) -> Option<ErrorClass>;
+}
+
+impl Classify for std::io::Error {
+    fn class(&self) -> Option<ErrorClass> {
+        use std::io::ErrorKind;
+        // Windows reports a file open elsewhere as a sharing or lock
+        // violation, without an error kind of its own
+        if cfg!(windows) && matches!(self.raw_os_error(), Some(32 | 33)) {
+            return Some(ErrorClass::LOCKED);
+        }
+        match self.kind() {
+            ErrorKind::NotFound => Some(ErrorClass::NOT_FOUND),
+            ErrorKind::ResourceBusy | ErrorKind::ExecutableFileBusy | ErrorKind::WouldBlock => {
+                Some(ErrorClass::LOCKED)
+            }
+            ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable
+            | ErrorKind::NetworkDown
+            | ErrorKind::TimedOut => Some(ErrorClass::NETWORK),
+            ErrorKind::InvalidData | ErrorKind::UnexpectedEof => Some(ErrorClass::CORRUPT),
+            ErrorKind::Unsupported => Some(ErrorClass::UNSUPPORTED),
+            _ => None,
+        }
+    }
+}
+
+impl Classify for sqlx::Error {
+    fn class(&self) -> Option<ErrorClass> {
+        match self {
+            // SQLite primary result codes, under any extended code
+            sqlx::Error::Database(e) => match e.code()?.parse::<i32>().ok()? & 0xff {
+                5 | 6 => Some(ErrorClass::LOCKED),
+                11 | 26 => Some(ErrorClass::CORRUPT),
+                _ => None,
+            },
+            sqlx::Error::PoolTimedOut => Some(ErrorClass::LOCKED),
+            sqlx::Error::RowNotFound => Some(ErrorClass::NOT_FOUND),
+            sqlx::Error::Io(e) => e.class(),
+            _ => None,
+        }
+    }
+}
+
+impl Classify for lofty::error::LoftyError {
+    fn class(&self) -> Option<ErrorClass> {
+        use lofty::error::ErrorKind;
+        match self.kind() {
+            ErrorKind::UnknownFormat
+            | ErrorKind::UnsupportedTag
+            | ErrorKind::UnsupportedPicture => Some(ErrorClass::UNSUPPORTED),
+            ErrorKind::Io(e) => e.class(),
+            ErrorKind::Fmt(_) | ErrorKind::Alloc(_) | ErrorKind::Infallible(_) => None,
+            _ => Some(ErrorClass::CORRUPT),
+        }
+    }
+}
+
+impl Classify for crate::enrichment::EnrichmentError {
+    fn class(&self) -> Option<ErrorClass> {
+        use crate::enrichment::EnrichmentError;
+        match self {
+            EnrichmentError::NoMatches => Some(ErrorClass::NOT_FOUND),
+            EnrichmentError::RateLimited => Some(ErrorClass::RATE_LIMITED),
+            // Refused in offline mode, or a request the service turned down
+            EnrichmentError::Network(message)
+                if message.starts_with("offline mode") || message.starts_with("HTTP 4") =>
+            {
+                None
+            }
+            EnrichmentError::Network(_) => Some(ErrorClass::NETWORK),
+            EnrichmentError::FingerprintError(message) => {
+                Some(ErrorClass::of_message(message).unwrap_or(ErrorClass::CORRUPT))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Classify for anyhow::Error {
+    fn class(&self) -> Option<ErrorClass> {
+        self.chain()
+            .find_map(|e| {
+                if let Some(e) = e.downcast_ref::<std::io::Error>() {
+                    e.class()
+                } else if let Some(e) = e.downcast_ref::<sqlx::Error>() {
+                    e.class()
+                } else if let Some(e) = e.downcast_ref::<lofty::error::LoftyError>() {
+                    e.class()
+                } else if let Some(e) = e.downcast_ref::<crate::enrichment::EnrichmentError>() {
+                    e.class()
+                } else {
+                    e.downcast_ref::<Failure>().and_then(|e| e.class)
+                }
+            })
+            .or_else(|| ErrorClass::of_message(&format!("{:#}", self)))
+    }
+}
+
+impl Classify for Error {
+    fn class(&self) -> Option<ErrorClass> {
+        match self {
+            Error::Io(e) => e.class(),
+            Error::Database(e) => e.class(),
+            Error::Enrichment(e) => e.class(),
+            Error::NotFound(_) => Some(ErrorClass::NOT_FOUND),
+            Error::InvalidFormat(_) => Some(ErrorClass::UNSUPPORTED),
+            Error::WithContext { source, .. } => source.class(),
+            _ => ErrorClass::of_message(&self.to_string()),
+        }
+    }
+}
+
+/// A classified error, kept as a value for messages and reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub class: Option<ErrorClass>,
+    pub message: String,
+}
+
+impl Failure {
+    /// A failure of the given class
+    pub fn new(class: Option<ErrorClass>, message: impl Into<String>) -> Self {
+        Self {
+            class,
+            message: message.into(),
+        }
+    }
+
+    /// Capture an error's class and message
+    pub fn of<E: Classify + fmt::Display>(err: &E) -> Self {
+        Self::new(err.class(), err.to_string())
+    }
+
+    /// A failure known only by its message, classified from the text
+    pub fn message(message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::new(ErrorClass::of_message(&message), message)
+    }
+
+    /// Whether trying again later might succeed
+    pub fn is_recoverable(&self) -> bool {
+        self.class.is_some_and(|c| c.is_recoverable())
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+impl Classify for Failure {
+    fn class(&self) -> Option<ErrorClass> {
+        self.class
+    }
+}
+
+/// How often, and after what pause, to try an operation again while it
+/// fails recoverably.
+///
+/// The pause doubles after each attempt, four times as long when rate
+/// limited. Permanent and unclassified failures return at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    /// Attempts in all, the first included
+    pub attempts: u32,
+    /// Pause before the first retry
+    pub base_delay: Duration,
+}
+
+impl Retry {
+    /// For web service requests; the first pause is longer than the
+    /// MusicBrainz one-request-a-second limit
+    pub const NETWORK: Retry = Retry {
+        attempts: 3,
+        base_delay: Duration::from_secs(2),
+    };
+
+    /// For files held open by another program (a player, a sync client,
+    /// a virus scanner)
+    pub const FILE: Retry = Retry {
+        attempts: 4,
+        base_delay: Duration::from_millis(250),
+    };
+
+    /// The pause after `attempt` (counting from 1) failed with `class`, or
+    /// `None` to stop trying
+    pub fn delay(&self, class: Option<ErrorClass>, attempt: u32) -> Option<Duration> {
+        let Some(ErrorClass::Recoverable(kind)) = class else {
+            return None;
+        };
+        if attempt == 0 || attempt >= self.attempts {
+            return None;
+        }
+        let delay = self.base_delay * 2u32.saturating_pow(attempt - 1);
+        Some(match kind {
+            Recoverable::RateLimited => delay * 4,
+            Recoverable::Network | Recoverable::Locked => delay,
+        })
+    }
+
+    /// Run `op`, trying again (and blocking the thread in between) while it
+    /// fails recoverably
+    pub fn run<T, E: Classify + fmt::Display>(
+        &self,
+        mut op: impl FnMut() -> std::result::Result<T, E>,
+    ) -> std::result::Result<T, E> {
+        let mut attempt = 1;
+        loop {
+            match op() {
+                Err(e) => match self.delay(e.class(), attempt) {
+                    Some(delay) => {
+                        tracing::debug!("Retrying in {:?} after: {}", delay, e);
+                        std::thread::sleep(delay);
+                        attempt += 1;
+                    }
+                    None => return Err(e),
+                },
+                ok => return ok,
+            }
+        }
+    }
+
+    /// Run `op`, trying again while it fails recoverably
+    pub async fn run_async<T, E, F, Fut>(&self, mut op: F) -> std::result::Result<T, E>
+    where
+        E: Classify + fmt::Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::result::Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match op().await {
+                Err(e) => match self.delay(e.class(), attempt) {
+                    Some(delay) => {
+                        tracing::debug!("Retrying in {:?} after: {}", delay, e);
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => return Err(e),
+                },
+                ok => return ok,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -175,4 +577,116 @@ mod tests {
                 .contains("additional context")
         );
     }
+
+    #[test]
+    fn test_classify_across_error_types() {
+        use crate::enrichment::EnrichmentError;
+        use std::io::ErrorKind;
+
+        let io = |kind| std::io::Error::from(kind);
+        assert_eq!(io(ErrorKind::NotFound).class(), Some(ErrorClass::NOT_FOUND));
+        assert_eq!(
+            io(ErrorKind::ResourceBusy).class(),
+            Some(ErrorClass::LOCKED)
+        );
+        assert_eq!(io(ErrorKind::TimedOut).class(), Some(ErrorClass::NETWORK));
+        assert_eq!(
+            io(ErrorKind::InvalidData).class(),
+            Some(ErrorClass::CORRUPT)
+        );
+        assert_eq!(io(ErrorKind::PermissionDenied).class(), None);
+
+        assert_eq!(sqlx::Error::PoolTimedOut.class(), Some(ErrorClass::LOCKED));
+        assert_eq!(
+            EnrichmentError::RateLimited.class(),
+            Some(ErrorClass::RATE_LIMITED)
+        );
+        assert_eq!(
+            EnrichmentError::Network("connection reset".into()).class(),
+            Some(ErrorClass::NETWORK)
+        );
+        assert_eq!(
+            EnrichmentError::Network("offline mode: not requesting".into()).class(),
+            None
+        );
+        assert_eq!(
+            EnrichmentError::NoMatches.class(),
+            Some(ErrorClass::NOT_FOUND)
+        );
+
+        // anyhow finds the cause under its context
+        let err = anyhow::Error::from(io(ErrorKind::ResourceBusy)).context("renaming");
+        assert_eq!(err.class(), Some(ErrorClass::LOCKED));
+        let err = anyhow::anyhow!("Failed to read tags: unknown format");
+        assert_eq!(err.class(), Some(ErrorClass::UNSUPPORTED));
+
+        let failure = Failure::of(&Error::not_found("/m/a.mp3"));
+        assert_eq!(failure.class, Some(ErrorClass::NOT_FOUND));
+        assert!(!failure.is_recoverable());
+        for class in ErrorClass::ALL {
+            assert_eq!(class.as_str().parse(), Ok(class));
+        }
+    }
+
+    #[test]
+    fn test_retry_only_recoverable_failures() {
+        let retry = Retry {
+            attempts: 3,
+            base_delay: Duration::from_millis(1),
+        };
+        assert_eq!(
+            retry.delay(Some(ErrorClass::NETWORK), 2),
+            Some(Duration::from_millis(2))
+        );
+        assert_eq!(
+            retry.delay(Some(ErrorClass::RATE_LIMITED), 1),
+            Some(Duration::from_millis(4))
+        );
+        assert_eq!(retry.delay(Some(ErrorClass::NETWORK), 3), None);
+        assert_eq!(retry.delay(Some(ErrorClass::CORRUPT), 1), None);
+        assert_eq!(retry.delay(None, 1), None);
+
+        let mut calls = 0;
+        let result: std::result::Result<(), Failure> = retry.run(|| {
+            calls += 1;
+            Err(Failure::new(Some(ErrorClass::LOCKED), "busy"))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        let mut calls = 0;
+        let result = retry.run(|| {
+            calls += 1;
+            if calls < 2 {
+                Err(Failure::new(Some(ErrorClass::LOCKED), "busy"))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result, Ok(2));
+
+        let mut calls = 0;
+        let _ = retry.run(|| {
+            calls += 1;
+            Err::<(), _>(Failure::new(Some(ErrorClass::CORRUPT), "bad frame"))
+        });
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_async() {
+        let retry = Retry {
+            attempts: 2,
+            base_delay: Duration::from_millis(1),
+        };
+        let mut calls = 0;
+        let result: std::result::Result<(), Failure> = retry
+            .run_async(|| {
+                calls += 1;
+                async { Err(Failure::new(Some(ErrorClass::NETWORK), "reset")) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 2);
+    }
 }
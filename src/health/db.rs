@@ -21,6 +21,7 @@ struct FileHealthRow {
     status: String,
     error_type: Option<String>,
     error_message: Option<String>,
+    error_class: Option<String>,
     acoustid_fingerprint: Option<String>,
     acoustid_confidence: Option<f64>,
     musicbrainz_id: Option<String>,
@@ -39,6 +40,7 @@ impl From<FileHealthRow> for FileHealth {
                 .error_type
                 .map(|s| s.parse().unwrap_or(ErrorType::Other("unknown".into()))),
             error_message: row.error_message,
+            error_class: row.error_class.and_then(|s| s.parse().ok()),
             acoustid_fingerprint: row.acoustid_fingerprint,
             acoustid_confidence: row.acoustid_confidence,
             musicbrainz_id: row.musicbrainz_id,
@@ -67,21 +69,24 @@ impl From<FileHealthRow> for FileHealth {
 ///
 /// The database ID of the upserted record.
 pub async fn upsert_health(pool: &SqlitePool, health: &FileHealth) -> sqlx::Result<i64> {
+    crate::readonly::ensure_writable("Recording file health")?;
     let last_checked = health.last_checked.to_rfc3339();
     let error_type = health.error_type.as_ref().map(|e| e.as_str().to_string());
+    let error_class = health.error_class.map(|c| c.as_str());
 
     let row: (i64,) = sqlx::query_as(
         r#"
         INSERT INTO file_health (
-            path, status, error_type, error_message,
+            path, status, error_type, error_message, error_class,
             acoustid_fingerprint, acoustid_confidence, musicbrainz_id,
             file_size, file_hash, last_checked
         )
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(path) DO UPDATE SET
             status = excluded.status,
             error_type = excluded.error_type,
             error_message = excluded.error_message,
+            error_class = excluded.error_class,
             acoustid_fingerprint = excluded.acoustid_fingerprint,
             acoustid_confidence = excluded.acoustid_confidence,
             musicbrainz_id = excluded.musicbrainz_id,
@@ -95,6 +100,7 @@ pub async fn upsert_health(pool: &SqlitePool, health: &FileHealth) -> sqlx::Resu
     .bind(health.status.as_str())
     .bind(&error_type)
     .bind(&health.error_message)
+    .bind(error_class)
     .bind(&health.acoustid_fingerprint)
     .bind(health.acoustid_confidence)
     .bind(&health.musicbrainz_id)
@@ -175,6 +181,7 @@ pub async fn get_errors(pool: &SqlitePool) -> sqlx::Result<Vec<FileHealth>> {
 ///
 /// True if a record was deleted, false if no record existed.
 pub async fn delete_health(pool: &SqlitePool, path: &str) -> sqlx::Result<bool> {
+    crate::readonly::ensure_writable("Removing file health records")?;
     let result = sqlx::query("DELETE FROM file_health WHERE path = ?")
         .bind(path)
         .execute(pool)
@@ -200,6 +207,9 @@ pub struct HealthSummary {
     pub no_match: i64,
     /// Files with LowConfidence status
     pub low_confidence: i64,
+    /// Errors worth checking again (network trouble, a file in use, a
+    /// rate limit)
+    pub retryable: i64,
 }
 
 /// Get health summary counts grouped by status.
@@ -225,6 +235,15 @@ pub async fn get_summary(pool: &SqlitePool) -> sqlx::Result<HealthSummary> {
         }
     }
 
+    // The recoverable classes (see `ErrorClass::as_str`)
+    let (retryable,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM file_health
+         WHERE status = 'error' AND error_class IN ('network', 'locked', 'rate_limited')",
+    )
+    .fetch_one(pool)
+    .await?;
+    summary.retryable = retryable;
+
     Ok(summary)
 }
 
@@ -254,6 +273,7 @@ pub async fn has_file_changed(pool: &SqlitePool, path: &Path) -> sqlx::Result<bo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::ErrorClass;
     use tempfile::tempdir;
 
     #[tokio::test]
@@ -290,7 +310,15 @@ mod tests {
             .unwrap();
         upsert_health(
             &pool,
-            &FileHealth::error("/bad.mp3", ErrorType::DecodeError, "corrupt"),
+            &FileHealth::error("/bad.mp3", ErrorType::DecodeError, "corrupt")
+                .with_class(Some(ErrorClass::CORRUPT)),
+        )
+        .await
+        .unwrap();
+        upsert_health(
+            &pool,
+            &FileHealth::error("/busy.mp3", ErrorType::IoError, "in use")
+                .with_class(Some(ErrorClass::LOCKED)),
         )
         .await
         .unwrap();
@@ -299,10 +327,14 @@ mod tests {
             .unwrap();
 
         let summary = get_summary(&pool).await.unwrap();
-        assert_eq!(summary.total, 4);
+        assert_eq!(summary.total, 5);
         assert_eq!(summary.ok, 2);
-        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.errors, 2);
+        assert_eq!(summary.retryable, 1);
         assert_eq!(summary.no_match, 1);
+
+        let busy = get_health(&pool, "/busy.mp3").await.unwrap().unwrap();
+        assert_eq!(busy.error_class, Some(ErrorClass::LOCKED));
     }
 
     #[tokio::test]
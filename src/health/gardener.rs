@@ -28,7 +28,7 @@ use tokio::time::interval;
 use crate::db::{
     QualityStats, TrackWithMetadata, get_tracks_needing_quality_check, update_track_quality,
 };
-use crate::health::{TrackQuality, assess_quality};
+use crate::health::{QualityFlags, TrackQuality, assess_quality};
 
 /// Configuration for the quality gardener.
 #[derive(Debug, Clone)]
@@ -188,6 +188,11 @@ impl QualityGardener {
 
     /// Process a batch of unchecked tracks.
     async fn process_batch(&self) {
+        // Assessments are stored in the database, which read-only mode forbids
+        if crate::readonly::is_enabled() {
+            return;
+        }
+
         let tracks =
             match get_tracks_needing_quality_check(&self.pool, self.config.batch_size).await {
                 Ok(t) => t,
@@ -231,7 +236,11 @@ impl QualityGardener {
                 COALESCE(a.name, 'Unknown Artist') as artist_name,
                 COALESCE(al.title, 'Unknown Album') as album_name,
                 al.year,
-                t.quality_score, t.quality_flags
+                t.quality_score, t.quality_flags,
+                t.added_at, t.updated_at, t.track_number_inferred,
+                t.replaygain_track_gain AS track_gain, t.replaygain_track_peak AS track_peak,
+                t.leading_silence_ms, t.trailing_silence_ms,
+                t.language, t.explicit, t.numbering_inconsistent, t.source
             FROM tracks t
             LEFT JOIN artists a ON t.artist_id = a.id
             LEFT JOIN albums al ON t.album_id = al.id
@@ -279,15 +288,36 @@ impl QualityGardener {
             &track.title,
             artist,
             album,
-            track.year,
-            track.track_number,
+            track.year.map(i64::from),
+            track.track_number.map(i64::from),
             filename,
             None, // Will be filled by verification if enabled
             None, // Will be filled by verification if enabled
         );
+        if track.track_number_inferred {
+            quality.mark_track_number_inferred();
+        }
+        if track.numbering_inconsistent {
+            quality.mark_numbering_inconsistent();
+        }
+        if track
+            .quality_flags()
+            .contains(QualityFlags::MISLABELED_SUSPECT)
+        {
+            quality.mark_mislabeled_suspect();
+        }
+        quality.mark_loudness(
+            track.track_gain.map(f64::from),
+            track.track_peak.map(f64::from),
+        );
+        quality.mark_silence(
+            track.leading_silence_ms.map(i64::from),
+            track.trailing_silence_ms.map(i64::from),
+        );
 
-        // If fingerprinting is enabled, verify against AcoustID
+        // If fingerprinting is enabled, verify files against AcoustID
         if self.config.enable_fingerprinting
+            && track.source.is_local()
             && let Some(verification) = self.verify_track(track).await
         {
             // Update quality based on verification result
@@ -317,82 +347,32 @@ impl QualityGardener {
 
     /// Verify a track against fingerprint database.
     /// Returns None if verification couldn't be performed.
+    #[cfg(feature = "enrichment")]
     async fn verify_track(
         &self,
         track: &TrackWithMetadata,
     ) -> Option<crate::health::VerificationResult> {
-        use crate::enrichment::{acoustid, fingerprint};
+        use crate::enrichment::{acoustid, budget::CpuBudget};
         use crate::health::{
-            ExistingMetadata, FingerprintMatch, ReleaseInfo, ReleaseType, VerificationStatus,
-            verify_metadata,
+            ExistingMetadata, FingerprintMatch, VerificationStatus, verify_metadata,
         };
 
-        // Generate fingerprint (blocking operation)
-        let path = std::path::PathBuf::from(&track.path);
-        let fp_result =
-            tokio::task::spawn_blocking(move || fingerprint::generate_fingerprint(&path))
-                .await
-                .ok()?
-                .ok()?;
+        // Generate fingerprint (within the CPU budget)
+        let fp_result = CpuBudget::global()
+            .fingerprint(std::path::Path::new(&track.path))
+            .await
+            .ok()?;
 
         // Query AcoustID
         let api_key = std::env::var("ACOUSTID_API_KEY").ok()?;
         let client = acoustid::AcoustIdClient::new(&api_key);
         let identifications = client.lookup(&fp_result).await.ok()?;
 
-        // Build existing metadata
-        let existing = ExistingMetadata {
-            title: Some(track.title.clone()),
-            artist: if track.artist_name != "Unknown Artist" {
-                Some(track.artist_name.clone())
-            } else {
-                None
-            },
-            album: if track.album_name != "Unknown Album" {
-                Some(track.album_name.clone())
-            } else {
-                None
-            },
-            year: track.year.map(|y| y as i32),
-            track_number: track.track_number.map(|t| t as u32),
-            musicbrainz_recording_id: None, // Would need to track this in DB
-        };
-
-        // Convert TrackIdentification results to FingerprintMatch
+        let existing = ExistingMetadata::from_track(track);
         let matches: Vec<FingerprintMatch> = identifications
             .iter()
             .filter(|id| id.score > 0.5)
-            .map(|id| {
-                // Build release info from the identified track
-                let releases: Vec<ReleaseInfo> = if let (Some(release_id), Some(album)) =
-                    (id.track.release_id.clone(), id.track.album.clone())
-                {
-                    vec![ReleaseInfo {
-                        release_id,
-                        title: album,
-                        year: id.track.year,
-                        release_type: id
-                            .track
-                            .release_type
-                            .as_ref()
-                            .map(|t| ReleaseType::parse(t))
-                            .unwrap_or_default(),
-                        track_number: id.track.track_number,
-                        album_match_score: 0.0,
-                    }]
-                } else {
-                    vec![]
-                };
-
-                FingerprintMatch {
-                    confidence: id.score,
-                    recording_id: id.track.recording_id.clone().unwrap_or_default(),
-                    title: id.track.title.clone().unwrap_or_default(),
-                    artist: id.track.artist.clone().unwrap_or_default(),
-                    releases: releases.clone(),
-                    best_release: releases.into_iter().next(),
-                }
-            })
+            .map(FingerprintMatch::from_identification)
             .collect();
 
         if matches.is_empty() {
@@ -410,12 +390,21 @@ impl QualityGardener {
     }
 
     /// Apply verification results to quality assessment.
+    /// Builds without the `enrichment` feature can't look tracks up.
+    #[cfg(not(feature = "enrichment"))]
+    async fn verify_track(
+        &self,
+        _track: &TrackWithMetadata,
+    ) -> Option<crate::health::VerificationResult> {
+        None
+    }
+
     fn apply_verification_to_quality(
         &self,
         mut quality: TrackQuality,
         verification: &crate::health::VerificationResult,
     ) -> TrackQuality {
-        use crate::health::{QualityFlags, VerificationIssue, VerificationStatus};
+        use crate::health::{VerificationIssue, VerificationStatus};
 
         // Update confidence and MusicBrainz ID from best match
         if let Some(ref best) = verification.best_match {
@@ -519,22 +508,44 @@ pub fn assess_track_quality(track: &TrackWithMetadata) -> TrackQuality {
         Some(track.album_name.as_str())
     };
 
-    assess_quality(
+    let mut quality = assess_quality(
         &track.title,
         artist,
         album,
-        track.year,
-        track.track_number,
+        track.year.map(i64::from),
+        track.track_number.map(i64::from),
         filename,
         None,
         None,
-    )
+    );
+    if track.track_number_inferred {
+        quality.mark_track_number_inferred();
+    }
+    if track.numbering_inconsistent {
+        quality.mark_numbering_inconsistent();
+    }
+    if track
+        .quality_flags()
+        .contains(QualityFlags::MISLABELED_SUSPECT)
+    {
+        quality.mark_mislabeled_suspect();
+    }
+    quality.mark_loudness(
+        track.track_gain.map(f64::from),
+        track.track_peak.map(f64::from),
+    );
+    quality.mark_silence(
+        track.leading_silence_ms.map(i64::from),
+        track.trailing_silence_ms.map(i64::from),
+    );
+    quality
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::health::QualityFlags;
+    use crate::model::TrackSource;
 
     #[test]
     fn test_default_config() {
@@ -552,11 +563,22 @@ mod tests {
             path: "/music/queen/11_a_night_at_the_opera.mp3".to_string(),
             duration: Some(354),
             track_number: Some(11),
-            artist_name: "Queen".to_string(),
-            album_name: "A Night at the Opera".to_string(),
+            artist_name: "Queen".into(),
+            album_name: "A Night at the Opera".into(),
             year: Some(1975),
             quality_score: None,
             quality_flags: None,
+            added_at: None,
+            updated_at: None,
+            track_number_inferred: false,
+            track_gain: None,
+            track_peak: None,
+            leading_silence_ms: None,
+            trailing_silence_ms: None,
+            language: None,
+            explicit: None,
+            numbering_inconsistent: false,
+            source: TrackSource::Local,
         };
 
         let quality = assess_track_quality(&track);
@@ -580,11 +602,22 @@ mod tests {
             path: "/music/track01.mp3".to_string(),
             duration: Some(200),
             track_number: None,
-            artist_name: "Unknown Artist".to_string(),
-            album_name: "Unknown Album".to_string(),
+            artist_name: "Unknown Artist".into(),
+            album_name: "Unknown Album".into(),
             year: None,
             quality_score: None,
             quality_flags: None,
+            added_at: None,
+            updated_at: None,
+            track_number_inferred: false,
+            track_gain: None,
+            track_peak: None,
+            leading_silence_ms: None,
+            trailing_silence_ms: None,
+            language: None,
+            explicit: None,
+            numbering_inconsistent: false,
+            source: TrackSource::Local,
         };
 
         let quality = assess_track_quality(&track);
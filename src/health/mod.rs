@@ -59,5 +59,5 @@ pub use gardener::{
 // Re-export verification
 pub use verification::{
     ExistingMetadata, FingerprintMatch, ReleaseInfo, ReleaseType, VerificationIssue,
-    VerificationResult, VerificationStatus, verify_metadata,
+    VerificationResult, VerificationStatus, string_similarity, verify_metadata,
 };
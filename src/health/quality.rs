@@ -15,6 +15,8 @@
 //! - `no_musicbrainz_id` - No MusicBrainz ID for verification
 //! - `low_confidence` - Identification match was uncertain
 //! - `better_match_available` - A higher-confidence match exists
+//! - `numbering_inconsistent` - The album's track/disc numbering doesn't add up
+//! - `mislabeled_suspect` - "Verify tags" found the audio is another recording
 //!
 //! # Quality Score
 //!
@@ -78,6 +80,27 @@ bitflags! {
         const AMBIGUOUS_MATCH = 1 << 17;
         /// Recording appears on multiple albums (compilation candidate)
         const MULTI_ALBUM = 1 << 18;
+        /// Track number was guessed from the file name or folder order
+        const TRACK_NUM_INFERRED = 1 << 19;
+
+        // === Loudness ===
+        /// File has ReplayGain or R128 track gain tags
+        const HAS_LOUDNESS = 1 << 20;
+        /// Track gain or peak suggests a brickwall-limited master
+        const LOUD_MASTER = 1 << 21;
+
+        // === Audio content ===
+        /// More than 5 seconds of silence at the start or end
+        const LONG_SILENCE = 1 << 22;
+
+        // === Album ===
+        /// The album's track totals, numbering or disc tags don't add up
+        const NUMBERING_INCONSISTENT = 1 << 23;
+
+        // === Verify tags ===
+        /// The fingerprint names another recording than the title and
+        /// artist tags (kept until they change)
+        const MISLABELED_SUSPECT = 1 << 24;
 
         // === Composite flags for common checks ===
         /// Any mismatch between metadata and fingerprint
@@ -86,6 +109,7 @@ bitflags! {
             | Self::ALBUM_MISMATCH.bits();
         /// Any critical issue needing attention
         const NEEDS_REVIEW = Self::POSSIBLY_MISLABELED.bits()
+            | Self::MISLABELED_SUSPECT.bits()
             | Self::AMBIGUOUS_MATCH.bits()
             | Self::UNIDENTIFIED.bits();
     }
@@ -117,6 +141,18 @@ impl QualityFlags {
         if self.contains(Self::GENERIC_METADATA) {
             descs.push("Generic placeholder text");
         }
+        if self.contains(Self::TRACK_NUM_INFERRED) {
+            descs.push("Track number guessed from file name");
+        }
+        if self.contains(Self::LOUD_MASTER) {
+            descs.push("Loud master (heavily limited or clipping)");
+        }
+        if self.contains(Self::LONG_SILENCE) {
+            descs.push("Over 5s of silence at start or end");
+        }
+        if self.contains(Self::NUMBERING_INCONSISTENT) {
+            descs.push("Album track/disc numbering inconsistent");
+        }
 
         // Identification status
         if self.contains(Self::NO_MUSICBRAINZ_ID) {
@@ -148,6 +184,9 @@ impl QualityFlags {
         if self.contains(Self::POSSIBLY_MISLABELED) {
             descs.push("⚠ Possibly mislabeled");
         }
+        if self.contains(Self::MISLABELED_SUSPECT) {
+            descs.push("⚠ Audio is another recording than the tags name");
+        }
         if self.contains(Self::VERIFIED) {
             descs.push("✓ Verified");
         }
@@ -167,7 +206,7 @@ impl QualityFlags {
     /// Get a short summary icon for display.
     #[allow(clippy::if_same_then_else)]
     pub fn summary_icon(&self) -> &'static str {
-        if self.contains(Self::POSSIBLY_MISLABELED) {
+        if self.intersects(Self::POSSIBLY_MISLABELED | Self::MISLABELED_SUSPECT) {
             "⚠" // Mislabeled - needs attention
         } else if self.contains(Self::VERIFIED) && self.intersection(Self::ANY_MISMATCH).is_empty()
         {
@@ -212,6 +251,48 @@ pub struct TrackQuality {
 }
 
 impl TrackQuality {
+    /// Note that the track number was guessed rather than read from tags.
+    pub fn mark_track_number_inferred(&mut self) {
+        self.flags |= QualityFlags::TRACK_NUM_INFERRED;
+        self.score = self.score.saturating_sub(3);
+    }
+
+    /// Note that the album's numbering failed the numbering audit.
+    pub fn mark_numbering_inconsistent(&mut self) {
+        self.flags |= QualityFlags::NUMBERING_INCONSISTENT;
+        self.score = self.score.saturating_sub(3);
+    }
+
+    /// Note that "Verify tags" found the audio is another recording.
+    pub fn mark_mislabeled_suspect(&mut self) {
+        self.flags |= QualityFlags::MISLABELED_SUSPECT;
+        self.score = self.score.saturating_sub(20);
+    }
+
+    /// Note the track's ReplayGain values. Loudness doesn't affect the
+    /// score; a loud master is a mastering choice, not a tagging problem.
+    pub fn mark_loudness(&mut self, track_gain: Option<f64>, track_peak: Option<f64>) {
+        if track_gain.is_some() {
+            self.flags |= QualityFlags::HAS_LOUDNESS;
+        }
+        if crate::metadata::loudness::is_loud_master(
+            track_gain.map(|g| g as f32),
+            track_peak.map(|p| p as f32),
+        ) {
+            self.flags |= QualityFlags::LOUD_MASTER;
+        }
+    }
+
+    /// Note silence measured at the track's ends. Like loudness, it
+    /// doesn't affect the score.
+    pub fn mark_silence(&mut self, leading_ms: Option<i64>, trailing_ms: Option<i64>) {
+        if let (Some(leading), Some(trailing)) = (leading_ms, trailing_ms)
+            && crate::player::silence::Silence::from_millis(leading, trailing).is_long()
+        {
+            self.flags |= QualityFlags::LONG_SILENCE;
+        }
+    }
+
     /// Check if this track needs attention.
     pub fn needs_attention(&self) -> bool {
         self.score < 70
@@ -465,6 +546,31 @@ mod tests {
         assert!(quality.needs_attention());
     }
 
+    #[test]
+    fn test_mark_loudness() {
+        let mut quality = assess_quality(
+            "Title",
+            Some("Artist"),
+            Some("Album"),
+            Some(2008),
+            Some(1),
+            "01 title",
+            None,
+            None,
+        );
+        let score = quality.score;
+
+        quality.mark_loudness(Some(-12.4), Some(0.99));
+        assert!(quality.flags.contains(QualityFlags::HAS_LOUDNESS));
+        assert!(quality.flags.contains(QualityFlags::LOUD_MASTER));
+        assert_eq!(quality.score, score);
+
+        let mut untagged = quality.clone();
+        untagged.flags = QualityFlags::empty();
+        untagged.mark_loudness(None, None);
+        assert!(untagged.flags.is_empty());
+    }
+
     #[test]
     fn test_quality_flags_roundtrip() {
         let flags = QualityFlags::MISSING_ARTIST | QualityFlags::LOW_CONFIDENCE;
@@ -9,6 +9,7 @@ use chrono::{DateTime, Utc};
 use std::path::Path;
 
 use super::hash::compute_file_hash;
+use crate::error::ErrorClass;
 
 /// Health status of an audio file.
 ///
@@ -130,6 +131,8 @@ pub struct FileHealth {
     pub error_type: Option<ErrorType>,
     /// Error message details
     pub error_message: Option<String>,
+    /// Whether the error is worth retrying, and why it happened
+    pub error_class: Option<ErrorClass>,
     /// AcoustID fingerprint
     pub acoustid_fingerprint: Option<String>,
     /// AcoustID match confidence (0.0-1.0)
@@ -159,6 +162,7 @@ impl FileHealth {
             status: HealthStatus::Ok,
             error_type: None,
             error_message: None,
+            error_class: None,
             acoustid_fingerprint: None,
             acoustid_confidence: Some(confidence),
             musicbrainz_id,
@@ -186,6 +190,7 @@ impl FileHealth {
             status: HealthStatus::Error,
             error_type: Some(error_type),
             error_message: Some(message.into()),
+            error_class: None,
             acoustid_fingerprint: None,
             acoustid_confidence: None,
             musicbrainz_id: None,
@@ -207,6 +212,7 @@ impl FileHealth {
             status: HealthStatus::NoMatch,
             error_type: None,
             error_message: None,
+            error_class: None,
             acoustid_fingerprint: None,
             acoustid_confidence: None,
             musicbrainz_id: None,
@@ -229,6 +235,7 @@ impl FileHealth {
             status: HealthStatus::LowConfidence,
             error_type: None,
             error_message: None,
+            error_class: None,
             acoustid_fingerprint: None,
             acoustid_confidence: Some(confidence),
             musicbrainz_id: None,
@@ -251,6 +258,17 @@ impl FileHealth {
         self
     }
 
+    /// Add the error's class to the record.
+    pub fn with_class(mut self, class: Option<ErrorClass>) -> Self {
+        self.error_class = class;
+        self
+    }
+
+    /// Whether checking again later might succeed
+    pub fn is_retryable(&self) -> bool {
+        self.error_class.is_some_and(|c| c.is_recoverable())
+    }
+
     /// Add fingerprint to the record.
     pub fn with_fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
         self.acoustid_fingerprint = Some(fingerprint.into());
@@ -305,5 +323,11 @@ mod tests {
         assert_eq!(health.status, HealthStatus::Error);
         assert_eq!(health.error_type, Some(ErrorType::DecodeError));
         assert_eq!(health.error_message, Some("corrupt audio".to_string()));
+        assert!(!health.is_retryable());
+        assert!(
+            FileHealth::error("/test/file.mp3", ErrorType::Timeout, "timed out")
+                .with_class(Some(ErrorClass::NETWORK))
+                .is_retryable()
+        );
     }
 }
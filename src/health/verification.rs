@@ -86,6 +86,21 @@ pub struct ExistingMetadata {
     pub musicbrainz_recording_id: Option<String>,
 }
 
+impl ExistingMetadata {
+    /// The tags the library holds for a track ("Unknown" names count as
+    /// missing)
+    pub fn from_track(track: &crate::db::TrackWithMetadata) -> Self {
+        Self {
+            title: Some(track.title.clone()),
+            artist: (track.artist_name != "Unknown Artist").then(|| track.artist_name.to_string()),
+            album: (track.album_name != "Unknown Album").then(|| track.album_name.to_string()),
+            year: track.year,
+            track_number: track.track_number,
+            musicbrainz_recording_id: None, // Would need to track this in DB
+        }
+    }
+}
+
 /// A fingerprint match from AcoustID/MusicBrainz.
 #[derive(Debug, Clone)]
 pub struct FingerprintMatch {
@@ -103,6 +118,40 @@ pub struct FingerprintMatch {
     pub best_release: Option<ReleaseInfo>,
 }
 
+impl FingerprintMatch {
+    /// A match from an identification, with its release (if it has one)
+    pub fn from_identification(identification: &crate::enrichment::TrackIdentification) -> Self {
+        let track = &identification.track;
+        let releases: Vec<ReleaseInfo> = if let (Some(release_id), Some(album)) =
+            (track.release_id.clone(), track.album.clone())
+        {
+            vec![ReleaseInfo {
+                release_id,
+                title: album,
+                year: track.year,
+                release_type: track
+                    .release_type
+                    .as_ref()
+                    .map(|t| ReleaseType::parse(t))
+                    .unwrap_or_default(),
+                track_number: track.track_number,
+                album_match_score: 0.0,
+            }]
+        } else {
+            vec![]
+        };
+
+        Self {
+            confidence: identification.score,
+            recording_id: track.recording_id.clone().unwrap_or_default(),
+            title: track.title.clone().unwrap_or_default(),
+            artist: track.artist.clone().unwrap_or_default(),
+            releases: releases.clone(),
+            best_release: releases.into_iter().next(),
+        }
+    }
+}
+
 /// Information about a release (album/single/compilation).
 #[derive(Debug, Clone)]
 pub struct ReleaseInfo {
@@ -246,91 +295,10 @@ impl VerificationIssue {
 
 /// Compare two strings for similarity (0.0-1.0).
 ///
-/// Uses normalized Levenshtein distance with some music-specific handling:
-/// - Ignores case
-/// - Ignores common variations like "The Beatles" vs "Beatles"
-/// - Handles featuring artists: "Song (feat. X)" ≈ "Song"
+/// Fuzzy, after music-specific normalization (case, diacritics, "The",
+/// featured artists, remaster notes); see [`crate::enrichment::similarity`].
 pub fn string_similarity(a: &str, b: &str) -> f32 {
-    let a = normalize_for_comparison(a);
-    let b = normalize_for_comparison(b);
-
-    if a == b {
-        return 1.0;
-    }
-
-    if a.is_empty() || b.is_empty() {
-        return 0.0;
-    }
-
-    // Simple Levenshtein-based similarity
-    let distance = levenshtein_distance(&a, &b);
-    let max_len = a.len().max(b.len());
-    1.0 - (distance as f32 / max_len as f32)
-}
-
-/// Normalize a string for comparison.
-fn normalize_for_comparison(s: &str) -> String {
-    let mut result = s.to_lowercase();
-
-    // Remove common prefixes
-    for prefix in &["the ", "a ", "an "] {
-        if result.starts_with(prefix) {
-            result = result[prefix.len()..].to_string();
-        }
-    }
-
-    // Remove featuring suffixes
-    for pattern in &[" (feat.", " (ft.", " feat.", " ft.", " featuring "] {
-        if let Some(pos) = result.find(pattern) {
-            result = result[..pos].to_string();
-        }
-    }
-
-    // Remove extra whitespace and punctuation
-    result
-        .chars()
-        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
-        .collect::<String>()
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ")
-}
-
-/// Simple Levenshtein distance.
-fn levenshtein_distance(a: &str, b: &str) -> usize {
-    let a: Vec<char> = a.chars().collect();
-    let b: Vec<char> = b.chars().collect();
-    let m = a.len();
-    let n = b.len();
-
-    if m == 0 {
-        return n;
-    }
-    if n == 0 {
-        return m;
-    }
-
-    let mut dp = vec![vec![0usize; n + 1]; m + 1];
-
-    #[allow(clippy::needless_range_loop)]
-    for i in 0..=m {
-        dp[i][0] = i;
-    }
-    #[allow(clippy::needless_range_loop)]
-    for j in 0..=n {
-        dp[0][j] = j;
-    }
-
-    for i in 1..=m {
-        for j in 1..=n {
-            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
-            dp[i][j] = (dp[i - 1][j] + 1)
-                .min(dp[i][j - 1] + 1)
-                .min(dp[i - 1][j - 1] + cost);
-        }
-    }
-
-    dp[m][n]
+    crate::enrichment::similarity::similarity(a, b)
 }
 
 /// Verify a track by comparing metadata against fingerprint results.
@@ -473,16 +441,6 @@ mod tests {
         assert!(string_similarity("completely different", "nothing alike") < 0.3);
     }
 
-    #[test]
-    fn test_normalize_for_comparison() {
-        assert_eq!(normalize_for_comparison("The Beatles"), "beatles");
-        assert_eq!(normalize_for_comparison("Song (feat. Guest)"), "song");
-        assert_eq!(
-            normalize_for_comparison("  Multiple   Spaces  "),
-            "multiple spaces"
-        );
-    }
-
     #[test]
     fn test_verify_empty_matches() {
         let existing = ExistingMetadata::default();
@@ -0,0 +1,535 @@
+//! Playback history and the last listening session.
+//!
+//! Every library track that starts playing is appended to `play_history`,
+//! which backs the "recently played" lists. When it stops or the next one
+//! starts, the time actually listened ([`ListenClock`]) and its genre are
+//! stored with the play for the usage statistics. A play left before
+//! [`SKIP_BEFORE`] of the track is marked skipped, giving each track a skip
+//! rate ([`skip_rates`], [`often_skipped`]). Separately, the queue and the
+//! playback position are saved to a small per-profile file so the next launch
+//! can offer to pick up where the user left off. [`radio`] picks tracks to
+//! carry on with once the queue runs out, leaving out recent plays.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::history::{LastSession, recent_albums};
+//!
+//! let albums = recent_albums(&pool, 5).await?;
+//! if let Some(session) = LastSession::load() {
+//!     println!("Last played {:?}", session.current_track());
+//! }
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub mod radio;
+
+// ============================================================================
+// Play History
+// ============================================================================
+
+/// An album from the playback history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecentAlbum {
+    /// Album title
+    pub album: String,
+    /// Album artist (or "Unknown Artist")
+    pub artist: String,
+    /// When a track from the album last started playing
+    pub last_played: DateTime<Utc>,
+}
+
+/// Database row for [`recent_albums`].
+#[derive(Debug, sqlx::FromRow)]
+struct RecentAlbumRow {
+    album: String,
+    artist: String,
+    last_played: i64,
+}
+
+/// Record that the track at `path` started playing.
+///
+/// Files that aren't in the library are ignored. Returns whether a play was
+/// recorded.
+pub async fn record_play(pool: &SqlitePool, path: &Path) -> sqlx::Result<bool> {
+    let result = sqlx::query(
+        "INSERT INTO play_history (track_id, played_at) SELECT id, ? FROM tracks WHERE path_key = ?",
+    )
+    .bind(Utc::now().timestamp())
+    .bind(crate::db::paths::key(&path.to_string_lossy()))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Store how long the latest play of the track at `path` lasted, and its
+/// genre tag.
+///
+/// Called with the running total whenever playback pauses or moves on, so
+/// it replaces the previous value rather than adding to it.
+pub async fn record_listened(
+    pool: &SqlitePool,
+    path: &Path,
+    listened: Duration,
+    genre: Option<&str>,
+) -> sqlx::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE play_history SET listened_secs = ?, genre = ?
+        WHERE id = (
+            SELECT MAX(h.id) FROM play_history h
+            JOIN tracks t ON h.track_id = t.id
+            WHERE t.path_key = ?
+        )
+        "#,
+    )
+    .bind(listened.as_secs() as i64)
+    .bind(genre)
+    .bind(crate::db::paths::key(&path.to_string_lossy()))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Time actually listened to the current track.
+///
+/// Fed every position update: forward steps of up to [`ListenClock::MAX_STEP`]
+/// count, anything else (a seek either way) only moves the clock to the new
+/// position.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ListenClock {
+    pub listened: Duration,
+    last: Option<Duration>,
+}
+
+impl ListenClock {
+    /// Largest gap between position updates still counted as playing
+    pub const MAX_STEP: Duration = Duration::from_secs(3);
+
+    pub fn advance(&mut self, position: Duration) {
+        if let Some(last) = self.last
+            && let Some(step) = position.checked_sub(last)
+            && step <= Self::MAX_STEP
+        {
+            self.listened += step;
+        }
+        self.last = Some(position);
+    }
+
+    /// Stop counting until the next update (paused: the next position is a
+    /// fresh start, not a step)
+    pub fn pause(&mut self) {
+        self.last = None;
+    }
+}
+
+// ============================================================================
+// Skips
+// ============================================================================
+
+/// A play left before this share of the track is a skip
+pub const SKIP_BEFORE: f64 = 0.3;
+
+/// Plays a track needs before its skip rate means anything
+pub const MIN_PLAYS_FOR_RATE: i64 = 3;
+
+/// Skip rate from which a track counts as often skipped
+pub const OFTEN_SKIPPED_RATE: f64 = 0.5;
+
+/// Whether leaving a track at `position` skips it. Tracks of unknown
+/// length are never skipped.
+pub fn is_skip(position: Duration, duration: Duration) -> bool {
+    !duration.is_zero() && position.as_secs_f64() < duration.as_secs_f64() * SKIP_BEFORE
+}
+
+/// Mark the latest play of the track at `path` as skipped.
+pub async fn record_skip(pool: &SqlitePool, path: &Path) -> sqlx::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE play_history SET skipped = 1
+        WHERE id = (
+            SELECT MAX(h.id) FROM play_history h
+            JOIN tracks t ON h.track_id = t.id
+            WHERE t.path_key = ?
+        )
+        "#,
+    )
+    .bind(crate::db::paths::key(&path.to_string_lossy()))
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// How often a track is skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct SkipRate {
+    pub track_id: i64,
+    pub title: String,
+    /// Track artist (or "Unknown Artist")
+    pub artist: String,
+    pub plays: i64,
+    pub skips: i64,
+}
+
+impl SkipRate {
+    /// Share of plays skipped
+    pub fn rate(&self) -> f64 {
+        if self.plays == 0 {
+            0.0
+        } else {
+            self.skips as f64 / self.plays as f64
+        }
+    }
+}
+
+/// Most skipped tracks among plays since `since` (Unix time), highest skip
+/// rate first. Tracks with fewer than [`MIN_PLAYS_FOR_RATE`] plays, or
+/// never skipped, are left out.
+pub async fn skip_rates(pool: &SqlitePool, since: i64, limit: i64) -> sqlx::Result<Vec<SkipRate>> {
+    sqlx::query_as(
+        r#"
+        SELECT
+            t.id AS track_id,
+            t.title,
+            COALESCE(a.name, 'Unknown Artist') AS artist,
+            COUNT(*) AS plays,
+            SUM(h.skipped) AS skips
+        FROM play_history h
+        JOIN tracks t ON h.track_id = t.id
+        LEFT JOIN artists a ON t.artist_id = a.id
+        WHERE h.played_at >= ?
+        GROUP BY t.id
+        HAVING COUNT(*) >= ? AND SUM(h.skipped) > 0
+        ORDER BY CAST(SUM(h.skipped) AS REAL) / COUNT(*) DESC, skips DESC, t.title
+        LIMIT ?
+        "#,
+    )
+    .bind(since)
+    .bind(MIN_PLAYS_FOR_RATE)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// IDs of the tracks skipped at least [`OFTEN_SKIPPED_RATE`] of the time,
+/// over all their plays.
+pub async fn often_skipped(pool: &SqlitePool) -> sqlx::Result<HashSet<i64>> {
+    let ids: Vec<(i64,)> = sqlx::query_as(
+        "SELECT track_id FROM play_history GROUP BY track_id
+         HAVING COUNT(*) >= ? AND SUM(skipped) >= ? * COUNT(*)",
+    )
+    .bind(MIN_PLAYS_FOR_RATE)
+    .bind(OFTEN_SKIPPED_RATE)
+    .fetch_all(pool)
+    .await?;
+    Ok(ids.into_iter().map(|(id,)| id).collect())
+}
+
+/// Albums played most recently, newest first.
+pub async fn recent_albums(pool: &SqlitePool, limit: u32) -> sqlx::Result<Vec<RecentAlbum>> {
+    let rows: Vec<RecentAlbumRow> = sqlx::query_as(
+        r#"
+        SELECT
+            al.title AS album,
+            COALESCE(a.name, 'Unknown Artist') AS artist,
+            MAX(h.played_at) AS last_played
+        FROM play_history h
+        JOIN tracks t ON h.track_id = t.id
+        JOIN albums al ON t.album_id = al.id
+        LEFT JOIN artists a ON t.artist_id = a.id
+        GROUP BY al.id, a.id
+        ORDER BY last_played DESC, MAX(h.id) DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(i64::from(limit))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| RecentAlbum {
+            album: row.album,
+            artist: row.artist,
+            last_played: DateTime::from_timestamp(row.last_played, 0).unwrap_or_default(),
+        })
+        .collect())
+}
+
+// ============================================================================
+// Last Session
+// ============================================================================
+
+/// The queue and playback position when the app was last used.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastSession {
+    /// Queue contents in queue order
+    pub queue: Vec<PathBuf>,
+    /// Index of the track that was playing
+    pub current: Option<usize>,
+    /// Playback position within that track, in seconds
+    pub position_secs: f64,
+    /// Length of that track, in seconds (0 if unknown)
+    pub duration_secs: f64,
+    /// When the session was saved (Unix timestamp)
+    pub saved_at: i64,
+}
+
+impl LastSession {
+    /// File name, relative to the active profile's data directory
+    const FILE: &'static str = "music_minder_session.json";
+
+    /// Load the last session of the active profile, if there is one to resume
+    pub fn load() -> Option<Self> {
+        Self::load_from(&crate::profile::data_path(Self::FILE))
+    }
+
+    fn load_from(path: &Path) -> Option<Self> {
+        let session: Self = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+        session.current_track().is_some().then_some(session)
+    }
+
+    /// Save this session for the active profile
+    pub fn save(&self) -> std::io::Result<()> {
+        self.save_to(&crate::profile::data_path(Self::FILE))
+    }
+
+    fn save_to(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+    }
+
+    /// The track that was playing
+    pub fn current_track(&self) -> Option<&PathBuf> {
+        self.queue.get(self.current?)
+    }
+
+    /// Playback position as a fraction of the track, for seeking on resume
+    pub fn position_fraction(&self) -> Option<f32> {
+        (self.duration_secs > 0.0)
+            .then(|| (self.position_secs / self.duration_secs).clamp(0.0, 1.0) as f32)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::metadata::TrackMetadata;
+
+    async fn test_pool(dir: &Path) -> SqlitePool {
+        let db_url = format!("sqlite:{}", dir.join("test.db").display());
+        db::init_db(&db_url).await.unwrap()
+    }
+
+    fn meta(title: &str, album: &str) -> TrackMetadata {
+        TrackMetadata {
+            title: title.to_string(),
+            artist: "Artist".to_string(),
+            album: album.to_string(),
+            duration: 180,
+            track_number: Some(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recent_albums_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+        for (title, album, path) in [
+            ("One", "First", "/m/1.mp3"),
+            ("Two", "First", "/m/2.mp3"),
+            ("Three", "Second", "/m/3.mp3"),
+        ] {
+            let artist_id = db::get_or_create_artist(&pool, "Artist").await.unwrap();
+            let album_id = db::get_or_create_album(&pool, album, Some(artist_id))
+                .await
+                .unwrap();
+            db::insert_track(
+                &pool,
+                &meta(title, album),
+                path,
+                Some(artist_id),
+                Some(album_id),
+            )
+            .await
+            .unwrap();
+        }
+
+        assert!(record_play(&pool, Path::new("/m/3.mp3")).await.unwrap());
+        assert!(record_play(&pool, Path::new("/m/1.mp3")).await.unwrap());
+        assert!(record_play(&pool, Path::new("/m/2.mp3")).await.unwrap());
+        assert!(
+            !record_play(&pool, Path::new("/elsewhere.mp3"))
+                .await
+                .unwrap()
+        );
+
+        let albums = recent_albums(&pool, 10).await.unwrap();
+        let names: Vec<&str> = albums.iter().map(|a| a.album.as_str()).collect();
+        assert_eq!(names, ["First", "Second"]);
+        assert_eq!(albums[0].artist, "Artist");
+        assert_eq!(recent_albums(&pool, 1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_history_removed_with_track() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+        let album_id = db::get_or_create_album(&pool, "First", None).await.unwrap();
+        db::insert_track(
+            &pool,
+            &meta("One", "First"),
+            "/m/1.mp3",
+            None,
+            Some(album_id),
+        )
+        .await
+        .unwrap();
+        record_play(&pool, Path::new("/m/1.mp3")).await.unwrap();
+        assert_eq!(recent_albums(&pool, 10).await.unwrap().len(), 1);
+
+        db::delete_track_by_path(&pool, "/m/1.mp3").await.unwrap();
+
+        assert!(recent_albums(&pool, 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_listened_time_goes_on_the_latest_play() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+        db::insert_track(&pool, &meta("One", "First"), "/m/1.mp3", None, None)
+            .await
+            .unwrap();
+        let path = Path::new("/m/1.mp3");
+        record_play(&pool, path).await.unwrap();
+        record_listened(&pool, path, Duration::from_secs(30), Some("Jazz"))
+            .await
+            .unwrap();
+        record_play(&pool, path).await.unwrap();
+        record_listened(&pool, path, Duration::from_secs(90), None)
+            .await
+            .unwrap();
+
+        let listened: Vec<(Option<i64>, Option<String>)> =
+            sqlx::query_as("SELECT listened_secs, genre FROM play_history ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            listened,
+            [(Some(30), Some("Jazz".to_string())), (Some(90), None)]
+        );
+        assert!(
+            !record_listened(&pool, Path::new("/m/none.mp3"), Duration::ZERO, None)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_is_skip() {
+        let secs = Duration::from_secs;
+        assert!(is_skip(secs(10), secs(200)));
+        assert!(!is_skip(secs(60), secs(200)));
+        assert!(!is_skip(secs(0), Duration::ZERO), "unknown length");
+    }
+
+    #[tokio::test]
+    async fn test_skip_rates() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+        let mut ids = Vec::new();
+        for (title, path) in [
+            ("One", "/m/1.mp3"),
+            ("Two", "/m/2.mp3"),
+            ("Three", "/m/3.mp3"),
+        ] {
+            ids.push(
+                db::insert_track(&pool, &meta(title, "First"), path, None, None)
+                    .await
+                    .unwrap(),
+            );
+        }
+        // One: skipped 2 of 3, Two: 1 of 4, Three: skipped but played twice
+        for (path, plays, skips) in [("/m/1.mp3", 3, 2), ("/m/2.mp3", 4, 1), ("/m/3.mp3", 2, 2)] {
+            let path = Path::new(path);
+            for play in 0..plays {
+                record_play(&pool, path).await.unwrap();
+                if play < skips {
+                    assert!(record_skip(&pool, path).await.unwrap());
+                }
+            }
+        }
+        assert!(!record_skip(&pool, Path::new("/m/none.mp3")).await.unwrap());
+
+        let rates = skip_rates(&pool, 0, 10).await.unwrap();
+        let names: Vec<(&str, i64, i64)> = rates
+            .iter()
+            .map(|r| (r.title.as_str(), r.plays, r.skips))
+            .collect();
+        assert_eq!(names, [("One", 3, 2), ("Two", 4, 1)]);
+        assert!((rates[1].rate() - 0.25).abs() < 1e-9);
+
+        let often = often_skipped(&pool).await.unwrap();
+        assert_eq!(often, HashSet::from([ids[0]]));
+    }
+
+    #[test]
+    fn test_listen_clock_skips_seeks_and_pauses() {
+        let secs = Duration::from_secs;
+        let mut clock = ListenClock::default();
+        for position in [0, 1, 2, 3] {
+            clock.advance(secs(position));
+        }
+        assert_eq!(clock.listened, secs(3));
+
+        // Seeking forward or back isn't listening
+        clock.advance(secs(60));
+        clock.advance(secs(61));
+        clock.advance(secs(10));
+        assert_eq!(clock.listened, secs(4));
+
+        // Neither is the time spent paused
+        clock.pause();
+        clock.advance(secs(12));
+        clock.advance(secs(13));
+        assert_eq!(clock.listened, secs(5));
+    }
+
+    #[test]
+    fn test_last_session_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        assert_eq!(LastSession::load_from(&path), None);
+
+        let session = LastSession {
+            queue: vec![PathBuf::from("/m/1.mp3"), PathBuf::from("/m/2.mp3")],
+            current: Some(1),
+            position_secs: 45.0,
+            duration_secs: 180.0,
+            saved_at: 1_700_000_000,
+        };
+        session.save_to(&path).unwrap();
+
+        let loaded = LastSession::load_from(&path).unwrap();
+        assert_eq!(loaded, session);
+        assert_eq!(loaded.current_track(), Some(&PathBuf::from("/m/2.mp3")));
+        assert_eq!(loaded.position_fraction(), Some(0.25));
+
+        // Nothing to resume without a current track
+        LastSession::default().save_to(&path).unwrap();
+        assert_eq!(LastSession::load_from(&path), None);
+    }
+}
@@ -0,0 +1,281 @@
+//! Tracks to carry on with once the queue runs out (see
+//! [`crate::player::EndOfQueue`]).
+//!
+//! "Similar" picks tracks sharing a genre with the last one in the queue
+//! (or by the same artist when it has no genre), closest first in loudness
+//! (ReplayGain) and year. "Library radio" picks at random. Both leave out
+//! tracks already queued and those played in the last [`RECENT_DAYS`] days,
+//! unless that leaves nothing to play.
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::metadata::genres;
+use crate::player::EndOfQueue;
+
+/// Tracks added each time the queue runs out
+pub const BATCH: usize = 10;
+
+/// Tracks played this recently aren't picked
+pub const RECENT_DAYS: i64 = 3;
+
+/// A library track that could be picked
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub path: String,
+    pub artist: String,
+    pub genre: Option<String>,
+    /// ReplayGain track gain in dB
+    pub gain: Option<f32>,
+    pub year: Option<i32>,
+    /// Played in the last [`RECENT_DAYS`] days
+    pub recent: bool,
+}
+
+impl Candidate {
+    fn genre_keys(&self) -> HashSet<String> {
+        genres::split(self.genre.as_deref().unwrap_or_default())
+            .iter()
+            .map(|g| genres::spelling_key(g))
+            .collect()
+    }
+}
+
+/// How alike `candidate` is to `seed`, higher is closer; `None` when it
+/// shares neither a genre nor (for a seed without genres) the artist
+fn likeness(seed: &Candidate, seed_genres: &HashSet<String>, candidate: &Candidate) -> Option<f32> {
+    let shared = seed_genres.intersection(&candidate.genre_keys()).count();
+    let same_artist = seed.artist.eq_ignore_ascii_case(&candidate.artist);
+    if shared == 0 && !(seed_genres.is_empty() && same_artist) {
+        return None;
+    }
+    let mut score = shared as f32 * 2.0;
+    // A few dB of loudness or a few years apart still count as close
+    if let (Some(a), Some(b)) = (seed.gain, candidate.gain) {
+        score -= ((a - b).abs() / 6.0).min(1.5);
+    }
+    if let (Some(a), Some(b)) = (seed.year, candidate.year) {
+        score -= ((a - b).abs() as f32 / 10.0).min(1.5);
+    }
+    Some(score)
+}
+
+/// Pick up to `count` tracks as `mode` would, from `candidates`, after
+/// `seed` (the last track in the queue). Queued tracks should already be
+/// left out of `candidates`.
+pub fn pick(
+    mode: EndOfQueue,
+    seed: Option<&Candidate>,
+    mut candidates: Vec<Candidate>,
+    count: usize,
+    rng: &mut impl Rng,
+) -> Vec<PathBuf> {
+    if let Some(seed) = seed {
+        candidates.retain(|c| c.path != seed.path);
+    }
+    // Recent plays only when there's nothing else
+    if candidates.iter().any(|c| !c.recent) {
+        candidates.retain(|c| !c.recent);
+    }
+    candidates.shuffle(rng);
+
+    let picked: Vec<Candidate> = match (mode, seed) {
+        (EndOfQueue::Similar, Some(seed)) => {
+            let seed_genres = seed.genre_keys();
+            let mut scored: Vec<(f32, Candidate)> = candidates
+                .into_iter()
+                .filter_map(|c| likeness(seed, &seed_genres, &c).map(|score| (score, c)))
+                .collect();
+            // Shuffled first, so equally close tracks come in random order
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+            scored.into_iter().take(count).map(|(_, c)| c).collect()
+        }
+        (EndOfQueue::Similar | EndOfQueue::Radio, _) => {
+            candidates.into_iter().take(count).collect()
+        }
+        (EndOfQueue::Stop | EndOfQueue::Repeat, _) => vec![],
+    };
+    picked.into_iter().map(|c| PathBuf::from(c.path)).collect()
+}
+
+/// Library tracks to add to a queue that ran out after `last`, leaving out
+/// `queued` (path keys)
+pub async fn continuation(
+    pool: &SqlitePool,
+    mode: EndOfQueue,
+    last: Option<&Path>,
+    queued: &HashSet<String>,
+) -> sqlx::Result<Vec<PathBuf>> {
+    if !mode.adds_tracks() {
+        return Ok(vec![]);
+    }
+    let since = chrono::Utc::now().timestamp() - RECENT_DAYS * 24 * 60 * 60;
+    let rows: Vec<(Candidate, String)> = sqlx::query_as::<_, CandidateRow>(
+        r#"
+        SELECT t.path, t.path_key, COALESCE(a.name, '') AS artist, t.genre,
+            t.replaygain_track_gain AS gain, al.year,
+            EXISTS (
+                SELECT 1 FROM play_history h WHERE h.track_id = t.id AND h.played_at >= ?
+            ) AS recent
+        FROM tracks t
+        LEFT JOIN artists a ON t.artist_id = a.id
+        LEFT JOIN albums al ON t.album_id = al.id
+        "#,
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(CandidateRow::split)
+    .collect();
+
+    let last_key = last.map(|p| crate::db::paths::key(&p.to_string_lossy()));
+    let seed = rows
+        .iter()
+        .find(|(_, key)| Some(key) == last_key.as_ref())
+        .map(|(c, _)| c.clone());
+    let candidates = rows
+        .into_iter()
+        .filter(|(_, key)| !queued.contains(key))
+        .map(|(c, _)| c)
+        .collect();
+    Ok(pick(
+        mode,
+        seed.as_ref(),
+        candidates,
+        BATCH,
+        &mut rand::rng(),
+    ))
+}
+
+/// Row of [`continuation`]'s query
+#[derive(sqlx::FromRow)]
+struct CandidateRow {
+    path: String,
+    path_key: Option<String>,
+    artist: String,
+    genre: Option<String>,
+    gain: Option<f32>,
+    year: Option<i32>,
+    recent: bool,
+}
+
+impl CandidateRow {
+    fn split(self) -> (Candidate, String) {
+        let key = self
+            .path_key
+            .unwrap_or_else(|| crate::db::paths::key(&self.path));
+        (
+            Candidate {
+                path: self.path,
+                artist: self.artist,
+                genre: self.genre,
+                gain: self.gain,
+                year: self.year,
+                recent: self.recent,
+            },
+            key,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn track(path: &str, artist: &str, genre: Option<&str>, gain: f32, year: i32) -> Candidate {
+        Candidate {
+            path: path.to_string(),
+            artist: artist.to_string(),
+            genre: genre.map(String::from),
+            gain: Some(gain),
+            year: Some(year),
+            recent: false,
+        }
+    }
+
+    #[test]
+    fn test_pick_similar_and_radio() {
+        let seed = track("seed", "A", Some("Jazz; Bebop"), -8.0, 1959);
+        let library = vec![
+            track("close", "B", Some("bebop"), -8.5, 1960),
+            track("far", "C", Some("Jazz"), -2.0, 2015),
+            track("rock", "A", Some("Rock"), -8.0, 1959),
+            Candidate {
+                recent: true,
+                ..track("played", "D", Some("Jazz; Bebop"), -8.0, 1959)
+            },
+        ];
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let similar = pick(
+            EndOfQueue::Similar,
+            Some(&seed),
+            library.clone(),
+            5,
+            &mut rng,
+        );
+        assert_eq!(similar, [PathBuf::from("close"), PathBuf::from("far")]);
+
+        let radio = pick(EndOfQueue::Radio, Some(&seed), library.clone(), 5, &mut rng);
+        assert_eq!(radio.len(), 3, "the recent play is left out");
+        assert!(!radio.contains(&PathBuf::from("played")));
+
+        // Only recent plays left: they're played rather than nothing
+        let recent: Vec<_> = library.into_iter().filter(|c| c.recent).collect();
+        assert_eq!(pick(EndOfQueue::Radio, None, recent, 5, &mut rng).len(), 1);
+
+        // No genres: the same artist is as close as it gets
+        let untagged = Candidate {
+            genre: None,
+            ..seed.clone()
+        };
+        let by_artist = pick(
+            EndOfQueue::Similar,
+            Some(&untagged),
+            vec![track("rock", "a", Some("Rock"), -8.0, 1959)],
+            5,
+            &mut rng,
+        );
+        assert_eq!(by_artist, [PathBuf::from("rock")]);
+    }
+
+    #[tokio::test]
+    async fn test_continuation_leaves_out_queued_and_recent() {
+        let (pool, _dir) = crate::test_utils::temp_db().await;
+        for path in ["/m/a.mp3", "/m/b.mp3", "/m/c.mp3", "/m/d.mp3"] {
+            let id = crate::test_utils::insert_mock_track(&pool, path).await;
+            crate::db::update_track_genres(&pool, id, &["Jazz".to_string()])
+                .await
+                .unwrap();
+        }
+        super::super::record_play(&pool, Path::new("/m/d.mp3"))
+            .await
+            .unwrap();
+        let queued: HashSet<String> = ["/m/a.mp3", "/m/b.mp3"]
+            .iter()
+            .map(|p| crate::db::paths::key(p))
+            .collect();
+
+        let added = continuation(
+            &pool,
+            EndOfQueue::Similar,
+            Some(Path::new("/m/b.mp3")),
+            &queued,
+        )
+        .await
+        .unwrap();
+        assert_eq!(added, [PathBuf::from("/m/c.mp3")]);
+        assert!(
+            continuation(&pool, EndOfQueue::Stop, None, &queued)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
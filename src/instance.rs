@@ -0,0 +1,192 @@
+//! Forwarding to a running instance.
+//!
+//! The GUI listens on a loopback port and writes the port and a random
+//! token to `instance` in the config directory. Launching the app again
+//! (or opening a `musicminder://` link) connects there and hands the
+//! running window a message instead of starting a second one: the token
+//! keeps other local programs that happen to get the port from talking to
+//! it.
+//!
+//! A message is one line, `<token> <message>`, answered with `ok`. A file
+//! left behind by a crash points at a closed port, so forwarding just
+//! fails and the new launch takes over.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+use crate::config;
+
+/// How long a forward waits for the running instance
+const FORWARD_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Longest message accepted
+const MAX_MESSAGE: usize = 8192;
+
+/// Where the running instance's port and token are kept
+fn instance_path() -> Option<PathBuf> {
+    config::config_dir().map(|d| d.join("instance"))
+}
+
+/// Port and token from the instance file
+fn read_instance(path: &Path) -> Option<(u16, String)> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let (port, token) = contents.trim().split_once(' ')?;
+    Some((port.parse().ok()?, token.to_string()))
+}
+
+/// Hand `message` to the running instance. Returns whether one took it;
+/// `false` when none is running.
+pub fn forward(message: &str) -> bool {
+    instance_path().is_some_and(|path| forward_to(&path, message))
+}
+
+fn forward_to(path: &Path, message: &str) -> bool {
+    let Some((port, token)) = read_instance(path) else {
+        return false;
+    };
+    match send(port, &token, message) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!("No running instance on port {}: {}", port, e);
+            false
+        }
+    }
+}
+
+fn send(port: u16, token: &str, message: &str) -> io::Result<()> {
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&addr, FORWARD_TIMEOUT)?;
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    stream.set_write_timeout(Some(FORWARD_TIMEOUT))?;
+    writeln!(stream, "{} {}", token, message.replace(['\r', '\n'], " "))?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    if reply.trim() == "ok" {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not a Music Minder instance",
+        ))
+    }
+}
+
+/// The running instance's end: messages forwarded by later launches.
+pub struct Listener {
+    listener: tokio::net::TcpListener,
+    token: String,
+    path: PathBuf,
+}
+
+impl Listener {
+    /// Start listening and advertise it in the instance file. `None` when
+    /// another instance is already listening (it keeps the messages).
+    pub async fn claim() -> io::Result<Option<Self>> {
+        let path = instance_path()
+            .ok_or_else(|| io::Error::other("Could not determine config directory"))?;
+        Self::claim_at(path).await
+    }
+
+    async fn claim_at(path: PathBuf) -> io::Result<Option<Self>> {
+        if let Some((port, token)) = read_instance(&path)
+            && tokio::task::spawn_blocking(move || send(port, &token, "ping").is_ok())
+                .await
+                .unwrap_or(false)
+        {
+            return Ok(None);
+        }
+
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let port = listener.local_addr()?.port();
+        let token: String = rand::rng()
+            .sample_iter(rand::distr::Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, format!("{} {}\n", port, token))?;
+        Ok(Some(Self {
+            listener,
+            token,
+            path,
+        }))
+    }
+
+    /// The next message forwarded here (pings aren't passed on)
+    pub async fn next(&self) -> String {
+        loop {
+            let Ok((stream, _)) = self.listener.accept().await else {
+                continue;
+            };
+            match tokio::time::timeout(FORWARD_TIMEOUT, self.receive(stream)).await {
+                Ok(Ok(Some(message))) if message != "ping" => return message,
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::debug!("Dropped a forwarded message: {}", e),
+                Err(_) => tracing::debug!("Dropped a forwarded message: timed out"),
+            }
+        }
+    }
+
+    /// Read one message, answering `ok` if it carries the token
+    async fn receive(&self, stream: tokio::net::TcpStream) -> io::Result<Option<String>> {
+        let (read, mut write) = stream.into_split();
+        let mut line = String::new();
+        tokio::io::BufReader::new(read.take(MAX_MESSAGE as u64))
+            .read_line(&mut line)
+            .await?;
+        let Some((token, message)) = line.trim_end().split_once(' ') else {
+            return Ok(None);
+        };
+        if token != self.token {
+            return Ok(None);
+        }
+        write.write_all(b"ok\n").await?;
+        Ok(Some(message.to_string()))
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        // Only our own file: a later instance may have taken over
+        if read_instance(&self.path).is_some_and(|(_, token)| token == self.token) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_forward_reaches_the_listener() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("instance");
+        assert!(!forward_to(&path, "focus"), "nothing is running yet");
+
+        let listener = Listener::claim_at(path.clone()).await.unwrap().unwrap();
+        let (message, (second, sent)) = tokio::join!(listener.next(), async {
+            let second = Listener::claim_at(path.clone()).await.unwrap();
+            let to = path.clone();
+            let sent =
+                tokio::task::spawn_blocking(move || forward_to(&to, "musicminder://search?q=a"))
+                    .await
+                    .unwrap();
+            (second, sent)
+        });
+        assert!(second.is_none(), "a second instance defers to the first");
+        assert!(sent);
+        assert_eq!(message, "musicminder://search?q=a");
+
+        drop(listener);
+        assert!(!path.exists());
+        assert!(!forward_to(&path, "focus"));
+    }
+}
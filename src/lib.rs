@@ -0,0 +1,52 @@
+//! Music Minder - A music library management application.
+//!
+//! This application provides tools for scanning, organizing, enriching, and
+//! playing music files. It can be run as a GUI application or used via CLI
+//! commands. Cargo features pick what gets built: `gui`, `player`,
+//! `enrichment` and `serve` are on by default; a CLI-only build
+//! (`--no-default-features --features enrichment`) leaves out iced, cpal
+//! and image decoding.
+
+pub mod activity;
+#[cfg(feature = "serve")]
+pub mod agent;
+#[cfg(feature = "cd-rip")]
+pub mod cdrip;
+pub mod cli;
+pub mod completeness;
+pub mod config;
+pub mod cover;
+pub mod db;
+pub mod deeplink;
+pub mod diagnostics;
+pub mod enrichment;
+pub mod error;
+pub mod health;
+pub mod history;
+pub mod instance;
+pub mod library;
+pub mod metadata;
+pub mod model;
+pub mod nfo;
+pub mod organizer;
+pub mod plan;
+pub mod player;
+pub mod portable;
+pub mod profile;
+pub mod provenance;
+pub mod readonly;
+pub mod scanner;
+pub mod scheduler;
+pub mod secrets;
+pub mod settings_bundle;
+pub mod startup;
+pub mod stats;
+pub mod tasks;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+#[cfg(feature = "gui")]
+pub mod ui;
+#[cfg(feature = "enrichment")]
+pub mod updates;
+#[cfg(any(feature = "gui", feature = "serve"))]
+pub mod write_queue;
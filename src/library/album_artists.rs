@@ -0,0 +1,660 @@
+//! Album artist fixes for libraries scanned before album artists were read.
+//!
+//! Older scans keyed albums by track artist, so an album with guest credits
+//! or several performers is split into one album per artist, and a folder of
+//! loose tracks can end up as a row of one-track albums. [`find_fixes`]
+//! looks for both patterns and proposes, for each, the album the tracks
+//! belong to: its title, its album artist (the artist most tracks share, or
+//! [`VARIOUS_ARTISTS`]) and how sure the guess is. Each proposal carries the
+//! album view before and after, so it can be previewed.
+//!
+//! [`apply_fixes`] writes the album and album artist tags of the chosen
+//! proposals and moves their tracks in the database. What it changed is
+//! returned as an [`AlbumArtistUndo`], which [`undo_fixes`] puts back.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::compilations::primary_artist;
+use super::{DEFAULT_COMPILATION_THRESHOLD, VARIOUS_ARTISTS, compilation_confidence};
+use crate::db;
+use crate::metadata::{self, AlbumTags};
+
+/// Default confidence a proposal needs to be listed
+pub const DEFAULT_MIN_CONFIDENCE: f32 = 0.5;
+
+/// Proposals at least this sure start out picked in the wizard
+pub const PRESELECT_CONFIDENCE: f32 = 0.8;
+
+/// Fewest one-track albums a folder needs before they're grouped
+const MIN_SINGLES: usize = 3;
+
+/// Why a group of tracks looks misfiled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuspectKind {
+    /// One album title in one folder, split across several artists
+    SplitTitle,
+    /// A folder of albums with one track each
+    SingleTrackFolder,
+}
+
+impl std::fmt::Display for SuspectKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SuspectKind::SplitTitle => "Album split across artists",
+            SuspectKind::SingleTrackFolder => "Folder of one-track albums",
+        })
+    }
+}
+
+/// An album as the library view shows it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlbumView {
+    pub title: String,
+    pub artist: String,
+    pub tracks: usize,
+}
+
+/// A proposed album artist assignment for a group of tracks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumArtistFix {
+    pub kind: SuspectKind,
+    /// Folder holding the tracks
+    pub folder: PathBuf,
+    /// Tracks to move to the proposed album
+    pub track_ids: Vec<i64>,
+    /// Albums the tracks are split across now
+    pub album_ids: Vec<i64>,
+    /// Those albums as they show now
+    pub before: Vec<AlbumView>,
+    /// The album the tracks would form
+    pub after: AlbumView,
+    /// How sure the guess is (0.0-1.0)
+    pub confidence: f32,
+}
+
+/// Database row for [`find_fixes`]
+#[derive(Debug, sqlx::FromRow)]
+struct TrackRow {
+    id: i64,
+    path: String,
+    album_id: i64,
+    album: String,
+    album_artist: Option<String>,
+    artist: String,
+    /// Tracks on the album, anywhere in the library
+    album_tracks: i64,
+}
+
+/// The album artist for tracks with these artists, and how sure that is:
+/// [`VARIOUS_ARTISTS`] if they look like a compilation, otherwise the
+/// artist most of them share (as credited on one of `album_artists`, when
+/// one of those is that artist without guests).
+fn album_artist_for(artists: &[&str], album_artists: &[&str]) -> (String, f32) {
+    let compilation = compilation_confidence(artists);
+    if compilation >= DEFAULT_COMPILATION_THRESHOLD {
+        return (VARIOUS_ARTISTS.to_string(), compilation);
+    }
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for artist in artists {
+        *counts.entry(primary_artist(artist)).or_default() += 1;
+    }
+    let Some((key, count)) = counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+    else {
+        return (String::new(), 0.0);
+    };
+    let name = album_artists
+        .iter()
+        .chain(artists)
+        .filter(|a| primary_artist(a) == key)
+        .min_by_key(|a| a.len())
+        .map_or(key.clone(), |a| a.trim().to_string());
+    (name, count as f32 / artists.len() as f32)
+}
+
+/// The album views of `rows`, one per album, in album order
+fn views(rows: &[&TrackRow]) -> (Vec<i64>, Vec<AlbumView>) {
+    let mut albums: BTreeMap<i64, AlbumView> = BTreeMap::new();
+    for row in rows {
+        albums.entry(row.album_id).or_insert_with(|| AlbumView {
+            title: row.album.clone(),
+            artist: row
+                .album_artist
+                .clone()
+                .unwrap_or_else(|| "Unknown Artist".to_string()),
+            tracks: row.album_tracks as usize,
+        });
+    }
+    albums.into_iter().unzip()
+}
+
+/// Build a proposal for `rows`, scaling the album artist's confidence by
+/// `certainty`. `None` if it would change nothing.
+fn propose(
+    kind: SuspectKind,
+    folder: &Path,
+    title: String,
+    rows: &[&TrackRow],
+    certainty: f32,
+) -> Option<AlbumArtistFix> {
+    let artists: Vec<&str> = rows.iter().map(|r| r.artist.as_str()).collect();
+    let album_artists: Vec<&str> = rows
+        .iter()
+        .filter_map(|r| r.album_artist.as_deref())
+        .collect();
+    let (artist, confidence) = album_artist_for(&artists, &album_artists);
+    let (album_ids, before) = views(rows);
+    let after = AlbumView {
+        title,
+        artist,
+        tracks: rows.len(),
+    };
+    if before.len() == 1 && before[0] == after {
+        return None;
+    }
+    Some(AlbumArtistFix {
+        kind,
+        folder: folder.to_path_buf(),
+        track_ids: rows.iter().map(|r| r.id).collect(),
+        album_ids,
+        before,
+        after,
+        confidence: confidence * certainty,
+    })
+}
+
+/// Groups of tracks that look filed under the wrong album artist, with a
+/// proposed fix for each, most confident first.
+///
+/// Within each folder, an album title spread over several albums is one
+/// group. Tracks that are alone on their album are another, once a folder
+/// holds [`MIN_SINGLES`] of them: they go under the album title most of
+/// them share, or else the folder's name, and the confidence is scaled by
+/// the share of the folder's tracks they make up. Only proposals at least
+/// `min_confidence` sure are returned.
+pub async fn find_fixes(
+    pool: &SqlitePool,
+    min_confidence: f32,
+) -> sqlx::Result<Vec<AlbumArtistFix>> {
+    let rows: Vec<TrackRow> = sqlx::query_as(
+        r#"
+        SELECT
+            t.id, t.path, t.album_id,
+            al.title AS album,
+            aa.name AS album_artist,
+            COALESCE(a.name, 'Unknown Artist') AS artist,
+            (SELECT COUNT(*) FROM tracks o WHERE o.album_id = t.album_id) AS album_tracks
+        FROM tracks t
+        JOIN albums al ON t.album_id = al.id
+        LEFT JOIN artists aa ON al.artist_id = aa.id
+        LEFT JOIN artists a ON t.artist_id = a.id
+        WHERE t.source = 'local'
+        ORDER BY t.path
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut folders: BTreeMap<PathBuf, Vec<TrackRow>> = BTreeMap::new();
+    for row in rows {
+        let Some(folder) = Path::new(&row.path).parent().map(Path::to_path_buf) else {
+            continue;
+        };
+        folders.entry(folder).or_default().push(row);
+    }
+
+    let mut fixes = Vec::new();
+    for (folder, rows) in &folders {
+        let mut titles: BTreeMap<&str, Vec<&TrackRow>> = BTreeMap::new();
+        for row in rows.iter().filter(|r| r.album != "Unknown Album") {
+            titles.entry(row.album.as_str()).or_default().push(row);
+        }
+        let mut grouped = Vec::new();
+        for (title, group) in &titles {
+            let first = group[0].album_id;
+            if group.iter().all(|r| r.album_id == first) {
+                continue;
+            }
+            grouped.extend(group.iter().map(|r| r.id));
+            fixes.extend(propose(
+                SuspectKind::SplitTitle,
+                folder,
+                title.to_string(),
+                group,
+                1.0,
+            ));
+        }
+
+        let singles: Vec<&TrackRow> = rows
+            .iter()
+            .filter(|r| r.album_tracks == 1 && !grouped.contains(&r.id))
+            .collect();
+        if singles.len() < MIN_SINGLES {
+            continue;
+        }
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for row in singles.iter().filter(|r| r.album != "Unknown Album") {
+            *counts.entry(row.album.as_str()).or_default() += 1;
+        }
+        let shared = counts
+            .into_iter()
+            .filter(|(_, n)| *n * 2 > singles.len())
+            .map(|(title, _)| title.to_string())
+            .next();
+        let Some(title) = shared.or_else(|| {
+            folder
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        }) else {
+            continue;
+        };
+        let certainty = singles.len() as f32 / rows.len() as f32;
+        fixes.extend(propose(
+            SuspectKind::SingleTrackFolder,
+            folder,
+            title,
+            &singles,
+            certainty,
+        ));
+    }
+
+    fixes.retain(|f| f.confidence >= min_confidence);
+    fixes.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.folder.cmp(&b.folder))
+    });
+    Ok(fixes)
+}
+
+/// A track as it was before a fix
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackBefore {
+    pub track_id: i64,
+    pub path: String,
+    /// Album title in the database
+    pub album: String,
+    /// Album artist in the database
+    pub album_artist: Option<String>,
+    /// The file's album tags
+    pub tags: AlbumTags,
+}
+
+/// What the last applied fixes changed, kept so they can be undone
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AlbumArtistUndo {
+    pub tracks: Vec<TrackBefore>,
+    pub timestamp: Option<String>,
+}
+
+impl AlbumArtistUndo {
+    const LOG_FILE: &'static str = "album_artist_undo.json";
+
+    fn path() -> PathBuf {
+        crate::profile::data_path(Self::LOG_FILE)
+    }
+
+    /// Load the undo log from disk
+    pub fn load() -> Option<Self> {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    /// Save the undo log to disk
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(Self::path(), json)?;
+        Ok(())
+    }
+
+    /// Clear the undo log
+    pub fn clear() -> Result<()> {
+        let path = Self::path();
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Check if there are fixes to undo
+    pub fn has_undo() -> bool {
+        Self::path().exists()
+    }
+}
+
+/// What applying or undoing fixes changed.
+#[derive(Debug, Clone, Default)]
+pub struct FixReport {
+    /// Tracks whose tags and album were changed
+    pub tracks: usize,
+    /// Files that couldn't be written, with the error; their tracks are
+    /// left where they were
+    pub failed: Vec<(String, String)>,
+    /// What to put back to undo this
+    pub undo: AlbumArtistUndo,
+}
+
+/// Move a track to the album `title` by `album_artist`, tags first. The
+/// album the track leaves is removed if that empties it.
+async fn move_track(
+    pool: &SqlitePool,
+    track_id: i64,
+    path: &str,
+    title: &str,
+    album_artist: Option<&str>,
+    tags: &AlbumTags,
+) -> std::result::Result<(), String> {
+    metadata::write_album_tags(Path::new(path), tags).map_err(|e| format!("{:#}", e))?;
+    let moved = async {
+        let artist_id = match album_artist {
+            Some(name) => Some(db::get_or_create_artist(pool, name).await?),
+            None => None,
+        };
+        let album_id = db::get_or_create_album(pool, title, artist_id).await?;
+        let old: Option<i64> = sqlx::query_scalar("SELECT album_id FROM tracks WHERE id = ?")
+            .bind(track_id)
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE tracks SET album_id = ? WHERE id = ?")
+            .bind(album_id)
+            .bind(track_id)
+            .execute(&mut *tx)
+            .await?;
+        if let Some(old) = old.filter(|&old| old != album_id) {
+            sqlx::query(
+                "DELETE FROM albums WHERE id = ? AND NOT EXISTS (SELECT 1 FROM tracks WHERE album_id = ?)",
+            )
+            .bind(old)
+            .bind(old)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    };
+    moved.await.map_err(|e| e.to_string())
+}
+
+/// Apply proposals: write each track's album and album artist tags, then
+/// move it to the proposed album. Albums left without tracks are removed.
+pub async fn apply_fixes(pool: &SqlitePool, fixes: &[AlbumArtistFix]) -> sqlx::Result<FixReport> {
+    crate::readonly::ensure_writable("Fixing album artists")?;
+    let mut report = FixReport {
+        undo: AlbumArtistUndo {
+            tracks: Vec::new(),
+            timestamp: Some(chrono::Utc::now().to_rfc3339()),
+        },
+        ..Default::default()
+    };
+    for fix in fixes {
+        for &track_id in &fix.track_ids {
+            let row: Option<(String, String, Option<String>)> = sqlx::query_as(
+                r#"SELECT t.path, al.title, aa.name
+                   FROM tracks t
+                   JOIN albums al ON t.album_id = al.id
+                   LEFT JOIN artists aa ON al.artist_id = aa.id
+                   WHERE t.id = ?"#,
+            )
+            .bind(track_id)
+            .fetch_optional(pool)
+            .await?;
+            let Some((path, album, album_artist)) = row else {
+                continue;
+            };
+            let tags = match metadata::read_album_tags(Path::new(&path)) {
+                Ok(tags) => tags,
+                Err(e) => {
+                    report.failed.push((path, format!("{:#}", e)));
+                    continue;
+                }
+            };
+            let fixed = AlbumTags {
+                album: Some(fix.after.title.clone()),
+                album_artist: Some(fix.after.artist.clone()),
+            };
+            match move_track(
+                pool,
+                track_id,
+                &path,
+                &fix.after.title,
+                Some(&fix.after.artist),
+                &fixed,
+            )
+            .await
+            {
+                Ok(()) => {
+                    report.tracks += 1;
+                    report.undo.tracks.push(TrackBefore {
+                        track_id,
+                        path,
+                        album,
+                        album_artist,
+                        tags,
+                    });
+                }
+                Err(e) => report.failed.push((path, e)),
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Put tracks back the way `undo` recorded them: their tags and their
+/// albums. Tracks that have since left the library are passed over.
+pub async fn undo_fixes(pool: &SqlitePool, undo: &AlbumArtistUndo) -> sqlx::Result<FixReport> {
+    crate::readonly::ensure_writable("Undoing album artist fixes")?;
+    let mut report = FixReport::default();
+    for before in &undo.tracks {
+        let exists: Option<i64> = sqlx::query_scalar("SELECT id FROM tracks WHERE id = ?")
+            .bind(before.track_id)
+            .fetch_optional(pool)
+            .await?;
+        if exists.is_none() {
+            continue;
+        }
+        match move_track(
+            pool,
+            before.track_id,
+            &before.path,
+            &before.album,
+            before.album_artist.as_deref(),
+            &before.tags,
+        )
+        .await
+        {
+            Ok(()) => report.tracks += 1,
+            Err(e) => report.failed.push((before.path.clone(), e)),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::TrackMetadata;
+    use crate::test_utils::{AudioFixture, temp_db, write_audio_fixture};
+
+    /// Insert a track the way older scans did: one album per track artist
+    async fn scan_track(pool: &SqlitePool, path: &str, artist: &str, album: &str) -> i64 {
+        let artist_id = db::get_or_create_artist(pool, artist).await.unwrap();
+        let album_id = db::get_or_create_album(pool, album, Some(artist_id))
+            .await
+            .unwrap();
+        let meta = TrackMetadata {
+            title: path.to_string(),
+            artist: artist.to_string(),
+            album: album.to_string(),
+            duration: 200,
+            track_number: None,
+        };
+        db::insert_track(pool, &meta, path, Some(artist_id), Some(album_id))
+            .await
+            .unwrap()
+    }
+
+    async fn album_views(pool: &SqlitePool) -> Vec<(String, String)> {
+        sqlx::query_as(
+            r#"SELECT al.title, COALESCE(a.name, '')
+               FROM albums al LEFT JOIN artists a ON al.artist_id = a.id
+               ORDER BY al.title, a.name"#,
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test_album_artist_for() {
+        let guests = ["Blur", "Blur", "Blur feat. Damon", "Blur ft. Graham"];
+        assert_eq!(
+            album_artist_for(&guests, &["Blur feat. Damon", "Blur"]),
+            ("Blur".to_string(), 1.0)
+        );
+
+        let compilation = ["A", "B", "C", "D", "E"];
+        let (artist, confidence) = album_artist_for(&compilation, &[]);
+        assert_eq!(artist, VARIOUS_ARTISTS);
+        assert!((confidence - 0.8).abs() < 1e-6);
+
+        // A duet album is a coin toss
+        let (_, confidence) = album_artist_for(&["A", "B"], &[]);
+        assert_eq!(confidence, 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_find_split_titles_and_single_track_folders() {
+        let (pool, _dir) = temp_db().await;
+        scan_track(&pool, "/m/Blur/Parklife/1.mp3", "Blur", "Parklife").await;
+        scan_track(&pool, "/m/Blur/Parklife/2.mp3", "Blur", "Parklife").await;
+        scan_track(
+            &pool,
+            "/m/Blur/Parklife/3.mp3",
+            "Blur feat. Phil Daniels",
+            "Parklife",
+        )
+        .await;
+        // Five loose tracks with their own album tags
+        for (i, artist) in ["A", "B", "C", "D", "E"].iter().enumerate() {
+            let path = format!("/m/Mixtape/{}.mp3", i);
+            scan_track(&pool, &path, artist, &format!("Single {}", i)).await;
+        }
+        // A tidy album is left alone
+        scan_track(&pool, "/m/Pulp/1.mp3", "Pulp", "Different Class").await;
+        scan_track(&pool, "/m/Pulp/2.mp3", "Pulp", "Different Class").await;
+
+        let fixes = find_fixes(&pool, DEFAULT_MIN_CONFIDENCE).await.unwrap();
+        assert_eq!(fixes.len(), 2);
+
+        let split = fixes
+            .iter()
+            .find(|f| f.kind == SuspectKind::SplitTitle)
+            .unwrap();
+        assert_eq!(split.before.len(), 2);
+        assert_eq!(
+            split.after,
+            AlbumView {
+                title: "Parklife".to_string(),
+                artist: "Blur".to_string(),
+                tracks: 3
+            }
+        );
+        assert_eq!(split.confidence, 1.0);
+
+        let mixtape = fixes
+            .iter()
+            .find(|f| f.kind == SuspectKind::SingleTrackFolder)
+            .unwrap();
+        assert_eq!(mixtape.after.title, "Mixtape");
+        assert_eq!(mixtape.after.artist, VARIOUS_ARTISTS);
+        assert_eq!(mixtape.before.len(), 5);
+        assert_eq!(mixtape.track_ids.len(), 5);
+
+        // Raising the bar drops the less certain guess
+        let sure = find_fixes(&pool, 0.9).await.unwrap();
+        assert_eq!(sure.len(), 1);
+        assert_eq!(sure[0].kind, SuspectKind::SplitTitle);
+    }
+
+    #[tokio::test]
+    async fn test_apply_and_undo() {
+        let (pool, dir) = temp_db().await;
+        let mut paths = Vec::new();
+        for (i, artist) in ["Blur", "Blur feat. Phil Daniels"].iter().enumerate() {
+            let folder = dir.path().join(format!("disc{}", i));
+            std::fs::create_dir(&folder).unwrap();
+            let path = write_audio_fixture(&folder, AudioFixture::Flac);
+            let path = path.to_string_lossy().into_owned();
+            scan_track(&pool, &path, artist, "Parklife").await;
+            paths.push(path);
+        }
+        let missing = scan_track(&pool, "/gone/3.flac", "Blur ft. Damon", "Parklife").await;
+        let before = album_views(&pool).await;
+        assert_eq!(before.len(), 3);
+
+        // The files sit in different folders, so build the fix by hand
+        let ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM tracks ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let fix = AlbumArtistFix {
+            kind: SuspectKind::SplitTitle,
+            folder: dir.path().to_path_buf(),
+            track_ids: ids.clone(),
+            album_ids: Vec::new(),
+            before: Vec::new(),
+            after: AlbumView {
+                title: "Parklife".to_string(),
+                artist: "Blur".to_string(),
+                tracks: 3,
+            },
+            confidence: 1.0,
+        };
+        let report = apply_fixes(&pool, &[fix]).await.unwrap();
+        assert_eq!(report.tracks, 2);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.undo.tracks.len(), 2);
+        assert!(report.undo.tracks.iter().all(|t| t.track_id != missing));
+
+        let tags = metadata::read_album_tags(Path::new(&paths[1])).unwrap();
+        assert_eq!(tags.album_artist.as_deref(), Some("Blur"));
+        assert_eq!(tags.album.as_deref(), Some("Parklife"));
+        // The unreadable track keeps its album; the emptied one is gone
+        assert_eq!(album_views(&pool).await.len(), 2);
+
+        let undone = undo_fixes(&pool, &report.undo).await.unwrap();
+        assert_eq!(undone.tracks, 2);
+        assert_eq!(album_views(&pool).await, before);
+        let tags = metadata::read_album_tags(Path::new(&paths[1])).unwrap();
+        assert_eq!(tags, AlbumTags::default());
+    }
+
+    #[tokio::test]
+    async fn test_fixed_tags_survive_rescan() {
+        let (pool, dir) = temp_db().await;
+        let path = write_audio_fixture(dir.path(), AudioFixture::Flac);
+        metadata::write_album_tags(
+            &path,
+            &AlbumTags {
+                album: Some("Parklife".to_string()),
+                album_artist: Some("Blur".to_string()),
+            },
+        )
+        .unwrap();
+
+        // The file has no artist tag, but files under its album artist
+        crate::library::index_changed_file(&pool, path).await;
+        assert_eq!(
+            album_views(&pool).await,
+            [("Parklife".to_string(), "Blur".to_string())]
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Various Artists detection.
+//!
+//! Albums are keyed by title and artist, so a compilation folder whose files
+//! only carry track artists is split into one album per artist. A folder
+//! holding many tracks of the same album by many different artists is almost
+//! certainly a compilation; such groups are merged into a single album with
+//! [`VARIOUS_ARTISTS`] as the album artist. Track artists are left alone.
+//!
+//! Detection runs at the end of every scan, and [`find_compilations`] /
+//! [`merge_compilation`] back the `compilations` command for libraries that
+//! were scanned before it existed.
+
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::db;
+
+/// Album artist given to detected compilations
+pub const VARIOUS_ARTISTS: &str = "Various Artists";
+
+/// Default confidence needed to treat a group as a compilation
+pub const DEFAULT_THRESHOLD: f32 = 0.6;
+
+/// Fewest tracks a group needs before it can be a compilation
+const MIN_TRACKS: usize = 3;
+
+/// Fewest distinct artists a group needs before it can be a compilation
+const MIN_ARTISTS: usize = 3;
+
+/// Tracks of one album title in one folder that look like a compilation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompilationCandidate {
+    /// Album title
+    pub album: String,
+    /// Folder holding the tracks
+    pub folder: PathBuf,
+    /// Tracks to move to the Various Artists album
+    pub track_ids: Vec<i64>,
+    /// Albums the tracks are split across now
+    pub album_ids: Vec<i64>,
+    /// Number of distinct (primary) track artists
+    pub artists: usize,
+    /// How sure the heuristic is (0.0-1.0)
+    pub confidence: f32,
+}
+
+/// How likely tracks with these artists are a compilation (0.0-1.0).
+///
+/// One minus the share of the most common artist, once "feat." credits are
+/// stripped: 10 tracks by 10 artists score 0.9, an artist's album with two
+/// guest tracks scores 0.2. Groups with fewer than three tracks or three
+/// artists score 0, so split albums and duets are never merged.
+pub fn compilation_confidence(artists: &[&str]) -> f32 {
+    if artists.len() < MIN_TRACKS {
+        return 0.0;
+    }
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for artist in artists {
+        *counts.entry(primary_artist(artist)).or_default() += 1;
+    }
+    if counts.len() < MIN_ARTISTS {
+        return 0.0;
+    }
+    let dominant = counts.values().copied().max().unwrap_or(0);
+    1.0 - dominant as f32 / artists.len() as f32
+}
+
+/// Lowercased artist credit without any featured artists
+pub(super) fn primary_artist(artist: &str) -> String {
+    let lower = artist.trim().to_lowercase();
+    let cut = [" feat.", " feat ", " ft.", " featuring ", " (feat", " (ft"]
+        .iter()
+        .filter_map(|p| lower.find(p))
+        .min()
+        .unwrap_or(lower.len());
+    lower[..cut].trim().to_string()
+}
+
+/// Database row for [`find_compilations`]
+#[derive(Debug, sqlx::FromRow)]
+struct GroupRow {
+    id: i64,
+    path: String,
+    album_id: i64,
+    album: String,
+    album_artist: Option<String>,
+    artist: String,
+}
+
+/// Album/folder groups under `root` (or the whole library) that look like
+/// compilations and aren't merged yet, most confident first.
+pub async fn find_compilations(
+    pool: &SqlitePool,
+    root: Option<&Path>,
+    threshold: f32,
+) -> sqlx::Result<Vec<CompilationCandidate>> {
+    let rows: Vec<GroupRow> = sqlx::query_as(
+        r#"
+        SELECT
+            t.id, t.path, t.album_id,
+            al.title AS album,
+            aa.name AS album_artist,
+            COALESCE(a.name, 'Unknown Artist') AS artist
+        FROM tracks t
+        JOIN albums al ON t.album_id = al.id
+        LEFT JOIN artists aa ON al.artist_id = aa.id
+        LEFT JOIN artists a ON t.artist_id = a.id
+        WHERE al.title != 'Unknown Album'
+        ORDER BY t.path
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut groups: HashMap<(String, PathBuf), Vec<GroupRow>> = HashMap::new();
+    for row in rows {
+        let Some(folder) = Path::new(&row.path).parent().map(Path::to_path_buf) else {
+            continue;
+        };
+        if root.is_some_and(|root| !folder.starts_with(root)) {
+            continue;
+        }
+        groups
+            .entry((row.album.clone(), folder))
+            .or_default()
+            .push(row);
+    }
+
+    let mut candidates: Vec<CompilationCandidate> = groups
+        .into_iter()
+        .filter(|(_, rows)| {
+            rows.iter()
+                .any(|r| r.album_artist.as_deref() != Some(VARIOUS_ARTISTS))
+        })
+        .filter_map(|((album, folder), rows)| {
+            let artists: Vec<&str> = rows.iter().map(|r| r.artist.as_str()).collect();
+            let confidence = compilation_confidence(&artists);
+            if confidence < threshold {
+                return None;
+            }
+            let artist_count = artists
+                .iter()
+                .map(|a| primary_artist(a))
+                .collect::<std::collections::HashSet<_>>()
+                .len();
+            let mut album_ids: Vec<i64> = rows.iter().map(|r| r.album_id).collect();
+            album_ids.sort_unstable();
+            album_ids.dedup();
+            Some(CompilationCandidate {
+                album,
+                folder,
+                track_ids: rows.iter().map(|r| r.id).collect(),
+                album_ids,
+                artists: artist_count,
+                confidence,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.folder.cmp(&b.folder))
+    });
+    Ok(candidates)
+}
+
+/// Move a candidate's tracks to one Various Artists album.
+///
+/// Albums left without tracks are removed. Returns the merged album's ID.
+pub async fn merge_compilation(
+    pool: &SqlitePool,
+    candidate: &CompilationCandidate,
+) -> sqlx::Result<i64> {
+    let artist_id = db::get_or_create_artist(pool, VARIOUS_ARTISTS).await?;
+    let album_id = db::get_or_create_album(pool, &candidate.album, Some(artist_id)).await?;
+
+    let mut tx = pool.begin().await?;
+    for track_id in &candidate.track_ids {
+        sqlx::query("UPDATE tracks SET album_id = ? WHERE id = ?")
+            .bind(album_id)
+            .bind(track_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for old_id in candidate.album_ids.iter().filter(|&&id| id != album_id) {
+        sqlx::query(
+            "DELETE FROM albums WHERE id = ? AND NOT EXISTS (SELECT 1 FROM tracks WHERE album_id = ?)",
+        )
+        .bind(old_id)
+        .bind(old_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(album_id)
+}
+
+/// Detect and merge every compilation under `root`. Returns how many albums
+/// were merged.
+pub async fn merge_compilations_under(
+    pool: &SqlitePool,
+    root: &Path,
+    threshold: f32,
+) -> sqlx::Result<usize> {
+    let candidates = find_compilations(pool, Some(root), threshold).await?;
+    for candidate in &candidates {
+        merge_compilation(pool, candidate).await?;
+        tracing::info!(
+            "Grouped {:?} in {:?} under {} ({} artists, confidence {:.2})",
+            candidate.album,
+            candidate.folder,
+            VARIOUS_ARTISTS,
+            candidate.artists,
+            candidate.confidence
+        );
+    }
+    Ok(candidates.len())
+}
+
+/// The Various Artists album a newly scanned file belongs to, if its folder
+/// was already merged into one.
+///
+/// Lets rescans keep compilations together instead of splitting them again.
+pub async fn existing_compilation(
+    pool: &SqlitePool,
+    album: &str,
+    path: &Path,
+) -> sqlx::Result<Option<i64>> {
+    let Some(folder) = path.parent() else {
+        return Ok(None);
+    };
+    let rows: Vec<(i64, String)> = sqlx::query_as(
+        r#"
+        SELECT al.id, t.path
+        FROM albums al
+        JOIN artists a ON al.artist_id = a.id
+        JOIN tracks t ON t.album_id = al.id
+        WHERE al.title = ? AND a.name = ?
+        "#,
+    )
+    .bind(album)
+    .bind(VARIOUS_ARTISTS)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .find(|(_, p)| Path::new(p).parent() == Some(folder))
+        .map(|(id, _)| id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::TrackMetadata;
+
+    async fn test_pool(dir: &Path) -> SqlitePool {
+        let db_url = format!("sqlite:{}", dir.join("test.db").display());
+        db::init_db(&db_url).await.unwrap()
+    }
+
+    /// Insert a track the way a scan does: one album per track artist
+    async fn scan_track(pool: &SqlitePool, path: &str, artist: &str, album: &str) {
+        let artist_id = db::get_or_create_artist(pool, artist).await.unwrap();
+        let album_id = match existing_compilation(pool, album, Path::new(path))
+            .await
+            .unwrap()
+        {
+            Some(id) => id,
+            None => db::get_or_create_album(pool, album, Some(artist_id))
+                .await
+                .unwrap(),
+        };
+        let meta = TrackMetadata {
+            title: path.to_string(),
+            artist: artist.to_string(),
+            album: album.to_string(),
+            duration: 200,
+            track_number: None,
+        };
+        db::insert_track(pool, &meta, path, Some(artist_id), Some(album_id))
+            .await
+            .unwrap();
+    }
+
+    async fn album_count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM albums")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_compilation_confidence() {
+        let compilation = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"];
+        assert!((compilation_confidence(&compilation) - 0.9).abs() < 1e-6);
+
+        // An artist's album with guests
+        let guests = ["A", "A", "A", "A", "A feat. B", "A ft. C", "D", "E"];
+        assert!(compilation_confidence(&guests) < DEFAULT_THRESHOLD);
+
+        // Split albums and tiny groups never qualify
+        assert_eq!(compilation_confidence(&["A", "A", "B", "B"]), 0.0);
+        assert_eq!(compilation_confidence(&["A", "B"]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_merge_compilation_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+        for (i, artist) in ["Blur", "Pulp", "Suede", "Oasis"].iter().enumerate() {
+            scan_track(&pool, &format!("/m/Britpop/{}.mp3", i), artist, "Britpop").await;
+        }
+        // Same title elsewhere by one artist: not part of the compilation
+        scan_track(&pool, "/m/Blur/Britpop/1.mp3", "Blur", "Britpop").await;
+        scan_track(&pool, "/m/Blur/Britpop/2.mp3", "Blur", "Britpop").await;
+        scan_track(&pool, "/m/Blur/Britpop/3.mp3", "Blur", "Britpop").await;
+        assert_eq!(album_count(&pool).await, 4);
+
+        // Nothing outside the scanned folder is touched
+        let elsewhere = merge_compilations_under(&pool, Path::new("/other"), DEFAULT_THRESHOLD)
+            .await
+            .unwrap();
+        assert_eq!(elsewhere, 0);
+
+        let candidates = find_compilations(&pool, None, DEFAULT_THRESHOLD)
+            .await
+            .unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].folder, PathBuf::from("/m/Britpop"));
+        assert_eq!(candidates[0].artists, 4);
+
+        let merged = merge_compilations_under(&pool, Path::new("/m"), DEFAULT_THRESHOLD)
+            .await
+            .unwrap();
+        assert_eq!(merged, 1);
+        // Pulp/Suede/Oasis albums are gone; Blur's own album and the VA one remain
+        assert_eq!(album_count(&pool).await, 2);
+
+        let albums = crate::completeness::library_albums(&pool).await.unwrap();
+        let va = albums.iter().find(|a| a.artist == VARIOUS_ARTISTS).unwrap();
+        assert_eq!(va.tracks.len(), 4);
+
+        // Already merged: nothing more to do
+        assert!(
+            find_compilations(&pool, None, DEFAULT_THRESHOLD)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rescan_keeps_compilation_together() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = test_pool(dir.path()).await;
+        for (i, artist) in ["Blur", "Pulp", "Suede"].iter().enumerate() {
+            scan_track(&pool, &format!("/m/Britpop/{}.mp3", i), artist, "Britpop").await;
+        }
+        merge_compilations_under(&pool, Path::new("/m"), DEFAULT_THRESHOLD)
+            .await
+            .unwrap();
+
+        // Rescanning the files and adding a new one keeps a single album
+        scan_track(&pool, "/m/Britpop/0.mp3", "Blur", "Britpop").await;
+        scan_track(&pool, "/m/Britpop/9.mp3", "Elastica", "Britpop").await;
+
+        assert_eq!(album_count(&pool).await, 1);
+    }
+}
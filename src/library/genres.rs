@@ -0,0 +1,260 @@
+//! Genre manager: every genre in the library with its track count, and
+//! merging or renaming genres across files and the database.
+//!
+//! Scans store each track's genres in `tracks.genre` after the genre rules
+//! of `tagging.genre_map` (see [`GenreMap`]), so a spelling with a rule is
+//! counted as the genre it maps to. Tracks indexed before genres were
+//! stored are read from their files by [`read_missing`].
+//!
+//! A merge rewrites the genre tags of every track carrying one of the
+//! merged spellings, then the stored genres of those tracks and of their
+//! play history, so listening stats follow. Remembering a merge as rules
+//! ([`add_rules`]) maps the spellings on future scans and enrichment writes
+//! too.
+
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::db;
+use crate::metadata::{
+    self,
+    genres::{GenreMap, SEPARATOR, spelling_key, split},
+};
+
+/// A genre and how many tracks carry it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenreCount {
+    pub name: String,
+    pub tracks: usize,
+}
+
+/// What a merge changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Retagged {
+    /// Tracks whose tags and stored genres were rewritten
+    pub tracks: usize,
+    /// Files that couldn't be written, with the error; their stored
+    /// genres are left alone
+    pub failed: Vec<(String, String)>,
+    /// Play history entries whose genre was rewritten
+    pub plays: usize,
+}
+
+/// Read the genres of tracks indexed before genres were stored. Returns
+/// how many were read; unreadable files are left for the next time.
+pub async fn read_missing(pool: &SqlitePool, genre_map: &GenreMap) -> sqlx::Result<usize> {
+    let rows: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, path FROM tracks WHERE genre IS NULL AND source = 'local'")
+            .fetch_all(pool)
+            .await?;
+    let mut read = 0;
+    for (id, path) in rows {
+        let Ok(tags) = metadata::read_for_index(Path::new(&path)) else {
+            continue;
+        };
+        db::update_track_genres(pool, id, &genre_map.apply(&tags.genres)).await?;
+        read += 1;
+    }
+    Ok(read)
+}
+
+/// Every stored genre with its track count, most used first.
+pub async fn counts(pool: &SqlitePool) -> sqlx::Result<Vec<GenreCount>> {
+    let stored: Vec<(String,)> =
+        sqlx::query_as("SELECT genre FROM tracks WHERE genre IS NOT NULL AND genre != ''")
+            .fetch_all(pool)
+            .await?;
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (genre,) in stored {
+        for name in split(&genre) {
+            *counts.entry(name).or_default() += 1;
+        }
+    }
+    let mut counts: Vec<GenreCount> = counts
+        .into_iter()
+        .map(|(name, tracks)| GenreCount { name, tracks })
+        .collect();
+    counts.sort_by(|a, b| b.tracks.cmp(&a.tracks).then_with(|| a.name.cmp(&b.name)));
+    Ok(counts)
+}
+
+/// `genres` with each of `from` replaced by `into`, keeping the first of
+/// any repeats that leaves
+fn replace(genres: &[String], from: &[String], into: &str) -> Vec<String> {
+    let mut replaced: Vec<String> = Vec::with_capacity(genres.len());
+    for genre in genres {
+        let genre = if from.contains(genre) {
+            into
+        } else {
+            genre.as_str()
+        };
+        if !replaced
+            .iter()
+            .any(|g| spelling_key(g) == spelling_key(genre))
+        {
+            replaced.push(genre.to_string());
+        }
+    }
+    replaced
+}
+
+/// Merge the genres `from` into `into` (a rename when `from` is one
+/// genre), in the tags of every track carrying one and in the database.
+pub async fn merge(pool: &SqlitePool, from: &[String], into: &str) -> sqlx::Result<Retagged> {
+    crate::readonly::ensure_writable("Merging genres")?;
+    let into = into.trim();
+    let mut report = Retagged::default();
+
+    let tracks: Vec<(i64, String, String)> = sqlx::query_as(
+        "SELECT id, path, genre FROM tracks WHERE genre IS NOT NULL AND genre != ''",
+    )
+    .fetch_all(pool)
+    .await?;
+    for (id, path, genre) in tracks {
+        let genres = split(&genre);
+        let merged = replace(&genres, from, into);
+        if merged == genres {
+            continue;
+        }
+        match metadata::write_genres(Path::new(&path), &merged) {
+            Ok(_) => {
+                db::update_track_genres(pool, id, &merged).await?;
+                report.tracks += 1;
+            }
+            Err(e) => report.failed.push((path, format!("{:#}", e))),
+        }
+    }
+
+    let plays: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT id, genre FROM play_history WHERE genre IS NOT NULL AND genre != ''",
+    )
+    .fetch_all(pool)
+    .await?;
+    for (id, genre) in plays {
+        let genres = split(&genre);
+        let merged = replace(&genres, from, into);
+        if merged == genres {
+            continue;
+        }
+        sqlx::query("UPDATE play_history SET genre = ? WHERE id = ?")
+            .bind(merged.join(SEPARATOR))
+            .bind(id)
+            .execute(pool)
+            .await?;
+        report.plays += 1;
+    }
+    Ok(report)
+}
+
+/// Add rules writing each of `from` as `into` to `tagging.genre_map`.
+/// Rules that led to one of `from` are pointed at `into` too, so earlier
+/// merges follow this one.
+pub fn add_rules(rules: &mut BTreeMap<String, String>, from: &[String], into: &str) {
+    let into = into.trim();
+    let from_keys: Vec<String> = from.iter().map(|g| spelling_key(g)).collect();
+    for target in rules.values_mut() {
+        if from_keys.contains(&spelling_key(target)) {
+            *target = into.to_string();
+        }
+    }
+    for (genre, key) in from.iter().zip(&from_keys) {
+        if genre.trim() == into || key.is_empty() {
+            continue;
+        }
+        rules.retain(|spelling, _| spelling_key(spelling) != *key);
+        rules.insert(genre.trim().to_string(), into.to_string());
+    }
+}
+
+/// Remove the rule for `spelling` (under any of its spellings). Returns
+/// whether there was one.
+pub fn remove_rule(rules: &mut BTreeMap<String, String>, spelling: &str) -> bool {
+    let key = spelling_key(spelling);
+    let before = rules.len();
+    rules.retain(|from, _| spelling_key(from) != key);
+    rules.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{AudioFixture, insert_mock_track, temp_db, write_audio_fixture};
+
+    fn names(genres: &[&str]) -> Vec<String> {
+        genres.iter().map(|g| g.to_string()).collect()
+    }
+
+    #[test]
+    fn test_replace_drops_repeats() {
+        let genres = names(&["Hip-Hop", "Jazz", "hip hop"]);
+        assert_eq!(
+            replace(&genres, &names(&["Hip-Hop", "hip hop"]), "Hip Hop"),
+            ["Hip Hop", "Jazz"]
+        );
+        let untouched = names(&["Rock", "Jazz"]);
+        assert_eq!(replace(&untouched, &names(&["Pop"]), "Rock"), untouched);
+    }
+
+    #[test]
+    fn test_rules_follow_later_merges() {
+        let mut rules = BTreeMap::new();
+        add_rules(&mut rules, &names(&["Hip-Hop", "Rap Music"]), "Hip Hop");
+        assert_eq!(rules.len(), 2);
+
+        // Hip Hop itself merged into Rap: the older rules now lead there too
+        add_rules(&mut rules, &names(&["Hip Hop"]), "Rap");
+        let map = GenreMap::new(&rules);
+        assert_eq!(map.map("HIP-HOP"), "Rap");
+        assert_eq!(map.map("rap music"), "Rap");
+        // One rule per spelling key
+        assert_eq!(rules.len(), 2);
+
+        assert!(remove_rule(&mut rules, "HipHop"));
+        assert!(!remove_rule(&mut rules, "Jazz"));
+        assert_eq!(GenreMap::new(&rules).map("hiphop"), "hiphop");
+    }
+
+    #[tokio::test]
+    async fn test_merge_rewrites_tags_and_rows() {
+        let (pool, dir) = temp_db().await;
+        let path = write_audio_fixture(dir.path(), AudioFixture::Flac);
+        metadata::write_genres(&path, &names(&["Hip-Hop", "Jazz"])).unwrap();
+        let id = insert_mock_track(&pool, &path.to_string_lossy()).await;
+        let missing = insert_mock_track(&pool, "/music/missing.flac").await;
+        db::update_track_genres(&pool, missing, &names(&["hiphop"]))
+            .await
+            .unwrap();
+
+        assert_eq!(read_missing(&pool, &GenreMap::default()).await.unwrap(), 1);
+        sqlx::query("INSERT INTO play_history (track_id, played_at, genre) VALUES (?, 100, ?)")
+            .bind(id)
+            .bind("Hip-Hop; Jazz")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(counts(&pool).await.unwrap().len(), 3);
+
+        let report = merge(&pool, &names(&["Hip-Hop", "hiphop"]), "Hip Hop")
+            .await
+            .unwrap();
+        assert_eq!(report.tracks, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.plays, 1);
+
+        let tags = metadata::read_for_index(&path).unwrap();
+        assert_eq!(tags.genres, ["Hip Hop", "Jazz"]);
+        let counts = counts(&pool).await.unwrap();
+        assert!(counts.contains(&GenreCount {
+            name: "Hip Hop".to_string(),
+            tracks: 1
+        }));
+        // The file that couldn't be written keeps its genre until it can be
+        assert!(counts.iter().any(|c| c.name == "hiphop"));
+        let play: String = sqlx::query_scalar("SELECT genre FROM play_history")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(play, "Hip Hop; Jazz");
+    }
+}
@@ -1,50 +1,374 @@
 //! Library scanning and management.
 //!
 //! Coordinates the scanning of directories for audio files, reading their
-//! metadata, and storing track information in the database.
+//! metadata, and storing track information in the database. Files without a
+//! track number tag get one guessed from their file name or folder order,
+//! flagged as inferred. Ratings and play counts other players wrote to the
+//! tags are imported (see `library.popm_email`), along with the language and
+//! explicit flag, and genres are stored after the genre rules ([`genres`]).
+//! Album numbering is audited on request ([`numbering`]), and loudness
+//! measured for ReplayGain ([`replaygain`]). Streams and cloud drive links
+//! are added by URL ([`remote`]). Albums split by older scans are found and
+//! regrouped under their album artist with [`album_artists`], and tags that
+//! name another recording than the audio found with [`verify_tags`].
+//! [`incremental_scan`] brings
+//! an already scanned folder up to date, reading only new and changed files.
+//! Finished scans are recorded for the usage statistics ([`crate::stats`]).
 
-use crate::{db, metadata, scanner};
+pub mod album_artists;
+mod compilations;
+pub mod genres;
+pub mod numbering;
+pub mod remote;
+pub mod replaygain;
+mod track_numbers;
+#[cfg(feature = "enrichment")]
+pub mod verify_tags;
+
+pub use compilations::{
+    CompilationCandidate, DEFAULT_THRESHOLD as DEFAULT_COMPILATION_THRESHOLD, VARIOUS_ARTISTS,
+    compilation_confidence, find_compilations, merge_compilation, merge_compilations_under,
+};
+pub use track_numbers::{
+    InferredTrackNumber, infer_track_number, inferred_tracks, mark_confirmed, mark_inferred,
+    number_from_file_name,
+};
+
+use crate::error::{Failure, Retry};
+use crate::tasks::TaskHandle;
+use crate::{config, db, metadata, scanner};
 use futures::{Stream, StreamExt};
 use sqlx::SqlitePool;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The settings each indexed file is read with
+#[derive(Debug, Clone, Default)]
+struct IndexSettings {
+    /// Player whose POPM frame ratings come from (`library.popm_email`)
+    popm_email: String,
+    /// Genre rules (`tagging.genre_map`)
+    genre_map: metadata::genres::GenreMap,
+}
+
+impl IndexSettings {
+    fn load() -> Self {
+        let config = config::load();
+        Self {
+            popm_email: config.library.popm_email,
+            genre_map: metadata::genres::GenreMap::from_config(&config.tagging),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum ScanEvent {
     Processed(PathBuf),
-    Error(PathBuf, String),
+    Error(PathBuf, Failure),
+    /// Album/folder groups merged under "Various Artists" after the scan
+    CompilationsGrouped(usize),
 }
 
 /// Scans a directory and updates the database with found tracks.
 /// Returns a stream of ScanEvents.
-pub fn scan_library(pool: SqlitePool, root: PathBuf) -> impl Stream<Item = ScanEvent> {
-    let paths = scanner::scan(root);
+///
+/// Once every file is processed, compilation folders under `root` are grouped
+/// under "Various Artists" (see `library.compilation_threshold`).
+///
+/// Each indexed file advances `task`; cancelling it stops the scan after the
+/// files in flight, skipping the grouping.
+pub fn scan_library(
+    pool: SqlitePool,
+    root: PathBuf,
+    task: TaskHandle,
+) -> impl Stream<Item = ScanEvent> {
+    task.set_phase("Reading tags");
+    let settings = std::sync::Arc::new(IndexSettings::load());
+    let paths = scanner::scan(root.clone());
+    let finish_pool = pool.clone();
+    let walk_task = task.clone();
+    let group_task = task.clone();
+    let (started_at, started) = (chrono::Utc::now(), std::time::Instant::now());
+    let read = std::sync::Arc::new(AtomicUsize::new(0));
+    let read_total = read.clone();
 
-    paths
+    let files = paths
+        .take_while(move |_| futures::future::ready(!walk_task.is_cancelled()))
         .map(move |path| {
             let pool = pool.clone();
+            let settings = settings.clone();
+            let task = task.clone();
+            let read = read.clone();
             async move {
-                match metadata::read(&path) {
-                    Ok(meta) => {
-                        let artist_id = db::get_or_create_artist(&pool, &meta.artist).await.ok();
-                        let album_id = db::get_or_create_album(&pool, &meta.album, artist_id)
-                            .await
-                            .ok();
-                        match db::insert_track(
-                            &pool,
-                            &meta,
-                            path.to_str().unwrap_or(""),
-                            artist_id,
-                            album_id,
-                        )
-                        .await
-                        {
-                            Ok(_) => ScanEvent::Processed(path),
-                            Err(e) => ScanEvent::Error(path, e.to_string()),
-                        }
-                    }
-                    Err(e) => ScanEvent::Error(path, e.to_string()),
-                }
+                let event = index_file(&pool, path, None, &settings).await;
+                task.advance(1);
+                read.fetch_add(1, Ordering::Relaxed);
+                event
             }
         })
-        .buffer_unordered(10) // Process 10 files in parallel
+        .buffer_unordered(10); // Process 10 files in parallel
+
+    let grouping = futures::stream::once(async move {
+        if group_task.is_cancelled() {
+            return None;
+        }
+        group_task.set_phase("Grouping compilations");
+        let threshold = config::load().library.compilation_threshold;
+        let grouped = merge_compilations_under(&finish_pool, &root, threshold).await;
+        let files = read_total.load(Ordering::Relaxed);
+        crate::stats::record_scan(&finish_pool, started_at, started.elapsed(), files).await;
+        match grouped {
+            Ok(0) => None,
+            Ok(n) => Some(ScanEvent::CompilationsGrouped(n)),
+            Err(e) => Some(ScanEvent::Error(root, Failure::of(&e))),
+        }
+    })
+    .filter_map(futures::future::ready);
+
+    files.chain(grouping)
+}
+
+/// Read one file's tags and add or update its track.
+///
+/// With `mtime`, the file's modification time is stored as well, so later
+/// incremental scans can tell whether it changed.
+async fn index_file(
+    pool: &SqlitePool,
+    path: PathBuf,
+    mtime: Option<i64>,
+    settings: &IndexSettings,
+) -> ScanEvent {
+    // Files being written by another program are read again after a pause
+    let tags = match Retry::FILE
+        .run_async(|| async { metadata::read_for_index(&path) })
+        .await
+    {
+        Ok(read) => read,
+        Err(e) => return ScanEvent::Error(path, Failure::of(&e)),
+    };
+    let mut meta = tags.metadata;
+    let inferred = meta.track_number.is_none();
+    if inferred {
+        meta.track_number = infer_track_number(&path);
+    }
+    let artist_id = db::get_or_create_artist(pool, &meta.artist).await.ok();
+    // Albums go under their album artist tag, or the track artist without one
+    let album_artist_id = match &tags.album_artist {
+        Some(album_artist) if *album_artist != meta.artist => {
+            db::get_or_create_artist(pool, album_artist).await.ok()
+        }
+        _ => artist_id,
+    };
+    let album_id = match compilations::existing_compilation(pool, &meta.album, &path).await {
+        Ok(Some(id)) => Some(id),
+        _ => db::get_or_create_album(pool, &meta.album, album_artist_id)
+            .await
+            .ok(),
+    };
+    let path_str = path.to_str().unwrap_or("");
+    let inserted = match mtime {
+        Some(mtime) => {
+            db::insert_track_with_mtime(pool, &meta, path_str, artist_id, album_id, mtime).await
+        }
+        None => db::insert_track(pool, &meta, path_str, artist_id, album_id).await,
+    };
+    match inserted {
+        Ok(id) => {
+            if inferred && meta.track_number.is_some() {
+                let _ = mark_inferred(pool, id).await;
+            }
+            let _ = db::update_track_loudness(pool, id, &tags.loudness).await;
+            let _ = db::update_track_ratings(pool, id, &tags.ratings.resolve(&settings.popm_email))
+                .await;
+            let _ = db::update_track_content(pool, id, &tags.content).await;
+            let _ =
+                db::update_track_genres(pool, id, &settings.genre_map.apply(&tags.genres)).await;
+            let _ = db::update_track_bitrate(pool, id, tags.bitrate).await;
+            ScanEvent::Processed(path)
+        }
+        Err(e) => ScanEvent::Error(path, Failure::of(&e)),
+    }
+}
+
+/// Add or update the track of one new or changed file.
+///
+/// For file watchers: the file's modification time is stored, and nothing
+/// else in its folder is looked at.
+pub async fn index_changed_file(pool: &SqlitePool, path: PathBuf) -> ScanEvent {
+    let mtime = path
+        .metadata()
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64);
+    index_file(pool, path, mtime, &IndexSettings::load()).await
+}
+
+/// Outcome of [`incremental_scan`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IncrementalScan {
+    /// Files new to the library
+    pub added: usize,
+    /// Known files whose modification time changed
+    pub updated: usize,
+    /// Tracks whose files are gone
+    pub removed: usize,
+    /// Known files left alone
+    pub unchanged: usize,
+    /// Files that couldn't be read
+    pub errors: usize,
+    /// Stopped early; nothing was removed
+    pub cancelled: bool,
+}
+
+impl std::fmt::Display for IncrementalScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} updated, {} removed",
+            self.added, self.updated, self.removed
+        )?;
+        if self.errors > 0 {
+            write!(f, ", {} unreadable", self.errors)?;
+        }
+        if self.cancelled {
+            write!(f, " (cancelled)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Bring the tracks under `root` up to date with the files on disk.
+///
+/// Only new files and files whose modification time changed are read;
+/// tracks whose files are gone are removed. A `root` that doesn't exist
+/// (an unplugged drive, say) changes nothing.
+///
+/// Each file checked advances `task`. Cancelling it stops after the current
+/// file and removes nothing, since the unchecked files weren't seen.
+pub async fn incremental_scan(
+    pool: &SqlitePool,
+    root: &Path,
+    task: &TaskHandle,
+) -> sqlx::Result<IncrementalScan> {
+    let mut result = IncrementalScan::default();
+    if !root.is_dir() {
+        return Ok(result);
+    }
+    let (started_at, started) = (chrono::Utc::now(), std::time::Instant::now());
+
+    task.set_phase(format!("Listing {}", root.display()));
+    let walk_root = root.to_path_buf();
+    let files: Vec<(PathBuf, Option<i64>)> = tokio::task::spawn_blocking(move || {
+        walkdir::WalkDir::new(walk_root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && scanner::is_audio_file(e.path()))
+            .map(|e| {
+                let mtime = e
+                    .metadata()
+                    .ok()
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs() as i64);
+                (e.into_path(), mtime)
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default();
+
+    // Matched by path key, so a file found under another spelling isn't
+    // taken for a new one (and its old row for a removed one)
+    let root_key = db::paths::key(&root.to_string_lossy());
+    let mut known: HashMap<String, (String, Option<i64>)> = db::get_all_track_file_info(pool)
+        .await?
+        .into_iter()
+        .map(|t| (db::paths::key(&t.path), (t.path, t.mtime)))
+        .filter(|(key, _)| Path::new(key).starts_with(&root_key))
+        .collect();
+
+    let settings = IndexSettings::load();
+    task.set_phase(format!("Checking {}", root.display()));
+    task.add_total(files.len() as u64);
+    for (path, mtime) in files {
+        if task.is_cancelled() {
+            result.cancelled = true;
+            return Ok(result);
+        }
+        task.advance(1);
+        let is_new = match known.remove(&db::paths::key(&path.to_string_lossy())) {
+            Some((_, stored)) if stored.is_some() && stored == mtime => {
+                result.unchanged += 1;
+                continue;
+            }
+            Some(_) => false,
+            None => true,
+        };
+        match index_file(pool, path, mtime, &settings).await {
+            ScanEvent::Error(path, e) => {
+                tracing::debug!(target: "scanner::incremental", "{}: {}", path.display(), e);
+                result.errors += 1;
+            }
+            _ if is_new => result.added += 1,
+            _ => result.updated += 1,
+        }
+    }
+
+    // Whatever wasn't seen on disk is gone
+    for (path, _) in known.into_values() {
+        if db::delete_track_by_path(pool, &path).await? {
+            result.removed += 1;
+        }
+    }
+
+    if result.added > 0 {
+        let threshold = config::load().library.compilation_threshold;
+        merge_compilations_under(pool, root, threshold).await?;
+    }
+    let files = result.added + result.updated + result.errors;
+    crate::stats::record_scan(pool, started_at, started.elapsed(), files).await;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::TaskKind;
+    use crate::test_utils::{
+        synthetic_library, temp_db, within_budget_async, write_synthetic_files,
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    #[ignore] // Performance budget - run with `cargo test --release perf_ -- --ignored`
+    async fn perf_scan_large_library() {
+        // Files are slower to set up than rows: a tenth of the library
+        let n = crate::test_utils::perf_library_size() / 10;
+        let dir = tempfile::tempdir().unwrap();
+        write_synthetic_files(dir.path(), &synthetic_library(n, 1));
+        let (pool, _db) = temp_db().await;
+        let task = TaskHandle::detached(TaskKind::Scan, "Scan");
+
+        let first = within_budget_async(
+            "first scan",
+            n,
+            Duration::from_secs(120),
+            incremental_scan(&pool, dir.path(), &task),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.added + first.errors, n);
+
+        let rescan = within_budget_async(
+            "rescan, nothing changed",
+            n,
+            Duration::from_secs(2),
+            incremental_scan(&pool, dir.path(), &task),
+        )
+        .await
+        .unwrap();
+        assert_eq!(rescan.unchanged, first.added);
+    }
 }
@@ -0,0 +1,602 @@
+//! Album track and disc numbering audit.
+//!
+//! Tags collected over the years rarely agree within an album: "3/12" next
+//! to a bare "3", totals that don't match the tracks on the disc, or "disc
+//! 1/1" on albums that only ever had one. The audit reads each album's tags
+//! and checks that track totals agree with the track count, that numbering
+//! runs from 1 without gaps or repeats, and that disc numbers are tagged on
+//! every track of a multi-disc album and on none of a single-disc one.
+//!
+//! Albums that fail get `numbering_inconsistent` set on their tracks, which
+//! the quality check reports. Each audit also works out the tag writes that
+//! normalize what can be fixed; gaps and repeats are only reported, since
+//! the right numbers can't be known from the tags alone.
+
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use crate::metadata::{self, Numbering};
+
+/// A track's numbering as read from its file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberedTrack {
+    /// Database track ID
+    pub track_id: i64,
+    /// File path
+    pub path: String,
+    /// Track and disc numbers and totals in the file's tags
+    pub numbering: Numbering,
+}
+
+/// How the tagged totals of a disc (or of an album's discs) go wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TotalProblem {
+    /// Some tracks carry a total and this many don't
+    Missing(usize),
+    /// Tracks carry different totals
+    Conflicting(Vec<u32>),
+    /// The total is smaller than the highest number tagged
+    TooSmall { total: u32, highest: u32 },
+}
+
+/// One way an album's numbering doesn't add up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumberingIssue {
+    /// Disc tags ("1/1") on an album with one disc
+    SingleDiscTagged,
+    /// This many tracks of a multi-disc album have no disc number
+    MissingDiscNumbers(usize),
+    /// Track totals on a disc (`None` on a one-disc album) disagree
+    TrackTotals {
+        disc: Option<u32>,
+        problem: TotalProblem,
+    },
+    /// Disc totals disagree
+    DiscTotals(TotalProblem),
+    /// Track numbers missing below the highest one
+    Gap {
+        disc: Option<u32>,
+        missing: Vec<u32>,
+    },
+    /// A track number used by more than one track
+    Duplicate { disc: Option<u32>, number: u32 },
+}
+
+impl fmt::Display for TotalProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(1) => write!(f, "1 track has none"),
+            Self::Missing(n) => write!(f, "{} tracks have none", n),
+            Self::Conflicting(totals) => write!(f, "tagged as {}", join(totals, ", ")),
+            Self::TooSmall { total, highest } => {
+                write!(f, "tagged as {} but the highest is {}", total, highest)
+            }
+        }
+    }
+}
+
+impl fmt::Display for NumberingIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let on_disc = |disc: &Option<u32>| match disc {
+            Some(d) => format!(" on disc {}", d),
+            None => String::new(),
+        };
+        match self {
+            Self::SingleDiscTagged => write!(f, "Disc tagged on a single-disc album"),
+            Self::MissingDiscNumbers(1) => write!(f, "1 track has no disc number"),
+            Self::MissingDiscNumbers(n) => write!(f, "{} tracks have no disc number", n),
+            Self::TrackTotals { disc, problem } => {
+                write!(f, "Track totals{}: {}", on_disc(disc), problem)
+            }
+            Self::DiscTotals(problem) => write!(f, "Disc totals: {}", problem),
+            Self::Gap { disc, missing } => {
+                write!(f, "Missing track {}{}", join(missing, ", "), on_disc(disc))
+            }
+            Self::Duplicate { disc, number } => {
+                write!(f, "Track {} appears twice{}", number, on_disc(disc))
+            }
+        }
+    }
+}
+
+fn join(numbers: &[u32], sep: &str) -> String {
+    numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+/// A tag write that normalizes one track's numbering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberingFix {
+    /// Database track ID
+    pub track_id: i64,
+    /// File path
+    pub path: String,
+    /// Numbering in the file now
+    pub before: Numbering,
+    /// Numbering to write
+    pub after: Numbering,
+}
+
+/// The audit of one album.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlbumNumbering {
+    /// Database album ID
+    pub album_id: i64,
+    /// Album title
+    pub album: String,
+    /// Album artist name
+    pub artist: String,
+    /// Tracks whose tags could be read
+    pub tracks: Vec<NumberedTrack>,
+    /// What doesn't add up (empty if the album is consistent)
+    pub issues: Vec<NumberingIssue>,
+    /// Writes that normalize the fixable issues
+    pub fixes: Vec<NumberingFix>,
+}
+
+impl AlbumNumbering {
+    /// Whether the album passed the audit.
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Check one album's numbering.
+///
+/// Returns the issues found and the writes that fix the totals and disc
+/// tags. A total is only written where it can be told: the one total the
+/// tracks agree on, or else the track count when numbering is complete.
+pub fn check(tracks: &[NumberedTrack]) -> (Vec<NumberingIssue>, Vec<NumberingFix>) {
+    let mut issues = Vec::new();
+    let mut after: Vec<Numbering> = tracks.iter().map(|t| t.numbering).collect();
+
+    let multi_disc = tracks.iter().any(|t| {
+        t.numbering.disc_number.is_some_and(|d| d > 1)
+            || t.numbering.total_discs.is_some_and(|d| d > 1)
+    });
+
+    // Group tracks by disc; on a one-disc album that's every track
+    let mut discs: BTreeMap<Option<u32>, Vec<usize>> = BTreeMap::new();
+    if multi_disc {
+        let untagged = tracks
+            .iter()
+            .filter(|t| t.numbering.disc_number.is_none())
+            .count();
+        if untagged > 0 {
+            issues.push(NumberingIssue::MissingDiscNumbers(untagged));
+        }
+        for (i, t) in tracks.iter().enumerate() {
+            if let Some(disc) = t.numbering.disc_number {
+                discs.entry(Some(disc)).or_default().push(i);
+            }
+        }
+
+        let on_discs: Vec<usize> = discs.values().flatten().copied().collect();
+        let numbers: BTreeSet<u32> = discs.keys().flatten().copied().collect();
+        let totals: Vec<Option<u32>> = on_discs
+            .iter()
+            .map(|&i| tracks[i].numbering.total_discs)
+            .collect();
+        if let Some(problem) = total_problem(&totals, &numbers) {
+            issues.push(NumberingIssue::DiscTotals(problem));
+            if let Some(expected) = expected_total(&totals, &numbers) {
+                for &i in &on_discs {
+                    after[i].total_discs = Some(expected);
+                }
+            }
+        }
+    } else {
+        if tracks
+            .iter()
+            .any(|t| t.numbering.disc_number.is_some() || t.numbering.total_discs.is_some())
+        {
+            issues.push(NumberingIssue::SingleDiscTagged);
+            for n in &mut after {
+                n.disc_number = None;
+                n.total_discs = None;
+            }
+        }
+        discs.insert(None, (0..tracks.len()).collect());
+    }
+
+    for (&disc, indices) in &discs {
+        let mut numbers = BTreeSet::new();
+        for &i in indices {
+            if let Some(number) = tracks[i].numbering.track_number
+                && !numbers.insert(number)
+            {
+                let issue = NumberingIssue::Duplicate { disc, number };
+                if !issues.contains(&issue) {
+                    issues.push(issue);
+                }
+            }
+        }
+        if let Some(&highest) = numbers.last() {
+            let missing: Vec<u32> = (1..highest).filter(|n| !numbers.contains(n)).collect();
+            if !missing.is_empty() {
+                issues.push(NumberingIssue::Gap { disc, missing });
+            }
+        }
+
+        let totals: Vec<Option<u32>> = indices
+            .iter()
+            .map(|&i| tracks[i].numbering.total_tracks)
+            .collect();
+        if let Some(problem) = total_problem(&totals, &numbers) {
+            issues.push(NumberingIssue::TrackTotals { disc, problem });
+            if let Some(expected) = expected_total(&totals, &numbers) {
+                for &i in indices {
+                    after[i].total_tracks = Some(expected);
+                }
+            }
+        }
+    }
+
+    let fixes = tracks
+        .iter()
+        .zip(after)
+        .filter(|(t, after)| t.numbering != *after)
+        .map(|(t, after)| NumberingFix {
+            track_id: t.track_id,
+            path: t.path.clone(),
+            before: t.numbering,
+            after,
+        })
+        .collect();
+    (issues, fixes)
+}
+
+/// What's wrong with a group's tagged totals, given the numbers in the
+/// group. Untagged totals across the whole group are fine.
+fn total_problem(totals: &[Option<u32>], numbers: &BTreeSet<u32>) -> Option<TotalProblem> {
+    let tagged: BTreeSet<u32> = totals.iter().flatten().copied().collect();
+    let untagged = totals.iter().filter(|t| t.is_none()).count();
+    let highest = numbers.last().copied().unwrap_or(0);
+    match tagged.len() {
+        0 => None,
+        1 => {
+            let total = *tagged.first()?;
+            if total < highest {
+                Some(TotalProblem::TooSmall { total, highest })
+            } else if untagged > 0 {
+                Some(TotalProblem::Missing(untagged))
+            } else {
+                None
+            }
+        }
+        _ => Some(TotalProblem::Conflicting(tagged.into_iter().collect())),
+    }
+}
+
+/// The total a group should carry, if it can be told from its tags: the one
+/// total the tracks agree on (when no number is above it), or else the
+/// highest number when numbering runs 1..n without gaps.
+fn expected_total(totals: &[Option<u32>], numbers: &BTreeSet<u32>) -> Option<u32> {
+    let tagged: BTreeSet<u32> = totals.iter().flatten().copied().collect();
+    let highest = numbers.last().copied()?;
+    let complete = (1..=highest).all(|n| numbers.contains(&n));
+    if tagged.len() == 1
+        && let Some(&total) = tagged.first()
+        && total >= highest
+    {
+        return Some(total);
+    }
+    // With conflicting totals, the count only wins if some track agrees
+    (complete && (tagged.len() <= 1 || tagged.contains(&highest))).then_some(highest)
+}
+
+/// Audit the numbering of the given albums (every album if `album_ids` is
+/// `None`), reading each track's tags, and store the result on the tracks.
+///
+/// Tracks whose files can't be read are left out of their album's audit.
+pub async fn audit(
+    pool: &SqlitePool,
+    album_ids: Option<&[i64]>,
+) -> sqlx::Result<Vec<AlbumNumbering>> {
+    let rows: Vec<(i64, String, String, i64, String)> = sqlx::query_as(
+        r#"SELECT al.id, al.title, COALESCE(a.name, 'Unknown Artist'), t.id, t.path
+           FROM tracks t
+           JOIN albums al ON t.album_id = al.id
+           LEFT JOIN artists a ON al.artist_id = a.id
+           ORDER BY al.id, t.path"#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut albums: Vec<AlbumNumbering> = Vec::new();
+    for (album_id, album, artist, track_id, path) in rows {
+        if album_ids.is_some_and(|ids| !ids.contains(&album_id)) {
+            continue;
+        }
+        let Ok(numbering) = metadata::read_numbering(Path::new(&path)) else {
+            continue;
+        };
+        if albums.last().is_none_or(|a| a.album_id != album_id) {
+            albums.push(AlbumNumbering {
+                album_id,
+                album,
+                artist,
+                tracks: Vec::new(),
+                issues: Vec::new(),
+                fixes: Vec::new(),
+            });
+        }
+        if let Some(current) = albums.last_mut() {
+            current.tracks.push(NumberedTrack {
+                track_id,
+                path,
+                numbering,
+            });
+        }
+    }
+
+    for album in &mut albums {
+        (album.issues, album.fixes) = check(&album.tracks);
+        mark(pool, album).await?;
+    }
+    Ok(albums)
+}
+
+/// Set or clear the inconsistent flag (and its quality flag) on an
+/// album's tracks.
+async fn mark(pool: &SqlitePool, album: &AlbumNumbering) -> sqlx::Result<()> {
+    crate::readonly::ensure_writable("Storing the numbering audit")?;
+    let flag = crate::health::QualityFlags::NUMBERING_INCONSISTENT.to_bits_i64();
+    let inconsistent = !album.is_consistent();
+    for track in &album.tracks {
+        sqlx::query(
+            r#"
+            UPDATE tracks SET
+                numbering_inconsistent = ?,
+                quality_flags = CASE WHEN quality_flags IS NULL THEN NULL
+                                     ELSE (quality_flags & ~?) | ? END
+            WHERE id = ?
+            "#,
+        )
+        .bind(inconsistent)
+        .bind(flag)
+        .bind(if inconsistent { flag } else { 0 })
+        .bind(track.track_id)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// The albums of the given tracks.
+pub async fn albums_of(pool: &SqlitePool, track_ids: &[i64]) -> sqlx::Result<Vec<i64>> {
+    let mut albums = BTreeSet::new();
+    for &id in track_ids {
+        let album: Option<i64> = sqlx::query_scalar("SELECT album_id FROM tracks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?
+            .flatten();
+        albums.extend(album);
+    }
+    Ok(albums.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(number: u32, total: Option<u32>, disc: Option<(u32, Option<u32>)>) -> NumberedTrack {
+        NumberedTrack {
+            track_id: i64::from(number) + 100 * i64::from(disc.map_or(0, |d| d.0)),
+            path: format!("/music/{:?}-{}.flac", disc, number),
+            numbering: Numbering {
+                track_number: Some(number),
+                total_tracks: total,
+                disc_number: disc.map(|d| d.0),
+                total_discs: disc.and_then(|d| d.1),
+            },
+        }
+    }
+
+    #[test]
+    fn test_consistent_albums_pass() {
+        let tagged: Vec<_> = (1..=3).map(|n| track(n, Some(3), None)).collect();
+        assert_eq!(check(&tagged), (Vec::new(), Vec::new()));
+
+        // No totals at all is untidy but not inconsistent
+        let bare: Vec<_> = (1..=3).map(|n| track(n, None, None)).collect();
+        assert_eq!(check(&bare), (Vec::new(), Vec::new()));
+
+        let two_discs: Vec<_> = (1..=2)
+            .flat_map(|d| (1..=2).map(move |n| track(n, Some(2), Some((d, Some(2))))))
+            .collect();
+        assert_eq!(check(&two_discs), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn test_missing_totals_filled_from_the_agreed_total() {
+        // Tracks 1-3 of a 12-track album, one without a total
+        let tracks = vec![
+            track(1, Some(12), None),
+            track(2, None, None),
+            track(3, Some(12), None),
+        ];
+        let (issues, fixes) = check(&tracks);
+        assert_eq!(
+            issues,
+            [NumberingIssue::TrackTotals {
+                disc: None,
+                problem: TotalProblem::Missing(1),
+            }]
+        );
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].track_id, 2);
+        assert_eq!(fixes[0].after.total_tracks, Some(12));
+    }
+
+    #[test]
+    fn test_conflicting_totals_use_the_track_count() {
+        let tracks = vec![
+            track(1, Some(3), None),
+            track(2, Some(12), None),
+            track(3, Some(3), None),
+        ];
+        let (issues, fixes) = check(&tracks);
+        assert_eq!(
+            issues,
+            [NumberingIssue::TrackTotals {
+                disc: None,
+                problem: TotalProblem::Conflicting(vec![3, 12]),
+            }]
+        );
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].after.total_tracks, Some(3));
+
+        // With a gap, neither total can be trusted
+        let gapped = vec![track(1, Some(5), None), track(3, Some(12), None)];
+        let (issues, fixes) = check(&gapped);
+        assert!(issues.contains(&NumberingIssue::Gap {
+            disc: None,
+            missing: vec![2],
+        }));
+        assert!(fixes.is_empty());
+    }
+
+    #[test]
+    fn test_total_smaller_than_track_number() {
+        let tracks: Vec<_> = (1..=4).map(|n| track(n, Some(3), None)).collect();
+        let (issues, fixes) = check(&tracks);
+        assert_eq!(
+            issues,
+            [NumberingIssue::TrackTotals {
+                disc: None,
+                problem: TotalProblem::TooSmall {
+                    total: 3,
+                    highest: 4,
+                },
+            }]
+        );
+        assert!(fixes.iter().all(|f| f.after.total_tracks == Some(4)));
+        assert_eq!(fixes.len(), 4);
+    }
+
+    #[test]
+    fn test_single_disc_tags_removed() {
+        let tracks = vec![
+            track(1, Some(2), Some((1, Some(1)))),
+            track(2, Some(2), None),
+        ];
+        let (issues, fixes) = check(&tracks);
+        assert_eq!(issues, [NumberingIssue::SingleDiscTagged]);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].after.disc_number, None);
+        assert_eq!(fixes[0].after.total_discs, None);
+        assert_eq!(fixes[0].after.total_tracks, Some(2));
+    }
+
+    #[test]
+    fn test_multi_disc_album() {
+        let tracks = vec![
+            track(1, Some(2), Some((1, Some(2)))),
+            track(2, Some(2), Some((1, None))),
+            track(1, Some(1), Some((2, Some(2)))),
+            track(1, Some(1), None),
+            track(1, None, Some((2, Some(2)))),
+        ];
+        let (issues, fixes) = check(&tracks);
+        assert_eq!(
+            issues,
+            [
+                NumberingIssue::MissingDiscNumbers(1),
+                NumberingIssue::DiscTotals(TotalProblem::Missing(1)),
+                NumberingIssue::Duplicate {
+                    disc: Some(2),
+                    number: 1,
+                },
+                NumberingIssue::TrackTotals {
+                    disc: Some(2),
+                    problem: TotalProblem::Missing(1),
+                },
+            ]
+        );
+        // Disc 1's second track gets its disc total, disc 2's last its
+        // track total; the track without a disc is left alone
+        let after: Vec<_> = fixes.iter().map(|f| (f.path.as_str(), f.after)).collect();
+        assert_eq!(after.len(), 2);
+        assert_eq!(after[0].1.total_discs, Some(2));
+        assert_eq!(after[1].1.total_tracks, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_audit_flags_and_normalizes_files() {
+        use crate::db;
+        use crate::metadata::TrackMetadata;
+        use crate::test_utils::{AudioFixture, temp_db, write_audio_fixture};
+
+        let (pool, _db) = temp_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let artist = db::get_or_create_artist(&pool, "Radiohead").await.unwrap();
+        let album = db::get_or_create_album(&pool, "OK Computer", Some(artist))
+            .await
+            .unwrap();
+        let mut ids = Vec::new();
+        for (n, total) in [(1, Some(2)), (2, None)] {
+            let path = dir.path().join(format!("{}.flac", n));
+            std::fs::rename(write_audio_fixture(dir.path(), AudioFixture::Flac), &path).unwrap();
+            let numbering = Numbering {
+                track_number: Some(n),
+                total_tracks: total,
+                ..Default::default()
+            };
+            let identified = crate::enrichment::domain::IdentifiedTrack {
+                track_number: Some(n),
+                ..Default::default()
+            };
+            metadata::write(&path, &identified, &Default::default()).unwrap();
+            metadata::write_numbering(&path, &numbering).unwrap();
+            let meta = TrackMetadata {
+                title: format!("Track {}", n),
+                artist: "Radiohead".to_string(),
+                album: "OK Computer".to_string(),
+                duration: 1,
+                track_number: Some(n),
+            };
+            ids.push(
+                db::insert_track(
+                    &pool,
+                    &meta,
+                    path.to_str().unwrap(),
+                    Some(artist),
+                    Some(album),
+                )
+                .await
+                .unwrap(),
+            );
+        }
+
+        let albums = audit(&pool, None).await.unwrap();
+        assert_eq!(albums.len(), 1);
+        assert!(!albums[0].is_consistent());
+        let flagged: Vec<bool> =
+            sqlx::query_scalar("SELECT numbering_inconsistent FROM tracks ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(flagged, [true, true]);
+
+        for fix in &albums[0].fixes {
+            let written = metadata::write_numbering(Path::new(&fix.path), &fix.after).unwrap();
+            assert_eq!(written, ["total_tracks"]);
+        }
+        let album_ids = albums_of(&pool, &ids).await.unwrap();
+        let albums = audit(&pool, Some(&album_ids)).await.unwrap();
+        assert!(albums[0].is_consistent());
+        let flagged: Vec<bool> =
+            sqlx::query_scalar("SELECT numbering_inconsistent FROM tracks ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(flagged, [false, false]);
+    }
+}
@@ -0,0 +1,105 @@
+//! Remote tracks: library entries whose path is an HTTP stream or a cloud
+//! drive share link instead of a file.
+//!
+//! They sit in the library next to files, with their source in the
+//! `tracks.source` column ([`TrackSource`]). There are no tags to read, so
+//! the title, artist and album are given when the track is added. Scans
+//! leave them alone, the player streams them, and organize, fingerprinting
+//! and tag writes pass them over.
+
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::error::{Error, Result};
+use crate::metadata::TrackMetadata;
+use crate::model::TrackSource;
+
+/// Add a stream or cloud drive URL to the library, or update the track
+/// already stored under it. Empty artist and album names are stored as
+/// unknown. Returns the track's ID.
+///
+/// # Errors
+///
+/// [`Error::InvalidFormat`] if `url` isn't an `http://` or `https://` URL.
+pub async fn add_remote(pool: &SqlitePool, url: &str, meta: &TrackMetadata) -> Result<i64> {
+    let source = TrackSource::detect(url);
+    if source.is_local() {
+        return Err(Error::InvalidFormat(format!(
+            "{} is not an http:// or https:// URL",
+            url
+        )));
+    }
+
+    let artist_id = match meta.artist.trim() {
+        "" => None,
+        artist => Some(db::get_or_create_artist(pool, artist).await?),
+    };
+    let album_id = match meta.album.trim() {
+        "" => None,
+        album => Some(db::get_or_create_album(pool, album, artist_id).await?),
+    };
+    let id = db::insert_track(pool, meta, url, artist_id, album_id).await?;
+    tracing::info!("Added {} track {}", source.as_str(), url);
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{insert_mock_track, temp_db};
+
+    fn remote_meta(title: &str) -> TrackMetadata {
+        TrackMetadata {
+            title: title.to_string(),
+            artist: "Station".to_string(),
+            album: String::new(),
+            duration: 0,
+            track_number: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_tracks_sit_beside_files() {
+        let (pool, _dir) = temp_db().await;
+        insert_mock_track(&pool, "/music/a.mp3").await;
+        let stream = add_remote(&pool, "http://radio.example/live", &remote_meta("Live"))
+            .await
+            .unwrap();
+        let cloud = add_remote(
+            &pool,
+            "https://www.dropbox.com/s/abc/b.flac?dl=0",
+            &remote_meta("Demo"),
+        )
+        .await
+        .unwrap();
+
+        let tracks = db::get_all_tracks_with_metadata(&pool).await.unwrap();
+        let source = |id| tracks.iter().find(|t| t.id == id).unwrap().source;
+        assert_eq!(source(stream), TrackSource::Stream);
+        assert_eq!(source(cloud), TrackSource::Cloud);
+        let stored = tracks.iter().find(|t| t.id == stream).unwrap();
+        assert_eq!(stored.path, "http://radio.example/live");
+        assert_eq!(stored.album_name.as_str(), "Unknown Album");
+
+        // Scans only compare files with the disk
+        let files = db::get_all_track_file_info(&pool).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "/music/a.mp3");
+
+        // Adding the URL again updates the track
+        let again = add_remote(&pool, "http://radio.example/live", &remote_meta("Live 2"))
+            .await
+            .unwrap();
+        assert_eq!(again, stream);
+    }
+
+    #[tokio::test]
+    async fn test_paths_are_not_remote() {
+        let (pool, _dir) = temp_db().await;
+        let err = add_remote(&pool, "/music/a.mp3", &remote_meta("A"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidFormat(_)));
+        assert_eq!(db::count_tracks(&pool).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,247 @@
+//! ReplayGain scanning of library tracks.
+//!
+//! Each track is decoded and measured ([`crate::player::replaygain`]), its
+//! `REPLAYGAIN_*` tags written and the values stored with it. Album gain
+//! and peak are measured over all of an album's tracks, so they're only
+//! worked out when the whole album is scanned; scanning part of one leaves
+//! its tracks' album values as they were.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use sqlx::SqlitePool;
+
+use crate::metadata::Loudness;
+use crate::player::replaygain::{self, TrackLoudness};
+use crate::tasks::TaskHandle;
+use crate::{db, metadata};
+
+/// Which tracks to scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    /// Every track in the library
+    Library,
+    /// Tracks inside this folder
+    Under(PathBuf),
+    /// These track IDs
+    Tracks(HashSet<i64>),
+}
+
+/// What a scan measured
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanReport {
+    /// Path and values of each track measured
+    pub tracks: Vec<(String, Loudness)>,
+    /// Albums whose album gain was measured
+    pub albums: usize,
+    /// Files that couldn't be measured or tagged, with the reason; their
+    /// stored values are left alone
+    pub failed: Vec<(String, String)>,
+}
+
+impl ScanReport {
+    /// One line for the status bar or console
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "ReplayGain: measured {} tracks and {} albums",
+            self.tracks.len(),
+            self.albums
+        );
+        if !self.failed.is_empty() {
+            summary.push_str(&format!(", {} failed", self.failed.len()));
+        }
+        summary
+    }
+}
+
+/// Database row for [`scan`], one per track
+#[derive(Debug, sqlx::FromRow)]
+struct TrackRow {
+    id: i64,
+    path: String,
+    album_id: Option<i64>,
+    album_gain: Option<f32>,
+    album_peak: Option<f32>,
+    /// Tracks of its album in the library
+    album_tracks: i64,
+}
+
+/// Measure the tracks in `scope`, an album at a time. Unless `dry_run`,
+/// their tags are written and the values stored.
+pub async fn scan(
+    pool: &SqlitePool,
+    scope: &Scope,
+    dry_run: bool,
+    task: &TaskHandle,
+) -> sqlx::Result<ScanReport> {
+    if !dry_run {
+        crate::readonly::ensure_writable("Writing ReplayGain tags")?;
+    }
+    let rows: Vec<TrackRow> = sqlx::query_as(
+        r#"
+        SELECT
+            t.id, t.path, t.album_id,
+            t.replaygain_album_gain AS album_gain, t.replaygain_album_peak AS album_peak,
+            (SELECT COUNT(*) FROM tracks o
+             WHERE o.album_id = t.album_id AND o.source = 'local') AS album_tracks
+        FROM tracks t
+        WHERE t.source = 'local'
+        ORDER BY t.album_id, t.track_number, t.path
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Albums in order, then tracks without one, each on its own
+    let mut groups: BTreeMap<(Option<i64>, i64), Vec<TrackRow>> = BTreeMap::new();
+    for row in rows {
+        let selected = match scope {
+            Scope::Library => true,
+            Scope::Under(folder) => Path::new(&row.path).starts_with(folder),
+            Scope::Tracks(ids) => ids.contains(&row.id),
+        };
+        if selected {
+            let key = match row.album_id {
+                Some(album) => (Some(album), 0),
+                None => (None, row.id),
+            };
+            groups.entry(key).or_default().push(row);
+        }
+    }
+
+    let mut report = ScanReport::default();
+    task.set_phase("Measuring loudness");
+    task.set_total(groups.values().map(|g| g.len() as u64).sum());
+    for tracks in groups.into_values() {
+        let mut measured = Vec::new();
+        for row in &tracks {
+            if task.is_cancelled() {
+                return Ok(report);
+            }
+            let path = PathBuf::from(&row.path);
+            let loudness = tokio::task::spawn_blocking(move || replaygain::analyze(&path))
+                .await
+                .map_err(std::io::Error::other)?;
+            match loudness {
+                Ok(loudness) if loudness.gain().is_some() => measured.push((row, loudness)),
+                Ok(_) => report
+                    .failed
+                    .push((row.path.clone(), "no sound to measure".to_string())),
+                Err(e) => report.failed.push((row.path.clone(), e.to_string())),
+            }
+            task.advance(1);
+        }
+
+        // The album's values need every one of its tracks
+        let whole_album =
+            tracks[0].album_id.is_some() && measured.len() as i64 == tracks[0].album_tracks;
+        let album = whole_album.then(|| {
+            let scans: Vec<TrackLoudness> = measured.iter().map(|(_, l)| l.clone()).collect();
+            replaygain::album(&scans)
+        });
+        if album.is_some() {
+            report.albums += 1;
+        }
+
+        for (row, scan) in measured {
+            let loudness = Loudness {
+                track_gain: scan.gain(),
+                track_peak: Some(scan.peak),
+                album_gain: album.map_or(row.album_gain, |(gain, _)| gain),
+                album_peak: album.map_or(row.album_peak, |(_, peak)| Some(peak)),
+            };
+            if !dry_run {
+                if let Err(e) = metadata::write_loudness(Path::new(&row.path), &loudness) {
+                    report.failed.push((row.path.clone(), format!("{:#}", e)));
+                    continue;
+                }
+                db::update_track_loudness(pool, row.id, &loudness).await?;
+            }
+            report.tracks.push((row.path.clone(), loudness));
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::TaskKind;
+    use crate::test_utils::insert_mock_track;
+
+    /// A 16-bit stereo 44.1 kHz sine wave at `amplitude` (of full scale)
+    fn write_wav(path: &Path, amplitude: f32, secs: f32) {
+        let frames = (44100.0 * secs) as u32;
+        let mut data = Vec::with_capacity(frames as usize * 4);
+        for i in 0..frames {
+            let t = i as f32 / 44100.0;
+            let sample =
+                ((2.0 * std::f32::consts::PI * 1000.0 * t).sin() * amplitude * 32767.0) as i16;
+            data.extend_from_slice(&sample.to_le_bytes());
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+        let mut wav = Vec::with_capacity(44 + data.len());
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&44100u32.to_le_bytes());
+        wav.extend_from_slice(&(44100u32 * 4).to_le_bytes());
+        wav.extend_from_slice(&4u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&data);
+        std::fs::write(path, wav).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scan_album_and_part_of_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", dir.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        let (loud, quiet) = (dir.path().join("loud.wav"), dir.path().join("quiet.wav"));
+        write_wav(&loud, 0.5, 3.0);
+        write_wav(&quiet, 0.15, 3.0);
+        // Both on the mock album
+        let loud_id = insert_mock_track(&pool, &loud.to_string_lossy()).await;
+        let quiet_id = insert_mock_track(&pool, &quiet.to_string_lossy()).await;
+        let task = TaskHandle::detached(TaskKind::Maintenance, "ReplayGain");
+
+        let report = scan(&pool, &Scope::Library, false, &task).await.unwrap();
+        assert!(report.failed.is_empty(), "{:?}", report.failed);
+        assert_eq!((report.tracks.len(), report.albums), (2, 1));
+        let (_, tagged) = metadata::read_with_loudness(&loud).unwrap();
+        let stored: (Option<f32>, Option<f32>) = sqlx::query_as(
+            "SELECT replaygain_track_gain, replaygain_album_gain FROM tracks WHERE id = ?",
+        )
+        .bind(loud_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        // The tags hold two decimals
+        let close = |a: Option<f32>, b: Option<f32>| (a.unwrap() - b.unwrap()).abs() < 0.01;
+        assert!(close(tagged.track_gain, stored.0) && close(tagged.album_gain, stored.1));
+        // 10.5 dB apart; the album is closer to the loud track
+        let quiet_gain = report.tracks[1].1.track_gain.unwrap();
+        let loud_gain = tagged.track_gain.unwrap();
+        assert!((quiet_gain - loud_gain - 10.46).abs() < 0.1);
+        let album_gain = tagged.album_gain.unwrap();
+        assert!(album_gain > loud_gain && album_gain < loud_gain + 3.0);
+        assert!((tagged.album_peak.unwrap() - 0.5).abs() < 0.01);
+
+        // Only part of the album: its album gain stays as it was
+        write_wav(&quiet, 0.5, 3.0);
+        let scope = Scope::Tracks(HashSet::from([quiet_id]));
+        let report = scan(&pool, &scope, true, &task).await.unwrap();
+        assert_eq!((report.tracks.len(), report.albums), (1, 0));
+        let (_, values) = &report.tracks[0];
+        assert!((values.track_gain.unwrap() - loud_gain).abs() < 0.1);
+        assert!(close(values.album_gain, Some(album_gain)));
+        // A dry run writes nothing (the file was replaced untagged)
+        let (_, tagged) = metadata::read_with_loudness(&quiet).unwrap();
+        assert!(tagged.is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+//! Track number inference for untagged files.
+//!
+//! When a file has no track number tag, the scan guesses one from a numeric
+//! file name prefix ("03 - Karma Police.mp3", "1-03 Karma Police.flac"), or
+//! else from the file's position among the audio files in its folder. Guessed
+//! numbers are stored with `track_number_inferred` set, which the quality
+//! check reports and the enrich pane can write to the files' tags.
+
+use sqlx::SqlitePool;
+use std::path::Path;
+
+use crate::scanner::is_audio_file;
+
+/// A track whose number was guessed rather than read from its tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredTrackNumber {
+    /// Database track ID
+    pub track_id: i64,
+    /// File path
+    pub path: String,
+    /// Guessed track number
+    pub track_number: u32,
+}
+
+/// Guess the track number of an untagged file.
+///
+/// Numeric file name prefixes win. Folder order is only used when no audio
+/// file in the folder has a prefix, since a mix of numbered and unnumbered
+/// names says nothing reliable about order.
+pub fn infer_track_number(path: &Path) -> Option<u32> {
+    let stem = path.file_stem()?.to_str()?;
+    if let Some(number) = number_from_file_name(stem) {
+        return Some(number);
+    }
+
+    let mut siblings: Vec<String> = std::fs::read_dir(path.parent()?)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && is_audio_file(p))
+        .filter_map(|p| p.file_name()?.to_str().map(String::from))
+        .collect();
+    let prefixed = siblings.iter().any(|name| {
+        Path::new(name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(number_from_file_name)
+            .is_some()
+    });
+    if prefixed {
+        return None;
+    }
+    siblings.sort_by_key(|name| name.to_lowercase());
+    let name = path.file_name()?.to_str()?;
+    let position = siblings.iter().position(|n| n == name)?;
+    u32::try_from(position + 1).ok()
+}
+
+/// Track number from a file name prefix.
+///
+/// Accepts "03 Title", "03 - Title", "03. Title", "03_Title", a bare "03",
+/// and disc-prefixed forms "1-03 Title", "1.03 Title" and "103 Title". Four
+/// or more digits (usually a year) and numbers run into words ("2Pac") are
+/// not track numbers.
+pub fn number_from_file_name(stem: &str) -> Option<u32> {
+    let s = stem.trim_start();
+    let digits = leading_digits(s);
+    let rest = &s[digits.len()..];
+    if digits.is_empty() || digits.len() > 3 {
+        return None;
+    }
+
+    // Disc-track: "1-03 Title" / "1.03 Title"
+    if digits.len() == 1
+        && let Some(after) = rest.strip_prefix(['-', '.'])
+    {
+        let track = leading_digits(after);
+        if track.len() == 2 && is_separator(&after[track.len()..]) {
+            return track.parse().ok().filter(|&n| n > 0);
+        }
+    }
+
+    if !is_separator(rest) {
+        return None;
+    }
+    let number: u32 = digits.parse().ok()?;
+    // "103 Title" is disc 1, track 3
+    let number = if digits.len() == 3 {
+        number % 100
+    } else {
+        number
+    };
+    (number > 0).then_some(number)
+}
+
+fn leading_digits(s: &str) -> &str {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    &s[..end]
+}
+
+/// Whether `rest` (what follows a number) starts with a separator or is empty
+fn is_separator(rest: &str) -> bool {
+    rest.is_empty() || rest.starts_with([' ', '.', '-', '_', ')'])
+}
+
+/// Flag a track's number as guessed.
+pub async fn mark_inferred(pool: &SqlitePool, track_id: i64) -> sqlx::Result<()> {
+    sqlx::query("UPDATE tracks SET track_number_inferred = TRUE WHERE id = ?")
+        .bind(track_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Clear the guessed flag once the number is in the file's tags.
+pub async fn mark_confirmed(pool: &SqlitePool, track_id: i64) -> sqlx::Result<()> {
+    sqlx::query("UPDATE tracks SET track_number_inferred = FALSE WHERE id = ?")
+        .bind(track_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Every track with a guessed number, by path.
+pub async fn inferred_tracks(pool: &SqlitePool) -> sqlx::Result<Vec<InferredTrackNumber>> {
+    let rows: Vec<(i64, String, i64)> = sqlx::query_as(
+        r#"SELECT id, path, track_number FROM tracks
+           WHERE track_number_inferred AND track_number IS NOT NULL
+           ORDER BY path"#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|(track_id, path, n)| {
+            Some(InferredTrackNumber {
+                track_id,
+                path,
+                track_number: u32::try_from(n).ok()?,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::metadata::TrackMetadata;
+    use std::fs::File;
+
+    #[test]
+    fn test_number_from_file_name() {
+        assert_eq!(number_from_file_name("03 - Karma Police"), Some(3));
+        assert_eq!(number_from_file_name("03. Karma Police"), Some(3));
+        assert_eq!(number_from_file_name("3_karma_police"), Some(3));
+        assert_eq!(number_from_file_name("12"), Some(12));
+        assert_eq!(number_from_file_name("1-03 Karma Police"), Some(3));
+        assert_eq!(number_from_file_name("2.11 Lucky"), Some(11));
+        assert_eq!(number_from_file_name("103 Karma Police"), Some(3));
+        assert_eq!(number_from_file_name("1997 - OK Computer"), None);
+        assert_eq!(number_from_file_name("2Pac - Changes"), None);
+        assert_eq!(number_from_file_name("Karma Police"), None);
+        assert_eq!(number_from_file_name("00 Hidden Intro"), None);
+    }
+
+    #[test]
+    fn test_infer_from_folder_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["Lucky.mp3", "airbag.mp3", "Karma Police.mp3", "cover.jpg"] {
+            File::create(dir.path().join(name)).unwrap();
+        }
+
+        assert_eq!(infer_track_number(&dir.path().join("airbag.mp3")), Some(1));
+        assert_eq!(
+            infer_track_number(&dir.path().join("Karma Police.mp3")),
+            Some(2)
+        );
+        assert_eq!(infer_track_number(&dir.path().join("Lucky.mp3")), Some(3));
+
+        // Once some files are numbered, order alone proves nothing
+        File::create(dir.path().join("04 Exit Music.mp3")).unwrap();
+        assert_eq!(infer_track_number(&dir.path().join("Lucky.mp3")), None);
+        assert_eq!(
+            infer_track_number(&dir.path().join("04 Exit Music.mp3")),
+            Some(4)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inferred_flag_cleared_by_tagged_rescan() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", temp.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        let meta = TrackMetadata {
+            title: "Airbag".to_string(),
+            artist: "Radiohead".to_string(),
+            album: "OK Computer".to_string(),
+            duration: 284,
+            track_number: Some(1),
+        };
+        let id = db::insert_track(&pool, &meta, "/m/01 Airbag.mp3", None, None)
+            .await
+            .unwrap();
+        mark_inferred(&pool, id).await.unwrap();
+        assert_eq!(
+            inferred_tracks(&pool).await.unwrap(),
+            [InferredTrackNumber {
+                track_id: id,
+                path: "/m/01 Airbag.mp3".to_string(),
+                track_number: 1,
+            }]
+        );
+
+        // A later scan that reads the number from tags makes it authoritative
+        db::insert_track(&pool, &meta, "/m/01 Airbag.mp3", None, None)
+            .await
+            .unwrap();
+        assert!(inferred_tracks(&pool).await.unwrap().is_empty());
+    }
+}
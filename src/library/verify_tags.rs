@@ -0,0 +1,301 @@
+//! "Verify tags": fingerprint tracks that are already tagged and compare the
+//! recording AcoustID finds with their title and artist
+//! ([`crate::health::verify_metadata`]).
+//!
+//! A track whose audio is confidently another recording gets the
+//! mislabeled-suspect quality flag ([`QualityFlags::MISLABELED_SUSPECT`]),
+//! which stays until a scan reads a different title or artist; tracks that
+//! check out have it cleared. Suspects come back with the identification,
+//! for review in the Enrich pane. Tracks without a real title and artist
+//! are left to ordinary identification.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use sqlx::SqlitePool;
+
+use crate::db::{self, TrackWithMetadata};
+use crate::enrichment::{EnrichmentError, EnrichmentService, TrackIdentification};
+use crate::health::{
+    self, ExistingMetadata, FingerprintMatch, QualityFlags, VerificationResult, VerificationStatus,
+};
+use crate::tasks::TaskHandle;
+
+/// Confidence a match needs before its track is called mislabeled
+pub const MIN_CONFIDENCE: f32 = 0.8;
+
+/// A track whose audio is another recording than its tags name
+#[derive(Debug, Clone)]
+pub struct Suspect {
+    pub track_id: i64,
+    pub path: String,
+    /// Title and artist in the tags
+    pub tagged_title: String,
+    pub tagged_artist: String,
+    /// The recording the fingerprint matched
+    pub identification: TrackIdentification,
+    /// Other releases of that recording
+    pub alternatives: Vec<TrackIdentification>,
+    /// What disagrees with the tags
+    pub issues: Vec<String>,
+}
+
+/// What a verification run found
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// Tracks whose title and artist match their fingerprint
+    pub verified: usize,
+    /// Tracks whose audio is another recording
+    pub suspects: Vec<Suspect>,
+    /// Tracks without a match confident enough to judge them by
+    pub unmatched: usize,
+    /// Tracks left out for having no title and artist to check
+    pub untagged: usize,
+    /// Files that couldn't be fingerprinted or looked up, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+impl VerifyReport {
+    /// One line for the status bar or console
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Verified {} track(s), {} possibly mislabeled, {} without a sure match",
+            self.verified,
+            self.suspects.len(),
+            self.unmatched
+        );
+        if self.untagged > 0 {
+            summary.push_str(&format!(", {} untagged skipped", self.untagged));
+        }
+        if !self.failed.is_empty() {
+            summary.push_str(&format!(", {} failed", self.failed.len()));
+        }
+        summary
+    }
+}
+
+/// Whether a track has tags worth checking: a known artist, and a title
+/// that isn't its file name or a placeholder
+pub fn is_tagged(track: &TrackWithMetadata) -> bool {
+    !health::assess_track_quality(track).flags.intersects(
+        QualityFlags::MISSING_ARTIST
+            | QualityFlags::TITLE_IS_FILENAME
+            | QualityFlags::GENERIC_METADATA,
+    )
+}
+
+/// Compare a track's tags with the identification of its audio
+pub fn check(
+    track: &TrackWithMetadata,
+    identification: &TrackIdentification,
+    alternatives: &[TrackIdentification],
+) -> VerificationResult {
+    let matches: Vec<FingerprintMatch> = std::iter::once(identification)
+        .chain(alternatives)
+        .map(FingerprintMatch::from_identification)
+        .collect();
+    health::verify_metadata(&ExistingMetadata::from_track(track), &matches)
+}
+
+/// Whether a verification says the audio is another recording: a
+/// confident match whose title or artist is nothing like the tags
+pub fn is_mislabeled(result: &VerificationResult) -> bool {
+    result.status == VerificationStatus::Mismatch
+        && result
+            .best_match
+            .as_ref()
+            .is_some_and(|m| m.confidence >= MIN_CONFIDENCE)
+}
+
+/// Verify the tags of the tracks in `track_ids` (every track when `None`).
+/// Streams and untagged tracks are skipped; cancelling through `task` stops
+/// before the next track.
+pub async fn verify(
+    pool: &SqlitePool,
+    service: &EnrichmentService,
+    track_ids: Option<&HashSet<i64>>,
+    task: &TaskHandle,
+) -> sqlx::Result<VerifyReport> {
+    crate::readonly::ensure_writable("Storing tag verification")?;
+    let tracks: Vec<TrackWithMetadata> = db::get_all_tracks_with_metadata(pool)
+        .await?
+        .into_iter()
+        .filter(|t| t.source.is_local() && track_ids.is_none_or(|ids| ids.contains(&t.id)))
+        .collect();
+
+    let mut report = VerifyReport::default();
+    task.set_phase("Fingerprinting and comparing tags");
+    task.set_total(tracks.len() as u64);
+    for track in tracks {
+        if task.is_cancelled() {
+            break;
+        }
+        if !is_tagged(&track) {
+            report.untagged += 1;
+            task.advance(1);
+            continue;
+        }
+        match service
+            .identify_track_with_alternatives(Path::new(&track.path))
+            .await
+        {
+            Ok((identification, alternatives)) => {
+                let result = check(&track, &identification, &alternatives);
+                if is_mislabeled(&result) {
+                    db::set_mislabeled_suspect(pool, track.id, true).await?;
+                    report.suspects.push(Suspect {
+                        track_id: track.id,
+                        path: track.path.clone(),
+                        tagged_title: track.title.clone(),
+                        tagged_artist: track.artist_name.to_string(),
+                        identification,
+                        alternatives,
+                        issues: result
+                            .issues
+                            .iter()
+                            .filter(|i| i.is_critical())
+                            .map(|i| i.description())
+                            .collect(),
+                    });
+                } else if result.status == VerificationStatus::Mismatch {
+                    // Nothing like the tags, but not sure enough to say so
+                    report.unmatched += 1;
+                } else {
+                    db::set_mislabeled_suspect(pool, track.id, false).await?;
+                    report.verified += 1;
+                }
+            }
+            Err(EnrichmentError::NoMatches) => report.unmatched += 1,
+            Err(e) => report.failed.push((track.path.clone(), e.to_string())),
+        }
+        task.advance(1);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enrichment::EnrichmentSource;
+    use crate::enrichment::domain::IdentifiedTrack;
+    use crate::test_utils::{
+        insert_mock_track, mock_track_metadata, mock_track_with_metadata, temp_db,
+    };
+
+    fn identification(title: &str, artist: &str, score: f32) -> TrackIdentification {
+        TrackIdentification {
+            score,
+            track: IdentifiedTrack {
+                recording_id: Some("rec-1".to_string()),
+                title: Some(title.to_string()),
+                artist: Some(artist.to_string()),
+                ..Default::default()
+            },
+            source: EnrichmentSource::AcoustId,
+            musicbrainz_fields: Vec::new(),
+            scores: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_is_tagged() {
+        let track = TrackWithMetadata {
+            title: "Bohemian Rhapsody".to_string(),
+            artist_name: "Queen".into(),
+            path: "/music/queen/11.mp3".to_string(),
+            ..mock_track_with_metadata()
+        };
+        assert!(is_tagged(&track));
+        let no_artist = TrackWithMetadata {
+            artist_name: "Unknown Artist".into(),
+            ..track.clone()
+        };
+        assert!(!is_tagged(&no_artist));
+        let file_name = TrackWithMetadata {
+            title: "11".to_string(),
+            ..track
+        };
+        assert!(!is_tagged(&file_name));
+    }
+
+    #[test]
+    fn test_mislabeled_needs_a_confident_mismatch() {
+        let track = TrackWithMetadata {
+            title: "Bohemian Rhapsody".to_string(),
+            artist_name: "Queen".into(),
+            ..mock_track_with_metadata()
+        };
+
+        let same = identification("Bohemian Rhapsody", "Queen", 0.95);
+        let result = check(&track, &same, &[]);
+        assert_eq!(result.status, VerificationStatus::Verified);
+        assert!(!is_mislabeled(&result));
+
+        let other = identification("Smells Like Teen Spirit", "Nirvana", 0.95);
+        let result = check(&track, &other, &[]);
+        assert!(is_mislabeled(&result), "{:?}", result.issues);
+
+        // Too unsure to accuse the tags
+        let unsure = identification("Smells Like Teen Spirit", "Nirvana", 0.6);
+        assert!(!is_mislabeled(&check(&track, &unsure, &[])));
+    }
+
+    #[tokio::test]
+    async fn test_flag_kept_until_tags_change() {
+        let (pool, _dir) = temp_db().await;
+        let id = insert_mock_track(&pool, "/music/a.mp3").await;
+        let flags = |pool: SqlitePool| async move {
+            let bits: Option<i64> =
+                sqlx::query_scalar("SELECT quality_flags FROM tracks WHERE id = ?")
+                    .bind(id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            QualityFlags::from_bits_i64(bits.unwrap_or(0))
+        };
+        db::set_mislabeled_suspect(&pool, id, true).await.unwrap();
+        assert!(
+            flags(pool.clone())
+                .await
+                .contains(QualityFlags::MISLABELED_SUSPECT)
+        );
+
+        // Rescanned with the same tags: still suspect, and reassessing keeps it
+        let meta = mock_track_metadata();
+        let artist = db::get_or_create_artist(&pool, &meta.artist).await.unwrap();
+        let album = db::get_or_create_album(&pool, &meta.album, Some(artist))
+            .await
+            .unwrap();
+        db::insert_track(&pool, &meta, "/music/a.mp3", Some(artist), Some(album))
+            .await
+            .unwrap();
+        let track = TrackWithMetadata {
+            quality_flags: Some(QualityFlags::MISLABELED_SUSPECT.bits()),
+            ..mock_track_with_metadata()
+        };
+        assert!(
+            health::assess_track_quality(&track)
+                .flags
+                .contains(QualityFlags::MISLABELED_SUSPECT)
+        );
+        assert!(
+            flags(pool.clone())
+                .await
+                .contains(QualityFlags::MISLABELED_SUSPECT)
+        );
+
+        // Retagged: cleared
+        let retagged = crate::metadata::TrackMetadata {
+            title: "The Right Title".to_string(),
+            ..meta
+        };
+        db::insert_track(&pool, &retagged, "/music/a.mp3", Some(artist), Some(album))
+            .await
+            .unwrap();
+        assert!(
+            !flags(pool.clone())
+                .await
+                .contains(QualityFlags::MISLABELED_SUSPECT)
+        );
+    }
+}
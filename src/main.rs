@@ -1,45 +1,34 @@
-//! Music Minder - A music library management application.
-//!
-//! This application provides tools for scanning, organizing, enriching, and
-//! playing music files. It can be run as a GUI application or used via CLI
-//! commands.
+//! The `music-minder` binary: sets up logging, the profile and config, then
+//! runs a CLI command or opens the window. The app itself is the
+//! `music_minder` library.
 
 // Hide console window on Windows when running as GUI
 // CLI commands will attach to the parent console or allocate one
 #![cfg_attr(target_os = "windows", windows_subsystem = "windows")]
 
-pub mod cli;
-pub mod config;
-pub mod cover;
-pub mod db;
-pub mod diagnostics;
-pub mod enrichment;
-pub mod error;
-pub mod health;
-pub mod library;
-pub mod metadata;
-pub mod model;
-pub mod organizer;
-pub mod player;
-pub mod scanner;
-#[cfg(test)]
-pub mod test_utils;
-pub mod ui;
-
-use clap::Parser;
+#[cfg(feature = "enrichment")]
+use music_minder::enrichment;
+#[cfg(feature = "gui")]
+use music_minder::ui::{self, MusicMinder};
+use music_minder::{
+    cli, config, cover, db, deeplink, instance, metadata, portable, profile, readonly, secrets,
+    startup,
+};
+
+#[cfg(feature = "gui")]
 use iced::application;
+#[cfg(feature = "gui")]
 use iced::window;
-use std::time::Instant;
 use tracing_subscriber::{EnvFilter, fmt, prelude::*};
-use ui::MusicMinder;
 
 /// Embedded app icon (32x32 RGBA PNG)
+#[cfg(feature = "gui")]
 const APP_ICON: &[u8] = include_bytes!("../assets/icon-32.png");
 
 fn main() -> anyhow::Result<()> {
-    let startup_start = Instant::now();
+    startup::begin();
 
-    let args = cli::Cli::parse();
+    let args = cli::Cli::parse_absolute();
 
     // If running CLI commands on Windows, attach to console for output
     #[cfg(target_os = "windows")]
@@ -47,47 +36,136 @@ fn main() -> anyhow::Result<()> {
         attach_console();
     }
 
-    // Initialize logging
+    // Initialize logging (to a file for the agent running as a service)
+    let log_file = match args.log_file() {
+        Some(path) => Some(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?,
+        ),
+        None => None,
+    };
+    let (console_log, file_log) = match log_file {
+        Some(file) => (
+            None,
+            Some(
+                fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(std::sync::Mutex::new(file)),
+            ),
+        ),
+        None => (Some(fmt::layer().with_target(true)), None),
+    };
     tracing_subscriber::registry()
-        .with(fmt::layer().with_target(true))
+        .with(console_log)
+        .with(file_log)
         .with(EnvFilter::from_default_env().add_directive("music_minder=info".parse().unwrap()))
         .init();
 
     tracing::info!("Startup initiated");
 
+    if let Some(dir) = &args.config_dir {
+        config::set_config_dir(dir.clone());
+    }
+    let portable = portable::init(args.portable)?;
+
+    // Profile decides which config and database everything below uses
+    let profile = startup::phase("profile", || profile::init(args.profile.as_deref()))?;
+    tracing::info!("Using profile {:?}", profile);
+
+    // Encrypted credentials open from the environment, or wait to be asked
+    if !secrets::unlock_from_env() {
+        tracing::info!(
+            "Credentials are encrypted; set {} or unlock them in Settings",
+            secrets::PASSPHRASE_ENV
+        );
+    }
+    let cfg = startup::phase("config", config::load);
+    let mut policy = db::paths::PathPolicy::from_config(&cfg.library);
+    if let Some(portable) = portable {
+        if let Some(root) = &portable.drive_root {
+            policy = policy.relative_to(root);
+        }
+        portable::enter(portable)?;
+    }
+    db::paths::set_policy(policy);
+    cover::set_cache_limit(cfg.library.cover_cache_mb * 1_000_000);
+    metadata::strategy::set(cfg.tagging.write);
+    if args.read_only || cfg.library.read_only {
+        readonly::set(true);
+        tracing::info!("Read-only mode: library changes are disabled");
+    }
+    #[cfg(feature = "enrichment")]
+    if cfg.network.offline {
+        enrichment::http::set_offline(true);
+        tracing::info!("Offline mode: no requests leave this machine");
+    }
+
     // Try to run a CLI command
     if cli::run_command(&args)? {
         // A command was executed, exit normally
         return Ok(());
     }
 
+    // Launching again brings up the window already open (another profile
+    // gets a window of its own)
+    if cfg!(feature = "gui")
+        && args.command.is_none()
+        && args.profile.is_none()
+        && instance::forward(deeplink::FOCUS)
+    {
+        tracing::info!("Music Minder is already running; brought it to the front");
+        return Ok(());
+    }
+
+    run_gui()
+}
+
+/// Launch the GUI (no command was given).
+#[cfg(feature = "gui")]
+fn run_gui() -> anyhow::Result<()> {
     // Load window icon from embedded PNG
-    let icon_start = Instant::now();
-    let icon = load_icon(APP_ICON);
-    tracing::debug!(
-        "Icon loaded in {:.1}ms",
-        icon_start.elapsed().as_secs_f64() * 1000.0
-    );
+    let icon = startup::phase("icon", || load_icon(APP_ICON));
 
     // No command specified, launch the GUI
-    let startup_end = Instant::now();
     tracing::info!(
         "Time to GUI startup: {:.1}ms",
-        startup_end.duration_since(startup_start).as_secs_f64() * 1000.0
+        startup::elapsed().as_secs_f64() * 1000.0
     );
 
-    application("Music Minder", MusicMinder::update, MusicMinder::view)
+    // Reopen in the mini-player if that's how the app was left
+    let appearance = config::load().appearance;
+    let mut window_settings = window::Settings {
+        icon,
+        ..Default::default()
+    };
+    if appearance.mini_player {
+        window_settings.size = ui::MiniPlayerState::SIZE;
+        if appearance.mini_player_always_on_top {
+            window_settings.level = window::Level::AlwaysOnTop;
+        }
+    }
+
+    application(MusicMinder::title, MusicMinder::update, MusicMinder::view)
         .subscription(MusicMinder::subscription)
-        .font(ui::icons::ICON_FONT_BYTES)
-        .window(window::Settings {
-            icon,
-            ..Default::default()
-        })
+        .window(window_settings)
+        // Closing waits for in-flight work (see ui::update::shutdown)
+        .exit_on_close_request(false)
         .run_with(MusicMinder::new)
         .map_err(|e| anyhow::anyhow!("GUI Error: {}", e))
 }
 
+/// Without the GUI, running with no command just explains how to use the CLI.
+#[cfg(not(feature = "gui"))]
+fn run_gui() -> anyhow::Result<()> {
+    use clap::CommandFactory;
+    cli::Cli::command().print_help()?;
+    anyhow::bail!("this build has no GUI (built without the `gui` feature); pass a command")
+}
+
 /// Load a PNG icon from bytes into an iced window icon
+#[cfg(feature = "gui")]
 fn load_icon(png_bytes: &[u8]) -> Option<window::Icon> {
     // Decode PNG using iced's image feature
     let image = image::load_from_memory(png_bytes).ok()?.into_rgba8();
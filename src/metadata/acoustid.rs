@@ -0,0 +1,167 @@
+//! AcoustID tags: the AcoustID track ID and the Chromaprint fingerprint.
+//!
+//! Written where MusicBrainz Picard puts them, so Picard and beets can reuse
+//! them instead of fingerprinting again: `TXXX` frames ("Acoustid Id",
+//! "Acoustid Fingerprint") in ID3v2, iTunes freeform atoms in MP4, and
+//! `ACOUSTID_ID`/`ACOUSTID_FINGERPRINT` elsewhere. A file that already
+//! carries a fingerprint isn't fingerprinted again here either (see
+//! [`read_fingerprint`]).
+
+use std::path::Path;
+
+use lofty::file::{AudioFile, TaggedFileExt};
+use lofty::probe::Probe;
+use lofty::tag::{ItemKey, ItemValue, Tag, TagItem, TagType};
+
+use crate::enrichment::domain::AudioFingerprint;
+
+/// Field names as (ID3v2 and APE, MP4, Vorbis comments)
+const ID_KEYS: [&str; 3] = [
+    "Acoustid Id",
+    "----:com.apple.iTunes:Acoustid Id",
+    "ACOUSTID_ID",
+];
+const FINGERPRINT_KEYS: [&str; 3] = [
+    "Acoustid Fingerprint",
+    "----:com.apple.iTunes:Acoustid Fingerprint",
+    "ACOUSTID_FINGERPRINT",
+];
+
+/// The AcoustID fields of a file's tags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AcoustIdTags {
+    /// AcoustID track ID (a UUID, like the MusicBrainz IDs)
+    pub id: Option<String>,
+    /// Chromaprint fingerprint, base64 as `fpcalc` prints it
+    pub fingerprint: Option<String>,
+}
+
+impl AcoustIdTags {
+    /// Read the fields from a tag, under any format's name for them
+    pub fn from_tag(tag: &Tag) -> Self {
+        let unknown = |names: &[&str]| {
+            tag.items().find_map(|item| match item.key() {
+                ItemKey::Unknown(key) if names.iter().any(|n| key.eq_ignore_ascii_case(n)) => {
+                    item.value().text().map(str::trim).filter(|v| !v.is_empty())
+                }
+                _ => None,
+            })
+        };
+        Self {
+            id: unknown(&ID_KEYS).map(String::from),
+            fingerprint: unknown(&FINGERPRINT_KEYS).map(String::from),
+        }
+    }
+}
+
+/// Set the AcoustID ID and fingerprint, replacing any under another
+/// format's name. Returns the fields written.
+pub(super) fn write(
+    tag: &mut Tag,
+    tag_type: TagType,
+    id: Option<&str>,
+    fingerprint: Option<&str>,
+) -> Vec<&'static str> {
+    let mut written = Vec::new();
+    for (value, names, field) in [
+        (id, &ID_KEYS, "acoustid_id"),
+        (fingerprint, &FINGERPRINT_KEYS, "acoustid_fingerprint"),
+    ] {
+        let Some(value) = value else {
+            continue;
+        };
+        tag.retain(|item| {
+            !matches!(item.key(), ItemKey::Unknown(key)
+                if names.iter().any(|n| key.eq_ignore_ascii_case(n)))
+        });
+        let name = match tag_type {
+            TagType::Id3v2 | TagType::Ape => names[0],
+            TagType::Mp4Ilst => names[1],
+            _ => names[2],
+        };
+        tag.insert_unchecked(TagItem::new(
+            ItemKey::Unknown(name.to_string()),
+            ItemValue::Text(value.to_string()),
+        ));
+        written.push(field);
+    }
+    written
+}
+
+/// The fingerprint a file's tags carry, with the file's length; `None` if
+/// it has none (or can't be read)
+pub fn read_fingerprint(path: &Path) -> Option<AudioFingerprint> {
+    let tagged_file = Probe::open(path).ok()?.read().ok()?;
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag())?;
+    let fingerprint = AcoustIdTags::from_tag(tag).fingerprint?;
+    let duration = tagged_file.properties().duration();
+    Some(AudioFingerprint {
+        fingerprint,
+        duration_secs: duration.as_secs_f64().round() as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enrichment::domain::IdentifiedTrack;
+    use crate::metadata::WriteOptions2;
+    use crate::test_utils::{AudioFixture, write_audio_fixture};
+
+    #[test]
+    fn test_read_under_any_formats_name() {
+        let mut tag = Tag::new(TagType::VorbisComments);
+        tag.insert_unchecked(TagItem::new(
+            ItemKey::Unknown("acoustid_id".to_string()),
+            ItemValue::Text("9ec6d2f4-0a5c-4b5e-9d4a-1f1b0c8e6a11".to_string()),
+        ));
+        tag.insert_unchecked(TagItem::new(
+            ItemKey::Unknown("Acoustid Fingerprint".to_string()),
+            ItemValue::Text("AQADtNIyRUkkZUqS".to_string()),
+        ));
+        let read = AcoustIdTags::from_tag(&tag);
+        assert_eq!(
+            read.id.as_deref(),
+            Some("9ec6d2f4-0a5c-4b5e-9d4a-1f1b0c8e6a11")
+        );
+        assert_eq!(read.fingerprint.as_deref(), Some("AQADtNIyRUkkZUqS"));
+
+        // Rewritten under this format's name, once
+        assert_eq!(
+            write(&mut tag, TagType::VorbisComments, None, Some("AQADnew")),
+            ["acoustid_fingerprint"]
+        );
+        assert_eq!(tag.item_count(), 2);
+        assert_eq!(
+            tag.get_string(&ItemKey::Unknown("ACOUSTID_FINGERPRINT".to_string())),
+            Some("AQADnew")
+        );
+    }
+
+    #[test]
+    fn test_written_fingerprint_is_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_audio_fixture(dir.path(), AudioFixture::Flac);
+        assert!(read_fingerprint(&path).is_none());
+
+        let track = IdentifiedTrack {
+            acoustid_id: Some("9ec6d2f4-0a5c-4b5e-9d4a-1f1b0c8e6a11".to_string()),
+            fingerprint: Some("AQADtNIyRUkkZUqS".to_string()),
+            ..Default::default()
+        };
+        let options = WriteOptions2 {
+            write_musicbrainz_ids: true,
+            ..Default::default()
+        };
+        let written = crate::metadata::write(&path, &track, &options).unwrap();
+        assert_eq!(
+            written.fields_written,
+            ["acoustid_id", "acoustid_fingerprint"]
+        );
+
+        let fp = read_fingerprint(&path).unwrap();
+        assert_eq!(fp.fingerprint, "AQADtNIyRUkkZUqS");
+    }
+}
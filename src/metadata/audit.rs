@@ -0,0 +1,273 @@
+//! What a tag save did to the file, for auditing writes afterwards.
+//!
+//! Every atomic save compares the file before and after: which tag blocks
+//! it has (an ID3v2.3 tag saved as ID3v2.4, say), its size, and whether the
+//! space before the audio changed, which means the tags no longer fit their
+//! padding and the audio was moved. The saved file is then read back and
+//! its audio properties checked against the original's.
+//!
+//! The result waits under the file's path until the write is logged, when
+//! [`crate::activity::record_tags_written`] takes it with [`take`] and
+//! stores it next to the activity entry.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use lofty::file::{AudioFile, TaggedFile};
+use lofty::probe::Probe;
+
+use super::technical::{self, TagBlock};
+
+/// Saved files whose audio length moved by more than this fail verification
+const DURATION_TOLERANCE: Duration = Duration::from_secs(1);
+
+/// Audits not taken yet; the oldest are dropped past this many
+const PENDING_LIMIT: usize = 256;
+
+static PENDING: Mutex<VecDeque<(PathBuf, WriteAudit)>> = Mutex::new(VecDeque::new());
+
+/// The file before and after one tag save
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteAudit {
+    /// Tag blocks before the save, e.g. "ID3v2.3, ID3v1"
+    pub tags_before: String,
+    pub tags_after: String,
+    pub size_before: u64,
+    pub size_after: u64,
+    /// The tags outgrew the space before the audio (or left it), so the
+    /// audio moved
+    pub padding_rewritten: bool,
+    /// Read back with the same audio properties as before the save
+    pub verified: bool,
+    /// What reading back found different, or why it failed
+    pub verify_note: Option<String>,
+}
+
+impl WriteAudit {
+    pub fn size_delta(&self) -> i64 {
+        self.size_after as i64 - self.size_before as i64
+    }
+
+    /// One line, e.g. "ID3v2.3 → ID3v2.4 · +1.2 KB · padding rewritten · verified"
+    pub fn summary(&self) -> String {
+        let tags = if self.tags_before == self.tags_after {
+            self.tags_after.clone()
+        } else {
+            format!(
+                "{} → {}",
+                or_none(&self.tags_before),
+                or_none(&self.tags_after)
+            )
+        };
+        let delta = self.size_delta();
+        let size = match delta {
+            0 => "same size".to_string(),
+            d if d.unsigned_abs() < 1024 => format!("{:+} B", d),
+            d => format!("{:+.1} KB", d as f64 / 1024.0),
+        };
+        let mut parts = vec![tags, size];
+        if self.padding_rewritten {
+            parts.push("padding rewritten".to_string());
+        }
+        parts.push(match (&self.verified, &self.verify_note) {
+            (true, _) => "verified".to_string(),
+            (false, Some(note)) => format!("verification failed: {}", note),
+            (false, None) => "verification failed".to_string(),
+        });
+        parts.join(" · ")
+    }
+}
+
+fn or_none(tags: &str) -> &str {
+    if tags.is_empty() { "no tags" } else { tags }
+}
+
+/// The audit waiting for `path`, if a save of it hasn't been logged yet
+pub fn take(path: &Path) -> Option<WriteAudit> {
+    let mut pending = PENDING.lock();
+    let index = pending.iter().rposition(|(p, _)| p == path)?;
+    pending.remove(index).map(|(_, audit)| audit)
+}
+
+/// The file as it was before a save
+pub(super) struct Before {
+    tags: Vec<TagBlock>,
+    size: u64,
+    properties: AudioProperties,
+}
+
+/// What a tag save must not change
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AudioProperties {
+    duration: Duration,
+    sample_rate: Option<u32>,
+    channels: Option<u8>,
+}
+
+impl AudioProperties {
+    fn of(tagged_file: &TaggedFile) -> Self {
+        let properties = tagged_file.properties();
+        Self {
+            duration: properties.duration(),
+            sample_rate: properties.sample_rate(),
+            channels: properties.channels(),
+        }
+    }
+
+    /// How `after` differs, if it does
+    fn compare(&self, after: &Self) -> Option<String> {
+        if self.sample_rate != after.sample_rate {
+            return Some(format!(
+                "sample rate {:?} → {:?}",
+                self.sample_rate, after.sample_rate
+            ));
+        }
+        if self.channels != after.channels {
+            return Some(format!(
+                "channels {:?} → {:?}",
+                self.channels, after.channels
+            ));
+        }
+        (self.duration.abs_diff(after.duration) > DURATION_TOLERANCE).then(|| {
+            format!(
+                "length {:.1} s → {:.1} s",
+                self.duration.as_secs_f64(),
+                after.duration.as_secs_f64()
+            )
+        })
+    }
+}
+
+/// Note the file's state before saving `tagged_file` (read from it) over it
+pub(super) fn before(path: &Path, tagged_file: &TaggedFile) -> Before {
+    Before {
+        tags: technical::tag_blocks(path).unwrap_or_default(),
+        size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        properties: AudioProperties::of(tagged_file),
+    }
+}
+
+/// Compare the saved file with `before` and keep the result for [`take`]
+pub(super) fn after(path: &Path, before: Before) {
+    let audit = compare(path, before);
+    if !audit.verified {
+        tracing::warn!(
+            target: "metadata::audit",
+            "{} changed while saving tags: {}",
+            path.display(),
+            audit.verify_note.as_deref().unwrap_or("unreadable")
+        );
+    }
+    let mut pending = PENDING.lock();
+    if pending.len() >= PENDING_LIMIT {
+        pending.pop_front();
+    }
+    pending.push_back((path.to_path_buf(), audit));
+}
+
+fn compare(path: &Path, before: Before) -> WriteAudit {
+    let tags_after = technical::tag_blocks(path).unwrap_or_default();
+    let read_back = Probe::open(path)
+        .and_then(|p| p.read())
+        .map(|file| AudioProperties::of(&file));
+    let (verified, verify_note) = match read_back {
+        Ok(after) => match before.properties.compare(&after) {
+            None => (true, None),
+            Some(change) => (false, Some(change)),
+        },
+        Err(e) => (false, Some(e.to_string())),
+    };
+    WriteAudit {
+        tags_before: describe(&before.tags),
+        tags_after: describe(&tags_after),
+        size_before: before.size,
+        size_after: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        padding_rewritten: total(&before.tags) != total(&tags_after),
+        verified,
+        verify_note,
+    }
+}
+
+/// Tag kinds, without the blocks that aren't tags
+fn describe(blocks: &[TagBlock]) -> String {
+    blocks
+        .iter()
+        .filter(|b| !matches!(b.kind.as_str(), "Padding" | "Seek table" | "Pictures"))
+        .map(|b| b.kind.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Bytes of metadata, padding included
+fn total(blocks: &[TagBlock]) -> u64 {
+    blocks.iter().map(|b| b.bytes).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(kind: &str, bytes: u64) -> TagBlock {
+        TagBlock {
+            kind: kind.to_string(),
+            bytes,
+        }
+    }
+
+    #[test]
+    fn test_summary_shows_version_size_and_padding() {
+        let audit = WriteAudit {
+            tags_before: describe(&[block("ID3v2.3", 4096), block("ID3v1", 128)]),
+            tags_after: describe(&[block("ID3v2.4", 6144), block("ID3v1", 128)]),
+            size_before: 1_000_000,
+            size_after: 1_002_048,
+            padding_rewritten: true,
+            verified: true,
+            verify_note: None,
+        };
+        assert_eq!(
+            audit.summary(),
+            "ID3v2.3, ID3v1 → ID3v2.4, ID3v1 · +2.0 KB · padding rewritten · verified"
+        );
+
+        let flac = WriteAudit {
+            tags_before: describe(&[block("Vorbis comment", 200), block("Padding", 8000)]),
+            tags_after: describe(&[block("Vorbis comment", 400), block("Padding", 7800)]),
+            size_before: 5_000,
+            size_after: 5_000,
+            padding_rewritten: false,
+            verified: false,
+            verify_note: Some("length 180.0 s → 90.0 s".to_string()),
+        };
+        assert_eq!(
+            flac.summary(),
+            "Vorbis comment · same size · verification failed: length 180.0 s → 90.0 s"
+        );
+    }
+
+    #[test]
+    fn test_take_returns_latest_audit_once() {
+        let path = Path::new("/audit-test/song.flac");
+        for size in [1, 2] {
+            PENDING.lock().push_back((
+                path.to_path_buf(),
+                WriteAudit {
+                    tags_before: String::new(),
+                    tags_after: String::new(),
+                    size_before: size,
+                    size_after: size,
+                    padding_rewritten: false,
+                    verified: true,
+                    verify_note: None,
+                },
+            ));
+        }
+        assert_eq!(take(path).map(|a| a.size_before), Some(2));
+        assert_eq!(take(path).map(|a| a.size_before), Some(1));
+        assert_eq!(take(path), None);
+    }
+}
@@ -0,0 +1,184 @@
+//! Language and explicit-content tags.
+//!
+//! Languages are ISO 639-2 codes, as ID3's `TLAN` and MusicBrainz work data
+//! have them ("eng", "jpn"); [`INSTRUMENTAL`] ("zxx", no linguistic content)
+//! marks tracks without vocals. Taggers that write names or two-letter
+//! codes ("English", "en") are read for the common languages.
+//!
+//! Explicit content is iTunes' advisory rating: the MP4 `rtng` atom, or an
+//! `ITUNESADVISORY` field elsewhere. 1 (or 4) is explicit, 2 clean.
+
+use lofty::tag::{ItemKey, Tag};
+
+/// Language code for "no lyrics"
+pub const INSTRUMENTAL: &str = "zxx";
+
+/// Common languages as (ISO 639-2, ISO 639-1, English name)
+const LANGUAGES: [(&str, &str, &str); 24] = [
+    ("ara", "ar", "Arabic"),
+    ("zho", "zh", "Chinese"),
+    ("ces", "cs", "Czech"),
+    ("dan", "da", "Danish"),
+    ("nld", "nl", "Dutch"),
+    ("eng", "en", "English"),
+    ("fin", "fi", "Finnish"),
+    ("fra", "fr", "French"),
+    ("deu", "de", "German"),
+    ("ell", "el", "Greek"),
+    ("heb", "he", "Hebrew"),
+    ("hin", "hi", "Hindi"),
+    ("isl", "is", "Icelandic"),
+    ("gle", "ga", "Irish"),
+    ("ita", "it", "Italian"),
+    ("jpn", "ja", "Japanese"),
+    ("kor", "ko", "Korean"),
+    ("nor", "no", "Norwegian"),
+    ("pol", "pl", "Polish"),
+    ("por", "pt", "Portuguese"),
+    ("rus", "ru", "Russian"),
+    ("spa", "es", "Spanish"),
+    ("swe", "sv", "Swedish"),
+    ("tur", "tr", "Turkish"),
+];
+
+/// Bibliographic ISO 639-2 codes some taggers use, as the terminology code
+const BIBLIOGRAPHIC: [(&str, &str); 6] = [
+    ("chi", "zho"),
+    ("cze", "ces"),
+    ("dut", "nld"),
+    ("fre", "fra"),
+    ("ger", "deu"),
+    ("gre", "ell"),
+];
+
+/// A track's language and explicit flag
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentTags {
+    /// ISO 639-2, lowercase
+    pub language: Option<String>,
+    /// `Some(false)` for a clean (edited) version
+    pub explicit: Option<bool>,
+}
+
+impl ContentTags {
+    pub fn from_tag(tag: &Tag) -> Self {
+        let unknown = |name: &str| {
+            tag.items().find_map(|item| match item.key() {
+                ItemKey::Unknown(key) if key.eq_ignore_ascii_case(name) => item.value().text(),
+                _ => None,
+            })
+        };
+        Self {
+            language: tag
+                .get_string(&ItemKey::Language)
+                .and_then(normalize_language),
+            explicit: tag
+                .get_string(&ItemKey::ParentalAdvisory)
+                .or_else(|| unknown("ITUNESADVISORY"))
+                .or_else(|| unknown("EXPLICIT"))
+                .and_then(parse_advisory),
+        }
+    }
+
+    pub fn is_instrumental(&self) -> bool {
+        is_instrumental(self.language.as_deref())
+    }
+}
+
+/// Whether a stored language says the track has no lyrics
+pub fn is_instrumental(language: Option<&str>) -> bool {
+    language == Some(INSTRUMENTAL)
+}
+
+/// A language tag as an ISO 639-2 code. Takes the first of several
+/// ("eng; fra"); unrecognized three-letter codes are kept as they are.
+pub fn normalize_language(value: &str) -> Option<String> {
+    let first = value.split([';', '/', ',']).next()?.trim();
+    let lower = first.to_lowercase();
+    if matches!(lower.as_str(), "instrumental" | "none" | "[no lyrics]") {
+        return Some(INSTRUMENTAL.to_string());
+    }
+    if let Some((_, code)) = BIBLIOGRAPHIC.iter().find(|(b, _)| *b == lower) {
+        return Some(code.to_string());
+    }
+    if let Some((code, _, _)) = LANGUAGES
+        .iter()
+        .find(|(_, short, name)| *short == lower || name.eq_ignore_ascii_case(&lower))
+    {
+        return Some(code.to_string());
+    }
+    (lower.len() == 3 && lower.bytes().all(|b| b.is_ascii_lowercase())).then_some(lower)
+}
+
+/// "English", "Instrumental", or the code itself for a language not listed
+pub fn language_name(code: &str) -> String {
+    if code == INSTRUMENTAL {
+        return "Instrumental".to_string();
+    }
+    LANGUAGES
+        .iter()
+        .find(|(known, _, _)| *known == code)
+        .map_or_else(|| code.to_uppercase(), |(_, _, name)| name.to_string())
+}
+
+/// "Explicit", "Clean", or "—" when the tags don't say
+pub fn explicit_label(explicit: Option<bool>) -> &'static str {
+    match explicit {
+        Some(true) => "Explicit",
+        Some(false) => "Clean",
+        None => "—",
+    }
+}
+
+/// An advisory rating as explicit (true), clean (false) or none
+pub fn parse_advisory(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "4" | "explicit" | "yes" | "true" => Some(true),
+        "2" | "clean" | "edited" => Some(false),
+        _ => None,
+    }
+}
+
+/// The advisory rating to write: 1 explicit, 2 clean
+pub fn advisory_value(explicit: bool) -> &'static str {
+    if explicit { "1" } else { "2" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lofty::tag::{ItemValue, TagItem, TagType};
+
+    #[test]
+    fn test_normalize_language() {
+        assert_eq!(normalize_language("eng").as_deref(), Some("eng"));
+        assert_eq!(normalize_language(" English ").as_deref(), Some("eng"));
+        assert_eq!(normalize_language("ja").as_deref(), Some("jpn"));
+        assert_eq!(normalize_language("ger").as_deref(), Some("deu"));
+        assert_eq!(normalize_language("fra; eng").as_deref(), Some("fra"));
+        assert_eq!(normalize_language("Instrumental").as_deref(), Some("zxx"));
+        assert_eq!(normalize_language("yor").as_deref(), Some("yor"));
+        assert_eq!(normalize_language("Klingon"), None);
+        assert_eq!(normalize_language(""), None);
+        assert_eq!(language_name("deu"), "German");
+        assert_eq!(language_name("yor"), "YOR");
+    }
+
+    #[test]
+    fn test_from_tag() {
+        let mut tag = Tag::new(TagType::VorbisComments);
+        tag.insert_text(ItemKey::Language, "zxx".to_string());
+        tag.insert_unchecked(TagItem::new(
+            ItemKey::Unknown("ITUNESADVISORY".to_string()),
+            ItemValue::Text("2".to_string()),
+        ));
+        let content = ContentTags::from_tag(&tag);
+        assert!(content.is_instrumental());
+        assert_eq!(content.explicit, Some(false));
+
+        let mut tag = Tag::new(TagType::Mp4Ilst);
+        tag.insert_text(ItemKey::ParentalAdvisory, "4".to_string());
+        assert_eq!(ContentTags::from_tag(&tag).explicit, Some(true));
+        assert_eq!(parse_advisory("0"), None);
+    }
+}
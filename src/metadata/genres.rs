@@ -0,0 +1,155 @@
+//! Multi-valued genre tags.
+//!
+//! A track can have several genres. Where the format has multi-valued
+//! fields they're written as separate values: ID3v2.4 keeps them in one
+//! `TCON` frame, separated by null characters, and Vorbis comments (FLAC,
+//! Ogg, Opus) get one `GENRE` field each. Everywhere else (MP4 atoms, APE,
+//! RIFF INFO and AIFF text chunks) players only read one value, so the
+//! genres are joined with "; " into it.
+//!
+//! Reading splits joined values again, so genres written either way, by
+//! Music Minder or by older versions that always joined them, read back as
+//! the same list.
+//!
+//! A [`GenreMap`] turns the many spellings of one genre ("hip-hop", "Hip
+//! Hop", "HipHop") into the one the library uses, on scans and tag writes.
+
+use std::collections::BTreeMap;
+
+use lofty::tag::{Accessor, ItemKey, ItemValue, Tag, TagItem, TagType};
+
+/// What joins genres in formats with one value per field
+pub const SEPARATOR: &str = "; ";
+
+/// Whether `tag_type` can hold several values of one field
+pub fn is_multi_valued(tag_type: TagType) -> bool {
+    matches!(tag_type, TagType::Id3v2 | TagType::VorbisComments)
+}
+
+/// Every genre in `tag`, in tag order
+pub fn read(tag: &Tag) -> Vec<String> {
+    tag.get_strings(&ItemKey::Genre).flat_map(split).collect()
+}
+
+/// Replace the genres of `tag` with `genres`, as separate values where
+/// the format allows
+pub fn write(tag: &mut Tag, genres: &[String]) {
+    if !is_multi_valued(tag.tag_type()) {
+        tag.set_genre(genres.join(SEPARATOR));
+        return;
+    }
+    tag.remove_key(&ItemKey::Genre);
+    for genre in genres {
+        tag.push(TagItem::new(ItemKey::Genre, ItemValue::Text(genre.clone())));
+    }
+}
+
+/// The genres in a joined value, e.g. "Rock; Pop" (a lone genre comes back as is)
+pub fn split(value: &str) -> Vec<String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|g| !g.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// What genre spellings are matched on: letters and digits, lowercased
+/// ("Hip-Hop" and "hip hop" are both "hiphop")
+pub fn spelling_key(genre: &str) -> String {
+    genre
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The genre rules of `tagging.genre_map`: spellings and the genre each is
+/// written as
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GenreMap {
+    /// Spelling key to genre
+    rules: BTreeMap<String, String>,
+}
+
+impl GenreMap {
+    pub fn new(rules: &BTreeMap<String, String>) -> Self {
+        Self {
+            rules: rules
+                .iter()
+                .map(|(from, to)| (spelling_key(from), to.trim().to_string()))
+                .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn from_config(config: &crate::config::TaggingConfig) -> Self {
+        Self::new(&config.genre_map)
+    }
+
+    /// The genre `genre` is written as
+    pub fn map<'a>(&'a self, genre: &'a str) -> &'a str {
+        self.rules
+            .get(&spelling_key(genre))
+            .map_or(genre, String::as_str)
+    }
+
+    /// Map each genre, dropping the repeats that leaves
+    /// (["Hip Hop", "hip-hop"] is just ["Hip Hop"])
+    pub fn apply(&self, genres: &[String]) -> Vec<String> {
+        let mut mapped: Vec<String> = Vec::with_capacity(genres.len());
+        for genre in genres {
+            let genre = self.map(genre);
+            if !mapped
+                .iter()
+                .any(|g| spelling_key(g) == spelling_key(genre))
+            {
+                mapped.push(genre.to_string());
+            }
+        }
+        mapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genres() -> Vec<String> {
+        vec!["Rock".to_string(), "Trip Hop".to_string()]
+    }
+
+    #[test]
+    fn test_write_keeps_values_separate_where_supported() {
+        let mut vorbis = Tag::new(TagType::VorbisComments);
+        vorbis.set_genre("Old".to_string());
+        write(&mut vorbis, &genres());
+        assert_eq!(vorbis.get_strings(&ItemKey::Genre).count(), 2);
+        assert_eq!(read(&vorbis), genres());
+
+        let mut mp4 = Tag::new(TagType::Mp4Ilst);
+        write(&mut mp4, &genres());
+        assert_eq!(mp4.genre().as_deref(), Some("Rock; Trip Hop"));
+        assert_eq!(read(&mp4), genres());
+    }
+
+    #[test]
+    fn test_split_drops_empty_parts() {
+        assert_eq!(split("Rock;  Trip Hop ;"), genres());
+        assert_eq!(split("Drum & Bass"), ["Drum & Bass"]);
+        assert!(split(" ; ").is_empty());
+    }
+
+    #[test]
+    fn test_genre_map_matches_any_spelling() {
+        let map = GenreMap::new(&BTreeMap::from([
+            ("hip-hop".to_string(), "Hip Hop".to_string()),
+            ("Rap/Hip-Hop".to_string(), "Hip Hop".to_string()),
+        ]));
+        assert_eq!(map.map("HIP HOP"), "Hip Hop");
+        assert_eq!(map.map("rap / hip hop"), "Hip Hop");
+        assert_eq!(map.map("Rap"), "Rap");
+        let genres = ["hiphop", "Jazz", "Rap/Hip-Hop", "jazz"].map(String::from);
+        assert_eq!(map.apply(&genres), ["Hip Hop", "Jazz"]);
+    }
+}
@@ -0,0 +1,218 @@
+//! Loudness values read from ReplayGain and R128 tags, and the ReplayGain
+//! tags written after measuring a track (see [`crate::player::replaygain`]).
+//!
+//! Gains are in dB relative to the ReplayGain reference level (-18 LUFS);
+//! peaks are linear sample values where 1.0 is full scale. Opus files carry
+//! `R128_TRACK_GAIN`/`R128_ALBUM_GAIN` instead, Q7.8 integers relative to
+//! -23 LUFS, which are converted to the ReplayGain reference.
+//!
+//! Heavily limited masters need a lot of negative gain and peak at full
+//! scale; [`Loudness::is_loud_master`] flags them.
+
+use lofty::tag::{ItemKey, Tag};
+
+/// Track gain at or below which a file counts as a loud master (dB)
+pub const LOUD_MASTER_GAIN_DB: f32 = -10.0;
+
+/// Offset from the R128 reference (-23 LUFS) to ReplayGain's (-18 LUFS)
+const R128_TO_REPLAYGAIN_DB: f32 = 5.0;
+
+/// ReplayGain values of a track, each missing when untagged
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Loudness {
+    pub track_gain: Option<f32>,
+    pub track_peak: Option<f32>,
+    pub album_gain: Option<f32>,
+    pub album_peak: Option<f32>,
+}
+
+impl Loudness {
+    /// Read the values from a tag, preferring ReplayGain over R128
+    pub fn from_tag(tag: &Tag) -> Self {
+        let text = |key: &ItemKey| tag.get_string(key);
+        let r128 = |name: &str| {
+            text(&ItemKey::Unknown(name.to_string()))
+                .or_else(|| text(&ItemKey::Unknown(name.to_lowercase())))
+                .and_then(parse_r128)
+        };
+
+        Self {
+            track_gain: text(&ItemKey::ReplayGainTrackGain)
+                .and_then(parse_gain)
+                .or_else(|| r128("R128_TRACK_GAIN")),
+            track_peak: text(&ItemKey::ReplayGainTrackPeak).and_then(parse_peak),
+            album_gain: text(&ItemKey::ReplayGainAlbumGain)
+                .and_then(parse_gain)
+                .or_else(|| r128("R128_ALBUM_GAIN")),
+            album_peak: text(&ItemKey::ReplayGainAlbumPeak).and_then(parse_peak),
+        }
+    }
+
+    /// Whether the file has no loudness tags at all
+    pub fn is_empty(&self) -> bool {
+        self.track_gain.is_none()
+            && self.track_peak.is_none()
+            && self.album_gain.is_none()
+            && self.album_peak.is_none()
+    }
+
+    /// Whether the track looks brickwall-limited: it needs a lot of
+    /// negative gain or clips
+    pub fn is_loud_master(&self) -> bool {
+        is_loud_master(self.track_gain, self.track_peak)
+    }
+}
+
+/// Set the `REPLAYGAIN_*` fields that have a value, as "-6.54 dB" and
+/// "0.988831". Returns the fields written.
+pub(super) fn write(tag: &mut Tag, loudness: &Loudness) -> Vec<&'static str> {
+    let gain = |g: f32| format!("{:.2} dB", g);
+    let peak = |p: f32| format!("{:.6}", p);
+    let mut written = Vec::new();
+    for (key, value, field) in [
+        (
+            ItemKey::ReplayGainTrackGain,
+            loudness.track_gain.map(gain),
+            "replaygain_track_gain",
+        ),
+        (
+            ItemKey::ReplayGainTrackPeak,
+            loudness.track_peak.map(peak),
+            "replaygain_track_peak",
+        ),
+        (
+            ItemKey::ReplayGainAlbumGain,
+            loudness.album_gain.map(gain),
+            "replaygain_album_gain",
+        ),
+        (
+            ItemKey::ReplayGainAlbumPeak,
+            loudness.album_peak.map(peak),
+            "replaygain_album_peak",
+        ),
+    ] {
+        if let Some(value) = value
+            && tag.get_string(&key) != Some(value.as_str())
+            && tag.insert_text(key, value)
+        {
+            written.push(field);
+        }
+    }
+    written
+}
+
+/// [`Loudness::is_loud_master`] for values stored separately
+pub fn is_loud_master(track_gain: Option<f32>, track_peak: Option<f32>) -> bool {
+    track_gain.is_some_and(|g| g <= LOUD_MASTER_GAIN_DB) || track_peak.is_some_and(|p| p >= 1.0)
+}
+
+/// Display a gain as "-6.54 dB", or "—"
+pub fn format_gain(gain: Option<f32>) -> String {
+    gain.map(|g| format!("{:+.2} dB", g))
+        .unwrap_or_else(|| "—".to_string())
+}
+
+/// Display a peak as "0.988 (-0.1 dBFS)", or "—"
+pub fn format_peak(peak: Option<f32>) -> String {
+    peak.map(|p| {
+        if p > 0.0 {
+            format!("{:.3} ({:.1} dBFS)", p, 20.0 * p.log10())
+        } else {
+            format!("{:.3}", p)
+        }
+    })
+    .unwrap_or_else(|| "—".to_string())
+}
+
+/// Parse "-6.54 dB", "+1.2dB" or "-6.54"
+fn parse_gain(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let number = value
+        .strip_suffix("dB")
+        .or_else(|| value.strip_suffix("db"))
+        .or_else(|| value.strip_suffix("DB"))
+        .unwrap_or(value);
+    number.trim().parse::<f32>().ok().filter(|g| g.is_finite())
+}
+
+fn parse_peak(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|p| p.is_finite() && *p >= 0.0)
+}
+
+/// Parse an R128 gain (Q7.8 fixed point) into ReplayGain dB
+fn parse_r128(value: &str) -> Option<f32> {
+    let raw = value.trim().parse::<i16>().ok()?;
+    Some(raw as f32 / 256.0 + R128_TO_REPLAYGAIN_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lofty::tag::{ItemValue, TagItem, TagType};
+
+    #[test]
+    fn test_parse_gain() {
+        assert_eq!(parse_gain("-6.54 dB"), Some(-6.54));
+        assert_eq!(parse_gain("+1.20dB"), Some(1.2));
+        assert_eq!(parse_gain(" -3 "), Some(-3.0));
+        assert_eq!(parse_gain("loud"), None);
+        assert_eq!(parse_peak("0.988831"), Some(0.988831));
+        assert_eq!(parse_peak("-1"), None);
+        // -2304 / 256 = -9 dB below -23 LUFS, so -4 dB for ReplayGain
+        assert_eq!(parse_r128("-2304"), Some(-4.0));
+    }
+
+    #[test]
+    fn test_from_tag() {
+        let mut tag = Tag::new(TagType::VorbisComments);
+        tag.insert_text(ItemKey::ReplayGainTrackGain, "-11.20 dB".to_string());
+        tag.insert_text(ItemKey::ReplayGainTrackPeak, "1.000000".to_string());
+        // Unknown keys as lofty keeps them when reading a file
+        tag.insert_unchecked(TagItem::new(
+            ItemKey::Unknown("R128_ALBUM_GAIN".to_string()),
+            ItemValue::Text("-1280".to_string()),
+        ));
+
+        let loudness = Loudness::from_tag(&tag);
+        assert_eq!(loudness.track_gain, Some(-11.2));
+        assert_eq!(loudness.track_peak, Some(1.0));
+        assert_eq!(loudness.album_gain, Some(0.0));
+        assert_eq!(loudness.album_peak, None);
+        assert!(loudness.is_loud_master());
+        assert!(!loudness.is_empty());
+
+        assert!(Loudness::from_tag(&Tag::new(TagType::VorbisComments)).is_empty());
+        assert!(!is_loud_master(Some(-7.5), Some(0.95)));
+    }
+
+    #[test]
+    fn test_written_tags_read_back() {
+        use crate::test_utils::{AudioFixture, write_audio_fixture};
+
+        let loudness = Loudness {
+            track_gain: Some(-6.54),
+            track_peak: Some(0.988831),
+            album_gain: Some(2.5),
+            album_peak: None,
+        };
+        for format in AudioFixture::ALL {
+            let dir = tempfile::tempdir().unwrap();
+            let path = write_audio_fixture(dir.path(), format);
+            let written = crate::metadata::write_loudness(&path, &loudness).unwrap();
+            assert_eq!(written.len(), 3, "{:?}", format);
+
+            let (_, read) = crate::metadata::read_with_loudness(&path).unwrap();
+            assert_eq!(read, loudness, "{:?}", format);
+            // Nothing to save the second time
+            assert!(
+                crate::metadata::write_loudness(&path, &loudness)
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+    }
+}
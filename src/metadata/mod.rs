@@ -8,18 +8,47 @@
 //! - Preview metadata changes before writing
 //! - Write enriched metadata from identification services
 //! - Support for MusicBrainz recording IDs
+//! - Write and read the AcoustID ID and Chromaprint fingerprint
 //! - Embed cover art images
+//! - Detect placeholder values ("Unknown Artist", "Track 01") in fill-only mode
+//! - Write several genres as separate values where the format allows
+//! - Read ReplayGain/R128 loudness tags, and write measured ReplayGain
+//! - Read ratings and play counts other players wrote (POPM, FMPS)
+//! - Read and write the language and explicit-content flag
+//! - Probe codec profile, encoder settings, true peak and tag sizes
+//! - Audit each save: tag versions and file size before and after, and a read-back check
+//! - Save ID3v2.3 or v2.4, in the text encoding and with the padding set per format
+
+pub mod acoustid;
+pub mod audit;
+pub mod content;
+pub mod genres;
+pub mod loudness;
+mod placeholder;
+pub mod ratings;
+pub mod strategy;
+pub mod technical;
+
+pub use acoustid::AcoustIdTags;
+pub use content::ContentTags;
+pub use loudness::Loudness;
+pub use placeholder::PlaceholderDetector;
+pub use ratings::TagRatings;
+pub use strategy::WriteStrategy;
 
 use anyhow::{Context, Result, bail};
-use lofty::config::WriteOptions;
-use lofty::file::{AudioFile, TaggedFileExt};
+use lofty::file::{AudioFile, FileType, TaggedFileExt};
+use lofty::id3::v2::Id3v2Tag;
 use lofty::picture::{MimeType, Picture, PictureType};
 use lofty::probe::Probe;
-use lofty::tag::{Accessor, ItemKey, ItemValue, Tag, TagExt, TagItem};
+use lofty::tag::{Accessor, ItemKey, ItemValue, Tag, TagExt, TagItem, TagType};
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::enrichment::domain::IdentifiedTrack;
+use crate::error::Retry;
 
 /// Track metadata - uses String for SQLx compatibility.
 /// The metadata is read once and stored, so allocation overhead is minimal.
@@ -41,7 +70,10 @@ pub struct FullMetadata {
     pub album: Option<String>,
     pub album_artist: Option<String>,
     pub year: Option<u32>,
+    /// Every genre, joined with "; " for display
     pub genre: Option<String>,
+    /// Every genre, in tag order
+    pub genres: Vec<String>,
 
     // Track positioning
     pub track_number: Option<u32>,
@@ -53,6 +85,10 @@ pub struct FullMetadata {
     pub composer: Option<String>,
     pub comment: Option<String>,
     pub lyrics: Option<String>,
+    /// ISO 639-2 ("zxx" = instrumental)
+    pub language: Option<String>,
+    /// Advisory rating: explicit, clean, or neither
+    pub explicit: Option<bool>,
 
     // MusicBrainz IDs
     pub musicbrainz_recording_id: Option<String>,
@@ -61,6 +97,9 @@ pub struct FullMetadata {
     pub musicbrainz_release_group_id: Option<String>,
     pub musicbrainz_track_id: Option<String>,
 
+    // AcoustID track ID and fingerprint
+    pub acoustid: AcoustIdTags,
+
     // Audio properties
     pub duration_secs: u64,
     pub bitrate: Option<u32>,
@@ -68,6 +107,9 @@ pub struct FullMetadata {
     pub channels: Option<u8>,
     pub bits_per_sample: Option<u8>,
 
+    // ReplayGain/R128 tags
+    pub loudness: Loudness,
+
     // Cover art
     pub has_cover_art: bool,
     pub cover_art_size: Option<(u32, u32)>, // width x height if known
@@ -82,8 +124,12 @@ pub struct FullMetadata {
 pub struct WriteOptions2 {
     /// Only write fields that are currently empty/unknown in the file
     pub only_fill_empty: bool,
-    /// Write MusicBrainz IDs to tags
+    /// Write MusicBrainz IDs, the AcoustID ID and the fingerprint to tags
     pub write_musicbrainz_ids: bool,
+    /// Decides which existing values count as empty when `only_fill_empty` is set
+    pub placeholders: PlaceholderDetector,
+    /// Spellings of the genres written as another (`tagging.genre_map`)
+    pub genre_map: genres::GenreMap,
 }
 
 /// Result of a write operation
@@ -91,11 +137,39 @@ pub struct WriteOptions2 {
 pub struct WriteResult {
     /// Number of fields that were updated
     pub fields_updated: usize,
+    /// Names of the fields written ("title", "album", "musicbrainz_release_id", ...)
+    pub fields_written: Vec<&'static str>,
     /// Fields that were skipped (already had values)
     pub fields_skipped: Vec<String>,
 }
 
+/// What a scan reads from a file: the track metadata, genres, loudness
+/// tags, ratings and play counts, and language and explicit flag
+#[derive(Debug, Clone)]
+pub struct IndexedTags {
+    pub metadata: TrackMetadata,
+    /// As tagged; the scan applies the genre rules
+    pub genres: Vec<String>,
+    pub loudness: Loudness,
+    pub ratings: TagRatings,
+    pub content: ContentTags,
+    /// Audio bitrate in kbps
+    pub bitrate: Option<u32>,
+    /// The album artist tag, if there is one
+    pub album_artist: Option<String>,
+}
+
 pub fn read(path: &Path) -> Result<TrackMetadata> {
+    read_with_loudness(path).map(|(metadata, _)| metadata)
+}
+
+/// Read track metadata along with its loudness tags
+pub fn read_with_loudness(path: &Path) -> Result<(TrackMetadata, Loudness)> {
+    read_for_index(path).map(|tags| (tags.metadata, tags.loudness))
+}
+
+/// Read everything a scan stores
+pub fn read_for_index(path: &Path) -> Result<IndexedTags> {
     // Probe the file to determine format and read tags
     let tagged_file = Probe::open(path)
         .context("Failed to open file for probing")?
@@ -121,17 +195,37 @@ pub fn read(path: &Path) -> Result<TrackMetadata> {
         .unwrap_or_else(|| "Unknown Album".to_string());
 
     let track_number = tag.and_then(|t| t.track());
+    let album_artist = tag
+        .and_then(|t| t.get_string(&ItemKey::AlbumArtist))
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string);
 
     // Get duration from properties
     let properties = tagged_file.properties();
     let duration = properties.duration().as_secs();
+    let loudness = tag.map(Loudness::from_tag).unwrap_or_default();
+    let content = tag.map(ContentTags::from_tag).unwrap_or_default();
+    let genres = tag.map(genres::read).unwrap_or_default();
+    let mut ratings = tag.map(TagRatings::from_tag).unwrap_or_default();
+    if tagged_file.tag(TagType::Id3v2).is_some() {
+        ratings.popm = ratings::read_popm(path, tagged_file.file_type());
+    }
 
-    Ok(TrackMetadata {
-        title,
-        artist,
-        album,
-        duration,
-        track_number,
+    Ok(IndexedTags {
+        metadata: TrackMetadata {
+            title,
+            artist,
+            album,
+            duration,
+            track_number,
+        },
+        genres,
+        loudness,
+        ratings,
+        content,
+        bitrate: properties.audio_bitrate(),
+        album_artist,
     })
 }
 
@@ -153,6 +247,8 @@ pub fn read_full(path: &Path) -> Result<FullMetadata> {
 
     // Determine format from file type
     let format = format!("{:?}", tagged_file.file_type());
+    let content = tag.map(ContentTags::from_tag).unwrap_or_default();
+    let genres = tag.map(genres::read).unwrap_or_default();
 
     // Helper to get tag text
     let get_text = |key: ItemKey| -> Option<String> {
@@ -182,7 +278,8 @@ pub fn read_full(path: &Path) -> Result<FullMetadata> {
         album: tag.and_then(|t| t.album().map(|s| s.to_string())),
         album_artist: get_text(ItemKey::AlbumArtist),
         year: tag.and_then(|t| t.year()),
-        genre: tag.and_then(|t| t.genre().map(|s| s.to_string())),
+        genre: (!genres.is_empty()).then(|| genres.join(genres::SEPARATOR)),
+        genres,
 
         // Track positioning
         track_number: tag.and_then(|t| t.track()),
@@ -194,6 +291,8 @@ pub fn read_full(path: &Path) -> Result<FullMetadata> {
         composer: get_text(ItemKey::Composer),
         comment: tag.and_then(|t| t.comment().map(|s| s.to_string())),
         lyrics: get_text(ItemKey::Lyrics),
+        language: content.language,
+        explicit: content.explicit,
 
         // MusicBrainz IDs
         musicbrainz_recording_id: {
@@ -205,6 +304,7 @@ pub fn read_full(path: &Path) -> Result<FullMetadata> {
         musicbrainz_release_id: get_text(ItemKey::MusicBrainzReleaseId),
         musicbrainz_release_group_id: get_text(ItemKey::MusicBrainzReleaseGroupId),
         musicbrainz_track_id: get_text(ItemKey::MusicBrainzTrackId),
+        acoustid: tag.map(AcoustIdTags::from_tag).unwrap_or_default(),
 
         // Audio properties
         duration_secs: properties.duration().as_secs(),
@@ -213,6 +313,8 @@ pub fn read_full(path: &Path) -> Result<FullMetadata> {
         channels: properties.channels(),
         bits_per_sample: properties.bit_depth(),
 
+        loudness: tag.map(Loudness::from_tag).unwrap_or_default(),
+
         // Cover art
         has_cover_art,
         cover_art_size,
@@ -228,6 +330,7 @@ pub fn read_full(path: &Path) -> Result<FullMetadata> {
 /// This updates the file's embedded metadata tags with the identified track info.
 /// Supports MP3 (ID3v2), FLAC, M4A/AAC, OGG Vorbis, and other formats via lofty.
 pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> Result<WriteResult> {
+    crate::readonly::ensure_writable("Writing tags")?;
     // Read the existing file
     let mut tagged_file = Probe::open(path)
         .context("Failed to open file for writing")?
@@ -247,25 +350,15 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         tagged_file.tag_mut(tag_type).expect("Just inserted tag")
     };
 
-    let mut fields_updated = 0;
+    let mut fields_written = Vec::new();
     let mut fields_skipped = Vec::new();
 
     // Helper to check if we should write a field
     let should_write =
         |existing: Option<&str>, field_name: &str, skipped: &mut Vec<String>| -> bool {
-            if options.only_fill_empty {
-                let dominated = existing
-                    .map(|s| {
-                        !s.is_empty()
-                            && s != "Unknown Title"
-                            && s != "Unknown Artist"
-                            && s != "Unknown Album"
-                    })
-                    .unwrap_or(false);
-                if dominated {
-                    skipped.push(field_name.to_string());
-                    return false;
-                }
+            if options.only_fill_empty && !options.placeholders.is_missing(existing) {
+                skipped.push(field_name.to_string());
+                return false;
             }
             true
         };
@@ -275,7 +368,7 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         && should_write(tag.title().as_deref(), "title", &mut fields_skipped)
     {
         tag.set_title(title.clone());
-        fields_updated += 1;
+        fields_written.push("title");
     }
 
     // Write artist
@@ -283,7 +376,7 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         && should_write(tag.artist().as_deref(), "artist", &mut fields_skipped)
     {
         tag.set_artist(artist.clone());
-        fields_updated += 1;
+        fields_written.push("artist");
     }
 
     // Write album artist (use track.album_artist, or fall back to track.artist for consistency)
@@ -291,11 +384,9 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         let existing = tag
             .get(&ItemKey::AlbumArtist)
             .and_then(|i| i.value().text());
-        if !options.only_fill_empty || existing.is_none() {
+        if should_write(existing, "album_artist", &mut fields_skipped) {
             tag.insert_text(ItemKey::AlbumArtist, album_artist.clone());
-            fields_updated += 1;
-        } else {
-            fields_skipped.push("album_artist".to_string());
+            fields_written.push("album_artist");
         }
     }
 
@@ -304,7 +395,7 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         && should_write(tag.album().as_deref(), "album", &mut fields_skipped)
     {
         tag.set_album(album.clone());
-        fields_updated += 1;
+        fields_written.push("album");
     }
 
     // Write track number
@@ -312,7 +403,7 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         let existing = tag.track();
         if !options.only_fill_empty || existing.is_none() {
             tag.set_track(track_num);
-            fields_updated += 1;
+            fields_written.push("track_number");
         } else {
             fields_skipped.push("track_number".to_string());
         }
@@ -323,7 +414,7 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         let existing = tag.track_total();
         if !options.only_fill_empty || existing.is_none() {
             tag.set_track_total(total);
-            fields_updated += 1;
+            fields_written.push("total_tracks");
         } else {
             fields_skipped.push("total_tracks".to_string());
         }
@@ -334,7 +425,7 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         let existing = tag.year();
         if !options.only_fill_empty || existing.is_none() {
             tag.set_year(year as u32);
-            fields_updated += 1;
+            fields_written.push("year");
         } else {
             fields_skipped.push("year".to_string());
         }
@@ -345,7 +436,7 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         let existing = tag.disk();
         if !options.only_fill_empty || existing.is_none() {
             tag.set_disk(disc_num);
-            fields_updated += 1;
+            fields_written.push("disc_number");
         } else {
             fields_skipped.push("disc_number".to_string());
         }
@@ -356,22 +447,48 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
         let existing = tag.disk_total();
         if !options.only_fill_empty || existing.is_none() {
             tag.set_disk_total(total_discs);
-            fields_updated += 1;
+            fields_written.push("total_discs");
         } else {
             fields_skipped.push("total_discs".to_string());
         }
     }
 
-    // Write genre (use first genre as primary)
-    if !track.genres.is_empty() {
-        let existing = tag.genre();
-        if !options.only_fill_empty || existing.is_none() {
-            // Join multiple genres with semicolon (common convention)
-            let genre_str = track.genres.join("; ");
-            tag.set_genre(genre_str);
-            fields_updated += 1;
-        } else {
-            fields_skipped.push("genre".to_string());
+    // Write genres (separate values where the format has them)
+    if !track.genres.is_empty()
+        && should_write(tag.genre().as_deref(), "genre", &mut fields_skipped)
+    {
+        genres::write(tag, &options.genre_map.apply(&track.genres));
+        fields_written.push("genre");
+    }
+
+    // Write language (ID3 TLAN, LANGUAGE elsewhere)
+    if let Some(ref language) = track.language
+        && should_write(
+            tag.get_string(&ItemKey::Language),
+            "language",
+            &mut fields_skipped,
+        )
+        && tag.insert_text(ItemKey::Language, language.clone())
+    {
+        fields_written.push("language");
+    }
+
+    // Write the advisory rating (Vorbis comments have no standard field;
+    // ITUNESADVISORY is what other taggers use)
+    if let Some(explicit) = track.explicit {
+        let existing = ContentTags::from_tag(tag)
+            .explicit
+            .map(content::advisory_value);
+        if should_write(existing, "explicit", &mut fields_skipped) {
+            let value = content::advisory_value(explicit).to_string();
+            if tag.insert_text(ItemKey::ParentalAdvisory, value.clone()) {
+                fields_written.push("explicit");
+            } else if tag_type == TagType::VorbisComments {
+                let key = ItemKey::Unknown("ITUNESADVISORY".to_string());
+                tag.remove_key(&key);
+                tag.insert_unchecked(TagItem::new(key, ItemValue::Text(value)));
+                fields_written.push("explicit");
+            }
         }
     }
 
@@ -409,15 +526,15 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
                 "[DEBUG WRITE] After insert_unchecked, tag has recording_id: {:?}",
                 check
             );
-            fields_updated += 1;
+            fields_written.push("musicbrainz_recording_id");
         }
         if let Some(ref artist_id) = track.artist_id {
             insert_mb_id(tag, ItemKey::MusicBrainzArtistId, artist_id.clone());
-            fields_updated += 1;
+            fields_written.push("musicbrainz_artist_id");
         }
         if let Some(ref release_id) = track.release_id {
             insert_mb_id(tag, ItemKey::MusicBrainzReleaseId, release_id.clone());
-            fields_updated += 1;
+            fields_written.push("musicbrainz_release_id");
         }
         if let Some(ref release_group_id) = track.release_group_id {
             insert_mb_id(
@@ -425,114 +542,157 @@ pub fn write(path: &Path, track: &IdentifiedTrack, options: &WriteOptions2) -> R
                 ItemKey::MusicBrainzReleaseGroupId,
                 release_group_id.clone(),
             );
-            fields_updated += 1;
-        }
-    }
-
-    // ATOMIC WRITE: Write to temp file, verify, then replace original
-    // This prevents corruption if the app crashes or power is lost mid-write
-    let temp_path = path.with_extension("tmp");
-    let backup_path = path.with_extension("bak");
-
-    // Step 1: Write to temp file
-    tagged_file
-        .save_to_path(&temp_path, WriteOptions::default())
-        .context("Failed to write tags to temp file")?;
-
-    // Step 2: Verify the temp file is valid audio
-    if let Err(e) = Probe::open(&temp_path).and_then(|p| p.read()) {
-        // Clean up temp file and fail
-        let _ = fs::remove_file(&temp_path);
-        bail!(
-            "Written file failed validation: {}. Original file unchanged.",
-            e
-        );
-    }
-
-    // Step 3: Rename original to backup
-    if path.exists() {
-        fs::rename(path, &backup_path).context("Failed to create backup of original file")?;
-    }
-
-    // Step 4: Rename temp to original
-    if let Err(e) = fs::rename(&temp_path, path) {
-        // Try to restore backup
-        if backup_path.exists() {
-            let _ = fs::rename(&backup_path, path);
+            fields_written.push("musicbrainz_release_group_id");
         }
-        return Err(e).context("Failed to replace original with updated file");
+        fields_written.extend(acoustid::write(
+            tag,
+            tag_type,
+            track.acoustid_id.as_deref(),
+            track.fingerprint.as_deref(),
+        ));
     }
 
-    // Step 5: Remove backup (success!)
-    let _ = fs::remove_file(&backup_path);
+    save_atomically(path, &tagged_file, tag_type)?;
 
     Ok(WriteResult {
-        fields_updated,
+        fields_updated: fields_written.len(),
+        fields_written,
         fields_skipped,
     })
 }
 
 /// Preview what changes would be made without actually writing
+///
+/// Lists every field [`write`] would set with the same track and options,
+/// under the names it reports in [`WriteResult::fields_written`], so a
+/// plan built from the preview writes exactly what it shows.
 pub fn preview_write(
     path: &Path,
     track: &IdentifiedTrack,
     options: &WriteOptions2,
 ) -> Result<WritePreview> {
-    let current = read(path)?;
+    let current = read_full(path)?;
 
     let mut changes = Vec::new();
+    let mut skipped = Vec::new();
+
+    // Fill-only skips fields that already have a value: text that isn't a
+    // placeholder, or any number
+    let mut add_change =
+        |field: &str, current_val: Option<String>, filled: bool, new_val: Option<String>| {
+            let Some(new) = new_val else {
+                return;
+            };
+            if options.only_fill_empty && filled {
+                skipped.push(field.to_string());
+                return;
+            }
+            changes.push(FieldChange {
+                field: field.to_string(),
+                current_value: current_val.unwrap_or_default(),
+                new_value: new,
+            });
+        };
+    let mut add_text = |field: &str, current_val: Option<String>, new_val: Option<String>| {
+        let filled = !options.placeholders.is_missing(current_val.as_deref());
+        add_change(field, current_val, filled, new_val);
+    };
+    add_text("title", current.title, track.title.clone());
+    add_text("artist", current.artist, track.artist.clone());
+    add_text(
+        "album_artist",
+        current.album_artist,
+        track.album_artist.clone(),
+    );
+    add_text("album", current.album, track.album.clone());
+    add_text(
+        "genre",
+        current.genre,
+        (!track.genres.is_empty()).then(|| {
+            options
+                .genre_map
+                .apply(&track.genres)
+                .join(genres::SEPARATOR)
+        }),
+    );
+    add_text("language", current.language, track.language.clone());
+    add_text(
+        "explicit",
+        current
+            .explicit
+            .map(|e| content::advisory_value(e).to_string()),
+        track
+            .explicit
+            .map(|e| content::advisory_value(e).to_string()),
+    );
 
-    // Helper to add a change
-    let mut add_change = |field: &str, current_val: &str, new_val: Option<&str>| {
-        if let Some(new) = new_val {
-            let is_unknown = current_val.starts_with("Unknown") || current_val.is_empty();
-            if !options.only_fill_empty || is_unknown {
+    let mut add_number = |field: &str, current_val: Option<u32>, new_val: Option<u32>| {
+        add_change(
+            field,
+            current_val.map(|n| n.to_string()),
+            current_val.is_some(),
+            new_val.map(|n| n.to_string()),
+        );
+    };
+    add_number("track_number", current.track_number, track.track_number);
+    add_number("total_tracks", current.total_tracks, track.total_tracks);
+    add_number("year", current.year, track.year.map(|y| y as u32));
+    add_number("disc_number", current.disc_number, track.disc_number);
+    add_number("total_discs", current.total_discs, track.total_discs);
+
+    // IDs are always replaced, as write does
+    if options.write_musicbrainz_ids {
+        for (field, current_val, new_val) in [
+            (
+                "musicbrainz_recording_id",
+                current.musicbrainz_recording_id,
+                &track.recording_id,
+            ),
+            (
+                "musicbrainz_artist_id",
+                current.musicbrainz_artist_id,
+                &track.artist_id,
+            ),
+            (
+                "musicbrainz_release_id",
+                current.musicbrainz_release_id,
+                &track.release_id,
+            ),
+            (
+                "musicbrainz_release_group_id",
+                current.musicbrainz_release_group_id,
+                &track.release_group_id,
+            ),
+            ("acoustid_id", current.acoustid.id, &track.acoustid_id),
+            (
+                "acoustid_fingerprint",
+                current.acoustid.fingerprint,
+                &track.fingerprint,
+            ),
+        ] {
+            if let Some(new) = new_val {
                 changes.push(FieldChange {
                     field: field.to_string(),
-                    current_value: current_val.to_string(),
-                    new_value: new.to_string(),
+                    current_value: current_val.unwrap_or_default(),
+                    new_value: new.clone(),
                 });
             }
         }
-    };
-
-    add_change("title", &current.title, track.title.as_deref());
-    add_change("artist", &current.artist, track.artist.as_deref());
-    add_change("album", &current.album, track.album.as_deref());
-
-    if let Some(track_num) = track.track_number {
-        let current_str = current
-            .track_number
-            .map(|n| n.to_string())
-            .unwrap_or_default();
-        if !options.only_fill_empty || current.track_number.is_none() {
-            changes.push(FieldChange {
-                field: "track_number".to_string(),
-                current_value: current_str,
-                new_value: track_num.to_string(),
-            });
-        }
     }
 
-    if let Some(year) = track.year {
-        changes.push(FieldChange {
-            field: "year".to_string(),
-            current_value: String::new(),
-            new_value: year.to_string(),
-        });
-    }
-
-    Ok(WritePreview { changes })
+    Ok(WritePreview { changes, skipped })
 }
 
 /// A preview of changes that would be made
 #[derive(Debug, Clone)]
 pub struct WritePreview {
     pub changes: Vec<FieldChange>,
+    /// Fields left alone because they already have a value (fill-only)
+    pub skipped: Vec<String>,
 }
 
 /// A single field change
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FieldChange {
     pub field: String,
     pub current_value: String,
@@ -549,6 +709,7 @@ pub fn write_cover_art(
     mime_type: &str,
     only_if_missing: bool,
 ) -> Result<bool> {
+    crate::readonly::ensure_writable("Writing cover art")?;
     // Read the existing file
     let mut tagged_file = Probe::open(path)
         .context("Failed to open file for cover art writing")?
@@ -557,6 +718,7 @@ pub fn write_cover_art(
 
     // Get the primary tag type for this format
     let tag_type = tagged_file.primary_tag_type();
+    let file_type = tagged_file.file_type();
 
     // Get or create the tag
     let tag = if let Some(tag) = tagged_file.tag_mut(tag_type) {
@@ -601,12 +763,344 @@ pub fn write_cover_art(
     tag.push_picture(picture);
 
     // Save the file
-    tag.save_to_path(path, WriteOptions::default())
-        .context("Failed to write cover art to file")?;
+    save_tag(path, tag, file_type).context("Failed to write cover art to file")?;
+
+    Ok(true)
+}
+
+/// Remove the front cover embedded in an audio file's tags
+///
+/// Returns false, and saves nothing, if the file has none.
+#[cfg(feature = "gui")]
+pub fn remove_cover_art(path: &Path) -> Result<bool> {
+    crate::readonly::ensure_writable("Writing cover art")?;
+    let mut tagged_file = Probe::open(path)
+        .context("Failed to open file for cover art writing")?
+        .read()
+        .context("Failed to read file for cover art writing")?;
+
+    let tag_type = tagged_file.primary_tag_type();
+    let file_type = tagged_file.file_type();
+    let Some(tag) = tagged_file.tag_mut(tag_type) else {
+        return Ok(false);
+    };
+    if !tag
+        .pictures()
+        .iter()
+        .any(|p| p.pic_type() == PictureType::CoverFront)
+    {
+        return Ok(false);
+    }
+    tag.remove_picture_type(PictureType::CoverFront);
+    save_tag(path, tag, file_type).context("Failed to remove cover art from file")?;
+
+    Ok(true)
+}
+
+/// Replace a file's genres with `genres` (removing them if empty).
+///
+/// Returns false, and saves nothing, if the file already has exactly those.
+pub fn write_genres(path: &Path, new_genres: &[String]) -> Result<bool> {
+    crate::readonly::ensure_writable("Writing tags")?;
+    let mut tagged_file = Probe::open(path)
+        .context("Failed to open file for writing")?
+        .read()
+        .context("Failed to read file for tag writing")?;
 
+    let tag_type = tagged_file.primary_tag_type();
+    let tag = if let Some(tag) = tagged_file.tag_mut(tag_type) {
+        tag
+    } else {
+        tagged_file.insert_tag(Tag::new(tag_type));
+        tagged_file.tag_mut(tag_type).expect("Just inserted tag")
+    };
+    if genres::read(tag) == new_genres {
+        return Ok(false);
+    }
+    if new_genres.is_empty() {
+        tag.remove_key(&ItemKey::Genre);
+    } else {
+        genres::write(tag, new_genres);
+    }
+    save_atomically(path, &tagged_file, tag_type)?;
     Ok(true)
 }
 
+/// Set a file's ReplayGain tags to the values `loudness` has, leaving the
+/// others alone.
+///
+/// Returns the names of the fields that changed (nothing is saved if none did).
+pub fn write_loudness(path: &Path, loudness: &Loudness) -> Result<Vec<&'static str>> {
+    crate::readonly::ensure_writable("Writing tags")?;
+    let mut tagged_file = Probe::open(path)
+        .context("Failed to open file for writing")?
+        .read()
+        .context("Failed to read file for tag writing")?;
+
+    let tag_type = tagged_file.primary_tag_type();
+    let tag = if let Some(tag) = tagged_file.tag_mut(tag_type) {
+        tag
+    } else {
+        tagged_file.insert_tag(Tag::new(tag_type));
+        tagged_file.tag_mut(tag_type).expect("Just inserted tag")
+    };
+    let fields_written = loudness::write(tag, loudness);
+    if !fields_written.is_empty() {
+        save_atomically(path, &tagged_file, tag_type)?;
+    }
+    Ok(fields_written)
+}
+
+/// Where a track sits on its album, as tagged.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Numbering {
+    pub track_number: Option<u32>,
+    pub total_tracks: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub total_discs: Option<u32>,
+}
+
+/// Read just the track and disc numbering of a file.
+pub fn read_numbering(path: &Path) -> Result<Numbering> {
+    let tagged_file = Probe::open(path)
+        .context("Failed to open file")?
+        .read()
+        .context("Failed to read file metadata")?;
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag());
+    Ok(Numbering {
+        track_number: tag.and_then(|t| t.track()),
+        total_tracks: tag.and_then(|t| t.track_total()),
+        disc_number: tag.and_then(|t| t.disk()),
+        total_discs: tag.and_then(|t| t.disk_total()),
+    })
+}
+
+/// Set a file's track total and disc tags to exactly `numbering`, removing
+/// the ones that are `None`. The track number itself is left alone.
+///
+/// Returns the names of the fields that changed (nothing is saved if none did).
+pub fn write_numbering(path: &Path, numbering: &Numbering) -> Result<Vec<&'static str>> {
+    crate::readonly::ensure_writable("Writing tags")?;
+    let mut tagged_file = Probe::open(path)
+        .context("Failed to open file for writing")?
+        .read()
+        .context("Failed to read file for tag writing")?;
+
+    let tag_type = tagged_file.primary_tag_type();
+    let tag = if let Some(tag) = tagged_file.tag_mut(tag_type) {
+        tag
+    } else {
+        tagged_file.insert_tag(Tag::new(tag_type));
+        tagged_file.tag_mut(tag_type).expect("Just inserted tag")
+    };
+
+    let mut fields_written = Vec::new();
+    if tag.track_total() != numbering.total_tracks {
+        match numbering.total_tracks {
+            Some(total) => tag.set_track_total(total),
+            None => tag.remove_track_total(),
+        }
+        fields_written.push("total_tracks");
+    }
+    if tag.disk() != numbering.disc_number {
+        match numbering.disc_number {
+            Some(disc) => tag.set_disk(disc),
+            None => tag.remove_disk(),
+        }
+        fields_written.push("disc_number");
+    }
+    if tag.disk_total() != numbering.total_discs {
+        match numbering.total_discs {
+            Some(total) => tag.set_disk_total(total),
+            None => tag.remove_disk_total(),
+        }
+        fields_written.push("total_discs");
+    }
+
+    if !fields_written.is_empty() {
+        save_atomically(path, &tagged_file, tag_type)?;
+    }
+    Ok(fields_written)
+}
+
+/// A track's album and album artist, as tagged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlbumTags {
+    pub album: Option<String>,
+    pub album_artist: Option<String>,
+}
+
+/// Read just the album and album artist of a file.
+pub fn read_album_tags(path: &Path) -> Result<AlbumTags> {
+    let tagged_file = Probe::open(path)
+        .context("Failed to open file")?
+        .read()
+        .context("Failed to read file metadata")?;
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag());
+    Ok(AlbumTags {
+        album: tag.and_then(|t| t.album().map(|s| s.to_string())),
+        album_artist: tag
+            .and_then(|t| t.get_string(&ItemKey::AlbumArtist))
+            .map(str::to_string),
+    })
+}
+
+/// Set a file's album and album artist tags to exactly `tags`, removing the
+/// ones that are `None`.
+///
+/// Returns the names of the fields that changed (nothing is saved if none did).
+pub fn write_album_tags(path: &Path, tags: &AlbumTags) -> Result<Vec<&'static str>> {
+    crate::readonly::ensure_writable("Writing tags")?;
+    let mut tagged_file = Probe::open(path)
+        .context("Failed to open file for writing")?
+        .read()
+        .context("Failed to read file for tag writing")?;
+
+    let tag_type = tagged_file.primary_tag_type();
+    let tag = if let Some(tag) = tagged_file.tag_mut(tag_type) {
+        tag
+    } else {
+        tagged_file.insert_tag(Tag::new(tag_type));
+        tagged_file.tag_mut(tag_type).expect("Just inserted tag")
+    };
+
+    let mut fields_written = Vec::new();
+    if tag.album().as_deref() != tags.album.as_deref() {
+        match &tags.album {
+            Some(album) => tag.set_album(album.clone()),
+            None => tag.remove_album(),
+        }
+        fields_written.push("album");
+    }
+    if tag.get_string(&ItemKey::AlbumArtist) != tags.album_artist.as_deref() {
+        match &tags.album_artist {
+            Some(artist) => {
+                tag.insert_text(ItemKey::AlbumArtist, artist.clone());
+            }
+            None => tag.remove_key(&ItemKey::AlbumArtist),
+        }
+        fields_written.push("album_artist");
+    }
+
+    if !fields_written.is_empty() {
+        save_atomically(path, &tagged_file, tag_type)?;
+    }
+    Ok(fields_written)
+}
+
+/// Save `tag` to `path` in place, the way [`strategy::current`] says
+fn save_tag(path: &Path, tag: &Tag, file_type: FileType) -> lofty::error::Result<()> {
+    let strategy = strategy::current();
+    let options = strategy.options(file_type);
+    if tag.tag_type() == TagType::Id3v2 {
+        let mut id3 = Id3v2Tag::from(tag.clone());
+        strategy.encode(&mut id3);
+        id3.save_to_path(path, options)
+    } else {
+        tag.save_to_path(path, options)
+    }
+}
+
+/// Tag saves under way in this process
+static WRITES_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of tag saves currently between their temp file and the final
+/// rename, so shutdown can wait for them rather than leave a `.tmp` behind
+pub fn writes_in_flight() -> usize {
+    WRITES_IN_FLIGHT.load(Ordering::Acquire)
+}
+
+/// Counts a save in [`WRITES_IN_FLIGHT`] until dropped
+struct InFlight;
+
+impl InFlight {
+    fn begin() -> Self {
+        WRITES_IN_FLIGHT.fetch_add(1, Ordering::AcqRel);
+        InFlight
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        WRITES_IN_FLIGHT.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Save the tags of `tagged_file` to `path` atomically: write to a temp
+/// file, verify it, then replace the original. This prevents corruption if
+/// the app crashes or power is lost mid-write.
+fn save_atomically(
+    path: &Path,
+    tagged_file: &lofty::file::TaggedFile,
+    tag_type: TagType,
+) -> Result<()> {
+    let _in_flight = InFlight::begin();
+    let before = audit::before(path, tagged_file);
+    let temp_path = path.with_extension("tmp");
+    let backup_path = path.with_extension("bak");
+
+    // Step 1: Write to temp file (lofty updates files in place, so start
+    // from a copy of the original)
+    fs::copy(path, &temp_path).context("Failed to create temp file")?;
+    let saved = if tag_type == TagType::Id3v2 {
+        // Saving the generic tag drops items that aren't plain ID3v2 text
+        // frames, like the recording ID (a UFID frame); converting keeps them
+        let tag = tagged_file
+            .tag(tag_type)
+            .cloned()
+            .unwrap_or_else(|| Tag::new(tag_type));
+        save_tag(&temp_path, &tag, tagged_file.file_type())
+    } else {
+        let options = strategy::current().options(tagged_file.file_type());
+        tagged_file.save_to_path(&temp_path, options)
+    };
+    if let Err(e) = saved {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e).context("Failed to write tags to temp file");
+    }
+
+    // Step 2: Verify the temp file is valid audio (by content: the temp
+    // extension says nothing about the format)
+    if let Err(e) = Probe::open(&temp_path)
+        .and_then(|p| Ok(p.guess_file_type()?))
+        .and_then(|p| p.read())
+    {
+        // Clean up temp file and fail
+        let _ = fs::remove_file(&temp_path);
+        bail!(
+            "Written file failed validation: {}. Original file unchanged.",
+            e
+        );
+    }
+
+    // Step 3: Rename original to backup
+    if path.exists() {
+        // A player or sync client may have the file open for a moment
+        if let Err(e) = Retry::FILE.run(|| fs::rename(path, &backup_path)) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(e).context("Failed to create backup of original file");
+        }
+    }
+
+    // Step 4: Rename temp to original
+    if let Err(e) = fs::rename(&temp_path, path) {
+        // Try to restore backup
+        if backup_path.exists() {
+            let _ = fs::rename(&backup_path, path);
+        }
+        return Err(e).context("Failed to replace original with updated file");
+    }
+
+    // Step 5: Remove backup (success!)
+    let _ = fs::remove_file(&backup_path);
+    audit::after(path, before);
+    Ok(())
+}
+
 /// Check if a file already has embedded cover art
 pub fn has_cover_art(path: &Path) -> Result<bool> {
     let tagged_file = Probe::open(path)
@@ -654,12 +1148,14 @@ mod tests {
         let options = WriteOptions2::default();
         assert!(!options.only_fill_empty);
         assert!(!options.write_musicbrainz_ids);
+        assert!(options.placeholders.is_placeholder("Unknown Artist"));
     }
 
     #[test]
     fn test_write_result_fields() {
         let result = WriteResult {
             fields_updated: 3,
+            fields_written: vec!["artist", "album", "year"],
             fields_skipped: vec!["title".to_string()],
         };
         assert_eq!(result.fields_updated, 3);
@@ -699,8 +1195,220 @@ mod tests {
                 current_value: "".to_string(),
                 new_value: "Queen".to_string(),
             }],
+            skipped: Vec::new(),
         };
         assert_eq!(preview.changes.len(), 1);
         assert_eq!(preview.changes[0].new_value, "Queen");
     }
 }
+
+/// Property-based round-trip and robustness tests using proptest
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use crate::test_utils::{AudioFixture, write_audio_fixture};
+    use proptest::prelude::*;
+
+    /// Tag text: printable, no surrounding whitespace (formats may trim it)
+    fn tag_text() -> impl Strategy<Value = String> {
+        prop::string::string_regex(
+            "[A-Za-z0-9À-ÿ&'()./!?-]([A-Za-z0-9À-ÿ &'()./!?-]{0,38}[A-Za-z0-9À-ÿ&'()./!?-])?",
+        )
+        .unwrap()
+    }
+
+    /// Genre names as MusicBrainz has them (bare numbers are ID3v1 genre
+    /// indexes in ID3v2, so "0" reads back as "Blues")
+    fn genre() -> impl Strategy<Value = String> {
+        prop::string::string_regex("[a-z][a-z&-]{1,12}( [a-z]{2,10})?").unwrap()
+    }
+
+    fn mbid() -> impl Strategy<Value = String> {
+        prop::string::string_regex("[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}")
+            .unwrap()
+    }
+
+    /// Chromaprint fingerprints as fpcalc prints them (URL-safe base64)
+    fn fingerprint() -> impl Strategy<Value = String> {
+        prop::string::string_regex("AQA[A-Za-z0-9_-]{16,400}").unwrap()
+    }
+
+    prop_compose! {
+        fn identified_track()(
+            title in tag_text(),
+            artist in tag_text(),
+            album_artist in tag_text(),
+            album in tag_text(),
+            track in 1u32..100,
+            total_tracks in 1u32..100,
+            disc in 1u32..10,
+            total_discs in 1u32..10,
+            year in 1900i32..2100,
+            genres in prop::collection::vec(genre(), 1..4),
+            ids in (mbid(), mbid(), mbid(), mbid()),
+            acoustid in (mbid(), fingerprint()),
+            language in prop::sample::select(vec!["eng", "jpn", "zxx"]),
+            explicit in any::<Option<bool>>(),
+        ) -> IdentifiedTrack {
+            IdentifiedTrack {
+                recording_id: Some(ids.0),
+                title: Some(title),
+                artist: Some(artist),
+                album_artist: Some(album_artist),
+                album: Some(album),
+                track_number: Some(track),
+                total_tracks: Some(total_tracks),
+                disc_number: Some(disc),
+                total_discs: Some(total_discs),
+                year: Some(year),
+                artist_id: Some(ids.1),
+                release_id: Some(ids.2),
+                release_group_id: Some(ids.3),
+                genres,
+                language: Some(language.to_string()),
+                explicit,
+                acoustid_id: Some(acoustid.0),
+                fingerprint: Some(acoustid.1),
+                ..Default::default()
+            }
+        }
+    }
+
+    fn check_round_trip(
+        format: AudioFixture,
+        track: &IdentifiedTrack,
+    ) -> Result<(), TestCaseError> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_audio_fixture(dir.path(), format);
+        let options = WriteOptions2 {
+            write_musicbrainz_ids: true,
+            ..Default::default()
+        };
+        write(&path, track, &options)
+            .map_err(|e| TestCaseError::fail(format!("{:?}: {:#}", format, e)))?;
+        let read =
+            read_full(&path).map_err(|e| TestCaseError::fail(format!("{:?}: {:#}", format, e)))?;
+
+        prop_assert_eq!(&read.title, &track.title, "{:?} title", format);
+        prop_assert_eq!(&read.artist, &track.artist, "{:?} artist", format);
+        prop_assert_eq!(
+            &read.album_artist,
+            &track.album_artist,
+            "{:?} album artist",
+            format
+        );
+        prop_assert_eq!(&read.album, &track.album, "{:?} album", format);
+        prop_assert_eq!(read.track_number, track.track_number, "{:?} track", format);
+        prop_assert_eq!(
+            read.total_tracks,
+            track.total_tracks,
+            "{:?} total tracks",
+            format
+        );
+        prop_assert_eq!(read.disc_number, track.disc_number, "{:?} disc", format);
+        prop_assert_eq!(
+            read.total_discs,
+            track.total_discs,
+            "{:?} total discs",
+            format
+        );
+        prop_assert_eq!(read.year, track.year.map(|y| y as u32), "{:?} year", format);
+        prop_assert_eq!(&read.genres, &track.genres, "{:?} genres", format);
+        prop_assert_eq!(
+            &read.musicbrainz_recording_id,
+            &track.recording_id,
+            "{:?} recording ID",
+            format
+        );
+        prop_assert_eq!(
+            &read.musicbrainz_artist_id,
+            &track.artist_id,
+            "{:?} artist ID",
+            format
+        );
+        prop_assert_eq!(
+            &read.musicbrainz_release_id,
+            &track.release_id,
+            "{:?} release ID",
+            format
+        );
+        prop_assert_eq!(
+            &read.musicbrainz_release_group_id,
+            &track.release_group_id,
+            "{:?} release group ID",
+            format
+        );
+        prop_assert_eq!(&read.language, &track.language, "{:?} language", format);
+        prop_assert_eq!(read.explicit, track.explicit, "{:?} explicit", format);
+        prop_assert_eq!(
+            &read.acoustid.id,
+            &track.acoustid_id,
+            "{:?} AcoustID ID",
+            format
+        );
+        prop_assert_eq!(
+            &read.acoustid.fingerprint,
+            &track.fingerprint,
+            "{:?} fingerprint",
+            format
+        );
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(24))]
+
+        /// Every field written is read back unchanged, in every format
+        #[test]
+        fn write_then_read_round_trips(track in identified_track()) {
+            for format in AudioFixture::ALL {
+                check_round_trip(format, &track)?;
+            }
+        }
+    }
+
+    /// A fixture with some bytes overwritten and maybe cut short
+    fn corrupted_fixture() -> impl Strategy<Value = (AudioFixture, Vec<u8>)> {
+        (
+            prop::sample::select(AudioFixture::ALL.to_vec()),
+            prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..16),
+            any::<prop::sample::Index>(),
+        )
+            .prop_map(|(format, edits, cut)| {
+                let dir = tempfile::tempdir().unwrap();
+                let mut bytes = fs::read(write_audio_fixture(dir.path(), format)).unwrap();
+                for (at, value) in edits {
+                    let at = at.index(bytes.len());
+                    bytes[at] = value;
+                }
+                bytes.truncate(cut.index(bytes.len()) + 1);
+                (format, bytes)
+            })
+    }
+
+    /// Reading must fail cleanly, never panic
+    fn read_both(extension: &str, bytes: &[u8]) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("fuzz.{}", extension));
+        fs::write(&path, bytes).unwrap();
+        let _ = read(&path);
+        let _ = read_full(&path);
+    }
+
+    proptest! {
+        /// Arbitrary bytes, named as each supported format
+        #[test]
+        fn read_survives_arbitrary_bytes(
+            bytes in prop::collection::vec(any::<u8>(), 0..4096),
+            extension in prop::sample::select(vec!["mp3", "flac", "ogg", "m4a", "wav"]),
+        ) {
+            read_both(extension, &bytes);
+        }
+
+        /// Valid files with damaged headers or missing ends
+        #[test]
+        fn read_survives_corrupted_files((format, bytes) in corrupted_fixture()) {
+            read_both(format.extension(), &bytes);
+        }
+    }
+}
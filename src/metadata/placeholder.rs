@@ -0,0 +1,310 @@
+//! Placeholder tag value detection.
+//!
+//! Rippers, players, and taggers fill missing tags with placeholder text
+//! ("Unknown Artist", "Track 01", "<no title>", "Artiste inconnu", ...).
+//! For "fill missing only" writes these values must count as empty, otherwise
+//! a file tagged "Track 01" would never receive its real title.
+//!
+//! [`PlaceholderDetector`] recognises:
+//! - Empty or whitespace-only values
+//! - Known "unknown/untitled" phrases in common locales
+//! - Numbered track stubs ("Track 01", "Piste 3", "AudioTrack 05")
+//! - Values wrapped in brackets or quotes ("<no title>", "[unknown]")
+//! - User-configured extra values (see `[tagging]` in the config file)
+
+/// Phrases treated as placeholders (lowercase, compared after normalization).
+const PLACEHOLDER_PHRASES: &[&str] = &[
+    // English
+    "unknown",
+    "unknown title",
+    "unknown artist",
+    "unknown album",
+    "unknown album artist",
+    "unknown track",
+    "unknown genre",
+    "untitled",
+    "untitled track",
+    "no title",
+    "no artist",
+    "no album",
+    "n/a",
+    // French
+    "inconnu",
+    "titre inconnu",
+    "artiste inconnu",
+    "album inconnu",
+    "sans titre",
+    // German
+    "unbekannt",
+    "unbekannter titel",
+    "unbekannter interpret",
+    "unbekannter künstler",
+    "unbekanntes album",
+    "ohne titel",
+    // Spanish
+    "desconocido",
+    "título desconocido",
+    "artista desconocido",
+    "álbum desconocido",
+    "sin título",
+    // Italian
+    "sconosciuto",
+    "titolo sconosciuto",
+    "artista sconosciuto",
+    "album sconosciuto",
+    "senza titolo",
+    // Portuguese
+    "desconhecido",
+    "título desconhecido",
+    "artista desconhecido",
+    "álbum desconhecido",
+    "sem título",
+    // Dutch
+    "onbekend",
+    "onbekende artiest",
+    "onbekend album",
+    "zonder titel",
+    // Polish
+    "nieznany",
+    "nieznany wykonawca",
+    "nieznany album",
+    "bez tytułu",
+    // Russian
+    "неизвестно",
+    "неизвестный исполнитель",
+    "неизвестный альбом",
+    "без названия",
+    // Japanese
+    "不明",
+    "不明なアーティスト",
+    "不明なアルバム",
+    // Chinese
+    "未知",
+    "未知艺术家",
+    "未知专辑",
+];
+
+/// Words that form "<word> <number>" track stubs (lowercase).
+const TRACK_STUB_WORDS: &[&str] = &[
+    "track",
+    "audio track",
+    "audiotrack",
+    "piste",    // French
+    "titel",    // German
+    "spur",     // German
+    "pista",    // Spanish
+    "traccia",  // Italian
+    "faixa",    // Portuguese
+    "nummer",   // Dutch
+    "utwór",    // Polish
+    "трек",     // Russian
+    "トラック", // Japanese
+    "曲目",     // Chinese
+];
+
+/// Detects placeholder tag values that should be treated as missing.
+#[derive(Debug, Clone, Default)]
+pub struct PlaceholderDetector {
+    /// Additional user-configured placeholder values (stored normalized)
+    extra: Vec<String>,
+}
+
+impl PlaceholderDetector {
+    /// Create a detector with the built-in placeholder rules only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add extra placeholder values on top of the built-in rules.
+    ///
+    /// Values are matched case-insensitively after trimming.
+    pub fn with_extra<I, S>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.extra.extend(
+            values
+                .into_iter()
+                .map(|v| normalize(v.as_ref()))
+                .filter(|v| !v.is_empty()),
+        );
+        self
+    }
+
+    /// Build a detector from the `[tagging]` config section.
+    pub fn from_config(config: &crate::config::TaggingConfig) -> Self {
+        Self::new().with_extra(&config.extra_placeholders)
+    }
+
+    /// Check if a tag value is a placeholder (or empty).
+    pub fn is_placeholder(&self, value: &str) -> bool {
+        let normalized = normalize(value);
+        if normalized.is_empty() {
+            return true;
+        }
+
+        if self.extra.contains(&normalized) {
+            return true;
+        }
+
+        is_known_phrase(&normalized) || is_track_stub(&normalized)
+    }
+
+    /// Check if an optional tag value is missing or a placeholder.
+    pub fn is_missing(&self, value: Option<&str>) -> bool {
+        value.is_none_or(|v| self.is_placeholder(v))
+    }
+}
+
+/// Lowercase, trim, and strip wrapping brackets/quotes.
+fn normalize(value: &str) -> String {
+    let mut s = value.trim();
+    loop {
+        let stripped = [('<', '>'), ('[', ']'), ('(', ')'), ('"', '"'), ('\'', '\'')]
+            .iter()
+            .find_map(|&(open, close)| {
+                s.strip_prefix(open)
+                    .and_then(|rest| rest.strip_suffix(close))
+            });
+        match stripped {
+            Some(inner) => s = inner.trim(),
+            None => break,
+        }
+    }
+    s.to_lowercase()
+}
+
+/// Match known phrases, allowing a trailing parenthetical suffix
+/// (e.g. Windows Media Player's "Unknown Album (1/2/2020 3:45 PM)").
+fn is_known_phrase(normalized: &str) -> bool {
+    let base = match normalized.find(" (") {
+        Some(idx) if normalized.ends_with(')') => normalized[..idx].trim_end(),
+        _ => normalized,
+    };
+    PLACEHOLDER_PHRASES.contains(&base)
+}
+
+/// Match "<word><separator><digits>" track stubs like "Track 01" or "Piste-3".
+fn is_track_stub(normalized: &str) -> bool {
+    const SEPARATORS: [char; 5] = [' ', '-', '_', '#', '.'];
+
+    TRACK_STUB_WORDS.iter().any(|word| {
+        normalized.strip_prefix(word).is_some_and(|rest| {
+            let rest = rest.trim_start_matches(SEPARATORS);
+            // Allow "Track No. 5"
+            let digits = rest
+                .strip_prefix("no")
+                .map(|r| r.trim_start_matches(SEPARATORS))
+                .unwrap_or(rest);
+            !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_and_whitespace_are_placeholders() {
+        let detector = PlaceholderDetector::new();
+        assert!(detector.is_placeholder(""));
+        assert!(detector.is_placeholder("   "));
+        assert!(detector.is_placeholder("\t\n"));
+        assert!(detector.is_missing(None));
+    }
+
+    #[test]
+    fn test_english_unknowns() {
+        let detector = PlaceholderDetector::new();
+        for value in [
+            "Unknown",
+            "Unknown Title",
+            "UNKNOWN ARTIST",
+            "unknown album",
+            "Untitled",
+            "<no title>",
+            "[Unknown]",
+            "(untitled)",
+            "  Unknown Artist  ",
+        ] {
+            assert!(detector.is_placeholder(value), "{value:?} should match");
+        }
+    }
+
+    #[test]
+    fn test_localized_unknowns() {
+        let detector = PlaceholderDetector::new();
+        for value in [
+            "Artiste inconnu",
+            "Unbekannter Künstler",
+            "Artista desconocido",
+            "Album sconosciuto",
+            "Sem título",
+            "Onbekende artiest",
+            "Неизвестный исполнитель",
+            "不明なアーティスト",
+            "未知艺术家",
+        ] {
+            assert!(detector.is_placeholder(value), "{value:?} should match");
+        }
+    }
+
+    #[test]
+    fn test_track_stubs() {
+        let detector = PlaceholderDetector::new();
+        for value in [
+            "Track 01",
+            "Track 1",
+            "Track01",
+            "track #12",
+            "Track No. 5",
+            "AudioTrack 05",
+            "Audio Track 3",
+            "Piste 7",
+            "Titel-02",
+            "Pista_4",
+        ] {
+            assert!(detector.is_placeholder(value), "{value:?} should match");
+        }
+    }
+
+    #[test]
+    fn test_wmp_unknown_album_with_date() {
+        let detector = PlaceholderDetector::new();
+        assert!(detector.is_placeholder("Unknown Album (1/2/2020 3:45:12 PM)"));
+    }
+
+    #[test]
+    fn test_real_values_are_not_placeholders() {
+        let detector = PlaceholderDetector::new();
+        for value in [
+            "Bohemian Rhapsody",
+            "Track",
+            "Track One",
+            "Tracks 1999",
+            "Unknown Pleasures",
+            "The Unknown",
+            "Untitled Unmastered.",
+            "Various Artists",
+            "1999",
+            "Nobody",
+        ] {
+            assert!(
+                !detector.is_placeholder(value),
+                "{value:?} should not match"
+            );
+        }
+        assert!(!detector.is_missing(Some("Queen")));
+    }
+
+    #[test]
+    fn test_extra_values() {
+        let detector = PlaceholderDetector::new().with_extra(["TBD", "  Sin Nombre "]);
+        assert!(detector.is_placeholder("tbd"));
+        assert!(detector.is_placeholder("SIN NOMBRE"));
+        assert!(detector.is_placeholder("<TBD>"));
+        assert!(!detector.is_placeholder("TBD Remix"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Ratings and play counts other players left in the tags.
+//!
+//! ID3v2 files carry them in POPM frames, one per player, each keyed by an
+//! email-like name ("Windows Media Player 9 Series", "MusicBee", ...) with a
+//! 1-255 rating and a play counter. Other formats use the FMPS fields
+//! (`FMPS_RATING`, 0.0-1.0, and `FMPS_PLAYCOUNT`) or a plain `RATING`.
+//!
+//! Ratings are stored as 0-100, 20 per star. With several sources the
+//! highest rating and play count win; `library.popm_email` narrows POPM to
+//! one player's frame.
+
+use std::path::Path;
+
+use lofty::file::FileType;
+use lofty::tag::{ItemKey, Tag};
+
+/// Players known to write POPM frames, as (name, frame email)
+pub const KNOWN_POPM_PLAYERS: [(&str, &str); 5] = [
+    ("Windows Media Player", "Windows Media Player 9 Series"),
+    ("MusicBee", "MusicBee"),
+    ("MediaMonkey", "no@email"),
+    ("Winamp", "rating@winamp.com"),
+    ("Quod Libet", "quodlibet@lists.sacredchao.net"),
+];
+
+/// One POPM frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Popularimeter {
+    pub email: String,
+    /// 1-255, 0 = unrated
+    pub rating: u8,
+    pub counter: u64,
+}
+
+/// Ratings and play counts found in a file's tags, before choosing
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagRatings {
+    pub popm: Vec<Popularimeter>,
+    /// `FMPS_RATING`, as 0-100
+    pub fmps_rating: Option<u8>,
+    pub fmps_play_count: Option<u32>,
+    /// `RATING` (Vorbis, APE) or `rate` (MP4), as 0-100
+    pub rating: Option<u8>,
+}
+
+/// The rating and play count to import, and where the rating came from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportedRatings {
+    /// 0-100
+    pub rating: Option<u8>,
+    pub play_count: Option<u32>,
+    /// "POPM (MusicBee)", "FMPS_RATING", "RATING"
+    pub source: Option<String>,
+}
+
+impl ImportedRatings {
+    pub fn is_empty(&self) -> bool {
+        self.rating.is_none() && self.play_count.is_none()
+    }
+}
+
+impl TagRatings {
+    /// Read the text fields from a tag. POPM frames aren't kept in a
+    /// generic [`Tag`]; see [`read_popm`].
+    pub fn from_tag(tag: &Tag) -> Self {
+        let unknown = |name: &str| {
+            tag.items().find_map(|item| match item.key() {
+                ItemKey::Unknown(key) if key.eq_ignore_ascii_case(name) => item.value().text(),
+                _ => None,
+            })
+        };
+        Self {
+            popm: Vec::new(),
+            fmps_rating: unknown("FMPS_RATING").and_then(parse_fmps_rating),
+            fmps_play_count: unknown("FMPS_PLAYCOUNT").and_then(parse_play_count),
+            rating: tag
+                .get_string(&ItemKey::Popularimeter)
+                .and_then(parse_rating),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.popm.is_empty()
+            && self.fmps_rating.is_none()
+            && self.fmps_play_count.is_none()
+            && self.rating.is_none()
+    }
+
+    /// Choose the rating and play count to import. `popm_email` limits POPM
+    /// to that player's frame; empty takes any.
+    pub fn resolve(&self, popm_email: &str) -> ImportedRatings {
+        let trusted = self
+            .popm
+            .iter()
+            .filter(|f| popm_email.is_empty() || f.email.eq_ignore_ascii_case(popm_email));
+
+        let mut rating: Option<(u8, String)> = None;
+        let mut play_count: Option<u32> = None;
+        let mut offer = |value: Option<u8>, source: &dyn Fn() -> String| {
+            if let Some(value) = value
+                && rating.as_ref().is_none_or(|(best, _)| value > *best)
+            {
+                rating = Some((value, source()));
+            }
+        };
+        for frame in trusted {
+            offer(popm_rating(frame.rating), &|| {
+                format!("POPM ({})", player_name(&frame.email))
+            });
+            if frame.counter > 0 {
+                let counter = u32::try_from(frame.counter).unwrap_or(u32::MAX);
+                play_count = play_count.max(Some(counter));
+            }
+        }
+        offer(self.fmps_rating, &|| "FMPS_RATING".to_string());
+        offer(self.rating, &|| "RATING".to_string());
+        play_count = play_count.max(self.fmps_play_count);
+
+        ImportedRatings {
+            rating: rating.as_ref().map(|(value, _)| *value),
+            play_count,
+            source: rating.map(|(_, source)| source),
+        }
+    }
+}
+
+/// The player's name for a POPM email, or the email itself
+pub fn player_name(email: &str) -> &str {
+    KNOWN_POPM_PLAYERS
+        .iter()
+        .find(|(_, known)| known.eq_ignore_ascii_case(email))
+        .map_or(email, |(name, _)| name)
+}
+
+/// 1-255 POPM rating as 0-100, bucketed the way most players read Windows
+/// Media Player's 1/64/128/196/255 stars. 0 is unrated.
+pub fn popm_rating(rating: u8) -> Option<u8> {
+    match rating {
+        0 => None,
+        1..=31 => Some(20),
+        32..=95 => Some(40),
+        96..=159 => Some(60),
+        160..=223 => Some(80),
+        224..=255 => Some(100),
+    }
+}
+
+/// The POPM frames of a file with an ID3v2 tag (MP3, WAV, AIFF). Reads the
+/// tag again, since the generic tag drops them.
+pub fn read_popm(path: &Path, file_type: FileType) -> Vec<Popularimeter> {
+    use lofty::config::ParseOptions;
+    use lofty::file::AudioFile;
+    use lofty::id3::v2::{Frame, Id3v2Tag};
+
+    fn frames(tag: Option<&Id3v2Tag>) -> Vec<Popularimeter> {
+        tag.into_iter()
+            .flatten()
+            .filter_map(|frame| match frame {
+                Frame::Popularimeter(popm) => Some(Popularimeter {
+                    email: popm.email.clone(),
+                    rating: popm.rating,
+                    counter: popm.counter,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    let Ok(mut file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    let options = ParseOptions::new().read_properties(false);
+    match file_type {
+        FileType::Mpeg => lofty::mpeg::MpegFile::read_from(&mut file, options)
+            .map(|f| frames(f.id3v2()))
+            .unwrap_or_default(),
+        FileType::Wav => lofty::iff::wav::WavFile::read_from(&mut file, options)
+            .map(|f| frames(f.id3v2()))
+            .unwrap_or_default(),
+        FileType::Aiff => lofty::iff::aiff::AiffFile::read_from(&mut file, options)
+            .map(|f| frames(f.id3v2()))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// "0.8" (0.0-1.0, half stars in steps of 0.1)
+fn parse_fmps_rating(value: &str) -> Option<u8> {
+    let rating = value.trim().parse::<f32>().ok()?;
+    (rating > 0.0 && rating <= 1.0).then(|| (rating * 100.0).round() as u8)
+}
+
+fn parse_play_count(value: &str) -> Option<u32> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n >= 1.0)
+        .map(|n| n as u32)
+}
+
+/// Stars (1-5) or a percentage (up to 100); 0 is unrated
+fn parse_rating(value: &str) -> Option<u8> {
+    let rating = value.trim().parse::<f32>().ok()?;
+    if rating <= 0.0 || !rating.is_finite() {
+        None
+    } else if rating <= 5.0 {
+        Some((rating * 20.0).round() as u8)
+    } else {
+        Some(rating.min(100.0).round() as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lofty::tag::{ItemValue, TagItem, TagType};
+
+    fn popm(email: &str, rating: u8, counter: u64) -> Popularimeter {
+        Popularimeter {
+            email: email.to_string(),
+            rating,
+            counter,
+        }
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(popm_rating(0), None);
+        assert_eq!(popm_rating(1), Some(20));
+        assert_eq!(popm_rating(196), Some(80));
+        assert_eq!(popm_rating(255), Some(100));
+        assert_eq!(parse_fmps_rating("0.7"), Some(70));
+        assert_eq!(parse_fmps_rating("1.5"), None);
+        assert_eq!(parse_rating("4"), Some(80));
+        assert_eq!(parse_rating("60"), Some(60));
+        assert_eq!(parse_rating("0"), None);
+        assert_eq!(parse_play_count("12"), Some(12));
+        assert_eq!(parse_play_count("12.0"), Some(12));
+        assert_eq!(parse_play_count("0"), None);
+    }
+
+    #[test]
+    fn test_from_tag() {
+        let mut tag = Tag::new(TagType::VorbisComments);
+        tag.insert_unchecked(TagItem::new(
+            ItemKey::Unknown("FMPS_Rating".to_string()),
+            ItemValue::Text("0.6".to_string()),
+        ));
+        tag.insert_unchecked(TagItem::new(
+            ItemKey::Unknown("FMPS_PLAYCOUNT".to_string()),
+            ItemValue::Text("31".to_string()),
+        ));
+        tag.insert_text(ItemKey::Popularimeter, "80".to_string());
+
+        let ratings = TagRatings::from_tag(&tag);
+        assert_eq!(ratings.fmps_rating, Some(60));
+        assert_eq!(ratings.fmps_play_count, Some(31));
+        assert_eq!(ratings.rating, Some(80));
+        assert!(TagRatings::from_tag(&Tag::new(TagType::VorbisComments)).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_takes_the_highest_or_the_trusted_player() {
+        let ratings = TagRatings {
+            popm: vec![
+                popm("Windows Media Player 9 Series", 64, 3),
+                popm("MusicBee", 255, 40),
+                popm("no@email", 0, 7),
+            ],
+            fmps_rating: Some(60),
+            ..Default::default()
+        };
+
+        let any = ratings.resolve("");
+        assert_eq!(any.rating, Some(100));
+        assert_eq!(any.play_count, Some(40));
+        assert_eq!(any.source.as_deref(), Some("POPM (MusicBee)"));
+
+        // Only Windows Media Player's frame: FMPS outrates it
+        let wmp = ratings.resolve("windows media player 9 series");
+        assert_eq!(wmp.rating, Some(60));
+        assert_eq!(wmp.play_count, Some(3));
+        assert_eq!(wmp.source.as_deref(), Some("FMPS_RATING"));
+
+        // MediaMonkey's frame is unrated but counts plays
+        let mm = ratings.resolve("no@email");
+        assert_eq!(mm.play_count, Some(7));
+
+        assert!(TagRatings::default().resolve("").is_empty());
+    }
+}
@@ -0,0 +1,287 @@
+//! How tags are laid out when they're saved, per format.
+//!
+//! Some car stereos and older players only read ID3v2.3, or only its
+//! Latin-1 and UTF-16 text. A [`WriteStrategy`] picks the ID3 version and
+//! text encoding, and the padding left after the tags of each format so a
+//! later edit that fits doesn't rewrite the whole file. It's set once at
+//! startup from `[tagging.write]` in the config, and again when changed in
+//! Settings; every tag save goes through [`current`].
+
+use lofty::TextEncoding;
+use lofty::config::WriteOptions;
+use lofty::file::FileType;
+use lofty::id3::v2::{Frame, Id3v2Tag};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::RwLock;
+
+/// ID3v2 version MP3, AIFF and WAV tags are saved as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Id3Version {
+    /// Read by nearly everything
+    V23,
+    #[default]
+    V24,
+}
+
+impl Id3Version {
+    pub const ALL: [Self; 2] = [Self::V23, Self::V24];
+}
+
+impl fmt::Display for Id3Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::V23 => write!(f, "ID3v2.3"),
+            Self::V24 => write!(f, "ID3v2.4"),
+        }
+    }
+}
+
+/// Encoding of ID3 text frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Id3Encoding {
+    /// ISO-8859-1 where the text fits in it, UTF-16 where it doesn't
+    Latin1,
+    Utf16,
+    /// ID3v2.4 only; ID3v2.3 tags get UTF-16
+    #[default]
+    Utf8,
+}
+
+impl Id3Encoding {
+    pub const ALL: [Self; 3] = [Self::Latin1, Self::Utf16, Self::Utf8];
+}
+
+impl fmt::Display for Id3Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Latin1 => write!(f, "Latin-1"),
+            Self::Utf16 => write!(f, "UTF-16"),
+            Self::Utf8 => write!(f, "UTF-8"),
+        }
+    }
+}
+
+/// A named set of ID3 version and encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    /// ID3v2.3 with Latin-1 text, for car stereos and old players
+    MaxCompatibility,
+    /// ID3v2.4 with UTF-8 text
+    Modern,
+}
+
+impl Preset {
+    pub const ALL: [Self; 2] = [Self::MaxCompatibility, Self::Modern];
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MaxCompatibility => write!(f, "Max compatibility"),
+            Self::Modern => write!(f, "Modern"),
+        }
+    }
+}
+
+/// Bytes of padding lofty leaves by default
+pub const DEFAULT_PADDING: u32 = WriteOptions::DEFAULT_PREFERRED_PADDING;
+
+/// Padding sizes offered in Settings, in bytes
+pub const PADDING_CHOICES: [u32; 5] = [0, 1024, 4096, 16384, 65536];
+
+/// How tags are saved (`[tagging.write]`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WriteStrategy {
+    /// ID3v2 version of MP3, AIFF and WAV tags
+    pub id3_version: Id3Version,
+    /// Encoding of ID3 text frames
+    pub id3_encoding: Id3Encoding,
+    /// Bytes of padding after ID3v2 tags
+    pub id3_padding: u32,
+    /// Bytes of padding after FLAC metadata
+    pub flac_padding: u32,
+    /// Bytes of padding after MP4 (M4A) metadata
+    pub mp4_padding: u32,
+}
+
+impl Default for WriteStrategy {
+    fn default() -> Self {
+        Self {
+            id3_version: Id3Version::default(),
+            id3_encoding: Id3Encoding::default(),
+            id3_padding: DEFAULT_PADDING,
+            flac_padding: DEFAULT_PADDING,
+            mp4_padding: DEFAULT_PADDING,
+        }
+    }
+}
+
+impl WriteStrategy {
+    /// Switch to the version and encoding of `preset`, keeping the padding
+    pub fn with_preset(self, preset: Preset) -> Self {
+        let (id3_version, id3_encoding) = match preset {
+            Preset::MaxCompatibility => (Id3Version::V23, Id3Encoding::Latin1),
+            Preset::Modern => (Id3Version::V24, Id3Encoding::Utf8),
+        };
+        Self {
+            id3_version,
+            id3_encoding,
+            ..self
+        }
+    }
+
+    /// The preset the version and encoding match, if any
+    pub fn preset(&self) -> Option<Preset> {
+        Preset::ALL
+            .into_iter()
+            .find(|&preset| self.with_preset(preset) == *self)
+    }
+
+    /// What lofty is told when saving a file of `file_type`
+    pub fn options(&self, file_type: FileType) -> WriteOptions {
+        let padding = match file_type {
+            FileType::Mpeg | FileType::Aac | FileType::Aiff | FileType::Wav => self.id3_padding,
+            FileType::Flac => self.flac_padding,
+            FileType::Mp4 => self.mp4_padding,
+            _ => DEFAULT_PADDING,
+        };
+        WriteOptions::new()
+            .preferred_padding(padding)
+            .use_id3v23(self.id3_version == Id3Version::V23)
+    }
+
+    /// Re-encode the text frames of `tag` as [`Self::id3_encoding`], where
+    /// the version allows it
+    pub fn encode(&self, tag: &mut Id3v2Tag) {
+        let wanted = match (self.id3_encoding, self.id3_version) {
+            (Id3Encoding::Latin1, _) => TextEncoding::Latin1,
+            (Id3Encoding::Utf16, _) | (Id3Encoding::Utf8, Id3Version::V23) => TextEncoding::UTF16,
+            (Id3Encoding::Utf8, Id3Version::V24) => TextEncoding::UTF8,
+        };
+        let encoding_for = |texts: &[&str]| {
+            let fits = texts
+                .iter()
+                .all(|text| text.chars().all(|c| u32::from(c) <= 0xFF));
+            if wanted == TextEncoding::Latin1 && !fits {
+                TextEncoding::UTF16
+            } else {
+                wanted
+            }
+        };
+
+        // Frames can't be changed in place: take them out and put them back
+        let flags = *tag.flags();
+        let frames: Vec<Frame<'static>> = std::mem::take(tag).into_iter().collect();
+        tag.set_flags(flags);
+        for mut frame in frames {
+            match &mut frame {
+                Frame::Text(f) => f.encoding = encoding_for(&[&f.value]),
+                Frame::UserText(f) => f.encoding = encoding_for(&[&f.description, &f.content]),
+                Frame::Comment(f) => f.encoding = encoding_for(&[&f.description, &f.content]),
+                Frame::UnsynchronizedText(f) => {
+                    f.encoding = encoding_for(&[&f.description, &f.content])
+                }
+                _ => {}
+            }
+            tag.insert(frame);
+        }
+    }
+}
+
+/// The strategy in use, set with [`set`]
+static STRATEGY: RwLock<Option<WriteStrategy>> = RwLock::new(None);
+
+/// Save tags the way `strategy` says from now on
+pub fn set(strategy: WriteStrategy) {
+    *STRATEGY.write().unwrap_or_else(|e| e.into_inner()) = Some(strategy);
+}
+
+/// The strategy in use: the one set, or the defaults
+pub fn current() -> WriteStrategy {
+    STRATEGY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lofty::tag::{Accessor, Tag, TagType};
+
+    fn text_encodings(tag: &Id3v2Tag) -> Vec<TextEncoding> {
+        tag.into_iter()
+            .filter_map(|frame| match frame {
+                Frame::Text(f) => Some(f.encoding),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_presets() {
+        let strategy = WriteStrategy {
+            id3_padding: 0,
+            ..Default::default()
+        };
+        assert_eq!(strategy.preset(), Some(Preset::Modern));
+
+        let compatible = strategy.with_preset(Preset::MaxCompatibility);
+        assert_eq!(compatible.id3_version, Id3Version::V23);
+        assert_eq!(compatible.id3_padding, 0);
+        assert_eq!(compatible.preset(), Some(Preset::MaxCompatibility));
+
+        let custom = WriteStrategy {
+            id3_encoding: Id3Encoding::Utf16,
+            ..strategy
+        };
+        assert_eq!(custom.preset(), None);
+    }
+
+    #[test]
+    fn test_encode_falls_back_from_latin1() {
+        let mut generic = Tag::new(TagType::Id3v2);
+        generic.set_title("Café".to_string());
+        generic.set_artist("坂本龍一".to_string());
+        let mut tag = Id3v2Tag::from(generic);
+
+        let strategy = WriteStrategy::default().with_preset(Preset::MaxCompatibility);
+        strategy.encode(&mut tag);
+        let mut encodings = text_encodings(&tag);
+        encodings.sort_by_key(|e| *e as u8);
+        assert_eq!(encodings, [TextEncoding::Latin1, TextEncoding::UTF16]);
+        assert_eq!(tag.title().as_deref(), Some("Café"));
+
+        // UTF-8 isn't allowed in ID3v2.3
+        let strategy = WriteStrategy {
+            id3_version: Id3Version::V23,
+            ..Default::default()
+        };
+        strategy.encode(&mut tag);
+        assert!(
+            text_encodings(&tag)
+                .iter()
+                .all(|&e| e == TextEncoding::UTF16)
+        );
+    }
+
+    #[test]
+    fn test_config_section() {
+        let strategy: WriteStrategy = toml::from_str(
+            r#"
+id3_version = "v23"
+id3_encoding = "utf16"
+flac_padding = 0
+"#,
+        )
+        .unwrap();
+        assert_eq!(strategy.id3_version, Id3Version::V23);
+        assert_eq!(strategy.id3_encoding, Id3Encoding::Utf16);
+        assert_eq!(strategy.flac_padding, 0);
+        assert_eq!(strategy.mp4_padding, DEFAULT_PADDING);
+    }
+}
@@ -0,0 +1,834 @@
+//! Decoder-level details of an audio file, for the track detail's Technical tab.
+//!
+//! Tags say what a file is; this says how it was made. [`probe`] reads:
+//! - the codec profile: MPEG version, layer and channel mode, the AAC object
+//!   type, or the FLAC bit depth and block size
+//! - the encoder, from the LAME or Fraunhofer header of an MP3, the vendor
+//!   string of a FLAC or Ogg file, or else the encoder tag
+//! - the LAME settings (`-V2`, `CBR 320 kbps`, lowpass), which LAME records
+//!   in every file it writes
+//! - the true peak and the decoded length, from decoding the whole file
+//!   (see [`crate::player::peak`]); MP3s without a VBR header often claim
+//!   the wrong length
+//! - the size of every tag block in the file and of the pictures in them
+//!
+//! Probing decodes the file, so results are cached in the database until the
+//! file changes ([`crate::db::get_technical_info`]).
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use lofty::config::ParseOptions;
+use lofty::file::{AudioFile, FileType, TaggedFileExt};
+use lofty::mp4::{AudioObjectType, Mp4Codec, Mp4File};
+use lofty::probe::Probe;
+use lofty::tag::ItemKey;
+use serde::{Deserialize, Serialize};
+
+/// Container and decoded lengths further apart than this are flagged
+pub const DURATION_TOLERANCE: Duration = Duration::from_secs(1);
+
+/// How far into an MP3 (after its ID3v2 tag) to look for the first frame
+const MPEG_SEARCH_BYTES: u64 = 64 * 1024;
+
+/// Technical details of one file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TechnicalInfo {
+    /// Codec name ("MP3", "AAC", "FLAC", ...)
+    pub codec: String,
+    /// Codec profile, e.g. "MPEG-1 Layer III, joint stereo" or "AAC-LC"
+    pub profile: Option<String>,
+    /// Encoder that wrote the audio, e.g. "LAME 3.100"
+    pub encoder: Option<String>,
+    /// Encoder settings or preset, e.g. "VBR -V0, lowpass 19.5 kHz"
+    pub encoder_settings: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub bit_depth: Option<u8>,
+    pub channels: Option<u8>,
+    pub bitrate_kbps: Option<u32>,
+    /// True peak in dBTP; `None` for silence or if decoding failed
+    pub true_peak_db: Option<f64>,
+    /// Length the container states
+    pub container_duration_ms: u64,
+    /// Length of the decoded audio; `None` if decoding failed
+    pub stream_duration_ms: Option<u64>,
+    /// Why the file couldn't be decoded
+    pub decode_error: Option<String>,
+    /// Tag blocks in file order
+    pub tags: Vec<TagBlock>,
+    /// Embedded pictures, across all tags
+    pub pictures: usize,
+    /// Total size of the embedded picture data
+    pub picture_bytes: u64,
+}
+
+/// One tag block and its size on disk, header included
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagBlock {
+    /// "ID3v2.4", "APEv2", "Vorbis comment", "Padding", ...
+    pub kind: String,
+    pub bytes: u64,
+}
+
+impl TechnicalInfo {
+    /// Decoded minus stated length in milliseconds, when they differ by more
+    /// than [`DURATION_TOLERANCE`]
+    pub fn duration_mismatch_ms(&self) -> Option<i64> {
+        let stream = self.stream_duration_ms? as i64;
+        let diff = stream - self.container_duration_ms as i64;
+        (diff.unsigned_abs() > DURATION_TOLERANCE.as_millis() as u64).then_some(diff)
+    }
+
+    /// Bytes taken by metadata rather than audio (padding included)
+    pub fn tag_bytes(&self) -> u64 {
+        self.tags.iter().map(|t| t.bytes).sum()
+    }
+
+    fn add_tag(&mut self, kind: &str, bytes: u64) {
+        match self.tags.iter_mut().find(|t| t.kind == kind) {
+            Some(tag) => tag.bytes += bytes,
+            None => self.tags.push(TagBlock {
+                kind: kind.to_string(),
+                bytes,
+            }),
+        }
+    }
+}
+
+/// Probe a file: read its headers and tag layout, then decode it whole.
+///
+/// Fails only if the file can't be read at all; a file that won't decode
+/// still gets its header details, with [`TechnicalInfo::decode_error`] set.
+pub fn probe(path: &Path) -> Result<TechnicalInfo> {
+    let tagged = Probe::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?
+        .read()
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let properties = tagged.properties();
+    let mut info = TechnicalInfo {
+        codec: codec_name(tagged.file_type()).to_string(),
+        sample_rate: properties.sample_rate(),
+        bit_depth: properties.bit_depth(),
+        channels: properties.channels(),
+        bitrate_kbps: properties.audio_bitrate(),
+        container_duration_ms: properties.duration().as_millis() as u64,
+        ..Default::default()
+    };
+    for picture in tagged.tags().iter().flat_map(|tag| tag.pictures()) {
+        info.pictures += 1;
+        info.picture_bytes += picture.data().len() as u64;
+    }
+
+    let mut file = File::open(path)?;
+    read_layout(&mut file, tagged.file_type(), &mut info)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    // Encoders that leave no header of their own usually tag themselves
+    let tag_text = |key: &ItemKey| {
+        tagged
+            .tags()
+            .iter()
+            .find_map(|tag| tag.get_string(key).map(str::to_string))
+    };
+    if info.encoder.is_none() {
+        info.encoder = tag_text(&ItemKey::EncoderSoftware);
+    }
+    if info.encoder_settings.is_none() {
+        info.encoder_settings = tag_text(&ItemKey::EncoderSettings);
+    }
+
+    match crate::player::peak::analyze(path) {
+        Ok(measure) => {
+            info.true_peak_db = measure.true_peak_db();
+            info.stream_duration_ms = Some(measure.duration.as_millis() as u64);
+        }
+        Err(e) => info.decode_error = Some(e.to_string()),
+    }
+    Ok(info)
+}
+
+/// Just the tag blocks of a file and their sizes, without decoding it
+pub fn tag_blocks(path: &Path) -> Result<Vec<TagBlock>> {
+    let file_type = Probe::open(path)?
+        .guess_file_type()?
+        .file_type()
+        .with_context(|| format!("Unknown format: {}", path.display()))?;
+    let mut info = TechnicalInfo::default();
+    read_layout(&mut File::open(path)?, file_type, &mut info)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(info.tags)
+}
+
+fn codec_name(file_type: FileType) -> &'static str {
+    match file_type {
+        FileType::Mpeg => "MP3",
+        FileType::Flac => "FLAC",
+        FileType::Vorbis => "Vorbis",
+        FileType::Opus => "Opus",
+        FileType::Speex => "Speex",
+        FileType::Mp4 => "AAC",
+        FileType::Aac => "AAC (ADTS)",
+        FileType::Wav => "PCM (WAV)",
+        FileType::Aiff => "PCM (AIFF)",
+        FileType::Ape => "Monkey's Audio",
+        FileType::WavPack => "WavPack",
+        FileType::Mpc => "Musepack",
+        _ => "Unknown",
+    }
+}
+
+/// Read the format-specific headers and find every tag block
+fn read_layout<R: Read + Seek>(
+    r: &mut R,
+    file_type: FileType,
+    info: &mut TechnicalInfo,
+) -> Result<()> {
+    let len = r.seek(SeekFrom::End(0))?;
+    let audio_start = read_id3v2(r, info)?;
+
+    match file_type {
+        FileType::Mpeg => {
+            let head = read_at(r, audio_start, MPEG_SEARCH_BYTES)?;
+            read_mpeg(&head, info);
+        }
+        FileType::Flac => read_flac(r, audio_start, info)?,
+        FileType::Vorbis | FileType::Opus | FileType::Speex => read_ogg(r, info)?,
+        FileType::Mp4 => read_mp4(r, len, info)?,
+        FileType::Wav => read_chunks(r, 12, len, false, info)?,
+        FileType::Aiff => read_chunks(r, 12, len, true, info)?,
+        _ => {}
+    }
+    read_trailing_tags(r, len, info)
+}
+
+fn read_at<R: Read + Seek>(r: &mut R, offset: u64, limit: u64) -> std::io::Result<Vec<u8>> {
+    r.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::new();
+    r.take(limit).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// An ID3v2 tag at the start of the file; returns where the audio starts
+fn read_id3v2<R: Read + Seek>(r: &mut R, info: &mut TechnicalInfo) -> Result<u64> {
+    let header = read_at(r, 0, 10)?;
+    if header.len() < 10 || &header[..3] != b"ID3" {
+        return Ok(0);
+    }
+    let size = header[6..10]
+        .iter()
+        .fold(0u64, |acc, b| (acc << 7) | u64::from(b & 0x7F));
+    let footer = if header[5] & 0x10 != 0 { 10 } else { 0 };
+    let total = 10 + size + footer;
+    info.add_tag(&format!("ID3v2.{}", header[3]), total);
+    Ok(total)
+}
+
+/// ID3v1 and APEv2 tags at the end of the file
+fn read_trailing_tags<R: Read + Seek>(r: &mut R, len: u64, info: &mut TechnicalInfo) -> Result<()> {
+    let id3v1 = len >= 128 && read_at(r, len - 128, 3)? == b"TAG";
+    let end = if id3v1 { len - 128 } else { len };
+    if end >= 32 {
+        let footer = read_at(r, end - 32, 32)?;
+        if footer.starts_with(b"APETAGEX") {
+            let size = u64::from(le32(&footer[12..16]));
+            let has_header = le32(&footer[20..24]) & 0x8000_0000 != 0;
+            info.add_tag("APEv2", size + if has_header { 32 } else { 0 });
+        }
+    }
+    if id3v1 {
+        info.add_tag("ID3v1", 128);
+    }
+    Ok(())
+}
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn be32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+// ============================================================================
+// MP3
+// ============================================================================
+
+/// The first MPEG frame header in `data`, its Xing/Info and LAME tags or
+/// its Fraunhofer VBRI header
+fn read_mpeg(data: &[u8], info: &mut TechnicalInfo) {
+    let Some((start, header)) = (0..data.len().saturating_sub(4))
+        .find_map(|i| FrameHeader::parse(&data[i..]).map(|h| (i, h)))
+    else {
+        return;
+    };
+    let frame = &data[start..];
+    info.codec = ["MP1", "MP2", "MP3"][usize::from(header.layer - 1)].to_string();
+    info.profile = Some(header.describe());
+
+    let side_info = match (header.version == MpegVersion::V1, header.mono()) {
+        (true, false) => 32,
+        (true, true) => 17,
+        (false, false) => 17,
+        (false, true) => 9,
+    };
+    let Some(tag) = frame.get(4 + side_info..) else {
+        return;
+    };
+    if tag.starts_with(b"Xing") || tag.starts_with(b"Info") {
+        read_xing(tag, info);
+    } else if frame.get(36..40) == Some(&b"VBRI"[..]) {
+        info.encoder = Some("Fraunhofer (VBRI header)".to_string());
+        info.encoder_settings = Some("VBR".to_string());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MpegVersion {
+    V1,
+    V2,
+    V25,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FrameHeader {
+    version: MpegVersion,
+    /// 1, 2 or 3
+    layer: u8,
+    /// Stereo, joint stereo, dual channel, mono
+    channel_mode: u8,
+}
+
+impl FrameHeader {
+    fn parse(b: &[u8]) -> Option<Self> {
+        if b.len() < 4 || b[0] != 0xFF || b[1] & 0xE0 != 0xE0 {
+            return None;
+        }
+        let version = match (b[1] >> 3) & 0x03 {
+            0 => MpegVersion::V25,
+            2 => MpegVersion::V2,
+            3 => MpegVersion::V1,
+            _ => return None,
+        };
+        let layer = match (b[1] >> 1) & 0x03 {
+            1 => 3,
+            2 => 2,
+            3 => 1,
+            _ => return None,
+        };
+        let bitrate_index = b[2] >> 4;
+        if bitrate_index == 0x0F || (b[2] >> 2) & 0x03 == 0x03 {
+            return None;
+        }
+        Some(Self {
+            version,
+            layer,
+            channel_mode: b[3] >> 6,
+        })
+    }
+
+    fn mono(&self) -> bool {
+        self.channel_mode == 3
+    }
+
+    fn describe(&self) -> String {
+        let version = match self.version {
+            MpegVersion::V1 => "MPEG-1",
+            MpegVersion::V2 => "MPEG-2",
+            MpegVersion::V25 => "MPEG-2.5",
+        };
+        let layer = ["I", "II", "III"][usize::from(self.layer - 1)];
+        let mode =
+            ["stereo", "joint stereo", "dual channel", "mono"][usize::from(self.channel_mode)];
+        format!("{version} Layer {layer}, {mode}")
+    }
+}
+
+/// A Xing ("VBR") or Info ("CBR") header and the LAME tag that may follow
+fn read_xing(tag: &[u8], info: &mut TechnicalInfo) {
+    let vbr = tag.starts_with(b"Xing");
+    let Some(flags) = tag.get(4..8).map(be32) else {
+        return;
+    };
+    let mut offset = 8;
+    for (flag, size) in [(0x1, 4), (0x2, 4), (0x4, 100), (0x8, 4)] {
+        if flags & flag != 0 {
+            offset += size;
+        }
+    }
+    info.encoder_settings = Some(if vbr { "VBR" } else { "CBR" }.to_string());
+
+    let Some(lame) = tag.get(offset..offset + 36) else {
+        return;
+    };
+    let version: String = lame[..9]
+        .iter()
+        .take_while(|b| b.is_ascii_graphic() || **b == b' ')
+        .map(|&b| char::from(b))
+        .collect();
+    let version = version.trim();
+    if version.len() < 4
+        || !version.as_bytes()[..4]
+            .iter()
+            .all(u8::is_ascii_alphanumeric)
+    {
+        return;
+    }
+    info.encoder = Some(encoder_name(version));
+    info.encoder_settings = Some(lame_settings(lame, vbr));
+}
+
+/// "LAME3.100" as "LAME 3.100"; FFmpeg's "Lavc58.54" as "FFmpeg (Lavc58.54)"
+fn encoder_name(version: &str) -> String {
+    if let Some(rest) = version.strip_prefix("LAME") {
+        format!("LAME {rest}")
+    } else if version.starts_with("Lavc") || version.starts_with("Lavf") {
+        format!("FFmpeg ({version})")
+    } else {
+        version.to_string()
+    }
+}
+
+/// The settings recorded in a 36-byte LAME tag
+fn lame_settings(lame: &[u8], vbr_header: bool) -> String {
+    let method = lame[9] & 0x0F;
+    let lowpass_hz = u32::from(lame[10]) * 100;
+    let bitrate = match lame[20] {
+        255 => "255+".to_string(),
+        b => b.to_string(),
+    };
+    let preset = u16::from_be_bytes([lame[26], lame[27]]) & 0x07FF;
+
+    let mut settings = match method {
+        1 | 8 if lame[20] > 0 => format!("CBR {bitrate} kbps"),
+        2 | 9 if lame[20] > 0 => format!("ABR {bitrate} kbps"),
+        1 | 8 => "CBR".to_string(),
+        2 | 9 => "ABR".to_string(),
+        3..=6 => match lame_preset(preset) {
+            Some(preset) => format!("VBR {preset}"),
+            None => "VBR".to_string(),
+        },
+        _ if vbr_header => "VBR".to_string(),
+        _ => "CBR".to_string(),
+    };
+    if lowpass_hz > 0 {
+        settings.push_str(&format!(
+            ", lowpass {:.1} kHz",
+            f64::from(lowpass_hz) / 1000.0
+        ));
+    }
+    settings
+}
+
+/// LAME's preset numbers: 410-500 are -V9 to -V0, 1000 and up the old
+/// named presets
+fn lame_preset(preset: u16) -> Option<String> {
+    let named = match preset {
+        410..=500 if preset.is_multiple_of(10) => {
+            return Some(format!("-V{}", (500 - preset) / 10));
+        }
+        1000 => "--preset r3mix",
+        1001 => "--preset standard",
+        1002 => "--preset extreme",
+        1003 => "--preset insane",
+        1004 => "--preset fast standard",
+        1005 => "--preset fast extreme",
+        1006 => "--preset medium",
+        1007 => "--preset fast medium",
+        _ => return None,
+    };
+    Some(named.to_string())
+}
+
+// ============================================================================
+// FLAC
+// ============================================================================
+
+/// Walk the FLAC metadata blocks
+fn read_flac<R: Read + Seek>(r: &mut R, start: u64, info: &mut TechnicalInfo) -> Result<()> {
+    if read_at(r, start, 4)? != b"fLaC" {
+        return Ok(());
+    }
+    let mut offset = start + 4;
+    loop {
+        let header = read_at(r, offset, 4)?;
+        if header.len() < 4 {
+            break;
+        }
+        let last = header[0] & 0x80 != 0;
+        let size = u64::from(be32(&[0, header[1], header[2], header[3]]));
+        let total = 4 + size;
+        match header[0] & 0x7F {
+            0 => {
+                let block = read_at(r, offset + 4, 34)?;
+                if block.len() == 34 {
+                    let min_block = u16::from_be_bytes([block[0], block[1]]);
+                    let max_block = u16::from_be_bytes([block[2], block[3]]);
+                    let bits = (((block[12] & 0x01) << 4) | (block[13] >> 4)) + 1;
+                    let blocks = if min_block == max_block {
+                        format!("{max_block}-sample blocks")
+                    } else {
+                        format!("{min_block}-{max_block}-sample blocks")
+                    };
+                    info.profile = Some(format!("{bits}-bit, {blocks}"));
+                }
+            }
+            1 => info.add_tag("Padding", total),
+            4 => {
+                info.add_tag("Vorbis comment", total);
+                let block = read_at(r, offset + 4, size.min(1024))?;
+                info.encoder = vorbis_vendor(&block);
+            }
+            6 => info.add_tag("Pictures", total),
+            3 => info.add_tag("Seek table", total),
+            _ => {}
+        }
+        offset += total;
+        if last {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// The vendor string a Vorbis comment block starts with
+fn vorbis_vendor(block: &[u8]) -> Option<String> {
+    let len = block.get(..4).map(le32)? as usize;
+    let vendor = String::from_utf8_lossy(block.get(4..4 + len)?)
+        .trim()
+        .to_string();
+    (!vendor.is_empty()).then_some(vendor)
+}
+
+// ============================================================================
+// Ogg
+// ============================================================================
+
+/// The comment header, the stream's second packet: its size and vendor.
+///
+/// Only the page headers are read, so a comment header spanning hundreds of
+/// pages (embedded art) costs no more than a small one.
+fn read_ogg<R: Read + Seek>(r: &mut R, info: &mut TechnicalInfo) -> Result<()> {
+    let mut offset = 0;
+    let mut packet = 0;
+    let mut comment_bytes = 0u64;
+    while packet < 2 {
+        let header = read_at(r, offset, 27)?;
+        if header.len() < 27 || &header[..4] != b"OggS" {
+            break;
+        }
+        let lacing = read_at(r, offset + 27, u64::from(header[26]))?;
+        let payload_start = offset + 27 + lacing.len() as u64;
+        let mut position = 0;
+        for &segment in &lacing {
+            if packet == 1 {
+                if comment_bytes == 0 {
+                    let start = read_at(r, payload_start + position, 1024)?;
+                    // Speex comments have no packet signature
+                    let skip = if start.starts_with(b"OpusTags") {
+                        8
+                    } else if start.starts_with(b"\x03vorbis") {
+                        7
+                    } else {
+                        0
+                    };
+                    info.encoder = start.get(skip..).and_then(vorbis_vendor);
+                }
+                comment_bytes += u64::from(segment);
+            }
+            position += u64::from(segment);
+            if segment < 255 {
+                packet += 1;
+                if packet == 2 {
+                    break;
+                }
+            }
+        }
+        offset = payload_start + lacing.iter().map(|&s| u64::from(s)).sum::<u64>();
+    }
+    if comment_bytes > 0 {
+        let kind = if info.codec == "Opus" {
+            "Opus tags"
+        } else {
+            "Vorbis comment"
+        };
+        info.add_tag(kind, comment_bytes);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// MP4
+// ============================================================================
+
+fn read_mp4<R: Read + Seek>(r: &mut R, len: u64, info: &mut TechnicalInfo) -> Result<()> {
+    r.seek(SeekFrom::Start(0))?;
+    let mp4 = Mp4File::read_from(r, ParseOptions::new().read_tags(false))?;
+    let properties = mp4.properties();
+    let (codec, lossless) = match properties.codec() {
+        Mp4Codec::ALAC => ("ALAC", true),
+        Mp4Codec::FLAC => ("FLAC", true),
+        Mp4Codec::MP3 => ("MP3", false),
+        _ => ("AAC", false),
+    };
+    info.codec = codec.to_string();
+    if !lossless {
+        info.profile = properties.audio_object_type().map(aac_profile);
+    }
+
+    if let Some(moov) = find_atom(r, 0, len, b"moov")?
+        && let Some(udta) = find_atom(r, moov.0, moov.1, b"udta")?
+        && let Some(meta) = find_atom(r, udta.0, udta.1, b"meta")?
+        // meta is a full atom: version and flags before its children
+        && let Some((start, end)) = find_atom(r, meta.0 + 4, meta.1, b"ilst")?
+    {
+        info.add_tag("iTunes metadata (ilst)", end - start + 8);
+    }
+    Ok(())
+}
+
+fn aac_profile(object_type: AudioObjectType) -> String {
+    match object_type {
+        AudioObjectType::AacMain => "AAC Main".to_string(),
+        AudioObjectType::AacLowComplexity => "AAC-LC".to_string(),
+        AudioObjectType::AacScalableSampleRate => "AAC SSR".to_string(),
+        AudioObjectType::AacLongTermPrediction => "AAC LTP".to_string(),
+        AudioObjectType::SpectralBandReplication => "HE-AAC (SBR)".to_string(),
+        AudioObjectType::ParametricStereo => "HE-AAC v2 (SBR + PS)".to_string(),
+        AudioObjectType::ErrorResilientAacLowDelay => "AAC-LD".to_string(),
+        AudioObjectType::ErrorResilientAacEnhancedLowDelay => "AAC-ELD".to_string(),
+        other => format!("{other:?}"),
+    }
+}
+
+/// The body (start and end offsets) of the first `name` atom between
+/// `start` and `end`. Sizes come from the file, so a walk that would
+/// overflow, stand still or run past `end` stops there.
+fn find_atom<R: Read + Seek>(
+    r: &mut R,
+    start: u64,
+    end: u64,
+    name: &[u8; 4],
+) -> Result<Option<(u64, u64)>> {
+    let mut offset = start;
+    while offset
+        .checked_add(8)
+        .is_some_and(|header_end| header_end <= end)
+    {
+        let header = read_at(r, offset, 16)?;
+        if header.len() < 8 {
+            break;
+        }
+        let (size, header_len) = match u64::from(be32(&header[..4])) {
+            0 => (end - offset, 8),
+            1 if header.len() == 16 => (u64::from_be_bytes(header[8..16].try_into()?), 16),
+            size => (size, 8),
+        };
+        if size < header_len {
+            break;
+        }
+        let Some(next) = offset.checked_add(size).filter(|&next| next > offset) else {
+            break;
+        };
+        if &header[4..8] == name {
+            return Ok(Some((offset + header_len, next.min(end))));
+        }
+        if next >= end {
+            break;
+        }
+        offset = next;
+    }
+    Ok(None)
+}
+
+// ============================================================================
+// WAV and AIFF
+// ============================================================================
+
+/// Walk RIFF (little-endian) or IFF (big-endian) chunks for tags
+fn read_chunks<R: Read + Seek>(
+    r: &mut R,
+    start: u64,
+    len: u64,
+    big_endian: bool,
+    info: &mut TechnicalInfo,
+) -> Result<()> {
+    let mut offset = start;
+    while offset + 8 <= len {
+        let header = read_at(r, offset, 8)?;
+        if header.len() < 8 {
+            break;
+        }
+        let size = u64::from(if big_endian {
+            be32(&header[4..])
+        } else {
+            le32(&header[4..])
+        });
+        match &header[..4] {
+            b"id3 " | b"ID3 " => info.add_tag("ID3v2", 8 + size),
+            b"LIST" => info.add_tag("RIFF INFO", 8 + size),
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        offset += 8 + size + (size & 1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{AudioFixture, write_audio_fixture};
+    use std::io::Cursor;
+
+    /// A 128 kbps MPEG-1 frame carrying an Info tag and a LAME tag
+    fn lame_frame(vbr: bool, method: u8, bitrate: u8, preset: u16) -> Vec<u8> {
+        let mut frame = vec![0u8; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x40]);
+        let tag = 4 + 32;
+        frame[tag..tag + 4].copy_from_slice(if vbr { b"Xing" } else { b"Info" });
+        frame[tag + 4..tag + 8].copy_from_slice(&0x0Fu32.to_be_bytes());
+        let lame = tag + 8 + 4 + 4 + 100 + 4;
+        frame[lame..lame + 9].copy_from_slice(b"LAME3.100");
+        frame[lame + 9] = 0x10 | method;
+        frame[lame + 10] = 195;
+        frame[lame + 20] = bitrate;
+        frame[lame + 26..lame + 28].copy_from_slice(&preset.to_be_bytes());
+        frame
+    }
+
+    #[test]
+    fn test_reads_lame_settings() {
+        let mut info = TechnicalInfo::default();
+        read_mpeg(&lame_frame(true, 4, 0, 500), &mut info);
+        assert_eq!(info.codec, "MP3");
+        assert_eq!(
+            info.profile.as_deref(),
+            Some("MPEG-1 Layer III, joint stereo")
+        );
+        assert_eq!(info.encoder.as_deref(), Some("LAME 3.100"));
+        assert_eq!(
+            info.encoder_settings.as_deref(),
+            Some("VBR -V0, lowpass 19.5 kHz")
+        );
+
+        let mut info = TechnicalInfo::default();
+        let mut data = vec![0u8; 100]; // junk before the first frame
+        data.extend(lame_frame(false, 1, 255, 320));
+        read_mpeg(&data, &mut info);
+        assert_eq!(
+            info.encoder_settings.as_deref(),
+            Some("CBR 255+ kbps, lowpass 19.5 kHz")
+        );
+
+        assert_eq!(lame_preset(420).as_deref(), Some("-V8"));
+        assert_eq!(lame_preset(1001).as_deref(), Some("--preset standard"));
+        assert_eq!(lame_preset(415), None);
+    }
+
+    #[test]
+    fn test_measures_tag_blocks() {
+        let mut bytes = b"ID3\x04\x00\x00\x00\x00\x01\x00".to_vec(); // 128-byte body
+        bytes.extend([0u8; 128]);
+        let audio_start = bytes.len();
+        bytes.extend(lame_frame(true, 4, 0, 480));
+        bytes.extend(b"APETAGEX");
+        bytes.extend(2000u32.to_le_bytes());
+        bytes.extend(64u32.to_le_bytes()); // size: items and footer
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend(0x8000_0000u32.to_le_bytes()); // has a header
+        bytes.extend([0u8; 8]);
+        bytes.extend(b"TAG");
+        bytes.extend([0u8; 125]);
+
+        let mut info = TechnicalInfo::default();
+        read_layout(&mut Cursor::new(&bytes), FileType::Mpeg, &mut info).unwrap();
+        let tags: Vec<_> = info
+            .tags
+            .iter()
+            .map(|t| (t.kind.as_str(), t.bytes))
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                ("ID3v2.4", audio_start as u64),
+                ("APEv2", 96),
+                ("ID3v1", 128)
+            ]
+        );
+        assert_eq!(
+            info.encoder_settings.as_deref(),
+            Some("VBR -V2, lowpass 19.5 kHz")
+        );
+    }
+
+    #[test]
+    fn test_probes_fixtures() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let flac = probe(&write_audio_fixture(dir.path(), AudioFixture::Flac)).unwrap();
+        assert_eq!(flac.codec, "FLAC");
+        assert_eq!(flac.profile.as_deref(), Some("16-bit, 4096-sample blocks"));
+        assert_eq!(
+            flac.tags,
+            [TagBlock {
+                kind: "Padding".to_string(),
+                bytes: 68
+            }]
+        );
+
+        let ogg = probe(&write_audio_fixture(dir.path(), AudioFixture::Ogg)).unwrap();
+        assert_eq!(ogg.codec, "Vorbis");
+        assert_eq!(ogg.encoder.as_deref(), Some("music-minder tests"));
+        assert_eq!(ogg.tags[0].kind, "Vorbis comment");
+
+        let m4a = probe(&write_audio_fixture(dir.path(), AudioFixture::M4a)).unwrap();
+        assert_eq!(m4a.codec, "AAC");
+    }
+
+    #[test]
+    fn test_find_atom_survives_bad_sizes() {
+        let atom = |size: u32, name: &[u8; 4], extended: Option<u64>| {
+            let mut bytes = size.to_be_bytes().to_vec();
+            bytes.extend_from_slice(name);
+            if let Some(extended) = extended {
+                bytes.extend_from_slice(&extended.to_be_bytes());
+            }
+            bytes
+        };
+        let find = |bytes: Vec<u8>| {
+            let end = bytes.len() as u64;
+            find_atom(&mut Cursor::new(bytes), 0, end, b"moov").unwrap()
+        };
+
+        // A 64-bit size that would overflow the offset
+        let mut bytes = atom(8, b"free", None);
+        bytes.extend(atom(1, b"mdat", Some(u64::MAX)));
+        bytes.extend(atom(8, b"moov", None));
+        assert_eq!(find(bytes), None);
+        // A 64-bit size smaller than its own header
+        let mut bytes = atom(1, b"free", Some(4));
+        bytes.extend(atom(8, b"moov", None));
+        assert_eq!(find(bytes), None);
+        // A size running past the end is cut off there
+        let mut bytes = atom(1, b"moov", Some(u64::MAX - 4));
+        bytes.extend([0; 8]);
+        assert_eq!(find(bytes), Some((16, 24)));
+    }
+
+    #[test]
+    fn test_duration_mismatch() {
+        let mut info = TechnicalInfo {
+            container_duration_ms: 180_000,
+            stream_duration_ms: Some(180_400),
+            ..Default::default()
+        };
+        assert_eq!(info.duration_mismatch_ms(), None);
+        info.stream_duration_ms = Some(95_000);
+        assert_eq!(info.duration_mismatch_ms(), Some(-85_000));
+        info.stream_duration_ms = None;
+        assert_eq!(info.duration_mismatch_ms(), None);
+    }
+}
@@ -1,7 +1,8 @@
 //! Core data models for the music library.
 //!
 //! Defines the primary entities: [`Track`], [`Artist`], and [`Album`].
-//! These are derived from SQLx for database mapping.
+//! These are derived from SQLx for database mapping. [`TrackSource`] tells
+//! files apart from tracks whose path is a URL.
 //!
 //! # Database Schema
 //!
@@ -84,3 +85,192 @@ impl Track {
             .unwrap_or_default()
     }
 }
+
+/// Where a track's audio comes from (the `tracks.source` column).
+///
+/// A track's path is usually a file, but can be a URL: an HTTP stream or a
+/// cloud drive share link. Those are played from the network; scans,
+/// organize, fingerprinting and tag writes only handle files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, sqlx::Type)]
+#[sqlx(rename_all = "lowercase")]
+pub enum TrackSource {
+    /// A file on a local or mounted drive
+    #[default]
+    Local,
+    /// An HTTP(S) URL played as it downloads
+    Stream,
+    /// A share link from Google Drive, Dropbox or OneDrive
+    Cloud,
+}
+
+/// Hosts whose share links are [`TrackSource::Cloud`]
+const CLOUD_HOSTS: [&str; 7] = [
+    "drive.google.com",
+    "docs.google.com",
+    "dropbox.com",
+    "dl.dropboxusercontent.com",
+    "onedrive.live.com",
+    "1drv.ms",
+    "sharepoint.com",
+];
+
+impl TrackSource {
+    /// The source of a track stored under `path`
+    pub fn detect(path: &str) -> Self {
+        match url_host(path) {
+            None => Self::Local,
+            Some(host) => {
+                let cloud = CLOUD_HOSTS.iter().any(|cloud| {
+                    host == *cloud
+                        || host
+                            .strip_suffix(cloud)
+                            .is_some_and(|sub| sub.ends_with('.'))
+                });
+                if cloud { Self::Cloud } else { Self::Stream }
+            }
+        }
+    }
+
+    /// Whether the track is a file (that can be scanned, moved and tagged)
+    pub fn is_local(self) -> bool {
+        self == Self::Local
+    }
+
+    /// The name stored in the `source` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Stream => "stream",
+            Self::Cloud => "cloud",
+        }
+    }
+
+    /// Short name for the track list's format column
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Local => "File",
+            Self::Stream => "Stream",
+            Self::Cloud => "Cloud",
+        }
+    }
+}
+
+/// The lower-cased host of an `http://` or `https://` URL, `None` for a path
+fn url_host(path: &str) -> Option<String> {
+    let path = path.trim();
+    let (scheme, rest) = path.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// The URL to download a remote track's audio from: share links that open
+/// a web page are turned into their direct download links (Dropbox's
+/// `dl=1`, Google Drive's `uc?export=download`). Other URLs are returned
+/// as they are.
+pub fn download_url(url: &str) -> String {
+    let url = url.trim();
+    match TrackSource::detect(url) {
+        TrackSource::Local | TrackSource::Stream => url.to_string(),
+        TrackSource::Cloud => {
+            let host = url_host(url).unwrap_or_default();
+            if host.ends_with("dropbox.com") {
+                dropbox_download(url)
+            } else if host == "drive.google.com" {
+                google_drive_id(url)
+                    .map(|id| format!("https://drive.google.com/uc?export=download&id={}", id))
+                    .unwrap_or_else(|| url.to_string())
+            } else {
+                url.to_string()
+            }
+        }
+    }
+}
+
+/// A Dropbox share link with `dl=1`, which serves the file itself
+fn dropbox_download(url: &str) -> String {
+    let (base, fragment) = url.split_once('#').unwrap_or((url, ""));
+    let (path, query) = base.split_once('?').unwrap_or((base, ""));
+    let mut params: Vec<&str> = query
+        .split('&')
+        .filter(|p| !p.is_empty() && !p.starts_with("dl=") && !p.starts_with("raw="))
+        .collect();
+    params.push("dl=1");
+    let mut direct = format!("{}?{}", path, params.join("&"));
+    if !fragment.is_empty() {
+        direct = format!("{}#{}", direct, fragment);
+    }
+    direct
+}
+
+/// The file ID in `drive.google.com/file/d/<id>/...` or `...?id=<id>`
+fn google_drive_id(url: &str) -> Option<&str> {
+    if let Some((_, rest)) = url.split_once("/file/d/") {
+        return rest
+            .split(['/', '?', '#'])
+            .next()
+            .filter(|id| !id.is_empty());
+    }
+    let (_, query) = url.split_once('?')?;
+    query
+        .split(['&', '#'])
+        .find_map(|p| p.strip_prefix("id="))
+        .filter(|id| !id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_source() {
+        assert_eq!(TrackSource::detect("/music/a.mp3"), TrackSource::Local);
+        assert_eq!(TrackSource::detect(r"C:\Music\a.mp3"), TrackSource::Local);
+        assert_eq!(TrackSource::detect("ftp://host/a.mp3"), TrackSource::Local);
+        assert_eq!(
+            TrackSource::detect("http://radio.example:8000/live.mp3"),
+            TrackSource::Stream
+        );
+        assert_eq!(
+            TrackSource::detect("HTTPS://www.Dropbox.com/s/abc/a.flac?dl=0"),
+            TrackSource::Cloud
+        );
+        assert_eq!(
+            TrackSource::detect("https://tenant.sharepoint.com/a.mp3"),
+            TrackSource::Cloud
+        );
+        // Only whole host labels count
+        assert_eq!(
+            TrackSource::detect("https://notdropbox.com/a.mp3"),
+            TrackSource::Stream
+        );
+    }
+
+    #[test]
+    fn test_download_url() {
+        assert_eq!(
+            download_url("https://www.dropbox.com/s/abc/a.flac?dl=0"),
+            "https://www.dropbox.com/s/abc/a.flac?dl=1"
+        );
+        assert_eq!(
+            download_url("https://www.dropbox.com/scl/fi/x/a.mp3?rlkey=k"),
+            "https://www.dropbox.com/scl/fi/x/a.mp3?rlkey=k&dl=1"
+        );
+        assert_eq!(
+            download_url("https://drive.google.com/file/d/1AbC/view?usp=sharing"),
+            "https://drive.google.com/uc?export=download&id=1AbC"
+        );
+        assert_eq!(
+            download_url("https://drive.google.com/open?id=1AbC"),
+            "https://drive.google.com/uc?export=download&id=1AbC"
+        );
+        assert_eq!(
+            download_url("http://radio.example/live"),
+            "http://radio.example/live"
+        );
+    }
+}
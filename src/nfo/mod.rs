@@ -0,0 +1,588 @@
+//! Sidecar files for media centers: `folder.jpg`, `album.nfo` and
+//! `artist.nfo`, as Kodi and Jellyfin read them.
+//!
+//! An album's folder is the one holding all its tracks (the common parent
+//! for multi-disc albums in `CD1`/`CD2` subfolders). An artist's folder is
+//! the parent of their album folders, but only when every album sits in the
+//! same one and it is named after the artist, as the default organize
+//! pattern lays it out; otherwise no `artist.nfo` is written rather than one
+//! in a folder the media center wouldn't associate with the artist.
+//!
+//! Contents come from the database, plus the MusicBrainz IDs and genre from
+//! the first track's tags. The cover is the first embedded or sidecar image
+//! among the album's tracks, falling back to the cover cache. The NFO files
+//! are rendered from templates (`[nfo]` in the config file) whose
+//! `{Placeholder}` values are XML-escaped.
+//!
+//! Existing files are left alone unless `overwrite` is set, so hand-edited
+//! NFOs survive a re-export.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::nfo::{self, Scope};
+//!
+//! let config = music_minder::config::load().nfo;
+//! let report = nfo::export(&pool, &Scope::Under("/music".into()), &config).await?;
+//! println!("{}", report.summary());
+//! ```
+
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::config::NfoConfig;
+use crate::cover::CoverResolver;
+
+/// Default `album.nfo` template; `{Tracks}` is one `track_template` per track
+pub const DEFAULT_ALBUM_TEMPLATE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<album>
+    <title>{Album}</title>
+    <artistdesc>{Artist}</artistdesc>
+    <year>{Year}</year>
+    <genre>{Genre}</genre>
+    <musicbrainzalbumid>{ReleaseId}</musicbrainzalbumid>
+    <musicbrainzreleasegroupid>{ReleaseGroupId}</musicbrainzreleasegroupid>
+    <albumArtistCredits>
+        <artist>{Artist}</artist>
+        <musicBrainzArtistID>{ArtistId}</musicBrainzArtistID>
+    </albumArtistCredits>
+{Tracks}</album>
+"#;
+
+/// Default template of one track in `album.nfo`
+pub const DEFAULT_TRACK_TEMPLATE: &str = "    <track>
+        <position>{Position}</position>
+        <title>{Title}</title>
+        <duration>{Duration}</duration>
+    </track>
+";
+
+/// Default `artist.nfo` template; `{Albums}` is one `artist_album_template`
+/// per album
+pub const DEFAULT_ARTIST_TEMPLATE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<artist>
+    <name>{Artist}</name>
+    <musicBrainzArtistID>{ArtistId}</musicBrainzArtistID>
+{Albums}</artist>
+"#;
+
+/// Default template of one album in `artist.nfo`
+pub const DEFAULT_ARTIST_ALBUM_TEMPLATE: &str = "    <album>
+        <title>{Album}</title>
+        <year>{Year}</year>
+    </album>
+";
+
+/// Export errors
+#[derive(Debug, thiserror::Error)]
+pub enum NfoError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error(transparent)]
+    ReadOnly(#[from] crate::readonly::ReadOnlyError),
+}
+
+/// Which albums to write files for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scope {
+    /// Every album in the library
+    Library,
+    /// Albums whose folder is inside this one
+    Under(PathBuf),
+    /// Albums holding any of these track paths
+    Containing(HashSet<String>),
+}
+
+/// An album as the sidecar files describe it
+#[derive(Debug, Clone, Default)]
+pub struct NfoAlbum {
+    pub album_id: i64,
+    pub title: String,
+    /// Album artist (or "Unknown Artist")
+    pub artist: String,
+    pub year: Option<i64>,
+    /// Folder holding all the album's tracks (empty when they're spread
+    /// over several; those albums are skipped)
+    pub folder: PathBuf,
+    pub tracks: Vec<NfoTrack>,
+}
+
+/// A track listed in `album.nfo`
+#[derive(Debug, Clone, Default)]
+pub struct NfoTrack {
+    pub path: String,
+    pub title: String,
+    pub track_number: Option<i64>,
+    /// Duration in seconds
+    pub duration: Option<i64>,
+}
+
+/// What an export wrote
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportReport {
+    pub albums: usize,
+    pub artists: usize,
+    pub covers: usize,
+    /// Files that already existed and were left alone
+    pub kept: usize,
+    /// Files that couldn't be written, with the reason
+    pub errors: Vec<String>,
+}
+
+impl ExportReport {
+    /// One line for the status bar or console
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Wrote {} album.nfo, {} artist.nfo, {} folder art",
+            self.albums, self.artists, self.covers
+        );
+        if self.kept > 0 {
+            summary.push_str(&format!(", kept {} existing", self.kept));
+        }
+        if !self.errors.is_empty() {
+            summary.push_str(&format!(", {} failed", self.errors.len()));
+        }
+        summary
+    }
+}
+
+/// Database row for [`library_albums`], one per track
+#[derive(Debug, sqlx::FromRow)]
+struct AlbumTrackRow {
+    album_id: i64,
+    album: String,
+    artist: String,
+    year: Option<i64>,
+    path: String,
+    title: String,
+    track_number: Option<i64>,
+    duration: Option<i64>,
+}
+
+/// Every album in the library with its tracks
+pub async fn library_albums(pool: &SqlitePool) -> sqlx::Result<Vec<NfoAlbum>> {
+    let rows: Vec<AlbumTrackRow> = sqlx::query_as(
+        r#"
+        SELECT
+            al.id AS album_id,
+            al.title AS album,
+            COALESCE(a.name, 'Unknown Artist') AS artist,
+            al.year,
+            t.path, t.title, t.track_number, t.duration
+        FROM tracks t
+        JOIN albums al ON t.album_id = al.id
+        LEFT JOIN artists a ON al.artist_id = a.id
+        ORDER BY artist, al.title, al.id, t.track_number, t.path
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut albums: Vec<NfoAlbum> = Vec::new();
+    for row in rows {
+        if albums.last().is_none_or(|a| a.album_id != row.album_id) {
+            albums.push(NfoAlbum {
+                album_id: row.album_id,
+                title: row.album,
+                artist: row.artist,
+                year: row.year,
+                ..Default::default()
+            });
+        }
+        if let Some(album) = albums.last_mut() {
+            album.tracks.push(NfoTrack {
+                path: row.path,
+                title: row.title,
+                track_number: row.track_number,
+                duration: row.duration,
+            });
+        }
+    }
+    for album in &mut albums {
+        album.folder =
+            album_folder(album.tracks.iter().map(|t| Path::new(&t.path))).unwrap_or_default();
+    }
+    Ok(albums)
+}
+
+/// Deepest folder containing every path, when each is in it or one level
+/// down (a disc subfolder); none for tracks spread around the library
+pub(crate) fn album_folder<'a>(paths: impl Iterator<Item = &'a Path>) -> Option<PathBuf> {
+    let parents: Vec<&Path> = paths.filter_map(Path::parent).collect();
+    let mut common = parents.first()?.to_path_buf();
+    for parent in &parents {
+        common = common
+            .ancestors()
+            .find(|a| parent.starts_with(a))?
+            .to_path_buf();
+    }
+    parents
+        .iter()
+        .all(|p| p == &common || p.parent() == Some(common.as_path()))
+        .then_some(common)
+}
+
+impl Scope {
+    fn includes(&self, album: &NfoAlbum) -> bool {
+        match self {
+            Scope::Library => true,
+            Scope::Under(root) => album.folder.starts_with(root),
+            Scope::Containing(paths) => album.tracks.iter().any(|t| paths.contains(&t.path)),
+        }
+    }
+}
+
+/// Write the sidecar files of the albums in `scope`, and of their artists
+pub async fn export(
+    pool: &SqlitePool,
+    scope: &Scope,
+    config: &NfoConfig,
+) -> Result<ExportReport, NfoError> {
+    crate::readonly::ensure_writable("Writing NFO files")?;
+    let albums = library_albums(pool).await?;
+    let scope = scope.clone();
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || Ok(export_albums(&albums, &scope, &config)))
+        .await
+        .unwrap_or_else(|e| {
+            Ok(ExportReport {
+                errors: vec![e.to_string()],
+                ..Default::default()
+            })
+        })
+}
+
+/// Write the files for the albums of `all` in `scope`. Blocking.
+///
+/// Takes the whole library so each `artist.nfo` lists all of the artist's
+/// albums, not just those being exported.
+pub fn export_albums(all: &[NfoAlbum], scope: &Scope, config: &NfoConfig) -> ExportReport {
+    let mut report = ExportReport::default();
+    let resolver = CoverResolver::new();
+    let mut artists = HashSet::new();
+
+    for album in all.iter().filter(|a| scope.includes(a)) {
+        if album.folder.as_os_str().is_empty() {
+            continue;
+        }
+        let tags = album
+            .tracks
+            .first()
+            .and_then(|t| crate::metadata::read_full(Path::new(&t.path)).ok())
+            .unwrap_or_default();
+
+        let nfo = render_album(album, &tags, config);
+        let written = write_file(&album.folder.join("album.nfo"), nfo.as_bytes(), config);
+        tally(&mut report, written, |r| r.albums += 1);
+
+        if config.folder_art {
+            write_cover(&mut report, album, &tags, &resolver, config);
+        }
+        artists.insert((album.artist.as_str(), tags.musicbrainz_artist_id));
+    }
+
+    for (artist, artist_id) in artists {
+        let albums: Vec<&NfoAlbum> = all.iter().filter(|a| a.artist == artist).collect();
+        let Some(folder) = artist_folder(artist, &albums) else {
+            continue;
+        };
+        let nfo = render_artist(artist, artist_id.as_deref(), &albums, config);
+        let written = write_file(&folder.join("artist.nfo"), nfo.as_bytes(), config);
+        tally(&mut report, written, |r| r.artists += 1);
+    }
+    report
+}
+
+/// The folder all of an artist's albums are in, if it is named after them
+fn artist_folder(artist: &str, albums: &[&NfoAlbum]) -> Option<PathBuf> {
+    let mut parents = albums.iter().map(|a| a.folder.parent());
+    let folder = parents.next()??;
+    if parents.any(|p| p != Some(folder)) {
+        return None;
+    }
+    let name = folder.file_name()?.to_string_lossy();
+    let expected = crate::organizer::sanitize_filename(artist);
+    name.eq_ignore_ascii_case(&expected)
+        .then(|| folder.to_path_buf())
+}
+
+/// Write the album's cover as `folder.jpg` (or `.png`)
+fn write_cover(
+    report: &mut ExportReport,
+    album: &NfoAlbum,
+    tags: &crate::metadata::FullMetadata,
+    resolver: &CoverResolver,
+    config: &NfoConfig,
+) {
+    let existing = ["folder.jpg", "folder.png"]
+        .iter()
+        .map(|name| album.folder.join(name))
+        .find(|p| p.exists());
+    if existing.is_some() && !config.overwrite {
+        report.kept += 1;
+        return;
+    }
+    let cover = album
+        .tracks
+        .iter()
+        .find_map(|t| resolver.resolve_local(Path::new(&t.path)))
+        .or_else(|| {
+            tags.musicbrainz_release_id
+                .as_deref()
+                .and_then(|id| resolver.resolve_cached(id))
+        });
+    let Some(cover) = cover else {
+        return;
+    };
+    let name = if cover.mime_type == "image/png" {
+        "folder.png"
+    } else {
+        "folder.jpg"
+    };
+    let path = album.folder.join(name);
+    // Don't leave both when the format changed
+    if let Some(old) = existing.filter(|p| *p != path)
+        && let Err(e) = std::fs::remove_file(&old)
+    {
+        report.errors.push(format!("{}: {}", old.display(), e));
+    }
+    let written = std::fs::write(&path, &cover.data)
+        .map(|_| true)
+        .map_err(|e| format!("{}: {}", path.display(), e));
+    tally(report, written, |r| r.covers += 1);
+}
+
+/// Write `contents` unless the file exists and overwriting is off; true if
+/// written
+fn write_file(path: &Path, contents: &[u8], config: &NfoConfig) -> Result<bool, String> {
+    if path.exists() && !config.overwrite {
+        return Ok(false);
+    }
+    std::fs::write(path, contents)
+        .map(|_| true)
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn tally(
+    report: &mut ExportReport,
+    written: Result<bool, String>,
+    count: impl Fn(&mut ExportReport),
+) {
+    match written {
+        Ok(true) => count(report),
+        Ok(false) => report.kept += 1,
+        Err(e) => report.errors.push(e),
+    }
+}
+
+/// Render `album.nfo`
+pub fn render_album(
+    album: &NfoAlbum,
+    tags: &crate::metadata::FullMetadata,
+    config: &NfoConfig,
+) -> String {
+    let tracks: String = album
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| {
+            let position = track.track_number.unwrap_or(i as i64 + 1).to_string();
+            let seconds = track.duration.unwrap_or(0);
+            render(
+                &config.track_template,
+                &[
+                    ("Position", &position),
+                    ("Title", &track.title),
+                    ("Duration", &format!("{}:{:02}", seconds / 60, seconds % 60)),
+                    ("Seconds", &seconds.to_string()),
+                ],
+            )
+        })
+        .collect();
+    let year = album.year.map(|y| y.to_string()).unwrap_or_default();
+    render(
+        &config.album_template,
+        &[
+            ("Album", &album.title),
+            ("Artist", &album.artist),
+            ("Year", &year),
+            ("Genre", tags.genre.as_deref().unwrap_or_default()),
+            (
+                "ReleaseId",
+                tags.musicbrainz_release_id.as_deref().unwrap_or_default(),
+            ),
+            (
+                "ReleaseGroupId",
+                tags.musicbrainz_release_group_id
+                    .as_deref()
+                    .unwrap_or_default(),
+            ),
+            (
+                "ArtistId",
+                tags.musicbrainz_artist_id.as_deref().unwrap_or_default(),
+            ),
+        ],
+    )
+    // Already-escaped, so substituted after the others
+    .replace("{Tracks}", &tracks)
+}
+
+/// Render `artist.nfo`
+pub fn render_artist(
+    artist: &str,
+    artist_id: Option<&str>,
+    albums: &[&NfoAlbum],
+    config: &NfoConfig,
+) -> String {
+    let albums: String = albums
+        .iter()
+        .map(|album| {
+            let year = album.year.map(|y| y.to_string()).unwrap_or_default();
+            render(
+                &config.artist_album_template,
+                &[("Album", &album.title), ("Year", &year)],
+            )
+        })
+        .collect();
+    render(
+        &config.artist_template,
+        &[
+            ("Artist", artist),
+            ("ArtistId", artist_id.unwrap_or_default()),
+        ],
+    )
+    .replace("{Albums}", &albums)
+}
+
+/// Substitute `{Name}` placeholders with XML-escaped values
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |out, (name, value)| {
+            out.replace(&format!("{{{}}}", name), &escape_xml(value))
+        })
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn album(artist: &str, title: &str, folder: &str) -> NfoAlbum {
+        NfoAlbum {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            year: Some(1997),
+            folder: PathBuf::from(folder),
+            tracks: vec![
+                NfoTrack {
+                    path: format!("{}/01.flac", folder),
+                    title: "Airbag".to_string(),
+                    track_number: Some(1),
+                    duration: Some(284),
+                },
+                NfoTrack {
+                    path: format!("{}/02.flac", folder),
+                    title: "Paranoid Android".to_string(),
+                    track_number: None,
+                    duration: Some(383),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_album() {
+        let mut album = album("Radiohead", "OK Computer", "/music/Radiohead/OK Computer");
+        album.artist = "Simon & Garfunkel".to_string();
+        let tags = crate::metadata::FullMetadata {
+            musicbrainz_release_id: Some("rel-1".to_string()),
+            ..Default::default()
+        };
+        let nfo = render_album(&album, &tags, &NfoConfig::default());
+
+        assert!(nfo.contains("<title>OK Computer</title>"));
+        assert!(nfo.contains("<artist>Simon &amp; Garfunkel</artist>"));
+        assert!(nfo.contains("<musicbrainzalbumid>rel-1</musicbrainzalbumid>"));
+        assert!(nfo.contains("<genre></genre>"));
+        // Numbered by position when the track number is missing
+        assert!(nfo.contains("<position>2</position>"));
+        assert!(nfo.contains("<duration>6:23</duration>"));
+        assert!(!nfo.contains('{'));
+    }
+
+    #[test]
+    fn test_album_and_artist_folder() {
+        let paths = ["/music/A/Album/CD1/01.flac", "/music/A/Album/CD2/01.flac"];
+        assert_eq!(
+            album_folder(paths.iter().map(Path::new)),
+            Some(PathBuf::from("/music/A/Album"))
+        );
+        let spread = ["/music/A/Album/01.flac", "/music/B/Other/02.flac"];
+        assert_eq!(album_folder(spread.iter().map(Path::new)), None);
+
+        let first = album("Radiohead", "OK Computer", "/music/Radiohead/OK Computer");
+        let second = album("Radiohead", "Kid A", "/music/Radiohead/Kid A");
+        assert_eq!(
+            artist_folder("Radiohead", &[&first, &second]),
+            Some(PathBuf::from("/music/Radiohead"))
+        );
+        // Albums in different folders, or a folder not named after the artist
+        let elsewhere = album("Radiohead", "Amnesiac", "/other/Radiohead/Amnesiac");
+        assert_eq!(artist_folder("Radiohead", &[&first, &elsewhere]), None);
+        let flat = album("Radiohead", "OK Computer", "/music/OK Computer");
+        assert_eq!(artist_folder("Radiohead", &[&flat]), None);
+    }
+
+    #[test]
+    fn test_export_keeps_existing() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("Radiohead").join("OK Computer");
+        std::fs::create_dir_all(&folder).unwrap();
+        let albums = vec![album("Radiohead", "OK Computer", &folder.to_string_lossy())];
+        let config = NfoConfig::default();
+
+        let report = export_albums(&albums, &Scope::Library, &config);
+        assert_eq!((report.albums, report.artists), (1, 1));
+        assert!(dir.path().join("Radiohead").join("artist.nfo").exists());
+
+        std::fs::write(folder.join("album.nfo"), "edited").unwrap();
+        let report = export_albums(&albums, &Scope::Library, &config);
+        assert_eq!((report.albums, report.kept), (0, 2));
+        assert_eq!(
+            std::fs::read_to_string(folder.join("album.nfo")).unwrap(),
+            "edited"
+        );
+
+        let overwrite = NfoConfig {
+            overwrite: true,
+            ..NfoConfig::default()
+        };
+        let report = export_albums(&albums, &Scope::Library, &overwrite);
+        assert_eq!(report.albums, 1);
+
+        // Out of scope
+        let scope = Scope::Under(dir.path().join("Other"));
+        assert_eq!(
+            export_albums(&albums, &scope, &config),
+            ExportReport::default()
+        );
+    }
+}
@@ -0,0 +1,553 @@
+//! Crash-safe organize journal.
+//!
+//! Every move is journaled before it touches the file system and marked
+//! committed only after the database has been updated:
+//!
+//! 1. `intent` record (fsynced) — "about to move track N from A to B"
+//! 2. Move the file
+//! 3. Update the track path in the database
+//! 4. `commit` record
+//!
+//! If the app dies mid-organize the journal stays behind. On the next start
+//! [`OrganizeJournal::load_incomplete`] finds it, and [`recover`] either
+//! resumes the remaining moves or rolls the whole operation back. Both
+//! directions inspect where each file actually is, so recovery is idempotent
+//! and leaves the database pointing at the real file location.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use super::{MoveRecord, PathSync, SyncReport, UndoLog, move_file};
+
+/// A single line in the journal file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalRecord {
+    Begin {
+        timestamp: String,
+    },
+    Intent {
+        seq: u64,
+        track_id: i64,
+        source: PathBuf,
+        destination: PathBuf,
+    },
+    Commit {
+        seq: u64,
+    },
+    /// The move failed before anything reached the destination
+    Abort {
+        seq: u64,
+    },
+}
+
+/// Append-only journal for an organize operation in progress.
+#[derive(Debug)]
+pub struct OrganizeJournal {
+    path: PathBuf,
+    file: File,
+    next_seq: u64,
+    /// Intents without a commit/abort yet
+    outstanding: HashSet<u64>,
+}
+
+impl OrganizeJournal {
+    const JOURNAL_FILE: &'static str = "music_minder_journal.jsonl";
+
+    /// Start a new journal at the default location.
+    ///
+    /// Fails if an incomplete journal from an earlier run still exists; it
+    /// must be recovered first.
+    pub fn begin() -> Result<Self> {
+        Self::begin_at(crate::profile::data_path(Self::JOURNAL_FILE))
+    }
+
+    /// Start a new journal at a specific path.
+    pub fn begin_at(path: impl Into<PathBuf>) -> Result<Self> {
+        crate::readonly::ensure_writable("Organizing files")?;
+        let path = path.into();
+        if path.exists() {
+            bail!(
+                "An interrupted organize must be resumed or rolled back first ({})",
+                path.display()
+            );
+        }
+
+        let file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to create journal: {:?}", path))?;
+        let mut journal = Self {
+            path,
+            file,
+            next_seq: 0,
+            outstanding: HashSet::new(),
+        };
+        journal.append(&JournalRecord::Begin {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        })?;
+        Ok(journal)
+    }
+
+    /// Journal the intent to move a file, then move it.
+    ///
+    /// Returns the sequence number to pass to [`record_commit`](Self::record_commit)
+    /// once the database reflects the new path.
+    pub fn journaled_move(
+        &mut self,
+        track_id: i64,
+        source: &Path,
+        destination: &Path,
+    ) -> Result<u64> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.append(&JournalRecord::Intent {
+            seq,
+            track_id,
+            source: source.to_path_buf(),
+            destination: destination.to_path_buf(),
+        })?;
+        self.outstanding.insert(seq);
+        if let Err(e) = move_file(source, destination) {
+            // Nothing to recover if nothing reached the destination
+            if !destination.exists() {
+                self.append(&JournalRecord::Abort { seq })?;
+                self.outstanding.remove(&seq);
+            }
+            return Err(e);
+        }
+        Ok(seq)
+    }
+
+    /// Mark a journaled move as fully applied (file moved and DB updated).
+    pub fn record_commit(&mut self, seq: u64) -> Result<()> {
+        self.append(&JournalRecord::Commit { seq })?;
+        self.outstanding.remove(&seq);
+        Ok(())
+    }
+
+    /// Whether every journaled move was committed or cleanly aborted.
+    pub fn is_complete(&self) -> bool {
+        self.outstanding.is_empty()
+    }
+
+    /// Finish the operation and remove the journal.
+    ///
+    /// Call only when [`is_complete`](Self::is_complete); otherwise keep the
+    /// journal so the operation can be recovered.
+    pub fn finish(self) -> Result<()> {
+        drop(self.file);
+        fs::remove_file(&self.path)
+            .with_context(|| format!("Failed to remove journal: {:?}", self.path))
+    }
+
+    /// Check for an interrupted organize at the default location.
+    pub fn load_incomplete() -> Option<IncompleteOrganize> {
+        Self::load_incomplete_at(&crate::profile::data_path(Self::JOURNAL_FILE))
+    }
+
+    /// Check for an interrupted organize at a specific path.
+    ///
+    /// A torn last line (crash while appending) is ignored.
+    pub fn load_incomplete_at(path: &Path) -> Option<IncompleteOrganize> {
+        let file = File::open(path).ok()?;
+        let mut started = None;
+        let mut entries: Vec<(u64, JournalEntry)> = Vec::new();
+
+        for line in BufReader::new(file).lines() {
+            let Ok(line) = line else { break };
+            let Ok(record) = serde_json::from_str::<JournalRecord>(&line) else {
+                continue;
+            };
+            match record {
+                JournalRecord::Begin { timestamp } => started = Some(timestamp),
+                JournalRecord::Intent {
+                    seq,
+                    track_id,
+                    source,
+                    destination,
+                } => entries.push((
+                    seq,
+                    JournalEntry {
+                        track_id,
+                        source,
+                        destination,
+                        committed: false,
+                    },
+                )),
+                JournalRecord::Commit { seq } => {
+                    if let Some((_, entry)) = entries.iter_mut().find(|(s, _)| *s == seq) {
+                        entry.committed = true;
+                    }
+                }
+                JournalRecord::Abort { seq } => entries.retain(|(s, _)| *s != seq),
+            }
+        }
+
+        Some(IncompleteOrganize {
+            path: path.to_path_buf(),
+            started,
+            entries: entries.into_iter().map(|(_, e)| e).collect(),
+        })
+    }
+
+    fn append(&mut self, record: &JournalRecord) -> Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        // The intent must be durable before the move happens
+        self.file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// A move recorded in an interrupted journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub track_id: i64,
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    /// File moved and database updated
+    pub committed: bool,
+}
+
+/// Where a journaled file currently lives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileLocation {
+    /// Not moved yet
+    Source,
+    /// Moved
+    Destination,
+    /// Cross-device copy not finished (destination may be partial)
+    Both,
+    /// Neither path exists
+    Missing,
+}
+
+impl JournalEntry {
+    /// Inspect the file system to see where the file is now.
+    pub fn location(&self) -> FileLocation {
+        match (self.source.exists(), self.destination.exists()) {
+            (true, false) => FileLocation::Source,
+            (false, true) => FileLocation::Destination,
+            (true, true) => FileLocation::Both,
+            (false, false) => FileLocation::Missing,
+        }
+    }
+}
+
+/// An organize operation that did not finish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncompleteOrganize {
+    /// Journal file location
+    pub path: PathBuf,
+    /// When the operation started (RFC 3339)
+    pub started: Option<String>,
+    pub entries: Vec<JournalEntry>,
+}
+
+impl IncompleteOrganize {
+    /// Number of moves that were not committed.
+    pub fn pending_count(&self) -> usize {
+        self.entries.iter().filter(|e| !e.committed).count()
+    }
+}
+
+/// How to recover an interrupted organize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryMode {
+    /// Finish the moves that were in flight
+    Resume,
+    /// Move every journaled file back to its original location
+    Rollback,
+}
+
+/// Outcome of a recovery run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// Files now at (and recorded in the DB at) their intended location
+    pub resumed: usize,
+    /// Files now at (and recorded in the DB at) their original location
+    pub rolled_back: usize,
+    pub errors: Vec<String>,
+    /// What followed the files that moved
+    pub sync: SyncReport,
+}
+
+/// Recover an interrupted organize, bringing files and DB back in sync.
+///
+/// On success the journal is removed. If any entry fails the journal is kept
+/// so recovery can be retried; already-recovered entries are detected by
+/// file location and simply re-confirmed in the database.
+pub async fn recover(
+    pool: &SqlitePool,
+    incomplete: &IncompleteOrganize,
+    mode: RecoveryMode,
+) -> RecoveryReport {
+    let mut report = RecoveryReport::default();
+    let mut sync = PathSync::new();
+    let mut undo_log = UndoLog {
+        moves: vec![],
+        timestamp: incomplete.started.clone(),
+    };
+
+    // Roll back in reverse order so nested moves unwind cleanly
+    let entries: Vec<JournalEntry> = match mode {
+        RecoveryMode::Resume => incomplete.entries.clone(),
+        RecoveryMode::Rollback => incomplete.entries.iter().rev().cloned().collect(),
+    };
+
+    for entry in entries {
+        let e = entry.clone();
+        let fs_result = tokio::task::spawn_blocking(move || match mode {
+            RecoveryMode::Resume => resume_file(&e),
+            RecoveryMode::Rollback => rollback_file(&e),
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Task error: {}", e))
+        .and_then(|r| r);
+
+        let final_path = match (fs_result, mode) {
+            (Ok(()), RecoveryMode::Resume) => &entry.destination,
+            (Ok(()), RecoveryMode::Rollback) => &entry.source,
+            (Err(e), _) => {
+                report
+                    .errors
+                    .push(format!("{}: {}", entry.source.display(), e));
+                continue;
+            }
+        };
+
+        if let Err(e) = sync.record(pool, entry.track_id, final_path).await {
+            report.errors.push(format!("DB error: {}", e));
+            continue;
+        }
+
+        match mode {
+            RecoveryMode::Resume => {
+                undo_log.moves.push(MoveRecord {
+                    source: entry.source,
+                    destination: entry.destination,
+                    track_id: entry.track_id,
+                });
+                report.resumed += 1;
+            }
+            RecoveryMode::Rollback => report.rolled_back += 1,
+        }
+    }
+
+    // Before this batch's undo log replaces the earlier one
+    report.sync = tokio::task::spawn_blocking(move || sync.finish())
+        .await
+        .unwrap_or_default();
+
+    if report.errors.is_empty() {
+        if mode == RecoveryMode::Resume
+            && let Err(e) = undo_log.save()
+        {
+            tracing::warn!("Failed to save undo log after resume: {}", e);
+        }
+        if let Err(e) = fs::remove_file(&incomplete.path) {
+            report
+                .errors
+                .push(format!("Failed to remove journal: {}", e));
+        }
+    }
+    report
+}
+
+/// Make sure the file ends up at its destination.
+fn resume_file(entry: &JournalEntry) -> Result<()> {
+    match entry.location() {
+        FileLocation::Destination => Ok(()),
+        FileLocation::Source => move_file(&entry.source, &entry.destination),
+        FileLocation::Both => {
+            // The copy may be partial; redo it from the intact source
+            fs::remove_file(&entry.destination)?;
+            move_file(&entry.source, &entry.destination)
+        }
+        FileLocation::Missing => bail!("file not found at source or destination"),
+    }
+}
+
+/// Make sure the file ends up back at its source.
+fn rollback_file(entry: &JournalEntry) -> Result<()> {
+    match entry.location() {
+        FileLocation::Source => Ok(()),
+        FileLocation::Destination => {
+            move_file(&entry.destination, &entry.source)?;
+            if let Some(parent) = entry.destination.parent() {
+                let _ = super::remove_empty_dirs(parent);
+            }
+            Ok(())
+        }
+        FileLocation::Both => {
+            fs::remove_file(&entry.destination)?;
+            Ok(())
+        }
+        FileLocation::Missing => bail!("file not found at source or destination"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_finished_journal_is_removed() {
+        let dir = tempdir().unwrap();
+        let journal_path = dir.path().join("journal.jsonl");
+        let src = dir.path().join("a.mp3");
+        let dest = dir.path().join("out/a.mp3");
+        fs::write(&src, b"audio").unwrap();
+
+        let mut journal = OrganizeJournal::begin_at(&journal_path).unwrap();
+        let seq = journal.journaled_move(1, &src, &dest).unwrap();
+        assert!(!journal.is_complete());
+        journal.record_commit(seq).unwrap();
+        assert!(journal.is_complete());
+        journal.finish().unwrap();
+
+        assert!(dest.exists());
+        assert!(OrganizeJournal::load_incomplete_at(&journal_path).is_none());
+    }
+
+    #[test]
+    fn test_interrupted_journal_is_detected() {
+        let dir = tempdir().unwrap();
+        let journal_path = dir.path().join("journal.jsonl");
+        let a = dir.path().join("a.mp3");
+        let b = dir.path().join("b.mp3");
+        fs::write(&a, b"a").unwrap();
+        fs::write(&b, b"b").unwrap();
+
+        let mut journal = OrganizeJournal::begin_at(&journal_path).unwrap();
+        let seq = journal
+            .journaled_move(1, &a, &dir.path().join("out/a.mp3"))
+            .unwrap();
+        journal.record_commit(seq).unwrap();
+        journal
+            .journaled_move(2, &b, &dir.path().join("out/b.mp3"))
+            .unwrap();
+        // Failed move: the file stays put and needs no recovery
+        let missing = dir.path().join("missing.mp3");
+        assert!(
+            journal
+                .journaled_move(3, &missing, &dir.path().join("out/missing.mp3"))
+                .is_err()
+        );
+        // Crash: no commit for track 2, no finish
+        drop(journal);
+
+        // A second organize must not start over the old journal
+        assert!(OrganizeJournal::begin_at(&journal_path).is_err());
+
+        let incomplete = OrganizeJournal::load_incomplete_at(&journal_path).unwrap();
+        assert!(incomplete.started.is_some());
+        assert_eq!(incomplete.entries.len(), 2);
+        assert!(incomplete.entries[0].committed);
+        assert!(!incomplete.entries[1].committed);
+        assert_eq!(incomplete.pending_count(), 1);
+        assert_eq!(incomplete.entries[1].location(), FileLocation::Destination);
+    }
+
+    #[test]
+    fn test_torn_last_line_is_ignored() {
+        let dir = tempdir().unwrap();
+        let journal_path = dir.path().join("journal.jsonl");
+        fs::write(
+            &journal_path,
+            concat!(
+                r#"{"op":"begin","timestamp":"2025-01-01T00:00:00Z"}"#,
+                "\n",
+                r#"{"op":"intent","seq":0,"track_id":7,"source":"/a.mp3","destination":"/b.mp3"}"#,
+                "\n",
+                r#"{"op":"com"#,
+            ),
+        )
+        .unwrap();
+
+        let incomplete = OrganizeJournal::load_incomplete_at(&journal_path).unwrap();
+        assert_eq!(incomplete.entries.len(), 1);
+        assert_eq!(incomplete.entries[0].track_id, 7);
+        assert!(!incomplete.entries[0].committed);
+    }
+
+    #[test]
+    fn test_resume_and_rollback_file_locations() {
+        let dir = tempdir().unwrap();
+        let entry = JournalEntry {
+            track_id: 1,
+            source: dir.path().join("src.mp3"),
+            destination: dir.path().join("out/dest.mp3"),
+            committed: false,
+        };
+
+        // Partial cross-device copy: resume redoes it from the source
+        fs::write(&entry.source, b"full audio").unwrap();
+        fs::create_dir_all(entry.destination.parent().unwrap()).unwrap();
+        fs::write(&entry.destination, b"part").unwrap();
+        assert_eq!(entry.location(), FileLocation::Both);
+        resume_file(&entry).unwrap();
+        assert_eq!(entry.location(), FileLocation::Destination);
+        assert_eq!(fs::read(&entry.destination).unwrap(), b"full audio");
+        // Idempotent
+        resume_file(&entry).unwrap();
+
+        rollback_file(&entry).unwrap();
+        assert_eq!(entry.location(), FileLocation::Source);
+        assert!(!dir.path().join("out").exists(), "empty dir cleaned up");
+        rollback_file(&entry).unwrap();
+
+        fs::remove_file(&entry.source).unwrap();
+        assert!(resume_file(&entry).is_err());
+        assert!(rollback_file(&entry).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_restores_files_and_db() {
+        let dir = tempdir().unwrap();
+        let pool = db::init_db(&format!("sqlite:{}", dir.path().join("test.db").display()))
+            .await
+            .unwrap();
+
+        let src = dir.path().join("a.mp3");
+        let dest = dir.path().join("out/a.mp3");
+        fs::write(&src, b"audio").unwrap();
+        let meta = crate::metadata::TrackMetadata {
+            title: "A".to_string(),
+            artist: String::new(),
+            album: String::new(),
+            duration: 0,
+            track_number: None,
+        };
+        let track_id = db::insert_track(&pool, &meta, &src.to_string_lossy(), None, None)
+            .await
+            .unwrap();
+
+        // Crash after the move, before the DB update
+        let journal_path = dir.path().join("journal.jsonl");
+        let mut journal = OrganizeJournal::begin_at(&journal_path).unwrap();
+        journal.journaled_move(track_id, &src, &dest).unwrap();
+        drop(journal);
+
+        let incomplete = OrganizeJournal::load_incomplete_at(&journal_path).unwrap();
+        let report = recover(&pool, &incomplete, RecoveryMode::Rollback).await;
+
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(report.rolled_back, 1);
+        assert!(src.exists());
+        assert!(!dest.exists());
+        assert!(!journal_path.exists());
+        let track = db::get_track_by_id(&pool, track_id).await.unwrap().unwrap();
+        assert_eq!(track.path, src.to_string_lossy());
+    }
+}
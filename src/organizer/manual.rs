@@ -0,0 +1,231 @@
+//! Organizing by hand: dropping an incoming folder (or file) onto a folder
+//! of the library.
+//!
+//! Tracks outside the library root are "incoming", grouped by the folder
+//! they sit in ([`incoming_folders`]). The library side is the folder tree
+//! above the tracks under the root ([`library_folders`]). A drop is turned
+//! into ordinary organize moves by [`drop_moves`], so it runs through the
+//! same simulation, journal, undo log and path sync as a pattern organize:
+//! the dropped folder keeps its name and layout inside the target.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::OrganizePreview;
+
+/// A folder holding incoming tracks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncomingFolder {
+    pub path: PathBuf,
+    /// Its tracks (not those of subfolders), by file name
+    pub tracks: Vec<(i64, PathBuf)>,
+}
+
+/// A folder of the library tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryFolder {
+    pub path: PathBuf,
+    /// Levels below the root (the root itself is 0)
+    pub depth: usize,
+    /// Tracks in it and its subfolders
+    pub tracks: usize,
+}
+
+/// Folders of the tracks outside `root`, by path
+pub fn incoming_folders<'a>(
+    tracks: impl IntoIterator<Item = (i64, &'a Path)>,
+    root: &Path,
+) -> Vec<IncomingFolder> {
+    let mut folders: BTreeMap<PathBuf, Vec<(i64, PathBuf)>> = BTreeMap::new();
+    for (id, path) in tracks {
+        if path.starts_with(root) {
+            continue;
+        }
+        let Some(parent) = path.parent() else {
+            continue;
+        };
+        folders
+            .entry(parent.to_path_buf())
+            .or_default()
+            .push((id, path.to_path_buf()));
+    }
+    folders
+        .into_iter()
+        .map(|(path, mut tracks)| {
+            tracks.sort_by(|a, b| a.1.cmp(&b.1));
+            IncomingFolder { path, tracks }
+        })
+        .collect()
+}
+
+/// The folder tree of the tracks under `root`, depth first with the root
+/// at the top
+pub fn library_folders<'a>(
+    tracks: impl IntoIterator<Item = (i64, &'a Path)>,
+    root: &Path,
+) -> Vec<LibraryFolder> {
+    let mut counts: BTreeMap<PathBuf, usize> = BTreeMap::new();
+    counts.insert(root.to_path_buf(), 0);
+    for (_, path) in tracks {
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let mut folder = root.to_path_buf();
+        *counts.entry(folder.clone()).or_default() += 1;
+        let components: Vec<_> = relative.components().collect();
+        // The last component is the file
+        for component in components.iter().take(components.len().saturating_sub(1)) {
+            folder.push(component);
+            *counts.entry(folder.clone()).or_default() += 1;
+        }
+    }
+    // Path order is depth first: a folder comes right before its subfolders
+    counts
+        .into_iter()
+        .map(|(path, tracks)| LibraryFolder {
+            depth: path
+                .strip_prefix(root)
+                .map_or(0, |rel| rel.components().count()),
+            path,
+            tracks,
+        })
+        .collect()
+}
+
+/// The moves dropping `source` (a track or a folder of them) onto `into`
+/// makes: a track goes straight into it, a folder's tracks go into a
+/// folder of the same name there, keeping their layout. Tracks already
+/// where they'd go are left out.
+pub fn drop_moves<'a>(
+    tracks: impl IntoIterator<Item = (i64, &'a Path)>,
+    source: &Path,
+    into: &Path,
+) -> Vec<OrganizePreview> {
+    let Some(name) = source.file_name() else {
+        return vec![];
+    };
+    // Dropping a folder into itself would never finish
+    if into.starts_with(source) {
+        return vec![];
+    }
+    tracks
+        .into_iter()
+        .filter_map(|(track_id, path)| {
+            let relative = path.strip_prefix(source).ok()?;
+            let mut destination = into.join(name);
+            if !relative.as_os_str().is_empty() {
+                destination.push(relative);
+            }
+            (destination != path).then(|| OrganizePreview {
+                source: path.to_path_buf(),
+                destination,
+                track_id,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracks() -> Vec<(i64, PathBuf)> {
+        [
+            "/music/Artist/Album/01.flac",
+            "/music/Artist/Album/02.flac",
+            "/music/Other/Single.mp3",
+            "/downloads/New Album/01.flac",
+            "/downloads/New Album/CD2/01.flac",
+            "/downloads/loose.mp3",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i as i64 + 1, PathBuf::from(p)))
+        .collect()
+    }
+
+    fn refs(tracks: &[(i64, PathBuf)]) -> impl Iterator<Item = (i64, &Path)> {
+        tracks.iter().map(|(id, p)| (*id, p.as_path()))
+    }
+
+    #[test]
+    fn test_incoming_and_library_folders() {
+        let tracks = tracks();
+        let root = Path::new("/music");
+
+        let incoming = incoming_folders(refs(&tracks), root);
+        let paths: Vec<_> = incoming.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/downloads"),
+                PathBuf::from("/downloads/New Album"),
+                PathBuf::from("/downloads/New Album/CD2"),
+            ]
+        );
+        assert_eq!(incoming[1].tracks.len(), 1);
+
+        let library = library_folders(refs(&tracks), root);
+        let rows: Vec<_> = library
+            .iter()
+            .map(|f| (f.path.to_string_lossy().into_owned(), f.depth, f.tracks))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("/music".to_string(), 0, 3),
+                ("/music/Artist".to_string(), 1, 2),
+                ("/music/Artist/Album".to_string(), 2, 2),
+                ("/music/Other".to_string(), 1, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drop_moves_keep_folder_layout() {
+        let tracks = tracks();
+        let moves = drop_moves(
+            refs(&tracks),
+            Path::new("/downloads/New Album"),
+            Path::new("/music/Artist"),
+        );
+        let pairs: Vec<_> = moves
+            .iter()
+            .map(|m| (m.track_id, m.destination.clone()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                (4, PathBuf::from("/music/Artist/New Album/01.flac")),
+                (5, PathBuf::from("/music/Artist/New Album/CD2/01.flac")),
+            ]
+        );
+
+        // A single track lands in the folder itself
+        let single = drop_moves(
+            refs(&tracks),
+            Path::new("/downloads/loose.mp3"),
+            Path::new("/music/Other"),
+        );
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].destination, Path::new("/music/Other/loose.mp3"));
+
+        // Already there, or into itself: nothing to do
+        assert!(
+            drop_moves(
+                refs(&tracks),
+                Path::new("/music/Other"),
+                Path::new("/music")
+            )
+            .is_empty()
+        );
+        assert!(
+            drop_moves(
+                refs(&tracks),
+                Path::new("/downloads"),
+                Path::new("/downloads/New Album")
+            )
+            .is_empty()
+        );
+    }
+}
@@ -5,16 +5,37 @@
 //!
 //! # Features
 //! - Pattern-based file organization
-//! - Preview mode to see changes before applying
+//! - Preview mode to see changes before applying, grouped by album with
+//!   the changed parts of each path marked
+//! - Conflict simulation that blocks moves which would overwrite a file
 //! - Undo support with logged move operations
+//! - Crash-safe journal with resume/rollback of interrupted organizes
+//! - Saved session, undo log and file health rows follow moved files
+//! - Manual moves of incoming folders into the library tree
 //! - Automatic cleanup of empty directories
 
+use crate::error::Retry;
 use crate::metadata::TrackMetadata;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+mod journal;
+mod manual;
+mod path_sync;
+mod review;
+mod simulate;
+
+pub use journal::{
+    FileLocation, IncompleteOrganize, JournalEntry, OrganizeJournal, RecoveryMode, RecoveryReport,
+    recover,
+};
+pub use manual::{IncomingFolder, LibraryFolder, drop_moves, incoming_folders, library_folders};
+pub use path_sync::{PathChange, PathSync, SyncReport};
+pub use review::{PathPart, PreviewGroup, group_by_album, path_diff};
+pub use simulate::{ConflictKind, OrganizeConflict, Simulation, simulate};
+
 /// A record of a file move operation, used for undo functionality
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoveRecord {
@@ -31,11 +52,15 @@ pub struct UndoLog {
 }
 
 impl UndoLog {
-    const LOG_PATH: &'static str = "music_minder_undo.json";
+    const LOG_FILE: &'static str = "music_minder_undo.json";
+
+    fn path() -> PathBuf {
+        crate::profile::data_path(Self::LOG_FILE)
+    }
 
     /// Load the undo log from disk
     pub fn load() -> Option<Self> {
-        fs::read_to_string(Self::LOG_PATH)
+        fs::read_to_string(Self::path())
             .ok()
             .and_then(|s| serde_json::from_str(&s).ok())
     }
@@ -43,21 +68,22 @@ impl UndoLog {
     /// Save the undo log to disk
     pub fn save(&self) -> Result<()> {
         let json = serde_json::to_string_pretty(self)?;
-        fs::write(Self::LOG_PATH, json)?;
+        fs::write(Self::path(), json)?;
         Ok(())
     }
 
     /// Clear the undo log
     pub fn clear() -> Result<()> {
-        if Path::new(Self::LOG_PATH).exists() {
-            fs::remove_file(Self::LOG_PATH)?;
+        let path = Self::path();
+        if path.exists() {
+            fs::remove_file(path)?;
         }
         Ok(())
     }
 
     /// Check if there's an undo operation available
     pub fn has_undo() -> bool {
-        Path::new(Self::LOG_PATH).exists()
+        Self::path().exists()
     }
 }
 
@@ -135,27 +161,40 @@ pub fn organize_track(
     // Build destination path
     let dest_path = destination_root.join(&path_str);
 
+    move_file(source_path, &dest_path)?;
+    Ok(dest_path)
+}
+
+/// Moves a file to an exact destination, creating parent directories.
+///
+/// Used when executing a previously generated plan, where the destination
+/// was already computed and must not be re-derived from (possibly changed)
+/// metadata.
+pub fn move_file(source_path: &Path, dest_path: &Path) -> Result<()> {
+    crate::readonly::ensure_writable("Moving files")?;
     // Create parent directories
     if let Some(parent) = dest_path.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create directory: {:?}", parent))?;
     }
 
-    // Move or copy the file
-    if let Err(_e) = fs::rename(source_path, &dest_path) {
-        // If rename fails (cross-device), try copy + delete
-
-        fs::copy(source_path, &dest_path)
-            .with_context(|| format!("Failed to copy file to: {:?}", dest_path))?;
-        fs::remove_file(source_path)
-            .with_context(|| format!("Failed to remove source file: {:?}", source_path))?;
-    }
+    relocate(source_path, dest_path)
+}
 
-    Ok(dest_path)
+/// Renames a file, or copies and deletes it when that fails (across
+/// devices). Tried again while the file is held open elsewhere.
+fn relocate(from: &Path, to: &Path) -> Result<()> {
+    Retry::FILE.run(|| {
+        if fs::rename(from, to).is_ok() {
+            return Ok(());
+        }
+        fs::copy(from, to).with_context(|| format!("Failed to copy file to: {:?}", to))?;
+        fs::remove_file(from).with_context(|| format!("Failed to remove file: {:?}", from))
+    })
 }
 
 /// Sanitizes a filename by removing/replacing invalid characters
-fn sanitize_filename(name: &str) -> String {
+pub(crate) fn sanitize_filename(name: &str) -> String {
     name.chars()
         .map(|c| match c {
             '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
@@ -166,20 +205,14 @@ fn sanitize_filename(name: &str) -> String {
 
 /// Moves a single file back to its original location (for undo)
 pub fn undo_move(record: &MoveRecord) -> Result<()> {
+    crate::readonly::ensure_writable("Undoing moves")?;
     // Create parent directories for the original location
     if let Some(parent) = record.source.parent() {
         fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create directory: {:?}", parent))?;
     }
 
-    // Move the file back
-    if let Err(_e) = fs::rename(&record.destination, &record.source) {
-        // If rename fails (cross-device), try copy + delete
-        fs::copy(&record.destination, &record.source)
-            .with_context(|| format!("Failed to copy file to: {:?}", record.source))?;
-        fs::remove_file(&record.destination)
-            .with_context(|| format!("Failed to remove file: {:?}", record.destination))?;
-    }
+    relocate(&record.destination, &record.source)?;
 
     // Try to clean up empty directories
     if let Some(parent) = record.destination.parent() {
@@ -0,0 +1,264 @@
+//! Path-change propagation: whatever refers to a file by path follows it
+//! when an organize, undo or recovery moves it.
+//!
+//! Each move goes through [`PathSync::record`], which points the track row
+//! (path and path key) and its file health row at the new location in one
+//! transaction. Once the batch is done, [`PathSync::finish`] rewrites the
+//! saved state that outlives the app: the session offered for resuming (its
+//! queue), and undo records of an earlier batch whose files the batch moved
+//! again. The returned [`SyncReport`] maps old paths to new ones for the
+//! holders only the running app has, like the play queue and the cover on
+//! show.
+//!
+//! A batch's changes are applied in one step, one hop per path, so a batch
+//! that moves `02.mp3` to `03.mp3` and then `01.mp3` to `02.mp3` leaves a
+//! queued `01.mp3` at `02.mp3`, not at `03.mp3`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use sqlx::SqlitePool;
+
+use super::UndoLog;
+use crate::db::paths;
+use crate::history::LastSession;
+
+/// A track file that moved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathChange {
+    pub track_id: i64,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Collects a batch's moves, updating the database as they happen
+#[derive(Debug, Default)]
+pub struct PathSync {
+    changes: Vec<PathChange>,
+}
+
+impl PathSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Point the database at the new location of a track's file, which has
+    /// just been moved to `to`
+    pub async fn record(
+        &mut self,
+        pool: &SqlitePool,
+        track_id: i64,
+        to: &Path,
+    ) -> sqlx::Result<()> {
+        crate::readonly::ensure_writable("Updating track paths")?;
+        let to_str = to.to_string_lossy();
+        let mut tx = pool.begin().await?;
+        let from: Option<String> = sqlx::query_scalar("SELECT path FROM tracks WHERE id = ?")
+            .bind(track_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE tracks SET path = ?, path_key = ?, updated_at = unixepoch() WHERE id = ?",
+        )
+        .bind(paths::canonical(&to_str))
+        .bind(paths::key(&to_str))
+        .bind(track_id)
+        .execute(&mut *tx)
+        .await?;
+        if let Some(from) = &from {
+            // A stale row for a file that used to be at `to` gives way
+            sqlx::query("UPDATE OR REPLACE file_health SET path = ? WHERE path = ?")
+                .bind(to_str.as_ref())
+                .bind(from)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        if let Some(from) = from.filter(|from| Path::new(from) != to) {
+            self.changes.push(PathChange {
+                track_id,
+                from: PathBuf::from(from),
+                to: to.to_path_buf(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The moves recorded so far
+    pub fn changes(&self) -> &[PathChange] {
+        &self.changes
+    }
+
+    /// Rewrite the saved session and undo log for the batch. Blocking file
+    /// I/O, so call from a blocking context.
+    pub fn finish(self) -> SyncReport {
+        let mut report = SyncReport::new(self.changes);
+        if report.moved.is_empty() {
+            return report;
+        }
+
+        if let Some(mut session) = LastSession::load() {
+            let n = relocate_all(session.queue.iter_mut(), &report.moved);
+            if n > 0 {
+                match session.save() {
+                    Ok(()) => report.session_entries = n,
+                    Err(e) => report.errors.push(format!("Saved session: {}", e)),
+                }
+            }
+        }
+
+        if let Some(mut log) = UndoLog::load() {
+            let n = relocate_all(
+                log.moves.iter_mut().map(|m| &mut m.destination),
+                &report.moved,
+            );
+            if n > 0 {
+                match log.save() {
+                    Ok(()) => report.undo_records = n,
+                    Err(e) => report.errors.push(format!("Undo log: {}", e)),
+                }
+            }
+        }
+
+        for error in &report.errors {
+            tracing::warn!(target: "organizer::path_sync", "Failed to update {}", error);
+        }
+        report
+    }
+}
+
+/// Move every path found in `moved` to its new location; returns how many moved
+fn relocate_all<'a>(
+    paths: impl Iterator<Item = &'a mut PathBuf>,
+    moved: &HashMap<PathBuf, PathBuf>,
+) -> usize {
+    let mut n = 0;
+    for path in paths {
+        if let Some(to) = moved.get(path.as_path()) {
+            *path = to.clone();
+            n += 1;
+        }
+    }
+    n
+}
+
+/// What a batch moved, and which saved state followed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub changes: Vec<PathChange>,
+    /// Old path -> new path
+    moved: HashMap<PathBuf, PathBuf>,
+    /// Saved session queue entries rewritten
+    pub session_entries: usize,
+    /// Undo records of an earlier batch rewritten
+    pub undo_records: usize,
+    pub errors: Vec<String>,
+}
+
+impl SyncReport {
+    fn new(changes: Vec<PathChange>) -> Self {
+        let moved = changes
+            .iter()
+            .map(|c| (c.from.clone(), c.to.clone()))
+            .collect();
+        Self {
+            changes,
+            moved,
+            ..Default::default()
+        }
+    }
+
+    /// Where the file that was at `path` is now, if the batch moved it
+    pub fn new_location(&self, path: &Path) -> Option<&Path> {
+        self.moved.get(path).map(PathBuf::as_path)
+    }
+
+    /// Move `path` to its new location if the batch moved it; returns whether it did
+    pub fn relocate(&self, path: &mut PathBuf) -> bool {
+        match self.new_location(path) {
+            Some(to) => {
+                *path = to.to_path_buf();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_db;
+
+    #[tokio::test]
+    async fn test_record_moves_track_and_health_rows_together() {
+        let (pool, _dir) = temp_db().await;
+        let id = crate::test_utils::insert_mock_track(&pool, "/a/01.mp3").await;
+        sqlx::query("INSERT INTO file_health (path, status, last_checked) VALUES (?, 'ok', '')")
+            .bind("/a/01.mp3")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut sync = PathSync::new();
+        sync.record(&pool, id, Path::new("/b/01.mp3"))
+            .await
+            .unwrap();
+        // Recording a file that didn't move isn't a change
+        sync.record(&pool, id, Path::new("/b/01.mp3"))
+            .await
+            .unwrap();
+        assert_eq!(
+            sync.changes(),
+            &[PathChange {
+                track_id: id,
+                from: PathBuf::from("/a/01.mp3"),
+                to: PathBuf::from("/b/01.mp3"),
+            }]
+        );
+
+        let path: String = sqlx::query_scalar("SELECT path FROM tracks WHERE id = ?")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(path, "/b/01.mp3");
+        let health: Vec<String> = sqlx::query_scalar("SELECT path FROM file_health")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(health, vec!["/b/01.mp3".to_string()]);
+    }
+
+    #[test]
+    fn test_report_relocates_one_hop_per_path() {
+        // Renumbering: 02 -> 03, then 01 -> 02
+        let report = SyncReport::new(vec![
+            PathChange {
+                track_id: 2,
+                from: PathBuf::from("/m/02.mp3"),
+                to: PathBuf::from("/m/03.mp3"),
+            },
+            PathChange {
+                track_id: 1,
+                from: PathBuf::from("/m/01.mp3"),
+                to: PathBuf::from("/m/02.mp3"),
+            },
+        ]);
+        let mut queue = vec![
+            PathBuf::from("/m/01.mp3"),
+            PathBuf::from("/m/02.mp3"),
+            PathBuf::from("/m/other.mp3"),
+        ];
+        assert_eq!(relocate_all(queue.iter_mut(), &report.moved), 2);
+        assert_eq!(
+            queue,
+            vec![
+                PathBuf::from("/m/02.mp3"),
+                PathBuf::from("/m/03.mp3"),
+                PathBuf::from("/m/other.mp3"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Reviewing an organize preview before it runs.
+//!
+//! [`path_diff`] marks which folders and file names a move changes, so the
+//! preview can show each move like a diff. [`group_by_album`] puts each
+//! album's moves together, so a large preview can be checked (and parts
+//! of it left out) an album at a time.
+
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+use super::OrganizePreview;
+
+/// One folder or file name of a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPart {
+    /// The name; empty for the root of an absolute path
+    pub text: String,
+    /// The other path doesn't have it here
+    pub changed: bool,
+}
+
+/// The parts of `from` and of `to`, those the two paths share (in the same
+/// order, as many as possible) unchanged and the others changed
+pub fn path_diff(from: &Path, to: &Path) -> (Vec<PathPart>, Vec<PathPart>) {
+    let parts = |path: &Path| -> Vec<String> {
+        path.components()
+            .map(|c| match c {
+                Component::RootDir => String::new(),
+                c => c.as_os_str().to_string_lossy().into_owned(),
+            })
+            .collect()
+    };
+    let (a, b) = (parts(from), parts(to));
+
+    // shared[i][j]: most parts a[i..] and b[j..] have in common, in order
+    let mut shared = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            shared[i][j] = if a[i] == b[j] {
+                shared[i + 1][j + 1] + 1
+            } else {
+                shared[i + 1][j].max(shared[i][j + 1])
+            };
+        }
+    }
+    let (mut kept_a, mut kept_b) = (vec![false; a.len()], vec![false; b.len()]);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            kept_a[i] = true;
+            kept_b[j] = true;
+            i += 1;
+            j += 1;
+        } else if shared[i + 1][j] >= shared[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    let mark = |parts: Vec<String>, kept: Vec<bool>| {
+        parts
+            .into_iter()
+            .zip(kept)
+            .map(|(text, kept)| PathPart {
+                text,
+                changed: !kept,
+            })
+            .collect()
+    };
+    (mark(a, kept_a), mark(b, kept_b))
+}
+
+/// The moves of one album in a preview
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewGroup {
+    /// e.g. "Kind of Blue – Miles Davis", or the folder of tracks whose
+    /// album isn't known
+    pub label: String,
+    /// Indices into the preview, in preview order
+    pub items: Vec<usize>,
+    /// Moves that change the file's path
+    pub changed: usize,
+}
+
+/// Group `previews` by the album `album_of` gives each track ID; albums in
+/// the order of their first move
+pub fn group_by_album(
+    previews: &[OrganizePreview],
+    album_of: impl Fn(i64) -> Option<String>,
+) -> Vec<PreviewGroup> {
+    let mut groups: Vec<PreviewGroup> = Vec::new();
+    let mut by_label: HashMap<String, usize> = HashMap::new();
+    for (index, preview) in previews.iter().enumerate() {
+        let label = album_of(preview.track_id).unwrap_or_else(|| {
+            preview
+                .source
+                .parent()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default()
+        });
+        let group = *by_label.entry(label.clone()).or_insert_with(|| {
+            groups.push(PreviewGroup {
+                label,
+                items: Vec::new(),
+                changed: 0,
+            });
+            groups.len() - 1
+        });
+        groups[group].items.push(index);
+        if preview.source != preview.destination {
+            groups[group].changed += 1;
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn changed(parts: &[PathPart]) -> Vec<&str> {
+        parts
+            .iter()
+            .filter(|p| p.changed)
+            .map(|p| p.text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_path_diff_marks_changed_parts() {
+        let (from, to) = path_diff(
+            Path::new("Miles Davis/Kind Of Blue/01 So What.flac"),
+            Path::new("Miles Davis/Kind of Blue (1959)/01 - So What.flac"),
+        );
+        assert_eq!(changed(&from), ["Kind Of Blue", "01 So What.flac"]);
+        assert_eq!(changed(&to), ["Kind of Blue (1959)", "01 - So What.flac"]);
+        assert_eq!(from[0].text, "Miles Davis");
+
+        // A folder added in between leaves the rest unchanged
+        let (from, to) = path_diff(Path::new("A/track.mp3"), Path::new("A/Disc 1/track.mp3"));
+        assert!(changed(&from).is_empty());
+        assert_eq!(changed(&to), ["Disc 1"]);
+
+        let (same, _) = path_diff(Path::new("/music/a.mp3"), Path::new("/music/a.mp3"));
+        assert!(changed(&same).is_empty());
+    }
+
+    #[test]
+    fn test_group_by_album() {
+        let preview = |id: i64, from: &str, to: &str| OrganizePreview {
+            source: PathBuf::from(from),
+            destination: PathBuf::from(to),
+            track_id: id,
+        };
+        let previews = [
+            preview(1, "/in/a/1.mp3", "/lib/A/1.mp3"),
+            preview(2, "/in/b/1.mp3", "/lib/B/1.mp3"),
+            preview(3, "/in/a/2.mp3", "/in/a/2.mp3"),
+            preview(9, "/in/c/1.mp3", "/lib/C/1.mp3"),
+        ];
+        let groups = group_by_album(&previews, |id| match id {
+            1 | 3 => Some("A".to_string()),
+            2 => Some("B".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            (groups[0].label.as_str(), &groups[0].items[..]),
+            ("A", &[0, 2][..])
+        );
+        assert_eq!(groups[0].changed, 1);
+        assert_eq!(groups[1].items, [1]);
+        // Not in the library: grouped by folder
+        assert_eq!(groups[2].label, Path::new("/in/c").display().to_string());
+    }
+}
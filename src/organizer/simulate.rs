@@ -0,0 +1,271 @@
+//! Conflict simulation for an organize, run before anything moves.
+//!
+//! The preview only works out where each file would go. [`simulate`] plays
+//! the whole batch against the destination tree, in the order it would run,
+//! and reports every move that would clobber something:
+//!
+//! - two different files mapped to one destination (the first keeps it),
+//! - a destination already taken by a file the batch doesn't move away first,
+//! - the same, where the file in the way is newer than the one replacing it.
+//!
+//! Destinations are compared ignoring case: music often ends up on FAT,
+//! exFAT, NTFS or APFS volumes, where `Song.mp3` and `song.mp3` are one file.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::OrganizePreview;
+
+/// Why a planned move can't run as it is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// An earlier file in the batch is moving to the same destination
+    SameDestination { claimed_by: PathBuf },
+    /// A file the batch doesn't move is already at the destination
+    ExistingFile,
+    /// The file already at the destination is newer than this one
+    OverwritesNewer,
+}
+
+/// A planned move that would overwrite something
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrganizeConflict {
+    pub track_id: i64,
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub kind: ConflictKind,
+}
+
+impl fmt::Display for OrganizeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dest = self.destination.display();
+        match &self.kind {
+            ConflictKind::SameDestination { claimed_by } => write!(
+                f,
+                "{}: {} is also the destination of {}",
+                self.source.display(),
+                dest,
+                claimed_by.display()
+            ),
+            ConflictKind::ExistingFile => {
+                write!(f, "{}: {} already exists", self.source.display(), dest)
+            }
+            ConflictKind::OverwritesNewer => write!(
+                f,
+                "{}: {} already exists and is newer",
+                self.source.display(),
+                dest
+            ),
+        }
+    }
+}
+
+/// What playing a batch of moves against the disk found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Simulation {
+    /// Moves that would actually change a file's location
+    pub moves: usize,
+    /// Moves that must be resolved before the organize can run
+    pub conflicts: Vec<OrganizeConflict>,
+}
+
+impl Simulation {
+    /// Whether every move can run without overwriting anything
+    pub fn is_clear(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Whether the move of `source` is one of the conflicts
+    pub fn is_conflicted(&self, source: &Path) -> bool {
+        self.conflicts.iter().any(|c| c.source == source)
+    }
+
+    /// Conflict counts in words, e.g. "2 share a destination, 1 would overwrite a newer file"
+    pub fn summary(&self) -> String {
+        let count =
+            |f: fn(&ConflictKind) -> bool| self.conflicts.iter().filter(|c| f(&c.kind)).count();
+        let parts = [
+            (
+                count(|k| matches!(k, ConflictKind::SameDestination { .. })),
+                "share a destination",
+            ),
+            (
+                count(|k| *k == ConflictKind::ExistingFile),
+                "would overwrite an existing file",
+            ),
+            (
+                count(|k| *k == ConflictKind::OverwritesNewer),
+                "would overwrite a newer file",
+            ),
+        ];
+        parts
+            .iter()
+            .filter(|(n, _)| *n > 0)
+            .map(|(n, what)| format!("{} {}", n, what))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Destination key that treats paths differing only in case as one file
+fn collision_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Whether both paths name the same file on disk (a case-only rename on a
+/// case-insensitive volume)
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Play `previews` against the file system in order and report every move
+/// that would overwrite a file. Reads file metadata, so call from a
+/// blocking context.
+pub fn simulate(previews: &[OrganizePreview]) -> Simulation {
+    let mut simulation = Simulation::default();
+    // Destination key -> the source that claimed it first
+    let mut claimed: HashMap<String, &Path> = HashMap::new();
+    // Sources already moved away by the time each move runs
+    let mut vacated: HashSet<String> = HashSet::new();
+
+    for preview in previews {
+        if preview.source == preview.destination {
+            continue;
+        }
+        simulation.moves += 1;
+        let key = collision_key(&preview.destination);
+        let conflict = |kind| OrganizeConflict {
+            track_id: preview.track_id,
+            source: preview.source.clone(),
+            destination: preview.destination.clone(),
+            kind,
+        };
+
+        if let Some(first) = claimed.get(&key) {
+            simulation
+                .conflicts
+                .push(conflict(ConflictKind::SameDestination {
+                    claimed_by: first.to_path_buf(),
+                }));
+        } else {
+            claimed.insert(key.clone(), &preview.source);
+            let occupied = preview.destination.exists()
+                && !vacated.contains(&key)
+                && !same_file(&preview.source, &preview.destination);
+            if occupied {
+                let newer = match (modified(&preview.destination), modified(&preview.source)) {
+                    (Some(existing), Some(source)) => existing > source,
+                    _ => false,
+                };
+                simulation.conflicts.push(conflict(if newer {
+                    ConflictKind::OverwritesNewer
+                } else {
+                    ConflictKind::ExistingFile
+                }));
+            }
+        }
+        vacated.insert(collision_key(&preview.source));
+    }
+    simulation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn preview(source: &Path, destination: &Path, track_id: i64) -> OrganizePreview {
+        OrganizePreview {
+            source: source.to_path_buf(),
+            destination: destination.to_path_buf(),
+            track_id,
+        }
+    }
+
+    #[test]
+    fn test_simulate_finds_each_kind_of_conflict() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        for name in ["a.mp3", "b.mp3", "c.mp3", "d.mp3", "old.mp3", "new.mp3"] {
+            fs::write(path(name), b"audio").unwrap();
+        }
+        // new.mp3 was written after d.mp3
+        let later = SystemTime::now() + Duration::from_secs(60);
+        fs::File::options()
+            .write(true)
+            .open(path("new.mp3"))
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+
+        let simulation = simulate(&[
+            preview(&path("a.mp3"), &path("out/Song.mp3"), 1),
+            preview(&path("b.mp3"), &path("out/song.mp3"), 2),
+            preview(&path("c.mp3"), &path("old.mp3"), 3),
+            preview(&path("d.mp3"), &path("new.mp3"), 4),
+        ]);
+
+        assert_eq!(simulation.moves, 4);
+        let kinds: Vec<_> = simulation
+            .conflicts
+            .iter()
+            .map(|c| (c.track_id, c.kind.clone()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    2,
+                    ConflictKind::SameDestination {
+                        claimed_by: path("a.mp3")
+                    }
+                ),
+                (3, ConflictKind::ExistingFile),
+                (4, ConflictKind::OverwritesNewer),
+            ]
+        );
+        assert!(!simulation.is_conflicted(&path("a.mp3")));
+        assert_eq!(
+            simulation.summary(),
+            "1 share a destination, 1 would overwrite an existing file, 1 would overwrite a newer file"
+        );
+    }
+
+    #[test]
+    fn test_simulate_allows_destinations_vacated_earlier() {
+        let dir = tempdir().unwrap();
+        let path = |name: &str| dir.path().join(name);
+        for name in ["01.mp3", "02.mp3", "04.mp3"] {
+            fs::write(path(name), b"audio").unwrap();
+        }
+
+        // Renumbering: 02 moves out of the way before 01 takes its place
+        let clear = simulate(&[
+            preview(&path("02.mp3"), &path("03.mp3"), 2),
+            preview(&path("01.mp3"), &path("02.mp3"), 1),
+            // Already in place: not a move, and not in anyone's way
+            preview(&path("04.mp3"), &path("04.mp3"), 4),
+        ]);
+        assert!(clear.is_clear(), "{:?}", clear.conflicts);
+        assert_eq!(clear.moves, 2);
+
+        // The other way round, 01 would land on 02 before it moved
+        let blocked = simulate(&[
+            preview(&path("01.mp3"), &path("02.mp3"), 1),
+            preview(&path("02.mp3"), &path("03.mp3"), 2),
+        ]);
+        assert_eq!(blocked.conflicts.len(), 1);
+        assert_eq!(blocked.conflicts[0].track_id, 1);
+    }
+}
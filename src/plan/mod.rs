@@ -0,0 +1,617 @@
+//! Dry-run operation plans.
+//!
+//! A plan records every file move and tag change that an organize or
+//! enrichment run would perform, so it can be reviewed, exported as JSON or
+//! CSV, and later executed exactly as previewed.
+//!
+//! # Drift detection
+//!
+//! - Every entry stores a [`FileStamp`] (size + mtime) of its file at
+//!   planning time. [`OperationPlan::detect_drift`] compares it against the
+//!   file on disk before execution.
+//! - The plan carries a SHA256 hash of its entries. [`OperationPlan::load`]
+//!   rejects exported plans whose contents no longer match the hash.
+
+use crate::enrichment::domain::IdentifiedTrack;
+use crate::metadata::{self, FieldChange, WriteOptions2, WriteResult, content, genres};
+use crate::organizer::OrganizePreview;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Current plan file format version.
+pub const PLAN_VERSION: u32 = 1;
+
+/// Errors that can occur when saving or loading plans.
+#[derive(Debug, thiserror::Error)]
+pub enum PlanError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid plan file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Unsupported plan version {0} (expected {PLAN_VERSION})")]
+    UnsupportedVersion(u32),
+
+    #[error("Plan hash mismatch: the plan file was modified after export")]
+    HashMismatch,
+}
+
+/// Which operation a plan describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanKind {
+    Organize,
+    Enrich,
+}
+
+impl fmt::Display for PlanKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanKind::Organize => write!(f, "organize"),
+            PlanKind::Enrich => write!(f, "enrich"),
+        }
+    }
+}
+
+/// Snapshot of a file's size and modification time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    /// Modification time in seconds since the Unix epoch
+    pub modified: Option<i64>,
+}
+
+impl FileStamp {
+    /// Take a stamp of the file at `path`, or `None` if it can't be read.
+    pub fn of(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64);
+        Some(Self {
+            size: meta.len(),
+            modified,
+        })
+    }
+}
+
+/// A single planned file move.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedMove {
+    pub track_id: i64,
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub stamp: Option<FileStamp>,
+}
+
+/// Planned tag changes for a single file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedTagEdit {
+    pub path: PathBuf,
+    pub stamp: Option<FileStamp>,
+    pub changes: Vec<FieldChange>,
+}
+
+impl PlannedTagEdit {
+    /// Create a tag edit, stamping the file as it is now.
+    pub fn new(path: PathBuf, changes: Vec<FieldChange>) -> Self {
+        let stamp = FileStamp::of(&path);
+        Self {
+            path,
+            stamp,
+            changes,
+        }
+    }
+
+    /// Write exactly the planned field values to the file.
+    ///
+    /// Only fields listed in the plan are touched; fill-only decisions were
+    /// already made when the plan was generated. Field names are those of
+    /// [`metadata::preview_write`].
+    pub fn apply(&self) -> anyhow::Result<WriteResult> {
+        let mut track = IdentifiedTrack::default();
+        for change in &self.changes {
+            let value = change.new_value.clone();
+            match change.field.as_str() {
+                "title" => track.title = Some(value),
+                "artist" => track.artist = Some(value),
+                "album_artist" => track.album_artist = Some(value),
+                "album" => track.album = Some(value),
+                "track_number" => track.track_number = value.parse().ok(),
+                "total_tracks" => track.total_tracks = value.parse().ok(),
+                "year" => track.year = value.parse().ok(),
+                "disc_number" => track.disc_number = value.parse().ok(),
+                "total_discs" => track.total_discs = value.parse().ok(),
+                // Already mapped when previewed
+                "genre" => {
+                    track.genres = value.split(genres::SEPARATOR).map(str::to_string).collect()
+                }
+                "language" => track.language = Some(value),
+                "explicit" => track.explicit = Some(value == content::advisory_value(true)),
+                "musicbrainz_recording_id" => track.recording_id = Some(value),
+                "musicbrainz_artist_id" => track.artist_id = Some(value),
+                "musicbrainz_release_id" => track.release_id = Some(value),
+                "musicbrainz_release_group_id" => track.release_group_id = Some(value),
+                "acoustid_id" => track.acoustid_id = Some(value),
+                "acoustid_fingerprint" => track.fingerprint = Some(value),
+                other => tracing::warn!("Ignoring unknown plan field '{}'", other),
+            }
+        }
+
+        // Only the IDs the plan lists are set, so this writes no others
+        let options = WriteOptions2 {
+            only_fill_empty: false,
+            write_musicbrainz_ids: true,
+            ..Default::default()
+        };
+        metadata::write(&self.path, &track, &options)
+    }
+}
+
+/// Why a plan entry no longer matches the file system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DriftReason {
+    /// The source file no longer exists
+    Missing,
+    /// The source file's size or modification time changed
+    Modified,
+    /// Another file now occupies the planned destination
+    DestinationExists(PathBuf),
+}
+
+impl fmt::Display for DriftReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriftReason::Missing => write!(f, "file is missing"),
+            DriftReason::Modified => write!(f, "file changed since the plan was made"),
+            DriftReason::DestinationExists(dest) => {
+                write!(f, "destination already exists: {}", dest.display())
+            }
+        }
+    }
+}
+
+/// A plan entry that drifted from the file system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanDrift {
+    pub path: PathBuf,
+    pub reason: DriftReason,
+}
+
+impl fmt::Display for PlanDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.reason)
+    }
+}
+
+/// A complete dry-run plan.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationPlan {
+    pub version: u32,
+    pub kind: PlanKind,
+    pub created_at: String,
+    /// SHA256 of the plan entries (see [`OperationPlan::compute_hash`])
+    pub hash: String,
+    #[serde(default)]
+    pub moves: Vec<PlannedMove>,
+    #[serde(default)]
+    pub tag_edits: Vec<PlannedTagEdit>,
+}
+
+impl OperationPlan {
+    fn new(kind: PlanKind, moves: Vec<PlannedMove>, tag_edits: Vec<PlannedTagEdit>) -> Self {
+        let mut plan = Self {
+            version: PLAN_VERSION,
+            kind,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            hash: String::new(),
+            moves,
+            tag_edits,
+        };
+        plan.hash = plan.compute_hash();
+        plan
+    }
+
+    /// Build an organize plan from previews.
+    ///
+    /// Previews whose destination equals the source are skipped. Reads file
+    /// metadata for stamping, so call from a blocking context.
+    pub fn organize(previews: &[OrganizePreview]) -> Self {
+        let moves = previews
+            .iter()
+            .filter(|p| p.source != p.destination)
+            .map(|p| PlannedMove {
+                track_id: p.track_id,
+                source: p.source.clone(),
+                destination: p.destination.clone(),
+                stamp: FileStamp::of(&p.source),
+            })
+            .collect();
+        Self::new(PlanKind::Organize, moves, Vec::new())
+    }
+
+    /// Build an enrichment plan from tag edits.
+    ///
+    /// Edits without any changes are dropped.
+    pub fn enrich(tag_edits: Vec<PlannedTagEdit>) -> Self {
+        let tag_edits = tag_edits
+            .into_iter()
+            .filter(|e| !e.changes.is_empty())
+            .collect();
+        Self::new(PlanKind::Enrich, Vec::new(), tag_edits)
+    }
+
+    /// Drop the moves of the given source files, re-hashing the plan.
+    pub fn without_moves<'a>(mut self, sources: impl IntoIterator<Item = &'a PathBuf>) -> Self {
+        let sources: HashSet<&PathBuf> = sources.into_iter().collect();
+        self.moves.retain(|m| !sources.contains(&m.source));
+        self.hash = self.compute_hash();
+        self
+    }
+
+    /// Number of entries (moves + tag edits) in the plan.
+    pub fn len(&self) -> usize {
+        self.moves.len() + self.tag_edits.len()
+    }
+
+    /// Whether the plan has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hash the plan contents (everything except `hash` and `created_at`).
+    pub fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.version.to_le_bytes());
+        hasher.update(self.kind.to_string().as_bytes());
+        // Serializing plain structs of strings/ints cannot fail
+        hasher.update(serde_json::to_vec(&self.moves).unwrap_or_default());
+        hasher.update(serde_json::to_vec(&self.tag_edits).unwrap_or_default());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Verify that the stored hash matches the plan contents.
+    pub fn verify_hash(&self) -> Result<(), PlanError> {
+        if self.compute_hash() == self.hash {
+            Ok(())
+        } else {
+            Err(PlanError::HashMismatch)
+        }
+    }
+
+    /// Compare the plan against the file system.
+    ///
+    /// Returns an entry for every file that changed since planning. An empty
+    /// result means the plan can be executed exactly as previewed.
+    pub fn detect_drift(&self) -> Vec<PlanDrift> {
+        let mut drift = Vec::new();
+
+        let mut check_stamp = |path: &Path, stamp: Option<FileStamp>| match FileStamp::of(path) {
+            None => {
+                drift.push(PlanDrift {
+                    path: path.to_path_buf(),
+                    reason: DriftReason::Missing,
+                });
+                false
+            }
+            Some(now) if stamp.is_some_and(|s| s != now) => {
+                drift.push(PlanDrift {
+                    path: path.to_path_buf(),
+                    reason: DriftReason::Modified,
+                });
+                false
+            }
+            Some(_) => true,
+        };
+
+        let mut blocked = Vec::new();
+        for m in &self.moves {
+            if check_stamp(&m.source, m.stamp) && m.destination.exists() {
+                blocked.push(PlanDrift {
+                    path: m.source.clone(),
+                    reason: DriftReason::DestinationExists(m.destination.clone()),
+                });
+            }
+        }
+        for edit in &self.tag_edits {
+            check_stamp(&edit.path, edit.stamp);
+        }
+
+        drift.extend(blocked);
+        drift
+    }
+
+    /// Organize previews for the planned moves.
+    pub fn to_previews(&self) -> Vec<OrganizePreview> {
+        self.moves
+            .iter()
+            .map(|m| OrganizePreview {
+                source: m.source.clone(),
+                destination: m.destination.clone(),
+                track_id: m.track_id,
+            })
+            .collect()
+    }
+
+    /// Serialize the plan as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, PlanError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Render the plan as CSV (one row per move or field change).
+    ///
+    /// CSV exports are for review only; use JSON to execute a plan later.
+    pub fn to_csv(&self) -> String {
+        let mut out =
+            String::from("action,track_id,path,destination,field,current_value,new_value\n");
+        for m in &self.moves {
+            push_csv_row(
+                &mut out,
+                &[
+                    "move",
+                    &m.track_id.to_string(),
+                    &m.source.to_string_lossy(),
+                    &m.destination.to_string_lossy(),
+                    "",
+                    "",
+                    "",
+                ],
+            );
+        }
+        for edit in &self.tag_edits {
+            let path = edit.path.to_string_lossy();
+            for change in &edit.changes {
+                push_csv_row(
+                    &mut out,
+                    &[
+                        "tag",
+                        "",
+                        &path,
+                        "",
+                        &change.field,
+                        &change.current_value,
+                        &change.new_value,
+                    ],
+                );
+            }
+        }
+        out
+    }
+
+    /// Save the plan, choosing CSV or JSON from the file extension.
+    pub fn save(&self, path: &Path) -> Result<(), PlanError> {
+        let is_csv = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        let contents = if is_csv {
+            self.to_csv()
+        } else {
+            self.to_json()?
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Load a JSON plan, checking its version and hash.
+    pub fn load(path: &Path) -> Result<Self, PlanError> {
+        let contents = fs::read_to_string(path)?;
+        let plan: Self = serde_json::from_str(&contents)?;
+        if plan.version != PLAN_VERSION {
+            return Err(PlanError::UnsupportedVersion(plan.version));
+        }
+        plan.verify_hash()?;
+        Ok(plan)
+    }
+}
+
+/// Append a CSV row, quoting fields that need it.
+pub(crate) fn push_csv_row(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{AudioFixture, write_audio_fixture};
+    use tempfile::tempdir;
+
+    fn preview(source: PathBuf, destination: PathBuf, track_id: i64) -> OrganizePreview {
+        OrganizePreview {
+            source,
+            destination,
+            track_id,
+        }
+    }
+
+    #[test]
+    fn test_organize_plan_skips_noop_moves() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.mp3");
+        fs::write(&a, b"audio").unwrap();
+
+        let plan = OperationPlan::organize(&[
+            preview(a.clone(), dir.path().join("out/a.mp3"), 1),
+            preview(a.clone(), a.clone(), 2),
+        ]);
+
+        assert_eq!(plan.kind, PlanKind::Organize);
+        assert_eq!(plan.moves.len(), 1);
+        assert_eq!(plan.moves[0].stamp.unwrap().size, 5);
+        assert!(plan.verify_hash().is_ok());
+
+        let trimmed = plan.clone().without_moves(&[a]);
+        assert!(trimmed.moves.is_empty());
+        assert!(trimmed.verify_hash().is_ok());
+        assert_ne!(trimmed.hash, plan.hash);
+    }
+
+    #[test]
+    fn test_plan_roundtrip_and_tamper_detection() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.mp3");
+        fs::write(&a, b"audio").unwrap();
+        let plan = OperationPlan::organize(&[preview(a, dir.path().join("b.mp3"), 1)]);
+
+        let plan_path = dir.path().join("plan.json");
+        plan.save(&plan_path).unwrap();
+        let loaded = OperationPlan::load(&plan_path).unwrap();
+        assert_eq!(loaded, plan);
+
+        // Redirect the move by hand; the hash must no longer match
+        let tampered = fs::read_to_string(&plan_path)
+            .unwrap()
+            .replace("b.mp3", "evil.mp3");
+        fs::write(&plan_path, tampered).unwrap();
+        assert!(matches!(
+            OperationPlan::load(&plan_path),
+            Err(PlanError::HashMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_detect_drift() {
+        let dir = tempdir().unwrap();
+        let unchanged = dir.path().join("unchanged.mp3");
+        let modified = dir.path().join("modified.mp3");
+        let missing = dir.path().join("missing.mp3");
+        let blocked = dir.path().join("blocked.mp3");
+        let occupied = dir.path().join("occupied.mp3");
+        for p in [&unchanged, &modified, &missing, &blocked, &occupied] {
+            fs::write(p, b"audio").unwrap();
+        }
+
+        let plan = OperationPlan::organize(&[
+            preview(unchanged.clone(), dir.path().join("out/1.mp3"), 1),
+            preview(modified.clone(), dir.path().join("out/2.mp3"), 2),
+            preview(missing.clone(), dir.path().join("out/3.mp3"), 3),
+            preview(blocked.clone(), occupied.clone(), 4),
+        ]);
+
+        fs::write(&modified, b"re-tagged audio").unwrap();
+        fs::remove_file(&missing).unwrap();
+
+        let drift = plan.detect_drift();
+        assert_eq!(drift.len(), 3);
+        assert!(drift.contains(&PlanDrift {
+            path: modified,
+            reason: DriftReason::Modified
+        }));
+        assert!(drift.contains(&PlanDrift {
+            path: missing,
+            reason: DriftReason::Missing
+        }));
+        assert!(drift.contains(&PlanDrift {
+            path: blocked,
+            reason: DriftReason::DestinationExists(occupied)
+        }));
+    }
+
+    #[test]
+    fn test_enrich_plan_csv_export() {
+        let edit = PlannedTagEdit {
+            path: PathBuf::from("/music/song.flac"),
+            stamp: None,
+            changes: vec![FieldChange {
+                field: "title".to_string(),
+                current_value: "Track 01".to_string(),
+                new_value: "Hello, \"World\"".to_string(),
+            }],
+        };
+        let empty = PlannedTagEdit {
+            path: PathBuf::from("/music/other.flac"),
+            stamp: None,
+            changes: vec![],
+        };
+        let plan = OperationPlan::enrich(vec![edit, empty]);
+        assert_eq!(plan.len(), 1);
+
+        let csv = plan.to_csv();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1],
+            "tag,,/music/song.flac,,title,Track 01,\"Hello, \"\"World\"\"\""
+        );
+    }
+
+    #[test]
+    fn test_tag_edit_writes_everything_previewed() {
+        let dir = tempdir().unwrap();
+        let path = write_audio_fixture(dir.path(), AudioFixture::Flac);
+        let track = IdentifiedTrack {
+            title: Some("Song".into()),
+            artist: Some("Artist".into()),
+            album_artist: Some("Various Artists".into()),
+            album: Some("Album".into()),
+            track_number: Some(3),
+            total_tracks: Some(12),
+            disc_number: Some(1),
+            total_discs: Some(2),
+            year: Some(1999),
+            genres: vec!["Rock".into(), "Pop".into()],
+            language: Some("eng".into()),
+            explicit: Some(true),
+            recording_id: Some("rec-1".into()),
+            artist_id: Some("artist-1".into()),
+            release_id: Some("release-1".into()),
+            release_group_id: Some("group-1".into()),
+            acoustid_id: Some("acoustid-1".into()),
+            fingerprint: Some("AQAAfingerprint".into()),
+            ..Default::default()
+        };
+        let options = WriteOptions2 {
+            write_musicbrainz_ids: true,
+            ..Default::default()
+        };
+        let preview = metadata::preview_write(&path, &track, &options).unwrap();
+        let planned: Vec<&str> = preview.changes.iter().map(|c| c.field.as_str()).collect();
+
+        let result = PlannedTagEdit::new(path.clone(), preview.changes.clone())
+            .apply()
+            .unwrap();
+        let mut written = result.fields_written.clone();
+        let mut planned_sorted = planned.clone();
+        written.sort_unstable();
+        planned_sorted.sort_unstable();
+        assert_eq!(written, planned_sorted, "writes exactly what was previewed");
+
+        let full = metadata::read_full(&path).unwrap();
+        assert_eq!(full.album_artist.as_deref(), Some("Various Artists"));
+        assert_eq!(full.total_tracks, Some(12));
+        assert_eq!(full.total_discs, Some(2));
+        assert_eq!(full.genres, vec!["Rock", "Pop"]);
+        assert_eq!(full.language.as_deref(), Some("eng"));
+        assert_eq!(full.explicit, Some(true));
+        assert_eq!(full.musicbrainz_recording_id.as_deref(), Some("rec-1"));
+        assert_eq!(
+            full.musicbrainz_release_group_id.as_deref(),
+            Some("group-1")
+        );
+        assert_eq!(full.acoustid.id.as_deref(), Some("acoustid-1"));
+        assert_eq!(
+            full.acoustid.fingerprint.as_deref(),
+            Some("AQAAfingerprint")
+        );
+    }
+}
@@ -2,7 +2,12 @@
 //!
 //! This module runs the real-time audio thread that:
 //! - Reads decoded audio from a lock-free ring buffer
-//! - Applies volume using atomic state
+//! - Applies volume and loudness normalization using atomic state (see
+//!   [`super::normalization`]), and fades on pause, stop and seek (see
+//!   [`super::fade`])
+//! - Crossfades into the next track, decoding both at once (see
+//!   [`super::crossfade`]), and equalizes what the decoder thread decodes
+//!   before it reaches the ring buffer (see [`super::equalizer`])
 //! - Sends samples to the FFT analyzer
 //! - Outputs to the audio device
 //!
@@ -12,11 +17,23 @@
 //! - No locks (RwLock/Mutex) - use atomics via `AudioSharedState`
 //! - No allocations - use `rtrb` ring buffer for sample data
 //! - No blocking operations
+//!
+//! # Buffering
+//!
+//! The ring buffer holds up to `buffering::MAX_AHEAD_MS` of audio; the
+//! decoder thread keeps it filled to the decode-ahead its
+//! [`BufferTuner`] picks, which grows after underruns on Auto.
+//!
+//! # Headless Output
+//!
+//! With the `headless-audio` feature, [`AudioOutput::headless`] runs the same
+//! decoder thread but drains the ring buffer from a plain thread at real time
+//! (or faster) instead of a sound card, so the player can be tested in CI.
 
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, SampleFormat, Stream, StreamConfig};
@@ -25,13 +42,19 @@ use parking_lot::RwLock;
 use rtrb::{Consumer, Producer, RingBuffer};
 
 use super::PlayerError;
+use super::buffering::{self, BufferTuner};
+use super::crossfade::Crossfade;
 use super::decoder::AudioDecoder;
+use super::equalizer::Equalizer;
+use super::fade::Fader;
+use super::normalization;
 use super::resampler::Resampler;
 use super::simd;
 use super::state::{
     AudioQuality, AudioSharedState, PlaybackStatus, PlayerCommand, PlayerEvent, PlayerState,
 };
 use super::visualization::SpectrumData;
+use crate::metadata::Loudness;
 
 /// Audio output configuration.
 #[derive(Debug, Clone)]
@@ -56,8 +79,11 @@ impl Default for AudioConfig {
 
 /// Audio output manager.
 pub struct AudioOutput {
-    _stream: Stream,
-    _audio_thread: JoinHandle<()>,
+    /// Device stream (`None` for headless output)
+    _stream: Option<Stream>,
+    /// Thread consuming samples in place of a device (headless output)
+    sink_thread: Option<JoinHandle<()>>,
+    audio_thread: Option<JoinHandle<()>>,
     /// Lock-free shared state for the audio callback
     pub audio_shared: Arc<AudioSharedState>,
 }
@@ -98,40 +124,11 @@ impl AudioOutput {
             buffer_size: cpal::BufferSize::Default,
         };
 
-        // Create lock-free ring buffer for audio samples
-        // Size: ~0.5 seconds of stereo audio at 48kHz = 48000 * 2 * 0.5 = 48000 samples
-        let (producer, consumer) = RingBuffer::<f32>::new(48000);
-
-        // Create lock-free shared state for the audio callback
-        let audio_shared = AudioSharedState::new();
-
-        // Initialize from UI state
-        {
-            let ui_state = state.read();
-            audio_shared.set_volume(ui_state.volume);
-            audio_shared.set_playing(ui_state.status == PlaybackStatus::Playing);
-        }
-
-        // Clone state for the audio thread
-        let state_for_thread = Arc::clone(&state);
-        let audio_shared_for_thread = Arc::clone(&audio_shared);
-
-        // Start the audio/decoder thread
-        let audio_thread = thread::Builder::new()
-            .name("audio-decoder".to_string())
-            .spawn(move || {
-                audio_thread_main(
-                    state_for_thread,
-                    audio_shared_for_thread,
-                    command_rx,
-                    event_tx,
-                    producer,
-                    viz_tx,
-                    sample_rate,
-                    channels,
-                );
-            })
-            .map_err(|e| PlayerError::AudioInit(e.to_string()))?;
+        let DecoderThread {
+            handle: audio_thread,
+            consumer,
+            audio_shared,
+        } = spawn_audio_thread(state, command_rx, event_tx, viz_tx, sample_rate, channels)?;
 
         // Clone audio shared state for the callback
         let callback_audio_shared = Arc::clone(&audio_shared);
@@ -159,12 +156,72 @@ impl AudioOutput {
             .map_err(|e| PlayerError::AudioInit(e.to_string()))?;
 
         Ok(Self {
-            _stream: stream,
-            _audio_thread: audio_thread,
+            _stream: Some(stream),
+            sink_thread: None,
+            audio_thread: Some(audio_thread),
             audio_shared,
         })
     }
 
+    /// Create an output that plays into nothing, consuming samples at
+    /// `speed` times real time (`config.sample_rate` 0 means 48 kHz).
+    #[cfg(feature = "headless-audio")]
+    pub fn headless(
+        state: Arc<RwLock<PlayerState>>,
+        command_rx: Receiver<PlayerCommand>,
+        event_tx: Sender<PlayerEvent>,
+        viz_tx: Sender<SpectrumData>,
+        config: &AudioConfig,
+        speed: f32,
+    ) -> Result<Self, PlayerError> {
+        let sample_rate = if config.sample_rate == 0 {
+            48000
+        } else {
+            config.sample_rate
+        };
+        let channels = config.channels.max(1);
+
+        let DecoderThread {
+            handle: audio_thread,
+            consumer,
+            audio_shared,
+        } = spawn_audio_thread(state, command_rx, event_tx, viz_tx, sample_rate, channels)?;
+
+        let sink_shared = Arc::clone(&audio_shared);
+        let sink_thread = thread::Builder::new()
+            .name("audio-headless".to_string())
+            .spawn(move || headless_sink(consumer, sink_shared, sample_rate, channels, speed))
+            .map_err(|e| PlayerError::AudioInit(e.to_string()))?;
+
+        Ok(Self {
+            _stream: None,
+            sink_thread: Some(sink_thread),
+            audio_thread: Some(audio_thread),
+            audio_shared,
+        })
+    }
+
+    /// Wait for the decoder thread (and the headless sink) to exit, once it
+    /// has been sent `PlayerCommand::Shutdown`. False if it was still running
+    /// at `timeout`; it is then left to end with the process.
+    pub fn join(&mut self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        for slot in [&mut self.audio_thread, &mut self.sink_thread] {
+            let Some(handle) = slot.take() else {
+                continue;
+            };
+            while !handle.is_finished() {
+                if Instant::now() >= deadline {
+                    *slot = Some(handle);
+                    return false;
+                }
+                thread::sleep(Duration::from_millis(5));
+            }
+            let _ = handle.join();
+        }
+        true
+    }
+
     /// Select the best audio device - prefer headphones if available.
     fn select_best_device(host: &cpal::Host) -> Result<Device, PlayerError> {
         let devices: Vec<Device> = host
@@ -207,6 +264,208 @@ impl AudioOutput {
     }
 }
 
+/// A running decoder thread and what the output needs from it
+struct DecoderThread {
+    handle: JoinHandle<()>,
+    /// Read end of the ring buffer the thread fills
+    consumer: Consumer<f32>,
+    audio_shared: Arc<AudioSharedState>,
+}
+
+/// Create the ring buffer and shared state and start the decoder thread.
+fn spawn_audio_thread(
+    state: Arc<RwLock<PlayerState>>,
+    command_rx: Receiver<PlayerCommand>,
+    event_tx: Sender<PlayerEvent>,
+    viz_tx: Sender<SpectrumData>,
+    sample_rate: u32,
+    channels: u16,
+) -> Result<DecoderThread, PlayerError> {
+    // Create lock-free ring buffer for audio samples, sized for the largest
+    // decode-ahead (how much of it is used is tuned by the decoder thread)
+    let capacity = buffering::samples_for(buffering::MAX_AHEAD_MS, sample_rate, channels);
+    let (producer, consumer) = RingBuffer::<f32>::new(capacity);
+
+    // Create lock-free shared state for the audio callback
+    let audio_shared = AudioSharedState::new();
+
+    // Initialize from UI state
+    {
+        let ui_state = state.read();
+        audio_shared.set_volume(ui_state.volume);
+        audio_shared.set_playing(ui_state.status == PlaybackStatus::Playing);
+    }
+
+    let audio_shared_for_thread = Arc::clone(&audio_shared);
+
+    // Start the audio/decoder thread
+    let audio_thread = thread::Builder::new()
+        .name("audio-decoder".to_string())
+        .spawn(move || {
+            audio_thread_main(
+                state,
+                audio_shared_for_thread,
+                command_rx,
+                event_tx,
+                producer,
+                viz_tx,
+                sample_rate,
+                channels,
+            );
+        })
+        .map_err(|e| PlayerError::AudioInit(e.to_string()))?;
+
+    Ok(DecoderThread {
+        handle: audio_thread,
+        consumer,
+        audio_shared,
+    })
+}
+
+/// Drain the ring buffer like a device would, until the decoder thread exits.
+#[cfg(feature = "headless-audio")]
+fn headless_sink(
+    mut consumer: Consumer<f32>,
+    audio_shared: Arc<AudioSharedState>,
+    sample_rate: u32,
+    channels: u16,
+    speed: f32,
+) {
+    const TICK: Duration = Duration::from_millis(10);
+    let per_tick = ((sample_rate as f32 * channels as f32 * TICK.as_secs_f32() * speed.max(0.01))
+        as usize)
+        .max(usize::from(channels));
+    let capacity = consumer.buffer().capacity();
+
+    while !consumer.is_abandoned() {
+        thread::sleep(TICK);
+        if audio_shared.is_flushing() {
+            while consumer.pop().is_ok() {}
+            continue;
+        }
+        if audio_shared.is_discarding() {
+            while consumer.pop().is_ok() {}
+            audio_shared.end_discard();
+            continue;
+        }
+        if !audio_shared.is_playing() {
+            continue;
+        }
+        let count = consumer.slots().min(per_tick);
+        if let Ok(chunk) = consumer.read_chunk(count) {
+            // The first slice starts at the read position, on a frame
+            let (first, _) = chunk.as_slices();
+            audio_shared.record_levels(&simd::channel_levels(first, usize::from(channels)));
+            chunk.commit_all();
+        }
+        audio_shared.record_callback(count as u32, 0);
+        let fill = (consumer.slots() * 100 / capacity) as u32;
+        audio_shared.set_buffer_fill(fill);
+    }
+}
+
+/// Room reserved for the callback's buffer, in samples; it only grows (and
+/// allocates) if the device asks for more in one callback
+const CALLBACK_SCRATCH_SAMPLES: usize = 16384;
+
+/// Fill `out` with the `len` samples the output callback plays next, faded,
+/// before volume. Returns the samples taken from the ring, or `None` when
+/// nothing is playing (flushing, or paused and faded out).
+///
+/// # Real-time Safety
+/// Only atomics and the ring buffer; `out` is reused between callbacks.
+fn next_samples(
+    consumer: &mut Consumer<f32>,
+    audio_shared: &AudioSharedState,
+    fader: &mut Fader,
+    out: &mut Vec<f32>,
+    len: usize,
+    channels: usize,
+) -> Option<u32> {
+    out.clear();
+    fader.set_length(audio_shared.fade_ms());
+    // Whole frames only, so a short read doesn't shift the channels
+    let frames = |n: usize| n - n % channels.max(1);
+
+    // When flushing, drain the buffer but output silence
+    // This clears stale audio when loading a new track
+    if audio_shared.is_flushing() {
+        while consumer.pop().is_ok() {}
+        audio_shared.clear_levels();
+        // The new track starts as recorded
+        fader.reset();
+        out.resize(len, 0.0);
+        return None;
+    }
+
+    let is_playing = audio_shared.is_playing();
+
+    // Seek: fade out what was buffered before it, then drop the rest
+    if audio_shared.is_discarding() {
+        let mut taken = 0;
+        if is_playing && !fader.is_silent() {
+            taken = frames(consumer.slots().min(len));
+            if let Ok(chunk) = consumer.read_chunk(taken) {
+                let (first, second) = chunk.as_slices();
+                out.extend_from_slice(first);
+                out.extend_from_slice(second);
+                chunk.commit_all();
+            }
+            fader.apply(out, 0.0);
+        }
+        if !is_playing || fader.is_silent() || taken < frames(len) {
+            while consumer.pop().is_ok() {}
+            fader.silence();
+            audio_shared.end_discard();
+        }
+        out.resize(len, 0.0);
+        return Some(taken as u32);
+    }
+
+    if !is_playing {
+        audio_shared.clear_levels();
+        if !fader.is_silent() {
+            // Fade out on what comes next without consuming it, so resuming
+            // plays it again
+            let ahead = fader.read_ahead;
+            let want = ahead + frames(consumer.slots().saturating_sub(ahead).min(len));
+            if let Ok(chunk) = consumer.read_chunk(want) {
+                let (first, second) = chunk.as_slices();
+                out.extend(first.iter().chain(second).skip(ahead));
+                chunk.commit(0);
+            }
+            fader.read_ahead += out.len();
+            fader.apply(out, 0.0);
+            if out.len() < len {
+                // Ran out of buffered audio before the fade did
+                fader.silence();
+            }
+        }
+        out.resize(len, 0.0);
+        return None;
+    }
+
+    // Playing: resume from the ring's read position, fading in if silent
+    fader.read_ahead = 0;
+    let available = frames(consumer.slots().min(len));
+    if let Ok(chunk) = consumer.read_chunk(available) {
+        let (first, second) = chunk.as_slices();
+        out.extend_from_slice(first);
+        out.extend_from_slice(second);
+        chunk.commit_all();
+    }
+    if !out.is_empty() {
+        // Meter the track's own level, before fades and volume
+        audio_shared.record_levels(&simd::channel_levels(out, channels));
+    }
+    fader.apply(out, 1.0);
+    for _ in out.len()..len {
+        audio_shared.increment_underruns();
+    }
+    out.resize(len, 0.0);
+    Some(available as u32)
+}
+
 /// Build output stream for f32 format.
 ///
 /// # Real-time Safety
@@ -223,6 +482,9 @@ where
     T: cpal::SizedSample + cpal::FromSample<f32>,
 {
     let buffer_capacity = consumer.buffer().capacity();
+    let channels = usize::from(config.channels);
+    let mut fader = Fader::new(config.sample_rate.0, config.channels);
+    let mut scratch: Vec<f32> = Vec::with_capacity(CALLBACK_SCRATCH_SAMPLES);
 
     device.build_output_stream(
         config,
@@ -230,88 +492,33 @@ where
             let start = std::time::Instant::now();
 
             // ✅ SAFE: Atomic reads - no locks in the audio callback
-            let volume = audio_shared.volume();
-            let is_playing = audio_shared.is_playing();
-            let is_flushing = audio_shared.is_flushing();
-
-            // When flushing, drain the buffer but output silence
-            // This clears stale audio when loading a new track
-            if is_flushing {
-                // Drain all available samples from the buffer
-                while consumer.pop().is_ok() {}
-                // Output silence
-                for sample in data.iter_mut() {
-                    *sample = T::from_sample(0.0f32);
+            let volume = audio_shared.volume() * audio_shared.replay_gain();
+            let samples_read = next_samples(
+                &mut consumer,
+                &audio_shared,
+                &mut fader,
+                &mut scratch,
+                data.len(),
+                channels,
+            );
+
+            // ✅ SIMD OPTIMIZATION: Vectorized volume scaling (in-place)
+            simd::apply_volume(&mut scratch, volume);
+            if volume > 1.0 {
+                // Normalization boosted a track without a known peak
+                for s in scratch.iter_mut() {
+                    *s = s.clamp(-1.0, 1.0);
                 }
-                return;
             }
 
-            if !is_playing {
-                // Output silence when paused
-                for sample in data.iter_mut() {
-                    *sample = T::from_sample(0.0f32);
-                }
-                return;
+            // Copy to output with sample type conversion
+            for (out, &s) in data.iter_mut().zip(scratch.iter()) {
+                *out = T::from_sample(s);
             }
 
-            let mut samples_read = 0u32;
-            let buffer_len = data.len();
-
-            // ✅ SIMD OPTIMIZATION: Batch read + vectorized volume scaling
-            // First, try to read all samples we need at once
-            let chunk = consumer.read_chunk(buffer_len);
-            match chunk {
-                Ok(chunk) => {
-                    let (first, second) = chunk.as_slices();
-                    let total_available = first.len() + second.len();
-
-                    if total_available >= buffer_len {
-                        // We have enough samples - use batch processing
-                        // Copy and apply volume using SIMD
-                        let mut temp_buffer: Vec<f32> = Vec::with_capacity(buffer_len);
-                        temp_buffer.extend_from_slice(&first[..first.len().min(buffer_len)]);
-                        if temp_buffer.len() < buffer_len {
-                            let remaining = buffer_len - temp_buffer.len();
-                            temp_buffer.extend_from_slice(&second[..remaining.min(second.len())]);
-                        }
-
-                        // Apply volume with SIMD (in-place)
-                        simd::apply_volume(&mut temp_buffer, volume);
-
-                        // Copy to output with sample type conversion
-                        for (out, &s) in data.iter_mut().zip(temp_buffer.iter()) {
-                            *out = T::from_sample(s);
-                        }
-
-                        samples_read = temp_buffer.len() as u32;
-                        // Commit the read
-                        chunk.commit(samples_read as usize);
-                    } else {
-                        // Not enough samples - partial fill + underrun
-                        chunk.commit(0); // Don't consume anything
-                        // Fall back to sample-by-sample for partial buffer
-                        for sample in data.iter_mut() {
-                            match consumer.pop() {
-                                Ok(s) => {
-                                    *sample = T::from_sample(s * volume);
-                                    samples_read += 1;
-                                }
-                                Err(_) => {
-                                    audio_shared.increment_underruns();
-                                    *sample = T::from_sample(0.0f32);
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(_) => {
-                    // Ring buffer empty - output silence
-                    for sample in data.iter_mut() {
-                        audio_shared.increment_underruns();
-                        *sample = T::from_sample(0.0f32);
-                    }
-                }
-            }
+            let Some(samples_read) = samples_read else {
+                return;
+            };
 
             // Update performance metrics (still lock-free)
             let elapsed_us = start.elapsed().as_micros() as u32;
@@ -342,6 +549,9 @@ fn build_stream_i16(
     audio_shared: Arc<AudioSharedState>,
 ) -> Result<Stream, cpal::BuildStreamError> {
     let buffer_capacity = consumer.buffer().capacity();
+    let channels = usize::from(config.channels);
+    let mut fader = Fader::new(config.sample_rate.0, config.channels);
+    let mut scratch: Vec<f32> = Vec::with_capacity(CALLBACK_SCRATCH_SAMPLES);
 
     device.build_output_stream(
         config,
@@ -349,76 +559,23 @@ fn build_stream_i16(
             let start = std::time::Instant::now();
 
             // ✅ SAFE: Atomic reads - no locks in the audio callback
-            let volume = audio_shared.volume();
-            let is_playing = audio_shared.is_playing();
-            let is_flushing = audio_shared.is_flushing();
-
-            // When flushing, drain the buffer but output silence
-            // This clears stale audio when loading a new track
-            if is_flushing {
-                while consumer.pop().is_ok() {}
-                for sample in data.iter_mut() {
-                    *sample = 0;
-                }
-                return;
-            }
-
-            if !is_playing {
-                for sample in data.iter_mut() {
-                    *sample = 0;
-                }
+            let volume = audio_shared.volume() * audio_shared.replay_gain();
+            let samples_read = next_samples(
+                &mut consumer,
+                &audio_shared,
+                &mut fader,
+                &mut scratch,
+                data.len(),
+                channels,
+            );
+
+            // ✅ SIMD OPTIMIZATION: Convert f32→i16 with volume (combined operation;
+            // clamps, so boosted samples can't wrap)
+            simd::f32_to_i16_with_volume(&scratch, data, volume);
+
+            let Some(samples_read) = samples_read else {
                 return;
-            }
-
-            let mut samples_read = 0u32;
-            let buffer_len = data.len();
-
-            // ✅ SIMD OPTIMIZATION: Batch read + vectorized f32→i16 conversion
-            let chunk = consumer.read_chunk(buffer_len);
-            match chunk {
-                Ok(chunk) => {
-                    let (first, second) = chunk.as_slices();
-                    let total_available = first.len() + second.len();
-
-                    if total_available >= buffer_len {
-                        // We have enough samples - use batch SIMD processing
-                        let mut temp_buffer: Vec<f32> = Vec::with_capacity(buffer_len);
-                        temp_buffer.extend_from_slice(&first[..first.len().min(buffer_len)]);
-                        if temp_buffer.len() < buffer_len {
-                            let remaining = buffer_len - temp_buffer.len();
-                            temp_buffer.extend_from_slice(&second[..remaining.min(second.len())]);
-                        }
-
-                        // Convert f32→i16 with volume using SIMD (combined operation)
-                        simd::f32_to_i16_with_volume(&temp_buffer, data, volume);
-
-                        samples_read = temp_buffer.len() as u32;
-                        chunk.commit(samples_read as usize);
-                    } else {
-                        // Not enough samples - partial fill
-                        chunk.commit(0);
-                        for sample in data.iter_mut() {
-                            match consumer.pop() {
-                                Ok(s) => {
-                                    *sample = (s * volume * 32767.0) as i16;
-                                    samples_read += 1;
-                                }
-                                Err(_) => {
-                                    audio_shared.increment_underruns();
-                                    *sample = 0;
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(_) => {
-                    // Ring buffer empty - output silence
-                    for sample in data.iter_mut() {
-                        audio_shared.increment_underruns();
-                        *sample = 0;
-                    }
-                }
-            }
+            };
 
             // Update performance metrics
             let elapsed_us = start.elapsed().as_micros() as u32;
@@ -440,6 +597,18 @@ fn build_stream_i16(
 struct AudioThreadContext {
     decoder: Option<AudioDecoder>,
     resampler: Option<Resampler>,
+    /// How far the current track has been decoded
+    decoded_to: Duration,
+    /// The track fading out while the current one fades in
+    outgoing: Option<Outgoing>,
+    /// Track to crossfade into once the current one nears its end
+    next_path: Option<PathBuf>,
+    /// ReplayGain values of the current track
+    loudness: Loudness,
+    /// Runs over everything decoded, before the ring buffer
+    equalizer: Equalizer,
+    /// `AudioSharedState::eq_generation` the equalizer's gains are from
+    eq_generation: u32,
     visualizer: super::visualization::Visualizer,
     pending_path: Option<PathBuf>,
     /// Event sender to notify UI of state changes
@@ -452,16 +621,41 @@ struct AudioThreadContext {
     samples_per_position_update: usize,
     /// Sample counter for position updates
     sample_counter: usize,
+    /// Picks how far ahead to decode
+    tuner: BufferTuner,
+    /// Shared state, for counting events dropped on a full channel
+    audio_shared: Arc<AudioSharedState>,
+}
+
+/// The second decoder slot: a track fading out under the next one
+struct Outgoing {
+    decoder: AudioDecoder,
+    resampler: Option<Resampler>,
+    mix: Crossfade,
+    /// Its normalization gain relative to the incoming track's, which the
+    /// output applies to both
+    gain: f32,
 }
 
 impl AudioThreadContext {
-    fn new(output_sample_rate: u32, output_channels: u16, event_tx: Sender<PlayerEvent>) -> Self {
+    fn new(
+        output_sample_rate: u32,
+        output_channels: u16,
+        event_tx: Sender<PlayerEvent>,
+        audio_shared: Arc<AudioSharedState>,
+    ) -> Self {
         // Update position every ~50ms based on OUTPUT sample rate
         let samples_per_position_update =
             (output_sample_rate as usize * output_channels as usize) / 20;
         Self {
             decoder: None,
             resampler: None,
+            decoded_to: Duration::ZERO,
+            outgoing: None,
+            next_path: None,
+            loudness: Loudness::default(),
+            equalizer: Equalizer::new(output_sample_rate, output_channels),
+            eq_generation: 0,
             visualizer: super::visualization::Visualizer::new(2048),
             pending_path: None,
             event_tx,
@@ -469,14 +663,78 @@ impl AudioThreadContext {
             output_channels,
             samples_per_position_update,
             sample_counter: 0,
+            tuner: BufferTuner::new(buffering::AUTO, std::time::Instant::now()),
+            audio_shared,
         }
     }
 
-    /// Clear the ring buffer by writing silence.
-    ///
-    /// This prevents stale audio from playing when loading a new track.
-    /// We write silence rather than just discarding because the consumer
-    /// (audio callback) may still be reading.
+    /// Decode-ahead in interleaved output samples
+    fn decode_ahead_samples(&self) -> usize {
+        buffering::samples_for(
+            self.tuner.target_ms(),
+            self.output_sample_rate,
+            self.output_channels,
+        )
+    }
+
+    /// Apply the buffer setting and let the tuner react to underruns
+    fn tune_buffer(&mut self, audio_shared: &AudioSharedState) {
+        let setting = self.tuner.set_setting(audio_shared.buffer_setting());
+        let tuned = self
+            .tuner
+            .update(audio_shared.underruns(), std::time::Instant::now());
+        if let Some(change) = setting.or(tuned) {
+            tracing::info!(target: "player::buffer", "{}", change);
+            audio_shared.set_decode_ahead(change.to_ms);
+        }
+    }
+
+    /// Work out the normalization gain for the current track, mode and
+    /// preamp, and hand it to the output if it changed
+    fn update_normalization(&self, state: &RwLock<PlayerState>, audio_shared: &AudioSharedState) {
+        let gain = normalization::amplitude(
+            &self.loudness,
+            audio_shared.normalization_mode(),
+            audio_shared.preamp_db(),
+        );
+        if (gain - audio_shared.replay_gain()).abs() > 1e-4 {
+            tracing::debug!(
+                target: "player::normalization",
+                "Normalization gain {}",
+                normalization::label(gain)
+            );
+            audio_shared.set_replay_gain(gain);
+            state.write().normalization_gain = gain;
+        }
+    }
+
+    /// Pick up equalizer changes, then equalize `samples`
+    fn equalize(&mut self, samples: &mut [f32], audio_shared: &AudioSharedState) {
+        let generation = audio_shared.eq_generation();
+        if generation != self.eq_generation {
+            self.eq_generation = generation;
+            self.equalizer.set_gains(&audio_shared.eq_gains());
+        }
+        self.equalizer.process(samples);
+    }
+
+    /// Have the output fade out what's buffered and drop the rest, before a
+    /// seek or a new track. Waits for it (a little longer than the fade),
+    /// so nothing new is pushed behind the old audio. Nothing to do with
+    /// fades off.
+    fn discard_buffered(&self, audio_shared: &AudioSharedState) {
+        let fade_ms = audio_shared.fade_ms();
+        if fade_ms == 0 {
+            return;
+        }
+        audio_shared.start_discard();
+        let deadline = Instant::now() + Duration::from_millis(u64::from(fade_ms) + 100);
+        while audio_shared.is_discarding() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(2));
+        }
+        audio_shared.end_discard();
+    }
+
     /// Send an event to the UI. Ignores send failures (UI may have disconnected).
     fn emit(&self, event: PlayerEvent) {
         // Log the event being emitted with full context
@@ -506,6 +764,13 @@ impl AudioThreadContext {
             PlayerEvent::PlaybackFinished => {
                 tracing::debug!(target: "player::events", "Emitting PlaybackFinished event");
             }
+            PlayerEvent::CrossfadeStarted(path) => {
+                tracing::debug!(
+                    target: "player::events",
+                    path = ?path.file_name(),
+                    "Emitting CrossfadeStarted event"
+                );
+            }
             PlayerEvent::Error(err) => {
                 tracing::warn!(target: "player::events", error = %err, "Emitting Error event");
             }
@@ -515,6 +780,7 @@ impl AudioThreadContext {
         match self.event_tx.try_send(event) {
             Ok(()) => {}
             Err(crossbeam_channel::TrySendError::Full(_)) => {
+                self.audio_shared.increment_events_dropped();
                 tracing::warn!(
                     target: "player::events",
                     "Event channel full - UI may be falling behind"
@@ -570,6 +836,7 @@ impl AudioThreadContext {
                 audio_shared.set_playing(false);
                 self.decoder = None;
                 self.resampler = None;
+                self.outgoing = None;
                 self.emit(PlayerEvent::StatusChanged(PlaybackStatus::Stopped));
             }
             PlayerCommand::Seek(pos) => {
@@ -578,6 +845,11 @@ impl AudioThreadContext {
                     seek_fraction = pos,
                     "Processing Seek command"
                 );
+                if self.decoder.is_some() {
+                    self.discard_buffered(audio_shared);
+                }
+                // What was mixed is gone; the incoming track plays on alone
+                self.outgoing = None;
                 if let Some(ref mut dec) = self.decoder {
                     // Flush the ring buffer before seeking
                     // This prevents hearing stale audio after seek
@@ -594,13 +866,17 @@ impl AudioThreadContext {
                         "Seek calculated position"
                     );
                     audio_shared.set_position(new_pos);
+                    self.decoded_to = new_pos;
 
                     self.sample_counter = 0;
+                    self.tuner
+                        .restart(audio_shared.underruns(), std::time::Instant::now());
 
-                    // Reset resampler state to avoid artifacts
+                    // Reset resampler and filter state to avoid artifacts
                     if let Some(ref mut resampler) = self.resampler {
                         resampler.reset();
                     }
+                    self.equalizer.reset();
 
                     if let Err(e) = dec.seek(pos) {
                         tracing::warn!(target: "player::commands", error = %e, "Seek failed");
@@ -610,6 +886,16 @@ impl AudioThreadContext {
                     tracing::warn!(target: "player::commands", "Seek ignored - no decoder");
                 }
             }
+            PlayerCommand::SetNext(path) => {
+                self.next_path = path;
+            }
+            PlayerCommand::StoredLoudness(path, loudness) => {
+                // Tags win over what the library stored
+                let current = state.read().current_track.as_ref() == Some(&path);
+                if current && self.loudness.is_empty() {
+                    self.loudness = loudness;
+                }
+            }
             PlayerCommand::Shutdown => {
                 tracing::info!(target: "player::commands", "Shutdown command received");
                 return false;
@@ -666,87 +952,20 @@ impl AudioThreadContext {
         audio_shared: &AudioSharedState,
         _producer: &mut Producer<f32>,
     ) {
+        // Fade out the track being replaced, if one is playing
+        if audio_shared.is_playing() {
+            self.discard_buffered(audio_shared);
+        }
+
         // Start flushing - audio callback will drain buffer and output silence
         // This prevents hearing stale audio from the previous track
         audio_shared.start_flush();
 
+        self.outgoing = None;
         match AudioDecoder::open(&path) {
-            Ok(mut dec) => {
-                let source_rate = dec.sample_rate();
-                let source_channels = dec.channels();
-                let duration = dec.duration();
-                let bits_per_sample = dec.format_info.bit_depth;
-
-                // Read file metadata before moving decoder
-                // This provides fallback info when track is not in DB
-                let file_metadata = dec.metadata();
-
-                // Create resampler if sample rates differ
-                let resampler =
-                    Resampler::new(source_rate, self.output_sample_rate, source_channels);
-
-                if resampler.needs_resampling() {
-                    tracing::info!(
-                        "Resampling: {}Hz → {}Hz",
-                        source_rate,
-                        self.output_sample_rate
-                    );
-                }
-
-                // Build quality info before we move decoder parts
-                let quality = AudioQuality {
-                    format: dec.format_info.codec.clone(),
-                    is_lossless: dec.format_info.is_lossless,
-                    bit_depth: bits_per_sample,
-                    source_sample_rate: source_rate,
-                    output_sample_rate: self.output_sample_rate,
-                    is_bit_perfect: dec.format_info.is_lossless
-                        && source_rate == self.output_sample_rate,
-                    latency_ms: 0.0,    // Updated dynamically
-                    buffer_size: 48000, // Ring buffer size
-                    buffer_fill: 0.0,   // Updated dynamically
-                };
-
-                // Update shared state
-                {
-                    let mut s = state.write();
-                    s.status = PlaybackStatus::Playing;
-                    s.current_track = Some(path.clone());
-                    s.duration = duration;
-                    s.position = Duration::ZERO;
-                    s.sample_rate = source_rate;
-                    s.channels = source_channels;
-                    s.bits_per_sample = bits_per_sample;
-                    s.quality = quality.clone();
-                }
-
-                tracing::info!(
-                    "Track loaded: {}Hz / {}ch / {}bit ({})",
-                    source_rate,
-                    source_channels,
-                    bits_per_sample,
-                    quality.format
-                );
-
-                // Sync atomic state
-                audio_shared.set_playing(true);
+            Ok(dec) => {
+                self.start_track(path, dec, state, audio_shared);
                 audio_shared.stop_flush(); // Resume normal playback - buffer is now drained
-                audio_shared.set_position(Duration::ZERO);
-                self.sample_counter = 0;
-                self.decoder = Some(dec);
-                self.resampler = Some(resampler);
-
-                // Emit events: track loaded and status changed
-                self.emit(PlayerEvent::TrackLoaded {
-                    path,
-                    duration,
-                    sample_rate: source_rate,
-                    channels: source_channels,
-                    bits_per_sample,
-                    quality,
-                    file_metadata,
-                });
-                self.emit(PlayerEvent::StatusChanged(PlaybackStatus::Playing));
             }
             Err(e) => {
                 tracing::error!("Failed to open file: {}", e);
@@ -759,6 +978,197 @@ impl AudioThreadContext {
         }
     }
 
+    /// Make `dec` the current track and tell the UI it's playing
+    fn start_track(
+        &mut self,
+        path: PathBuf,
+        mut dec: AudioDecoder,
+        state: &RwLock<PlayerState>,
+        audio_shared: &AudioSharedState,
+    ) {
+        let source_rate = dec.sample_rate();
+        let source_channels = dec.channels();
+        let duration = dec.duration();
+        let bits_per_sample = dec.format_info.bit_depth;
+
+        // Read file metadata before moving decoder
+        // This provides fallback info when track is not in DB
+        let file_metadata = dec.metadata();
+        self.loudness = normalization::read_tags(&path);
+
+        // Create resampler if sample rates differ
+        let resampler = Resampler::new(source_rate, self.output_sample_rate, source_channels);
+
+        if resampler.needs_resampling() {
+            tracing::info!(
+                "Resampling: {}Hz → {}Hz",
+                source_rate,
+                self.output_sample_rate
+            );
+        }
+
+        // Build quality info before we move decoder parts
+        let quality = AudioQuality {
+            format: dec.format_info.codec.clone(),
+            is_lossless: dec.format_info.is_lossless,
+            bit_depth: bits_per_sample,
+            source_sample_rate: source_rate,
+            output_sample_rate: self.output_sample_rate,
+            is_bit_perfect: dec.format_info.is_lossless && source_rate == self.output_sample_rate,
+            latency_ms: 0.0, // Updated dynamically
+            buffer_size: self.decode_ahead_samples(),
+            buffer_fill: 0.0, // Updated dynamically
+        };
+
+        // Update shared state
+        {
+            let mut s = state.write();
+            s.status = PlaybackStatus::Playing;
+            s.current_track = Some(path.clone());
+            s.duration = duration;
+            s.position = Duration::ZERO;
+            s.sample_rate = source_rate;
+            s.channels = source_channels;
+            s.bits_per_sample = bits_per_sample;
+            s.quality = quality.clone();
+        }
+
+        tracing::info!(
+            "Track loaded: {}Hz / {}ch / {}bit ({})",
+            source_rate,
+            source_channels,
+            bits_per_sample,
+            quality.format
+        );
+
+        // Sync atomic state
+        audio_shared.set_playing(true);
+        audio_shared.set_position(Duration::ZERO);
+        self.sample_counter = 0;
+        self.decoded_to = Duration::ZERO;
+        self.tuner
+            .restart(audio_shared.underruns(), std::time::Instant::now());
+        self.decoder = Some(dec);
+        self.resampler = Some(resampler);
+        self.update_normalization(state, audio_shared);
+
+        // Emit events: track loaded and status changed
+        self.emit(PlayerEvent::TrackLoaded {
+            path,
+            duration,
+            sample_rate: source_rate,
+            channels: source_channels,
+            bits_per_sample,
+            quality,
+            file_metadata: Box::new(file_metadata),
+        });
+        self.emit(PlayerEvent::StatusChanged(PlaybackStatus::Playing));
+    }
+
+    /// Start fading into the next track once the current one is within the
+    /// crossfade of its end. The current track moves to the outgoing slot.
+    fn start_crossfade(&mut self, state: &RwLock<PlayerState>, audio_shared: &AudioSharedState) {
+        let secs = audio_shared.crossfade_secs();
+        if secs == 0 || self.outgoing.is_some() || self.next_path.is_none() {
+            return;
+        }
+        let Some(dec) = &self.decoder else {
+            return;
+        };
+        let remaining = dec.duration().saturating_sub(self.decoded_to);
+        if remaining.is_zero() || remaining > Duration::from_secs(u64::from(secs)) {
+            return;
+        }
+        // Tried once: on failure the track ends and the next loads as usual
+        let Some(path) = self.next_path.take() else {
+            return;
+        };
+        let next = match AudioDecoder::open(&path) {
+            Ok(next) if next.channels() == dec.channels() => next,
+            Ok(_) => {
+                tracing::debug!(
+                    target: "player::crossfade",
+                    "Not crossfading into {:?}: different channel count",
+                    path.file_name()
+                );
+                return;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    target: "player::crossfade",
+                    "Can't crossfade into {:?}: {}",
+                    path.file_name(),
+                    e
+                );
+                return;
+            }
+        };
+
+        let frames = u64::from(self.output_sample_rate) * remaining.as_millis() as u64 / 1000;
+        let mix = Crossfade::new(frames as usize, dec.channels());
+        let outgoing_gain = audio_shared.replay_gain();
+        if let Some(decoder) = self.decoder.take() {
+            self.outgoing = Some(Outgoing {
+                decoder,
+                resampler: self.resampler.take(),
+                mix,
+                gain: 1.0,
+            });
+        }
+        tracing::info!(
+            target: "player::crossfade",
+            "Crossfading into {:?} over {} ms",
+            path.file_name(),
+            remaining.as_millis()
+        );
+        self.emit(PlayerEvent::CrossfadeStarted(path.clone()));
+        self.start_track(path, next, state, audio_shared);
+        // The output now applies the incoming track's gain; keep the
+        // outgoing one at its own
+        let incoming_gain = audio_shared.replay_gain();
+        if let Some(out) = &mut self.outgoing
+            && incoming_gain > 0.0
+        {
+            out.gain = outgoing_gain / incoming_gain;
+        }
+    }
+
+    /// Mix the track fading out into `samples` of the one fading in,
+    /// decoding as much of it as they cover
+    fn mix_outgoing(&mut self, samples: &mut [f32]) {
+        let Some(out) = &mut self.outgoing else {
+            return;
+        };
+        while out.mix.wants(samples.len()) {
+            let mut decoded = Vec::with_capacity(4096);
+            match out.decoder.decode_next(|s| decoded.extend_from_slice(s)) {
+                Ok(Some(_)) => {
+                    if let Some(resampler) = &mut out.resampler {
+                        decoded = resampler.process(&decoded);
+                    }
+                    simd::apply_volume(&mut decoded, out.gain);
+                    out.mix.push_outgoing(&decoded);
+                }
+                Ok(None) => {
+                    if let Some(resampler) = &mut out.resampler {
+                        let mut flushed = resampler.flush();
+                        simd::apply_volume(&mut flushed, out.gain);
+                        out.mix.push_outgoing(&flushed);
+                    }
+                    out.mix.end_outgoing();
+                }
+                Err(e) => {
+                    tracing::warn!(target: "player::crossfade", "Decode error fading out: {}", e);
+                    out.mix.end_outgoing();
+                }
+            }
+        }
+        out.mix.mix(samples);
+        if out.mix.is_done() {
+            self.outgoing = None;
+        }
+    }
+
     /// Decode next chunk and send to outputs. Returns false if playback ended.
     fn decode_and_send(
         &mut self,
@@ -767,28 +1177,38 @@ impl AudioThreadContext {
         state: &RwLock<PlayerState>,
         audio_shared: &AudioSharedState,
     ) -> bool {
+        let decode_ahead = self.decode_ahead_samples();
+        self.start_crossfade(state, audio_shared);
         let Some(ref mut dec) = self.decoder else {
             return true;
         };
 
-        // Check if ring buffer has space
+        // Stop once the decode-ahead is buffered (or the ring is full)
         let available = producer.slots();
-        if available < 1024 {
-            // Buffer is nearly full, wait a bit
+        let buffered = producer.buffer().capacity() - available;
+        if available < 1024 || buffered >= decode_ahead {
             thread::sleep(Duration::from_millis(5));
             return true;
         }
 
         let mut samples = Vec::with_capacity(4096);
 
+        let decode_start = Instant::now();
         match dec.decode_next(|s| samples.extend_from_slice(s)) {
             Ok(Some(frame)) => {
+                let decode_time = decode_start.elapsed();
+
                 // Resample if needed
-                let output_samples = if let Some(ref mut resampler) = self.resampler {
+                let resample_start = Instant::now();
+                let mut output_samples = if let Some(ref mut resampler) = self.resampler {
                     resampler.process(&samples)
                 } else {
                     samples.clone()
                 };
+                audio_shared.record_decode(decode_time, resample_start.elapsed());
+                self.decoded_to = frame.timestamp;
+                self.mix_outgoing(&mut output_samples);
+                self.equalize(&mut output_samples, audio_shared);
 
                 // Extract left channel for visualization (from resampled output)
                 let output_channels = self.output_channels as usize;
@@ -822,7 +1242,8 @@ impl AudioThreadContext {
             Ok(None) => {
                 // Flush resampler at end of stream
                 if let Some(ref mut resampler) = self.resampler {
-                    let flushed = resampler.flush();
+                    let mut flushed = resampler.flush();
+                    self.equalize(&mut flushed, audio_shared);
                     for &sample in &flushed {
                         while producer.push(sample).is_err() {
                             thread::sleep(Duration::from_micros(100));
@@ -835,6 +1256,7 @@ impl AudioThreadContext {
                 audio_shared.set_playing(false);
                 self.decoder = None;
                 self.resampler = None;
+                self.outgoing = None;
                 self.emit(PlayerEvent::PlaybackFinished);
                 self.emit(PlayerEvent::StatusChanged(PlaybackStatus::Stopped));
                 true
@@ -845,6 +1267,7 @@ impl AudioThreadContext {
                 audio_shared.set_playing(false);
                 self.decoder = None;
                 self.resampler = None;
+                self.outgoing = None;
                 self.emit(PlayerEvent::Error(format!("Decode error: {}", e)));
                 self.emit(PlayerEvent::StatusChanged(PlaybackStatus::Stopped));
                 true
@@ -865,7 +1288,12 @@ fn audio_thread_main(
     output_sample_rate: u32,
     output_channels: u16,
 ) {
-    let mut ctx = AudioThreadContext::new(output_sample_rate, output_channels, event_tx);
+    let mut ctx = AudioThreadContext::new(
+        output_sample_rate,
+        output_channels,
+        event_tx,
+        Arc::clone(&audio_shared),
+    );
 
     loop {
         let is_idle = matches!(
@@ -873,11 +1301,19 @@ fn audio_thread_main(
             PlaybackStatus::Stopped | PlaybackStatus::Paused
         );
 
-        // Block on commands when idle, poll when playing
+        // Block on commands when idle, poll when playing; stop once the
+        // player is gone
         let command = if is_idle {
-            command_rx.recv().ok()
+            match command_rx.recv() {
+                Ok(cmd) => Some(cmd),
+                Err(_) => break,
+            }
         } else {
-            command_rx.try_recv().ok()
+            match command_rx.try_recv() {
+                Ok(cmd) => Some(cmd),
+                Err(crossbeam_channel::TryRecvError::Empty) => None,
+                Err(crossbeam_channel::TryRecvError::Disconnected) => break,
+            }
         };
 
         // Process command if received
@@ -886,9 +1322,12 @@ fn audio_thread_main(
         {
             break;
         }
+        // Settings and stored loudness can change the gain mid-track
+        ctx.update_normalization(&state, &audio_shared);
 
         // Decode audio when playing
         if state.read().status == PlaybackStatus::Playing {
+            ctx.tune_buffer(&audio_shared);
             if !ctx.decode_and_send(&mut producer, &viz_tx, &state, &audio_shared) {
                 break;
             }
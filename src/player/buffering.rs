@@ -0,0 +1,228 @@
+//! Decode-ahead sizing.
+//!
+//! The ring buffer is allocated once at [`MAX_AHEAD_MS`]; how much of it the
+//! decoder thread keeps filled is the decode-ahead. More of it rides out
+//! slow disks, busy CPUs and flaky USB DACs; less of it keeps memory low and
+//! what's buffered short. With the buffer setting on Auto, [`BufferTuner`]
+//! grows the decode-ahead after an underrun and shrinks it again once
+//! playback has been stable for a while. A fixed setting pins it.
+//!
+//! Everything here runs in the decoder thread. The audio callback only
+//! counts underruns and reports the fill level, through atomics.
+
+use std::time::{Duration, Instant};
+
+/// Buffer setting meaning "tune automatically"
+pub const AUTO: u32 = 0;
+
+/// Smallest decode-ahead Auto shrinks to
+pub const MIN_AHEAD_MS: u32 = 150;
+
+/// Decode-ahead Auto starts at
+pub const DEFAULT_AHEAD_MS: u32 = 500;
+
+/// Largest decode-ahead, and the size of the ring buffer
+pub const MAX_AHEAD_MS: u32 = 2000;
+
+/// Fixed sizes offered in settings (besides Auto)
+pub const BUFFER_CHOICES_MS: [u32; 5] = [100, 250, 500, 1000, 2000];
+
+/// Underruns right after a load or seek are the buffer filling, not a
+/// sign it's too small
+const GRACE: Duration = Duration::from_secs(2);
+
+/// How long playback must run without underruns before Auto shrinks
+const STABLE_FOR: Duration = Duration::from_secs(60);
+
+/// Why the decode-ahead changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferChangeReason {
+    /// Auto grew it after an underrun
+    Underrun,
+    /// Auto shrank it after stable playback
+    Stable,
+    /// The buffer setting changed
+    Setting,
+}
+
+/// A change of decode-ahead, for logging its latency impact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferChange {
+    pub from_ms: u32,
+    pub to_ms: u32,
+    pub reason: BufferChangeReason,
+}
+
+impl std::fmt::Display for BufferChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let why = match self.reason {
+            BufferChangeReason::Underrun => "after an underrun",
+            BufferChangeReason::Stable => "after stable playback",
+            BufferChangeReason::Setting => "by the buffer setting",
+        };
+        let delta = i64::from(self.to_ms) - i64::from(self.from_ms);
+        write!(
+            f,
+            "Decode-ahead {} ms → {} ms {} ({:+} ms latency)",
+            self.from_ms, self.to_ms, why, delta
+        )
+    }
+}
+
+/// Chooses the decode-ahead from the underrun count
+#[derive(Debug, Clone)]
+pub struct BufferTuner {
+    /// [`AUTO`] or a fixed size in ms
+    setting_ms: u32,
+    target_ms: u32,
+    /// Underrun count already accounted for
+    underruns: u32,
+    /// When playback was last disturbed (underrun, load, seek)
+    quiet_since: Instant,
+    /// Underruns before this are ignored
+    grace_until: Instant,
+}
+
+impl BufferTuner {
+    pub fn new(setting_ms: u32, now: Instant) -> Self {
+        Self {
+            setting_ms,
+            target_ms: Self::initial(setting_ms),
+            underruns: 0,
+            quiet_since: now,
+            grace_until: now + GRACE,
+        }
+    }
+
+    fn initial(setting_ms: u32) -> u32 {
+        if setting_ms == AUTO {
+            DEFAULT_AHEAD_MS
+        } else {
+            setting_ms.clamp(1, MAX_AHEAD_MS)
+        }
+    }
+
+    /// Current decode-ahead in ms
+    pub fn target_ms(&self) -> u32 {
+        self.target_ms
+    }
+
+    /// A track was loaded or seeked: the buffer restarts from empty
+    pub fn restart(&mut self, underruns: u32, now: Instant) {
+        self.underruns = underruns;
+        self.quiet_since = now;
+        self.grace_until = now + GRACE;
+    }
+
+    /// Take a new buffer setting. Auto keeps the current size.
+    pub fn set_setting(&mut self, setting_ms: u32) -> Option<BufferChange> {
+        if setting_ms == self.setting_ms {
+            return None;
+        }
+        self.setting_ms = setting_ms;
+        if setting_ms == AUTO {
+            return None;
+        }
+        self.change(Self::initial(setting_ms), BufferChangeReason::Setting)
+    }
+
+    /// Look at the underrun count (monotonic, or reset to 0) and resize
+    /// if Auto calls for it
+    pub fn update(&mut self, underruns: u32, now: Instant) -> Option<BufferChange> {
+        let new_underruns = underruns != self.underruns;
+        self.underruns = underruns;
+        if new_underruns {
+            self.quiet_since = now;
+        }
+        if self.setting_ms != AUTO {
+            return None;
+        }
+
+        if new_underruns && now >= self.grace_until {
+            let grown = (self.target_ms * 3 / 2).min(MAX_AHEAD_MS);
+            return self.change(grown, BufferChangeReason::Underrun);
+        }
+        if now.duration_since(self.quiet_since) >= STABLE_FOR {
+            self.quiet_since = now;
+            let shrunk = (self.target_ms * 4 / 5).max(MIN_AHEAD_MS);
+            return self.change(shrunk, BufferChangeReason::Stable);
+        }
+        None
+    }
+
+    fn change(&mut self, to_ms: u32, reason: BufferChangeReason) -> Option<BufferChange> {
+        if to_ms == self.target_ms {
+            return None;
+        }
+        let change = BufferChange {
+            from_ms: self.target_ms,
+            to_ms,
+            reason,
+        };
+        self.target_ms = to_ms;
+        Some(change)
+    }
+}
+
+/// Interleaved samples in `ms` of audio
+pub fn samples_for(ms: u32, sample_rate: u32, channels: u16) -> usize {
+    (u64::from(ms) * u64::from(sample_rate) * u64::from(channels.max(1)) / 1000) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_grows_on_underruns_and_shrinks_when_stable() {
+        let start = Instant::now();
+        let mut tuner = BufferTuner::new(AUTO, start);
+        assert_eq!(tuner.target_ms(), DEFAULT_AHEAD_MS);
+
+        // Underruns while the buffer first fills don't count
+        assert_eq!(tuner.update(800, start + Duration::from_secs(1)), None);
+
+        let grown = tuner.update(900, start + Duration::from_secs(5)).unwrap();
+        assert_eq!((grown.from_ms, grown.to_ms), (500, 750));
+        assert_eq!(grown.reason, BufferChangeReason::Underrun);
+        assert!(grown.to_string().contains("+250 ms latency"));
+        tuner.update(1000, start + Duration::from_secs(6));
+        tuner.update(1100, start + Duration::from_secs(7));
+        tuner.update(1200, start + Duration::from_secs(8));
+        assert_eq!(tuner.target_ms(), MAX_AHEAD_MS);
+
+        // A minute without underruns shrinks it, down to the floor
+        let quiet = start + Duration::from_secs(8) + STABLE_FOR;
+        let shrunk = tuner.update(1200, quiet).unwrap();
+        assert_eq!((shrunk.from_ms, shrunk.to_ms), (2000, 1600));
+        let mut now = quiet;
+        for _ in 0..20 {
+            now += STABLE_FOR;
+            tuner.update(1200, now);
+        }
+        assert_eq!(tuner.target_ms(), MIN_AHEAD_MS);
+
+        // A seek restarts the grace period
+        tuner.restart(1200, now);
+        assert_eq!(tuner.update(1300, now + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn test_fixed_setting_pins_the_size() {
+        let start = Instant::now();
+        let mut tuner = BufferTuner::new(1000, start);
+        assert_eq!(tuner.target_ms(), 1000);
+        assert_eq!(tuner.update(50, start + Duration::from_secs(10)), None);
+        assert_eq!(tuner.update(50, start + STABLE_FOR * 2), None);
+        assert_eq!(tuner.target_ms(), 1000);
+
+        let change = tuner.set_setting(250).unwrap();
+        assert_eq!(change.reason, BufferChangeReason::Setting);
+        assert_eq!(tuner.target_ms(), 250);
+        // Back to Auto: tuning carries on from here
+        assert_eq!(tuner.set_setting(AUTO), None);
+        assert_eq!(tuner.target_ms(), 250);
+
+        assert_eq!(samples_for(500, 48000, 2), 48000);
+    }
+}
@@ -0,0 +1,151 @@
+//! Crossfades between tracks.
+//!
+//! With `audio.crossfade_secs` set, the decoder thread opens the next track
+//! that long before the current one ends and plays both at once: the
+//! outgoing track fades out while the incoming one fades in. The player
+//! tells the thread which track comes next (see [`super::Player::sync_next`]),
+//! so it can start without waiting for the UI.
+//!
+//! The outgoing track keeps its own decoder in a second slot while it
+//! fades. [`Crossfade`] mixes its samples into the incoming track's, with
+//! equal-power gains so the loudness doesn't dip halfway. Nothing crosses
+//! over a stop mark, on repeat one, or between tracks with different
+//! channel counts: those end and start as before.
+
+use std::collections::VecDeque;
+use std::f32::consts::FRAC_PI_2;
+
+/// Longest crossfade, in seconds
+pub const MAX_CROSSFADE_SECS: u32 = 12;
+
+/// Crossfade unless the config sets one (off)
+pub const DEFAULT_CROSSFADE_SECS: u32 = 0;
+
+/// Gains of the outgoing and incoming track `progress` (0–1) through a
+/// crossfade
+pub fn gains(progress: f32) -> (f32, f32) {
+    let angle = progress.clamp(0.0, 1.0) * FRAC_PI_2;
+    (angle.cos(), angle.sin())
+}
+
+/// Mixes the end of one track into the start of the next
+#[derive(Debug, Clone)]
+pub struct Crossfade {
+    /// Frames the crossfade lasts
+    frames: usize,
+    /// Frames mixed so far
+    mixed: usize,
+    channels: usize,
+    /// Decoded outgoing samples not mixed yet
+    outgoing: VecDeque<f32>,
+    /// The outgoing track has no more samples
+    outgoing_ended: bool,
+}
+
+impl Crossfade {
+    /// A crossfade lasting `frames` frames of interleaved `channels`
+    pub fn new(frames: usize, channels: u16) -> Self {
+        Self {
+            frames: frames.max(1),
+            mixed: 0,
+            channels: usize::from(channels.max(1)),
+            outgoing: VecDeque::new(),
+            outgoing_ended: false,
+        }
+    }
+
+    /// Whether mixing `len` incoming samples needs more of the outgoing
+    /// track decoded first
+    pub fn wants(&self, len: usize) -> bool {
+        !self.outgoing_ended && self.outgoing.len() < len.min(self.remaining_samples())
+    }
+
+    /// Add decoded samples of the outgoing track
+    pub fn push_outgoing(&mut self, samples: &[f32]) {
+        self.outgoing.extend(samples);
+    }
+
+    /// The outgoing track ended (or failed): the rest fades in alone
+    pub fn end_outgoing(&mut self) {
+        self.outgoing_ended = true;
+    }
+
+    /// Fade `incoming` in and mix the outgoing track into it, fading out
+    pub fn mix(&mut self, incoming: &mut [f32]) {
+        for frame in incoming.chunks_mut(self.channels) {
+            if self.is_done() {
+                break;
+            }
+            let (fade_out, fade_in) = gains(self.mixed as f32 / self.frames as f32);
+            for sample in frame {
+                let outgoing = self.outgoing.pop_front().unwrap_or(0.0);
+                *sample = *sample * fade_in + outgoing * fade_out;
+            }
+            self.mixed += 1;
+        }
+        if self.is_done() {
+            // Anything decoded past the end would play at zero gain
+            self.outgoing.clear();
+        }
+    }
+
+    /// Whether the incoming track has reached full gain
+    pub fn is_done(&self) -> bool {
+        self.mixed >= self.frames
+    }
+
+    fn remaining_samples(&self) -> usize {
+        (self.frames - self.mixed.min(self.frames)) * self.channels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gains_keep_power() {
+        assert_eq!(gains(0.0), (1.0, 0.0));
+        let (out, inc) = gains(1.0);
+        assert!(out.abs() < 1e-6 && (inc - 1.0).abs() < 1e-6);
+        for progress in [0.1, 0.25, 0.5, 0.9] {
+            let (out, inc) = gains(progress);
+            assert!((out * out + inc * inc - 1.0).abs() < 1e-5);
+        }
+        assert_eq!(gains(2.0), gains(1.0));
+    }
+
+    #[test]
+    fn test_mix_fades_across() {
+        // 4 frames of stereo: outgoing at 1.0, incoming at 0.5
+        let mut fade = Crossfade::new(4, 2);
+        assert!(fade.wants(8));
+        fade.push_outgoing(&[1.0; 8]);
+        assert!(!fade.wants(8));
+
+        let mut incoming = vec![0.5; 10];
+        fade.mix(&mut incoming);
+        assert_eq!(&incoming[..2], &[1.0, 1.0]);
+        let (out, inc) = gains(0.5);
+        assert!((incoming[4] - (out + 0.5 * inc)).abs() < 1e-6);
+        // Past the crossfade the incoming track plays as it is
+        assert_eq!(&incoming[8..], &[0.5, 0.5]);
+        assert!(fade.is_done());
+        assert!(!fade.wants(8));
+    }
+
+    #[test]
+    fn test_mix_after_outgoing_ends() {
+        let mut fade = Crossfade::new(4, 1);
+        fade.push_outgoing(&[1.0]);
+        fade.end_outgoing();
+        assert!(!fade.wants(4));
+
+        let mut incoming = vec![1.0; 4];
+        fade.mix(&mut incoming);
+        // The incoming track keeps ramping up, with nothing under it
+        assert_eq!(incoming[0], 1.0);
+        assert!(incoming[1] < incoming[2] && incoming[2] < incoming[3]);
+        assert!(fade.is_done());
+    }
+}
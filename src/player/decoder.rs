@@ -23,13 +23,15 @@ use symphonia::core::codecs::{
 };
 use symphonia::core::errors::Error as SymphoniaError;
 use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
-use symphonia::core::io::MediaSourceStream;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 use symphonia::core::units::Time;
 
 use super::PlayerError;
+use super::gapless::{EncoderPadding, PaddingTrim};
 use super::state::TrackInfo;
+use crate::model::TrackSource;
 
 /// Audio format information for quality tracking.
 #[derive(Debug, Clone)]
@@ -64,23 +66,25 @@ pub struct AudioDecoder {
     channels: u16,
     duration: Duration,
     time_base: Option<symphonia::core::units::TimeBase>,
+    /// How encoder delay and padding are handled
+    padding: EncoderPadding,
+    /// Trims the padding when symphonia can't (no frame count in the header)
+    padding_trim: Option<PaddingTrim>,
     /// Format information for quality tracking
     pub format_info: AudioFormatInfo,
 }
 
 impl AudioDecoder {
-    /// Open a file for decoding.
+    /// Open a file for decoding. A stream or cloud drive URL is decoded
+    /// as it downloads.
     pub fn open(path: &Path) -> Result<Self, PlayerError> {
-        // Create a media source stream
-        let file = File::open(path)
-            .map_err(|e| PlayerError::FileNotFound(format!("{}: {}", path.display(), e)))?;
-
-        let mss = MediaSourceStream::new(Box::new(file), Default::default());
+        let (source, extension) = Self::media_source(path)?;
+        let mss = MediaSourceStream::new(source, Default::default());
 
         // Probe the format
         let mut hint = Hint::new();
-        if let Some(ext) = path.extension() {
-            hint.with_extension(&ext.to_string_lossy());
+        if let Some(ext) = &extension {
+            hint.with_extension(ext);
         }
 
         let format_opts = FormatOptions {
@@ -112,7 +116,7 @@ impl AudioDecoder {
         let channels = codec_params.channels.map(|c| c.count() as u16).unwrap_or(2);
 
         // Extract format information for quality tracking
-        let format_info = Self::extract_format_info(&codec_params, path);
+        let format_info = Self::extract_format_info(&codec_params, extension.as_deref());
 
         // Calculate duration
         let time_base = codec_params.time_base;
@@ -128,6 +132,18 @@ impl AudioDecoder {
             Duration::ZERO
         };
 
+        // Symphonia trims the delay, and the padding when it knows where the
+        // stream ends
+        let padding = EncoderPadding::from_params(&codec_params);
+        let padding_trim = match padding {
+            EncoderPadding::Trimmed { padding, .. }
+                if padding > 0 && codec_params.n_frames.is_none() =>
+            {
+                Some(PaddingTrim::new(padding, channels))
+            }
+            _ => None,
+        };
+
         // Create decoder
         let decoder_opts = DecoderOptions::default();
         let decoder = symphonia::default::get_codecs()
@@ -142,20 +158,44 @@ impl AudioDecoder {
             channels,
             duration,
             time_base,
+            padding,
+            padding_trim,
             format_info,
         })
     }
 
+    /// The file at `path`, or the download of a remote track, with its
+    /// extension as a hint for the probe
+    fn media_source(path: &Path) -> Result<(Box<dyn MediaSource>, Option<String>), PlayerError> {
+        let path_str = path.to_string_lossy();
+        if !TrackSource::detect(&path_str).is_local() {
+            #[cfg(feature = "enrichment")]
+            {
+                let (stream, extension) = super::stream::HttpStream::open(&path_str)?;
+                return Ok((Box::new(stream), extension));
+            }
+            #[cfg(not(feature = "enrichment"))]
+            return Err(PlayerError::Stream(format!(
+                "{}: streaming needs the enrichment feature",
+                path_str
+            )));
+        }
+
+        let file = File::open(path)
+            .map_err(|e| PlayerError::FileNotFound(format!("{}: {}", path.display(), e)))?;
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned());
+        Ok((Box::new(file), extension))
+    }
+
     /// Extract format information from codec parameters.
     fn extract_format_info(
         params: &symphonia::core::codecs::CodecParameters,
-        path: &Path,
+        extension: Option<&str>,
     ) -> AudioFormatInfo {
         let codec = params.codec;
-        let ext = path
-            .extension()
-            .map(|e| e.to_string_lossy().to_lowercase())
-            .unwrap_or_default();
+        let ext = extension.map(str::to_lowercase).unwrap_or_default();
 
         // Determine codec name and lossless status
         let (codec_name, is_lossless) = match codec {
@@ -228,6 +268,11 @@ impl AudioDecoder {
         self.duration
     }
 
+    /// Get how encoder delay and padding are handled.
+    pub fn padding(&self) -> EncoderPadding {
+        self.padding
+    }
+
     /// Get track metadata.
     pub fn metadata(&mut self) -> TrackInfo {
         // Extract metadata from the format reader
@@ -242,6 +287,9 @@ impl AudioDecoder {
                     Some(symphonia::core::meta::StandardTagKey::Artist) => {
                         info.artist = Some(tag.value.to_string());
                     }
+                    Some(symphonia::core::meta::StandardTagKey::AlbumArtist) => {
+                        info.album_artist = Some(tag.value.to_string());
+                    }
                     Some(symphonia::core::meta::StandardTagKey::Album) => {
                         info.album = Some(tag.value.to_string());
                     }
@@ -288,6 +336,9 @@ impl AudioDecoder {
 
         // Reset decoder state after seeking
         self.decoder.reset();
+        if let Some(trim) = &mut self.padding_trim {
+            trim.reset();
+        }
 
         Ok(())
     }
@@ -338,7 +389,10 @@ impl AudioDecoder {
 
             // Convert to f32 samples
             let channels = self.channels;
-            let samples = Self::convert_to_f32(&decoded, channels);
+            let mut samples = Self::convert_to_f32(&decoded, channels);
+            if let Some(trim) = &mut self.padding_trim {
+                samples = trim.apply(samples);
+            }
             let frame = DecodedFrame {
                 samples: samples.len() / channels as usize,
                 timestamp,
@@ -435,4 +489,54 @@ mod tests {
         let result = AudioDecoder::open(Path::new("/nonexistent/file.mp3"));
         assert!(result.is_err());
     }
+
+    /// One second of 16-bit mono 8 kHz silence
+    #[cfg(feature = "enrichment")]
+    fn wav_bytes() -> Vec<u8> {
+        let data = vec![0u8; 16000];
+        let mut wav = Vec::with_capacity(44 + data.len());
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&data);
+        wav
+    }
+
+    #[cfg(feature = "enrichment")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_decoder_streams_urls() {
+        use crate::test_utils::{FixtureResponse, FixtureServer};
+
+        let server = FixtureServer::start(vec![(
+            "/live",
+            FixtureResponse::bytes("audio/wav", &wav_bytes()),
+        )])
+        .await;
+        let url = format!("{}/live", server.url());
+        let missing = format!("{}/gone.mp3", server.url());
+
+        let (samples, missing) = tokio::task::spawn_blocking(move || {
+            let mut decoder = AudioDecoder::open(Path::new(&url)).unwrap();
+            assert_eq!(decoder.sample_rate(), 8000);
+            assert_eq!(decoder.format_info.codec, "PCM/WAV");
+            let mut samples = 0;
+            while let Some(frame) = decoder.decode_next(|_| {}).unwrap() {
+                samples += frame.samples;
+            }
+            (samples, AudioDecoder::open(Path::new(&missing)).err())
+        })
+        .await
+        .unwrap();
+        assert_eq!(samples, 8000);
+        assert!(matches!(missing, Some(PlayerError::Stream(e)) if e.contains("404")));
+    }
 }
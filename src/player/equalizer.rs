@@ -0,0 +1,339 @@
+//! Ten-band graphic equalizer.
+//!
+//! The decoder thread runs the EQ over each decoded (and resampled) chunk
+//! before it goes into the ring buffer, so the output callback does no
+//! extra work. Band gains live in [`AudioSharedState`](super::AudioSharedState)
+//! as atomics: the UI sets them without locks, and the decoder thread
+//! rebuilds its filter coefficients when it sees they changed. Changes are
+//! heard once the audio already buffered has played.
+//!
+//! Each band is a peaking biquad an octave wide (RBJ cookbook), centred on
+//! the ISO frequencies from 31 Hz to 16 kHz. Bands at 0 dB, and bands too
+//! close to the output's Nyquist frequency, are skipped. Boosts could push
+//! samples past full scale, so the input is lowered by the largest boost.
+
+use serde::{Deserialize, Serialize};
+
+/// Number of bands
+pub const BANDS: usize = 10;
+
+/// Centre frequency of each band, in Hz
+pub const BAND_FREQUENCIES_HZ: [f32; BANDS] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+
+/// Lowest and highest band gain, in dB
+pub const GAIN_RANGE_DB: (f32, f32) = (-12.0, 12.0);
+
+/// Q of an octave-wide band
+const BAND_Q: f32 = std::f32::consts::SQRT_2;
+
+/// Gains under this (in dB) count as flat
+const FLAT_DB: f32 = 0.05;
+
+/// Gain of each band, in dB
+pub type EqGains = [f32; BANDS];
+
+/// Named sets of band gains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EqPreset {
+    #[default]
+    Flat,
+    BassBoost,
+    TrebleBoost,
+    Vocal,
+    Rock,
+    Pop,
+    Jazz,
+    Classical,
+    Electronic,
+    /// Gains set band by band
+    Custom,
+}
+
+impl EqPreset {
+    pub const ALL: [EqPreset; 10] = [
+        EqPreset::Flat,
+        EqPreset::BassBoost,
+        EqPreset::TrebleBoost,
+        EqPreset::Vocal,
+        EqPreset::Rock,
+        EqPreset::Pop,
+        EqPreset::Jazz,
+        EqPreset::Classical,
+        EqPreset::Electronic,
+        EqPreset::Custom,
+    ];
+
+    /// The preset's gains (none for Custom)
+    pub fn gains(self) -> Option<EqGains> {
+        Some(match self {
+            EqPreset::Flat => [0.0; BANDS],
+            EqPreset::BassBoost => [6.0, 5.0, 4.0, 2.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0],
+            EqPreset::TrebleBoost => [0.0, 0.0, 0.0, 0.0, 0.0, 0.5, 2.0, 4.0, 5.0, 6.0],
+            EqPreset::Vocal => [-2.0, -2.0, -1.0, 1.0, 3.0, 4.0, 3.0, 1.0, 0.0, -1.0],
+            EqPreset::Rock => [4.0, 3.0, 2.0, 0.0, -1.0, -1.0, 0.0, 2.0, 3.0, 4.0],
+            EqPreset::Pop => [-1.0, 0.0, 2.0, 3.0, 4.0, 3.0, 1.0, 0.0, -1.0, -1.0],
+            EqPreset::Jazz => [3.0, 2.0, 1.0, 2.0, -1.0, -1.0, 0.0, 1.0, 2.0, 3.0],
+            EqPreset::Classical => [4.0, 3.0, 2.0, 1.0, -1.0, -1.0, 0.0, 2.0, 3.0, 4.0],
+            EqPreset::Electronic => [5.0, 4.0, 1.0, 0.0, -2.0, 1.0, 0.0, 1.0, 4.0, 5.0],
+            EqPreset::Custom => return None,
+        })
+    }
+
+    /// The preset with exactly these gains, or Custom
+    pub fn matching(gains: &EqGains) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|preset| {
+                preset.gains().is_some_and(|preset| {
+                    preset
+                        .iter()
+                        .zip(gains)
+                        .all(|(a, b)| (a - b).abs() < FLAT_DB)
+                })
+            })
+            .unwrap_or(EqPreset::Custom)
+    }
+}
+
+impl std::fmt::Display for EqPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EqPreset::Flat => "Flat",
+            EqPreset::BassBoost => "Bass boost",
+            EqPreset::TrebleBoost => "Treble boost",
+            EqPreset::Vocal => "Vocal",
+            EqPreset::Rock => "Rock",
+            EqPreset::Pop => "Pop",
+            EqPreset::Jazz => "Jazz",
+            EqPreset::Classical => "Classical",
+            EqPreset::Electronic => "Electronic",
+            EqPreset::Custom => "Custom",
+        })
+    }
+}
+
+/// A band's centre frequency for display: "31", "1k", "16k"
+pub fn band_label(band: usize) -> String {
+    let hz = BAND_FREQUENCIES_HZ[band];
+    if hz >= 1000.0 {
+        format!("{}k", hz / 1000.0)
+    } else {
+        format!("{}", hz)
+    }
+}
+
+/// `gains` held to [`GAIN_RANGE_DB`]
+pub fn clamp_gains(gains: &EqGains) -> EqGains {
+    gains.map(|g| g.clamp(GAIN_RANGE_DB.0, GAIN_RANGE_DB.1))
+}
+
+/// Biquad coefficients, normalized so a0 = 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Coefficients {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Coefficients {
+    /// Peaking filter at `freq` Hz boosting or cutting by `gain_db`. None
+    /// when flat, or when `freq` is too close to Nyquist to filter.
+    fn peaking(freq: f32, gain_db: f32, sample_rate: u32) -> Option<Self> {
+        let nyquist = sample_rate as f32 / 2.0;
+        if gain_db.abs() < FLAT_DB || freq >= nyquist * 0.9 {
+            return None;
+        }
+        let a = 10f32.powf(gain_db / 40.0);
+        let w0 = std::f32::consts::TAU * freq / sample_rate as f32;
+        let alpha = w0.sin() / (2.0 * BAND_Q);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha / a;
+        Some(Self {
+            b0: (1.0 + alpha * a) / a0,
+            b1: -2.0 * cos / a0,
+            b2: (1.0 - alpha * a) / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha / a) / a0,
+        })
+    }
+}
+
+/// One band's filter memory for one channel (transposed direct form II)
+#[derive(Debug, Clone, Copy, Default)]
+struct History {
+    s1: f32,
+    s2: f32,
+}
+
+/// The equalizer's filters for one output format
+#[derive(Debug)]
+pub struct Equalizer {
+    sample_rate: u32,
+    channels: usize,
+    /// Filter of each band (none where the band is flat)
+    bands: [Option<Coefficients>; BANDS],
+    /// Filter memory, per channel
+    history: Vec<[History; BANDS]>,
+    /// Input gain leaving room for the largest boost
+    headroom: f32,
+}
+
+impl Equalizer {
+    /// A flat equalizer for interleaved audio at `sample_rate`
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        let channels = usize::from(channels.max(1));
+        Self {
+            sample_rate,
+            channels,
+            bands: [None; BANDS],
+            history: vec![[History::default(); BANDS]; channels],
+            headroom: 1.0,
+        }
+    }
+
+    /// Use these band gains (held to [`GAIN_RANGE_DB`]). Filter memory is
+    /// kept, so a change mid-track doesn't click.
+    pub fn set_gains(&mut self, gains: &EqGains) {
+        let gains = clamp_gains(gains);
+        for (band, gain) in gains.iter().enumerate() {
+            self.bands[band] =
+                Coefficients::peaking(BAND_FREQUENCIES_HZ[band], *gain, self.sample_rate);
+        }
+        let boost = gains
+            .iter()
+            .zip(&self.bands)
+            .filter(|(_, filter)| filter.is_some())
+            .map(|(gain, _)| *gain)
+            .fold(0.0f32, f32::max);
+        self.headroom = 10f32.powf(-boost / 20.0);
+        if self.is_flat() {
+            self.reset();
+        }
+    }
+
+    /// Whether every band is flat (processing leaves audio untouched)
+    pub fn is_flat(&self) -> bool {
+        self.bands.iter().all(Option::is_none)
+    }
+
+    /// Forget the filter memory, e.g. after a seek
+    pub fn reset(&mut self) {
+        for channel in &mut self.history {
+            *channel = [History::default(); BANDS];
+        }
+    }
+
+    /// Equalize interleaved samples in place
+    pub fn process(&mut self, samples: &mut [f32]) {
+        if self.is_flat() {
+            return;
+        }
+        for frame in samples.chunks_mut(self.channels) {
+            for (sample, history) in frame.iter_mut().zip(&mut self.history) {
+                let mut x = *sample * self.headroom;
+                for (filter, h) in self.bands.iter().zip(history.iter_mut()) {
+                    let Some(c) = filter else {
+                        continue;
+                    };
+                    let y = c.b0 * x + h.s1;
+                    h.s1 = c.b1 * x - c.a1 * y + h.s2;
+                    h.s2 = c.b2 * x - c.a2 * y;
+                    x = y;
+                }
+                *sample = x;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    /// RMS of a stereo sine at `freq` after the EQ, past the settling time
+    fn rms_through(eq: &mut Equalizer, freq: f32) -> f32 {
+        let frames = RATE as usize / 2;
+        let mut samples: Vec<f32> = (0..frames)
+            .flat_map(|i| {
+                let s = 0.5 * (std::f32::consts::TAU * freq * i as f32 / RATE as f32).sin();
+                [s, s]
+            })
+            .collect();
+        eq.process(&mut samples);
+        let tail = &samples[samples.len() / 2..];
+        (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt()
+    }
+
+    fn db(ratio: f32) -> f32 {
+        20.0 * ratio.log10()
+    }
+
+    #[test]
+    fn test_flat_leaves_audio_alone() {
+        let mut eq = Equalizer::new(RATE, 2);
+        eq.set_gains(&EqPreset::Flat.gains().unwrap());
+        assert!(eq.is_flat());
+        let mut samples = vec![0.25, -0.5, 0.75, -1.0];
+        eq.process(&mut samples);
+        assert_eq!(samples, [0.25, -0.5, 0.75, -1.0]);
+    }
+
+    #[test]
+    fn test_band_boosts_its_frequency() {
+        let reference = rms_through(&mut Equalizer::new(RATE, 2), 1000.0);
+        let mut gains = [0.0; BANDS];
+        gains[5] = 6.0;
+        let mut eq = Equalizer::new(RATE, 2);
+        eq.set_gains(&gains);
+
+        // The boosted band is lowered by the headroom, then lifted by 6 dB
+        let centre = db(rms_through(&mut eq, 1000.0) / reference);
+        assert!(centre.abs() < 0.5, "1 kHz moved {:.2} dB", centre);
+        // Far from the band only the headroom applies
+        eq.reset();
+        let far = db(rms_through(&mut eq, 62.0) / reference);
+        assert!((far + 6.0).abs() < 0.5, "62 Hz moved {:.2} dB", far);
+
+        // A cut needs no headroom
+        gains[5] = -6.0;
+        eq.set_gains(&gains);
+        eq.reset();
+        let cut = db(rms_through(&mut eq, 1000.0) / reference);
+        assert!((cut + 6.0).abs() < 0.5, "1 kHz cut by {:.2} dB", cut);
+    }
+
+    #[test]
+    fn test_bands_above_nyquist_are_skipped() {
+        let mut gains = [0.0; BANDS];
+        gains[9] = 12.0;
+        let mut eq = Equalizer::new(22_050, 1);
+        eq.set_gains(&gains);
+        assert!(eq.is_flat());
+    }
+
+    #[test]
+    fn test_presets() {
+        for preset in EqPreset::ALL {
+            match preset.gains() {
+                Some(gains) => {
+                    assert_eq!(EqPreset::matching(&gains), preset);
+                    assert_eq!(clamp_gains(&gains), gains);
+                }
+                None => assert_eq!(preset, EqPreset::Custom),
+            }
+        }
+        let mut gains = EqPreset::Rock.gains().unwrap();
+        gains[0] = 1.0;
+        assert_eq!(EqPreset::matching(&gains), EqPreset::Custom);
+        assert_eq!(band_label(0), "31");
+        assert_eq!(band_label(5), "1k");
+        assert_eq!(band_label(9), "16k");
+    }
+}
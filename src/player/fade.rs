@@ -0,0 +1,153 @@
+//! Short gain ramps on playback transitions.
+//!
+//! Cutting audio off mid-waveform clicks. The output callback ramps the
+//! gain down over `audio.fade_ms` when playback pauses or stops and back
+//! up when it resumes. A seek (or a new track picked while playing) fades
+//! out what was buffered before it, drops the rest and fades the new audio
+//! in. The fade-out on pause reads ahead without consuming, so resuming
+//! starts again from where the fade began.
+//!
+//! The ramp works in place on the callback's buffer, so it needs no locks
+//! or allocations. A length of 0 turns fades off: every change is instant,
+//! as before fades existed.
+
+/// Fade lengths offered in settings, in ms (0 = off)
+pub const FADE_CHOICES_MS: [u32; 5] = [0, 50, 100, 150, 200];
+
+/// Fade length unless the config sets one
+pub const DEFAULT_FADE_MS: u32 = 100;
+
+/// Gain ramp state of the output callback
+#[derive(Debug, Clone)]
+pub struct Fader {
+    /// Gain of the last frame played
+    gain: f32,
+    /// Gain change per frame (0 = instant)
+    step: f32,
+    /// Fade length `step` was worked out for
+    fade_ms: u32,
+    sample_rate: u32,
+    channels: usize,
+    /// Samples read past the ring's read position without consuming them,
+    /// while fading out on pause
+    pub read_ahead: usize,
+}
+
+impl Fader {
+    /// A fader at full gain, with fades off until [`Fader::set_length`]
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            gain: 1.0,
+            step: 0.0,
+            fade_ms: 0,
+            sample_rate: sample_rate.max(1),
+            channels: usize::from(channels.max(1)),
+            read_ahead: 0,
+        }
+    }
+
+    /// Use fades of `fade_ms` (0 = instant)
+    pub fn set_length(&mut self, fade_ms: u32) {
+        if fade_ms == self.fade_ms {
+            return;
+        }
+        self.fade_ms = fade_ms;
+        let frames = u64::from(self.sample_rate) * u64::from(fade_ms) / 1000;
+        self.step = if frames == 0 {
+            0.0
+        } else {
+            1.0 / frames as f32
+        };
+    }
+
+    /// Whether fades are off
+    pub fn is_instant(&self) -> bool {
+        self.step == 0.0
+    }
+
+    /// Whether the gain has reached silence
+    pub fn is_silent(&self) -> bool {
+        self.gain <= 0.0
+    }
+
+    /// Jump to silence (after the buffer is drained)
+    pub fn silence(&mut self) {
+        self.gain = 0.0;
+        self.read_ahead = 0;
+    }
+
+    /// Jump to full gain (a new track starts as recorded)
+    pub fn reset(&mut self) {
+        self.gain = 1.0;
+        self.read_ahead = 0;
+    }
+
+    /// Ramp the gain toward `target` (0.0 or 1.0) across the interleaved
+    /// `samples`, scaling them in place. Past the target the gain stays put,
+    /// so silence zeroes the rest.
+    pub fn apply(&mut self, samples: &mut [f32], target: f32) {
+        if self.is_instant() {
+            self.gain = target;
+        }
+        if self.gain == target {
+            if target <= 0.0 {
+                samples.fill(0.0);
+            }
+            return;
+        }
+        for frame in samples.chunks_mut(self.channels) {
+            self.gain = if self.gain < target {
+                (self.gain + self.step).min(target)
+            } else {
+                (self.gain - self.step).max(target)
+            };
+            for sample in frame {
+                *sample *= self.gain;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fade_ramps_over_its_length() {
+        // 10ms at 1kHz: 10 frames
+        let mut fader = Fader::new(1000, 2);
+        fader.set_length(10);
+
+        let mut out = [1.0f32; 8];
+        fader.apply(&mut out, 0.0);
+        assert!((out[0] - 0.9).abs() < 1e-6);
+        assert_eq!(out[0], out[1], "both channels of a frame get one gain");
+        assert!(out[7] < out[0]);
+        assert!(!fader.is_silent());
+
+        let mut rest = [1.0f32; 20];
+        fader.apply(&mut rest, 0.0);
+        assert!(fader.is_silent());
+        assert_eq!(rest[19], 0.0);
+
+        // And back up
+        let mut up = [1.0f32; 40];
+        fader.apply(&mut up, 1.0);
+        assert!(up[0] > 0.0 && up[0] < 0.2);
+        assert_eq!(up[39], 1.0);
+    }
+
+    #[test]
+    fn test_instant_without_fade_length() {
+        let mut fader = Fader::new(48000, 2);
+        fader.set_length(0);
+        let mut out = [0.5f32; 4];
+        fader.apply(&mut out, 0.0);
+        assert_eq!(out, [0.0; 4]);
+        assert!(fader.is_silent());
+
+        let mut out = [0.5f32; 4];
+        fader.apply(&mut out, 1.0);
+        assert_eq!(out, [0.5; 4]);
+    }
+}
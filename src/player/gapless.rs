@@ -0,0 +1,277 @@
+//! Gapless playback support and verification.
+//!
+//! MP3 (and raw AAC) encoders add a few hundred samples of priming silence
+//! at the start of a stream and pad the last frame to a full block. LAME
+//! records both in its header: symphonia trims them when gapless mode is on,
+//! but it can only trim the padding when the header also gives the frame
+//! count. [`PaddingTrim`] covers the rest by holding back the last frames of
+//! the stream until the decoder knows they are padding.
+//!
+//! [`check_boundary`] decodes the end of one track and the start of the
+//! next and measures the silence between them. Continuous albums (live
+//! recordings, DJ mixes, classical movements) should join without any;
+//! files without gapless info typically leave 25–50 ms.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::Duration;
+
+use symphonia::core::codecs::{CODEC_TYPE_AAC, CODEC_TYPE_MP3, CodecParameters};
+
+use super::PlayerError;
+use super::decoder::AudioDecoder;
+use super::silence::SILENCE_THRESHOLD;
+
+/// Audio looked at on each side of a boundary
+const EDGE_WINDOW: Duration = Duration::from_millis(250);
+
+/// Silence at a boundary below this is inaudible and counts as seamless
+const GAP_TOLERANCE: Duration = Duration::from_millis(5);
+
+/// How a stream's encoder delay and padding are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncoderPadding {
+    /// Lossless, or a container with exact sample counts: nothing to trim
+    None,
+    /// Delay and padding (in frames) read from the file and trimmed
+    Trimmed { delay: u32, padding: u32 },
+    /// A lossy stream without gapless info: expect a short gap at each end
+    Unknown,
+}
+
+impl EncoderPadding {
+    pub fn from_params(params: &CodecParameters) -> Self {
+        if params.delay.is_some() || params.padding.is_some() {
+            return Self::Trimmed {
+                delay: params.delay.unwrap_or(0),
+                padding: params.padding.unwrap_or(0),
+            };
+        }
+        match params.codec {
+            CODEC_TYPE_MP3 | CODEC_TYPE_AAC => Self::Unknown,
+            _ => Self::None,
+        }
+    }
+
+    /// Why a gap next to this file might be there, if it explains one
+    pub fn gap_cause(&self, codec: &str) -> Option<String> {
+        match self {
+            Self::Unknown if codec == "MP3" => Some("MP3 without a LAME header".to_string()),
+            Self::Unknown => Some(format!("{} without gapless info", codec)),
+            _ => None,
+        }
+    }
+}
+
+/// Drops the encoder padding at the end of a stream whose length isn't
+/// known up front, by always holding back that many frames.
+#[derive(Debug, Clone)]
+pub struct PaddingTrim {
+    /// Interleaved samples held back
+    hold: usize,
+    held: Vec<f32>,
+}
+
+impl PaddingTrim {
+    pub fn new(padding_frames: u32, channels: u16) -> Self {
+        Self {
+            hold: padding_frames as usize * usize::from(channels.max(1)),
+            held: Vec::new(),
+        }
+    }
+
+    /// Take decoded samples, returning the ones that are safe to play
+    pub fn apply(&mut self, samples: Vec<f32>) -> Vec<f32> {
+        if self.held.is_empty() && samples.len() <= self.hold {
+            self.held = samples;
+            return Vec::new();
+        }
+        self.held.extend(samples);
+        let ready = self.held.len().saturating_sub(self.hold);
+        self.held.drain(..ready).collect()
+    }
+
+    /// Forget the held samples (after a seek; at the end they're padding)
+    pub fn reset(&mut self) {
+        self.held.clear();
+    }
+}
+
+/// Silence at one side of a boundary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    /// Silence between the boundary and the nearest sound
+    pub silence: Duration,
+    /// Whether the whole window next to the boundary is silent
+    pub silent: bool,
+}
+
+impl Edge {
+    /// Measure the silence at the start (`from_end` false) or end of
+    /// interleaved samples
+    pub fn measure(samples: &[f32], channels: u16, sample_rate: u32, from_end: bool) -> Self {
+        let channels = usize::from(channels.max(1));
+        let frames = samples.chunks(channels);
+        let is_silent = |frame: &[f32]| frame.iter().all(|s| s.abs() < SILENCE_THRESHOLD);
+        let total = samples.len() / channels;
+        let silent_frames = if from_end {
+            frames.rev().take_while(|f| is_silent(f)).count()
+        } else {
+            frames.take_while(|f| is_silent(f)).count()
+        };
+        Self {
+            silence: Duration::from_secs_f64(silent_frames as f64 / sample_rate.max(1) as f64),
+            silent: silent_frames >= total,
+        }
+    }
+}
+
+/// What's heard between two consecutive tracks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// The music carries on without a break
+    Seamless,
+    /// The music carries on, but a gap of this length interrupts it
+    Gap(Duration),
+    /// One of the tracks fades out or starts from silence: a normal pause
+    Pause,
+}
+
+impl Boundary {
+    /// Classify a boundary from the end of one track and the start of the next
+    pub fn classify(end: Edge, start: Edge) -> Self {
+        if end.silent || start.silent {
+            return Self::Pause;
+        }
+        let gap = end.silence + start.silence;
+        if gap <= GAP_TOLERANCE {
+            Self::Seamless
+        } else {
+            Self::Gap(gap)
+        }
+    }
+}
+
+/// The boundary between two tracks, with each file's padding handling
+#[derive(Debug, Clone)]
+pub struct BoundaryCheck {
+    pub boundary: Boundary,
+    pub first: (String, EncoderPadding),
+    pub second: (String, EncoderPadding),
+}
+
+impl BoundaryCheck {
+    /// The likely cause of a gap, if a file lacks gapless info
+    pub fn cause(&self) -> Option<String> {
+        self.first
+            .1
+            .gap_cause(&self.first.0)
+            .or_else(|| self.second.1.gap_cause(&self.second.0))
+    }
+}
+
+/// Decode the end of `first` and the start of `second` and measure the
+/// silence between them. Blocking.
+pub fn check_boundary(first: &Path, second: &Path) -> Result<BoundaryCheck, PlayerError> {
+    let mut a = AudioDecoder::open(first)?;
+    let end = tail_edge(&mut a)?;
+    let mut b = AudioDecoder::open(second)?;
+    let start = head_edge(&mut b)?;
+    Ok(BoundaryCheck {
+        boundary: Boundary::classify(end, start),
+        first: (a.format_info.codec.clone(), a.padding()),
+        second: (b.format_info.codec.clone(), b.padding()),
+    })
+}
+
+fn window_samples(decoder: &AudioDecoder) -> usize {
+    let frames = (EDGE_WINDOW.as_secs_f64() * decoder.sample_rate() as f64) as usize;
+    frames * usize::from(decoder.channels().max(1))
+}
+
+fn head_edge(decoder: &mut AudioDecoder) -> Result<Edge, PlayerError> {
+    let window = window_samples(decoder);
+    let mut samples = Vec::with_capacity(window);
+    while samples.len() < window
+        && decoder
+            .decode_next(|s| samples.extend_from_slice(s))?
+            .is_some()
+    {}
+    samples.truncate(window);
+    Ok(Edge::measure(
+        &samples,
+        decoder.channels(),
+        decoder.sample_rate(),
+        false,
+    ))
+}
+
+fn tail_edge(decoder: &mut AudioDecoder) -> Result<Edge, PlayerError> {
+    let window = window_samples(decoder);
+    let duration = decoder.duration().as_secs_f64();
+    // Start a little before the window so an inexact seek still covers it
+    let lead_in = EDGE_WINDOW.as_secs_f64() * 4.0;
+    if duration > lead_in {
+        decoder.seek(((duration - lead_in) / duration) as f32)?;
+    }
+    let mut samples = VecDeque::with_capacity(window * 2);
+    while decoder
+        .decode_next(|s| {
+            samples.extend(s);
+            let excess = samples.len().saturating_sub(window);
+            samples.drain(..excess);
+        })?
+        .is_some()
+    {}
+    Ok(Edge::measure(
+        samples.make_contiguous(),
+        decoder.channels(),
+        decoder.sample_rate(),
+        true,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_padding_trim_holds_back_the_end() {
+        // Stereo, 3 frames of padding
+        let mut trim = PaddingTrim::new(3, 2);
+        assert!(trim.apply(vec![1.0; 4]).is_empty());
+        assert_eq!(trim.apply(vec![2.0; 8]), [[1.0; 4], [2.0; 4]].concat()[..6]);
+        assert_eq!(
+            trim.apply(vec![3.0; 10]),
+            [[2.0; 6], [3.0; 6]].concat()[..10]
+        );
+        // What's left at the end is the padding
+        assert_eq!(trim.held, vec![3.0; 6]);
+
+        trim.reset();
+        assert_eq!(trim.apply(vec![4.0; 8]), vec![4.0; 2]);
+    }
+
+    #[test]
+    fn test_boundary_classification() {
+        // 1 kHz mono: music up to 20 frames before the end
+        let mut end = vec![0.5; 200];
+        end.extend([0.0; 20]);
+        let end = Edge::measure(&end, 1, 1000, true);
+        assert_eq!(end.silence, Duration::from_millis(20));
+        assert!(!end.silent);
+
+        let start = Edge::measure(&[0.3; 100], 1, 1000, false);
+        assert_eq!(
+            Boundary::classify(end, start),
+            Boundary::Gap(Duration::from_millis(20))
+        );
+
+        let tight = Edge::measure(&[0.0, 0.0, 0.5, 0.5], 1, 1000, true);
+        assert_eq!(Boundary::classify(tight, start), Boundary::Seamless);
+
+        let faded = Edge::measure(&[0.0; 50], 2, 1000, true);
+        assert!(faded.silent);
+        assert_eq!(Boundary::classify(faded, start), Boundary::Pause);
+    }
+}
@@ -0,0 +1,293 @@
+//! Media key fallback for sessions without working OS media controls.
+//!
+//! Media keys normally reach the player through the OS media controls
+//! ([`super::media_controls`]). On stripped-down Windows sessions and some
+//! Linux window managers that integration comes up fine but no key ever
+//! arrives. With the `media-keys` feature built in and
+//! `audio.media_key_fallback` set, a global keyboard listener (rdev) also
+//! watches for the play/pause, next, previous and stop keys and feeds them
+//! into the same [`MediaControlCommand`](super::MediaControlCommand) path.
+//!
+//! Where the OS integration works, each press then arrives twice. Presses
+//! the listener sees are held for [`DUPLICATE_WINDOW`]; if the OS sends the
+//! same key in that time the listener's copy is dropped and counted as a
+//! conflict ([`KeyArbiter`]).
+//!
+//! macOS delivers media keys as system events the listener can't see, so
+//! the fallback only helps on Windows and X11.
+
+use std::time::{Duration, Instant};
+
+/// How long a listener press waits for the OS to send the same key
+pub const DUPLICATE_WINDOW: Duration = Duration::from_millis(250);
+
+/// The media keys the listener knows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKey {
+    PlayPause,
+    Next,
+    Previous,
+    Stop,
+}
+
+impl MediaKey {
+    /// The key for a raw key code the listener reports: a virtual-key code
+    /// on Windows, an X11 keycode elsewhere
+    pub fn from_code(code: u32) -> Option<Self> {
+        #[cfg(target_os = "windows")]
+        let key = match code {
+            0xB0 => MediaKey::Next,
+            0xB1 => MediaKey::Previous,
+            0xB2 => MediaKey::Stop,
+            0xB3 => MediaKey::PlayPause,
+            _ => return None,
+        };
+        #[cfg(not(target_os = "windows"))]
+        let key = match code {
+            171 => MediaKey::Next,
+            172 => MediaKey::PlayPause,
+            173 => MediaKey::Previous,
+            174 => MediaKey::Stop,
+            _ => return None,
+        };
+        Some(key)
+    }
+}
+
+/// Drops listener presses the OS media controls also delivered
+#[derive(Debug, Default)]
+pub struct KeyArbiter {
+    /// Keys the OS sent recently
+    from_os: Vec<(MediaKey, Instant)>,
+    /// Listener presses waiting out the window
+    held: Vec<(MediaKey, Instant)>,
+    /// Listener presses dropped as duplicates
+    duplicates: u32,
+}
+
+impl KeyArbiter {
+    /// Note a key the OS media controls sent (those are always handled)
+    pub fn os_pressed(&mut self, key: MediaKey, now: Instant) {
+        if let Some(index) = self.held.iter().position(|(held, _)| *held == key) {
+            self.held.remove(index);
+            self.duplicates += 1;
+        } else {
+            self.from_os.push((key, now));
+        }
+        self.forget(now);
+    }
+
+    /// Note a key the listener saw; it's released by [`Self::take_ready`]
+    /// unless the OS sends it too
+    pub fn key_pressed(&mut self, key: MediaKey, now: Instant) {
+        self.forget(now);
+        if let Some(index) = self.from_os.iter().position(|(os, _)| *os == key) {
+            self.from_os.remove(index);
+            self.duplicates += 1;
+        } else {
+            self.held.push((key, now));
+        }
+    }
+
+    /// Listener presses the OS didn't send within the window
+    pub fn take_ready(&mut self, now: Instant) -> Vec<MediaKey> {
+        let (ready, held) = self
+            .held
+            .drain(..)
+            .partition(|(_, at)| now.duration_since(*at) >= DUPLICATE_WINDOW);
+        self.held = held;
+        ready.into_iter().map(|(key, _)| key).collect()
+    }
+
+    /// Listener presses dropped because the OS sent them too
+    pub fn duplicates(&self) -> u32 {
+        self.duplicates
+    }
+
+    fn forget(&mut self, now: Instant) {
+        self.from_os
+            .retain(|(_, at)| now.duration_since(*at) < DUPLICATE_WINDOW);
+    }
+}
+
+#[cfg(feature = "player")]
+pub use listener::MediaKeyListener;
+
+#[cfg(feature = "player")]
+mod listener {
+    use std::sync::mpsc::Receiver;
+    use std::time::Instant;
+
+    use super::{KeyArbiter, MediaKey};
+    use crate::player::MediaControlCommand;
+
+    impl MediaKey {
+        fn command(self) -> MediaControlCommand {
+            match self {
+                MediaKey::PlayPause => MediaControlCommand::Toggle,
+                MediaKey::Next => MediaControlCommand::Next,
+                MediaKey::Previous => MediaControlCommand::Previous,
+                MediaKey::Stop => MediaControlCommand::Stop,
+            }
+        }
+
+        /// The key an OS command came from, if it was a key
+        fn from_command(command: &MediaControlCommand) -> Option<Self> {
+            match command {
+                MediaControlCommand::Play
+                | MediaControlCommand::Pause
+                | MediaControlCommand::Toggle => Some(MediaKey::PlayPause),
+                MediaControlCommand::Next => Some(MediaKey::Next),
+                MediaControlCommand::Previous => Some(MediaKey::Previous),
+                MediaControlCommand::Stop => Some(MediaKey::Stop),
+                MediaControlCommand::Seek(_) | MediaControlCommand::SeekRelative(_) => None,
+            }
+        }
+    }
+
+    /// The global media key listener, and the presses it saw
+    pub struct MediaKeyListener {
+        keys: Receiver<MediaKey>,
+        arbiter: KeyArbiter,
+        /// Whether [`Self::take_conflict`] reported one yet
+        conflict_reported: bool,
+    }
+
+    impl MediaKeyListener {
+        /// Start listening on its own thread. `None` when the `media-keys`
+        /// feature isn't built in or the thread can't start.
+        pub fn start() -> Option<Self> {
+            #[cfg(feature = "media-keys")]
+            {
+                let (tx, keys) = std::sync::mpsc::channel();
+                let spawned =
+                    std::thread::Builder::new()
+                        .name("media-keys".into())
+                        .spawn(move || {
+                            let result = rdev::listen(move |event| {
+                                if let rdev::EventType::KeyPress(rdev::Key::Unknown(code)) =
+                                    event.event_type
+                                    && let Some(key) = MediaKey::from_code(code)
+                                {
+                                    tracing::debug!("Media key from listener: {:?}", key);
+                                    let _ = tx.send(key);
+                                }
+                            });
+                            if let Err(e) = result {
+                                tracing::warn!("Media key listener stopped: {:?}", e);
+                            }
+                        });
+                match spawned {
+                    Ok(_) => {
+                        tracing::info!("Media key listener started");
+                        Some(Self {
+                            keys,
+                            arbiter: KeyArbiter::default(),
+                            conflict_reported: false,
+                        })
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to spawn media key listener: {}", e);
+                        None
+                    }
+                }
+            }
+            #[cfg(not(feature = "media-keys"))]
+            {
+                tracing::warn!(
+                    "audio.media_key_fallback is set, but this build has no media-keys feature"
+                );
+                None
+            }
+        }
+
+        /// Note a command from the OS media controls, so the listener's
+        /// copy of the same press is dropped
+        pub fn os_command(&mut self, command: &MediaControlCommand) {
+            if let Some(key) = MediaKey::from_command(command) {
+                self.arbiter.os_pressed(key, Instant::now());
+            }
+        }
+
+        /// Listener presses to handle now: those the OS didn't also send
+        pub fn take_commands(&mut self) -> Vec<MediaControlCommand> {
+            let now = Instant::now();
+            while let Ok(key) = self.keys.try_recv() {
+                self.arbiter.key_pressed(key, now);
+            }
+            self.arbiter
+                .take_ready(now)
+                .into_iter()
+                .map(MediaKey::command)
+                .collect()
+        }
+
+        /// True once, after the first press the OS media controls also
+        /// delivered: they work, and the fallback isn't needed
+        pub fn take_conflict(&mut self) -> bool {
+            if self.conflict_reported || self.arbiter.duplicates() == 0 {
+                return false;
+            }
+            self.conflict_reported = true;
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listener_press_released_after_window() {
+        let start = Instant::now();
+        let mut arbiter = KeyArbiter::default();
+        arbiter.key_pressed(MediaKey::Next, start);
+        assert!(arbiter.take_ready(start).is_empty());
+        assert_eq!(
+            arbiter.take_ready(start + DUPLICATE_WINDOW),
+            vec![MediaKey::Next]
+        );
+        assert!(arbiter.take_ready(start + DUPLICATE_WINDOW * 2).is_empty());
+        assert_eq!(arbiter.duplicates(), 0);
+    }
+
+    #[test]
+    fn test_os_copy_drops_listener_press() {
+        let start = Instant::now();
+        let later = start + Duration::from_millis(40);
+        let mut arbiter = KeyArbiter::default();
+
+        // Listener first, then the OS
+        arbiter.key_pressed(MediaKey::PlayPause, start);
+        arbiter.os_pressed(MediaKey::PlayPause, later);
+        assert!(arbiter.take_ready(start + DUPLICATE_WINDOW).is_empty());
+
+        // OS first, then the listener
+        arbiter.os_pressed(MediaKey::Next, start);
+        arbiter.key_pressed(MediaKey::Next, later);
+        assert!(arbiter.take_ready(later + DUPLICATE_WINDOW).is_empty());
+        assert_eq!(arbiter.duplicates(), 2);
+
+        // A different key, or the same one long after, isn't a copy
+        arbiter.os_pressed(MediaKey::Stop, start);
+        arbiter.key_pressed(MediaKey::Previous, later);
+        arbiter.key_pressed(MediaKey::Stop, start + DUPLICATE_WINDOW * 2);
+        assert_eq!(
+            arbiter.take_ready(start + DUPLICATE_WINDOW * 3),
+            vec![MediaKey::Previous, MediaKey::Stop]
+        );
+        assert_eq!(arbiter.duplicates(), 2);
+    }
+
+    #[test]
+    fn test_key_codes() {
+        #[cfg(target_os = "windows")]
+        let (play, next) = (0xB3, 0xB0);
+        #[cfg(not(target_os = "windows"))]
+        let (play, next) = (172, 171);
+        assert_eq!(MediaKey::from_code(play), Some(MediaKey::PlayPause));
+        assert_eq!(MediaKey::from_code(next), Some(MediaKey::Next));
+        assert_eq!(MediaKey::from_code(65), None);
+    }
+}
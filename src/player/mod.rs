@@ -53,32 +53,71 @@
 //! DEBUG ui::events: Received StatusChanged: Stopped -> Playing
 //! ```
 
+//!
+//! # Features
+//!
+//! Audio output, media controls, resampling and the visualizer need the
+//! `player` feature. Decoding, silence, gapless and loudness analysis, the
+//! queue and the shared state types are always built, since scans and CLI
+//! checks use them.
+//!
+//! Tracks whose path is a stream or cloud drive URL are decoded as they
+//! download, which needs the `enrichment` feature (the HTTP client).
+
+#[cfg(feature = "player")]
 mod audio;
+pub mod buffering;
+pub mod crossfade;
 mod decoder;
+pub mod equalizer;
+pub mod fade;
+pub mod gapless;
+#[cfg(feature = "player")]
 pub mod media_controls;
+pub mod media_keys;
+pub mod normalization;
+pub mod peak;
 mod queue;
+pub mod replaygain;
+#[cfg(feature = "player")]
 mod resampler;
+pub mod silence;
 pub mod simd;
 mod state;
+#[cfg(feature = "enrichment")]
+mod stream;
+#[cfg(feature = "player")]
 mod visualization;
+pub mod volume;
 
+#[cfg(feature = "player")]
 pub use audio::{AudioConfig, AudioOutput};
 pub use decoder::AudioDecoder;
+#[cfg(feature = "player")]
 pub use media_controls::{
     MediaControlCommand, MediaControlsHandle, MediaControlsMetadata, MediaPlaybackState,
 };
-pub use queue::{PlayQueue, QueueItem, RepeatMode};
+#[cfg(feature = "player")]
+pub use media_keys::MediaKeyListener;
+pub use queue::{EndOfQueue, PlayQueue, QueueItem, RepeatMode, ShuffleMode};
+#[cfg(feature = "player")]
 pub use resampler::Resampler;
 pub use state::{
     AudioQuality, AudioSharedState, PlaybackStatus, PlayerCommand, PlayerEvent, PlayerState,
     TrackInfo, format_duration, format_duration_secs,
 };
+#[cfg(feature = "player")]
 pub use visualization::{SpectrumData, VisualizationMode, Visualizer};
 
+#[cfg(feature = "player")]
 use crossbeam_channel::{Receiver, Sender, bounded};
+#[cfg(feature = "player")]
 use parking_lot::RwLock;
-use std::path::PathBuf;
+#[cfg(feature = "player")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "player")]
 use std::sync::Arc;
+#[cfg(feature = "player")]
 use std::time::Duration;
 
 /// The integrated audio player.
@@ -97,6 +136,7 @@ use std::time::Duration;
 /// 3. UI calls `poll_events()` to receive confirmed state changes
 ///
 /// This avoids race conditions from reading state immediately after commands.
+#[cfg(feature = "player")]
 pub struct Player {
     /// Current player state (shared with audio thread)
     state: Arc<RwLock<PlayerState>>,
@@ -111,31 +151,63 @@ pub struct Player {
     /// The play queue
     queue: PlayQueue,
     /// Audio output handle
-    _audio: Option<AudioOutput>,
+    audio: Option<AudioOutput>,
+    /// Next track last sent to the audio thread
+    next_sent: Option<PathBuf>,
 }
 
+#[cfg(feature = "player")]
 impl Player {
     /// Create a new player instance.
     ///
     /// Returns `None` if audio output cannot be initialized.
     pub fn new() -> Option<Self> {
+        Self::with_output(AudioOutput::new).ok()
+    }
+
+    /// Create a player without a sound card that plays at `speed` times
+    /// real time, for tests.
+    #[cfg(feature = "headless-audio")]
+    pub fn headless(speed: f32) -> Result<Self, PlayerError> {
+        Self::with_output(|state, command_rx, event_tx, viz_tx| {
+            AudioOutput::headless(
+                state,
+                command_rx,
+                event_tx,
+                viz_tx,
+                &AudioConfig::default(),
+                speed,
+            )
+        })
+    }
+
+    fn with_output<F>(build: F) -> Result<Self, PlayerError>
+    where
+        F: FnOnce(
+            Arc<RwLock<PlayerState>>,
+            Receiver<PlayerCommand>,
+            Sender<PlayerEvent>,
+            Sender<SpectrumData>,
+        ) -> Result<AudioOutput, PlayerError>,
+    {
         let state = Arc::new(RwLock::new(PlayerState::default()));
         let (command_tx, command_rx) = bounded(32);
         let (event_tx, event_rx) = bounded(64); // Events from audio thread
         let (viz_tx, viz_rx) = bounded(4); // Small buffer, drop old frames
 
         // Try to initialize audio output
-        let audio = AudioOutput::new(Arc::clone(&state), command_rx, event_tx, viz_tx).ok()?;
+        let audio = build(Arc::clone(&state), command_rx, event_tx, viz_tx)?;
         let audio_shared = Some(Arc::clone(&audio.audio_shared));
 
-        Some(Self {
+        Ok(Self {
             state,
             audio_shared,
             command_tx,
             event_rx,
             viz_rx,
             queue: PlayQueue::new(),
-            _audio: Some(audio),
+            audio: Some(audio),
+            next_sent: None,
         })
     }
 
@@ -165,9 +237,25 @@ impl Player {
                 .send(PlayerCommand::Play)
                 .map_err(|_| PlayerError::ChannelClosed)?;
         }
+        self.sync_next();
         Ok(())
     }
 
+    /// Tell the audio thread which track follows the current one, so it can
+    /// crossfade into it. Only sends when that changed; call it after the
+    /// queue changes (the UI does on each position update).
+    pub fn sync_next(&mut self) {
+        let next = self.queue.next_up().map(|item| item.path.clone());
+        if next != self.next_sent
+            && self
+                .command_tx
+                .try_send(PlayerCommand::SetNext(next.clone()))
+                .is_ok()
+        {
+            self.next_sent = next;
+        }
+    }
+
     /// Play a file immediately (clears queue and starts playback).
     pub fn play_file(&mut self, path: PathBuf) -> Result<(), PlayerError> {
         self.queue.clear();
@@ -181,6 +269,11 @@ impl Player {
         self.queue.add(QueueItem::from_path(path));
     }
 
+    /// Queue a file to play right after the current track.
+    pub fn queue_file_next(&mut self, path: PathBuf) {
+        self.queue.insert_after_current(QueueItem::from_path(path));
+    }
+
     /// Play the current track in the queue (loading it if necessary).
     ///
     /// If paused, resumes playback.
@@ -237,6 +330,14 @@ impl Player {
         }
     }
 
+    /// Stop the audio thread and wait up to `timeout` for it to exit.
+    ///
+    /// True once it has; the player can't play anything afterwards.
+    pub fn shutdown(&mut self, timeout: Duration) -> bool {
+        let _ = self.command_tx.send(PlayerCommand::Shutdown);
+        self.audio.as_mut().is_none_or(|audio| audio.join(timeout))
+    }
+
     /// Stop playback.
     pub fn stop(&self) -> Result<(), PlayerError> {
         self.command_tx
@@ -275,6 +376,40 @@ impl Player {
         Ok(())
     }
 
+    /// Move on after the current track played to the end.
+    ///
+    /// Plays the next track unless the finished one was marked to stop after,
+    /// in which case the audio thread has already stopped cleanly and the
+    /// queue just steps forward so Play resumes with the next track. Returns
+    /// whether playback continued.
+    pub fn advance_after_finish(&mut self) -> Result<bool, PlayerError> {
+        if self.queue.take_stop_after_current() {
+            self.queue.skip_forward();
+            return Ok(false);
+        }
+        let continued = self.queue.skip_forward().is_some();
+        if continued {
+            self.load_and_play_current()?;
+        }
+        Ok(continued)
+    }
+
+    /// Move the queue on to the track the audio thread started crossfading
+    /// into, without loading it again. False if it's no longer queued.
+    pub fn advance_after_crossfade(&mut self, path: &Path) -> bool {
+        // The audio thread used up the next track it was sent
+        self.next_sent = None;
+        if self.queue.next_up().is_some_and(|item| item.path == path) {
+            self.queue.skip_forward();
+        } else if let Some(index) = self.queue.items().iter().position(|item| item.path == path) {
+            self.queue.jump_to(index);
+        } else {
+            return false;
+        }
+        self.sync_next();
+        true
+    }
+
     /// Skip to previous track (or restart if > 3 seconds in).
     pub fn previous(&mut self) -> Result<(), PlayerError> {
         let position = self.state.read().position;
@@ -303,9 +438,9 @@ impl Player {
             state.quality.buffer_fill = audio_shared.buffer_fill() as f32 / 100.0;
 
             // Estimate latency: ring buffer fill + typical WASAPI buffer (~10ms)
-            // Ring buffer: 48000 samples at 48kHz stereo = ~500ms max
-            // Current fill represents how much audio is buffered
-            let buffer_latency_ms = state.quality.buffer_fill * 500.0;
+            // The ring buffer holds up to MAX_AHEAD_MS; the fill is how much
+            // of it is buffered
+            let buffer_latency_ms = state.quality.buffer_fill * buffering::MAX_AHEAD_MS as f32;
             let wasapi_latency_ms = 10.0; // Typical WASAPI shared mode latency
             state.quality.latency_ms = buffer_latency_ms + wasapi_latency_ms;
         }
@@ -322,10 +457,85 @@ impl Player {
                 peak_callback_us: shared.peak_callback_us(),
                 underruns: shared.underruns(),
                 buffer_fill_percent: shared.buffer_fill(),
+                decode_ahead_ms: shared.decode_ahead(),
+                clipped_samples: shared.clipped_samples(),
+                decode_nanos: shared.decode_nanos(),
+                resample_nanos: shared.resample_nanos(),
+                events_dropped: shared.events_dropped(),
                 simd_level: simd::current_simd_level().name(),
             })
     }
 
+    /// How full the channels between the UI and the audio thread are.
+    /// Events pile up between [`Player::poll_events`] calls, so read this
+    /// before polling.
+    pub fn channel_backlogs(&self) -> ChannelBacklogs {
+        ChannelBacklogs {
+            commands: ChannelBacklog::new(self.command_tx.len(), self.command_tx.capacity()),
+            events: ChannelBacklog::new(self.event_rx.len(), self.event_rx.capacity()),
+            visualization: ChannelBacklog::new(self.viz_rx.len(), self.viz_rx.capacity()),
+        }
+    }
+
+    /// Peak and RMS levels since the last call, and the samples clipped in
+    /// that time. Peaks reset on each call, so call it from one place.
+    pub fn take_levels(&self) -> Option<simd::ChannelLevels> {
+        self.audio_shared
+            .as_ref()
+            .map(|shared| shared.take_levels())
+    }
+
+    /// Set the buffer size in ms (`buffering::AUTO` tunes it to underruns).
+    /// Takes effect while playing.
+    pub fn set_buffer_ms(&self, ms: u32) {
+        if let Some(ref audio_shared) = self.audio_shared {
+            audio_shared.set_buffer_setting(ms);
+        }
+    }
+
+    /// Set the fade on pause, stop and seek in ms (0 = instant).
+    pub fn set_fade_ms(&self, ms: u32) {
+        if let Some(ref audio_shared) = self.audio_shared {
+            audio_shared.set_fade_ms(ms);
+        }
+    }
+
+    /// Set the crossfade between tracks in seconds (0 = off).
+    pub fn set_crossfade_secs(&self, secs: u32) {
+        if let Some(ref audio_shared) = self.audio_shared {
+            audio_shared.set_crossfade_secs(secs);
+        }
+    }
+
+    /// Set the loudness normalization mode and preamp (dB). Takes effect
+    /// on the track playing.
+    pub fn set_normalization(&self, mode: normalization::NormalizationMode, preamp_db: f32) {
+        self.state.write().normalization = mode;
+        if let Some(ref audio_shared) = self.audio_shared {
+            audio_shared.set_normalization(mode, preamp_db);
+        }
+    }
+
+    /// Turn the equalizer on or off and set its band gains (dB). Heard once
+    /// the audio already buffered has played.
+    pub fn set_equalizer(&self, enabled: bool, gains: &equalizer::EqGains) {
+        if let Some(ref audio_shared) = self.audio_shared {
+            audio_shared.set_equalizer(enabled, gains);
+        }
+    }
+
+    /// Hand the audio thread the loudness the library stored for a track,
+    /// for when its file has no loudness tags. Ignored unless `path` is
+    /// the track playing.
+    pub fn set_stored_loudness(&self, path: PathBuf, loudness: crate::metadata::Loudness) {
+        if loudness.is_empty() {
+            return;
+        }
+        let _ = self
+            .command_tx
+            .try_send(PlayerCommand::StoredLoudness(path, loudness));
+    }
+
     /// Reset performance statistics.
     pub fn reset_stats(&self) {
         if let Some(ref audio_shared) = self.audio_shared {
@@ -352,8 +562,21 @@ impl Player {
     pub fn queue_mut(&mut self) -> &mut PlayQueue {
         &mut self.queue
     }
+
+    /// Follow files that moved on disk: the queue and the current track.
+    /// Playback of a moved file carries on from its open handle.
+    pub fn relocate(&mut self, moved: impl Fn(&Path) -> Option<PathBuf>) -> usize {
+        let mut n = self.queue.relocate(&moved);
+        let mut state = self.state.write();
+        if let Some(to) = state.current_track.as_deref().and_then(&moved) {
+            state.current_track = Some(to);
+            n += 1;
+        }
+        n
+    }
 }
 
+#[cfg(feature = "player")]
 impl Default for Player {
     fn default() -> Self {
         Self::new().expect("Failed to initialize audio output")
@@ -361,6 +584,7 @@ impl Default for Player {
 }
 
 /// List available audio output devices.
+#[cfg(feature = "player")]
 pub fn list_audio_devices() -> Vec<String> {
     use cpal::traits::{DeviceTrait, HostTrait};
     let host = cpal::default_host();
@@ -370,6 +594,7 @@ pub fn list_audio_devices() -> Vec<String> {
 }
 
 /// Get the current/default audio device name.
+#[cfg(feature = "player")]
 pub fn current_audio_device() -> String {
     use cpal::traits::{DeviceTrait, HostTrait};
     let host = cpal::default_host();
@@ -395,6 +620,9 @@ pub enum PlayerError {
 
     #[error("File not found: {0}")]
     FileNotFound(String),
+
+    #[error("Stream failed: {0}")]
+    Stream(String),
 }
 
 /// Audio performance statistics for monitoring.
@@ -410,6 +638,16 @@ pub struct AudioPerformanceStats {
     pub underruns: u32,
     /// Current buffer fill percentage (0-100)
     pub buffer_fill_percent: u32,
+    /// Decode-ahead the decoder thread keeps buffered, in ms
+    pub decode_ahead_ms: u32,
+    /// Samples at or beyond full scale before volume
+    pub clipped_samples: u64,
+    /// Time the decoder thread spent decoding, in nanoseconds
+    pub decode_nanos: u64,
+    /// Time the decoder thread spent resampling, in nanoseconds
+    pub resample_nanos: u64,
+    /// Events dropped because the UI didn't take them in time
+    pub events_dropped: u32,
     /// SIMD acceleration level in use
     pub simd_level: &'static str,
 }
@@ -443,9 +681,44 @@ impl AudioPerformanceStats {
     }
 }
 
+/// Messages waiting in one channel, and how many it holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChannelBacklog {
+    pub queued: usize,
+    pub capacity: usize,
+}
+
+impl ChannelBacklog {
+    /// Capacity 0 stands for unbounded
+    pub fn new(queued: usize, capacity: Option<usize>) -> Self {
+        Self {
+            queued,
+            capacity: capacity.unwrap_or(0),
+        }
+    }
+
+    /// Whether the channel has no room left
+    pub fn is_full(&self) -> bool {
+        self.capacity > 0 && self.queued >= self.capacity
+    }
+}
+
+/// Backlogs of the player's channels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChannelBacklogs {
+    /// UI → audio thread
+    pub commands: ChannelBacklog,
+    /// Audio thread → UI
+    pub events: ChannelBacklog,
+    /// Spectrum frames, audio thread → UI
+    pub visualization: ChannelBacklog,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
 
     #[test]
     fn test_player_state_default() {
@@ -467,3 +740,334 @@ mod tests {
         assert!(queue.is_empty());
     }
 }
+
+/// End-to-end tests of the command/event flow on the headless output.
+#[cfg(all(test, feature = "headless-audio"))]
+mod headless_tests {
+    use super::*;
+    use std::path::Path;
+    use std::time::Instant;
+
+    /// Plenty for the decoder thread at 20x real time, even in debug builds
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Write a 16-bit stereo 44.1 kHz sine wave
+    fn write_wav(path: &Path, secs: f32) {
+        let frames = (44100.0 * secs) as u32;
+        let mut data = Vec::with_capacity(frames as usize * 4);
+        for i in 0..frames {
+            let sample = ((i as f32 * 0.05).sin() * 8000.0) as i16;
+            data.extend_from_slice(&sample.to_le_bytes());
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+        let mut wav = Vec::with_capacity(44 + data.len());
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&44100u32.to_le_bytes());
+        wav.extend_from_slice(&(44100u32 * 4).to_le_bytes());
+        wav.extend_from_slice(&4u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&data);
+        std::fs::write(path, wav).unwrap();
+    }
+
+    /// Poll events until one matches, panicking after [`TIMEOUT`]
+    fn wait_for(player: &Player, what: &str, matches: impl Fn(&PlayerEvent) -> bool) {
+        let start = Instant::now();
+        while start.elapsed() < TIMEOUT {
+            if player.poll_events().iter().any(&matches) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("timed out waiting for {}", what);
+    }
+
+    fn wait_for_status(player: &Player, status: PlaybackStatus) {
+        wait_for(
+            player,
+            &format!("{:?}", status),
+            |e| matches!(e, PlayerEvent::StatusChanged(s) if *s == status),
+        );
+        assert_eq!(player.state().status, status);
+    }
+
+    #[test]
+    fn test_load_play_pause_resume_stop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("one.wav");
+        write_wav(&path, 2.0);
+        let mut player = Player::headless(1.0).unwrap();
+
+        player.play_file(path.clone()).unwrap();
+        wait_for(&player, "TrackLoaded", |e| {
+            matches!(e, PlayerEvent::TrackLoaded { path: p, duration, .. }
+                if *p == path && duration.as_millis() == 2000)
+        });
+        assert_eq!(player.state().status, PlaybackStatus::Playing);
+        assert_eq!(
+            player.state().current_track.as_deref(),
+            Some(path.as_path())
+        );
+
+        player.pause().unwrap();
+        wait_for_status(&player, PlaybackStatus::Paused);
+        let paused_at = player.state().position;
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(player.state().position, paused_at);
+
+        player.toggle().unwrap();
+        wait_for_status(&player, PlaybackStatus::Playing);
+
+        player.stop().unwrap();
+        wait_for_status(&player, PlaybackStatus::Stopped);
+    }
+
+    #[test]
+    fn test_seek_moves_position() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long.wav");
+        write_wav(&path, 4.0);
+        let mut player = Player::headless(1.0).unwrap();
+
+        player.play_file(path).unwrap();
+        wait_for_status(&player, PlaybackStatus::Playing);
+        player.seek(0.5).unwrap();
+
+        let start = Instant::now();
+        while player.state().position < Duration::from_secs(2) {
+            assert!(start.elapsed() < TIMEOUT, "seek never took effect");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(player.state().position < Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_track_end_advances_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("01.wav");
+        let second = dir.path().join("02.wav");
+        write_wav(&first, 0.5);
+        write_wav(&second, 0.5);
+        let mut player = Player::headless(20.0).unwrap();
+
+        player.play_file(first).unwrap();
+        player.queue_file(second.clone());
+        wait_for(&player, "first track end", |e| {
+            matches!(e, PlayerEvent::PlaybackFinished)
+        });
+        assert!(player.advance_after_finish().unwrap());
+        wait_for(
+            &player,
+            "second track",
+            |e| matches!(e, PlayerEvent::TrackLoaded { path, .. } if *path == second),
+        );
+
+        wait_for(&player, "second track end", |e| {
+            matches!(e, PlayerEvent::PlaybackFinished)
+        });
+        assert!(!player.advance_after_finish().unwrap());
+        assert_eq!(player.state().status, PlaybackStatus::Stopped);
+    }
+
+    #[test]
+    fn test_stored_loudness_normalizes_untagged_track() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("untagged.wav");
+        write_wav(&path, 2.0);
+        let mut player = Player::headless(1.0).unwrap();
+        player.set_normalization(normalization::NormalizationMode::Track, 0.0);
+        player.play_file(path.clone()).unwrap();
+        wait_for_status(&player, PlaybackStatus::Playing);
+        assert_eq!(player.state().normalization_gain, 1.0);
+
+        let wait_for_gain = |gain: f32| {
+            let start = Instant::now();
+            while (player.state().normalization_gain - gain).abs() > 0.01 {
+                assert!(start.elapsed() < TIMEOUT, "gain never reached {}", gain);
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        };
+        player.set_stored_loudness(
+            path,
+            crate::metadata::Loudness {
+                track_gain: Some(-6.0),
+                ..Default::default()
+            },
+        );
+        wait_for_gain(0.501);
+
+        // Turning it off takes effect on the track playing
+        player.set_normalization(normalization::NormalizationMode::Off, 0.0);
+        wait_for_gain(1.0);
+    }
+
+    #[test]
+    fn test_crossfade_into_next_track() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("01.wav");
+        let second = dir.path().join("02.wav");
+        write_wav(&first, 2.0);
+        write_wav(&second, 2.0);
+        let mut player = Player::headless(20.0).unwrap();
+        player.set_crossfade_secs(1);
+
+        player.queue_file(first.clone());
+        player.queue_file(second.clone());
+        player.queue_mut().jump_to(0);
+        player.load_and_play_current().unwrap();
+
+        // The second track starts before the first finishes
+        let start = Instant::now();
+        let fading = loop {
+            assert!(start.elapsed() < TIMEOUT, "never crossfaded");
+            let events = player.poll_events();
+            assert!(
+                !events
+                    .iter()
+                    .any(|e| matches!(e, PlayerEvent::PlaybackFinished))
+            );
+            if let Some(path) = events.iter().find_map(|e| match e {
+                PlayerEvent::CrossfadeStarted(path) => Some(path.clone()),
+                _ => None,
+            }) {
+                break path;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        };
+        assert_eq!(fading, second);
+        assert!(player.advance_after_crossfade(&fading));
+        assert_eq!(player.queue().current().map(|i| &i.path), Some(&second));
+
+        wait_for(&player, "second track end", |e| {
+            matches!(e, PlayerEvent::PlaybackFinished)
+        });
+        assert_eq!(
+            player.state().current_track.as_deref(),
+            Some(second.as_path())
+        );
+        assert!(!player.advance_after_finish().unwrap());
+    }
+
+    #[test]
+    fn test_stop_after_current_keeps_next_track_ready() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("01.wav");
+        let second = dir.path().join("02.wav");
+        write_wav(&first, 0.5);
+        write_wav(&second, 0.5);
+        let mut player = Player::headless(20.0).unwrap();
+
+        player.play_file(first).unwrap();
+        player.queue_file(second.clone());
+        player.queue_mut().set_stop_after(Some(0));
+        wait_for(&player, "track end", |e| {
+            matches!(e, PlayerEvent::PlaybackFinished)
+        });
+        assert!(!player.advance_after_finish().unwrap());
+        assert_eq!(player.state().status, PlaybackStatus::Stopped);
+        assert_eq!(player.queue().current().map(|i| &i.path), Some(&second));
+
+        player.play_current().unwrap();
+        wait_for(
+            &player,
+            "second track",
+            |e| matches!(e, PlayerEvent::TrackLoaded { path, .. } if *path == second),
+        );
+    }
+
+    #[test]
+    fn test_buffer_setting_limits_decode_ahead() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long.wav");
+        write_wav(&path, 4.0);
+        let mut player = Player::headless(1.0).unwrap();
+        player.set_buffer_ms(250);
+
+        player.play_file(path).unwrap();
+        wait_for_status(&player, PlaybackStatus::Playing);
+        std::thread::sleep(Duration::from_millis(500));
+
+        let stats = player.performance_stats().unwrap();
+        assert_eq!(stats.decode_ahead_ms, 250);
+        // 250 of the ring's 2000 ms, plus at most one decoded packet
+        let limit = 250 * 100 / buffering::MAX_AHEAD_MS + 2;
+        assert!(
+            stats.buffer_fill_percent <= limit,
+            "{}% buffered",
+            stats.buffer_fill_percent
+        );
+    }
+
+    #[test]
+    fn test_levels_are_metered_before_volume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sine.wav");
+        write_wav(&path, 2.0);
+        let mut player = Player::headless(1.0).unwrap();
+        player.set_volume(0.1);
+
+        player.play_file(path).unwrap();
+        wait_for_status(&player, PlaybackStatus::Playing);
+        std::thread::sleep(Duration::from_millis(300));
+
+        // The sine peaks at 8000 / 32768, whatever the volume
+        let levels = player.take_levels().unwrap();
+        for ch in 0..2 {
+            assert!((levels.peak[ch] - 0.244).abs() < 0.01, "{:?}", levels);
+            assert!(levels.rms[ch] > 0.1 && levels.rms[ch] < levels.peak[ch]);
+        }
+        assert_eq!(levels.clipped, 0);
+        assert_eq!(player.performance_stats().unwrap().clipped_samples, 0);
+    }
+
+    #[test]
+    fn test_decode_time_and_backlogs_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sine.wav");
+        write_wav(&path, 2.0);
+        let mut player = Player::headless(1.0).unwrap();
+
+        player.play_file(path).unwrap();
+        wait_for_status(&player, PlaybackStatus::Playing);
+        std::thread::sleep(Duration::from_millis(300));
+
+        let stats = player.performance_stats().unwrap();
+        assert!(stats.decode_nanos > 0);
+        assert_eq!(stats.events_dropped, 0);
+        let backlogs = player.channel_backlogs();
+        assert_eq!(backlogs.events.capacity, 64);
+        assert!(!backlogs.commands.is_full());
+    }
+
+    #[test]
+    fn test_missing_file_reports_error_and_stops() {
+        let mut player = Player::headless(1.0).unwrap();
+        player
+            .play_file(PathBuf::from("/nonexistent/track.wav"))
+            .unwrap();
+        wait_for(&player, "load error", |e| {
+            matches!(e, PlayerEvent::Error(_))
+        });
+        assert_eq!(player.state().status, PlaybackStatus::Stopped);
+    }
+
+    #[test]
+    fn test_shutdown_joins_audio_thread() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("one.wav");
+        write_wav(&path, 2.0);
+        let mut player = Player::headless(1.0).unwrap();
+
+        player.play_file(path).unwrap();
+        wait_for_status(&player, PlaybackStatus::Playing);
+        assert!(player.shutdown(TIMEOUT));
+        assert!(player.play().is_err());
+    }
+}
@@ -0,0 +1,205 @@
+//! Loudness normalization during playback.
+//!
+//! When a track loads, the decoder thread reads its ReplayGain tags (R128
+//! tags count too, see [`crate::metadata::loudness`]); tracks without any
+//! fall back to the values stored in the library, if the player was given
+//! them. The gain for the chosen mode, plus the preamp, becomes one
+//! amplitude the output callback multiplies in alongside the volume.
+//!
+//! Clipping protection: with the peak known, the gain never lifts it past
+//! full scale. Tracks without a peak can still be boosted, so the callback
+//! clamps samples whenever the gain is above unity.
+//!
+//! Tracks with no loudness values at all play as they are.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::metadata::Loudness;
+use crate::model::TrackSource;
+
+/// Lowest and highest preamp, in dB
+pub const PREAMP_RANGE_DB: (f32, f32) = (-12.0, 12.0);
+
+/// Preamps offered in settings, in dB
+pub const PREAMP_CHOICES_DB: [f32; 9] = [-12.0, -9.0, -6.0, -3.0, 0.0, 3.0, 6.0, 9.0, 12.0];
+
+/// Preamp unless the config sets one, in dB
+pub const DEFAULT_PREAMP_DB: f32 = 0.0;
+
+/// Which ReplayGain value playback applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NormalizationMode {
+    /// Tracks play as recorded
+    #[default]
+    Off,
+    /// Every track at the same loudness
+    Track,
+    /// Albums at the same loudness, keeping the levels between their
+    /// tracks; tracks without an album gain use their track gain
+    Album,
+}
+
+impl NormalizationMode {
+    pub const ALL: [NormalizationMode; 3] = [
+        NormalizationMode::Off,
+        NormalizationMode::Track,
+        NormalizationMode::Album,
+    ];
+
+    /// The mode's code, for the shared state
+    pub fn to_u8(self) -> u8 {
+        match self {
+            NormalizationMode::Off => 0,
+            NormalizationMode::Track => 1,
+            NormalizationMode::Album => 2,
+        }
+    }
+
+    /// The mode for a code from [`Self::to_u8`] (Off for anything else)
+    pub fn from_u8(code: u8) -> Self {
+        match code {
+            1 => NormalizationMode::Track,
+            2 => NormalizationMode::Album,
+            _ => NormalizationMode::Off,
+        }
+    }
+}
+
+impl std::fmt::Display for NormalizationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            NormalizationMode::Off => "Off",
+            NormalizationMode::Track => "Track gain",
+            NormalizationMode::Album => "Album gain",
+        })
+    }
+}
+
+/// The gain and peak `mode` uses, in dB and linear
+fn gain_and_peak(loudness: &Loudness, mode: NormalizationMode) -> Option<(f32, Option<f32>)> {
+    let track = loudness.track_gain.map(|gain| (gain, loudness.track_peak));
+    match mode {
+        NormalizationMode::Off => None,
+        NormalizationMode::Track => track,
+        NormalizationMode::Album => loudness
+            .album_gain
+            .map(|gain| (gain, loudness.album_peak.or(loudness.track_peak)))
+            .or(track),
+    }
+}
+
+/// Amplitude to play a track at: its gain for `mode` plus `preamp_db`,
+/// held down so its peak stays at or under full scale. 1.0 when off or
+/// when the track has no gain.
+pub fn amplitude(loudness: &Loudness, mode: NormalizationMode, preamp_db: f32) -> f32 {
+    let Some((gain, peak)) = gain_and_peak(loudness, mode) else {
+        return 1.0;
+    };
+    let preamp = preamp_db.clamp(PREAMP_RANGE_DB.0, PREAMP_RANGE_DB.1);
+    let amplitude = 10f32.powf((gain + preamp) / 20.0);
+    match peak {
+        Some(peak) if peak > 0.0 => amplitude.min(1.0 / peak),
+        _ => amplitude,
+    }
+}
+
+/// A track's loudness tags; none for streams and unreadable files
+pub fn read_tags(path: &Path) -> Loudness {
+    if !TrackSource::detect(&path.to_string_lossy()).is_local() {
+        return Loudness::default();
+    }
+    match crate::metadata::read_with_loudness(path) {
+        Ok((_, loudness)) => loudness,
+        Err(e) => {
+            tracing::debug!(
+                target: "player::normalization",
+                "No loudness tags for {:?}: {}",
+                path.file_name(),
+                e
+            );
+            Loudness::default()
+        }
+    }
+}
+
+/// The gain for display: "+2.5 dB", or "—" at unity
+pub fn label(amplitude: f32) -> String {
+    let db = 20.0 * amplitude.max(f32::MIN_POSITIVE).log10();
+    if db.abs() < 0.05 {
+        "—".to_string()
+    } else {
+        format!("{:+.1} dB", db)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loudness() -> Loudness {
+        Loudness {
+            track_gain: Some(-6.0),
+            track_peak: Some(0.5),
+            album_gain: Some(-4.0),
+            album_peak: Some(0.9),
+        }
+    }
+
+    fn db(amplitude: f32) -> f32 {
+        20.0 * amplitude.log10()
+    }
+
+    #[test]
+    fn test_modes_pick_their_gain() {
+        let loudness = loudness();
+        assert_eq!(amplitude(&loudness, NormalizationMode::Off, 6.0), 1.0);
+        assert!((db(amplitude(&loudness, NormalizationMode::Track, 0.0)) + 6.0).abs() < 0.01);
+        assert!((db(amplitude(&loudness, NormalizationMode::Album, 0.0)) + 4.0).abs() < 0.01);
+        assert!((db(amplitude(&loudness, NormalizationMode::Track, 3.0)) + 3.0).abs() < 0.01);
+
+        // No album gain: album mode uses the track's
+        let single = Loudness {
+            album_gain: None,
+            album_peak: None,
+            ..loudness
+        };
+        assert!((db(amplitude(&single, NormalizationMode::Album, 0.0)) + 6.0).abs() < 0.01);
+
+        // Untagged tracks play as they are, preamp or not
+        let untagged = Loudness::default();
+        assert_eq!(amplitude(&untagged, NormalizationMode::Track, 6.0), 1.0);
+    }
+
+    #[test]
+    fn test_peak_limits_gain() {
+        // +12 dB would lift a 0.5 peak past full scale; stops at 2x
+        let quiet = Loudness {
+            track_gain: Some(6.0),
+            track_peak: Some(0.5),
+            ..Default::default()
+        };
+        let gain = amplitude(&quiet, NormalizationMode::Track, 6.0);
+        assert!((gain - 2.0).abs() < 1e-4);
+        assert!(gain * 0.5 <= 1.0 + 1e-6);
+
+        // The preamp is held to its range
+        let no_peak = Loudness {
+            track_gain: Some(0.0),
+            ..Default::default()
+        };
+        assert!((db(amplitude(&no_peak, NormalizationMode::Track, 40.0)) - 12.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_mode_codes_round_trip() {
+        for mode in NormalizationMode::ALL {
+            assert_eq!(NormalizationMode::from_u8(mode.to_u8()), mode);
+        }
+        assert_eq!(NormalizationMode::from_u8(9), NormalizationMode::Off);
+        assert_eq!(label(1.0), "—");
+        assert_eq!(label(0.5), "-6.0 dB");
+    }
+}
@@ -1,8 +1,12 @@
 //! Play queue management.
 
 use super::state::TrackInfo;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use std::path::PathBuf;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// A single item in the play queue.
 #[derive(Debug, Clone)]
@@ -11,12 +15,18 @@ pub struct QueueItem {
     pub path: PathBuf,
     /// Cached metadata (populated after loading)
     pub info: Option<TrackInfo>,
+    /// Stop playback once this item finishes instead of moving on
+    pub stop_after: bool,
 }
 
 impl QueueItem {
     /// Create a queue item from a file path.
     pub fn from_path(path: PathBuf) -> Self {
-        Self { path, info: None }
+        Self {
+            path,
+            info: None,
+            stop_after: false,
+        }
     }
 
     /// Create a queue item with metadata.
@@ -24,6 +34,7 @@ impl QueueItem {
         Self {
             path,
             info: Some(info),
+            stop_after: false,
         }
     }
 
@@ -39,6 +50,45 @@ impl QueueItem {
                     .unwrap_or_else(|| "Unknown".to_string())
             })
     }
+
+    /// Key grouping items from the same album: the album tag with the
+    /// album artist, or with the containing folder when there's no album
+    /// artist (so a compilation's tracks stay together whoever performs
+    /// them); just the folder when there's no album tag.
+    fn album_key(&self) -> String {
+        let folder = || {
+            self.path
+                .parent()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default()
+        };
+        let Some(info) = self.info.as_ref() else {
+            return folder();
+        };
+        match (info.album_artist.as_deref(), info.album.as_deref()) {
+            (Some(album_artist), Some(album)) => format!(
+                "{}\u{0}{}",
+                album_artist.to_lowercase(),
+                album.to_lowercase()
+            ),
+            (None, Some(album)) => format!("{}\u{0}{}", folder(), album.to_lowercase()),
+            (_, None) => folder(),
+        }
+    }
+
+    /// Key grouping items by the same artist: the artist tag when known,
+    /// otherwise the folder above the album folder (`Artist/Album/track`).
+    fn artist_key(&self) -> String {
+        match self.info.as_ref().and_then(|i| i.artist.as_deref()) {
+            Some(artist) => artist.to_lowercase(),
+            None => self
+                .path
+                .parent()
+                .and_then(|p| p.parent())
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+        }
+    }
 }
 
 /// The play queue with current position tracking.
@@ -54,8 +104,14 @@ pub struct PlayQueue {
     shuffle_order: Vec<usize>,
     /// Current position in shuffle_order when shuffling
     shuffle_position: i32,
+    /// How the shuffle order is built
+    shuffle_mode: ShuffleMode,
+    /// Source of randomness for shuffling (seedable for reproducible orders)
+    rng: StdRng,
     /// Repeat mode
     repeat: RepeatMode,
+    /// What happens after the last item
+    end_of_queue: EndOfQueue,
 }
 
 impl Default for PlayQueue {
@@ -66,7 +122,10 @@ impl Default for PlayQueue {
             shuffle: false,
             shuffle_order: Vec::new(),
             shuffle_position: -1,
+            shuffle_mode: ShuffleMode::default(),
+            rng: StdRng::from_os_rng(),
             repeat: RepeatMode::Off,
+            end_of_queue: EndOfQueue::Stop,
         }
     }
 }
@@ -82,6 +141,77 @@ pub enum RepeatMode {
     One,
 }
 
+/// What happens once the last item of the queue has played (with repeat
+/// off; repeat one and all take precedence).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndOfQueue {
+    /// Playback stops
+    #[default]
+    Stop,
+    /// The queue starts over
+    Repeat,
+    /// Tracks like the last one are added (see [`crate::history::radio`])
+    Similar,
+    /// Random library tracks not played recently are added
+    Radio,
+}
+
+impl EndOfQueue {
+    pub const ALL: [EndOfQueue; 4] = [
+        EndOfQueue::Stop,
+        EndOfQueue::Repeat,
+        EndOfQueue::Similar,
+        EndOfQueue::Radio,
+    ];
+
+    /// Whether more tracks are added to the queue when it runs out
+    pub fn adds_tracks(self) -> bool {
+        matches!(self, EndOfQueue::Similar | EndOfQueue::Radio)
+    }
+}
+
+impl std::fmt::Display for EndOfQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EndOfQueue::Stop => "Then stop",
+            EndOfQueue::Repeat => "Then repeat",
+            EndOfQueue::Similar => "Then similar",
+            EndOfQueue::Radio => "Then radio",
+        })
+    }
+}
+
+/// How shuffle picks the play order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShuffleMode {
+    /// Every track in a uniformly random order
+    #[default]
+    Uniform,
+    /// Albums in random order, each album's tracks together in queue order
+    Album,
+    /// Random order, avoiding the same artist twice in a row where possible
+    ArtistSpread,
+}
+
+impl ShuffleMode {
+    pub const ALL: [ShuffleMode; 3] = [
+        ShuffleMode::Uniform,
+        ShuffleMode::Album,
+        ShuffleMode::ArtistSpread,
+    ];
+}
+
+impl std::fmt::Display for ShuffleMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ShuffleMode::Uniform => "Random",
+            ShuffleMode::Album => "By album",
+            ShuffleMode::ArtistSpread => "Spread artists",
+        })
+    }
+}
+
 impl PlayQueue {
     /// Create an empty queue.
     pub fn new() -> Self {
@@ -104,7 +234,8 @@ impl PlayQueue {
         self.items.push(item);
         // Add new item to shuffle order (at random position if shuffling)
         if self.shuffle {
-            if self.shuffle_order.is_empty() {
+            if self.shuffle_order.is_empty() || self.shuffle_mode == ShuffleMode::Album {
+                // Album shuffle keeps albums together, so new items play last
                 self.shuffle_order.push(new_index);
             } else {
                 // Insert at random position after current
@@ -116,22 +247,39 @@ impl PlayQueue {
                 let insert_pos = if insert_after >= self.shuffle_order.len() {
                     self.shuffle_order.len()
                 } else {
-                    let mut rng = rand::rng();
-                    rand::Rng::random_range(&mut rng, insert_after..=self.shuffle_order.len())
+                    self.rng
+                        .random_range(insert_after..=self.shuffle_order.len())
                 };
                 self.shuffle_order.insert(insert_pos, new_index);
             }
         }
     }
 
-    /// Add an item after the current position.
-    pub fn add_next(&mut self, item: QueueItem) {
+    /// Insert an item so it plays right after the current track ("Play next").
+    ///
+    /// The item goes directly below the current track in the queue and, when
+    /// shuffling, directly after it in the shuffle order, so it is always the
+    /// next track played. Inserting several in a row plays the most recent
+    /// first. Returns the item's index.
+    pub fn insert_after_current(&mut self, item: QueueItem) -> usize {
         let insert_pos = if self.position < 0 {
             0
         } else {
             (self.position as usize + 1).min(self.items.len())
         };
         self.items.insert(insert_pos, item);
+
+        if self.shuffle {
+            for idx in &mut self.shuffle_order {
+                if *idx >= insert_pos {
+                    *idx += 1;
+                }
+            }
+            let shuffle_pos = (self.shuffle_position + 1).max(0) as usize;
+            self.shuffle_order
+                .insert(shuffle_pos.min(self.shuffle_order.len()), insert_pos);
+        }
+        insert_pos
     }
 
     /// Clear the queue.
@@ -189,6 +337,51 @@ impl PlayQueue {
         }
     }
 
+    /// Move several items as one block to the insertion point `to`.
+    ///
+    /// `indices` are positions in the list as shown (any order, duplicates
+    /// ignored); `to` is the gap before item `to`, with `len()` meaning the
+    /// end. The moved items keep their relative order and end up adjacent.
+    /// The current track stays current, and when shuffling the upcoming
+    /// shuffle sequence is unchanged. Returns the items' new indices.
+    pub fn move_items(&mut self, indices: &[usize], to: usize) -> Vec<usize> {
+        let len = self.items.len();
+        let mut moving: Vec<usize> = indices.iter().copied().filter(|&i| i < len).collect();
+        moving.sort_unstable();
+        moving.dedup();
+        if moving.is_empty() {
+            return Vec::new();
+        }
+
+        // New order, expressed as old indices
+        let to = to.min(len);
+        let insert_at = to - moving.iter().filter(|&&i| i < to).count();
+        let mut order: Vec<usize> = (0..len)
+            .filter(|i| moving.binary_search(i).is_err())
+            .collect();
+        order.splice(insert_at..insert_at, moving.iter().copied());
+
+        let mut new_index = vec![0; len];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+
+        let mut slots: Vec<Option<QueueItem>> = std::mem::take(&mut self.items)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.items = order.iter().filter_map(|&old| slots[old].take()).collect();
+
+        if self.position >= 0 && (self.position as usize) < len {
+            self.position = new_index[self.position as usize] as i32;
+        }
+        for idx in &mut self.shuffle_order {
+            *idx = new_index[*idx];
+        }
+
+        (insert_at..insert_at + moving.len()).collect()
+    }
+
     /// Move an item up one position. Returns the new index if moved.
     ///
     /// If shuffle is enabled, this reorders the shuffle sequence instead.
@@ -276,6 +469,75 @@ impl PlayQueue {
         }
     }
 
+    /// Item indices that will play after the current one, in play order
+    /// (shuffle order when shuffling), without wrapping around for repeat.
+    pub fn upcoming(&self) -> Vec<usize> {
+        if self.shuffle && !self.shuffle_order.is_empty() {
+            let start = (self.shuffle_position + 1).max(0) as usize;
+            self.shuffle_order.get(start..).unwrap_or_default().to_vec()
+        } else {
+            ((self.position + 1).max(0) as usize..self.items.len()).collect()
+        }
+    }
+
+    /// The item playing on past the current one moves to, when that's known
+    /// ahead: not after a stop mark, on repeat one, or when a shuffled
+    /// queue starts over (it's reshuffled first).
+    pub fn next_up(&self) -> Option<&QueueItem> {
+        let current = self.current()?;
+        if current.stop_after || self.forward_repeat() == RepeatMode::One {
+            return None;
+        }
+        if self.shuffle && !self.shuffle_order.is_empty() {
+            let next = self.shuffle_order.get(self.shuffle_position as usize + 1)?;
+            return self.items.get(*next);
+        }
+        match self.items.get(self.position as usize + 1) {
+            Some(item) => Some(item),
+            None if self.wraps() => self.items.first(),
+            None => None,
+        }
+    }
+
+    /// Index of the item marked to stop playback after it, if any.
+    pub fn stop_after_index(&self) -> Option<usize> {
+        self.items.iter().position(|i| i.stop_after)
+    }
+
+    /// Mark the item to stop after (replacing any previous mark), or clear
+    /// the mark with `None`.
+    pub fn set_stop_after(&mut self, index: Option<usize>) {
+        for (i, item) in self.items.iter_mut().enumerate() {
+            item.stop_after = Some(i) == index;
+        }
+    }
+
+    /// Index of the last item of the current track's album, following play
+    /// order from the current track.
+    pub fn album_end_index(&self) -> Option<usize> {
+        let current = self.current_index()?;
+        let album = self.items[current].album_key();
+        Some(
+            self.upcoming()
+                .into_iter()
+                .take_while(|&i| self.items[i].album_key() == album)
+                .last()
+                .unwrap_or(current),
+        )
+    }
+
+    /// Clear and report the stop mark on the current item, called when it
+    /// finishes playing.
+    pub fn take_stop_after_current(&mut self) -> bool {
+        match self.current_index() {
+            Some(i) if self.items[i].stop_after => {
+                self.items[i].stop_after = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Get count of remaining tracks after current position.
     pub fn remaining_count(&self) -> usize {
         if self.position < 0 {
@@ -300,7 +562,7 @@ impl PlayQueue {
             return self.skip_forward_shuffle();
         }
 
-        match self.repeat {
+        match self.forward_repeat() {
             RepeatMode::One => {
                 // Stay on current track
                 if self.position < 0 {
@@ -327,7 +589,7 @@ impl PlayQueue {
 
     /// Skip forward in shuffle mode.
     fn skip_forward_shuffle(&mut self) -> Option<&QueueItem> {
-        match self.repeat {
+        match self.forward_repeat() {
             RepeatMode::One => {
                 // Stay on current track
                 if self.shuffle_position < 0 {
@@ -450,6 +712,24 @@ impl PlayQueue {
         }
     }
 
+    /// Set how the shuffle order is built, reshuffling if shuffle is on.
+    pub fn set_shuffle_mode(&mut self, mode: ShuffleMode) {
+        self.shuffle_mode = mode;
+        if self.shuffle {
+            self.generate_shuffle_order();
+        }
+    }
+
+    /// Get the shuffle mode.
+    pub fn shuffle_mode(&self) -> ShuffleMode {
+        self.shuffle_mode
+    }
+
+    /// Reseed the shuffle so the orders it produces are reproducible.
+    pub fn set_shuffle_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
     /// Generate a new shuffle order, keeping current track first if playing.
     fn generate_shuffle_order(&mut self) {
         let len = self.items.len();
@@ -459,26 +739,128 @@ impl PlayQueue {
             return;
         }
 
-        // Create indices
-        let mut indices: Vec<usize> = (0..len).collect();
+        let current = (self.position >= 0).then_some(self.position as usize);
+        self.shuffle_order = match self.shuffle_mode {
+            ShuffleMode::Uniform => self.uniform_order(current),
+            ShuffleMode::Album => self.album_order(current),
+            ShuffleMode::ArtistSpread => self.artist_spread_order(current),
+        };
+        self.shuffle_position = if current.is_some() { 0 } else { -1 };
+    }
 
+    /// All indices in random order, with `current` moved to the front.
+    fn uniform_order(&mut self, current: Option<usize>) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.items.len()).collect();
         // Shuffle using Fisher-Yates
-        let mut rng = rand::rng();
-        indices.shuffle(&mut rng);
-
-        // If we're currently playing, move that track to front of shuffle
-        if self.position >= 0 {
-            let current_idx = self.position as usize;
-            if let Some(pos) = indices.iter().position(|&i| i == current_idx) {
-                indices.remove(pos);
-                indices.insert(0, current_idx);
+        indices.shuffle(&mut self.rng);
+
+        if let Some(current) = current
+            && let Some(pos) = indices.iter().position(|&i| i == current)
+        {
+            indices.remove(pos);
+            indices.insert(0, current);
+        }
+        indices
+    }
+
+    /// Albums in random order, tracks within each in queue order.
+    ///
+    /// The current track's album goes first, starting from the current track;
+    /// any of its tracks queued before the current one play at the very end.
+    fn album_order(&mut self, current: Option<usize>) -> Vec<usize> {
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut group_of: HashMap<String, usize> = HashMap::new();
+        for (i, item) in self.items.iter().enumerate() {
+            let group = *group_of.entry(item.album_key()).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[group].push(i);
+        }
+        groups.shuffle(&mut self.rng);
+
+        let mut earlier = Vec::new();
+        if let Some(current) = current
+            && let Some(g) = groups.iter().position(|g| g.contains(&current))
+        {
+            let mut group = groups.remove(g);
+            let split = group.iter().position(|&i| i == current).unwrap_or(0);
+            earlier = group.drain(..split).collect();
+            groups.insert(0, group);
+        }
+
+        let mut order: Vec<usize> = groups.into_iter().flatten().collect();
+        order.extend(earlier);
+        order
+    }
+
+    /// Random order that avoids the same artist twice in a row.
+    ///
+    /// Each pick is a random artist weighted by how many of their tracks are
+    /// left, except when one artist has so many left that they must be taken
+    /// now to stay spread out. Back-to-back tracks only happen when the queue
+    /// is dominated by one artist and there's no other way to order it.
+    fn artist_spread_order(&mut self, current: Option<usize>) -> Vec<usize> {
+        // Remaining tracks per artist, each list already shuffled
+        let mut artists: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut artist_of: HashMap<String, usize> = HashMap::new();
+        for (i, item) in self.items.iter().enumerate() {
+            if Some(i) == current {
+                continue;
             }
-            self.shuffle_position = 0;
-        } else {
-            self.shuffle_position = -1;
+            let key = item.artist_key();
+            let a = *artist_of.entry(key.clone()).or_insert_with(|| {
+                artists.push((key, Vec::new()));
+                artists.len() - 1
+            });
+            artists[a].1.push(i);
+        }
+        for (_, tracks) in &mut artists {
+            tracks.shuffle(&mut self.rng);
         }
 
-        self.shuffle_order = indices;
+        let mut order = Vec::with_capacity(self.items.len());
+        let mut last = current.map(|c| self.items[c].artist_key());
+        order.extend(current);
+
+        let mut remaining = self.items.len() - order.len();
+        while remaining > 0 {
+            let candidates: Vec<usize> = (0..artists.len())
+                .filter(|&a| !artists[a].1.is_empty() && last.as_ref() != Some(&artists[a].0))
+                .collect();
+
+            let pick = if candidates.is_empty() {
+                // Only the previous artist is left
+                artists.iter().position(|(_, t)| !t.is_empty()).unwrap_or(0)
+            } else if let Some(&critical) = candidates
+                .iter()
+                .find(|&&a| artists[a].1.len() * 2 > remaining)
+            {
+                critical
+            } else {
+                let total: usize = candidates.iter().map(|&a| artists[a].1.len()).sum();
+                let mut n = self.rng.random_range(0..total);
+                *candidates
+                    .iter()
+                    .find(|&&a| {
+                        let count = artists[a].1.len();
+                        if n < count {
+                            true
+                        } else {
+                            n -= count;
+                            false
+                        }
+                    })
+                    .unwrap_or(&candidates[0])
+            };
+
+            if let Some(track) = artists[pick].1.pop() {
+                order.push(track);
+            }
+            last = Some(artists[pick].0.clone());
+            remaining -= 1;
+        }
+        order
     }
 
     /// Get shuffle mode.
@@ -505,12 +887,57 @@ impl PlayQueue {
         self.repeat
     }
 
+    /// Set what happens after the last item.
+    pub fn set_end_of_queue(&mut self, end: EndOfQueue) {
+        self.end_of_queue = end;
+    }
+
+    /// What happens after the last item.
+    pub fn end_of_queue(&self) -> EndOfQueue {
+        self.end_of_queue
+    }
+
+    /// Repeat mode going forward: with repeat off, the end-of-queue setting
+    /// can still start the queue over
+    fn forward_repeat(&self) -> RepeatMode {
+        match (self.repeat, self.end_of_queue) {
+            (RepeatMode::Off, EndOfQueue::Repeat) => RepeatMode::All,
+            (repeat, _) => repeat,
+        }
+    }
+
+    /// Whether moving on from the last item starts the queue over
+    pub fn wraps(&self) -> bool {
+        self.forward_repeat() == RepeatMode::All
+    }
+
+    /// Whether the queue has run out and wants more tracks added: nothing
+    /// follows the current item, and it's set to add some at the end
+    pub fn wants_more(&self) -> bool {
+        self.repeat == RepeatMode::Off
+            && self.end_of_queue.adds_tracks()
+            && self.upcoming().is_empty()
+    }
+
     /// Update metadata for an item.
     pub fn update_info(&mut self, index: usize, info: TrackInfo) {
         if let Some(item) = self.items.get_mut(index) {
             item.info = Some(info);
         }
     }
+
+    /// Point items at the new locations of files that moved, as given by
+    /// `moved`. Returns how many items changed.
+    pub fn relocate(&mut self, moved: impl Fn(&Path) -> Option<PathBuf>) -> usize {
+        let mut n = 0;
+        for item in &mut self.items {
+            if let Some(to) = moved(&item.path) {
+                item.path = to;
+                n += 1;
+            }
+        }
+        n
+    }
 }
 
 #[cfg(test)]
@@ -541,6 +968,21 @@ mod tests {
         assert_eq!(queue.current_index(), Some(1));
     }
 
+    #[test]
+    fn test_relocate_keeps_position() {
+        let mut queue = PlayQueue::new();
+        queue.add(make_item("a.mp3"));
+        queue.add(make_item("b.mp3"));
+        queue.skip_forward();
+        queue.skip_forward();
+
+        let moved = queue.relocate(|p| (p == Path::new("b.mp3")).then(|| PathBuf::from("x/b.mp3")));
+        assert_eq!(moved, 1);
+        assert_eq!(queue.current_index(), Some(1));
+        assert_eq!(queue.current().unwrap().path, PathBuf::from("x/b.mp3"));
+        assert_eq!(queue.items()[0].path, PathBuf::from("a.mp3"));
+    }
+
     #[test]
     fn test_queue_repeat_all() {
         let mut queue = PlayQueue::new();
@@ -553,6 +995,37 @@ mod tests {
         assert_eq!(queue.skip_forward().unwrap().path, PathBuf::from("a.mp3")); // wraps
     }
 
+    #[test]
+    fn test_queue_end_of_queue() {
+        let mut queue = PlayQueue::new();
+        queue.add(make_item("a.mp3"));
+        queue.add(make_item("b.mp3"));
+        queue.skip_forward(); // a
+        queue.skip_forward(); // b
+
+        // Stopping at the end is the default
+        assert!(!queue.wraps());
+        assert!(!queue.wants_more());
+        assert!(queue.skip_forward().is_none());
+
+        queue.set_end_of_queue(EndOfQueue::Repeat);
+        assert!(queue.wraps());
+        assert_eq!(queue.skip_forward().unwrap().path, PathBuf::from("a.mp3"));
+
+        // Similar and radio ask for more once nothing follows
+        queue.set_end_of_queue(EndOfQueue::Radio);
+        assert!(!queue.wants_more());
+        queue.skip_forward(); // b
+        assert!(queue.wants_more());
+        queue.add(make_item("c.mp3"));
+        assert!(!queue.wants_more());
+        assert_eq!(queue.skip_forward().unwrap().path, PathBuf::from("c.mp3"));
+
+        // An explicit repeat mode wins
+        queue.set_repeat(RepeatMode::One);
+        assert!(!queue.wants_more());
+    }
+
     #[test]
     fn test_queue_repeat_one() {
         let mut queue = PlayQueue::new();
@@ -565,19 +1038,81 @@ mod tests {
     }
 
     #[test]
-    fn test_queue_add_next() {
+    fn test_queue_insert_after_current() {
         let mut queue = PlayQueue::new();
         queue.add(make_item("a.mp3"));
         queue.add(make_item("c.mp3"));
         queue.skip_forward(); // Start playing a
 
-        queue.add_next(make_item("b.mp3"));
+        assert_eq!(queue.insert_after_current(make_item("b.mp3")), 1);
 
         assert_eq!(queue.items()[0].path, PathBuf::from("a.mp3"));
         assert_eq!(queue.items()[1].path, PathBuf::from("b.mp3"));
         assert_eq!(queue.items()[2].path, PathBuf::from("c.mp3"));
     }
 
+    #[test]
+    fn test_insert_after_current_before_playback() {
+        let mut queue = PlayQueue::new();
+        queue.add(make_item("b.mp3"));
+        queue.insert_after_current(make_item("a.mp3"));
+
+        assert_eq!(queue.items()[0].path, PathBuf::from("a.mp3"));
+        assert_eq!(
+            queue.skip_forward().map(|i| i.path.clone()),
+            Some(PathBuf::from("a.mp3"))
+        );
+    }
+
+    #[test]
+    fn test_insert_after_current_shuffle_plays_next() {
+        let mut queue = PlayQueue::new();
+        for i in 0..8 {
+            queue.add(make_item(&format!("{}.mp3", i)));
+        }
+        queue.set_shuffle(true);
+        queue.skip_forward();
+        queue.skip_forward();
+        let current = queue.current().unwrap().path.clone();
+
+        let index = queue.insert_after_current(make_item("next.mp3"));
+
+        // Current track unchanged, new item sits just below it
+        assert_eq!(queue.current().unwrap().path, current);
+        assert_eq!(queue.items()[index].path, PathBuf::from("next.mp3"));
+        assert_eq!(queue.items()[index - 1].path, current);
+        assert_eq!(
+            queue.skip_forward().map(|i| i.path.clone()),
+            Some(PathBuf::from("next.mp3"))
+        );
+
+        // Every track is still visited exactly once
+        let mut visited = std::collections::HashSet::new();
+        while let Some(item) = queue.skip_forward() {
+            assert!(visited.insert(item.path.clone()));
+        }
+        assert_eq!(visited.len(), 6);
+        assert!(!visited.contains(&current));
+    }
+
+    #[test]
+    fn test_insert_after_current_shuffle_repeated() {
+        let mut queue = PlayQueue::new();
+        queue.add(make_item("a.mp3"));
+        queue.add(make_item("b.mp3"));
+        queue.set_shuffle(true);
+        queue.skip_forward();
+
+        queue.insert_after_current(make_item("x.mp3"));
+        queue.insert_after_current(make_item("y.mp3"));
+
+        let played: Vec<PathBuf> =
+            std::iter::from_fn(|| queue.skip_forward().map(|i| i.path.clone())).collect();
+        assert_eq!(played[0], PathBuf::from("y.mp3"));
+        assert_eq!(played[1], PathBuf::from("x.mp3"));
+        assert_eq!(played.len(), 3);
+    }
+
     #[test]
     fn test_shuffle_visits_all_tracks() {
         let mut queue = PlayQueue::new();
@@ -838,4 +1373,337 @@ mod tests {
         // shuffle_position should follow the item
         assert_eq!(queue.shuffle_position, 2);
     }
+
+    fn paths(queue: &PlayQueue) -> Vec<String> {
+        queue
+            .items()
+            .iter()
+            .map(|i| i.path.display().to_string())
+            .collect()
+    }
+
+    fn queue_of(names: &[&str]) -> PlayQueue {
+        let mut queue = PlayQueue::new();
+        for name in names {
+            queue.add(make_item(name));
+        }
+        queue
+    }
+
+    #[test]
+    fn test_move_items_block_down() {
+        let mut queue = queue_of(&["a", "b", "c", "d", "e"]);
+        // Drop a, c before e
+        let moved = queue.move_items(&[2, 0], 4);
+        assert_eq!(paths(&queue), ["b", "d", "a", "c", "e"]);
+        assert_eq!(moved, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_move_items_block_up_and_to_end() {
+        let mut queue = queue_of(&["a", "b", "c", "d", "e"]);
+        let moved = queue.move_items(&[3, 4], 1);
+        assert_eq!(paths(&queue), ["a", "d", "e", "b", "c"]);
+        assert_eq!(moved, vec![1, 2]);
+
+        let moved = queue.move_items(&[0], queue.len());
+        assert_eq!(paths(&queue), ["d", "e", "b", "c", "a"]);
+        assert_eq!(moved, vec![4]);
+    }
+
+    #[test]
+    fn test_move_items_inside_own_block_is_noop() {
+        let mut queue = queue_of(&["a", "b", "c", "d"]);
+        for gap in 1..=3 {
+            let moved = queue.move_items(&[1, 2], gap);
+            assert_eq!(paths(&queue), ["a", "b", "c", "d"]);
+            assert_eq!(moved, vec![1, 2]);
+        }
+        assert!(queue.move_items(&[9], 0).is_empty());
+    }
+
+    #[test]
+    fn test_move_items_keeps_current_and_shuffle_sequence() {
+        let mut queue = queue_of(&["a", "b", "c", "d", "e"]);
+        queue.jump_to(1); // b playing
+        queue.set_shuffle(true);
+        let upcoming: Vec<PathBuf> = queue
+            .shuffle_order
+            .iter()
+            .map(|&i| queue.items()[i].path.clone())
+            .collect();
+
+        queue.move_items(&[1, 3], 5);
+
+        assert_eq!(paths(&queue), ["a", "c", "e", "b", "d"]);
+        assert_eq!(queue.current().unwrap().path, PathBuf::from("b"));
+        let after: Vec<PathBuf> = queue
+            .shuffle_order
+            .iter()
+            .map(|&i| queue.items()[i].path.clone())
+            .collect();
+        assert_eq!(after, upcoming);
+    }
+
+    /// Play the whole queue through once and return the paths in play order.
+    fn play_order(queue: &mut PlayQueue) -> Vec<String> {
+        let mut played: Vec<String> = queue
+            .current()
+            .map(|i| i.path.display().to_string())
+            .into_iter()
+            .collect();
+        while let Some(item) = queue.skip_forward() {
+            played.push(item.path.display().to_string());
+        }
+        played
+    }
+
+    fn artist_of(path: &str) -> &str {
+        path.split('/').next().unwrap()
+    }
+
+    fn shuffled(names: &[&str], mode: ShuffleMode, seed: u64) -> Vec<String> {
+        let mut queue = queue_of(names);
+        queue.set_shuffle_seed(seed);
+        queue.set_shuffle_mode(mode);
+        queue.set_shuffle(true);
+        play_order(&mut queue)
+    }
+
+    #[test]
+    fn test_shuffle_seed_reproducible() {
+        let names: Vec<String> = (0..20).map(|i| format!("{}.mp3", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        for mode in ShuffleMode::ALL {
+            let first = shuffled(&names, mode, 7);
+            assert_eq!(first, shuffled(&names, mode, 7));
+            assert_eq!(first.len(), 20);
+        }
+        assert_ne!(
+            shuffled(&names, ShuffleMode::Uniform, 7),
+            shuffled(&names, ShuffleMode::Uniform, 8)
+        );
+    }
+
+    #[test]
+    fn test_album_shuffle_keeps_albums_together() {
+        let names = [
+            "A/one/1.mp3",
+            "A/one/2.mp3",
+            "A/one/3.mp3",
+            "B/two/1.mp3",
+            "B/two/2.mp3",
+            "C/three/1.mp3",
+            "C/three/2.mp3",
+            "C/three/3.mp3",
+        ];
+        for seed in 0..10 {
+            let order = shuffled(&names, ShuffleMode::Album, seed);
+            assert_eq!(order.len(), names.len());
+            for album in ["A/one/", "B/two/", "C/three/"] {
+                let positions: Vec<usize> = order
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| p.starts_with(album))
+                    .map(|(i, _)| i)
+                    .collect();
+                // Contiguous and in track order
+                assert!(positions.windows(2).all(|w| w[1] == w[0] + 1));
+                let tracks: Vec<&String> = positions.iter().map(|&i| &order[i]).collect();
+                assert!(tracks.windows(2).all(|w| w[0] < w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_album_shuffle_starts_from_current_track() {
+        let mut queue = queue_of(&["A/one/1", "A/one/2", "A/one/3", "B/two/1", "B/two/2"]);
+        queue.set_shuffle_seed(3);
+        queue.set_shuffle_mode(ShuffleMode::Album);
+        queue.jump_to(1);
+        queue.set_shuffle(true);
+
+        let order = play_order(&mut queue);
+        assert_eq!(
+            order,
+            ["A/one/2", "A/one/3", "B/two/1", "B/two/2", "A/one/1"]
+        );
+    }
+
+    #[test]
+    fn test_artist_spread_avoids_back_to_back() {
+        let names = [
+            "A/x/1", "A/x/2", "A/x/3", "A/x/4", "B/y/1", "B/y/2", "B/y/3", "C/z/1", "C/z/2",
+        ];
+        for seed in 0..20 {
+            let order = shuffled(&names, ShuffleMode::ArtistSpread, seed);
+            assert_eq!(order.len(), names.len());
+            for pair in order.windows(2) {
+                assert_ne!(artist_of(&pair[0]), artist_of(&pair[1]), "{:?}", order);
+            }
+        }
+    }
+
+    #[test]
+    fn test_artist_spread_tight_and_impossible() {
+        // Exactly enough other tracks to separate A's
+        let order = shuffled(
+            &["A/x/1", "A/x/2", "A/x/3", "B/y/1", "C/z/1"],
+            ShuffleMode::ArtistSpread,
+            1,
+        );
+        let artists: Vec<&str> = order.iter().map(|p| artist_of(p)).collect();
+        assert_eq!(artists[0], "A");
+        assert_eq!(artists[2], "A");
+        assert_eq!(artists[4], "A");
+
+        // Can't be avoided; still plays everything once
+        let mut order = shuffled(
+            &["A/x/1", "A/x/2", "A/x/3", "A/x/4", "B/y/1"],
+            ShuffleMode::ArtistSpread,
+            1,
+        );
+        order.sort();
+        assert_eq!(order, ["A/x/1", "A/x/2", "A/x/3", "A/x/4", "B/y/1"]);
+    }
+
+    #[test]
+    fn test_artist_spread_respects_current_artist() {
+        for seed in 0..10 {
+            let mut queue = queue_of(&["A/x/1", "A/x/2", "B/y/1"]);
+            queue.set_shuffle_seed(seed);
+            queue.set_shuffle_mode(ShuffleMode::ArtistSpread);
+            queue.jump_to(0);
+            queue.set_shuffle(true);
+            assert_eq!(play_order(&mut queue), ["A/x/1", "B/y/1", "A/x/2"]);
+        }
+    }
+
+    #[test]
+    fn test_set_shuffle_mode_keeps_current() {
+        let mut queue = queue_of(&["A/x/1", "A/x/2", "B/y/1", "B/y/2"]);
+        queue.set_shuffle(true);
+        queue.skip_forward();
+        let current = queue.current().unwrap().path.clone();
+
+        queue.set_shuffle_mode(ShuffleMode::Album);
+
+        assert_eq!(queue.shuffle_mode(), ShuffleMode::Album);
+        assert_eq!(queue.current().unwrap().path, current);
+        assert_eq!(play_order(&mut queue).len(), 4);
+    }
+
+    #[test]
+    fn test_stop_after_mark_follows_item() {
+        let mut queue = queue_of(&["a", "b", "c", "d"]);
+        queue.jump_to(0);
+        queue.set_stop_after(Some(1));
+        assert_eq!(queue.stop_after_index(), Some(1));
+
+        queue.insert_after_current(make_item("x"));
+        assert_eq!(queue.stop_after_index(), Some(2));
+        queue.move_items(&[2], 5);
+        assert_eq!(paths(&queue), ["a", "x", "c", "d", "b"]);
+        assert_eq!(queue.stop_after_index(), Some(4));
+
+        // Only one mark at a time
+        queue.set_stop_after(Some(0));
+        assert_eq!(queue.stop_after_index(), Some(0));
+        queue.set_stop_after(None);
+        assert_eq!(queue.stop_after_index(), None);
+    }
+
+    #[test]
+    fn test_take_stop_after_current() {
+        let mut queue = queue_of(&["a", "b"]);
+        queue.jump_to(0);
+        queue.set_stop_after(Some(1));
+        assert!(!queue.take_stop_after_current());
+
+        queue.skip_forward();
+        assert!(queue.take_stop_after_current());
+        assert!(!queue.take_stop_after_current());
+        assert_eq!(queue.stop_after_index(), None);
+    }
+
+    #[test]
+    fn test_next_up() {
+        let mut queue = queue_of(&["a", "b", "c"]);
+        assert!(queue.next_up().is_none());
+
+        queue.jump_to(1);
+        assert_eq!(queue.next_up().map(|i| i.path.clone()), Some("c".into()));
+        queue.set_stop_after(Some(1));
+        assert!(queue.next_up().is_none());
+        queue.set_stop_after(None);
+
+        queue.jump_to(2);
+        assert!(queue.next_up().is_none());
+        queue.set_repeat(RepeatMode::All);
+        assert_eq!(queue.next_up().map(|i| i.path.clone()), Some("a".into()));
+        queue.set_repeat(RepeatMode::One);
+        assert!(queue.next_up().is_none());
+
+        // Shuffled: the next in shuffle order
+        queue.set_repeat(RepeatMode::Off);
+        queue.jump_to(0);
+        queue.set_shuffle(true);
+        let next = queue.upcoming()[0];
+        assert_eq!(
+            queue.next_up().map(|i| &i.path),
+            Some(&queue.items()[next].path)
+        );
+    }
+
+    #[test]
+    fn test_album_end_index() {
+        let mut queue = queue_of(&["A/one/1", "A/one/2", "A/one/3", "B/two/1", "A/one/4"]);
+        assert_eq!(queue.album_end_index(), None);
+
+        queue.jump_to(1);
+        assert_eq!(queue.album_end_index(), Some(2));
+        queue.jump_to(3);
+        assert_eq!(queue.album_end_index(), Some(3));
+    }
+
+    #[test]
+    fn test_album_key_keeps_compilations_together() {
+        let tagged = |path: &str, artist: &str, album_artist: Option<&str>, album: &str| {
+            QueueItem::with_info(
+                PathBuf::from(path),
+                TrackInfo {
+                    artist: Some(artist.to_string()),
+                    album_artist: album_artist.map(str::to_string),
+                    album: Some(album.to_string()),
+                    ..Default::default()
+                },
+            )
+        };
+        // Same album artist, different track artists
+        let a = tagged("Hits/1.mp3", "A", Some("Various Artists"), "Hits");
+        let b = tagged("Hits/2.mp3", "B", Some("Various Artists"), "Hits");
+        assert_eq!(a.album_key(), b.album_key());
+
+        // No album artist: the folder decides
+        let a = tagged("Hits/1.mp3", "A", None, "Hits");
+        let b = tagged("Hits/2.mp3", "B", None, "Hits");
+        let elsewhere = tagged("Other/Hits/1.mp3", "A", None, "Hits");
+        assert_eq!(a.album_key(), b.album_key());
+        assert_ne!(a.album_key(), elsewhere.album_key());
+
+        // Two albums sharing a title
+        let a = tagged("x/1.mp3", "A", Some("A"), "Greatest Hits");
+        let b = tagged("x/2.mp3", "B", Some("B"), "Greatest Hits");
+        assert_ne!(a.album_key(), b.album_key());
+    }
+
+    #[test]
+    fn test_album_end_index_follows_shuffle_order() {
+        let mut queue = queue_of(&["A/one/1", "B/two/1", "A/one/2", "B/two/2"]);
+        queue.set_shuffle_mode(ShuffleMode::Album);
+        queue.jump_to(0);
+        queue.set_shuffle(true);
+
+        assert_eq!(queue.album_end_index(), Some(2));
+    }
 }
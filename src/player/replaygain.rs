@@ -0,0 +1,300 @@
+//! ReplayGain 2.0 loudness measurement (EBU R128).
+//!
+//! A track is decoded once and measured the way ITU-R BS.1770 and EBU R128
+//! say: each channel goes through the K-weighting filter (a high shelf,
+//! then a high pass), energy is averaged over 400 ms blocks overlapping by
+//! 75%, and blocks quieter than -70 LUFS, then those more than 10 LU below
+//! the rest, are left out. The gain brings what's left to the ReplayGain
+//! 2.0 reference of -18 LUFS. An album is measured by gating the blocks of
+//! all its tracks together, so a quiet interlude doesn't pull it down.
+//!
+//! Peaks are sample peaks, linear, where 1.0 is full scale.
+
+use std::path::Path;
+
+use super::PlayerError;
+use super::decoder::AudioDecoder;
+
+/// Loudness ReplayGain 2.0 brings tracks to (LUFS)
+pub const REFERENCE_LUFS: f64 = -18.0;
+
+/// Blocks quieter than this are silence (LUFS)
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Blocks this far below the ungated loudness are left out (LU)
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// 100 ms steps per 400 ms block
+const STEPS_PER_BLOCK: usize = 4;
+
+/// Loudness of a block of mean (weighted) energy `energy`, in LUFS
+fn lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+/// Energy of a block as loud as `lufs`
+fn energy(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+/// One second-order filter section, transposed direct form II
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// The K-weighting filter at `sample_rate`, as BS.1770 specifies it at
+/// 48 kHz, recomputed for other rates
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let rate = f64::from(sample_rate);
+
+    // Head: high shelf, +4 dB above ~1.7 kHz
+    let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    // Low frequencies: high pass at ~38 Hz
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (std::f64::consts::PI * f0 / rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+    [shelf, high_pass]
+}
+
+/// How much a channel counts: 5.1 (L R C LFE Ls Rs) leaves out the LFE
+/// and weights the surrounds +1.5 dB
+fn channel_weight(channel: usize, channels: usize) -> f64 {
+    match (channels, channel) {
+        (6.., 3) => 0.0,
+        (6.., 4 | 5) => 1.41,
+        _ => 1.0,
+    }
+}
+
+/// Measures interleaved samples fed in chunks
+#[derive(Debug, Clone)]
+pub struct LoudnessMeter {
+    /// K-weighting filters of each channel
+    filters: Vec<[Biquad; 2]>,
+    weights: Vec<f64>,
+    /// Frames in a 100 ms step
+    step_frames: usize,
+    /// Frames and weighted energy of the step being filled
+    frames: usize,
+    sum: f64,
+    /// Mean energy of each finished step
+    steps: Vec<f64>,
+    peak: f32,
+}
+
+impl LoudnessMeter {
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        let channels = usize::from(channels.max(1));
+        let sample_rate = sample_rate.max(1);
+        Self {
+            filters: vec![k_weighting(sample_rate); channels],
+            weights: (0..channels).map(|c| channel_weight(c, channels)).collect(),
+            step_frames: (sample_rate as usize / 10).max(1),
+            frames: 0,
+            sum: 0.0,
+            steps: Vec::new(),
+            peak: 0.0,
+        }
+    }
+
+    /// Feed the next interleaved samples (whole frames)
+    pub fn feed(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.filters.len()) {
+            for ((&sample, [shelf, high_pass]), weight) in
+                frame.iter().zip(&mut self.filters).zip(&self.weights)
+            {
+                self.peak = self.peak.max(sample.abs());
+                let weighted = high_pass.process(shelf.process(f64::from(sample)));
+                self.sum += weight * weighted * weighted;
+            }
+            self.frames += 1;
+            if self.frames == self.step_frames {
+                self.steps.push(self.sum / self.frames as f64);
+                self.frames = 0;
+                self.sum = 0.0;
+            }
+        }
+    }
+
+    /// The measurement; a last partial block is left out
+    pub fn finish(self) -> TrackLoudness {
+        TrackLoudness {
+            blocks: self
+                .steps
+                .windows(STEPS_PER_BLOCK)
+                .map(|steps| steps.iter().sum::<f64>() / STEPS_PER_BLOCK as f64)
+                .collect(),
+            peak: self.peak,
+        }
+    }
+}
+
+/// A measured track
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackLoudness {
+    /// Mean energy of each 400 ms block
+    blocks: Vec<f64>,
+    /// Sample peak, linear
+    pub peak: f32,
+}
+
+impl TrackLoudness {
+    /// Integrated loudness in LUFS; `None` for silence or under 400 ms
+    pub fn integrated(&self) -> Option<f64> {
+        integrated(&[self])
+    }
+
+    /// ReplayGain in dB
+    pub fn gain(&self) -> Option<f32> {
+        self.integrated().map(replay_gain)
+    }
+}
+
+/// ReplayGain and peak of an album of `tracks`
+pub fn album(tracks: &[TrackLoudness]) -> (Option<f32>, f32) {
+    let tracks: Vec<&TrackLoudness> = tracks.iter().collect();
+    let peak = tracks.iter().map(|t| t.peak).fold(0.0, f32::max);
+    (integrated(&tracks).map(replay_gain), peak)
+}
+
+fn replay_gain(integrated: f64) -> f32 {
+    (REFERENCE_LUFS - integrated) as f32
+}
+
+/// Gated loudness of the blocks of `tracks` together
+fn integrated(tracks: &[&TrackLoudness]) -> Option<f64> {
+    let blocks = || tracks.iter().flat_map(|t| t.blocks.iter().copied());
+    let mean_above = |threshold: f64| {
+        let (sum, count) = blocks()
+            .filter(|&e| e > threshold)
+            .fold((0.0, 0usize), |(sum, count), e| (sum + e, count + 1));
+        (count > 0).then(|| sum / count as f64)
+    };
+    let ungated = mean_above(energy(ABSOLUTE_GATE_LUFS))?;
+    let relative = energy(lufs(ungated) + RELATIVE_GATE_LU).max(energy(ABSOLUTE_GATE_LUFS));
+    mean_above(relative).map(lufs)
+}
+
+/// Decode a whole file and measure its loudness
+pub fn analyze(path: &Path) -> Result<TrackLoudness, PlayerError> {
+    let mut decoder = AudioDecoder::open(path)?;
+    let mut meter = LoudnessMeter::new(decoder.channels(), decoder.sample_rate());
+    while decoder
+        .decode_next(|samples| meter.feed(samples))?
+        .is_some()
+    {}
+    Ok(meter.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48000;
+
+    /// `secs` of a stereo 1 kHz sine peaking at `dbfs`
+    fn sine(dbfs: f64, secs: f64) -> Vec<f32> {
+        let amplitude = 10f64.powf(dbfs / 20.0);
+        let frames = (f64::from(RATE) * secs) as usize;
+        (0..frames)
+            .flat_map(|i| {
+                let t = i as f64 / f64::from(RATE);
+                let sample = (amplitude * (2.0 * std::f64::consts::PI * 1000.0 * t).sin()) as f32;
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    fn measure(chunks: &[Vec<f32>]) -> TrackLoudness {
+        let mut meter = LoudnessMeter::new(2, RATE);
+        for chunk in chunks {
+            meter.feed(chunk);
+        }
+        meter.finish()
+    }
+
+    #[test]
+    fn test_reference_tone() {
+        // EBU Tech 3341: a stereo 1 kHz sine at -23 dBFS reads -23 LUFS
+        let track = measure(&[sine(-23.0, 10.0)]);
+        let integrated = track.integrated().unwrap();
+        assert!((integrated + 23.0).abs() < 0.1, "{}", integrated);
+        assert!((track.gain().unwrap() - 5.0).abs() < 0.1);
+        let peak = 10f32.powf(-23.0 / 20.0);
+        assert!((track.peak - peak).abs() < 1e-3);
+
+        // At 44.1 kHz too
+        let mut meter = LoudnessMeter::new(1, 44100);
+        let amplitude = 10f32.powf(-20.0 / 20.0);
+        let tone: Vec<f32> = (0..441000)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 44100.0).sin())
+            .collect();
+        meter.feed(&tone);
+        // One channel of a sine at -20 dBFS is 3 dB quieter than two
+        let integrated = meter.finish().integrated().unwrap();
+        assert!((integrated + 23.0).abs() < 0.1, "{}", integrated);
+    }
+
+    #[test]
+    fn test_gating() {
+        // Long enough that the blocks straddling a change hardly count
+        let tone = measure(&[sine(-23.0, 20.0)]).integrated().unwrap();
+        // Silence is below the absolute gate
+        let with_silence = measure(&[sine(-23.0, 20.0), vec![0.0; 2 * RATE as usize * 5]]);
+        assert!((with_silence.integrated().unwrap() - tone).abs() < 0.1);
+        // A passage 20 LU down is below the relative gate
+        let with_quiet = measure(&[sine(-23.0, 20.0), sine(-43.0, 10.0)]);
+        assert!((with_quiet.integrated().unwrap() - tone).abs() < 0.1);
+
+        assert_eq!(measure(&[vec![0.0; 2 * RATE as usize]]).gain(), None);
+        assert_eq!(measure(&[sine(-23.0, 0.3)]).gain(), None);
+    }
+
+    #[test]
+    fn test_album_gates_tracks_together() {
+        let loud = measure(&[sine(-23.0, 5.0)]);
+        let quiet = measure(&[sine(-33.0, 5.0)]);
+        let (gain, peak) = album(&[loud.clone(), quiet.clone()]);
+        let gain = gain.unwrap();
+        // Energy-weighted, so closer to the loud track
+        assert!(gain > loud.gain().unwrap() && gain < quiet.gain().unwrap());
+        assert!(
+            (gain - (REFERENCE_LUFS as f32 + 25.6)).abs() < 0.2,
+            "{}",
+            gain
+        );
+        assert_eq!(peak, loud.peak);
+        assert_eq!(album(&[]), (None, 0.0));
+    }
+}
@@ -0,0 +1,165 @@
+//! Leading and trailing silence detection.
+//!
+//! CDs from the hidden-track era often end a track with minutes of digital
+//! silence, and some rips start with a few seconds of it. A track is decoded
+//! once (the `silence` maintenance job) and the silent stretches at both
+//! ends are stored; with "trim silence" on, playback skips them.
+//!
+//! Silence is anything below [`SILENCE_THRESHOLD`] on every channel, which
+//! is well under tape hiss or vinyl surface noise, so quiet intros and fade
+//! outs are kept.
+
+use std::path::Path;
+use std::time::Duration;
+
+use super::PlayerError;
+use super::decoder::AudioDecoder;
+
+/// Sample level below which audio counts as silence (-60 dBFS)
+pub const SILENCE_THRESHOLD: f32 = 0.001;
+
+/// Silence longer than this is flagged in quality checks
+pub const LONG_SILENCE: Duration = Duration::from_secs(5);
+
+/// Shorter silence isn't trimmed: it's the usual gap between album tracks
+pub const MIN_TRIM: Duration = Duration::from_secs(2);
+
+/// Silent stretches at the ends of a track
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Silence {
+    /// Silence before the first sound
+    pub leading: Duration,
+    /// Silence after the last sound
+    pub trailing: Duration,
+}
+
+impl Silence {
+    /// Whether either end is silent for longer than [`LONG_SILENCE`]
+    pub fn is_long(&self) -> bool {
+        self.leading > LONG_SILENCE || self.trailing > LONG_SILENCE
+    }
+
+    /// Where playback should start with trimming on
+    pub fn trimmed_start(&self) -> Option<Duration> {
+        (self.leading >= MIN_TRIM).then_some(self.leading)
+    }
+
+    /// Where playback should stop with trimming on, for a track of `duration`
+    pub fn trimmed_end(&self, duration: Duration) -> Option<Duration> {
+        (self.trailing >= MIN_TRIM)
+            .then(|| duration.saturating_sub(self.trailing))
+            .filter(|end| *end > self.leading)
+    }
+
+    /// From milliseconds as stored in the database
+    pub fn from_millis(leading: i64, trailing: i64) -> Self {
+        Self {
+            leading: Duration::from_millis(leading.max(0) as u64),
+            trailing: Duration::from_millis(trailing.max(0) as u64),
+        }
+    }
+}
+
+/// Finds the first and last audible frame of interleaved samples fed in
+/// chunks.
+#[derive(Debug, Clone)]
+pub struct SilenceDetector {
+    channels: usize,
+    sample_rate: u32,
+    frames: u64,
+    first_sound: Option<u64>,
+    last_sound: u64,
+}
+
+impl SilenceDetector {
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        Self {
+            channels: usize::from(channels.max(1)),
+            sample_rate: sample_rate.max(1),
+            frames: 0,
+            first_sound: None,
+            last_sound: 0,
+        }
+    }
+
+    /// Feed the next interleaved samples (whole frames)
+    pub fn feed(&mut self, samples: &[f32]) {
+        for frame in samples.chunks(self.channels) {
+            if frame.iter().any(|s| s.abs() >= SILENCE_THRESHOLD) {
+                self.first_sound.get_or_insert(self.frames);
+                self.last_sound = self.frames;
+            }
+            self.frames += 1;
+        }
+    }
+
+    /// The silence found; a track with no sound at all is all leading silence
+    pub fn finish(&self) -> Silence {
+        let secs = |frames: u64| Duration::from_secs_f64(frames as f64 / self.sample_rate as f64);
+        match self.first_sound {
+            Some(first) => Silence {
+                leading: secs(first),
+                trailing: secs(self.frames - self.last_sound - 1),
+            },
+            None => Silence {
+                leading: secs(self.frames),
+                trailing: Duration::ZERO,
+            },
+        }
+    }
+}
+
+/// Decode a whole file and measure the silence at its ends
+pub fn analyze(path: &Path) -> Result<Silence, PlayerError> {
+    let mut decoder = AudioDecoder::open(path)?;
+    let mut detector = SilenceDetector::new(decoder.channels(), decoder.sample_rate());
+    while decoder
+        .decode_next(|samples| detector.feed(samples))?
+        .is_some()
+    {}
+    Ok(detector.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detector_finds_silent_ends() {
+        // Stereo at 10 Hz: 3 s of silence, 2 s of sound, 6 s of silence
+        let mut detector = SilenceDetector::new(2, 10);
+        detector.feed(&[0.0; 60]);
+        detector.feed(&[0.0005, -0.2].repeat(20));
+        detector.feed(&[0.0; 40]);
+        detector.feed(&[0.0; 80]);
+
+        let silence = detector.finish();
+        assert_eq!(silence.leading, Duration::from_secs(3));
+        assert_eq!(silence.trailing, Duration::from_secs(6));
+        assert!(silence.is_long());
+        assert_eq!(silence.trimmed_start(), Some(Duration::from_secs(3)));
+        assert_eq!(
+            silence.trimmed_end(Duration::from_secs(11)),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn test_short_or_total_silence() {
+        let gap = Silence {
+            leading: Duration::from_millis(500),
+            trailing: Duration::from_secs(1),
+        };
+        assert!(!gap.is_long());
+        assert_eq!(gap.trimmed_start(), None);
+        assert_eq!(gap.trimmed_end(Duration::from_secs(200)), None);
+
+        let mut detector = SilenceDetector::new(1, 10);
+        detector.feed(&[0.0; 100]);
+        let silent = detector.finish();
+        assert_eq!(silent.leading, Duration::from_secs(10));
+        assert_eq!(silent.trimmed_end(Duration::from_secs(10)), None);
+
+        assert_eq!(Silence::from_millis(1500, -1).trailing, Duration::ZERO);
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides vectorized implementations of critical audio operations:
 //! - Volume scaling (multiply samples by volume)
 //! - f32 → i16 conversion (for i16 output devices)
+//! - Peak/RMS measurement (for level meters)
 //!
 //! # Architecture
 //!
@@ -346,6 +347,185 @@ unsafe fn f32_to_i16_avx2(input: &[f32], output: &mut [i16], volume: f32) {
     }
 }
 
+/// Peak and RMS of one batch of samples, for level meters.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChannelLevels {
+    /// Peak absolute sample per channel (left, right), 1.0 = full scale
+    pub peak: [f32; 2],
+    /// RMS per channel (left, right)
+    pub rms: [f32; 2],
+    /// Samples at or beyond full scale (any channel)
+    pub clipped: u32,
+}
+
+/// Measure peak and RMS of interleaved samples, using best available SIMD.
+///
+/// Mono is reported on both channels. With more than two channels only the
+/// front left and right are metered, but clipping is counted on all of them.
+#[inline]
+pub fn channel_levels(samples: &[f32], channels: usize) -> ChannelLevels {
+    let channels = channels.max(1);
+    let mut sums = LevelSums::default();
+
+    // Vector lanes map onto channels only for mono and stereo
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    let start = if channels <= 2 {
+        match detect_simd_level() {
+            SimdLevel::Avx2 => unsafe { channel_levels_avx2(samples, channels, &mut sums) },
+            SimdLevel::Sse41 => unsafe { channel_levels_sse41(samples, channels, &mut sums) },
+            _ => 0,
+        }
+    } else {
+        0
+    };
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    let start = 0;
+
+    sums.add_scalar(samples, start, channels);
+    sums.finish(samples.len(), channels)
+}
+
+/// Running per-channel sums while measuring levels
+#[derive(Debug, Default)]
+struct LevelSums {
+    peak: [f32; 2],
+    sum_sq: [f32; 2],
+    clipped: u32,
+}
+
+impl LevelSums {
+    /// Fold in samples from index `start` on.
+    ///
+    /// Note: `#[inline(never)]` keeps this an honest scalar fallback.
+    #[inline(never)]
+    fn add_scalar(&mut self, samples: &[f32], start: usize, channels: usize) {
+        for (i, &s) in samples.iter().enumerate().skip(start) {
+            let abs = s.abs();
+            if abs >= 1.0 {
+                self.clipped += 1;
+            }
+            let ch = i % channels;
+            if ch < 2 {
+                self.peak[ch] = self.peak[ch].max(abs);
+                self.sum_sq[ch] += s * s;
+            }
+        }
+    }
+
+    /// Fold in vector lanes. Lane `i` holds channel `i % channels`, since
+    /// vectors start at even sample indices.
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    fn add_lanes(&mut self, peak: &[f32], sum_sq: &[f32], channels: usize) {
+        for (i, (&p, &sq)) in peak.iter().zip(sum_sq).enumerate() {
+            let ch = i % channels;
+            self.peak[ch] = self.peak[ch].max(p);
+            self.sum_sq[ch] += sq;
+        }
+    }
+
+    fn finish(self, len: usize, channels: usize) -> ChannelLevels {
+        let frames = (len / channels).max(1) as f32;
+        let rms = |ch: usize| (self.sum_sq[ch] / frames).sqrt();
+        if channels == 1 {
+            ChannelLevels {
+                peak: [self.peak[0]; 2],
+                rms: [rms(0); 2],
+                clipped: self.clipped,
+            }
+        } else {
+            ChannelLevels {
+                peak: self.peak,
+                rms: [rms(0), rms(1)],
+                clipped: self.clipped,
+            }
+        }
+    }
+}
+
+/// SSE4.1 implementation: 4 samples at a time. Returns the index the
+/// scalar tail should start from.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse4.1")]
+unsafe fn channel_levels_sse41(samples: &[f32], channels: usize, sums: &mut LevelSums) -> usize {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    let sign = _mm_set1_ps(-0.0);
+    let full_scale = _mm_set1_ps(1.0);
+    let mut peak = _mm_setzero_ps();
+    let mut sum_sq = _mm_setzero_ps();
+    let len = samples.len();
+    let ptr = samples.as_ptr();
+
+    let mut i = 0;
+    while i + 4 <= len {
+        unsafe {
+            let data = _mm_loadu_ps(ptr.add(i));
+            let abs = _mm_andnot_ps(sign, data);
+            peak = _mm_max_ps(peak, abs);
+            sum_sq = _mm_add_ps(sum_sq, _mm_mul_ps(data, data));
+            let clipped = _mm_movemask_ps(_mm_cmpge_ps(abs, full_scale));
+            sums.clipped += clipped.count_ones();
+        }
+        i += 4;
+    }
+
+    let mut peak_lanes = [0.0f32; 4];
+    let mut sq_lanes = [0.0f32; 4];
+    unsafe {
+        _mm_storeu_ps(peak_lanes.as_mut_ptr(), peak);
+        _mm_storeu_ps(sq_lanes.as_mut_ptr(), sum_sq);
+    }
+    sums.add_lanes(&peak_lanes, &sq_lanes, channels);
+    i
+}
+
+/// AVX2 implementation: 8 samples at a time. Returns the index the scalar
+/// tail should start from.
+///
+/// # Safety
+///
+/// Caller must ensure AVX2 is available (checked by channel_levels).
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn channel_levels_avx2(samples: &[f32], channels: usize, sums: &mut LevelSums) -> usize {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    let sign = _mm256_set1_ps(-0.0);
+    let full_scale = _mm256_set1_ps(1.0);
+    let mut peak = _mm256_setzero_ps();
+    let mut sum_sq = _mm256_setzero_ps();
+    let len = samples.len();
+    let ptr = samples.as_ptr();
+
+    let mut i = 0;
+    while i + 8 <= len {
+        unsafe {
+            let data = _mm256_loadu_ps(ptr.add(i));
+            let abs = _mm256_andnot_ps(sign, data);
+            peak = _mm256_max_ps(peak, abs);
+            sum_sq = _mm256_add_ps(sum_sq, _mm256_mul_ps(data, data));
+            let clipped = _mm256_movemask_ps(_mm256_cmp_ps::<_CMP_GE_OQ>(abs, full_scale));
+            sums.clipped += clipped.count_ones();
+        }
+        i += 8;
+    }
+
+    let mut peak_lanes = [0.0f32; 8];
+    let mut sq_lanes = [0.0f32; 8];
+    unsafe {
+        _mm256_storeu_ps(peak_lanes.as_mut_ptr(), peak);
+        _mm256_storeu_ps(sq_lanes.as_mut_ptr(), sum_sq);
+    }
+    sums.add_lanes(&peak_lanes, &sq_lanes, channels);
+    i
+}
+
 /// Get the current SIMD level for diagnostics.
 pub fn current_simd_level() -> SimdLevel {
     detect_simd_level()
@@ -675,6 +855,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_channel_levels() {
+        // Odd length exercises the scalar tail
+        let mut stereo: Vec<f32> = (0..1027)
+            .map(|i| if i % 2 == 0 { 0.5 } else { -0.25 })
+            .collect();
+        stereo[10] = -1.2;
+        let levels = channel_levels(&stereo, 2);
+        assert_eq!(levels.peak, [1.2, 0.25]);
+        assert!((levels.rms[1] - 0.25).abs() < 1e-4);
+        assert_eq!(levels.clipped, 1);
+
+        let mono = vec![0.5f32; 100];
+        let levels = channel_levels(&mono, 1);
+        assert_eq!(levels.peak, [0.5, 0.5]);
+        assert!((levels.rms[0] - 0.5).abs() < 1e-4);
+        assert_eq!(levels.rms[0], levels.rms[1]);
+
+        // 5.1: front left/right metered, clipping counted on every channel
+        let mut surround = vec![0.1f32; 60];
+        surround[4] = 1.0;
+        let levels = channel_levels(&surround, 6);
+        assert_eq!(levels.peak, [0.1, 0.1]);
+        assert_eq!(levels.clipped, 1);
+
+        assert_eq!(channel_levels(&[], 2), ChannelLevels::default());
+    }
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    #[test]
+    fn test_simd_matches_scalar_levels() {
+        let input: Vec<f32> = (0..1030).map(|i| ((i as f32) * 0.37).sin() * 1.1).collect();
+        for channels in [1, 2] {
+            let mut scalar = LevelSums::default();
+            scalar.add_scalar(&input, 0, channels);
+            let scalar = scalar.finish(input.len(), channels);
+            let simd = channel_levels(&input, channels);
+
+            assert_eq!(scalar.peak, simd.peak);
+            assert_eq!(scalar.clipped, simd.clipped);
+            for (s, d) in scalar.rms.iter().zip(simd.rms.iter()) {
+                assert!(
+                    (s - d).abs() < 1e-4,
+                    "RMS mismatch: scalar={}, simd={}",
+                    s,
+                    d
+                );
+            }
+        }
+    }
+
     #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
     #[test]
     fn test_simd_matches_scalar_volume() {
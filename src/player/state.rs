@@ -2,9 +2,14 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU32, AtomicU64, Ordering};
 use std::time::Duration;
 
+use super::equalizer::{self, BANDS, EqGains};
+use super::normalization::{self, NormalizationMode};
+use super::simd::ChannelLevels;
+use crate::metadata::Loudness;
+
 /// Lock-free shared state for the audio callback.
 ///
 /// This struct uses atomics to avoid priority inversion in the real-time audio thread.
@@ -17,6 +22,25 @@ pub struct AudioSharedState {
     is_playing: AtomicBool,
     /// Whether the buffer is being flushed (drain old samples, output silence)
     is_flushing: AtomicBool,
+    /// Whether the buffered samples are being faded out and dropped (seek)
+    is_discarding: AtomicBool,
+    /// Fade on pause, stop and seek in ms (0 = instant)
+    fade_ms: AtomicU32,
+    /// Crossfade between tracks in seconds (0 = off)
+    crossfade_secs: AtomicU32,
+    /// Loudness normalization mode (`NormalizationMode::to_u8`)
+    normalization_mode: AtomicU8,
+    /// Normalization preamp in dB, as f32 bits
+    preamp_db_bits: AtomicU32,
+    /// Normalization gain of the current track (amplitude), as f32 bits
+    replay_gain_bits: AtomicU32,
+    /// Whether the equalizer is on
+    eq_enabled: AtomicBool,
+    /// Equalizer band gains in dB, as f32 bits
+    eq_gain_bits: [AtomicU32; BANDS],
+    /// Bumped on every equalizer change, so the decoder thread rebuilds
+    /// its filters
+    eq_generation: AtomicU32,
     /// Current position in nanoseconds
     position_nanos: AtomicU64,
     /// Buffer underrun count
@@ -29,6 +53,24 @@ pub struct AudioSharedState {
     peak_callback_us: AtomicU32,
     /// Ring buffer fill level (0-100)
     buffer_fill_percent: AtomicU32,
+    /// Buffer setting in ms (`buffering::AUTO` = tune automatically)
+    buffer_setting_ms: AtomicU32,
+    /// Decode-ahead the decoder thread is keeping, in ms
+    decode_ahead_ms: AtomicU32,
+    /// Peak level per channel since the levels were last taken, as f32 bits
+    level_peak_bits: [AtomicU32; 2],
+    /// RMS level per channel of the latest callback, as f32 bits
+    level_rms_bits: [AtomicU32; 2],
+    /// Clipped samples since the levels were last taken
+    clips_untaken: AtomicU32,
+    /// Clipped samples since the stats were reset
+    clipped_samples: AtomicU64,
+    /// Time the decoder thread spent decoding, in nanoseconds
+    decode_nanos: AtomicU64,
+    /// Time the decoder thread spent resampling, in nanoseconds
+    resample_nanos: AtomicU64,
+    /// Events the decoder thread dropped because the UI's channel was full
+    events_dropped: AtomicU32,
 }
 
 impl Default for AudioSharedState {
@@ -37,12 +79,30 @@ impl Default for AudioSharedState {
             volume_bits: AtomicU32::new(1.0_f32.to_bits()),
             is_playing: AtomicBool::new(false),
             is_flushing: AtomicBool::new(false),
+            is_discarding: AtomicBool::new(false),
+            fade_ms: AtomicU32::new(super::fade::DEFAULT_FADE_MS),
+            crossfade_secs: AtomicU32::new(super::crossfade::DEFAULT_CROSSFADE_SECS),
+            normalization_mode: AtomicU8::new(NormalizationMode::Off.to_u8()),
+            preamp_db_bits: AtomicU32::new(normalization::DEFAULT_PREAMP_DB.to_bits()),
+            replay_gain_bits: AtomicU32::new(1.0_f32.to_bits()),
+            eq_enabled: AtomicBool::new(false),
+            eq_gain_bits: Default::default(),
+            eq_generation: AtomicU32::new(0),
             position_nanos: AtomicU64::new(0),
             underruns: AtomicU32::new(0),
             callback_count: AtomicU64::new(0),
             samples_processed: AtomicU64::new(0),
             peak_callback_us: AtomicU32::new(0),
             buffer_fill_percent: AtomicU32::new(0),
+            buffer_setting_ms: AtomicU32::new(super::buffering::AUTO),
+            decode_ahead_ms: AtomicU32::new(super::buffering::DEFAULT_AHEAD_MS),
+            level_peak_bits: Default::default(),
+            level_rms_bits: Default::default(),
+            clips_untaken: AtomicU32::new(0),
+            clipped_samples: AtomicU64::new(0),
+            decode_nanos: AtomicU64::new(0),
+            resample_nanos: AtomicU64::new(0),
+            events_dropped: AtomicU32::new(0),
         }
     }
 }
@@ -96,6 +156,112 @@ impl AudioSharedState {
         self.is_flushing.store(false, Ordering::Release);
     }
 
+    /// Check if the buffered samples are being faded out and dropped.
+    #[inline]
+    pub fn is_discarding(&self) -> bool {
+        self.is_discarding.load(Ordering::Acquire)
+    }
+
+    /// Ask the audio callback to fade out the buffered samples and drop
+    /// the rest; it clears this once done.
+    #[inline]
+    pub fn start_discard(&self) {
+        self.is_discarding.store(true, Ordering::Release);
+    }
+
+    /// The buffered samples are gone.
+    #[inline]
+    pub fn end_discard(&self) {
+        self.is_discarding.store(false, Ordering::Release);
+    }
+
+    /// Fade length in ms (0 = instant).
+    #[inline]
+    pub fn fade_ms(&self) -> u32 {
+        self.fade_ms.load(Ordering::Relaxed)
+    }
+
+    /// Set the fade length in ms.
+    #[inline]
+    pub fn set_fade_ms(&self, ms: u32) {
+        self.fade_ms.store(ms, Ordering::Relaxed);
+    }
+
+    /// Crossfade between tracks in seconds (0 = off).
+    #[inline]
+    pub fn crossfade_secs(&self) -> u32 {
+        self.crossfade_secs.load(Ordering::Relaxed)
+    }
+
+    /// Set the crossfade in seconds, up to `crossfade::MAX_CROSSFADE_SECS`.
+    #[inline]
+    pub fn set_crossfade_secs(&self, secs: u32) {
+        self.crossfade_secs.store(
+            secs.min(super::crossfade::MAX_CROSSFADE_SECS),
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Loudness normalization mode.
+    #[inline]
+    pub fn normalization_mode(&self) -> NormalizationMode {
+        NormalizationMode::from_u8(self.normalization_mode.load(Ordering::Relaxed))
+    }
+
+    /// Set the normalization mode and preamp (dB, held to
+    /// `normalization::PREAMP_RANGE_DB`).
+    #[inline]
+    pub fn set_normalization(&self, mode: NormalizationMode, preamp_db: f32) {
+        let (min, max) = normalization::PREAMP_RANGE_DB;
+        self.preamp_db_bits
+            .store(preamp_db.clamp(min, max).to_bits(), Ordering::Relaxed);
+        self.normalization_mode
+            .store(mode.to_u8(), Ordering::Relaxed);
+    }
+
+    /// Normalization preamp in dB.
+    #[inline]
+    pub fn preamp_db(&self) -> f32 {
+        f32::from_bits(self.preamp_db_bits.load(Ordering::Relaxed))
+    }
+
+    /// Normalization gain the callback applies (amplitude; 1.0 = none).
+    #[inline]
+    pub fn replay_gain(&self) -> f32 {
+        f32::from_bits(self.replay_gain_bits.load(Ordering::Relaxed))
+    }
+
+    /// Set the normalization gain of the current track (decoder thread).
+    #[inline]
+    pub fn set_replay_gain(&self, amplitude: f32) {
+        self.replay_gain_bits
+            .store(amplitude.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// Turn the equalizer on or off and set its band gains (dB, held to
+    /// `equalizer::GAIN_RANGE_DB`).
+    pub fn set_equalizer(&self, enabled: bool, gains: &EqGains) {
+        for (bits, gain) in self.eq_gain_bits.iter().zip(equalizer::clamp_gains(gains)) {
+            bits.store(gain.to_bits(), Ordering::Relaxed);
+        }
+        self.eq_enabled.store(enabled, Ordering::Relaxed);
+        self.eq_generation.fetch_add(1, Ordering::Release);
+    }
+
+    /// Counter bumped by every [`Self::set_equalizer`].
+    #[inline]
+    pub fn eq_generation(&self) -> u32 {
+        self.eq_generation.load(Ordering::Acquire)
+    }
+
+    /// Band gains the equalizer applies, in dB (all flat when it's off).
+    pub fn eq_gains(&self) -> EqGains {
+        if !self.eq_enabled.load(Ordering::Relaxed) {
+            return [0.0; BANDS];
+        }
+        std::array::from_fn(|band| f32::from_bits(self.eq_gain_bits[band].load(Ordering::Relaxed)))
+    }
+
     /// Get the current position as Duration.
     #[inline]
     pub fn position(&self) -> Duration {
@@ -156,6 +322,73 @@ impl AudioSharedState {
         self.buffer_fill_percent.load(Ordering::Relaxed)
     }
 
+    /// Get the buffer setting in ms (`buffering::AUTO` = automatic).
+    #[inline]
+    pub fn buffer_setting(&self) -> u32 {
+        self.buffer_setting_ms.load(Ordering::Relaxed)
+    }
+
+    /// Set the buffer size in ms (`buffering::AUTO` = automatic).
+    #[inline]
+    pub fn set_buffer_setting(&self, ms: u32) {
+        self.buffer_setting_ms.store(ms, Ordering::Relaxed);
+    }
+
+    /// Get the decode-ahead in ms.
+    #[inline]
+    pub fn decode_ahead(&self) -> u32 {
+        self.decode_ahead_ms.load(Ordering::Relaxed)
+    }
+
+    /// Set the decode-ahead in ms (decoder thread).
+    #[inline]
+    pub fn set_decode_ahead(&self, ms: u32) {
+        self.decode_ahead_ms.store(ms, Ordering::Relaxed);
+    }
+
+    /// Record the levels of a callback's samples.
+    ///
+    /// Peaks are kept until taken, so none are missed between UI frames.
+    /// Non-negative f32 bits order like the floats, so `fetch_max` works.
+    #[inline]
+    pub fn record_levels(&self, levels: &ChannelLevels) {
+        for ch in 0..2 {
+            self.level_peak_bits[ch].fetch_max(levels.peak[ch].abs().to_bits(), Ordering::Relaxed);
+            self.level_rms_bits[ch].store(levels.rms[ch].to_bits(), Ordering::Relaxed);
+        }
+        if levels.clipped > 0 {
+            self.clips_untaken
+                .fetch_add(levels.clipped, Ordering::Relaxed);
+            self.clipped_samples
+                .fetch_add(u64::from(levels.clipped), Ordering::Relaxed);
+        }
+    }
+
+    /// Drop the RMS levels to silence (paused, flushing).
+    #[inline]
+    pub fn clear_levels(&self) {
+        for rms in &self.level_rms_bits {
+            rms.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Take the levels since the last call: peaks and clips are reset.
+    pub fn take_levels(&self) -> ChannelLevels {
+        let peak = |ch: usize| f32::from_bits(self.level_peak_bits[ch].swap(0, Ordering::Relaxed));
+        let rms = |ch: usize| f32::from_bits(self.level_rms_bits[ch].load(Ordering::Relaxed));
+        ChannelLevels {
+            peak: [peak(0), peak(1)],
+            rms: [rms(0), rms(1)],
+            clipped: self.clips_untaken.swap(0, Ordering::Relaxed),
+        }
+    }
+
+    /// Get clipped samples since the stats were reset.
+    #[inline]
+    pub fn clipped_samples(&self) -> u64 {
+        self.clipped_samples.load(Ordering::Relaxed)
+    }
+
     /// Get the callback count.
     #[inline]
     pub fn callback_count(&self) -> u64 {
@@ -174,12 +407,49 @@ impl AudioSharedState {
         self.peak_callback_us.load(Ordering::Relaxed)
     }
 
+    /// Record the time one packet took to decode and to resample (decoder thread).
+    #[inline]
+    pub fn record_decode(&self, decode: Duration, resample: Duration) {
+        self.decode_nanos
+            .fetch_add(decode.as_nanos() as u64, Ordering::Relaxed);
+        self.resample_nanos
+            .fetch_add(resample.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Get the time spent decoding, in nanoseconds.
+    #[inline]
+    pub fn decode_nanos(&self) -> u64 {
+        self.decode_nanos.load(Ordering::Relaxed)
+    }
+
+    /// Get the time spent resampling, in nanoseconds.
+    #[inline]
+    pub fn resample_nanos(&self) -> u64 {
+        self.resample_nanos.load(Ordering::Relaxed)
+    }
+
+    /// Count an event dropped on a full channel (decoder thread).
+    #[inline]
+    pub fn increment_events_dropped(&self) {
+        self.events_dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the events dropped on a full channel.
+    #[inline]
+    pub fn events_dropped(&self) -> u32 {
+        self.events_dropped.load(Ordering::Relaxed)
+    }
+
     /// Reset performance counters.
     pub fn reset_stats(&self) {
         self.underruns.store(0, Ordering::Relaxed);
         self.callback_count.store(0, Ordering::Relaxed);
         self.samples_processed.store(0, Ordering::Relaxed);
         self.peak_callback_us.store(0, Ordering::Relaxed);
+        self.clipped_samples.store(0, Ordering::Relaxed);
+        self.decode_nanos.store(0, Ordering::Relaxed);
+        self.resample_nanos.store(0, Ordering::Relaxed);
+        self.events_dropped.store(0, Ordering::Relaxed);
     }
 }
 
@@ -265,6 +535,10 @@ pub struct PlayerState {
     pub underruns: u32,
     /// Audio quality information
     pub quality: AudioQuality,
+    /// Which ReplayGain value is applied
+    pub normalization: NormalizationMode,
+    /// Normalization gain applied to the current track (amplitude)
+    pub normalization_gain: f32,
 }
 
 impl Default for PlayerState {
@@ -280,6 +554,8 @@ impl Default for PlayerState {
             bits_per_sample: 16,
             underruns: 0,
             quality: AudioQuality::default(),
+            normalization: NormalizationMode::Off,
+            normalization_gain: 1.0,
         }
     }
 }
@@ -367,6 +643,11 @@ pub enum PlayerCommand {
     Stop,
     /// Seek to position (0.0 - 1.0)
     Seek(f32),
+    /// The track that plays after the current one, to crossfade into
+    SetNext(Option<PathBuf>),
+    /// Loudness stored in the library for a track, used when its file has
+    /// no loudness tags
+    StoredLoudness(PathBuf, Loudness),
     /// Shutdown the audio thread
     Shutdown,
 }
@@ -392,12 +673,16 @@ pub enum PlayerEvent {
         bits_per_sample: u16,
         quality: AudioQuality,
         /// Metadata read directly from file tags (fallback when not in DB)
-        file_metadata: TrackInfo,
+        file_metadata: Box<TrackInfo>,
     },
     /// Position updated (sent periodically during playback)
     PositionChanged(Duration),
     /// Playback finished (end of track)
     PlaybackFinished,
+    /// The next track started fading in over the end of the current one;
+    /// its `TrackLoaded` follows. No `PlaybackFinished` comes for the
+    /// track fading out.
+    CrossfadeStarted(PathBuf),
     /// An error occurred
     Error(String),
 }
@@ -407,6 +692,7 @@ pub enum PlayerEvent {
 pub struct TrackInfo {
     pub title: Option<String>,
     pub artist: Option<String>,
+    pub album_artist: Option<String>,
     pub album: Option<String>,
     pub track_number: Option<u32>,
     pub year: Option<i32>,
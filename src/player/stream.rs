@@ -0,0 +1,239 @@
+//! Streaming decode path for remote tracks.
+//!
+//! A track whose path is an HTTP stream or cloud drive link is downloaded
+//! on its own thread while the decoder reads it, a few chunks ahead.
+//! Streams can't seek: the decoder only moves forward through them.
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use symphonia::core::io::MediaSource;
+
+use super::PlayerError;
+use crate::model::download_url;
+
+/// Chunks downloaded ahead of the decoder
+const CHUNKS_AHEAD: usize = 64;
+
+/// How long to wait for the server to answer
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long a read waits for the next chunk before giving up
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A remote track's audio, read as it downloads
+pub struct HttpStream {
+    chunks: Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    /// Read position in `chunk`
+    offset: usize,
+    /// Bytes read so far
+    position: u64,
+    /// From the Content-Length header
+    len: Option<u64>,
+}
+
+/// What the server answered before the body
+struct Head {
+    content_type: Option<String>,
+    len: Option<u64>,
+}
+
+impl HttpStream {
+    /// Start downloading `url` (share links go to their direct download
+    /// link). Returns the stream and the file extension its content type
+    /// or URL suggests, as a hint for the format probe.
+    pub fn open(url: &str) -> Result<(Self, Option<String>), PlayerError> {
+        let url = download_url(url);
+        let (head_tx, head_rx) = crossbeam_channel::bounded(1);
+        let (chunk_tx, chunk_rx) = crossbeam_channel::bounded(CHUNKS_AHEAD);
+        let thread_url = url.clone();
+        std::thread::Builder::new()
+            .name("stream-download".to_string())
+            .spawn(move || download(&thread_url, head_tx, chunk_tx))
+            .map_err(|e| PlayerError::Stream(e.to_string()))?;
+
+        let head = match head_rx.recv_timeout(CONNECT_TIMEOUT) {
+            Ok(head) => head?,
+            Err(_) => {
+                return Err(PlayerError::Stream(format!(
+                    "{}: no answer from the server",
+                    url
+                )));
+            }
+        };
+        let hint = head
+            .content_type
+            .as_deref()
+            .and_then(extension_for_content_type)
+            .map(str::to_string)
+            .or_else(|| url_extension(&url));
+        Ok((
+            Self {
+                chunks: chunk_rx,
+                chunk: Vec::new(),
+                offset: 0,
+                position: 0,
+                len: head.len,
+            },
+            hint,
+        ))
+    }
+}
+
+/// Download `url`, sending the head and then the body in chunks. Stops when
+/// the stream is dropped.
+fn download(
+    url: &str,
+    head_tx: Sender<Result<Head, PlayerError>>,
+    chunk_tx: Sender<io::Result<Vec<u8>>>,
+) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            let _ = head_tx.send(Err(PlayerError::Stream(e.to_string())));
+            return;
+        }
+    };
+    runtime.block_on(async {
+        let response = match reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+        {
+            Ok(client) => client.get(url).send().await,
+            Err(e) => Err(e),
+        };
+        let mut response = match response {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                let _ = head_tx.send(Err(PlayerError::Stream(format!(
+                    "{}: HTTP {}",
+                    url,
+                    response.status()
+                ))));
+                return;
+            }
+            Err(e) => {
+                let _ = head_tx.send(Err(PlayerError::Stream(format!("{}: {}", url, e))));
+                return;
+            }
+        };
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let head = Head {
+            content_type,
+            len: response.content_length(),
+        };
+        if head_tx.send(Ok(head)).is_err() {
+            return;
+        }
+
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => Ok(chunk.to_vec()),
+                Ok(None) => return,
+                Err(e) => Err(io::Error::other(e)),
+            };
+            let failed = chunk.is_err();
+            // The receiver is gone once the track stops
+            if chunk_tx.send(chunk).is_err() || failed {
+                return;
+            }
+        }
+    });
+}
+
+impl Read for HttpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset >= self.chunk.len() {
+            match self.chunks.recv_timeout(READ_TIMEOUT) {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.offset = 0;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, "stream stalled"));
+                }
+                // Downloaded to the end
+                Err(RecvTimeoutError::Disconnected) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.offset);
+        buf[..n].copy_from_slice(&self.chunk[self.offset..self.offset + n]);
+        self.offset += n;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for HttpStream {
+    /// Only reports the position: streams play forward
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match pos {
+            SeekFrom::Current(0) => Ok(self.position),
+            SeekFrom::Start(n) if n == self.position => Ok(self.position),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "streams can't seek",
+            )),
+        }
+    }
+}
+
+impl MediaSource for HttpStream {
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        self.len
+    }
+}
+
+/// File extension for an audio content type
+fn extension_for_content_type(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    Some(match mime.as_str() {
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/flac" | "audio/x-flac" => "flac",
+        "audio/ogg" | "application/ogg" | "audio/vorbis" | "audio/opus" => "ogg",
+        "audio/aac" | "audio/aacp" => "aac",
+        "audio/mp4" | "audio/x-m4a" => "m4a",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        _ => return None,
+    })
+}
+
+/// File extension at the end of a URL's path
+fn url_extension(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next()?;
+    let name = path.rsplit('/').next()?;
+    let (_, ext) = name.rsplit_once('.')?;
+    (!ext.is_empty() && ext.len() <= 4).then(|| ext.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_hints() {
+        assert_eq!(
+            extension_for_content_type("audio/mpeg; charset=binary"),
+            Some("mp3")
+        );
+        assert_eq!(extension_for_content_type("text/html"), None);
+        assert_eq!(
+            url_extension("http://host/music/a.FLAC?token=1"),
+            Some("flac".to_string())
+        );
+        assert_eq!(url_extension("http://radio.example:8000/live"), None);
+    }
+}
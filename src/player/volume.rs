@@ -0,0 +1,108 @@
+//! Mapping between the volume slider and the output gain.
+//!
+//! Loudness is heard logarithmically, so a slider linear in amplitude packs
+//! everything but the quietest levels into its top half. The slider instead
+//! moves through [`RANGE_DB`] of attenuation evenly, and the keyboard and
+//! mouse wheel step through it in dB. Full travel is unity gain and nothing
+//! boosts past it, so tracks with ReplayGain applied keep their headroom.
+//!
+//! Only the mapping changes: the player still stores and the audio callback
+//! still reads a single amplitude (0.0 - 1.0).
+
+/// Attenuation at the bottom of the slider, just above mute, in dB
+pub const RANGE_DB: f32 = 60.0;
+
+/// Volume steps offered in settings, in dB
+pub const STEP_CHOICES_DB: [f32; 4] = [1.0, 2.0, 3.0, 6.0];
+
+/// Volume step unless the config sets one, in dB
+pub const DEFAULT_STEP_DB: f32 = 3.0;
+
+/// Amplitude for a slider position (0.0 - 1.0; 0 mutes)
+pub fn amplitude(position: f32) -> f32 {
+    let position = position.clamp(0.0, 1.0);
+    if position == 0.0 {
+        return 0.0;
+    }
+    from_db((position - 1.0) * RANGE_DB)
+}
+
+/// Slider position for an amplitude, the inverse of [`amplitude`]
+pub fn position(amplitude: f32) -> f32 {
+    match db(amplitude) {
+        Some(db) => (1.0 + db / RANGE_DB).clamp(0.0, 1.0),
+        None => 0.0,
+    }
+}
+
+/// Gain of an amplitude in dB (`None` when muted)
+pub fn db(amplitude: f32) -> Option<f32> {
+    let amplitude = amplitude.clamp(0.0, 1.0);
+    (amplitude > 0.0).then(|| 20.0 * amplitude.log10())
+}
+
+/// Amplitude one step of `step_db` up or down from `amplitude`. Stepping
+/// down past [`RANGE_DB`] mutes; stepping up from mute starts at the bottom
+/// of the range.
+pub fn stepped(amplitude: f32, step_db: f32, up: bool) -> f32 {
+    let step_db = step_db.abs().max(0.1);
+    let target = match db(amplitude) {
+        Some(db) if up => db + step_db,
+        Some(db) => db - step_db,
+        None if up => step_db - RANGE_DB,
+        None => return 0.0,
+    };
+    if target < -RANGE_DB {
+        0.0
+    } else {
+        from_db(target.min(0.0))
+    }
+}
+
+/// The gain for display: "-12.0 dB", "0 dB" or "Muted"
+pub fn label(amplitude: f32) -> String {
+    match db(amplitude) {
+        None => "Muted".to_string(),
+        Some(db) if db > -0.05 => "0 dB".to_string(),
+        Some(db) => format!("{:.1} dB", db),
+    }
+}
+
+fn from_db(db: f32) -> f32 {
+    10f32.powf(db / 20.0).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_volume_curve_is_logarithmic() {
+        assert_eq!(amplitude(0.0), 0.0);
+        assert_eq!(amplitude(1.0), 1.0);
+        // Halfway is -30 dB, not half the amplitude (-6 dB)
+        assert!((db(amplitude(0.5)).unwrap() + 30.0).abs() < 1e-3);
+        for p in [0.1, 0.25, 0.5, 0.9] {
+            assert!((position(amplitude(p)) - p).abs() < 1e-4);
+        }
+        assert_eq!(position(0.0), 0.0);
+        assert_eq!(label(0.0), "Muted");
+        assert_eq!(label(1.0), "0 dB");
+        assert_eq!(label(amplitude(0.8)), "-12.0 dB");
+    }
+
+    #[test]
+    fn test_volume_steps_in_db() {
+        // Never above unity gain
+        assert_eq!(stepped(1.0, 3.0, true), 1.0);
+        let down = stepped(1.0, 3.0, false);
+        assert!((db(down).unwrap() + 3.0).abs() < 1e-3);
+        assert!((stepped(down, 3.0, true) - 1.0).abs() < 1e-5);
+
+        // Off the bottom mutes, and back up starts at the bottom
+        assert_eq!(stepped(amplitude(0.01), 3.0, false), 0.0);
+        let up = stepped(0.0, 3.0, true);
+        assert!((db(up).unwrap() + 57.0).abs() < 1e-3);
+        assert_eq!(stepped(0.0, 3.0, false), 0.0);
+    }
+}
@@ -0,0 +1,149 @@
+//! Portable mode: everything on one USB stick.
+//!
+//! Turned on by a `portable.txt` file next to the executable, or by
+//! `--portable`. The database, config, profiles and cover cache then live in
+//! `data/` next to the executable instead of the OS directories, and tracks
+//! on the executable's drive are stored relative to the drive's root (see
+//! [`crate::db::paths::PathPolicy::relative_to`]).
+//!
+//! The working directory is moved to that root at startup, so the relative
+//! paths open the same files whatever drive letter or mount point the stick
+//! gets on the next machine. Paths given on the command line are made
+//! absolute before that happens (see [`crate::cli::Cli::parse_absolute`]).
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config;
+
+/// File next to the executable that turns portable mode on
+pub const MARKER: &str = "portable.txt";
+
+/// Directory next to the executable holding the portable data
+pub const DATA_DIR: &str = "data";
+
+static PORTABLE: OnceLock<Portable> = OnceLock::new();
+
+/// Where a portable install keeps its files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Portable {
+    /// Database, config, profiles and caches
+    pub data_dir: PathBuf,
+    /// Root of the drive the executable is on (`E:\`, or the mount point)
+    pub drive_root: Option<PathBuf>,
+}
+
+impl Portable {
+    /// Portable layout for an executable, if `flag` is set or the marker is
+    /// next to it
+    pub fn detect(exe: &Path, flag: bool) -> Option<Self> {
+        let exe_dir = exe.parent()?;
+        if !flag && !exe_dir.join(MARKER).exists() {
+            return None;
+        }
+        Some(Self {
+            data_dir: exe_dir.join(DATA_DIR),
+            drive_root: drive_root(exe_dir),
+        })
+    }
+
+    /// Directory for a cache (`covers`, ...)
+    pub fn cache_dir(&self, name: &str) -> PathBuf {
+        self.data_dir.join("cache").join(name)
+    }
+}
+
+/// Decide at startup whether this is a portable install. If it is, the
+/// data directory is created and used as the config directory (unless
+/// `--config-dir` set one already).
+pub fn init(flag: bool) -> std::io::Result<Option<&'static Portable>> {
+    let exe = std::env::current_exe()?;
+    let Some(portable) = Portable::detect(&exe, flag) else {
+        return Ok(None);
+    };
+    std::fs::create_dir_all(&portable.data_dir)?;
+    config::set_config_dir(portable.data_dir.clone());
+    Ok(Some(PORTABLE.get_or_init(|| portable)))
+}
+
+/// The portable layout in use, if any
+pub fn get() -> Option<&'static Portable> {
+    PORTABLE.get()
+}
+
+/// Whether this is a portable install
+pub fn is_active() -> bool {
+    get().is_some()
+}
+
+/// Move the working directory to the drive root, which relative library
+/// paths are resolved against
+pub fn enter(portable: &Portable) -> std::io::Result<()> {
+    if let Some(root) = &portable.drive_root {
+        std::env::set_current_dir(root)?;
+        tracing::info!(
+            "Portable mode: library paths are relative to {}",
+            root.display()
+        );
+    }
+    Ok(())
+}
+
+/// Root of the drive a path is on: `E:\` or `\\server\share\` on Windows,
+/// the mount point elsewhere
+#[cfg(windows)]
+pub fn drive_root(path: &Path) -> Option<PathBuf> {
+    let path = std::path::absolute(path).ok()?;
+    path.ancestors().last().map(Path::to_path_buf)
+}
+
+/// Root of the drive a path is on: `E:\` or `\\server\share\` on Windows,
+/// the mount point elsewhere
+#[cfg(unix)]
+pub fn drive_root(path: &Path) -> Option<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = std::fs::canonicalize(path).ok()?;
+    let device = std::fs::metadata(&path).ok()?.dev();
+    path.ancestors()
+        .take_while(|dir| std::fs::metadata(dir).is_ok_and(|m| m.dev() == device))
+        .last()
+        .map(Path::to_path_buf)
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn drive_root(_path: &Path) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_by_marker_or_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("music-minder");
+        assert_eq!(Portable::detect(&exe, false), None);
+
+        let flagged = Portable::detect(&exe, true).unwrap();
+        assert_eq!(flagged.data_dir, dir.path().join(DATA_DIR));
+        assert_eq!(
+            flagged.cache_dir("covers"),
+            dir.path().join("data").join("cache").join("covers")
+        );
+
+        std::fs::write(dir.path().join(MARKER), "").unwrap();
+        assert_eq!(Portable::detect(&exe, false), Some(flagged));
+    }
+
+    #[test]
+    fn test_drive_root_contains_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = drive_root(dir.path()).unwrap();
+        let real = std::fs::canonicalize(dir.path()).unwrap();
+        assert!(real.starts_with(&root), "{:?} not under {:?}", real, root);
+        // The root is on the same drive as what's under it
+        assert_eq!(drive_root(&root), Some(root));
+    }
+}
@@ -0,0 +1,287 @@
+//! Named library profiles.
+//!
+//! Each profile is a separate library with its own database, config file
+//! (and therefore its own library/watch paths), undo log, and organize
+//! journal. The `default` profile keeps the original locations so existing
+//! installs carry on unchanged; other profiles live under
+//! `<config dir>/profiles/<name>/`.
+//!
+//! The active profile is chosen at startup from `--profile`, or else the one
+//! last selected in the UI (remembered in `<config dir>/profiles.toml`).
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::config;
+
+/// Name of the profile that uses the original, pre-profile file locations.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Active profile name; empty means [`DEFAULT_PROFILE`].
+static ACTIVE: RwLock<String> = RwLock::new(String::new());
+
+/// Profile errors
+#[derive(Debug, thiserror::Error)]
+pub enum ProfileError {
+    #[error("Invalid profile name {0:?}: use letters, digits, '-' or '_' (at most 64 characters)")]
+    InvalidName(String),
+
+    #[error("Could not determine config directory")]
+    NoConfigDir,
+
+    #[error("Profile I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to serialize profile state: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Contents of `profiles.toml`
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ProfilesFile {
+    /// Profile selected the last time the UI switched
+    active: Option<String>,
+}
+
+// ============================================================================
+// Active Profile
+// ============================================================================
+
+/// Name of the active profile.
+pub fn active() -> String {
+    let name = ACTIVE.read().unwrap_or_else(|e| e.into_inner());
+    if name.is_empty() {
+        DEFAULT_PROFILE.to_string()
+    } else {
+        name.clone()
+    }
+}
+
+/// Whether the default profile is active.
+pub fn is_default() -> bool {
+    active() == DEFAULT_PROFILE
+}
+
+/// Make `name` the active profile for the rest of this process.
+///
+/// Does not touch the remembered profile; see [`remember`].
+pub fn set_active(name: &str) -> Result<(), ProfileError> {
+    validate_name(name)?;
+    let mut active = ACTIVE.write().unwrap_or_else(|e| e.into_inner());
+    *active = if name == DEFAULT_PROFILE {
+        String::new()
+    } else {
+        name.to_string()
+    };
+    Ok(())
+}
+
+/// Pick the startup profile and make it active.
+///
+/// An explicit `requested` profile is created if it doesn't exist yet. A
+/// remembered profile that has since been deleted falls back to the default.
+pub fn init(requested: Option<&str>) -> Result<String, ProfileError> {
+    let name = match requested {
+        Some(name) => {
+            validate_name(name)?;
+            if !exists(name) {
+                create(name)?;
+                tracing::info!("Created profile {:?}", name);
+            }
+            name.to_string()
+        }
+        None => match remembered() {
+            Some(name) if validate_name(&name).is_ok() && exists(&name) => name,
+            Some(name) => {
+                tracing::warn!("Remembered profile {:?} not found, using default", name);
+                DEFAULT_PROFILE.to_string()
+            }
+            None => DEFAULT_PROFILE.to_string(),
+        },
+    };
+    set_active(&name)?;
+    Ok(name)
+}
+
+// ============================================================================
+// Paths
+// ============================================================================
+
+/// Directory holding the active profile's files.
+///
+/// Empty for the default profile, so joined file names stay relative to the
+/// working directory exactly as before profiles existed; a portable install
+/// keeps them in its data directory instead.
+pub fn data_dir() -> PathBuf {
+    if is_default() {
+        crate::portable::get()
+            .map(|p| p.data_dir.clone())
+            .unwrap_or_default()
+    } else {
+        profile_dir_in(&config::config_dir().unwrap_or_default(), &active())
+    }
+}
+
+/// Per-profile file of the GUI's view state (see `ui::state::PaneStates`)
+pub const VIEW_STATE_FILE: &str = "music_minder_view_state.json";
+
+/// Path of a per-profile data file such as the undo log.
+pub fn data_path(file: &str) -> PathBuf {
+    data_dir().join(file)
+}
+
+/// Path of the active profile's database.
+pub fn db_path() -> PathBuf {
+    data_path(crate::db::DEFAULT_DB_NAME)
+}
+
+/// SQLite URL of the active profile's database.
+pub fn db_url() -> String {
+    format!("sqlite:{}", db_path().display())
+}
+
+/// Path of the active profile's config file.
+pub fn config_path() -> Option<PathBuf> {
+    let root = config::config_dir()?;
+    Some(if is_default() {
+        root.join("config.toml")
+    } else {
+        profile_dir_in(&root, &active()).join("config.toml")
+    })
+}
+
+fn profile_dir_in(root: &Path, name: &str) -> PathBuf {
+    root.join("profiles").join(name)
+}
+
+// ============================================================================
+// Profile Management
+// ============================================================================
+
+/// Check that a profile name is safe to use as a directory name.
+pub fn validate_name(name: &str) -> Result<(), ProfileError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ProfileError::InvalidName(name.to_string()))
+    }
+}
+
+/// All profiles, default first, then the rest alphabetically.
+pub fn list() -> Vec<String> {
+    match config::config_dir() {
+        Some(root) => list_in(&root),
+        None => vec![DEFAULT_PROFILE.to_string()],
+    }
+}
+
+fn list_in(root: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(root.join("profiles"))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| name != DEFAULT_PROFILE && validate_name(name).is_ok())
+        .collect();
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    names
+}
+
+/// Whether a profile exists.
+pub fn exists(name: &str) -> bool {
+    list().iter().any(|n| n == name)
+}
+
+/// Create a new, empty profile.
+pub fn create(name: &str) -> Result<(), ProfileError> {
+    validate_name(name)?;
+    let root = config::config_dir().ok_or(ProfileError::NoConfigDir)?;
+    create_in(&root, name)
+}
+
+fn create_in(root: &Path, name: &str) -> Result<(), ProfileError> {
+    if name != DEFAULT_PROFILE {
+        std::fs::create_dir_all(profile_dir_in(root, name))?;
+    }
+    Ok(())
+}
+
+/// The profile selected last time, if any.
+pub fn remembered() -> Option<String> {
+    remembered_in(&config::config_dir()?)
+}
+
+fn remembered_in(root: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(root.join("profiles.toml")).ok()?;
+    toml::from_str::<ProfilesFile>(&contents).ok()?.active
+}
+
+/// Remember `name` as the profile to open next time.
+pub fn remember(name: &str) -> Result<(), ProfileError> {
+    validate_name(name)?;
+    let root = config::config_dir().ok_or(ProfileError::NoConfigDir)?;
+    remember_in(&root, name)
+}
+
+fn remember_in(root: &Path, name: &str) -> Result<(), ProfileError> {
+    std::fs::create_dir_all(root)?;
+    let file = ProfilesFile {
+        active: Some(name.to_string()),
+    };
+    std::fs::write(root.join("profiles.toml"), toml::to_string_pretty(&file)?)?;
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("archive").is_ok());
+        assert!(validate_name("mp3_portable-2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name("with space").is_err());
+        assert!(validate_name(&"x".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_list_and_create() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(list_in(dir.path()), vec!["default"]);
+
+        create_in(dir.path(), "portable").unwrap();
+        create_in(dir.path(), "archive").unwrap();
+        create_in(dir.path(), DEFAULT_PROFILE).unwrap();
+        std::fs::write(dir.path().join("profiles/stray.txt"), "").unwrap();
+
+        assert_eq!(list_in(dir.path()), vec!["default", "archive", "portable"]);
+        assert!(dir.path().join("profiles/archive").is_dir());
+    }
+
+    #[test]
+    fn test_remember_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(remembered_in(dir.path()), None);
+
+        remember_in(dir.path(), "archive").unwrap();
+        assert_eq!(remembered_in(dir.path()), Some("archive".to_string()));
+
+        remember_in(dir.path(), DEFAULT_PROFILE).unwrap();
+        assert_eq!(remembered_in(dir.path()), Some("default".to_string()));
+    }
+}
@@ -0,0 +1,422 @@
+//! Keeping re-enrichment away from hand-edited fields.
+//!
+//! Before an identification is written, [`guard_manual_edits`] looks up the
+//! fields the user last set by hand. Under the default
+//! [`ManualEditPolicy::Protect`] those fields are left out of the write, and
+//! where the new match disagrees with the user's value the suggestion is
+//! queued as a [`TagConflict`] for review. The user then keeps their value or
+//! takes the suggestion.
+//!
+//! The policy is set per field in the config file:
+//!
+//! ```toml
+//! [tagging.manual_edits]
+//! genre = "overwrite"   # re-enrichment may replace hand-set genres
+//! ```
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePool;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::{FieldSource, record_written, track_id};
+use crate::enrichment::{IdentifiedTrack, TrackIdentification};
+use crate::metadata::{self, FullMetadata};
+
+/// What re-enrichment may do with a field the user set by hand
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManualEditPolicy {
+    /// Never write it; queue disagreements for review
+    #[default]
+    Protect,
+    /// Write the new match like any other field
+    Overwrite,
+}
+
+/// Per-field [`ManualEditPolicy`], by tag field name. Fields not listed are
+/// protected.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ManualEditRules(pub BTreeMap<String, ManualEditPolicy>);
+
+impl ManualEditRules {
+    pub fn policy(&self, field: &str) -> ManualEditPolicy {
+        self.0.get(field).copied().unwrap_or_default()
+    }
+}
+
+/// A suggestion waiting for the user to pick between it and their own value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagConflict {
+    pub id: i64,
+    pub track_id: i64,
+    pub path: PathBuf,
+    pub field: String,
+    /// The hand-set value in the file, if it could be read
+    pub current: Option<String>,
+    pub proposed: String,
+    pub source: FieldSource,
+    pub confidence: Option<f32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Errors resolving a conflict
+#[derive(Debug, thiserror::Error)]
+pub enum ConflictError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Conflict {0} not found")]
+    NotFound(i64),
+
+    #[error("Failed to write tags: {0}")]
+    Write(String),
+}
+
+/// Strip protected hand-edited fields from `identification` before it is
+/// written to `path`, queueing the ones it disagrees with.
+///
+/// Returns the track to write and how many conflicts were queued. Best
+/// effort like the rest of provenance: if the history can't be read, the
+/// identification is returned unchanged.
+pub async fn guard_manual_edits(
+    pool: &SqlitePool,
+    path: &Path,
+    identification: &TrackIdentification,
+    rules: &ManualEditRules,
+) -> (IdentifiedTrack, usize) {
+    let mut track = identification.track.clone();
+    let manual = match manual_fields(pool, &path.to_string_lossy()).await {
+        Ok(Some(manual)) => manual,
+        Ok(None) => return (track, 0),
+        Err(e) => {
+            tracing::warn!("Failed to check manual edits of {}: {}", path.display(), e);
+            return (track, 0);
+        }
+    };
+    let (track_id, fields) = manual;
+    let protected: Vec<String> = fields
+        .into_iter()
+        .filter(|field| rules.policy(field) == ManualEditPolicy::Protect)
+        .filter(|field| track.field_value(field).is_some())
+        .collect();
+    if protected.is_empty() {
+        return (track, 0);
+    }
+
+    let current = {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || metadata::read_full(&path))
+            .await
+            .ok()
+            .and_then(Result::ok)
+    };
+
+    let mut queued = 0;
+    for field in protected {
+        let Some(proposed) = track.field_value(&field) else {
+            continue;
+        };
+        track.set_field(&field, None);
+        let Some(ref current) = current else {
+            continue;
+        };
+        let current = current_value(current, &field);
+        if current.as_deref() == Some(proposed.as_str()) {
+            continue;
+        }
+        let source = identification.field_source(&field).into();
+        match queue(
+            pool,
+            track_id,
+            &field,
+            current.as_deref(),
+            &proposed,
+            source,
+            Some(identification.score),
+        )
+        .await
+        {
+            Ok(true) => queued += 1,
+            Ok(false) => {}
+            Err(e) => tracing::warn!("Failed to queue tag conflict for {}: {}", field, e),
+        }
+    }
+    (track, queued)
+}
+
+/// The track id and hand-set fields of the library track at `path`
+async fn manual_fields(pool: &SqlitePool, path: &str) -> sqlx::Result<Option<(i64, Vec<String>)>> {
+    let Some(id) = track_id(pool, path).await? else {
+        return Ok(None);
+    };
+    let fields =
+        sqlx::query_scalar("SELECT field FROM field_provenance WHERE track_id = ? AND source = ?")
+            .bind(id)
+            .bind(FieldSource::Manual.as_str())
+            .fetch_all(pool)
+            .await?;
+    Ok(Some((id, fields)))
+}
+
+/// A field's value in the file, in the same form as
+/// [`IdentifiedTrack::field_value`]
+fn current_value(meta: &FullMetadata, field: &str) -> Option<String> {
+    let number = |n: Option<u32>| n.map(|n| n.to_string());
+    match field {
+        "title" => meta.title.clone(),
+        "artist" => meta.artist.clone(),
+        "album_artist" => meta.album_artist.clone(),
+        "album" => meta.album.clone(),
+        "track_number" => number(meta.track_number),
+        "total_tracks" => number(meta.total_tracks),
+        "disc_number" => number(meta.disc_number),
+        "total_discs" => number(meta.total_discs),
+        "year" => number(meta.year),
+        "genre" => meta.genre.clone(),
+        "language" => meta.language.clone(),
+        "explicit" => meta
+            .explicit
+            .map(|e| if e { "explicit" } else { "clean" }.to_string()),
+        "musicbrainz_recording_id" => meta.musicbrainz_recording_id.clone(),
+        "musicbrainz_artist_id" => meta.musicbrainz_artist_id.clone(),
+        "musicbrainz_release_id" => meta.musicbrainz_release_id.clone(),
+        "musicbrainz_release_group_id" => meta.musicbrainz_release_group_id.clone(),
+        "acoustid_id" => meta.acoustid.id.clone(),
+        "acoustid_fingerprint" => meta.acoustid.fingerprint.clone(),
+        _ => None,
+    }
+}
+
+/// Queue a suggestion, replacing any earlier one for the field.
+///
+/// Returns false when the user already kept their value against this very
+/// suggestion.
+async fn queue(
+    pool: &SqlitePool,
+    track_id: i64,
+    field: &str,
+    current: Option<&str>,
+    proposed: &str,
+    source: FieldSource,
+    confidence: Option<f32>,
+) -> sqlx::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO tag_conflicts
+            (track_id, field, current_value, proposed_value, source, confidence, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(track_id, field) DO UPDATE SET
+            current_value = excluded.current_value,
+            proposed_value = excluded.proposed_value,
+            source = excluded.source,
+            confidence = excluded.confidence,
+            created_at = excluded.created_at,
+            kept = 0
+        WHERE NOT (tag_conflicts.kept = 1
+                   AND tag_conflicts.proposed_value = excluded.proposed_value)
+        "#,
+    )
+    .bind(track_id)
+    .bind(field)
+    .bind(current)
+    .bind(proposed)
+    .bind(source.as_str())
+    .bind(confidence.map(f64::from))
+    .bind(Utc::now().timestamp())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[derive(sqlx::FromRow)]
+struct ConflictRow {
+    id: i64,
+    track_id: i64,
+    path: String,
+    field: String,
+    current_value: Option<String>,
+    proposed_value: String,
+    source: String,
+    confidence: Option<f64>,
+    created_at: i64,
+}
+
+impl ConflictRow {
+    fn into_conflict(self) -> Option<TagConflict> {
+        Some(TagConflict {
+            id: self.id,
+            track_id: self.track_id,
+            path: PathBuf::from(self.path),
+            field: self.field,
+            current: self.current_value,
+            proposed: self.proposed_value,
+            source: self.source.parse().ok()?,
+            confidence: self.confidence.map(|c| c as f32),
+            created_at: Utc.timestamp_opt(self.created_at, 0).single()?,
+        })
+    }
+}
+
+const SELECT_CONFLICTS: &str = "SELECT c.id, c.track_id, t.path, c.field, c.current_value,
+        c.proposed_value, c.source, c.confidence, c.created_at
+    FROM tag_conflicts c JOIN tracks t ON t.id = c.track_id";
+
+/// Conflicts waiting for review, oldest first
+pub async fn pending(pool: &SqlitePool) -> sqlx::Result<Vec<TagConflict>> {
+    let rows: Vec<ConflictRow> = sqlx::query_as(&format!(
+        "{} WHERE c.kept = 0 ORDER BY c.created_at, c.id",
+        SELECT_CONFLICTS
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(ConflictRow::into_conflict)
+        .collect())
+}
+
+/// Keep the user's value; the same suggestion won't be raised again
+pub async fn keep(pool: &SqlitePool, id: i64) -> Result<(), ConflictError> {
+    let result = sqlx::query("UPDATE tag_conflicts SET kept = 1 WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(ConflictError::NotFound(id));
+    }
+    Ok(())
+}
+
+/// Write the suggestion to the file, crediting it to its source
+pub async fn accept(pool: &SqlitePool, id: i64) -> Result<(), ConflictError> {
+    let row: Option<ConflictRow> = sqlx::query_as(&format!("{} WHERE c.id = ?", SELECT_CONFLICTS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    let conflict = row
+        .and_then(ConflictRow::into_conflict)
+        .ok_or(ConflictError::NotFound(id))?;
+
+    let mut track = IdentifiedTrack::default();
+    if !track.set_field(&conflict.field, Some(&conflict.proposed)) {
+        return Err(ConflictError::Write(format!(
+            "can't write {:?} to {}",
+            conflict.proposed, conflict.field
+        )));
+    }
+    let options = metadata::WriteOptions2 {
+        only_fill_empty: false,
+        write_musicbrainz_ids: true,
+        ..Default::default()
+    };
+    let path = conflict.path.clone();
+    let result = tokio::task::spawn_blocking(move || metadata::write(&path, &track, &options))
+        .await
+        .map_err(|e| ConflictError::Write(e.to_string()))?
+        .map_err(|e| ConflictError::Write(e.to_string()))?;
+
+    crate::activity::record_tags_written(pool, &conflict.path, result.fields_updated).await;
+    record_written(
+        pool,
+        &conflict.path,
+        &result.fields_written,
+        conflict.source,
+        conflict.confidence,
+    )
+    .await;
+    sqlx::query("DELETE FROM tag_conflicts WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::enrichment::EnrichmentSource;
+    use crate::metadata::TrackMetadata;
+
+    #[tokio::test]
+    async fn test_manual_edits_are_protected() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", temp.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        let path = temp.path().join("missing.mp3");
+        let path_str = path.to_string_lossy().to_string();
+        let meta = TrackMetadata {
+            title: "Airbag".to_string(),
+            artist: "Radiohead".to_string(),
+            album: "OK Computer".to_string(),
+            duration: 284,
+            track_number: Some(1),
+        };
+        let id = db::insert_track(&pool, &meta, &path_str, None, None)
+            .await
+            .unwrap();
+        record_written(&pool, &path, &["album", "genre"], FieldSource::Manual, None).await;
+
+        let identification = TrackIdentification {
+            score: 0.9,
+            track: IdentifiedTrack {
+                title: Some("Airbag".to_string()),
+                album: Some("OK Computer OKNOTOK".to_string()),
+                genres: vec!["Rock".to_string()],
+                ..Default::default()
+            },
+            source: EnrichmentSource::AcoustId,
+            musicbrainz_fields: Vec::new(),
+            scores: Default::default(),
+        };
+        let mut rules = ManualEditRules::default();
+        rules
+            .0
+            .insert("genre".to_string(), ManualEditPolicy::Overwrite);
+
+        // The file can't be read, so the album is withheld but not queued
+        let (track, queued) = guard_manual_edits(&pool, &path, &identification, &rules).await;
+        assert_eq!(track.album, None);
+        assert_eq!(track.title.as_deref(), Some("Airbag"));
+        assert_eq!(track.genres, ["Rock"]);
+        assert_eq!(queued, 0);
+
+        // Queueing, keeping and re-suggesting
+        let q = |proposed: &'static str| {
+            let pool = pool.clone();
+            async move {
+                queue(
+                    &pool,
+                    id,
+                    "album",
+                    Some("OK Computer"),
+                    proposed,
+                    FieldSource::MusicBrainz,
+                    Some(0.9),
+                )
+                .await
+                .unwrap()
+            }
+        };
+        assert!(q("OKNOTOK").await);
+        let conflicts = pending(&pool).await.unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].proposed, "OKNOTOK");
+        assert_eq!(conflicts[0].path, path);
+
+        keep(&pool, conflicts[0].id).await.unwrap();
+        assert!(pending(&pool).await.unwrap().is_empty());
+        assert!(!q("OKNOTOK").await);
+        assert!(pending(&pool).await.unwrap().is_empty());
+        assert!(q("OK Computer (Remastered)").await);
+        assert_eq!(pending(&pool).await.unwrap().len(), 1);
+
+        assert!(matches!(
+            keep(&pool, 999).await,
+            Err(ConflictError::NotFound(999))
+        ));
+    }
+}
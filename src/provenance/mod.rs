@@ -0,0 +1,364 @@
+//! Per-field tag provenance.
+//!
+//! Every time the app writes tags it records, for each field written, where
+//! the value came from (AcoustID, MusicBrainz, typed in by hand, guessed from
+//! the file name, or an applied plan), how confident the match was, and
+//! when. Only the latest write of a field is kept. A field with no record
+//! still has the value it came with.
+//!
+//! Fields the user set by hand are protected from later re-enrichment (see
+//! [`conflicts`]).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::provenance::{self, FieldSource};
+//!
+//! let result = metadata::write(&path, &identification.track, &options)?;
+//! provenance::record_identification(&pool, &path, &result.fields_written, &identification).await;
+//!
+//! // Tracks whose album name a service picked
+//! let ids = provenance::machine_written(&pool, "album").await?;
+//! ```
+
+use chrono::{DateTime, Local, TimeZone, Utc};
+use sqlx::sqlite::SqlitePool;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::enrichment::{EnrichmentSource, TrackIdentification};
+
+pub mod conflicts;
+
+pub use conflicts::{
+    ConflictError, ManualEditPolicy, ManualEditRules, TagConflict, guard_manual_edits,
+};
+
+/// Who wrote a field's value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FieldSource {
+    /// Matched by audio fingerprint
+    AcoustId,
+    /// Looked up on MusicBrainz
+    MusicBrainz,
+    /// Typed in by the user
+    Manual,
+    /// Guessed from the file name, folder order or the rest of the album
+    Inferred,
+    /// Applied from a saved plan
+    Plan,
+}
+
+impl FieldSource {
+    /// All sources, in display order.
+    pub const ALL: [FieldSource; 5] = [
+        FieldSource::AcoustId,
+        FieldSource::MusicBrainz,
+        FieldSource::Manual,
+        FieldSource::Inferred,
+        FieldSource::Plan,
+    ];
+
+    /// Key stored in the database
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FieldSource::AcoustId => "acoustid",
+            FieldSource::MusicBrainz => "musicbrainz",
+            FieldSource::Manual => "manual",
+            FieldSource::Inferred => "inferred",
+            FieldSource::Plan => "plan",
+        }
+    }
+
+    /// Human-readable name
+    pub fn label(&self) -> &'static str {
+        match self {
+            FieldSource::AcoustId => "AcoustID",
+            FieldSource::MusicBrainz => "MusicBrainz",
+            FieldSource::Manual => "you",
+            FieldSource::Inferred => "a guess",
+            FieldSource::Plan => "an applied plan",
+        }
+    }
+
+    /// Whether the value was picked by the app rather than a person
+    pub fn is_machine(&self) -> bool {
+        matches!(
+            self,
+            FieldSource::AcoustId | FieldSource::MusicBrainz | FieldSource::Inferred
+        )
+    }
+
+    /// Sources counted as machine-written, by database key
+    fn machine_keys() -> Vec<&'static str> {
+        Self::ALL
+            .iter()
+            .filter(|s| s.is_machine())
+            .map(FieldSource::as_str)
+            .collect()
+    }
+}
+
+impl std::str::FromStr for FieldSource {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|source| source.as_str() == s)
+            .ok_or(())
+    }
+}
+
+impl From<EnrichmentSource> for FieldSource {
+    fn from(source: EnrichmentSource) -> Self {
+        match source {
+            EnrichmentSource::AcoustId => FieldSource::AcoustId,
+            EnrichmentSource::MusicBrainz => FieldSource::MusicBrainz,
+            EnrichmentSource::Manual => FieldSource::Manual,
+        }
+    }
+}
+
+/// Where one field's current value came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldProvenance {
+    pub field: String,
+    pub source: FieldSource,
+    /// Match score 0.0-1.0, for identified values
+    pub confidence: Option<f32>,
+    pub written_at: DateTime<Utc>,
+}
+
+impl FieldProvenance {
+    /// One line for a tooltip: "Set by MusicBrainz (92% match), 01 Oct 2026 14:03"
+    pub fn describe(&self) -> String {
+        let when = self
+            .written_at
+            .with_timezone(&Local)
+            .format("%d %b %Y %H:%M");
+        match self.confidence {
+            Some(score) => format!(
+                "Set by {} ({:.0}% match), {}",
+                self.source.label(),
+                score * 100.0,
+                when
+            ),
+            None => format!("Set by {}, {}", self.source.label(), when),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ProvenanceRow {
+    field: String,
+    source: String,
+    confidence: Option<f64>,
+    written_at: i64,
+}
+
+/// Record that `fields` of track `track_id` were just written by `source`.
+///
+/// Returns the number of fields recorded.
+pub async fn record(
+    pool: &SqlitePool,
+    track_id: i64,
+    fields: &[&str],
+    source: FieldSource,
+    confidence: Option<f32>,
+) -> sqlx::Result<usize> {
+    let now = Utc::now().timestamp();
+    let mut tx = pool.begin().await?;
+    for field in fields {
+        sqlx::query(
+            r#"
+            INSERT INTO field_provenance (track_id, field, source, confidence, written_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(track_id, field) DO UPDATE SET
+                source = excluded.source,
+                confidence = excluded.confidence,
+                written_at = excluded.written_at
+            "#,
+        )
+        .bind(track_id)
+        .bind(field)
+        .bind(source.as_str())
+        .bind(confidence.map(f64::from))
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(fields.len())
+}
+
+/// Record a successful tag write to the file at `path`, all from one source.
+///
+/// Best effort, like the activity log: a failure is traced but never fails
+/// the write that already happened on disk. Files not in the library are
+/// skipped.
+pub async fn record_written(
+    pool: &SqlitePool,
+    path: &Path,
+    fields: &[&str],
+    source: FieldSource,
+    confidence: Option<f32>,
+) {
+    if fields.is_empty() {
+        return;
+    }
+    let path = path.to_string_lossy();
+    let result = match track_id(pool, &path).await {
+        Ok(Some(id)) => record(pool, id, fields, source, confidence).await,
+        Ok(None) => return,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to record provenance for {}: {}", path, e);
+    }
+}
+
+/// Record a tag write from `identification`, crediting each field to the
+/// service it came from.
+pub async fn record_identification(
+    pool: &SqlitePool,
+    path: &Path,
+    fields: &[&str],
+    identification: &TrackIdentification,
+) {
+    let confidence = Some(identification.score);
+    for source in [EnrichmentSource::AcoustId, EnrichmentSource::MusicBrainz] {
+        let from_source: Vec<&str> = fields
+            .iter()
+            .copied()
+            .filter(|field| identification.field_source(field) == source)
+            .collect();
+        record_written(pool, path, &from_source, source.into(), confidence).await;
+    }
+}
+
+async fn track_id(pool: &SqlitePool, path: &str) -> sqlx::Result<Option<i64>> {
+    sqlx::query_scalar("SELECT id FROM tracks WHERE path_key = ?")
+        .bind(crate::db::paths::key(path))
+        .fetch_optional(pool)
+        .await
+}
+
+/// Provenance of every recorded field of a track, by field name.
+pub async fn for_track(pool: &SqlitePool, track_id: i64) -> sqlx::Result<Vec<FieldProvenance>> {
+    let rows: Vec<ProvenanceRow> = sqlx::query_as(
+        "SELECT field, source, confidence, written_at FROM field_provenance
+         WHERE track_id = ? ORDER BY field",
+    )
+    .bind(track_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(FieldProvenance {
+                source: row.source.parse().ok()?,
+                field: row.field,
+                confidence: row.confidence.map(|c| c as f32),
+                written_at: Utc.timestamp_opt(row.written_at, 0).single()?,
+            })
+        })
+        .collect())
+}
+
+/// Tracks whose `field` was last written by a service or a guess rather than
+/// by hand.
+pub async fn machine_written(pool: &SqlitePool, field: &str) -> sqlx::Result<HashSet<i64>> {
+    let rows: Vec<(i64, String)> =
+        sqlx::query_as("SELECT track_id, source FROM field_provenance WHERE field = ?")
+            .bind(field)
+            .fetch_all(pool)
+            .await?;
+    let machine = FieldSource::machine_keys();
+    Ok(rows
+        .into_iter()
+        .filter(|(_, source)| machine.contains(&source.as_str()))
+        .map(|(id, _)| id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::enrichment::IdentifiedTrack;
+    use crate::metadata::TrackMetadata;
+
+    async fn pool_with_track() -> (tempfile::TempDir, SqlitePool, i64) {
+        let temp = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", temp.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        let meta = TrackMetadata {
+            title: "Airbag".to_string(),
+            artist: "Radiohead".to_string(),
+            album: "OK Computer".to_string(),
+            duration: 284,
+            track_number: Some(1),
+        };
+        let id = db::insert_track(&pool, &meta, "/m/01 Airbag.mp3", None, None)
+            .await
+            .unwrap();
+        (temp, pool, id)
+    }
+
+    #[tokio::test]
+    async fn test_identification_provenance() {
+        let (_temp, pool, id) = pool_with_track().await;
+        let mut identification = TrackIdentification {
+            score: 0.92,
+            track: IdentifiedTrack {
+                title: Some("Airbag".to_string()),
+                ..Default::default()
+            },
+            source: EnrichmentSource::AcoustId,
+            musicbrainz_fields: Vec::new(),
+            scores: Default::default(),
+        };
+        identification.merge_musicbrainz(&IdentifiedTrack {
+            title: Some("Ignored".to_string()),
+            album: Some("OK Computer".to_string()),
+            ..Default::default()
+        });
+
+        let path = Path::new("/m/01 Airbag.mp3");
+        record_identification(&pool, path, &["title", "album"], &identification).await;
+        let fields = for_track(&pool, id).await.unwrap();
+        let sources: Vec<_> = fields
+            .iter()
+            .map(|f| (f.field.as_str(), f.source))
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                ("album", FieldSource::MusicBrainz),
+                ("title", FieldSource::AcoustId)
+            ]
+        );
+        assert_eq!(fields[0].confidence, Some(0.92));
+        assert!(
+            fields[0]
+                .describe()
+                .starts_with("Set by MusicBrainz (92% match)")
+        );
+        assert_eq!(
+            machine_written(&pool, "album").await.unwrap(),
+            HashSet::from([id])
+        );
+
+        // Typing the album in by hand replaces the machine record
+        record_written(&pool, path, &["album"], FieldSource::Manual, None).await;
+        assert!(machine_written(&pool, "album").await.unwrap().is_empty());
+        assert_eq!(for_track(&pool, id).await.unwrap().len(), 2);
+
+        // Provenance goes with the track
+        db::delete_track_by_path(&pool, "/m/01 Airbag.mp3")
+            .await
+            .unwrap();
+        assert!(for_track(&pool, id).await.unwrap().is_empty());
+    }
+}
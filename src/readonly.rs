@@ -0,0 +1,196 @@
+//! Read-only (locked) library mode.
+//!
+//! When enabled, every operation that would change the user's files or the
+//! records describing them fails with [`ReadOnlyError`]: tag and cover-art
+//! writes, moving files (organize, undo, crash recovery), deleting tracks,
+//! storing enrichment results, and merging or splitting libraries. The
+//! check lives in those functions themselves rather than in the UI, so no
+//! code path can bypass it.
+//!
+//! Scanning still indexes new files, so a locked archive can be browsed
+//! and played without being modified.
+//!
+//! The mode is set once at startup from `library.read_only` in the config
+//! file or the `--read-only` flag.
+
+#[cfg(test)]
+use std::cell::Cell;
+#[cfg(not(test))]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(not(test))]
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+// Tests run in parallel; keep the switch per-thread so a test exercising the
+// lock can't make unrelated tests fail.
+#[cfg(test)]
+thread_local! {
+    static READ_ONLY: Cell<bool> = const { Cell::new(false) };
+}
+
+/// An operation was refused because the library is read-only.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{0} is disabled: the library is in read-only mode")]
+pub struct ReadOnlyError(pub &'static str);
+
+impl From<ReadOnlyError> for sqlx::Error {
+    fn from(e: ReadOnlyError) -> Self {
+        sqlx::Error::Configuration(Box::new(e))
+    }
+}
+
+/// Turn read-only mode on or off.
+pub fn set(enabled: bool) {
+    #[cfg(not(test))]
+    READ_ONLY.store(enabled, Ordering::SeqCst);
+    #[cfg(test)]
+    READ_ONLY.with(|flag: &Cell<bool>| flag.set(enabled));
+}
+
+/// Whether read-only mode is on.
+pub fn is_enabled() -> bool {
+    #[cfg(not(test))]
+    return READ_ONLY.load(Ordering::SeqCst);
+    #[cfg(test)]
+    return READ_ONLY.with(Cell::get);
+}
+
+/// Fail with [`ReadOnlyError`] if read-only mode is on.
+///
+/// `operation` names what was refused, e.g. "Writing tags".
+pub fn ensure_writable(operation: &'static str) -> Result<(), ReadOnlyError> {
+    if is_enabled() {
+        Err(ReadOnlyError(operation))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db, metadata, organizer};
+    use std::fs;
+
+    /// Turns read-only mode off again when dropped, even if the test fails.
+    struct Locked;
+
+    impl Locked {
+        fn new() -> Self {
+            set(true);
+            Locked
+        }
+    }
+
+    impl Drop for Locked {
+        fn drop(&mut self) {
+            set(false);
+        }
+    }
+
+    #[test]
+    fn test_ensure_writable() {
+        assert!(ensure_writable("Anything").is_ok());
+        let _lock = Locked::new();
+        let err = ensure_writable("Writing tags").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Writing tags is disabled: the library is in read-only mode"
+        );
+    }
+
+    #[test]
+    fn test_file_operations_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("a.mp3");
+        let dest = dir.path().join("b/a.mp3");
+        fs::write(&src, b"audio").unwrap();
+
+        let _lock = Locked::new();
+        let refused = |e: anyhow::Error| e.to_string().contains("read-only mode");
+        assert!(organizer::move_file(&src, &dest).is_err_and(refused));
+        assert!(
+            metadata::write(&src, &Default::default(), &Default::default()).is_err_and(refused)
+        );
+        assert!(src.exists());
+        assert!(!dest.exists());
+    }
+
+    #[tokio::test]
+    async fn test_db_mutations_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", dir.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        let meta = metadata::TrackMetadata {
+            title: "Song".to_string(),
+            artist: "Artist".to_string(),
+            album: "Album".to_string(),
+            duration: 180,
+            track_number: None,
+        };
+        let id = db::insert_track(&pool, &meta, "/a.mp3", None, None)
+            .await
+            .unwrap();
+
+        let _lock = Locked::new();
+        assert!(db::update_track_path(&pool, id, "/b.mp3").await.is_err());
+        assert!(db::delete_track_by_path(&pool, "/a.mp3").await.is_err());
+
+        let track = db::get_track_by_id(&pool, id).await.unwrap().unwrap();
+        assert_eq!(track.path, "/a.mp3");
+    }
+
+    #[tokio::test]
+    async fn test_library_transfers_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", dir.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        let other = dir.path().join("other.db");
+        db::init_db(&db::db_url(Some(&other)))
+            .await
+            .unwrap()
+            .close()
+            .await;
+        let split_to = dir.path().join("split.db");
+        let selection = db::TrackSelection {
+            format: Some("flac".to_string()),
+            ..Default::default()
+        };
+
+        let _lock = Locked::new();
+        assert!(matches!(
+            db::merge(&pool, &other, |_| {}).await,
+            Err(db::TransferError::ReadOnly(_))
+        ));
+        assert!(matches!(
+            db::split(&pool, &split_to, &selection, true, |_| {}).await,
+            Err(db::TransferError::ReadOnly(_))
+        ));
+        assert!(!split_to.exists());
+    }
+
+    #[tokio::test]
+    async fn test_path_merge_only_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", dir.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        for path in [r"D:\Music\x.mp3", r"d:\music\X.MP3"] {
+            sqlx::query("INSERT INTO tracks (title, path) VALUES ('t', ?)")
+                .bind(path)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let _lock = Locked::new();
+        let policy = db::paths::PathPolicy::default();
+        let merged = db::paths::normalize_track_paths(&pool, &policy, true).await;
+        assert_eq!(merged.unwrap(), 0);
+        let (tracks, keyed): (i64, i64) =
+            sqlx::query_as("SELECT COUNT(*), COUNT(path_key) FROM tracks")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((tracks, keyed), (2, 0));
+    }
+}
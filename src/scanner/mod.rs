@@ -3,9 +3,10 @@
 //! Provides async streaming of discovered audio file paths within a directory tree.
 //! Filters for common audio formats: MP3, FLAC, OGG, M4A, WAV.
 
+pub mod quarantine;
 mod watcher;
 
-pub use watcher::{FileWatcher, WatchError, WatchEvent};
+pub use watcher::{FileWatcher, WatchCommand, WatchError, WatchEvent};
 
 use futures::stream::Stream;
 use std::path::{Path, PathBuf};
@@ -0,0 +1,275 @@
+//! Quarantine for files that are still being written.
+//!
+//! A file the watcher sees appear is often mid-copy or mid-download, and
+//! reading it then fails like a corrupt file would. New and changed files
+//! are held until their size and modification time have stayed the same
+//! for the settle time (`library.watch_settle_secs`) and they open as audio,
+//! then released to the watcher's channel. A file that still won't open
+//! after [`GIVE_UP_AFTER`] is released anyway, so its error is reported
+//! once rather than never.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, unbounded};
+
+use super::WatchEvent;
+
+/// How often held files are checked
+const TICK: Duration = Duration::from_millis(500);
+
+/// How long a settled file that won't open is held before releasing it
+pub const GIVE_UP_AFTER: Duration = Duration::from_secs(300);
+
+/// A file's size and modification time, the two things a copy changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileState {
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl FileState {
+    fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        meta.is_file().then(|| Self {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Held {
+    event: WatchEvent,
+    state: Option<FileState>,
+    stable_since: Instant,
+    first_seen: Instant,
+}
+
+/// Files waiting to settle
+#[derive(Debug)]
+pub struct Quarantine {
+    settle: Duration,
+    held: HashMap<PathBuf, Held>,
+}
+
+impl Quarantine {
+    /// `settle` of zero lets every event straight through
+    pub fn new(settle: Duration) -> Self {
+        Self {
+            settle,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Number of files being held
+    pub fn len(&self) -> usize {
+        self.held.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.held.is_empty()
+    }
+
+    /// Take an event from the watcher. Returns it if it can go straight
+    /// through; new and changed files are held instead.
+    pub fn admit(&mut self, event: WatchEvent, now: Instant) -> Option<WatchEvent> {
+        if self.settle.is_zero() {
+            return Some(event);
+        }
+        match event {
+            WatchEvent::Created(ref path) | WatchEvent::Modified(ref path) => {
+                let path = path.clone();
+                match self.held.get_mut(&path) {
+                    // Still changing: the first event (Created wins) waits on
+                    Some(held) => held.stable_since = now,
+                    None => {
+                        tracing::debug!(target: "scanner::watcher", path = %path.display(), "Holding file until it settles");
+                        self.held.insert(
+                            path,
+                            Held {
+                                event,
+                                state: None,
+                                stable_since: now,
+                                first_seen: now,
+                            },
+                        );
+                    }
+                }
+                None
+            }
+            WatchEvent::Removed(ref path) => {
+                // Removed before it settled: nothing was imported yet
+                if let Some(held) = self.held.remove(path)
+                    && matches!(held.event, WatchEvent::Created(_))
+                {
+                    return None;
+                }
+                Some(event)
+            }
+            other => Some(other),
+        }
+    }
+
+    /// Check the held files and release those that have settled and open
+    pub fn poll(
+        &mut self,
+        now: Instant,
+        state_of: impl Fn(&Path) -> Option<FileState>,
+        opens: impl Fn(&Path) -> bool,
+    ) -> Vec<WatchEvent> {
+        let settle = self.settle;
+        let mut released = Vec::new();
+        self.held.retain(|path, held| {
+            let Some(state) = state_of(path) else {
+                // Gone (a temporary file renamed away, or deleted)
+                return false;
+            };
+            if held.state != Some(state) {
+                held.state = Some(state);
+                held.stable_since = now;
+                return true;
+            }
+            if now.duration_since(held.stable_since) < settle {
+                return true;
+            }
+            if opens(path) {
+                tracing::debug!(target: "scanner::watcher", path = %path.display(), "File settled");
+            } else if now.duration_since(held.first_seen) >= GIVE_UP_AFTER {
+                tracing::warn!(target: "scanner::watcher", path = %path.display(), "File still doesn't open after settling; importing anyway");
+            } else {
+                // Same size but unreadable: a preallocated download, give it longer
+                held.stable_since = now;
+                return true;
+            }
+            released.push(held.event.clone());
+            false
+        });
+        released
+    }
+}
+
+/// Whether a file reads as audio: its format is recognized and the tags and
+/// stream headers parse
+pub fn opens_as_audio(path: &Path) -> bool {
+    lofty::probe::Probe::open(path)
+        .and_then(|probe| probe.read())
+        .is_ok()
+}
+
+/// Run a quarantine on its own thread, feeding released events to `sink`.
+/// The thread ends when the returned sender is dropped.
+pub fn spawn(settle: Duration, sink: impl Fn(WatchEvent) + Send + 'static) -> Sender<WatchEvent> {
+    let (tx, rx) = unbounded();
+    std::thread::Builder::new()
+        .name("watch-quarantine".to_string())
+        .spawn(move || run(settle, rx, sink))
+        .expect("Failed to spawn watcher quarantine thread");
+    tx
+}
+
+fn run(settle: Duration, rx: Receiver<WatchEvent>, sink: impl Fn(WatchEvent)) {
+    let mut quarantine = Quarantine::new(settle);
+    loop {
+        match rx.recv_timeout(TICK) {
+            Ok(event) => {
+                if let Some(event) = quarantine.admit(event, Instant::now()) {
+                    sink(event);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        if !quarantine.is_empty() {
+            for event in quarantine.poll(Instant::now(), FileState::of, opens_as_audio) {
+                sink(event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn state(len: u64) -> Option<FileState> {
+        Some(FileState {
+            len,
+            modified: None,
+        })
+    }
+
+    #[test]
+    fn test_files_are_held_until_they_settle_and_open() {
+        let settle = Duration::from_secs(5);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let path = PathBuf::from("/music/new.flac");
+        let mut quarantine = Quarantine::new(settle);
+
+        assert!(
+            quarantine
+                .admit(WatchEvent::Created(path.clone()), start)
+                .is_none()
+        );
+        // A later modify doesn't turn it into one
+        assert!(
+            quarantine
+                .admit(WatchEvent::Modified(path.clone()), at(1))
+                .is_none()
+        );
+
+        // Still growing
+        let len = Cell::new(1000);
+        let opens = Cell::new(false);
+        let poll =
+            |q: &mut Quarantine, secs| q.poll(at(secs), |_| state(len.get()), |_| opens.get());
+        assert!(poll(&mut quarantine, 2).is_empty());
+        len.set(2000);
+        assert!(poll(&mut quarantine, 4).is_empty());
+        assert!(poll(&mut quarantine, 8).is_empty());
+
+        // Settled but unreadable: held for another settle period
+        assert!(poll(&mut quarantine, 9).is_empty());
+        assert_eq!(quarantine.len(), 1);
+
+        opens.set(true);
+        let released = poll(&mut quarantine, 14);
+        assert!(matches!(&released[..], [WatchEvent::Created(p)] if *p == path));
+        assert!(quarantine.is_empty());
+    }
+
+    #[test]
+    fn test_removed_and_unreadable_files() {
+        let start = Instant::now();
+        let path = PathBuf::from("/music/partial.mp3");
+        let mut quarantine = Quarantine::new(Duration::from_secs(2));
+
+        // Created then removed before settling: neither event goes through
+        quarantine.admit(WatchEvent::Created(path.clone()), start);
+        assert!(
+            quarantine
+                .admit(WatchEvent::Removed(path.clone()), start)
+                .is_none()
+        );
+        assert!(quarantine.is_empty());
+
+        // Never opens: released after giving up, so the error is reported
+        quarantine.admit(WatchEvent::Modified(path.clone()), start);
+        quarantine.poll(start, |_| state(10), |_| false);
+        let late = start + GIVE_UP_AFTER;
+        let released = quarantine.poll(late, |_| state(10), |_| false);
+        assert!(matches!(&released[..], [WatchEvent::Modified(_)]));
+
+        // Vanished files are dropped
+        quarantine.admit(WatchEvent::Created(path.clone()), start);
+        assert!(quarantine.poll(start, |_| None, |_| true).is_empty());
+        assert!(quarantine.is_empty());
+
+        // No settle time: straight through
+        let mut off = Quarantine::new(Duration::ZERO);
+        assert!(off.admit(WatchEvent::Created(path), start).is_some());
+    }
+}
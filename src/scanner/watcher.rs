@@ -6,6 +6,8 @@
 //! # Design
 //!
 //! - **Debounced events**: Multiple rapid changes coalesce into single events
+//! - **Quarantine**: New and changed files are held until they stop changing
+//!   and open cleanly (see [`super::quarantine`])
 //! - **Audio files only**: Filters for supported extensions (mp3, flac, etc.)
 //! - **Non-blocking**: Runs on a dedicated thread, sends events via channel
 //! - **Graceful shutdown**: Stop watching via the returned handle
@@ -13,7 +15,7 @@
 //! # Usage
 //!
 //! ```rust,ignore
-//! let (watcher, rx) = FileWatcher::new(vec!["/music".into()])?;
+//! let (watcher, rx) = FileWatcher::new(vec!["/music".into()], Duration::from_secs(5))?;
 //!
 //! // In another task/thread:
 //! while let Ok(event) = rx.recv() {
@@ -41,7 +43,7 @@ use std::time::Duration;
 use tokio::sync::mpsc as tokio_mpsc;
 
 // Re-use the shared is_audio_file from parent module
-use super::is_audio_file;
+use super::{is_audio_file, quarantine};
 
 /// Events emitted by the file watcher.
 #[derive(Debug, Clone)]
@@ -58,6 +60,16 @@ pub enum WatchEvent {
     Error(String),
 }
 
+/// Changes to the folders of a running watcher, for code that owns it on
+/// another task (the app's watcher subscription).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchCommand {
+    /// Start watching a folder
+    Watch(PathBuf),
+    /// Stop watching a folder
+    Unwatch(PathBuf),
+}
+
 /// Handle to a running file watcher.
 ///
 /// Dropping this handle will stop the watcher.
@@ -67,38 +79,20 @@ pub struct FileWatcher {
 }
 
 impl FileWatcher {
-    /// Create a new file watcher for the given directories.
+    /// Create a new file watcher for the given directories. New and changed
+    /// files are reported once they've been unchanged for `settle` (zero
+    /// reports them right away).
     ///
     /// Returns the watcher handle and a receiver for watch events.
-    pub fn new(watch_paths: Vec<PathBuf>) -> Result<(Self, Receiver<WatchEvent>), WatchError> {
+    pub fn new(
+        watch_paths: Vec<PathBuf>,
+        settle: Duration,
+    ) -> Result<(Self, Receiver<WatchEvent>), WatchError> {
         let (tx, rx) = bounded(256);
-        let running = Arc::new(AtomicBool::new(true));
-        let running_clone = Arc::clone(&running);
-
-        // Create debouncer with 500ms timeout
-        let debouncer = new_debouncer(
-            Duration::from_millis(500),
-            None, // No tick rate limit
-            move |result: DebounceEventResult| {
-                if !running_clone.load(Ordering::Relaxed) {
-                    return;
-                }
-                Self::handle_debounced_events(result, &tx);
-            },
-        )
-        .map_err(|e| WatchError::Init(e.to_string()))?;
-
-        let mut watcher = Self {
-            _debouncer: debouncer,
-            running,
-        };
-
-        // Watch all paths
-        for path in watch_paths {
-            watcher.watch(&path)?;
-        }
-
-        Ok((watcher, rx))
+        let quarantine = quarantine::spawn(settle, move |event| {
+            let _ = tx.try_send(event);
+        });
+        Self::start(watch_paths, quarantine).map(|watcher| (watcher, rx))
     }
 
     /// Create a new file watcher for the given directories with an async channel.
@@ -109,8 +103,18 @@ impl FileWatcher {
     /// Returns the watcher handle and a tokio mpsc receiver for watch events.
     pub fn new_async(
         watch_paths: Vec<PathBuf>,
+        settle: Duration,
     ) -> Result<(Self, tokio_mpsc::Receiver<WatchEvent>), WatchError> {
         let (tx, rx) = tokio_mpsc::channel(256);
+        // try_send is non-blocking and safe from the quarantine thread
+        let quarantine = quarantine::spawn(settle, move |event| {
+            let _ = tx.try_send(event);
+        });
+        Self::start(watch_paths, quarantine).map(|watcher| (watcher, rx))
+    }
+
+    /// Start watching, sending events through the quarantine
+    fn start(watch_paths: Vec<PathBuf>, tx: Sender<WatchEvent>) -> Result<Self, WatchError> {
         let running = Arc::new(AtomicBool::new(true));
         let running_clone = Arc::clone(&running);
 
@@ -122,7 +126,7 @@ impl FileWatcher {
                 if !running_clone.load(Ordering::Relaxed) {
                     return;
                 }
-                Self::handle_debounced_events_async(result, &tx);
+                Self::handle_debounced_events(result, &tx);
             },
         )
         .map_err(|e| WatchError::Init(e.to_string()))?;
@@ -137,7 +141,7 @@ impl FileWatcher {
             watcher.watch(&path)?;
         }
 
-        Ok((watcher, rx))
+        Ok(watcher)
     }
 
     /// Add a directory to watch.
@@ -218,71 +222,6 @@ impl FileWatcher {
             }
         }
     }
-
-    /// Handle debounced events from notify (async channel variant).
-    ///
-    /// Uses `try_send()` which is safe to call from sync code (notify's callback thread).
-    fn handle_debounced_events_async(
-        result: DebounceEventResult,
-        tx: &tokio_mpsc::Sender<WatchEvent>,
-    ) {
-        match result {
-            Ok(events) => {
-                for event in events {
-                    for path in &event.paths {
-                        // Skip non-audio files
-                        if path.is_file() && !is_audio_file(path) {
-                            continue;
-                        }
-
-                        let watch_event = match event.kind {
-                            notify::EventKind::Create(CreateKind::File) => {
-                                if is_audio_file(path) {
-                                    tracing::debug!(target: "scanner::watcher", path = %path.display(), "File created");
-                                    Some(WatchEvent::Created(path.clone()))
-                                } else {
-                                    None
-                                }
-                            }
-                            notify::EventKind::Create(CreateKind::Folder) => {
-                                tracing::debug!(target: "scanner::watcher", path = %path.display(), "Directory created");
-                                Some(WatchEvent::DirCreated(path.clone()))
-                            }
-                            notify::EventKind::Modify(ModifyKind::Data(_))
-                            | notify::EventKind::Modify(ModifyKind::Metadata(_)) => {
-                                if is_audio_file(path) {
-                                    tracing::debug!(target: "scanner::watcher", path = %path.display(), "File modified");
-                                    Some(WatchEvent::Modified(path.clone()))
-                                } else {
-                                    None
-                                }
-                            }
-                            notify::EventKind::Remove(RemoveKind::File) => {
-                                if is_audio_file(path) {
-                                    tracing::debug!(target: "scanner::watcher", path = %path.display(), "File removed");
-                                    Some(WatchEvent::Removed(path.clone()))
-                                } else {
-                                    None
-                                }
-                            }
-                            _ => None,
-                        };
-
-                        if let Some(evt) = watch_event {
-                            // try_send is non-blocking and safe from sync context
-                            let _ = tx.try_send(evt);
-                        }
-                    }
-                }
-            }
-            Err(errors) => {
-                for error in errors {
-                    tracing::warn!(target: "scanner::watcher", error = %error, "Watch error");
-                    let _ = tx.try_send(WatchEvent::Error(error.to_string()));
-                }
-            }
-        }
-    }
 }
 
 impl Drop for FileWatcher {
@@ -320,14 +259,16 @@ mod tests {
     #[test]
     fn test_watcher_creation() {
         let dir = tempdir().unwrap();
-        let (watcher, _rx) = FileWatcher::new(vec![dir.path().to_path_buf()]).unwrap();
+        let (watcher, _rx) =
+            FileWatcher::new(vec![dir.path().to_path_buf()], Duration::ZERO).unwrap();
         drop(watcher); // Should not panic
     }
 
     #[test]
     fn test_watcher_detects_new_file() {
         let dir = tempdir().unwrap();
-        let (watcher, rx) = FileWatcher::new(vec![dir.path().to_path_buf()]).unwrap();
+        let (watcher, rx) =
+            FileWatcher::new(vec![dir.path().to_path_buf()], Duration::ZERO).unwrap();
 
         // Create a file
         let file_path = dir.path().join("new_song.mp3");
@@ -352,7 +293,8 @@ mod tests {
     #[test]
     fn test_async_watcher_creation() {
         let dir = tempdir().unwrap();
-        let (watcher, _rx) = FileWatcher::new_async(vec![dir.path().to_path_buf()]).unwrap();
+        let (watcher, _rx) =
+            FileWatcher::new_async(vec![dir.path().to_path_buf()], Duration::ZERO).unwrap();
         drop(watcher); // Should not panic
     }
 
@@ -370,7 +312,8 @@ mod tests {
         use std::sync::atomic::{AtomicUsize, Ordering};
 
         let dir = tempdir().unwrap();
-        let (watcher, mut rx) = FileWatcher::new_async(vec![dir.path().to_path_buf()]).unwrap();
+        let (watcher, mut rx) =
+            FileWatcher::new_async(vec![dir.path().to_path_buf()], Duration::ZERO).unwrap();
 
         // Counter to track how many times our "tick" task runs
         let tick_count = Arc::new(AtomicUsize::new(0));
@@ -414,7 +357,8 @@ mod tests {
     #[tokio::test]
     async fn test_async_watcher_receives_events() {
         let dir = tempdir().unwrap();
-        let (watcher, mut rx) = FileWatcher::new_async(vec![dir.path().to_path_buf()]).unwrap();
+        let (watcher, mut rx) =
+            FileWatcher::new_async(vec![dir.path().to_path_buf()], Duration::ZERO).unwrap();
 
         // Create a file in a separate task
         let dir_path = dir.path().to_path_buf();
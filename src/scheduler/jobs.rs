@@ -0,0 +1,281 @@
+//! What each maintenance job does.
+
+use chrono::Utc;
+use sqlx::sqlite::SqlitePool;
+use std::path::{Path, PathBuf};
+
+use super::{Job, SchedulerError};
+use crate::config::Config;
+use crate::player::silence;
+use crate::tasks::TaskHandle;
+use crate::{db, health, library, profile, readonly};
+
+/// Tracks re-assessed per quality re-check run
+const RECHECK_BATCH: u32 = 500;
+/// Assessments older than this are re-done
+const RECHECK_AFTER_DAYS: i64 = 30;
+/// Tracks decoded per silence analysis run
+const SILENCE_BATCH: u32 = 200;
+/// Folder (in the profile's data directory) backups go to
+const BACKUP_DIR: &str = "backups";
+
+/// Run `job`, returning a one-line summary of what it did.
+pub(super) async fn run(
+    pool: &SqlitePool,
+    job: Job,
+    config: &Config,
+    task: &TaskHandle,
+) -> Result<String, SchedulerError> {
+    match job {
+        Job::Scan => scan(pool, config, task).await,
+        Job::Gardener => recheck_quality(pool, task).await,
+        Job::Backup => {
+            // A single statement; it can only be cancelled before it starts
+            if task.is_cancelled() {
+                return Err(SchedulerError::Cancelled);
+            }
+            task.set_phase("Copying database");
+            let dir = profile::data_path(BACKUP_DIR);
+            let path = backup_database(pool, &dir, config.scheduler.backups_kept).await?;
+            Ok(format!("Saved {}", path.display()))
+        }
+        Job::Verify => verify_library(pool, task).await,
+        Job::Silence => analyze_silence(pool, task).await,
+    }
+}
+
+async fn scan(
+    pool: &SqlitePool,
+    config: &Config,
+    task: &TaskHandle,
+) -> Result<String, SchedulerError> {
+    if readonly::is_enabled() {
+        return Ok("Skipped: library is read-only".to_string());
+    }
+    let mut roots = config.library.paths.clone();
+    if roots.is_empty() {
+        roots.extend(config.library.last_scan_path.clone());
+    }
+    if roots.is_empty() {
+        return Ok("No library folders configured".to_string());
+    }
+
+    let mut parts = Vec::new();
+    for root in &roots {
+        if !root.is_dir() {
+            parts.push(format!("{} not found", root.display()));
+            continue;
+        }
+        let result = library::incremental_scan(pool, root, task).await?;
+        if result.cancelled {
+            return Err(SchedulerError::Cancelled);
+        }
+        parts.push(if roots.len() > 1 {
+            format!("{}: {}", root.display(), result)
+        } else {
+            result.to_string()
+        });
+    }
+    Ok(parts.join("; "))
+}
+
+/// Re-assess tracks whose quality check is more than a month old
+async fn recheck_quality(pool: &SqlitePool, task: &TaskHandle) -> Result<String, SchedulerError> {
+    if readonly::is_enabled() {
+        return Ok("Skipped: library is read-only".to_string());
+    }
+    let cutoff = (Utc::now() - chrono::Duration::days(RECHECK_AFTER_DAYS)).to_rfc3339();
+    let tracks = db::get_tracks_quality_checked_before(pool, &cutoff, RECHECK_BATCH).await?;
+    task.set_phase("Assessing quality");
+    task.set_total(tracks.len() as u64);
+    let mut needing_attention = 0;
+    for track in &tracks {
+        if task.is_cancelled() {
+            return Err(SchedulerError::Cancelled);
+        }
+        task.advance(1);
+        let quality = health::assess_track_quality(track);
+        if quality.needs_attention() {
+            needing_attention += 1;
+        }
+        db::update_track_quality(pool, track.id, &quality).await?;
+    }
+    Ok(match tracks.len() {
+        0 => "All assessments are recent".to_string(),
+        n => format!(
+            "Re-checked {} tracks, {} need attention",
+            n, needing_attention
+        ),
+    })
+}
+
+/// Measure the silence of tracks not analyzed yet, a batch per run
+async fn analyze_silence(pool: &SqlitePool, task: &TaskHandle) -> Result<String, SchedulerError> {
+    if readonly::is_enabled() {
+        return Ok("Skipped: library is read-only".to_string());
+    }
+    let tracks = db::get_tracks_needing_silence_check(pool, SILENCE_BATCH).await?;
+    task.set_phase("Decoding tracks");
+    task.set_total(tracks.len() as u64);
+    let mut long = 0;
+    for (id, path) in &tracks {
+        if task.is_cancelled() {
+            return Err(SchedulerError::Cancelled);
+        }
+        let file = PathBuf::from(path);
+        let silence = tokio::task::spawn_blocking(move || silence::analyze(&file))
+            .await
+            .map_err(std::io::Error::other)?;
+        let silence = match silence {
+            Ok(silence) => Some(silence),
+            Err(e) => {
+                tracing::debug!("Silence analysis of {} failed: {}", path, e);
+                None
+            }
+        };
+        if silence.is_some_and(|s| s.is_long()) {
+            long += 1;
+        }
+        db::update_track_silence(pool, *id, silence.as_ref()).await?;
+        task.advance(1);
+    }
+    Ok(match tracks.len() {
+        0 => "All tracks analyzed".to_string(),
+        n => format!("Analyzed {} tracks, {} with long silence", n, long),
+    })
+}
+
+/// Copy the database into `dir` under a timestamped name, then delete all but
+/// the newest `keep` backups there. Returns the new backup's path.
+pub async fn backup_database(
+    pool: &SqlitePool,
+    dir: &Path,
+    keep: usize,
+) -> Result<PathBuf, SchedulerError> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "music_minder-{}.db",
+        Utc::now().format("%Y%m%d-%H%M%S")
+    ));
+    // VACUUM INTO writes a consistent, compacted copy without stopping writers
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().as_ref())
+        .execute(pool)
+        .await?;
+
+    // Timestamped names sort oldest first
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("music_minder-") && n.ends_with(".db"))
+        })
+        .collect();
+    backups.sort();
+    let excess = backups.len().saturating_sub(keep.max(1));
+    for old in &backups[..excess] {
+        if let Err(e) = std::fs::remove_file(old) {
+            tracing::warn!("Failed to delete old backup {:?}: {}", old, e);
+        }
+    }
+    Ok(path)
+}
+
+/// Run SQLite's integrity check and count tracks whose files are missing.
+///
+/// Missing files are only reported; the scan job removes them.
+pub async fn verify_library(
+    pool: &SqlitePool,
+    task: &TaskHandle,
+) -> Result<String, SchedulerError> {
+    task.set_phase("Checking integrity");
+    let problems = db::maintenance::integrity_problems(pool).await?;
+    if let Some(first) = problems.into_iter().next() {
+        return Err(SchedulerError::Integrity(first));
+    }
+
+    let tracks = db::get_all_track_file_info(pool).await?;
+    let total = tracks.len();
+    task.set_phase("Looking for missing files");
+    task.set_total(total as u64);
+    let file_task = task.clone();
+    let missing = tokio::task::spawn_blocking(move || {
+        let mut missing = 0;
+        for track in &tracks {
+            if file_task.is_cancelled() {
+                return None;
+            }
+            file_task.advance(1);
+            if !Path::new(&track.path).exists() {
+                missing += 1;
+            }
+        }
+        Some(missing)
+    })
+    .await
+    .unwrap_or(Some(0))
+    .ok_or(SchedulerError::Cancelled)?;
+    Ok(match missing {
+        0 => format!("Database OK, all {} files present", total),
+        n => format!("Database OK, {} of {} files missing", n, total),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::TrackMetadata;
+    use crate::tasks::TaskKind;
+
+    #[tokio::test]
+    async fn test_backup_and_verify() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", temp.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+        let meta = TrackMetadata {
+            title: "Airbag".to_string(),
+            artist: "Radiohead".to_string(),
+            album: "OK Computer".to_string(),
+            duration: 284,
+            track_number: Some(1),
+        };
+        db::insert_track(&pool, &meta, "/nowhere/01 Airbag.mp3", None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            verify_library(
+                &pool,
+                &TaskHandle::detached(TaskKind::Maintenance, "Verify")
+            )
+            .await
+            .unwrap(),
+            "Database OK, 1 of 1 files missing"
+        );
+
+        // Old backups beyond `keep` are pruned
+        let dir = temp.path().join("backups");
+        std::fs::create_dir_all(&dir).unwrap();
+        for stamp in ["20200101-000000", "20200102-000000"] {
+            std::fs::write(dir.join(format!("music_minder-{}.db", stamp)), b"").unwrap();
+        }
+        let path = backup_database(&pool, &dir, 2).await.unwrap();
+        let mut left: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            [dir.join("music_minder-20200102-000000.db"), path.clone()]
+        );
+
+        // The backup is a working database
+        let copy = db::init_db(&format!("sqlite:{}", path.display()))
+            .await
+            .unwrap();
+        assert_eq!(db::count_tracks(&copy).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,410 @@
+//! Scheduled background maintenance.
+//!
+//! While the app (or the headless [`crate::agent`]) is running, a handful of
+//! [`Job`]s run on cron-like rules from the `[scheduler]` config section: an
+//! incremental library scan, a quality re-check of tracks not assessed in a
+//! while, a database backup, a database/missing-file verification and silence
+//! analysis of new tracks. Each finished run is stored in
+//! `job_runs`, which is where the last run (and from it, the next) comes from
+//! and what the per-job log in Settings shows.
+//!
+//! The caller decides *when* to check (a timer) and whether it is idle;
+//! [`due_jobs`] decides what is due and [`run_and_record`] does the work.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::scheduler::{due_jobs, run_and_record};
+//! use music_minder::tasks::TaskKind;
+//!
+//! for job in due_jobs(&cfg.scheduler, &runs, started, now, idle) {
+//!     let task = tasks.start(TaskKind::Maintenance, job.label());
+//!     let run = run_and_record(&pool, job, &cfg, &task).await;
+//!     task.finish();
+//!     println!("{}: {}", job.label(), run.summary);
+//! }
+//! ```
+
+mod jobs;
+mod schedule;
+
+pub use jobs::{backup_database, verify_library};
+pub use schedule::{Cron, IDLE_RUN_GAP, Schedule, ScheduleError};
+
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use sqlx::sqlite::SqlitePool;
+
+use crate::config::{Config, SchedulerConfig};
+use crate::tasks::TaskHandle;
+
+/// Runs shown per job in the Settings log
+pub const RUNS_KEPT_PER_JOB: u32 = 5;
+
+/// Errors a job can fail with.
+#[derive(Debug, thiserror::Error)]
+pub enum SchedulerError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Database integrity check failed: {0}")]
+    Integrity(String),
+
+    #[error("Cancelled")]
+    Cancelled,
+}
+
+/// A maintenance job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Job {
+    /// Incremental scan of the library folders
+    Scan,
+    /// Re-assess the quality of tracks not checked in a while
+    Gardener,
+    /// Copy the database to the backups folder
+    Backup,
+    /// Database integrity check and missing-file count
+    Verify,
+    /// Measure silence at the ends of tracks not analyzed yet
+    Silence,
+}
+
+impl Job {
+    /// All jobs, in display order.
+    pub const ALL: [Job; 5] = [
+        Job::Scan,
+        Job::Gardener,
+        Job::Backup,
+        Job::Verify,
+        Job::Silence,
+    ];
+
+    /// Convert to string representation for storage.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Job::Scan => "scan",
+            Job::Gardener => "gardener",
+            Job::Backup => "backup",
+            Job::Verify => "verify",
+            Job::Silence => "silence",
+        }
+    }
+
+    /// Human-readable label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Job::Scan => "Library scan",
+            Job::Gardener => "Quality re-check",
+            Job::Backup => "Database backup",
+            Job::Verify => "Verify library",
+            Job::Silence => "Silence analysis",
+        }
+    }
+
+    /// What the job does, for Settings.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Job::Scan => "Pick up new, changed and deleted files in the library folders",
+            Job::Gardener => "Re-assess tags of tracks not checked in the last month",
+            Job::Backup => "Copy the database to the backups folder",
+            Job::Verify => "Check the database for corruption and count missing files",
+            Job::Silence => "Find long silence at the start and end of new tracks",
+        }
+    }
+
+    /// Rule used when the config doesn't set one.
+    pub fn default_schedule(&self) -> &'static str {
+        match self {
+            Job::Scan => "0 3 * * *",
+            Job::Gardener => "@idle",
+            Job::Backup => "@weekly",
+            Job::Verify => "@monthly",
+            Job::Silence => "@idle",
+        }
+    }
+
+    /// The job's rule from `config`, or its default.
+    pub fn schedule_rule<'a>(&self, config: &'a SchedulerConfig) -> &'a str {
+        config
+            .job(*self)
+            .schedule
+            .as_deref()
+            .unwrap_or(self.default_schedule())
+    }
+
+    /// The job's parsed schedule from `config`.
+    pub fn schedule(&self, config: &SchedulerConfig) -> Result<Schedule, ScheduleError> {
+        Schedule::parse(self.schedule_rule(config))
+    }
+}
+
+impl std::fmt::Display for Job {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for Job {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Job::ALL
+            .into_iter()
+            .find(|j| j.as_str() == s)
+            .ok_or_else(|| format!("unknown job '{}'", s))
+    }
+}
+
+/// One finished run of a job.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobRun {
+    pub job: Job,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Whether the job completed
+    pub success: bool,
+    /// What the job did, or why it failed
+    pub summary: String,
+}
+
+impl JobRun {
+    /// When the run started, as naive local time (what schedules use)
+    pub fn started_local(&self) -> NaiveDateTime {
+        self.started_at.with_timezone(&Local).naive_local()
+    }
+}
+
+/// Database row for the job_runs table.
+#[derive(Debug, sqlx::FromRow)]
+struct JobRunRow {
+    job: String,
+    started_at: i64,
+    finished_at: i64,
+    success: bool,
+    summary: String,
+}
+
+impl TryFrom<JobRunRow> for JobRun {
+    type Error = String;
+
+    fn try_from(row: JobRunRow) -> Result<Self, Self::Error> {
+        Ok(JobRun {
+            job: row.job.parse()?,
+            started_at: DateTime::from_timestamp(row.started_at, 0).unwrap_or_default(),
+            finished_at: DateTime::from_timestamp(row.finished_at, 0).unwrap_or_default(),
+            success: row.success,
+            summary: row.summary,
+        })
+    }
+}
+
+/// Store a finished run.
+pub async fn record_run(pool: &SqlitePool, run: &JobRun) -> sqlx::Result<i64> {
+    let result = sqlx::query(
+        "INSERT INTO job_runs (job, started_at, finished_at, success, summary) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(run.job.as_str())
+    .bind(run.started_at.timestamp())
+    .bind(run.finished_at.timestamp())
+    .bind(run.success)
+    .bind(&run.summary)
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// The latest `per_job` runs of every job, newest first.
+pub async fn recent_runs(pool: &SqlitePool, per_job: u32) -> sqlx::Result<Vec<JobRun>> {
+    let rows: Vec<JobRunRow> = sqlx::query_as(
+        r#"
+        SELECT job, started_at, finished_at, success, summary
+        FROM job_runs r
+        WHERE r.id IN (
+            SELECT id FROM job_runs WHERE job = r.job ORDER BY id DESC LIMIT ?
+        )
+        ORDER BY r.id DESC
+        "#,
+    )
+    .bind(i64::from(per_job))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| JobRun::try_from(row).ok())
+        .collect())
+}
+
+/// Latest run of `job` among `runs` (newest first).
+pub fn last_run(runs: &[JobRun], job: Job) -> Option<&JobRun> {
+    runs.iter().find(|r| r.job == job)
+}
+
+/// Add a finished run to a newest-first list, keeping at most
+/// [`RUNS_KEPT_PER_JOB`] per job.
+pub fn push_run(runs: &mut Vec<JobRun>, run: JobRun) {
+    let job = run.job;
+    runs.insert(0, run);
+    let mut seen = 0;
+    runs.retain(|r| {
+        if r.job != job {
+            return true;
+        }
+        seen += 1;
+        seen <= RUNS_KEPT_PER_JOB
+    });
+}
+
+/// Enabled jobs that should run now, in [`Job::ALL`] order.
+///
+/// `runs` are recent runs, newest first; `started` is when the scheduler
+/// started (see [`Schedule::is_due`]). Jobs with an invalid rule never run.
+pub fn due_jobs(
+    config: &SchedulerConfig,
+    runs: &[JobRun],
+    started: NaiveDateTime,
+    now: NaiveDateTime,
+    idle: bool,
+) -> Vec<Job> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    Job::ALL
+        .into_iter()
+        .filter(|job| config.job(*job).enabled)
+        .filter(|job| {
+            job.schedule(config).is_ok_and(|schedule| {
+                let last = last_run(runs, *job).map(JobRun::started_local);
+                schedule.is_due(last, started, now, idle)
+            })
+        })
+        .collect()
+}
+
+/// Run a job and store the outcome.
+///
+/// Failures are recorded too; the returned run says whether it succeeded.
+/// The job reports progress through `task`, and cancelling it ends the run as
+/// a failure.
+pub async fn run_and_record(
+    pool: &SqlitePool,
+    job: Job,
+    config: &Config,
+    task: &TaskHandle,
+) -> JobRun {
+    tracing::info!(job = job.as_str(), "Job started");
+    let started_at = Utc::now();
+    let result = jobs::run(pool, job, config, task).await;
+    let run = JobRun {
+        job,
+        started_at,
+        finished_at: Utc::now(),
+        success: result.is_ok(),
+        summary: match result {
+            Ok(summary) => summary,
+            Err(e) => e.to_string(),
+        },
+    };
+    if run.success {
+        tracing::info!(job = job.as_str(), "Job finished: {}", run.summary);
+    } else {
+        tracing::warn!(job = job.as_str(), "Job failed: {}", run.summary);
+    }
+    if let Err(e) = record_run(pool, &run).await {
+        tracing::warn!("Failed to record {} run: {}", job, e);
+    }
+    run
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn run(job: Job, started: &str) -> JobRun {
+        let started_at = NaiveDateTime::parse_from_str(started, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap()
+            .with_timezone(&Utc);
+        JobRun {
+            job,
+            started_at,
+            finished_at: started_at,
+            success: true,
+            summary: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_due_jobs() {
+        let at = |s| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let mut config = SchedulerConfig::default();
+        config.verify.schedule = Some("not a rule".into());
+        let started = at("2026-03-10 09:00");
+        let runs = vec![
+            run(Job::Scan, "2026-03-10 03:00"),
+            run(Job::Backup, "2026-03-01 00:00"),
+        ];
+
+        // Backup is a week overdue; the scan already ran today; the gardener
+        // and silence analysis wait for idle; verify has no valid rule
+        assert_eq!(
+            due_jobs(&config, &runs, started, at("2026-03-10 12:00"), false),
+            [Job::Backup]
+        );
+        assert_eq!(
+            due_jobs(&config, &runs, started, at("2026-03-11 03:00"), true),
+            [Job::Scan, Job::Gardener, Job::Backup, Job::Silence]
+        );
+
+        config.backup.enabled = false;
+        assert!(due_jobs(&config, &runs, started, at("2026-03-10 12:00"), false).is_empty());
+        config.enabled = false;
+        assert!(due_jobs(&config, &runs, started, at("2026-03-11 03:00"), true).is_empty());
+    }
+
+    #[test]
+    fn test_push_run_keeps_latest_per_job() {
+        let mut runs = vec![run(Job::Backup, "2026-03-01 00:00")];
+        for day in 1..=RUNS_KEPT_PER_JOB + 2 {
+            push_run(
+                &mut runs,
+                run(Job::Scan, &format!("2026-03-{:02} 03:00", day)),
+            );
+        }
+        let scans: Vec<_> = runs.iter().filter(|r| r.job == Job::Scan).collect();
+        assert_eq!(scans.len(), RUNS_KEPT_PER_JOB as usize);
+        assert_eq!(
+            last_run(&runs, Job::Scan),
+            Some(&run(Job::Scan, "2026-03-07 03:00"))
+        );
+        assert!(last_run(&runs, Job::Backup).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_recent_runs_per_job() {
+        let temp = tempfile::tempdir().unwrap();
+        let db_url = format!("sqlite:{}", temp.path().join("test.db").display());
+        let pool = db::init_db(&db_url).await.unwrap();
+
+        for day in 1..=3 {
+            record_run(&pool, &run(Job::Scan, &format!("2026-03-{:02} 03:00", day)))
+                .await
+                .unwrap();
+        }
+        let mut failed = run(Job::Verify, "2026-03-02 00:00");
+        failed.success = false;
+        failed.summary = "Database integrity check failed".into();
+        record_run(&pool, &failed).await.unwrap();
+
+        let runs = recent_runs(&pool, 2).await.unwrap();
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0], failed);
+        assert_eq!(
+            last_run(&runs, Job::Scan),
+            Some(&run(Job::Scan, "2026-03-03 03:00"))
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Cron-like schedule rules.
+//!
+//! A rule is either a five-field cron expression (`minute hour day-of-month
+//! month day-of-week`), one of the shortcuts `@hourly`, `@daily` (or
+//! `@nightly`), `@weekly` and `@monthly`, or `@idle` for "whenever the app
+//! has been left alone for a while". Fields take `*`, numbers, ranges
+//! (`1-5`), steps (`*/15`, `0-30/10`) and comma lists; months and weekdays
+//! also take three-letter names (`jan`, `mon`). As in cron, when both the
+//! day-of-month and day-of-week fields are restricted, either one matching is
+//! enough. Times are local.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// Minimum gap between two runs of an `@idle` job
+pub const IDLE_RUN_GAP: Duration = Duration::hours(1);
+
+/// How far ahead [`Cron::next_after`] looks before giving up (e.g. "30 2 31
+/// feb *" never matches)
+const SEARCH_DAYS: i64 = 5 * 366;
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Errors in a schedule rule.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("expected 5 fields (minute hour day month weekday), found {0}")]
+    FieldCount(usize),
+
+    #[error("unknown shortcut '{0}'")]
+    UnknownShortcut(String),
+
+    #[error("invalid {field} value '{value}'")]
+    InvalidValue { field: &'static str, value: String },
+}
+
+/// When a job should run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// At the times a cron expression matches
+    Cron(Cron),
+    /// When the app is idle, at most once per [`IDLE_RUN_GAP`]
+    Idle,
+}
+
+impl Schedule {
+    /// Parse a rule (see the module docs for the syntax).
+    pub fn parse(rule: &str) -> Result<Self, ScheduleError> {
+        let rule = rule.trim();
+        let expression = match rule.to_ascii_lowercase().as_str() {
+            "@idle" => return Ok(Schedule::Idle),
+            "@hourly" => "0 * * * *",
+            "@daily" | "@nightly" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * sun",
+            "@monthly" => "0 0 1 * *",
+            s if s.starts_with('@') => return Err(ScheduleError::UnknownShortcut(rule.into())),
+            _ => rule,
+        };
+        Cron::parse(expression).map(Schedule::Cron)
+    }
+
+    /// Whether a job on this schedule should run now.
+    ///
+    /// `last_run` is when the job last ran, if ever; a job that never ran
+    /// counts from `started` (when the scheduler started), so turning the app
+    /// on doesn't fire every job at once. A cron time missed while the app
+    /// was closed is made up once, at the next check.
+    pub fn is_due(
+        &self,
+        last_run: Option<NaiveDateTime>,
+        started: NaiveDateTime,
+        now: NaiveDateTime,
+        idle: bool,
+    ) -> bool {
+        match self {
+            Schedule::Cron(cron) => cron
+                .next_after(last_run.unwrap_or(started))
+                .is_some_and(|next| next <= now),
+            Schedule::Idle => idle && last_run.is_none_or(|last| now - last >= IDLE_RUN_GAP),
+        }
+    }
+
+    /// Next time the job will run, when that is known in advance.
+    pub fn next_run(
+        &self,
+        last_run: Option<NaiveDateTime>,
+        started: NaiveDateTime,
+    ) -> Option<NaiveDateTime> {
+        match self {
+            Schedule::Cron(cron) => cron.next_after(last_run.unwrap_or(started)),
+            Schedule::Idle => None,
+        }
+    }
+}
+
+/// A parsed five-field cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    /// Day-of-month field was `*`
+    any_day: bool,
+    /// Day-of-week field was `*`
+    any_weekday: bool,
+}
+
+impl Cron {
+    /// Parse `minute hour day-of-month month day-of-week`.
+    pub fn parse(expression: &str) -> Result<Self, ScheduleError> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(ScheduleError::FieldCount(fields.len()));
+        };
+        let lower = weekday.to_ascii_lowercase();
+        // 7 is Sunday too
+        let weekdays = parse_field(&lower, "weekday", 0, 7, &WEEKDAY_NAMES)?;
+        let weekdays = (weekdays | (weekdays >> 7)) & 0x7f;
+        Ok(Cron {
+            minutes: parse_field(minute, "minute", 0, 59, &[])?,
+            hours: parse_field(hour, "hour", 0, 23, &[])? as u32,
+            days: parse_field(day, "day", 1, 31, &[])? as u32,
+            months: parse_field(&month.to_ascii_lowercase(), "month", 1, 12, &MONTH_NAMES)? as u16,
+            weekdays: weekdays as u8,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+
+    /// First matching minute strictly after `after`.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date();
+        let mut from = start.time();
+        let last = date + Duration::days(SEARCH_DAYS);
+
+        while date <= last {
+            if self.months & (1 << date.month()) != 0
+                && self.day_matches(date)
+                && let Some(time) = self.time_on_or_after(from)
+            {
+                return Some(date.and_time(time));
+            }
+            date = date.succ_opt()?;
+            from = NaiveTime::MIN;
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// First matching time of day at or after `from`
+    fn time_on_or_after(&self, from: NaiveTime) -> Option<NaiveTime> {
+        for hour in from.hour()..24 {
+            if self.hours & (1 << hour) == 0 {
+                continue;
+            }
+            let first_minute = if hour == from.hour() {
+                from.minute()
+            } else {
+                0
+            };
+            for minute in first_minute..60 {
+                if self.minutes & (1 << minute) != 0 {
+                    return NaiveTime::from_hms_opt(hour, minute, 0);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Parse one field into a bitmask of allowed values
+fn parse_field(
+    field: &str,
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<u64, ScheduleError> {
+    let invalid = || ScheduleError::InvalidValue {
+        field: name,
+        value: field.to_string(),
+    };
+    let value = |s: &str| -> Result<u32, ScheduleError> {
+        let n = match names.iter().position(|n| *n == s) {
+            // Names start at the field's minimum (jan = 1, sun = 0)
+            Some(i) => i as u32 + min,
+            None => s.parse().map_err(|_| invalid())?,
+        };
+        if (min..=max).contains(&n) {
+            Ok(n)
+        } else {
+            Err(invalid())
+        }
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(invalid());
+        }
+        let (low, high) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((low, high)) => (value(low)?, value(high)?),
+                // "5/10" means from 5 to the end, every 10
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let n = value(range)?;
+                    (n, n)
+                }
+            },
+        };
+        if low > high {
+            return Err(invalid());
+        }
+        for n in (low..=high).step_by(step as usize) {
+            mask |= 1 << n;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(rule: &str, after: &str) -> Option<NaiveDateTime> {
+        match Schedule::parse(rule).unwrap() {
+            Schedule::Cron(cron) => cron.next_after(at(after)),
+            Schedule::Idle => None,
+        }
+    }
+
+    #[test]
+    fn test_next_after() {
+        // Nightly at 03:00
+        assert_eq!(
+            next("0 3 * * *", "2026-03-10 02:59"),
+            Some(at("2026-03-10 03:00"))
+        );
+        assert_eq!(
+            next("0 3 * * *", "2026-03-10 03:00"),
+            Some(at("2026-03-11 03:00"))
+        );
+        // Every 15 minutes
+        assert_eq!(
+            next("*/15 * * * *", "2026-03-10 10:46"),
+            Some(at("2026-03-10 11:00"))
+        );
+        // 2026-03-10 is a Tuesday; @weekly is Sunday midnight
+        assert_eq!(
+            next("@weekly", "2026-03-10 12:00"),
+            Some(at("2026-03-15 00:00"))
+        );
+        assert_eq!(
+            next("30 4 * * mon-fri", "2026-03-13 05:00"),
+            Some(at("2026-03-16 04:30"))
+        );
+        // 7 is Sunday as well
+        assert_eq!(
+            next("0 0 * * 7", "2026-03-10 12:00"),
+            next("@weekly", "2026-03-10 12:00")
+        );
+        // Monthly, across a year boundary
+        assert_eq!(
+            next("@monthly", "2026-12-15 00:00"),
+            Some(at("2027-01-01 00:00"))
+        );
+        // Day-of-month or day-of-week: the 13th or any Friday
+        assert_eq!(
+            next("0 0 13 * fri", "2026-03-01 00:00"),
+            Some(at("2026-03-06 00:00"))
+        );
+        // Never matches
+        assert_eq!(next("0 0 31 feb *", "2026-01-01 00:00"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Schedule::parse("0 3 * *"),
+            Err(ScheduleError::FieldCount(4))
+        );
+        assert_eq!(
+            Schedule::parse("@sometimes"),
+            Err(ScheduleError::UnknownShortcut("@sometimes".into()))
+        );
+        assert!(matches!(
+            Schedule::parse("61 * * * *"),
+            Err(ScheduleError::InvalidValue {
+                field: "minute",
+                ..
+            })
+        ));
+        assert!(matches!(
+            Schedule::parse("*/0 * * * *"),
+            Err(ScheduleError::InvalidValue { .. })
+        ));
+        assert_eq!(Schedule::parse(" @IDLE "), Ok(Schedule::Idle));
+    }
+
+    #[test]
+    fn test_is_due() {
+        let nightly = Schedule::parse("0 3 * * *").unwrap();
+        let started = at("2026-03-10 09:00");
+
+        // Never ran: counts from startup, so not due until 03:00 tomorrow
+        assert!(!nightly.is_due(None, started, at("2026-03-10 23:00"), false));
+        assert!(nightly.is_due(None, started, at("2026-03-11 03:00"), false));
+        // Last ran three days ago: the missed runs are made up once
+        assert!(nightly.is_due(Some(at("2026-03-07 03:00")), started, started, false));
+        assert!(!nightly.is_due(Some(at("2026-03-10 03:00")), started, started, false));
+
+        let idle = Schedule::Idle;
+        assert!(!idle.is_due(None, started, started, false));
+        assert!(idle.is_due(None, started, started, true));
+        assert!(!idle.is_due(Some(at("2026-03-10 08:30")), started, started, true));
+        assert!(idle.is_due(Some(at("2026-03-10 07:59")), started, started, true));
+    }
+}
@@ -0,0 +1,519 @@
+//! Passphrase encryption of the config's credentials.
+//!
+//! On a shared machine without an OS keychain the API keys in the config
+//! file needn't be readable by everyone who can read the file. With
+//! encryption on, the `[credentials]` section is written empty and a sealed
+//! copy goes in `[secrets]`:
+//!
+//! ```toml
+//! [secrets]
+//! encrypted = "mm-secrets-v2$200000$<base64>"
+//! ```
+//!
+//! The passphrase is stretched with PBKDF2-HMAC-SHA256 (the iteration count
+//! is in the header) into a key for ChaCha20-Poly1305. The credentials are
+//! sealed under a fresh nonce, with the header and salt as associated data,
+//! so a wrong passphrase or an edited file fails to open. Derived keys and
+//! decrypted credentials are wiped from memory when dropped.
+//!
+//! The passphrase is asked for once per session: [`unlock`] keeps the
+//! derived keys in memory, after which [`crate::config::load`] opens the
+//! credentials and [`crate::config::save`] seals them again. Until then the
+//! app runs without them. Services and scripts can unlock with
+//! `MUSIC_MINDER_PASSPHRASE` ([`unlock_from_env`]).
+
+#[cfg(not(test))]
+use std::sync::Mutex;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::config::{self, Config, Credentials};
+
+/// First field of a sealed block, naming the format
+const FORMAT: &str = "mm-secrets-v2";
+
+/// PBKDF2 iterations for new blocks
+const ITERATIONS: u32 = 200_000;
+
+/// Most iterations a block may ask for; more means an edited or corrupt
+/// file, which would otherwise hang the unlock
+const MAX_ITERATIONS: u32 = ITERATIONS * 10;
+
+/// Environment variable the passphrase can come from
+pub const PASSPHRASE_ENV: &str = "MUSIC_MINDER_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Keys of the unlocked credentials, for the rest of the session
+#[cfg(not(test))]
+static SESSION: Mutex<Option<Keys>> = Mutex::new(None);
+
+// Tests run in parallel; keep the session per-thread so one test's unlock
+// can't leak into another
+#[cfg(test)]
+thread_local! {
+    static SESSION: std::cell::RefCell<Option<Keys>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Credential encryption errors
+#[derive(Debug, thiserror::Error)]
+pub enum SecretsError {
+    #[error("Wrong passphrase, or the encrypted credentials were changed")]
+    BadPassphrase,
+
+    #[error("The passphrase can't be empty")]
+    EmptyPassphrase,
+
+    #[error("Not an encrypted credentials block")]
+    Malformed,
+
+    #[error("Credentials aren't encrypted")]
+    NotEncrypted,
+
+    #[error("Credentials are already encrypted")]
+    AlreadyEncrypted,
+
+    #[error("Credentials are locked: unlock them with the passphrase first")]
+    Locked,
+
+    #[error("Failed to read the decrypted credentials: {0}")]
+    Parse(String),
+
+    #[error(transparent)]
+    Config(#[from] config::ConfigError),
+}
+
+/// Where the credentials stand
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Status {
+    /// Stored in plain text
+    #[default]
+    Plain,
+    /// Encrypted, and the passphrase hasn't been given this session
+    Locked,
+    /// Encrypted and open for this session
+    Unlocked,
+}
+
+/// The key derived from the passphrase and one salt
+#[derive(Clone)]
+struct Keys {
+    salt: [u8; SALT_LEN],
+    iterations: u32,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl std::fmt::Debug for Keys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Keys").finish_non_exhaustive()
+    }
+}
+
+impl Keys {
+    fn derive(passphrase: &str, salt: [u8; SALT_LEN], iterations: u32) -> Self {
+        let mut key = Zeroizing::new([0u8; 32]);
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), &salt, iterations, key.as_mut());
+        Self {
+            salt,
+            iterations,
+            key,
+        }
+    }
+
+    fn header(&self) -> String {
+        format!("{}${}$", FORMAT, self.iterations)
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.key[..]))
+    }
+
+    /// Associated data: the header and salt, so neither can be swapped
+    fn aad(&self) -> Vec<u8> {
+        [self.header().as_bytes(), &self.salt].concat()
+    }
+
+    /// Encrypt `plaintext` under a fresh nonce
+    fn seal(&self, plaintext: &[u8]) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rng().fill_bytes(&mut nonce);
+        let aad = self.aad();
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad: &aad,
+                },
+            )
+            .expect("ChaCha20-Poly1305 seals any length this small");
+        let body = [&self.salt[..], &nonce, &ciphertext].concat();
+        format!("{}{}", self.header(), BASE64.encode(body))
+    }
+
+    /// Check and decrypt a block sealed with this key
+    fn open(&self, sealed: &Sealed) -> Result<Zeroizing<Vec<u8>>, SecretsError> {
+        let (nonce, ciphertext) = sealed.body[SALT_LEN..].split_at(NONCE_LEN);
+        let aad = self.aad();
+        self.cipher()
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map(Zeroizing::new)
+            .map_err(|_| SecretsError::BadPassphrase)
+    }
+}
+
+/// A sealed block, split up
+struct Sealed {
+    iterations: u32,
+    salt: [u8; SALT_LEN],
+    /// Salt, nonce, and ciphertext with its tag
+    body: Vec<u8>,
+}
+
+impl Sealed {
+    fn parse(sealed: &str) -> Result<Self, SecretsError> {
+        let mut fields = sealed.trim().splitn(3, '$');
+        if fields.next() != Some(FORMAT) {
+            return Err(SecretsError::Malformed);
+        }
+        let iterations: u32 = fields
+            .next()
+            .and_then(|n| n.parse().ok())
+            .filter(|n| (1..=MAX_ITERATIONS).contains(n))
+            .ok_or(SecretsError::Malformed)?;
+        let body = fields
+            .next()
+            .and_then(|b| BASE64.decode(b).ok())
+            .filter(|b| b.len() >= SALT_LEN + NONCE_LEN + TAG_LEN)
+            .ok_or(SecretsError::Malformed)?;
+        Ok(Self {
+            iterations,
+            salt: body[..SALT_LEN].try_into().expect("salt length"),
+            body,
+        })
+    }
+}
+
+/// The session's keys, if unlocked
+fn session_keys() -> Option<Keys> {
+    #[cfg(not(test))]
+    return SESSION.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    #[cfg(test)]
+    return SESSION.with(|session| session.borrow().clone());
+}
+
+fn set_session(keys: Option<Keys>) {
+    #[cfg(not(test))]
+    {
+        *SESSION.lock().unwrap_or_else(|e| e.into_inner()) = keys;
+    }
+    #[cfg(test)]
+    SESSION.with(|session| *session.borrow_mut() = keys);
+}
+
+/// The session's keys, if they open `sealed`
+fn session_keys_for(sealed: &Sealed) -> Option<Keys> {
+    session_keys().filter(|keys| keys.salt == sealed.salt && keys.iterations == sealed.iterations)
+}
+
+fn seal_credentials(keys: &Keys, credentials: &Credentials) -> Result<String, SecretsError> {
+    let plaintext = Zeroizing::new(
+        toml::to_string(credentials).map_err(|e| SecretsError::Parse(e.to_string()))?,
+    );
+    Ok(keys.seal(plaintext.as_bytes()))
+}
+
+fn open_credentials(keys: &Keys, sealed: &Sealed) -> Result<Credentials, SecretsError> {
+    let plaintext = keys.open(sealed)?;
+    let plaintext =
+        std::str::from_utf8(&plaintext).map_err(|e| SecretsError::Parse(e.to_string()))?;
+    toml::from_str(plaintext).map_err(|e| SecretsError::Parse(e.to_string()))
+}
+
+/// Where the credentials of `config` stand
+pub fn status(config: &Config) -> Status {
+    match &config.secrets.encrypted {
+        None => Status::Plain,
+        Some(sealed) => match Sealed::parse(sealed)
+            .ok()
+            .and_then(|s| session_keys_for(&s))
+        {
+            Some(_) => Status::Unlocked,
+            None => Status::Locked,
+        },
+    }
+}
+
+/// Fill in the credentials of a just loaded config, if they're encrypted
+/// and unlocked
+pub(crate) fn open_loaded(config: &mut Config) {
+    let Some(sealed) = &config.secrets.encrypted else {
+        return;
+    };
+    let opened = Sealed::parse(sealed).and_then(|sealed| match session_keys_for(&sealed) {
+        Some(keys) => open_credentials(&keys, &sealed).map(Some),
+        None => Ok(None),
+    });
+    match opened {
+        Ok(Some(credentials)) => config.credentials = credentials,
+        Ok(None) => config.credentials = Credentials::default(),
+        Err(e) => {
+            tracing::warn!("Failed to open the encrypted credentials: {}", e);
+            config.credentials = Credentials::default();
+        }
+    }
+}
+
+/// The config as it goes on disk: with encryption on, the credentials are
+/// sealed into `[secrets]` and left out of `[credentials]`
+pub(crate) fn for_disk(config: &Config) -> Result<Option<Config>, SecretsError> {
+    let Some(sealed) = &config.secrets.encrypted else {
+        return Ok(None);
+    };
+    let mut disk = config.clone();
+    disk.credentials = Credentials::default();
+    match session_keys_for(&Sealed::parse(sealed)?) {
+        Some(keys) => {
+            disk.secrets.encrypted = Some(seal_credentials(&keys, &config.credentials)?);
+        }
+        // Locked: the sealed copy stays as it is, and nothing new can go in
+        None if config.credentials != Credentials::default() => {
+            return Err(SecretsError::Locked);
+        }
+        None => {}
+    }
+    Ok(Some(disk))
+}
+
+/// Unlock the encrypted credentials for this session
+pub fn unlock(passphrase: &str) -> Result<Credentials, SecretsError> {
+    unlock_config(&config::load(), passphrase)
+}
+
+fn unlock_config(config: &Config, passphrase: &str) -> Result<Credentials, SecretsError> {
+    let sealed = config
+        .secrets
+        .encrypted
+        .as_deref()
+        .ok_or(SecretsError::NotEncrypted)?;
+    let sealed = Sealed::parse(sealed)?;
+    let keys = Keys::derive(passphrase, sealed.salt, sealed.iterations);
+    let credentials = open_credentials(&keys, &sealed)?;
+    set_session(Some(keys));
+    Ok(credentials)
+}
+
+/// Unlock with the passphrase in `MUSIC_MINDER_PASSPHRASE`, if it's set
+/// and the credentials are locked. Returns whether they're open now.
+pub fn unlock_from_env() -> bool {
+    let config = config::load();
+    match status(&config) {
+        Status::Plain | Status::Unlocked => return true,
+        Status::Locked => {}
+    }
+    let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) else {
+        return false;
+    };
+    match unlock(&passphrase) {
+        Ok(_) => true,
+        Err(e) => {
+            tracing::warn!("{} didn't unlock the credentials: {}", PASSPHRASE_ENV, e);
+            false
+        }
+    }
+}
+
+/// Start encrypting the credentials with `passphrase`
+pub fn encrypt(passphrase: &str) -> Result<(), SecretsError> {
+    let mut config = config::load();
+    encrypt_config(&mut config, passphrase, ITERATIONS)?;
+    config::save(&config)?;
+    Ok(())
+}
+
+/// Seal the credentials of `config` and unlock them for the session
+fn encrypt_config(
+    config: &mut Config,
+    passphrase: &str,
+    iterations: u32,
+) -> Result<(), SecretsError> {
+    if passphrase.is_empty() {
+        return Err(SecretsError::EmptyPassphrase);
+    }
+    if config.secrets.encrypted.is_some() {
+        return Err(SecretsError::AlreadyEncrypted);
+    }
+    let mut salt = [0u8; SALT_LEN];
+    rand::rng().fill_bytes(&mut salt);
+    let keys = Keys::derive(passphrase, salt, iterations);
+    config.secrets.encrypted = Some(seal_credentials(&keys, &config.credentials)?);
+    set_session(Some(keys));
+    Ok(())
+}
+
+/// Go back to plain-text credentials. They have to be unlocked.
+pub fn decrypt() -> Result<(), SecretsError> {
+    let mut config = config::load();
+    match status(&config) {
+        Status::Plain => return Err(SecretsError::NotEncrypted),
+        Status::Locked => return Err(SecretsError::Locked),
+        Status::Unlocked => {}
+    }
+    config.secrets.encrypted = None;
+    config::save(&config)?;
+    set_session(None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let keys = Keys::derive("correct horse", [7; SALT_LEN], 10);
+        let credentials = Credentials {
+            acoustid_api_key: Some("secret-key".to_string()),
+        };
+        let sealed = seal_credentials(&keys, &credentials).unwrap();
+        assert!(sealed.starts_with("mm-secrets-v2$10$"));
+        assert!(!sealed.contains("secret-key"));
+        // A fresh nonce every time
+        assert_ne!(sealed, seal_credentials(&keys, &credentials).unwrap());
+
+        let parsed = Sealed::parse(&sealed).unwrap();
+        assert_eq!(open_credentials(&keys, &parsed).unwrap(), credentials);
+
+        let wrong = Keys::derive("wrong horse", [7; SALT_LEN], 10);
+        assert!(matches!(
+            open_credentials(&wrong, &parsed),
+            Err(SecretsError::BadPassphrase)
+        ));
+
+        // Any changed byte fails the tag
+        let mut tampered = Sealed::parse(&sealed).unwrap();
+        let at = SALT_LEN + NONCE_LEN;
+        tampered.body[at] ^= 1;
+        assert!(matches!(
+            open_credentials(&keys, &tampered),
+            Err(SecretsError::BadPassphrase)
+        ));
+        assert!(matches!(
+            Sealed::parse("mm-secrets-v2$10$bm9wZQ=="),
+            Err(SecretsError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn test_header_and_salt_are_authenticated() {
+        let keys = Keys::derive("correct horse", [7; SALT_LEN], 10);
+        let sealed = Sealed::parse(&keys.seal(b"api key")).unwrap();
+
+        // The same key under another header or salt doesn't open the block
+        let other_header = Keys {
+            iterations: 11,
+            ..keys.clone()
+        };
+        assert!(matches!(
+            other_header.open(&sealed),
+            Err(SecretsError::BadPassphrase)
+        ));
+        let other_salt = Keys {
+            salt: [8; SALT_LEN],
+            ..keys.clone()
+        };
+        assert!(matches!(
+            other_salt.open(&sealed),
+            Err(SecretsError::BadPassphrase)
+        ));
+        assert_eq!(keys.open(&sealed).unwrap().as_slice(), b"api key");
+    }
+
+    #[test]
+    fn test_iterations_out_of_range_are_malformed() {
+        let keys = Keys::derive("correct horse", [7; SALT_LEN], 10);
+        let body = keys.seal(b"api key");
+        let body = body.rsplit('$').next().unwrap();
+        for iterations in [0, MAX_ITERATIONS + 1, u32::MAX] {
+            assert!(matches!(
+                Sealed::parse(&format!("{FORMAT}${iterations}${body}")),
+                Err(SecretsError::Malformed)
+            ));
+        }
+        assert!(Sealed::parse(&format!("{FORMAT}${MAX_ITERATIONS}${body}")).is_ok());
+    }
+
+    #[test]
+    fn test_encrypt_save_and_unlock() {
+        let credentials = Credentials {
+            acoustid_api_key: Some("secret-key".to_string()),
+        };
+        let mut config = Config {
+            credentials: credentials.clone(),
+            ..Default::default()
+        };
+        encrypt_config(&mut config, "correct horse", 10).unwrap();
+        assert_eq!(status(&config), Status::Unlocked);
+
+        // Saved without the plain-text key
+        let disk = for_disk(&config).unwrap().expect("encrypted");
+        let saved = toml::to_string_pretty(&disk).unwrap();
+        assert!(!saved.contains("secret-key"));
+
+        // A new session starts locked
+        set_session(None);
+        let mut loaded: Config = toml::from_str(&saved).unwrap();
+        open_loaded(&mut loaded);
+        assert_eq!(status(&loaded), Status::Locked);
+        assert_eq!(loaded.credentials, Credentials::default());
+
+        assert!(matches!(
+            unlock_config(&loaded, "wrong horse"),
+            Err(SecretsError::BadPassphrase)
+        ));
+        assert_eq!(status(&loaded), Status::Locked);
+        assert_eq!(
+            unlock_config(&loaded, "correct horse").unwrap(),
+            credentials
+        );
+        assert_eq!(status(&loaded), Status::Unlocked);
+        open_loaded(&mut loaded);
+        assert_eq!(loaded.credentials, credentials);
+    }
+
+    #[test]
+    fn test_saving_while_locked() {
+        let mut config = Config {
+            credentials: Credentials {
+                acoustid_api_key: Some("secret-key".to_string()),
+            },
+            ..Default::default()
+        };
+        encrypt_config(&mut config, "correct horse", 10).unwrap();
+        let sealed = config.secrets.encrypted.clone();
+        set_session(None);
+
+        // New credentials can't be sealed without the passphrase
+        assert!(matches!(for_disk(&config), Err(SecretsError::Locked)));
+
+        // Other settings still save, keeping the sealed copy as it was
+        config.credentials = Credentials::default();
+        let disk = for_disk(&config).unwrap().expect("encrypted");
+        assert_eq!(disk.secrets.encrypted, sealed);
+    }
+}
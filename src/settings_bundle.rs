@@ -0,0 +1,416 @@
+//! Settings bundles: every setting in one JSON file, to carry to another
+//! machine.
+//!
+//! A bundle holds the config file's sections and the GUI's saved view state
+//! (sort orders, filters and the panes' layout), under a format name and a
+//! version:
+//!
+//! ```json
+//! {
+//!   "format": "music-minder-settings",
+//!   "version": 1,
+//!   "app_version": "0.1.7",
+//!   "exported_at": "2025-06-01T12:00:00Z",
+//!   "config": { "audio": { ... }, "library": { ... } },
+//!   "view_state": { ... }
+//! }
+//! ```
+//!
+//! Importing picks [`Section`]s out of it; the rest of the settings stay as
+//! they are. Bundles from older versions are brought up to date by
+//! [`migrate`] first, and a section missing settings added since is filled
+//! in with their defaults. Credentials are only exported when asked for,
+//! and encrypted ones (see [`crate::secrets`]) stay encrypted in the bundle.
+
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use crate::config::{self, Config};
+use crate::profile;
+
+/// `format` of every bundle
+pub const FORMAT: &str = "music-minder-settings";
+
+/// Bundle version written by this build
+pub const VERSION: u32 = 1;
+
+/// Bundle errors
+#[derive(Debug, thiserror::Error)]
+pub enum BundleError {
+    #[error("Failed to read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+
+    #[error("Failed to write {0}: {1}")]
+    Write(PathBuf, std::io::Error),
+
+    #[error("Not a settings bundle: {0}")]
+    NotABundle(String),
+
+    #[error("The bundle is version {0}, newer than this build reads ({VERSION}); update the app")]
+    TooNew(u32),
+
+    #[error("The {0} settings in the bundle are invalid: {1}")]
+    InvalidSection(&'static str, serde_json::Error),
+
+    #[error("Failed to serialize settings: {0}")]
+    Serialize(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Config(#[from] config::ConfigError),
+}
+
+/// A group of settings imported or left out together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    Appearance,
+    Audio,
+    Library,
+    Tagging,
+    History,
+    Scheduler,
+    Agent,
+    Analysis,
+    Nfo,
+    Covers,
+    Network,
+    Credentials,
+    ViewState,
+}
+
+impl Section {
+    pub const ALL: [Section; 13] = [
+        Section::Appearance,
+        Section::Audio,
+        Section::Library,
+        Section::Tagging,
+        Section::History,
+        Section::Scheduler,
+        Section::Agent,
+        Section::Analysis,
+        Section::Nfo,
+        Section::Covers,
+        Section::Network,
+        Section::Credentials,
+        Section::ViewState,
+    ];
+
+    /// Name on the command line
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Section::Appearance => "appearance",
+            Section::Audio => "audio",
+            Section::Library => "library",
+            Section::Tagging => "tagging",
+            Section::History => "history",
+            Section::Scheduler => "scheduler",
+            Section::Agent => "agent",
+            Section::Analysis => "analysis",
+            Section::Nfo => "nfo",
+            Section::Covers => "covers",
+            Section::Network => "network",
+            Section::Credentials => "credentials",
+            Section::ViewState => "view_state",
+        }
+    }
+
+    /// Human-readable label
+    pub fn label(self) -> &'static str {
+        match self {
+            Section::Appearance => "Appearance",
+            Section::Audio => "Audio",
+            Section::Library => "Library folders and scanning",
+            Section::Tagging => "Tag writing and genre rules",
+            Section::History => "Recent inputs",
+            Section::Scheduler => "Scheduled jobs",
+            Section::Agent => "Background agent",
+            Section::Analysis => "Fingerprinting CPU use",
+            Section::Nfo => "Kodi/Jellyfin files",
+            Section::Covers => "Cover art",
+            Section::Network => "Network",
+            Section::Credentials => "API keys",
+            Section::ViewState => "Saved views",
+        }
+    }
+
+    /// Exported unless left out: everything but the API keys
+    pub fn exported_by_default(self) -> bool {
+        self != Section::Credentials
+    }
+
+    /// Keys of the config file it covers (none for the view state)
+    fn config_keys(self) -> &'static [&'static str] {
+        match self {
+            Section::Appearance => &["appearance"],
+            Section::Audio => &["audio"],
+            Section::Library => &["library"],
+            Section::Tagging => &["tagging"],
+            Section::History => &["history"],
+            Section::Scheduler => &["scheduler"],
+            Section::Agent => &["agent"],
+            Section::Analysis => &["analysis"],
+            Section::Nfo => &["nfo"],
+            Section::Covers => &["covers"],
+            Section::Network => &["network"],
+            Section::Credentials => &["credentials", "secrets"],
+            Section::ViewState => &[],
+        }
+    }
+}
+
+impl std::fmt::Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+impl std::str::FromStr for Section {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Section::ALL
+            .into_iter()
+            .find(|section| section.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Section::ALL.iter().map(|s| s.as_str()).collect();
+                format!("unknown section '{}' (one of {})", s, names.join(", "))
+            })
+    }
+}
+
+/// Exported settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    /// Version of the app that exported it
+    pub app_version: String,
+    /// When, in RFC 3339
+    pub exported_at: String,
+    /// Config file sections, by key
+    #[serde(default)]
+    pub config: Map<String, Value>,
+    /// The GUI's per-pane view state
+    #[serde(default)]
+    pub view_state: Option<Value>,
+}
+
+impl Bundle {
+    /// Sections the bundle has settings for
+    pub fn sections(&self) -> Vec<Section> {
+        Section::ALL
+            .into_iter()
+            .filter(|section| match section {
+                Section::ViewState => self.view_state.is_some(),
+                _ => section
+                    .config_keys()
+                    .iter()
+                    .any(|key| self.config.contains_key(*key)),
+            })
+            .collect()
+    }
+}
+
+/// Bundle `config` and `view_state`, keeping `sections`
+pub fn collect(config: &Config, view_state: Option<Value>, sections: &[Section]) -> Bundle {
+    let all = match serde_json::to_value(config) {
+        Ok(Value::Object(all)) => all,
+        _ => Map::new(),
+    };
+    let config = sections
+        .iter()
+        .flat_map(|section| section.config_keys())
+        .filter_map(|key| Some((key.to_string(), all.get(*key)?.clone())))
+        .collect();
+    Bundle {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        config,
+        view_state: view_state.filter(|_| sections.contains(&Section::ViewState)),
+    }
+}
+
+/// Put the `sections` of `bundle` into `config` (those it has). The view
+/// state isn't in the config; it's returned when picked.
+pub fn apply(
+    config: &mut Config,
+    bundle: &Bundle,
+    sections: &[Section],
+) -> Result<Option<Value>, BundleError> {
+    let mut merged = match serde_json::to_value(&*config)? {
+        Value::Object(all) => all,
+        _ => Map::new(),
+    };
+    let present = bundle.sections();
+    for section in sections.iter().filter(|s| present.contains(s)) {
+        let mut candidate = merged.clone();
+        for key in section.config_keys() {
+            match bundle.config.get(*key) {
+                Some(value) => candidate.insert(key.to_string(), value.clone()),
+                // Plain-text credentials come without a sealed copy
+                None => candidate.remove(*key),
+            };
+        }
+        // Checked one section at a time, to say which one is broken
+        serde_json::from_value::<Config>(Value::Object(candidate.clone()))
+            .map_err(|e| BundleError::InvalidSection(section.label(), e))?;
+        merged = candidate;
+    }
+    *config = serde_json::from_value(Value::Object(merged))?;
+    Ok(bundle
+        .view_state
+        .clone()
+        .filter(|_| sections.contains(&Section::ViewState)))
+}
+
+/// Bring a bundle written by an older version up to [`VERSION`]. Each
+/// change to the format adds a step here, so old bundles keep importing.
+pub fn migrate(value: Value) -> Result<Value, BundleError> {
+    if value.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err(BundleError::NotABundle(format!(
+            "its format isn't \"{}\"",
+            FORMAT
+        )));
+    }
+    let version = value
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| BundleError::NotABundle("it has no version".to_string()))?
+        as u32;
+    if version > VERSION {
+        return Err(BundleError::TooNew(version));
+    }
+    // Version 1 is the first; nothing to bring forward yet
+    Ok(value)
+}
+
+/// Parse a bundle, migrating it from an older version if need be
+pub fn parse(json: &str) -> Result<Bundle, BundleError> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| BundleError::NotABundle(e.to_string()))?;
+    let mut value = migrate(value)?;
+    value["version"] = VERSION.into();
+    serde_json::from_value(value).map_err(|e| BundleError::NotABundle(e.to_string()))
+}
+
+fn view_state_path() -> PathBuf {
+    profile::data_path(profile::VIEW_STATE_FILE)
+}
+
+/// Export the active profile's `sections` to `path`
+pub fn export(path: &Path, sections: &[Section]) -> Result<Bundle, BundleError> {
+    let config = config::load();
+    // Encrypted credentials go out as they're stored: sealed
+    let config = match crate::secrets::for_disk(&config) {
+        Ok(Some(disk)) => disk,
+        _ => config,
+    };
+    let view_state = std::fs::read_to_string(view_state_path())
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok());
+    let bundle = collect(&config, view_state, sections);
+    let json = serde_json::to_string_pretty(&bundle)?;
+    std::fs::write(path, json).map_err(|e| BundleError::Write(path.to_path_buf(), e))?;
+    Ok(bundle)
+}
+
+/// Read the bundle at `path`
+pub fn read(path: &Path) -> Result<Bundle, BundleError> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| BundleError::Read(path.to_path_buf(), e))?;
+    parse(&json)
+}
+
+/// Import the `sections` of `bundle` into the active profile
+pub fn import(bundle: &Bundle, sections: &[Section]) -> Result<(), BundleError> {
+    let mut config = config::load();
+    let view_state = apply(&mut config, bundle, sections)?;
+    config::save(&config)?;
+    if let Some(view_state) = view_state {
+        let path = view_state_path();
+        let json = serde_json::to_string_pretty(&view_state)?;
+        std::fs::write(&path, json).map_err(|e| BundleError::Write(path, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_and_apply_picked_sections() {
+        let mut source = Config::default();
+        source.audio.fade_ms = 40;
+        source.library.paths = vec![PathBuf::from("/music")];
+        source.credentials.acoustid_api_key = Some("key".to_string());
+        let view = serde_json::json!({ "last_pane": "Stats" });
+
+        let bundle = collect(
+            &source,
+            Some(view.clone()),
+            &[Section::Audio, Section::Library, Section::ViewState],
+        );
+        assert_eq!(
+            bundle.sections(),
+            [Section::Audio, Section::Library, Section::ViewState]
+        );
+        assert!(!bundle.config.contains_key("credentials"));
+
+        // Round trip through the file format
+        let bundle = parse(&serde_json::to_string(&bundle).unwrap()).unwrap();
+        let mut target = Config::default();
+        target.library.paths = vec![PathBuf::from("/here")];
+        let applied = apply(&mut target, &bundle, &[Section::Audio]).unwrap();
+        assert_eq!(target.audio.fade_ms, 40);
+        assert_eq!(target.library.paths, [PathBuf::from("/here")], "not picked");
+        assert_eq!(applied, None);
+        assert_eq!(
+            apply(&mut target, &bundle, &[Section::ViewState]).unwrap(),
+            Some(view)
+        );
+    }
+
+    #[test]
+    fn test_parse_checks_format_and_version() {
+        let bundle = |version: u32| {
+            serde_json::json!({
+                "format": FORMAT,
+                "version": version,
+                "app_version": "0.1.0",
+                "exported_at": "2025-06-01T12:00:00Z",
+                // Settings added since are filled in with defaults
+                "config": { "audio": { "fade_ms": 25 } },
+            })
+            .to_string()
+        };
+        let old = parse(&bundle(1)).unwrap();
+        let mut config = Config::default();
+        apply(&mut config, &old, &[Section::Audio]).unwrap();
+        assert_eq!(config.audio.fade_ms, 25);
+        assert_eq!(config.audio.volume, 1.0);
+
+        assert!(matches!(
+            parse(&bundle(VERSION + 1)),
+            Err(BundleError::TooNew(v)) if v == VERSION + 1
+        ));
+        assert!(matches!(
+            parse(r#"{"version": 1}"#),
+            Err(BundleError::NotABundle(_))
+        ));
+
+        let broken = serde_json::json!({
+            "format": FORMAT, "version": 1, "app_version": "0.1.0",
+            "exported_at": "2025-06-01T12:00:00Z",
+            "config": { "audio": { "fade_ms": "loud" } },
+        });
+        let broken = parse(&broken.to_string()).unwrap();
+        assert!(matches!(
+            apply(&mut config, &broken, &[Section::Audio]),
+            Err(BundleError::InvalidSection("Audio", _))
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+//! Startup phases and subsystem readiness.
+//!
+//! The window opens before anything slow has happened. The database, the
+//! audio output, device enumeration, the watcher and diagnostics start in
+//! the background once there is something on screen, and report in as they
+//! become ready. Every step is timed from launch and logged at debug level
+//! under the `startup` target (`RUST_LOG=startup=debug`), ending with a
+//! breakdown of the whole startup once the last subsystem is up.
+
+use parking_lot::Mutex;
+use std::fmt::Write as _;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+/// When the process started, as far as the timeline is concerned
+static LAUNCHED: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// Every phase timed so far, in the order they finished
+static TIMELINE: Mutex<Timeline> = Mutex::new(Timeline::new());
+
+/// Pin the launch time. Called first thing in `main`.
+pub fn begin() {
+    LazyLock::force(&LAUNCHED);
+}
+
+/// Time since launch
+pub fn elapsed() -> Duration {
+    LAUNCHED.elapsed()
+}
+
+/// Run `f` as the named phase
+pub fn phase<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let value = f();
+    record(name, started);
+    value
+}
+
+/// Record a phase that began at `started` and has just finished
+pub fn record(name: &'static str, started: Instant) {
+    let took = started.elapsed();
+    let at = elapsed();
+    tracing::debug!(
+        target: "startup",
+        phase = name,
+        took_ms = ms(took),
+        at_ms = ms(at),
+        "Startup phase finished"
+    );
+    TIMELINE.lock().push(name, at, took);
+}
+
+/// The phases recorded so far
+pub fn breakdown() -> String {
+    TIMELINE.lock().to_string()
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Timed phases, kept for the breakdown
+#[derive(Debug, Default)]
+struct Timeline {
+    /// Phase name, time since launch it finished at, and how long it took
+    phases: Vec<(&'static str, Duration, Duration)>,
+}
+
+impl Timeline {
+    const fn new() -> Self {
+        Self { phases: Vec::new() }
+    }
+
+    fn push(&mut self, name: &'static str, at: Duration, took: Duration) {
+        self.phases.push((name, at, took));
+    }
+}
+
+impl std::fmt::Display for Timeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self.phases.iter().map(|p| p.0.len()).max().unwrap_or(0);
+        let mut out = String::new();
+        for (name, at, took) in &self.phases {
+            let _ = writeln!(
+                out,
+                "  {:<width$}  {:>8.1}ms  (at {:.1}ms)",
+                name,
+                ms(*took),
+                ms(*at),
+            );
+        }
+        f.write_str(out.trim_end())
+    }
+}
+
+/// A part of the app that starts in the background
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    Database,
+    Library,
+    Audio,
+    Devices,
+    Watcher,
+    Diagnostics,
+}
+
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+impl Subsystem {
+    pub const ALL: [Subsystem; 6] = [
+        Subsystem::Database,
+        Subsystem::Library,
+        Subsystem::Audio,
+        Subsystem::Devices,
+        Subsystem::Watcher,
+        Subsystem::Diagnostics,
+    ];
+
+    /// Name shown while it's still starting
+    pub fn label(self) -> &'static str {
+        match self {
+            Subsystem::Database => "database",
+            Subsystem::Library => "library",
+            Subsystem::Audio => "audio",
+            Subsystem::Devices => "devices",
+            Subsystem::Watcher => "watcher",
+            Subsystem::Diagnostics => "diagnostics",
+        }
+    }
+
+    /// Phase name in the timeline
+    fn phase(self) -> &'static str {
+        match self {
+            Subsystem::Database => "database ready",
+            Subsystem::Library => "library ready",
+            Subsystem::Audio => "audio ready",
+            Subsystem::Devices => "devices ready",
+            Subsystem::Watcher => "watcher ready",
+            Subsystem::Diagnostics => "diagnostics ready",
+        }
+    }
+}
+
+/// Which subsystems have reported in since the window opened
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+#[derive(Debug, Clone)]
+pub struct Readiness {
+    since: Instant,
+    ready: Vec<Subsystem>,
+}
+
+#[cfg_attr(not(feature = "gui"), allow(dead_code))]
+impl Readiness {
+    pub fn new() -> Self {
+        Self {
+            since: Instant::now(),
+            ready: Vec::new(),
+        }
+    }
+
+    /// Note that `subsystem` is up. Logs the startup breakdown when it was
+    /// the last one, and returns whether it was.
+    pub fn mark(&mut self, subsystem: Subsystem) -> bool {
+        if self.ready.contains(&subsystem) {
+            return false;
+        }
+        self.ready.push(subsystem);
+        record(subsystem.phase(), self.since);
+        if !self.is_complete() {
+            return false;
+        }
+        tracing::debug!(
+            target: "startup",
+            "Startup complete in {:.1}ms:\n{}",
+            ms(elapsed()),
+            breakdown()
+        );
+        true
+    }
+
+    pub fn is_ready(&self, subsystem: Subsystem) -> bool {
+        self.ready.contains(&subsystem)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pending().is_empty()
+    }
+
+    /// Subsystems still starting, in startup order
+    pub fn pending(&self) -> Vec<Subsystem> {
+        Subsystem::ALL
+            .into_iter()
+            .filter(|s| !self.ready.contains(s))
+            .collect()
+    }
+}
+
+impl Default for Readiness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_completes_once() {
+        let mut readiness = Readiness::new();
+        for subsystem in &Subsystem::ALL[..5] {
+            assert!(!readiness.mark(*subsystem));
+        }
+        assert_eq!(readiness.pending(), vec![Subsystem::Diagnostics]);
+        assert!(readiness.is_ready(Subsystem::Audio));
+        // Reporting twice doesn't count twice
+        assert!(!readiness.mark(Subsystem::Audio));
+
+        assert!(readiness.mark(Subsystem::Diagnostics));
+        assert!(readiness.is_complete());
+        assert!(!readiness.mark(Subsystem::Diagnostics));
+    }
+
+    #[test]
+    fn test_breakdown_lists_phases_in_order() {
+        let mut timeline = Timeline::new();
+        timeline.push("config", Duration::from_millis(3), Duration::from_millis(2));
+        timeline.push(
+            "database",
+            Duration::from_millis(40),
+            Duration::from_micros(36_500),
+        );
+        let text = timeline.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].trim_start().starts_with("config"));
+        assert!(lines[1].contains("36.5ms"));
+        assert!(lines[1].contains("(at 40.0ms)"));
+    }
+}
@@ -0,0 +1,439 @@
+//! Local usage statistics.
+//!
+//! Listening time per day, artist and genre and the most skipped tracks
+//! (from [`crate::history`]), how often identification finds a match, and
+//! how long library scans take.
+//! Everything is recorded in and read from the local database; nothing is
+//! sent anywhere. The Stats pane shows a period of it, and [`export`] saves
+//! that as JSON or CSV.
+//!
+//! Plays from before listening time was recorded count as the whole track.
+//! [`wrapped`] turns a calendar year of it into a year-in-review report.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use music_minder::stats;
+//!
+//! // The last 30 days
+//! let usage = stats::load(&pool, Some(30)).await?;
+//! println!("{} hours listened", usage.listened_secs / 3600);
+//! ```
+
+pub mod wrapped;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::sqlite::SqlitePool;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::history::{self, SkipRate};
+use crate::plan::push_csv_row;
+
+/// Artists, genres and skipped tracks listed per period
+pub const TOP: i64 = 10;
+
+/// Most recent scans listed per period
+pub const SCANS: i64 = 100;
+
+/// Errors that can occur when exporting statistics.
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// How an identification attempt ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifyOutcome {
+    Identified,
+    NoMatch,
+    Error,
+}
+
+impl IdentifyOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdentifyOutcome::Identified => "identified",
+            IdentifyOutcome::NoMatch => "no_match",
+            IdentifyOutcome::Error => "error",
+        }
+    }
+
+    /// The outcome of an identification result
+    pub fn of<T>(result: &Result<T, crate::enrichment::EnrichmentError>) -> Self {
+        match result {
+            Ok(_) => IdentifyOutcome::Identified,
+            Err(crate::enrichment::EnrichmentError::NoMatches) => IdentifyOutcome::NoMatch,
+            Err(_) => IdentifyOutcome::Error,
+        }
+    }
+}
+
+/// Record an identification attempt (best effort)
+pub async fn record_identification(pool: &SqlitePool, outcome: IdentifyOutcome) {
+    let result =
+        sqlx::query("INSERT INTO identification_attempts (attempted_at, outcome) VALUES (?, ?)")
+            .bind(Utc::now().timestamp())
+            .bind(outcome.as_str())
+            .execute(pool)
+            .await;
+    if let Err(e) = result {
+        tracing::warn!("Failed to record identification attempt: {}", e);
+    }
+}
+
+/// Record a finished scan that read `files` files (best effort)
+pub async fn record_scan(
+    pool: &SqlitePool,
+    started_at: DateTime<Utc>,
+    duration: Duration,
+    files: usize,
+) {
+    let result =
+        sqlx::query("INSERT INTO scan_runs (started_at, duration_ms, files) VALUES (?, ?, ?)")
+            .bind(started_at.timestamp())
+            .bind(duration.as_millis() as i64)
+            .bind(files as i64)
+            .execute(pool)
+            .await;
+    if let Err(e) = result {
+        tracing::warn!("Failed to record scan: {}", e);
+    }
+}
+
+/// Listening time for one day, artist or genre
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct Listening {
+    /// Local date (YYYY-MM-DD), artist or genre
+    pub name: String,
+    pub seconds: i64,
+    pub plays: i64,
+}
+
+/// Identification attempts by outcome
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct IdentificationRates {
+    pub identified: i64,
+    pub no_match: i64,
+    pub errors: i64,
+}
+
+impl IdentificationRates {
+    pub fn total(&self) -> i64 {
+        self.identified + self.no_match + self.errors
+    }
+
+    /// Share of attempts that found a match, if there were any
+    pub fn success_rate(&self) -> Option<f32> {
+        let total = self.total();
+        (total > 0).then(|| self.identified as f32 / total as f32)
+    }
+}
+
+/// One finished scan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanRun {
+    /// RFC 3339
+    pub started_at: String,
+    pub duration_ms: i64,
+    pub files: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct ScanRow {
+    started_at: i64,
+    duration_ms: i64,
+    files: i64,
+}
+
+/// Usage over a period
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageStats {
+    /// Last N days, or all time
+    pub days: Option<u32>,
+    pub listened_secs: i64,
+    pub plays: i64,
+    /// Oldest day first
+    pub by_day: Vec<Listening>,
+    /// Most listened first, at most [`TOP`]
+    pub by_artist: Vec<Listening>,
+    /// Most listened first, at most [`TOP`]
+    pub by_genre: Vec<Listening>,
+    /// Highest skip rate first, at most [`TOP`]
+    pub most_skipped: Vec<SkipRate>,
+    pub identification: IdentificationRates,
+    /// Oldest first, the last [`SCANS`]
+    pub scans: Vec<ScanRun>,
+}
+
+/// Seconds a play counts for
+const LISTENED: &str = "COALESCE(h.listened_secs, t.duration, 0)";
+
+/// Listening grouped by `name` for plays between two bound timestamps
+fn listening_query(name: &str, order: &str, limit: i64) -> String {
+    format!(
+        "SELECT {name} AS name, SUM({LISTENED}) AS seconds, COUNT(*) AS plays
+         FROM play_history h
+         JOIN tracks t ON h.track_id = t.id
+         LEFT JOIN artists a ON t.artist_id = a.id
+         WHERE h.played_at >= ? AND h.played_at < ?
+         GROUP BY 1
+         ORDER BY {order}
+         LIMIT {limit}"
+    )
+}
+
+/// Top artists by listening time
+const BY_ARTIST: [&str; 2] = ["COALESCE(a.name, 'Unknown Artist')", "seconds DESC, name"];
+
+/// Top genres by listening time
+const BY_GENRE: [&str; 2] = [
+    "COALESCE(NULLIF(TRIM(h.genre), ''), 'Unknown')",
+    "seconds DESC, name",
+];
+
+/// Usage in the last `days` days, or all time
+pub async fn load(pool: &SqlitePool, days: Option<u32>) -> sqlx::Result<UsageStats> {
+    let since = days.map_or(0, |days| {
+        Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60
+    });
+    let by_day: Vec<Listening> = sqlx::query_as(&listening_query(
+        "date(h.played_at, 'unixepoch', 'localtime')",
+        "name",
+        -1,
+    ))
+    .bind(since)
+    .bind(i64::MAX)
+    .fetch_all(pool)
+    .await?;
+    let [name, order] = BY_ARTIST;
+    let by_artist = sqlx::query_as(&listening_query(name, order, TOP))
+        .bind(since)
+        .bind(i64::MAX)
+        .fetch_all(pool)
+        .await?;
+    let [name, order] = BY_GENRE;
+    let by_genre = sqlx::query_as(&listening_query(name, order, TOP))
+        .bind(since)
+        .bind(i64::MAX)
+        .fetch_all(pool)
+        .await?;
+    let most_skipped = history::skip_rates(pool, since, TOP).await?;
+
+    let outcomes: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT outcome, COUNT(*) FROM identification_attempts
+         WHERE attempted_at >= ? GROUP BY outcome",
+    )
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    let mut identification = IdentificationRates::default();
+    for (outcome, count) in outcomes {
+        match outcome.as_str() {
+            "identified" => identification.identified = count,
+            "no_match" => identification.no_match = count,
+            _ => identification.errors += count,
+        }
+    }
+
+    let rows: Vec<ScanRow> = sqlx::query_as(
+        "SELECT started_at, duration_ms, files FROM scan_runs
+         WHERE started_at >= ? ORDER BY started_at DESC, id DESC LIMIT ?",
+    )
+    .bind(since)
+    .bind(SCANS)
+    .fetch_all(pool)
+    .await?;
+    let scans = rows
+        .into_iter()
+        .rev()
+        .map(|row| ScanRun {
+            started_at: DateTime::from_timestamp(row.started_at, 0)
+                .unwrap_or_default()
+                .to_rfc3339(),
+            duration_ms: row.duration_ms,
+            files: row.files,
+        })
+        .collect();
+
+    Ok(UsageStats {
+        days,
+        listened_secs: by_day.iter().map(|d| d.seconds).sum(),
+        plays: by_day.iter().map(|d| d.plays).sum(),
+        by_day,
+        by_artist,
+        by_genre,
+        most_skipped,
+        identification,
+        scans,
+    })
+}
+
+/// Format as CSV, one row per figure: `section,name,value,count`.
+///
+/// Listening rows (`day`, `artist`, `genre`) hold seconds and plays,
+/// `skipped` rows a track's skips and plays, `identification` rows an
+/// outcome's attempts, and `scan` rows the start time, milliseconds taken
+/// and files read.
+pub fn to_csv(stats: &UsageStats) -> String {
+    let mut out = String::from("section,name,value,count\n");
+    push_csv_row(
+        &mut out,
+        &[
+            "total",
+            "listened",
+            &stats.listened_secs.to_string(),
+            &stats.plays.to_string(),
+        ],
+    );
+    for (section, rows) in [
+        ("day", &stats.by_day),
+        ("artist", &stats.by_artist),
+        ("genre", &stats.by_genre),
+    ] {
+        for row in rows {
+            push_csv_row(
+                &mut out,
+                &[
+                    section,
+                    &row.name,
+                    &row.seconds.to_string(),
+                    &row.plays.to_string(),
+                ],
+            );
+        }
+    }
+    for track in &stats.most_skipped {
+        push_csv_row(
+            &mut out,
+            &[
+                "skipped",
+                &format!("{} - {}", track.artist, track.title),
+                &track.skips.to_string(),
+                &track.plays.to_string(),
+            ],
+        );
+    }
+    let rates = &stats.identification;
+    for (outcome, count) in [
+        (IdentifyOutcome::Identified, rates.identified),
+        (IdentifyOutcome::NoMatch, rates.no_match),
+        (IdentifyOutcome::Error, rates.errors),
+    ] {
+        push_csv_row(
+            &mut out,
+            &["identification", outcome.as_str(), &count.to_string(), ""],
+        );
+    }
+    for scan in &stats.scans {
+        push_csv_row(
+            &mut out,
+            &[
+                "scan",
+                &scan.started_at,
+                &scan.duration_ms.to_string(),
+                &scan.files.to_string(),
+            ],
+        );
+    }
+    out
+}
+
+/// Save statistics to a file, choosing CSV or JSON from the extension.
+pub fn export(stats: &UsageStats, path: &Path) -> Result<(), StatsError> {
+    let is_csv = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+    let contents = if is_csv {
+        to_csv(stats)
+    } else {
+        serde_json::to_string_pretty(stats)?
+    };
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::TrackMetadata;
+    use crate::test_utils::temp_db;
+    use crate::{db, history};
+
+    #[tokio::test]
+    async fn test_usage_stats() {
+        let (pool, _dir) = temp_db().await;
+        for (title, artist, path) in [("One", "Alpha", "/m/1.flac"), ("Two", "Beta", "/m/2.flac")] {
+            let artist_id = db::get_or_create_artist(&pool, artist).await.unwrap();
+            let meta = TrackMetadata {
+                title: title.to_string(),
+                artist: artist.to_string(),
+                album: "Album".to_string(),
+                duration: 200,
+                track_number: None,
+            };
+            db::insert_track(&pool, &meta, path, Some(artist_id), None)
+                .await
+                .unwrap();
+        }
+        let one = Path::new("/m/1.flac");
+        history::record_play(&pool, one).await.unwrap();
+        history::record_listened(&pool, one, Duration::from_secs(50), Some("Jazz"))
+            .await
+            .unwrap();
+        history::record_play(&pool, one).await.unwrap();
+        history::record_listened(&pool, one, Duration::from_secs(60), Some("Jazz"))
+            .await
+            .unwrap();
+        // Not stopped yet: counts as the whole track
+        history::record_play(&pool, Path::new("/m/2.flac"))
+            .await
+            .unwrap();
+        history::record_play(&pool, one).await.unwrap();
+        history::record_skip(&pool, one).await.unwrap();
+        history::record_listened(&pool, one, Duration::from_secs(10), None)
+            .await
+            .unwrap();
+
+        for outcome in [
+            IdentifyOutcome::Identified,
+            IdentifyOutcome::Identified,
+            IdentifyOutcome::NoMatch,
+            IdentifyOutcome::Error,
+        ] {
+            record_identification(&pool, outcome).await;
+        }
+        record_scan(&pool, Utc::now(), Duration::from_millis(1500), 42).await;
+
+        let stats = load(&pool, Some(7)).await.unwrap();
+        assert_eq!(stats.listened_secs, 320);
+        assert_eq!(stats.plays, 4);
+        assert_eq!(stats.by_day.len(), 1);
+        let artists: Vec<(&str, i64)> = stats
+            .by_artist
+            .iter()
+            .map(|a| (a.name.as_str(), a.seconds))
+            .collect();
+        assert_eq!(artists, [("Beta", 200), ("Alpha", 120)]);
+        assert_eq!(stats.by_genre[0].name, "Unknown");
+        assert_eq!(stats.by_genre[1].plays, 2);
+        assert_eq!(stats.most_skipped.len(), 1);
+        assert_eq!(stats.most_skipped[0].skips, 1);
+        assert_eq!(stats.identification.success_rate(), Some(0.5));
+        assert_eq!(stats.scans[0].files, 42);
+
+        let csv = to_csv(&stats);
+        assert!(csv.contains("artist,Alpha,120,3\n"));
+        assert!(csv.contains("skipped,Alpha - One,1,3\n"));
+        assert!(csv.contains("identification,no_match,1,\n"));
+        assert!(csv.contains(",1500,42\n"));
+    }
+}
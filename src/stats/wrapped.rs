@@ -0,0 +1,521 @@
+//! Year in review.
+//!
+//! A "Wrapped" style summary of one calendar year (local time): listening
+//! hours, top artists, tracks and genres, artists heard for the first time
+//! and how the library grew. Shown in the Stats pane and saved as a
+//! standalone HTML page, an SVG image to share, or JSON.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use chrono::{Datelike, Local, NaiveDate};
+use serde::Serialize;
+use sqlx::sqlite::SqlitePool;
+
+use super::{BY_ARTIST, BY_GENRE, LISTENED, Listening, StatsError, TOP, listening_query};
+use crate::enrichment::report::escape_html;
+
+/// Entries per list in the SVG image
+const IMAGE_TOP: usize = 5;
+
+/// Listening time for one track
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct TrackListening {
+    pub title: String,
+    pub artist: String,
+    pub seconds: i64,
+    pub plays: i64,
+}
+
+/// One year of listening and library changes
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Wrapped {
+    pub year: i32,
+    pub listened_secs: i64,
+    pub plays: i64,
+    /// Different tracks played
+    pub tracks_played: i64,
+    /// Most listened first, at most [`TOP`]
+    pub top_artists: Vec<Listening>,
+    /// Most played first, at most [`TOP`]
+    pub top_tracks: Vec<TrackListening>,
+    /// Most listened first, at most [`TOP`]
+    pub top_genres: Vec<Listening>,
+    /// Artists first played this year
+    pub discoveries: i64,
+    /// The most listened of them, at most [`TOP`]
+    pub top_discoveries: Vec<Listening>,
+    /// Local date (YYYY-MM-DD) with the most listening
+    pub busiest_day: Option<Listening>,
+    pub tracks_added: i64,
+    pub tracks_removed: i64,
+    /// Tracks in the library now
+    pub library_tracks: i64,
+}
+
+impl Wrapped {
+    /// Whether anything was played this year
+    pub fn is_empty(&self) -> bool {
+        self.plays == 0
+    }
+}
+
+/// Unix timestamps of the start of `year` and of the next, in local time
+fn year_bounds(year: i32) -> (i64, i64) {
+    let start = |year: i32| {
+        NaiveDate::from_ymd_opt(year, 1, 1)
+            .and_then(|day| day.and_hms_opt(0, 0, 0))
+            .and_then(|midnight| midnight.and_local_timezone(Local).earliest())
+            .map_or(0, |at| at.timestamp())
+    };
+    (start(year), start(year + 1))
+}
+
+/// Years with plays, newest first. Always includes this year.
+pub async fn years(pool: &SqlitePool) -> sqlx::Result<Vec<i32>> {
+    let played: Vec<(String,)> = sqlx::query_as(
+        "SELECT DISTINCT strftime('%Y', played_at, 'unixepoch', 'localtime')
+         FROM play_history ORDER BY 1 DESC",
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut years: Vec<i32> = played
+        .into_iter()
+        .filter_map(|(year,)| year.parse().ok())
+        .collect();
+    let this_year = Local::now().year();
+    if !years.contains(&this_year) {
+        years.insert(0, this_year);
+    }
+    Ok(years)
+}
+
+/// The review of `year`
+pub async fn load(pool: &SqlitePool, year: i32) -> sqlx::Result<Wrapped> {
+    let (start, end) = year_bounds(year);
+
+    let (listened_secs, plays, tracks_played): (i64, i64, i64) = sqlx::query_as(&format!(
+        "SELECT COALESCE(SUM({LISTENED}), 0), COUNT(*), COUNT(DISTINCT h.track_id)
+         FROM play_history h
+         JOIN tracks t ON h.track_id = t.id
+         WHERE h.played_at >= ? AND h.played_at < ?"
+    ))
+    .bind(start)
+    .bind(end)
+    .fetch_one(pool)
+    .await?;
+
+    let [name, order] = BY_ARTIST;
+    let top_artists = sqlx::query_as(&listening_query(name, order, TOP))
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await?;
+    let [name, order] = BY_GENRE;
+    let top_genres = sqlx::query_as(&listening_query(name, order, TOP))
+        .bind(start)
+        .bind(end)
+        .fetch_all(pool)
+        .await?;
+    let busiest_day = sqlx::query_as(&listening_query(
+        "date(h.played_at, 'unixepoch', 'localtime')",
+        "seconds DESC, name",
+        1,
+    ))
+    .bind(start)
+    .bind(end)
+    .fetch_optional(pool)
+    .await?;
+
+    let top_tracks = sqlx::query_as(&format!(
+        "SELECT t.title AS title, COALESCE(a.name, 'Unknown Artist') AS artist,
+                SUM({LISTENED}) AS seconds, COUNT(*) AS plays
+         FROM play_history h
+         JOIN tracks t ON h.track_id = t.id
+         LEFT JOIN artists a ON t.artist_id = a.id
+         WHERE h.played_at >= ? AND h.played_at < ?
+         GROUP BY t.id
+         ORDER BY plays DESC, seconds DESC, title
+         LIMIT {TOP}"
+    ))
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+
+    // Artists whose first play ever falls in the year
+    let mut discovered: Vec<Listening> = sqlx::query_as(&format!(
+        "WITH first_plays AS (
+             SELECT t.artist_id, MIN(h.played_at) AS first_played
+             FROM play_history h
+             JOIN tracks t ON h.track_id = t.id
+             WHERE t.artist_id IS NOT NULL
+             GROUP BY t.artist_id
+         )
+         SELECT a.name AS name, SUM({LISTENED}) AS seconds, COUNT(*) AS plays
+         FROM play_history h
+         JOIN tracks t ON h.track_id = t.id
+         JOIN artists a ON t.artist_id = a.id
+         JOIN first_plays f ON f.artist_id = t.artist_id
+         WHERE f.first_played >= ?1 AND f.first_played < ?2
+           AND h.played_at >= ?1 AND h.played_at < ?2
+         GROUP BY a.id
+         ORDER BY seconds DESC, name"
+    ))
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+    let discoveries = discovered.len() as i64;
+    discovered.truncate(TOP as usize);
+
+    let (tracks_added, tracks_removed): (i64, i64) = sqlx::query_as(
+        "SELECT COALESCE(SUM(kind = 'track_added'), 0), COALESCE(SUM(kind = 'track_removed'), 0)
+         FROM activity_log
+         WHERE timestamp >= ? AND timestamp < ?",
+    )
+    .bind(start)
+    .bind(end)
+    .fetch_one(pool)
+    .await?;
+    let (library_tracks,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tracks")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(Wrapped {
+        year,
+        listened_secs,
+        plays,
+        tracks_played,
+        top_artists,
+        top_tracks,
+        top_genres,
+        discoveries,
+        top_discoveries: discovered,
+        busiest_day,
+        tracks_added,
+        tracks_removed,
+        library_tracks,
+    })
+}
+
+/// Listening time in words: "1,234 hours", "45 minutes"
+pub fn listening_time(secs: i64) -> String {
+    let hours = secs / 3600;
+    if hours > 0 {
+        format!("{} hour{}", group_thousands(hours), plural(hours))
+    } else {
+        let minutes = secs / 60;
+        format!("{} minute{}", minutes, plural(minutes))
+    }
+}
+
+/// The headline lines, shared by every format
+pub fn highlights(wrapped: &Wrapped) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{} listened over {} plays of {} tracks",
+        listening_time(wrapped.listened_secs),
+        group_thousands(wrapped.plays),
+        group_thousands(wrapped.tracks_played)
+    )];
+    if let Some(artist) = wrapped.top_artists.first() {
+        lines.push(format!("Top artist: {}", artist.name));
+    }
+    if let Some(genre) = wrapped.top_genres.first() {
+        lines.push(format!("Top genre: {}", genre.name));
+    }
+    if wrapped.discoveries > 0 {
+        lines.push(format!(
+            "{} new artist{} discovered",
+            wrapped.discoveries,
+            plural(wrapped.discoveries)
+        ));
+    }
+    if let Some(ref day) = wrapped.busiest_day {
+        lines.push(format!(
+            "Busiest day: {} ({})",
+            day.name,
+            listening_time(day.seconds)
+        ));
+    }
+    lines.push(format!(
+        "Library: {} tracks added, {} removed, {} now",
+        group_thousands(wrapped.tracks_added),
+        group_thousands(wrapped.tracks_removed),
+        group_thousands(wrapped.library_tracks)
+    ));
+    lines
+}
+
+fn plural(count: i64) -> &'static str {
+    if count == 1 { "" } else { "s" }
+}
+
+/// "12345" as "12,345"
+fn group_thousands(value: i64) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut out = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    if value < 0 {
+        out.insert(0, '-');
+    }
+    out
+}
+
+/// A standalone page: the highlights, then each top list
+pub fn to_html(wrapped: &Wrapped) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Music Minder {year} in Review</title>\n<style>\n\
+         body {{ font-family: sans-serif; margin: 0; padding: 2em; color: #f4f4f8; \
+         background: linear-gradient(160deg, #2b1a5e, #0f3b57); min-height: 100vh; }}\n\
+         h1 {{ font-size: 3em; margin-bottom: 0.2em; }}\n\
+         .highlights {{ font-size: 1.3em; line-height: 1.6; }}\n\
+         .lists {{ display: flex; flex-wrap: wrap; gap: 1.5em; }}\n\
+         section {{ background: rgba(255, 255, 255, 0.08); border-radius: 12px; \
+         padding: 1em 1.5em; min-width: 16em; }}\n\
+         ol {{ padding-left: 1.4em; }} li {{ margin: 0.3em 0; }}\n\
+         .muted {{ color: #b8b8c8; font-size: 0.9em; }}\n\
+         </style>\n</head>\n<body>\n<h1>{year} in Review</h1>\n<ul class=\"highlights\">\n",
+        year = wrapped.year
+    );
+    for line in highlights(wrapped) {
+        let _ = writeln!(out, "<li>{}</li>", escape_html(&line));
+    }
+    out.push_str("</ul>\n<div class=\"lists\">\n");
+
+    let list = |out: &mut String, title: &str, rows: Vec<(String, String)>| {
+        if rows.is_empty() {
+            return;
+        }
+        let _ = writeln!(out, "<section>\n<h2>{}</h2>\n<ol>", title);
+        for (name, detail) in rows {
+            let _ = writeln!(
+                out,
+                "<li>{} <span class=\"muted\">{}</span></li>",
+                escape_html(&name),
+                escape_html(&detail)
+            );
+        }
+        out.push_str("</ol>\n</section>\n");
+    };
+    let by_time = |rows: &[Listening]| {
+        rows.iter()
+            .map(|row| (row.name.clone(), listening_time(row.seconds)))
+            .collect()
+    };
+    list(&mut out, "Top Artists", by_time(&wrapped.top_artists));
+    list(
+        &mut out,
+        "Top Tracks",
+        wrapped
+            .top_tracks
+            .iter()
+            .map(|track| {
+                (
+                    format!("{} - {}", track.title, track.artist),
+                    format!("{} plays", track.plays),
+                )
+            })
+            .collect(),
+    );
+    list(&mut out, "Top Genres", by_time(&wrapped.top_genres));
+    list(
+        &mut out,
+        "New Discoveries",
+        by_time(&wrapped.top_discoveries),
+    );
+    out.push_str("</div>\n<p class=\"muted\">Made by Music Minder from this computer's play history.</p>\n</body>\n</html>\n");
+    out
+}
+
+/// A 1080×1350 image card for sharing
+pub fn to_svg(wrapped: &Wrapped) -> String {
+    let mut out = String::from(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"1080\" height=\"1350\" \
+         viewBox=\"0 0 1080 1350\" font-family=\"sans-serif\">\n\
+         <defs><linearGradient id=\"bg\" x1=\"0\" y1=\"0\" x2=\"1\" y2=\"1\">\
+         <stop offset=\"0\" stop-color=\"#2b1a5e\"/><stop offset=\"1\" stop-color=\"#0f3b57\"/>\
+         </linearGradient></defs>\n\
+         <rect width=\"1080\" height=\"1350\" fill=\"url(#bg)\"/>\n",
+    );
+    let mut text = |x: u32, y: u32, size: u32, weight: &str, fill: &str, content: &str| {
+        let _ = writeln!(
+            out,
+            "<text x=\"{x}\" y=\"{y}\" font-size=\"{size}\" font-weight=\"{weight}\" \
+             fill=\"{fill}\">{}</text>",
+            escape_html(content)
+        );
+    };
+    text(80, 170, 110, "bold", "#ffffff", &wrapped.year.to_string());
+    text(80, 240, 48, "normal", "#d8d0ff", "in Review");
+    text(
+        80,
+        380,
+        84,
+        "bold",
+        "#ffffff",
+        &listening_time(wrapped.listened_secs),
+    );
+    text(
+        80,
+        440,
+        36,
+        "normal",
+        "#b8b8c8",
+        &format!(
+            "{} plays · {} new artists",
+            group_thousands(wrapped.plays),
+            wrapped.discoveries
+        ),
+    );
+
+    let columns: [(u32, &str, Vec<String>); 2] = [
+        (
+            80,
+            "Top Artists",
+            wrapped
+                .top_artists
+                .iter()
+                .map(|artist| artist.name.clone())
+                .collect(),
+        ),
+        (
+            560,
+            "Top Tracks",
+            wrapped
+                .top_tracks
+                .iter()
+                .map(|track| track.title.clone())
+                .collect(),
+        ),
+    ];
+    for (x, title, names) in columns {
+        text(x, 560, 40, "bold", "#d8d0ff", title);
+        for (i, name) in names.iter().take(IMAGE_TOP).enumerate() {
+            let line = format!("{}. {}", i + 1, ellipsize(name, 22));
+            text(x, 630 + i as u32 * 62, 36, "normal", "#ffffff", &line);
+        }
+    }
+    if let Some(genre) = wrapped.top_genres.first() {
+        text(80, 1060, 40, "bold", "#d8d0ff", "Top Genre");
+        text(80, 1120, 52, "bold", "#ffffff", &ellipsize(&genre.name, 30));
+    }
+    text(
+        80,
+        1270,
+        28,
+        "normal",
+        "#b8b8c8",
+        &format!(
+            "Library: +{} tracks · {} in total · Music Minder",
+            group_thousands(wrapped.tracks_added),
+            group_thousands(wrapped.library_tracks)
+        ),
+    );
+    out.push_str("</svg>\n");
+    out
+}
+
+/// At most `max` characters, with "…" when cut
+fn ellipsize(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(max - 1).collect();
+        format!("{}…", cut.trim_end())
+    }
+}
+
+/// Save the review, choosing HTML, SVG or JSON from the extension.
+pub fn export(wrapped: &Wrapped, path: &Path) -> Result<(), StatsError> {
+    let contents = match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .as_deref()
+    {
+        Some("html" | "htm") => to_html(wrapped),
+        Some("svg") => to_svg(wrapped),
+        _ => serde_json::to_string_pretty(wrapped)?,
+    };
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::TrackMetadata;
+    use crate::test_utils::temp_db;
+    use crate::{db, history};
+
+    async fn play_at(pool: &SqlitePool, path: &str, played_at: i64) {
+        history::record_play(pool, Path::new(path)).await.unwrap();
+        sqlx::query(
+            "UPDATE play_history SET played_at = ? WHERE id = (SELECT MAX(id) FROM play_history)",
+        )
+        .bind(played_at)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_year_in_review() {
+        let (pool, _dir) = temp_db().await;
+        for (title, artist, path) in [
+            ("Old Song", "Alpha", "/m/1.flac"),
+            ("New Song", "Beta", "/m/2.flac"),
+        ] {
+            let artist_id = db::get_or_create_artist(&pool, artist).await.unwrap();
+            let meta = TrackMetadata {
+                title: title.to_string(),
+                artist: artist.to_string(),
+                album: "Album".to_string(),
+                duration: 180,
+                track_number: None,
+            };
+            db::insert_track(&pool, &meta, path, Some(artist_id), None)
+                .await
+                .unwrap();
+        }
+        let (last_year, _) = year_bounds(2025);
+        let (this_year, _) = year_bounds(2026);
+        // Alpha was heard before 2026, Beta is new
+        play_at(&pool, "/m/1.flac", last_year + 3600).await;
+        play_at(&pool, "/m/1.flac", this_year + 3600).await;
+        play_at(&pool, "/m/2.flac", this_year + 7200).await;
+        play_at(&pool, "/m/2.flac", this_year + 86_400 * 40).await;
+
+        let wrapped = load(&pool, 2026).await.unwrap();
+        assert_eq!(wrapped.plays, 3);
+        assert_eq!(wrapped.tracks_played, 2);
+        assert_eq!(wrapped.listened_secs, 540);
+        assert_eq!(wrapped.top_artists[0].name, "Beta");
+        assert_eq!(wrapped.top_tracks[0].title, "New Song");
+        assert_eq!(wrapped.top_tracks[0].plays, 2);
+        assert_eq!(wrapped.discoveries, 1);
+        assert_eq!(wrapped.top_discoveries[0].name, "Beta");
+        assert_eq!(wrapped.library_tracks, 2);
+        assert!(years(&pool).await.unwrap().contains(&2025));
+
+        let empty = load(&pool, 2020).await.unwrap();
+        assert!(empty.is_empty());
+        assert!(empty.busiest_day.is_none());
+
+        let html = to_html(&wrapped);
+        assert!(html.contains("<h1>2026 in Review</h1>"));
+        assert!(html.contains("<li>Top artist: Beta</li>"));
+        assert!(to_svg(&wrapped).contains("1. New Song"));
+        assert_eq!(group_thousands(1_234_567), "1,234,567");
+        assert_eq!(listening_time(3 * 3600 + 60), "3 hours");
+        assert_eq!(listening_time(60), "1 minute");
+    }
+}
@@ -0,0 +1,310 @@
+//! Progress reporting and cancellation for long-running operations.
+//!
+//! Scans, organizes, batch identification and maintenance jobs each get a
+//! [`TaskHandle`] from a [`TaskRegistry`]. The operation reports its phase
+//! and counts through the handle and checks [`TaskHandle::is_cancelled`]
+//! between units of work; whoever holds the registry (the app's "Background
+//! tasks" popover, the agent's API) lists the running tasks and cancels them
+//! by id.
+//!
+//! Cancelling only sets a flag: the operation stops at its next check and
+//! keeps whatever it already did.
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Identifies a task within its registry
+pub type TaskId = u64;
+
+/// What kind of operation a task is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskKind {
+    Scan,
+    Organize,
+    Enrichment,
+    Maintenance,
+}
+
+impl TaskKind {
+    /// Stable name for the API and logs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskKind::Scan => "scan",
+            TaskKind::Organize => "organize",
+            TaskKind::Enrichment => "enrichment",
+            TaskKind::Maintenance => "maintenance",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+struct TaskInner {
+    id: TaskId,
+    kind: TaskKind,
+    label: String,
+    phase: Mutex<String>,
+    done: AtomicU64,
+    /// 0 while the total isn't known
+    total: AtomicU64,
+    started: Instant,
+    cancelled: AtomicBool,
+    finished: AtomicBool,
+}
+
+/// Shared handle to one running operation.
+///
+/// Clones refer to the same task. The operation updates it; the registry
+/// and UI read it and set the cancel flag.
+#[derive(Debug, Clone)]
+pub struct TaskHandle(Arc<TaskInner>);
+
+impl TaskHandle {
+    fn new(id: TaskId, kind: TaskKind, label: String) -> Self {
+        Self(Arc::new(TaskInner {
+            id,
+            kind,
+            label,
+            phase: Mutex::new(String::new()),
+            done: AtomicU64::new(0),
+            total: AtomicU64::new(0),
+            started: Instant::now(),
+            cancelled: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+        }))
+    }
+
+    /// A handle not listed in any registry, for callers (the CLI, tests)
+    /// with nothing to show progress in.
+    pub fn detached(kind: TaskKind, label: impl Into<String>) -> Self {
+        Self::new(0, kind, label.into())
+    }
+
+    pub fn id(&self) -> TaskId {
+        self.0.id
+    }
+
+    pub fn kind(&self) -> TaskKind {
+        self.0.kind
+    }
+
+    /// Describe the current step, e.g. "Reading tags"
+    pub fn set_phase(&self, phase: impl Into<String>) {
+        *self.0.phase.lock() = phase.into();
+    }
+
+    /// Set how many units of work there are
+    pub fn set_total(&self, total: u64) {
+        self.0.total.store(total, Ordering::Relaxed);
+    }
+
+    /// Add `n` units to the total, for work found in stages
+    pub fn add_total(&self, n: u64) {
+        self.0.total.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Count `n` more units as done
+    pub fn advance(&self, n: u64) {
+        self.0.done.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Ask the operation to stop
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Mark the operation as over, removing it from its registry
+    pub fn finish(&self) {
+        self.0.finished.store(true, Ordering::Relaxed);
+    }
+
+    /// Snapshot of where the task is
+    pub fn progress(&self) -> Progress {
+        let total = self.0.total.load(Ordering::Relaxed);
+        Progress {
+            id: self.0.id,
+            kind: self.0.kind,
+            label: self.0.label.clone(),
+            phase: self.0.phase.lock().clone(),
+            done: self.0.done.load(Ordering::Relaxed),
+            total: (total > 0).then_some(total),
+            elapsed: self.0.started.elapsed(),
+            cancelled: self.is_cancelled(),
+        }
+    }
+}
+
+/// Point-in-time view of a task.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub id: TaskId,
+    pub kind: TaskKind,
+    pub label: String,
+    pub phase: String,
+    pub done: u64,
+    /// `None` while the amount of work isn't known yet
+    pub total: Option<u64>,
+    pub elapsed: Duration,
+    /// Cancel was requested and the task hasn't stopped yet
+    pub cancelled: bool,
+}
+
+impl Progress {
+    /// Share of the work done, 0.0 to 1.0, when the total is known
+    pub fn fraction(&self) -> Option<f32> {
+        self.total
+            .map(|total| (self.done.min(total) as f64 / total as f64) as f32)
+    }
+
+    /// Time left, extrapolated from the rate so far
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total?;
+        if self.done == 0 {
+            return None;
+        }
+        let left = total.saturating_sub(self.done);
+        Some(self.elapsed.mul_f64(left as f64 / self.done as f64))
+    }
+
+    /// "12 / 340" or just "12" without a total
+    pub fn counts(&self) -> String {
+        match self.total {
+            Some(total) => format!("{} / {}", self.done, total),
+            None => self.done.to_string(),
+        }
+    }
+}
+
+/// Short human form of an ETA: "45s", "3m 20s", "1h 05m"
+pub fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// The running tasks of one process.
+///
+/// Cheap to clone; clones share the same list.
+#[derive(Debug, Clone, Default)]
+pub struct TaskRegistry {
+    tasks: Arc<Mutex<Vec<TaskHandle>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new task and return its handle
+    pub fn start(&self, kind: TaskKind, label: impl Into<String>) -> TaskHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let handle = TaskHandle::new(id, kind, label.into());
+        self.tasks.lock().push(handle.clone());
+        handle
+    }
+
+    /// Request cancellation of task `id`; false if it isn't running
+    pub fn cancel(&self, id: TaskId) -> bool {
+        self.prune();
+        match self.tasks.lock().iter().find(|t| t.id() == id) {
+            Some(task) => {
+                task.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Request cancellation of every running task
+    pub fn cancel_all(&self) {
+        self.prune();
+        for task in self.tasks.lock().iter() {
+            task.cancel();
+        }
+    }
+
+    /// Progress of every running task, oldest first
+    pub fn running(&self) -> Vec<Progress> {
+        self.prune();
+        self.tasks.lock().iter().map(TaskHandle::progress).collect()
+    }
+
+    /// Whether any task is running
+    pub fn is_empty(&self) -> bool {
+        self.prune();
+        self.tasks.lock().is_empty()
+    }
+
+    /// Drop finished tasks, and tasks whose operation went away without
+    /// finishing (only the registry still holds them)
+    fn prune(&self) {
+        self.tasks
+            .lock()
+            .retain(|t| !t.0.finished.load(Ordering::Relaxed) && Arc::strong_count(&t.0) > 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_and_eta() {
+        let task = TaskHandle::detached(TaskKind::Scan, "Scan");
+        let p = task.progress();
+        assert_eq!(
+            (p.done, p.total, p.fraction(), p.eta()),
+            (0, None, None, None)
+        );
+        assert_eq!(p.counts(), "0");
+
+        task.set_total(4);
+        task.advance(1);
+        let mut p = task.progress();
+        p.elapsed = Duration::from_secs(10);
+        assert_eq!(p.fraction(), Some(0.25));
+        assert_eq!(p.eta(), Some(Duration::from_secs(30)));
+        assert_eq!(p.counts(), "1 / 4");
+
+        assert_eq!(format_eta(Duration::from_secs(45)), "45s");
+        assert_eq!(format_eta(Duration::from_secs(200)), "3m 20s");
+        assert_eq!(format_eta(Duration::from_secs(3900)), "1h 05m");
+    }
+
+    #[test]
+    fn test_registry_cancel_and_finish() {
+        let registry = TaskRegistry::new();
+        let scan = registry.start(TaskKind::Scan, "Scan");
+        let organize = registry.start(TaskKind::Organize, "Organize");
+        assert_ne!(scan.id(), organize.id());
+        assert_eq!(registry.running().len(), 2);
+
+        assert!(registry.cancel(scan.id()));
+        assert!(scan.is_cancelled());
+        assert!(!organize.is_cancelled());
+        assert!(registry.running()[0].cancelled);
+
+        // Finished tasks and abandoned ones drop out
+        scan.finish();
+        assert!(!registry.cancel(scan.id()));
+        drop(organize);
+        assert!(registry.is_empty());
+    }
+}
@@ -17,10 +17,14 @@
 //! ```
 
 use sqlx::sqlite::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 
 use crate::db::TrackWithMetadata;
 use crate::metadata::TrackMetadata;
+use crate::model::TrackSource;
 
 /// Creates a temporary database for testing.
 ///
@@ -92,11 +96,22 @@ pub fn mock_track_with_metadata() -> TrackWithMetadata {
         path: "/test/path/song.mp3".to_string(),
         duration: Some(180),
         track_number: Some(1),
-        artist_name: "Test Artist".to_string(),
-        album_name: "Test Album".to_string(),
+        artist_name: "Test Artist".into(),
+        album_name: "Test Album".into(),
         year: Some(2023),
         quality_score: None,
         quality_flags: None,
+        added_at: None,
+        updated_at: None,
+        track_number_inferred: false,
+        track_gain: None,
+        track_peak: None,
+        leading_silence_ms: None,
+        trailing_silence_ms: None,
+        language: None,
+        explicit: None,
+        numbering_inconsistent: false,
+        source: TrackSource::Local,
     }
 }
 
@@ -109,12 +124,23 @@ pub fn mock_track_at_path(id: i64, path: &str) -> TrackWithMetadata {
         title: format!("Track {}", id),
         path: path.to_string(),
         duration: Some(180),
-        track_number: Some(id),
-        artist_name: "Test Artist".to_string(),
-        album_name: "Test Album".to_string(),
+        track_number: Some(id as u32),
+        artist_name: "Test Artist".into(),
+        album_name: "Test Album".into(),
         year: Some(2023),
         quality_score: None,
         quality_flags: None,
+        added_at: None,
+        updated_at: None,
+        track_number_inferred: false,
+        track_gain: None,
+        track_peak: None,
+        leading_silence_ms: None,
+        trailing_silence_ms: None,
+        language: None,
+        explicit: None,
+        numbering_inconsistent: false,
+        source: TrackSource::Local,
     }
 }
 
@@ -144,10 +170,824 @@ pub async fn insert_mock_track(pool: &SqlitePool, path: &str) -> i64 {
         .expect("Failed to insert track")
 }
 
+// ============================================================================
+// Audio Fixtures
+// ============================================================================
+
+/// Formats [`write_audio_fixture`] can create
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFixture {
+    Mp3,
+    Flac,
+    Ogg,
+    M4a,
+}
+
+impl AudioFixture {
+    pub const ALL: [AudioFixture; 4] = [Self::Mp3, Self::Flac, Self::Ogg, Self::M4a];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Flac => "flac",
+            Self::Ogg => "ogg",
+            Self::M4a => "m4a",
+        }
+    }
+}
+
+/// Writes the smallest untagged file of `format` that tag libraries accept
+/// (valid headers, silent or empty audio) and returns its path.
+///
+/// There are no encoders in CI, so the containers are assembled by hand.
+pub fn write_audio_fixture(dir: &Path, format: AudioFixture) -> PathBuf {
+    let bytes = match format {
+        AudioFixture::Mp3 => mp3_fixture(),
+        AudioFixture::Flac => flac_fixture(),
+        AudioFixture::Ogg => ogg_fixture(),
+        AudioFixture::M4a => m4a_fixture(),
+    };
+    let path = dir.join(format!("fixture.{}", format.extension()));
+    std::fs::write(&path, bytes).expect("Failed to write audio fixture");
+    path
+}
+
+/// Ten MPEG-1 Layer III frames, 128 kbps, 44.1 kHz, all zero
+fn mp3_fixture() -> Vec<u8> {
+    const FRAME_LEN: usize = 417; // 144 * 128000 / 44100
+    let mut frame = vec![0u8; FRAME_LEN];
+    frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+    frame.repeat(10)
+}
+
+/// STREAMINFO for 44.1 kHz 16-bit stereo, padding (as encoders leave it)
+/// and no frames
+fn flac_fixture() -> Vec<u8> {
+    let mut bytes = b"fLaC".to_vec();
+    bytes.extend_from_slice(&[0, 0, 0, 34]); // STREAMINFO
+    bytes.extend_from_slice(&4096u16.to_be_bytes());
+    bytes.extend_from_slice(&4096u16.to_be_bytes());
+    bytes.extend_from_slice(&[0; 6]); // frame sizes unknown
+    let packed: u64 = (44100 << 44) | (1 << 41) | (15 << 36);
+    bytes.extend_from_slice(&packed.to_be_bytes());
+    bytes.extend_from_slice(&[0; 16]); // MD5
+    bytes.extend_from_slice(&[0x81, 0, 0, 64]); // last block, PADDING
+    bytes.extend_from_slice(&[0; 64]);
+    bytes
+}
+
+/// Vorbis headers and one empty audio packet, one second long
+fn ogg_fixture() -> Vec<u8> {
+    let mut ident = vec![1];
+    ident.extend_from_slice(b"vorbis");
+    ident.extend_from_slice(&0u32.to_le_bytes());
+    ident.push(2);
+    ident.extend_from_slice(&44100u32.to_le_bytes());
+    ident.extend_from_slice(&0i32.to_le_bytes());
+    ident.extend_from_slice(&128000i32.to_le_bytes());
+    ident.extend_from_slice(&0i32.to_le_bytes());
+    ident.extend_from_slice(&[0xB8, 1]);
+
+    let vendor = b"music-minder tests";
+    let mut comments = vec![3];
+    comments.extend_from_slice(b"vorbis");
+    comments.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    comments.extend_from_slice(vendor);
+    comments.extend_from_slice(&0u32.to_le_bytes());
+    comments.push(1);
+
+    let mut setup = vec![5];
+    setup.extend_from_slice(b"vorbis");
+    setup.extend_from_slice(&[0; 8]);
+
+    let mut bytes = ogg_page(0x02, 0, 0, &[&ident]);
+    bytes.extend(ogg_page(0, 0, 1, &[&comments, &setup]));
+    bytes.extend(ogg_page(0x04, 44100, 2, &[&[0]]));
+    bytes
+}
+
+/// One Ogg page holding whole packets (each under 255 bytes)
+fn ogg_page(flags: u8, granule: u64, sequence: u32, packets: &[&[u8]]) -> Vec<u8> {
+    let mut page = b"OggS".to_vec();
+    page.push(0);
+    page.push(flags);
+    page.extend_from_slice(&granule.to_le_bytes());
+    page.extend_from_slice(&0x4D4Du32.to_le_bytes()); // stream serial
+    page.extend_from_slice(&sequence.to_le_bytes());
+    page.extend_from_slice(&[0; 4]); // CRC, filled in below
+    page.push(packets.len() as u8);
+    for packet in packets {
+        page.push(packet.len() as u8);
+    }
+    for packet in packets {
+        page.extend_from_slice(packet);
+    }
+    let crc = ogg_crc(&page);
+    page[22..26].copy_from_slice(&crc.to_le_bytes());
+    page
+}
+
+fn ogg_crc(data: &[u8]) -> u32 {
+    let mut crc = 0u32;
+    for &byte in data {
+        crc ^= u32::from(byte) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// An AAC track of one second with no samples
+fn m4a_fixture() -> Vec<u8> {
+    fn atom(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut atom = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        atom.extend_from_slice(name);
+        atom.extend_from_slice(body);
+        atom
+    }
+    fn full_atom(name: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        atom(name, &[&[0u8; 4][..], body].concat())
+    }
+    const MATRIX: [u32; 9] = [0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x4000_0000];
+    let matrix: Vec<u8> = MATRIX.iter().flat_map(|v| v.to_be_bytes()).collect();
+    let be32 = |v: u32| v.to_be_bytes();
+
+    let mvhd = full_atom(
+        b"mvhd",
+        &[
+            &[0; 8][..],
+            &be32(44100),
+            &be32(44100),
+            &be32(0x10000),
+            &[0x01, 0x00],
+            &[0; 10],
+            &matrix,
+            &[0; 24],
+            &be32(2),
+        ]
+        .concat(),
+    );
+    let tkhd = atom(
+        b"tkhd",
+        &[
+            &[0, 0, 0, 7][..],
+            &[0; 8],
+            &be32(1),
+            &[0; 4],
+            &be32(44100),
+            &[0; 12],
+            &[0x01, 0x00, 0, 0],
+            &matrix,
+            &[0; 8],
+        ]
+        .concat(),
+    );
+    let mdhd = full_atom(
+        b"mdhd",
+        &[&[0; 8][..], &be32(44100), &be32(44100), &[0x55, 0xC4, 0, 0]].concat(),
+    );
+    let hdlr = full_atom(b"hdlr", &[&[0; 4][..], b"soun", &[0; 13]].concat());
+
+    // ES descriptor: AAC LC, 44.1 kHz, stereo
+    let esds = full_atom(
+        b"esds",
+        &[
+            &[0x03, 25, 0, 1, 0][..],
+            &[0x04, 17, 0x40, 0x15, 0, 0, 0],
+            &be32(128000),
+            &be32(128000),
+            &[0x05, 2, 0x12, 0x10],
+            &[0x06, 1, 0x02],
+        ]
+        .concat(),
+    );
+    let mp4a = atom(
+        b"mp4a",
+        &[
+            &[0; 6][..],
+            &[0, 1],
+            &[0; 8],
+            &[0, 2, 0, 16],
+            &[0; 4],
+            &be32(44100 << 16),
+            &esds,
+        ]
+        .concat(),
+    );
+    let stsd = full_atom(b"stsd", &[&be32(1)[..], &mp4a].concat());
+    let empty_table = |name: &[u8; 4]| full_atom(name, &be32(0));
+    let stsz = full_atom(b"stsz", &[0; 8]);
+    let stbl = atom(
+        b"stbl",
+        &[
+            stsd,
+            empty_table(b"stts"),
+            empty_table(b"stsc"),
+            stsz,
+            empty_table(b"stco"),
+        ]
+        .concat(),
+    );
+    let smhd = full_atom(b"smhd", &[0; 4]);
+    let minf = atom(b"minf", &[smhd, stbl].concat());
+    let mdia = atom(b"mdia", &[mdhd, hdlr, minf].concat());
+    let trak = atom(b"trak", &[tkhd, mdia].concat());
+    let moov = atom(b"moov", &[mvhd, trak].concat());
+
+    let ftyp = atom(b"ftyp", &[&b"M4A "[..], &[0; 4], b"M4A mp42isom"].concat());
+    [ftyp, moov, atom(b"mdat", &[])].concat()
+}
+
+// ============================================================================
+// Synthetic Library
+// ============================================================================
+
+/// Generates a library of `tracks` tracks shaped like a real collection,
+/// the same for the same `seed`.
+///
+/// A few artists have most of the albums, albums hold 6-18 tracks, and
+/// about 3% of tracks are loose files without an album. Most files are
+/// MP3 or FLAC, most tracks are from recent decades, were added in the
+/// last few years and have ReplayGain tags. Names include accented and
+/// non-Latin text. IDs run from 1 and paths are under `/music`.
+pub fn synthetic_library(tracks: usize, seed: u64) -> Vec<TrackWithMetadata> {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const SYLLABLES: &[&str] = &[
+        "ka", "lo", "mi", "ra", "ven", "dor", "sil", "ta", "ne", "bri", "ghe", "ös", "mé", "zu",
+        "ląd", "ño", "音", "楽",
+    ];
+    const WORDS: &[&str] = &[
+        "Love",
+        "Night",
+        "Blue",
+        "Fire",
+        "River",
+        "Song",
+        "Heart",
+        "Dream",
+        "Light",
+        "Road",
+        "Ghost",
+        "Summer",
+        "Rain",
+        "City",
+        "Echo",
+        "Gold",
+        "Ocean",
+        "Électrique",
+        "Sueño",
+    ];
+    const FORMATS: &[(&str, f64)] = &[("mp3", 0.6), ("flac", 0.3), ("m4a", 0.07), ("ogg", 0.03)];
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let name = |rng: &mut StdRng, parts: std::ops::Range<usize>| {
+        let mut name = String::new();
+        for _ in 0..rng.random_range(parts) {
+            name.push_str(SYLLABLES[rng.random_range(0..SYLLABLES.len())]);
+        }
+        let mut chars = name.chars();
+        chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    };
+    let title = |rng: &mut StdRng| {
+        (0..rng.random_range(1..4))
+            .map(|_| WORDS[rng.random_range(0..WORDS.len())])
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let artists: Vec<String> = (0..(tracks / 40).max(1))
+        .map(|i| match i % 5 {
+            0 => format!("The {}s", name(&mut rng, 2..4)),
+            1 => format!("{} {}", name(&mut rng, 1..3), name(&mut rng, 2..4)),
+            _ => name(&mut rng, 2..5),
+        })
+        .collect();
+    let now = chrono::Utc::now().timestamp();
+
+    let mut album_names = std::collections::HashSet::new();
+    let mut library = Vec::with_capacity(tracks);
+    while library.len() < tracks {
+        // Squaring skews towards the first, most prolific artists
+        let artist = &artists[(rng.random::<f64>().powi(2) * artists.len() as f64) as usize];
+        let year = 1960 + (rng.random::<f64>().sqrt() * 65.0) as i32;
+        let added = now - (rng.random::<f64>().powi(2) * 5.0 * 365.0 * 86_400.0) as i64;
+        let mut pick = rng.random::<f64>();
+        let ext = FORMATS
+            .iter()
+            .find(|(_, share)| {
+                pick -= share;
+                pick < 0.0
+            })
+            .map_or("mp3", |(ext, _)| ext);
+
+        let loose = rng.random_bool(0.03);
+        let (album, count) = if loose {
+            (None, 1)
+        } else {
+            let mut album = title(&mut rng);
+            while !album_names.insert((artist.clone(), album.clone())) {
+                album = title(&mut rng);
+            }
+            (Some(album), rng.random_range(6..19))
+        };
+        for number in 1..=count.min(tracks - library.len()) {
+            let id = library.len() as i64 + 1;
+            let track_title = title(&mut rng);
+            let path = match &album {
+                Some(album) => format!(
+                    "/music/{}/{} - {}/{:02} - {}.{}",
+                    artist, year, album, number, track_title, ext
+                ),
+                None => format!("/music/{}/{} ({}).{}", artist, track_title, id, ext),
+            };
+            let tagged = rng.random_bool(0.75);
+            library.push(TrackWithMetadata {
+                id,
+                title: track_title,
+                path,
+                duration: Some(120 + (rng.random::<f64>().powi(3) * 600.0) as u32),
+                track_number: album.is_some().then_some(number as u32),
+                artist_name: artist.as_str().into(),
+                album_name: album.as_deref().unwrap_or("Unknown Album").into(),
+                year: album.is_some().then_some(year),
+                quality_score: None,
+                quality_flags: None,
+                added_at: Some(added),
+                updated_at: Some(added + rng.random_range(0..86_400)),
+                track_number_inferred: false,
+                track_gain: tagged.then(|| rng.random_range(-14.0..2.0)),
+                track_peak: tagged.then(|| rng.random_range(0.6..1.0)),
+                leading_silence_ms: None,
+                trailing_silence_ms: None,
+                language: None,
+                explicit: None,
+                numbering_inconsistent: false,
+                source: TrackSource::Local,
+            });
+        }
+    }
+    library
+}
+
+/// Stores a [`synthetic_library`] in an empty database, in one transaction
+pub async fn insert_synthetic_library(pool: &SqlitePool, library: &[TrackWithMetadata]) {
+    let mut tx = pool.begin().await.expect("Failed to start transaction");
+    let mut artists: std::collections::HashMap<&str, i64> = std::collections::HashMap::new();
+    let mut albums: std::collections::HashMap<(&str, &str), i64> = std::collections::HashMap::new();
+
+    for track in library {
+        let next = artists.len() as i64 + 1;
+        let artist_id = *artists.entry(&track.artist_name).or_insert(next);
+        if artist_id == next {
+            sqlx::query("INSERT INTO artists (id, name) VALUES (?, ?)")
+                .bind(artist_id)
+                .bind(&track.artist_name)
+                .execute(&mut *tx)
+                .await
+                .expect("Failed to insert artist");
+        }
+        let album_id = match track.year {
+            Some(year) => {
+                let next = albums.len() as i64 + 1;
+                let id = *albums
+                    .entry((&track.artist_name, &track.album_name))
+                    .or_insert(next);
+                if id == next {
+                    sqlx::query(
+                        "INSERT INTO albums (id, title, artist_id, year) VALUES (?, ?, ?, ?)",
+                    )
+                    .bind(id)
+                    .bind(&track.album_name)
+                    .bind(artist_id)
+                    .bind(year)
+                    .execute(&mut *tx)
+                    .await
+                    .expect("Failed to insert album");
+                }
+                Some(id)
+            }
+            None => None,
+        };
+        sqlx::query(
+            "INSERT INTO tracks (id, title, artist_id, album_id, path, path_key, duration,
+                                 track_number, added_at, updated_at, replaygain_track_gain,
+                                 replaygain_track_peak)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(track.id)
+        .bind(&track.title)
+        .bind(artist_id)
+        .bind(album_id)
+        .bind(&track.path)
+        .bind(crate::db::paths::key(&track.path))
+        .bind(track.duration)
+        .bind(track.track_number)
+        .bind(track.added_at)
+        .bind(track.updated_at)
+        .bind(track.track_gain)
+        .bind(track.track_peak)
+        .execute(&mut *tx)
+        .await
+        .expect("Failed to insert track");
+    }
+    tx.commit()
+        .await
+        .expect("Failed to commit synthetic library");
+}
+
+/// Writes an untagged fixture file for each track of a [`synthetic_library`]
+/// under `dir` (its `/music` prefix replaced) and returns the paths.
+pub fn write_synthetic_files(dir: &Path, library: &[TrackWithMetadata]) -> Vec<PathBuf> {
+    let fixtures: std::collections::HashMap<&str, Vec<u8>> = AudioFixture::ALL
+        .iter()
+        .map(|format| {
+            let bytes = match format {
+                AudioFixture::Mp3 => mp3_fixture(),
+                AudioFixture::Flac => flac_fixture(),
+                AudioFixture::Ogg => ogg_fixture(),
+                AudioFixture::M4a => m4a_fixture(),
+            };
+            (format.extension(), bytes)
+        })
+        .collect();
+
+    library
+        .iter()
+        .map(|track| {
+            let path = dir.join(track.path.trim_start_matches("/music/"));
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("mp3");
+            std::fs::create_dir_all(path.parent().unwrap()).expect("Failed to create folder");
+            std::fs::write(&path, &fixtures[ext]).expect("Failed to write synthetic file");
+            path
+        })
+        .collect()
+}
+
+// ============================================================================
+// Performance Budgets
+// ============================================================================
+
+/// Library size the budgets below are set for
+pub const PERF_LIBRARY_SIZE: usize = 100_000;
+
+/// Library size for the `perf_` tests: [`PERF_LIBRARY_SIZE`], or
+/// `MUSIC_MINDER_PERF_TRACKS`
+pub fn perf_library_size() -> usize {
+    std::env::var("MUSIC_MINDER_PERF_TRACKS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(PERF_LIBRARY_SIZE)
+}
+
+/// How long [`machine_speed`]'s reference work took on the machine the
+/// budgets were measured on
+const PERF_REFERENCE: std::time::Duration = std::time::Duration::from_millis(25);
+
+/// Leeway on top of the measured budgets, for noisy shared runners
+const PERF_HEADROOM: f64 = 2.0;
+
+/// How much slower this machine is than the one the budgets were measured
+/// on (never below 1): the best of three runs of a fixed sort, against
+/// [`PERF_REFERENCE`]. Measured once per test run.
+pub fn machine_speed() -> f64 {
+    static FACTOR: std::sync::OnceLock<f64> = std::sync::OnceLock::new();
+    *FACTOR.get_or_init(|| {
+        let best = (0..3)
+            .map(|_| {
+                let mut state = 0x9E37_79B9_7F4A_7C15u64;
+                let mut values: Vec<u64> = (0..1_000_000)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+                        state
+                    })
+                    .collect();
+                let start = std::time::Instant::now();
+                values.sort_unstable();
+                std::hint::black_box(&values);
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or_default();
+        (best.as_secs_f64() / PERF_REFERENCE.as_secs_f64()).max(1.0)
+    })
+}
+
+/// Runs `operation` and fails if it takes longer than `budget`, which is
+/// set for [`PERF_LIBRARY_SIZE`] tracks and scaled to `tracks`. Budgets are
+/// relative: they grow on machines slower than the one they were measured
+/// on ([`machine_speed`]) and leave [`PERF_HEADROOM`] for noise.
+///
+/// The `perf_` tests that use this are ignored by default: budgets are for
+/// release builds. CI runs them with
+/// `cargo test --release perf_ -- --ignored`.
+pub fn within_budget<T>(
+    label: &str,
+    tracks: usize,
+    budget: std::time::Duration,
+    operation: impl FnOnce() -> T,
+) -> T {
+    let start = std::time::Instant::now();
+    let result = operation();
+    check_budget(label, tracks, budget, start.elapsed());
+    result
+}
+
+/// [`within_budget`] for async operations
+pub async fn within_budget_async<F: std::future::Future>(
+    label: &str,
+    tracks: usize,
+    budget: std::time::Duration,
+    operation: F,
+) -> F::Output {
+    let start = std::time::Instant::now();
+    let result = operation.await;
+    check_budget(label, tracks, budget, start.elapsed());
+    result
+}
+
+fn check_budget(
+    label: &str,
+    tracks: usize,
+    budget: std::time::Duration,
+    elapsed: std::time::Duration,
+) {
+    // Never under 20 ms, so timer noise doesn't fail small runs
+    let speed = machine_speed();
+    let budget = budget
+        .mul_f64(tracks as f64 / PERF_LIBRARY_SIZE as f64 * speed * PERF_HEADROOM)
+        .max(std::time::Duration::from_millis(20));
+    println!(
+        "{:<40} {:>8.1} ms (budget {:.1} ms, {} tracks, machine {:.2}x)",
+        label,
+        elapsed.as_secs_f64() * 1000.0,
+        budget.as_secs_f64() * 1000.0,
+        tracks,
+        speed
+    );
+    assert!(
+        elapsed <= budget,
+        "{} took {:?}, over its {:?} budget for {} tracks",
+        label,
+        elapsed,
+        budget,
+        tracks
+    );
+}
+
+// ============================================================================
+// Fake External Tools
+// ============================================================================
+
+/// A shell script answering like `fpcalc -json`: the fingerprint is
+/// `FAKE-` and the file's name without extension, files with `corrupt` in
+/// their name fail to decode and missing files can't be opened
+#[cfg(unix)]
+const FAKE_FPCALC: &str = r#"#!/bin/sh
+if [ "$1" = "-version" ]; then
+    echo "fpcalc version 1.5.1 (fake)"
+    exit 0
+fi
+file="$2"
+if [ ! -f "$file" ]; then
+    echo "ERROR: Could not open the input file ($file)" >&2
+    exit 2
+fi
+case "$file" in
+    *corrupt*)
+        echo "ERROR: Error decoding audio frame" >&2
+        exit 3 ;;
+esac
+name=$(basename "$file")
+printf '{"duration": 301.0, "fingerprint": "FAKE-%s"}\n' "${name%.*}"
+"#;
+
+/// Point fingerprinting at a fake fpcalc (see [`FAKE_FPCALC`]) for the rest
+/// of the test run, so enrichment runs end to end without Chromaprint.
+/// Every test gets the same one, so it's safe to call from parallel tests.
+///
+/// Pair it with a [`FixtureServer`] serving `FAKE-<name>` lookups:
+///
+/// ```ignore
+/// install_fake_fpcalc();
+/// let server = FixtureServer::start(vec![(
+///     "/v2/lookup?fingerprint=FAKE-teen",
+///     FixtureResponse::json(include_str!("fixtures/acoustid_lookup.json")),
+/// )])
+/// .await;
+/// ```
+#[cfg(unix)]
+pub fn install_fake_fpcalc() {
+    use std::os::unix::fs::PermissionsExt;
+
+    static FPCALC: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+    let fpcalc = FPCALC.get_or_init(|| {
+        let dir = tempfile::Builder::new()
+            .prefix("fake-fpcalc")
+            .tempdir()
+            .expect("Failed to create temp directory")
+            .keep();
+        let path = dir.join("fpcalc");
+        std::fs::write(&path, FAKE_FPCALC).expect("Failed to write fake fpcalc");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .expect("Failed to make fake fpcalc executable");
+        path
+    });
+    crate::enrichment::fingerprint::set_fpcalc(Some(fpcalc.clone()));
+}
+
+// ============================================================================
+// HTTP Fixtures
+// ============================================================================
+
+/// A canned response served by [`FixtureServer`]
+#[derive(Debug, Clone)]
+pub struct FixtureResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+    /// Requests left to answer before the route is passed over (`None`
+    /// for every request)
+    pub times: Option<usize>,
+}
+
+impl FixtureResponse {
+    /// A 200 response with a JSON body (usually a recorded fixture)
+    pub fn json(body: &str) -> Self {
+        Self::status(200, body)
+    }
+
+    /// A JSON response with another status, for error paths
+    pub fn status(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.as_bytes().to_vec(),
+            times: None,
+        }
+    }
+
+    /// A 200 response with a binary body (cover images)
+    pub fn bytes(content_type: &'static str, body: &[u8]) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body: body.to_vec(),
+            times: None,
+        }
+    }
+
+    /// Answer only the first `n` matching requests, leaving the rest to
+    /// the routes after it: a 503 once, then the fixture, for retries
+    pub fn times(self, n: usize) -> Self {
+        Self {
+            times: Some(n),
+            ..self
+        }
+    }
+}
+
+/// A local HTTP server standing in for AcoustID, MusicBrainz or the Cover
+/// Art Archive. Point a client's `with_base_url` at [`url`](Self::url).
+///
+/// Requests are answered from the first route whose path prefix matches
+/// (404 otherwise) and recorded, so tests can check the URLs the clients
+/// build. A route like `/v2/lookup?fingerprint=abc` also needs the query
+/// to contain what follows the `?`.
+///
+/// ```ignore
+/// let server = FixtureServer::start(vec![(
+///     "/recording/",
+///     FixtureResponse::json(include_str!("fixtures/musicbrainz_recording.json")),
+/// )])
+/// .await;
+/// let client = MusicBrainzClient::with_base_url(server.url());
+/// ```
+pub struct FixtureServer {
+    url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl FixtureServer {
+    pub async fn start(routes: Vec<(&'static str, FixtureResponse)>) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind fixture server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let routes = Arc::new(Mutex::new(routes));
+
+        let recorded = Arc::clone(&requests);
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (routes, recorded) = (Arc::clone(&routes), recorded.clone());
+                tokio::spawn(async move {
+                    let (read, mut write) = stream.into_split();
+                    let mut reader = BufReader::new(read);
+                    let mut head = String::new();
+                    while reader.read_line(&mut head).await.unwrap_or(0) > 0
+                        && !head.ends_with("\r\n\r\n")
+                    {}
+                    let target = head.split_whitespace().nth(1).unwrap_or("/").to_string();
+                    recorded.lock().unwrap().push(target.clone());
+
+                    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+                    let response = routes
+                        .lock()
+                        .unwrap()
+                        .iter_mut()
+                        .find(|(route, response)| {
+                            let (prefix, wanted) = route.split_once('?').unwrap_or((route, ""));
+                            path.starts_with(prefix)
+                                && query.contains(wanted)
+                                && response.times != Some(0)
+                        })
+                        .map(|(_, response)| {
+                            if let Some(times) = &mut response.times {
+                                *times -= 1;
+                            }
+                            response.clone()
+                        })
+                        .unwrap_or_else(|| {
+                            FixtureResponse::status(404, r#"{"error":"Not Found"}"#)
+                        });
+                    let head = format!(
+                        "HTTP/1.1 {} Fixture\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        response.status,
+                        response.content_type,
+                        response.body.len()
+                    );
+                    let _ = write.write_all(head.as_bytes()).await;
+                    let _ = write.write_all(&response.body).await;
+                    let _ = write.shutdown().await;
+                });
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            task,
+        }
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:41234`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Request targets (path and query) received so far, in order
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for FixtureServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_synthetic_library() {
+        let library = synthetic_library(2000, 7);
+        assert_eq!(library.len(), 2000);
+        assert_eq!(library[100].path, synthetic_library(2000, 7)[100].path);
+
+        let paths: std::collections::HashSet<_> = library.iter().map(|t| &t.path).collect();
+        assert_eq!(paths.len(), library.len());
+        let flac = library.iter().filter(|t| t.path.ends_with(".flac")).count();
+        assert!((400..800).contains(&flac), "{} FLAC files", flac);
+        let loose = library.iter().filter(|t| t.year.is_none()).count();
+        assert!(loose > 0 && loose < 200, "{} loose tracks", loose);
+
+        let (pool, _dir) = temp_db().await;
+        insert_synthetic_library(&pool, &library).await;
+        let stored = crate::db::get_all_tracks_with_metadata(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), library.len());
+        let track = stored.iter().find(|t| t.id == 100).unwrap();
+        assert_eq!(track.album_name, library[99].album_name);
+        assert_eq!(track.track_gain, library[99].track_gain);
+    }
+
     #[tokio::test]
     async fn test_temp_db_creates_working_database() {
         let (pool, _dir) = temp_db().await;
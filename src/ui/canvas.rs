@@ -6,9 +6,7 @@
 //! - 3D wave simulations like an ocean
 //! - All reactive to audio frequency and amplitude
 //!
-//! Note: This module is prepared for future visualization features.
-
-#![allow(dead_code)]
+//! Shown in the full-screen Now Playing view.
 
 use iced::mouse::Cursor;
 use iced::widget::canvas::{self, Canvas, Frame, Geometry, Path, Stroke};
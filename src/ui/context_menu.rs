@@ -0,0 +1,371 @@
+//! Right-click menus for library tracks, albums and queue items.
+//!
+//! A [`ContextMenu`] records what was clicked and where; [`actions`] assembles
+//! the entries for that kind of item. Features hook in by adding entries to
+//! the builder for their item type - the overlay in `views::context_menu`
+//! only renders what it is given.
+
+use iced::{Point, Size};
+use std::path::PathBuf;
+
+use super::icons;
+use super::messages::Message;
+use super::state::{ActivePane, LibraryScope, LoadedState};
+use crate::db;
+
+/// Width of the menu panel
+pub const MENU_WIDTH: f32 = 240.0;
+/// Height of one entry
+pub const ITEM_HEIGHT: f32 = 30.0;
+/// Height of the divider between groups
+pub const SEPARATOR_HEIGHT: f32 = 9.0;
+/// Padding around the entries
+pub const MENU_PADDING: f32 = 4.0;
+/// Playlists offered to add a track to, most recently changed first
+const MENU_PLAYLISTS: usize = 5;
+
+/// What a context menu was opened on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextTarget {
+    /// Library track, by index into `LoadedState::tracks`
+    Track(usize),
+    /// The album of the library track at this index
+    Album(usize),
+    /// Queue item, by queue position
+    QueueItem(usize),
+}
+
+/// An open context menu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContextMenu {
+    pub target: ContextTarget,
+    /// Cursor position when the menu was opened (window coordinates)
+    pub position: Point,
+}
+
+/// One menu entry. Choosing it closes the menu and sends `messages` in order.
+#[derive(Debug, Clone)]
+pub struct MenuAction {
+    pub icon: char,
+    pub label: String,
+    pub messages: Vec<Message>,
+    /// Drawn in the error color (removals)
+    pub destructive: bool,
+}
+
+impl MenuAction {
+    fn new(icon: char, label: impl Into<String>, message: Message) -> Self {
+        Self {
+            icon,
+            label: label.into(),
+            messages: vec![message],
+            destructive: false,
+        }
+    }
+
+    /// Send another message after the first
+    fn then(mut self, message: Message) -> Self {
+        self.messages.push(message);
+        self
+    }
+
+    fn destructive(mut self) -> Self {
+        self.destructive = true;
+        self
+    }
+}
+
+/// Menu entries for `target`, in groups separated by dividers.
+///
+/// Empty when the target no longer exists (e.g. the queue changed under an
+/// open menu).
+pub fn actions(s: &LoadedState, target: ContextTarget) -> Vec<Vec<MenuAction>> {
+    let groups = match target {
+        ContextTarget::Track(idx) => track_actions(s, idx),
+        ContextTarget::Album(idx) => album_actions(s, idx),
+        ContextTarget::QueueItem(pos) => queue_actions(s, pos),
+    };
+    groups.into_iter().filter(|g| !g.is_empty()).collect()
+}
+
+fn track_actions(s: &LoadedState, idx: usize) -> Vec<Vec<MenuAction>> {
+    let Some(track) = s.tracks.get(idx) else {
+        return Vec::new();
+    };
+    let path = PathBuf::from(&track.path);
+    vec![
+        vec![
+            MenuAction::new(icons::PLAY, "Play", Message::PlayerPlayTrack(idx)),
+            MenuAction::new(icons::FORWARD, "Play Next", Message::PlayerPlayNext(idx)),
+            MenuAction::new(icons::PLUS, "Add to Queue", Message::PlayerQueueTrack(idx)),
+        ],
+        playlist_actions(s, track.id),
+        vec![
+            MenuAction::new(
+                icons::FORWARD,
+                "Play Album Next",
+                Message::PlayerPlayAlbumNext(idx),
+            ),
+            MenuAction::new(
+                icons::PLUS,
+                "Add Album to Queue",
+                Message::PlayerQueueAlbum(idx),
+            ),
+        ],
+        go_to_actions(track),
+        vec![
+            MenuAction::new(icons::WAND, "Enrich", Message::EnrichAddTracks(vec![idx]))
+                .then(Message::SwitchPane(ActivePane::Enrich)),
+            MenuAction::new(icons::PEN, "Edit Tags", Message::TrackDetailOpen(idx)),
+            MenuAction::new(
+                icons::GAUGE,
+                "Measure ReplayGain",
+                Message::ReplayGainScan(vec![idx]),
+            ),
+            MenuAction::new(
+                icons::FOLDER,
+                "Organize Files…",
+                Message::ShowOrganizeSection,
+            ),
+        ],
+        file_actions(path.clone()),
+        vec![
+            MenuAction::new(
+                icons::TRASH,
+                "Remove from Library",
+                Message::LibraryRemoveTrack(path),
+            )
+            .destructive(),
+        ],
+    ]
+}
+
+fn album_actions(s: &LoadedState, idx: usize) -> Vec<Vec<MenuAction>> {
+    let Some(track) = s.tracks.get(idx) else {
+        return Vec::new();
+    };
+    let album = super::update::album_track_indices(s, idx);
+    let paths = album.iter().map(|&i| s.tracks[i].path.clone()).collect();
+    vec![
+        vec![
+            MenuAction::new(
+                icons::FORWARD,
+                "Play Album Next",
+                Message::PlayerPlayAlbumNext(idx),
+            ),
+            MenuAction::new(
+                icons::PLUS,
+                "Add Album to Queue",
+                Message::PlayerQueueAlbum(idx),
+            ),
+        ],
+        go_to_actions(track),
+        vec![
+            MenuAction::new(
+                icons::WAND,
+                format!("Enrich Album ({} tracks)", album.len()),
+                Message::EnrichAddTracks(album.clone()),
+            )
+            .then(Message::SwitchPane(ActivePane::Enrich)),
+            MenuAction::new(
+                icons::GAUGE,
+                format!("Measure ReplayGain ({} tracks)", album.len()),
+                Message::ReplayGainScan(album),
+            ),
+        ],
+        vec![
+            MenuAction::new(
+                icons::FILE_EXPORT,
+                "Write NFO Files",
+                Message::NfoExport(crate::nfo::Scope::Containing(paths)),
+            ),
+            MenuAction::new(
+                icons::FOLDER_OPEN,
+                "Show in Folder",
+                Message::RevealInFolder(PathBuf::from(&track.path)),
+            ),
+        ],
+    ]
+}
+
+/// "Go to Album" and "Go to Artist" for a library track
+fn go_to_actions(track: &db::TrackWithMetadata) -> Vec<MenuAction> {
+    vec![
+        MenuAction::new(
+            icons::COMPACT_DISC,
+            "Go to Album",
+            Message::GoTo(LibraryScope::album_of(track)),
+        ),
+        MenuAction::new(
+            icons::MICROPHONE,
+            "Go to Artist",
+            Message::GoTo(LibraryScope::Artist(track.artist_name.to_string())),
+        ),
+    ]
+}
+
+fn queue_actions(s: &LoadedState, pos: usize) -> Vec<Vec<MenuAction>> {
+    let Some(item) = s.player.as_ref().and_then(|p| p.queue().items().get(pos)) else {
+        return Vec::new();
+    };
+    let library_idx = s
+        .tracks
+        .iter()
+        .position(|t| std::path::Path::new(&t.path) == item.path);
+    let stop_label = if item.stop_after {
+        "Don't Stop After This"
+    } else {
+        "Stop After This"
+    };
+
+    let mut library = Vec::new();
+    if let Some(idx) = library_idx {
+        library.extend(go_to_actions(&s.tracks[idx]));
+        library.push(MenuAction::new(
+            icons::PEN,
+            "Edit Tags",
+            Message::TrackDetailOpen(idx),
+        ));
+        library.push(
+            MenuAction::new(icons::WAND, "Enrich", Message::EnrichAddTracks(vec![idx]))
+                .then(Message::SwitchPane(ActivePane::Enrich)),
+        );
+    }
+
+    let playlists = match library_idx {
+        Some(idx) => playlist_actions(s, s.tracks[idx].id),
+        None => Vec::new(),
+    };
+
+    vec![
+        vec![
+            MenuAction::new(icons::PLAY, "Play", Message::QueueJumpTo(pos)),
+            MenuAction::new(icons::STOP, stop_label, Message::QueueToggleStopAfter(pos)),
+        ],
+        playlists,
+        library,
+        file_actions(item.path.clone()),
+        vec![
+            MenuAction::new(icons::XMARK, "Remove from Queue", Message::QueueRemove(pos))
+                .destructive(),
+        ],
+    ]
+}
+
+/// "Add to <playlist>" for the playlists changed last, and a new playlist
+fn playlist_actions(s: &LoadedState, track_id: i64) -> Vec<MenuAction> {
+    let mut recent: Vec<_> = s.playlists.playlists.iter().collect();
+    recent.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
+    let mut actions: Vec<MenuAction> = recent
+        .into_iter()
+        .take(MENU_PLAYLISTS)
+        .map(|p| {
+            MenuAction::new(
+                icons::LIST_OL,
+                format!("Add to {}", p.name),
+                Message::PlaylistAddTracks(p.id, vec![track_id]),
+            )
+        })
+        .collect();
+    actions.push(
+        MenuAction::new(
+            icons::PLUS,
+            "Add to New Playlist",
+            Message::PlaylistCreateWith(vec![track_id]),
+        )
+        .then(Message::SwitchPane(ActivePane::Playlists)),
+    );
+    actions
+}
+
+/// Show in folder / copy path entries shared by every file-backed item
+fn file_actions(path: PathBuf) -> Vec<MenuAction> {
+    vec![
+        MenuAction::new(
+            icons::FOLDER_OPEN,
+            "Show in Folder",
+            Message::RevealInFolder(path.clone()),
+        ),
+        MenuAction::new(icons::COPY, "Copy Path", Message::CopyPath(path.clone())),
+        MenuAction::new(
+            icons::COPY,
+            "Copy Relative Path",
+            Message::CopyRelativePath(path),
+        ),
+    ]
+}
+
+/// Size of the menu panel for these groups
+pub fn menu_size(groups: &[Vec<MenuAction>]) -> Size {
+    let items: usize = groups.iter().map(Vec::len).sum();
+    let separators = groups.len().saturating_sub(1);
+    Size::new(
+        MENU_WIDTH,
+        items as f32 * ITEM_HEIGHT + separators as f32 * SEPARATOR_HEIGHT + 2.0 * MENU_PADDING,
+    )
+}
+
+/// Top-left corner for a menu opened at `cursor`.
+///
+/// The menu opens down and to the right of the cursor, flipping to the other
+/// side where it would run off the window. An unknown (zero) window size
+/// leaves the cursor position alone.
+pub fn menu_origin(cursor: Point, menu: Size, window: Size) -> Point {
+    let place = |at: f32, len: f32, limit: f32| {
+        if limit <= 0.0 || at + len <= limit {
+            at
+        } else if at >= len {
+            at - len
+        } else {
+            (limit - len).max(0.0)
+        }
+    };
+    Point::new(
+        place(cursor.x, menu.width, window.width),
+        place(cursor.y, menu.height, window.height),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menu_origin_flips_at_window_edges() {
+        let menu = Size::new(200.0, 300.0);
+        let window = Size::new(1000.0, 800.0);
+
+        // Room below and to the right
+        assert_eq!(
+            menu_origin(Point::new(100.0, 100.0), menu, window),
+            Point::new(100.0, 100.0)
+        );
+        // Near the bottom-right corner: open up and to the left
+        assert_eq!(
+            menu_origin(Point::new(900.0, 700.0), menu, window),
+            Point::new(700.0, 400.0)
+        );
+        // Window too short either way: pin to the bottom edge
+        assert_eq!(
+            menu_origin(Point::new(100.0, 200.0), menu, Size::new(1000.0, 400.0)),
+            Point::new(100.0, 100.0)
+        );
+        // Window size not known yet
+        assert_eq!(
+            menu_origin(Point::new(900.0, 700.0), menu, Size::ZERO),
+            Point::new(900.0, 700.0)
+        );
+    }
+
+    #[test]
+    fn menu_size_counts_items_and_separators() {
+        let action = || MenuAction::new(icons::PLAY, "Play", Message::Noop);
+        let groups = vec![vec![action(), action()], vec![action()]];
+        let size = menu_size(&groups);
+        assert_eq!(size.width, MENU_WIDTH);
+        assert_eq!(
+            size.height,
+            3.0 * ITEM_HEIGHT + SEPARATOR_HEIGHT + 2.0 * MENU_PADDING
+        );
+    }
+}
@@ -42,6 +42,9 @@ pub const SKIP_BACK: char = '\u{f048}';
 /// Skip forward - fa-forward-step (U+F051)
 pub const SKIP_FORWARD: char = '\u{f051}';
 
+/// Forward - fa-forward (U+F04E), used for "Play next"
+pub const FORWARD: char = '\u{f04e}';
+
 /// Shuffle - fa-shuffle (U+F074)
 pub const SHUFFLE: char = '\u{f074}';
 
@@ -74,12 +77,24 @@ pub const VOLUME_MED: char = '\u{f027}';
 /// Music/Library - fa-music (U+F001)
 pub const MUSIC: char = '\u{f001}';
 
+/// Expand/Full screen - fa-expand (U+F065)
+pub const EXPAND: char = '\u{f065}';
+
+/// Compress/Leave full screen - fa-compress (U+F066)
+pub const COMPRESS: char = '\u{f066}';
+
+/// Thumbtack - fa-thumbtack (U+F08D)
+pub const THUMBTACK: char = '\u{f08d}';
+
 /// Folder - fa-folder (U+F07B)
 pub const FOLDER: char = '\u{f07b}';
 
 /// Folder open - fa-folder-open (U+F07C)
 pub const FOLDER_OPEN: char = '\u{f07c}';
 
+/// Copy - fa-copy (U+F0C5)
+pub const COPY: char = '\u{f0c5}';
+
 /// Floppy disk/Save - fa-floppy-disk (U+F0C7)
 pub const FLOPPY: char = '\u{f0c7}';
 
@@ -89,6 +104,9 @@ pub const FILE_EXPORT: char = '\u{f56e}';
 /// List/Library - fa-list (U+F03A)
 pub const LIST: char = '\u{f03a}';
 
+/// Numbered list/Track numbering - fa-list-ol (U+F0CB)
+pub const LIST_OL: char = '\u{f0cb}';
+
 /// Gear/Settings - fa-gear (U+F013)
 pub const GEAR: char = '\u{f013}';
 
@@ -210,6 +228,12 @@ pub const XMARK: char = '\u{f00d}';
 /// Check - fa-check (U+F00C)
 pub const CHECK: char = '\u{f00c}';
 
+/// Pen/Edit - fa-pen (U+F304)
+pub const PEN: char = '\u{f304}';
+
+/// Trash/Remove - fa-trash (U+F1F8)
+pub const TRASH: char = '\u{f1f8}';
+
 /// Search - fa-magnifying-glass (U+F002)
 pub const SEARCH: char = '\u{f002}';
 
@@ -250,6 +274,12 @@ pub const CHIP: char = '\u{f2db}';
 /// Clock - fa-clock (U+F017)
 pub const CLOCK: char = '\u{f017}';
 
+/// Chart column - fa-chart-column (U+E0E3)
+pub const CHART: char = '\u{e0e3}';
+
+/// Crosshairs - fa-crosshairs (U+F05B)
+pub const LOCATE: char = '\u{f05b}';
+
 /// Gauge high - fa-gauge-high (U+F625)
 pub const GAUGE: char = '\u{f625}';
 
@@ -296,6 +326,9 @@ pub const EYE: char = '\u{f06e}';
 /// Eye slash - fa-eye-slash (U+F070)
 pub const EYE_SLASH: char = '\u{f070}';
 
+/// Lock - fa-lock (U+F023)
+pub const LOCK: char = '\u{f023}';
+
 // ============================================================================
 // Spinner Animation Frames
 // ============================================================================
@@ -1,11 +1,22 @@
 //! Message types for the Music Minder UI.
 
-use super::state::{ActivePane, LoadedCoverArt, SortColumn, VisualizationMode};
-use crate::{db, diagnostics, enrichment, library, organizer, player, scanner};
+use super::context_menu::ContextTarget;
+use super::state::{
+    ActivePane, BufferSizeChoice, CrossfadeChoice, FadeChoice, LibraryScope, LoadedCoverArt,
+    PopmSourceChoice, PreampChoice, SeekMarker, SeekMarkerKind, SortColumn, TrackDetailTab,
+    VisualizationMode, VolumeStepChoice,
+};
+use crate::{
+    activity, db, diagnostics, enrichment, history, library, organizer, plan, player, scanner,
+};
 use iced::keyboard;
 use iced::widget::scrollable::Viewport;
 use sqlx::SqlitePool;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// `tagging.genre_map`: spelling to genre
+pub type GenreRules = std::collections::BTreeMap<String, String>;
 
 /// All possible messages that can be sent in the application
 #[derive(Debug, Clone)]
@@ -17,9 +28,18 @@ pub enum Message {
     DbInitialized(Result<SqlitePool, String>),
     AudioDevicesEnumerated(Vec<String>), // Deferred audio device list
     FontLoaded,
+    StartAudio,          // Open the audio output once the window is up
+    FpcalcChecked(bool), // Whether fpcalc is installed (checked in the background)
 
     // Navigation
     SwitchPane(ActivePane),
+    GoTo(LibraryScope), // Show an artist's or album's tracks in the library
+    LocateCurrentTrack, // Scroll to and select the playing track
+
+    // Profile messages
+    SwitchProfile(String),         // Reopen the app on another library profile
+    NewProfileNameChanged(String), // Edit the "new profile" name field
+    CreateProfile,                 // Create the named profile and switch to it
 
     // Keyboard shortcuts
     KeyPressed(keyboard::Key, keyboard::Modifiers),
@@ -32,6 +52,7 @@ pub enum Message {
     ScanStopped,
     ScanEventReceived(library::ScanEvent),
     ScanFinished,
+    RatingSourceChanged(PopmSourceChoice), // Whose POPM ratings scans import
     TracksLoaded(Result<Vec<db::TrackWithMetadata>, String>),
     /// Progressive loading: first batch of tracks with total count
     TracksLoadedInitial(Result<(Vec<db::TrackWithMetadata>, i64), String>),
@@ -41,12 +62,23 @@ pub enum Message {
     // Scroll messages
     ScrollChanged(Viewport),
     PreviewScrollChanged(Viewport),
+    PaneScrolled(ActivePane, Viewport), // Main scrollable of any other pane
 
     // Search and filter messages
     SearchQueryChanged(String),
     SortByColumn(SortColumn),
     FilterByFormat(Option<String>),
     FilterByLossless(Option<bool>),
+    FilterByAddedWithin(Option<u32>),
+    FilterByLoudMaster(bool),
+    FilterByInstrumental(bool),
+    FilterHideExplicit(bool),
+    FilterByScope(Option<LibraryScope>),
+    AlbumSummaryLoaded(Result<Option<crate::db::AlbumSummary>, String>), // Rollups of the scoped album
+    FilterByMachineWritten(Option<&'static str>), // Tag field name, e.g. "album"
+    MachineWrittenLoaded(&'static str, Result<std::collections::HashSet<i64>, String>),
+    FilterHideSkipped(bool), // Leave out often skipped tracks (and keep them out of auto-queue)
+    OftenSkippedLoaded(Result<std::collections::HashSet<i64>, String>),
     ClearFilters,
 
     // Organize messages
@@ -59,23 +91,63 @@ pub enum Message {
     OrganizePreviewComplete,
     OrganizeConfirmPressed,
     OrganizeFileComplete(Result<(i64, String), String>),
-    OrganizeFinished,
+    OrganizeFinished(organizer::SyncReport),
     OrganizeCancelPressed,
+    OrganizePlanReady(plan::OperationPlan), // Dry-run plan built from the preview
+    OrganizeSimulated(organizer::Simulation), // Moves that would overwrite a file
+    OrganizeSkipConflicts,                  // Drop the conflicting moves from the plan
+    OrganizeToggleMove(usize),              // Leave a previewed move out, or back in
+    OrganizeToggleAlbum(usize),             // ...every move of an album (index of its group)
+    OrganizeExpandAlbum(usize),             // Show or hide an album's moves
+    OrganizeCollapseAll(bool),              // Hide (or show) the moves of every album
+    OrganizeDriftChecked(Vec<String>),      // Drift found before executing (empty = OK)
+    OrganizeExportPlan,                     // Save the dry-run plan as JSON/CSV
+    OrganizeLoadPlan,                       // Load a saved plan for execution
+    OrganizePlanLoaded(Result<plan::OperationPlan, String>),
+    PlanExported(Result<Option<PathBuf>, String>), // None = dialog cancelled
+    OrganizeRecover(organizer::RecoveryMode),      // Resume/roll back an interrupted organize
+    OrganizeRecoverComplete(organizer::RecoveryReport),
+    OrganizeManualOpen,  // Two panes: incoming folders and the library tree
+    OrganizeManualClose, // Back to the pattern inputs
+    OrganizeManualExpand(PathBuf), // Show or hide an incoming folder's files
+    OrganizeManualGrab(PathBuf), // Pick up an incoming folder or file
+    OrganizeManualHover(Option<PathBuf>), // Library folder under the cursor
+    OrganizeManualRelease, // Button released away from a library folder
+    OrganizeManualUnpick,
+    OrganizeManualDrop(PathBuf), // Move what's held into a library folder
+    OrganizeManualChecked(organizer::Simulation), // Conflicts of the dropped moves
+
+    // Kodi/Jellyfin sidecar files
+    NfoAfterOrganizeToggled(bool),
+    NfoExport(crate::nfo::Scope), // Write folder art and NFO files for these albums
+    NfoExportComplete(Result<crate::nfo::ExportReport, String>),
 
     // Undo messages
     UndoPressed,
-    UndoComplete(Result<usize, String>),
+    UndoComplete(Result<(usize, organizer::SyncReport), String>),
 
     // Enrichment messages
     EnrichmentApiKeyChanged(String),
     EnrichmentApiKeySave,  // Save API key to database
     EnrichmentApiKeySaved, // API key was saved successfully
+    SecretsPassphraseChanged(String),
+    SecretsUnlock,  // Unlock the encrypted credentials for this session
+    SecretsEncrypt, // Encrypt the credentials with the passphrase
+    SecretsDecrypt, // Store them in plain text again
+    SecretsDone(Result<crate::secrets::Status, String>),
+    EnrichmentAnalysisChanged(crate::config::AnalysisConfig), // CPU budget settings
+    EnrichmentWriteStrategyChanged(crate::metadata::WriteStrategy), // ID3 version, encoding, padding
+    EnrichmentFolderDefaultsLoaded(enrichment::folders::FolderRules),
+    EnrichmentFolderDefaultsAdd, // Pick a folder to add a rule for
+    EnrichmentFolderDefaultsPicked(Option<PathBuf>),
+    EnrichmentFolderDefaultsChanged(enrichment::folders::FolderDefaults),
+    EnrichmentFolderDefaultsRemove(String),
     EnrichmentTrackSelected(usize),
     EnrichmentIdentifyPressed,
     EnrichmentIdentifyResult(Result<enrichment::TrackIdentification, String>),
     EnrichmentClearResult,
     EnrichmentWriteTagsPressed,
-    EnrichmentWriteTagsResult(Result<usize, String>),
+    EnrichmentWriteTagsResult(Result<(usize, usize), String>), // Written, and put off while in use
 
     // Enrich pane messages (batch operations)
     EnrichAddFromLibrary,             // Open library selection
@@ -86,6 +158,7 @@ pub enum Message {
     EnrichFillOnlyToggled(bool),      // Toggle fill-only option
     EnrichFetchCoverArtToggled(bool), // Toggle fetch cover art option
     EnrichBatchIdentify,              // Start batch identification
+    EnrichBatchDiscMatched(Vec<(usize, enrichment::TrackIdentification)>), // Tracks matched by disc ID or album clustering
     EnrichBatchIdentifyResult(usize, Result<enrichment::TrackIdentification, String>), // Single track result
     EnrichBatchIdentifyWithAlts(
         usize,
@@ -94,16 +167,31 @@ pub enum Message {
                 enrichment::TrackIdentification,
                 Vec<enrichment::TrackIdentification>,
             ),
-            String,
+            crate::error::Failure,
         >,
     ), // With alternatives
-    EnrichBatchComplete,                   // All tracks processed
-    EnrichReviewResult(usize),             // Open result for review (show/hide alternatives)
-    EnrichWriteResult(usize),              // Write single result
-    EnrichWriteAllConfirmed,               // Write all confirmed results
-    EnrichExportReport,                    // Export results to file
-    EnrichToggleAlternatives(usize),       // Toggle alternatives list for result at index
+    EnrichBatchComplete, // All tracks processed
+    EnrichVerifyTags,    // Check the checked tracks' tags against their fingerprints
+    EnrichTagsVerified(Result<library::verify_tags::VerifyReport, String>), // Suspects go to the results
+    EnrichReviewResult(usize), // Open result for review (show/hide alternatives)
+    EnrichWriteResult(usize),  // Write single result
+    EnrichWriteAllConfirmed,   // Write all confirmed results
+    EnrichExportReport,        // Export results as a JSON/CSV/HTML report
+    EnrichReportExported(Result<Option<PathBuf>, String>), // Report saved (None if cancelled)
+    EnrichExportPlan,          // Export confirmed writes as a dry-run plan
+    EnrichPlanReady(plan::OperationPlan), // Tag-change plan built, ask where to save
+    EnrichToggleAlternatives(usize), // Toggle alternatives list for result at index
     EnrichSelectAlternative(usize, usize), // Select alternative for result (result_idx, alt_idx)
+    EnrichWriteInferredTrackNumbers, // Write guessed track numbers to tags
+    EnrichInferredTrackNumbersWritten(Result<usize, String>), // Tracks whose numbers were written
+    EnrichConflictsLoaded(Vec<crate::provenance::TagConflict>), // Suggestions awaiting review
+    EnrichConflictKeep(i64),   // Keep the hand-set value
+    EnrichConflictAccept(i64), // Write the suggestion instead
+    EnrichConflictResolved(Result<bool, String>), // Ok(true) if the file was written
+    EnrichAuditNumbering,      // Check track/disc numbering of the selected tracks' albums (or all)
+    EnrichNumberingAudited(Result<(usize, Vec<library::numbering::AlbumNumbering>), String>), // Albums checked, failing ones
+    EnrichNormalizeNumbering(Option<i64>), // Write the fixes for one album (None = all)
+    EnrichNumberingNormalized(Result<(usize, Vec<library::numbering::AlbumNumbering>), String>), // Files written, re-audited albums
 
     // Player messages
     PlayerPlay,
@@ -114,20 +202,48 @@ pub enum Message {
     PlayerPrevious,
     PlayerSeekPreview(f32), // While dragging - updates display only
     PlayerSeekRelease,      // On release - performs actual seek using stored preview position
-    PlayerVolumeChanged(f32),
-    PlayerPlayTrack(usize),     // Play track at index from library
-    PlayerQueueTrack(usize),    // Add track to queue
-    PlayerShuffleRandom,        // Shuffle 20-30 random tracks
-    PlayerSelectDevice(String), // Switch audio output device
-    PlayerTick,                 // Timer tick for updating UI
+    PlayerSeekTo(Duration), // Clicked a seek bar marker
+    SeekMarkersLoaded(PathBuf, SeekMarkerKind, Vec<SeekMarker>), // Markers found for a track
+    SilenceLoaded(PathBuf, Option<player::silence::Silence>), // Stored silence of the track that just loaded
+    PlayerTrimSilenceToggled(bool),
+    PlayerBufferSizeChanged(BufferSizeChoice),
+    PlayerFadeChanged(FadeChoice),
+    PlayerCrossfadeChanged(CrossfadeChoice),
+    PlayerNormalizationChanged(player::normalization::NormalizationMode),
+    PlayerPreampChanged(PreampChoice),
+    EqToggled(bool),
+    EqPresetChosen(player::equalizer::EqPreset),
+    EqBandChanged(usize, f32), // While dragging a band: heard, not saved
+    EqBandReleased,            // Save the gains
+    PlayerVolumeChanged(f32),  // New gain (amplitude, 0.0 - 1.0)
+    PlayerVolumeStep {
+        up: bool,
+    }, // Keyboard or mouse wheel: one volume step
+    PlayerVolumeStepChanged(VolumeStepChoice),
+    PlayerPlayTrack(usize),         // Play track at index from library
+    PlayerQueueTrack(usize),        // Add track to end of queue
+    PlayerPlayNext(usize),          // Insert track right after the current one
+    PlayerQueueAlbum(usize),        // Add track's album to end of queue
+    PlayerPlayAlbumNext(usize),     // Insert track's album right after the current one
+    PlayerPlayFiles(Vec<PathBuf>),  // Replace the queue with these files and play
+    PlayerQueueFiles(Vec<PathBuf>), // Add these files to end of queue
+    PlayerShuffleRandom,            // Shuffle 20-30 random tracks
+    PlayerStopAfterTrack,           // Toggle stopping once the current track ends
+    PlayerStopAfterAlbum,           // Toggle stopping once the current album ends
+    PlayerSelectDevice(String),     // Switch audio output device
+    PlayerTick,                     // Timer tick for updating UI
 
     // Queue management messages
-    QueueJumpTo(usize),      // Jump to track at index in queue
-    QueueRemove(usize),      // Remove track at index from queue
-    QueueClear,              // Clear entire queue
-    QueueToggleShuffle,      // Toggle shuffle mode
-    QueueCycleRepeat,        // Cycle repeat mode (Off -> All -> One -> Off)
-    PlayerVisualizationTick, // Fast tick for visualization
+    QueueJumpTo(usize),                       // Jump to track at index in queue
+    QueueRemove(usize),                       // Remove track at index from queue
+    QueueToggleStopAfter(usize),              // Toggle stopping after the track at index
+    QueueClear,                               // Clear entire queue
+    QueueToggleShuffle,                       // Toggle shuffle mode
+    QueueSetShuffleMode(player::ShuffleMode), // Choose how shuffle orders tracks
+    QueueCycleRepeat,                         // Cycle repeat mode (Off -> All -> One -> Off)
+    QueueSetEndOfQueue(player::EndOfQueue),   // Choose what plays once the queue runs out
+    QueueExtended(Vec<PathBuf>),              // Tracks to carry on with were picked
+    PlayerVisualizationTick,                  // Fast tick for visualization
     PlayerVisualizationModeChanged(VisualizationMode),
     PlayerEvent(player::PlayerEvent), // Event from audio thread (state changed, track loaded, etc.)
 
@@ -135,20 +251,128 @@ pub enum Message {
     MediaControlCommand(player::MediaControlCommand),
     MediaControlPoll, // Timer tick to poll for media control events
 
+    // Resume card messages
+    RecentAlbumsLoaded(Result<Vec<history::RecentAlbum>, String>),
+    ResumeSession,          // Restore the last queue and position
+    PlayRecentAlbum(usize), // Play an album from the resume card
+    ResumeDismiss,          // Hide the resume card
+
+    // Update check (Settings → About)
+    CheckForUpdates,
+    UpdateChecked(Result<Option<crate::updates::Release>, String>),
+    OpenReleasePage(String),
+
     // Diagnostics messages
     DiagnosticsRunPressed,
     DiagnosticsComplete(diagnostics::DiagnosticReport),
     DiagnosticsToggleCheck(String), // Toggle expanded state of a check by name
+    DiagnosticsFix(diagnostics::FixAction), // Run the tool a check links to
+    DiagnosticsFixDone(Result<String, String>),
+
+    // Usage statistics messages
+    StatsRefresh,
+    StatsLoaded(Result<crate::stats::UsageStats, String>),
+    StatsRangeChanged(Option<u32>), // Last N days (None = all time)
+    StatsExport,                    // Save the shown period as JSON/CSV
+    StatsExported(Result<Option<PathBuf>, String>),
+    StatsWrappedOpen(i32), // Show a year in review
+    StatsWrappedLoaded(Result<(Vec<i32>, crate::stats::wrapped::Wrapped), String>),
+    StatsWrappedClose,
+    StatsWrappedExport, // Save the year in review as HTML/SVG/JSON
+
+    // Playlists pane messages
+    PlaylistsLoaded(Result<Vec<db::playlists::Playlist>, String>),
+    PlaylistSelect(i64),
+    PlaylistTracksLoaded(i64, Result<Vec<db::playlists::PlaylistTrack>, String>),
+    PlaylistNewNameChanged(String),
+    PlaylistCreate,               // Create a playlist with the typed name
+    PlaylistCreateWith(Vec<i64>), // New playlist holding these tracks (by ID)
+    PlaylistRenameStart,          // Edit the shown playlist's name
+    PlaylistRenameChanged(String),
+    PlaylistRenameSubmit,
+    PlaylistRenameCancel,
+    PlaylistDelete(i64),
+    PlaylistAddTracks(i64, Vec<i64>), // Append tracks (by ID) to a playlist
+    PlaylistRemoveTrack(usize),       // Remove the shown playlist's entry at this position
+    PlaylistMoveTrack(usize, usize),  // Move the shown playlist's entry (from, to)
+    PlaylistPlay,                     // Play the shown playlist from the top
+    PlaylistQueue,                    // Add the shown playlist to the queue
+    PlaylistChanged(Result<Option<i64>, String>), // Edit saved; the playlist to show if it changes
+
+    // Activity timeline messages
+    ActivityRefresh,
+    ActivityLoaded(Result<Vec<activity::ActivityEntry>, String>),
+    ActivityRangeChanged(Option<u32>), // Last N days (None = all time)
+    ActivityKindFilter(Option<activity::ActivityKind>),
+    ActivityDateChanged(String), // "Go to date" input edited
+    ActivityDateSubmitted,       // Show only the entered day
+    ActivityExport,              // Save the visible entries as JSON/CSV
+    ActivityExported(Result<Option<PathBuf>, String>),
 
     // Cover art messages (background, non-blocking)
     CoverArtResolved(PathBuf, Result<LoadedCoverArt, String>),
+    CoverCacheLoaded(crate::cover::CacheStats), // Size of the downloaded cover cache
+    CoverCacheClear,                            // Delete every cached cover
+    CoverCacheCleared(Result<(), String>),
+    CoversFetch, // Fetch and write covers for albums missing one
+    CoversFetchComplete(Result<crate::cover::CoverBatchReport, String>),
+    ReplayGainScanShown,        // Measure the tracks the library pane shows
+    ReplayGainScan(Vec<usize>), // Measure these tracks (by index)
+    ReplayGainComplete(Result<crate::library::replaygain::ScanReport, String>),
+    FileHovered,                 // A file is dragged over the window
+    FileHoverLeft,               // ...and taken away again
+    FileDropped(PathBuf),        // Dropped on the window: an image sets the open album's cover
+    CoverPaste,                  // Ctrl+V: set the open album's cover from the copied image file
+    CoverPasted(Option<String>), // Clipboard text
+    CoverSet(
+        LibraryScope,
+        Result<crate::cover::manual::CoverChange, String>,
+    ),
+    CoverUndo, // Put back what the last cover set from an image replaced
+    CoverUndone(Result<(), String>),
+
+    // Genre manager (Settings → Library)
+    GenresLoad, // Read and count the library's genres
+    GenresLoaded(Result<Vec<library::genres::GenreCount>, String>),
+    GenresFilterChanged(String),
+    GenreToggled(String, bool), // Pick a genre to merge
+    GenresIntoChanged(String),  // Genre the picked ones merge into
+    GenresRememberToggled(bool),
+    GenresMerge,
+    GenresMerged(Result<(library::genres::Retagged, Option<GenreRules>), String>),
+    GenreRuleRemove(String), // Drop the rule for a spelling
+    GenreRulesSaved(Result<GenreRules, String>),
+
+    // Album artist fix wizard (Settings → Library)
+    AlbumArtistsScan, // Look for misfiled albums
+    AlbumArtistsScanned(Result<Vec<library::album_artists::AlbumArtistFix>, String>),
+    AlbumArtistFixToggled(usize, bool), // Pick a proposal to apply
+    AlbumArtistFixPreview(Option<usize>), // Show a proposal's albums before and after
+    AlbumArtistsApply,
+    AlbumArtistsApplied(Result<library::album_artists::FixReport, String>),
+    AlbumArtistsUndo, // Put back what the last apply changed
+    AlbumArtistsUndone(Result<library::album_artists::FixReport, String>),
+
+    // musicminder:// links
+    DeepLinkReceived(String), // From a later launch, or the command line that started the app
+    DeepLinkAlbumFound(Result<Option<LibraryScope>, String>), // Album of a linked release
 
     // Background scanner messages
     WatcherEvent(scanner::WatchEvent),
-    WatcherStarted,
+    WatcherStarted(tokio::sync::mpsc::Sender<scanner::WatchCommand>),
     WatcherStopped,
+    WatcherFolderStatus(PathBuf, Result<(), String>), // A folder started watching or went offline
+    WatchPathAdd,                                     // Pick a folder to add to the library
+    WatchPathPicked(Option<PathBuf>),
+    WatchPathRemove(PathBuf), // Stop watching a folder and drop it from the library
+    WatchPathToggle(PathBuf), // Pause or resume watching a folder
     LibraryFileChanged(PathBuf), // A file in the library changed, may need refresh
-    RescanLibrary,               // Force a full library rescan
+    RescanLibrary,            // Force a full library rescan
+
+    // Shutdown messages
+    CloseRequested,  // The window was asked to close
+    ShutdownTick,    // Check whether in-flight work has finished
+    ShutdownFlushed, // A final database write finished
 
     // Quality gardener messages
     GardenerStarted,
@@ -169,8 +393,13 @@ pub enum Message {
     QueueSelectPrevious,       // Move queue selection up
     QueueSelectNext,           // Move queue selection down
     QueueSelectIndex(usize),   // Select specific queue index
-    QueueMoveUp,               // Move selected queue item up (Alt+Up)
-    QueueMoveDown,             // Move selected queue item down (Alt+Down)
+    QueueExtendSelection {
+        down: bool,
+    }, // Grow queue selection (Shift+Up/Down)
+    QueueItemClicked(usize),   // Row click: play, or Ctrl/Shift+click to select
+    QueueMoveUp,               // Move selected queue items up (Ctrl+Up)
+    QueueMoveDown,             // Move selected queue items down (Ctrl+Down)
+    ModifiersChanged(keyboard::Modifiers), // Track held Ctrl/Shift for clicks
 
     // Queue drag-and-drop messages
     QueueDragStart {
@@ -180,8 +409,9 @@ pub enum Message {
     QueueDragMove {
         y: f32,
     }, // Mouse moved while dragging
-    QueueDragEnd,    // Mouse released - complete the drop
-    QueueDragCancel, // Drag cancelled (Escape, focus lost, etc.)
+    QueueDragEnd,        // Mouse released - complete the drop
+    QueueDragAutoScroll, // Timer tick while dragging near the queue's edges
+    QueueDragCancel,     // Drag cancelled (Escape, focus lost, etc.)
 
     PlaySelected,            // Play the selected track (Enter key)
     RemoveSelectedFromQueue, // Remove selected from queue (Delete key)
@@ -189,13 +419,75 @@ pub enum Message {
     // Easter egg messages
     PlaceholderClicked, // User clicked the empty album art placeholder
 
+    // File location actions (library list, queue, track detail)
+    RevealInFolder(PathBuf),   // Show the file in the system file manager
+    CopyPath(PathBuf),         // Copy the full path to the clipboard
+    CopyRelativePath(PathBuf), // Copy the path relative to its library folder
+
+    // Full-screen Now Playing view
+    NowPlayingBarClicked, // Click on the player bar's track info (double-click opens)
+    NowPlayingViewOpen,   // Show the full-screen Now Playing view
+    NowPlayingViewClose,  // Back to the normal layout
+    NowPlayingToggleFullscreen, // Window in/out of full screen (opens the view)
+    NowPlayingToggleLyrics, // Show/hide the lyrics panel
+    NowPlayingToggleIdleDim, // Dim after a while without input
+    NowPlayingCycleVisualization, // Next visualizer mode
+    NowPlayingLyricsLoaded(PathBuf, Option<String>),
+
+    // Scheduled maintenance
+    SchedulerTick,                                  // Check whether a job is due
+    SchedulerRunNow(crate::scheduler::Job),         // "Run now" in Settings
+    SchedulerToggleJob(crate::scheduler::Job),      // Enable/disable a job's schedule
+    SchedulerJobFinished(crate::scheduler::JobRun), // A job finished (or failed)
+    SchedulerRunsLoaded(Vec<crate::scheduler::JobRun>),
+
+    // Settings bundles
+    SettingsExport, // Pick where to save a bundle, then export
+    SettingsExported(Result<Option<PathBuf>, String>), // None: dialog cancelled
+    SettingsImportPick, // Pick a bundle to import
+    SettingsImportRead(Result<Option<(PathBuf, crate::settings_bundle::Bundle)>, String>),
+    SettingsImportToggle(crate::settings_bundle::Section), // Tick/untick in the import dialog
+    SettingsImportConfirm,                                 // Import the ticked sections
+    SettingsImportCancel,
+    SettingsImported(Result<Vec<crate::settings_bundle::Section>, String>),
+
+    // Config file
+    ConfigCheck, // See whether the config file changed on disk
+
+    // Background tasks popover
+    TasksPopoverToggle,
+    TasksPopoverClose,
+    TaskCancel(crate::tasks::TaskId), // Ask a running task to stop
+    WriteQueueTick,                   // Try deferred tag writes that are due
+    WriteQueueSettled(Vec<crate::write_queue::Settled>),
+    WriteQueueRetry(u64),         // Try a deferred write at the next tick
+    WriteQueueDiscard(u64),       // Give up on a deferred write
+    WriteQueueWatchToggled(bool), // Write as soon as a file is let go of
+
+    // Mini-player
+    MiniPlayerToggle,            // Shrink the window to the mini-player and back
+    MiniPlayerToggleAlwaysOnTop, // Keep the mini-player above other windows
+
+    // Performance overlay
+    PerfOverlayToggle, // F12: show/hide decoder, buffer and frame timing stats
+
+    // Context menu
+    ContextMenuOpen(ContextTarget), // Right-click on a track, album or queue item
+    ContextMenuClose,               // Click outside the menu or Escape
+    ContextMenuSelect(Vec<Message>), // Entry chosen: close, then send these
+    CursorMoved(iced::Point),       // Window cursor position, for placing menus
+    WindowResized(iced::Size),      // Window size, for keeping menus on screen
+    LibraryRemoveTrack(PathBuf),    // Remove a track from the library (file stays)
+    LibraryTrackRemoved(Result<PathBuf, String>),
+    ShowOrganizeSection, // Switch to the library with the organize section open
+
     // Track detail messages
     TrackDetailOpen(usize), // Open detail view for track at index
     TrackDetailClose,       // Close detail view
     TrackDetailIdentify,    // Start identification for detailed track
     TrackDetailIdentifyResult(Result<enrichment::TrackIdentification, String>),
     TrackDetailWriteTags, // Write identified metadata to file
-    TrackDetailWriteResult(Result<usize, String>),
+    TrackDetailWriteResult(Result<crate::write_queue::Outcome, String>),
     TrackDetailRefresh, // Refresh current file's metadata from disk
     TrackDetailRefreshed(
         Result<
@@ -207,6 +499,17 @@ pub enum Message {
         >,
     ),
 
+    TrackDetailCompletenessLoaded(Result<Option<crate::completeness::AlbumCompleteness>, String>),
+    TrackDetailProvenanceLoaded(Result<Vec<crate::provenance::FieldProvenance>, String>),
+    TrackDetailCheckAlbum, // Compare the track's album with its MusicBrainz release
+    TrackDetailAlbumChecked(Result<Option<crate::completeness::AlbumCompleteness>, String>),
+    TrackDetailTab(TrackDetailTab), // Switch tabs; the Technical tab probes the file
+    // Technical info of the track at an index, probed or from the cache
+    TrackDetailTechnicalLoaded(
+        usize,
+        Result<crate::metadata::technical::TechnicalInfo, String>,
+    ),
+
     // Toast notification messages
     ToastDismiss(u64), // Dismiss a specific toast by ID
     ToastExpireTick,   // Periodic tick to remove expired toasts
@@ -1,6 +1,7 @@
 //! UI module for Music Minder.
 
 mod canvas;
+mod context_menu;
 pub mod icons;
 mod messages;
 mod platform;
@@ -11,64 +12,65 @@ mod update;
 mod views;
 
 use iced::widget::{container, text};
-use iced::{Element, Length, Subscription, Task, keyboard, time};
+use iced::{Element, Length, Subscription, Task, keyboard, time, window};
 use std::path::PathBuf;
 use std::time::Duration;
 
 pub use messages::Message;
-use state::AppState;
+pub use state::MiniPlayerState;
+use state::{ActivePane, AppState};
 
 pub struct MusicMinder {
     state: AppState,
 }
 
 impl MusicMinder {
+    /// Opens on the startup skeleton. The database and the icon font load
+    /// in the background; everything else starts once the database is open.
     pub fn new() -> (Self, Task<Message>) {
         use std::time::Instant;
 
-        let ui_init_start = Instant::now();
         tracing::debug!("UI::new() started");
 
-        let init_db = Task::perform(
-            async {
-                let db_start = Instant::now();
-                let result = crate::db::init_db("sqlite:music_minder.db")
-                    .await
-                    .map_err(|e| e.to_string());
-                tracing::info!(
-                    "Database init completed in {:.1}ms",
-                    db_start.elapsed().as_secs_f64() * 1000.0
-                );
-                result
-            },
-            Message::DbInitialized,
-        );
-
-        tracing::debug!(
-            "UI::new() task created in {:.1}ms",
-            ui_init_start.elapsed().as_secs_f64() * 1000.0
-        );
+        let font_start = Instant::now();
+        let load_font = iced::font::load(icons::ICON_FONT_BYTES).map(move |result| {
+            if let Err(e) = result {
+                tracing::warn!("Failed to load the icon font: {:?}", e);
+            }
+            crate::startup::record("icon font", font_start);
+            Message::FontLoaded
+        });
 
         (
             Self {
                 state: AppState::Loading,
             },
-            init_db,
+            Task::batch([update::init_db_task(), load_font]),
         )
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
+        // Closing the window goes through the shutdown coordinator
+        let close_requests = window::close_requests().map(|_| Message::CloseRequested);
         let AppState::Loaded(s) = &self.state else {
-            return Subscription::none();
+            return close_requests;
         };
 
-        let mut subscriptions = Vec::new();
+        let mut subscriptions = vec![close_requests];
+
+        // Waiting for in-flight work before exiting
+        if s.shutdown.is_some() {
+            subscriptions
+                .push(time::every(Duration::from_millis(100)).map(|_| Message::ShutdownTick));
+        }
 
         // Scan subscription
-        if s.is_scanning {
+        if s.is_scanning
+            && let Some(task) = &s.scan_task
+        {
             subscriptions.push(Subscription::run_with_id(
                 "scan-library",
-                streams::scan_stream(s.pool.clone(), s.scan_path.clone()),
+                streams::scan_stream(s.pool.clone(), s.scan_path.clone(), task.clone()),
             ));
         }
 
@@ -85,10 +87,12 @@ impl MusicMinder {
         }
 
         // Background file watcher - uses async channel to avoid blocking the runtime
-        if s.watcher_state.active && !s.watcher_state.watch_paths.is_empty() {
+        // The paths only matter when it starts: later changes go through
+        // the watcher's command channel
+        if s.watcher_state.active {
             subscriptions.push(Subscription::run_with_id(
                 "file-watcher",
-                streams::watcher_stream(s.watcher_state.watch_paths.clone()),
+                streams::watcher_stream(s.watcher_state.watch_paths()),
             ));
         }
 
@@ -100,17 +104,86 @@ impl MusicMinder {
         // Audio playback is on a separate thread and unaffected by this rate.
         subscriptions.push(time::every(Duration::from_millis(16)).map(|_| Message::PlayerTick));
 
+        // Scheduled maintenance: check once a minute whether a job is due
+        if s.scheduler.config.enabled {
+            subscriptions
+                .push(time::every(Duration::from_secs(60)).map(|_| Message::SchedulerTick));
+        }
+
+        // Edits to the config file made outside the UI
+        subscriptions.push(time::every(Duration::from_secs(2)).map(|_| Message::ConfigCheck));
+
+        // Tag writes put off while their file was in use
+        if crate::write_queue::len() > 0 {
+            subscriptions
+                .push(time::every(Duration::from_secs(5)).map(|_| Message::WriteQueueTick));
+        }
+
         // Keyboard shortcuts - global within the app
         subscriptions.push(keyboard::on_key_press(|key, modifiers| {
             Some(Message::KeyPressed(key, modifiers))
         }));
 
+        // Held modifiers, so row clicks can tell Ctrl/Shift+click apart; cursor
+        // position and window size, so context menus open where the click was;
+        // files dragged onto the window, which set covers
+        subscriptions.push(iced::event::listen_with(
+            |event, _status, _window| match event {
+                iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                    Some(Message::ModifiersChanged(modifiers))
+                }
+                iced::Event::Mouse(iced::mouse::Event::CursorMoved { position }) => {
+                    Some(Message::CursorMoved(position))
+                }
+                iced::Event::Window(
+                    iced::window::Event::Resized(size) | iced::window::Event::Opened { size, .. },
+                ) => Some(Message::WindowResized(size)),
+                iced::Event::Window(iced::window::Event::FileHovered(_)) => {
+                    Some(Message::FileHovered)
+                }
+                iced::Event::Window(iced::window::Event::FilesHoveredLeft) => {
+                    Some(Message::FileHoverLeft)
+                }
+                iced::Event::Window(iced::window::Event::FileDropped(path)) => {
+                    Some(Message::FileDropped(path))
+                }
+                _ => None,
+            },
+        ));
+
+        // Links and launches forwarded by later starts of the app
+        subscriptions.push(Subscription::run_with_id(
+            "instance",
+            streams::instance_stream(),
+        ));
+
+        // Keep scrolling the queue while a drag is held near its edge
+        if s.queue_drag.auto_scroll != 0.0 {
+            subscriptions
+                .push(time::every(Duration::from_millis(30)).map(|_| Message::QueueDragAutoScroll));
+        }
+
         Subscription::batch(subscriptions)
     }
 
+    /// Window title, naming the profile unless it's the default
+    pub fn title(&self) -> String {
+        if crate::profile::is_default() {
+            "Music Minder".to_string()
+        } else {
+            format!("Music Minder - {}", crate::profile::active())
+        }
+    }
+
     pub fn view(&self) -> Element<'_, Message> {
         let content: Element<Message> = match &self.state {
-            AppState::Loading => text("Loading database...").size(30).into(),
+            AppState::Loading => views::startup_skeleton(),
+            AppState::Loaded(s) if s.perf.open => {
+                let start = std::time::Instant::now();
+                let view = views::loaded_view(s);
+                s.perf.record_view(start.elapsed());
+                view
+            }
             AppState::Loaded(s) => views::loaded_view(s),
             AppState::Error(e) => text(format!("Error: {}", e))
                 .size(30)
@@ -124,7 +197,19 @@ impl MusicMinder {
             .into()
     }
 
+    /// Handle a message, timing it for the performance overlay
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        let start = std::time::Instant::now();
+        let task = self.dispatch(message);
+        if let AppState::Loaded(s) = &mut self.state
+            && s.perf.open
+        {
+            s.perf.record_update(start.elapsed());
+        }
+        task
+    }
+
+    fn dispatch(&mut self, message: Message) -> Task<Message> {
         // Debug: log every message type at top level
         let is_tick = matches!(
             message,
@@ -144,10 +229,18 @@ impl MusicMinder {
                 if let AppState::Loaded(s) = &mut self.state {
                     tracing::debug!("Audio devices enumerated: {:?}", devices);
                     s.audio_devices = devices.clone();
+                    s.startup.mark(crate::startup::Subsystem::Devices);
                 }
                 return Task::none();
             }
+            Message::SwitchProfile(name) => {
+                return update::handle_switch_profile(&mut self.state, name);
+            }
             Message::PickPath => return pick_folder(Message::PathPicked),
+            // Nothing to finish before the library has loaded
+            Message::CloseRequested if !matches!(self.state, AppState::Loaded(_)) => {
+                return iced::exit();
+            }
             Message::FontLoaded => return Task::none(), // Font loaded successfully
             _ => {}
         }
@@ -157,16 +250,96 @@ impl MusicMinder {
             return Task::none();
         };
 
+        // No new work from shortcuts while finishing up
+        if s.shutdown.is_some() && matches!(message, Message::KeyPressed(..)) {
+            return Task::none();
+        }
+
+        // Loaded track lists are moved into the state: a copy of a large
+        // library would briefly double its memory
+        let message = match message {
+            // Tracks loaded (legacy - full load)
+            Message::TracksLoaded(Ok(tracks)) => {
+                s.tracks = tracks;
+                s.tracks_loading = false;
+                s.tracks_total = Some(s.tracks.len() as i64);
+                s.status_message = format!("{} tracks loaded.", s.tracks.len());
+                s.startup.mark(crate::startup::Subsystem::Library);
+                update::refresh_manual(s);
+                return update::library_loaded(s);
+            }
+            // Progressive loading: initial batch
+            Message::TracksLoadedInitial(Ok((tracks, total))) => {
+                s.tracks = tracks;
+                s.tracks_total = Some(total);
+                let loaded = s.tracks.len();
+
+                if loaded as i64 >= total {
+                    // All tracks fit in initial batch
+                    s.tracks_loading = false;
+                    s.status_message = format!("{} tracks loaded.", loaded);
+                    s.startup.mark(crate::startup::Subsystem::Library);
+                    update::refresh_manual(s);
+                    return Task::batch([
+                        update::restore_scroll_task(s, ActivePane::Library),
+                        update::library_loaded(s),
+                    ]);
+                } else {
+                    // More tracks to load - update status and kick off remaining load
+                    s.status_message = format!("Loaded {} of {} tracks...", loaded, total);
+                    return update::load_tracks_remaining_task(
+                        s.pool.clone(),
+                        loaded as i64,
+                        total,
+                    );
+                }
+            }
+            // Progressive loading: remaining tracks
+            Message::TracksLoadedMore(Ok(tracks)) => {
+                s.tracks.extend(tracks);
+                s.tracks_loading = false;
+                s.status_message = format!("{} tracks loaded.", s.tracks.len());
+                s.startup.mark(crate::startup::Subsystem::Library);
+                update::refresh_manual(s);
+                // The list only appears once loading finishes
+                return Task::batch([
+                    update::restore_scroll_task(s, ActivePane::Library),
+                    update::library_loaded(s),
+                ]);
+            }
+            message => message,
+        };
+
         match &message {
             // Navigation
-            Message::SwitchPane(pane) => {
-                s.active_pane = *pane;
+            Message::SwitchPane(_)
+            | Message::PaneScrolled(..)
+            | Message::GoTo(_)
+            | Message::LocateCurrentTrack => {
+                return update::handle_navigation(s, message);
+            }
+            Message::NewProfileNameChanged(name) => {
+                s.new_profile_name = name.clone();
+            }
+            Message::CreateProfile => {
+                let name = s.new_profile_name.trim().to_string();
+                if let Err(e) = crate::profile::create(&name) {
+                    s.toasts.error(e.to_string());
+                    return Task::none();
+                }
+                s.new_profile_name.clear();
+                s.profiles = crate::profile::list();
+                return Task::done(Message::SwitchProfile(name));
             }
             Message::ToggleSidebar => {
                 s.sidebar_collapsed = !s.sidebar_collapsed;
             }
             Message::ToggleOrganizeSection => {
-                s.organize_collapsed = !s.organize_collapsed;
+                s.panes.library.organize_collapsed = !s.panes.library.organize_collapsed;
+            }
+            Message::ShowOrganizeSection => {
+                s.panes.library.organize_collapsed = false;
+                return Task::done(Message::SwitchPane(ActivePane::Library));
             }
             Message::PlaceholderClicked => {
                 s.easter_egg_clicks += 1;
@@ -179,12 +352,12 @@ impl MusicMinder {
 
             // Scroll updates
             Message::ScrollChanged(v) => {
-                s.scroll_offset = v.absolute_offset().y;
-                s.viewport_height = v.bounds().height;
+                s.panes.library.scroll_offset = v.absolute_offset().y;
+                s.panes.library.viewport_height = v.bounds().height;
             }
             Message::PreviewScrollChanged(v) => {
-                s.preview_scroll_offset = v.absolute_offset().y;
-                s.preview_viewport_height = v.bounds().height;
+                s.panes.library.preview_scroll_offset = v.absolute_offset().y;
+                s.panes.library.preview_viewport_height = v.bounds().height;
             }
 
             // Path updates
@@ -195,62 +368,32 @@ impl MusicMinder {
                 s.scan_path = p.clone();
             }
 
-            // Tracks loaded (legacy - full load)
-            Message::TracksLoaded(Ok(tracks)) => {
-                s.tracks = tracks.clone();
-                s.tracks_loading = false;
-                s.tracks_total = Some(s.tracks.len() as i64);
-                s.status_message = format!("{} tracks loaded.", s.tracks.len());
-            }
             Message::TracksLoaded(Err(e)) => {
                 s.tracks_loading = false;
                 s.status_message = format!("Error loading tracks: {}", e);
+                s.startup.mark(crate::startup::Subsystem::Library);
             }
 
-            // Progressive loading: initial batch
-            Message::TracksLoadedInitial(Ok((tracks, total))) => {
-                s.tracks = tracks.clone();
-                s.tracks_total = Some(*total);
-                let loaded = s.tracks.len();
-
-                if loaded as i64 >= *total {
-                    // All tracks fit in initial batch
-                    s.tracks_loading = false;
-                    s.status_message = format!("{} tracks loaded.", loaded);
-                    return Task::none();
-                } else {
-                    // More tracks to load - update status and kick off remaining load
-                    s.status_message = format!("Loaded {} of {} tracks...", loaded, total);
-                    return update::load_tracks_remaining_task(
-                        s.pool.clone(),
-                        loaded as i64,
-                        *total,
-                    );
-                }
-            }
             Message::TracksLoadedInitial(Err(e)) => {
                 s.tracks_loading = false;
                 s.status_message = format!("Error loading tracks: {}", e);
+                s.startup.mark(crate::startup::Subsystem::Library);
             }
 
-            // Progressive loading: remaining tracks
-            Message::TracksLoadedMore(Ok(tracks)) => {
-                s.tracks.extend(tracks.iter().cloned());
-                s.tracks_loading = false;
-                s.status_message = format!("{} tracks loaded.", s.tracks.len());
-            }
             Message::TracksLoadedMore(Err(e)) => {
                 // Keep partial results, just log error
                 s.tracks_loading = false;
                 tracing::error!("Error loading remaining tracks: {}", e);
                 s.status_message = format!("{} tracks loaded (some failed).", s.tracks.len());
+                s.startup.mark(crate::startup::Subsystem::Library);
             }
 
             // Scan messages
             Message::ScanPressed
             | Message::ScanStopped
             | Message::ScanFinished
-            | Message::ScanEventReceived(_) => {
+            | Message::ScanEventReceived(_)
+            | Message::RatingSourceChanged(_) => {
                 return update::handle_scan(s, &message);
             }
 
@@ -262,13 +405,42 @@ impl MusicMinder {
             | Message::OrganizePreviewPressed
             | Message::OrganizePreviewBatch(_)
             | Message::OrganizePreviewComplete
+            | Message::OrganizeToggleMove(_)
+            | Message::OrganizeToggleAlbum(_)
+            | Message::OrganizeExpandAlbum(_)
+            | Message::OrganizeCollapseAll(_)
             | Message::OrganizeCancelPressed
             | Message::OrganizeConfirmPressed
             | Message::OrganizeFileComplete(_)
-            | Message::OrganizeFinished => {
+            | Message::OrganizeFinished(_)
+            | Message::OrganizePlanReady(_)
+            | Message::OrganizeSimulated(_)
+            | Message::OrganizeSkipConflicts
+            | Message::OrganizeDriftChecked(_)
+            | Message::OrganizeExportPlan
+            | Message::OrganizeLoadPlan
+            | Message::OrganizePlanLoaded(_)
+            | Message::PlanExported(_)
+            | Message::OrganizeRecover(_)
+            | Message::OrganizeRecoverComplete(_)
+            | Message::OrganizeManualOpen
+            | Message::OrganizeManualClose
+            | Message::OrganizeManualExpand(_)
+            | Message::OrganizeManualGrab(_)
+            | Message::OrganizeManualHover(_)
+            | Message::OrganizeManualRelease
+            | Message::OrganizeManualUnpick
+            | Message::OrganizeManualDrop(_)
+            | Message::OrganizeManualChecked(_) => {
                 return update::handle_organize(s, message);
             }
 
+            Message::NfoAfterOrganizeToggled(_)
+            | Message::NfoExport(_)
+            | Message::NfoExportComplete(_) => {
+                return update::handle_nfo(s, message);
+            }
+
             // Undo messages
             Message::UndoPressed | Message::UndoComplete(_) => {
                 return update::handle_undo(s, message);
@@ -278,6 +450,18 @@ impl MusicMinder {
             Message::EnrichmentApiKeyChanged(_)
             | Message::EnrichmentApiKeySave
             | Message::EnrichmentApiKeySaved
+            | Message::SecretsPassphraseChanged(_)
+            | Message::SecretsUnlock
+            | Message::SecretsEncrypt
+            | Message::SecretsDecrypt
+            | Message::SecretsDone(_)
+            | Message::EnrichmentAnalysisChanged(_)
+            | Message::EnrichmentWriteStrategyChanged(_)
+            | Message::EnrichmentFolderDefaultsLoaded(_)
+            | Message::EnrichmentFolderDefaultsAdd
+            | Message::EnrichmentFolderDefaultsPicked(_)
+            | Message::EnrichmentFolderDefaultsChanged(_)
+            | Message::EnrichmentFolderDefaultsRemove(_)
             | Message::EnrichmentTrackSelected(_)
             | Message::EnrichmentIdentifyPressed
             | Message::EnrichmentIdentifyResult(_)
@@ -298,13 +482,29 @@ impl MusicMinder {
             | Message::EnrichBatchIdentify
             | Message::EnrichBatchIdentifyResult(_, _)
             | Message::EnrichBatchIdentifyWithAlts(_, _)
+            | Message::EnrichBatchDiscMatched(_)
             | Message::EnrichBatchComplete
+            | Message::EnrichVerifyTags
+            | Message::EnrichTagsVerified(_)
             | Message::EnrichReviewResult(_)
             | Message::EnrichToggleAlternatives(_)
             | Message::EnrichSelectAlternative(_, _)
             | Message::EnrichWriteResult(_)
             | Message::EnrichWriteAllConfirmed
-            | Message::EnrichExportReport => {
+            | Message::EnrichExportReport
+            | Message::EnrichReportExported(_)
+            | Message::EnrichExportPlan
+            | Message::EnrichPlanReady(_)
+            | Message::EnrichWriteInferredTrackNumbers
+            | Message::EnrichInferredTrackNumbersWritten(_)
+            | Message::EnrichConflictsLoaded(_)
+            | Message::EnrichConflictKeep(_)
+            | Message::EnrichConflictAccept(_)
+            | Message::EnrichConflictResolved(_)
+            | Message::EnrichAuditNumbering
+            | Message::EnrichNumberingAudited(_)
+            | Message::EnrichNormalizeNumbering(_)
+            | Message::EnrichNumberingNormalized(_) => {
                 return update::handle_enrich_pane(s, message);
             }
 
@@ -317,11 +517,30 @@ impl MusicMinder {
             | Message::PlayerPrevious
             | Message::PlayerSeekPreview(_)
             | Message::PlayerSeekRelease
+            | Message::PlayerSeekTo(_)
+            | Message::SeekMarkersLoaded(_, _, _)
+            | Message::SilenceLoaded(_, _)
+            | Message::PlayerTrimSilenceToggled(_)
+            | Message::PlayerBufferSizeChanged(_)
+            | Message::PlayerFadeChanged(_)
+            | Message::PlayerCrossfadeChanged(_)
+            | Message::PlayerNormalizationChanged(_)
+            | Message::PlayerPreampChanged(_)
             | Message::PlayerVolumeChanged(_)
+            | Message::PlayerVolumeStep { .. }
+            | Message::PlayerVolumeStepChanged(_)
             | Message::PlayerPlayTrack(_)
             | Message::PlayerQueueTrack(_)
+            | Message::PlayerPlayNext(_)
+            | Message::PlayerQueueAlbum(_)
+            | Message::PlayerPlayAlbumNext(_)
+            | Message::PlayerPlayFiles(_)
+            | Message::PlayerQueueFiles(_)
             | Message::PlayerTick
             | Message::PlayerShuffleRandom
+            | Message::PlayerStopAfterTrack
+            | Message::PlayerStopAfterAlbum
+            | Message::QueueToggleStopAfter(_)
             | Message::PlayerSelectDevice(_)
             | Message::PlayerVisualizationTick
             | Message::PlayerVisualizationModeChanged(_)
@@ -332,23 +551,159 @@ impl MusicMinder {
             | Message::QueueRemove(_)
             | Message::QueueClear
             | Message::QueueToggleShuffle
-            | Message::QueueCycleRepeat => {
+            | Message::QueueSetShuffleMode(_)
+            | Message::QueueCycleRepeat
+            | Message::QueueSetEndOfQueue(_)
+            | Message::QueueExtended(_)
+            | Message::ResumeSession
+            | Message::PlayRecentAlbum(_) => {
                 // Note: MediaControlPoll is now handled in PlayerTick for simplicity,
                 // but we keep it routed here as a fallback
                 return update::handle_player(s, message);
             }
 
+            // Resume card messages
+            Message::RecentAlbumsLoaded(_) | Message::ResumeDismiss => {
+                return update::handle_resume(s, message);
+            }
+
+            // Equalizer pane
+            Message::EqToggled(_)
+            | Message::EqPresetChosen(_)
+            | Message::EqBandChanged(_, _)
+            | Message::EqBandReleased => {
+                return update::handle_equalizer(s, message);
+            }
+
+            // Usage statistics messages
+            Message::StatsRefresh
+            | Message::StatsLoaded(_)
+            | Message::StatsRangeChanged(_)
+            | Message::StatsExport
+            | Message::StatsExported(_)
+            | Message::StatsWrappedOpen(_)
+            | Message::StatsWrappedLoaded(_)
+            | Message::StatsWrappedClose
+            | Message::StatsWrappedExport => {
+                return update::handle_stats(s, message);
+            }
+
+            // Playlists pane messages
+            Message::PlaylistsLoaded(_)
+            | Message::PlaylistSelect(_)
+            | Message::PlaylistTracksLoaded(_, _)
+            | Message::PlaylistNewNameChanged(_)
+            | Message::PlaylistCreate
+            | Message::PlaylistCreateWith(_)
+            | Message::PlaylistRenameStart
+            | Message::PlaylistRenameChanged(_)
+            | Message::PlaylistRenameSubmit
+            | Message::PlaylistRenameCancel
+            | Message::PlaylistDelete(_)
+            | Message::PlaylistAddTracks(_, _)
+            | Message::PlaylistRemoveTrack(_)
+            | Message::PlaylistMoveTrack(_, _)
+            | Message::PlaylistPlay
+            | Message::PlaylistQueue
+            | Message::PlaylistChanged(_) => {
+                return update::handle_playlists(s, message);
+            }
+
+            // Activity timeline messages
+            Message::ActivityRefresh
+            | Message::ActivityLoaded(_)
+            | Message::ActivityRangeChanged(_)
+            | Message::ActivityKindFilter(_)
+            | Message::ActivityDateChanged(_)
+            | Message::ActivityDateSubmitted
+            | Message::ActivityExport
+            | Message::ActivityExported(_) => {
+                return update::handle_activity(s, message);
+            }
+
+            // Update check
+            Message::CheckForUpdates | Message::UpdateChecked(_) | Message::OpenReleasePage(_) => {
+                return update::handle_updates(s, message);
+            }
+
             // Diagnostics messages
             Message::DiagnosticsRunPressed
             | Message::DiagnosticsComplete(_)
             | Message::DiagnosticsToggleCheck(_)
-            | Message::CoverArtResolved(_, _) => {
+            | Message::DiagnosticsFix(_)
+            | Message::DiagnosticsFixDone(_)
+            | Message::CoverArtResolved(_, _)
+            | Message::CoverCacheLoaded(_)
+            | Message::CoverCacheClear
+            | Message::CoverCacheCleared(_)
+            | Message::CoversFetch
+            | Message::CoversFetchComplete(_) => {
                 return update::handle_diagnostics(s, message);
             }
+            Message::ReplayGainScanShown
+            | Message::ReplayGainScan(_)
+            | Message::ReplayGainComplete(_) => {
+                return update::handle_replaygain(s, message);
+            }
+
+            // Covers set from a dropped or pasted image
+            Message::FileHovered
+            | Message::FileHoverLeft
+            | Message::FileDropped(_)
+            | Message::CoverPaste
+            | Message::CoverPasted(_)
+            | Message::CoverSet(_, _)
+            | Message::CoverUndo
+            | Message::CoverUndone(_) => {
+                return update::handle_covers(s, message);
+            }
+
+            // Album artist fix wizard
+            Message::AlbumArtistsScan
+            | Message::AlbumArtistsScanned(_)
+            | Message::AlbumArtistFixToggled(_, _)
+            | Message::AlbumArtistFixPreview(_)
+            | Message::AlbumArtistsApply
+            | Message::AlbumArtistsApplied(_)
+            | Message::AlbumArtistsUndo
+            | Message::AlbumArtistsUndone(_) => {
+                return update::handle_album_artists(s, message);
+            }
+            // Genre manager
+            Message::GenresLoad
+            | Message::GenresLoaded(_)
+            | Message::GenresFilterChanged(_)
+            | Message::GenreToggled(_, _)
+            | Message::GenresIntoChanged(_)
+            | Message::GenresRememberToggled(_)
+            | Message::GenresMerge
+            | Message::GenresMerged(_)
+            | Message::GenreRuleRemove(_)
+            | Message::GenreRulesSaved(_) => {
+                return update::handle_genres(s, message);
+            }
+            Message::DeepLinkReceived(_) | Message::DeepLinkAlbumFound(_) => {
+                return update::handle_links(s, message);
+            }
+
+            // Subsystems starting in the background
+            Message::StartAudio | Message::FpcalcChecked(_) => {
+                return update::handle_startup(s, message);
+            }
+
+            // Closing the app
+            Message::CloseRequested | Message::ShutdownTick | Message::ShutdownFlushed => {
+                return update::handle_shutdown(s, message);
+            }
 
             // File watcher messages
-            Message::WatcherStarted
+            Message::WatcherStarted(_)
             | Message::WatcherStopped
+            | Message::WatcherFolderStatus(_, _)
+            | Message::WatchPathAdd
+            | Message::WatchPathPicked(_)
+            | Message::WatchPathRemove(_)
+            | Message::WatchPathToggle(_)
             | Message::WatcherEvent(_)
             | Message::LibraryFileChanged(_)
             | Message::RescanLibrary => {
@@ -360,6 +715,16 @@ impl MusicMinder {
             | Message::SortByColumn(_)
             | Message::FilterByFormat(_)
             | Message::FilterByLossless(_)
+            | Message::FilterByLoudMaster(_)
+            | Message::FilterByInstrumental(_)
+            | Message::FilterHideExplicit(_)
+            | Message::FilterByScope(_)
+            | Message::AlbumSummaryLoaded(_)
+            | Message::FilterByAddedWithin(_)
+            | Message::FilterByMachineWritten(_)
+            | Message::MachineWrittenLoaded(..)
+            | Message::FilterHideSkipped(_)
+            | Message::OftenSkippedLoaded(_)
             | Message::ClearFilters => {
                 return update::handle_search_filter(s, message);
             }
@@ -371,11 +736,15 @@ impl MusicMinder {
             | Message::QueueSelectPrevious
             | Message::QueueSelectNext
             | Message::QueueSelectIndex(_)
+            | Message::QueueExtendSelection { .. }
+            | Message::QueueItemClicked(_)
             | Message::QueueMoveUp
             | Message::QueueMoveDown
+            | Message::ModifiersChanged(_)
             | Message::QueueDragStart { .. }
             | Message::QueueDragMove { .. }
             | Message::QueueDragEnd
+            | Message::QueueDragAutoScroll
             | Message::QueueDragCancel
             | Message::PlaySelected
             | Message::RemoveSelectedFromQueue => {
@@ -383,6 +752,69 @@ impl MusicMinder {
             }
 
             // Track detail messages
+            Message::NowPlayingBarClicked
+            | Message::NowPlayingViewOpen
+            | Message::NowPlayingViewClose
+            | Message::NowPlayingToggleFullscreen
+            | Message::NowPlayingToggleLyrics
+            | Message::NowPlayingToggleIdleDim
+            | Message::NowPlayingCycleVisualization
+            | Message::NowPlayingLyricsLoaded(_, _) => {
+                return update::handle_now_playing_view(s, message);
+            }
+
+            Message::SchedulerTick
+            | Message::SchedulerRunNow(_)
+            | Message::SchedulerToggleJob(_)
+            | Message::SchedulerJobFinished(_)
+            | Message::SchedulerRunsLoaded(_) => {
+                return update::handle_scheduler(s, message);
+            }
+
+            Message::SettingsExport
+            | Message::SettingsExported(_)
+            | Message::SettingsImportPick
+            | Message::SettingsImportRead(_)
+            | Message::SettingsImportToggle(_)
+            | Message::SettingsImportConfirm
+            | Message::SettingsImportCancel
+            | Message::SettingsImported(_) => {
+                return update::handle_settings_transfer(s, message);
+            }
+
+            Message::ConfigCheck => {
+                return update::handle_config_reload(s, message);
+            }
+
+            Message::TasksPopoverToggle
+            | Message::TasksPopoverClose
+            | Message::TaskCancel(_)
+            | Message::WriteQueueTick
+            | Message::WriteQueueSettled(_)
+            | Message::WriteQueueRetry(_)
+            | Message::WriteQueueDiscard(_)
+            | Message::WriteQueueWatchToggled(_) => {
+                return update::handle_tasks(s, message);
+            }
+
+            Message::MiniPlayerToggle | Message::MiniPlayerToggleAlwaysOnTop => {
+                return update::handle_mini_player(s, message);
+            }
+
+            Message::ContextMenuOpen(_)
+            | Message::ContextMenuClose
+            | Message::ContextMenuSelect(_)
+            | Message::CursorMoved(_)
+            | Message::WindowResized(_)
+            | Message::LibraryRemoveTrack(_)
+            | Message::LibraryTrackRemoved(_) => {
+                return update::handle_context_menu(s, message);
+            }
+
+            Message::RevealInFolder(_) | Message::CopyPath(_) | Message::CopyRelativePath(_) => {
+                return update::handle_file_actions(s, message);
+            }
+
             Message::TrackDetailOpen(_)
             | Message::TrackDetailClose
             | Message::TrackDetailIdentify
@@ -390,10 +822,20 @@ impl MusicMinder {
             | Message::TrackDetailWriteTags
             | Message::TrackDetailWriteResult(_)
             | Message::TrackDetailRefresh
-            | Message::TrackDetailRefreshed(_) => {
+            | Message::TrackDetailRefreshed(_)
+            | Message::TrackDetailCompletenessLoaded(_)
+            | Message::TrackDetailProvenanceLoaded(_)
+            | Message::TrackDetailCheckAlbum
+            | Message::TrackDetailAlbumChecked(_)
+            | Message::TrackDetailTab(_)
+            | Message::TrackDetailTechnicalLoaded(..) => {
                 return update::handle_track_detail(s, message);
             }
 
+            Message::PerfOverlayToggle => {
+                s.perf.toggle();
+            }
+
             // Toast notification messages
             Message::ToastDismiss(id) => {
                 s.toasts.remove(*id);
@@ -404,6 +846,8 @@ impl MusicMinder {
 
             // Keyboard shortcuts
             Message::KeyPressed(key, modifiers) => {
+                s.now_playing_view.wake();
+                s.scheduler.touch();
                 return update::handle_keyboard(s, key.clone(), *modifiers);
             }
 
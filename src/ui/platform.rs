@@ -1,6 +1,8 @@
-//! Platform-specific utilities for detecting user folders and system paths.
+//! Platform-specific utilities for detecting user folders and system paths,
+//! and for showing files in the system file manager.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 /// Get the user's Music folder, with fallbacks for various OS configurations.
 ///
@@ -40,10 +42,83 @@ pub fn get_user_music_folder() -> PathBuf {
     std::env::current_dir().unwrap_or_default()
 }
 
+/// Show a file in Explorer, Finder or the desktop's file manager.
+///
+/// Explorer and Finder select the file; elsewhere the containing folder is
+/// opened with `xdg-open`, which has no way to select a file.
+pub fn reveal_in_file_manager(path: &Path) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut c = Command::new("explorer");
+        c.arg(format!("/select,{}", path.display()));
+        c
+    } else if cfg!(target_os = "macos") {
+        let mut c = Command::new("open");
+        c.arg("-R").arg(path);
+        c
+    } else {
+        let mut c = Command::new("xdg-open");
+        c.arg(path.parent().unwrap_or(path));
+        c
+    };
+    command.spawn().map(|_| ())
+}
+
+/// Open a web page in the default browser
+pub fn open_url(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut c = Command::new("explorer");
+        c.arg(url);
+        c
+    } else if cfg!(target_os = "macos") {
+        let mut c = Command::new("open");
+        c.arg(url);
+        c
+    } else {
+        let mut c = Command::new("xdg-open");
+        c.arg(url);
+        c
+    };
+    command.spawn().map(|_| ())
+}
+
+/// A path relative to the library folder that contains it.
+///
+/// The deepest matching root wins, so nested watch folders give the shortest
+/// relative path. Returns `None` when the file is outside every root.
+pub fn library_relative_path(path: &Path, roots: &[PathBuf]) -> Option<PathBuf> {
+    roots
+        .iter()
+        .filter(|root| !root.as_os_str().is_empty())
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .min_by_key(|relative| relative.components().count())
+        .map(Path::to_path_buf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn library_relative_path_uses_deepest_root() {
+        let roots = [
+            PathBuf::from("/music"),
+            PathBuf::from("/music/Radiohead"),
+            PathBuf::new(),
+        ];
+        assert_eq!(
+            library_relative_path(Path::new("/music/Radiohead/OK Computer/01.flac"), &roots),
+            Some(PathBuf::from("OK Computer/01.flac"))
+        );
+        assert_eq!(
+            library_relative_path(Path::new("/music/Bjork/Post/01.flac"), &roots),
+            Some(PathBuf::from("Bjork/Post/01.flac"))
+        );
+        assert_eq!(
+            library_relative_path(Path::new("/elsewhere/01.flac"), &roots),
+            None
+        );
+    }
+
     #[test]
     fn get_music_folder_returns_valid_path() {
         let path = get_user_music_folder();
@@ -1,9 +1,15 @@
 //! Application state types for the Music Minder UI.
 
-use crate::{cover, db, diagnostics, enrichment, organizer, player};
+use crate::tasks::{TaskHandle, TaskRegistry};
+use crate::{cover, db, diagnostics, enrichment, history, organizer, plan, player};
+use iced::widget::scrollable;
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use sqlx::SqlitePool;
-use std::path::PathBuf;
+use std::cell::Cell;
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// Top-level application state
 ///
@@ -21,10 +27,11 @@ pub enum OrganizeView {
     Input, // Showing destination/pattern inputs
     Preview,    // Showing dry-run preview
     Organizing, // Currently organizing files
+    Manual,     // Dragging incoming folders into the library tree
 }
 
 /// The active tab/pane in the main view
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ActivePane {
     #[default]
     Library,
@@ -32,6 +39,605 @@ pub enum ActivePane {
     Enrich,
     Settings,
     Diagnostics,
+    Activity,
+    Stats,
+    Equalizer,
+    Playlists,
+}
+
+impl ActivePane {
+    /// Id of the scrollable holding the pane's main content
+    pub fn scroll_id(self) -> scrollable::Id {
+        scrollable::Id::new(match self {
+            ActivePane::Library => "library-tracks",
+            ActivePane::NowPlaying => "now-playing-queue",
+            ActivePane::Enrich => "enrich-tracks",
+            ActivePane::Settings => "settings",
+            ActivePane::Diagnostics => "diagnostics",
+            ActivePane::Activity => "activity-timeline",
+            ActivePane::Stats => "usage-stats",
+            ActivePane::Equalizer => "equalizer",
+            ActivePane::Playlists => "playlist-tracks",
+        })
+    }
+}
+
+/// Id of the organize preview scrollable in the library pane
+pub fn organize_preview_scroll_id() -> scrollable::Id {
+    scrollable::Id::new("organize-preview")
+}
+
+// ============================================================================
+// Per-pane view state
+// ============================================================================
+
+/// View state each pane keeps while another pane is showing.
+///
+/// Panes are rebuilt from scratch when shown again, so anything the user
+/// expects to find where they left it (scroll position, selection, expanded
+/// sections) lives here instead of in the widget tree. Saved per profile on
+/// every pane switch so it also survives restarts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaneStates {
+    /// Pane showing when this was saved (reopened on the next start)
+    pub last_pane: ActivePane,
+    pub library: LibraryPaneState,
+    pub now_playing: ScrollState,
+    pub enrich: ScrollState,
+    pub settings: ScrollState,
+    pub diagnostics: DiagnosticsPaneState,
+    pub activity: ScrollState,
+    pub stats: ScrollState,
+    pub equalizer: ScrollState,
+    pub playlists: ScrollState,
+    /// Full-screen Now Playing view preferences
+    pub now_playing_view: NowPlayingViewPrefs,
+}
+
+impl PaneStates {
+    const FILE: &'static str = crate::profile::VIEW_STATE_FILE;
+
+    /// Load the saved view state for the active profile
+    pub fn load() -> Option<Self> {
+        std::fs::read_to_string(crate::profile::data_path(Self::FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+    }
+
+    /// Save the view state for the active profile
+    pub fn save(&self) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(crate::profile::data_path(Self::FILE), json)
+    }
+
+    /// Saved scroll offset of a pane's main scrollable
+    pub fn scroll_offset(&self, pane: ActivePane) -> f32 {
+        match pane {
+            ActivePane::Library => self.library.scroll_offset,
+            ActivePane::NowPlaying => self.now_playing.offset,
+            ActivePane::Enrich => self.enrich.offset,
+            ActivePane::Settings => self.settings.offset,
+            ActivePane::Diagnostics => self.diagnostics.scroll_offset,
+            ActivePane::Activity => self.activity.offset,
+            ActivePane::Stats => self.stats.offset,
+            ActivePane::Equalizer => self.equalizer.offset,
+            ActivePane::Playlists => self.playlists.offset,
+        }
+    }
+
+    /// Record the scroll offset of a pane's main scrollable
+    pub fn set_scroll_offset(&mut self, pane: ActivePane, offset: f32) {
+        match pane {
+            ActivePane::Library => self.library.scroll_offset = offset,
+            ActivePane::NowPlaying => self.now_playing.offset = offset,
+            ActivePane::Enrich => self.enrich.offset = offset,
+            ActivePane::Settings => self.settings.offset = offset,
+            ActivePane::Diagnostics => self.diagnostics.scroll_offset = offset,
+            ActivePane::Activity => self.activity.offset = offset,
+            ActivePane::Stats => self.stats.offset = offset,
+            ActivePane::Equalizer => self.equalizer.offset = offset,
+            ActivePane::Playlists => self.playlists.offset = offset,
+        }
+    }
+}
+
+/// Scroll position of a pane with a single scrollable
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrollState {
+    pub offset: f32,
+    #[serde(skip)]
+    pub viewport_height: f32,
+}
+
+/// Library pane view state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibraryPaneState {
+    /// Track list scroll offset (drives virtualization)
+    pub scroll_offset: f32,
+    #[serde(skip)]
+    pub viewport_height: f32,
+    /// Organize preview scroll offset
+    pub preview_scroll_offset: f32,
+    #[serde(skip)]
+    pub preview_viewport_height: f32,
+    /// Selected index in the library list (into filtered_indices or tracks).
+    /// Not saved: filters and search start empty after a restart.
+    #[serde(skip)]
+    pub selection: Option<usize>,
+    /// Organize section collapsed state
+    pub organize_collapsed: bool,
+}
+
+impl Default for LibraryPaneState {
+    fn default() -> Self {
+        Self {
+            scroll_offset: 0.0,
+            viewport_height: 0.0,
+            preview_scroll_offset: 0.0,
+            preview_viewport_height: 0.0,
+            selection: None,
+            organize_collapsed: true, // Collapsed by default per design spec
+        }
+    }
+}
+
+/// Diagnostics pane view state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsPaneState {
+    pub scroll_offset: f32,
+    /// Which diagnostic checks are expanded (by check name)
+    pub expanded: HashSet<String>,
+}
+
+/// Full-screen Now Playing view preferences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NowPlayingViewPrefs {
+    /// Show the lyrics panel when the track has lyrics
+    pub show_lyrics: bool,
+    /// Dim the view after a while without mouse or keyboard input
+    pub idle_dim: bool,
+}
+
+impl Default for NowPlayingViewPrefs {
+    fn default() -> Self {
+        Self {
+            show_lyrics: true,
+            idle_dim: true,
+        }
+    }
+}
+
+/// Full-screen Now Playing view (opened by double-clicking the player bar)
+#[derive(Debug, Clone)]
+pub struct NowPlayingViewState {
+    /// Whether the view covers the app
+    pub open: bool,
+    /// Whether the window was switched to full screen for the view
+    pub window_fullscreen: bool,
+    /// Last mouse or keyboard input, for idle dimming
+    pub last_activity: std::time::Instant,
+    /// Lyrics of `lyrics_for`, if it has any
+    pub lyrics: Option<String>,
+    /// Track the lyrics were read from
+    pub lyrics_for: Option<PathBuf>,
+    /// Last click on the player bar's track info, for double-click detection
+    pub last_bar_click: Option<std::time::Instant>,
+}
+
+impl Default for NowPlayingViewState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            window_fullscreen: false,
+            last_activity: std::time::Instant::now(),
+            lyrics: None,
+            lyrics_for: None,
+            last_bar_click: None,
+        }
+    }
+}
+
+impl NowPlayingViewState {
+    /// Input-free time before the view dims
+    pub const IDLE_DIM_AFTER: std::time::Duration = std::time::Duration::from_secs(8);
+    /// Longest gap between the two clicks of a double-click
+    pub const DOUBLE_CLICK: std::time::Duration = std::time::Duration::from_millis(400);
+
+    /// Note mouse or keyboard input (undims the view)
+    pub fn wake(&mut self) {
+        self.last_activity = std::time::Instant::now();
+    }
+
+    /// Whether the open view should be drawn dimmed
+    pub fn is_dimmed(&self, prefs: NowPlayingViewPrefs) -> bool {
+        self.open && prefs.idle_dim && self.last_activity.elapsed() >= Self::IDLE_DIM_AFTER
+    }
+
+    /// Record a click on the player bar; true when it completes a double-click
+    pub fn register_bar_click(&mut self, now: std::time::Instant) -> bool {
+        let double = self
+            .last_bar_click
+            .is_some_and(|last| now.duration_since(last) <= Self::DOUBLE_CLICK);
+        // A third click starts a new pair rather than reopening
+        self.last_bar_click = (!double).then_some(now);
+        double
+    }
+
+    /// Lyrics for `track`, if they have been read
+    pub fn lyrics_of(&self, track: Option<&PathBuf>) -> Option<&str> {
+        (self.lyrics_for.as_ref() == track && track.is_some())
+            .then_some(self.lyrics.as_deref())
+            .flatten()
+    }
+}
+
+/// Compact mini-player window mode
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MiniPlayerState {
+    /// Whether the window is showing only the mini-player
+    pub active: bool,
+    /// Keep the mini-player window above other windows
+    pub always_on_top: bool,
+    /// Window size to go back to when leaving the mini-player
+    pub restore_size: Option<iced::Size>,
+}
+
+impl MiniPlayerState {
+    /// Window size of the mini-player
+    pub const SIZE: iced::Size = iced::Size::new(420.0, 132.0);
+}
+
+/// What a seek bar marker stands for; each feature sets its own kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)] // LoopPoint has no producer yet
+pub enum SeekMarkerKind {
+    /// Chapter or cue sheet track start
+    Chapter,
+    /// A-B loop point
+    LoopPoint,
+    /// Boundary found by silence detection
+    Silence,
+}
+
+/// A point on the seek bar; clicking it seeks there
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeekMarker {
+    pub kind: SeekMarkerKind,
+    /// Offset into the track
+    pub position: std::time::Duration,
+    /// Shown when hovering the marker
+    pub label: String,
+}
+
+/// Markers drawn on the seek bar (player bar and mini-player).
+///
+/// Markers belong to one track: setting markers for another track drops
+/// the old ones, and none are shown while a different track plays.
+#[derive(Debug, Clone, Default)]
+pub struct SeekMarkers {
+    track: Option<PathBuf>,
+    markers: Vec<SeekMarker>,
+}
+
+impl SeekMarkers {
+    /// Replace the markers of `kind` for `track`, leaving other kinds alone
+    pub fn set(&mut self, track: &Path, kind: SeekMarkerKind, markers: Vec<SeekMarker>) {
+        if self.track.as_deref() != Some(track) {
+            self.track = Some(track.to_path_buf());
+            self.markers.clear();
+        }
+        self.markers.retain(|m| m.kind != kind);
+        self.markers
+            .extend(markers.into_iter().filter(|m| m.kind == kind));
+        self.markers.sort_by_key(|m| m.position);
+    }
+
+    /// Keep the markers when their track's file moves to `to`
+    pub fn relocate(&mut self, from: &Path, to: &Path) {
+        if self.track.as_deref() == Some(from) {
+            self.track = Some(to.to_path_buf());
+        }
+    }
+
+    /// Remove the markers of `kind`
+    #[allow(dead_code)] // For features that turn their markers off
+    pub fn clear(&mut self, kind: SeekMarkerKind) {
+        self.markers.retain(|m| m.kind != kind);
+    }
+
+    /// Markers to draw while `track` plays, by position
+    pub fn for_track(&self, track: Option<&Path>) -> &[SeekMarker] {
+        if track.is_some() && self.track.as_deref() == track {
+            &self.markers
+        } else {
+            &[]
+        }
+    }
+}
+
+/// Seconds of level history kept for the performance section
+pub const LEVEL_HISTORY_SECS: u64 = 60;
+
+/// Time covered by one point of level history
+const LEVEL_HISTORY_STEP: Duration = Duration::from_millis(250);
+
+/// Points of level history kept
+pub const LEVEL_HISTORY_LEN: usize =
+    (LEVEL_HISTORY_SECS as u128 * 1000 / LEVEL_HISTORY_STEP.as_millis()) as usize;
+
+/// How long a peak stays on the meter before it falls
+const PEAK_HOLD: Duration = Duration::from_millis(1500);
+
+/// How long the clip indicator stays lit after a clip
+const CLIP_HOLD: Duration = Duration::from_secs(2);
+
+/// Quietest level the meters show
+pub const METER_FLOOR_DB: f32 = -60.0;
+
+/// Level in dBFS, floored at [`METER_FLOOR_DB`]
+pub fn to_dbfs(level: f32) -> f32 {
+    (20.0 * level.max(1e-6).log10()).max(METER_FLOOR_DB)
+}
+
+/// One step of level history (louder channel)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LevelPoint {
+    pub peak: f32,
+    pub rms: f32,
+    pub clipped: bool,
+}
+
+/// Live level meters in the player bar, and the last minute of levels
+#[derive(Debug, Clone, Default)]
+pub struct LevelMeterState {
+    /// Latest reading: peak since the previous tick, current RMS
+    pub current: player::simd::ChannelLevels,
+    /// Held peak per channel, and when it was reached
+    peak_hold: [(f32, Option<Instant>); 2],
+    /// Oldest first, one point per [`LEVEL_HISTORY_STEP`]
+    pub history: VecDeque<LevelPoint>,
+    /// Step being accumulated, and when it started
+    pending: (LevelPoint, Option<Instant>),
+    /// When samples last clipped
+    last_clip: Option<Instant>,
+    /// Clipped samples in the playing track
+    pub track_clips: u64,
+}
+
+impl LevelMeterState {
+    /// Take a reading from the player (while playing)
+    pub fn update(&mut self, levels: player::simd::ChannelLevels, now: Instant) {
+        for (ch, hold) in self.peak_hold.iter_mut().enumerate() {
+            let expired = hold.1.is_none_or(|at| now.duration_since(at) >= PEAK_HOLD);
+            if expired || levels.peak[ch] >= hold.0 {
+                *hold = (levels.peak[ch], Some(now));
+            }
+        }
+        if levels.clipped > 0 {
+            self.last_clip = Some(now);
+            self.track_clips += u64::from(levels.clipped);
+        }
+
+        let (point, started) = &mut self.pending;
+        point.peak = point.peak.max(levels.peak[0].max(levels.peak[1]));
+        point.rms = point.rms.max(levels.rms[0].max(levels.rms[1]));
+        point.clipped |= levels.clipped > 0;
+        let started = *started.get_or_insert(now);
+        if now.duration_since(started) >= LEVEL_HISTORY_STEP {
+            self.history.push_back(self.pending.0);
+            self.pending = (LevelPoint::default(), Some(now));
+            while self.history.len() > LEVEL_HISTORY_LEN {
+                self.history.pop_front();
+            }
+        }
+        self.current = levels;
+    }
+
+    /// Not playing: the meters drop to silence, the history pauses
+    pub fn idle(&mut self) {
+        self.current = Default::default();
+        self.peak_hold = Default::default();
+    }
+
+    /// A new track started
+    pub fn track_changed(&mut self) {
+        self.track_clips = 0;
+        self.last_clip = None;
+    }
+
+    /// Peak shown on the meter for a channel, including the hold
+    pub fn held_peak(&self, ch: usize) -> f32 {
+        self.peak_hold[ch].0.max(self.current.peak[ch])
+    }
+
+    /// Whether the clip indicator is lit
+    pub fn is_clipping(&self, now: Instant) -> bool {
+        self.last_clip
+            .is_some_and(|at| now.duration_since(at) < CLIP_HOLD)
+    }
+
+    /// Loudest peak in the history
+    pub fn max_peak(&self) -> f32 {
+        self.history.iter().map(|p| p.peak).fold(0.0, f32::max)
+    }
+}
+
+/// Ticks of ring buffer fill kept for the performance overlay (~4s)
+pub const PERF_HISTORY_LEN: usize = 240;
+
+/// How often the performance overlay's figures are recalculated
+const PERF_WINDOW: Duration = Duration::from_millis(500);
+
+/// Average and slowest of a set of timings
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimingStats {
+    pub avg: Duration,
+    pub max: Duration,
+}
+
+impl TimingStats {
+    fn of(times: &[Duration]) -> Self {
+        if times.is_empty() {
+            return Self::default();
+        }
+        Self {
+            avg: times.iter().sum::<Duration>() / times.len() as u32,
+            max: times.iter().copied().max().unwrap_or_default(),
+        }
+    }
+}
+
+/// Figures of the performance overlay, over the last window
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PerfReport {
+    /// Share of the time the decoder thread spent decoding (0.0 - 1.0)
+    pub decode_load: f32,
+    /// Share of the time it spent resampling
+    pub resample_load: f32,
+    /// Time `update` took per message
+    pub update: TimingStats,
+    /// Time `view` took per frame
+    pub view: TimingStats,
+    /// Time between player ticks (16ms when the UI keeps up)
+    pub tick: TimingStats,
+    /// Messages handled per second
+    pub messages_per_sec: f32,
+    /// Most player events waiting at a tick
+    pub peak_event_backlog: usize,
+    /// Channel backlogs at the latest tick
+    pub backlogs: player::ChannelBacklogs,
+    /// Player events dropped on a full channel since playback started
+    pub events_dropped: u32,
+    pub underruns: u32,
+}
+
+/// Debug overlay (F12): decoder load, ring buffer fill, the UI's
+/// update/view timing and the player's channel backlogs. Collects nothing
+/// while closed.
+#[derive(Debug, Default)]
+pub struct PerfOverlayState {
+    pub open: bool,
+    /// Ring buffer fill per tick (0 - 100), oldest first
+    pub fill_history: VecDeque<u8>,
+    /// Figures of the last full window
+    pub report: PerfReport,
+    /// `view` takes `&self`, so it leaves its time here for the next tick
+    last_view: Cell<Duration>,
+    update_times: Vec<Duration>,
+    view_times: Vec<Duration>,
+    tick_intervals: Vec<Duration>,
+    last_tick: Option<Instant>,
+    peak_event_backlog: usize,
+    /// Start of the window, with the decode and resample time then
+    window_start: Option<(Instant, u64, u64)>,
+}
+
+impl PerfOverlayState {
+    /// Show or hide the overlay; it starts afresh each time it opens
+    pub fn toggle(&mut self) {
+        *self = Self {
+            open: !self.open,
+            ..Default::default()
+        };
+    }
+
+    /// Time one message took to handle
+    pub fn record_update(&mut self, took: Duration) {
+        self.update_times.push(took);
+    }
+
+    /// Time one view took to build
+    pub fn record_view(&self, took: Duration) {
+        self.last_view.set(took);
+    }
+
+    /// Take a reading at a player tick. `backlogs` should be read before
+    /// the tick polls the player's events.
+    pub fn tick(
+        &mut self,
+        stats: &player::AudioPerformanceStats,
+        backlogs: player::ChannelBacklogs,
+        now: Instant,
+    ) {
+        if let Some(last) = self.last_tick.replace(now) {
+            self.tick_intervals.push(now.duration_since(last));
+        }
+        self.view_times.push(self.last_view.get());
+        self.fill_history
+            .push_back(stats.buffer_fill_percent.min(100) as u8);
+        while self.fill_history.len() > PERF_HISTORY_LEN {
+            self.fill_history.pop_front();
+        }
+        self.peak_event_backlog = self.peak_event_backlog.max(backlogs.events.queued);
+        self.report.backlogs = backlogs;
+        self.report.events_dropped = stats.events_dropped;
+        self.report.underruns = stats.underruns;
+
+        let Some((start, decode, resample)) = self.window_start else {
+            self.window_start = Some((now, stats.decode_nanos, stats.resample_nanos));
+            return;
+        };
+        let elapsed = now.duration_since(start);
+        if elapsed < PERF_WINDOW {
+            return;
+        }
+        let wall = elapsed.as_nanos() as f32;
+        let share = |now: u64, then: u64| (now.saturating_sub(then) as f32 / wall).min(1.0);
+        self.report.decode_load = share(stats.decode_nanos, decode);
+        self.report.resample_load = share(stats.resample_nanos, resample);
+        self.report.update = TimingStats::of(&self.update_times);
+        self.report.view = TimingStats::of(&self.view_times);
+        self.report.tick = TimingStats::of(&self.tick_intervals);
+        self.report.messages_per_sec = self.update_times.len() as f32 / elapsed.as_secs_f32();
+        self.report.peak_event_backlog = std::mem::take(&mut self.peak_event_backlog);
+        self.update_times.clear();
+        self.view_times.clear();
+        self.tick_intervals.clear();
+        self.window_start = Some((now, stats.decode_nanos, stats.resample_nanos));
+    }
+}
+
+/// Silence at the ends of the playing track, for "trim silence"
+#[derive(Debug, Clone, Default)]
+pub struct SilenceTrimState {
+    /// Skip leading and trailing silence during playback
+    pub enabled: bool,
+    /// Track `silence` was measured for
+    pub track: Option<PathBuf>,
+    /// Silence of that track, once loaded (None if never analyzed)
+    pub silence: Option<player::silence::Silence>,
+    /// The trailing silence was reached and the track ended early
+    pub end_reached: bool,
+}
+
+/// The play whose listening time is being counted
+#[derive(Debug, Clone, Default)]
+pub struct ListeningState {
+    pub track: Option<PathBuf>,
+    /// Genre tag of the track, stored with the play
+    pub genre: Option<String>,
+    pub clock: crate::history::ListenClock,
+    /// Length of the track, and the last position reached in it
+    pub duration: std::time::Duration,
+    pub position: std::time::Duration,
+}
+
+impl ListeningState {
+    /// The play so far, to store: (path, time listened, genre)
+    pub fn snapshot(&self) -> Option<(PathBuf, std::time::Duration, Option<String>)> {
+        let track = self.track.clone()?;
+        Some((track, self.clock.listened, self.genre.clone()))
+    }
+
+    /// Whether moving on now skips the track
+    pub fn is_skip(&self) -> bool {
+        self.track.is_some() && crate::history::is_skip(self.position, self.duration)
+    }
 }
 
 /// Visualization mode for the player
@@ -44,6 +650,139 @@ pub enum VisualizationMode {
     Off,
 }
 
+impl VisualizationMode {
+    /// The mode `audio.visualization_mode` names; the spectrum otherwise
+    pub fn from_config(name: &str) -> Self {
+        match name {
+            "waveform" => Self::Waveform,
+            "vu_meter" => Self::VuMeter,
+            "off" => Self::Off,
+            _ => Self::Spectrum,
+        }
+    }
+}
+
+/// Buffer size choice in the audio settings, in ms (`buffering::AUTO` = Auto)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferSizeChoice(pub u32);
+
+impl std::fmt::Display for BufferSizeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            player::buffering::AUTO => write!(f, "Auto"),
+            ms => write!(f, "{} ms", ms),
+        }
+    }
+}
+
+/// Fade length choice in the audio settings, in ms (0 = Off)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FadeChoice(pub u32);
+
+impl std::fmt::Display for FadeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            0 => write!(f, "Off"),
+            ms => write!(f, "{} ms", ms),
+        }
+    }
+}
+
+/// Crossfade choice in the audio settings, in seconds (0 = Off)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrossfadeChoice(pub u32);
+
+impl std::fmt::Display for CrossfadeChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            0 => write!(f, "Off"),
+            secs => write!(f, "{} s", secs),
+        }
+    }
+}
+
+/// Tag padding choice in the enrichment settings, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaddingChoice(pub u32);
+
+impl std::fmt::Display for PaddingChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            0 => write!(f, "None"),
+            bytes if bytes % 1024 == 0 => write!(f, "{} KB", bytes / 1024),
+            bytes => write!(f, "{} bytes", bytes),
+        }
+    }
+}
+
+/// Normalization preamp choice in the audio settings, in dB
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreampChoice(pub f32);
+
+impl std::fmt::Display for PreampChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 == 0.0 {
+            write!(f, "0 dB")
+        } else {
+            write!(f, "{:+} dB", self.0)
+        }
+    }
+}
+
+/// Volume step choice in the audio settings, in dB
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeStepChoice(pub f32);
+
+impl std::fmt::Display for VolumeStepChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} dB", self.0)
+    }
+}
+
+/// A folder's auto-accept confidence in the enrichment settings
+/// (`None` = the default)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceChoice(pub Option<f32>);
+
+impl ConfidenceChoice {
+    pub const ALL: [Self; 6] = [
+        Self(None),
+        Self(Some(0.5)),
+        Self(Some(0.7)),
+        Self(Some(0.8)),
+        Self(Some(0.9)),
+        Self(Some(0.95)),
+    ];
+}
+
+impl std::fmt::Display for ConfidenceChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            None => write!(
+                f,
+                "Default ({:.0}%)",
+                enrichment::folders::DEFAULT_MIN_CONFIDENCE * 100.0
+            ),
+            Some(c) => write!(f, "{:.0}%", c * 100.0),
+        }
+    }
+}
+
+/// Whose POPM frame ratings are imported from, in the library settings
+/// (empty = any player, highest wins)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PopmSourceChoice(pub String);
+
+impl std::fmt::Display for PopmSourceChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            write!(f, "Any player (highest)")
+        } else {
+            write!(f, "{}", crate::metadata::ratings::player_name(&self.0))
+        }
+    }
+}
+
 /// Which list currently has keyboard focus for navigation
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum FocusedList {
@@ -54,26 +793,54 @@ pub enum FocusedList {
 
 /// State for drag-and-drop reordering in the queue
 #[derive(Debug, Clone, Default)]
-#[allow(dead_code)] // Fields will be used when drag-drop is fully implemented
 pub struct QueueDragState {
     /// Item currently being dragged (if any)
     pub dragging: Option<DragInfo>,
-    /// Current drop target index (for insertion line visual)
+    /// Insertion point: the gap before this queue index (`len` = the end)
     pub drop_target: Option<usize>,
+    /// Auto-scroll speed in pixels per tick while the cursor is near an
+    /// edge (negative = up, 0 = not scrolling)
+    pub auto_scroll: f32,
+}
+
+impl QueueDragState {
+    /// Whether dropping now would change the queue order (used to hide the
+    /// insertion line while the block is still over its own position)
+    pub fn drop_changes_order(&self) -> bool {
+        let (Some(drag), Some(gap)) = (&self.dragging, self.drop_target) else {
+            return false;
+        };
+        let (Some(&first), Some(&last)) = (drag.indices.first(), drag.indices.last()) else {
+            return false;
+        };
+        let contiguous = last - first + 1 == drag.indices.len();
+        !(contiguous && (first..=last + 1).contains(&gap))
+    }
 }
 
 /// Information about an item being dragged
 #[derive(Debug, Clone)]
-#[allow(dead_code)] // Fields will be used when drag-drop is fully implemented
 pub struct DragInfo {
-    /// Original index of the dragged item in the queue
+    /// Index of the item whose grip was grabbed
     pub index: usize,
-    /// Y position at drag start (captured on first move event)
+    /// Every index moving with it (the multi-selection), sorted
+    pub indices: Vec<usize>,
+    /// Cursor Y at drag start in content coordinates (captured on first move)
     pub origin_y: Option<f32>,
-    /// Current cursor Y position
+    /// Current cursor Y position within the queue viewport
     pub current_y: f32,
-    /// Snapshot of shuffle state at drag start
-    pub is_shuffle_mode: bool,
+}
+
+impl VisualizationMode {
+    /// The mode after this one, for cycling with a shortcut
+    pub fn next(self) -> Self {
+        match self {
+            Self::Spectrum => Self::Waveform,
+            Self::Waveform => Self::VuMeter,
+            Self::VuMeter => Self::Off,
+            Self::Off => Self::Spectrum,
+        }
+    }
 }
 
 impl std::fmt::Display for VisualizationMode {
@@ -96,7 +863,41 @@ pub enum SortColumn {
     Album,
     Year,
     Duration,
+    /// ReplayGain track gain
+    Gain,
     Format,
+    DateAdded,
+    DateModified,
+}
+
+/// An artist's or album's tracks, shown by narrowing the library to them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LibraryScope {
+    Artist(String),
+    /// Albums are told apart by artist, like the `albums` table does
+    Album {
+        album: String,
+        artist: String,
+    },
+}
+
+impl LibraryScope {
+    /// The album of a track
+    pub fn album_of(track: &db::TrackWithMetadata) -> Self {
+        LibraryScope::Album {
+            album: track.album_name.to_string(),
+            artist: track.artist_name.to_string(),
+        }
+    }
+
+    pub fn matches(&self, track: &db::TrackWithMetadata) -> bool {
+        match self {
+            LibraryScope::Artist(artist) => track.artist_name == *artist,
+            LibraryScope::Album { album, artist } => {
+                track.album_name == *album && track.artist_name == *artist
+            }
+        }
+    }
 }
 
 /// Virtualization constants - defined once, used everywhere
@@ -109,6 +910,17 @@ pub mod virtualization {
     pub const DEFAULT_VIEWPORT_HEIGHT: f32 = 400.0;
     /// Number of items to render above/below visible area for smooth scrolling
     pub const SCROLL_BUFFER: usize = 5;
+
+    /// Scroll offset that puts row `index` in the middle of the viewport
+    /// (or as near as the top of the list allows)
+    pub fn offset_to_center(index: usize, viewport: f32, row_height: f32) -> f32 {
+        let viewport = if viewport > 0.0 {
+            viewport
+        } else {
+            DEFAULT_VIEWPORT_HEIGHT
+        };
+        (index as f32 * row_height - (viewport - row_height) / 2.0).max(0.0)
+    }
 }
 
 /// State for a fully loaded application
@@ -117,6 +929,8 @@ pub struct LoadedState {
 
     // Active pane
     pub active_pane: ActivePane,
+    /// View state each pane keeps across navigation and restarts
+    pub panes: PaneStates,
 
     // Scan state - PathBuf avoids repeated String->PathBuf conversions
     pub scan_path: PathBuf,
@@ -127,10 +941,8 @@ pub struct LoadedState {
     pub tracks_total: Option<i64>,
     pub status_message: String,
     pub scan_count: usize,
-
-    // Scroll state for track list
-    pub scroll_offset: f32,
-    pub viewport_height: f32,
+    /// Progress and cancel flag of the running scan
+    pub scan_task: Option<TaskHandle>,
 
     // Search and filter state
     pub search_query: String,
@@ -139,20 +951,43 @@ pub struct LoadedState {
     pub sort_ascending: bool,
     pub filter_format: Option<String>, // None = all formats, Some("FLAC") = only FLAC
     pub filter_lossless: Option<bool>, // None = all, Some(true) = lossless only
-
-    // Scroll state for preview list
-    pub preview_scroll_offset: f32,
-    pub preview_viewport_height: f32,
+    pub filter_added_within_days: Option<u32>, // None = any time, Some(30) = added in last 30 days
+    pub filter_loud_master: bool, // Only tracks whose ReplayGain says heavily limited or clipping
+    pub filter_instrumental: bool, // Only tracks whose language is "zxx" (no lyrics)
+    pub filter_hide_explicit: bool, // Leave out tracks tagged explicit
+    /// Only one artist's or album's tracks ("Go to artist/album")
+    pub filter_scope: Option<LibraryScope>,
+    /// Track count, length and bitrate of the scoped album, once loaded
+    pub album_summary: Option<db::AlbumSummary>,
+    /// Only tracks whose field was last written by a service or a guess:
+    /// the field name and the matching track ids
+    pub filter_machine_written: Option<(&'static str, HashSet<i64>)>,
+    /// Leave out often skipped tracks, by id (auto-queue too)
+    pub filter_hide_skipped: Option<HashSet<i64>>,
 
     // Organize state - PathBuf for destination avoids conversions
     pub organize_destination: PathBuf,
     pub organize_pattern: String,
+    /// Recent scan paths, destinations and patterns (persisted in config)
+    pub input_history: crate::config::InputHistory,
     pub organize_view: OrganizeView,
     pub organize_preview: Vec<organizer::OrganizePreview>,
     pub organize_progress: usize,
     pub organize_total: usize,
     // SmallVec: most organizes have 0-8 errors, avoid heap allocation
     pub organize_errors: SmallVec<[String; 8]>,
+    /// Progress and cancel flag of the running organize
+    pub organize_task: Option<TaskHandle>,
+    /// Dry-run plan for the current preview (stamped files + hash)
+    pub organize_plan: Option<plan::OperationPlan>,
+    /// Conflicts found playing the plan against the disk (`None` while checking)
+    pub organize_simulation: Option<organizer::Simulation>,
+    /// Organize journal left behind by a crash, awaiting resume/rollback
+    pub interrupted_organize: Option<organizer::IncompleteOrganize>,
+    /// The preview by album, and the moves left out of it
+    pub organize_review: PreviewReviewState,
+    /// Two-pane manual organize (incoming folders → library tree)
+    pub manual_organize: ManualOrganizeState,
     pub can_undo: bool,
     pub preview_loading: bool,
 
@@ -162,6 +997,24 @@ pub struct LoadedState {
     // Enrichment pane state (batch operations)
     pub enrichment_pane: EnrichmentPaneState,
 
+    /// Placeholder tag values treated as empty in fill-only writes
+    pub placeholders: crate::metadata::PlaceholderDetector,
+    /// Genre spellings written as another (`tagging.genre_map`)
+    pub genre_map: crate::metadata::genres::GenreMap,
+    /// Which hand-edited fields re-enrichment must leave alone
+    pub manual_edits: crate::provenance::ManualEditRules,
+    /// Kodi/Jellyfin sidecar file settings
+    pub nfo: crate::config::NfoConfig,
+
+    // Activity timeline state
+    pub activity: ActivityState,
+    pub stats: StatsState,
+    /// Playlists pane, and the playlists "Add to playlist" offers
+    pub playlists: PlaylistsState,
+
+    // "Pick up where you left off" card
+    pub resume: ResumeState,
+
     // Player state
     pub player: Option<player::Player>,
     pub player_state: player::PlayerState,
@@ -175,12 +1028,58 @@ pub struct LoadedState {
     /// Seek preview position - when user is dragging the slider
     /// None = not seeking, Some(pos) = user is dragging to this position
     pub seek_preview: Option<f32>,
+    /// Chapter, loop and silence markers on the seek bar
+    pub seek_markers: SeekMarkers,
+    pub silence_trim: SilenceTrimState,
+    /// Time listened to the current track, for the usage statistics
+    pub listening: ListeningState,
+    /// Buffer size setting in ms (`buffering::AUTO` = tune automatically)
+    pub audio_buffer_ms: u32,
+    /// Fade on pause, stop and seek in ms (0 = instant)
+    pub audio_fade_ms: u32,
+    /// Crossfade between tracks in seconds (0 = off)
+    pub audio_crossfade_secs: u32,
+    /// Which ReplayGain value playback applies
+    pub audio_normalization: player::normalization::NormalizationMode,
+    /// Gain added to the normalization gain, in dB
+    pub audio_preamp_db: f32,
+    /// Equalizer pane
+    pub equalizer: EqualizerState,
+    /// What plays once the queue runs out
+    pub end_of_queue: player::EndOfQueue,
+    /// Keyboard and mouse wheel volume step in dB
+    pub volume_step_db: f32,
+    /// Last queued track more were added after, so a queue is extended once
+    /// each time it runs out (even when nothing was found)
+    pub queue_extended_after: Option<PathBuf>,
+    pub level_meter: LevelMeterState,
+    /// Performance overlay (F12)
+    pub perf: PerfOverlayState,
+
+    /// POPM frame email ratings are imported from (empty = any player)
+    pub popm_email: String,
 
     // OS media controls (SMTC/MPRIS)
     pub media_controls: Option<player::MediaControlsHandle>,
+    /// Global media key listener, when `audio.media_key_fallback` is set
+    pub media_keys: Option<player::MediaKeyListener>,
 
     // Cover art state (non-blocking, resolved in background)
     pub cover_art: CoverArtState,
+    /// Size of the downloaded cover cache, read when Settings opens
+    pub cover_cache: Option<cover::CacheStats>,
+    /// An album cover batch is running
+    pub cover_fetch_running: bool,
+    /// Result of the last album cover batch
+    pub cover_fetch_report: Option<cover::CoverBatchReport>,
+    /// A ReplayGain scan is running
+    pub replaygain_running: bool,
+    /// A file is being dragged over the window
+    pub file_hovering: bool,
+    /// A cover is being set from an image, or undone
+    pub cover_setting: bool,
+    /// The last cover set from an image and its album, until undone
+    pub cover_undo: Option<(LibraryScope, cover::manual::CoverChange)>,
 
     // Diagnostics state
     pub diagnostics: Option<diagnostics::DiagnosticReport>,
@@ -188,8 +1087,18 @@ pub struct LoadedState {
     pub diagnostics_started_tick: u32,
     /// Pending result waiting for animation to complete
     pub diagnostics_pending: Option<diagnostics::DiagnosticReport>,
-    /// Which diagnostic checks are expanded (by check name)
-    pub diagnostics_expanded: std::collections::HashSet<String>,
+
+    /// Update check (Settings → About)
+    pub updates: UpdateState,
+
+    /// Genre manager (Settings → Library)
+    pub genres: GenreManagerState,
+
+    /// Album artist fix wizard (Settings → Library)
+    pub album_artists: AlbumArtistWizardState,
+
+    /// `musicminder://` link waiting for the library to load
+    pub pending_link: Option<String>,
 
     /// High resolution timer guard - requests 1ms timer while app runs
     /// This improves audio scheduling precision on Windows
@@ -205,25 +1114,49 @@ pub struct LoadedState {
     // Background file watcher state
     pub watcher_state: WatcherState,
 
+    /// The config file as last applied, to see what an edit changes
+    pub config_watch: ConfigWatchState,
+
     // Background quality gardener state
     pub gardener_state: GardenerState,
 
+    // Scheduled maintenance jobs
+    pub scheduler: SchedulerState,
+
+    /// Settings bundle export and the selective import dialog
+    pub settings_transfer: SettingsTransferState,
+
+    /// Subsystems that have finished starting in the background
+    pub startup: crate::startup::Readiness,
+
+    /// Set once the window was asked to close, while work is finishing
+    pub shutdown: Option<ShutdownState>,
+
+    // Long-running operations, listed in the "Background tasks" popover
+    pub tasks: TaskRegistry,
+    pub tasks_popover_open: bool,
+    /// `tagging.watch_locked_files`
+    pub watch_locked_files: bool,
+    /// `tagging.write`
+    pub write_strategy: crate::metadata::WriteStrategy,
+
     // Sidebar state
     pub sidebar_collapsed: bool,
 
-    // Organize section collapsed state
-    pub organize_collapsed: bool,
-
     // Selection tracking for keyboard navigation
     /// Which list has keyboard focus (Library or Queue)
     pub focused_list: FocusedList,
-    /// Selected index in the library list (into filtered_indices or tracks)
-    pub library_selection: Option<usize>,
-    /// Selected index in the queue list
+    /// Selected index in the queue list (the keyboard cursor)
     pub queue_selection: Option<usize>,
+    /// Further queue indices selected with Ctrl/Shift for moving as a block
+    pub queue_multi_selection: std::collections::BTreeSet<usize>,
+    /// Last queue change described in words, shown in the queue header so
+    /// keyboard and screen reader users get feedback on reorders
+    pub queue_announcement: Option<String>,
+    /// Keyboard modifiers currently held (for Ctrl/Shift+click)
+    pub keyboard_modifiers: iced::keyboard::Modifiers,
 
     // Queue drag-and-drop state
-    #[allow(dead_code)] // Will be used when drag-drop UI is fully implemented
     pub queue_drag: QueueDragState,
 
     // Easter egg state for empty album art placeholder
@@ -235,13 +1168,55 @@ pub struct LoadedState {
 
     // Toast notifications
     pub toasts: super::views::ToastQueue,
+
+    // Full-screen Now Playing view
+    pub now_playing_view: NowPlayingViewState,
+    // Compact mini-player window
+    pub mini_player: MiniPlayerState,
+
+    // Context menu (right-click on tracks, albums and queue items)
+    pub context_menu: Option<super::context_menu::ContextMenu>,
+    /// Last cursor position in the window
+    pub cursor_position: iced::Point,
+    /// Window size (zero until the window reports it)
+    pub window_size: iced::Size,
+
+    // Library profiles
+    /// Known profile names (default first)
+    pub profiles: Vec<String>,
+    /// Name typed into the "new profile" field
+    pub new_profile_name: String,
 }
 
 impl LoadedState {
+    /// Queue indices that reorders act on: the multi-selection plus the
+    /// cursor, sorted (empty when nothing is selected)
+    pub fn queue_selected_indices(&self) -> Vec<usize> {
+        let len = self.player.as_ref().map(|p| p.queue().len()).unwrap_or(0);
+        let mut indices: std::collections::BTreeSet<usize> = self.queue_multi_selection.clone();
+        indices.extend(self.queue_selection);
+        indices.into_iter().filter(|&i| i < len).collect()
+    }
+
+    /// Whether a queue row is part of the selection
+    pub fn is_queue_selected(&self, index: usize) -> bool {
+        self.queue_selection == Some(index) || self.queue_multi_selection.contains(&index)
+    }
+
     /// Initialize player if not already done
     pub fn ensure_player(&mut self) {
         if self.player.is_none() {
             self.player = player::Player::new();
+            if let Some(player) = &self.player {
+                player.set_buffer_ms(self.audio_buffer_ms);
+                player.set_fade_ms(self.audio_fade_ms);
+                player.set_crossfade_secs(self.audio_crossfade_secs);
+                player.set_normalization(self.audio_normalization, self.audio_preamp_db);
+                player.set_equalizer(self.equalizer.enabled, &self.equalizer.gains);
+            }
+            if let Some(player) = &mut self.player {
+                player.queue_mut().set_end_of_queue(self.end_of_queue);
+            }
             if self.player.is_none() {
                 self.status_message = "Failed to initialize audio output".to_string();
             }
@@ -281,8 +1256,8 @@ impl LoadedState {
         if let Some(track) = self.current_track_info() {
             return Some((
                 track.title.clone(),
-                track.artist_name.clone(),
-                track.album_name.clone(),
+                track.artist_name.to_string(),
+                track.album_name.to_string(),
             ));
         }
 
@@ -323,6 +1298,144 @@ impl LoadedState {
     }
 }
 
+/// State of the update check
+#[derive(Debug, Default)]
+pub struct UpdateState {
+    pub checking: bool,
+    /// Whether a check has finished since startup
+    pub checked: bool,
+    /// A newer release, if the last check found one
+    pub available: Option<crate::updates::Release>,
+    pub error: Option<String>,
+}
+
+/// State of the manual organize view
+#[derive(Debug, Default)]
+pub struct ManualOrganizeState {
+    /// Folders of tracks outside the destination folder
+    pub incoming: Vec<organizer::IncomingFolder>,
+    /// Folder tree under the destination folder
+    pub library: Vec<organizer::LibraryFolder>,
+    /// Incoming folder whose files are listed
+    pub expanded: Option<PathBuf>,
+    /// Incoming folder or file picked up, to drop on a library folder
+    pub held: Option<PathBuf>,
+    /// Whether the mouse button is still down on `held`
+    pub dragging: bool,
+    /// Library folder under the cursor
+    pub hover: Option<PathBuf>,
+    /// Moves of the drop being checked or run
+    pub moves: Vec<organizer::OrganizePreview>,
+}
+
+/// The organize preview by album, and the moves left out of it
+#[derive(Debug, Default)]
+pub struct PreviewReviewState {
+    /// Moves by album, grouped once the preview has loaded
+    pub groups: Vec<organizer::PreviewGroup>,
+    /// Albums showing only their header row
+    pub collapsed: HashSet<usize>,
+    /// Rows of the list: album headers, each followed by its moves unless
+    /// collapsed
+    pub rows: Vec<PreviewRow>,
+    /// Sources of the moves left out of the organize
+    pub excluded: HashSet<PathBuf>,
+}
+
+/// A row of the organize preview list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewRow {
+    /// Index into [`PreviewReviewState::groups`]
+    Album(usize),
+    /// Index into `LoadedState::organize_preview`
+    Move(usize),
+}
+
+impl PreviewReviewState {
+    /// Group `previews` by album, keeping what's left out
+    pub fn regroup(
+        &mut self,
+        previews: &[organizer::OrganizePreview],
+        album_of: impl Fn(i64) -> Option<String>,
+    ) {
+        self.groups = organizer::group_by_album(previews, album_of);
+        self.collapsed.clear();
+        let sources: HashSet<&Path> = previews.iter().map(|p| p.source.as_path()).collect();
+        self.excluded
+            .retain(|source| sources.contains(source.as_path()));
+        self.rebuild_rows();
+    }
+
+    /// Lay out the rows after albums open or close
+    pub fn rebuild_rows(&mut self) {
+        self.rows.clear();
+        for (index, group) in self.groups.iter().enumerate() {
+            self.rows.push(PreviewRow::Album(index));
+            if !self.collapsed.contains(&index) {
+                self.rows
+                    .extend(group.items.iter().map(|&item| PreviewRow::Move(item)));
+            }
+        }
+    }
+
+    /// Moves of album `group` that are left out
+    pub fn excluded_in(&self, group: usize, previews: &[organizer::OrganizePreview]) -> usize {
+        self.groups.get(group).map_or(0, |g| {
+            g.items
+                .iter()
+                .filter(|&&i| self.excluded.contains(&previews[i].source))
+                .count()
+        })
+    }
+
+    /// Whether every conflict `simulation` found is in a move left out
+    pub fn clears(&self, simulation: &organizer::Simulation) -> bool {
+        simulation
+            .conflicts
+            .iter()
+            .all(|c| self.excluded.contains(&c.source))
+    }
+
+    /// Start over for a new preview
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// State of the genre manager
+#[derive(Debug, Default)]
+pub struct GenreManagerState {
+    pub loading: bool,
+    /// Genres with their track counts, once loaded
+    pub counts: Option<Vec<crate::library::genres::GenreCount>>,
+    pub filter: String,
+    /// Genres picked to merge
+    pub selected: std::collections::BTreeSet<String>,
+    /// Genre the picked ones merge into
+    pub into: String,
+    /// Save the merge as rules for future scans and tag writes
+    pub remember: bool,
+    pub merging: bool,
+    /// `tagging.genre_map`, as last read or saved
+    pub rules: std::collections::BTreeMap<String, String>,
+}
+
+/// State of the album artist fix wizard
+#[derive(Debug, Default)]
+pub struct AlbumArtistWizardState {
+    pub scanning: bool,
+    /// Proposed fixes, once looked for
+    pub fixes: Option<Vec<crate::library::album_artists::AlbumArtistFix>>,
+    /// Proposals picked to apply, by index
+    pub selected: std::collections::BTreeSet<usize>,
+    /// Proposal whose albums are shown before and after
+    pub preview: Option<usize>,
+    /// Applying or undoing
+    pub applying: bool,
+    /// Whether the last apply can be undone
+    pub can_undo: bool,
+}
+
 /// State for the enrichment feature
 #[derive(Default)]
 pub struct EnrichmentState {
@@ -340,6 +1453,16 @@ pub struct EnrichmentState {
     pub last_error: Option<String>,
     /// Whether fpcalc is available
     pub fpcalc_available: bool,
+    /// CPU budget for fingerprinting
+    pub analysis: crate::config::AnalysisConfig,
+    /// Per-folder defaults, applied by the Enrich pane
+    pub folder_defaults: enrichment::folders::FolderRules,
+    /// Whether the credentials are encrypted, and unlocked
+    pub secrets: crate::secrets::Status,
+    /// Passphrase being typed to unlock or encrypt them
+    pub passphrase: String,
+    /// Whether a passphrase is being checked (it takes a moment)
+    pub secrets_busy: bool,
 }
 
 /// State for track detail modal view
@@ -361,6 +1484,33 @@ pub struct TrackDetailState {
     pub error: Option<String>,
     /// Whether tags were recently written
     pub tags_written: bool,
+    /// Stored completeness of the track's album
+    pub completeness: Option<crate::completeness::AlbumCompleteness>,
+    /// Whether the album is being checked against MusicBrainz
+    pub checking_album: bool,
+    /// Why the last album check gave no result
+    pub album_note: Option<String>,
+    /// Where the track's written fields came from
+    pub provenance: Vec<crate::provenance::FieldProvenance>,
+    /// Tab shown
+    pub tab: TrackDetailTab,
+    /// Codec, encoder and tag layout details, probed when the Technical
+    /// tab is first shown
+    pub technical: Option<crate::metadata::technical::TechnicalInfo>,
+    /// Whether the file is being probed
+    pub technical_loading: bool,
+    /// Why probing failed
+    pub technical_error: Option<String>,
+}
+
+/// Tabs of the track detail modal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrackDetailTab {
+    /// Tags, album completeness and identification
+    #[default]
+    Overview,
+    /// Codec profile, encoder, true peak and tag sizes
+    Technical,
 }
 
 /// Audio file format information
@@ -378,6 +1528,135 @@ pub struct FileFormatInfo {
     pub is_lossless: bool,
 }
 
+/// State for the "Pick up where you left off" card on the library pane
+#[derive(Debug, Clone, Default)]
+pub struct ResumeState {
+    /// Queue and position saved when the app was last used
+    pub session: Option<history::LastSession>,
+    /// Albums played most recently
+    pub recent_albums: Vec<history::RecentAlbum>,
+    /// Card closed by the user
+    pub dismissed: bool,
+}
+
+impl ResumeState {
+    /// Albums offered on the card
+    pub const MAX_ALBUMS: u32 = 5;
+
+    /// Whether there is anything to offer
+    pub fn has_content(&self) -> bool {
+        self.session.is_some() || !self.recent_albums.is_empty()
+    }
+}
+
+/// State of the equalizer pane
+#[derive(Debug, Clone, Default)]
+pub struct EqualizerState {
+    pub enabled: bool,
+    /// Preset the gains came from (Custom once a band is moved)
+    pub preset: player::equalizer::EqPreset,
+    /// Band gains in dB
+    pub gains: player::equalizer::EqGains,
+}
+
+impl EqualizerState {
+    pub fn from_config(audio: &crate::config::AudioConfig) -> Self {
+        Self {
+            enabled: audio.eq_enabled,
+            preset: audio.eq_preset,
+            gains: player::equalizer::clamp_gains(&audio.eq_gains_db),
+        }
+    }
+}
+
+/// State for the usage statistics pane
+#[derive(Default)]
+pub struct StatsState {
+    pub stats: crate::stats::UsageStats,
+    pub loading: bool,
+    /// Last N days (None = all time)
+    pub days: Option<u32>,
+    /// Year in review being shown instead of the period
+    pub wrapped: Option<crate::stats::wrapped::Wrapped>,
+    /// Years that can be reviewed, newest first
+    pub wrapped_years: Vec<i32>,
+    pub wrapped_loading: bool,
+}
+
+/// State for the playlists pane
+#[derive(Default)]
+pub struct PlaylistsState {
+    /// Every playlist, by name
+    pub playlists: Vec<crate::db::playlists::Playlist>,
+    /// Playlist whose tracks are shown
+    pub selected: Option<i64>,
+    /// Tracks of the shown playlist, in order
+    pub tracks: Vec<crate::db::playlists::PlaylistTrack>,
+    /// Name typed for a new playlist
+    pub new_name: String,
+    /// New name of the shown playlist while it's being renamed
+    pub renaming: Option<String>,
+}
+
+impl PlaylistsState {
+    /// The shown playlist
+    pub fn current(&self) -> Option<&crate::db::playlists::Playlist> {
+        let id = self.selected?;
+        self.playlists.iter().find(|p| p.id == id)
+    }
+
+    /// Whether another playlist than `except` has this name (names ignore case)
+    pub fn name_taken(&self, name: &str, except: Option<i64>) -> bool {
+        self.playlists
+            .iter()
+            .any(|p| Some(p.id) != except && p.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// "New Playlist", numbered past those already taken
+    pub fn unused_name(&self) -> String {
+        (1..)
+            .map(|n| match n {
+                1 => "New Playlist".to_string(),
+                n => format!("New Playlist {}", n),
+            })
+            .find(|name| !self.name_taken(name, None))
+            .unwrap_or_default()
+    }
+}
+
+/// State for the activity timeline pane
+#[derive(Default)]
+pub struct ActivityState {
+    /// Entries for the current filter, newest first
+    pub entries: Vec<crate::activity::ActivityEntry>,
+    pub loading: bool,
+    /// Range shown when no specific day is picked (None = all time)
+    pub days: Option<u32>,
+    /// Specific local day being inspected (overrides `days`)
+    pub day: Option<chrono::NaiveDate>,
+    /// Text of the "go to date" input (YYYY-MM-DD)
+    pub date_input: String,
+    /// Only show this kind of change
+    pub kind: Option<crate::activity::ActivityKind>,
+}
+
+impl ActivityState {
+    /// Maximum entries loaded into the timeline at once
+    pub const MAX_ENTRIES: u32 = 1000;
+
+    /// Build the database query for the current filter.
+    pub fn query(&self) -> crate::activity::ActivityQuery {
+        let mut query = match (self.day, self.days) {
+            (Some(day), _) => crate::activity::ActivityQuery::on_day(day),
+            (None, Some(days)) => crate::activity::ActivityQuery::last_days(days),
+            (None, None) => Default::default(),
+        };
+        query.kind = self.kind;
+        query.limit = Some(Self::MAX_ENTRIES);
+        query
+    }
+}
+
 /// State for the enrichment pane (batch operations)
 #[derive(Default)]
 pub struct EnrichmentPaneState {
@@ -399,8 +1678,28 @@ pub struct EnrichmentPaneState {
 
     /// Whether batch identification is in progress
     pub is_identifying: bool,
+    /// Whether a tag verification is in progress
+    pub verifying: bool,
+    /// What the last tag verification found (its suspects are the results)
+    pub verify_summary: Option<String>,
+    /// Progress and cancel flag of the running batch identification
+    pub task: Option<TaskHandle>,
+    /// The running batch's lookups, shared so MusicBrainz results are reused
+    pub service: Option<std::sync::Arc<crate::enrichment::EnrichmentService>>,
     /// Results of identification
     pub results: Vec<EnrichmentResult>,
+    /// Positions being identified right now
+    pub in_flight: std::collections::HashSet<usize>,
+    /// Whether guessed track numbers are being written to tags
+    pub writing_track_numbers: bool,
+    /// Suggestions held back because they disagree with hand-set values
+    pub conflicts: Vec<crate::provenance::TagConflict>,
+    /// Albums checked by the last numbering audit (None until one ran)
+    pub numbering_checked: Option<usize>,
+    /// Albums whose numbering failed the audit
+    pub numbering: Vec<crate::library::numbering::AlbumNumbering>,
+    /// Whether a numbering audit or normalization is running
+    pub numbering_busy: bool,
 }
 
 impl EnrichmentPaneState {
@@ -410,6 +1709,22 @@ impl EnrichmentPaneState {
             .iter()
             .any(|r| r.confirmed && r.status == ResultStatus::Success)
     }
+
+    /// Result indices in the order to review them: as they arrive while the
+    /// batch runs, then the least sure matches first and failures last
+    pub fn review_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.results.len()).collect();
+        if !self.is_identifying {
+            let key = |i: &usize| {
+                self.results[*i]
+                    .identification
+                    .as_ref()
+                    .map_or(f32::INFINITY, |id| id.review_score())
+            };
+            order.sort_by(|a, b| key(a).total_cmp(&key(b)));
+        }
+        order
+    }
 }
 
 /// Rate limit status for display
@@ -470,8 +1785,8 @@ pub struct EnrichmentResult {
     pub confidence: Option<f32>,
     /// List of fields that would change
     pub changes: Vec<String>,
-    /// Error message (if status is Error)
-    pub error: Option<String>,
+    /// What went wrong (if status is Error)
+    pub error: Option<crate::error::Failure>,
     /// Whether this result is confirmed for writing
     pub confirmed: bool,
     /// Full identification result for writing
@@ -523,6 +1838,14 @@ impl From<cover::CoverArt> for LoadedCoverArt {
     }
 }
 
+/// The config file as the running app last saw it
+pub struct ConfigWatchState {
+    /// The file's stamp when last read
+    pub stamp: Option<crate::config::FileStamp>,
+    /// The settings last applied
+    pub config: crate::config::Config,
+}
+
 /// State for background file watching.
 ///
 /// The watcher monitors the library directories and emits events when
@@ -532,12 +1855,89 @@ impl From<cover::CoverArt> for LoadedCoverArt {
 pub struct WatcherState {
     /// Whether the watcher is currently active
     pub active: bool,
-    /// Directories being watched
-    pub watch_paths: Vec<PathBuf>,
+    /// Library folders, watched or not (`library.paths`)
+    pub folders: Vec<WatchedFolder>,
     /// Number of pending file changes (not yet processed)
     pub pending_changes: usize,
     /// Last error (if any)
     pub last_error: Option<String>,
+    /// Adds and removes folders of the running watcher
+    pub command_tx: Option<tokio::sync::mpsc::Sender<crate::scanner::WatchCommand>>,
+}
+
+impl WatcherState {
+    /// Folders from the config; the user's music folder until any are added
+    pub fn from_config(library: &crate::config::LibraryConfig, music_folder: PathBuf) -> Self {
+        let paths = if library.paths.is_empty() {
+            vec![music_folder]
+        } else {
+            library.paths.clone()
+        };
+        Self {
+            active: true, // Start watching by default
+            folders: paths
+                .into_iter()
+                .map(|path| WatchedFolder {
+                    enabled: !library.unwatched_paths.contains(&path),
+                    ..WatchedFolder::new(path)
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Folders the watcher should be watching
+    pub fn watch_paths(&self) -> Vec<PathBuf> {
+        self.folders
+            .iter()
+            .filter(|f| f.enabled)
+            .map(|f| f.path.clone())
+            .collect()
+    }
+
+    /// The folder holding `path`
+    pub fn folder_of(&mut self, path: &Path) -> Option<&mut WatchedFolder> {
+        self.folders.iter_mut().find(|f| path.starts_with(&f.path))
+    }
+}
+
+/// A library folder and what the watcher has seen in it this session
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedFolder {
+    pub path: PathBuf,
+    /// Watched for changes; disabled folders stay in the library
+    pub enabled: bool,
+    /// Whether the watcher could watch it
+    pub status: FolderStatus,
+    /// When the last change in it was seen
+    pub last_event: Option<chrono::DateTime<chrono::Local>>,
+    pub created: u32,
+    pub modified: u32,
+    pub removed: u32,
+}
+
+impl WatchedFolder {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            enabled: true,
+            status: FolderStatus::Starting,
+            last_event: None,
+            created: 0,
+            modified: 0,
+            removed: 0,
+        }
+    }
+}
+
+/// Watch status of a library folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FolderStatus {
+    /// Not reported by the watcher yet
+    Starting,
+    Watching,
+    /// Couldn't be watched (missing drive or share); retried periodically
+    Offline(String),
 }
 
 /// State for the background quality gardener.
@@ -552,3 +1952,336 @@ pub struct GardenerState {
     /// Tracks needing attention
     pub tracks_needing_attention: usize,
 }
+
+/// Progress of closing the app (see `update::shutdown`)
+#[derive(Debug, Clone)]
+pub struct ShutdownState {
+    pub started: std::time::Instant,
+    /// Final database writes not acknowledged yet
+    pub flushing: usize,
+    /// What's still running, shown on the "finishing up" screen
+    pub waiting_on: Vec<String>,
+}
+
+/// Exporting and importing settings bundles (see [`crate::settings_bundle`]).
+#[derive(Debug, Default)]
+pub struct SettingsTransferState {
+    /// Bundle read for import, waiting for the sections to be picked
+    pub pending: Option<(PathBuf, crate::settings_bundle::Bundle)>,
+    /// Sections ticked in the import dialog
+    pub picked: HashSet<crate::settings_bundle::Section>,
+    /// Export or import in progress
+    pub busy: bool,
+}
+
+/// Scheduled maintenance jobs (see [`crate::scheduler`]).
+pub struct SchedulerState {
+    /// `[scheduler]` config section
+    pub config: crate::config::SchedulerConfig,
+    /// When the scheduler started (local time); jobs that never ran count
+    /// from here
+    pub started: chrono::NaiveDateTime,
+    /// Recent runs of every job, newest first
+    pub runs: Vec<crate::scheduler::JobRun>,
+    /// Job currently running (one at a time)
+    pub running: Option<crate::scheduler::Job>,
+    /// The running job was started from Settings rather than its schedule
+    pub running_manually: bool,
+    /// Progress and cancel flag of the running job
+    pub task: Option<TaskHandle>,
+    /// Last keyboard or mouse input, for `@idle` jobs
+    pub last_input: std::time::Instant,
+}
+
+impl SchedulerState {
+    pub fn new(config: crate::config::SchedulerConfig) -> Self {
+        Self {
+            config,
+            started: chrono::Local::now().naive_local(),
+            runs: Vec::new(),
+            running: None,
+            running_manually: false,
+            task: None,
+            last_input: std::time::Instant::now(),
+        }
+    }
+
+    /// Note user input
+    pub fn touch(&mut self) {
+        self.last_input = std::time::Instant::now();
+    }
+
+    /// Whether there has been no input for the configured idle time
+    pub fn is_idle(&self) -> bool {
+        self.last_input.elapsed()
+            >= std::time::Duration::from_secs(u64::from(self.config.idle_minutes) * 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pane_states_roundtrip() {
+        let mut panes = PaneStates {
+            last_pane: ActivePane::Settings,
+            ..Default::default()
+        };
+        panes.set_scroll_offset(ActivePane::Library, 1200.0);
+        panes.set_scroll_offset(ActivePane::Diagnostics, 80.0);
+        panes.library.viewport_height = 600.0;
+        panes.library.selection = Some(40);
+        panes.library.organize_collapsed = false;
+        panes
+            .diagnostics
+            .expanded
+            .insert("Audio Device".to_string());
+
+        let json = serde_json::to_string(&panes).unwrap();
+        let restored: PaneStates = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.last_pane, ActivePane::Settings);
+        assert_eq!(restored.scroll_offset(ActivePane::Library), 1200.0);
+        assert_eq!(restored.scroll_offset(ActivePane::Diagnostics), 80.0);
+        assert!(!restored.library.organize_collapsed);
+        assert!(restored.diagnostics.expanded.contains("Audio Device"));
+        // Layout-dependent and filter-dependent state is not carried over
+        assert_eq!(restored.library.viewport_height, 0.0);
+        assert_eq!(restored.library.selection, None);
+    }
+
+    #[test]
+    fn test_watcher_state_from_config() {
+        let library = crate::config::LibraryConfig {
+            paths: vec![PathBuf::from("/music"), PathBuf::from("/mnt/usb")],
+            unwatched_paths: vec![PathBuf::from("/mnt/usb")],
+            ..Default::default()
+        };
+        let mut state = WatcherState::from_config(&library, PathBuf::from("/home/me/Music"));
+
+        assert_eq!(state.folders.len(), 2);
+        assert_eq!(state.watch_paths(), vec![PathBuf::from("/music")]);
+        let folder = state
+            .folder_of(Path::new("/mnt/usb/Album/01.flac"))
+            .unwrap();
+        assert_eq!(folder.path, PathBuf::from("/mnt/usb"));
+        assert!(!folder.enabled);
+        assert!(state.folder_of(Path::new("/elsewhere/x.mp3")).is_none());
+
+        // No library paths yet: the platform music folder
+        let state = WatcherState::from_config(
+            &crate::config::LibraryConfig {
+                paths: Vec::new(),
+                ..Default::default()
+            },
+            PathBuf::from("/home/me/Music"),
+        );
+        assert_eq!(state.watch_paths(), vec![PathBuf::from("/home/me/Music")]);
+    }
+
+    #[test]
+    fn test_now_playing_double_click_and_idle_dim() {
+        use std::time::{Duration, Instant};
+
+        let mut view = NowPlayingViewState::default();
+        let t0 = Instant::now();
+        assert!(!view.register_bar_click(t0));
+        assert!(view.register_bar_click(t0 + Duration::from_millis(200)));
+        // The pair is used up; a third quick click doesn't count again
+        assert!(!view.register_bar_click(t0 + Duration::from_millis(300)));
+        // Too slow
+        assert!(!view.register_bar_click(t0 + Duration::from_secs(2)));
+
+        let prefs = NowPlayingViewPrefs::default();
+        view.last_activity = Instant::now() - NowPlayingViewState::IDLE_DIM_AFTER;
+        assert!(!view.is_dimmed(prefs), "closed view never dims");
+        view.open = true;
+        assert!(view.is_dimmed(prefs));
+        assert!(!view.is_dimmed(NowPlayingViewPrefs {
+            idle_dim: false,
+            ..prefs
+        }));
+        view.wake();
+        assert!(!view.is_dimmed(prefs));
+    }
+
+    #[test]
+    fn test_pane_states_defaults_for_missing_fields() {
+        let panes: PaneStates = serde_json::from_str(r#"{"last_pane":"Activity"}"#).unwrap();
+        assert_eq!(panes.last_pane, ActivePane::Activity);
+        assert!(panes.library.organize_collapsed);
+        assert_eq!(panes.scroll_offset(ActivePane::Settings), 0.0);
+    }
+
+    #[test]
+    fn test_seek_markers_per_track_and_kind() {
+        use std::time::Duration;
+
+        let marker = |kind, secs| SeekMarker {
+            kind,
+            position: Duration::from_secs(secs),
+            label: String::new(),
+        };
+        let (a, b) = (Path::new("/music/a.flac"), Path::new("/music/b.flac"));
+        let mut markers = SeekMarkers::default();
+        markers.set(
+            a,
+            SeekMarkerKind::Chapter,
+            vec![
+                marker(SeekMarkerKind::Chapter, 200),
+                marker(SeekMarkerKind::Chapter, 0),
+            ],
+        );
+        markers.set(
+            a,
+            SeekMarkerKind::LoopPoint,
+            vec![marker(SeekMarkerKind::LoopPoint, 90)],
+        );
+        let positions: Vec<u64> = markers
+            .for_track(Some(a))
+            .iter()
+            .map(|m| m.position.as_secs())
+            .collect();
+        assert_eq!(positions, [0, 90, 200]);
+        assert!(markers.for_track(Some(b)).is_empty());
+        assert!(markers.for_track(None).is_empty());
+
+        // Setting a kind again replaces only that kind
+        markers.set(a, SeekMarkerKind::Chapter, Vec::new());
+        assert_eq!(markers.for_track(Some(a)).len(), 1);
+        markers.clear(SeekMarkerKind::LoopPoint);
+        assert!(markers.for_track(Some(a)).is_empty());
+
+        // Another track starts from scratch
+        markers.set(
+            a,
+            SeekMarkerKind::Chapter,
+            vec![marker(SeekMarkerKind::Chapter, 5)],
+        );
+        markers.set(
+            b,
+            SeekMarkerKind::LoopPoint,
+            vec![marker(SeekMarkerKind::LoopPoint, 9)],
+        );
+        assert!(markers.for_track(Some(a)).is_empty());
+        assert_eq!(markers.for_track(Some(b)).len(), 1);
+    }
+
+    #[test]
+    fn test_drop_changes_order() {
+        let drag = |indices: Vec<usize>, gap| QueueDragState {
+            dragging: Some(DragInfo {
+                index: indices[0],
+                indices,
+                origin_y: None,
+                current_y: 0.0,
+            }),
+            drop_target: Some(gap),
+            auto_scroll: 0.0,
+        };
+        // A contiguous block dropped at its own edges or inside stays put
+        for gap in 2..=4 {
+            assert!(!drag(vec![2, 3], gap).drop_changes_order());
+        }
+        assert!(drag(vec![2, 3], 1).drop_changes_order());
+        assert!(drag(vec![2, 3], 5).drop_changes_order());
+        // A scattered selection always gets gathered together
+        assert!(drag(vec![1, 3], 2).drop_changes_order());
+        assert!(!QueueDragState::default().drop_changes_order());
+    }
+
+    #[test]
+    fn test_offset_to_center() {
+        use virtualization::{TRACK_ROW_HEIGHT, offset_to_center};
+        // Rows near the top can't be centred
+        assert_eq!(offset_to_center(2, 300.0, TRACK_ROW_HEIGHT), 0.0);
+        // Row 100 starts at 3000; its middle lands mid-viewport
+        assert_eq!(offset_to_center(100, 330.0, TRACK_ROW_HEIGHT), 2850.0);
+        // Before the first layout the default viewport is assumed
+        assert_eq!(
+            offset_to_center(100, 0.0, TRACK_ROW_HEIGHT),
+            offset_to_center(
+                100,
+                virtualization::DEFAULT_VIEWPORT_HEIGHT,
+                TRACK_ROW_HEIGHT
+            )
+        );
+    }
+
+    #[test]
+    fn test_level_meter_holds_peaks_and_clips() {
+        let start = Instant::now();
+        let reading = |peak: f32, clipped: u32| player::simd::ChannelLevels {
+            peak: [peak, peak / 2.0],
+            rms: [peak / 2.0, peak / 4.0],
+            clipped,
+        };
+        let mut meter = LevelMeterState::default();
+        meter.update(reading(1.0, 3), start);
+        assert!(meter.is_clipping(start + Duration::from_secs(1)));
+        assert!(!meter.is_clipping(start + CLIP_HOLD));
+
+        // The peak is held over quieter readings, then falls
+        meter.update(reading(0.1, 0), start + Duration::from_millis(500));
+        assert_eq!(meter.held_peak(0), 1.0);
+        meter.update(reading(0.1, 0), start + PEAK_HOLD);
+        assert_eq!(meter.held_peak(0), 0.1);
+
+        // One history point per step, capped at a minute
+        let mut now = start + PEAK_HOLD;
+        for _ in 0..LEVEL_HISTORY_LEN + 10 {
+            now += LEVEL_HISTORY_STEP;
+            meter.update(reading(0.5, 0), now);
+        }
+        assert_eq!(meter.history.len(), LEVEL_HISTORY_LEN);
+        assert_eq!(meter.track_clips, 3);
+        assert_eq!(meter.max_peak(), 0.5);
+
+        meter.track_changed();
+        assert_eq!(meter.track_clips, 0);
+        assert_eq!(to_dbfs(1.0), 0.0);
+        assert_eq!(to_dbfs(0.0), METER_FLOOR_DB);
+    }
+
+    #[test]
+    fn test_perf_overlay_windows() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut perf = PerfOverlayState::default();
+        perf.toggle();
+        assert!(perf.open);
+
+        let mut stats = player::AudioPerformanceStats {
+            buffer_fill_percent: 40,
+            ..Default::default()
+        };
+        let mut backlogs = player::ChannelBacklogs::default();
+        perf.tick(&stats, backlogs, start);
+
+        // Half a second: 100ms decoding, 50ms resampling, 3 events waiting once
+        for (at, update, queued) in [(200, 2, 0), (350, 4, 3), (500, 6, 1)] {
+            perf.record_update(ms(update));
+            perf.record_view(ms(1));
+            backlogs.events.queued = queued;
+            stats.decode_nanos += 33_333_334;
+            stats.resample_nanos += 16_666_667;
+            perf.tick(&stats, backlogs, start + ms(at));
+        }
+        let report = perf.report;
+        assert!((report.decode_load - 0.2).abs() < 0.01, "{:?}", report);
+        assert!((report.resample_load - 0.1).abs() < 0.01);
+        assert_eq!(report.update.avg, ms(4));
+        assert_eq!(report.update.max, ms(6));
+        assert_eq!(report.view.max, ms(1));
+        assert_eq!(report.tick.max, ms(200));
+        assert_eq!(report.messages_per_sec, 6.0);
+        assert_eq!(report.peak_event_backlog, 3);
+        assert_eq!(perf.fill_history.len(), 4);
+
+        // Closing drops what was collected
+        perf.toggle();
+        assert!(!perf.open);
+        assert!(perf.fill_history.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 //! Async streams for background operations (scanning, preview generation).
 
 use super::messages::Message;
+use crate::tasks::TaskHandle;
 use crate::{db, library, metadata, organizer, scanner};
 use futures::StreamExt;
 use rayon::prelude::*;
@@ -8,11 +9,22 @@ use sqlx::SqlitePool;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-/// Create a stream that scans a library directory and emits scan events
-pub fn scan_stream(pool: SqlitePool, path: PathBuf) -> impl futures::Stream<Item = Message> {
-    library::scan_library(pool, path)
+/// Create a stream that scans a library directory and emits scan events,
+/// ending with ScanStopped if `task` was cancelled
+pub fn scan_stream(
+    pool: SqlitePool,
+    path: PathBuf,
+    task: TaskHandle,
+) -> impl futures::Stream<Item = Message> {
+    library::scan_library(pool, path, task.clone())
         .map(Message::ScanEventReceived)
-        .chain(futures::stream::once(async { Message::ScanFinished }))
+        .chain(futures::stream::once(async move {
+            if task.is_cancelled() {
+                Message::ScanStopped
+            } else {
+                Message::ScanFinished
+            }
+        }))
 }
 
 /// Create a stream that generates organize previews in batches
@@ -87,6 +99,10 @@ pub fn preview_stream(
                         batch_tracks
                             .par_iter()
                             .filter_map(|track| {
+                                // Streams and cloud links have no file to move
+                                if !track.source.is_local() {
+                                    return None;
+                                }
                                 let source = PathBuf::from(&track.path);
                                 // File existence check - now runs in parallel
                                 if !source.exists() {
@@ -94,10 +110,10 @@ pub fn preview_stream(
                                 }
                                 let meta = metadata::TrackMetadata {
                                     title: track.title.clone(),
-                                    artist: track.artist_name.clone(),
-                                    album: track.album_name.clone(),
+                                    artist: track.artist_name.to_string(),
+                                    album: track.album_name.to_string(),
                                     duration: track.duration.unwrap_or(0) as u64,
-                                    track_number: track.track_number.map(|n| n as u32),
+                                    track_number: track.track_number,
                                 };
                                 Some(organizer::preview_organize(
                                     &source,
@@ -146,10 +162,19 @@ enum PreviewStreamState {
     Done,
 }
 
-/// Create a stream that watches a directory for file changes.
+/// How often folders that went offline are checked for coming back, and
+/// watched ones for disappearing
+const WATCH_RECHECK: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Create a stream that watches directories for file changes.
 ///
 /// Emits `WatcherEvent` messages whenever audio files are created,
-/// modified, or removed in the watched directories.
+/// modified, or removed in the watched directories, and `WatcherFolderStatus`
+/// when a folder starts being watched or can't be (an unplugged drive or
+/// unreachable share). Offline folders are retried every [`WATCH_RECHECK`].
+///
+/// `WatcherStarted` hands out a sender for [`scanner::WatchCommand`]s, so
+/// folders are added and removed without restarting the stream.
 ///
 /// Uses `tokio::sync::mpsc` with async `.recv().await` to avoid blocking
 /// Iced's cooperative async scheduler. This allows other subscriptions
@@ -161,15 +186,26 @@ pub fn watcher_stream(watch_paths: Vec<PathBuf>) -> impl futures::Stream<Item =
             match state {
                 WatcherStreamState::Init { watch_paths } => {
                     // Create the file watcher with async channel
-                    match scanner::FileWatcher::new_async(watch_paths.clone()) {
+                    let settle = crate::config::load().library.watch_settle();
+                    match scanner::FileWatcher::new_async(Vec::new(), settle) {
                         Ok((watcher, rx)) => {
                             tracing::info!(target: "ui::watcher", paths = ?watch_paths, "File watcher started (async)");
+                            let (command_tx, commands) = tokio::sync::mpsc::channel(16);
+                            let mut running = WatchRunning {
+                                watcher,
+                                rx,
+                                commands,
+                                watching: Vec::new(),
+                                offline: Vec::new(),
+                                pending: std::collections::VecDeque::new(),
+                                recheck: tokio::time::interval(WATCH_RECHECK),
+                            };
+                            for path in watch_paths {
+                                running.watch(path);
+                            }
                             Some((
-                                Message::WatcherStarted,
-                                WatcherStreamState::Running {
-                                    _watcher: watcher,
-                                    rx,
-                                },
+                                Message::WatcherStarted(command_tx),
+                                WatcherStreamState::Running(Box::new(running)),
                             ))
                         }
                         Err(e) => {
@@ -181,20 +217,14 @@ pub fn watcher_stream(watch_paths: Vec<PathBuf>) -> impl futures::Stream<Item =
                         }
                     }
                 }
-                WatcherStreamState::Running { _watcher, mut rx } => {
-                    // Non-blocking async receive - yields to other tasks while waiting
-                    match rx.recv().await {
-                        Some(event) => Some((
-                            Message::WatcherEvent(event),
-                            WatcherStreamState::Running { _watcher, rx },
-                        )),
-                        None => {
-                            // Channel closed (watcher dropped)
-                            tracing::warn!(target: "ui::watcher", "File watcher channel closed");
-                            Some((Message::WatcherStopped, WatcherStreamState::Done))
-                        }
+                WatcherStreamState::Running(mut running) => match running.next().await {
+                    Some(message) => Some((message, WatcherStreamState::Running(running))),
+                    None => {
+                        // Channel closed (watcher dropped)
+                        tracing::warn!(target: "ui::watcher", "File watcher channel closed");
+                        Some((Message::WatcherStopped, WatcherStreamState::Done))
                     }
-                }
+                },
                 WatcherStreamState::Done => None,
             }
         },
@@ -203,12 +233,116 @@ pub fn watcher_stream(watch_paths: Vec<PathBuf>) -> impl futures::Stream<Item =
 
 /// Internal state machine for watcher streaming
 enum WatcherStreamState {
-    Init {
-        watch_paths: Vec<PathBuf>,
-    },
-    Running {
-        _watcher: scanner::FileWatcher,
-        rx: tokio::sync::mpsc::Receiver<scanner::WatchEvent>,
-    },
+    Init { watch_paths: Vec<PathBuf> },
+    Running(Box<WatchRunning>),
     Done,
 }
+
+/// A running watcher and the folders it should be watching
+struct WatchRunning {
+    watcher: scanner::FileWatcher,
+    rx: tokio::sync::mpsc::Receiver<scanner::WatchEvent>,
+    commands: tokio::sync::mpsc::Receiver<scanner::WatchCommand>,
+    /// Folders being watched
+    watching: Vec<PathBuf>,
+    /// Folders to watch that couldn't be, retried on `recheck`
+    offline: Vec<PathBuf>,
+    /// Folder status changes not yet emitted
+    pending: std::collections::VecDeque<Message>,
+    recheck: tokio::time::Interval,
+}
+
+impl WatchRunning {
+    /// The next message; `None` once the watcher's channel closes
+    async fn next(&mut self) -> Option<Message> {
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                return Some(message);
+            }
+            tokio::select! {
+                // Non-blocking async receive - yields to other tasks while waiting
+                event = self.rx.recv() => return event.map(Message::WatcherEvent),
+                Some(command) = self.commands.recv() => match command {
+                    scanner::WatchCommand::Watch(path) => self.watch(path),
+                    scanner::WatchCommand::Unwatch(path) => self.unwatch(&path),
+                },
+                _ = self.recheck.tick() => self.recheck().await,
+            }
+        }
+    }
+
+    fn watch(&mut self, path: PathBuf) {
+        if self.watching.contains(&path) {
+            return;
+        }
+        let was_offline = self.offline.contains(&path);
+        self.offline.retain(|p| p != &path);
+        let result = self.watcher.watch(&path).map_err(|e| e.to_string());
+        match &result {
+            Ok(()) => self.watching.push(path.clone()),
+            Err(e) => {
+                self.offline.push(path.clone());
+                if was_offline {
+                    // Still offline: nothing new to report
+                    return;
+                }
+                tracing::warn!(target: "ui::watcher", path = %path.display(), error = %e, "Folder offline");
+            }
+        }
+        self.pending
+            .push_back(Message::WatcherFolderStatus(path, result));
+    }
+
+    fn unwatch(&mut self, path: &PathBuf) {
+        self.offline.retain(|p| p != path);
+        if let Some(i) = self.watching.iter().position(|p| p == path) {
+            self.watching.remove(i);
+            let _ = self.watcher.unwatch(path);
+        }
+    }
+
+    /// Watch offline folders that are back; mark watched ones that vanished
+    /// (a removed drive doesn't always produce a watch error)
+    async fn recheck(&mut self) {
+        for path in self.watching.clone() {
+            if !tokio::fs::try_exists(&path).await.unwrap_or(false) {
+                self.unwatch(&path);
+                self.offline.push(path.clone());
+                self.pending.push_back(Message::WatcherFolderStatus(
+                    path,
+                    Err("Folder not found".to_string()),
+                ));
+            }
+        }
+        for path in self.offline.clone() {
+            if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+                self.watch(path);
+            }
+        }
+    }
+}
+
+/// Create a stream of the links later launches forward here (see
+/// [`crate::instance`]). Ends at once if another window already takes them.
+pub fn instance_stream() -> impl futures::Stream<Item = Message> {
+    futures::stream::once(crate::instance::Listener::claim())
+        .filter_map(|claimed| async move {
+            match claimed {
+                Ok(Some(listener)) => Some(listener),
+                Ok(None) => {
+                    tracing::info!("Another window is taking links; this one won't");
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("Couldn't listen for other launches: {}", e);
+                    None
+                }
+            }
+        })
+        .flat_map(|listener| {
+            futures::stream::unfold(listener, |listener| async move {
+                let message = listener.next().await;
+                Some((Message::DeepLinkReceived(message), listener))
+            })
+        })
+}
@@ -479,6 +479,25 @@ pub fn button_ghost(_theme: &Theme, status: button::Status) -> button::Style {
     }
 }
 
+/// Link button - inline text that navigates (artist and album names)
+pub fn button_link(_theme: &Theme, status: button::Status) -> button::Style {
+    let bg = match status {
+        button::Status::Hovered => color::SURFACE_HOVER,
+        button::Status::Pressed => color::SURFACE_ELEVATED,
+        button::Status::Active | button::Status::Disabled => Color::TRANSPARENT,
+    };
+
+    button::Style {
+        background: Some(iced::Background::Color(bg)),
+        text_color: color::TEXT_SECONDARY,
+        border: Border {
+            radius: radius::SM.into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
 /// Danger button - for destructive actions
 pub fn button_danger(_theme: &Theme, status: button::Status) -> button::Style {
     let (bg, text) = match status {
@@ -0,0 +1,105 @@
+//! Activity timeline handlers.
+
+use iced::Task;
+
+use crate::activity;
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+
+/// Handle activity timeline messages
+pub fn handle_activity(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::ActivityRefresh => {
+            return load_activity(s);
+        }
+        Message::ActivityLoaded(result) => {
+            s.activity.loading = false;
+            match result {
+                Ok(entries) => s.activity.entries = entries,
+                Err(e) => s.toasts.error(format!("Failed to load activity: {}", e)),
+            }
+        }
+        Message::ActivityRangeChanged(days) => {
+            s.activity.days = days;
+            s.activity.day = None;
+            s.activity.date_input.clear();
+            return load_activity(s);
+        }
+        Message::ActivityKindFilter(kind) => {
+            s.activity.kind = kind;
+            return load_activity(s);
+        }
+        Message::ActivityDateChanged(input) => {
+            s.activity.date_input = input;
+        }
+        Message::ActivityDateSubmitted => {
+            let input = s.activity.date_input.trim();
+            if input.is_empty() {
+                s.activity.day = None;
+                return load_activity(s);
+            }
+            match chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+                Ok(day) => {
+                    s.activity.day = Some(day);
+                    return load_activity(s);
+                }
+                Err(_) => s
+                    .toasts
+                    .warning(format!("'{}' is not a date (use YYYY-MM-DD)", input)),
+            }
+        }
+        Message::ActivityExport => {
+            let entries = s.activity.entries.clone();
+            if entries.is_empty() {
+                s.toasts.info("Nothing to export for this period");
+                return Task::none();
+            }
+            return Task::perform(
+                async move {
+                    let Some(handle) = rfd::AsyncFileDialog::new()
+                        .set_file_name("music-minder-activity.csv")
+                        .add_filter("Spreadsheet (CSV)", &["csv"])
+                        .add_filter("JSON", &["json"])
+                        .save_file()
+                        .await
+                    else {
+                        return Ok(None);
+                    };
+                    let path = handle.path().to_path_buf();
+                    let save_path = path.clone();
+                    tokio::task::spawn_blocking(move || activity::export(&entries, &save_path))
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .map_err(|e| e.to_string())?;
+                    Ok(Some(path))
+                },
+                Message::ActivityExported,
+            );
+        }
+        Message::ActivityExported(result) => match result {
+            Ok(Some(path)) => s
+                .toasts
+                .success(format!("Activity exported to {}", path.display())),
+            Ok(None) => {}
+            Err(e) => s.toasts.error(format!("Export failed: {}", e)),
+        },
+        _ => {}
+    }
+    Task::none()
+}
+
+/// Reload the timeline for the current filter
+fn load_activity(s: &mut LoadedState) -> Task<Message> {
+    s.activity.loading = true;
+    let pool = s.pool.clone();
+    let query = s.activity.query();
+    Task::perform(
+        async move {
+            activity::query(&pool, &query)
+                .await
+                .map_err(|e| e.to_string())
+        },
+        Message::ActivityLoaded,
+    )
+}
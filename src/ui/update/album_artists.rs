@@ -0,0 +1,178 @@
+//! Album artist fix wizard handlers: finding misfiled albums, applying the
+//! picked fixes, and undoing them.
+
+use iced::Task;
+
+use crate::library::album_artists::{self, AlbumArtistUndo, FixReport};
+use crate::tasks::TaskKind;
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+use super::load_tracks_task;
+
+/// Report a finished apply or undo, then look again
+fn finished(s: &mut LoadedState, report: &FixReport, done: &str) -> Task<Message> {
+    for (path, error) in &report.failed {
+        tracing::warn!("Album artist fix: {}: {}", path, error);
+    }
+    let summary = format!("{} {} track(s)", done, report.tracks);
+    if report.failed.is_empty() {
+        s.toasts.success(summary);
+    } else {
+        s.toasts.warning(format!(
+            "{}; {} file(s) couldn't be written",
+            summary,
+            report.failed.len()
+        ));
+    }
+    s.album_artists.can_undo = AlbumArtistUndo::has_undo();
+    Task::batch([
+        handle_album_artists(s, Message::AlbumArtistsScan),
+        load_tracks_task(s.pool.clone()),
+    ])
+}
+
+/// Handle album artist fix wizard messages
+pub fn handle_album_artists(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::AlbumArtistsScan => {
+            if s.album_artists.scanning {
+                return Task::none();
+            }
+            s.album_artists.scanning = true;
+            let task = s.tasks.start(TaskKind::Maintenance, "Find misfiled albums");
+            task.set_phase("Comparing albums and folders");
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    let result =
+                        album_artists::find_fixes(&pool, album_artists::DEFAULT_MIN_CONFIDENCE)
+                            .await;
+                    task.finish();
+                    result.map_err(|e| e.to_string())
+                },
+                Message::AlbumArtistsScanned,
+            );
+        }
+        Message::AlbumArtistsScanned(result) => {
+            s.album_artists.scanning = false;
+            match result {
+                Ok(fixes) => {
+                    s.album_artists.selected = fixes
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, f)| f.confidence >= album_artists::PRESELECT_CONFIDENCE)
+                        .map(|(i, _)| i)
+                        .collect();
+                    s.album_artists.preview = None;
+                    s.album_artists.fixes = Some(fixes);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to find misfiled albums: {}", e);
+                    s.toasts
+                        .error(format!("Failed to find misfiled albums: {}", e));
+                }
+            }
+        }
+        Message::AlbumArtistFixToggled(index, on) => {
+            if on {
+                s.album_artists.selected.insert(index);
+            } else {
+                s.album_artists.selected.remove(&index);
+            }
+        }
+        Message::AlbumArtistFixPreview(index) => s.album_artists.preview = index,
+        Message::AlbumArtistsApply => {
+            let wizard = &mut s.album_artists;
+            let Some(fixes) = &wizard.fixes else {
+                return Task::none();
+            };
+            if wizard.applying || wizard.selected.is_empty() {
+                return Task::none();
+            }
+            let picked: Vec<_> = wizard
+                .selected
+                .iter()
+                .filter_map(|&i| fixes.get(i).cloned())
+                .collect();
+            wizard.applying = true;
+            let task = s.tasks.start(
+                TaskKind::Maintenance,
+                format!("Fix album artists of {} album(s)", picked.len()),
+            );
+            task.set_phase("Writing album artist tags");
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    let result = async {
+                        let report = album_artists::apply_fixes(&pool, &picked)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        if !report.undo.tracks.is_empty() {
+                            report.undo.save().map_err(|e| e.to_string())?;
+                        }
+                        Ok(report)
+                    }
+                    .await;
+                    task.finish();
+                    result
+                },
+                Message::AlbumArtistsApplied,
+            );
+        }
+        Message::AlbumArtistsApplied(result) => {
+            s.album_artists.applying = false;
+            match result {
+                Ok(report) => return finished(s, &report, "Regrouped"),
+                Err(e) => {
+                    tracing::warn!("Failed to fix album artists: {}", e);
+                    s.toasts
+                        .error(format!("Failed to fix album artists: {}", e));
+                }
+            }
+        }
+        Message::AlbumArtistsUndo => {
+            if s.album_artists.applying {
+                return Task::none();
+            }
+            s.album_artists.applying = true;
+            let task = s
+                .tasks
+                .start(TaskKind::Maintenance, "Undo album artist fixes");
+            task.set_phase("Restoring album tags");
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    let result = async {
+                        let log = tokio::task::spawn_blocking(AlbumArtistUndo::load)
+                            .await
+                            .map_err(|e| format!("Task error: {}", e))?
+                            .ok_or_else(|| "No album artist fixes to undo".to_string())?;
+                        let report = album_artists::undo_fixes(&pool, &log)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        AlbumArtistUndo::clear().map_err(|e| e.to_string())?;
+                        Ok(report)
+                    }
+                    .await;
+                    task.finish();
+                    result
+                },
+                Message::AlbumArtistsUndone,
+            );
+        }
+        Message::AlbumArtistsUndone(result) => {
+            s.album_artists.applying = false;
+            match result {
+                Ok(report) => return finished(s, &report, "Put back"),
+                Err(e) => {
+                    tracing::warn!("Failed to undo album artist fixes: {}", e);
+                    s.toasts
+                        .error(format!("Failed to undo album artist fixes: {}", e));
+                }
+            }
+        }
+        _ => {}
+    }
+    Task::none()
+}
@@ -0,0 +1,153 @@
+//! Picking up edits to the config file while the app runs.
+//!
+//! The file's stamp is checked every couple of seconds. When it changed,
+//! the file is read again and the settings that differ from the ones last
+//! applied take effect right away: audio, silence trimming, tagging rules,
+//! the CPU budget, scheduled jobs, offline mode, the cover cache limit and
+//! the watched library folders. A toast lists what changed; the few
+//! settings only read at startup get a second one asking for a restart.
+//!
+//! Saves made by the UI itself land in the file too. Those were applied
+//! when they were made, so they only update the snapshot.
+
+use iced::Task;
+
+use crate::config::{self, Config};
+use crate::{cover, enrichment, metadata};
+
+use super::super::messages::Message;
+use super::super::state::{EqualizerState, LoadedState, VisualizationMode};
+use super::db::acoustid_api_key;
+use super::watcher::sync_watch_paths;
+
+/// Handle config file checks
+pub fn handle_config_reload(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::ConfigCheck => {
+            if config::file_stamp() == s.config_watch.stamp {
+                return Task::none();
+            }
+            reload(s, false)
+        }
+        _ => Task::none(),
+    }
+}
+
+/// Read the config file again and apply what changed. Changes this process
+/// saved itself are only announced with `announce_own` (after an import).
+pub(super) fn reload(s: &mut LoadedState, announce_own: bool) -> Task<Message> {
+    // Taken first, so a file that doesn't parse is reported once per edit
+    s.config_watch.stamp = config::file_stamp();
+    let reloaded = match config::read_for_reload() {
+        Ok(reloaded) => reloaded,
+        Err(e) => {
+            tracing::warn!("Config file not reloaded: {}", e);
+            s.toasts
+                .error(format!("{}. Keeping the current settings", e));
+            return Task::none();
+        }
+    };
+
+    let new = reloaded.config;
+    let changed = config::changed_settings(&s.config_watch.config, &new);
+    if changed.is_empty() || (reloaded.own_save && !announce_own) {
+        s.config_watch.config = new;
+        return Task::none();
+    }
+    tracing::info!("Config file changed: {}", changed.join(", "));
+
+    let is_changed = |prefix: &str| {
+        changed.iter().any(|setting| {
+            setting == prefix
+                || setting
+                    .strip_prefix(prefix)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    };
+    let task = apply(s, &new, is_changed);
+    s.config_watch.config = new;
+
+    let (restart, live): (Vec<&String>, Vec<&String>) = changed
+        .iter()
+        .partition(|setting| config::needs_restart(setting));
+    if !live.is_empty() {
+        s.toasts.info(format!(
+            "Settings changed in the config file: {}",
+            join(&live)
+        ));
+    }
+    if !restart.is_empty() {
+        s.toasts
+            .warning(format!("Restart Music Minder to apply {}", join(&restart)));
+    }
+    task
+}
+
+/// Take on the settings of `new` that `is_changed` says differ and can
+/// change while running
+fn apply(s: &mut LoadedState, new: &Config, is_changed: impl Fn(&str) -> bool) -> Task<Message> {
+    let audio = &new.audio;
+    s.silence_trim.enabled = audio.trim_silence;
+    s.audio_buffer_ms = audio.buffer_ms;
+    s.audio_fade_ms = audio.fade_ms;
+    s.audio_crossfade_secs = audio.crossfade_secs;
+    s.audio_normalization = audio.normalization;
+    s.audio_preamp_db = audio.preamp_db;
+    s.equalizer = EqualizerState::from_config(audio);
+    s.end_of_queue = audio.end_of_queue;
+    s.volume_step_db = audio.volume_step_db;
+    if is_changed("audio.visualization_mode") {
+        s.visualization_mode = VisualizationMode::from_config(&audio.visualization_mode);
+    }
+    if let Some(player) = &mut s.player {
+        player.set_buffer_ms(audio.buffer_ms);
+        player.set_fade_ms(audio.fade_ms);
+        player.set_crossfade_secs(audio.crossfade_secs);
+        player.set_normalization(audio.normalization, audio.preamp_db);
+        player.set_equalizer(s.equalizer.enabled, &s.equalizer.gains);
+        player.queue_mut().set_end_of_queue(audio.end_of_queue);
+    }
+
+    if is_changed("appearance.sidebar_collapsed") {
+        s.sidebar_collapsed = new.appearance.sidebar_collapsed;
+    }
+
+    s.auto_queue_enabled = new.library.auto_queue;
+    s.popm_email = new.library.popm_email.clone();
+    cover::set_cache_limit(new.library.cover_cache_mb * 1_000_000);
+
+    let tagging = &new.tagging;
+    s.placeholders = metadata::PlaceholderDetector::from_config(tagging);
+    s.genre_map = metadata::genres::GenreMap::from_config(tagging);
+    s.genres.rules = tagging.genre_map.clone();
+    s.manual_edits = tagging.manual_edits.clone();
+    s.watch_locked_files = tagging.watch_locked_files;
+    metadata::strategy::set(tagging.write);
+    s.write_strategy = tagging.write;
+
+    enrichment::budget::CpuBudget::global().configure(&new.analysis);
+    s.enrichment.analysis = new.analysis.clone();
+    // Leave a key being typed in Settings alone unless the file's changed
+    if is_changed("credentials.acoustid_api_key") {
+        let api_key = acoustid_api_key(new);
+        s.enrichment.api_key = api_key.clone();
+        s.enrichment_pane.api_key = api_key;
+    }
+    enrichment::http::set_offline(new.network.offline);
+
+    s.nfo = new.nfo.clone();
+    s.scheduler.config = new.scheduler.clone();
+
+    if is_changed("library.paths") || is_changed("library.unwatched_paths") {
+        return sync_watch_paths(s, &new.library);
+    }
+    Task::none()
+}
+
+fn join(settings: &[&String]) -> String {
+    settings
+        .iter()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,69 @@
+//! Context menu handlers.
+//!
+//! Opening and closing the menu, dispatching the chosen entry, and the
+//! actions that are only reachable from a menu.
+
+use iced::Task;
+
+use super::super::context_menu::ContextMenu;
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+use super::load_tracks_task;
+use crate::db;
+
+/// Handle context menu messages
+pub fn handle_context_menu(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::CursorMoved(position) => {
+            s.cursor_position = position;
+            // Any mouse movement also undims the Now Playing view and keeps
+            // idle-only maintenance from starting
+            s.now_playing_view.wake();
+            s.scheduler.touch();
+        }
+        Message::WindowResized(size) => {
+            s.window_size = size;
+            s.context_menu = None;
+        }
+        Message::ContextMenuOpen(target) => {
+            s.context_menu = Some(ContextMenu {
+                target,
+                position: s.cursor_position,
+            });
+        }
+        Message::ContextMenuClose => {
+            s.context_menu = None;
+        }
+        Message::ContextMenuSelect(messages) => {
+            s.context_menu = None;
+            return Task::batch(messages.into_iter().map(Task::done));
+        }
+        Message::LibraryRemoveTrack(path) => {
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    match db::delete_track_by_path(&pool, &path.to_string_lossy()).await {
+                        Ok(true) => Ok(path),
+                        Ok(false) => Err("track is no longer in the library".to_string()),
+                        Err(e) => Err(e.to_string()),
+                    }
+                },
+                Message::LibraryTrackRemoved,
+            );
+        }
+        Message::LibraryTrackRemoved(result) => match result {
+            Ok(path) => {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                // The file stays on disk; a rescan picks it up again
+                s.toasts.info(format!("Removed {} from the library", name));
+                return load_tracks_task(s.pool.clone());
+            }
+            Err(e) => s.toasts.error(format!("Could not remove track: {}", e)),
+        },
+        _ => {}
+    }
+    Task::none()
+}
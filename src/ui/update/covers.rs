@@ -0,0 +1,183 @@
+//! Album covers set from an image dropped on the window or pasted.
+//!
+//! The image goes to the album of the track whose details are open, else
+//! the album the library is scoped to. It's written by
+//! [`cover::manual::apply`] and the last one can be undone until the app
+//! closes.
+
+use iced::Task;
+use std::path::PathBuf;
+
+use crate::cover::{self, manual::CoverChange};
+use crate::{activity, config};
+
+use super::super::messages::Message;
+use super::super::state::{LibraryScope, LoadedState};
+use super::resolve_cover_art_task;
+
+/// Handle cover drops, pastes and undo
+pub fn handle_covers(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::FileHovered => s.file_hovering = true,
+        Message::FileHoverLeft => s.file_hovering = false,
+        Message::FileDropped(path) => {
+            s.file_hovering = false;
+            return set_cover(s, path);
+        }
+        Message::CoverPaste => return iced::clipboard::read().map(Message::CoverPasted),
+        Message::CoverPasted(text) => {
+            match text.as_deref().and_then(cover::manual::path_from_clipboard) {
+                Some(path) => return set_cover(s, path),
+                None => s
+                    .toasts
+                    .info("Copy an image file (or its path) to paste it as the cover"),
+            }
+        }
+        Message::CoverSet(scope, result) => {
+            s.cover_setting = false;
+            match result {
+                Ok(change) => {
+                    let done = format!("Cover set for {}: {}", label(&scope), change.summary());
+                    for failure in &change.failures {
+                        tracing::warn!("Cover of {}: {}", label(&scope), failure);
+                    }
+                    if change.failures.is_empty() {
+                        s.toasts.success(done.clone());
+                    } else {
+                        s.toasts.warning(done.clone());
+                    }
+                    s.status_message = done;
+                    let refresh = refresh(s, &change);
+                    s.cover_undo = Some((scope, change));
+                    return refresh;
+                }
+                Err(e) => {
+                    s.status_message = format!("Couldn't set the cover: {}", e);
+                    s.toasts.error(format!("Couldn't set the cover: {}", e));
+                }
+            }
+        }
+        Message::CoverUndo => {
+            let Some((_, change)) = s.cover_undo.clone() else {
+                return Task::none();
+            };
+            if s.cover_setting {
+                return Task::none();
+            }
+            s.cover_setting = true;
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    let restored = change.clone();
+                    tokio::task::spawn_blocking(move || {
+                        cover::manual::undo(&restored, &cover::CoverCache::default_location())
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())?;
+                    for path in change.embedded_paths() {
+                        activity::record_tags_written(&pool, path, 1).await;
+                    }
+                    Ok(())
+                },
+                Message::CoverUndone,
+            );
+        }
+        Message::CoverUndone(result) => {
+            s.cover_setting = false;
+            // Undone once, even in part: the files no longer hold what it recorded
+            let Some((scope, change)) = s.cover_undo.take() else {
+                return Task::none();
+            };
+            match result {
+                Ok(()) => s
+                    .toasts
+                    .success(format!("Cover of {} put back", label(&scope))),
+                Err(e) => s.toasts.error(format!(
+                    "Couldn't put back all of the cover of {}: {}",
+                    label(&scope),
+                    e
+                )),
+            }
+            return refresh(s, &change);
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+/// The album an image dropped or pasted now would be the cover of
+pub(super) fn target(s: &LoadedState) -> Option<LibraryScope> {
+    if let Some(track) = s.track_detail.track_index.and_then(|i| s.tracks.get(i)) {
+        return Some(LibraryScope::album_of(track));
+    }
+    s.filter_scope
+        .clone()
+        .filter(|scope| matches!(scope, LibraryScope::Album { .. }))
+}
+
+/// Set the image at `image` as the cover of the album on view
+fn set_cover(s: &mut LoadedState, image: PathBuf) -> Task<Message> {
+    let Some(scope) = target(s) else {
+        s.toasts
+            .info("Open a track's details or an album to set its cover from an image");
+        return Task::none();
+    };
+    if s.cover_setting {
+        return Task::none();
+    }
+    let tracks: Vec<PathBuf> = s
+        .tracks
+        .iter()
+        .filter(|t| scope.matches(t))
+        .map(|t| PathBuf::from(&t.path))
+        .collect();
+    s.cover_setting = true;
+    s.status_message = format!("Setting the cover of {}...", label(&scope));
+    let covers = config::load().covers;
+    let pool = s.pool.clone();
+    Task::perform(
+        async move {
+            let change = tokio::task::spawn_blocking(move || {
+                let cover = cover::manual::prepare(&image)?;
+                cover::manual::apply(
+                    &cover,
+                    &tracks,
+                    &covers,
+                    &cover::CoverCache::default_location(),
+                )
+            })
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+            for path in change.embedded_paths() {
+                activity::record_tags_written(&pool, path, 1).await;
+            }
+            Ok(change)
+        },
+        move |result| Message::CoverSet(scope.clone(), result),
+    )
+}
+
+/// Show the new cover: the open track's details and the playing track's art
+fn refresh(s: &mut LoadedState, change: &CoverChange) -> Task<Message> {
+    let mut tasks = Vec::new();
+    if s.track_detail.track_index.is_some() {
+        tasks.push(Task::done(Message::TrackDetailRefresh));
+    }
+    if let Some(path) = s.cover_art.for_track.clone()
+        && change.touches(&path)
+    {
+        s.cover_art.loading = true;
+        tasks.push(resolve_cover_art_task(path, None));
+    }
+    Task::batch(tasks)
+}
+
+/// e.g. "Kind of Blue by Miles Davis"
+fn label(scope: &LibraryScope) -> String {
+    match scope {
+        LibraryScope::Album { album, artist } => format!("{} by {}", album, artist),
+        LibraryScope::Artist(artist) => artist.clone(),
+    }
+}
@@ -4,13 +4,17 @@ use iced::Task;
 use smallvec::smallvec;
 use std::time::Instant;
 
-use crate::{config, diagnostics, enrichment, health, organizer, player};
+use crate::startup::Subsystem;
+use crate::tasks::TaskRegistry;
+use crate::{config, diagnostics, enrichment, health, history, organizer, player};
 
 use super::super::messages::Message;
 use super::super::platform::get_user_music_folder;
 use super::super::state::{
-    ActivePane, AppState, EnrichmentPaneState, EnrichmentState, FocusedList, GardenerState,
-    LoadedState, OrganizeView, SortColumn, VisualizationMode, WatcherState,
+    ActivePane, ActivityState, AppState, ConfigWatchState, EnrichmentPaneState, EnrichmentState,
+    FocusedList, GardenerState, LoadedState, MiniPlayerState, OrganizeView, PaneStates,
+    ResumeState, SchedulerState, SilenceTrimState, SortColumn, StatsState, VisualizationMode,
+    WatcherState,
 };
 use super::load_tracks_initial_task;
 
@@ -45,6 +49,71 @@ fn enumerate_audio_devices_task() -> Task<Message> {
     )
 }
 
+/// The AcoustID API key to use: the config file's, the environment's
+/// (`ACOUSTID_API_KEY`), or the built-in one
+pub(super) fn acoustid_api_key(cfg: &config::Config) -> String {
+    cfg.credentials.acoustid_api_key.clone().unwrap_or_else(|| {
+        std::env::var("ACOUSTID_API_KEY")
+            .unwrap_or_else(|_| enrichment::DEFAULT_ACOUSTID_API_KEY.to_string())
+    })
+}
+
+/// Open the active profile's database
+pub(crate) fn init_db_task() -> Task<Message> {
+    Task::perform(
+        async {
+            let db_start = Instant::now();
+            let result = crate::db::init_db(&crate::db::db_url(None))
+                .await
+                .map_err(|e| e.to_string());
+            tracing::info!(
+                "Database init completed in {:.1}ms",
+                db_start.elapsed().as_secs_f64() * 1000.0
+            );
+            crate::startup::record("database", db_start);
+            result
+        },
+        Message::DbInitialized,
+    )
+}
+
+/// Switch to another library profile.
+///
+/// Tears down the loaded library (stopping playback and background work) and
+/// reopens the app on the new profile's database and config, the same way it
+/// starts up. The choice is remembered for the next launch.
+pub fn handle_switch_profile(state: &mut AppState, name: &str) -> Task<Message> {
+    if name == crate::profile::active() {
+        return Task::none();
+    }
+    if let Err(e) = crate::profile::set_active(name) {
+        if let AppState::Loaded(s) = state {
+            s.toasts.error(e.to_string());
+        }
+        return Task::none();
+    }
+    if let Err(e) = crate::profile::remember(name) {
+        tracing::warn!("Failed to remember profile {:?}: {}", name, e);
+    }
+    // A profile can lock itself, but switching never unlocks a session that
+    // was started read-only
+    let cfg = config::load();
+    if cfg.library.read_only {
+        crate::readonly::set(true);
+    }
+    crate::metadata::strategy::set(cfg.tagging.write);
+    tracing::info!("Switching to profile {:?}", name);
+
+    if let AppState::Loaded(s) = state
+        && let Some(player) = &s.player
+    {
+        let _ = player.stop();
+    }
+    // Dropping the loaded state also ends its subscriptions (watcher, gardener)
+    *state = AppState::Loading;
+    init_db_task()
+}
+
 /// Handle database initialization
 pub fn handle_db_init(
     state: &mut AppState,
@@ -56,19 +125,18 @@ pub fn handle_db_init(
             tracing::debug!("handle_db_init() started");
 
             // Load config from disk (or defaults)
-            let cfg = config::load();
+            let cfg = crate::startup::phase("config reload", config::load);
+            enrichment::budget::CpuBudget::global().configure(&cfg.analysis);
+            // Pick up where the user left off
+            let panes =
+                crate::startup::phase("pane state", || PaneStates::load().unwrap_or_default());
 
             let music_folder = get_user_music_folder();
-            let fpcalc_available = enrichment::fingerprint::is_fpcalc_available();
+            let history = &cfg.history;
 
-            // API key priority: config file > environment variable > default
-            let api_key = cfg.credentials.acoustid_api_key.clone().unwrap_or_else(|| {
-                std::env::var("ACOUSTID_API_KEY")
-                    .unwrap_or_else(|_| enrichment::DEFAULT_ACOUSTID_API_KEY.to_string())
-            });
+            let api_key = acoustid_api_key(&cfg);
 
-            // Try to initialize player
-            let player_instance = player::Player::new();
+            // The audio output opens once the window is up (see update::startup)
             let player_state = player::PlayerState::default();
 
             // OPTIMIZATION: Defer audio device enumeration to background task
@@ -76,136 +144,289 @@ pub fn handle_db_init(
             let audio_devices = vec![];
             let current_audio_device = cfg.audio.output_device.clone();
 
-            // Parse visualization mode from config
-            let visualization_mode = match cfg.audio.visualization_mode.as_str() {
-                "waveform" => VisualizationMode::Waveform,
-                "vu_meter" => VisualizationMode::VuMeter,
-                "off" => VisualizationMode::Off,
-                _ => VisualizationMode::Spectrum,
-            };
+            let visualization_mode = VisualizationMode::from_config(&cfg.audio.visualization_mode);
 
             // Initialize OS media controls (SMTC on Windows, MPRIS on Linux)
-            let media_controls = player::MediaControlsHandle::new();
+            let media_controls =
+                crate::startup::phase("media controls", player::MediaControlsHandle::new);
             if media_controls.is_some() {
                 tracing::info!("OS media controls initialized");
             } else {
                 tracing::warn!("OS media controls not available");
             }
+            let media_keys = if cfg.audio.media_key_fallback {
+                player::MediaKeyListener::start()
+            } else {
+                None
+            };
 
-            *state = AppState::Loaded(Box::new(LoadedState {
-                pool: pool.clone(),
-                active_pane: ActivePane::Library,
-                scan_path: music_folder.clone(),
-                is_scanning: false,
-                tracks: vec![],
-                tracks_loading: true,
-                tracks_total: None,
-                status_message: "Loading library...".to_string(),
-                scan_count: 0,
-                scroll_offset: 0.0,
-                viewport_height: 0.0,
-                preview_scroll_offset: 0.0,
-                preview_viewport_height: 0.0,
-                organize_destination: music_folder.clone(),
-                organize_pattern: "{Artist}/{Album}/{TrackNum} - {Title}.{ext}".to_string(),
-                organize_view: OrganizeView::default(),
-                organize_preview: vec![],
-                organize_progress: 0,
-                organize_total: 0,
-                organize_errors: smallvec![],
-                can_undo: organizer::UndoLog::has_undo(),
-                preview_loading: false,
-                enrichment: EnrichmentState {
-                    api_key: api_key.clone(),
-                    fpcalc_available,
-                    ..Default::default()
-                },
-                enrichment_pane: EnrichmentPaneState {
-                    api_key,
-                    fpcalc_available,
-                    fill_only: true, // Default to safer option
-                    fetch_cover_art: true,
-                    ..Default::default()
-                },
-                player: player_instance,
-                player_state,
-                file_metadata: None,
-                visualization: player::SpectrumData::default(),
-                visualization_mode,
-                auto_queue_enabled: cfg.library.auto_queue,
-                audio_devices,
-                current_audio_device,
-                seek_preview: None,
-                media_controls,
-                cover_art: Default::default(),
-                diagnostics: None,
-                diagnostics_loading: true,
-                diagnostics_started_tick: 0, // Starting at tick 0
-                diagnostics_pending: None,
-                diagnostics_expanded: std::collections::HashSet::new(),
-                // Request high resolution timer for better audio scheduling
-                #[cfg(windows)]
-                high_res_timer: diagnostics::HighResolutionTimer::request(),
-                animation_tick: 0,
-                watcher_state: WatcherState {
-                    active: true, // Start watching by default
-                    watch_paths: vec![music_folder],
-                    ..Default::default()
-                },
-                // Start the quality gardener
-                gardener_state: {
-                    let gardener = health::QualityGardener::new(pool.clone());
-                    let command_tx = gardener.command_sender();
-                    // Start the gardener in the background
-                    let _handle = gardener.start();
-                    tracing::info!("Quality gardener started");
-                    GardenerState {
-                        active: true,
-                        command_tx: Some(command_tx),
+            *state =
+                AppState::Loaded(Box::new(LoadedState {
+                    pool: pool.clone(),
+                    active_pane: panes.last_pane,
+                    panes,
+                    // Inputs start from their last-used values
+                    scan_path: history
+                        .scan_paths
+                        .first()
+                        .or(cfg.library.last_scan_path.as_ref())
+                        .unwrap_or(&music_folder)
+                        .clone(),
+                    is_scanning: false,
+                    tracks: vec![],
+                    tracks_loading: true,
+                    tracks_total: None,
+                    status_message: "Loading library...".to_string(),
+                    scan_count: 0,
+                    scan_task: None,
+                    organize_destination: history
+                        .organize_destinations
+                        .first()
+                        .unwrap_or(&music_folder)
+                        .clone(),
+                    organize_pattern: history.organize_patterns.first().cloned().unwrap_or_else(
+                        || "{Artist}/{Album}/{TrackNum} - {Title}.{ext}".to_string(),
+                    ),
+                    input_history: history.clone(),
+                    organize_view: OrganizeView::default(),
+                    organize_preview: vec![],
+                    organize_progress: 0,
+                    organize_total: 0,
+                    organize_errors: smallvec![],
+                    organize_task: None,
+                    organize_plan: None,
+                    organize_simulation: None,
+                    interrupted_organize: organizer::OrganizeJournal::load_incomplete(),
+                    organize_review: Default::default(),
+                    manual_organize: Default::default(),
+                    can_undo: organizer::UndoLog::has_undo(),
+                    preview_loading: false,
+                    enrichment: EnrichmentState {
+                        api_key: api_key.clone(),
+                        analysis: cfg.analysis.clone(),
+                        secrets: crate::secrets::status(&cfg),
+                        // fpcalc is looked for in the background (see update::startup)
                         ..Default::default()
-                    }
-                },
-                // Search and filter state
-                search_query: String::new(),
-                filtered_indices: vec![],
-                sort_column: SortColumn::Title,
-                sort_ascending: true,
-                filter_format: None,
-                filter_lossless: None,
-                // Sidebar state
-                sidebar_collapsed: cfg.appearance.sidebar_collapsed,
-                // Organize section collapsed state
-                organize_collapsed: true, // Collapsed by default per design spec
-                // Selection and focus state for keyboard navigation
-                focused_list: FocusedList::Library,
-                library_selection: None,
-                queue_selection: None,
-                // Queue drag-and-drop state
-                queue_drag: Default::default(),
-                // Easter egg state - random starting point
-                easter_egg_index: (std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_millis() as usize)
-                    % 8, // 8 easter eggs
-                easter_egg_clicks: 0,
-                // Track detail modal state
-                track_detail: Default::default(),
-                // Toast notifications
-                toasts: Default::default(),
-            }));
-
-            tracing::debug!(
-                "LoadedState created in {:.1}ms",
-                startup_start.elapsed().as_secs_f64() * 1000.0
-            );
+                    },
+                    enrichment_pane: EnrichmentPaneState {
+                        api_key,
+                        fill_only: true, // Default to safer option
+                        fetch_cover_art: true,
+                        ..Default::default()
+                    },
+                    placeholders: crate::metadata::PlaceholderDetector::from_config(&cfg.tagging),
+                    genre_map: crate::metadata::genres::GenreMap::from_config(&cfg.tagging),
+                    manual_edits: cfg.tagging.manual_edits.clone(),
+                    nfo: cfg.nfo.clone(),
+                    stats: StatsState {
+                        days: Some(30),
+                        ..Default::default()
+                    },
+                    activity: ActivityState {
+                        days: Some(7),
+                        ..Default::default()
+                    },
+                    playlists: Default::default(),
+                    resume: ResumeState {
+                        session: history::LastSession::load(),
+                        ..Default::default()
+                    },
+                    player: None,
+                    player_state,
+                    file_metadata: None,
+                    visualization: player::SpectrumData::default(),
+                    visualization_mode,
+                    auto_queue_enabled: cfg.library.auto_queue,
+                    audio_devices,
+                    current_audio_device,
+                    seek_preview: None,
+                    seek_markers: Default::default(),
+                    listening: Default::default(),
+                    silence_trim: SilenceTrimState {
+                        enabled: cfg.audio.trim_silence,
+                        ..Default::default()
+                    },
+                    audio_buffer_ms: cfg.audio.buffer_ms,
+                    audio_fade_ms: cfg.audio.fade_ms,
+                    audio_crossfade_secs: cfg.audio.crossfade_secs,
+                    audio_normalization: cfg.audio.normalization,
+                    audio_preamp_db: cfg.audio.preamp_db,
+                    equalizer: crate::ui::state::EqualizerState::from_config(&cfg.audio),
+                    end_of_queue: cfg.audio.end_of_queue,
+                    volume_step_db: cfg.audio.volume_step_db,
+                    queue_extended_after: None,
+                    level_meter: Default::default(),
+                    perf: Default::default(),
+                    popm_email: cfg.library.popm_email.clone(),
+                    media_controls,
+                    media_keys,
+                    cover_art: Default::default(),
+                    cover_cache: None,
+                    cover_fetch_running: false,
+                    cover_fetch_report: None,
+                    replaygain_running: false,
+                    file_hovering: false,
+                    cover_setting: false,
+                    cover_undo: None,
+                    diagnostics: None,
+                    diagnostics_loading: true,
+                    diagnostics_started_tick: 0, // Starting at tick 0
+                    diagnostics_pending: None,
+                    updates: Default::default(),
+                    genres: crate::ui::state::GenreManagerState {
+                        rules: cfg.tagging.genre_map.clone(),
+                        ..Default::default()
+                    },
+                    album_artists: crate::ui::state::AlbumArtistWizardState {
+                        can_undo: crate::library::album_artists::AlbumArtistUndo::has_undo(),
+                        ..Default::default()
+                    },
+                    // Given to `open` when no window was running
+                    pending_link: crate::deeplink::take_pending(),
+                    // Request high resolution timer for better audio scheduling
+                    #[cfg(windows)]
+                    high_res_timer: diagnostics::HighResolutionTimer::request(),
+                    animation_tick: 0,
+                    watcher_state: WatcherState::from_config(&cfg.library, music_folder),
+                    config_watch: ConfigWatchState {
+                        stamp: config::file_stamp(),
+                        config: cfg.clone(),
+                    },
+                    // Start the quality gardener
+                    gardener_state: {
+                        let gardener = health::QualityGardener::new(pool.clone());
+                        let command_tx = gardener.command_sender();
+                        // Start the gardener in the background
+                        let _handle = gardener.start();
+                        tracing::info!("Quality gardener started");
+                        GardenerState {
+                            active: true,
+                            command_tx: Some(command_tx),
+                            ..Default::default()
+                        }
+                    },
+                    // Scheduled maintenance
+                    scheduler: SchedulerState::new(cfg.scheduler.clone()),
+                    settings_transfer: Default::default(),
+                    startup: {
+                        let mut startup = crate::startup::Readiness::new();
+                        startup.mark(Subsystem::Database);
+                        startup
+                    },
+                    shutdown: None,
+                    tasks: TaskRegistry::new(),
+                    tasks_popover_open: false,
+                    watch_locked_files: cfg.tagging.watch_locked_files,
+                    write_strategy: cfg.tagging.write,
+                    // Search and filter state
+                    search_query: String::new(),
+                    filtered_indices: vec![],
+                    sort_column: SortColumn::Title,
+                    sort_ascending: true,
+                    filter_format: None,
+                    filter_lossless: None,
+                    filter_loud_master: false,
+                    filter_instrumental: false,
+                    filter_hide_explicit: false,
+                    filter_scope: None,
+                    album_summary: None,
+                    filter_added_within_days: None,
+                    filter_machine_written: None,
+                    filter_hide_skipped: None,
+                    // Sidebar state
+                    sidebar_collapsed: cfg.appearance.sidebar_collapsed,
+                    // Selection and focus state for keyboard navigation
+                    focused_list: FocusedList::Library,
+                    queue_selection: None,
+                    queue_multi_selection: Default::default(),
+                    queue_announcement: None,
+                    keyboard_modifiers: Default::default(),
+                    // Queue drag-and-drop state
+                    queue_drag: Default::default(),
+                    // Easter egg state - random starting point
+                    easter_egg_index: (std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as usize)
+                        % 8, // 8 easter eggs
+                    easter_egg_clicks: 0,
+                    // Track detail modal state
+                    track_detail: Default::default(),
+                    // Toast notifications
+                    toasts: Default::default(),
+                    // Full-screen Now Playing view
+                    now_playing_view: Default::default(),
+                    // Mini-player (the window itself was sized for it in main)
+                    mini_player: MiniPlayerState {
+                        active: cfg.appearance.mini_player,
+                        always_on_top: cfg.appearance.mini_player_always_on_top,
+                        restore_size: None,
+                    },
+                    // Context menu
+                    context_menu: None,
+                    cursor_position: iced::Point::ORIGIN,
+                    window_size: iced::Size::ZERO,
+                    // Library profiles
+                    profiles: crate::profile::list(),
+                    new_profile_name: String::new(),
+                }));
+
+            // Surface an organize that was interrupted by a crash
+            if let AppState::Loaded(s) = state
+                && let Some(interrupted) = &s.interrupted_organize
+            {
+                tracing::warn!(
+                    "Found interrupted organize journal ({} pending moves)",
+                    interrupted.pending_count()
+                );
+                s.panes.library.organize_collapsed = false;
+                s.toasts
+                    .warning("The last organize was interrupted. Resume or roll it back.");
+            }
+
+            if let AppState::Loaded(s) = state
+                && s.enrichment.secrets == crate::secrets::Status::Locked
+            {
+                s.toasts
+                    .info("Your API keys are encrypted. Unlock them in Settings › Enrichment.");
+            }
+
+            crate::startup::record("loaded state", startup_start);
+
+            // Reopen the pane the user left (the library restores its
+            // scroll position once its tracks have loaded)
+            let reopen_pane = match state {
+                AppState::Loaded(s) if s.active_pane == ActivePane::Activity => {
+                    super::handle_activity(s, Message::ActivityRefresh)
+                }
+                AppState::Loaded(s) if s.active_pane == ActivePane::Stats => {
+                    super::handle_stats(s, Message::StatsRefresh)
+                }
+                AppState::Loaded(s) if s.active_pane != ActivePane::Library => {
+                    super::restore_scroll_task(s, s.active_pane)
+                }
+                _ => Task::none(),
+            };
 
             // Progressive loading: load first batch quickly, then rest in background
             // Also run diagnostics and enumerate audio devices in parallel
             Task::batch([
+                super::recent_albums_task(pool.clone()),
+                super::job_runs_task(pool.clone()),
+                super::load_conflicts_task(pool.clone()),
+                super::load_folder_defaults_task(pool.clone()),
+                super::load_playlists_task(pool.clone()),
                 load_tracks_initial_task(pool),
                 run_diagnostics_task(),
                 enumerate_audio_devices_task(),
+                super::startup::start_audio_task(),
+                super::startup::check_fpcalc_task(),
+                reopen_pane,
+                if cfg.network.check_for_updates && !cfg.network.offline {
+                    super::check_updates_task()
+                } else {
+                    Task::none()
+                },
             ])
         }
         Err(e) => {
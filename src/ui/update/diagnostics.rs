@@ -1,8 +1,10 @@
-//! Diagnostics and cover art handlers.
+//! Diagnostics, cover art and cover cache handlers.
 
 use iced::Task;
 
-use crate::diagnostics;
+use crate::startup::Subsystem;
+use crate::tasks::TaskKind;
+use crate::{config, cover, diagnostics};
 
 use super::super::messages::Message;
 use super::super::state::LoadedState;
@@ -16,10 +18,32 @@ pub fn handle_diagnostics(s: &mut LoadedState, msg: Message) -> Task<Message> {
             // Record when diagnostics started for animation timing
             s.diagnostics_started_tick = s.animation_tick;
 
+            let sample = diagnostics::sample_paths(s.tracks.iter().map(|t| t.path.as_str()));
+            let pool = s.pool.clone();
+            let track_count = s.tracks.len();
+            let track_bytes = crate::db::track_list_bytes(&s.tracks)
+                + (s.tracks.capacity() - s.tracks.len())
+                    * std::mem::size_of::<crate::db::TrackWithMetadata>()
+                + s.filtered_indices.capacity() * std::mem::size_of::<usize>();
             return Task::perform(
-                async {
-                    match tokio::task::spawn_blocking(diagnostics::DiagnosticReport::generate).await
-                    {
+                async move {
+                    let health = match diagnostics::LibraryHealth::check(&pool).await {
+                        Ok(health) => Some(health),
+                        Err(e) => {
+                            tracing::warn!("Library checks failed: {}", e);
+                            None
+                        }
+                    };
+                    let generate = move || {
+                        let report = diagnostics::DiagnosticReport::generate()
+                            .with_library(&sample)
+                            .with_track_list(track_count, track_bytes);
+                        match health {
+                            Some(health) => report.with_library_health(&health),
+                            None => report,
+                        }
+                    };
+                    match tokio::task::spawn_blocking(generate).await {
                         Ok(report) => report,
                         Err(e) => {
                             tracing::error!("Diagnostics task panicked: {}", e);
@@ -33,28 +57,134 @@ pub fn handle_diagnostics(s: &mut LoadedState, msg: Message) -> Task<Message> {
         Message::DiagnosticsComplete(report) => {
             // Store in pending - will be revealed when animation completes
             s.diagnostics_pending = Some(report);
+            s.startup.mark(Subsystem::Diagnostics);
         }
         Message::DiagnosticsToggleCheck(name) => {
             // Toggle expanded state for this check
-            if s.diagnostics_expanded.contains(&name) {
-                s.diagnostics_expanded.remove(&name);
+            if s.panes.diagnostics.expanded.contains(&name) {
+                s.panes.diagnostics.expanded.remove(&name);
             } else {
-                s.diagnostics_expanded.insert(name);
+                s.panes.diagnostics.expanded.insert(name);
             }
         }
-        Message::CoverArtResolved(path, result) => {
-            // Only update if this is still the current track
-            if s.cover_art.for_track.as_ref() == Some(&path) {
-                s.cover_art.loading = false;
-                match result {
-                    Ok(cover) => {
-                        s.cover_art.current = Some(cover);
-                        s.cover_art.error = None;
+        Message::DiagnosticsFix(fix) => match fix {
+            diagnostics::FixAction::Rescan => {
+                return Task::done(Message::SchedulerRunNow(crate::scheduler::Job::Scan));
+            }
+            diagnostics::FixAction::ClearCoverCache => {
+                return Task::done(Message::CoverCacheClear);
+            }
+            _ => {
+                let pool = s.pool.clone();
+                return Task::perform(
+                    async move {
+                        diagnostics::apply_fix(&pool, fix)
+                            .await
+                            .map_err(|e| format!("{} failed: {}", fix.label(), e))
+                    },
+                    Message::DiagnosticsFixDone,
+                );
+            }
+        },
+        Message::DiagnosticsFixDone(result) => {
+            match result {
+                Ok(done) => s.toasts.success(done),
+                Err(e) => s.toasts.error(e),
+            }
+            // Merged and removed rows leave the track list; show what's left
+            return Task::batch([
+                super::load_tracks_task(s.pool.clone()),
+                Task::done(Message::DiagnosticsRunPressed),
+            ]);
+        }
+        // Only update if this is still the current track
+        Message::CoverArtResolved(path, result)
+            if s.cover_art.for_track.as_ref() == Some(&path) =>
+        {
+            s.cover_art.loading = false;
+            match result {
+                Ok(cover) => {
+                    s.cover_art.current = Some(cover);
+                    s.cover_art.error = None;
+                }
+                Err(e) => {
+                    s.cover_art.current = None;
+                    s.cover_art.error = Some(e);
+                }
+            }
+        }
+        Message::CoverCacheLoaded(stats) => s.cover_cache = Some(stats),
+        Message::CoverCacheClear => {
+            return Task::perform(
+                async {
+                    tokio::task::spawn_blocking(|| {
+                        cover::CoverCache::default_location()
+                            .clear()
+                            .map_err(|e| e.to_string())
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                },
+                Message::CoverCacheCleared,
+            );
+        }
+        Message::CoverCacheCleared(result) => {
+            match result {
+                Ok(()) => s.toasts.success("Cover cache cleared"),
+                Err(e) => s
+                    .toasts
+                    .error(format!("Failed to clear the cover cache: {}", e)),
+            }
+            return load_cover_cache_task();
+        }
+        Message::CoversFetch => {
+            let covers = config::load().covers;
+            if !covers.folder_file && !covers.embed {
+                s.toasts
+                    .warning("Set covers.folder_file or covers.embed in the config file");
+                return Task::none();
+            }
+            s.cover_fetch_running = true;
+            let task = s.tasks.start(TaskKind::Maintenance, "Fetch album covers");
+            task.set_phase("Looking up albums");
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    let result = cover::fetch_album_covers(&pool, &covers, true, &task).await;
+                    task.finish();
+                    result.map_err(|e| e.to_string())
+                },
+                Message::CoversFetchComplete,
+            );
+        }
+        Message::CoversFetchComplete(result) => {
+            s.cover_fetch_running = false;
+            match result {
+                Ok(report) => {
+                    for album in &report.albums {
+                        if let cover::CoverOutcome::Unavailable(e)
+                        | cover::CoverOutcome::Failed(e) = &album.outcome
+                        {
+                            tracing::warn!(
+                                "Album cover for {} - {}: {}",
+                                album.artist,
+                                album.album,
+                                e
+                            );
+                        }
                     }
-                    Err(e) => {
-                        s.cover_art.current = None;
-                        s.cover_art.error = Some(e);
+                    s.status_message = report.summary();
+                    if report.failed() == 0 {
+                        s.toasts.success(report.summary());
+                    } else {
+                        s.toasts.warning(report.summary());
                     }
+                    s.cover_fetch_report = Some(report);
+                    return load_cover_cache_task();
+                }
+                Err(e) => {
+                    s.status_message = format!("Fetching album covers failed: {}", e);
+                    s.toasts.error("Fetching album covers failed");
                 }
             }
         }
@@ -62,3 +192,15 @@ pub fn handle_diagnostics(s: &mut LoadedState, msg: Message) -> Task<Message> {
     }
     Task::none()
 }
+
+/// Read the size of the downloaded cover cache
+pub(crate) fn load_cover_cache_task() -> Task<Message> {
+    Task::perform(
+        async {
+            tokio::task::spawn_blocking(|| cover::CoverCache::default_location().stats())
+                .await
+                .unwrap_or_default()
+        },
+        Message::CoverCacheLoaded,
+    )
+}
@@ -2,12 +2,316 @@
 
 use iced::Task;
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use crate::{config, enrichment, metadata};
+use crate::enrichment::report::{EnrichmentReport, FileReport};
+use crate::error::Failure;
+use crate::provenance::{self, FieldSource};
+use crate::stats::{self, IdentifyOutcome};
+use crate::tasks::TaskKind;
+use crate::write_queue::{self, Outcome};
+use crate::{activity, config, enrichment, library, metadata, plan, secrets};
 
 use super::super::messages::Message;
 use super::super::state::{EnrichmentResult, LoadedState, ResultStatus};
-use super::load_tracks_task;
+use super::{load_tracks_task, pick_folder_task, save_plan_task};
+
+/// A batch identification result as shown in the results list. Matches at
+/// or above `min_confidence` are confirmed for writing.
+fn batch_result(
+    pos: usize,
+    min_confidence: f32,
+    result: Result<
+        (
+            enrichment::TrackIdentification,
+            Vec<enrichment::TrackIdentification>,
+        ),
+        Failure,
+    >,
+) -> EnrichmentResult {
+    match result {
+        Ok((identification, alternatives_raw)) => {
+            let mut changes = Vec::new();
+            if identification.track.title.is_some() {
+                changes.push("title".to_string());
+            }
+            if identification.track.artist.is_some() {
+                changes.push("artist".to_string());
+            }
+            if identification.track.album.is_some() {
+                changes.push("album".to_string());
+            }
+            if identification.track.year.is_some() {
+                changes.push("year".to_string());
+            }
+
+            let result_status =
+                if identification.score >= 0.9 && !identification.scores.contradicts_tags() {
+                    ResultStatus::Success
+                } else {
+                    ResultStatus::Warning
+                };
+
+            // Convert raw alternatives to UI model
+            let alternatives: Vec<crate::ui::state::AlternativeMatch> = alternatives_raw
+                .iter()
+                .map(|alt| crate::ui::state::AlternativeMatch {
+                    album: alt.track.album.clone().unwrap_or_default(),
+                    year: alt.track.year,
+                    confidence: alt.score,
+                    release_type: alt.track.release_type.clone().unwrap_or_default(),
+                    track_number: alt.track.track_number,
+                    identification: alt.clone(),
+                })
+                .collect();
+
+            EnrichmentResult {
+                track_index: pos,
+                status: result_status,
+                title: identification.track.title.clone(),
+                artist: identification.track.artist.clone(),
+                album: identification.track.album.clone(),
+                confidence: Some(identification.score),
+                changes,
+                error: None,
+                confirmed: identification.auto_accepted(min_confidence), // Auto-confirm high confidence
+                identification: Some(identification.clone()),
+                alternatives,
+                show_alternatives: false, // Hidden by default, expanded on review
+                selected_alternative: None,
+            }
+        }
+        Err(e) => EnrichmentResult {
+            track_index: pos,
+            status: ResultStatus::Error,
+            title: None,
+            artist: None,
+            album: None,
+            confidence: None,
+            changes: vec![],
+            error: Some(e),
+            confirmed: false,
+            identification: None,
+            alternatives: vec![],
+            show_alternatives: false,
+            selected_alternative: None,
+        },
+    }
+}
+
+/// Path of the track at a position in the pane's track list
+fn pane_track_path(s: &LoadedState, pos: usize) -> Option<PathBuf> {
+    let track = s.tracks.get(*s.enrichment_pane.selected_tracks.get(pos)?)?;
+    Some(PathBuf::from(&track.path))
+}
+
+/// Auto-accept confidence for the track at a position, from its folder's rule
+fn pane_min_confidence(s: &LoadedState, pos: usize) -> f32 {
+    pane_track_path(s, pos).map_or(enrichment::folders::DEFAULT_MIN_CONFIDENCE, |path| {
+        s.enrichment.folder_defaults.min_confidence(&path)
+    })
+}
+
+/// Confirmed results as (path, identification), optionally only those whose
+/// folder writes without review
+fn confirmed_writes(
+    s: &LoadedState,
+    auto_write_only: bool,
+) -> Vec<(PathBuf, enrichment::TrackIdentification)> {
+    s.enrichment_pane
+        .results
+        .iter()
+        .filter(|r| r.confirmed)
+        .filter_map(|r| {
+            let path = pane_track_path(s, r.track_index)?;
+            let identification = r.identification.as_ref()?;
+            Some((path, identification.clone()))
+        })
+        .filter(|(path, _)| !auto_write_only || s.enrichment.folder_defaults.auto_write(path))
+        .collect()
+}
+
+/// Write identifications to their files. Fill-only applies when the pane
+/// asks for it or the file's folder does.
+fn write_identifications_task(
+    s: &LoadedState,
+    to_write: Vec<(PathBuf, enrichment::TrackIdentification)>,
+) -> Task<Message> {
+    let to_write: Vec<_> = to_write
+        .into_iter()
+        .map(|(path, identification)| {
+            let fill_only =
+                s.enrichment_pane.fill_only || s.enrichment.folder_defaults.fill_only(&path);
+            (path, identification, fill_only)
+        })
+        .collect();
+    let placeholders = s.placeholders.clone();
+    let genre_map = s.genre_map.clone();
+    let manual_edits = s.manual_edits.clone();
+    let pool = s.pool.clone();
+
+    Task::perform(
+        async move {
+            let mut success = 0;
+            let mut deferred = 0;
+            let mut errors = Vec::new();
+
+            for (path, identification, fill_only) in to_write {
+                let options = metadata::WriteOptions2 {
+                    only_fill_empty: fill_only,
+                    write_musicbrainz_ids: true,
+                    placeholders: placeholders.clone(),
+                    genre_map: genre_map.clone(),
+                };
+                match write_queue::write_match(
+                    &pool,
+                    &path,
+                    &identification,
+                    options,
+                    &manual_edits,
+                )
+                .await
+                {
+                    Ok(Outcome::Written(_)) => success += 1,
+                    Ok(Outcome::Deferred) => deferred += 1,
+                    Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+                }
+            }
+
+            if errors.is_empty() {
+                Ok((success, deferred))
+            } else {
+                Err(format!(
+                    "{} succeeded, {} failed: {}",
+                    success,
+                    errors.len(),
+                    errors.join("; ")
+                ))
+            }
+        },
+        Message::EnrichmentWriteTagsResult,
+    )
+}
+
+/// Wrap up batch identification once every track is done (or it was
+/// cancelled). Confirmed matches in auto-write folders are written.
+fn finish_batch(s: &mut LoadedState, cancelled: bool) -> Task<Message> {
+    s.enrichment_pane.is_identifying = false;
+    if let Some(task) = s.enrichment_pane.task.take() {
+        task.finish();
+    }
+    let lookups = s
+        .enrichment_pane
+        .service
+        .take()
+        .map(|service| service.lookup_stats())
+        .unwrap_or_default();
+    if lookups.saved > 0 {
+        tracing::info!(
+            target: "ui::enrich",
+            requests = lookups.requests,
+            saved = lookups.saved,
+            "MusicBrainz lookups coalesced"
+        );
+    }
+    let success_count = s
+        .enrichment_pane
+        .results
+        .iter()
+        .filter(|r| r.status == ResultStatus::Success)
+        .count();
+    let warning_count = s
+        .enrichment_pane
+        .results
+        .iter()
+        .filter(|r| r.status == ResultStatus::Warning)
+        .count();
+    let total = s.enrichment_pane.results.len();
+    s.status_message = format!(
+        "Identification complete: {} of {} matched",
+        success_count, total
+    );
+    if lookups.saved > 0 {
+        s.status_message.push_str(&format!(
+            " ({} MusicBrainz requests, {} saved)",
+            lookups.requests, lookups.saved
+        ));
+    }
+
+    // Show appropriate toast
+    if cancelled {
+        s.status_message = format!(
+            "Identification cancelled: {} of {} matched",
+            success_count, total
+        );
+        s.toasts
+            .warning(format!("Identification cancelled after {} tracks", total));
+    } else if success_count == total {
+        s.toasts.success(format!("All {} tracks identified", total));
+    } else if success_count + warning_count > 0 {
+        s.toasts.info(format!(
+            "{} matched, {} low confidence",
+            success_count, warning_count
+        ));
+    } else {
+        s.toasts.warning("No matches found");
+    }
+
+    let auto_write = confirmed_writes(s, true);
+    if auto_write.is_empty() {
+        return Task::none();
+    }
+    s.toasts.info(format!(
+        "Writing {} match(es) from auto-write folders",
+        auto_write.len()
+    ));
+    write_identifications_task(s, auto_write)
+}
+
+/// The running batch's service, created with its first lookup
+fn batch_service(
+    pane: &mut crate::ui::state::EnrichmentPaneState,
+) -> Arc<enrichment::EnrichmentService> {
+    let api_key = pane.api_key.clone();
+    pane.service
+        .get_or_insert_with(|| {
+            Arc::new(enrichment::EnrichmentService::new(
+                enrichment::EnrichmentConfig {
+                    acoustid_api_key: api_key,
+                    min_confidence: 0.5,
+                    use_musicbrainz: true,
+                    ..Default::default()
+                },
+            ))
+        })
+        .clone()
+}
+
+/// Identify the next checked track that isn't done or already running
+fn identify_next_task(s: &mut LoadedState) -> Option<Task<Message>> {
+    let pane = &mut s.enrichment_pane;
+    let (pos, path) = pane
+        .checked_tracks
+        .iter()
+        .filter(|pos| !pane.in_flight.contains(pos))
+        .filter(|pos| !pane.results.iter().any(|r| r.track_index == **pos))
+        .find_map(|&pos| {
+            let track = s.tracks.get(*pane.selected_tracks.get(pos)?)?;
+            Some((pos, PathBuf::from(&track.path)))
+        })?;
+    pane.in_flight.insert(pos);
+    let service = batch_service(pane);
+    let pool = s.pool.clone();
+
+    Some(Task::perform(
+        async move {
+            let result = service.identify_track_with_alternatives(&path).await;
+            stats::record_identification(&pool, IdentifyOutcome::of(&result)).await;
+            (pos, result.map_err(|e| Failure::of(&e)))
+        },
+        |(pos, result)| Message::EnrichBatchIdentifyWithAlts(pos, result),
+    ))
+}
 
 /// Handle enrichment-related messages (single track - Settings pane)
 pub fn handle_enrichment(s: &mut LoadedState, msg: Message) -> Task<Message> {
@@ -18,7 +322,83 @@ pub fn handle_enrichment(s: &mut LoadedState, msg: Message) -> Task<Message> {
             // Also update the enrichment pane
             s.enrichment_pane.api_key = key;
         }
+        Message::EnrichmentAnalysisChanged(analysis) => {
+            enrichment::budget::CpuBudget::global().configure(&analysis);
+            s.enrichment.analysis = analysis.clone();
+            return Task::perform(
+                async move {
+                    let mut cfg = config::load();
+                    cfg.analysis = analysis;
+                    config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save CPU budget settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+        Message::EnrichmentWriteStrategyChanged(strategy) => {
+            crate::metadata::strategy::set(strategy);
+            s.write_strategy = strategy;
+            return Task::perform(
+                async move {
+                    let mut cfg = config::load();
+                    cfg.tagging.write = strategy;
+                    config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save tag writing settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+        Message::EnrichmentFolderDefaultsLoaded(rules) => {
+            s.enrichment.folder_defaults = rules;
+        }
+        Message::EnrichmentFolderDefaultsAdd => {
+            return pick_folder_task(Message::EnrichmentFolderDefaultsPicked);
+        }
+        Message::EnrichmentFolderDefaultsPicked(Some(folder)) => {
+            let folder = folder.to_string_lossy().into_owned();
+            if s.enrichment
+                .folder_defaults
+                .0
+                .iter()
+                .any(|rule| rule.folder == folder)
+            {
+                return Task::none();
+            }
+            return save_folder_defaults_task(
+                s.pool.clone(),
+                enrichment::folders::FolderDefaults::new(folder),
+            );
+        }
+        Message::EnrichmentFolderDefaultsChanged(rule) => {
+            return save_folder_defaults_task(s.pool.clone(), rule);
+        }
+        Message::EnrichmentFolderDefaultsRemove(folder) => {
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    if let Err(e) = enrichment::folders::remove(&pool, &folder).await {
+                        tracing::warn!("Failed to remove folder defaults: {}", e);
+                    }
+                    enrichment::folders::load(&pool).await
+                },
+                folder_defaults_loaded,
+            );
+        }
         Message::EnrichmentApiKeySave => {
+            // A locked config can't take a new key without losing the old ones
+            if s.enrichment.secrets == secrets::Status::Locked {
+                s.toasts
+                    .warning("Unlock your encrypted API keys before changing them");
+                return Task::none();
+            }
             // Save API key to config file
             let key = s.enrichment.api_key.clone();
             return Task::perform(
@@ -42,6 +422,54 @@ pub fn handle_enrichment(s: &mut LoadedState, msg: Message) -> Task<Message> {
             s.enrichment.api_key_saved = true;
             tracing::info!("API key saved to config file");
         }
+        Message::SecretsPassphraseChanged(passphrase) => {
+            s.enrichment.passphrase = passphrase;
+        }
+        Message::SecretsUnlock | Message::SecretsEncrypt | Message::SecretsDecrypt => {
+            if s.enrichment.secrets_busy {
+                return Task::none();
+            }
+            let run: fn(&str) -> Result<(), secrets::SecretsError> = match msg {
+                Message::SecretsUnlock => |p| secrets::unlock(p).map(|_| ()),
+                Message::SecretsEncrypt => secrets::encrypt,
+                _ => |_| secrets::decrypt(),
+            };
+            let passphrase = std::mem::take(&mut s.enrichment.passphrase);
+            s.enrichment.secrets_busy = true;
+            return Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        run(&passphrase).map_err(|e| e.to_string())?;
+                        Ok(secrets::status(&config::load()))
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                },
+                Message::SecretsDone,
+            );
+        }
+        Message::SecretsDone(result) => {
+            s.enrichment.secrets_busy = false;
+            match result {
+                Ok(status) => {
+                    s.enrichment.secrets = status;
+                    s.toasts.success(match status {
+                        secrets::Status::Plain => "API keys are stored in plain text again",
+                        _ => "API keys unlocked for this session",
+                    });
+                    // The keys hidden until now
+                    if let Some(key) = config::load().credentials.acoustid_api_key {
+                        s.enrichment.api_key = key.clone();
+                        s.enrichment.api_key_saved = true;
+                        s.enrichment_pane.api_key = key;
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Credential encryption failed: {}", e);
+                    s.toasts.error(e);
+                }
+            }
+        }
         Message::EnrichmentTrackSelected(idx) => {
             s.enrichment.selected_track = Some(idx);
             s.enrichment.last_result = None;
@@ -73,6 +501,7 @@ pub fn handle_enrichment(s: &mut LoadedState, msg: Message) -> Task<Message> {
 
             let path = PathBuf::from(&track.path);
             let api_key = s.enrichment.api_key.clone();
+            let pool = s.pool.clone();
 
             return Task::perform(
                 async move {
@@ -83,10 +512,9 @@ pub fn handle_enrichment(s: &mut LoadedState, msg: Message) -> Task<Message> {
                         ..Default::default()
                     };
                     let service = enrichment::EnrichmentService::new(config);
-                    service
-                        .identify_track(&path)
-                        .await
-                        .map_err(|e| e.to_string())
+                    let result = service.identify_track(&path).await;
+                    stats::record_identification(&pool, IdentifyOutcome::of(&result)).await;
+                    result.map_err(|e| e.to_string())
                 },
                 Message::EnrichmentIdentifyResult,
             );
@@ -120,32 +548,50 @@ pub fn handle_enrichment(s: &mut LoadedState, msg: Message) -> Task<Message> {
             };
 
             let path = PathBuf::from(&track.path);
-            let identified = result.track.clone();
+            let identification = result.clone();
+            let manual_edits = s.manual_edits.clone();
+
+            let pool = s.pool.clone();
 
             return Task::perform(
                 async move {
                     let options = metadata::WriteOptions2 {
                         only_fill_empty: false, // Overwrite with enriched data
                         write_musicbrainz_ids: true,
+                        ..Default::default()
                     };
-                    tokio::task::spawn_blocking(move || {
-                        metadata::write(&path, &identified, &options)
-                            .map(|r| r.fields_updated)
-                            .map_err(|e| e.to_string())
-                    })
+                    let outcome = write_queue::write_match(
+                        &pool,
+                        &path,
+                        &identification,
+                        options,
+                        &manual_edits,
+                    )
                     .await
-                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())?;
+                    Ok(match outcome {
+                        Outcome::Written(fields) => (fields, 0),
+                        Outcome::Deferred => (0, 1),
+                    })
                 },
                 Message::EnrichmentWriteTagsResult,
             );
         }
         Message::EnrichmentWriteTagsResult(result) => {
             match result {
-                Ok(count) => {
+                Ok((0, deferred)) if deferred > 0 => super::tasks::toast_deferred(s, deferred),
+                Ok((count, deferred)) => {
                     s.status_message = format!("✓ Tags written ({} fields updated)", count);
                     s.toasts.success(format!("Tags written ({} fields)", count));
-                    // Reload tracks to show updated metadata
-                    return load_tracks_task(s.pool.clone());
+                    if deferred > 0 {
+                        super::tasks::toast_deferred(s, deferred);
+                    }
+                    // Reload tracks to show updated metadata, and any
+                    // suggestions held back from hand-edited fields
+                    return Task::batch([
+                        load_tracks_task(s.pool.clone()),
+                        load_conflicts_task(s.pool.clone()),
+                    ]);
                 }
                 Err(e) => {
                     s.enrichment.last_error = Some(format!("Failed to write tags: {}", e));
@@ -158,6 +604,50 @@ pub fn handle_enrichment(s: &mut LoadedState, msg: Message) -> Task<Message> {
     Task::none()
 }
 
+/// Add library tracks to the Enrich pane, checked. Tracks in folders with
+/// enrichment turned off are left out, as are streams and cloud links,
+/// which can't be fingerprinted.
+fn add_to_pane(s: &mut LoadedState, indices: Vec<usize>) {
+    let mut skipped = 0;
+    let mut remote = 0;
+    for idx in indices {
+        if s.enrichment_pane.selected_tracks.contains(&idx) {
+            continue;
+        }
+        let Some(track) = s.tracks.get(idx) else {
+            continue;
+        };
+        if !track.source.is_local() {
+            remote += 1;
+            continue;
+        }
+        if !s
+            .enrichment
+            .folder_defaults
+            .is_enabled(std::path::Path::new(&track.path))
+        {
+            skipped += 1;
+            continue;
+        }
+        s.enrichment_pane.selected_tracks.push(idx);
+        // Auto-check new tracks
+        let pos = s.enrichment_pane.selected_tracks.len() - 1;
+        s.enrichment_pane.checked_tracks.insert(pos);
+    }
+    if skipped > 0 {
+        s.toasts.info(format!(
+            "Skipped {} track(s) in folders with enrichment turned off",
+            skipped
+        ));
+    }
+    if remote > 0 {
+        s.toasts.info(format!(
+            "Skipped {} stream or cloud track(s): only files can be identified",
+            remote
+        ));
+    }
+}
+
 /// Handle enrich pane messages (batch operations)
 pub fn handle_enrich_pane(s: &mut LoadedState, msg: Message) -> Task<Message> {
     match msg {
@@ -171,44 +661,30 @@ pub fn handle_enrich_pane(s: &mut LoadedState, msg: Message) -> Task<Message> {
                 s.filtered_indices.iter().take(50).copied().collect()
             };
 
-            for idx in indices_to_add {
-                if !s.enrichment_pane.selected_tracks.contains(&idx) {
-                    s.enrichment_pane.selected_tracks.push(idx);
-                    // Auto-check new tracks
-                    let pos = s.enrichment_pane.selected_tracks.len() - 1;
-                    s.enrichment_pane.checked_tracks.insert(pos);
-                }
-            }
+            add_to_pane(s, indices_to_add);
         }
         Message::EnrichAddTracks(indices) => {
-            for idx in indices {
-                if !s.enrichment_pane.selected_tracks.contains(&idx) {
-                    s.enrichment_pane.selected_tracks.push(idx);
-                    let pos = s.enrichment_pane.selected_tracks.len() - 1;
-                    s.enrichment_pane.checked_tracks.insert(pos);
-                }
-            }
+            add_to_pane(s, indices);
         }
-        Message::EnrichRemoveTrack(pos) => {
-            if pos < s.enrichment_pane.selected_tracks.len() {
-                s.enrichment_pane.selected_tracks.remove(pos);
-                s.enrichment_pane.checked_tracks.remove(&pos);
-                // Re-index checked tracks above this position
-                let mut new_checked = std::collections::HashSet::new();
-                for &i in &s.enrichment_pane.checked_tracks {
-                    if i > pos {
-                        new_checked.insert(i - 1);
-                    } else {
-                        new_checked.insert(i);
-                    }
+        Message::EnrichRemoveTrack(pos) if pos < s.enrichment_pane.selected_tracks.len() => {
+            s.enrichment_pane.selected_tracks.remove(pos);
+            s.enrichment_pane.checked_tracks.remove(&pos);
+            // Re-index checked tracks above this position
+            let mut new_checked = std::collections::HashSet::new();
+            for &i in &s.enrichment_pane.checked_tracks {
+                if i > pos {
+                    new_checked.insert(i - 1);
+                } else {
+                    new_checked.insert(i);
                 }
-                s.enrichment_pane.checked_tracks = new_checked;
             }
+            s.enrichment_pane.checked_tracks = new_checked;
         }
         Message::EnrichClearTracks => {
             s.enrichment_pane.selected_tracks.clear();
             s.enrichment_pane.checked_tracks.clear();
             s.enrichment_pane.results.clear();
+            s.enrichment_pane.verify_summary = None;
         }
         Message::EnrichTrackChecked(pos, checked) => {
             if checked {
@@ -235,210 +711,232 @@ pub fn handle_enrich_pane(s: &mut LoadedState, msg: Message) -> Task<Message> {
                 s.status_message = "fpcalc not installed".to_string();
                 return Task::none();
             }
-            if s.enrichment_pane.checked_tracks.is_empty() {
+            if s.enrichment_pane.checked_tracks.is_empty() || s.enrichment_pane.verifying {
                 return Task::none();
             }
 
             s.enrichment_pane.is_identifying = true;
             s.enrichment_pane.results.clear();
+            s.enrichment_pane.verify_summary = None;
 
-            // Get paths for checked tracks
-            let tracks_to_process: Vec<(usize, PathBuf)> = s
+            // Checked tracks that are still in the library
+            let to_process = s
                 .enrichment_pane
                 .checked_tracks
                 .iter()
-                .filter_map(|&pos| {
+                .filter(|&&pos| {
                     s.enrichment_pane
                         .selected_tracks
                         .get(pos)
-                        .and_then(|&track_idx| s.tracks.get(track_idx))
-                        .map(|track| (pos, PathBuf::from(&track.path)))
+                        .is_some_and(|&track_idx| track_idx < s.tracks.len())
                 })
-                .collect();
+                .count();
 
-            if tracks_to_process.is_empty() {
+            if to_process == 0 {
                 s.enrichment_pane.is_identifying = false;
                 return Task::none();
             }
 
-            // Start with first track
-            let (first_pos, first_path) = tracks_to_process[0].clone();
-            let api_key = s.enrichment_pane.api_key.clone();
+            // Disc IDs and full-album folders, then as many tracks at once as the CPU budget runs
+            // fingerprints; cancelling stops before the next one
+            let task = s.tasks.start(
+                TaskKind::Enrichment,
+                format!("Identify {} tracks", to_process),
+            );
+            task.set_phase("Matching disc IDs and albums");
+            task.set_total(to_process as u64);
+            if let Some(old) = s.enrichment_pane.task.replace(task) {
+                old.finish();
+            }
+
+            s.enrichment_pane.in_flight.clear();
 
-            // Store remaining tracks for sequential processing
-            // We'll process one at a time to respect rate limits
+            // Whole ripped CDs first: one disc ID lookup instead of a
+            // fingerprint per track. Then folders that vote on one release.
+            let checked: Vec<(usize, PathBuf)> = s
+                .enrichment_pane
+                .checked_tracks
+                .iter()
+                .filter_map(|&pos| {
+                    let track = s.tracks.get(*s.enrichment_pane.selected_tracks.get(pos)?)?;
+                    Some((pos, PathBuf::from(&track.path)))
+                })
+                .collect();
+            s.enrichment_pane.service = None;
+            let service = batch_service(&mut s.enrichment_pane);
+            let pool = s.pool.clone();
             return Task::perform(
                 async move {
-                    let config = enrichment::EnrichmentConfig {
-                        acoustid_api_key: api_key,
-                        min_confidence: 0.5,
-                        use_musicbrainz: true,
-                        ..Default::default()
-                    };
-                    let service = enrichment::EnrichmentService::new(config);
-                    // Call the new method that returns alternatives
-                    let result = service
-                        .identify_track_with_alternatives(&first_path)
-                        .await
-                        .map_err(|e| e.to_string());
-                    (first_pos, result)
+                    let paths: Vec<PathBuf> = checked.iter().map(|(_, p)| p.clone()).collect();
+                    let mut matched = service.identify_discs(&paths).await;
+                    let rest: Vec<PathBuf> = paths
+                        .into_iter()
+                        .filter(|p| !matched.contains_key(p))
+                        .collect();
+                    matched.extend(service.identify_albums(&rest).await);
+                    for _ in 0..matched.len() {
+                        stats::record_identification(&pool, IdentifyOutcome::Identified).await;
+                    }
+                    checked
+                        .into_iter()
+                        .filter_map(|(pos, path)| Some((pos, matched.remove(&path)?)))
+                        .collect()
                 },
-                |(pos, result)| Message::EnrichBatchIdentifyWithAlts(pos, result),
+                Message::EnrichBatchDiscMatched,
             );
         }
 
-        Message::EnrichBatchIdentifyWithAlts(pos, result) => {
-            // Create result entry with alternatives support
-            let enrich_result = match result {
-                Ok((identification, alternatives_raw)) => {
-                    let mut changes = Vec::new();
-                    if identification.track.title.is_some() {
-                        changes.push("title".to_string());
-                    }
-                    if identification.track.artist.is_some() {
-                        changes.push("artist".to_string());
-                    }
-                    if identification.track.album.is_some() {
-                        changes.push("album".to_string());
-                    }
-                    if identification.track.year.is_some() {
-                        changes.push("year".to_string());
-                    }
-
-                    let result_status = if identification.score >= 0.9 {
-                        ResultStatus::Success
-                    } else {
-                        ResultStatus::Warning
-                    };
+        Message::EnrichBatchDiscMatched(matched) => {
+            if !s.enrichment_pane.is_identifying {
+                return Task::none();
+            }
+            let count = matched.len();
+            for (pos, identification) in matched {
+                let min_confidence = pane_min_confidence(s, pos);
+                s.enrichment_pane.results.push(batch_result(
+                    pos,
+                    min_confidence,
+                    Ok((identification, Vec::new())),
+                ));
+            }
+            let cancelled = match &s.enrichment_pane.task {
+                Some(task) => {
+                    task.advance(count as u64);
+                    task.set_phase("Fingerprinting and looking up");
+                    task.is_cancelled()
+                }
+                None => false,
+            };
+            if count > 0 {
+                s.toasts
+                    .info(format!("{} track(s) matched by disc ID or album", count));
+            }
 
-                    // Convert raw alternatives to UI model
-                    let alternatives: Vec<crate::ui::state::AlternativeMatch> = alternatives_raw
-                        .iter()
-                        .map(|alt| crate::ui::state::AlternativeMatch {
-                            album: alt.track.album.clone().unwrap_or_default(),
-                            year: alt.track.year,
-                            confidence: alt.score,
-                            release_type: alt.track.release_type.clone().unwrap_or_default(),
-                            track_number: alt.track.track_number,
-                            identification: alt.clone(),
-                        })
-                        .collect();
+            let workers = enrichment::budget::CpuBudget::global().threads();
+            let next: Vec<Task<Message>> = if cancelled {
+                Vec::new()
+            } else {
+                (0..workers).map_while(|_| identify_next_task(s)).collect()
+            };
+            if next.is_empty() {
+                return finish_batch(s, cancelled);
+            }
+            return Task::batch(next);
+        }
 
-                    EnrichmentResult {
-                        track_index: pos,
-                        status: result_status,
-                        title: identification.track.title.clone(),
-                        artist: identification.track.artist.clone(),
-                        album: identification.track.album.clone(),
-                        confidence: Some(identification.score),
-                        changes,
-                        error: None,
-                        confirmed: identification.score >= 0.7, // Auto-confirm high confidence
-                        identification: Some(identification.clone()),
-                        alternatives,
-                        show_alternatives: false, // Hidden by default, expanded on review
-                        selected_alternative: None,
-                    }
+        Message::EnrichBatchIdentifyWithAlts(pos, result) => {
+            let min_confidence = pane_min_confidence(s, pos);
+            s.enrichment_pane
+                .results
+                .push(batch_result(pos, min_confidence, result));
+            let cancelled = match &s.enrichment_pane.task {
+                Some(task) => {
+                    task.advance(1);
+                    task.is_cancelled()
                 }
-                Err(ref e) => EnrichmentResult {
-                    track_index: pos,
-                    status: ResultStatus::Error,
-                    title: None,
-                    artist: None,
-                    album: None,
-                    confidence: None,
-                    changes: vec![],
-                    error: Some(e.clone()),
-                    confirmed: false,
-                    identification: None,
-                    alternatives: vec![],
-                    show_alternatives: false,
-                    selected_alternative: None,
-                },
+                None => false,
             };
 
-            s.enrichment_pane.results.push(enrich_result);
+            s.enrichment_pane.in_flight.remove(&pos);
+            if !cancelled && let Some(next) = identify_next_task(s) {
+                return next;
+            }
+            if !s.enrichment_pane.in_flight.is_empty() {
+                return Task::none();
+            }
 
-            // Check if there are more tracks to process
-            let processed_positions: std::collections::HashSet<usize> = s
-                .enrichment_pane
-                .results
-                .iter()
-                .map(|r| r.track_index)
-                .collect();
+            return finish_batch(s, cancelled);
+        }
 
-            let next_track = s
-                .enrichment_pane
+        Message::EnrichBatchComplete => {
+            s.enrichment_pane.is_identifying = false;
+            if let Some(task) = s.enrichment_pane.task.take() {
+                task.finish();
+            }
+        }
+
+        Message::EnrichVerifyTags => {
+            let pane = &s.enrichment_pane;
+            if pane.api_key.is_empty() {
+                s.status_message = "API key required".to_string();
+                return Task::none();
+            }
+            if !pane.fpcalc_available {
+                s.status_message = "fpcalc not installed".to_string();
+                return Task::none();
+            }
+            if pane.is_identifying || pane.verifying {
+                return Task::none();
+            }
+            let track_ids: std::collections::HashSet<i64> = pane
                 .checked_tracks
                 .iter()
-                .find(|&&p| !processed_positions.contains(&p))
-                .copied();
+                .filter_map(|&pos| Some(s.tracks.get(*pane.selected_tracks.get(pos)?)?.id))
+                .collect();
+            if track_ids.is_empty() {
+                return Task::none();
+            }
 
-            if let Some(next_pos) = next_track
-                && let Some(&track_idx) = s.enrichment_pane.selected_tracks.get(next_pos)
-                && let Some(track) = s.tracks.get(track_idx)
-            {
-                // Process next track
-                let path = PathBuf::from(&track.path);
-                let api_key = s.enrichment_pane.api_key.clone();
-
-                return Task::perform(
-                    async move {
-                        // Small delay for rate limiting
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-
-                        let config = enrichment::EnrichmentConfig {
-                            acoustid_api_key: api_key,
-                            min_confidence: 0.5,
-                            use_musicbrainz: true,
-                            ..Default::default()
-                        };
-                        let service = enrichment::EnrichmentService::new(config);
-                        let result = service
-                            .identify_track_with_alternatives(&path)
-                            .await
-                            .map_err(|e| e.to_string());
-                        (next_pos, result)
-                    },
-                    |(pos, result)| Message::EnrichBatchIdentifyWithAlts(pos, result),
-                );
-            }
-
-            // All done
-            s.enrichment_pane.is_identifying = false;
-            let success_count = s
-                .enrichment_pane
-                .results
-                .iter()
-                .filter(|r| r.status == ResultStatus::Success)
-                .count();
-            let warning_count = s
-                .enrichment_pane
-                .results
-                .iter()
-                .filter(|r| r.status == ResultStatus::Warning)
-                .count();
-            let total = s.enrichment_pane.results.len();
-            s.status_message = format!(
-                "Identification complete: {} of {} matched",
-                success_count, total
+            s.enrichment_pane.verifying = true;
+            s.enrichment_pane.results.clear();
+            s.enrichment_pane.verify_summary = None;
+            let task = s.tasks.start(
+                TaskKind::Enrichment,
+                format!("Verify tags of {} tracks", track_ids.len()),
+            );
+            s.enrichment_pane.service = None;
+            let service = batch_service(&mut s.enrichment_pane);
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    let result =
+                        library::verify_tags::verify(&pool, &service, Some(&track_ids), &task)
+                            .await;
+                    task.finish();
+                    result.map_err(|e| e.to_string())
+                },
+                Message::EnrichTagsVerified,
             );
-
-            // Show appropriate toast
-            if success_count == total {
-                s.toasts.success(format!("All {} tracks identified", total));
-            } else if success_count + warning_count > 0 {
-                s.toasts.info(format!(
-                    "{} matched, {} low confidence",
-                    success_count, warning_count
-                ));
-            } else {
-                s.toasts.warning("No matches found");
-            }
         }
 
-        Message::EnrichBatchComplete => {
-            s.enrichment_pane.is_identifying = false;
+        Message::EnrichTagsVerified(result) => {
+            s.enrichment_pane.verifying = false;
+            s.enrichment_pane.service = None;
+            match result {
+                Ok(report) => {
+                    // Suspects go up for review, never confirmed for writing
+                    for suspect in &report.suspects {
+                        let Some(pos) = s.enrichment_pane.selected_tracks.iter().position(|&i| {
+                            s.tracks.get(i).is_some_and(|t| t.id == suspect.track_id)
+                        }) else {
+                            continue;
+                        };
+                        let mut result = batch_result(
+                            pos,
+                            pane_min_confidence(s, pos),
+                            Ok((suspect.identification.clone(), suspect.alternatives.clone())),
+                        );
+                        result.status = ResultStatus::Warning;
+                        result.confirmed = false;
+                        result.changes = suspect.issues.clone();
+                        s.enrichment_pane.results.push(result);
+                    }
+                    for (path, e) in &report.failed {
+                        tracing::warn!("Couldn't verify {}: {}", path, e);
+                    }
+                    if report.suspects.is_empty() {
+                        s.toasts.success(report.summary());
+                    } else {
+                        s.toasts.warning(format!(
+                            "{} track(s) may be mislabeled - review them below",
+                            report.suspects.len()
+                        ));
+                    }
+                    s.enrichment_pane.verify_summary = Some(report.summary());
+                }
+                Err(e) => s.toasts.error(format!("Tag verification failed: {}", e)),
+            }
+            return load_tracks_task(s.pool.clone());
         }
 
         // Result actions
@@ -506,62 +1004,93 @@ pub fn handle_enrich_pane(s: &mut LoadedState, msg: Message) -> Task<Message> {
             };
 
             let path = PathBuf::from(&track.path);
-            let identified = identification.track.clone();
-            let fill_only = s.enrichment_pane.fill_only;
+            let identification = identification.clone();
+            let fill_only =
+                s.enrichment_pane.fill_only || s.enrichment.folder_defaults.fill_only(&path);
+            let placeholders = s.placeholders.clone();
+            let genre_map = s.genre_map.clone();
+            let manual_edits = s.manual_edits.clone();
+
+            let pool = s.pool.clone();
 
             return Task::perform(
                 async move {
                     let options = metadata::WriteOptions2 {
                         only_fill_empty: fill_only,
                         write_musicbrainz_ids: true,
+                        placeholders,
+                        genre_map,
                     };
-                    tokio::task::spawn_blocking(move || {
-                        metadata::write(&path, &identified, &options)
-                            .map(|r| r.fields_updated)
-                            .map_err(|e| e.to_string())
-                    })
+                    let outcome = write_queue::write_match(
+                        &pool,
+                        &path,
+                        &identification,
+                        options,
+                        &manual_edits,
+                    )
                     .await
-                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())?;
+                    Ok(match outcome {
+                        Outcome::Written(fields) => (fields, 0),
+                        Outcome::Deferred => (0, 1),
+                    })
                 },
                 Message::EnrichmentWriteTagsResult,
             );
         }
 
         Message::EnrichWriteAllConfirmed => {
-            // Collect all confirmed results with identifications
-            let to_write: Vec<(PathBuf, enrichment::domain::IdentifiedTrack)> = s
-                .enrichment_pane
-                .results
-                .iter()
-                .filter(|r| r.confirmed && r.identification.is_some())
-                .filter_map(|r| {
-                    let track_idx = s.enrichment_pane.selected_tracks.get(r.track_index)?;
-                    let track = s.tracks.get(*track_idx)?;
-                    let identification = r.identification.as_ref()?;
-                    Some((PathBuf::from(&track.path), identification.track.clone()))
-                })
-                .collect();
-
+            let to_write = confirmed_writes(s, false);
             if to_write.is_empty() {
                 s.status_message = "No confirmed results to write".to_string();
                 return Task::none();
             }
+            return write_identifications_task(s, to_write);
+        }
 
-            let fill_only = s.enrichment_pane.fill_only;
-            let _count = to_write.len();
+        Message::EnrichWriteInferredTrackNumbers => {
+            if s.enrichment_pane.writing_track_numbers {
+                return Task::none();
+            }
+            s.enrichment_pane.writing_track_numbers = true;
+            let placeholders = s.placeholders.clone();
+            let pool = s.pool.clone();
 
             return Task::perform(
                 async move {
+                    let tracks = library::inferred_tracks(&pool)
+                        .await
+                        .map_err(|e| e.to_string())?;
                     let mut success = 0;
                     let mut errors = Vec::new();
 
-                    for (path, identified) in to_write {
+                    for track in tracks {
+                        let path = PathBuf::from(&track.path);
+                        let identified = enrichment::domain::IdentifiedTrack {
+                            track_number: Some(track.track_number),
+                            ..Default::default()
+                        };
+                        // Never overwrite a number that was tagged since the scan
                         let options = metadata::WriteOptions2 {
-                            only_fill_empty: fill_only,
-                            write_musicbrainz_ids: true,
+                            only_fill_empty: true,
+                            write_musicbrainz_ids: false,
+                            placeholders: placeholders.clone(),
+                            ..Default::default()
                         };
                         match metadata::write(&path, &identified, &options) {
-                            Ok(_) => success += 1,
+                            Ok(r) => {
+                                let _ = library::mark_confirmed(&pool, track.track_id).await;
+                                activity::record_tags_written(&pool, &path, r.fields_updated).await;
+                                provenance::record_written(
+                                    &pool,
+                                    &path,
+                                    &r.fields_written,
+                                    FieldSource::Inferred,
+                                    None,
+                                )
+                                .await;
+                                success += 1;
+                            }
                             Err(e) => errors.push(format!("{}: {}", path.display(), e)),
                         }
                     }
@@ -577,67 +1106,410 @@ pub fn handle_enrich_pane(s: &mut LoadedState, msg: Message) -> Task<Message> {
                         ))
                     }
                 },
-                move |result: Result<usize, String>| match result {
-                    Ok(n) => Message::EnrichmentWriteTagsResult(Ok(n)),
-                    Err(e) => Message::EnrichmentWriteTagsResult(Err(e)),
+                Message::EnrichInferredTrackNumbersWritten,
+            );
+        }
+
+        Message::EnrichInferredTrackNumbersWritten(result) => {
+            s.enrichment_pane.writing_track_numbers = false;
+            match result {
+                Ok(n) => s
+                    .toasts
+                    .success(format!("Track numbers written to {} file(s)", n)),
+                Err(e) => s
+                    .toasts
+                    .error(format!("Failed to write track numbers: {}", e)),
+            }
+            return load_tracks_task(s.pool.clone());
+        }
+
+        Message::EnrichAuditNumbering => {
+            if s.enrichment_pane.numbering_busy {
+                return Task::none();
+            }
+            s.enrichment_pane.numbering_busy = true;
+            // The batch's albums, or the whole library when nothing is selected
+            let track_ids: Vec<i64> = s
+                .enrichment_pane
+                .selected_tracks
+                .iter()
+                .filter_map(|&i| s.tracks.get(i).map(|t| t.id))
+                .collect();
+            let pool = s.pool.clone();
+
+            return Task::perform(
+                async move {
+                    let album_ids = if track_ids.is_empty() {
+                        None
+                    } else {
+                        Some(library::numbering::albums_of(&pool, &track_ids).await?)
+                    };
+                    let albums = library::numbering::audit(&pool, album_ids.as_deref()).await?;
+                    let checked = albums.len();
+                    let failing = albums.into_iter().filter(|a| !a.is_consistent()).collect();
+                    Ok((checked, failing))
+                },
+                |result: sqlx::Result<_>| {
+                    Message::EnrichNumberingAudited(result.map_err(|e| e.to_string()))
                 },
             );
         }
 
-        Message::EnrichExportReport => {
-            // Generate a simple text report
-            let mut report = String::new();
-            report.push_str("Music Minder Enrichment Report\n");
-            report.push_str("==============================\n\n");
-
-            for (i, result) in s.enrichment_pane.results.iter().enumerate() {
-                let status = match result.status {
-                    ResultStatus::Success => "✓",
-                    ResultStatus::Warning => "⚠",
-                    ResultStatus::Error => "✗",
-                    ResultStatus::Pending => "…",
-                };
+        Message::EnrichNumberingAudited(result) => {
+            s.enrichment_pane.numbering_busy = false;
+            match result {
+                Ok((checked, failing)) => {
+                    if failing.is_empty() {
+                        s.toasts
+                            .success(format!("Numbering consistent on {} album(s)", checked));
+                    }
+                    s.enrichment_pane.numbering_checked = Some(checked);
+                    s.enrichment_pane.numbering = failing;
+                }
+                Err(e) => s.toasts.error(format!("Numbering audit failed: {}", e)),
+            }
+            return load_tracks_task(s.pool.clone());
+        }
 
-                let title = result.title.as_deref().unwrap_or("Unknown");
-                let confidence = result
-                    .confidence
-                    .map(|c| format!("{:.0}%", c * 100.0))
-                    .unwrap_or_default();
-
-                report.push_str(&format!(
-                    "{} {}. {} ({})\n",
-                    status,
-                    i + 1,
-                    title,
-                    confidence
-                ));
+        Message::EnrichNormalizeNumbering(album_id) => {
+            if s.enrichment_pane.numbering_busy {
+                return Task::none();
+            }
+            let albums: Vec<_> = s
+                .enrichment_pane
+                .numbering
+                .iter()
+                .filter(|a| album_id.is_none_or(|id| a.album_id == id))
+                .cloned()
+                .collect();
+            if albums.iter().all(|a| a.fixes.is_empty()) {
+                return Task::none();
+            }
+            s.enrichment_pane.numbering_busy = true;
+            let pool = s.pool.clone();
 
-                if let Some(ref artist) = result.artist {
-                    report.push_str(&format!("   Artist: {}\n", artist));
+            return Task::perform(
+                async move {
+                    let mut written = 0;
+                    let mut errors = Vec::new();
+                    for fix in albums.iter().flat_map(|a| &a.fixes) {
+                        let path = PathBuf::from(&fix.path);
+                        match metadata::write_numbering(&path, &fix.after) {
+                            Ok(fields) => {
+                                activity::record_tags_written(&pool, &path, fields.len()).await;
+                                provenance::record_written(
+                                    &pool,
+                                    &path,
+                                    &fields,
+                                    FieldSource::Inferred,
+                                    None,
+                                )
+                                .await;
+                                written += 1;
+                            }
+                            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+                        }
+                    }
+
+                    // Audit again so the list and flags show what's left
+                    let album_ids: Vec<i64> = albums.iter().map(|a| a.album_id).collect();
+                    let audited = library::numbering::audit(&pool, Some(&album_ids))
+                        .await
+                        .map_err(|e| e.to_string())?;
+
+                    if errors.is_empty() {
+                        Ok((written, audited))
+                    } else {
+                        Err(format!(
+                            "{} succeeded, {} failed: {}",
+                            written,
+                            errors.len(),
+                            errors.join("; ")
+                        ))
+                    }
+                },
+                Message::EnrichNumberingNormalized,
+            );
+        }
+
+        Message::EnrichNumberingNormalized(result) => {
+            s.enrichment_pane.numbering_busy = false;
+            match result {
+                Ok((written, audited)) => {
+                    s.toasts
+                        .success(format!("Numbering normalized in {} file(s)", written));
+                    // Replace the re-audited albums, dropping the ones that now pass
+                    let pane = &mut s.enrichment_pane;
+                    pane.numbering
+                        .retain(|a| !audited.iter().any(|b| b.album_id == a.album_id));
+                    pane.numbering
+                        .extend(audited.into_iter().filter(|a| !a.is_consistent()));
+                    pane.numbering.sort_by_key(|a| a.album_id);
                 }
-                if let Some(ref album) = result.album {
-                    report.push_str(&format!("   Album: {}\n", album));
+                Err(e) => s
+                    .toasts
+                    .error(format!("Failed to normalize numbering: {}", e)),
+            }
+            return load_tracks_task(s.pool.clone());
+        }
+
+        Message::EnrichConflictsLoaded(conflicts) => {
+            let new = conflicts
+                .len()
+                .saturating_sub(s.enrichment_pane.conflicts.len());
+            if new > 0 {
+                s.toasts.warning(format!(
+                    "{} suggestion{} kept back from fields you edited by hand - review in Enrich",
+                    new,
+                    if new == 1 { "" } else { "s" }
+                ));
+            }
+            s.enrichment_pane.conflicts = conflicts;
+        }
+
+        Message::EnrichConflictKeep(id) => {
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    provenance::conflicts::keep(&pool, id)
+                        .await
+                        .map(|_| false)
+                        .map_err(|e| e.to_string())
+                },
+                Message::EnrichConflictResolved,
+            );
+        }
+
+        Message::EnrichConflictAccept(id) => {
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    provenance::conflicts::accept(&pool, id)
+                        .await
+                        .map(|_| true)
+                        .map_err(|e| e.to_string())
+                },
+                Message::EnrichConflictResolved,
+            );
+        }
+
+        Message::EnrichConflictResolved(result) => {
+            let pool = s.pool.clone();
+            return match result {
+                Ok(true) => {
+                    Task::batch([load_conflicts_task(pool.clone()), load_tracks_task(pool)])
                 }
-                if let Some(ref error) = result.error {
-                    report.push_str(&format!("   Error: {}\n", error));
+                Ok(false) => load_conflicts_task(pool),
+                Err(e) => {
+                    s.toasts.error(format!("Failed to resolve conflict: {}", e));
+                    load_conflicts_task(pool)
                 }
-                report.push('\n');
-            }
-
-            // For now, just copy to status message (could save to file later)
-            s.status_message = format!(
-                "Report: {} results ({} confirmed)",
-                s.enrichment_pane.results.len(),
-                s.enrichment_pane
-                    .results
-                    .iter()
-                    .filter(|r| r.confirmed)
-                    .count()
+            };
+        }
+
+        Message::EnrichExportPlan => {
+            // Same selection and options as "Write All Confirmed"
+            let to_plan: Vec<(PathBuf, enrichment::domain::IdentifiedTrack)> = s
+                .enrichment_pane
+                .results
+                .iter()
+                .filter(|r| r.confirmed && r.identification.is_some())
+                .filter_map(|r| {
+                    let track_idx = s.enrichment_pane.selected_tracks.get(r.track_index)?;
+                    let track = s.tracks.get(*track_idx)?;
+                    let identification = r.identification.as_ref()?;
+                    Some((PathBuf::from(&track.path), identification.track.clone()))
+                })
+                .collect();
+
+            if to_plan.is_empty() {
+                s.status_message = "No confirmed results to export".to_string();
+                return Task::none();
+            }
+
+            let options = metadata::WriteOptions2 {
+                only_fill_empty: s.enrichment_pane.fill_only,
+                write_musicbrainz_ids: true,
+                placeholders: s.placeholders.clone(),
+                genre_map: s.genre_map.clone(),
+            };
+
+            return Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        let edits = to_plan
+                            .into_iter()
+                            .filter_map(|(path, identified)| {
+                                let preview =
+                                    metadata::preview_write(&path, &identified, &options).ok()?;
+                                Some(plan::PlannedTagEdit::new(path, preview.changes))
+                            })
+                            .collect();
+                        plan::OperationPlan::enrich(edits)
+                    })
+                    .await
+                },
+                |result| match result {
+                    Ok(plan) => Message::EnrichPlanReady(plan),
+                    Err(_) => Message::Noop,
+                },
+            );
+        }
+
+        Message::EnrichPlanReady(plan) => {
+            if plan.is_empty() {
+                s.status_message = "Confirmed results would not change any tags".to_string();
+                return Task::none();
+            }
+            return save_plan_task(plan);
+        }
+
+        Message::EnrichExportReport => {
+            if s.enrichment_pane.results.is_empty() {
+                s.toasts.info("No results to export yet");
+                return Task::none();
+            }
+            let no_match = enrichment::EnrichmentError::NoMatches.to_string();
+            let entries: Vec<_> = s
+                .enrichment_pane
+                .results
+                .iter()
+                .filter_map(|r| {
+                    let track_idx = s.enrichment_pane.selected_tracks.get(r.track_index)?;
+                    let track = s.tracks.get(*track_idx)?;
+                    Some((
+                        PathBuf::from(&track.path),
+                        r.status,
+                        r.identification.clone(),
+                        r.error.clone(),
+                    ))
+                })
+                .collect();
+            let fill_only = s.enrichment_pane.fill_only;
+            let options = metadata::WriteOptions2 {
+                only_fill_empty: fill_only,
+                write_musicbrainz_ids: true,
+                placeholders: s.placeholders.clone(),
+                genre_map: s.genre_map.clone(),
+            };
+
+            return Task::perform(
+                async move {
+                    let Some(handle) = rfd::AsyncFileDialog::new()
+                        .set_file_name("music-minder-enrichment-report.html")
+                        .add_filter("Web page (HTML)", &["html"])
+                        .add_filter("Spreadsheet (CSV)", &["csv"])
+                        .add_filter("JSON", &["json"])
+                        .save_file()
+                        .await
+                    else {
+                        return Ok(None);
+                    };
+                    let path = handle.path().to_path_buf();
+                    let save_path = path.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let files = entries
+                            .into_iter()
+                            .map(|(path, status, identification, error)| {
+                                match (identification, error) {
+                                    (Some(identification), _) => {
+                                        let preview = metadata::preview_write(
+                                            &path,
+                                            &identification.track,
+                                            &options,
+                                        )
+                                        .ok();
+                                        let matched_by = match identification.source {
+                                            enrichment::EnrichmentSource::AcoustId => "fingerprint",
+                                            enrichment::EnrichmentSource::MusicBrainz => "disc ID",
+                                            enrichment::EnrichmentSource::Manual => "manual",
+                                        };
+                                        FileReport::identified(
+                                            path,
+                                            &identification,
+                                            matched_by,
+                                            preview,
+                                        )
+                                    }
+                                    (None, Some(e)) if e.message == no_match => {
+                                        FileReport::no_match(path)
+                                    }
+                                    (None, Some(e)) => FileReport::error(path, e.message),
+                                    (None, None) if status == ResultStatus::Pending => {
+                                        FileReport::pending(path)
+                                    }
+                                    (None, None) => FileReport::no_match(path),
+                                }
+                            })
+                            .collect();
+                        EnrichmentReport::new(files, fill_only).save(&save_path)
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())?;
+                    Ok(Some(path))
+                },
+                Message::EnrichReportExported,
             );
-            tracing::info!(target: "ui::enrich", "\n{}", report);
         }
 
+        Message::EnrichReportExported(result) => match result {
+            Ok(Some(path)) => s
+                .toasts
+                .success(format!("Report saved to {}", path.display())),
+            Ok(None) => {}
+            Err(e) => s.toasts.error(format!("Export failed: {}", e)),
+        },
+
         _ => {}
     }
     Task::none()
 }
+
+/// Load the suggestions held back from hand-edited fields
+pub(crate) fn load_conflicts_task(pool: sqlx::SqlitePool) -> Task<Message> {
+    Task::perform(
+        async move { provenance::conflicts::pending(&pool).await },
+        |result| match result {
+            Ok(conflicts) => Message::EnrichConflictsLoaded(conflicts),
+            Err(e) => {
+                tracing::warn!("Failed to load tag conflicts: {}", e);
+                Message::Noop
+            }
+        },
+    )
+}
+
+/// Load the per-folder enrichment defaults
+pub(crate) fn load_folder_defaults_task(pool: sqlx::SqlitePool) -> Task<Message> {
+    Task::perform(
+        async move { enrichment::folders::load(&pool).await },
+        folder_defaults_loaded,
+    )
+}
+
+/// Save a folder's defaults, then reload them all
+fn save_folder_defaults_task(
+    pool: sqlx::SqlitePool,
+    rule: enrichment::folders::FolderDefaults,
+) -> Task<Message> {
+    Task::perform(
+        async move {
+            if let Err(e) = enrichment::folders::save(&pool, &rule).await {
+                tracing::warn!("Failed to save folder defaults: {}", e);
+            }
+            enrichment::folders::load(&pool).await
+        },
+        folder_defaults_loaded,
+    )
+}
+
+fn folder_defaults_loaded(result: sqlx::Result<enrichment::folders::FolderRules>) -> Message {
+    match result {
+        Ok(rules) => Message::EnrichmentFolderDefaultsLoaded(rules),
+        Err(e) => {
+            tracing::warn!("Failed to load folder defaults: {}", e);
+            Message::Noop
+        }
+    }
+}
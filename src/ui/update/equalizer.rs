@@ -0,0 +1,68 @@
+//! Equalizer pane handlers: the on/off switch, presets and band gains.
+
+use iced::Task;
+
+use crate::player::equalizer::{self, EqPreset};
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+
+/// Hand the equalizer settings to the player, if it's running
+fn push(s: &LoadedState) {
+    if let Some(player) = &s.player {
+        player.set_equalizer(s.equalizer.enabled, &s.equalizer.gains);
+    }
+}
+
+/// Save the equalizer settings to the config file
+fn save(s: &LoadedState) -> Task<Message> {
+    let eq = s.equalizer.clone();
+    Task::perform(
+        async move {
+            let mut cfg = crate::config::load();
+            cfg.audio.eq_enabled = eq.enabled;
+            cfg.audio.eq_preset = eq.preset;
+            cfg.audio.eq_gains_db = eq.gains;
+            crate::config::save_async(cfg).await
+        },
+        |result| {
+            if let Err(e) = result {
+                tracing::warn!("Failed to save equalizer settings: {}", e);
+            }
+            Message::Noop
+        },
+    )
+}
+
+/// Handle equalizer pane messages
+pub fn handle_equalizer(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::EqToggled(on) => {
+            s.equalizer.enabled = on;
+            push(s);
+            save(s)
+        }
+        Message::EqPresetChosen(preset) => {
+            s.equalizer.preset = preset;
+            if let Some(gains) = preset.gains() {
+                s.equalizer.gains = gains;
+            }
+            // Picking a preset is asking to hear it
+            s.equalizer.enabled = true;
+            push(s);
+            save(s)
+        }
+        Message::EqBandChanged(band, gain) => {
+            if band < equalizer::BANDS {
+                s.equalizer.gains[band] =
+                    gain.clamp(equalizer::GAIN_RANGE_DB.0, equalizer::GAIN_RANGE_DB.1);
+                s.equalizer.preset = EqPreset::matching(&s.equalizer.gains);
+                s.equalizer.enabled = true;
+                push(s);
+            }
+            Task::none()
+        }
+        Message::EqBandReleased => save(s),
+        _ => Task::none(),
+    }
+}
@@ -0,0 +1,66 @@
+//! File location actions: show a track in the file manager or copy its path.
+
+use iced::Task;
+use std::path::{Path, PathBuf};
+
+use super::super::messages::Message;
+use super::super::platform;
+use super::super::state::{ActivePane, FocusedList, LoadedState};
+
+/// Handle reveal-in-folder and copy-path messages
+pub fn handle_file_actions(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::RevealInFolder(path) => {
+            if let Err(e) = platform::reveal_in_file_manager(&path) {
+                tracing::warn!(target: "ui::files", "Could not open file manager for {}: {}", path.display(), e);
+                s.toasts
+                    .error(format!("Could not open file manager: {}", e));
+            }
+        }
+        Message::CopyPath(path) => {
+            s.toasts.info("Path copied");
+            return iced::clipboard::write(path.to_string_lossy().into_owned());
+        }
+        Message::CopyRelativePath(path) => {
+            let roots = library_roots(s);
+            return match platform::library_relative_path(&path, &roots) {
+                Some(relative) => {
+                    s.toasts.info("Relative path copied");
+                    iced::clipboard::write(relative.to_string_lossy().into_owned())
+                }
+                None => {
+                    s.toasts
+                        .warning("File is outside the library folders; copied the full path");
+                    iced::clipboard::write(path.to_string_lossy().into_owned())
+                }
+            };
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+/// Folders the library was scanned from or is watching
+fn library_roots(s: &LoadedState) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = s
+        .watcher_state
+        .folders
+        .iter()
+        .map(|f| f.path.clone())
+        .collect();
+    if !roots.iter().any(|r| r == &s.scan_path) {
+        roots.push(s.scan_path.clone());
+    }
+    roots
+}
+
+/// Path of the track under the keyboard cursor in the focused list
+pub(super) fn selected_path(s: &LoadedState) -> Option<&Path> {
+    if s.active_pane == ActivePane::NowPlaying && s.focused_list == FocusedList::Queue {
+        let player = s.player.as_ref()?;
+        let item = player.queue().items().get(s.queue_selection?)?;
+        return Some(item.path.as_path());
+    }
+    let idx = super::selection::library_selection_to_track_index(s, s.panes.library.selection?)?;
+    s.tracks.get(idx).map(|t| Path::new(t.path.as_str()))
+}
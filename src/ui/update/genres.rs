@@ -0,0 +1,167 @@
+//! Genre manager handlers: counting genres, merging them, and the rules.
+
+use iced::Task;
+
+use crate::config;
+use crate::library::genres;
+use crate::metadata::genres::GenreMap;
+use crate::tasks::TaskKind;
+
+use super::super::messages::{GenreRules, Message};
+use super::super::state::LoadedState;
+
+/// Save `tagging.genre_map` after `edit`, returning the saved rules
+async fn save_rules(edit: impl FnOnce(&mut GenreRules) + Send) -> Result<GenreRules, String> {
+    let mut cfg = config::load();
+    edit(&mut cfg.tagging.genre_map);
+    let rules = cfg.tagging.genre_map.clone();
+    config::save_async(cfg).await.map_err(|e| e.to_string())?;
+    Ok(rules)
+}
+
+/// Start using a new set of rules
+fn use_rules(s: &mut LoadedState, rules: GenreRules) {
+    s.genre_map = GenreMap::new(&rules);
+    s.genres.rules = rules;
+}
+
+/// Handle genre manager messages
+pub fn handle_genres(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::GenresLoad => {
+            if s.genres.loading {
+                return Task::none();
+            }
+            s.genres.loading = true;
+            s.genres.rules = config::load().tagging.genre_map;
+            let task = s.tasks.start(TaskKind::Maintenance, "Count genres");
+            task.set_phase("Reading genres");
+            let pool = s.pool.clone();
+            let genre_map = s.genre_map.clone();
+            return Task::perform(
+                async move {
+                    let result = async {
+                        genres::read_missing(&pool, &genre_map).await?;
+                        genres::counts(&pool).await
+                    }
+                    .await;
+                    task.finish();
+                    result.map_err(|e| e.to_string())
+                },
+                Message::GenresLoaded,
+            );
+        }
+        Message::GenresLoaded(result) => {
+            s.genres.loading = false;
+            match result {
+                Ok(counts) => {
+                    s.genres
+                        .selected
+                        .retain(|g| counts.iter().any(|c| c.name == *g));
+                    s.genres.counts = Some(counts);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to count genres: {}", e);
+                    s.toasts.error(format!("Failed to count genres: {}", e));
+                }
+            }
+        }
+        Message::GenresFilterChanged(filter) => s.genres.filter = filter,
+        Message::GenreToggled(genre, on) => {
+            if on {
+                if s.genres.into.trim().is_empty() {
+                    s.genres.into = genre.clone();
+                }
+                s.genres.selected.insert(genre);
+            } else {
+                s.genres.selected.remove(&genre);
+            }
+        }
+        Message::GenresIntoChanged(into) => s.genres.into = into,
+        Message::GenresRememberToggled(on) => s.genres.remember = on,
+        Message::GenresMerge => {
+            let into = s.genres.into.trim().to_string();
+            if s.genres.merging || s.genres.selected.is_empty() || into.is_empty() {
+                return Task::none();
+            }
+            s.genres.merging = true;
+            let from: Vec<String> = s.genres.selected.iter().cloned().collect();
+            let remember = s.genres.remember;
+            let task = s
+                .tasks
+                .start(TaskKind::Maintenance, format!("Merge genres into {}", into));
+            task.set_phase("Rewriting genre tags");
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    let result = async {
+                        let report = genres::merge(&pool, &from, &into)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        let rules = if remember {
+                            Some(save_rules(|rules| genres::add_rules(rules, &from, &into)).await?)
+                        } else {
+                            None
+                        };
+                        Ok::<_, String>((report, rules))
+                    }
+                    .await;
+                    task.finish();
+                    result
+                },
+                Message::GenresMerged,
+            );
+        }
+        Message::GenresMerged(result) => {
+            s.genres.merging = false;
+            match result {
+                Ok((report, rules)) => {
+                    for (path, error) in &report.failed {
+                        tracing::warn!("Genre merge: {}: {}", path, error);
+                    }
+                    let summary = format!(
+                        "Retagged {} track(s) as {}",
+                        report.tracks,
+                        s.genres.into.trim()
+                    );
+                    if report.failed.is_empty() {
+                        s.toasts.success(summary);
+                    } else {
+                        s.toasts.warning(format!(
+                            "{}; {} file(s) couldn't be written",
+                            summary,
+                            report.failed.len()
+                        ));
+                    }
+                    if let Some(rules) = rules {
+                        use_rules(s, rules);
+                    }
+                    s.genres.selected.clear();
+                    s.genres.into.clear();
+                    return handle_genres(s, Message::GenresLoad);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to merge genres: {}", e);
+                    s.toasts.error(format!("Failed to merge genres: {}", e));
+                }
+            }
+        }
+        Message::GenreRuleRemove(spelling) => {
+            return Task::perform(
+                save_rules(move |rules| {
+                    genres::remove_rule(rules, &spelling);
+                }),
+                Message::GenreRulesSaved,
+            );
+        }
+        Message::GenreRulesSaved(result) => match result {
+            Ok(rules) => use_rules(s, rules),
+            Err(e) => {
+                tracing::warn!("Failed to save genre rules: {}", e);
+                s.toasts.error(format!("Failed to save genre rules: {}", e));
+            }
+        },
+        _ => {}
+    }
+    Task::none()
+}
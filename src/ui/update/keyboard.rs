@@ -20,13 +20,41 @@ pub fn handle_keyboard(
     // Don't handle keys when search box might be focused
     // (We'll refine this later with proper focus tracking)
 
+    // Single-key shortcuts of the full-screen Now Playing view
+    if s.now_playing_view.open
+        && modifiers.is_empty()
+        && let keyboard::Key::Character(c) = key.as_ref()
+    {
+        let message = match c.to_ascii_lowercase().as_str() {
+            "v" => Some(Message::NowPlayingCycleVisualization),
+            "l" => Some(Message::NowPlayingToggleLyrics),
+            "d" => Some(Message::NowPlayingToggleIdleDim),
+            "f" => Some(Message::NowPlayingToggleFullscreen),
+            _ => None,
+        };
+        if let Some(message) = message {
+            tracing::debug!(target: "ui::keyboard", "Now Playing shortcut '{}'", c);
+            return Task::done(message);
+        }
+    }
+
     match key.as_ref() {
+        // F11: Full-screen Now Playing view
+        keyboard::Key::Named(key::Named::F11) if modifiers.is_empty() => {
+            tracing::debug!(target: "ui::keyboard", "F11 pressed - toggling full screen");
+            return Task::done(Message::NowPlayingToggleFullscreen);
+        }
+
+        // F12: Performance overlay
+        keyboard::Key::Named(key::Named::F12) if modifiers.is_empty() => {
+            tracing::debug!(target: "ui::keyboard", "F12 pressed - toggling performance overlay");
+            return Task::done(Message::PerfOverlayToggle);
+        }
+
         // Space: Play/Pause toggle
-        keyboard::Key::Named(key::Named::Space) => {
-            if modifiers.is_empty() {
-                tracing::debug!(target: "ui::keyboard", "Space pressed - toggling playback");
-                return Task::done(Message::PlayerToggle);
-            }
+        keyboard::Key::Named(key::Named::Space) if modifiers.is_empty() => {
+            tracing::debug!(target: "ui::keyboard", "Space pressed - toggling playback");
+            return Task::done(Message::PlayerToggle);
         }
 
         // Left Arrow: Previous track (or seek with Shift)
@@ -73,16 +101,23 @@ pub fn handle_keyboard(
 
         // Up Arrow: Move selection up (or volume up with modifier)
         keyboard::Key::Named(key::Named::ArrowUp) => {
-            if modifiers.alt() {
+            let queue_focused =
+                s.active_pane == ActivePane::NowPlaying && s.focused_list == FocusedList::Queue;
+            if queue_focused && modifiers.command() {
+                // Ctrl+Up: Move the selected queue items up
+                tracing::debug!(target: "ui::keyboard", "Ctrl+Up pressed - moving queue selection");
+                return Task::done(Message::QueueMoveUp);
+            } else if queue_focused && modifiers.shift() {
+                // Shift+Up: Extend the queue selection
+                return Task::done(Message::QueueExtendSelection { down: false });
+            } else if modifiers.alt() {
                 // Alt+Up: Move queue item up (if queue focused) OR volume up
                 if s.active_pane == ActivePane::NowPlaying && s.focused_list == FocusedList::Queue {
                     tracing::debug!(target: "ui::keyboard", "Alt+Up pressed - moving queue item up");
                     return Task::done(Message::QueueMoveUp);
-                } else if let Some(player) = &s.player {
-                    let current = player.volume();
-                    let new_vol = (current + 0.05).min(1.1);
-                    tracing::debug!(target: "ui::keyboard", "Alt+Up pressed - volume {:.0}%", new_vol * 100.0);
-                    return Task::done(Message::PlayerVolumeChanged(new_vol));
+                } else if s.player.is_some() {
+                    tracing::debug!(target: "ui::keyboard", "Alt+Up pressed - volume up");
+                    return Task::done(Message::PlayerVolumeStep { up: true });
                 }
             } else if modifiers.is_empty() {
                 // Up: Move selection up in focused list
@@ -97,11 +132,9 @@ pub fn handle_keyboard(
                     }
                     _ => {
                         // Fallback to volume for other panes
-                        if let Some(player) = &s.player {
-                            let current = player.volume();
-                            let new_vol = (current + 0.05).min(1.1);
-                            tracing::debug!(target: "ui::keyboard", "Up pressed - volume {:.0}%", new_vol * 100.0);
-                            Task::done(Message::PlayerVolumeChanged(new_vol))
+                        if s.player.is_some() {
+                            tracing::debug!(target: "ui::keyboard", "Up pressed - volume up");
+                            Task::done(Message::PlayerVolumeStep { up: true })
                         } else {
                             Task::none()
                         }
@@ -112,16 +145,23 @@ pub fn handle_keyboard(
 
         // Down Arrow: Move selection down (or volume down with modifier)
         keyboard::Key::Named(key::Named::ArrowDown) => {
-            if modifiers.alt() {
+            let queue_focused =
+                s.active_pane == ActivePane::NowPlaying && s.focused_list == FocusedList::Queue;
+            if queue_focused && modifiers.command() {
+                // Ctrl+Down: Move the selected queue items down
+                tracing::debug!(target: "ui::keyboard", "Ctrl+Down pressed - moving queue selection");
+                return Task::done(Message::QueueMoveDown);
+            } else if queue_focused && modifiers.shift() {
+                // Shift+Down: Extend the queue selection
+                return Task::done(Message::QueueExtendSelection { down: true });
+            } else if modifiers.alt() {
                 // Alt+Down: Move queue item down (if queue focused) OR volume down
                 if s.active_pane == ActivePane::NowPlaying && s.focused_list == FocusedList::Queue {
                     tracing::debug!(target: "ui::keyboard", "Alt+Down pressed - moving queue item down");
                     return Task::done(Message::QueueMoveDown);
-                } else if let Some(player) = &s.player {
-                    let current = player.volume();
-                    let new_vol = (current - 0.05).max(0.0);
-                    tracing::debug!(target: "ui::keyboard", "Alt+Down pressed - volume {:.0}%", new_vol * 100.0);
-                    return Task::done(Message::PlayerVolumeChanged(new_vol));
+                } else if s.player.is_some() {
+                    tracing::debug!(target: "ui::keyboard", "Alt+Down pressed - volume down");
+                    return Task::done(Message::PlayerVolumeStep { up: false });
                 }
             } else if modifiers.is_empty() {
                 // Down: Move selection down in focused list
@@ -136,11 +176,9 @@ pub fn handle_keyboard(
                     }
                     _ => {
                         // Fallback to volume for other panes
-                        if let Some(player) = &s.player {
-                            let current = player.volume();
-                            let new_vol = (current - 0.05).max(0.0);
-                            tracing::debug!(target: "ui::keyboard", "Down pressed - volume {:.0}%", new_vol * 100.0);
-                            Task::done(Message::PlayerVolumeChanged(new_vol))
+                        if s.player.is_some() {
+                            tracing::debug!(target: "ui::keyboard", "Down pressed - volume down");
+                            Task::done(Message::PlayerVolumeStep { up: false })
                         } else {
                             Task::none()
                         }
@@ -150,48 +188,111 @@ pub fn handle_keyboard(
         }
 
         // Enter: Play selected track
-        keyboard::Key::Named(key::Named::Enter) => {
-            if modifiers.is_empty() {
-                tracing::debug!(target: "ui::keyboard", "Enter pressed - play selected");
-                return Task::done(Message::PlaySelected);
-            }
+        keyboard::Key::Named(key::Named::Enter) if modifiers.is_empty() => {
+            tracing::debug!(target: "ui::keyboard", "Enter pressed - play selected");
+            return Task::done(Message::PlaySelected);
         }
 
         // Delete: Remove selected from queue
-        keyboard::Key::Named(key::Named::Delete) => {
-            if modifiers.is_empty() {
-                tracing::debug!(target: "ui::keyboard", "Delete pressed - remove from queue");
-                return Task::done(Message::RemoveSelectedFromQueue);
-            }
+        keyboard::Key::Named(key::Named::Delete) if modifiers.is_empty() => {
+            tracing::debug!(target: "ui::keyboard", "Delete pressed - remove from queue");
+            return Task::done(Message::RemoveSelectedFromQueue);
         }
 
         // Escape: Cancel drag / Clear search / close panels
-        keyboard::Key::Named(key::Named::Escape) => {
-            if modifiers.is_empty() {
-                // First priority: cancel any active drag
-                if s.queue_drag.dragging.is_some() {
-                    tracing::debug!(target: "ui::keyboard", "Escape pressed - cancelling drag");
-                    return Task::done(Message::QueueDragCancel);
-                }
-                // Second: clear search if active
-                if !s.search_query.is_empty() {
-                    tracing::debug!(target: "ui::keyboard", "Escape pressed - clearing search");
-                    return Task::done(Message::SearchQueryChanged(String::new()));
-                }
+        keyboard::Key::Named(key::Named::Escape) if modifiers.is_empty() => {
+            // First priority: close an open context menu
+            if s.context_menu.is_some() {
+                return Task::done(Message::ContextMenuClose);
+            }
+            // Then: the background tasks popover
+            if s.tasks_popover_open {
+                return Task::done(Message::TasksPopoverClose);
+            }
+            // Then: leave the full-screen Now Playing view
+            if s.now_playing_view.open {
+                return Task::done(Message::NowPlayingViewClose);
+            }
+            // Then: cancel any active drag
+            if s.queue_drag.dragging.is_some() {
+                tracing::debug!(target: "ui::keyboard", "Escape pressed - cancelling drag");
+                return Task::done(Message::QueueDragCancel);
+            }
+            // Second: clear search if active
+            if !s.search_query.is_empty() {
+                tracing::debug!(target: "ui::keyboard", "Escape pressed - clearing search");
+                return Task::done(Message::SearchQueryChanged(String::new()));
             }
             // Future: close other panels
         }
 
-        // Ctrl+F: Focus search (we'll just clear and let user type)
-        keyboard::Key::Character(c) => {
-            if modifiers.control() && c == "f" {
-                tracing::debug!(target: "ui::keyboard", "Ctrl+F pressed - focus search");
-                // For now, clear search to indicate focus
-                // Proper focus management needs widget ID tracking
-                return Task::done(Message::SearchQueryChanged(String::new()));
+        // Ctrl+S: Stop after this track; Ctrl+Shift+S: stop after this album
+        keyboard::Key::Character(c) if modifiers.control() && c.eq_ignore_ascii_case("s") => {
+            if modifiers.shift() {
+                tracing::debug!(target: "ui::keyboard", "Ctrl+Shift+S pressed - stop after album");
+                return Task::done(Message::PlayerStopAfterAlbum);
+            }
+            tracing::debug!(target: "ui::keyboard", "Ctrl+S pressed - stop after track");
+            return Task::done(Message::PlayerStopAfterTrack);
+        }
+
+        // Ctrl+Shift+C: Copy path of the selected track; Ctrl+Alt+C: relative path
+        keyboard::Key::Character(c)
+            if modifiers.control()
+                && (modifiers.shift() || modifiers.alt())
+                && c.eq_ignore_ascii_case("c") =>
+        {
+            if let Some(path) = super::files::selected_path(s) {
+                let path = path.to_path_buf();
+                tracing::debug!(target: "ui::keyboard", "Copy path shortcut - {}", path.display());
+                return Task::done(if modifiers.alt() {
+                    Message::CopyRelativePath(path)
+                } else {
+                    Message::CopyPath(path)
+                });
             }
         }
 
+        // Ctrl+Shift+E: Show the selected track in the file manager
+        keyboard::Key::Character(c)
+            if modifiers.control() && modifiers.shift() && c.eq_ignore_ascii_case("e") =>
+        {
+            if let Some(path) = super::files::selected_path(s) {
+                tracing::debug!(target: "ui::keyboard", "Ctrl+Shift+E pressed - reveal in folder");
+                return Task::done(Message::RevealInFolder(path.to_path_buf()));
+            }
+        }
+
+        // Ctrl+J: Jump to the playing track in the library (or queue)
+        keyboard::Key::Character(c) if modifiers.command() && c.eq_ignore_ascii_case("j") => {
+            tracing::debug!(target: "ui::keyboard", "Ctrl+J pressed - locate current track");
+            return Task::done(Message::LocateCurrentTrack);
+        }
+
+        // Ctrl+M: Mini-player
+        keyboard::Key::Character(c) if modifiers.command() && c.eq_ignore_ascii_case("m") => {
+            tracing::debug!(target: "ui::keyboard", "Ctrl+M pressed - toggling mini-player");
+            return Task::done(Message::MiniPlayerToggle);
+        }
+
+        // Ctrl+V: Set the open album's cover from a copied image file
+        keyboard::Key::Character(c)
+            if modifiers.command()
+                && c.eq_ignore_ascii_case("v")
+                && super::covers::target(s).is_some() =>
+        {
+            tracing::debug!(target: "ui::keyboard", "Ctrl+V pressed - paste cover");
+            return Task::done(Message::CoverPaste);
+        }
+
+        // Ctrl+F: Focus search (we'll just clear and let user type)
+        keyboard::Key::Character(c) if modifiers.control() && c == "f" => {
+            tracing::debug!(target: "ui::keyboard", "Ctrl+F pressed - focus search");
+            // For now, clear search to indicate focus
+            // Proper focus management needs widget ID tracking
+            return Task::done(Message::SearchQueryChanged(String::new()));
+        }
+
         _ => {}
     }
 
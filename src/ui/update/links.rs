@@ -0,0 +1,107 @@
+//! `musicminder://` links: from later launches (see [`crate::instance`])
+//! or the command line that started the app.
+
+use iced::{Task, window};
+use std::path::Path;
+
+use crate::completeness;
+use crate::db;
+use crate::deeplink::DeepLink;
+
+use super::super::messages::Message;
+use super::super::state::{ActivePane, LibraryScope, LoadedState};
+
+/// Bring the window up: out of the taskbar and in front
+fn focus_window() -> Task<Message> {
+    window::get_latest()
+        .and_then(|id| Task::batch([window::minimize(id, false), window::gain_focus(id)]))
+}
+
+/// Library index of the track at `path`
+fn library_index(s: &LoadedState, path: &Path) -> Option<usize> {
+    let key = db::paths::key(&path.to_string_lossy());
+    s.tracks.iter().position(|t| db::paths::key(&t.path) == key)
+}
+
+/// Handle a link left for once the library has loaded
+pub fn library_loaded(s: &mut LoadedState) -> Task<Message> {
+    match s.pending_link.take() {
+        Some(uri) => handle_links(s, Message::DeepLinkReceived(uri)),
+        None => Task::none(),
+    }
+}
+
+/// Handle link messages
+pub fn handle_links(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::DeepLinkReceived(uri) => {
+            let link = match DeepLink::parse(&uri) {
+                Ok(link) => link,
+                Err(e) => {
+                    tracing::warn!("Ignored link {}: {}", uri, e);
+                    s.toasts.error(e.to_string());
+                    return focus_window();
+                }
+            };
+            tracing::info!("Opening link {}", uri);
+            let action = match link {
+                DeepLink::Focus => Task::none(),
+                DeepLink::Play { path } | DeepLink::Queue { path } if s.tracks_loading => {
+                    // The track may not have loaded yet
+                    tracing::debug!("Holding {} until the library loads", path.display());
+                    s.pending_link = Some(uri);
+                    Task::none()
+                }
+                DeepLink::Play { path } => match library_index(s, &path) {
+                    Some(idx) => Task::done(Message::PlayerPlayTrack(idx)),
+                    None => {
+                        s.toasts
+                            .warning(format!("{} isn't in the library", path.display()));
+                        Task::none()
+                    }
+                },
+                DeepLink::Queue { path } => match library_index(s, &path) {
+                    Some(idx) => Task::done(Message::PlayerQueueTrack(idx)),
+                    None => {
+                        s.toasts
+                            .warning(format!("{} isn't in the library", path.display()));
+                        Task::none()
+                    }
+                },
+                DeepLink::Album { mbid } => {
+                    let pool = s.pool.clone();
+                    Task::perform(
+                        async move {
+                            let album = completeness::album_of_release(&pool, &mbid)
+                                .await
+                                .map_err(|e| e.to_string())?;
+                            Ok(album.map(|a| LibraryScope::Album {
+                                album: a.album,
+                                artist: a.artist,
+                            }))
+                        },
+                        Message::DeepLinkAlbumFound,
+                    )
+                }
+                DeepLink::Search { query } => Task::batch([
+                    Task::done(Message::SwitchPane(ActivePane::Library)),
+                    Task::done(Message::SearchQueryChanged(query)),
+                ]),
+            };
+            return Task::batch([focus_window(), action]);
+        }
+        Message::DeepLinkAlbumFound(result) => match result {
+            Ok(Some(scope)) => return Task::done(Message::GoTo(scope)),
+            Ok(None) => s
+                .toasts
+                .warning("No album in the library is identified as that release"),
+            Err(e) => {
+                tracing::warn!("Failed to look up the linked album: {}", e);
+                s.toasts
+                    .error(format!("Failed to look up the linked album: {}", e));
+            }
+        },
+        _ => {}
+    }
+    Task::none()
+}
@@ -0,0 +1,94 @@
+//! Mini-player window mode handlers.
+//!
+//! The mini-player is the same window shrunk to art, title, transport and
+//! seek bar. Whether it is active (and pinned on top) is kept in the config
+//! so the app reopens the way it was left.
+
+use iced::{Size, Task, window};
+
+use super::super::messages::Message;
+use super::super::state::{LoadedState, MiniPlayerState};
+use crate::config;
+
+/// Handle mini-player messages
+pub fn handle_mini_player(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::MiniPlayerToggle => {
+            let mini = &mut s.mini_player;
+            mini.active = !mini.active;
+            let window_task = if mini.active {
+                // Remember the full-size window to come back to
+                if s.window_size.width > MiniPlayerState::SIZE.width
+                    || s.window_size.height > MiniPlayerState::SIZE.height
+                {
+                    mini.restore_size = Some(s.window_size);
+                }
+                s.context_menu = None;
+                s.now_playing_view.open = false;
+                resize_window(MiniPlayerState::SIZE, level(mini))
+            } else {
+                let size = mini
+                    .restore_size
+                    .take()
+                    .unwrap_or_else(|| window::Settings::default().size);
+                resize_window(size, window::Level::Normal)
+            };
+            let (active, on_top) = (mini.active, mini.always_on_top);
+            return Task::batch([window_task, save_task(active, on_top)]);
+        }
+        Message::MiniPlayerToggleAlwaysOnTop => {
+            let mini = &mut s.mini_player;
+            mini.always_on_top = !mini.always_on_top;
+            s.toasts.info(if mini.always_on_top {
+                "Mini-player stays on top"
+            } else {
+                "Mini-player no longer on top"
+            });
+            let level = level(mini);
+            let (active, on_top) = (mini.active, mini.always_on_top);
+            let save = save_task(active, on_top);
+            if active {
+                return Task::batch([
+                    window::get_latest().and_then(move |id| window::change_level(id, level)),
+                    save,
+                ]);
+            }
+            return save;
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+/// Window level for the mini-player
+fn level(mini: &MiniPlayerState) -> window::Level {
+    if mini.active && mini.always_on_top {
+        window::Level::AlwaysOnTop
+    } else {
+        window::Level::Normal
+    }
+}
+
+fn resize_window(size: Size, level: window::Level) -> Task<Message> {
+    window::get_latest().and_then(move |id| {
+        Task::batch([window::resize(id, size), window::change_level(id, level)])
+    })
+}
+
+/// Persist the mini-player preference
+fn save_task(active: bool, always_on_top: bool) -> Task<Message> {
+    Task::perform(
+        async move {
+            let mut cfg = config::load();
+            cfg.appearance.mini_player = active;
+            cfg.appearance.mini_player_always_on_top = always_on_top;
+            config::save_async(cfg).await
+        },
+        |result| {
+            if let Err(e) = result {
+                tracing::warn!("Failed to save mini-player preference: {}", e);
+            }
+            Message::Noop
+        },
+    )
+}
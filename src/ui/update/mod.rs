@@ -1,26 +1,69 @@
 //! Update handlers for application messages.
 //!
 //! This module is split into submodules for maintainability:
-//! - `db`: Database initialization
+//! - `activity`: Library change feed timeline
+//! - `album_artists`: Album artist fix wizard: find, apply and undo
+//! - `context_menu`: Right-click menus and the actions only they offer
+//! - `db`: Database initialization and profile switching
 //! - `scan`: Library scanning
-//! - `organize`: File organization and undo
+//! - `organize`: File organization, undo, and dry-run plans
 //! - `enrichment`: Track identification and metadata writing
+//! - `equalizer`: Equalizer switch, presets and band gains
 //! - `player`: Audio playback and media controls
+//! - `playlists`: Playlists: creating, renaming, reordering and adding to them
+//! - `covers`: Album covers set from a dropped or pasted image
 //! - `diagnostics`: System diagnostics and cover art
+//! - `files`: Reveal tracks in the file manager and copy their paths
+//! - `genres`: Genre manager counts, merges and rules
+//! - `links`: `musicminder://` links from other launches
 //! - `watcher`: Background file system watching
 //! - `search`: Search and filter functionality
 //! - `keyboard`: Keyboard shortcut handling
+//! - `mini_player`: Compact always-on-top window mode
+//! - `navigation`: Pane switching and per-pane view state
+//! - `now_playing`: Full-screen Now Playing view
+//! - `replaygain`: ReplayGain scans of the shown or selected tracks
+//! - `resume`: Playback history and the "pick up where you left off" card
+//! - `scheduler`: Scheduled background maintenance jobs
+//! - `settings_transfer`: Settings bundle export and selective import
+//! - `shutdown`: Finishing in-flight work when the window closes
+//! - `startup`: Subsystems that start after the window is up
+//! - `stats`: Local usage statistics
+//! - `tasks`: Background tasks popover (progress and cancel)
+//! - `updates`: Checking GitHub for a newer release
 
+mod activity;
+mod album_artists;
+mod config_reload;
+mod context_menu;
+mod covers;
 mod db;
 mod diagnostics;
 mod enrichment;
+mod equalizer;
+mod files;
+mod genres;
 mod keyboard;
+mod links;
+mod mini_player;
+mod navigation;
+mod now_playing;
 mod organize;
 mod player;
+mod playlists;
+mod replaygain;
+mod resume;
 mod scan;
+mod scheduler;
 mod search;
 mod selection;
+mod settings_transfer;
+mod shutdown;
+mod startup;
+mod stats;
+mod tasks;
 mod track_detail;
+mod updates;
 mod watcher;
 
 use iced::Task;
@@ -31,16 +74,46 @@ use crate::cover;
 use super::messages::Message;
 
 // Re-export all handler functions
-pub use db::handle_db_init;
+pub use activity::handle_activity;
+pub use album_artists::handle_album_artists;
+pub use config_reload::handle_config_reload;
+pub use context_menu::handle_context_menu;
+pub use covers::handle_covers;
+pub(crate) use db::init_db_task;
+pub use db::{handle_db_init, handle_switch_profile};
 pub use diagnostics::handle_diagnostics;
 pub use enrichment::{handle_enrich_pane, handle_enrichment};
+pub(crate) use enrichment::{load_conflicts_task, load_folder_defaults_task};
+pub use equalizer::handle_equalizer;
+pub use files::handle_file_actions;
+pub use genres::handle_genres;
 pub use keyboard::handle_keyboard;
-pub use organize::{handle_organize, handle_undo};
+pub use links::{handle_links, library_loaded};
+pub use mini_player::handle_mini_player;
+pub use navigation::handle_navigation;
+pub(crate) use navigation::restore_scroll_task;
+pub use now_playing::handle_now_playing_view;
+pub use organize::{handle_nfo, handle_organize, handle_undo, refresh_manual};
+pub(crate) use player::album_track_indices;
 pub use player::handle_player;
+pub use playlists::handle_playlists;
+pub(crate) use playlists::load_playlists_task;
+pub use replaygain::handle_replaygain;
+pub use resume::handle_resume;
+pub(crate) use resume::recent_albums_task;
 pub use scan::handle_scan;
+pub use scheduler::handle_scheduler;
+pub(crate) use scheduler::job_runs_task;
 pub use search::handle_search_filter;
 pub use selection::handle_selection;
+pub use settings_transfer::handle_settings_transfer;
+pub use shutdown::handle_shutdown;
+pub use startup::handle_startup;
+pub use stats::handle_stats;
+pub use tasks::handle_tasks;
 pub use track_detail::handle_track_detail;
+pub(crate) use updates::check_updates_task;
+pub use updates::handle_updates;
 pub use watcher::handle_watcher;
 
 /// Initial batch size for progressive loading - show UI quickly
@@ -144,6 +217,51 @@ pub(crate) fn pick_folder_task(on_pick: fn(Option<PathBuf>) -> Message) -> Task<
     )
 }
 
+/// Helper to persist the scan/organize input history
+pub(crate) fn save_input_history_task(history: crate::config::InputHistory) -> Task<Message> {
+    Task::perform(
+        async move {
+            let mut cfg = crate::config::load();
+            if let Some(path) = history.scan_paths.first() {
+                cfg.library.last_scan_path = Some(path.clone());
+            }
+            cfg.history = history;
+            crate::config::save_async(cfg).await
+        },
+        |result| {
+            if let Err(e) = result {
+                tracing::warn!("Failed to save input history: {}", e);
+            }
+            Message::Noop
+        },
+    )
+}
+
+/// Helper to ask for a file name and save a dry-run plan (JSON or CSV).
+pub(crate) fn save_plan_task(plan: crate::plan::OperationPlan) -> Task<Message> {
+    Task::perform(
+        async move {
+            let Some(handle) = rfd::AsyncFileDialog::new()
+                .set_file_name(format!("music-minder-{}-plan.json", plan.kind))
+                .add_filter("Plan (JSON)", &["json"])
+                .add_filter("Spreadsheet (CSV)", &["csv"])
+                .save_file()
+                .await
+            else {
+                return Ok(None);
+            };
+            let path = handle.path().to_path_buf();
+            let save_path = path.clone();
+            tokio::task::spawn_blocking(move || plan.save(&save_path))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())?;
+            Ok(Some(path))
+        },
+        Message::PlanExported,
+    )
+}
+
 /// Helper to resolve cover art in the background.
 ///
 /// This is non-blocking and will never interfere with audio playback.
@@ -174,7 +292,7 @@ pub(crate) fn resolve_cover_art_task(
             if let Some(ref id) = release_id {
                 match resolver.fetch_remote(id).await {
                     Ok(cover) => return Ok(cover.into()),
-                    Err(e) => return Err(e),
+                    Err(e) => return Err(e.message),
                 }
             }
 
@@ -0,0 +1,149 @@
+//! Pane navigation, "Go to artist/album" links, locating the playing track
+//! and per-pane view state.
+
+use iced::Task;
+use iced::widget::scrollable::{self, AbsoluteOffset};
+use std::path::Path;
+
+use super::super::messages::Message;
+use super::super::state::{
+    ActivePane, FocusedList, LoadedState, PaneStates, organize_preview_scroll_id,
+    virtualization as virt,
+};
+use super::search::LibraryQuery;
+use super::selection::QUEUE_ROW_HEIGHT;
+use super::{handle_activity, handle_playlists, handle_search_filter, handle_stats};
+
+/// Handle navigation messages
+pub fn handle_navigation(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::SwitchPane(pane) => {
+            s.active_pane = pane;
+            s.panes.last_pane = pane;
+            let mut tasks = vec![restore_scroll_task(s, pane), save_task(s.panes.clone())];
+            if pane == ActivePane::Activity {
+                tasks.push(handle_activity(s, Message::ActivityRefresh));
+            }
+            if pane == ActivePane::Stats {
+                tasks.push(handle_stats(s, Message::StatsRefresh));
+            }
+            // Tracks may have been retagged or removed since
+            if pane == ActivePane::Playlists
+                && let Some(id) = s.playlists.selected
+            {
+                tasks.push(handle_playlists(s, Message::PlaylistSelect(id)));
+            }
+            if pane == ActivePane::Settings {
+                tasks.push(super::diagnostics::load_cover_cache_task());
+            }
+            Task::batch(tasks)
+        }
+        Message::GoTo(scope) => {
+            // The search would hide the tracks asked for
+            s.search_query.clear();
+            s.panes.library.selection = None;
+            let mut tasks = vec![
+                handle_search_filter(s, Message::FilterByScope(Some(scope))),
+                handle_navigation(s, Message::SwitchPane(ActivePane::Library)),
+            ];
+            if s.now_playing_view.open {
+                tasks.push(Task::done(Message::NowPlayingViewClose));
+            }
+            Task::batch(tasks)
+        }
+        Message::LocateCurrentTrack => {
+            let Some(path) = s.player_state.current_track.clone() else {
+                s.toasts.info("Nothing is playing");
+                return Task::none();
+            };
+            let mut tasks = Vec::new();
+            if s.now_playing_view.open {
+                tasks.push(Task::done(Message::NowPlayingViewClose));
+            }
+
+            if let Some(idx) = s.tracks.iter().position(|t| Path::new(&t.path) == path) {
+                // Filters hiding the track make way for it
+                if library_position(s, idx).is_none() {
+                    tasks.push(handle_search_filter(s, Message::ClearFilters));
+                }
+                let Some(pos) = library_position(s, idx) else {
+                    return Task::batch(tasks);
+                };
+                s.focused_list = FocusedList::Library;
+                s.panes.library.selection = Some(pos);
+                // Switching restores this offset, which also moves the
+                // virtualized window before the scroll event comes back
+                s.panes.library.scroll_offset = virt::offset_to_center(
+                    pos,
+                    s.panes.library.viewport_height,
+                    virt::TRACK_ROW_HEIGHT,
+                );
+                tasks.push(handle_navigation(
+                    s,
+                    Message::SwitchPane(ActivePane::Library),
+                ));
+            } else if let Some(pos) = s.player.as_ref().and_then(|p| p.queue().current_index()) {
+                // Played from outside the library: show it in the queue
+                s.focused_list = FocusedList::Queue;
+                s.queue_selection = Some(pos);
+                s.panes.now_playing.offset = virt::offset_to_center(
+                    pos,
+                    s.panes.now_playing.viewport_height,
+                    QUEUE_ROW_HEIGHT,
+                );
+                tasks.push(handle_navigation(
+                    s,
+                    Message::SwitchPane(ActivePane::NowPlaying),
+                ));
+            }
+            Task::batch(tasks)
+        }
+        Message::PaneScrolled(pane, viewport) => {
+            s.panes
+                .set_scroll_offset(pane, viewport.absolute_offset().y);
+            if pane == ActivePane::NowPlaying {
+                // Needed to auto-scroll the queue while dragging near its bottom edge
+                s.panes.now_playing.viewport_height = viewport.bounds().height;
+            }
+            Task::none()
+        }
+        _ => Task::none(),
+    }
+}
+
+/// Where a library track is in the list as shown, if the filters let it through
+fn library_position(s: &LoadedState, track_idx: usize) -> Option<usize> {
+    if LibraryQuery::from_state(s).is_default() {
+        Some(track_idx)
+    } else {
+        s.filtered_indices.iter().position(|&i| i == track_idx)
+    }
+}
+
+/// Scroll a pane's freshly built widgets back to where the user left them
+pub(crate) fn restore_scroll_task(s: &LoadedState, pane: ActivePane) -> Task<Message> {
+    let to = |id, y| scrollable::scroll_to(id, AbsoluteOffset { x: 0.0, y });
+    match pane {
+        ActivePane::Library => Task::batch([
+            to(pane.scroll_id(), s.panes.library.scroll_offset),
+            to(
+                organize_preview_scroll_id(),
+                s.panes.library.preview_scroll_offset,
+            ),
+        ]),
+        _ => to(pane.scroll_id(), s.panes.scroll_offset(pane)),
+    }
+}
+
+/// Save view state in the background so it survives a restart
+pub(super) fn save_task(panes: PaneStates) -> Task<Message> {
+    Task::perform(
+        async move { tokio::task::spawn_blocking(move || panes.save()).await },
+        |result| {
+            if let Ok(Err(e)) = result {
+                tracing::warn!("Failed to save view state: {}", e);
+            }
+            Message::Noop
+        },
+    )
+}
@@ -0,0 +1,102 @@
+//! Full-screen Now Playing view handlers.
+
+use iced::{Task, window};
+use std::path::PathBuf;
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+use super::navigation::save_task;
+use crate::metadata;
+
+/// Handle Now Playing view messages
+pub fn handle_now_playing_view(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::NowPlayingBarClicked
+            if s.now_playing_view
+                .register_bar_click(std::time::Instant::now()) =>
+        {
+            return Task::done(Message::NowPlayingViewOpen);
+        }
+        Message::NowPlayingViewOpen => {
+            s.now_playing_view.open = true;
+            s.now_playing_view.wake();
+            s.context_menu = None;
+            // Lyrics normally arrive with the track; read them if they haven't
+            if let Some(path) = s.player_state.current_track.clone()
+                && s.now_playing_view.lyrics_for.as_ref() != Some(&path)
+            {
+                return lyrics_task(path);
+            }
+        }
+        Message::NowPlayingViewClose => {
+            s.now_playing_view.open = false;
+            let save = save_task(s.panes.clone());
+            if s.now_playing_view.window_fullscreen {
+                s.now_playing_view.window_fullscreen = false;
+                return Task::batch([save, set_window_mode(window::Mode::Windowed)]);
+            }
+            return save;
+        }
+        Message::NowPlayingToggleFullscreen => {
+            let fullscreen = !s.now_playing_view.window_fullscreen;
+            s.now_playing_view.window_fullscreen = fullscreen;
+            let mode = if fullscreen {
+                window::Mode::Fullscreen
+            } else {
+                window::Mode::Windowed
+            };
+            if fullscreen && !s.now_playing_view.open {
+                return Task::batch([
+                    Task::done(Message::NowPlayingViewOpen),
+                    set_window_mode(mode),
+                ]);
+            }
+            return set_window_mode(mode);
+        }
+        Message::NowPlayingToggleLyrics => {
+            let prefs = &mut s.panes.now_playing_view;
+            prefs.show_lyrics = !prefs.show_lyrics;
+        }
+        Message::NowPlayingToggleIdleDim => {
+            let prefs = &mut s.panes.now_playing_view;
+            prefs.idle_dim = !prefs.idle_dim;
+            s.toasts.info(if prefs.idle_dim {
+                "Idle dimming on"
+            } else {
+                "Idle dimming off"
+            });
+        }
+        Message::NowPlayingCycleVisualization => {
+            s.visualization_mode = s.visualization_mode.next();
+            s.toasts
+                .info(format!("Visualizer: {}", s.visualization_mode));
+        }
+        Message::NowPlayingLyricsLoaded(path, lyrics) => {
+            s.now_playing_view.lyrics_for = Some(path);
+            s.now_playing_view.lyrics = lyrics;
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+/// Read the lyrics tag of a track in the background
+pub(super) fn lyrics_task(path: PathBuf) -> Task<Message> {
+    Task::perform(
+        async move {
+            let read = path.clone();
+            let lyrics = tokio::task::spawn_blocking(move || metadata::read_full(&read))
+                .await
+                .ok()
+                .and_then(|r| r.ok())
+                .and_then(|m| m.lyrics)
+                .filter(|l| !l.trim().is_empty());
+            (path, lyrics)
+        },
+        |(path, lyrics)| Message::NowPlayingLyricsLoaded(path, lyrics),
+    )
+}
+
+fn set_window_mode(mode: window::Mode) -> Task<Message> {
+    window::get_latest().and_then(move |id| window::change_mode(id, mode))
+}
@@ -1,19 +1,26 @@
 //! File organization and undo handlers.
 
 use iced::Task;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::{db, metadata, organizer};
+use crate::plan::{OperationPlan, PlanKind};
+use crate::tasks::TaskKind;
+use crate::{config, nfo, organizer};
 
 use super::super::messages::Message;
 use super::super::state::{LoadedState, OrganizeView};
-use super::{load_tracks_task, pick_folder_task};
+use super::{
+    load_tracks_task, pick_folder_task, resolve_cover_art_task, save_input_history_task,
+    save_plan_task,
+};
 
 /// Handle organize-related messages
 pub fn handle_organize(s: &mut LoadedState, msg: Message) -> Task<Message> {
     match msg {
         Message::OrganizeDestinationChanged(dest) => {
             s.organize_destination = PathBuf::from(dest);
+            refresh_manual(s);
         }
         Message::OrganizePatternChanged(pattern) => {
             s.organize_pattern = pattern;
@@ -23,48 +30,451 @@ pub fn handle_organize(s: &mut LoadedState, msg: Message) -> Task<Message> {
         }
         Message::OrganizeDestinationPicked(Some(path)) => {
             s.organize_destination = path;
+            refresh_manual(s);
         }
         Message::OrganizePreviewPressed => {
+            let history = &mut s.input_history;
+            config::remember(
+                &mut history.organize_destinations,
+                s.organize_destination.clone(),
+            );
+            config::remember(&mut history.organize_patterns, s.organize_pattern.clone());
+            let save = save_input_history_task(history.clone());
             s.organize_preview.clear();
+            s.organize_review.clear();
+            s.organize_plan = None;
+            s.organize_simulation = None;
             s.organize_view = OrganizeView::Preview;
             s.preview_loading = true;
-            s.preview_scroll_offset = 0.0;
+            s.panes.library.preview_scroll_offset = 0.0;
+            return save;
         }
         Message::OrganizePreviewBatch(batch) => {
             s.organize_preview.extend(batch);
         }
         Message::OrganizePreviewComplete => {
             s.preview_loading = false;
+            regroup(s);
+            // Stamp the previewed files so execution can detect drift
+            let previews = s.organize_preview.clone();
+            return Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || OperationPlan::organize(&previews)).await
+                },
+                |result| match result {
+                    Ok(plan) => Message::OrganizePlanReady(plan),
+                    Err(_) => Message::Noop,
+                },
+            );
+        }
+        Message::OrganizePlanReady(plan) => {
+            let simulate = simulate_task(&plan);
+            s.organize_plan = Some(plan);
+            return simulate;
+        }
+        Message::OrganizeSimulated(simulation) => {
+            if !simulation.is_clear() {
+                s.status_message = format!(
+                    "{} of {} moves conflict: {}. Skip them or change the pattern.",
+                    simulation.conflicts.len(),
+                    simulation.moves,
+                    simulation.summary()
+                );
+                s.toasts.warning(format!(
+                    "{} moves would overwrite files",
+                    simulation.conflicts.len()
+                ));
+            }
+            s.organize_simulation = Some(simulation);
+        }
+        Message::OrganizeSkipConflicts => {
+            let (Some(plan), Some(simulation)) =
+                (s.organize_plan.take(), s.organize_simulation.take())
+            else {
+                return Task::none();
+            };
+            let skipped: Vec<PathBuf> =
+                simulation.conflicts.into_iter().map(|c| c.source).collect();
+            s.organize_preview.retain(|p| !skipped.contains(&p.source));
+            regroup(s);
+            let plan = plan.without_moves(&skipped);
+            // Skipping a move can leave its source in another one's way
+            let simulate = simulate_task(&plan);
+            s.organize_plan = Some(plan);
+            s.status_message = format!("Skipped {} conflicting moves", skipped.len());
+            return simulate;
+        }
+        Message::OrganizeToggleMove(index) => {
+            if let Some(preview) = s.organize_preview.get(index) {
+                let excluded = &mut s.organize_review.excluded;
+                if !excluded.remove(&preview.source) {
+                    excluded.insert(preview.source.clone());
+                }
+            }
+        }
+        Message::OrganizeToggleAlbum(group) => {
+            let review = &mut s.organize_review;
+            let Some(items) = review.groups.get(group).map(|g| g.items.clone()) else {
+                return Task::none();
+            };
+            // Back in once all of it is left out, else all of it out
+            let include = review.excluded_in(group, &s.organize_preview) == items.len();
+            for source in items.iter().map(|&i| &s.organize_preview[i].source) {
+                if include {
+                    review.excluded.remove(source);
+                } else {
+                    review.excluded.insert(source.clone());
+                }
+            }
+        }
+        Message::OrganizeExpandAlbum(group) => {
+            let review = &mut s.organize_review;
+            if !review.collapsed.remove(&group) {
+                review.collapsed.insert(group);
+            }
+            review.rebuild_rows();
+        }
+        Message::OrganizeCollapseAll(collapse) => {
+            let review = &mut s.organize_review;
+            review.collapsed = if collapse {
+                (0..review.groups.len()).collect()
+            } else {
+                Default::default()
+            };
+            review.rebuild_rows();
         }
         Message::OrganizeCancelPressed => {
             s.organize_view = OrganizeView::Input;
             s.organize_preview.clear();
+            s.organize_review.clear();
+            s.organize_plan = None;
+            s.organize_simulation = None;
+            s.preview_loading = false;
+        }
+        Message::OrganizeConfirmPressed => {
+            let Some(plan) = chosen_plan(s) else {
+                return Task::none();
+            };
+            // Conflicts have to be resolved (or left out) first
+            if !s
+                .organize_simulation
+                .as_ref()
+                .is_some_and(|sim| s.organize_review.clears(sim))
+            {
+                return Task::none();
+            }
+            s.status_message = "Checking plan against files on disk...".to_string();
+            return Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        // Files may have appeared in the destination since
+                        // the preview
+                        let conflicts = organizer::simulate(&plan.to_previews()).conflicts;
+                        plan.detect_drift()
+                            .iter()
+                            .map(ToString::to_string)
+                            .chain(conflicts.iter().map(ToString::to_string))
+                            .collect::<Vec<_>>()
+                    })
+                    .await
+                    .unwrap_or_else(|e| vec![format!("Task error: {}", e)])
+                },
+                Message::OrganizeDriftChecked,
+            );
+        }
+        Message::OrganizeDriftChecked(drift) => {
+            if drift.is_empty() {
+                return start_organize(s);
+            }
+            s.status_message = format!(
+                "{} files changed or got in the way since the preview (e.g. {}). Re-run Preview.",
+                drift.len(),
+                drift[0]
+            );
+            s.toasts.warning(format!(
+                "Plan is out of date: {} files changed",
+                drift.len()
+            ));
+        }
+        Message::OrganizeExportPlan => {
+            if let Some(plan) = chosen_plan(s) {
+                return save_plan_task(plan);
+            }
+        }
+        Message::OrganizeLoadPlan => {
+            return Task::perform(
+                async {
+                    let handle = rfd::AsyncFileDialog::new()
+                        .add_filter("Plan (JSON)", &["json"])
+                        .pick_file()
+                        .await?;
+                    let path = handle.path().to_path_buf();
+                    let result = tokio::task::spawn_blocking(move || OperationPlan::load(&path))
+                        .await
+                        .map_err(|e| e.to_string())
+                        .and_then(|r| r.map_err(|e| e.to_string()));
+                    Some(result)
+                },
+                |result| result.map_or(Message::Noop, Message::OrganizePlanLoaded),
+            );
+        }
+        Message::OrganizePlanLoaded(Ok(plan)) => {
+            if plan.kind != PlanKind::Organize {
+                s.toasts.warning(format!(
+                    "This is an {} plan; apply it with `music-minder apply-plan`",
+                    plan.kind
+                ));
+                return Task::none();
+            }
+            s.status_message = format!(
+                "Loaded plan with {} moves (created {})",
+                plan.moves.len(),
+                plan.created_at
+            );
+            s.organize_preview = plan.to_previews();
+            s.organize_review.clear();
+            regroup(s);
+            let simulate = simulate_task(&plan);
+            s.organize_plan = Some(plan);
+            s.organize_simulation = None;
+            s.organize_view = OrganizeView::Preview;
             s.preview_loading = false;
+            s.panes.library.preview_scroll_offset = 0.0;
+            return simulate;
+        }
+        Message::OrganizePlanLoaded(Err(e)) => {
+            s.status_message = format!("Failed to load plan: {}", e);
+            s.toasts.error("Failed to load plan");
+        }
+        Message::PlanExported(Ok(Some(path))) => {
+            s.status_message = format!("Plan saved to {}", path.display());
+            s.toasts.success("Plan exported");
+        }
+        Message::PlanExported(Ok(None)) => {}
+        Message::PlanExported(Err(e)) => {
+            s.status_message = format!("Failed to export plan: {}", e);
+            s.toasts.error("Failed to export plan");
         }
-        Message::OrganizeConfirmPressed => return start_organize(s),
         Message::OrganizeFileComplete(result) => {
             s.organize_progress += 1;
             if let Err(e) = result {
                 s.organize_errors.push(e);
             }
         }
-        Message::OrganizeFinished => return finish_organize(s),
+        Message::OrganizeFinished(report) => return finish_organize(s, report),
+        Message::OrganizeRecover(mode) => {
+            let Some(incomplete) = s.interrupted_organize.clone() else {
+                return Task::none();
+            };
+            s.status_message = match mode {
+                organizer::RecoveryMode::Resume => "Resuming interrupted organize...",
+                organizer::RecoveryMode::Rollback => "Rolling back interrupted organize...",
+            }
+            .to_string();
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move { organizer::recover(&pool, &incomplete, mode).await },
+                Message::OrganizeRecoverComplete,
+            );
+        }
+        Message::OrganizeRecoverComplete(report) => {
+            let follow = follow_moves(s, &report.sync);
+            s.interrupted_organize = organizer::OrganizeJournal::load_incomplete();
+            s.can_undo = organizer::UndoLog::has_undo();
+            if report.errors.is_empty() {
+                s.status_message = format!(
+                    "Recovery complete: {} resumed, {} rolled back.",
+                    report.resumed, report.rolled_back
+                );
+                s.toasts.success("Library and files are back in sync");
+            } else {
+                s.status_message = format!(
+                    "Recovery finished with {} errors (e.g. {}). Retry or fix the files manually.",
+                    report.errors.len(),
+                    report.errors[0]
+                );
+                s.toasts.warning(format!(
+                    "Recovery incomplete: {} errors",
+                    report.errors.len()
+                ));
+            }
+            return Task::batch([load_tracks_task(s.pool.clone()), follow]);
+        }
+        Message::OrganizeManualOpen => {
+            s.organize_view = OrganizeView::Manual;
+            refresh_manual(s);
+        }
+        Message::OrganizeManualClose => {
+            s.organize_view = OrganizeView::Input;
+            s.manual_organize = Default::default();
+        }
+        Message::OrganizeManualExpand(folder) => {
+            let manual = &mut s.manual_organize;
+            manual.expanded = match manual.expanded.take() {
+                Some(open) if open == folder => None,
+                _ => Some(folder),
+            };
+        }
+        Message::OrganizeManualGrab(path) => {
+            s.manual_organize.held = Some(path);
+            s.manual_organize.dragging = true;
+        }
+        Message::OrganizeManualHover(folder) => {
+            s.manual_organize.hover = folder;
+        }
+        Message::OrganizeManualRelease => {
+            // Let go away from a folder: stays picked so a click on a
+            // folder can drop it
+            s.manual_organize.dragging = false;
+        }
+        Message::OrganizeManualUnpick => {
+            s.manual_organize.held = None;
+            s.manual_organize.dragging = false;
+        }
+        Message::OrganizeManualDrop(into) => {
+            let manual = &mut s.manual_organize;
+            manual.dragging = false;
+            if !manual.moves.is_empty() {
+                return Task::none();
+            }
+            let Some(held) = manual.held.take() else {
+                return Task::none();
+            };
+            let moves = organizer::drop_moves(
+                s.tracks.iter().map(|t| (t.id, Path::new(&t.path))),
+                &held,
+                &into,
+            );
+            if moves.is_empty() {
+                s.toasts.info(format!(
+                    "Nothing to move: {} is already there",
+                    held.display()
+                ));
+                return Task::none();
+            }
+            s.status_message = format!("Checking {} moves into {}...", moves.len(), into.display());
+            manual.moves = moves.clone();
+            return Task::perform(
+                async move { tokio::task::spawn_blocking(move || organizer::simulate(&moves)).await },
+                |result| match result {
+                    Ok(simulation) => Message::OrganizeManualChecked(simulation),
+                    Err(_) => Message::Noop,
+                },
+            );
+        }
+        Message::OrganizeManualChecked(simulation) => {
+            if !simulation.is_clear() {
+                s.manual_organize.moves.clear();
+                s.status_message = format!(
+                    "{} of {} moves would overwrite files: {}. Nothing was moved.",
+                    simulation.conflicts.len(),
+                    simulation.moves,
+                    simulation.summary()
+                );
+                s.toasts.warning(format!(
+                    "{} moves would overwrite files",
+                    simulation.conflicts.len()
+                ));
+                return Task::none();
+            }
+            // The same path as a previewed organize: journal, undo log and
+            // database paths
+            s.organize_plan = Some(OperationPlan::organize(&s.manual_organize.moves));
+            let start = start_organize(s);
+            if s.organize_view != OrganizeView::Organizing {
+                s.manual_organize.moves.clear();
+            }
+            return start;
+        }
         _ => {}
     }
     Task::none()
 }
 
-/// Start the organize operation
+/// Rebuild the manual organize panes from the loaded library
+pub fn refresh_manual(s: &mut LoadedState) {
+    if s.organize_view != OrganizeView::Manual {
+        return;
+    }
+    let tracks = || s.tracks.iter().map(|t| (t.id, Path::new(&t.path)));
+    let root = &s.organize_destination;
+    let manual = &mut s.manual_organize;
+    manual.incoming = organizer::incoming_folders(tracks(), root);
+    manual.library = organizer::library_folders(tracks(), root);
+    if let Some(open) = &manual.expanded
+        && !manual.incoming.iter().any(|f| f.path == *open)
+    {
+        manual.expanded = None;
+    }
+}
+
+/// Group the preview by album, for reviewing it
+fn regroup(s: &mut LoadedState) {
+    let albums: HashMap<i64, String> = s
+        .tracks
+        .iter()
+        .map(|t| (t.id, format!("{} – {}", t.album_name, t.artist_name)))
+        .collect();
+    s.organize_review
+        .regroup(&s.organize_preview, |id| albums.get(&id).cloned());
+}
+
+/// The plan without the moves left out of it
+fn chosen_plan(s: &LoadedState) -> Option<OperationPlan> {
+    let plan = s.organize_plan.clone()?;
+    let excluded = &s.organize_review.excluded;
+    Some(if excluded.is_empty() {
+        plan
+    } else {
+        plan.without_moves(excluded)
+    })
+}
+
+/// Play the plan's moves against the disk in the background
+fn simulate_task(plan: &OperationPlan) -> Task<Message> {
+    let previews = plan.to_previews();
+    Task::perform(
+        async move { tokio::task::spawn_blocking(move || organizer::simulate(&previews)).await },
+        |result| match result {
+            Ok(simulation) => Message::OrganizeSimulated(simulation),
+            Err(_) => Message::Noop,
+        },
+    )
+}
+
+/// Start the organize operation, moving files exactly as planned.
+///
+/// Cancelling its task stops before the next file; files already moved stay
+/// moved (and undoable).
 fn start_organize(s: &mut LoadedState) -> Task<Message> {
+    let Some(plan) = chosen_plan(s) else {
+        return Task::none();
+    };
+    s.organize_plan = None;
+    s.organize_review.clear();
+    s.organize_simulation = None;
+    let previews = plan.to_previews();
+
+    let mut journal = match organizer::OrganizeJournal::begin() {
+        Ok(j) => j,
+        Err(e) => {
+            s.status_message = format!("Cannot start organize: {}", e);
+            s.toasts.error("Cannot start organize");
+            s.interrupted_organize = organizer::OrganizeJournal::load_incomplete();
+            return Task::none();
+        }
+    };
+
     s.organize_view = OrganizeView::Organizing;
     s.organize_progress = 0;
-    s.organize_total = s.organize_preview.len();
+    s.organize_total = previews.len();
     s.organize_errors.clear();
+    let task = s.tasks.start(TaskKind::Organize, "Organize files");
+    task.set_phase("Moving files");
+    task.set_total(previews.len() as u64);
+    s.organize_task = Some(task.clone());
 
     let pool = s.pool.clone();
-    let pattern = s.organize_pattern.clone();
-    let destination = s.organize_destination.clone();
-    let previews = s.organize_preview.clone();
 
     Task::perform(
         async move {
@@ -73,52 +483,90 @@ fn start_organize(s: &mut LoadedState) -> Task<Message> {
                 timestamp: Some(chrono::Utc::now().to_rfc3339()),
             };
             let mut results = vec![];
+            let mut sync = organizer::PathSync::new();
 
             for preview in previews {
+                if task.is_cancelled() {
+                    break;
+                }
                 let src = preview.source.clone();
-                let pat = pattern.clone();
-                let dest = destination.clone();
+                let dest = preview.destination.clone();
+                let track_id = preview.track_id;
 
                 let res = tokio::task::spawn_blocking(move || {
-                    let meta = metadata::read(&src)?;
-                    organizer::organize_track(&src, &meta, &pat, &dest).map(|p| (src, p))
+                    let seq = journal.journaled_move(track_id, &src, &dest);
+                    (journal, seq.map(|seq| (seq, src, dest)))
                 })
                 .await;
 
+                let Ok((j, res)) = res else {
+                    // Journal handle lost; leave it on disk for recovery
+                    results.push(Err("Task error: organize aborted".to_string()));
+                    return tokio::task::spawn_blocking(move || sync.finish())
+                        .await
+                        .unwrap_or_default();
+                };
+                journal = j;
+
                 match res {
-                    Ok(Ok((src, new_path))) => {
-                        let path_str = new_path.to_string_lossy().to_string();
-                        if let Err(e) =
-                            db::update_track_path(&pool, preview.track_id, &path_str).await
-                        {
+                    Ok((seq, src, new_path)) => {
+                        if let Err(e) = sync.record(&pool, track_id, &new_path).await {
                             results.push(Err(format!("DB error: {}", e)));
                         } else {
+                            if let Err(e) = journal.record_commit(seq) {
+                                tracing::warn!("Failed to commit journal entry: {}", e);
+                            }
                             undo_log.moves.push(organizer::MoveRecord {
                                 source: src,
                                 destination: new_path,
-                                track_id: preview.track_id,
+                                track_id,
                             });
                             results.push(Ok(()));
                         }
                     }
-                    Ok(Err(e)) => results.push(Err(format!("{}: {}", preview.source.display(), e))),
-                    Err(e) => results.push(Err(format!("Task error: {}", e))),
+                    Err(e) => results.push(Err(format!("{}: {}", preview.source.display(), e))),
                 }
+                task.advance(1);
             }
 
             let log = undo_log;
-            let _ = tokio::task::spawn_blocking(move || log.save()).await;
-            results
+            tokio::task::spawn_blocking(move || {
+                // Before this batch's undo log replaces the earlier one
+                let report = sync.finish();
+                let _ = log.save();
+                // Anything left uncommitted is offered for resume/rollback
+                if journal.is_complete() {
+                    let _ = journal.finish();
+                }
+                report
+            })
+            .await
+            .unwrap_or_default()
         },
-        |_| Message::OrganizeFinished,
+        Message::OrganizeFinished,
     )
 }
 
 /// Finish the organize operation
-fn finish_organize(s: &mut LoadedState) -> Task<Message> {
+fn finish_organize(s: &mut LoadedState, report: organizer::SyncReport) -> Task<Message> {
+    let follow = follow_moves(s, &report);
     let errors = s.organize_errors.len();
-    let success = s.organize_total - errors;
-    if errors == 0 {
+    let (done, cancelled) = match s.organize_task.take() {
+        Some(task) => {
+            task.finish();
+            (task.progress().done as usize, task.is_cancelled())
+        }
+        None => (s.organize_total, false),
+    };
+    let success = done.saturating_sub(errors);
+    if cancelled {
+        s.status_message = format!(
+            "Organize cancelled: {} of {} files moved.",
+            success, s.organize_total
+        );
+        s.toasts
+            .warning(format!("Organize cancelled after {} files", success));
+    } else if errors == 0 {
         s.status_message = format!("Organized {} files successfully.", success);
         s.toasts.success(format!("Organized {} files", success));
     } else {
@@ -129,10 +577,118 @@ fn finish_organize(s: &mut LoadedState) -> Task<Message> {
         s.toasts
             .warning(format!("{} files organized, {} errors", success, errors));
     }
-    s.organize_view = OrganizeView::Input;
+    // A manual drop goes back to its panes, refreshed once tracks reload
+    s.organize_view = if s.manual_organize.moves.is_empty() {
+        OrganizeView::Input
+    } else {
+        OrganizeView::Manual
+    };
+    s.manual_organize.moves.clear();
     s.organize_preview.clear();
     s.can_undo = organizer::UndoLog::has_undo();
-    load_tracks_task(s.pool.clone())
+    s.interrupted_organize = organizer::OrganizeJournal::load_incomplete();
+    let reload = Task::batch([load_tracks_task(s.pool.clone()), follow]);
+    if s.nfo.after_organize && !cancelled && success > 0 {
+        let scope = nfo::Scope::Under(s.organize_destination.clone());
+        return Task::batch([reload, Task::done(Message::NfoExport(scope))]);
+    }
+    reload
+}
+
+/// Point everything the running app holds by path at the files' new
+/// locations, and look the cover up again if the playing track moved (its
+/// folder art may have stayed behind or been replaced)
+fn follow_moves(s: &mut LoadedState, report: &organizer::SyncReport) -> Task<Message> {
+    if report.changes.is_empty() {
+        return Task::none();
+    }
+    let moved = |path: &Path| report.new_location(path).map(Path::to_path_buf);
+
+    if let Some(player) = &mut s.player {
+        player.relocate(moved);
+        s.player_state = player.state();
+    }
+    for path in [
+        &mut s.silence_trim.track,
+        &mut s.listening.track,
+        &mut s.now_playing_view.lyrics_for,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        report.relocate(path);
+    }
+    if let Some(session) = &mut s.resume.session {
+        session.queue.iter_mut().for_each(|path| {
+            report.relocate(path);
+        });
+    }
+    for change in &report.changes {
+        s.seek_markers.relocate(&change.from, &change.to);
+    }
+
+    let Some(old) = s.cover_art.for_track.clone() else {
+        return Task::none();
+    };
+    let Some(new) = moved(&old) else {
+        return Task::none();
+    };
+    s.cover_art.for_track = Some(new.clone());
+    s.cover_art.loading = true;
+    resolve_cover_art_task(new, None)
+}
+
+/// Handle Kodi/Jellyfin sidecar file messages
+pub fn handle_nfo(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::NfoAfterOrganizeToggled(enabled) => {
+            s.nfo.after_organize = enabled;
+            return Task::perform(
+                async move {
+                    let mut cfg = config::load();
+                    cfg.nfo.after_organize = enabled;
+                    config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save NFO settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+        Message::NfoExport(scope) => {
+            let task = s.tasks.start(TaskKind::Maintenance, "Write NFO files");
+            task.set_phase("Writing folder art and NFO files");
+            let pool = s.pool.clone();
+            let nfo_config = s.nfo.clone();
+            return Task::perform(
+                async move {
+                    let result = nfo::export(&pool, &scope, &nfo_config).await;
+                    task.finish();
+                    result.map_err(|e| e.to_string())
+                },
+                Message::NfoExportComplete,
+            );
+        }
+        Message::NfoExportComplete(Ok(report)) => {
+            for error in &report.errors {
+                tracing::warn!("NFO export: {}", error);
+            }
+            s.status_message = report.summary();
+            if report.errors.is_empty() {
+                s.toasts.success(report.summary());
+            } else {
+                s.toasts.warning(report.summary());
+            }
+        }
+        Message::NfoExportComplete(Err(e)) => {
+            s.status_message = format!("NFO export failed: {}", e);
+            s.toasts.error("NFO export failed");
+        }
+        _ => {}
+    }
+    Task::none()
 }
 
 /// Handle undo-related messages
@@ -152,39 +708,42 @@ pub fn handle_undo(s: &mut LoadedState, msg: Message) -> Task<Message> {
                     };
 
                     let mut count = 0;
+                    let mut sync = organizer::PathSync::new();
                     for rec in &log.moves {
                         let r = rec.clone();
                         if let Ok(Ok(())) =
                             tokio::task::spawn_blocking(move || organizer::undo_move(&r)).await
                         {
-                            let _ = db::update_track_path(
-                                &pool,
-                                rec.track_id,
-                                &rec.source.to_string_lossy(),
-                            )
-                            .await;
+                            let _ = sync.record(&pool, rec.track_id, &rec.source).await;
                             count += 1;
                         }
                     }
-                    let _ = tokio::task::spawn_blocking(organizer::UndoLog::clear).await;
-                    Ok(count)
+                    let report = tokio::task::spawn_blocking(move || {
+                        let _ = organizer::UndoLog::clear();
+                        sync.finish()
+                    })
+                    .await
+                    .map_err(|e| format!("Task error: {}", e))?;
+                    Ok((count, report))
                 },
                 Message::UndoComplete,
             )
         }
         Message::UndoComplete(result) => {
-            match result {
-                Ok(n) => {
+            let follow = match result {
+                Ok((n, report)) => {
                     s.status_message = format!("Undo complete. Restored {} files.", n);
                     s.toasts.success(format!("Restored {} files", n));
                     s.can_undo = false;
+                    follow_moves(s, &report)
                 }
                 Err(e) => {
                     s.status_message = format!("Undo failed: {}", e);
                     s.toasts.error("Undo failed");
+                    Task::none()
                 }
-            }
-            load_tracks_task(s.pool.clone())
+            };
+            Task::batch([load_tracks_task(s.pool.clone()), follow])
         }
         _ => Task::none(),
     }
@@ -40,13 +40,20 @@
 //! See `docs/ARCHITECTURE.md` for the full control flow diagram.
 
 use iced::Task;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
-use crate::player::{self, Player, PlayerEvent};
+use crate::enrichment::discid;
+use crate::history;
+use crate::player::silence::Silence;
+use crate::player::{self, Player, PlayerEvent, volume};
 
 use super::super::messages::Message;
-use super::super::state::{CoverArtState, LoadedState};
-use super::resolve_cover_art_task;
+use super::super::state::{
+    BufferSizeChoice, CoverArtState, CrossfadeChoice, FadeChoice, ListeningState, LoadedState,
+    PreampChoice, SeekMarker, SeekMarkerKind, VolumeStepChoice,
+};
+use super::{now_playing, resolve_cover_art_task, resume};
 
 // ============================================================================
 // Main message handler
@@ -75,11 +82,44 @@ pub fn handle_player(s: &mut LoadedState, msg: Message) -> Task<Message> {
     };
 
     let result = handle_player_inner(&mut player, s, msg);
+    let extend = extend_queue_task(&player, s);
 
     // Put the player back
     s.player = Some(player);
 
-    result
+    Task::batch([result, extend])
+}
+
+/// Pick tracks to add once the queue is on its last track, when it's set to
+/// carry on with similar tracks or library radio
+fn extend_queue_task(player: &Player, s: &mut LoadedState) -> Task<Message> {
+    let queue = player.queue();
+    let (Some(current), Some(last)) = (queue.current(), queue.items().last()) else {
+        return Task::none();
+    };
+    if !queue.wants_more() || s.queue_extended_after.as_ref() == Some(&last.path) {
+        return Task::none();
+    }
+    s.queue_extended_after = Some(last.path.clone());
+    let queued: HashSet<String> = queue
+        .items()
+        .iter()
+        .map(|item| crate::db::paths::key(&item.path.to_string_lossy()))
+        .collect();
+    let mode = queue.end_of_queue();
+    let seed = current.path.clone();
+    let pool = s.pool.clone();
+    Task::perform(
+        async move {
+            history::radio::continuation(&pool, mode, Some(&seed), &queued)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to pick tracks to carry on with: {}", e);
+                    vec![]
+                })
+        },
+        Message::QueueExtended,
+    )
 }
 
 /// Inner handler with player borrowed separately from state.
@@ -165,6 +205,131 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
             }
         }
 
+        Message::PlayerSeekTo(position) => do_seek_absolute(player, s, position),
+
+        Message::SeekMarkersLoaded(path, kind, markers) => {
+            s.seek_markers.set(&path, kind, markers);
+        }
+
+        Message::SilenceLoaded(path, silence) if s.silence_trim.track.as_ref() == Some(&path) => {
+            s.silence_trim.silence = silence;
+            let markers = silence_markers(silence, s.player_state.duration);
+            s.seek_markers.set(&path, SeekMarkerKind::Silence, markers);
+            if s.silence_trim.enabled
+                && let Some(start) = silence.and_then(|silence| silence.trimmed_start())
+                && s.player_state.position < start
+            {
+                do_seek_absolute(player, s, start);
+            }
+        }
+
+        Message::PlayerTrimSilenceToggled(enabled) => {
+            s.silence_trim.enabled = enabled;
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.audio.trim_silence = enabled;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save audio settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+
+        Message::PlayerBufferSizeChanged(BufferSizeChoice(ms)) => {
+            s.audio_buffer_ms = ms;
+            player.set_buffer_ms(ms);
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.audio.buffer_ms = ms;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save audio settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+
+        Message::PlayerFadeChanged(FadeChoice(ms)) => {
+            s.audio_fade_ms = ms;
+            player.set_fade_ms(ms);
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.audio.fade_ms = ms;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save audio settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+
+        Message::PlayerCrossfadeChanged(CrossfadeChoice(secs)) => {
+            s.audio_crossfade_secs = secs;
+            player.set_crossfade_secs(secs);
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.audio.crossfade_secs = secs;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save audio settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+
+        Message::PlayerNormalizationChanged(mode) => {
+            s.audio_normalization = mode;
+            player.set_normalization(mode, s.audio_preamp_db);
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.audio.normalization = mode;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save audio settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+
+        Message::PlayerPreampChanged(PreampChoice(db)) => {
+            s.audio_preamp_db = db;
+            player.set_normalization(s.audio_normalization, db);
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.audio.preamp_db = db;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save audio settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+
         Message::PlayerVolumeChanged(vol) => {
             tracing::debug!(
                 target: "ui::volume",
@@ -176,6 +341,30 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
             s.player_state.volume = vol;
         }
 
+        Message::PlayerVolumeStep { up } => {
+            let vol = volume::stepped(s.player_state.volume, s.volume_step_db, up);
+            tracing::debug!(target: "ui::volume", "Volume {}", volume::label(vol));
+            player.set_volume(vol);
+            s.player_state.volume = vol;
+        }
+
+        Message::PlayerVolumeStepChanged(VolumeStepChoice(db)) => {
+            s.volume_step_db = db;
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.audio.volume_step_db = db;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save audio settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+
         Message::PlayerPlayTrack(idx) => {
             return play_track_at_index(player, s, idx);
         }
@@ -188,10 +377,91 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
             }
         }
 
+        Message::PlayerPlayNext(idx) => {
+            if let Some(track) = s.tracks.get(idx) {
+                // Rows below the current track shift down
+                s.queue_multi_selection.clear();
+                player.queue_file_next(PathBuf::from(&track.path));
+                s.status_message = format!("Playing next: {}", track.title);
+            }
+        }
+
+        Message::PlayerQueueAlbum(idx) => {
+            let album = album_track_indices(s, idx);
+            for &i in &album {
+                player.queue_file(PathBuf::from(&s.tracks[i].path));
+            }
+            if let Some(track) = s.tracks.get(idx) {
+                s.status_message = format!(
+                    "Queued album: {} ({} tracks)",
+                    track.album_name,
+                    album.len()
+                );
+            }
+        }
+
+        Message::PlayerPlayAlbumNext(idx) => {
+            let album = album_track_indices(s, idx);
+            s.queue_multi_selection.clear();
+            // Each insert lands right after the current track, so go in
+            // reverse to keep the album in order
+            for &i in album.iter().rev() {
+                player.queue_file_next(PathBuf::from(&s.tracks[i].path));
+            }
+            if let Some(track) = s.tracks.get(idx) {
+                s.status_message = format!(
+                    "Playing album next: {} ({} tracks)",
+                    track.album_name,
+                    album.len()
+                );
+            }
+        }
+
+        Message::PlayerPlayFiles(paths) => {
+            let Some((first, rest)) = paths.split_first() else {
+                return Task::none();
+            };
+            if let Err(e) = player.play_file(first.clone()) {
+                s.status_message = format!("Failed to play: {}", e);
+                return Task::none();
+            }
+            s.queue_multi_selection.clear();
+            for path in rest {
+                player.queue_file(path.clone());
+            }
+            s.status_message = format!("Playing {} tracks", paths.len());
+            on_track_changed(player, s);
+        }
+
+        Message::PlayerQueueFiles(paths) => {
+            for path in &paths {
+                player.queue_file(path.clone());
+            }
+            s.status_message = format!("Queued {} tracks", paths.len());
+        }
+
         Message::PlayerShuffleRandom => {
             shuffle_random_tracks(player, s);
         }
 
+        Message::ResumeSession => {
+            resume_session(player, s);
+        }
+
+        Message::PlayRecentAlbum(i) => {
+            play_recent_album(player, s, i);
+        }
+
+        Message::PlayerStopAfterTrack => {
+            let index = player.queue().current_index();
+            toggle_stop_after(player, s, index, "this track");
+        }
+
+        Message::PlayerStopAfterAlbum => {
+            let index = player.queue().album_end_index();
+            toggle_stop_after(player, s, index, "this album");
+        }
+
         Message::PlayerTick => {
             // Increment animation tick for spinners and other subtle animations
             s.animation_tick = s.animation_tick.wrapping_add(1);
@@ -199,14 +469,22 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
             // Check if diagnostics animation is complete and we have pending results
             if s.diagnostics_pending.is_some() {
                 let elapsed = s.animation_tick.wrapping_sub(s.diagnostics_started_tick);
-                // 7 phases × 90 ticks each = 630 ticks minimum
-                if elapsed >= 630 {
+                // 8 phases × 90 ticks each = 720 ticks minimum
+                if elapsed >= 720 {
                     // Commit pending results to final state
                     s.diagnostics = s.diagnostics_pending.take();
                     s.diagnostics_loading = false;
                 }
             }
 
+            // Performance overlay: backlogs before the events are taken
+            if s.perf.open
+                && let Some(stats) = player.performance_stats()
+            {
+                s.perf
+                    .tick(&stats, player.channel_backlogs(), std::time::Instant::now());
+            }
+
             // === PHASE 1: Poll events from audio thread ===
             let events = player.poll_events();
             let event_count = events.len();
@@ -264,6 +542,15 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
 
             s.player_state = real_state;
             auto_queue_if_needed(player, s);
+            crate::enrichment::budget::CpuBudget::global()
+                .set_playing(s.player_state.status == crate::player::PlaybackStatus::Playing);
+
+            // Keep the saved session current (~every 10s at 60 ticks/s)
+            if s.player_state.status == crate::player::PlaybackStatus::Playing
+                && s.animation_tick.is_multiple_of(600)
+            {
+                tasks.push(resume::save_session_task(player, s));
+            }
 
             // === PHASE 3: Update visualization if playing ===
             if s.player_state.status == crate::player::PlaybackStatus::Playing
@@ -272,10 +559,19 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
                 s.visualization = viz;
             }
 
+            // Level meters: always take the levels so peaks don't pile up
+            let levels = player.take_levels();
+            match levels {
+                Some(levels) if s.player_state.status == crate::player::PlaybackStatus::Playing => {
+                    s.level_meter.update(levels, std::time::Instant::now());
+                }
+                _ => s.level_meter.idle(),
+            }
+
             // === PHASE 4: Poll media controls ===
             // IMPORTANT: Process commands directly here, NOT via handle_player()
             // to avoid re-entrancy issues (player is already borrowed)
-            let commands: Vec<_> = s
+            let mut commands: Vec<_> = s
                 .media_controls
                 .as_ref()
                 .map(|mc| {
@@ -287,6 +583,23 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
                 })
                 .unwrap_or_default();
 
+            // Fallback media keys, minus the presses the OS sent as well
+            if let Some(keys) = &mut s.media_keys {
+                for cmd in &commands {
+                    keys.os_command(cmd);
+                }
+                commands.extend(keys.take_commands());
+                if keys.take_conflict() {
+                    tracing::info!(
+                        target: "ui::media_control",
+                        "OS media controls deliver media keys; ignoring the listener's copies"
+                    );
+                    s.toasts.info(
+                        "Media keys work through the OS media controls, so the fallback listener (audio.media_key_fallback) isn't needed",
+                    );
+                }
+            }
+
             // Process commands directly using the already-borrowed player
             for cmd in commands {
                 tracing::debug!(target: "ui::media_control", command = ?cmd, "Processing media control");
@@ -368,15 +681,28 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
 
         Message::QueueRemove(idx) => {
             tracing::debug!(target: "ui::queue", index = idx, "Removing from queue");
+            // Indices after the removed row shift, so drop any multi-selection
+            s.queue_multi_selection.clear();
             if let Some(removed) = player.queue_mut().remove(idx) {
                 let name = removed.display_title();
                 s.status_message = format!("Removed: {}", name);
             }
         }
 
+        Message::QueueToggleStopAfter(idx) => {
+            let name = player
+                .queue()
+                .items()
+                .get(idx)
+                .map(|item| item.display_title())
+                .unwrap_or_default();
+            toggle_stop_after(player, s, Some(idx), &name);
+        }
+
         Message::QueueClear => {
             tracing::debug!(target: "ui::queue", "Clearing queue");
             player.queue_mut().clear();
+            s.queue_multi_selection.clear();
             if let Err(e) = player.stop() {
                 s.status_message = format!("Stop error: {}", e);
             } else {
@@ -392,6 +718,12 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
             tracing::debug!(target: "ui::queue", shuffle = !current, "Toggled shuffle");
         }
 
+        Message::QueueSetShuffleMode(mode) => {
+            player.queue_mut().set_shuffle_mode(mode);
+            s.status_message = format!("Shuffle mode: {}", mode);
+            tracing::debug!(target: "ui::queue", mode = ?mode, "Changed shuffle mode");
+        }
+
         Message::QueueCycleRepeat => {
             player.queue_mut().cycle_repeat();
             let mode = player.queue().repeat();
@@ -404,6 +736,49 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
             tracing::debug!(target: "ui::queue", mode = ?mode, "Cycled repeat mode");
         }
 
+        Message::QueueSetEndOfQueue(end) => {
+            s.end_of_queue = end;
+            s.queue_extended_after = None;
+            player.queue_mut().set_end_of_queue(end);
+            s.status_message = format!("End of queue: {}", end);
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.audio.end_of_queue = end;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save audio settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+
+        Message::QueueExtended(paths) => {
+            let end = player.queue().end_of_queue();
+            if !end.adds_tracks() {
+                return Task::none();
+            }
+            if paths.is_empty() {
+                s.status_message = "No more tracks to carry on with".to_string();
+            } else {
+                s.status_message = format!(
+                    "Added {} {}",
+                    paths.len(),
+                    if end == player::EndOfQueue::Similar {
+                        "similar tracks"
+                    } else {
+                        "tracks from library radio"
+                    }
+                );
+                for path in paths {
+                    player.queue_file(path);
+                }
+            }
+        }
+
         _ => {}
     }
     Task::none()
@@ -419,13 +794,26 @@ fn handle_player_inner(player: &mut Player, s: &mut LoadedState, msg: Message) -
 /// Events arrive in order, so rapid button mashing resolves deterministically.
 ///
 /// Returns a Task if the event requires async follow-up (e.g., cover art resolution).
-fn handle_player_event(event: PlayerEvent, _player: &Player, s: &mut LoadedState) -> Task<Message> {
+fn handle_player_event(
+    event: PlayerEvent,
+    player: &mut Player,
+    s: &mut LoadedState,
+) -> Task<Message> {
     match event {
         PlayerEvent::StatusChanged(status) => {
             tracing::debug!(target: "ui::events", "Received StatusChanged: {:?} -> {:?}", s.player_state.status, status);
             s.player_state.status = status;
             update_smtc_playback_state(s);
-            Task::none()
+            match status {
+                crate::player::PlaybackStatus::Paused | crate::player::PlaybackStatus::Stopped => {
+                    s.listening.clock.pause();
+                    Task::batch([
+                        resume::save_session_task(player, s),
+                        resume::record_listened_task(s.pool.clone(), s.listening.snapshot()),
+                    ])
+                }
+                _ => Task::none(),
+            }
         }
 
         PlayerEvent::TrackLoaded {
@@ -445,9 +833,34 @@ fn handle_player_event(event: PlayerEvent, _player: &Player, s: &mut LoadedState
             s.player_state.channels = channels;
             s.player_state.bits_per_sample = bits_per_sample;
             s.player_state.quality = quality;
+            s.level_meter.track_changed();
+            // For files without loudness tags
+            if let Some(track) = s.current_track_info() {
+                player.set_stored_loudness(
+                    path.clone(),
+                    crate::metadata::Loudness {
+                        track_gain: track.track_gain,
+                        track_peak: track.track_peak,
+                        ..Default::default()
+                    },
+                );
+            }
+            s.silence_trim.track = Some(path.clone());
+            s.silence_trim.silence = None;
+            s.silence_trim.end_reached = false;
+
+            // Start counting this play's listening time
+            let previous = s.listening.snapshot();
+            let skipped = s.listening.is_skip();
+            s.listening = ListeningState {
+                track: Some(path.clone()),
+                genre: file_metadata.genre.clone(),
+                duration,
+                ..Default::default()
+            };
 
             // Store file metadata for fallback when track not in DB
-            s.file_metadata = Some(file_metadata);
+            s.file_metadata = Some(*file_metadata);
 
             // Sync metadata to OS media controls
             sync_metadata(s);
@@ -459,20 +872,46 @@ fn handle_player_event(event: PlayerEvent, _player: &Player, s: &mut LoadedState
                 loading: true,
                 error: None,
             };
-            resolve_cover_art_task(path, None)
+            Task::batch([
+                resume::record_play_task(s.pool.clone(), previous, skipped, path.clone()),
+                resume::save_session_task(player, s),
+                resolve_cover_art_task(path.clone(), None),
+                now_playing::lyrics_task(path.clone()),
+                silence_task(s.pool.clone(), path.clone()),
+                cue_chapters_task(path),
+            ])
         }
 
         PlayerEvent::PositionChanged(position) => {
+            player.sync_next();
             s.player_state.position = position;
+            s.listening.position = position;
+            if s.player_state.status == crate::player::PlaybackStatus::Playing {
+                s.listening.clock.advance(position);
+            }
+            if reached_trailing_silence(player, s) {
+                tracing::debug!(target: "ui::events", "Trailing silence reached, skipping");
+                s.silence_trim.end_reached = true;
+                finish_track(player, s);
+            }
             Task::none()
         }
 
         PlayerEvent::PlaybackFinished => {
             tracing::debug!(target: "ui::events", "Received PlaybackFinished");
+            finish_track(player, s);
             // Auto-queue next track if needed (handled in PlayerTick)
             Task::none()
         }
 
+        PlayerEvent::CrossfadeStarted(path) => {
+            tracing::debug!(target: "ui::events", "Received CrossfadeStarted: {:?}", path.file_name());
+            if player.advance_after_crossfade(&path) {
+                on_track_changed(player, s);
+            }
+            Task::none()
+        }
+
         PlayerEvent::Error(err) => {
             tracing::warn!(target: "ui::events", "Received Error: {}", err);
             s.status_message = format!("Player error: {}", err);
@@ -481,6 +920,121 @@ fn handle_player_event(event: PlayerEvent, _player: &Player, s: &mut LoadedState
     }
 }
 
+/// Move on once a track has played to the end
+fn finish_track(player: &mut Player, s: &mut LoadedState) {
+    let stopping = player.queue().current().filter(|item| item.stop_after);
+    let stopping = stopping.map(|item| item.display_title());
+    match player.advance_after_finish() {
+        Ok(true) => on_track_changed(player, s),
+        Ok(false) => {
+            on_track_changed(player, s);
+            if let Some(title) = stopping {
+                s.status_message = format!("Stopped after {}", title);
+            }
+        }
+        Err(e) => s.status_message = format!("Next error: {}", e),
+    }
+}
+
+/// Whether "trim silence" should end the track now. Only when another
+/// track follows: stopping (end of queue, stop-after marks, repeat one) is
+/// left to the real end of the file.
+fn reached_trailing_silence(player: &Player, s: &LoadedState) -> bool {
+    let trim = &s.silence_trim;
+    let queue = player.queue();
+    trim.enabled
+        && !trim.end_reached
+        && trim.track == s.player_state.current_track
+        && trim
+            .silence
+            .and_then(|silence| silence.trimmed_end(s.player_state.duration))
+            .is_some_and(|end| s.player_state.position >= end)
+        && !queue.current().is_some_and(|item| item.stop_after)
+        && queue.repeat() != player::RepeatMode::One
+        && (!queue.upcoming().is_empty() || queue.wraps())
+}
+
+/// Load the stored silence of a track that started playing
+fn silence_task(pool: sqlx::SqlitePool, path: PathBuf) -> Task<Message> {
+    Task::perform(
+        async move {
+            let silence = crate::db::get_track_silence(&pool, &path.to_string_lossy())
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::debug!("Failed to load silence of {}: {}", path.display(), e);
+                    None
+                });
+            (path, silence)
+        },
+        |(path, silence)| Message::SilenceLoaded(path, silence),
+    )
+}
+
+/// Where the sound starts and stops, when the silence is long enough to trim
+fn silence_markers(silence: Option<Silence>, duration: std::time::Duration) -> Vec<SeekMarker> {
+    let Some(silence) = silence else {
+        return Vec::new();
+    };
+    let start = silence.trimmed_start().map(|position| SeekMarker {
+        kind: SeekMarkerKind::Silence,
+        position,
+        label: format!("Sound starts ({}s of silence)", silence.leading.as_secs()),
+    });
+    let end = silence.trimmed_end(duration).map(|position| SeekMarker {
+        kind: SeekMarkerKind::Silence,
+        position,
+        label: format!("Silence to the end ({}s)", silence.trailing.as_secs()),
+    });
+    start.into_iter().chain(end).collect()
+}
+
+/// Mark the tracks of a single-file image on the seek bar, from the cue
+/// sheet next to it (same name first, then any sheet naming the file)
+fn cue_chapters_task(path: PathBuf) -> Task<Message> {
+    Task::perform(
+        async move {
+            let read = path.clone();
+            let markers = tokio::task::spawn_blocking(move || cue_chapter_markers(&read))
+                .await
+                .unwrap_or_default();
+            (path, markers)
+        },
+        |(path, markers)| Message::SeekMarkersLoaded(path, SeekMarkerKind::Chapter, markers),
+    )
+}
+
+fn cue_chapter_markers(path: &Path) -> Vec<SeekMarker> {
+    let (Some(folder), Some(file_name)) = (path.parent(), path.file_name()) else {
+        return Vec::new();
+    };
+    let file_name = file_name.to_string_lossy();
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let mut sheets: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("cue")))
+        .collect();
+    sheets.sort_by_key(|p| p.file_stem() != path.file_stem());
+
+    sheets
+        .iter()
+        .filter_map(|sheet| std::fs::read_to_string(sheet).ok())
+        .map(|text| discid::cue_chapters(&text, &file_name))
+        .find(|chapters| !chapters.is_empty())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|chapter| SeekMarker {
+            kind: SeekMarkerKind::Chapter,
+            position: chapter.start,
+            label: match chapter.title {
+                Some(title) => format!("{}. {}", chapter.number, title),
+                None => format!("Track {}", chapter.number),
+            },
+        })
+        .collect()
+}
+
 // ============================================================================
 // Internal helper functions - each action sends a command (no optimistic updates)
 // ============================================================================
@@ -581,6 +1135,24 @@ fn do_seek_relative(player: &mut Player, s: &mut LoadedState, direction: souvlak
     do_seek(player, s, new_pos.clamp(0.0, 1.0));
 }
 
+/// Set or clear the stop-after mark on queue item `index`.
+///
+/// Pressing again on the already-marked item clears it.
+fn toggle_stop_after(player: &mut Player, s: &mut LoadedState, index: Option<usize>, what: &str) {
+    let Some(index) = index else {
+        s.status_message = "Nothing is playing".to_string();
+        return;
+    };
+    if player.queue().stop_after_index() == Some(index) {
+        player.queue_mut().set_stop_after(None);
+        s.status_message = "Playback will continue".to_string();
+    } else {
+        player.queue_mut().set_stop_after(Some(index));
+        s.status_message = format!("Playback will stop after {}", what);
+    }
+    tracing::debug!(target: "ui::queue", index, "Toggled stop-after mark");
+}
+
 /// Called after skip operations to sync queue state for metadata.
 /// The actual state update comes via TrackLoaded event.
 fn on_track_changed(player: &Player, s: &mut LoadedState) {
@@ -641,8 +1213,8 @@ fn send_track_to_smtc(mc: &player::MediaControlsHandle, track: &crate::db::Track
         .map(|d| std::time::Duration::from_secs(d as u64))
         .unwrap_or_default();
     let meta = player::MediaControlsMetadata::with_title(&track.title)
-        .artist(&track.artist_name)
-        .album(&track.album_name)
+        .artist(track.artist_name.as_str())
+        .album(track.album_name.as_str())
         .duration(duration);
     tracing::info!(
         "Sending SMTC metadata: {} - {}",
@@ -656,16 +1228,29 @@ fn send_track_to_smtc(mc: &player::MediaControlsHandle, track: &crate::db::Track
 // Complex operations
 // ============================================================================
 
+/// Library indices random picks are drawn from: every track, less the
+/// often skipped ones while "Hide skipped" is on
+fn random_candidates(s: &LoadedState) -> Vec<usize> {
+    (0..s.tracks.len())
+        .filter(|&i| {
+            s.filter_hide_skipped
+                .as_ref()
+                .is_none_or(|ids| !ids.contains(&s.tracks[i].id))
+        })
+        .collect()
+}
+
 /// Shuffle and play random tracks (clears current queue).
 fn shuffle_random_tracks(player: &mut Player, s: &mut LoadedState) {
     use rand::seq::SliceRandom;
     let mut rng = rand::rng();
 
-    let mut indices: Vec<usize> = (0..s.tracks.len()).collect();
+    let mut indices = random_candidates(s);
     indices.shuffle(&mut rng);
     let count = 25.min(indices.len());
 
     player.queue_mut().clear();
+    s.queue_multi_selection.clear();
     for &idx in indices.iter().take(count) {
         if let Some(track) = s.tracks.get(idx) {
             player.queue_file(PathBuf::from(&track.path));
@@ -681,6 +1266,97 @@ fn shuffle_random_tracks(player: &mut Player, s: &mut LoadedState) {
     }
 }
 
+/// Indices of the library tracks on the same album as track `idx`, in album order.
+///
+/// Tracks without an album tag only match themselves, so "Unknown Album"
+/// doesn't sweep up half the library.
+pub(crate) fn album_track_indices(s: &LoadedState, idx: usize) -> Vec<usize> {
+    let Some(track) = s.tracks.get(idx) else {
+        return Vec::new();
+    };
+    if track.album_name.is_empty() || track.album_name == "Unknown Album" {
+        return vec![idx];
+    }
+    album_tracks(s, &track.album_name, &track.artist_name)
+}
+
+/// Indices of the library tracks on an album, in album order.
+fn album_tracks(s: &LoadedState, album: &str, artist: &str) -> Vec<usize> {
+    let mut album: Vec<usize> = s
+        .tracks
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.album_name == album && t.artist_name == artist)
+        .map(|(i, _)| i)
+        .collect();
+    album.sort_by(|&a, &b| {
+        let (a, b) = (&s.tracks[a], &s.tracks[b]);
+        a.track_number
+            .unwrap_or(u32::MAX)
+            .cmp(&b.track_number.unwrap_or(u32::MAX))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    album
+}
+
+/// Restore the queue saved last time and continue from the saved position.
+fn resume_session(player: &mut Player, s: &mut LoadedState) {
+    let Some(session) = s.resume.session.take() else {
+        return;
+    };
+    let Some(current) = session.current else {
+        return;
+    };
+
+    player.queue_mut().clear();
+    s.queue_multi_selection.clear();
+    for path in &session.queue {
+        player.queue_file(path.clone());
+    }
+    player.queue_mut().jump_to(current);
+    if let Err(e) = player.load_and_play_current() {
+        s.status_message = format!("Failed to resume: {}", e);
+        return;
+    }
+    // Commands run in order, so this seeks the track that was just loaded
+    if let Some(fraction) = session.position_fraction()
+        && let Err(e) = player.seek(fraction)
+    {
+        tracing::warn!("Failed to restore position: {}", e);
+    }
+
+    s.resume.dismissed = true;
+    s.status_message = format!(
+        "Resumed at {} ({} tracks queued)",
+        crate::player::format_duration_secs(session.position_secs as f32),
+        session.queue.len()
+    );
+    on_track_changed(player, s);
+}
+
+/// Play an album from the resume card's recently played list.
+fn play_recent_album(player: &mut Player, s: &mut LoadedState, i: usize) {
+    let Some(recent) = s.resume.recent_albums.get(i).cloned() else {
+        return;
+    };
+    let album = album_tracks(s, &recent.album, &recent.artist);
+    let Some((&first, rest)) = album.split_first() else {
+        s.status_message = format!("{} is no longer in the library", recent.album);
+        return;
+    };
+
+    if let Err(e) = player.play_file(PathBuf::from(&s.tracks[first].path)) {
+        s.status_message = format!("Failed to play: {}", e);
+        return;
+    }
+    s.queue_multi_selection.clear();
+    for &idx in rest {
+        player.queue_file(PathBuf::from(&s.tracks[idx].path));
+    }
+    s.status_message = format!("Playing {} ({} tracks)", recent.album, album.len());
+    on_track_changed(player, s);
+}
+
 /// Play a specific track by index and queue more from same artist.
 fn play_track_at_index(player: &mut Player, s: &mut LoadedState, idx: usize) -> Task<Message> {
     let Some(track) = s.tracks.get(idx) else {
@@ -735,7 +1411,7 @@ fn auto_queue_if_needed(player: &mut Player, s: &mut LoadedState) {
     use rand::seq::SliceRandom;
     let mut rng = rand::rng();
 
-    let mut indices: Vec<usize> = (0..s.tracks.len()).collect();
+    let mut indices = random_candidates(s);
     indices.shuffle(&mut rng);
     let add_count = 8.min(indices.len());
 
@@ -0,0 +1,206 @@
+//! Playlists pane handlers: creating, renaming, reordering and playing
+//! playlists, and adding tracks to them from the library and queue.
+
+use iced::Task;
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+
+use crate::db::playlists;
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+
+/// Load every playlist
+pub(crate) fn load_playlists_task(pool: SqlitePool) -> Task<Message> {
+    Task::perform(
+        async move { playlists::list(&pool).await.map_err(|e| e.to_string()) },
+        Message::PlaylistsLoaded,
+    )
+}
+
+/// Load the tracks of a playlist
+fn load_tracks_task(pool: SqlitePool, playlist_id: i64) -> Task<Message> {
+    Task::perform(
+        async move {
+            playlists::tracks(&pool, playlist_id)
+                .await
+                .map_err(|e| e.to_string())
+        },
+        move |result| Message::PlaylistTracksLoaded(playlist_id, result),
+    )
+}
+
+/// Save an edit, then reload; the edit returns the playlist to show if
+/// that changes
+fn edit_task<F>(edit: F) -> Task<Message>
+where
+    F: Future<Output = sqlx::Result<Option<i64>>> + Send + 'static,
+{
+    Task::perform(edit, |result| {
+        Message::PlaylistChanged(result.map_err(|e| e.to_string()))
+    })
+}
+
+/// Files of a playlist's tracks, in order
+fn paths(tracks: &[playlists::PlaylistTrack]) -> Vec<PathBuf> {
+    tracks.iter().map(|t| PathBuf::from(&t.path)).collect()
+}
+
+/// Handle playlists pane messages
+pub fn handle_playlists(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    let pool = s.pool.clone();
+    let state = &mut s.playlists;
+    match msg {
+        Message::PlaylistsLoaded(result) => match result {
+            Ok(list) => {
+                state.playlists = list;
+                // The shown playlist was deleted
+                if state.current().is_none() {
+                    state.selected = None;
+                    state.tracks.clear();
+                    state.renaming = None;
+                }
+            }
+            Err(e) => s.toasts.error(format!("Failed to load playlists: {}", e)),
+        },
+        Message::PlaylistSelect(id) => {
+            if state.selected != Some(id) {
+                state.selected = Some(id);
+                state.tracks.clear();
+                state.renaming = None;
+            }
+            return load_tracks_task(pool, id);
+        }
+        Message::PlaylistTracksLoaded(id, result) => match result {
+            // A slow load for a playlist no longer shown
+            _ if state.selected != Some(id) => {}
+            Ok(tracks) => state.tracks = tracks,
+            Err(e) => s.toasts.error(format!("Failed to load playlist: {}", e)),
+        },
+        Message::PlaylistNewNameChanged(name) => {
+            state.new_name = name;
+        }
+        Message::PlaylistCreate => {
+            let name = state.new_name.trim().to_string();
+            if name.is_empty() {
+                return Task::none();
+            }
+            if state.name_taken(&name, None) {
+                s.toasts
+                    .warning(format!("There's already a playlist named {}", name));
+                return Task::none();
+            }
+            state.new_name.clear();
+            return edit_task(async move { playlists::create(&pool, &name).await.map(Some) });
+        }
+        Message::PlaylistCreateWith(track_ids) => {
+            let name = state.unused_name();
+            s.toasts
+                .success(format!("Added {} track(s) to {}", track_ids.len(), name));
+            return edit_task(async move {
+                let id = playlists::create(&pool, &name).await?;
+                playlists::add_tracks(&pool, id, &track_ids).await?;
+                Ok(Some(id))
+            });
+        }
+        Message::PlaylistRenameStart => {
+            state.renaming = state.current().map(|p| p.name.clone());
+        }
+        Message::PlaylistRenameChanged(name) if state.renaming.is_some() => {
+            state.renaming = Some(name);
+        }
+        Message::PlaylistRenameCancel => {
+            state.renaming = None;
+        }
+        Message::PlaylistRenameSubmit => {
+            let (Some(id), Some(name)) = (state.selected, state.renaming.take()) else {
+                return Task::none();
+            };
+            let name = name.trim().to_string();
+            if name.is_empty() || state.current().is_some_and(|p| p.name == name) {
+                return Task::none();
+            }
+            if state.name_taken(&name, Some(id)) {
+                s.toasts
+                    .warning(format!("There's already a playlist named {}", name));
+                state.renaming = Some(name);
+                return Task::none();
+            }
+            return edit_task(
+                async move { playlists::rename(&pool, id, &name).await.map(|_| None) },
+            );
+        }
+        Message::PlaylistDelete(id) => {
+            if let Some(playlist) = state.playlists.iter().find(|p| p.id == id) {
+                s.toasts.info(format!("Deleted {}", playlist.name));
+            }
+            return edit_task(async move { playlists::delete(&pool, id).await.map(|_| None) });
+        }
+        Message::PlaylistAddTracks(id, track_ids) => {
+            if let Some(playlist) = state.playlists.iter().find(|p| p.id == id) {
+                s.toasts.success(format!(
+                    "Added {} track(s) to {}",
+                    track_ids.len(),
+                    playlist.name
+                ));
+            }
+            return edit_task(async move {
+                playlists::add_tracks(&pool, id, &track_ids)
+                    .await
+                    .map(|_| None)
+            });
+        }
+        Message::PlaylistRemoveTrack(pos) => {
+            let Some(id) = state.selected else {
+                return Task::none();
+            };
+            if pos < state.tracks.len() {
+                state.tracks.remove(pos);
+            }
+            return edit_task(
+                async move { playlists::remove(&pool, id, &[pos]).await.map(|_| None) },
+            );
+        }
+        Message::PlaylistMoveTrack(from, to) => {
+            let Some(id) = state.selected else {
+                return Task::none();
+            };
+            // Shown moved straight away; the reload after saving confirms it
+            if from < state.tracks.len() && to < state.tracks.len() {
+                let track = state.tracks.remove(from);
+                state.tracks.insert(to, track);
+            }
+            return edit_task(async move {
+                playlists::move_track(&pool, id, from, to)
+                    .await
+                    .map(|_| None)
+            });
+        }
+        Message::PlaylistPlay if !state.tracks.is_empty() => {
+            return Task::done(Message::PlayerPlayFiles(paths(&state.tracks)));
+        }
+        Message::PlaylistQueue if !state.tracks.is_empty() => {
+            return Task::done(Message::PlayerQueueFiles(paths(&state.tracks)));
+        }
+        Message::PlaylistChanged(result) => {
+            match result {
+                Ok(Some(id)) => {
+                    if state.selected != Some(id) {
+                        state.tracks.clear();
+                    }
+                    state.selected = Some(id);
+                    state.renaming = None;
+                }
+                Ok(None) => {}
+                Err(e) => s.toasts.error(format!("Failed to save playlist: {}", e)),
+            }
+            let mut tasks = vec![load_playlists_task(pool.clone())];
+            if let Some(id) = s.playlists.selected {
+                tasks.push(load_tracks_task(pool, id));
+            }
+            return Task::batch(tasks);
+        }
+        _ => {}
+    }
+    Task::none()
+}
@@ -0,0 +1,82 @@
+//! ReplayGain batch scans started from the library pane.
+
+use std::collections::HashSet;
+
+use iced::Task;
+
+use crate::library::replaygain::{self, Scope};
+use crate::tasks::TaskKind;
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+use super::load_tracks_task;
+
+/// Handle ReplayGain scan messages
+pub fn handle_replaygain(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::ReplayGainScanShown => {
+            let scope = if s.filtered_indices.is_empty() && s.search_query.is_empty() {
+                Scope::Library
+            } else {
+                Scope::Tracks(track_ids(s, &s.filtered_indices))
+            };
+            return start(s, scope);
+        }
+        Message::ReplayGainScan(indices) => {
+            let scope = Scope::Tracks(track_ids(s, &indices));
+            return start(s, scope);
+        }
+        Message::ReplayGainComplete(result) => {
+            s.replaygain_running = false;
+            match result {
+                Ok(report) => {
+                    for (path, e) in &report.failed {
+                        tracing::warn!("ReplayGain for {}: {}", path, e);
+                    }
+                    s.status_message = report.summary();
+                    if report.failed.is_empty() {
+                        s.toasts.success(report.summary());
+                    } else {
+                        s.toasts.warning(report.summary());
+                    }
+                    return load_tracks_task(s.pool.clone());
+                }
+                Err(e) => {
+                    s.status_message = format!("ReplayGain scan failed: {}", e);
+                    s.toasts.error("ReplayGain scan failed");
+                }
+            }
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+fn track_ids(s: &LoadedState, indices: &[usize]) -> HashSet<i64> {
+    indices
+        .iter()
+        .filter_map(|&i| s.tracks.get(i))
+        .map(|t| t.id)
+        .collect()
+}
+
+fn start(s: &mut LoadedState, scope: Scope) -> Task<Message> {
+    if s.replaygain_running {
+        s.toasts.info("A ReplayGain scan is already running");
+        return Task::none();
+    }
+    if matches!(&scope, Scope::Tracks(ids) if ids.is_empty()) {
+        return Task::none();
+    }
+    s.replaygain_running = true;
+    let task = s.tasks.start(TaskKind::Maintenance, "Measure ReplayGain");
+    let pool = s.pool.clone();
+    Task::perform(
+        async move {
+            let result = replaygain::scan(&pool, &scope, false, &task).await;
+            task.finish();
+            result.map_err(|e| e.to_string())
+        },
+        Message::ReplayGainComplete,
+    )
+}
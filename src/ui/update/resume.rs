@@ -0,0 +1,120 @@
+//! "Pick up where you left off": playback history and the saved session.
+
+use iced::Task;
+use sqlx::SqlitePool;
+use std::path::PathBuf;
+
+use super::super::messages::Message;
+use super::super::state::{LoadedState, ResumeState};
+use crate::history::{self, LastSession};
+use crate::player::Player;
+
+/// Handle resume card messages that don't touch the player
+pub fn handle_resume(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::RecentAlbumsLoaded(Ok(albums)) => {
+            s.resume.recent_albums = albums;
+        }
+        Message::RecentAlbumsLoaded(Err(e)) => {
+            tracing::warn!("Failed to load recently played albums: {}", e);
+        }
+        Message::ResumeDismiss => {
+            s.resume.dismissed = true;
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+/// Load the albums shown on the resume card
+pub(crate) fn recent_albums_task(pool: SqlitePool) -> Task<Message> {
+    Task::perform(
+        async move {
+            history::recent_albums(&pool, ResumeState::MAX_ALBUMS)
+                .await
+                .map_err(|e| e.to_string())
+        },
+        Message::RecentAlbumsLoaded,
+    )
+}
+
+/// The previous track's listening time so far: (path, listened, genre)
+pub(crate) type Listened = (PathBuf, std::time::Duration, Option<String>);
+
+/// Store how long the previous track was listened to (and whether it was
+/// skipped), then add a play to the history (best effort). In that order,
+/// so a repeated track's time goes on its previous play.
+pub(crate) fn record_play_task(
+    pool: SqlitePool,
+    previous: Option<Listened>,
+    skipped: bool,
+    path: PathBuf,
+) -> Task<Message> {
+    Task::perform(
+        async move {
+            if let Some((previous, listened, genre)) = previous {
+                history::record_listened(&pool, &previous, listened, genre.as_deref()).await?;
+                if skipped {
+                    history::record_skip(&pool, &previous).await?;
+                }
+            }
+            history::record_play(&pool, &path).await
+        },
+        |result| {
+            if let Err(e) = result {
+                tracing::warn!("Failed to record play: {}", e);
+            }
+            Message::Noop
+        },
+    )
+}
+
+/// Store how long the current track has been listened to (best effort)
+pub(crate) fn record_listened_task(pool: SqlitePool, listened: Option<Listened>) -> Task<Message> {
+    let Some((path, listened, genre)) = listened else {
+        return Task::none();
+    };
+    Task::perform(
+        async move { history::record_listened(&pool, &path, listened, genre.as_deref()).await },
+        |result| {
+            if let Err(e) = result {
+                tracing::warn!("Failed to record listening time: {}", e);
+            }
+            Message::Noop
+        },
+    )
+}
+
+/// The current queue and position, unless the queue is empty
+pub(crate) fn last_session(player: &Player, s: &LoadedState) -> Option<LastSession> {
+    let queue = player.queue();
+    if queue.is_empty() {
+        return None;
+    }
+    Some(LastSession {
+        queue: queue.items().iter().map(|item| item.path.clone()).collect(),
+        current: queue.current_index(),
+        position_secs: s.player_state.position.as_secs_f64(),
+        duration_secs: s.player_state.duration.as_secs_f64(),
+        saved_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// Save the current queue and position so the next launch can resume.
+///
+/// An empty queue is never saved, so clearing it doesn't throw away the
+/// last session that was worth resuming.
+pub(crate) fn save_session_task(player: &Player, s: &LoadedState) -> Task<Message> {
+    let Some(session) = last_session(player, s) else {
+        return Task::none();
+    };
+    Task::perform(
+        async move { tokio::task::spawn_blocking(move || session.save()).await },
+        |result| {
+            if let Ok(Err(e)) = result {
+                tracing::warn!("Failed to save session: {}", e);
+            }
+            Message::Noop
+        },
+    )
+}
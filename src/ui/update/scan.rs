@@ -2,29 +2,30 @@
 
 use iced::Task;
 
-use crate::library;
+use crate::tasks::TaskKind;
+use crate::{config, library};
 
 use super::super::messages::Message;
 use super::super::state::LoadedState;
-use super::load_tracks_task;
+use super::{load_tracks_task, save_input_history_task};
 
 /// Handle scan-related messages
 pub fn handle_scan(s: &mut LoadedState, msg: &Message) -> Task<Message> {
     match msg {
         Message::ScanPressed => {
-            s.is_scanning = true;
-            s.scan_count = 0;
+            begin_scan(s);
             s.status_message = "Scanning...".to_string();
-            Task::none()
+            config::remember(&mut s.input_history.scan_paths, s.scan_path.clone());
+            save_input_history_task(s.input_history.clone())
         }
         Message::ScanStopped => {
-            s.is_scanning = false;
+            end_scan(s);
             s.status_message = "Scan stopped by user.".to_string();
             s.toasts.warning("Scan stopped");
             load_tracks_task(s.pool.clone())
         }
         Message::ScanFinished => {
-            s.is_scanning = false;
+            end_scan(s);
             s.status_message = format!("Scan Complete. Processed {} files.", s.scan_count);
             s.toasts
                 .success(format!("Scan complete: {} files", s.scan_count));
@@ -41,11 +42,62 @@ pub fn handle_scan(s: &mut LoadedState, msg: &Message) -> Task<Message> {
                     );
                 }
                 library::ScanEvent::Error(path, err) => {
-                    s.status_message = format!("Error scanning {:?}: {}", path, err);
+                    s.status_message = match err.class {
+                        Some(class) => {
+                            format!("Error scanning {:?} ({}): {}", path, class.label(), err)
+                        }
+                        None => format!("Error scanning {:?}: {}", path, err),
+                    };
+                }
+                library::ScanEvent::CompilationsGrouped(n) => {
+                    s.toasts.info(format!(
+                        "Grouped {} compilation album{} under {}",
+                        n,
+                        if *n == 1 { "" } else { "s" },
+                        library::VARIOUS_ARTISTS
+                    ));
                 }
             }
             Task::none()
         }
+        Message::RatingSourceChanged(choice) => {
+            s.popm_email = choice.0.clone();
+            let email = choice.0.clone();
+            Task::perform(
+                async move {
+                    let mut cfg = config::load();
+                    cfg.library.popm_email = email;
+                    config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save library settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            )
+        }
         _ => Task::none(),
     }
 }
+
+/// Start scanning `s.scan_path`; the scan subscription runs while
+/// `is_scanning` is set
+pub(super) fn begin_scan(s: &mut LoadedState) {
+    if let Some(old) = s.scan_task.take() {
+        old.finish();
+    }
+    s.is_scanning = true;
+    s.scan_count = 0;
+    s.scan_task = Some(
+        s.tasks
+            .start(TaskKind::Scan, format!("Scan {}", s.scan_path.display())),
+    );
+}
+
+fn end_scan(s: &mut LoadedState) {
+    s.is_scanning = false;
+    if let Some(task) = s.scan_task.take() {
+        task.finish();
+    }
+}
@@ -0,0 +1,127 @@
+//! Scheduled maintenance handlers.
+//!
+//! A once-a-minute tick asks [`scheduler::due_jobs`] what should run and
+//! starts the first due job; only one runs at a time, and none start while a
+//! scan or organize is in progress.
+
+use iced::Task;
+use sqlx::SqlitePool;
+
+use super::super::messages::Message;
+use super::super::state::{LoadedState, OrganizeView};
+use super::load_tracks_task;
+use crate::config;
+use crate::scheduler::{self, Job};
+use crate::tasks::TaskKind;
+
+/// Handle scheduler messages
+pub fn handle_scheduler(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::SchedulerTick => {
+            if s.scheduler.running.is_some() || busy(s) {
+                return Task::none();
+            }
+            let now = chrono::Local::now().naive_local();
+            let idle = s.scheduler.is_idle();
+            let sched = &s.scheduler;
+            if let Some(job) =
+                scheduler::due_jobs(&sched.config, &sched.runs, sched.started, now, idle)
+                    .into_iter()
+                    .next()
+            {
+                return start_job(s, job, false);
+            }
+        }
+        Message::SchedulerRunNow(job) => {
+            if s.scheduler.running.is_some() {
+                s.toasts.warning("Another maintenance job is running");
+            } else if busy(s) {
+                s.toasts.warning("Wait for the scan or organize to finish");
+            } else {
+                return start_job(s, job, true);
+            }
+        }
+        Message::SchedulerToggleJob(job) => {
+            let job_config = s.scheduler.config.job_mut(job);
+            job_config.enabled = !job_config.enabled;
+            let enabled = job_config.enabled;
+            return Task::perform(
+                async move {
+                    let mut cfg = config::load();
+                    cfg.scheduler.job_mut(job).enabled = enabled;
+                    config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save scheduler settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+        Message::SchedulerJobFinished(run) => {
+            let manual = std::mem::take(&mut s.scheduler.running_manually);
+            s.scheduler.running = None;
+            let cancelled = s.scheduler.task.take().is_some_and(|task| {
+                task.finish();
+                task.is_cancelled()
+            });
+            let job = run.job;
+            if cancelled {
+                s.toasts.info(format!("{} cancelled", job.label()));
+            } else if !run.success {
+                s.toasts
+                    .error(format!("{} failed: {}", job.label(), run.summary));
+            } else if manual {
+                s.toasts
+                    .success(format!("{}: {}", job.label(), run.summary));
+            }
+            let success = run.success;
+            scheduler::push_run(&mut s.scheduler.runs, run);
+            // Scans and re-checks change what the library list shows, even
+            // when cancelled partway
+            if (success || cancelled) && matches!(job, Job::Scan | Job::Gardener) {
+                return load_tracks_task(s.pool.clone());
+            }
+        }
+        Message::SchedulerRunsLoaded(runs) => {
+            s.scheduler.runs = runs;
+        }
+        _ => {}
+    }
+    Task::none()
+}
+
+/// Whether a foreground library operation is in progress
+fn busy(s: &LoadedState) -> bool {
+    s.is_scanning || s.organize_view == OrganizeView::Organizing
+}
+
+fn start_job(s: &mut LoadedState, job: Job, manual: bool) -> Task<Message> {
+    s.scheduler.running = Some(job);
+    s.scheduler.running_manually = manual;
+    let task = s.tasks.start(TaskKind::Maintenance, job.label());
+    s.scheduler.task = Some(task.clone());
+    let pool = s.pool.clone();
+    Task::perform(
+        async move {
+            let cfg = config::load();
+            scheduler::run_and_record(&pool, job, &cfg, &task).await
+        },
+        Message::SchedulerJobFinished,
+    )
+}
+
+/// Load recent job runs for the status in Settings
+pub(crate) fn job_runs_task(pool: SqlitePool) -> Task<Message> {
+    Task::perform(
+        async move { scheduler::recent_runs(&pool, scheduler::RUNS_KEPT_PER_JOB).await },
+        |result| match result {
+            Ok(runs) => Message::SchedulerRunsLoaded(runs),
+            Err(e) => {
+                tracing::warn!("Failed to load maintenance runs: {}", e);
+                Message::Noop
+            }
+        },
+    )
+}
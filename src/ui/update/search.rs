@@ -1,11 +1,18 @@
 //! Search and filter handlers.
 //!
-//! Handles search query changes, column sorting, and format filtering.
+//! Handles search query changes, column sorting, and format/date/loudness/
+//! content/provenance/skip/artist/album filtering.
+
+use std::collections::HashSet;
 
 use iced::Task;
 
 use super::super::messages::Message;
-use super::super::state::{LoadedState, SortColumn};
+use super::super::state::{LibraryScope, LoadedState, SortColumn};
+use crate::db::{self, TrackWithMetadata};
+use crate::history;
+use crate::metadata::{content, loudness};
+use crate::provenance;
 use crate::ui::views::helpers::{format_from_path, is_lossless};
 
 /// Handle search and filter messages
@@ -33,10 +40,111 @@ pub fn handle_search_filter(s: &mut LoadedState, message: Message) -> Task<Messa
             s.filter_lossless = lossless;
             apply_filters_and_sort(s);
         }
+        Message::FilterByAddedWithin(days) => {
+            s.filter_added_within_days = days;
+            apply_filters_and_sort(s);
+        }
+        Message::FilterByLoudMaster(loud) => {
+            s.filter_loud_master = loud;
+            apply_filters_and_sort(s);
+        }
+        Message::FilterByInstrumental(instrumental) => {
+            s.filter_instrumental = instrumental;
+            apply_filters_and_sort(s);
+        }
+        Message::FilterHideExplicit(hide) => {
+            s.filter_hide_explicit = hide;
+            apply_filters_and_sort(s);
+        }
+        Message::FilterByScope(scope) => {
+            s.album_summary = None;
+            let summary = match &scope {
+                Some(LibraryScope::Album { album, artist }) => {
+                    let pool = s.pool.clone();
+                    let (album, artist) = (album.clone(), artist.clone());
+                    Task::perform(
+                        async move {
+                            db::get_album_summary(&pool, &album, &artist)
+                                .await
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::AlbumSummaryLoaded,
+                    )
+                }
+                _ => Task::none(),
+            };
+            s.filter_scope = scope;
+            apply_filters_and_sort(s);
+            return summary;
+        }
+        Message::AlbumSummaryLoaded(result) => match result {
+            // The scope may have moved on while it loaded
+            Ok(summary) => {
+                s.album_summary = summary.filter(|summary| {
+                    matches!(&s.filter_scope, Some(LibraryScope::Album { album, .. })
+                        if *album == summary.title)
+                });
+            }
+            Err(e) => tracing::warn!("Failed to load the album summary: {}", e),
+        },
+        Message::FilterByMachineWritten(None) => {
+            s.filter_machine_written = None;
+            apply_filters_and_sort(s);
+        }
+        Message::FilterByMachineWritten(Some(field)) => {
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    provenance::machine_written(&pool, field)
+                        .await
+                        .map_err(|e| e.to_string())
+                },
+                move |result| Message::MachineWrittenLoaded(field, result),
+            );
+        }
+        Message::MachineWrittenLoaded(field, result) => match result {
+            Ok(ids) => {
+                s.filter_machine_written = Some((field, ids));
+                apply_filters_and_sort(s);
+            }
+            Err(e) => s
+                .toasts
+                .error(format!("Failed to load tag provenance: {}", e)),
+        },
+        Message::FilterHideSkipped(false) => {
+            s.filter_hide_skipped = None;
+            apply_filters_and_sort(s);
+        }
+        Message::FilterHideSkipped(true) => {
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    history::often_skipped(&pool)
+                        .await
+                        .map_err(|e| e.to_string())
+                },
+                Message::OftenSkippedLoaded,
+            );
+        }
+        Message::OftenSkippedLoaded(result) => match result {
+            Ok(ids) => {
+                s.filter_hide_skipped = Some(ids);
+                apply_filters_and_sort(s);
+            }
+            Err(e) => s.toasts.error(format!("Failed to load skip rates: {}", e)),
+        },
         Message::ClearFilters => {
             s.search_query.clear();
             s.filter_format = None;
             s.filter_lossless = None;
+            s.filter_added_within_days = None;
+            s.filter_loud_master = false;
+            s.filter_instrumental = false;
+            s.filter_hide_explicit = false;
+            s.filter_machine_written = None;
+            s.filter_hide_skipped = None;
+            s.filter_scope = None;
+            s.album_summary = None;
             s.filtered_indices.clear();
             // Keep sort settings but rebuild indices
             apply_filters_and_sort(s);
@@ -48,95 +156,371 @@ pub fn handle_search_filter(s: &mut LoadedState, message: Message) -> Task<Messa
 
 /// Apply all active filters and sorting to create filtered_indices
 fn apply_filters_and_sort(s: &mut LoadedState) {
-    let query = s.search_query.to_lowercase();
-    let has_search = !query.is_empty();
-    let has_format = s.filter_format.is_some();
-    let has_lossless = s.filter_lossless.is_some();
+    let query = LibraryQuery::from_state(s);
 
     // If no filters and default sort, clear filtered_indices
     // (track_list will iterate all tracks directly)
-    if !has_search
-        && !has_format
-        && !has_lossless
-        && s.sort_column == SortColumn::Title
-        && s.sort_ascending
-    {
-        s.filtered_indices.clear();
-        return;
+    s.filtered_indices = if query.is_default() {
+        Vec::new()
+    } else {
+        query.indices(&s.tracks)
+    };
+
+    // Reset scroll position when filters change
+    s.panes.library.scroll_offset = 0.0;
+}
+
+/// The library's search, filters and sort order, borrowed from the state
+pub(crate) struct LibraryQuery<'a> {
+    /// Lowercased search text
+    pub search: String,
+    pub format: Option<&'a str>,
+    pub lossless: Option<bool>,
+    /// Unix time tracks must have been added after
+    pub added_since: Option<i64>,
+    pub loud_master: bool,
+    /// Only tracks without lyrics
+    pub instrumental: bool,
+    /// Leave out tracks tagged explicit
+    pub hide_explicit: bool,
+    pub machine_written: Option<&'a HashSet<i64>>,
+    /// Often skipped tracks, left out
+    pub skipped: Option<&'a HashSet<i64>>,
+    /// One artist's or album's tracks
+    pub scope: Option<&'a LibraryScope>,
+    pub sort_column: SortColumn,
+    pub sort_ascending: bool,
+}
+
+impl<'a> LibraryQuery<'a> {
+    pub fn from_state(s: &'a LoadedState) -> Self {
+        Self {
+            search: s.search_query.to_lowercase(),
+            format: s.filter_format.as_deref(),
+            lossless: s.filter_lossless,
+            added_since: s
+                .filter_added_within_days
+                .map(|days| chrono::Utc::now().timestamp() - i64::from(days) * 86_400),
+            loud_master: s.filter_loud_master,
+            instrumental: s.filter_instrumental,
+            hide_explicit: s.filter_hide_explicit,
+            machine_written: s.filter_machine_written.as_ref().map(|(_, ids)| ids),
+            skipped: s.filter_hide_skipped.as_ref(),
+            scope: s.filter_scope.as_ref(),
+            sort_column: s.sort_column,
+            sort_ascending: s.sort_ascending,
+        }
     }
 
-    // Build filtered indices
-    let mut indices: Vec<usize> = s
-        .tracks
-        .iter()
-        .enumerate()
-        .filter(|(_, track)| {
-            // Search filter
-            if has_search {
-                let title_match = track.title.to_lowercase().contains(&query);
-                let artist_match = track.artist_name.to_lowercase().contains(&query);
-                let album_match = track.album_name.to_lowercase().contains(&query);
-                if !title_match && !artist_match && !album_match {
-                    return false;
-                }
-            }
+    /// No filters and the default sort: every track in load order
+    pub fn is_default(&self) -> bool {
+        self.search.is_empty()
+            && self.format.is_none()
+            && self.lossless.is_none()
+            && self.added_since.is_none()
+            && !self.loud_master
+            && !self.instrumental
+            && !self.hide_explicit
+            && self.machine_written.is_none()
+            && self.skipped.is_none()
+            && self.scope.is_none()
+            && self.sort_column == SortColumn::Title
+            && self.sort_ascending
+    }
 
-            // Format filter
-            if let Some(ref fmt) = s.filter_format {
-                let track_format = format_from_path(&track.path);
-                if track_format != fmt {
-                    return false;
-                }
+    /// Whether a track passes the search and every filter
+    pub fn matches(&self, track: &TrackWithMetadata) -> bool {
+        // Search filter
+        if !self.search.is_empty() {
+            let title_match = track.title.to_lowercase().contains(&self.search);
+            let artist_match = track.artist_name.to_lowercase().contains(&self.search);
+            let album_match = track.album_name.to_lowercase().contains(&self.search);
+            if !title_match && !artist_match && !album_match {
+                return false;
             }
+        }
+
+        // Format filter
+        if let Some(fmt) = self.format
+            && format_from_path(&track.path) != fmt
+        {
+            return false;
+        }
+
+        // Lossless filter
+        if let Some(true) = self.lossless
+            && !is_lossless(format_from_path(&track.path))
+        {
+            return false;
+        }
+
+        // Recently added filter (tracks with no date never match)
+        if let Some(since) = self.added_since
+            && track.added_at.is_none_or(|added| added < since)
+        {
+            return false;
+        }
+
+        // Loud master filter (untagged tracks never match)
+        if self.loud_master && !loudness::is_loud_master(track.track_gain, track.track_peak) {
+            return false;
+        }
+
+        // Instrumental filter (tracks without a language never match)
+        if self.instrumental && !content::is_instrumental(track.language.as_deref()) {
+            return false;
+        }
 
-            // Lossless filter
-            if let Some(true) = s.filter_lossless {
-                let track_format = format_from_path(&track.path);
-                if !is_lossless(track_format) {
-                    return false;
+        // Explicit filter (only tracks tagged explicit are hidden)
+        if self.hide_explicit && track.explicit == Some(true) {
+            return false;
+        }
+
+        // Machine-written field filter
+        if let Some(ids) = self.machine_written
+            && !ids.contains(&track.id)
+        {
+            return false;
+        }
+
+        // Often skipped tracks
+        if let Some(ids) = self.skipped
+            && ids.contains(&track.id)
+        {
+            return false;
+        }
+
+        // Artist or album scope
+        if let Some(scope) = self.scope
+            && !scope.matches(track)
+        {
+            return false;
+        }
+
+        true
+    }
+
+    /// Indices of the matching tracks, in sort order
+    pub fn indices(&self, tracks: &[TrackWithMetadata]) -> Vec<usize> {
+        let mut indices: Vec<usize> = tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, track)| self.matches(track))
+            .map(|(i, _)| i)
+            .collect();
+
+        // An album reads in track order until another column is picked
+        let track_order = matches!(self.scope, Some(LibraryScope::Album { .. }))
+            && self.sort_column == SortColumn::Title;
+
+        indices.sort_by(|&a, &b| {
+            let track_a = &tracks[a];
+            let track_b = &tracks[b];
+
+            let cmp = match self.sort_column {
+                SortColumn::Title if track_order => track_a
+                    .track_number
+                    .cmp(&track_b.track_number)
+                    .then_with(|| {
+                        track_a
+                            .title
+                            .to_lowercase()
+                            .cmp(&track_b.title.to_lowercase())
+                    }),
+                SortColumn::Title => track_a
+                    .title
+                    .to_lowercase()
+                    .cmp(&track_b.title.to_lowercase()),
+                SortColumn::Artist => track_a
+                    .artist_name
+                    .to_lowercase()
+                    .cmp(&track_b.artist_name.to_lowercase()),
+                SortColumn::Album => track_a
+                    .album_name
+                    .to_lowercase()
+                    .cmp(&track_b.album_name.to_lowercase()),
+                SortColumn::Year => track_a.year.cmp(&track_b.year),
+                SortColumn::Duration => track_a.duration.cmp(&track_b.duration),
+                // Loudest (most negative gain) first, untagged last
+                SortColumn::Gain => match (track_a.track_gain, track_b.track_gain) {
+                    (Some(a), Some(b)) => a.total_cmp(&b),
+                    (a, b) => b.is_some().cmp(&a.is_some()),
+                },
+                // Files by format, then streams and cloud links
+                SortColumn::Format => {
+                    let fmt_a = format_from_path(&track_a.path);
+                    let fmt_b = format_from_path(&track_b.path);
+                    (track_a.source, fmt_a).cmp(&(track_b.source, fmt_b))
                 }
-            }
+                SortColumn::DateAdded => track_a.added_at.cmp(&track_b.added_at),
+                SortColumn::DateModified => track_a.updated_at.cmp(&track_b.updated_at),
+            };
 
-            true
-        })
-        .map(|(i, _)| i)
-        .collect();
-
-    // Sort indices based on current sort column
-    let tracks = &s.tracks;
-    let ascending = s.sort_ascending;
-
-    indices.sort_by(|&a, &b| {
-        let track_a = &tracks[a];
-        let track_b = &tracks[b];
-
-        let cmp = match s.sort_column {
-            SortColumn::Title => track_a
-                .title
-                .to_lowercase()
-                .cmp(&track_b.title.to_lowercase()),
-            SortColumn::Artist => track_a
-                .artist_name
-                .to_lowercase()
-                .cmp(&track_b.artist_name.to_lowercase()),
-            SortColumn::Album => track_a
-                .album_name
-                .to_lowercase()
-                .cmp(&track_b.album_name.to_lowercase()),
-            SortColumn::Year => track_a.year.cmp(&track_b.year),
-            SortColumn::Duration => track_a.duration.cmp(&track_b.duration),
-            SortColumn::Format => {
-                let fmt_a = format_from_path(&track_a.path);
-                let fmt_b = format_from_path(&track_b.path);
-                fmt_a.cmp(fmt_b)
+            if self.sort_ascending {
+                cmp
+            } else {
+                cmp.reverse()
             }
+        });
+        indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{mock_track_with_metadata, synthetic_library, within_budget};
+    use std::time::Duration;
+
+    fn query(search: &str, sort_column: SortColumn) -> LibraryQuery<'static> {
+        LibraryQuery {
+            search: search.to_lowercase(),
+            format: None,
+            lossless: None,
+            added_since: None,
+            loud_master: false,
+            instrumental: false,
+            hide_explicit: false,
+            machine_written: None,
+            skipped: None,
+            scope: None,
+            sort_column,
+            sort_ascending: true,
+        }
+    }
+
+    #[test]
+    fn test_query_filters_and_sorts() {
+        let tracks = vec![
+            TrackWithMetadata {
+                id: 1,
+                title: "b side".to_string(),
+                path: "/m/1.flac".to_string(),
+                ..mock_track_with_metadata()
+            },
+            TrackWithMetadata {
+                id: 2,
+                title: "A Side".to_string(),
+                path: "/m/2.mp3".to_string(),
+                ..mock_track_with_metadata()
+            },
+            TrackWithMetadata {
+                id: 3,
+                title: "Other".to_string(),
+                path: "/m/3.flac".to_string(),
+                ..mock_track_with_metadata()
+            },
+        ];
+        assert!(query("", SortColumn::Title).is_default());
+        assert_eq!(query("SIDE", SortColumn::Title).indices(&tracks), [1, 0]);
+
+        let lossless = LibraryQuery {
+            lossless: Some(true),
+            sort_ascending: false,
+            ..query("", SortColumn::Title)
         };
+        assert!(!lossless.is_default());
+        assert_eq!(lossless.indices(&tracks), [2, 0]);
 
-        if ascending { cmp } else { cmp.reverse() }
-    });
+        let often_skipped = HashSet::from([3]);
+        let unskipped = LibraryQuery {
+            skipped: Some(&often_skipped),
+            ..query("", SortColumn::Title)
+        };
+        assert_eq!(unskipped.indices(&tracks), [1, 0]);
+    }
 
-    s.filtered_indices = indices;
+    #[test]
+    fn test_content_filters() {
+        let track = |id, language: Option<&str>, explicit| TrackWithMetadata {
+            id,
+            language: language.map(Into::into),
+            explicit,
+            ..mock_track_with_metadata()
+        };
+        let tracks = vec![
+            track(1, Some("eng"), Some(true)),
+            track(2, Some("zxx"), None),
+            track(3, None, Some(false)),
+        ];
 
-    // Reset scroll position when filters change
-    s.scroll_offset = 0.0;
+        let instrumental = LibraryQuery {
+            instrumental: true,
+            ..query("", SortColumn::Title)
+        };
+        assert!(!instrumental.is_default());
+        assert_eq!(instrumental.indices(&tracks), [1]);
+
+        // Untagged and clean tracks stay
+        let clean = LibraryQuery {
+            hide_explicit: true,
+            ..query("", SortColumn::Title)
+        };
+        assert_eq!(clean.indices(&tracks), [1, 2]);
+    }
+
+    #[test]
+    fn test_artist_and_album_scope() {
+        let track = |id, title: &str, artist: &str, album: &str, number| TrackWithMetadata {
+            id,
+            title: title.to_string(),
+            artist_name: artist.into(),
+            album_name: album.into(),
+            track_number: Some(number),
+            ..mock_track_with_metadata()
+        };
+        let tracks = vec![
+            track(1, "Zebra", "Alpha", "Hits", 1),
+            track(2, "Apple", "Alpha", "Hits", 2),
+            track(3, "Mango", "Beta", "Hits", 1),
+            track(4, "Kiwi", "Alpha", "Live", 1),
+        ];
+
+        let artist = LibraryScope::Artist("Alpha".to_string());
+        let by_artist = LibraryQuery {
+            scope: Some(&artist),
+            ..query("", SortColumn::Title)
+        };
+        assert!(!by_artist.is_default());
+        assert_eq!(by_artist.indices(&tracks), [1, 3, 0]);
+
+        // Same album title by another artist is another album, in track order
+        let album = LibraryScope::album_of(&tracks[0]);
+        let by_album = LibraryQuery {
+            scope: Some(&album),
+            ..query("", SortColumn::Title)
+        };
+        assert_eq!(by_album.indices(&tracks), [0, 1]);
+    }
+
+    #[test]
+    #[ignore] // Performance budget - run with `cargo test --release perf_ -- --ignored`
+    fn perf_search_and_sort_large_library() {
+        let n = crate::test_utils::perf_library_size();
+        let tracks = synthetic_library(n, 1);
+
+        let found = within_budget("search as you type", n, Duration::from_millis(250), || {
+            query("lo", SortColumn::Title).indices(&tracks)
+        });
+        assert!(!found.is_empty());
+        within_budget("sort by artist", n, Duration::from_millis(500), || {
+            query("", SortColumn::Artist).indices(&tracks)
+        });
+        within_budget("sort by gain", n, Duration::from_millis(100), || {
+            query("", SortColumn::Gain).indices(&tracks)
+        });
+        within_budget(
+            "lossless, recently added",
+            n,
+            Duration::from_millis(100),
+            || {
+                LibraryQuery {
+                    lossless: Some(true),
+                    added_since: Some(chrono::Utc::now().timestamp() - 365 * 86_400),
+                    ..query("", SortColumn::DateAdded)
+                }
+                .indices(&tracks)
+            },
+        );
+    }
 }
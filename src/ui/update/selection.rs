@@ -1,12 +1,14 @@
 //! Selection and keyboard navigation handling.
 //!
 //! Handles track selection in library and queue views,
-//! enabling keyboard navigation (Up/Down/Enter/Delete).
+//! enabling keyboard navigation (Up/Down/Enter/Delete), multi-selection,
+//! and moving queue items by keyboard or drag-and-drop.
 
 use iced::Task;
+use iced::widget::scrollable::{self, AbsoluteOffset};
 
 use super::super::messages::Message;
-use super::super::state::{ActivePane, DragInfo, FocusedList, LoadedState};
+use super::super::state::{ActivePane, DragInfo, FocusedList, LoadedState, virtualization as virt};
 
 /// Handle selection-related messages.
 pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message> {
@@ -17,12 +19,12 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
             if count == 0 {
                 return Task::none();
             }
-            s.library_selection = Some(match s.library_selection {
+            s.panes.library.selection = Some(match s.panes.library.selection {
                 None => 0,    // Start at first item
                 Some(0) => 0, // Stay at top
                 Some(i) => i.saturating_sub(1),
             });
-            tracing::debug!(target: "ui::selection", "Library selection: {:?}", s.library_selection);
+            tracing::debug!(target: "ui::selection", "Library selection: {:?}", s.panes.library.selection);
         }
 
         Message::LibrarySelectNext => {
@@ -32,18 +34,18 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
                 return Task::none();
             }
             let max_idx = count.saturating_sub(1);
-            s.library_selection = Some(match s.library_selection {
+            s.panes.library.selection = Some(match s.panes.library.selection {
                 None => 0, // Start at first item
                 Some(i) => (i + 1).min(max_idx),
             });
-            tracing::debug!(target: "ui::selection", "Library selection: {:?}", s.library_selection);
+            tracing::debug!(target: "ui::selection", "Library selection: {:?}", s.panes.library.selection);
         }
 
         Message::LibrarySelectIndex(idx) => {
             s.focused_list = FocusedList::Library;
             let count = visible_library_count(s);
             if idx < count {
-                s.library_selection = Some(idx);
+                s.panes.library.selection = Some(idx);
             }
         }
 
@@ -53,6 +55,7 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
             if count == 0 {
                 return Task::none();
             }
+            s.queue_multi_selection.clear();
             s.queue_selection = Some(match s.queue_selection {
                 None => 0,
                 Some(0) => 0,
@@ -67,6 +70,7 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
             if count == 0 {
                 return Task::none();
             }
+            s.queue_multi_selection.clear();
             let max_idx = count.saturating_sub(1);
             s.queue_selection = Some(match s.queue_selection {
                 None => 0,
@@ -79,15 +83,94 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
             s.focused_list = FocusedList::Queue;
             let count = queue_count(s);
             if idx < count {
+                s.queue_multi_selection.clear();
                 s.queue_selection = Some(idx);
             }
         }
 
+        Message::QueueExtendSelection { down } => {
+            // Shift+Up/Down: keep the current item selected and move the cursor
+            s.focused_list = FocusedList::Queue;
+            let count = queue_count(s);
+            let Some(cursor) = s.queue_selection else {
+                return Task::done(if down {
+                    Message::QueueSelectNext
+                } else {
+                    Message::QueueSelectPrevious
+                });
+            };
+            let next = if down {
+                (cursor + 1).min(count.saturating_sub(1))
+            } else {
+                cursor.saturating_sub(1)
+            };
+            s.queue_multi_selection.insert(cursor);
+            s.queue_selection = Some(next);
+            announce_selection(s);
+        }
+
+        Message::QueueItemClicked(idx) => {
+            s.focused_list = FocusedList::Queue;
+            if idx >= queue_count(s) {
+                return Task::none();
+            }
+            let modifiers = s.keyboard_modifiers;
+            if modifiers.command() {
+                // Ctrl+click toggles the row, keeping the rest of the selection
+                if let Some(cursor) = s.queue_selection {
+                    s.queue_multi_selection.insert(cursor);
+                }
+                if !s.queue_multi_selection.remove(&idx) {
+                    s.queue_multi_selection.insert(idx);
+                }
+                s.queue_selection = s.queue_multi_selection.contains(&idx).then_some(idx);
+                announce_selection(s);
+            } else if modifiers.shift() {
+                // Shift+click selects the range from the cursor
+                let anchor = s.queue_selection.unwrap_or(idx);
+                s.queue_multi_selection = (anchor.min(idx)..=anchor.max(idx)).collect();
+                s.queue_selection = Some(idx);
+                announce_selection(s);
+            } else {
+                s.queue_multi_selection.clear();
+                s.queue_selection = Some(idx);
+                return Task::done(Message::QueueJumpTo(idx));
+            }
+        }
+
+        Message::ModifiersChanged(modifiers) => {
+            s.keyboard_modifiers = modifiers;
+        }
+
+        Message::QueueMoveUp | Message::QueueMoveDown => {
+            // Move the selected block one step, as a unit
+            let up = matches!(message, Message::QueueMoveUp);
+            let indices = s.queue_selected_indices();
+            let (Some(&first), Some(&last)) = (indices.first(), indices.last()) else {
+                return Task::none();
+            };
+            let count = queue_count(s);
+            let gap = if up {
+                if first == 0 {
+                    s.queue_announcement = Some("Already at the top of the queue".to_string());
+                    return Task::none();
+                }
+                first - 1
+            } else {
+                if last + 1 >= count {
+                    s.queue_announcement = Some("Already at the bottom of the queue".to_string());
+                    return Task::none();
+                }
+                last + 2
+            };
+            move_selection(s, &indices, gap);
+        }
+
         Message::PlaySelected => {
             // Play the selected track based on current focus and pane
             match (s.active_pane, s.focused_list) {
                 (ActivePane::Library, FocusedList::Library) => {
-                    if let Some(sel_idx) = s.library_selection {
+                    if let Some(sel_idx) = s.panes.library.selection {
                         // Convert selection index to actual track index
                         let track_idx = library_selection_to_track_index(s, sel_idx);
                         if let Some(idx) = track_idx {
@@ -104,7 +187,7 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
                 }
                 // For other combinations, try library first if we have a selection
                 _ => {
-                    if let Some(sel_idx) = s.library_selection {
+                    if let Some(sel_idx) = s.panes.library.selection {
                         let track_idx = library_selection_to_track_index(s, sel_idx);
                         if let Some(idx) = track_idx {
                             return Task::done(Message::PlayerPlayTrack(idx));
@@ -135,110 +218,74 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
             }
         }
 
-        Message::QueueMoveUp => {
-            // Move selected queue item up one position
-            if let Some(idx) = s.queue_selection
-                && let Some(player) = &mut s.player
-                && let Some(new_idx) = player.queue_mut().move_up(idx)
-            {
-                s.queue_selection = Some(new_idx);
-                tracing::info!(target: "ui::selection", "Moved queue item {} -> {}", idx, new_idx);
-            }
-        }
-
-        Message::QueueMoveDown => {
-            // Move selected queue item down one position
-            if let Some(idx) = s.queue_selection
-                && let Some(player) = &mut s.player
-                && let Some(new_idx) = player.queue_mut().move_down(idx)
-            {
-                s.queue_selection = Some(new_idx);
-                tracing::info!(target: "ui::selection", "Moved queue item {} -> {}", idx, new_idx);
-            }
-        }
-
         Message::QueueDragStart { index, y: _ } => {
-            // Start dragging a queue item
+            // Start dragging a queue item (with the rest of the selection if
+            // the grabbed row is part of it)
             let count = queue_count(s);
             if index >= count {
                 return Task::none();
             }
-            let is_shuffle = s
-                .player
-                .as_ref()
-                .map(|p| p.queue().shuffle())
-                .unwrap_or(false);
+            if !s.is_queue_selected(index) {
+                s.queue_multi_selection.clear();
+            }
+            s.queue_selection = Some(index);
             s.queue_drag.dragging = Some(DragInfo {
                 index,
+                indices: s.queue_selected_indices(),
                 origin_y: None, // Will be captured on first move event
                 current_y: 0.0,
-                is_shuffle_mode: is_shuffle,
             });
             s.queue_drag.drop_target = Some(index);
-            s.queue_selection = Some(index);
             s.focused_list = FocusedList::Queue;
             tracing::debug!(target: "ui::selection", "Drag start: index={}", index);
         }
 
         Message::QueueDragMove { y } => {
-            // Update drag position and calculate drop target
-            // Get count first to avoid borrow conflict
-            let count = queue_count(s);
-            if count == 0 {
-                return Task::none();
-            }
-
             if let Some(ref mut drag) = s.queue_drag.dragging {
-                // Capture origin_y on first move event (on_press doesn't give us coordinates)
-                let origin = match drag.origin_y {
-                    Some(o) => o,
-                    None => {
-                        drag.origin_y = Some(y);
-                        y
-                    }
-                };
                 drag.current_y = y;
+            }
+            // Scroll when the cursor nears the top or bottom of the list
+            let height = match s.panes.now_playing.viewport_height {
+                h if h > 0.0 => h,
+                _ => virt::DEFAULT_VIEWPORT_HEIGHT,
+            };
+            s.queue_drag.auto_scroll = if y < AUTO_SCROLL_EDGE {
+                -AUTO_SCROLL_MAX_STEP * (AUTO_SCROLL_EDGE - y.max(0.0)) / AUTO_SCROLL_EDGE
+            } else if y > height - AUTO_SCROLL_EDGE {
+                AUTO_SCROLL_MAX_STEP * (y.min(height) - (height - AUTO_SCROLL_EDGE))
+                    / AUTO_SCROLL_EDGE
+            } else {
+                0.0
+            };
+            update_drop_target(s);
+        }
 
-                // Calculate drop target based on cursor position delta
-                // Item height is approximately 30px (XS padding * 2 + font size)
-                const ITEM_HEIGHT: f32 = 30.0;
-                let delta = y - origin;
-
-                // Simple approach: round delta to nearest item slot
-                // Positive delta = moving down, negative = moving up
-                // Use rounding for smooth, predictable behavior
-                let items_moved = (delta / ITEM_HEIGHT).round() as i32;
-
-                // Calculate new target position
-                // When moving down: target is where the item will END UP (after items shift up)
-                // When moving up: target is where the item will END UP (after items shift down)
-                let new_target =
-                    (drag.index as i32 + items_moved).clamp(0, count as i32 - 1) as usize;
-
-                if s.queue_drag.drop_target != Some(new_target) {
-                    s.queue_drag.drop_target = Some(new_target);
-                    tracing::trace!(target: "ui::selection", 
-                        "Drag move: y={:.1}, delta={:.1}, items_moved={}, target={}", 
-                        y, delta, items_moved, new_target);
-                }
+        Message::QueueDragAutoScroll => {
+            if s.queue_drag.dragging.is_none() || s.queue_drag.auto_scroll == 0.0 {
+                s.queue_drag.auto_scroll = 0.0;
+                return Task::none();
             }
+            // The scroll offset catches up via PaneScrolled; retarget on the
+            // offset seen so far so the line follows the list
+            update_drop_target(s);
+            return scrollable::scroll_by(
+                ActivePane::NowPlaying.scroll_id(),
+                AbsoluteOffset {
+                    x: 0.0,
+                    y: s.queue_drag.auto_scroll,
+                },
+            );
         }
 
         Message::QueueDragEnd => {
             // Complete the drop - reorder the queue
+            let changes = s.queue_drag.drop_changes_order();
             if let Some(drag) = s.queue_drag.dragging.take()
                 && let Some(target) = s.queue_drag.drop_target.take()
-                && drag.index != target
-                && let Some(player) = &mut s.player
+                && changes
             {
-                // Use shuffle-aware or regular reorder
-                if drag.is_shuffle_mode {
-                    player.queue_mut().reorder_shuffle(drag.index, target);
-                } else {
-                    player.queue_mut().reorder(drag.index, target);
-                }
-                s.queue_selection = Some(target);
-                tracing::info!(target: "ui::selection", "Drag complete: {} -> {}", drag.index, target);
+                move_selection(s, &drag.indices, target);
+                tracing::info!(target: "ui::selection", "Drag complete: {:?} -> gap {}", drag.indices, target);
             }
             s.queue_drag = Default::default();
         }
@@ -247,6 +294,7 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
             // Cancel the drag, restore original state
             if s.queue_drag.dragging.is_some() {
                 tracing::debug!(target: "ui::selection", "Drag cancelled");
+                s.queue_announcement = Some("Move cancelled".to_string());
             }
             s.queue_drag = Default::default();
         }
@@ -257,6 +305,87 @@ pub fn handle_selection(s: &mut LoadedState, message: Message) -> Task<Message>
     Task::none()
 }
 
+/// Approximate height of a queue row (XS padding * 2 + font size)
+pub(super) const QUEUE_ROW_HEIGHT: f32 = 30.0;
+/// Distance from the queue's top/bottom edge where dragging auto-scrolls
+const AUTO_SCROLL_EDGE: f32 = 40.0;
+/// Fastest auto-scroll step, reached at the very edge
+const AUTO_SCROLL_MAX_STEP: f32 = 18.0;
+
+/// Recalculate the insertion point from the cursor and scroll offset
+fn update_drop_target(s: &mut LoadedState) {
+    let count = queue_count(s);
+    let offset = s.panes.now_playing.offset;
+    let Some(ref mut drag) = s.queue_drag.dragging else {
+        return;
+    };
+    if count == 0 {
+        return;
+    }
+    // Work in content coordinates so auto-scrolling moves the target too;
+    // origin_y is captured on the first move (on_press has no coordinates)
+    let content_y = drag.current_y + offset;
+    let origin = *drag.origin_y.get_or_insert(content_y);
+
+    // The grabbed row's centre follows the cursor; the gap nearest to it
+    // is where the block lands
+    let centre = drag.index as f32 + 0.5 + (content_y - origin) / QUEUE_ROW_HEIGHT;
+    let gap = centre.round().clamp(0.0, count as f32) as usize;
+    if s.queue_drag.drop_target != Some(gap) {
+        s.queue_drag.drop_target = Some(gap);
+        tracing::trace!(target: "ui::selection", "Drag move: y={:.1}, gap={}", drag.current_y, gap);
+    }
+}
+
+/// Move the given queue items to a gap, keep them selected, and announce it
+fn move_selection(s: &mut LoadedState, indices: &[usize], gap: usize) {
+    let Some(player) = &mut s.player else {
+        return;
+    };
+    let moved = player.queue_mut().move_items(indices, gap);
+    let count = player.queue().len();
+    let (Some(&first), Some(&last)) = (moved.first(), moved.last()) else {
+        return;
+    };
+
+    let announcement = if moved.len() == 1 {
+        let title = player.queue().items()[first].display_title();
+        format!("Moved {} to position {} of {}", title, first + 1, count)
+    } else {
+        format!(
+            "Moved {} tracks to positions {}-{} of {}",
+            moved.len(),
+            first + 1,
+            last + 1,
+            count
+        )
+    };
+    tracing::info!(target: "ui::selection", "{}", announcement);
+    s.queue_announcement = Some(announcement);
+
+    // Keep the moved block selected, with the cursor on the row it was on
+    let cursor_offset = s
+        .queue_selection
+        .and_then(|c| indices.iter().position(|&i| i == c))
+        .unwrap_or(0);
+    s.queue_multi_selection = if moved.len() > 1 {
+        moved.iter().copied().collect()
+    } else {
+        Default::default()
+    };
+    s.queue_selection = moved.get(cursor_offset).copied();
+}
+
+/// Describe the current queue selection
+fn announce_selection(s: &mut LoadedState) {
+    let count = s.queue_selected_indices().len();
+    s.queue_announcement = Some(match count {
+        0 => "Selection cleared".to_string(),
+        1 => "1 track selected".to_string(),
+        n => format!("{} tracks selected - Ctrl+Up/Down or drag to move them", n),
+    });
+}
+
 /// Get count of visible library items (filtered or all)
 fn visible_library_count(s: &LoadedState) -> usize {
     if s.filtered_indices.is_empty() && s.search_query.is_empty() {
@@ -273,7 +402,7 @@ fn queue_count(s: &LoadedState) -> usize {
 
 /// Convert a library selection index to the actual track index
 /// (handles filtered vs unfiltered state)
-fn library_selection_to_track_index(s: &LoadedState, sel_idx: usize) -> Option<usize> {
+pub(super) fn library_selection_to_track_index(s: &LoadedState, sel_idx: usize) -> Option<usize> {
     if s.filtered_indices.is_empty() && s.search_query.is_empty() {
         // No filtering - selection index IS the track index
         if sel_idx < s.tracks.len() {
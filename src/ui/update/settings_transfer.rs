@@ -0,0 +1,166 @@
+//! Settings bundle handlers.
+//!
+//! Export writes the sections exported by default (everything but the API
+//! keys). Import reads the bundle first and shows its sections in a dialog,
+//! all ticked but the keys, so only the picked ones replace this machine's.
+
+use iced::Task;
+
+use super::super::messages::Message;
+use super::super::state::{LoadedState, PaneStates};
+use super::config_reload::reload;
+use crate::settings_bundle::{self, Section};
+
+/// Handle settings bundle messages
+pub fn handle_settings_transfer(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::SettingsExport => {
+            if s.settings_transfer.busy {
+                return Task::none();
+            }
+            s.settings_transfer.busy = true;
+            // The view state file is read back, so it's current
+            if let Err(e) = s.panes.save() {
+                tracing::warn!("Failed to save pane state: {}", e);
+            }
+            return Task::perform(
+                async move {
+                    let Some(handle) = rfd::AsyncFileDialog::new()
+                        .set_file_name("music-minder-settings.json")
+                        .add_filter("Settings bundle (JSON)", &["json"])
+                        .save_file()
+                        .await
+                    else {
+                        return Ok(None);
+                    };
+                    let path = handle.path().to_path_buf();
+                    let save_path = path.clone();
+                    let sections: Vec<Section> = Section::ALL
+                        .into_iter()
+                        .filter(|s| s.exported_by_default())
+                        .collect();
+                    tokio::task::spawn_blocking(move || {
+                        settings_bundle::export(&save_path, &sections)
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())?;
+                    Ok(Some(path))
+                },
+                Message::SettingsExported,
+            );
+        }
+        Message::SettingsExported(result) => {
+            s.settings_transfer.busy = false;
+            match result {
+                Ok(Some(path)) => s
+                    .toasts
+                    .success(format!("Settings exported to {}", path.display())),
+                Ok(None) => {}
+                Err(e) => s.toasts.error(format!("Export failed: {}", e)),
+            }
+        }
+        Message::SettingsImportPick => {
+            if s.settings_transfer.busy {
+                return Task::none();
+            }
+            s.settings_transfer.busy = true;
+            return Task::perform(
+                async move {
+                    let Some(handle) = rfd::AsyncFileDialog::new()
+                        .add_filter("Settings bundle (JSON)", &["json"])
+                        .pick_file()
+                        .await
+                    else {
+                        return Ok(None);
+                    };
+                    let path = handle.path().to_path_buf();
+                    let read_path = path.clone();
+                    let bundle =
+                        tokio::task::spawn_blocking(move || settings_bundle::read(&read_path))
+                            .await
+                            .map_err(|e| e.to_string())?
+                            .map_err(|e| e.to_string())?;
+                    Ok(Some((path, bundle)))
+                },
+                Message::SettingsImportRead,
+            );
+        }
+        Message::SettingsImportRead(result) => {
+            s.settings_transfer.busy = false;
+            match result {
+                Ok(Some((path, bundle))) => {
+                    let sections = bundle.sections();
+                    if sections.is_empty() {
+                        s.toasts
+                            .warning(format!("{} has no settings to import", path.display()));
+                        return Task::none();
+                    }
+                    s.settings_transfer.picked = sections
+                        .into_iter()
+                        .filter(|s| s.exported_by_default())
+                        .collect();
+                    s.settings_transfer.pending = Some((path, bundle));
+                }
+                Ok(None) => {}
+                Err(e) => s.toasts.error(format!("Can't import: {}", e)),
+            }
+        }
+        Message::SettingsImportToggle(section) => {
+            let picked = &mut s.settings_transfer.picked;
+            if !picked.remove(&section) {
+                picked.insert(section);
+            }
+        }
+        Message::SettingsImportCancel => {
+            s.settings_transfer.pending = None;
+            s.settings_transfer.picked.clear();
+        }
+        Message::SettingsImportConfirm => {
+            let Some((_, bundle)) = s.settings_transfer.pending.take() else {
+                return Task::none();
+            };
+            // In the bundle's order
+            let sections: Vec<Section> = bundle
+                .sections()
+                .into_iter()
+                .filter(|section| s.settings_transfer.picked.contains(section))
+                .collect();
+            s.settings_transfer.picked.clear();
+            if sections.is_empty() {
+                return Task::none();
+            }
+            s.settings_transfer.busy = true;
+            return Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        settings_bundle::import(&bundle, &sections).map(|()| sections)
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .map_err(|e| e.to_string())
+                },
+                Message::SettingsImported,
+            );
+        }
+        Message::SettingsImported(result) => {
+            s.settings_transfer.busy = false;
+            match result {
+                Ok(sections) => {
+                    // Otherwise the old view state is saved over the import
+                    if sections.contains(&Section::ViewState) {
+                        s.panes = PaneStates::load().unwrap_or_default();
+                    }
+                    s.toasts.success(format!(
+                        "Imported {} of the bundle's sections",
+                        sections.len()
+                    ));
+                    return reload(s, true);
+                }
+                Err(e) => s.toasts.error(format!("Import failed: {}", e)),
+            }
+        }
+        _ => {}
+    }
+    Task::none()
+}
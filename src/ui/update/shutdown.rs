@@ -0,0 +1,123 @@
+//! Shutdown coordinator: closing the window lets in-flight work finish.
+//!
+//! The window's close request is intercepted. Scans, organizes and batch
+//! jobs are cancelled (they stop before their next file, an organize after
+//! saving its undo log), the watcher and gardener are stopped, and tag saves
+//! already under way plus the listening time are waited for. Then the queue
+//! is saved for resuming, the audio thread joined and the app exits. A
+//! second close, or [`DRAIN_TIMEOUT`], exits without waiting any longer.
+
+use iced::Task;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::health::GardenerCommand;
+
+use super::super::messages::Message;
+use super::super::state::{LoadedState, ShutdownState};
+use super::resume;
+
+/// Longest the app waits for running work before exiting anyway
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the audio thread gets to stop
+const AUDIO_JOIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Handle the close request and the wait that follows it
+pub fn handle_shutdown(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::CloseRequested => {
+            if s.shutdown.is_some() {
+                warn!(target: "ui::shutdown", "Closed again, exiting without waiting");
+                return finish(s);
+            }
+            info!(target: "ui::shutdown", "Close requested, finishing up");
+            s.tasks.cancel_all();
+            s.watcher_state.active = false;
+            if let Some(tx) = s.gardener_state.command_tx.take() {
+                let _ = tx.try_send(GardenerCommand::Stop);
+            }
+
+            let mut flushes = Vec::new();
+            if let Some(listened) = s.listening.snapshot() {
+                flushes.push(
+                    resume::record_listened_task(s.pool.clone(), Some(listened))
+                        .map(|_| Message::ShutdownFlushed),
+                );
+            }
+            s.shutdown = Some(ShutdownState {
+                started: Instant::now(),
+                flushing: flushes.len(),
+                waiting_on: Vec::new(),
+            });
+            Task::batch(flushes)
+        }
+
+        Message::ShutdownFlushed => {
+            if let Some(shutdown) = &mut s.shutdown {
+                shutdown.flushing = shutdown.flushing.saturating_sub(1);
+            }
+            Task::none()
+        }
+
+        Message::ShutdownTick => {
+            let waiting_on = pending_work(s);
+            let Some(shutdown) = &mut s.shutdown else {
+                return Task::none();
+            };
+            if waiting_on.is_empty() {
+                return finish(s);
+            }
+            if shutdown.started.elapsed() >= DRAIN_TIMEOUT {
+                warn!(target: "ui::shutdown", waiting_on = ?waiting_on, "Gave up waiting, exiting");
+                return finish(s);
+            }
+            shutdown.waiting_on = waiting_on;
+            Task::none()
+        }
+
+        _ => Task::none(),
+    }
+}
+
+/// What the app is still waiting for, in words
+fn pending_work(s: &LoadedState) -> Vec<String> {
+    let mut pending: Vec<String> = s.tasks.running().into_iter().map(|t| t.label).collect();
+    // Finished, but its result (undo state, track list) not handled yet
+    if s.is_scanning && pending.is_empty() {
+        pending.push("Scan".to_string());
+    }
+    if s.organize_task.is_some() && pending.is_empty() {
+        pending.push("Organize files".to_string());
+    }
+    if crate::metadata::writes_in_flight() > 0 {
+        pending.push("Tag writes".to_string());
+    }
+    if s.shutdown.as_ref().is_some_and(|sd| sd.flushing > 0) {
+        pending.push("Listening history".to_string());
+    }
+    pending
+}
+
+/// Save the session, stop the audio thread and the media controls, and exit
+fn finish(s: &mut LoadedState) -> Task<Message> {
+    if let Some(mut player) = s.player.take() {
+        if let Some(session) = resume::last_session(&player, s)
+            && let Err(e) = session.save()
+        {
+            warn!(target: "ui::shutdown", "Failed to save session: {}", e);
+        }
+        if !player.shutdown(AUDIO_JOIN_TIMEOUT) {
+            warn!(target: "ui::shutdown", "Audio thread didn't stop in time");
+        }
+    }
+    if let Some(media_controls) = s.media_controls.take() {
+        media_controls.shutdown();
+    }
+    let waiting = crate::write_queue::len();
+    if waiting > 0 {
+        warn!(target: "ui::shutdown", waiting, "Dropping tag writes still waiting for files in use");
+    }
+    info!(target: "ui::shutdown", "Shutdown complete");
+    iced::exit()
+}
@@ -0,0 +1,54 @@
+//! Subsystems that start after the window is up.
+//!
+//! Opening the audio output and looking for fpcalc both block, so neither
+//! happens before the first frame: the audio output opens on a
+//! [`Message::StartAudio`] sent once the library view is showing, and
+//! fpcalc is looked for on a blocking thread. Each subsystem marks itself
+//! ready in [`LoadedState::startup`] from wherever it finishes.
+
+use iced::Task;
+use std::time::Duration;
+
+use crate::enrichment;
+use crate::startup::Subsystem;
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+
+/// Long enough for the first frame of the library view to be drawn
+const AUDIO_DELAY: Duration = Duration::from_millis(50);
+
+/// Open the audio output shortly after the library view is up
+pub(crate) fn start_audio_task() -> Task<Message> {
+    Task::perform(tokio::time::sleep(AUDIO_DELAY), |_| Message::StartAudio)
+}
+
+/// Look for fpcalc without blocking the first frame
+pub(crate) fn check_fpcalc_task() -> Task<Message> {
+    Task::perform(
+        async {
+            tokio::task::spawn_blocking(|| {
+                crate::startup::phase("fpcalc check", enrichment::fingerprint::is_fpcalc_available)
+            })
+            .await
+            .unwrap_or(false)
+        },
+        Message::FpcalcChecked,
+    )
+}
+
+/// Handle the subsystems that report back here
+pub fn handle_startup(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::StartAudio => {
+            s.ensure_player();
+            s.startup.mark(Subsystem::Audio);
+        }
+        Message::FpcalcChecked(available) => {
+            s.enrichment.fpcalc_available = available;
+            s.enrichment_pane.fpcalc_available = available;
+        }
+        _ => {}
+    }
+    Task::none()
+}
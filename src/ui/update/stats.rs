@@ -0,0 +1,128 @@
+//! Usage statistics and year-in-review handlers.
+
+use iced::Task;
+
+use crate::stats::{self, wrapped};
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+
+/// Handle usage statistics messages
+pub fn handle_stats(s: &mut LoadedState, message: Message) -> Task<Message> {
+    match message {
+        Message::StatsRefresh => {
+            return load_stats(s);
+        }
+        Message::StatsLoaded(result) => {
+            s.stats.loading = false;
+            match result {
+                Ok(stats) => s.stats.stats = stats,
+                Err(e) => s.toasts.error(format!("Failed to load stats: {}", e)),
+            }
+        }
+        Message::StatsRangeChanged(days) => {
+            s.stats.days = days;
+            return load_stats(s);
+        }
+        Message::StatsExport => {
+            let usage = s.stats.stats.clone();
+            return Task::perform(
+                async move {
+                    let Some(handle) = rfd::AsyncFileDialog::new()
+                        .set_file_name("music-minder-stats.csv")
+                        .add_filter("Spreadsheet (CSV)", &["csv"])
+                        .add_filter("JSON", &["json"])
+                        .save_file()
+                        .await
+                    else {
+                        return Ok(None);
+                    };
+                    let path = handle.path().to_path_buf();
+                    let save_path = path.clone();
+                    tokio::task::spawn_blocking(move || stats::export(&usage, &save_path))
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .map_err(|e| e.to_string())?;
+                    Ok(Some(path))
+                },
+                Message::StatsExported,
+            );
+        }
+        Message::StatsWrappedOpen(year) => {
+            s.stats.wrapped_loading = true;
+            let pool = s.pool.clone();
+            return Task::perform(
+                async move {
+                    let years = wrapped::years(&pool).await.map_err(|e| e.to_string())?;
+                    let review = wrapped::load(&pool, year)
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    Ok((years, review))
+                },
+                Message::StatsWrappedLoaded,
+            );
+        }
+        Message::StatsWrappedLoaded(result) => {
+            s.stats.wrapped_loading = false;
+            match result {
+                Ok((years, review)) => {
+                    s.stats.wrapped_years = years;
+                    s.stats.wrapped = Some(review);
+                }
+                Err(e) => s
+                    .toasts
+                    .error(format!("Failed to load the year in review: {}", e)),
+            }
+        }
+        Message::StatsWrappedClose => {
+            s.stats.wrapped = None;
+        }
+        Message::StatsWrappedExport => {
+            let Some(review) = s.stats.wrapped.clone() else {
+                return Task::none();
+            };
+            return Task::perform(
+                async move {
+                    let Some(handle) = rfd::AsyncFileDialog::new()
+                        .set_file_name(format!("music-minder-{}-in-review.html", review.year))
+                        .add_filter("Web page (HTML)", &["html"])
+                        .add_filter("Image (SVG)", &["svg"])
+                        .add_filter("JSON", &["json"])
+                        .save_file()
+                        .await
+                    else {
+                        return Ok(None);
+                    };
+                    let path = handle.path().to_path_buf();
+                    let save_path = path.clone();
+                    tokio::task::spawn_blocking(move || wrapped::export(&review, &save_path))
+                        .await
+                        .map_err(|e| e.to_string())?
+                        .map_err(|e| e.to_string())?;
+                    Ok(Some(path))
+                },
+                Message::StatsExported,
+            );
+        }
+        Message::StatsExported(result) => match result {
+            Ok(Some(path)) => s
+                .toasts
+                .success(format!("Stats exported to {}", path.display())),
+            Ok(None) => {}
+            Err(e) => s.toasts.error(format!("Export failed: {}", e)),
+        },
+        _ => {}
+    }
+    Task::none()
+}
+
+/// Reload the statistics for the current period
+fn load_stats(s: &mut LoadedState) -> Task<Message> {
+    s.stats.loading = true;
+    let pool = s.pool.clone();
+    let days = s.stats.days;
+    Task::perform(
+        async move { stats::load(&pool, days).await.map_err(|e| e.to_string()) },
+        Message::StatsLoaded,
+    )
+}
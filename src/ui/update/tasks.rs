@@ -0,0 +1,113 @@
+//! Background tasks popover handlers.
+//!
+//! Every long-running operation registers a task in `LoadedState::tasks`;
+//! cancelling one here only sets its flag, and the operation's own finish
+//! message does the cleanup. Tag writes put off while their file was in use
+//! (see [`crate::write_queue`]) are tried again from here and listed in the
+//! same popover.
+
+use iced::Task;
+use std::path::Path;
+
+use crate::write_queue;
+
+use super::super::messages::Message;
+use super::super::state::LoadedState;
+use super::{load_conflicts_task, load_tracks_task};
+
+/// Tell that `count` writes wait for their files, and where to find them
+pub(super) fn toast_deferred(s: &mut LoadedState, count: usize) {
+    let files = if count == 1 {
+        "A file is".to_string()
+    } else {
+        format!("{} files are", count)
+    };
+    s.status_message = format!("{} in use by another program", files);
+    s.toasts.info(format!(
+        "{} in use by another program; the tags are written once it lets go (see Background tasks)",
+        files
+    ));
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Handle background task messages
+pub fn handle_tasks(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::TasksPopoverToggle => {
+            s.tasks_popover_open = !s.tasks_popover_open;
+        }
+        Message::TasksPopoverClose => {
+            s.tasks_popover_open = false;
+        }
+        Message::TaskCancel(id) if !s.tasks.cancel(id) => {
+            tracing::debug!(id, "Task already finished");
+        }
+        Message::WriteQueueTick => {
+            return Task::perform(
+                write_queue::retry_due(s.watch_locked_files),
+                Message::WriteQueueSettled,
+            );
+        }
+        Message::WriteQueueSettled(settled) => {
+            if settled.is_empty() {
+                return Task::none();
+            }
+            let mut written = false;
+            for done in settled {
+                match done.result {
+                    Ok(_) => {
+                        written = true;
+                        s.toasts.success(format!(
+                            "{} written to {}",
+                            done.what,
+                            file_name(&done.path)
+                        ));
+                    }
+                    Err(e) => s.toasts.error(format!(
+                        "{} couldn't be written to {}: {}",
+                        done.what,
+                        file_name(&done.path),
+                        e
+                    )),
+                }
+            }
+            if written {
+                return Task::batch([
+                    load_tracks_task(s.pool.clone()),
+                    load_conflicts_task(s.pool.clone()),
+                ]);
+            }
+        }
+        Message::WriteQueueRetry(id) => {
+            write_queue::retry_now(id);
+            return Task::done(Message::WriteQueueTick);
+        }
+        Message::WriteQueueDiscard(id) if !write_queue::discard(id) => {
+            tracing::debug!(id, "Deferred write already being tried or done");
+        }
+        Message::WriteQueueWatchToggled(on) => {
+            s.watch_locked_files = on;
+            return Task::perform(
+                async move {
+                    let mut cfg = crate::config::load();
+                    cfg.tagging.watch_locked_files = on;
+                    crate::config::save_async(cfg).await
+                },
+                |result| {
+                    if let Err(e) = result {
+                        tracing::warn!("Failed to save tagging settings: {}", e);
+                    }
+                    Message::Noop
+                },
+            );
+        }
+        _ => {}
+    }
+    Task::none()
+}
@@ -6,10 +6,11 @@
 use iced::Task;
 use std::path::PathBuf;
 
-use crate::{enrichment, metadata};
+use crate::write_queue::{self, Outcome};
+use crate::{completeness, db, enrichment, metadata, provenance, stats};
 
 use super::super::messages::Message;
-use super::super::state::LoadedState;
+use super::super::state::{LoadedState, TrackDetailTab};
 use super::load_tracks_task;
 
 /// Handle track detail messages
@@ -28,10 +29,30 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
             s.track_detail.error = None;
             s.track_detail.is_identifying = false;
             s.track_detail.tags_written = false;
+            s.track_detail.completeness = None;
+            s.track_detail.checking_album = false;
+            s.track_detail.album_note = None;
+            s.track_detail.provenance.clear();
+            s.track_detail.tab = TrackDetailTab::Overview;
+            s.track_detail.technical = None;
+            s.track_detail.technical_loading = false;
+            s.track_detail.technical_error = None;
+
+            let pool = s.pool.clone();
+            let track_id = track.id;
+            let provenance_task = load_provenance_task(pool.clone(), track_id);
+            let completeness_task = Task::perform(
+                async move {
+                    completeness::for_track(&pool, track_id)
+                        .await
+                        .map_err(|e| e.to_string())
+                },
+                Message::TrackDetailCompletenessLoaded,
+            );
 
             // Read fresh metadata from the file (both simple and full)
             let path = PathBuf::from(&track.path);
-            return Task::perform(
+            let metadata_task = Task::perform(
                 async move {
                     tokio::task::spawn_blocking(move || {
                         let simple = metadata::read(&path).map_err(|e| e.to_string())?;
@@ -43,6 +64,7 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
                 },
                 Message::TrackDetailRefreshed,
             );
+            return Task::batch([metadata_task, completeness_task, provenance_task]);
         }
 
         Message::TrackDetailClose => {
@@ -87,6 +109,87 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
             );
         }
 
+        Message::TrackDetailCompletenessLoaded(result) => match result {
+            Ok(result) => s.track_detail.completeness = result,
+            Err(e) => tracing::warn!("Failed to load album completeness: {}", e),
+        },
+
+        Message::TrackDetailProvenanceLoaded(result) => match result {
+            Ok(fields) => s.track_detail.provenance = fields,
+            Err(e) => tracing::warn!("Failed to load tag provenance: {}", e),
+        },
+
+        Message::TrackDetailCheckAlbum => {
+            let Some(index) = s.track_detail.track_index else {
+                return Task::none();
+            };
+            let Some(track) = s.tracks.get(index) else {
+                return Task::none();
+            };
+
+            s.track_detail.checking_album = true;
+            s.track_detail.album_note = None;
+
+            let pool = s.pool.clone();
+            let track_id = track.id;
+            return Task::perform(
+                async move {
+                    let Some(album) = completeness::album_of_track(&pool, track_id)
+                        .await
+                        .map_err(|e| e.to_string())?
+                    else {
+                        return Ok(None);
+                    };
+                    let client = enrichment::musicbrainz::MusicBrainzClient::new();
+                    completeness::check_album(&pool, &client, album)
+                        .await
+                        .map_err(|e| e.to_string())
+                },
+                Message::TrackDetailAlbumChecked,
+            );
+        }
+
+        Message::TrackDetailAlbumChecked(result) => {
+            s.track_detail.checking_album = false;
+            match result {
+                Ok(Some(result)) => s.track_detail.completeness = Some(result),
+                Ok(None) => {
+                    s.track_detail.album_note = Some(
+                        "Album not identified yet - identify a track and write its tags first"
+                            .to_string(),
+                    );
+                }
+                Err(e) => s.track_detail.album_note = Some(e),
+            }
+        }
+
+        Message::TrackDetailTab(tab) => {
+            s.track_detail.tab = tab;
+            let detail = &s.track_detail;
+            if tab == TrackDetailTab::Technical
+                && detail.technical.is_none()
+                && !detail.technical_loading
+                && let Some(index) = detail.track_index
+                && let Some(track) = s.tracks.get(index)
+            {
+                s.track_detail.technical_loading = true;
+                s.track_detail.technical_error = None;
+                return load_technical_task(s.pool.clone(), index, track.path.clone());
+            }
+        }
+
+        Message::TrackDetailTechnicalLoaded(index, result) => {
+            // Ignore a probe that finished after another track was opened
+            if s.track_detail.track_index != Some(index) {
+                return Task::none();
+            }
+            s.track_detail.technical_loading = false;
+            match result {
+                Ok(info) => s.track_detail.technical = Some(info),
+                Err(e) => s.track_detail.technical_error = Some(e),
+            }
+        }
+
         Message::TrackDetailIdentify => {
             let Some(index) = s.track_detail.track_index else {
                 return Task::none();
@@ -114,6 +217,7 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
 
             let path = PathBuf::from(&track.path);
             let api_key = s.enrichment.api_key.clone();
+            let pool = s.pool.clone();
 
             return Task::perform(
                 async move {
@@ -124,10 +228,9 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
                         ..Default::default()
                     };
                     let service = enrichment::EnrichmentService::new(config);
-                    service
-                        .identify_track(&path)
-                        .await
-                        .map_err(|e| e.to_string())
+                    let result = service.identify_track(&path).await;
+                    stats::record_identification(&pool, stats::IdentifyOutcome::of(&result)).await;
+                    result.map_err(|e| e.to_string())
                 },
                 Message::TrackDetailIdentifyResult,
             );
@@ -159,21 +262,21 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
             };
 
             let path = PathBuf::from(&track.path);
-            let identified = identification.track.clone();
+            let identification = identification.clone();
+            let manual_edits = s.manual_edits.clone();
+
+            let pool = s.pool.clone();
 
             return Task::perform(
                 async move {
                     let options = metadata::WriteOptions2 {
                         only_fill_empty: false, // Overwrite with enriched data
                         write_musicbrainz_ids: true,
+                        ..Default::default()
                     };
-                    tokio::task::spawn_blocking(move || {
-                        metadata::write(&path, &identified, &options)
-                            .map(|r| r.fields_updated)
-                            .map_err(|e| e.to_string())
-                    })
-                    .await
-                    .map_err(|e| e.to_string())?
+                    write_queue::write_match(&pool, &path, &identification, options, &manual_edits)
+                        .await
+                        .map_err(|e| e.to_string())
                 },
                 Message::TrackDetailWriteResult,
             );
@@ -181,14 +284,23 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
 
         Message::TrackDetailWriteResult(result) => {
             match result {
-                Ok(count) => {
+                Ok(Outcome::Deferred) => super::tasks::toast_deferred(s, 1),
+                Ok(Outcome::Written(count)) => {
                     s.track_detail.tags_written = true;
+                    // Tag sizes changed; probed again when next shown
+                    s.track_detail.technical = None;
                     s.status_message = format!("✓ Tags written ({} fields updated)", count);
 
                     // Refresh the file metadata to show updated values
                     if let Some(index) = s.track_detail.track_index
                         && let Some(track) = s.tracks.get(index)
                     {
+                        let technical_task = if s.track_detail.tab == TrackDetailTab::Technical {
+                            s.track_detail.technical_loading = true;
+                            load_technical_task(s.pool.clone(), index, track.path.clone())
+                        } else {
+                            Task::none()
+                        };
                         let path = PathBuf::from(&track.path);
                         let refresh_task = Task::perform(
                             async move {
@@ -206,7 +318,13 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
                         );
 
                         // Also reload tracks to update the library view
-                        return Task::batch([refresh_task, load_tracks_task(s.pool.clone())]);
+                        return Task::batch([
+                            refresh_task,
+                            technical_task,
+                            load_provenance_task(s.pool.clone(), track.id),
+                            super::load_conflicts_task(s.pool.clone()),
+                            load_tracks_task(s.pool.clone()),
+                        ]);
                     }
                 }
                 Err(e) => {
@@ -219,3 +337,47 @@ pub fn handle_track_detail(s: &mut LoadedState, msg: Message) -> Task<Message> {
     }
     Task::none()
 }
+
+/// Load where each of a track's fields came from
+fn load_provenance_task(pool: sqlx::SqlitePool, track_id: i64) -> Task<Message> {
+    Task::perform(
+        async move {
+            provenance::for_track(&pool, track_id)
+                .await
+                .map_err(|e| e.to_string())
+        },
+        Message::TrackDetailProvenanceLoaded,
+    )
+}
+
+/// Technical info of a track: cached while the file is unchanged, else
+/// probed (which decodes the whole file) and cached
+fn load_technical_task(pool: sqlx::SqlitePool, index: usize, path: String) -> Task<Message> {
+    Task::perform(
+        async move {
+            let file = PathBuf::from(&path);
+            let mtime = file
+                .metadata()
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            match db::get_technical_info(&pool, &path, mtime).await {
+                Ok(Some(info)) => return Ok(info),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to read cached technical info: {}", e),
+            }
+
+            let info = tokio::task::spawn_blocking(move || metadata::technical::probe(&file))
+                .await
+                .map_err(|e| e.to_string())?
+                .map_err(|e| format!("{:#}", e))?;
+            if let Err(e) = db::store_technical_info(&pool, &path, mtime, &info).await {
+                tracing::debug!("Technical info not cached: {}", e);
+            }
+            Ok(info)
+        },
+        move |result| Message::TrackDetailTechnicalLoaded(index, result),
+    )
+}
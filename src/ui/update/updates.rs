@@ -0,0 +1,57 @@
+//! Update check handlers.
+
+use iced::Task;
+
+use crate::updates;
+
+use super::super::messages::Message;
+use super::super::platform;
+use super::super::state::LoadedState;
+
+/// Ask GitHub for the latest release
+pub(crate) fn check_updates_task() -> Task<Message> {
+    Task::perform(
+        async { updates::check().await.map_err(|e| e.to_string()) },
+        Message::UpdateChecked,
+    )
+}
+
+/// Handle update check messages
+pub fn handle_updates(s: &mut LoadedState, msg: Message) -> Task<Message> {
+    match msg {
+        Message::CheckForUpdates => {
+            if s.updates.checking {
+                return Task::none();
+            }
+            s.updates.checking = true;
+            s.updates.error = None;
+            return check_updates_task();
+        }
+        Message::UpdateChecked(result) => {
+            s.updates.checking = false;
+            s.updates.checked = true;
+            match result {
+                Ok(Some(release)) => {
+                    s.toasts.info(format!(
+                        "Music Minder {} is available (Settings → About)",
+                        release.version
+                    ));
+                    s.updates.available = Some(release);
+                }
+                Ok(None) => s.updates.available = None,
+                Err(e) => {
+                    tracing::warn!(target: "ui::updates", "Update check failed: {}", e);
+                    s.updates.error = Some(e);
+                }
+            }
+        }
+        Message::OpenReleasePage(url) => {
+            if let Err(e) = platform::open_url(&url) {
+                tracing::warn!(target: "ui::updates", "Could not open {}: {}", url, e);
+                s.toasts.error(format!("Could not open the browser: {}", e));
+            }
+        }
+        _ => {}
+    }
+    Task::none()
+}
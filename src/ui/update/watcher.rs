@@ -9,11 +9,14 @@ use std::path::PathBuf;
 use tracing::{debug, info, warn};
 
 use crate::health::GardenerCommand;
-use crate::scanner::WatchEvent;
+use crate::scanner::{WatchCommand, WatchEvent};
+use crate::startup::Subsystem;
 
 use super::super::messages::Message;
-use super::super::state::LoadedState;
-use super::load_tracks_task;
+use super::super::platform::get_user_music_folder;
+use super::super::state::{FolderStatus, LoadedState, WatchedFolder, WatcherState};
+use super::scan::begin_scan;
+use super::{load_tracks_task, pick_folder_task};
 
 /// Handle file watcher messages.
 pub fn handle_watcher(s: &mut LoadedState, message: Message) -> Task<Message> {
@@ -22,29 +25,85 @@ pub fn handle_watcher(s: &mut LoadedState, message: Message) -> Task<Message> {
             // Trigger a full rescan using the first watch path (or scan_path)
             let scan_path = s
                 .watcher_state
-                .watch_paths
+                .folders
                 .first()
-                .cloned()
+                .map(|f| f.path.clone())
                 .unwrap_or_else(|| s.scan_path.clone());
 
             info!(target: "ui::watcher", path = %scan_path.display(), "Manual rescan triggered");
-            s.is_scanning = true;
-            s.scan_count = 0;
             s.scan_path = scan_path;
+            begin_scan(s);
             s.status_message = "Rescanning library...".to_string();
             Task::none()
         }
 
-        Message::WatcherStarted => {
+        Message::WatcherStarted(command_tx) => {
             info!(target: "ui::watcher", "Background file watcher started");
             s.watcher_state.active = true;
             s.watcher_state.last_error = None;
+            s.watcher_state.command_tx = Some(command_tx);
+            s.startup.mark(Subsystem::Watcher);
             Task::none()
         }
 
+        Message::WatcherFolderStatus(path, result) => {
+            if let Some(folder) = s.watcher_state.folder_of(&path) {
+                folder.status = match result {
+                    Ok(()) => FolderStatus::Watching,
+                    Err(e) => {
+                        warn!(target: "ui::watcher", path = %path.display(), error = %e, "Watched folder offline");
+                        FolderStatus::Offline(e)
+                    }
+                };
+            }
+            Task::none()
+        }
+
+        Message::WatchPathAdd => pick_folder_task(Message::WatchPathPicked),
+
+        Message::WatchPathPicked(Some(path)) => {
+            if s.watcher_state.folders.iter().any(|f| f.path == path) {
+                s.toasts
+                    .info(format!("{} is already in the library", path.display()));
+                return Task::none();
+            }
+            info!(target: "ui::watcher", path = %path.display(), "Watch path added");
+            s.watcher_state
+                .folders
+                .push(WatchedFolder::new(path.clone()));
+            Task::batch([
+                send_watch_command(s, WatchCommand::Watch(path)),
+                save_watch_paths(s),
+            ])
+        }
+
+        Message::WatchPathRemove(path) => {
+            info!(target: "ui::watcher", path = %path.display(), "Watch path removed");
+            s.watcher_state.folders.retain(|f| f.path != path);
+            Task::batch([
+                send_watch_command(s, WatchCommand::Unwatch(path)),
+                save_watch_paths(s),
+            ])
+        }
+
+        Message::WatchPathToggle(path) => {
+            let Some(folder) = s.watcher_state.folder_of(&path) else {
+                return Task::none();
+            };
+            folder.enabled = !folder.enabled;
+            folder.status = FolderStatus::Starting;
+            let command = if folder.enabled {
+                WatchCommand::Watch(path)
+            } else {
+                WatchCommand::Unwatch(path)
+            };
+            Task::batch([send_watch_command(s, command), save_watch_paths(s)])
+        }
+
         Message::WatcherStopped => {
             warn!(target: "ui::watcher", "Background file watcher stopped");
             s.watcher_state.active = false;
+            s.startup.mark(Subsystem::Watcher);
             Task::none()
         }
 
@@ -89,16 +148,28 @@ pub fn handle_watcher(s: &mut LoadedState, message: Message) -> Task<Message> {
                 WatchEvent::Created(path) => {
                     debug!(target: "ui::watcher", path = %path.display(), "File created");
                     s.watcher_state.pending_changes += 1;
+                    if let Some(folder) = s.watcher_state.folder_of(&path) {
+                        folder.created += 1;
+                        folder.last_event = Some(chrono::Local::now());
+                    }
                     handle_file_created(s, path)
                 }
                 WatchEvent::Modified(path) => {
                     debug!(target: "ui::watcher", path = %path.display(), "File modified");
                     s.watcher_state.pending_changes += 1;
+                    if let Some(folder) = s.watcher_state.folder_of(&path) {
+                        folder.modified += 1;
+                        folder.last_event = Some(chrono::Local::now());
+                    }
                     handle_file_modified(s, path)
                 }
                 WatchEvent::Removed(path) => {
                     debug!(target: "ui::watcher", path = %path.display(), "File removed");
                     s.watcher_state.pending_changes += 1;
+                    if let Some(folder) = s.watcher_state.folder_of(&path) {
+                        folder.removed += 1;
+                        folder.last_event = Some(chrono::Local::now());
+                    }
                     handle_file_removed(s, path)
                 }
                 WatchEvent::DirCreated(path) => {
@@ -109,6 +180,8 @@ pub fn handle_watcher(s: &mut LoadedState, message: Message) -> Task<Message> {
                 WatchEvent::Error(e) => {
                     warn!(target: "ui::watcher", error = %e, "Watcher error");
                     s.watcher_state.last_error = Some(e);
+                    // Also how a watcher that failed to start reports in
+                    s.startup.mark(Subsystem::Watcher);
                     Task::none()
                 }
             }
@@ -132,21 +205,114 @@ pub fn handle_watcher(s: &mut LoadedState, message: Message) -> Task<Message> {
     }
 }
 
+/// Pass a watch change to the running watcher. Without one (watching is
+/// off) the change only lands in the config, for the next start.
+fn send_watch_command(s: &LoadedState, command: WatchCommand) -> Task<Message> {
+    let Some(tx) = s.watcher_state.command_tx.clone() else {
+        return Task::none();
+    };
+    Task::perform(
+        async move {
+            let _ = tx.send(command).await;
+        },
+        |_| Message::Noop,
+    )
+}
+
+/// Take on the library folders of a reloaded config: folders still in it
+/// keep their status and counts, and the running watcher is told which to
+/// start and stop watching
+pub(super) fn sync_watch_paths(
+    s: &mut LoadedState,
+    library: &crate::config::LibraryConfig,
+) -> Task<Message> {
+    let wanted = WatcherState::from_config(library, get_user_music_folder()).folders;
+    let old = std::mem::take(&mut s.watcher_state.folders);
+    let was_watched = |path: &PathBuf| old.iter().any(|f| &f.path == path && f.enabled);
+
+    let mut commands = Vec::new();
+    for folder in &wanted {
+        if folder.enabled && !was_watched(&folder.path) {
+            commands.push(WatchCommand::Watch(folder.path.clone()));
+        }
+    }
+    for folder in &old {
+        if folder.enabled && !wanted.iter().any(|f| f.path == folder.path && f.enabled) {
+            commands.push(WatchCommand::Unwatch(folder.path.clone()));
+        }
+    }
+
+    s.watcher_state.folders = wanted
+        .into_iter()
+        .map(|folder| match old.iter().find(|f| f.path == folder.path) {
+            Some(kept) if kept.enabled == folder.enabled => kept.clone(),
+            // Turned on or off: counts stay, the status starts over
+            Some(kept) => WatchedFolder {
+                enabled: folder.enabled,
+                status: FolderStatus::Starting,
+                ..kept.clone()
+            },
+            None => folder,
+        })
+        .collect();
+    Task::batch(
+        commands
+            .into_iter()
+            .map(|command| send_watch_command(s, command)),
+    )
+}
+
+/// Persist the watched folders: all of them are library paths, the
+/// disabled ones are also listed as unwatched.
+fn save_watch_paths(s: &LoadedState) -> Task<Message> {
+    let folders = &s.watcher_state.folders;
+    let paths: Vec<PathBuf> = folders.iter().map(|f| f.path.clone()).collect();
+    let unwatched: Vec<PathBuf> = folders
+        .iter()
+        .filter(|f| !f.enabled)
+        .map(|f| f.path.clone())
+        .collect();
+    Task::perform(
+        async move {
+            let mut cfg = crate::config::load();
+            cfg.library.paths = paths;
+            cfg.library.unwatched_paths = unwatched;
+            crate::config::save_async(cfg).await
+        },
+        |result| {
+            if let Err(e) = result {
+                warn!(target: "ui::watcher", error = %e, "Failed to save watched folders");
+            }
+            Message::Noop
+        },
+    )
+}
+
 /// Handle a new file being created in the library.
 fn handle_file_created(s: &mut LoadedState, path: PathBuf) -> Task<Message> {
     let pool = s.pool.clone();
+    let popm_email = s.popm_email.clone();
+    let genre_map = s.genre_map.clone();
     let gardener_tx = s.gardener_state.command_tx.clone();
 
     Task::perform(
         async move {
             // Read metadata from the new file
-            let meta = match crate::metadata::read(&path) {
-                Ok(m) => m,
-                Err(e) => {
-                    warn!(target: "ui::watcher", path = %path.display(), error = %e, "Failed to read metadata");
-                    return path;
-                }
-            };
+            let (meta, genres, loudness, ratings, content, bitrate) =
+                match crate::metadata::read_for_index(&path) {
+                    Ok(read) => (
+                        read.metadata,
+                        genre_map.apply(&read.genres),
+                        read.loudness,
+                        read.ratings.resolve(&popm_email),
+                        read.content,
+                        read.bitrate,
+                    ),
+                    Err(e) => {
+                        warn!(target: "ui::watcher", path = %path.display(), error = %e, "Failed to read metadata");
+                        return path;
+                    }
+                };
 
             // Get file mtime
             let mtime = path
@@ -184,6 +350,11 @@ fn handle_file_created(s: &mut LoadedState, path: PathBuf) -> Task<Message> {
             {
                 Ok(id) => {
                     info!(target: "ui::watcher", path = %path.display(), title = %meta.title, "Track added to library");
+                    let _ = crate::db::update_track_loudness(&pool, id, &loudness).await;
+                    let _ = crate::db::update_track_ratings(&pool, id, &ratings).await;
+                    let _ = crate::db::update_track_content(&pool, id, &content).await;
+                    let _ = crate::db::update_track_genres(&pool, id, &genres).await;
+                    let _ = crate::db::update_track_bitrate(&pool, id, bitrate).await;
                     Some(id)
                 }
                 Err(e) => {
@@ -207,6 +378,8 @@ fn handle_file_created(s: &mut LoadedState, path: PathBuf) -> Task<Message> {
 /// Handle a file being modified in the library.
 fn handle_file_modified(s: &mut LoadedState, path: PathBuf) -> Task<Message> {
     let pool = s.pool.clone();
+    let popm_email = s.popm_email.clone();
+    let genre_map = s.genre_map.clone();
     let gardener_tx = s.gardener_state.command_tx.clone();
 
     Task::perform(
@@ -235,13 +408,21 @@ fn handle_file_modified(s: &mut LoadedState, path: PathBuf) -> Task<Message> {
                 }
 
                 // Re-read metadata and update
-                let meta = match crate::metadata::read(&path) {
-                    Ok(m) => m,
-                    Err(e) => {
-                        warn!(target: "ui::watcher", path = %path.display(), error = %e, "Failed to read metadata");
-                        return path;
-                    }
-                };
+                let (meta, genres, loudness, ratings, content, bitrate) =
+                    match crate::metadata::read_for_index(&path) {
+                        Ok(read) => (
+                            read.metadata,
+                            genre_map.apply(&read.genres),
+                            read.loudness,
+                            read.ratings.resolve(&popm_email),
+                            read.content,
+                            read.bitrate,
+                        ),
+                        Err(e) => {
+                            warn!(target: "ui::watcher", path = %path.display(), error = %e, "Failed to read metadata");
+                            return path;
+                        }
+                    };
 
                 // Get or create artist/album
                 let artist_id = if !meta.artist.is_empty() {
@@ -267,6 +448,11 @@ fn handle_file_modified(s: &mut LoadedState, path: PathBuf) -> Task<Message> {
                 {
                     Ok(id) => {
                         debug!(target: "ui::watcher", path = %path.display(), "Track updated");
+                        let _ = crate::db::update_track_loudness(&pool, id, &loudness).await;
+                        let _ = crate::db::update_track_ratings(&pool, id, &ratings).await;
+                        let _ = crate::db::update_track_content(&pool, id, &content).await;
+                        let _ = crate::db::update_track_genres(&pool, id, &genres).await;
+                        let _ = crate::db::update_track_bitrate(&pool, id, bitrate).await;
                         Some(id)
                     }
                     Err(e) => {
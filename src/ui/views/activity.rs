@@ -0,0 +1,260 @@
+//! Activity timeline pane - what the app changed in the library, by day.
+
+use iced::widget::{Space, button, column, container, row, scrollable, text, text_input};
+use iced::{Element, Length};
+
+use crate::activity::{ActivityEntry, ActivityKind};
+use crate::ui::icons::{self, icon_sized, spinner_frame};
+use crate::ui::messages::Message;
+use crate::ui::state::{ActivePane, ActivityState, LoadedState};
+use crate::ui::theme::{self, color, radius, spacing, typography};
+use crate::ui::views::helpers::filter_chip;
+
+/// Activity timeline pane
+pub fn activity_pane(s: &LoadedState) -> Element<'_, Message> {
+    let state = &s.activity;
+
+    let header = row![
+        column![
+            text("Activity")
+                .size(typography::SIZE_TITLE)
+                .color(color::TEXT_PRIMARY),
+            text("Everything Music Minder changed in your library")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(spacing::XS),
+        Space::with_width(Length::Fill),
+        button(
+            row![
+                icon_sized(icons::REFRESH, typography::SIZE_SMALL),
+                text("Refresh").size(typography::SIZE_SMALL),
+            ]
+            .spacing(spacing::XS)
+            .align_y(iced::Alignment::Center)
+        )
+        .padding([spacing::XS, spacing::MD])
+        .style(theme::button_secondary)
+        .on_press(Message::ActivityRefresh),
+        button(
+            row![
+                icon_sized(icons::FILE_EXPORT, typography::SIZE_SMALL),
+                text("Export").size(typography::SIZE_SMALL),
+            ]
+            .spacing(spacing::XS)
+            .align_y(iced::Alignment::Center)
+        )
+        .padding([spacing::XS, spacing::MD])
+        .style(theme::button_secondary)
+        .on_press(Message::ActivityExport),
+    ]
+    .spacing(spacing::SM)
+    .align_y(iced::Alignment::Center);
+
+    column![
+        header,
+        Space::with_height(spacing::MD),
+        filters(state),
+        Space::with_height(spacing::SM),
+        summary(s),
+        Space::with_height(spacing::SM),
+        timeline(state),
+    ]
+    .into()
+}
+
+/// Range, date, and kind filters
+fn filters(state: &ActivityState) -> Element<'_, Message> {
+    let ranges: [(&'static str, Option<u32>); 4] = [
+        ("24 hours", Some(1)),
+        ("7 days", Some(7)),
+        ("30 days", Some(30)),
+        ("All time", None),
+    ];
+    let range_chips: Vec<Element<Message>> = ranges
+        .into_iter()
+        .map(|(label, days)| {
+            let active = state.day.is_none() && state.days == days;
+            filter_chip(label, active, Message::ActivityRangeChanged(days))
+        })
+        .collect();
+
+    let date_input = text_input("Go to date (YYYY-MM-DD)", &state.date_input)
+        .on_input(Message::ActivityDateChanged)
+        .on_submit(Message::ActivityDateSubmitted)
+        .size(typography::SIZE_SMALL)
+        .padding([spacing::XS, spacing::SM])
+        .width(Length::Fixed(200.0));
+
+    let mut kind_chips: Vec<Element<Message>> = vec![filter_chip(
+        "All changes",
+        state.kind.is_none(),
+        Message::ActivityKindFilter(None),
+    )];
+    kind_chips.extend(ActivityKind::ALL.into_iter().map(|kind| {
+        let active = state.kind == Some(kind);
+        let msg = if active {
+            Message::ActivityKindFilter(None)
+        } else {
+            Message::ActivityKindFilter(Some(kind))
+        };
+        filter_chip(kind.label(), active, msg)
+    }));
+
+    column![
+        row![
+            row(range_chips).spacing(spacing::XS),
+            Space::with_width(spacing::MD),
+            date_input,
+        ]
+        .align_y(iced::Alignment::Center),
+        row(kind_chips).spacing(spacing::XS),
+    ]
+    .spacing(spacing::SM)
+    .into()
+}
+
+/// One-line description of what is being shown
+fn summary(s: &LoadedState) -> Element<'_, Message> {
+    let state = &s.activity;
+    if state.loading {
+        return text(format!("{} Loading...", spinner_frame(s.animation_tick)))
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED)
+            .into();
+    }
+
+    let period = match (state.day, state.days) {
+        (Some(day), _) => day.format("on %A %Y-%m-%d").to_string(),
+        (None, Some(1)) => "in the last 24 hours".to_string(),
+        (None, Some(days)) => format!("in the last {} days", days),
+        (None, None) => "since the log began".to_string(),
+    };
+    let count = state.entries.len();
+    let mut label = format!(
+        "{} change{} {}",
+        count,
+        if count == 1 { "" } else { "s" },
+        period
+    );
+    if count as u32 >= ActivityState::MAX_ENTRIES {
+        label.push_str(" (showing the most recent; export or narrow the range for more)");
+    }
+
+    text(label)
+        .size(typography::SIZE_SMALL)
+        .color(color::TEXT_SECONDARY)
+        .into()
+}
+
+/// Entries grouped under a heading per local day
+fn timeline(state: &ActivityState) -> Element<'_, Message> {
+    if state.entries.is_empty() && !state.loading {
+        return container(
+            text("No changes recorded for this period")
+                .size(typography::SIZE_BODY)
+                .color(color::TEXT_MUTED),
+        )
+        .padding(spacing::XL)
+        .center_x(Length::Fill)
+        .into();
+    }
+
+    let mut items: Vec<Element<Message>> = Vec::new();
+    let mut current_day = None;
+    for entry in &state.entries {
+        let day = entry.local_date();
+        if current_day != Some(day) {
+            if current_day.is_some() {
+                items.push(Space::with_height(spacing::SM).into());
+            }
+            items.push(
+                text(day.format("%A, %-d %B %Y").to_string())
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_PRIMARY)
+                    .into(),
+            );
+            current_day = Some(day);
+        }
+        items.push(entry_row(entry));
+    }
+
+    scrollable(column(items).spacing(spacing::XS).padding([0, spacing::SM]))
+        .id(ActivePane::Activity.scroll_id())
+        .on_scroll(|v| Message::PaneScrolled(ActivePane::Activity, v))
+        .height(Length::Fill)
+        .into()
+}
+
+/// A single timeline entry: time, kind badge, path, detail
+/// The entry's detail, and for tag writes what the save did to the file
+fn detail(entry: &ActivityEntry) -> Element<'_, Message> {
+    let detail = text(entry.detail.as_deref().unwrap_or(""))
+        .size(typography::SIZE_TINY)
+        .color(color::TEXT_MUTED);
+    let Some(audit) = &entry.audit else {
+        return container(detail).width(Length::FillPortion(2)).into();
+    };
+    let audit_color = if audit.verified {
+        color::TEXT_MUTED
+    } else {
+        color::ERROR
+    };
+    column![
+        detail,
+        text(audit.summary())
+            .size(typography::SIZE_TINY)
+            .color(audit_color),
+    ]
+    .width(Length::FillPortion(2))
+    .into()
+}
+
+fn entry_row(entry: &ActivityEntry) -> Element<'_, Message> {
+    let badge_color = match entry.kind {
+        ActivityKind::TrackAdded => color::SUCCESS,
+        ActivityKind::TrackRemoved => color::ERROR,
+        ActivityKind::TagsWritten => color::PRIMARY,
+        ActivityKind::FileOrganized => color::WARNING,
+        ActivityKind::RecordingMatched => color::TEXT_SECONDARY,
+        ActivityKind::PathNormalized => color::TEXT_MUTED,
+    };
+    let time = entry
+        .timestamp
+        .with_timezone(&chrono::Local)
+        .format("%H:%M")
+        .to_string();
+
+    let badge = container(
+        text(entry.kind.label())
+            .size(typography::SIZE_TINY)
+            .color(badge_color),
+    )
+    .padding([2, spacing::XS])
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: iced::Border {
+            radius: radius::SM.into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    row![
+        container(
+            text(time)
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED)
+        )
+        .width(Length::Fixed(40.0)),
+        container(badge).width(Length::Fixed(90.0)),
+        text(&entry.path)
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_SECONDARY)
+            .width(Length::FillPortion(3)),
+        detail(entry),
+    ]
+    .spacing(spacing::SM)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
@@ -0,0 +1,118 @@
+//! Context menu overlay.
+//!
+//! Renders the entries `ui::context_menu::actions` assembled for the open
+//! menu as a floating panel at the click position. A transparent layer
+//! behind the panel closes the menu on any click outside it.
+
+use iced::widget::{Space, button, column, container, mouse_area, row, text};
+use iced::{Element, Length, Padding};
+
+use crate::ui::context_menu::{self, ITEM_HEIGHT, MENU_PADDING, MENU_WIDTH, MenuAction};
+use crate::ui::icons::icon_sized;
+use crate::ui::messages::Message;
+use crate::ui::state::LoadedState;
+use crate::ui::theme::{color, radius, spacing, typography};
+
+/// The open context menu, if any
+pub fn context_menu_overlay(s: &LoadedState) -> Option<Element<'_, Message>> {
+    let menu = s.context_menu?;
+    let groups = context_menu::actions(s, menu.target);
+    if groups.is_empty() {
+        return None;
+    }
+
+    let origin = context_menu::menu_origin(
+        menu.position,
+        context_menu::menu_size(&groups),
+        s.window_size,
+    );
+
+    let mut entries = column![].spacing(0);
+    for (i, group) in groups.into_iter().enumerate() {
+        if i > 0 {
+            entries = entries.push(separator());
+        }
+        for action in group {
+            entries = entries.push(menu_item(action));
+        }
+    }
+
+    let panel = container(entries)
+        .width(Length::Fixed(MENU_WIDTH))
+        .padding(MENU_PADDING)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+            border: iced::Border {
+                color: color::BORDER_SUBTLE,
+                width: 1.0,
+                radius: radius::MD.into(),
+            },
+            shadow: iced::Shadow {
+                color: iced::Color::from_rgba(0.0, 0.0, 0.0, 0.4),
+                offset: iced::Vector::new(0.0, 4.0),
+                blur_radius: 12.0,
+            },
+            ..Default::default()
+        });
+
+    // The panel swallows its own clicks, so only clicks outside it close
+    let positioned = container(mouse_area(panel).on_press(Message::Noop))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .padding(Padding {
+            top: origin.y,
+            left: origin.x,
+            right: 0.0,
+            bottom: 0.0,
+        });
+
+    Some(
+        mouse_area(positioned)
+            .on_press(Message::ContextMenuClose)
+            .on_right_press(Message::ContextMenuClose)
+            .into(),
+    )
+}
+
+fn menu_item(action: MenuAction) -> Element<'static, Message> {
+    let fg = if action.destructive {
+        color::ERROR
+    } else {
+        color::TEXT_PRIMARY
+    };
+    button(
+        row![
+            container(icon_sized(action.icon, typography::SIZE_SMALL).color(fg))
+                .width(Length::Fixed(20.0)),
+            text(action.label).size(typography::SIZE_SMALL).color(fg),
+        ]
+        .spacing(spacing::SM)
+        .align_y(iced::Alignment::Center),
+    )
+    .width(Length::Fill)
+    .height(Length::Fixed(ITEM_HEIGHT))
+    .padding([0, spacing::SM])
+    .style(|_, status| button::Style {
+        background: matches!(status, button::Status::Hovered | button::Status::Pressed)
+            .then_some(iced::Background::Color(color::SURFACE_HOVER)),
+        border: iced::Border {
+            radius: radius::SM.into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .on_press(Message::ContextMenuSelect(action.messages))
+    .into()
+}
+
+fn separator() -> Element<'static, Message> {
+    container(
+        container(Space::new(Length::Fill, Length::Fixed(1.0))).style(|_| container::Style {
+            background: Some(iced::Background::Color(color::BORDER_SUBTLE)),
+            ..Default::default()
+        }),
+    )
+    .height(Length::Fixed(context_menu::SEPARATOR_HEIGHT))
+    .center_y(Length::Fixed(context_menu::SEPARATOR_HEIGHT))
+    .into()
+}
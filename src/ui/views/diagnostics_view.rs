@@ -3,15 +3,17 @@
 use iced::widget::{Space, button, column, container, row, scrollable, text};
 use iced::{Element, Length};
 
-use crate::diagnostics::{AudioReadiness, CheckStatus};
+use crate::diagnostics::{
+    AudioDeviceInfo, AudioDeviceType, AudioReadiness, CheckStatus, ExclusiveMode,
+};
 use crate::ui::icons::{self, icon_sized, spinner_frame};
 use crate::ui::messages::Message;
-use crate::ui::state::LoadedState;
+use crate::ui::state::{ActivePane, LoadedState};
 use crate::ui::theme::{self, color, layout, spacing, typography};
 
-/// Minimum animation phases to show (7 checks)
+/// Minimum animation phases to show (8 checks)
 #[allow(dead_code)]
-const _MIN_ANIMATION_PHASES: u32 = 7;
+const _MIN_ANIMATION_PHASES: u32 = 8;
 /// Ticks per phase at 60fps (~1.5 seconds each)
 const TICKS_PER_PHASE: u32 = 90;
 
@@ -82,6 +84,7 @@ fn running_view(s: &LoadedState, elapsed: u32) -> Element<'_, Message> {
         ("Memory Status", icons::MEMORY),
         ("Power Plan", icons::BOLT),
         ("Audio Devices", icons::HEADPHONES),
+        ("Library Database", icons::DATABASE),
         ("Finalizing...", icons::CIRCLE_CHECK),
     ];
 
@@ -216,7 +219,7 @@ fn results_view<'a>(
         let check_rows: Vec<Element<'a, Message>> = checks
             .iter()
             .map(|check| {
-                let is_expanded = s.diagnostics_expanded.contains(&check.name);
+                let is_expanded = s.panes.diagnostics.expanded.contains(&check.name);
                 check_row(check, is_expanded)
             })
             .collect();
@@ -234,6 +237,28 @@ fn results_view<'a>(
         sections.push(Space::with_height(spacing::LG).into());
     }
 
+    // Output device capabilities
+    let devices: Vec<Element<'a, Message>> = diag
+        .audio_devices
+        .iter()
+        .filter(|d| d.device_type == AudioDeviceType::Output)
+        .map(device_row)
+        .collect();
+    if !devices.is_empty() {
+        sections.push(
+            column![
+                text("Output Devices")
+                    .size(typography::SIZE_BODY)
+                    .color(color::TEXT_MUTED),
+                Space::with_height(spacing::SM),
+                column(devices).spacing(spacing::SM),
+            ]
+            .spacing(spacing::XS)
+            .into(),
+        );
+        sections.push(Space::with_height(spacing::LG).into());
+    }
+
     // Re-run button - uses primary style to match initial button
     let rerun_button = button(
         row![
@@ -278,6 +303,8 @@ fn results_view<'a>(
                 left: 0.0
             })
         )
+        .id(ActivePane::Diagnostics.scroll_id())
+        .on_scroll(|v| Message::PaneScrolled(ActivePane::Diagnostics, v))
         .height(Length::Fill),
     ]
     .spacing(spacing::XS)
@@ -423,6 +450,67 @@ fn get_check_explanation(name: &str, status: CheckStatus) -> (&'static str, &'st
             "Number of audio output devices detected on your system.",
             "Multiple devices give you flexibility in choosing where to output audio.",
         ),
+        "Library Sample Rate" => (
+            "Music Minder plays through the default output at the rate the device is set to, and resamples tracks at any other rate. This compares that rate with the one most of your library is at (from a sample of up to 200 files).",
+            match status {
+                CheckStatus::Pass => {
+                    "Most of your library plays at its own sample rate, without resampling."
+                }
+                _ => {
+                    "Most of your library is resampled on the way out. Resampling is high quality, but matching the device's rate to the library keeps playback bit-perfect."
+                }
+            },
+        ),
+        "Database Integrity" => (
+            "SQLite's own check of the library database: every page, row and index is read and cross-checked.",
+            match status {
+                CheckStatus::Pass => "The database is sound.",
+                _ => {
+                    "The database is damaged. Rebuilding the indexes fixes index problems; anything else needs a backup restored."
+                }
+            },
+        ),
+        "Orphan Rows" => (
+            "Rows that point at tracks, albums or artists no longer in the library: plays, matches and tag history of removed tracks, and albums and artists left without tracks.",
+            match status {
+                CheckStatus::Pass => "Nothing is left behind by removed tracks.",
+                _ => "They take space and can show up in statistics. Removing them is safe.",
+            },
+        ),
+        "Missing Files" => (
+            "Tracks in the library whose files aren't where the library says they are.",
+            match status {
+                CheckStatus::Pass => "Every track's file is in place.",
+                _ => {
+                    "If the files are on a drive that isn't connected, reconnect it. A library scan drops tracks whose files were deleted."
+                }
+            },
+        ),
+        "Duplicate Paths" => (
+            "Tracks stored twice under two spellings of one path, such as different letter case on Windows.",
+            match status {
+                CheckStatus::Pass => "Each file is one track.",
+                _ => {
+                    "Merging keeps the track whose file exists and moves the other's plays and rating onto it."
+                }
+            },
+        ),
+        "Cover Cache" => (
+            "Album covers downloaded for the player and track detail, kept on disk up to the configured limit.",
+            match status {
+                CheckStatus::Warning => {
+                    "The cache is nearly full, so older covers are evicted and downloaded again. Clear it or raise library.cover_cache_mb."
+                }
+                _ => "The cache has room to spare.",
+            },
+        ),
+        "Write-Ahead Log" => (
+            "SQLite writes changes to a log next to the database first and folds them in later. A log that keeps growing slows reads down.",
+            match status {
+                CheckStatus::Pass => "The log is small.",
+                _ => "A checkpoint folds the log into the database and shrinks it.",
+            },
+        ),
         _ => (
             "This diagnostic check provides information about your system's audio capabilities.",
             "See the value for current status.",
@@ -430,6 +518,62 @@ fn get_check_explanation(name: &str, status: CheckStatus) -> (&'static str, &'st
     }
 }
 
+/// An output device's name and what it supports
+fn device_row(device: &AudioDeviceInfo) -> Element<'_, Message> {
+    let khz = |rate: &u32| format!("{}", *rate as f64 / 1000.0);
+    let mut details = Vec::new();
+    if let Some(rate) = device.default_sample_rate {
+        details.push(format!("Running at {} kHz", khz(&rate)));
+    }
+    if !device.sample_rates.is_empty() {
+        let rates: Vec<String> = device.sample_rates.iter().map(khz).collect();
+        details.push(format!("Supports {} kHz", rates.join(", ")));
+    }
+    if !device.sample_formats.is_empty() {
+        details.push(device.sample_formats.join(", "));
+    }
+    if let Some((min, max)) = device.buffer_size {
+        details.push(format!("Buffer {}-{} frames", min, max));
+    }
+    if device.exclusive != ExclusiveMode::Unknown {
+        details.push(format!("Exclusive mode {}", device.exclusive.as_str()));
+    }
+
+    let name = if device.is_default {
+        format!("{} (default)", device.name)
+    } else {
+        device.name.clone()
+    };
+    container(
+        column![
+            row![
+                icon_sized(icons::HEADPHONES, typography::SIZE_BODY).color(color::TEXT_SECONDARY),
+                Space::with_width(spacing::SM),
+                text(name)
+                    .size(typography::SIZE_BODY)
+                    .color(color::TEXT_PRIMARY),
+            ]
+            .align_y(iced::Alignment::Center),
+            text(details.join(" · "))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+        ]
+        .spacing(spacing::XS),
+    )
+    .padding([spacing::SM, spacing::MD])
+    .width(Length::Fill)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE)),
+        border: iced::Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: 6.0.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
+
 /// Single check row with status, value, and expandable details
 fn check_row(
     check: &crate::diagnostics::DiagnosticCheck,
@@ -526,6 +670,18 @@ fn check_row(
                 .align_y(iced::Alignment::Center),
             );
         }
+
+        // Link to the maintenance tool that fixes it
+        if let Some(fix) = check.fix
+            && matches!(check.status, CheckStatus::Warning | CheckStatus::Fail)
+        {
+            content = content.push(
+                button(text(fix.label()).size(typography::SIZE_SMALL))
+                    .padding([spacing::XS, spacing::MD])
+                    .style(theme::button_secondary)
+                    .on_press(Message::DiagnosticsFix(fix)),
+            );
+        }
     }
 
     let check_name = check.name.clone();
@@ -7,6 +7,10 @@
 //! - Progress display during identification
 //! - Results list with confidence scores
 //! - Batch write actions
+//! - Writing guessed track numbers to tags
+//! - Auditing and normalizing album track/disc numbering
+//! - Reviewing suggestions held back from hand-edited fields
+//! - Verifying the tags of tagged tracks against their fingerprints
 
 mod results;
 mod selection;
@@ -15,6 +19,8 @@ mod status;
 use iced::widget::{Space, button, checkbox, column, container, row, text};
 use iced::{Element, Length};
 
+use crate::library::numbering::AlbumNumbering;
+use crate::provenance::TagConflict;
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
 use crate::ui::state::LoadedState;
@@ -46,7 +52,8 @@ pub fn enrich_pane(s: &LoadedState) -> Element<'_, Message> {
     let can_identify = enrich.fpcalc_available
         && !enrich.api_key.is_empty()
         && !enrich.selected_tracks.is_empty()
-        && !enrich.is_identifying;
+        && !enrich.is_identifying
+        && !enrich.verifying;
 
     let identify_btn = if enrich.is_identifying {
         button(
@@ -78,8 +85,46 @@ pub fn enrich_pane(s: &LoadedState) -> Element<'_, Message> {
         }
     };
 
-    // Progress section (visible during identification)
-    let progress = if enrich.is_identifying || !enrich.results.is_empty() {
+    // Check tagged tracks' title and artist against their fingerprints
+    let verify_btn = if enrich.verifying {
+        button(
+            row![
+                icon_sized(icons::SPINNER, typography::SIZE_BODY).color(color::TEXT_SECONDARY),
+                text("Verifying...").color(color::TEXT_SECONDARY),
+            ]
+            .spacing(spacing::SM)
+            .align_y(iced::Alignment::Center),
+        )
+        .padding([spacing::SM, spacing::LG])
+        .style(theme::button_secondary)
+    } else {
+        let btn = button(
+            row![
+                icon_sized(icons::CHECK_CIRCLE, typography::SIZE_BODY).color(color::TEXT_SECONDARY),
+                text("Verify Tags").color(color::TEXT_SECONDARY),
+            ]
+            .spacing(spacing::SM)
+            .align_y(iced::Alignment::Center),
+        )
+        .padding([spacing::SM, spacing::LG])
+        .style(theme::button_secondary);
+
+        if can_identify {
+            btn.on_press(Message::EnrichVerifyTags)
+        } else {
+            btn
+        }
+    };
+    let actions = row![identify_btn, verify_btn].spacing(spacing::SM);
+
+    // Progress section (visible during identification), or what the last
+    // verification found
+    let progress = if let Some(summary) = &enrich.verify_summary {
+        text(summary)
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED)
+            .into()
+    } else if enrich.is_identifying || !enrich.results.is_empty() {
         progress_section(enrich, s.animation_tick)
     } else {
         Space::new(0, 0).into()
@@ -92,6 +137,32 @@ pub fn enrich_pane(s: &LoadedState) -> Element<'_, Message> {
         Space::new(0, 0).into()
     };
 
+    // Guessed track numbers (visible when the scan inferred any)
+    let inferred = s.tracks.iter().filter(|t| t.track_number_inferred).count();
+    let track_numbers: Element<Message> = if inferred > 0 {
+        column![
+            inferred_numbers_section(inferred, enrich.writing_track_numbers),
+            Space::with_height(spacing::MD),
+        ]
+        .into()
+    } else {
+        Space::new(0, 0).into()
+    };
+
+    // Track/disc numbering audit of the batch's albums (or the library)
+    let numbering = numbering_section(s);
+
+    // Suggestions that disagree with hand-edited values
+    let conflicts: Element<Message> = if enrich.conflicts.is_empty() {
+        Space::new(0, 0).into()
+    } else {
+        column![
+            conflicts_section(&enrich.conflicts),
+            Space::with_height(spacing::MD),
+        ]
+        .into()
+    };
+
     // Batch actions (visible when we have confirmed results)
     let batch_actions = if enrich.has_confirmed_results() {
         batch_actions_section()
@@ -108,7 +179,11 @@ pub fn enrich_pane(s: &LoadedState) -> Element<'_, Message> {
         Space::with_height(spacing::MD),
         options,
         Space::with_height(spacing::MD),
-        identify_btn,
+        track_numbers,
+        numbering,
+        Space::with_height(spacing::MD),
+        conflicts,
+        actions,
         Space::with_height(spacing::LG),
         progress,
         results,
@@ -155,6 +230,294 @@ fn options_section(enrich: &crate::ui::state::EnrichmentPaneState) -> Element<'_
     .into()
 }
 
+/// Guessed track numbers - count and a button to write them to tags
+fn inferred_numbers_section(count: usize, writing: bool) -> Element<'static, Message> {
+    let label = if writing {
+        "Writing..."
+    } else {
+        "Write Track Numbers to Tags"
+    };
+    let write_btn = button(
+        row![
+            icon_sized(icons::FLOPPY, typography::SIZE_BODY).color(color::TEXT_SECONDARY),
+            text(label).color(color::TEXT_SECONDARY),
+        ]
+        .spacing(spacing::SM)
+        .align_y(iced::Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::LG])
+    .style(theme::button_secondary)
+    .on_press_maybe((!writing).then_some(Message::EnrichWriteInferredTrackNumbers));
+
+    let summary = if count == 1 {
+        "1 track number was guessed from its file name or folder order".to_string()
+    } else {
+        format!(
+            "{} track numbers were guessed from file names or folder order",
+            count
+        )
+    };
+
+    container(
+        column![
+            row![
+                text(summary)
+                    .size(typography::SIZE_BODY)
+                    .color(color::TEXT_PRIMARY),
+                Space::with_width(Length::Fill),
+                write_btn,
+            ]
+            .align_y(iced::Alignment::Center),
+            text("Only files without a track number tag are changed.")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(spacing::XS),
+    )
+    .padding(spacing::MD)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE)),
+        border: iced::Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: 6.0.into(),
+        },
+        ..Default::default()
+    })
+    .width(Length::Fill)
+    .into()
+}
+
+/// Most albums listed at once by the numbering audit
+const NUMBERING_SHOWN: usize = 20;
+
+/// Album numbering audit - a check button, then the albums that failed
+/// with their issues and a button to normalize each
+fn numbering_section(s: &LoadedState) -> Element<'_, Message> {
+    let enrich = &s.enrichment_pane;
+    let busy = enrich.numbering_busy;
+    let check_label = match (busy, enrich.selected_tracks.is_empty()) {
+        (true, _) => "Checking...",
+        (false, true) => "Check Library",
+        (false, false) => "Check Selected Albums",
+    };
+    let check_btn = button(
+        row![
+            icon_sized(icons::LIST_OL, typography::SIZE_BODY).color(color::TEXT_SECONDARY),
+            text(check_label).color(color::TEXT_SECONDARY),
+        ]
+        .spacing(spacing::SM)
+        .align_y(iced::Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::LG])
+    .style(theme::button_secondary)
+    .on_press_maybe((!busy).then_some(Message::EnrichAuditNumbering));
+
+    let fixable: usize = enrich.numbering.iter().map(|a| a.fixes.len()).sum();
+    let normalize_all: Element<Message> = if fixable > 0 {
+        button(
+            text(format!("Normalize All ({} files)", fixable))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_INVERSE),
+        )
+        .padding([spacing::SM, spacing::MD])
+        .style(theme::button_primary)
+        .on_press_maybe((!busy).then_some(Message::EnrichNormalizeNumbering(None)))
+        .into()
+    } else {
+        Space::with_width(0).into()
+    };
+
+    let summary = match enrich.numbering_checked {
+        None => {
+            "Check that track totals, track numbers and disc tags agree within albums".to_string()
+        }
+        Some(checked) if enrich.numbering.is_empty() => {
+            format!("All {} checked albums are consistent", checked)
+        }
+        Some(checked) => format!(
+            "{} of {} checked albums have inconsistent numbering",
+            enrich.numbering.len(),
+            checked
+        ),
+    };
+
+    let mut list = column![
+        text("ALBUM NUMBERING")
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+        row![
+            text(summary)
+                .size(typography::SIZE_BODY)
+                .color(color::TEXT_PRIMARY),
+            Space::with_width(Length::Fill),
+            normalize_all,
+            check_btn,
+        ]
+        .spacing(spacing::SM)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(spacing::XS);
+
+    for album in enrich.numbering.iter().take(NUMBERING_SHOWN) {
+        list = list.push(numbering_row(album, busy));
+    }
+    if enrich.numbering.len() > NUMBERING_SHOWN {
+        list = list.push(
+            text(format!(
+                "and {} more",
+                enrich.numbering.len() - NUMBERING_SHOWN
+            ))
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED),
+        );
+    }
+
+    container(list)
+        .padding(spacing::MD)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE)),
+            border: iced::Border {
+                color: color::BORDER_SUBTLE,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        })
+        .width(Length::Fill)
+        .into()
+}
+
+/// One album that failed the numbering audit
+fn numbering_row(album: &AlbumNumbering, busy: bool) -> Element<'_, Message> {
+    let mut details = column![
+        text(format!("{} · {}", album.album, album.artist))
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_PRIMARY),
+    ]
+    .spacing(2)
+    .width(Length::Fill);
+    for issue in &album.issues {
+        details = details.push(
+            text(issue.to_string())
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+        );
+    }
+
+    let action: Element<Message> = if album.fixes.is_empty() {
+        text("Needs a manual fix")
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED)
+            .into()
+    } else {
+        button(text(format!("Normalize ({})", album.fixes.len())).size(typography::SIZE_SMALL))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_secondary)
+            .on_press_maybe(
+                (!busy).then_some(Message::EnrichNormalizeNumbering(Some(album.album_id))),
+            )
+            .into()
+    };
+
+    row![details, action]
+        .spacing(spacing::SM)
+        .align_y(iced::Alignment::Center)
+        .into()
+}
+
+/// Most conflicts listed at once
+const CONFLICTS_SHOWN: usize = 20;
+
+/// Suggestions held back from fields the user set by hand, each with
+/// "keep mine" and "use suggested" buttons
+fn conflicts_section(conflicts: &[TagConflict]) -> Element<'_, Message> {
+    let mut list = column![
+        text("REVIEW HAND-EDITED FIELDS")
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+        text("New matches disagreed with values you set yourself, so they weren't written.")
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED),
+        Space::with_height(spacing::XS),
+    ]
+    .spacing(spacing::XS);
+
+    for conflict in conflicts.iter().take(CONFLICTS_SHOWN) {
+        let file = conflict
+            .path
+            .file_name()
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let confidence = conflict
+            .confidence
+            .map(|c| format!(", {:.0}%", c * 100.0))
+            .unwrap_or_default();
+        let details = column![
+            text(format!("{} · {}", file, conflict.field.replace('_', " ")))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_PRIMARY),
+            text(format!(
+                "Yours: {}",
+                conflict.current.as_deref().unwrap_or("(empty)")
+            ))
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_SECONDARY),
+            text(format!(
+                "Suggested: {} ({}{})",
+                conflict.proposed,
+                conflict.source.label(),
+                confidence
+            ))
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_SECONDARY),
+        ]
+        .spacing(2)
+        .width(Length::Fill);
+
+        list = list.push(
+            row![
+                details,
+                button(text("Keep Mine").size(typography::SIZE_SMALL))
+                    .padding([spacing::XS, spacing::SM])
+                    .style(theme::button_secondary)
+                    .on_press(Message::EnrichConflictKeep(conflict.id)),
+                button(
+                    text("Use Suggested")
+                        .size(typography::SIZE_SMALL)
+                        .color(color::TEXT_INVERSE)
+                )
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_primary)
+                .on_press(Message::EnrichConflictAccept(conflict.id)),
+            ]
+            .spacing(spacing::SM)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+    if conflicts.len() > CONFLICTS_SHOWN {
+        list = list.push(
+            text(format!("and {} more", conflicts.len() - CONFLICTS_SHOWN))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        );
+    }
+
+    container(list)
+        .padding(spacing::MD)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE)),
+            border: iced::Border {
+                color: color::BORDER_SUBTLE,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        })
+        .width(Length::Fill)
+        .into()
+}
+
 /// Progress section with determinate bar and fun messages
 fn progress_section(
     enrich: &crate::ui::state::EnrichmentPaneState,
@@ -217,7 +580,7 @@ fn progress_section(
     .into()
 }
 
-/// Batch actions - Write All Confirmed, Export Report
+/// Batch actions - Write All Confirmed, Export Report, Export Plan
 fn batch_actions_section() -> Element<'static, Message> {
     let write_all_btn = button(
         row![
@@ -243,9 +606,27 @@ fn batch_actions_section() -> Element<'static, Message> {
     .style(theme::button_secondary)
     .on_press(Message::EnrichExportReport);
 
+    let plan_btn = button(
+        row![
+            icon_sized(icons::FILE_EXPORT, typography::SIZE_BODY).color(color::TEXT_SECONDARY),
+            text("Export Plan").color(color::TEXT_SECONDARY),
+        ]
+        .spacing(spacing::SM)
+        .align_y(iced::Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::LG])
+    .style(theme::button_secondary)
+    .on_press(Message::EnrichExportPlan);
+
     container(
-        row![write_all_btn, Space::with_width(spacing::MD), export_btn,]
-            .align_y(iced::Alignment::Center),
+        row![
+            write_all_btn,
+            Space::with_width(spacing::MD),
+            export_btn,
+            Space::with_width(spacing::MD),
+            plan_btn,
+        ]
+        .align_y(iced::Alignment::Center),
     )
     .padding([spacing::LG, 0])
     .into()
@@ -7,6 +7,7 @@ use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
 use crate::ui::state::{EnrichmentPaneState, EnrichmentResult, ResultStatus};
 use crate::ui::theme::{self, color, spacing, typography};
+use crate::ui::views::helpers::error_badge;
 
 /// Results section showing identification outcomes
 pub fn results_section(enrich: &EnrichmentPaneState) -> Element<'_, Message> {
@@ -19,10 +20,9 @@ pub fn results_section(enrich: &EnrichmentPaneState) -> Element<'_, Message> {
     .align_y(iced::Alignment::Center);
 
     let results_list: Vec<Element<Message>> = enrich
-        .results
-        .iter()
-        .enumerate()
-        .map(|(i, result)| result_row(i, result))
+        .review_order()
+        .into_iter()
+        .map(|i| result_row(i, &enrich.results[i]))
         .collect();
 
     container(
@@ -79,11 +79,33 @@ fn result_row(index: usize, result: &EnrichmentResult) -> Element<'_, Message> {
     let title_text = result.title.as_deref().unwrap_or("Unknown");
 
     let changes_text: Element<Message> = if !result.changes.is_empty() {
-        let changes_str = result.changes.join(", ");
+        let mut changes_str = result.changes.join(", ");
+        // How the match compares with the tags already there
+        let scores = result.identification.as_ref().map(|id| id.scores);
+        if let Some(scores) = scores.filter(|s| *s != Default::default()) {
+            changes_str.push_str(&format!(" · vs tags: {}", scores));
+        }
+        let changes_color = if scores.is_some_and(|s| s.contradicts_tags()) {
+            color::WARNING
+        } else {
+            color::TEXT_MUTED
+        };
         text(changes_str)
             .size(typography::SIZE_TINY)
-            .color(color::TEXT_MUTED)
+            .color(changes_color)
             .into()
+    } else if let Some(failure) = &result.error {
+        // What went wrong, and whether another run might get further
+        let mut line = row![].spacing(spacing::SM).align_y(iced::Alignment::Center);
+        if let Some(class) = failure.class {
+            line = line.push(error_badge(class));
+        }
+        line.push(
+            text(&failure.message)
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED),
+        )
+        .into()
     } else {
         Space::new(0, 0).into()
     };
@@ -5,7 +5,7 @@ use iced::{Element, Length};
 
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::state::LoadedState;
+use crate::ui::state::{ActivePane, LoadedState};
 use crate::ui::theme::{self, color, spacing, typography};
 
 /// Track selection section with checkboxes
@@ -112,6 +112,8 @@ pub fn selection_section(s: &LoadedState) -> Element<'_, Message> {
             .collect();
 
         scrollable(column(items).spacing(1))
+            .id(ActivePane::Enrich.scroll_id())
+            .on_scroll(|v| Message::PaneScrolled(ActivePane::Enrich, v))
             .height(Length::Fixed(200.0))
             .into()
     };
@@ -3,12 +3,13 @@
 use iced::widget::{Space, container, row, text};
 use iced::{Element, Length};
 
+use crate::enrichment::budget::{BudgetStatus, CpuBudget};
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
 use crate::ui::state::{EnrichmentPaneState, RateLimitStatus};
 use crate::ui::theme::{color, spacing, typography};
 
-/// Status section showing fpcalc, API key, and rate limit status
+/// Status section showing fpcalc, API key, rate limit and CPU budget status
 pub fn status_section(enrich: &EnrichmentPaneState) -> Element<'_, Message> {
     // fpcalc status
     let fpcalc_status = status_indicator(
@@ -46,6 +47,8 @@ pub fn status_section(enrich: &EnrichmentPaneState) -> Element<'_, Message> {
             api_key_status,
             Space::with_width(spacing::LG),
             rate_status,
+            Space::with_width(spacing::LG),
+            budget_indicator(CpuBudget::global().status()),
         ]
         .align_y(iced::Alignment::Center),
     )
@@ -104,3 +107,38 @@ fn rate_limit_indicator(status: &RateLimitStatus) -> Element<'_, Message> {
     .align_y(iced::Alignment::Center)
     .into()
 }
+
+/// Fingerprint threads in use and their rough CPU share, or why they're paused
+fn budget_indicator(status: BudgetStatus) -> Element<'static, Message> {
+    let (icon, icon_color, label) = match status.paused {
+        Some(reason) => (
+            icons::CIRCLE_EXCLAIM,
+            color::WARNING,
+            format!("Fingerprinting paused: {}", reason.label()),
+        ),
+        None => (
+            icons::CIRCLE_CHECK,
+            if status.busy > 0 {
+                color::PRIMARY
+            } else {
+                color::SUCCESS
+            },
+            format!(
+                "Threads: {}/{} · ~{:.0}% CPU",
+                status.busy,
+                status.threads,
+                status.cpu_share() * 100.0
+            ),
+        ),
+    };
+
+    row![
+        icon_sized(icon, typography::SIZE_SMALL).color(icon_color),
+        Space::with_width(spacing::XS),
+        text(label)
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_SECONDARY),
+    ]
+    .align_y(iced::Alignment::Center)
+    .into()
+}
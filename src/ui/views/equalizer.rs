@@ -0,0 +1,106 @@
+//! Equalizer pane - the on/off switch, presets, and a slider per band.
+
+use iced::widget::{
+    Space, checkbox, column, container, pick_list, row, scrollable, text, vertical_slider,
+};
+use iced::{Alignment, Element, Length};
+
+use crate::player::equalizer::{self, EqPreset};
+use crate::ui::messages::Message;
+use crate::ui::state::{ActivePane, EqualizerState, LoadedState};
+use crate::ui::theme::{self, color, radius, spacing, typography};
+
+/// Height of the band sliders
+const SLIDER_HEIGHT: f32 = 220.0;
+
+/// Equalizer pane
+pub fn equalizer_pane(s: &LoadedState) -> Element<'_, Message> {
+    let eq = &s.equalizer;
+
+    let header = row![
+        column![
+            text("Equalizer")
+                .size(typography::SIZE_TITLE)
+                .color(color::TEXT_PRIMARY),
+            text("Shape the sound with ten bands from 31 Hz to 16 kHz. The level is lowered by the biggest boost, so boosting doesn't clip")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(spacing::XS),
+        Space::with_width(Length::Fill),
+        checkbox("On", eq.enabled)
+            .on_toggle(Message::EqToggled)
+            .text_size(typography::SIZE_BODY),
+        pick_list(EqPreset::ALL, Some(eq.preset), Message::EqPresetChosen)
+            .text_size(typography::SIZE_SMALL)
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::pick_list_icon_only)
+            .menu_style(theme::pick_list_menu),
+    ]
+    .spacing(spacing::MD)
+    .align_y(Alignment::Center);
+
+    let content = column![bands(eq)]
+        .spacing(spacing::MD)
+        .padding([0, spacing::SM]);
+
+    column![
+        header,
+        Space::with_height(spacing::MD),
+        scrollable(content)
+            .id(ActivePane::Equalizer.scroll_id())
+            .on_scroll(|v| Message::PaneScrolled(ActivePane::Equalizer, v))
+            .height(Length::Fill),
+    ]
+    .into()
+}
+
+/// The band sliders, gain above and frequency below
+fn bands(eq: &EqualizerState) -> Element<'_, Message> {
+    let (min, max) = equalizer::GAIN_RANGE_DB;
+    let gain_color = if eq.enabled {
+        color::TEXT_PRIMARY
+    } else {
+        color::TEXT_MUTED
+    };
+
+    let sliders: Vec<Element<Message>> = eq
+        .gains
+        .iter()
+        .enumerate()
+        .map(|(band, &gain)| {
+            let slider = vertical_slider(min..=max, gain, move |v| Message::EqBandChanged(band, v))
+                .on_release(Message::EqBandReleased)
+                .step(0.5)
+                .height(Length::Fixed(SLIDER_HEIGHT))
+                .style(theme::slider_style);
+            column![
+                text(format!("{:+.1}", gain))
+                    .size(typography::SIZE_SMALL)
+                    .color(gain_color),
+                slider,
+                text(equalizer::band_label(band))
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_MUTED),
+            ]
+            .spacing(spacing::SM)
+            .align_x(Alignment::Center)
+            .width(Length::Fill)
+            .into()
+        })
+        .collect();
+
+    container(row(sliders).spacing(spacing::SM))
+        .padding(spacing::LG)
+        .width(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(color::SURFACE_ELEVATED.into()),
+            border: iced::Border {
+                color: color::BORDER,
+                width: 1.0,
+                radius: radius::MD.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+}
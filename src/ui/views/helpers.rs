@@ -2,10 +2,75 @@
 
 use std::path::Path;
 
-use iced::widget::button;
+use iced::widget::{Space, button, container, pick_list, row, text};
+use iced::{Element, Length};
 
+use crate::error::ErrorClass;
 use crate::ui::messages::Message;
 use crate::ui::state::virtualization as virt;
+use crate::ui::theme::{self, color, radius, spacing, typography};
+
+/// Characters of a history entry shown in the dropdown
+const HISTORY_LABEL_CHARS: usize = 24;
+
+/// A previously used input value, shown shortened in a history dropdown
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    value: String,
+    label: String,
+}
+
+impl HistoryEntry {
+    fn new(value: String) -> Self {
+        // Keep the end: the last folders of a path say the most
+        let count = value.chars().count();
+        let label = if count > HISTORY_LABEL_CHARS {
+            let tail: String = value
+                .chars()
+                .skip(count - HISTORY_LABEL_CHARS + 1)
+                .collect();
+            format!("…{}", tail)
+        } else {
+            value.clone()
+        };
+        Self { value, label }
+    }
+}
+
+impl PartialEq for HistoryEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl std::fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+/// "Recent" dropdown (and a gap after it) next to an input, sending the
+/// picked value through the input's own change message. Nothing when there
+/// is no history yet.
+pub fn history_picker<'a>(
+    values: impl IntoIterator<Item = String>,
+    on_select: impl Fn(String) -> Message + 'a,
+) -> Element<'a, Message> {
+    let entries: Vec<HistoryEntry> = values.into_iter().map(HistoryEntry::new).collect();
+    if entries.is_empty() {
+        return Space::with_width(0).into();
+    }
+    let picker = pick_list(entries, None::<HistoryEntry>, move |entry| {
+        on_select(entry.value)
+    })
+    .placeholder("Recent")
+    .text_size(typography::SIZE_SMALL)
+    .padding(spacing::SM)
+    .width(Length::Fixed(170.0))
+    .style(theme::pick_list_icon_only)
+    .menu_style(theme::pick_list_menu);
+    row![picker, Space::with_width(spacing::SM)].into()
+}
 
 /// Helper to create a conditionally-enabled button
 pub fn action_button<'a>(
@@ -64,3 +129,99 @@ pub fn format_from_path(path: &str) -> &'static str {
 pub fn is_lossless(format: &str) -> bool {
     matches!(format, "FLAC" | "WAV" | "AIFF" | "APE" | "WV")
 }
+
+/// Format a Unix timestamp as a local `YYYY-MM-DD` date, or empty if unknown.
+pub fn format_date(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| {
+            dt.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Inline text that navigates when clicked (artist and album names)
+pub fn link<'a>(
+    content: impl Into<Element<'a, Message>>,
+    on_press: Message,
+) -> Element<'a, Message> {
+    button(content)
+        .padding(0)
+        .style(theme::button_link)
+        .on_press(on_press)
+        .into()
+}
+
+/// Creates a pill-shaped filter chip
+pub fn filter_chip<'a>(
+    label: impl Into<String>,
+    is_active: bool,
+    on_press: Message,
+) -> Element<'a, Message> {
+    let (bg, text_color, border_color) = if is_active {
+        (color::PRIMARY, color::TEXT_PRIMARY, color::PRIMARY)
+    } else {
+        (
+            color::SURFACE_ELEVATED,
+            color::TEXT_SECONDARY,
+            color::BORDER_SUBTLE,
+        )
+    };
+
+    button(text(label.into()).size(typography::SIZE_TINY))
+        .padding([spacing::XS, spacing::SM])
+        .style(move |_theme, status| {
+            let bg = match status {
+                button::Status::Hovered => {
+                    if is_active {
+                        color::PRIMARY_HOVER
+                    } else {
+                        color::SURFACE_HOVER
+                    }
+                }
+                button::Status::Pressed => {
+                    if is_active {
+                        color::PRIMARY_PRESSED
+                    } else {
+                        color::SURFACE_ELEVATED
+                    }
+                }
+                _ => bg,
+            };
+            button::Style {
+                background: Some(iced::Background::Color(bg)),
+                text_color,
+                border: iced::Border {
+                    color: border_color,
+                    width: 1.0,
+                    radius: radius::PILL.into(),
+                },
+                ..Default::default()
+            }
+        })
+        .on_press(on_press)
+        .into()
+}
+
+/// A pill naming an error's class: amber for failures worth retrying, red
+/// for those that won't change
+pub fn error_badge<'a>(class: ErrorClass) -> Element<'a, Message> {
+    let tint = if class.is_recoverable() {
+        color::WARNING
+    } else {
+        color::ERROR
+    };
+    container(text(class.label()).size(typography::SIZE_TINY).color(tint))
+        .padding([1.0, f32::from(spacing::SM)])
+        .style(move |_| container::Style {
+            border: iced::Border {
+                color: tint,
+                width: 1.0,
+                radius: radius::PILL.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+}
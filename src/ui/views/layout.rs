@@ -1,29 +1,56 @@
 //! Layout composition and main pane structure.
 
+use crate::ui::context_menu::ContextTarget;
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::state::{ActivePane, LoadedState};
-use crate::ui::theme::{self, color, layout, spacing, typography};
-use iced::widget::{Space, button, column, container, mouse_area, row, scrollable, text, tooltip};
+use crate::ui::state::{ActivePane, LibraryScope, LoadedState};
+use crate::ui::theme::{self, color, layout, radius, spacing, typography};
+use iced::widget::{
+    Space, button, column, container, mouse_area, pick_list, row, scrollable, text, tooltip,
+};
 use iced::{Element, Length, mouse::Interaction};
 
+use super::activity::activity_pane;
+use super::context_menu::context_menu_overlay;
 use super::diagnostics_view::diagnostics_pane;
 use super::enrich::enrich_pane;
+use super::equalizer::equalizer_pane;
+use super::helpers::link;
 use super::library::library_pane;
+use super::mini_player::mini_player_view;
+use super::now_playing::now_playing_view;
+use super::perf_overlay::perf_overlay;
 use super::player::player_controls;
+use super::playlists::playlists_pane;
 use super::settings::settings_pane;
+use super::stats::stats_pane;
+use super::tasks::{background_tasks_indicator, background_tasks_popover};
 use super::toast::toast_overlay;
 use super::track_detail::track_detail_modal;
 
 /// Main loaded state view - integrated layout with sidebar
 pub fn loaded_view(s: &LoadedState) -> Element<'_, Message> {
+    // Closing: the rest of the UI no longer takes input
+    if let Some(view) = shutdown_view(s) {
+        return view;
+    }
+
+    // The mini-player window has room for nothing else
+    if s.mini_player.active {
+        return mini_player_view(s);
+    }
+
     let sidebar = sidebar_view(s);
     let main_content = match s.active_pane {
         ActivePane::Library => library_pane(s),
+        ActivePane::Playlists => playlists_pane(s),
         ActivePane::NowPlaying => now_playing_pane(s),
+        ActivePane::Equalizer => equalizer_pane(s),
         ActivePane::Enrich => enrich_pane(s),
         ActivePane::Settings => settings_pane(s),
         ActivePane::Diagnostics => diagnostics_pane(s),
+        ActivePane::Activity => activity_pane(s),
+        ActivePane::Stats => stats_pane(s),
     };
 
     // Player controls always visible at bottom
@@ -47,11 +74,32 @@ pub fn loaded_view(s: &LoadedState) -> Element<'_, Message> {
     // Build stack of overlays
     let mut layers: Vec<Element<'_, Message>> = vec![base_layout];
 
+    // Full-screen Now Playing view covers the layout (but not the modals);
+    // opaque so clicks and scrolls don't reach the panes underneath
+    if let Some(view) = now_playing_view(s) {
+        layers.push(iced::widget::opaque(view));
+    }
+
     // Track detail modal (if open)
     if let Some(modal) = track_detail_modal(s) {
         layers.push(modal);
     }
 
+    // Background tasks popover
+    if let Some(popover) = background_tasks_popover(s) {
+        layers.push(popover);
+    }
+
+    // Right-click menu (above everything but toasts)
+    if let Some(menu) = context_menu_overlay(s) {
+        layers.push(menu);
+    }
+
+    // Performance overlay (F12)
+    if let Some(overlay) = perf_overlay(s) {
+        layers.push(overlay);
+    }
+
     // Toast notifications (always on top)
     if let Some(toasts) = toast_overlay(&s.toasts) {
         layers.push(toasts);
@@ -64,6 +112,107 @@ pub fn loaded_view(s: &LoadedState) -> Element<'_, Message> {
     }
 }
 
+/// Placeholder for the layout, shown from the first frame while the
+/// database opens
+pub fn startup_skeleton() -> Element<'static, Message> {
+    let bar = |width: Length, height: f32| -> Element<'static, Message> {
+        container(Space::new(width, Length::Fixed(height)))
+            .style(|_| container::Style {
+                background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+                border: iced::Border {
+                    radius: radius::SM.into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .into()
+    };
+
+    let sidebar = container(
+        column![
+            container(
+                text("Music Minder")
+                    .size(typography::SIZE_TITLE)
+                    .color(color::TEXT_PRIMARY)
+            )
+            .padding([spacing::SM, 0]),
+            Space::with_height(spacing::MD),
+            sidebar_divider(),
+            Space::with_height(spacing::MD),
+        ]
+        .extend((0..6).map(|_| bar(Length::Fill, 28.0)))
+        .spacing(spacing::XS),
+    )
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE)),
+        border: iced::Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: 0.0.into(),
+        },
+        ..Default::default()
+    })
+    .padding(spacing::MD)
+    .height(Length::Fill)
+    .width(Length::Fixed(layout::SIDEBAR_WIDTH as f32));
+
+    // Rows of varying length, like a track list
+    let rows = column((0..12u16).map(|i| bar(Length::FillPortion(6 + (i * 7) % 4), 18.0)))
+        .spacing(spacing::MD)
+        .width(Length::Fill);
+    let main = column![
+        text("Opening library…")
+            .size(typography::SIZE_HEADING)
+            .color(color::TEXT_MUTED),
+        Space::with_height(spacing::MD),
+        rows,
+    ]
+    .padding(spacing::LG)
+    .width(Length::Fill)
+    .height(Length::Fill);
+
+    row![sidebar, main].into()
+}
+
+/// "Finishing up" screen shown between the close request and exit
+fn shutdown_view(s: &LoadedState) -> Option<Element<'_, Message>> {
+    let shutdown = s.shutdown.as_ref()?;
+    let detail = if shutdown.waiting_on.is_empty() {
+        "Saving your session".to_string()
+    } else {
+        format!("Waiting for: {}", shutdown.waiting_on.join(", "))
+    };
+    Some(
+        container(
+            column![
+                text(format!(
+                    "{} Finishing up…",
+                    icons::spinner_frame(s.animation_tick)
+                ))
+                .size(typography::SIZE_HEADING)
+                .color(color::TEXT_PRIMARY),
+                text(detail)
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_MUTED),
+                text("Close again to quit now")
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_MUTED),
+            ]
+            .spacing(spacing::SM)
+            .align_x(iced::Alignment::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::BASE)),
+            ..Default::default()
+        })
+        .into(),
+    )
+}
+
 /// Watcher status indicator - shows if background scanning is active
 fn watcher_status_indicator(s: &LoadedState, collapsed: bool) -> Element<'_, Message> {
     if collapsed {
@@ -135,6 +284,83 @@ fn watcher_status_indicator(s: &LoadedState, collapsed: bool) -> Element<'_, Mes
     }
 }
 
+/// Subsystems still starting in the background (nothing once all are up)
+fn startup_indicator(s: &LoadedState, collapsed: bool) -> Element<'_, Message> {
+    let pending = s.startup.pending();
+    if pending.is_empty() {
+        return Space::with_height(0).into();
+    }
+    let names: Vec<&str> = pending.iter().map(|p| p.label()).collect();
+    let label = format!("Starting {}", names.join(", "));
+    let spinner = text(icons::spinner_frame(s.animation_tick))
+        .size(typography::SIZE_SMALL)
+        .color(color::TEXT_MUTED);
+    if collapsed {
+        tooltip(
+            container(spinner).center_x(Length::Fill),
+            text(label).size(typography::SIZE_SMALL),
+            tooltip::Position::Right,
+        )
+        .gap(spacing::SM as f32)
+        .into()
+    } else {
+        row![
+            spinner,
+            Space::with_width(spacing::XS),
+            text(label)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .align_y(iced::Alignment::Center)
+        .into()
+    }
+}
+
+/// Read-only mode indicator (empty when the library is writable)
+fn read_only_indicator(collapsed: bool) -> Element<'static, Message> {
+    if !crate::readonly::is_enabled() {
+        return Space::with_height(0).into();
+    }
+    let lock = icon_sized(icons::LOCK, typography::SIZE_SMALL).color(color::WARNING);
+    if collapsed {
+        tooltip(
+            container(lock).center_x(Length::Fill),
+            text("Read-only: library changes are disabled").size(typography::SIZE_SMALL),
+            tooltip::Position::Right,
+        )
+        .gap(spacing::SM as f32)
+        .into()
+    } else {
+        row![
+            lock,
+            Space::with_width(spacing::XS),
+            text("Read-only")
+                .size(typography::SIZE_SMALL)
+                .color(color::WARNING),
+        ]
+        .align_y(iced::Alignment::Center)
+        .into()
+    }
+}
+
+/// Profile switcher under the app title (empty with only one profile)
+fn profile_switcher(s: &LoadedState) -> Element<'_, Message> {
+    if s.profiles.len() < 2 {
+        return Space::with_height(0).into();
+    }
+    pick_list(
+        s.profiles.as_slice(),
+        Some(crate::profile::active()),
+        Message::SwitchProfile,
+    )
+    .text_size(typography::SIZE_SMALL)
+    .padding([spacing::XS, spacing::SM])
+    .width(Length::Fill)
+    .style(theme::pick_list_icon_only)
+    .menu_style(theme::pick_list_menu)
+    .into()
+}
+
 /// Horizontal divider for sidebar sections
 fn sidebar_divider() -> Element<'static, Message> {
     container(Space::new(Length::Fill, Length::Fixed(1.0)))
@@ -156,10 +382,14 @@ fn sidebar_view(s: &LoadedState) -> Element<'_, Message> {
     };
 
     let is_library = s.active_pane == ActivePane::Library;
+    let is_playlists = s.active_pane == ActivePane::Playlists;
     let is_playing = s.active_pane == ActivePane::NowPlaying;
+    let is_equalizer = s.active_pane == ActivePane::Equalizer;
     let is_enrich = s.active_pane == ActivePane::Enrich;
     let is_settings = s.active_pane == ActivePane::Settings;
     let is_diagnostics = s.active_pane == ActivePane::Diagnostics;
+    let is_activity = s.active_pane == ActivePane::Activity;
+    let is_stats = s.active_pane == ActivePane::Stats;
 
     // Track count for stats section
     let track_count = s.tracks.len();
@@ -296,14 +526,31 @@ fn sidebar_view(s: &LoadedState) -> Element<'_, Message> {
                 is_playing,
                 ActivePane::NowPlaying
             ),
+            nav_button(
+                icons::SLIDERS,
+                "Equalizer",
+                is_equalizer,
+                ActivePane::Equalizer
+            ),
             nav_button(icons::LIST, "Library", is_library, ActivePane::Library),
+            nav_button(
+                icons::LIST_OL,
+                "Playlists",
+                is_playlists,
+                ActivePane::Playlists
+            ),
             nav_button(icons::WAND, "Enrich", is_enrich, ActivePane::Enrich),
+            nav_button(icons::CLOCK, "Activity", is_activity, ActivePane::Activity),
+            nav_button(icons::CHART, "Stats", is_stats, ActivePane::Stats),
             nav_button(icons::GEAR, "Settings", is_settings, ActivePane::Settings),
             Space::with_height(Length::Fill),
             // Status section (compact)
             sidebar_divider(),
             Space::with_height(spacing::SM),
             watcher_status_indicator(s, true),
+            startup_indicator(s, true),
+            background_tasks_indicator(s, true),
+            read_only_indicator(true),
             Space::with_height(spacing::XS),
             // Track count as icon with tooltip
             tooltip(
@@ -370,6 +617,7 @@ fn sidebar_view(s: &LoadedState) -> Element<'_, Message> {
                     .color(color::TEXT_PRIMARY)
             )
             .padding([spacing::SM, 0]),
+            profile_switcher(s),
             Space::with_height(spacing::MD),
             sidebar_divider(),
             Space::with_height(spacing::MD),
@@ -380,8 +628,22 @@ fn sidebar_view(s: &LoadedState) -> Element<'_, Message> {
                 is_playing,
                 ActivePane::NowPlaying
             ),
+            nav_button(
+                icons::SLIDERS,
+                "Equalizer",
+                is_equalizer,
+                ActivePane::Equalizer
+            ),
             nav_button(icons::LIST, "Library", is_library, ActivePane::Library),
+            nav_button(
+                icons::LIST_OL,
+                "Playlists",
+                is_playlists,
+                ActivePane::Playlists
+            ),
             nav_button(icons::WAND, "Enrich", is_enrich, ActivePane::Enrich),
+            nav_button(icons::CLOCK, "Activity", is_activity, ActivePane::Activity),
+            nav_button(icons::CHART, "Stats", is_stats, ActivePane::Stats),
             nav_button(icons::GEAR, "Settings", is_settings, ActivePane::Settings),
             Space::with_height(Length::Fill),
             // Stats section header
@@ -393,6 +655,8 @@ fn sidebar_view(s: &LoadedState) -> Element<'_, Message> {
             Space::with_height(spacing::SM),
             // Watcher status with icon
             watcher_status_indicator(s, false),
+            startup_indicator(s, false),
+            background_tasks_indicator(s, false),
             // Track count
             row![
                 icon_sized(icons::DISC, typography::SIZE_SMALL).color(color::TEXT_MUTED),
@@ -402,6 +666,7 @@ fn sidebar_view(s: &LoadedState) -> Element<'_, Message> {
                     .color(color::TEXT_MUTED),
             ]
             .align_y(iced::Alignment::Center),
+            read_only_indicator(false),
             Space::with_height(spacing::MD),
             sidebar_divider(),
             Space::with_height(spacing::MD),
@@ -577,14 +842,23 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                 .size(typography::SIZE_HERO)
                 .color(color::TEXT_PRIMARY),
             Space::with_height(spacing::XS),
-            // Artist
-            text(artist_name)
-                .size(typography::SIZE_TITLE)
-                .color(color::TEXT_SECONDARY),
-            // Album
-            text(album_name)
-                .size(typography::SIZE_HEADING)
-                .color(color::TEXT_MUTED),
+            // Artist (goes to the artist's tracks)
+            link(
+                text(artist_name.clone())
+                    .size(typography::SIZE_TITLE)
+                    .color(color::TEXT_SECONDARY),
+                Message::GoTo(LibraryScope::Artist(artist_name.clone())),
+            ),
+            // Album (goes to the album)
+            link(
+                text(album_name.clone())
+                    .size(typography::SIZE_HEADING)
+                    .color(color::TEXT_MUTED),
+                Message::GoTo(LibraryScope::Album {
+                    album: album_name,
+                    artist: artist_name,
+                }),
+            ),
             Space::with_height(spacing::LG),
             // Format info with lossless badge
             row![
@@ -622,7 +896,7 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
 
     // Queue display with controls
     let queue_section = {
-        let (queue_len, current_idx, shuffle_on, repeat_mode) = s
+        let (queue_len, current_idx, shuffle_on, shuffle_mode, repeat_mode) = s
             .player
             .as_ref()
             .map(|p| {
@@ -630,10 +904,17 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                     p.queue().items().len(),
                     p.queue().current_index(),
                     p.queue().shuffle(),
+                    p.queue().shuffle_mode(),
                     p.queue().repeat(),
                 )
             })
-            .unwrap_or((0, None, false, crate::player::RepeatMode::Off));
+            .unwrap_or((
+                0,
+                None,
+                false,
+                crate::player::ShuffleMode::default(),
+                crate::player::RepeatMode::Off,
+            ));
 
         // Track position indicator (e.g., "Track 3 of 25")
         let position_text = if let Some(idx) = current_idx {
@@ -656,6 +937,15 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
             .padding([spacing::XS, spacing::SM])
             .style(shuffle_style)
             .on_press(Message::QueueToggleShuffle);
+        let shuffle_mode_picker = pick_list(
+            crate::player::ShuffleMode::ALL,
+            Some(shuffle_mode),
+            Message::QueueSetShuffleMode,
+        )
+        .text_size(typography::SIZE_SMALL)
+        .padding([spacing::XS, spacing::SM])
+        .style(theme::pick_list_icon_only)
+        .menu_style(theme::pick_list_menu);
 
         // Repeat button with mode indicator
         let repeat_icon = match repeat_mode {
@@ -673,6 +963,61 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
             .style(repeat_style)
             .on_press(Message::QueueCycleRepeat);
 
+        // What plays once the queue runs out
+        let end_of_queue_picker = tooltip(
+            pick_list(
+                crate::player::EndOfQueue::ALL,
+                Some(s.end_of_queue),
+                Message::QueueSetEndOfQueue,
+            )
+            .text_size(typography::SIZE_SMALL)
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::pick_list_icon_only)
+            .menu_style(theme::pick_list_menu),
+            text("When the queue ends: stop, start over, add similar tracks, or library radio")
+                .size(typography::SIZE_TINY),
+            tooltip::Position::Bottom,
+        )
+        .gap(spacing::XS)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+            border: iced::Border {
+                color: color::BORDER,
+                width: 1.0,
+                radius: 4.0.into(),
+            },
+            ..Default::default()
+        });
+
+        // Stop-after toggle for the current track
+        let stop_after_on = s.player.as_ref().is_some_and(|p| {
+            p.queue().current_index().is_some()
+                && p.queue().stop_after_index() == p.queue().current_index()
+        });
+        let stop_after_btn = tooltip(
+            button(icon_sized(icons::STOP, typography::SIZE_SMALL))
+                .padding([spacing::XS, spacing::SM])
+                .style(if stop_after_on {
+                    theme::button_active
+                } else {
+                    theme::button_ghost
+                })
+                .on_press(Message::PlayerStopAfterTrack),
+            text("Stop after this track (Ctrl+S, Ctrl+Shift+S for the album)")
+                .size(typography::SIZE_TINY),
+            tooltip::Position::Bottom,
+        )
+        .gap(spacing::XS)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+            border: iced::Border {
+                color: color::BORDER,
+                width: 1.0,
+                radius: 4.0.into(),
+            },
+            ..Default::default()
+        });
+
         // Clear button
         let clear_btn = button(icon_sized(icons::XMARK, typography::SIZE_SMALL))
             .padding([spacing::XS, spacing::SM])
@@ -685,7 +1030,10 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                 .color(color::TEXT_PRIMARY),
             Space::with_width(spacing::MD),
             shuffle_btn,
+            shuffle_mode_picker,
             repeat_btn,
+            end_of_queue_picker,
+            stop_after_btn,
             Space::with_width(Length::Fill),
             position_text,
             Space::with_width(spacing::SM),
@@ -694,7 +1042,14 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
         .align_y(iced::Alignment::Center);
 
         let queue_selection = s.queue_selection;
+        // Insertion line: drawn in the gap before row `drop_gap` (after the
+        // last row when it equals the queue length)
+        let drop_gap = s
+            .queue_drag
+            .drop_target
+            .filter(|_| s.queue_drag.drop_changes_order());
         let queue_list = if let Some(ref player) = s.player {
+            let queue_len = player.queue().len();
             let items: Vec<Element<Message>> = player
                 .queue()
                 .items()
@@ -702,31 +1057,19 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                 .enumerate()
                 .map(|(i, item)| {
                     let is_current = player.queue().current_index() == Some(i);
-                    let is_selected = queue_selection == Some(i);
+                    let is_selected = s.is_queue_selected(i);
+                    let is_cursor = queue_selection == Some(i);
 
                     // Check if this item is being dragged (for visual feedback)
                     let is_being_dragged = s
                         .queue_drag
                         .dragging
                         .as_ref()
-                        .map(|d| d.index == i)
+                        .map(|d| d.indices.contains(&i))
                         .unwrap_or(false);
 
-                    // Check if drop indicator should show ABOVE or BELOW this item
-                    // When moving UP (target < origin): show indicator ABOVE target
-                    // When moving DOWN (target > origin): show indicator BELOW target
-                    let (show_indicator_above, show_indicator_below) =
-                        if let Some(ref drag) = s.queue_drag.dragging {
-                            let target = s.queue_drag.drop_target;
-                            let moving_down = target.map(|t| t > drag.index).unwrap_or(false);
-                            let moving_up = target.map(|t| t < drag.index).unwrap_or(false);
-
-                            let above = moving_up && target == Some(i);
-                            let below = moving_down && target == Some(i);
-                            (above, below)
-                        } else {
-                            (false, false)
-                        };
+                    let show_indicator_above = drop_gap == Some(i);
+                    let show_indicator_below = drop_gap == Some(queue_len) && i + 1 == queue_len;
 
                     // Priority: keyboard selection > current playing > alternating
                     // Dimmed if being dragged
@@ -763,7 +1106,7 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                         icon_sized(icons::PLAY, typography::SIZE_TINY)
                             .color(color::PRIMARY)
                             .into()
-                    } else if is_selected {
+                    } else if is_cursor {
                         icon_sized(icons::CHEVRON_RIGHT, typography::SIZE_TINY)
                             .color(color::PRIMARY)
                             .into()
@@ -790,6 +1133,26 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
 
                     let grip_handle: Element<Message> = grip_area.into();
 
+                    // Stop-after toggle; highlighted on the marked item
+                    let stop_after_btn = button(
+                        icon_sized(icons::STOP, typography::SIZE_TINY).color(if item.stop_after {
+                            color::WARNING
+                        } else {
+                            color::TEXT_MUTED
+                        }),
+                    )
+                    .padding([spacing::XS, spacing::SM])
+                    .style(theme::button_ghost)
+                    .on_press(Message::QueueToggleStopAfter(i));
+                    let stop_label: Element<Message> = if item.stop_after {
+                        text("Stops after")
+                            .size(typography::SIZE_TINY)
+                            .color(color::WARNING)
+                            .into()
+                    } else {
+                        Space::with_width(0).into()
+                    };
+
                     // Remove button for this item
                     let remove_btn = button(icon_sized(icons::XMARK, typography::SIZE_TINY))
                         .padding([spacing::XS, spacing::SM])
@@ -802,6 +1165,7 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                             container(index_widget).width(Length::Fixed(24.0)),
                             text(display_text).size(typography::SIZE_SMALL).color(fg),
                             Space::with_width(Length::Fill),
+                            stop_label,
                         ]
                         .align_y(iced::Alignment::Center),
                     )
@@ -818,7 +1182,9 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                             ..Default::default()
                         }
                     })
-                    .on_press(Message::QueueJumpTo(i));
+                    .on_press(Message::QueueItemClicked(i));
+                    let track_btn = mouse_area(track_btn)
+                        .on_right_press(Message::ContextMenuOpen(ContextTarget::QueueItem(i)));
 
                     // Wrap grip handle in a container for consistent sizing
                     let grip_container = container(grip_handle)
@@ -828,6 +1194,7 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                     let queue_row = row![
                         grip_container,
                         track_btn,
+                        stop_after_btn,
                         remove_btn,
                         Space::with_width(spacing::SM)
                     ]
@@ -845,10 +1212,8 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
                     };
 
                     if show_indicator_above {
-                        // Moving UP: indicator above target shows where item lands
                         column![drop_line(), queue_row].spacing(0).into()
                     } else if show_indicator_below {
-                        // Moving DOWN: indicator below target shows where item lands
                         column![queue_row, drop_line()].spacing(0).into()
                     } else {
                         queue_row.into()
@@ -879,7 +1244,10 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
 
         // Wrap queue list in MouseArea for drag tracking
         let is_dragging = s.queue_drag.dragging.is_some();
-        let queue_scrollable = scrollable(queue_list).height(Length::Fill);
+        let queue_scrollable = scrollable(queue_list)
+            .id(ActivePane::NowPlaying.scroll_id())
+            .on_scroll(|v| Message::PaneScrolled(ActivePane::NowPlaying, v))
+            .height(Length::Fill);
 
         // When dragging, track mouse moves and handle release anywhere
         let queue_with_drag: Element<Message> = if is_dragging {
@@ -892,8 +1260,18 @@ fn now_playing_pane(s: &LoadedState) -> Element<'_, Message> {
             queue_scrollable.into()
         };
 
+        // Spoken-style description of the last reorder/selection change
+        let announcement: Element<Message> = match &s.queue_announcement {
+            Some(msg) => text(msg)
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED)
+                .into(),
+            None => Space::with_height(0).into(),
+        };
+
         column![
             queue_header,
+            announcement,
             Space::with_height(spacing::SM),
             queue_with_drag,
         ]
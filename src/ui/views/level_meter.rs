@@ -0,0 +1,201 @@
+//! Level meters: the peak/RMS bars and clip indicator in the player bar, and
+//! the level history chart in the audio settings.
+//!
+//! Levels are measured in the audio callback before volume, so they show the
+//! track itself: a clip here means the decoded audio reached full scale.
+
+use std::time::Instant;
+
+use iced::mouse::Cursor;
+use iced::widget::canvas::{self, Canvas, Frame, Geometry, Path};
+use iced::widget::{Space, column, container, row, text, tooltip};
+use iced::{Border, Color, Element, Length, Point, Rectangle, Size, Theme};
+
+use crate::ui::messages::Message;
+use crate::ui::state::{LEVEL_HISTORY_LEN, LevelMeterState, LevelPoint, METER_FLOOR_DB, to_dbfs};
+use crate::ui::theme::{color, spacing, typography};
+
+/// Width of each meter bar in the player bar
+const METER_WIDTH: f32 = 56.0;
+
+/// Height of each meter bar
+const METER_HEIGHT: f32 = 4.0;
+
+/// Where a level falls on the meter's dB scale (0.0 - 1.0)
+fn meter_fraction(level: f32) -> f32 {
+    (to_dbfs(level) - METER_FLOOR_DB) / -METER_FLOOR_DB
+}
+
+/// Green up to -6 dBFS, amber up to -1 dBFS, red above
+fn level_color(level: f32) -> Color {
+    let db = to_dbfs(level);
+    if db >= -1.0 {
+        color::ERROR
+    } else if db >= -6.0 {
+        color::WARNING
+    } else {
+        color::SUCCESS
+    }
+}
+
+/// Left/right meters and the clip indicator, for the player bar
+pub fn level_meter(meter: &LevelMeterState) -> Element<'_, Message> {
+    let bars = column![
+        meter_bar(meter.current.rms[0], meter.held_peak(0)),
+        meter_bar(meter.current.rms[1], meter.held_peak(1)),
+    ]
+    .spacing(2);
+
+    let clipping = meter.is_clipping(Instant::now());
+    let clip_label = text("CLIP").size(typography::SIZE_TINY).color(if clipping {
+        color::ERROR
+    } else {
+        color::with_alpha(color::TEXT_MUTED, 0.4)
+    });
+    let clip_tip = if meter.track_clips == 0 {
+        "No clipping in this track".to_string()
+    } else {
+        format!("{} clipped samples in this track", meter.track_clips)
+    };
+
+    row![
+        bars,
+        tooltip(
+            clip_label,
+            text(clip_tip).size(typography::SIZE_SMALL),
+            tooltip::Position::Top,
+        ),
+    ]
+    .spacing(spacing::XS)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+/// One channel: RMS as the bar, the held peak as a tick
+fn meter_bar<'a>(rms: f32, peak: f32) -> Element<'a, Message> {
+    let rms_px = meter_fraction(rms) * METER_WIDTH;
+    let peak_px = (meter_fraction(peak) * METER_WIDTH).max(rms_px);
+    let fill = level_color(rms);
+    let tick = level_color(peak);
+
+    let mut bar = row![
+        container(Space::new(Length::Fixed(rms_px), Length::Fill)).style(move |_| {
+            container::Style {
+                background: Some(iced::Background::Color(fill)),
+                ..Default::default()
+            }
+        }),
+    ];
+    if peak > 0.0 {
+        bar = bar
+            .push(Space::with_width(Length::Fixed(
+                (peak_px - rms_px - 2.0).max(0.0),
+            )))
+            .push(
+                container(Space::new(Length::Fixed(2.0), Length::Fill)).style(move |_| {
+                    container::Style {
+                        background: Some(iced::Background::Color(tick)),
+                        ..Default::default()
+                    }
+                }),
+            );
+    }
+
+    container(bar)
+        .width(Length::Fixed(METER_WIDTH))
+        .height(Length::Fixed(METER_HEIGHT))
+        .clip(true)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+            border: Border {
+                radius: 1.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
+/// Chart of the last minute of levels: RMS filled, peaks as a line, clips
+/// marked in red along the top
+pub fn level_history(meter: &LevelMeterState, height: f32) -> Element<'_, Message> {
+    Canvas::new(LevelHistoryChart {
+        points: meter.history.iter().copied().collect(),
+    })
+    .width(Length::Fill)
+    .height(Length::Fixed(height))
+    .into()
+}
+
+struct LevelHistoryChart {
+    points: Vec<LevelPoint>,
+}
+
+impl canvas::Program<Message> for LevelHistoryChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let size = bounds.size();
+        frame.fill_rectangle(Point::ORIGIN, size, color::SURFACE_ELEVATED);
+
+        // -6 dBFS guide
+        let guide_y = size.height * (1.0 - meter_fraction(0.5));
+        frame.fill_rectangle(
+            Point::new(0.0, guide_y),
+            Size::new(size.width, 1.0),
+            color::BORDER_SUBTLE,
+        );
+
+        // Newest point at the right edge
+        let step = size.width / LEVEL_HISTORY_LEN as f32;
+        let x0 = size.width - self.points.len() as f32 * step;
+        for (i, point) in self.points.iter().enumerate() {
+            let x = x0 + i as f32 * step;
+            let rms_h = meter_fraction(point.rms) * size.height;
+            frame.fill_rectangle(
+                Point::new(x, size.height - rms_h),
+                Size::new(step.max(1.0), rms_h),
+                color::with_alpha(level_color(point.rms), 0.6),
+            );
+            if point.clipped {
+                frame.fill_rectangle(
+                    Point::new(x, 0.0),
+                    Size::new(step.max(1.0), 3.0),
+                    color::ERROR,
+                );
+            }
+        }
+
+        if self.points.len() > 1 {
+            let peaks = Path::new(|b| {
+                for (i, point) in self.points.iter().enumerate() {
+                    let p = Point::new(
+                        x0 + (i as f32 + 0.5) * step,
+                        size.height * (1.0 - meter_fraction(point.peak)),
+                    );
+                    if i == 0 {
+                        b.move_to(p);
+                    } else {
+                        b.line_to(p);
+                    }
+                }
+            });
+            frame.stroke(
+                &peaks,
+                canvas::Stroke::default()
+                    .with_color(color::TEXT_SECONDARY)
+                    .with_width(1.0),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
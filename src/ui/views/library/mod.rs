@@ -5,9 +5,11 @@
 //! - `track_list`: Track table header, rows, virtualized list
 //! - `organize`: File organization section (collapsible)
 //! - `enrichment`: Track identification via AcoustID
+//! - `resume`: "Pick up where you left off" card
 
 mod enrichment;
 mod organize;
+mod resume;
 mod search;
 mod track_list;
 
@@ -18,6 +20,7 @@ use crate::ui::icons::spinner_frame;
 use crate::ui::messages::Message;
 use crate::ui::state::LoadedState;
 use crate::ui::theme::{self, color, spacing, typography};
+use crate::ui::views::helpers::history_picker;
 
 /// Library pane with scanning, organizing, and track list
 pub fn library_pane(s: &LoadedState) -> Element<'_, Message> {
@@ -42,9 +45,13 @@ pub fn library_pane(s: &LoadedState) -> Element<'_, Message> {
         // Scan progress indicator (only shown when scanning)
         scan_progress(s),
         Space::with_height(spacing::MD),
+        // Resume last session / recently played (startup only)
+        resume::resume_card(s),
         // Search and filters section
         search::search_and_filters(s),
         Space::with_height(spacing::SM),
+        // Artist or album being shown ("Go to artist/album")
+        search::scope_header(s),
         // Track count and sort controls
         search::track_count_and_sort(s, filtered_count, total_count),
         Space::with_height(spacing::SM),
@@ -77,6 +84,14 @@ fn scan_controls(state: &LoadedState, path_display: String) -> Element<'_, Messa
             .width(Length::Fill)
             .style(theme::text_input_style),
         Space::with_width(spacing::SM),
+        history_picker(
+            state
+                .input_history
+                .scan_paths
+                .iter()
+                .map(|p| p.display().to_string()),
+            Message::PathChanged,
+        ),
         button(text("Browse").size(typography::SIZE_SMALL))
             .on_press(Message::PickPath)
             .padding([spacing::SM, spacing::MD])
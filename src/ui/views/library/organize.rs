@@ -2,18 +2,24 @@
 
 use std::path::Path;
 
-use iced::widget::{Space, button, column, container, row, scrollable, text, text_input};
+use iced::mouse::Interaction;
+use iced::widget::{
+    Space, button, checkbox, column, container, mouse_area, row, scrollable, text, text_input,
+};
 use iced::{Element, Length};
 
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::state::{LoadedState, OrganizeView, virtualization as virt};
+use crate::ui::state::{
+    LoadedState, OrganizeView, PreviewRow, organize_preview_scroll_id, virtualization as virt,
+};
 use crate::ui::theme::{self, color, radius, spacing, typography};
-use crate::ui::views::helpers::{action_button, calc_visible_range};
+use crate::ui::views::helpers::{action_button, calc_visible_range, history_picker};
+use crate::{organizer, tasks};
 
 /// Collapsible organize section
 pub fn organize_section_collapsible(state: &LoadedState) -> Element<'_, Message> {
-    let is_collapsed = state.organize_collapsed;
+    let is_collapsed = state.panes.library.organize_collapsed;
     let toggle_icon = if is_collapsed {
         icons::CHEVRON_RIGHT
     } else {
@@ -87,12 +93,70 @@ pub fn organize_section_collapsible(state: &LoadedState) -> Element<'_, Message>
 /// Renders the organize section based on current view
 pub fn organize_section(state: &LoadedState, dest: String) -> Element<'_, Message> {
     match &state.organize_view {
-        OrganizeView::Input => organize_input(state, dest),
+        OrganizeView::Input => match &state.interrupted_organize {
+            Some(incomplete) => column![
+                interrupted_banner(incomplete),
+                Space::with_height(spacing::SM),
+                organize_input(state, dest),
+            ]
+            .into(),
+            None => organize_input(state, dest),
+        },
         OrganizeView::Preview => organize_preview(state, dest),
         OrganizeView::Organizing => organize_progress(state),
+        OrganizeView::Manual => organize_manual(state, dest),
     }
 }
 
+/// Renders the resume/rollback banner for an interrupted organize
+fn interrupted_banner(incomplete: &organizer::IncompleteOrganize) -> Element<'_, Message> {
+    let started = incomplete
+        .started
+        .as_deref()
+        .unwrap_or("an earlier session");
+    container(
+        row![
+            column![
+                text("Organize was interrupted")
+                    .size(typography::SIZE_BODY)
+                    .color(color::WARNING),
+                text(format!(
+                    "Started {}: {} of {} moves were not finished.",
+                    started,
+                    incomplete.pending_count(),
+                    incomplete.entries.len()
+                ))
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED),
+            ]
+            .spacing(spacing::XS),
+            Space::with_width(Length::Fill),
+            button(text("Roll Back").size(typography::SIZE_SMALL))
+                .on_press(Message::OrganizeRecover(organizer::RecoveryMode::Rollback))
+                .padding([spacing::SM, spacing::MD])
+                .style(theme::button_secondary),
+            Space::with_width(spacing::XS),
+            button(text("Resume").size(typography::SIZE_SMALL))
+                .on_press(Message::OrganizeRecover(organizer::RecoveryMode::Resume))
+                .padding([spacing::SM, spacing::MD])
+                .style(theme::button_primary),
+        ]
+        .align_y(iced::Alignment::Center),
+    )
+    .padding(spacing::SM)
+    .width(Length::Fill)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: iced::Border {
+            color: color::WARNING,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
+
 /// Renders the organize input view
 fn organize_input(state: &LoadedState, dest: String) -> Element<'_, Message> {
     let undo = if state.can_undo {
@@ -109,6 +173,14 @@ fn organize_input(state: &LoadedState, dest: String) -> Element<'_, Message> {
                 .width(Length::Fill)
                 .style(theme::text_input_style),
             Space::with_width(spacing::SM),
+            history_picker(
+                state
+                    .input_history
+                    .organize_destinations
+                    .iter()
+                    .map(|p| p.display().to_string()),
+                Message::OrganizeDestinationChanged,
+            ),
             button(text("Browse").size(typography::SIZE_SMALL))
                 .on_press(Message::PickOrganizeDestination)
                 .padding([spacing::SM, spacing::MD])
@@ -126,12 +198,20 @@ fn organize_input(state: &LoadedState, dest: String) -> Element<'_, Message> {
             .width(Length::Fill)
             .style(theme::text_input_style),
             Space::with_width(spacing::SM),
+            history_picker(
+                state.input_history.organize_patterns.clone(),
+                Message::OrganizePatternChanged,
+            ),
             button(text("Preview").size(typography::SIZE_SMALL))
                 .on_press(Message::OrganizePreviewPressed)
                 .padding([spacing::SM, spacing::MD])
                 .style(theme::button_primary),
             Space::with_width(spacing::XS),
             action_button("Undo", undo),
+            Space::with_width(spacing::XS),
+            action_button("Load Plan", Some(Message::OrganizeLoadPlan)),
+            Space::with_width(spacing::XS),
+            action_button("Manual", Some(Message::OrganizeManualOpen)),
         ]
         .align_y(iced::Alignment::Center),
     ]
@@ -142,15 +222,43 @@ fn organize_input(state: &LoadedState, dest: String) -> Element<'_, Message> {
 /// Renders the organize preview view
 fn organize_preview(state: &LoadedState, dest: String) -> Element<'_, Message> {
     let n = state.organize_preview.len();
+    let review = &state.organize_review;
+    let excluded = review.excluded.len();
     let title = if state.preview_loading {
         format!("Loading... {} files so far", n)
+    } else if excluded > 0 {
+        format!(
+            "{} of {} files will be moved ({} left out)",
+            n - excluded,
+            n,
+            excluded
+        )
     } else {
         format!("{} files will be moved", n)
     };
-    let confirm = if state.preview_loading {
-        None
-    } else {
-        Some(Message::OrganizeConfirmPressed)
+    // Execution and export need the stamped plan, built once loading finishes
+    let plan_ready = !state.preview_loading && state.organize_plan.is_some();
+    // Nothing moves until every conflict is resolved or left out
+    let clear = state
+        .organize_simulation
+        .as_ref()
+        .is_some_and(|sim| review.clears(sim));
+    let confirm = (plan_ready && clear).then_some(Message::OrganizeConfirmPressed);
+    let export = plan_ready.then_some(Message::OrganizeExportPlan);
+    let plan_info = match (&state.organize_plan, &state.organize_simulation) {
+        (Some(plan), None) => format!(
+            "Plan {} · {} moves · checking for conflicts…",
+            &plan.hash[..12],
+            plan.moves.len()
+        ),
+        (Some(plan), Some(_)) => {
+            format!("Plan {} · {} moves", &plan.hash[..12], plan.moves.len())
+        }
+        (None, _) => String::new(),
+    };
+    let conflicts: Element<Message> = match &state.organize_simulation {
+        Some(sim) if !review.clears(sim) => conflict_banner(sim),
+        _ => Space::with_height(0).into(),
     };
 
     let header = column![
@@ -160,6 +268,10 @@ fn organize_preview(state: &LoadedState, dest: String) -> Element<'_, Message> {
         text(format!("Destination: {}", dest))
             .size(typography::SIZE_TINY)
             .color(color::TEXT_MUTED),
+        text(plan_info)
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+        conflicts,
         Space::with_height(spacing::SM),
         row![
             button(text("Cancel").size(typography::SIZE_SMALL))
@@ -167,6 +279,10 @@ fn organize_preview(state: &LoadedState, dest: String) -> Element<'_, Message> {
                 .padding([spacing::SM, spacing::MD])
                 .style(theme::button_secondary),
             Space::with_width(Length::Fill),
+            albums_button(state),
+            Space::with_width(spacing::XS),
+            action_button("Export Plan", export),
+            Space::with_width(spacing::XS),
             action_button("Organize Files", confirm),
         ],
     ]
@@ -187,16 +303,97 @@ fn organize_preview(state: &LoadedState, dest: String) -> Element<'_, Message> {
         .into()
 }
 
+/// Explains what the conflicting moves would overwrite and offers to skip them
+fn conflict_banner(sim: &organizer::Simulation) -> Element<'_, Message> {
+    let first = sim
+        .conflicts
+        .first()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    container(
+        row![
+            column![
+                text(format!(
+                    "{} of {} moves would overwrite files",
+                    sim.conflicts.len(),
+                    sim.moves
+                ))
+                .size(typography::SIZE_BODY)
+                .color(color::WARNING),
+                text(format!(
+                    "{}. Skip or untick them, or change the pattern and preview again.",
+                    sim.summary()
+                ))
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED),
+                text(format!("e.g. {}", first))
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_MUTED),
+            ]
+            .spacing(spacing::XS)
+            .width(Length::Fill),
+            Space::with_width(spacing::SM),
+            button(text("Skip Conflicting").size(typography::SIZE_SMALL))
+                .on_press(Message::OrganizeSkipConflicts)
+                .padding([spacing::SM, spacing::MD])
+                .style(theme::button_secondary),
+        ]
+        .align_y(iced::Alignment::Center),
+    )
+    .padding(spacing::SM)
+    .width(Length::Fill)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: iced::Border {
+            color: color::WARNING,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
+
 /// Renders the organizing progress view
 fn organize_progress(state: &LoadedState) -> Element<'_, Message> {
     let errors = state.organize_errors.len();
+    let progress = state.organize_task.as_ref().map(|task| task.progress());
+    let done = progress
+        .as_ref()
+        .map_or(state.organize_progress as u64, |p| p.done);
+    let eta = progress
+        .as_ref()
+        .and_then(|p| p.eta())
+        .map(|eta| format!(" · {} left", tasks::format_eta(eta)))
+        .unwrap_or_default();
+
+    let cancelling = progress.as_ref().is_some_and(|p| p.cancelled);
+    let cancel_btn = button(text(if cancelling {
+        "Cancelling…"
+    } else {
+        "Cancel"
+    }))
+    .padding([spacing::SM, spacing::MD])
+    .style(theme::button_secondary)
+    .on_press_maybe(
+        progress
+            .as_ref()
+            .filter(|p| !p.cancelled)
+            .map(|p| Message::TaskCancel(p.id)),
+    );
+
     column![
-        text(format!(
-            "Organizing... {} of {} files",
-            state.organize_progress, state.organize_total
-        ))
-        .size(typography::SIZE_BODY)
-        .color(color::TEXT_PRIMARY),
+        row![
+            text(format!(
+                "Organizing... {} of {} files{}",
+                done, state.organize_total, eta
+            ))
+            .size(typography::SIZE_BODY)
+            .color(color::TEXT_PRIMARY),
+            Space::with_width(Length::Fill),
+            cancel_btn,
+        ]
+        .align_y(iced::Alignment::Center),
         if errors > 0 {
             text(format!("{} errors", errors))
                 .size(typography::SIZE_SMALL)
@@ -209,19 +406,314 @@ fn organize_progress(state: &LoadedState) -> Element<'_, Message> {
     .into()
 }
 
+/// Renders the manual organize view: incoming folders on the left, the
+/// library tree on the right, dragged from one to the other
+fn organize_manual(state: &LoadedState, dest: String) -> Element<'_, Message> {
+    let manual = &state.manual_organize;
+    let undo = state.can_undo.then_some(Message::UndoPressed);
+    let hint = match &manual.held {
+        Some(held) => format!(
+            "Drop {} on a library folder",
+            held.file_name().unwrap_or_default().to_string_lossy()
+        ),
+        None => "Drag an incoming folder or file onto a library folder".to_string(),
+    };
+    let unpick: Element<Message> = match manual.held {
+        Some(_) => button(text("Put Down").size(typography::SIZE_SMALL))
+            .on_press(Message::OrganizeManualUnpick)
+            .padding([spacing::SM, spacing::MD])
+            .style(theme::button_secondary)
+            .into(),
+        None => Space::with_width(0).into(),
+    };
+
+    let header = row![
+        column![
+            text(hint)
+                .size(typography::SIZE_BODY)
+                .color(color::TEXT_PRIMARY),
+            text(format!("Library: {}", dest))
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(spacing::XS)
+        .width(Length::Fill),
+        unpick,
+        Space::with_width(spacing::XS),
+        action_button("Undo", undo),
+        Space::with_width(spacing::XS),
+        button(text("Done").size(typography::SIZE_SMALL))
+            .on_press(Message::OrganizeManualClose)
+            .padding([spacing::SM, spacing::MD])
+            .style(theme::button_secondary),
+    ]
+    .align_y(iced::Alignment::Center);
+
+    let panes = row![
+        manual_pane("Incoming", incoming_list(state)),
+        Space::with_width(spacing::SM),
+        manual_pane("Library", library_tree(state)),
+    ]
+    .height(Length::Fill);
+    // Letting go anywhere but a library folder keeps the pick
+    let panes: Element<Message> = if manual.dragging {
+        mouse_area(panes)
+            .on_release(Message::OrganizeManualRelease)
+            .interaction(Interaction::Grabbing)
+            .into()
+    } else {
+        panes.into()
+    };
+
+    column![header, Space::with_height(spacing::SM), panes]
+        .spacing(0)
+        .height(Length::Fixed(360.0))
+        .into()
+}
+
+/// One titled, scrolling pane of the manual view
+fn manual_pane<'a>(title: &'a str, list: Element<'a, Message>) -> Element<'a, Message> {
+    column![
+        text(title)
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_SECONDARY),
+        Space::with_height(spacing::XS),
+        container(
+            scrollable(list)
+                .height(Length::Fill)
+                .style(theme::scrollbar_style)
+        )
+        .padding(spacing::XS)
+        .height(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::BASE)),
+            border: iced::Border {
+                color: color::BORDER_SUBTLE,
+                width: 1.0,
+                radius: radius::SM.into(),
+            },
+            ..Default::default()
+        }),
+    ]
+    .width(Length::FillPortion(1))
+    .into()
+}
+
+/// Row background: highlighted when held or targeted
+fn row_style(highlight: bool) -> impl Fn(&iced::Theme) -> container::Style {
+    move |_| container::Style {
+        background: highlight.then_some(iced::Background::Color(color::SURFACE_HOVER)),
+        border: iced::Border {
+            color: if highlight {
+                color::PRIMARY
+            } else {
+                iced::Color::TRANSPARENT
+            },
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    }
+}
+
+/// File or folder name, for rows
+fn name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Incoming folders, the expanded one with its files
+fn incoming_list(state: &LoadedState) -> Element<'_, Message> {
+    let manual = &state.manual_organize;
+    if manual.incoming.is_empty() {
+        return text("Every track is inside the library folder")
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED)
+            .into();
+    }
+    let held = |path: &Path| manual.held.as_deref() == Some(path);
+    let mut rows = column![].spacing(2);
+    for folder in &manual.incoming {
+        let open = manual.expanded.as_ref() == Some(&folder.path);
+        let chevron = button(
+            icon_sized(
+                if open {
+                    icons::CHEVRON_DOWN
+                } else {
+                    icons::CHEVRON_RIGHT
+                },
+                typography::SIZE_TINY,
+            )
+            .color(color::TEXT_MUTED),
+        )
+        .padding(spacing::XS)
+        .style(theme::button_ghost)
+        .on_press(Message::OrganizeManualExpand(folder.path.clone()));
+        let grip = mouse_area(
+            row![
+                icon_sized(icons::FOLDER, typography::SIZE_SMALL).color(color::TEXT_MUTED),
+                Space::with_width(spacing::SM),
+                text(folder.path.display().to_string())
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_PRIMARY)
+                    .width(Length::Fill),
+                text(format!("{} tracks", folder.tracks.len()))
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_MUTED),
+            ]
+            .align_y(iced::Alignment::Center),
+        )
+        .on_press(Message::OrganizeManualGrab(folder.path.clone()))
+        .interaction(Interaction::Grab);
+        rows = rows.push(
+            container(row![chevron, grip].align_y(iced::Alignment::Center))
+                .padding([2, spacing::XS])
+                .style(row_style(held(&folder.path))),
+        );
+        if !open {
+            continue;
+        }
+        for (_, path) in &folder.tracks {
+            let file = mouse_area(
+                row![
+                    Space::with_width(spacing::XL),
+                    icon_sized(icons::MUSIC, typography::SIZE_TINY).color(color::TEXT_MUTED),
+                    Space::with_width(spacing::SM),
+                    text(name_of(path))
+                        .size(typography::SIZE_TINY)
+                        .color(color::TEXT_SECONDARY),
+                ]
+                .align_y(iced::Alignment::Center),
+            )
+            .on_press(Message::OrganizeManualGrab(path.clone()))
+            .interaction(Interaction::Grab);
+            rows = rows.push(
+                container(file)
+                    .padding([2, spacing::XS])
+                    .width(Length::Fill)
+                    .style(row_style(held(path))),
+            );
+        }
+    }
+    rows.into()
+}
+
+/// Folders under the library root, indented by depth; drop targets while
+/// something is held
+fn library_tree(state: &LoadedState) -> Element<'_, Message> {
+    let manual = &state.manual_organize;
+    let holding = manual.held.is_some();
+    let mut rows = column![].spacing(2);
+    for folder in &manual.library {
+        let label = if folder.depth == 0 {
+            folder.path.display().to_string()
+        } else {
+            name_of(&folder.path)
+        };
+        let targeted = holding && manual.hover.as_ref() == Some(&folder.path);
+        let item = container(
+            row![
+                Space::with_width(Length::Fixed(folder.depth as f32 * f32::from(spacing::LG))),
+                icon_sized(
+                    if targeted {
+                        icons::FOLDER_OPEN
+                    } else {
+                        icons::FOLDER
+                    },
+                    typography::SIZE_SMALL,
+                )
+                .color(color::TEXT_MUTED),
+                Space::with_width(spacing::SM),
+                text(label)
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_PRIMARY)
+                    .width(Length::Fill),
+                text(folder.tracks.to_string())
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_MUTED),
+            ]
+            .align_y(iced::Alignment::Center),
+        )
+        .padding([2, spacing::XS])
+        .width(Length::Fill)
+        .style(row_style(targeted));
+        let target = mouse_area(item)
+            .on_enter(Message::OrganizeManualHover(Some(folder.path.clone())))
+            .on_exit(Message::OrganizeManualHover(None));
+        // A click with something picked drops it as well as a release
+        let target = if holding {
+            target
+                .on_release(Message::OrganizeManualDrop(folder.path.clone()))
+                .interaction(Interaction::Pointer)
+        } else {
+            target
+        };
+        rows = rows.push(target);
+    }
+    rows.into()
+}
+
+/// Collapses every album of the preview, or expands them all if they are
+fn albums_button(state: &LoadedState) -> Element<'_, Message> {
+    let review = &state.organize_review;
+    let collapse = review.collapsed.len() < review.groups.len();
+    let label = if collapse {
+        "Collapse Albums"
+    } else {
+        "Expand Albums"
+    };
+    let ready = !state.preview_loading && !review.groups.is_empty();
+    action_button(
+        label,
+        ready.then_some(Message::OrganizeCollapseAll(collapse)),
+    )
+    .into()
+}
+
+/// Renders the preview rows in view: album headers and their moves once
+/// loaded, a flat list of moves while still loading
 /// Renders virtualized preview list
 fn virtualized_preview_list(state: &LoadedState) -> Element<'_, Message> {
     let (start, end, top, bottom) = calc_visible_range(
-        state.preview_scroll_offset,
-        state.preview_viewport_height,
-        state.organize_preview.len(),
+        state.panes.library.preview_scroll_offset,
+        state.panes.library.preview_viewport_height,
+        if state.preview_loading {
+            state.organize_preview.len()
+        } else {
+            state.organize_review.rows.len()
+        },
         virt::PREVIEW_ROW_HEIGHT,
     );
     let dest = &state.organize_destination;
-    let items: Vec<_> = state.organize_preview[start..end]
-        .iter()
-        .map(|p| preview_item(p, dest, virt::PREVIEW_ROW_HEIGHT))
-        .collect();
+    let review = &state.organize_review;
+    let move_row = |index: usize| {
+        let p = &state.organize_preview[index];
+        let conflicted = state
+            .organize_simulation
+            .as_ref()
+            .is_some_and(|sim| sim.is_conflicted(&p.source));
+        let excluded = review.excluded.contains(&p.source);
+        preview_item(
+            index,
+            p,
+            dest,
+            conflicted,
+            excluded,
+            virt::PREVIEW_ROW_HEIGHT,
+        )
+    };
+    let items: Vec<_> = if state.preview_loading {
+        (start..end).map(move_row).collect()
+    } else {
+        review.rows[start..end]
+            .iter()
+            .map(|row| match *row {
+                PreviewRow::Album(group) => album_item(state, group, virt::PREVIEW_ROW_HEIGHT),
+                PreviewRow::Move(index) => move_row(index),
+            })
+            .collect()
+    };
 
     scrollable(
         column![
@@ -233,38 +725,136 @@ fn virtualized_preview_list(state: &LoadedState) -> Element<'_, Message> {
     )
     .height(Length::Fill)
     .width(Length::Fill)
+    .id(organize_preview_scroll_id())
     .on_scroll(Message::PreviewScrollChanged)
     .style(theme::scrollbar_style)
     .into()
 }
 
-/// Renders a single preview item
+/// Renders an album's header row: open/close, leave out and counts
+fn album_item(state: &LoadedState, group: usize, h: f32) -> Element<'_, Message> {
+    let review = &state.organize_review;
+    let g = &review.groups[group];
+    let excluded = review.excluded_in(group, &state.organize_preview);
+    let chevron = if review.collapsed.contains(&group) {
+        icons::CHEVRON_RIGHT
+    } else {
+        icons::CHEVRON_DOWN
+    };
+    let mut counts = format!("{} files · {} change", g.items.len(), g.changed);
+    if excluded > 0 {
+        counts.push_str(&format!(" · {} left out", excluded));
+    }
+
+    container(
+        row![
+            button(icon_sized(chevron, typography::SIZE_TINY).color(color::TEXT_MUTED))
+                .on_press(Message::OrganizeExpandAlbum(group))
+                .padding(0)
+                .style(theme::button_ghost),
+            Space::with_width(spacing::XS),
+            checkbox("", excluded < g.items.len())
+                .on_toggle(move |_| Message::OrganizeToggleAlbum(group))
+                .size(typography::SIZE_TINY),
+            text(&g.label)
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_PRIMARY),
+            Space::with_width(spacing::SM),
+            text(counts)
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED),
+        ]
+        .align_y(iced::Alignment::Center),
+    )
+    .height(Length::Fixed(h))
+    .width(Length::Fill)
+    .into()
+}
+
+/// The parts of a path as text, changed ones in `changed_color`
+fn path_parts<'a>(
+    parts: Vec<organizer::PathPart>,
+    changed_color: iced::Color,
+    unchanged_color: iced::Color,
+) -> Vec<Element<'a, Message>> {
+    let separator = std::path::MAIN_SEPARATOR.to_string();
+    let mut items = Vec::with_capacity(parts.len() * 2);
+    for (i, part) in parts.into_iter().enumerate() {
+        if i > 0 {
+            items.push(
+                text(separator.clone())
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_MUTED)
+                    .into(),
+            );
+        }
+        let part_color = if part.changed {
+            changed_color
+        } else {
+            unchanged_color
+        };
+        items.push(
+            text(part.text)
+                .size(typography::SIZE_TINY)
+                .color(part_color)
+                .into(),
+        );
+    }
+    items
+}
+
+/// Renders a single preview item, the parts of the path it changes
+/// highlighted
 fn preview_item<'a>(
+    index: usize,
     p: &'a crate::organizer::OrganizePreview,
     base: &Path,
+    conflicted: bool,
+    excluded: bool,
     h: f32,
 ) -> Element<'a, Message> {
-    let from = p
-        .source
-        .strip_prefix(base)
-        .unwrap_or(&p.source)
-        .display()
-        .to_string();
-    let to = p
-        .destination
-        .strip_prefix(base)
-        .unwrap_or(&p.destination)
-        .display()
-        .to_string();
-    let same = from == to;
-
-    let (txt, txt_color) = if same {
-        (format!("{} → (no change)", from), color::TEXT_MUTED)
+    let from = p.source.strip_prefix(base).unwrap_or(&p.source);
+    let to = p.destination.strip_prefix(base).unwrap_or(&p.destination);
+    let include = checkbox("", !excluded)
+        .on_toggle(move |_| Message::OrganizeToggleMove(index))
+        .size(typography::SIZE_TINY);
+
+    let mut items: Vec<Element<'a, Message>> = vec![include.into()];
+    if from == to {
+        items.push(
+            text(format!("{} → (no change)", from.display()))
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED)
+                .into(),
+        );
+    } else if excluded {
+        items.push(
+            text(format!("{} → {} (left out)", from.display(), to.display()))
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED)
+                .into(),
+        );
     } else {
-        (format!("{} → {}", from, to), color::TEXT_SECONDARY)
-    };
+        let (old, new) = organizer::path_diff(from, to);
+        items.extend(path_parts(old, color::ERROR, color::TEXT_MUTED));
+        items.push(
+            text(" → ")
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED)
+                .into(),
+        );
+        items.extend(path_parts(new, color::SUCCESS, color::TEXT_SECONDARY));
+        if conflicted {
+            items.push(
+                text(" (conflict)")
+                    .size(typography::SIZE_TINY)
+                    .color(color::WARNING)
+                    .into(),
+            );
+        }
+    }
 
-    container(text(txt).size(typography::SIZE_TINY).color(txt_color))
+    container(row(items).align_y(iced::Alignment::Center))
         .height(Length::Fixed(h))
         .width(Length::Fill)
         .into()
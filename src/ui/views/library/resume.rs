@@ -0,0 +1,153 @@
+//! "Pick up where you left off" card shown on startup.
+
+use iced::widget::{Space, button, column, container, mouse_area, row, text};
+use iced::{Element, Length};
+
+use crate::player::format_duration_secs;
+use crate::ui::context_menu::ContextTarget;
+use crate::ui::icons::{self, icon_sized};
+use crate::ui::messages::Message;
+use crate::ui::state::LoadedState;
+use crate::ui::theme::{self, color, radius, spacing, typography};
+
+/// Resume card: the last track and position, plus recently played albums.
+///
+/// Hidden once anything plays or the user closes it.
+pub fn resume_card(s: &LoadedState) -> Element<'_, Message> {
+    let resume = &s.resume;
+    if resume.dismissed || !resume.has_content() || s.player_state.current_track.is_some() {
+        return Space::with_height(0).into();
+    }
+
+    let mut content = column![
+        row![
+            text("Pick up where you left off")
+                .size(typography::SIZE_BODY)
+                .color(color::TEXT_PRIMARY),
+            Space::with_width(Length::Fill),
+            button(icon_sized(icons::XMARK, typography::SIZE_TINY))
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_ghost)
+                .on_press(Message::ResumeDismiss),
+        ]
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(spacing::SM);
+
+    if let Some(session) = &resume.session
+        && let Some(path) = session.current_track()
+    {
+        let path_str = path.to_string_lossy();
+        let title = s
+            .tracks
+            .iter()
+            .find(|t| t.path == path_str)
+            .map(|t| format!("{} - {}", t.artist_name, t.title))
+            .or_else(|| path.file_stem().map(|f| f.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "Unknown".to_string());
+        let position = if session.duration_secs > 0.0 {
+            format!(
+                "at {} of {}",
+                format_duration_secs(session.position_secs as f32),
+                format_duration_secs(session.duration_secs as f32)
+            )
+        } else {
+            format!("at {}", format_duration_secs(session.position_secs as f32))
+        };
+        let queued = session.queue.len();
+
+        content = content.push(
+            row![
+                column![
+                    text(title)
+                        .size(typography::SIZE_SMALL)
+                        .color(color::TEXT_SECONDARY),
+                    text(format!(
+                        "{} · {} track{} in queue",
+                        position,
+                        queued,
+                        if queued == 1 { "" } else { "s" }
+                    ))
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_MUTED),
+                ]
+                .spacing(2),
+                Space::with_width(Length::Fill),
+                button(
+                    row![
+                        icon_sized(icons::PLAY, typography::SIZE_SMALL),
+                        text("Resume").size(typography::SIZE_SMALL),
+                    ]
+                    .spacing(spacing::XS)
+                    .align_y(iced::Alignment::Center),
+                )
+                .padding([spacing::SM, spacing::MD])
+                .style(theme::button_primary)
+                .on_press(Message::ResumeSession),
+            ]
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    if !resume.recent_albums.is_empty() {
+        let albums: Vec<Element<Message>> = resume
+            .recent_albums
+            .iter()
+            .enumerate()
+            .map(|(i, album)| {
+                let album_btn = button(
+                    column![
+                        text(&album.album)
+                            .size(typography::SIZE_SMALL)
+                            .color(color::TEXT_PRIMARY),
+                        text(&album.artist)
+                            .size(typography::SIZE_TINY)
+                            .color(color::TEXT_MUTED),
+                    ]
+                    .spacing(2),
+                )
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_secondary)
+                .on_press(Message::PlayRecentAlbum(i));
+                // Right-click offers the album menu of any track on it
+                let anchor = s
+                    .tracks
+                    .iter()
+                    .position(|t| t.album_name == album.album && t.artist_name == album.artist);
+                match anchor {
+                    Some(idx) => mouse_area(album_btn)
+                        .on_right_press(Message::ContextMenuOpen(ContextTarget::Album(idx)))
+                        .into(),
+                    None => album_btn.into(),
+                }
+            })
+            .collect();
+
+        content = content.push(
+            column![
+                text("Recently played")
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_MUTED),
+                row(albums).spacing(spacing::SM),
+            ]
+            .spacing(spacing::XS),
+        );
+    }
+
+    column![
+        container(content)
+            .padding(spacing::MD)
+            .width(Length::Fill)
+            .style(|_| container::Style {
+                background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+                border: iced::Border {
+                    color: color::BORDER_SUBTLE,
+                    width: 1.0,
+                    radius: radius::SM.into(),
+                },
+                ..Default::default()
+            }),
+        Space::with_height(spacing::MD),
+    ]
+    .into()
+}
@@ -1,12 +1,13 @@
-//! Search bar, filter chips, and track count/sort controls.
+//! Search bar, filter chips, artist/album scope, and track count/sort controls.
 
-use iced::widget::{Space, button, container, row, text, text_input};
+use iced::widget::{Space, button, column, container, row, text, text_input};
 use iced::{Element, Length};
 
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::state::{LoadedState, SortColumn};
+use crate::ui::state::{LibraryScope, LoadedState, SortColumn};
 use crate::ui::theme::{self, color, radius, spacing, typography};
+use crate::ui::views::helpers::filter_chip;
 
 /// Renders the search bar with icon and filter chips
 pub fn search_and_filters(state: &LoadedState) -> Element<'_, Message> {
@@ -62,10 +63,70 @@ pub fn search_and_filters(state: &LoadedState) -> Element<'_, Message> {
         },
     );
 
+    // Recently added filter chip
+    let recent_active = state.filter_added_within_days.is_some();
+    let recent_chip = filter_chip(
+        "Added 30d",
+        recent_active,
+        if recent_active {
+            Message::FilterByAddedWithin(None)
+        } else {
+            Message::FilterByAddedWithin(Some(30))
+        },
+    );
+
+    // Heavily limited or clipping masters, by ReplayGain
+    let loud_chip = filter_chip(
+        "Loud master",
+        state.filter_loud_master,
+        Message::FilterByLoudMaster(!state.filter_loud_master),
+    );
+
+    // No lyrics, by the language tag or MusicBrainz work data
+    let instrumental_chip = filter_chip(
+        "Instrumental",
+        state.filter_instrumental,
+        Message::FilterByInstrumental(!state.filter_instrumental),
+    );
+
+    // Leave out tracks tagged explicit (untagged ones stay)
+    let explicit_chip = filter_chip(
+        "Hide explicit",
+        state.filter_hide_explicit,
+        Message::FilterHideExplicit(!state.filter_hide_explicit),
+    );
+
+    // Album name picked by identification rather than by hand
+    let machine_album_active = state.filter_machine_written.is_some();
+    let machine_album_chip = filter_chip(
+        "Machine album",
+        machine_album_active,
+        if machine_album_active {
+            Message::FilterByMachineWritten(None)
+        } else {
+            Message::FilterByMachineWritten(Some("album"))
+        },
+    );
+
+    // Leave out tracks usually skipped before 30%
+    let skipped_active = state.filter_hide_skipped.is_some();
+    let skipped_chip = filter_chip(
+        "Hide skipped",
+        skipped_active,
+        Message::FilterHideSkipped(!skipped_active),
+    );
+
     // Clear filters button (only show when filters active)
     let has_filters = !state.search_query.is_empty()
         || state.filter_format.is_some()
-        || state.filter_lossless.is_some();
+        || state.filter_lossless.is_some()
+        || state.filter_added_within_days.is_some()
+        || state.filter_loud_master
+        || state.filter_instrumental
+        || state.filter_hide_explicit
+        || state.filter_machine_written.is_some()
+        || state.filter_hide_skipped.is_some()
+        || state.filter_scope.is_some();
 
     let clear_btn: Element<Message> = if has_filters {
         button(
@@ -92,6 +153,12 @@ pub fn search_and_filters(state: &LoadedState) -> Element<'_, Message> {
         row(format_chips).spacing(spacing::XS),
         Space::with_width(spacing::XS),
         lossless_chip,
+        recent_chip,
+        loud_chip,
+        instrumental_chip,
+        explicit_chip,
+        machine_album_chip,
+        skipped_chip,
         Space::with_width(Length::Fill),
         clear_btn,
     ]
@@ -99,55 +166,124 @@ pub fn search_and_filters(state: &LoadedState) -> Element<'_, Message> {
     .into()
 }
 
-/// Creates a pill-shaped filter chip
-fn filter_chip<'a>(
-    label: &'static str,
-    is_active: bool,
-    on_press: Message,
-) -> Element<'a, Message> {
-    let (bg, text_color, border_color) = if is_active {
-        (color::PRIMARY, color::TEXT_PRIMARY, color::PRIMARY)
-    } else {
-        (
-            color::SURFACE_ELEVATED,
-            color::TEXT_SECONDARY,
-            color::BORDER_SUBTLE,
-        )
+/// The artist or album the library is narrowed to, with a way back
+pub fn scope_header(state: &LoadedState) -> Element<'_, Message> {
+    let Some(ref scope) = state.filter_scope else {
+        return Space::with_height(0).into();
+    };
+    let (icon, kind, name) = match scope {
+        LibraryScope::Artist(artist) => (icons::MICROPHONE, "Artist", artist.clone()),
+        LibraryScope::Album { album, artist } => (
+            icons::COMPACT_DISC,
+            "Album",
+            format!("{} by {}", album, artist),
+        ),
     };
 
-    button(text(label).size(typography::SIZE_TINY))
+    // An album links on to its artist
+    let artist_link: Element<Message> = match scope {
+        LibraryScope::Album { artist, .. } => button(
+            text(format!("All by {}", artist))
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_SECONDARY),
+        )
         .padding([spacing::XS, spacing::SM])
-        .style(move |_theme, status| {
-            let bg = match status {
-                button::Status::Hovered => {
-                    if is_active {
-                        color::PRIMARY_HOVER
-                    } else {
-                        color::SURFACE_HOVER
-                    }
-                }
-                button::Status::Pressed => {
-                    if is_active {
-                        color::PRIMARY_PRESSED
-                    } else {
-                        color::SURFACE_ELEVATED
-                    }
-                }
-                _ => bg,
-            };
-            button::Style {
-                background: Some(iced::Background::Color(bg)),
-                text_color,
-                border: iced::Border {
-                    color: border_color,
-                    width: 1.0,
-                    radius: radius::PILL.into(),
-                },
-                ..Default::default()
+        .style(theme::button_ghost)
+        .on_press(Message::FilterByScope(Some(LibraryScope::Artist(
+            artist.clone(),
+        ))))
+        .into(),
+        LibraryScope::Artist(_) => Space::with_width(0).into(),
+    };
+
+    // Covers are set by dropping an image on an album; the last can be undone
+    let cover: Element<Message> = match scope {
+        LibraryScope::Album { .. }
+            if state
+                .cover_undo
+                .as_ref()
+                .is_some_and(|(album, _)| album == scope) =>
+        {
+            button(
+                text("Undo cover")
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_SECONDARY),
+            )
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press_maybe((!state.cover_setting).then_some(Message::CoverUndo))
+            .into()
+        }
+        LibraryScope::Album { .. } if state.file_hovering => text("Drop to set the cover")
+            .size(typography::SIZE_TINY)
+            .color(color::PRIMARY)
+            .into(),
+        _ => Space::with_width(0).into(),
+    };
+
+    // Album totals from the rollups, once loaded
+    let totals = match (scope, &state.album_summary) {
+        (LibraryScope::Album { .. }, Some(summary)) => {
+            let mut totals = format!(
+                "{} tracks · {}",
+                summary.track_count,
+                crate::tasks::format_eta(std::time::Duration::from_secs(
+                    summary.total_duration.max(0) as u64
+                ))
+            );
+            if let Some(kbps) = summary.avg_bitrate {
+                totals.push_str(&format!(" · {} kbps avg", kbps));
             }
-        })
-        .on_press(on_press)
-        .into()
+            totals
+        }
+        _ => String::new(),
+    };
+
+    let header = container(
+        row![
+            icon_sized(icon, typography::SIZE_BODY).color(color::PRIMARY),
+            text(kind)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+            text(name)
+                .size(typography::SIZE_HEADING)
+                .color(color::TEXT_PRIMARY),
+            text(totals)
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED),
+            Space::with_width(Length::Fill),
+            cover,
+            artist_link,
+            button(
+                row![
+                    icon_sized(icons::XMARK, typography::SIZE_TINY).color(color::TEXT_MUTED),
+                    text("Whole library")
+                        .size(typography::SIZE_TINY)
+                        .color(color::TEXT_MUTED),
+                ]
+                .spacing(spacing::XS)
+                .align_y(iced::Alignment::Center),
+            )
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press(Message::FilterByScope(None)),
+        ]
+        .spacing(spacing::SM)
+        .align_y(iced::Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::MD])
+    .width(Length::Fill)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: iced::Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    });
+
+    column![header, Space::with_height(spacing::SM)].into()
 }
 
 /// Search input style (no border, transparent bg)
@@ -194,7 +330,10 @@ pub fn track_count_and_sort(
         SortColumn::Album => "Album",
         SortColumn::Year => "Year",
         SortColumn::Duration => "Duration",
+        SortColumn::Gain => "Gain",
         SortColumn::Format => "Format",
+        SortColumn::DateAdded => "Date Added",
+        SortColumn::DateModified => "Date Modified",
     };
     let sort_arrow = if state.sort_ascending {
         icons::ARROW_UP
@@ -217,9 +356,36 @@ pub fn track_count_and_sort(
     .style(theme::button_ghost)
     .on_press(Message::SortByColumn(state.sort_column)); // Clicking toggles direction
 
-    row![count_text, Space::with_width(Length::Fill), sort_btn,]
-        .align_y(iced::Alignment::Center)
-        .into()
+    // Measure ReplayGain of the tracks shown
+    let replaygain_label = if state.replaygain_running {
+        "Measuring…"
+    } else {
+        "ReplayGain"
+    };
+    let replaygain_btn = button(
+        row![
+            icon_sized(icons::GAUGE, typography::SIZE_TINY).color(color::TEXT_MUTED),
+            Space::with_width(spacing::XS),
+            text(replaygain_label)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+        ]
+        .align_y(iced::Alignment::Center),
+    )
+    .padding([spacing::XS, spacing::SM])
+    .style(theme::button_ghost)
+    .on_press_maybe(
+        (!state.replaygain_running && filtered > 0).then_some(Message::ReplayGainScanShown),
+    );
+
+    row![
+        count_text,
+        Space::with_width(Length::Fill),
+        replaygain_btn,
+        sort_btn,
+    ]
+    .align_y(iced::Alignment::Center)
+    .into()
 }
 
 /// Format number with commas (e.g., 3428 -> "3,428")
@@ -1,17 +1,22 @@
 //! Track list table with virtualization, headers, and row rendering.
 
-use iced::widget::{Space, button, column, container, row, scrollable, text, tooltip};
+use iced::widget::{Space, button, column, container, mouse_area, row, scrollable, text, tooltip};
 use iced::{Element, Length};
+use std::path::Path;
 
 use crate::db::TrackWithMetadata;
 #[allow(unused_imports)]
 use crate::health::QualityFlags;
+use crate::metadata::loudness;
 use crate::player::format_duration_secs;
+use crate::ui::context_menu::ContextTarget;
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::state::{LoadedState, SortColumn, virtualization as virt};
+use crate::ui::state::{ActivePane, LibraryScope, LoadedState, SortColumn, virtualization as virt};
 use crate::ui::theme::{self, color, radius, spacing, typography};
-use crate::ui::views::helpers::{calc_visible_range, format_from_path, is_lossless};
+use crate::ui::views::helpers::{
+    calc_visible_range, format_date, format_from_path, is_lossless, link,
+};
 
 /// Renders virtualized track list with play buttons
 pub fn track_list(state: &LoadedState) -> Element<'_, Message> {
@@ -30,6 +35,13 @@ pub fn track_list(state: &LoadedState) -> Element<'_, Message> {
         && state.search_query.is_empty()
         && state.filter_format.is_none()
         && state.filter_lossless.is_none()
+        && state.filter_added_within_days.is_none()
+        && !state.filter_loud_master
+        && !state.filter_instrumental
+        && !state.filter_hide_explicit
+        && state.filter_machine_written.is_none()
+        && state.filter_hide_skipped.is_none()
+        && state.filter_scope.is_none()
     {
         // No filtering - create indices for all tracks (done inline)
         &[]
@@ -50,8 +62,8 @@ pub fn track_list(state: &LoadedState) -> Element<'_, Message> {
     }
 
     let (start, end, top, bottom) = calc_visible_range(
-        state.scroll_offset,
-        state.viewport_height,
+        state.panes.library.scroll_offset,
+        state.panes.library.viewport_height,
         total_count,
         virt::TRACK_ROW_HEIGHT,
     );
@@ -59,7 +71,9 @@ pub fn track_list(state: &LoadedState) -> Element<'_, Message> {
     // Enrichment selection (for batch operations)
     let enrichment_selected = state.enrichment.selected_track;
     // Keyboard navigation selection (visual_idx is index into display list)
-    let keyboard_selection = state.library_selection;
+    let keyboard_selection = state.panes.library.selection;
+    // The playing track is marked wherever it is
+    let playing = state.player_state.current_track.as_deref();
 
     // Build track rows based on whether we're filtering or not
     let items: Vec<Element<Message>> =
@@ -78,6 +92,7 @@ pub fn track_list(state: &LoadedState) -> Element<'_, Message> {
                         idx,
                         is_enrichment_selected,
                         is_keyboard_selected,
+                        playing == Some(Path::new(&t.path)),
                         visual_idx,
                     )
                 })
@@ -97,6 +112,7 @@ pub fn track_list(state: &LoadedState) -> Element<'_, Message> {
                             idx,
                             is_enrichment_selected,
                             is_keyboard_selected,
+                            playing == Some(Path::new(&t.path)),
                             visual_idx,
                         )
                     } else {
@@ -116,6 +132,7 @@ pub fn track_list(state: &LoadedState) -> Element<'_, Message> {
     )
     .height(Length::Fill)
     .width(Length::Fill)
+    .id(ActivePane::Library.scroll_id())
     .on_scroll(Message::ScrollChanged)
     .style(theme::scrollbar_style)
     .into()
@@ -125,8 +142,8 @@ pub fn track_list(state: &LoadedState) -> Element<'_, Message> {
 pub fn track_table_header(state: &LoadedState) -> Element<'_, Message> {
     container(
         row![
-            // Spacer for play/queue buttons
-            Space::with_width(Length::Fixed(70.0)),
+            // Spacer for play/play-next/queue buttons
+            Space::with_width(Length::Fixed(105.0)),
             // Quality column (non-sortable for now) with tooltip
             tooltip(
                 container(
@@ -191,6 +208,19 @@ pub fn track_table_header(state: &LoadedState) -> Element<'_, Message> {
             // Duration column
             container(sortable_header_btn("Time", SortColumn::Duration, state))
                 .width(Length::Fixed(60.0)),
+            // ReplayGain column
+            container(sortable_header_btn("Gain", SortColumn::Gain, state))
+                .width(Length::Fixed(60.0)),
+            // Date added column
+            container(sortable_header_btn("Added", SortColumn::DateAdded, state))
+                .width(Length::Fixed(80.0)),
+            // Date modified column
+            container(sortable_header_btn(
+                "Modified",
+                SortColumn::DateModified,
+                state
+            ))
+            .width(Length::Fixed(80.0)),
             // Format column
             container(sortable_header_btn("Format", SortColumn::Format, state))
                 .width(Length::Fixed(60.0)),
@@ -290,19 +320,28 @@ fn header_btn_style(_theme: &iced::Theme, status: button::Status) -> button::Sty
 ///
 /// - `is_enrichment_selected`: Track is selected for enrichment operations
 /// - `is_keyboard_selected`: Track is selected via keyboard navigation (visual focus)
+/// - `is_playing`: Track is the one playing (speaker icon, highlighted title)
 /// - `visual_idx`: Index in the displayed list (for keyboard navigation selection)
 fn track_row(
     t: &TrackWithMetadata,
     idx: usize,
     is_enrichment_selected: bool,
     is_keyboard_selected: bool,
+    is_playing: bool,
     visual_idx: usize,
 ) -> Element<'_, Message> {
-    let format_str = format_from_path(&t.path);
+    // Streams and cloud links show their source in the format column
+    let format_str = if t.source.is_local() {
+        format_from_path(&t.path)
+    } else {
+        t.source.label()
+    };
     let lossless = is_lossless(format_str);
 
     // Format badge colors - subtle differentiation
-    let (badge_bg, badge_text) = if lossless {
+    let (badge_bg, badge_text) = if !t.source.is_local() {
+        (color::SURFACE_ELEVATED, color::PRIMARY)
+    } else if lossless {
         (color::SURFACE_ELEVATED, color::SUCCESS) // Green text, subtle bg
     } else {
         (color::SURFACE_ELEVATED, color::TEXT_MUTED)
@@ -310,6 +349,12 @@ fn track_row(
 
     let year_str = t.year.map(|y| y.to_string()).unwrap_or_default();
     let duration_str = format_duration_secs(t.duration.unwrap_or(0) as f32);
+    let added_str = format_date(t.added_at);
+    let modified_str = format_date(t.updated_at);
+    let gain_str = t
+        .track_gain
+        .map(|g| format!("{:+.1}", g))
+        .unwrap_or_default();
 
     // Row background based on selection and alternating
     // Priority: keyboard selection > enrichment selection > alternating
@@ -328,11 +373,22 @@ fn track_row(
     } else {
         color::TEXT_SECONDARY
     };
+    // On a selected row the highlight already stands out
+    let title_color = if is_playing && !is_keyboard_selected && !is_enrichment_selected {
+        color::PRIMARY
+    } else {
+        text_color
+    };
     let muted_color = if is_keyboard_selected || is_enrichment_selected {
         color::TEXT_SECONDARY
     } else {
         color::TEXT_MUTED
     };
+    let gain_color = if loudness::is_loud_master(t.track_gain, t.track_peak) {
+        color::WARNING
+    } else {
+        muted_color
+    };
 
     // Left border indicator for keyboard selection
     let selection_indicator = if is_keyboard_selected {
@@ -352,40 +408,45 @@ fn track_row(
     let row_content = row![
         // Selection indicator (left edge highlight)
         selection_indicator,
-        // Play button
-        button(icon_sized(icons::PLAY, typography::SIZE_TINY).color(color::TEXT_MUTED))
-            .padding([spacing::XS, spacing::SM])
-            .style(theme::button_ghost)
-            .on_press(Message::PlayerPlayTrack(idx)),
+        // Play button (a speaker on the playing track)
+        button(if is_playing {
+            icon_sized(icons::VOLUME_HIGH, typography::SIZE_TINY).color(title_color)
+        } else {
+            icon_sized(icons::PLAY, typography::SIZE_TINY).color(color::TEXT_MUTED)
+        })
+        .padding([spacing::XS, spacing::SM])
+        .style(theme::button_ghost)
+        .on_press(Message::PlayerPlayTrack(idx)),
+        // Play next button
+        row_action(icons::FORWARD, "Play next", Message::PlayerPlayNext(idx)),
         // Queue button
-        button(icon_sized(icons::PLUS, typography::SIZE_TINY).color(color::TEXT_MUTED))
-            .padding([spacing::XS, spacing::SM])
-            .style(theme::button_ghost)
-            .on_press(Message::PlayerQueueTrack(idx)),
+        row_action(icons::PLUS, "Add to queue", Message::PlayerQueueTrack(idx)),
         // Quality indicator
         quality_indicator,
         // Title
         container(
             text(&t.title)
                 .size(typography::SIZE_SMALL)
-                .color(text_color)
+                .color(title_color)
         )
         .width(Length::FillPortion(3))
         .center_y(Length::Fixed(virt::TRACK_ROW_HEIGHT)),
-        // Artist
-        container(
-            text(&t.artist_name)
+        // Artist (goes to the artist's tracks)
+        container(link(
+            text(t.artist_name.as_str())
                 .size(typography::SIZE_SMALL)
-                .color(text_color)
-        )
+                .color(text_color),
+            Message::GoTo(LibraryScope::Artist(t.artist_name.to_string())),
+        ))
         .width(Length::FillPortion(2))
         .center_y(Length::Fixed(virt::TRACK_ROW_HEIGHT)),
-        // Album
-        container(
-            text(&t.album_name)
+        // Album (goes to the album)
+        container(link(
+            text(t.album_name.as_str())
                 .size(typography::SIZE_TINY)
-                .color(muted_color)
-        )
+                .color(muted_color),
+            Message::GoTo(LibraryScope::album_of(t)),
+        ))
         .width(Length::FillPortion(2))
         .center_y(Length::Fixed(virt::TRACK_ROW_HEIGHT)),
         // Year
@@ -404,6 +465,26 @@ fn track_row(
         )
         .width(Length::Fixed(60.0))
         .center_y(Length::Fixed(virt::TRACK_ROW_HEIGHT)),
+        // ReplayGain track gain (loud masters highlighted)
+        container(text(gain_str).size(typography::SIZE_TINY).color(gain_color))
+            .width(Length::Fixed(60.0))
+            .center_y(Length::Fixed(virt::TRACK_ROW_HEIGHT)),
+        // Date added
+        container(
+            text(added_str)
+                .size(typography::SIZE_TINY)
+                .color(muted_color)
+        )
+        .width(Length::Fixed(80.0))
+        .center_y(Length::Fixed(virt::TRACK_ROW_HEIGHT)),
+        // Date modified
+        container(
+            text(modified_str)
+                .size(typography::SIZE_TINY)
+                .color(muted_color)
+        )
+        .width(Length::Fixed(80.0))
+        .center_y(Length::Fixed(virt::TRACK_ROW_HEIGHT)),
         // Format badge
         container(
             container(
@@ -424,11 +505,11 @@ fn track_row(
         .width(Length::Fixed(60.0))
         .center_y(Length::Fixed(virt::TRACK_ROW_HEIGHT))
         .center_x(Length::Fixed(60.0)),
-        // Context menu button (opens track detail for now, will become dropdown)
+        // Context menu button (same menu as right-clicking the row)
         button(icon_sized(icons::ELLIPSIS_V, typography::SIZE_SMALL).color(color::TEXT_MUTED))
             .padding([spacing::XS, spacing::SM])
             .style(theme::button_ghost)
-            .on_press(Message::TrackDetailOpen(idx)),
+            .on_press(Message::ContextMenuOpen(ContextTarget::Track(idx))),
         // Right padding to match left side and avoid scrollbar
         Space::with_width(spacing::SM),
     ]
@@ -436,8 +517,8 @@ fn track_row(
     .align_y(iced::Alignment::Center);
 
     // Wrap in button for hover effect and selection
-    // Click selects the track for keyboard navigation
-    button(
+    // Click selects the track for keyboard navigation; right-click opens the menu
+    let row_button = button(
         container(row_content)
             .height(Length::Fixed(virt::TRACK_ROW_HEIGHT))
             .width(Length::Fill),
@@ -457,8 +538,11 @@ fn track_row(
     })
     .padding(0)
     .width(Length::Fill)
-    .on_press(Message::LibrarySelectIndex(visual_idx))
-    .into()
+    .on_press(Message::LibrarySelectIndex(visual_idx));
+
+    mouse_area(row_button)
+        .on_right_press(Message::ContextMenuOpen(ContextTarget::Track(idx)))
+        .into()
 }
 
 /// Render quality indicator badge with tooltip
@@ -584,3 +668,26 @@ fn loading_state(tick: u32) -> Element<'static, Message> {
     use super::super::loading::{LoadingContext, loading_state_large};
     loading_state_large(LoadingContext::Library, tick, None)
 }
+
+/// Small icon button in a track row, labelled by a tooltip
+fn row_action(icon: char, label: &'static str, msg: Message) -> Element<'static, Message> {
+    tooltip(
+        button(icon_sized(icon, typography::SIZE_TINY).color(color::TEXT_MUTED))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press(msg),
+        text(label).size(typography::SIZE_TINY),
+        tooltip::Position::Top,
+    )
+    .gap(spacing::XS)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: iced::Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
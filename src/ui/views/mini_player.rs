@@ -0,0 +1,166 @@
+//! Compact mini-player window.
+//!
+//! Replaces the whole layout while `MiniPlayerState::active`: cover art,
+//! title and artist, transport controls and the seek bar, plus buttons to
+//! pin the window on top and to go back to the full window.
+
+use iced::widget::{Space, button, column, container, image, row, text, tooltip};
+use iced::{Border, Element, Length};
+
+use crate::player::{PlaybackStatus, format_duration_secs};
+use crate::ui::icons::{self, icon_sized};
+use crate::ui::messages::Message;
+use crate::ui::state::LoadedState;
+use crate::ui::theme::{self, color, spacing, typography};
+
+/// Cover art edge length
+const COVER_SIZE: f32 = 96.0;
+
+/// The mini-player, filling the window
+pub fn mini_player_view(s: &LoadedState) -> Element<'_, Message> {
+    let state = &s.player_state;
+
+    let (title, artist) = s
+        .current_track_display()
+        .map(|(title, artist, _)| (title, artist))
+        .unwrap_or_else(|| ("No track playing".to_string(), String::new()));
+
+    let pin_color = if s.mini_player.always_on_top {
+        color::PRIMARY
+    } else {
+        color::TEXT_MUTED
+    };
+    let pin_label = if s.mini_player.always_on_top {
+        "Unpin from top"
+    } else {
+        "Keep on top"
+    };
+    let header = row![
+        column![
+            text(title)
+                .size(typography::SIZE_BODY)
+                .color(if state.current_track.is_some() {
+                    color::TEXT_PRIMARY
+                } else {
+                    color::TEXT_MUTED
+                }),
+            text(artist)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+        ]
+        .spacing(2)
+        .width(Length::Fill),
+        tooltip(
+            button(icon_sized(icons::THUMBTACK, typography::SIZE_SMALL).color(pin_color))
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_ghost)
+                .on_press(Message::MiniPlayerToggleAlwaysOnTop),
+            text(pin_label).size(typography::SIZE_SMALL),
+            tooltip::Position::Bottom,
+        ),
+        tooltip(
+            button(icon_sized(icons::EXPAND, typography::SIZE_SMALL))
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_ghost)
+                .on_press(Message::MiniPlayerToggle),
+            text("Full window (Ctrl+M)").size(typography::SIZE_SMALL),
+            tooltip::Position::Bottom,
+        ),
+    ]
+    .align_y(iced::Alignment::Start);
+
+    let play_btn = match state.status {
+        PlaybackStatus::Playing => button(icon_sized(icons::PAUSE, typography::SIZE_BODY))
+            .padding([spacing::XS, spacing::MD])
+            .style(theme::button_primary)
+            .on_press(Message::PlayerPause),
+        _ => button(icon_sized(icons::PLAY, typography::SIZE_BODY))
+            .padding([spacing::XS, spacing::MD])
+            .style(theme::button_primary)
+            .on_press(Message::PlayerPlay),
+    };
+    let transport = row![
+        button(icon_sized(icons::SKIP_BACK, typography::SIZE_SMALL))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press(Message::PlayerPrevious),
+        play_btn,
+        button(icon_sized(icons::SKIP_FORWARD, typography::SIZE_SMALL))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press(Message::PlayerNext),
+    ]
+    .spacing(spacing::XS)
+    .align_y(iced::Alignment::Center);
+
+    // Same seek behaviour as the player bar: preview while dragging
+    let position = s.seek_preview.unwrap_or_else(|| state.position_fraction());
+    let time = if s.seek_preview.is_some() {
+        format_duration_secs(position * state.duration.as_secs_f32())
+    } else {
+        state.position_str()
+    };
+    let seek_row = row![
+        text(time)
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_SECONDARY),
+        super::seek_bar::seek_bar(s),
+        text(state.duration_str())
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_SECONDARY),
+    ]
+    .spacing(spacing::SM)
+    .align_y(iced::Alignment::Center);
+
+    let controls = column![
+        header,
+        Space::with_height(Length::Fill),
+        transport,
+        seek_row
+    ]
+    .spacing(spacing::XS)
+    .height(Length::Fill);
+
+    container(
+        row![cover(s), controls]
+            .spacing(spacing::MD)
+            .height(Length::Fill),
+    )
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .padding(spacing::SM)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::BASE)),
+        ..Default::default()
+    })
+    .into()
+}
+
+/// Cover art (placeholder icon when there is none)
+fn cover(s: &LoadedState) -> Element<'_, Message> {
+    let art: Element<Message> = match &s.cover_art.current {
+        Some(cover) => image(image::Handle::from_bytes(cover.data.clone()))
+            .width(Length::Fixed(COVER_SIZE))
+            .height(Length::Fixed(COVER_SIZE))
+            .content_fit(iced::ContentFit::Cover)
+            .into(),
+        None => icon_sized(icons::MUSIC, typography::SIZE_TITLE)
+            .color(color::TEXT_MUTED)
+            .into(),
+    };
+    container(art)
+        .width(Length::Fixed(COVER_SIZE))
+        .height(Length::Fixed(COVER_SIZE))
+        .center_x(Length::Fixed(COVER_SIZE))
+        .center_y(Length::Fixed(COVER_SIZE))
+        .clip(true)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+            border: Border {
+                radius: 4.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .into()
+}
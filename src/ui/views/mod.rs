@@ -2,25 +2,46 @@
 //!
 //! This module is organized into submodules by concern:
 //! - `layout`: Main layout composition (sidebar, panes)
+//! - `activity`: Library change feed timeline
+//! - `stats`: Local usage statistics
+//! - `context_menu`: Right-click menu overlay
 //! - `player`: Player controls and visualization
+//! - `level_meter`: Level meters, clip indicator and level history
 //! - `library`: Library pane with track list and organization
 //! - `settings`: Settings pane with organized sections
 //! - `enrich`: Batch enrichment pane
 //! - `diagnostics`: System diagnostics view
+//! - `mini_player`: Compact mini-player window
+//! - `now_playing`: Full-screen Now Playing view
+//! - `equalizer`: Ten-band equalizer
+//! - `playlists`: Playlists and their tracks
 //! - `track_detail`: Track detail modal
+//! - `tasks`: Background tasks popover
+//! - `perf_overlay`: Decoder, buffer and frame timing overlay (F12)
 //! - `toast`: Toast notifications
 //! - `loading`: Loading states with fun messages
 
+mod activity;
+mod context_menu;
 mod diagnostics_view;
 mod enrich;
+mod equalizer;
 pub mod helpers;
 mod layout;
+mod level_meter;
 mod library;
 pub mod loading;
+mod mini_player;
+mod now_playing;
+mod perf_overlay;
 mod player;
+mod playlists;
+mod seek_bar;
 mod settings;
+mod stats;
+mod tasks;
 pub mod toast;
 mod track_detail;
 
-pub use layout::loaded_view;
+pub use layout::{loaded_view, startup_skeleton};
 pub use toast::ToastQueue;
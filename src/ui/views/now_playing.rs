@@ -0,0 +1,281 @@
+//! Full-screen Now Playing view.
+//!
+//! Covers the whole window with large cover art, the visualizer, the
+//! current track, what plays next and the track's lyrics. Meant to be left
+//! running on a TV or party screen: after a while without input only the
+//! art and title remain, on black (see `NowPlayingViewState::is_dimmed`).
+
+use iced::widget::{Space, button, column, container, image, row, scrollable, text};
+use iced::{Element, Length};
+use std::path::Path;
+
+use crate::player::format_duration_secs;
+use crate::ui::canvas::visualization_view;
+use crate::ui::icons::{self, icon_sized};
+use crate::ui::messages::Message;
+use crate::ui::state::{LibraryScope, LoadedState, VisualizationMode};
+use crate::ui::theme::{self, color, layout, spacing, typography};
+use crate::ui::views::helpers::link;
+
+/// Queue items listed under "Up next"
+const UPCOMING_SHOWN: usize = 5;
+/// Visualizer height
+const VISUALIZER_HEIGHT: f32 = 140.0;
+/// Shortcut hint along the bottom
+const SHORTCUTS: &str = "Esc close  •  Space play/pause  •  ←/→ previous/next  •  \
+    V visualizer  •  L lyrics  •  D idle dim  •  F full screen";
+
+/// The view, when it is open
+pub fn now_playing_view(s: &LoadedState) -> Option<Element<'_, Message>> {
+    let view = &s.now_playing_view;
+    if !view.open {
+        return None;
+    }
+    let prefs = s.panes.now_playing_view;
+    let dimmed = view.is_dimmed(prefs);
+
+    let (title, artist, album) = s
+        .current_track_display()
+        .unwrap_or_else(|| ("No Track Playing".to_string(), String::new(), String::new()));
+    if dimmed {
+        return Some(dimmed_view(s, title, artist));
+    }
+
+    let state = &s.player_state;
+    let progress = if state.current_track.is_some() {
+        format!(
+            "{} / {}",
+            format_duration_secs(state.position.as_secs_f32()),
+            format_duration_secs(state.duration.as_secs_f32())
+        )
+    } else {
+        String::new()
+    };
+
+    let info = column![
+        text(title)
+            .size(typography::SIZE_HERO)
+            .color(color::TEXT_PRIMARY),
+        // Links close the view and go to the library
+        link(
+            text(artist.clone())
+                .size(typography::SIZE_TITLE)
+                .color(color::TEXT_SECONDARY),
+            Message::GoTo(LibraryScope::Artist(artist.clone())),
+        ),
+        link(
+            text(album.clone())
+                .size(typography::SIZE_HEADING)
+                .color(color::TEXT_MUTED),
+            Message::GoTo(LibraryScope::Album { album, artist }),
+        ),
+        Space::with_height(spacing::LG),
+        text(progress)
+            .size(typography::SIZE_BODY)
+            .color(color::TEXT_MUTED),
+        text(state.format_info())
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED),
+        Space::with_height(spacing::XL),
+        upcoming_section(s),
+    ]
+    .spacing(spacing::XS)
+    .width(Length::Fill);
+
+    let lyrics = view
+        .lyrics_of(state.current_track.as_ref())
+        .filter(|_| prefs.show_lyrics);
+    let mut middle = row![cover(s), Space::with_width(spacing::XL), info]
+        .align_y(iced::Alignment::Center)
+        .height(Length::Fill);
+    if let Some(lyrics) = lyrics {
+        middle = middle.push(lyrics_panel(lyrics));
+    }
+
+    let visualizer: Element<Message> = if s.visualization_mode == VisualizationMode::Off {
+        Space::with_height(VISUALIZER_HEIGHT).into()
+    } else {
+        visualization_view(s.visualization_mode, &s.visualization, VISUALIZER_HEIGHT)
+    };
+
+    let content = column![
+        header(view.window_fullscreen),
+        Space::with_height(spacing::LG),
+        middle,
+        Space::with_height(spacing::LG),
+        visualizer,
+        Space::with_height(spacing::SM),
+        text(SHORTCUTS)
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+    ]
+    .padding(spacing::XL);
+
+    let base = container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::BASE)),
+            ..Default::default()
+        });
+
+    Some(base.into())
+}
+
+/// Idle layout: just the art and track on black, so a TV isn't lit by
+/// controls nobody is using
+fn dimmed_view<'a>(s: &'a LoadedState, title: String, artist: String) -> Element<'a, Message> {
+    let content = column![
+        cover(s),
+        Space::with_height(spacing::LG),
+        text(title)
+            .size(typography::SIZE_HERO)
+            .color(color::TEXT_SECONDARY),
+        text(artist)
+            .size(typography::SIZE_TITLE)
+            .color(color::TEXT_MUTED),
+    ]
+    .align_x(iced::Alignment::Center);
+
+    container(content)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x(Length::Fill)
+        .center_y(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(iced::Color::BLACK)),
+            ..Default::default()
+        })
+        .into()
+}
+
+fn header(fullscreen: bool) -> Element<'static, Message> {
+    let (icon, label) = if fullscreen {
+        (icons::COMPRESS, "Leave full screen")
+    } else {
+        (icons::EXPAND, "Full screen")
+    };
+    row![
+        text("NOW PLAYING")
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+        Space::with_width(Length::Fill),
+        button(
+            row![
+                icon_sized(icon, typography::SIZE_SMALL).color(color::TEXT_SECONDARY),
+                text(label).size(typography::SIZE_SMALL),
+            ]
+            .spacing(spacing::XS)
+            .align_y(iced::Alignment::Center),
+        )
+        .padding([spacing::XS, spacing::SM])
+        .style(theme::button_ghost)
+        .on_press(Message::NowPlayingToggleFullscreen),
+        button(icon_sized(icons::XMARK, typography::SIZE_BODY))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press(Message::NowPlayingViewClose),
+    ]
+    .spacing(spacing::SM)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
+/// Cover art scaled to the window (placeholder icon when there is none)
+fn cover(s: &LoadedState) -> Element<'_, Message> {
+    let size = if s.window_size.height > 0.0 {
+        (s.window_size.height * 0.5).clamp(layout::COVER_ART_LARGE as f32, 640.0)
+    } else {
+        layout::COVER_ART_LARGE as f32
+    };
+
+    let art: Element<Message> = match &s.cover_art.current {
+        Some(cover) => image(image::Handle::from_bytes(cover.data.clone()))
+            .width(Length::Fixed(size))
+            .height(Length::Fixed(size))
+            .content_fit(iced::ContentFit::Cover)
+            .into(),
+        None => icon_sized(icons::MUSIC, 96).color(color::TEXT_MUTED).into(),
+    };
+    container(art)
+        .width(Length::Fixed(size))
+        .height(Length::Fixed(size))
+        .center_x(Length::Fixed(size))
+        .center_y(Length::Fixed(size))
+        .clip(true)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+            border: iced::Border {
+                radius: 8.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
+/// The next few queue items in play order
+fn upcoming_section(s: &LoadedState) -> Element<'_, Message> {
+    let Some(player) = &s.player else {
+        return Space::with_height(0).into();
+    };
+    let queue = player.queue();
+    let upcoming: Vec<Element<Message>> = queue
+        .upcoming()
+        .into_iter()
+        .take(UPCOMING_SHOWN)
+        .filter_map(|i| queue.items().get(i))
+        .map(|item| {
+            let label = match s.track_info_by_path(&item.path) {
+                Some(track) => format!("{} - {}", track.artist_name, track.title),
+                None => display_name(&item.path),
+            };
+            text(label)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY)
+                .into()
+        })
+        .collect();
+    if upcoming.is_empty() {
+        return Space::with_height(0).into();
+    }
+
+    column![
+        text("UP NEXT")
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+        column(upcoming).spacing(2),
+    ]
+    .spacing(spacing::XS)
+    .into()
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+fn lyrics_panel(lyrics: &str) -> Element<'_, Message> {
+    container(
+        scrollable(
+            text(lyrics)
+                .size(typography::SIZE_BODY)
+                .color(color::TEXT_SECONDARY),
+        )
+        .height(Length::Fill),
+    )
+    .width(Length::Fixed(360.0))
+    .height(Length::Fill)
+    .padding(spacing::MD)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE)),
+        border: iced::Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: 6.0.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
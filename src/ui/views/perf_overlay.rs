@@ -0,0 +1,216 @@
+//! Performance overlay (F12): decoder thread load, the ring buffer fill
+//! over the last few seconds, the UI's update/view timing and the player's
+//! channel backlogs.
+//!
+//! Meant for chasing stutter and "Event channel full" warnings on slow
+//! machines: a decoder near full load can't keep the buffer filled, and
+//! ticks far apart from 16ms mean the UI is falling behind its events.
+
+use std::time::Duration;
+
+use iced::mouse::Cursor;
+use iced::widget::canvas::{self, Canvas, Frame, Geometry};
+use iced::widget::{column, container, row, text};
+use iced::{Border, Color, Element, Length, Padding, Point, Rectangle, Size, Theme};
+
+use crate::player::ChannelBacklog;
+use crate::ui::messages::Message;
+use crate::ui::state::{LoadedState, PERF_HISTORY_LEN, TimingStats};
+use crate::ui::theme::{color, radius, spacing, typography};
+
+/// Width of the overlay panel
+const PANEL_WIDTH: f32 = 280.0;
+
+/// Height of the buffer fill graph
+const GRAPH_HEIGHT: f32 = 48.0;
+
+/// Decoder load past which it is shown as a warning
+const LOAD_WARN: f32 = 0.5;
+
+/// Tick interval past which the UI is shown as falling behind
+const TICK_WARN: Duration = Duration::from_millis(33);
+
+/// The overlay in the top-right corner, if open
+pub fn perf_overlay(s: &LoadedState) -> Option<Element<'_, Message>> {
+    if !s.perf.open {
+        return None;
+    }
+    let report = &s.perf.report;
+
+    let panel = column![
+        text("Performance (F12)")
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_PRIMARY),
+        section("Decoder"),
+        stat(
+            "Decode thread",
+            percent(report.decode_load + report.resample_load),
+            report.decode_load + report.resample_load >= LOAD_WARN,
+        ),
+        stat(
+            "Resampler",
+            percent(report.resample_load),
+            report.resample_load >= LOAD_WARN / 2.0,
+        ),
+        stat(
+            "Underruns",
+            report.underruns.to_string(),
+            report.underruns > 0,
+        ),
+        section("Ring buffer fill"),
+        Canvas::new(FillGraph {
+            points: s.perf.fill_history.iter().copied().collect(),
+        })
+        .width(Length::Fill)
+        .height(Length::Fixed(GRAPH_HEIGHT)),
+        section("UI"),
+        stat(
+            "Tick interval",
+            timing(report.tick),
+            report.tick.max >= TICK_WARN,
+        ),
+        stat("Update", timing(report.update), false),
+        stat("View", timing(report.view), false),
+        stat(
+            "Messages",
+            format!("{:.0}/s", report.messages_per_sec),
+            false,
+        ),
+        section("Channels"),
+        stat(
+            "Events",
+            format!(
+                "{} (peak {})",
+                backlog(report.backlogs.events),
+                report.peak_event_backlog
+            ),
+            report.peak_event_backlog >= report.backlogs.events.capacity.max(1),
+        ),
+        stat(
+            "Commands",
+            backlog(report.backlogs.commands),
+            report.backlogs.commands.is_full(),
+        ),
+        stat("Spectrum", backlog(report.backlogs.visualization), false),
+        stat(
+            "Events dropped",
+            report.events_dropped.to_string(),
+            report.events_dropped > 0,
+        ),
+    ]
+    .spacing(spacing::XS)
+    .width(Length::Fixed(PANEL_WIDTH));
+
+    let card = container(panel)
+        .padding(spacing::MD)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::with_alpha(
+                color::SURFACE_ELEVATED,
+                0.92,
+            ))),
+            border: Border {
+                color: color::BORDER,
+                width: 1.0,
+                radius: radius::MD.into(),
+            },
+            ..Default::default()
+        });
+
+    Some(
+        container(card)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(iced::alignment::Horizontal::Right)
+            .align_y(iced::alignment::Vertical::Top)
+            .padding(Padding {
+                top: spacing::XL as f32,
+                right: spacing::XL as f32,
+                bottom: 0.0,
+                left: 0.0,
+            })
+            .into(),
+    )
+}
+
+fn section<'a>(title: &'a str) -> Element<'a, Message> {
+    text(title)
+        .size(typography::SIZE_TINY)
+        .color(color::TEXT_MUTED)
+        .into()
+}
+
+/// A labelled figure, in amber when `warn`
+fn stat<'a>(label: &'a str, value: String, warn: bool) -> Element<'a, Message> {
+    row![
+        text(label)
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_SECONDARY)
+            .width(Length::Fill),
+        text(value).size(typography::SIZE_SMALL).color(if warn {
+            color::WARNING
+        } else {
+            color::TEXT_PRIMARY
+        }),
+    ]
+    .into()
+}
+
+fn percent(share: f32) -> String {
+    format!("{:.1}%", share * 100.0)
+}
+
+fn timing(stats: TimingStats) -> String {
+    format!("{} avg, {} max", millis(stats.avg), millis(stats.max))
+}
+
+fn millis(d: Duration) -> String {
+    format!("{:.1}ms", d.as_secs_f32() * 1000.0)
+}
+
+fn backlog(channel: ChannelBacklog) -> String {
+    format!("{}/{}", channel.queued, channel.capacity)
+}
+
+/// Ring buffer fill per tick, newest at the right
+struct FillGraph {
+    points: Vec<u8>,
+}
+
+impl canvas::Program<Message> for FillGraph {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let size = bounds.size();
+        frame.fill_rectangle(Point::ORIGIN, size, color::SURFACE);
+
+        let step = size.width / PERF_HISTORY_LEN as f32;
+        let x0 = size.width - self.points.len() as f32 * step;
+        for (i, &fill) in self.points.iter().enumerate() {
+            let h = size.height * f32::from(fill) / 100.0;
+            frame.fill_rectangle(
+                Point::new(x0 + i as f32 * step, size.height - h),
+                Size::new(step.max(1.0), h),
+                fill_color(fill),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Red when the buffer has nearly run dry, amber when low
+fn fill_color(fill: u8) -> Color {
+    match fill {
+        0..10 => color::ERROR,
+        10..25 => color::WARNING,
+        _ => color::with_alpha(color::SUCCESS, 0.7),
+    }
+}
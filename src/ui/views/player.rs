@@ -1,13 +1,16 @@
 //! Player controls and related UI components.
 
-use iced::widget::{Space, button, column, container, image, pick_list, row, slider, text};
+use iced::widget::{
+    Space, button, column, container, image, mouse_area, pick_list, row, slider, text, tooltip,
+};
 use iced::{Border, Element, Length};
 
-use crate::player::{PlaybackStatus, format_duration_secs};
+use crate::player::{PlaybackStatus, format_duration_secs, volume};
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::state::LoadedState;
+use crate::ui::state::{LibraryScope, LoadedState};
 use crate::ui::theme::{self, color, layout, spacing, typography};
+use crate::ui::views::helpers::link;
 
 /// Maximum volume level (because this one goes to 11)
 const MAX_VOLUME: f32 = 11.0;
@@ -59,21 +62,36 @@ pub fn player_controls(s: &LoadedState) -> Element<'_, Message> {
             .into()
     };
 
-    // Track info - stacked: Title on top, "Artist • Album" below
+    // Track info - stacked: Title on top, "Artist • Album" below, each a
+    // link to its tracks in the library
     // Uses fallback chain: DB → file tags → filename
-    let (title, artist_album) =
-        if let Some((track_title, artist, album)) = s.current_track_display() {
-            let artist_album_str = if !album.is_empty() {
-                format!("{} • {}", artist, album)
-            } else if !artist.is_empty() {
-                artist
-            } else {
-                String::new()
-            };
-            (track_title, artist_album_str)
-        } else {
-            ("No track playing".to_string(), String::new())
-        };
+    let (title, artist, album) = s
+        .current_track_display()
+        .unwrap_or_else(|| ("No track playing".to_string(), String::new(), String::new()));
+    let mut artist_album = row![].spacing(spacing::XS);
+    if !artist.is_empty() {
+        artist_album = artist_album.push(link(
+            text(artist.clone())
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+            Message::GoTo(LibraryScope::Artist(artist.clone())),
+        ));
+    }
+    if !album.is_empty() {
+        if !artist.is_empty() {
+            artist_album = artist_album.push(
+                text("•")
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_MUTED),
+            );
+        }
+        artist_album = artist_album.push(link(
+            text(album.clone())
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+            Message::GoTo(LibraryScope::Album { album, artist }),
+        ));
+    }
 
     let track_info_col = column![
         text(title)
@@ -83,20 +101,23 @@ pub fn player_controls(s: &LoadedState) -> Element<'_, Message> {
             } else {
                 color::TEXT_MUTED
             }),
-        text(artist_album)
-            .size(typography::SIZE_SMALL)
-            .color(color::TEXT_SECONDARY),
+        artist_album,
     ]
     .spacing(2);
 
-    let left_section = row![
-        Space::with_width(spacing::SM), // Padding before cover art
-        cover_widget,
-        Space::with_width(spacing::MD),
-        track_info_col,
-    ]
-    .align_y(iced::Alignment::Center)
-    .width(Length::Fixed(240.0)); // Fixed width for cover + track info
+    // Double-click opens the full-screen Now Playing view
+    let left_section = mouse_area(
+        row![
+            Space::with_width(spacing::SM), // Padding before cover art
+            cover_widget,
+            Space::with_width(spacing::MD),
+            track_info_col,
+        ]
+        .align_y(iced::Alignment::Center)
+        .width(Length::Fixed(240.0)), // Fixed width for cover + track info
+    )
+    .on_press(Message::NowPlayingBarClicked)
+    .interaction(iced::mouse::Interaction::Pointer);
 
     // =========================================================================
     // CENTER SECTION: Transport Controls + Seek Bar
@@ -145,12 +166,8 @@ pub fn player_controls(s: &LoadedState) -> Element<'_, Message> {
         .size(typography::SIZE_TINY)
         .color(color::TEXT_SECONDARY);
 
-    // Seek slider - fills available space
-    let seek_slider = slider(0.0..=1.0, display_pos, Message::PlayerSeekPreview)
-        .on_release(Message::PlayerSeekRelease)
-        .step(0.001)
-        .width(Length::Fill)
-        .style(theme::slider_style);
+    // Seek slider with markers - fills available space
+    let seek_slider = super::seek_bar::seek_bar(s);
 
     let seek_row = row![
         time_current,
@@ -167,7 +184,7 @@ pub fn player_controls(s: &LoadedState) -> Element<'_, Message> {
         .width(Length::Fill); // Stretch to fill available space
 
     // =========================================================================
-    // RIGHT SECTION: Shuffle/Repeat + Volume + Device
+    // RIGHT SECTION: Shuffle/Repeat + Volume + Levels + Device
     // =========================================================================
 
     // Shuffle button
@@ -181,9 +198,9 @@ pub fn player_controls(s: &LoadedState) -> Element<'_, Message> {
         .padding([spacing::XS, spacing::SM])
         .style(theme::button_ghost);
 
-    // Volume section
-    let volume_display = state.volume * MAX_VOLUME;
-    let volume_icon_char = if volume_display < 0.1 {
+    // Volume section: the slider moves on a dB scale (see `player::volume`)
+    let volume_display = volume::position(state.volume) * MAX_VOLUME;
+    let volume_icon_char = if state.volume <= 0.0 {
         icons::VOLUME_MUTE
     } else if volume_display < 5.0 {
         icons::VOLUME_LOW
@@ -199,11 +216,33 @@ pub fn player_controls(s: &LoadedState) -> Element<'_, Message> {
     .align_x(iced::alignment::Horizontal::Left);
 
     let volume_slider = slider(0.0..=MAX_VOLUME, volume_display, |v| {
-        Message::PlayerVolumeChanged(v / MAX_VOLUME)
+        Message::PlayerVolumeChanged(volume::amplitude(v / MAX_VOLUME))
     })
-    .step(0.5)
+    .step(0.1)
     .width(Length::Fixed(80.0))
     .style(theme::slider_style);
+    // The wheel steps like the arrow keys, by the configured dB
+    let volume_slider = tooltip(
+        mouse_area(volume_slider).on_scroll(|delta| {
+            let y = match delta {
+                iced::mouse::ScrollDelta::Lines { y, .. }
+                | iced::mouse::ScrollDelta::Pixels { y, .. } => y,
+            };
+            Message::PlayerVolumeStep { up: y > 0.0 }
+        }),
+        text(volume::label(state.volume)).size(typography::SIZE_TINY),
+        tooltip::Position::Top,
+    )
+    .gap(spacing::XS)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: 4.0.into(),
+        },
+        ..Default::default()
+    });
 
     // Audio device - icon with dropdown picker (fixed width to prevent layout shift)
     // Choose icon based on device name (headphone vs speaker)
@@ -257,18 +296,45 @@ pub fn player_controls(s: &LoadedState) -> Element<'_, Message> {
         volume_icon_container,
         volume_slider,
         Space::with_width(spacing::SM),
+        super::level_meter::level_meter(&s.level_meter),
+        Space::with_width(spacing::SM),
         device_section,
     ]
     .spacing(spacing::XS)
     .align_y(iced::Alignment::Center)
     .width(Length::Shrink); // Fixed size, don't stretch or squish
 
+    // Scroll the library (or queue) to the playing track
+    let locate_btn = tooltip(
+        button(icon_sized(icons::LOCATE, typography::SIZE_SMALL))
+            .padding(spacing::XS)
+            .style(theme::button_icon)
+            .on_press_maybe(
+                state
+                    .current_track
+                    .is_some()
+                    .then_some(Message::LocateCurrentTrack),
+            ),
+        text("Show playing track (Ctrl+J)").size(typography::SIZE_TINY),
+        tooltip::Position::Top,
+    )
+    .gap(spacing::XS)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: 4.0.into(),
+        },
+        ..Default::default()
+    });
+
     // =========================================================================
     // ASSEMBLE PLAYER BAR
     // =========================================================================
 
     container(
-        row![left_section, center_section, right_section,]
+        row![left_section, locate_btn, center_section, right_section,]
             .spacing(spacing::LG)
             .align_y(iced::Alignment::Center)
             .padding([spacing::SM, spacing::LG]),
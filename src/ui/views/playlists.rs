@@ -0,0 +1,304 @@
+//! Playlists pane - the playlists on the left, the chosen one's tracks on the
+//! right with buttons to play, rename, delete and reorder.
+
+use iced::widget::{Space, button, column, container, row, scrollable, text, text_input};
+use iced::{Alignment, Element, Length};
+
+use crate::db::playlists::{Playlist, PlaylistTrack};
+use crate::player::format_duration_secs;
+use crate::ui::icons::{self, icon_sized};
+use crate::ui::messages::Message;
+use crate::ui::state::{ActivePane, LoadedState, PlaylistsState};
+use crate::ui::theme::{self, color, radius, spacing, typography};
+
+/// Width of the playlist list
+const LIST_WIDTH: f32 = 260.0;
+
+/// Playlists pane
+pub fn playlists_pane(s: &LoadedState) -> Element<'_, Message> {
+    let state = &s.playlists;
+
+    let header = column![
+        text("Playlists")
+            .size(typography::SIZE_TITLE)
+            .color(color::TEXT_PRIMARY),
+        text("Saved sets of tracks. Right-click a track in the library or the queue to add it")
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED),
+    ]
+    .spacing(spacing::XS);
+
+    let detail = match state.current() {
+        Some(playlist) => playlist_detail(state, playlist),
+        None => container(
+            text(if state.playlists.is_empty() {
+                "No playlists yet - name one to create it"
+            } else {
+                "Pick a playlist to see its tracks"
+            })
+            .size(typography::SIZE_BODY)
+            .color(color::TEXT_MUTED),
+        )
+        .padding(spacing::XL)
+        .center_x(Length::Fill)
+        .into(),
+    };
+
+    column![
+        header,
+        Space::with_height(spacing::MD),
+        row![playlist_list(state), detail]
+            .spacing(spacing::LG)
+            .height(Length::Fill),
+    ]
+    .into()
+}
+
+/// "12 tracks · 48:10"
+fn summary(playlist: &Playlist) -> String {
+    format!(
+        "{} track{} · {}",
+        playlist.track_count,
+        if playlist.track_count == 1 { "" } else { "s" },
+        format_duration_secs(playlist.total_duration as f32)
+    )
+}
+
+/// New playlist input and the list of playlists
+fn playlist_list(state: &PlaylistsState) -> Element<'_, Message> {
+    let create = row![
+        text_input("New playlist name", &state.new_name)
+            .on_input(Message::PlaylistNewNameChanged)
+            .on_submit(Message::PlaylistCreate)
+            .size(typography::SIZE_SMALL)
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::text_input_style),
+        button(icon_sized(icons::PLUS, typography::SIZE_SMALL))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_secondary)
+            .on_press_maybe((!state.new_name.trim().is_empty()).then_some(Message::PlaylistCreate)),
+    ]
+    .spacing(spacing::XS)
+    .align_y(Alignment::Center);
+
+    let items: Vec<Element<Message>> = state
+        .playlists
+        .iter()
+        .map(|playlist| {
+            let selected = state.selected == Some(playlist.id);
+            button(
+                column![
+                    text(&playlist.name)
+                        .size(typography::SIZE_BODY)
+                        .color(color::TEXT_PRIMARY),
+                    text(summary(playlist))
+                        .size(typography::SIZE_TINY)
+                        .color(color::TEXT_MUTED),
+                ]
+                .spacing(2),
+            )
+            .padding([spacing::XS, spacing::SM])
+            .width(Length::Fill)
+            .style(if selected {
+                theme::button_nav_active
+            } else {
+                theme::button_nav
+            })
+            .on_press(Message::PlaylistSelect(playlist.id))
+            .into()
+        })
+        .collect();
+
+    column![
+        create,
+        Space::with_height(spacing::SM),
+        scrollable(column(items).spacing(spacing::XS)).height(Length::Fill),
+    ]
+    .width(Length::Fixed(LIST_WIDTH))
+    .into()
+}
+
+/// Name, actions and tracks of the shown playlist
+fn playlist_detail<'a>(state: &'a PlaylistsState, playlist: &'a Playlist) -> Element<'a, Message> {
+    let has_tracks = !state.tracks.is_empty();
+    let action = |icon: char, label: &'static str, msg: Option<Message>| {
+        button(
+            row![
+                icon_sized(icon, typography::SIZE_SMALL),
+                text(label).size(typography::SIZE_SMALL),
+            ]
+            .spacing(spacing::XS)
+            .align_y(Alignment::Center),
+        )
+        .padding([spacing::XS, spacing::MD])
+        .style(theme::button_secondary)
+        .on_press_maybe(msg)
+    };
+
+    let title: Element<Message> = match &state.renaming {
+        Some(name) => row![
+            text_input("Playlist name", name)
+                .on_input(Message::PlaylistRenameChanged)
+                .on_submit(Message::PlaylistRenameSubmit)
+                .size(typography::SIZE_BODY)
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::text_input_style),
+            action(icons::CHECK, "Save", Some(Message::PlaylistRenameSubmit)),
+            action(icons::XMARK, "Cancel", Some(Message::PlaylistRenameCancel)),
+        ]
+        .spacing(spacing::SM)
+        .align_y(Alignment::Center)
+        .into(),
+        None => column![
+            text(&playlist.name)
+                .size(typography::SIZE_HEADING)
+                .color(color::TEXT_PRIMARY),
+            text(summary(playlist))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(spacing::XS)
+        .into(),
+    };
+
+    let actions = row![
+        button(
+            row![
+                icon_sized(icons::PLAY, typography::SIZE_SMALL).color(color::TEXT_INVERSE),
+                text("Play")
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_INVERSE),
+            ]
+            .spacing(spacing::XS)
+            .align_y(Alignment::Center),
+        )
+        .padding([spacing::XS, spacing::MD])
+        .style(theme::button_primary)
+        .on_press_maybe(has_tracks.then_some(Message::PlaylistPlay)),
+        action(
+            icons::PLUS,
+            "Add to Queue",
+            has_tracks.then_some(Message::PlaylistQueue)
+        ),
+        action(
+            icons::PEN,
+            "Rename",
+            state
+                .renaming
+                .is_none()
+                .then_some(Message::PlaylistRenameStart)
+        ),
+        Space::with_width(Length::Fill),
+        button(
+            row![
+                icon_sized(icons::TRASH, typography::SIZE_SMALL),
+                text("Delete").size(typography::SIZE_SMALL),
+            ]
+            .spacing(spacing::XS)
+            .align_y(Alignment::Center),
+        )
+        .padding([spacing::XS, spacing::MD])
+        .style(theme::button_danger)
+        .on_press(Message::PlaylistDelete(playlist.id)),
+    ]
+    .spacing(spacing::SM)
+    .align_y(Alignment::Center);
+
+    let tracks: Element<Message> = if has_tracks {
+        let last = state.tracks.len() - 1;
+        let rows: Vec<Element<Message>> = state
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(pos, track)| track_row(pos, last, track))
+            .collect();
+        scrollable(column(rows).spacing(2).padding([0, spacing::SM]))
+            .id(ActivePane::Playlists.scroll_id())
+            .on_scroll(|v| Message::PaneScrolled(ActivePane::Playlists, v))
+            .height(Length::Fill)
+            .into()
+    } else {
+        container(
+            text("Empty - right-click a track in the library or the queue to add it")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        )
+        .padding(spacing::XL)
+        .center_x(Length::Fill)
+        .into()
+    };
+
+    column![
+        title,
+        Space::with_height(spacing::SM),
+        actions,
+        Space::with_height(spacing::MD),
+        tracks,
+    ]
+    .width(Length::Fill)
+    .into()
+}
+
+/// One entry: position, title and artist, album, length, and buttons to
+/// move it up, down or out
+fn track_row(pos: usize, last: usize, track: &PlaylistTrack) -> Element<'_, Message> {
+    let icon_button = |icon: char, msg: Option<Message>| {
+        button(icon_sized(icon, typography::SIZE_TINY).color(color::TEXT_SECONDARY))
+            .padding(spacing::XS)
+            .style(theme::button_icon)
+            .on_press_maybe(msg)
+    };
+
+    container(
+        row![
+            text(format!("{}", pos + 1))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED)
+                .width(Length::Fixed(32.0)),
+            column![
+                text(&track.title)
+                    .size(typography::SIZE_BODY)
+                    .color(color::TEXT_PRIMARY),
+                text(&track.artist_name)
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_SECONDARY),
+            ]
+            .width(Length::FillPortion(3)),
+            text(&track.album_title)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED)
+                .width(Length::FillPortion(2)),
+            text(format_duration_secs(track.duration.unwrap_or(0) as f32))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+            icon_button(
+                icons::ARROW_UP,
+                (pos > 0).then(|| Message::PlaylistMoveTrack(pos, pos - 1))
+            ),
+            icon_button(
+                icons::ARROW_DOWN,
+                (pos < last).then_some(Message::PlaylistMoveTrack(pos, pos + 1))
+            ),
+            icon_button(icons::XMARK, Some(Message::PlaylistRemoveTrack(pos))),
+        ]
+        .spacing(spacing::SM)
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::XS, spacing::SM])
+    .style(move |_| container::Style {
+        background: Some(
+            if pos.is_multiple_of(2) {
+                color::SURFACE
+            } else {
+                color::BASE
+            }
+            .into(),
+        ),
+        border: iced::Border {
+            radius: radius::SM.into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .into()
+}
@@ -0,0 +1,115 @@
+//! Seek slider with clickable markers.
+//!
+//! Markers come from [`SeekMarkers`](crate::ui::state::SeekMarkers): any
+//! feature that knows positions in the current track (cue sheet chapters,
+//! loop points, silence boundaries) sets its kind there and the player bar
+//! and mini-player draw them. Clicking a marker seeks to it.
+
+use iced::widget::{Space, button, container, row, slider, stack, text, tooltip};
+use iced::{Color, Element, Length};
+
+use crate::ui::messages::Message;
+use crate::ui::state::{LoadedState, SeekMarker, SeekMarkerKind};
+use crate::ui::theme::{self, color, radius, spacing, typography};
+
+/// Width of a marker tick
+const MARKER_WIDTH: f32 = 4.0;
+/// Height of a marker tick (the slider handle is 12px)
+const MARKER_HEIGHT: f32 = 12.0;
+/// Radius of the slider handle: the handle's centre, which the marker
+/// positions line up with, never gets closer to the ends than this
+const HANDLE_RADIUS: f32 = 6.0;
+/// Fill portions per track (marker position resolution)
+const PORTIONS: f32 = 1000.0;
+
+/// The seek slider, previewing while dragged, with the current track's markers
+pub fn seek_bar(s: &LoadedState) -> Element<'_, Message> {
+    let state = &s.player_state;
+    let position = s.seek_preview.unwrap_or_else(|| state.position_fraction());
+    let seek_slider = slider(0.0..=1.0, position, Message::PlayerSeekPreview)
+        .on_release(Message::PlayerSeekRelease)
+        .step(0.001)
+        .width(Length::Fill)
+        .style(theme::slider_style);
+
+    let duration = state.duration.as_secs_f32();
+    let markers = s.seek_markers.for_track(state.current_track.as_deref());
+    if markers.is_empty() || duration <= 0.0 {
+        return seek_slider.into();
+    }
+
+    // Gaps between markers as fill portions, so the ticks sit where the
+    // slider handle would be at their positions
+    let mut ticks = row![].align_y(iced::Alignment::Center);
+    let mut last = 0.0;
+    for marker in markers {
+        let fraction = (marker.position.as_secs_f32() / duration).clamp(0.0, 1.0);
+        let gap = ((fraction - last) * PORTIONS).round() as u16;
+        if gap > 0 {
+            ticks = ticks.push(Space::with_width(Length::FillPortion(gap)));
+        }
+        ticks = ticks.push(marker_tick(marker));
+        last = fraction;
+    }
+    let rest = ((1.0 - last) * PORTIONS).round() as u16;
+    if rest > 0 {
+        ticks = ticks.push(Space::with_width(Length::FillPortion(rest)));
+    }
+
+    stack![
+        container(seek_slider).center_y(Length::Fill),
+        container(ticks)
+            .padding([0.0, HANDLE_RADIUS - MARKER_WIDTH / 2.0])
+            .center_y(Length::Fill),
+    ]
+    .width(Length::Fill)
+    .height(Length::Fixed(MARKER_HEIGHT + spacing::XS as f32))
+    .into()
+}
+
+/// One marker: a tick that seeks to its position, labelled on hover
+fn marker_tick(marker: &SeekMarker) -> Element<'_, Message> {
+    let tick_color = marker_color(marker.kind);
+    let tick = button(Space::new(
+        Length::Fixed(MARKER_WIDTH),
+        Length::Fixed(MARKER_HEIGHT),
+    ))
+    .padding(0)
+    .style(move |_, status| button::Style {
+        background: Some(iced::Background::Color(match status {
+            button::Status::Hovered | button::Status::Pressed => color::TEXT_PRIMARY,
+            _ => tick_color,
+        })),
+        border: iced::Border {
+            radius: 1.0.into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .on_press(Message::PlayerSeekTo(marker.position));
+
+    tooltip(
+        tick,
+        text(&marker.label).size(typography::SIZE_TINY),
+        tooltip::Position::Top,
+    )
+    .gap(spacing::XS)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: iced::Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
+
+fn marker_color(kind: SeekMarkerKind) -> Color {
+    match kind {
+        SeekMarkerKind::Chapter => color::TEXT_SECONDARY,
+        SeekMarkerKind::LoopPoint => color::SUCCESS,
+        SeekMarkerKind::Silence => color::WARNING,
+    }
+}
@@ -1,11 +1,12 @@
-//! About section - version info, tagline, credits.
+//! About section - version info, tagline, update check, credits.
 
-use iced::widget::{Space, column, container, row, text};
+use iced::widget::{Space, button, column, container, row, text};
 use iced::{Alignment, Element, Length};
 
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::theme::{color, radius, spacing, typography};
+use crate::ui::state::LoadedState;
+use crate::ui::theme::{self, color, radius, spacing, typography};
 
 use super::section_header;
 
@@ -24,7 +25,7 @@ const TAGLINES: &[&str] = &[
 ];
 
 /// About section with version and credits
-pub fn about_section() -> Element<'static, Message> {
+pub fn about_section(s: &LoadedState) -> Element<'_, Message> {
     // Pick a tagline based on... something deterministic but fun
     // Using version string hash for now (changes each release)
     let tagline_idx = VERSION.bytes().map(|b| b as usize).sum::<usize>() % TAGLINES.len();
@@ -36,6 +37,9 @@ pub fn about_section() -> Element<'static, Message> {
         // App name and version
         app_info_card(tagline),
         Space::with_height(spacing::MD),
+        // Newer release on GitHub
+        update_check(s),
+        Space::with_height(spacing::MD),
         // Credits
         credits_section(),
     ]
@@ -97,6 +101,95 @@ fn app_info_card(tagline: &str) -> Element<'_, Message> {
     .into()
 }
 
+/// Check button and its result: a newer release's notes and link
+fn update_check(s: &LoadedState) -> Element<'_, Message> {
+    let updates = &s.updates;
+    let offline = crate::enrichment::http::is_offline();
+    let status = if offline {
+        "Offline mode is on (network.offline): no update checks".to_string()
+    } else if updates.checking {
+        "Checking…".to_string()
+    } else if let Some(e) = &updates.error {
+        format!("Couldn't check: {}", e)
+    } else if updates.available.is_some() {
+        String::new()
+    } else if updates.checked {
+        "You're running the latest release".to_string()
+    } else {
+        "Set network.check_for_updates to check at startup".to_string()
+    };
+
+    let check = button(
+        row![
+            icon_sized(icons::REFRESH, typography::SIZE_SMALL),
+            Space::with_width(spacing::XS),
+            text("Check for Updates").size(typography::SIZE_SMALL),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::XS, spacing::SM])
+    .style(theme::button_ghost)
+    .on_press_maybe((!offline && !updates.checking).then_some(Message::CheckForUpdates));
+
+    let mut content = column![
+        row![
+            check,
+            Space::with_width(spacing::SM),
+            text(status)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .align_y(Alignment::Center)
+    ]
+    .spacing(spacing::SM);
+
+    if let Some(release) = &updates.available {
+        let published = release
+            .published_at
+            .as_deref()
+            .and_then(|at| at.get(..10))
+            .map(|date| format!(" — released {}", date))
+            .unwrap_or_default();
+        content = content.push(
+            text(format!(
+                "{} is available (you have {}){}",
+                release.name,
+                crate::updates::CURRENT_VERSION,
+                published
+            ))
+            .size(typography::SIZE_BODY)
+            .color(color::SUCCESS),
+        );
+        if !release.notes.trim().is_empty() {
+            content = content.push(
+                text(release.notes.trim())
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_SECONDARY),
+            );
+        }
+        content = content.push(
+            button(text("Open Release Page").size(typography::SIZE_SMALL))
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_primary)
+                .on_press(Message::OpenReleasePage(release.url.clone())),
+        );
+    }
+
+    container(content)
+        .padding(spacing::MD)
+        .width(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(color::SURFACE_ELEVATED.into()),
+            border: iced::Border {
+                color: color::BORDER,
+                width: 1.0,
+                radius: radius::MD.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
 /// Credits and acknowledgments
 fn credits_section() -> Element<'static, Message> {
     column![
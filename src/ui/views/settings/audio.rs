@@ -1,11 +1,18 @@
-//! Audio settings section - device selection, visualization mode.
+//! Audio settings section - device selection, visualization mode, silence
+//! trimming, buffer size, fades, crossfade, loudness normalization, volume
+//! step, and playback levels and performance.
 
-use iced::widget::{Space, column, container, pick_list, row};
+use iced::widget::{Space, checkbox, column, container, pick_list, row, text};
 use iced::{Alignment, Element, Length};
 
+use crate::player::normalization::{self, NormalizationMode};
+use crate::player::{buffering, crossfade, fade, volume};
 use crate::ui::icons;
 use crate::ui::messages::Message;
-use crate::ui::state::{LoadedState, VisualizationMode};
+use crate::ui::state::{
+    BufferSizeChoice, CrossfadeChoice, FadeChoice, LoadedState, PreampChoice, VisualizationMode,
+    VolumeStepChoice, to_dbfs,
+};
 use crate::ui::theme::{color, radius, spacing, typography};
 
 use super::{section_header, setting_description, setting_label};
@@ -28,6 +35,51 @@ pub fn audio_section(s: &LoadedState) -> Element<'_, Message> {
             "Visual display mode for the Now Playing view",
             visualization_picker(s),
         ),
+        Space::with_height(spacing::MD),
+        // Silence trimming
+        setting_row(
+            "Trim Silence",
+            "Skip silence of 2s or more at the start and end of tracks (found by the silence analysis job)",
+            checkbox("", s.silence_trim.enabled)
+                .on_toggle(Message::PlayerTrimSilenceToggled)
+                .into(),
+        ),
+        Space::with_height(spacing::MD),
+        // Buffer size
+        buffer_row(s),
+        Space::with_height(spacing::MD),
+        // Fades on transitions
+        setting_row(
+            "Fades",
+            "Fade out on pause, stop and seek and back in on resume, instead of cutting the sound off with a click",
+            fade_picker(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Crossfade between tracks
+        setting_row(
+            "Crossfade",
+            "Fade the next track in over the end of the one playing. Not across a stop mark or on repeat one",
+            crossfade_picker(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Loudness normalization
+        normalization_row(s),
+        Space::with_height(spacing::MD),
+        setting_row(
+            "Preamp",
+            "Added to the normalization gain. A track's gain never lifts its peak past full scale",
+            preamp_picker(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Keyboard and wheel volume step
+        setting_row(
+            "Volume Step",
+            "How much the arrow keys and mouse wheel over the volume slider change the volume",
+            volume_step_picker(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Levels and performance of the current playback
+        levels_row(s),
     ]
     .spacing(spacing::XS)
     .into()
@@ -53,6 +105,196 @@ fn setting_row<'a>(
     .into()
 }
 
+/// Buffer size picker, with the size Auto has settled on
+fn buffer_row(s: &LoadedState) -> Element<'_, Message> {
+    let mut description = "How much audio is decoded ahead. More rides out dropouts from slow disks or USB DACs, at the cost of latency; Auto grows it after underruns and shrinks it when playback is stable".to_string();
+    if let Some(stats) = s.player.as_ref().and_then(|p| p.performance_stats()) {
+        description.push_str(&format!(
+            " (now {} ms, {} underruns)",
+            stats.decode_ahead_ms, stats.underruns
+        ));
+    }
+    let choices: Vec<BufferSizeChoice> = std::iter::once(buffering::AUTO)
+        .chain(buffering::BUFFER_CHOICES_MS)
+        .map(BufferSizeChoice)
+        .collect();
+    let picker = pick_list(
+        choices,
+        Some(BufferSizeChoice(s.audio_buffer_ms)),
+        Message::PlayerBufferSizeChanged,
+    )
+    .text_size(typography::SIZE_BODY)
+    .padding(spacing::SM)
+    .style(dropdown_style);
+
+    row![
+        column![
+            setting_label("Buffer Size"),
+            text(description)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(2)
+        .width(Length::FillPortion(2)),
+        container(picker)
+            .width(Length::FillPortion(1))
+            .align_x(iced::alignment::Horizontal::Right),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(spacing::MD)
+    .padding([spacing::SM, 0])
+    .into()
+}
+
+/// Fade length picker
+fn fade_picker(s: &LoadedState) -> Element<'_, Message> {
+    let choices: Vec<FadeChoice> = fade::FADE_CHOICES_MS.into_iter().map(FadeChoice).collect();
+    pick_list(
+        choices,
+        Some(FadeChoice(s.audio_fade_ms)),
+        Message::PlayerFadeChanged,
+    )
+    .text_size(typography::SIZE_BODY)
+    .padding(spacing::SM)
+    .style(dropdown_style)
+    .into()
+}
+
+/// Crossfade length picker
+fn crossfade_picker(s: &LoadedState) -> Element<'_, Message> {
+    let choices: Vec<CrossfadeChoice> = (0..=crossfade::MAX_CROSSFADE_SECS)
+        .map(CrossfadeChoice)
+        .collect();
+    pick_list(
+        choices,
+        Some(CrossfadeChoice(s.audio_crossfade_secs)),
+        Message::PlayerCrossfadeChanged,
+    )
+    .text_size(typography::SIZE_BODY)
+    .padding(spacing::SM)
+    .style(dropdown_style)
+    .into()
+}
+
+/// Normalization mode picker, with the gain on the track playing
+fn normalization_row(s: &LoadedState) -> Element<'_, Message> {
+    let mut description = "Play tracks at matching loudness from their ReplayGain tags, or the values stored in the library. Album gain keeps the levels between an album's tracks".to_string();
+    let playing = s.player.as_ref().map(|p| p.state());
+    if s.audio_normalization != NormalizationMode::Off
+        && let Some(state) = playing.filter(|state| state.current_track.is_some())
+    {
+        description.push_str(&format!(
+            " (now {})",
+            normalization::label(state.normalization_gain)
+        ));
+    }
+    let picker = pick_list(
+        NormalizationMode::ALL,
+        Some(s.audio_normalization),
+        Message::PlayerNormalizationChanged,
+    )
+    .text_size(typography::SIZE_BODY)
+    .padding(spacing::SM)
+    .style(dropdown_style);
+
+    row![
+        column![
+            setting_label("Normalization"),
+            text(description)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(2)
+        .width(Length::FillPortion(2)),
+        container(picker)
+            .width(Length::FillPortion(1))
+            .align_x(iced::alignment::Horizontal::Right),
+    ]
+    .align_y(Alignment::Center)
+    .spacing(spacing::MD)
+    .padding([spacing::SM, 0])
+    .into()
+}
+
+/// Normalization preamp picker
+fn preamp_picker(s: &LoadedState) -> Element<'_, Message> {
+    let choices: Vec<PreampChoice> = normalization::PREAMP_CHOICES_DB
+        .into_iter()
+        .map(PreampChoice)
+        .collect();
+    pick_list(
+        choices,
+        Some(PreampChoice(s.audio_preamp_db)),
+        Message::PlayerPreampChanged,
+    )
+    .text_size(typography::SIZE_BODY)
+    .padding(spacing::SM)
+    .style(dropdown_style)
+    .into()
+}
+
+/// Volume step picker
+fn volume_step_picker(s: &LoadedState) -> Element<'_, Message> {
+    let choices: Vec<VolumeStepChoice> = volume::STEP_CHOICES_DB
+        .into_iter()
+        .map(VolumeStepChoice)
+        .collect();
+    pick_list(
+        choices,
+        Some(VolumeStepChoice(s.volume_step_db)),
+        Message::PlayerVolumeStepChanged,
+    )
+    .text_size(typography::SIZE_BODY)
+    .padding(spacing::SM)
+    .style(dropdown_style)
+    .into()
+}
+
+/// Playback performance, and the last minute of levels
+fn levels_row(s: &LoadedState) -> Element<'_, Message> {
+    let meter = &s.level_meter;
+    let mut description = match s.player.as_ref().and_then(|p| p.performance_stats()) {
+        Some(stats) => format!(
+            "{} · {} · {} underruns · {} clipped samples",
+            stats.health_rating(),
+            stats.callback_timing(),
+            stats.underruns,
+            stats.clipped_samples
+        ),
+        None => "No audio output".to_string(),
+    };
+    if !meter.history.is_empty() {
+        description.push_str(&format!(
+            " · peak {:.1} dBFS in the last minute",
+            to_dbfs(meter.max_peak())
+        ));
+    }
+
+    column![
+        setting_label("Levels & Performance"),
+        text(description)
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED),
+        Space::with_height(spacing::XS),
+        container(crate::ui::views::level_meter::level_history(meter, 64.0)).style(|_| {
+            container::Style {
+                border: iced::Border {
+                    color: color::BORDER_SUBTLE,
+                    width: 1.0,
+                    radius: radius::SM.into(),
+                },
+                ..Default::default()
+            }
+        }),
+        setting_description(
+            "Track levels before volume: RMS filled, peaks as a line, clipping marked in red",
+        ),
+    ]
+    .spacing(2)
+    .padding([spacing::SM, 0])
+    .into()
+}
+
 /// Audio device picker dropdown
 fn device_picker(s: &LoadedState) -> Element<'_, Message> {
     let devices: Vec<String> = s.audio_devices.clone();
@@ -1,11 +1,18 @@
-//! Enrichment settings section - AcoustID API key, fpcalc status.
+//! Enrichment settings section - AcoustID API key and its encryption, fpcalc
+//! status, CPU budget, how tags are saved, per-folder defaults.
 
-use iced::widget::{Space, button, column, container, row, text, text_input};
+use iced::widget::{Space, button, checkbox, column, container, pick_list, row, text, text_input};
 use iced::{Alignment, Element, Length};
 
+use crate::config::AnalysisConfig;
+use crate::enrichment::budget::{CpuBudget, cores};
+use crate::enrichment::folders::FolderDefaults;
+use crate::metadata::WriteStrategy;
+use crate::metadata::strategy::{Id3Encoding, Id3Version, PADDING_CHOICES, Preset};
+use crate::secrets::Status;
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::state::LoadedState;
+use crate::ui::state::{ConfidenceChoice, LoadedState, PaddingChoice};
 use crate::ui::theme::{self, color, radius, spacing, typography};
 
 use super::{section_header, setting_description, setting_label};
@@ -28,11 +35,276 @@ pub fn enrichment_section(s: &LoadedState) -> Element<'_, Message> {
             "Required for track identification. Get one free at acoustid.org",
             api_key_input(s),
         ),
+        setting_row_vertical(
+            "Encrypt API Keys",
+            secrets_description(s.enrichment.secrets),
+            secrets_control(s),
+        ),
+        Space::with_height(spacing::MD),
+        // CPU budget
+        setting_row(
+            "Fingerprint threads",
+            "Most tracks fingerprinted at once. Auto uses half the CPU cores",
+            threads_stepper(s),
+        ),
+        setting_row(
+            "Pause on battery",
+            "Hold fingerprinting back while the laptop is unplugged",
+            pause_checkbox(s, s.enrichment.analysis.pause_on_battery, |a, on| {
+                a.pause_on_battery = on
+            }),
+        ),
+        setting_row(
+            "Pause while playing",
+            "Hold fingerprinting back while music is playing",
+            pause_checkbox(s, s.enrichment.analysis.pause_while_playing, |a, on| {
+                a.pause_while_playing = on
+            }),
+        ),
+        setting_row(
+            "Wait for files in use",
+            "Write tags put off while a player had the file open as soon as it lets go, rather than at the next retry",
+            checkbox("", s.watch_locked_files)
+                .text_size(typography::SIZE_BODY)
+                .on_toggle(Message::WriteQueueWatchToggled)
+                .into(),
+        ),
+        Space::with_height(spacing::MD),
+        // How tags are saved
+        setting_row(
+            "Tag compatibility",
+            "Max compatibility saves ID3v2.3 with Latin-1 text (UTF-16 where it doesn't fit) for car stereos and older players; Modern saves ID3v2.4 with UTF-8",
+            strategy_picker(s, Preset::ALL, s.write_strategy.preset(), |w, preset| {
+                w.with_preset(preset)
+            }),
+        ),
+        setting_row(
+            "ID3 version",
+            "Version of the tags of MP3, AIFF and WAV files",
+            strategy_picker(
+                s,
+                Id3Version::ALL,
+                Some(s.write_strategy.id3_version),
+                |w, id3_version| WriteStrategy { id3_version, ..w },
+            ),
+        ),
+        setting_row(
+            "ID3 text encoding",
+            "UTF-8 needs ID3v2.4; ID3v2.3 tags get UTF-16 instead",
+            strategy_picker(
+                s,
+                Id3Encoding::ALL,
+                Some(s.write_strategy.id3_encoding),
+                |w, id3_encoding| WriteStrategy { id3_encoding, ..w },
+            ),
+        ),
+        setting_row(
+            "Padding",
+            "Room left after the tags of MP3, FLAC and M4A files, so a later edit that fits doesn't rewrite the whole file",
+            padding_pickers(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Per-folder defaults
+        setting_row_vertical(
+            "Folder Defaults",
+            "How tracks under a folder are enriched, in the Enrich pane and by the background agent. The deepest matching folder wins",
+            folder_defaults_list(s),
+        ),
     ]
     .spacing(spacing::XS)
     .into()
 }
 
+/// Thread limit with -/+ buttons; below 1 is "Auto"
+fn threads_stepper(s: &LoadedState) -> Element<'_, Message> {
+    let analysis = &s.enrichment.analysis;
+    let threads = analysis.max_threads;
+    let label = match threads {
+        0 => format!("Auto ({})", CpuBudget::global().threads()),
+        n => n.to_string(),
+    };
+    let with_threads = |max_threads| {
+        Message::EnrichmentAnalysisChanged(AnalysisConfig {
+            max_threads,
+            ..analysis.clone()
+        })
+    };
+
+    row![
+        button(text("-").size(typography::SIZE_BODY))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press_maybe((threads > 0).then(|| with_threads(threads - 1))),
+        text(label).size(typography::SIZE_BODY),
+        button(text("+").size(typography::SIZE_BODY))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press_maybe((threads < cores()).then(|| with_threads(threads + 1))),
+    ]
+    .spacing(spacing::SM)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Picker of one tag writing setting
+fn strategy_picker<T>(
+    s: &LoadedState,
+    choices: impl Into<Vec<T>>,
+    selected: Option<T>,
+    set: fn(WriteStrategy, T) -> WriteStrategy,
+) -> Element<'_, Message>
+where
+    T: std::fmt::Display + Clone + PartialEq + 'static,
+{
+    let strategy = s.write_strategy;
+    pick_list(choices.into(), selected, move |choice| {
+        Message::EnrichmentWriteStrategyChanged(set(strategy, choice))
+    })
+    .placeholder("Custom")
+    .text_size(typography::SIZE_SMALL)
+    .padding(spacing::XS)
+    .into()
+}
+
+/// Padding of ID3, FLAC and MP4 tags, one picker each
+fn padding_pickers(s: &LoadedState) -> Element<'_, Message> {
+    let strategy = s.write_strategy;
+    let picker = |label: &'static str, bytes: u32, set: fn(WriteStrategy, u32) -> WriteStrategy| {
+        let mut choices: Vec<PaddingChoice> = PADDING_CHOICES.map(PaddingChoice).into();
+        if !PADDING_CHOICES.contains(&bytes) {
+            // Set in the config file by hand
+            choices.push(PaddingChoice(bytes));
+        }
+        row![
+            text(label).size(typography::SIZE_SMALL),
+            pick_list(choices, Some(PaddingChoice(bytes)), move |choice| {
+                Message::EnrichmentWriteStrategyChanged(set(strategy, choice.0))
+            })
+            .text_size(typography::SIZE_SMALL)
+            .padding(spacing::XS),
+        ]
+        .spacing(spacing::XS)
+        .align_y(Alignment::Center)
+    };
+
+    row![
+        picker("MP3", strategy.id3_padding, |w, id3_padding| {
+            WriteStrategy { id3_padding, ..w }
+        }),
+        picker("FLAC", strategy.flac_padding, |w, flac_padding| {
+            WriteStrategy { flac_padding, ..w }
+        }),
+        picker("M4A", strategy.mp4_padding, |w, mp4_padding| {
+            WriteStrategy { mp4_padding, ..w }
+        }),
+    ]
+    .spacing(spacing::SM)
+    .into()
+}
+
+/// Checkbox that flips one pause setting
+fn pause_checkbox(
+    s: &LoadedState,
+    checked: bool,
+    set: fn(&mut AnalysisConfig, bool),
+) -> Element<'_, Message> {
+    let analysis = s.enrichment.analysis.clone();
+    checkbox("", checked)
+        .text_size(typography::SIZE_BODY)
+        .on_toggle(move |on| {
+            let mut analysis = analysis.clone();
+            set(&mut analysis, on);
+            Message::EnrichmentAnalysisChanged(analysis)
+        })
+        .into()
+}
+
+/// One row per folder rule, then the add button
+fn folder_defaults_list(s: &LoadedState) -> Element<'_, Message> {
+    let mut rows: Vec<Element<'_, Message>> = s
+        .enrichment
+        .folder_defaults
+        .0
+        .iter()
+        .map(folder_defaults_row)
+        .collect();
+    rows.push(
+        button(
+            row![
+                icon_sized(icons::PLUS, typography::SIZE_SMALL),
+                Space::with_width(spacing::XS),
+                text("Add Folder…").size(typography::SIZE_SMALL),
+            ]
+            .align_y(Alignment::Center),
+        )
+        .padding([spacing::XS, spacing::SM])
+        .style(theme::button_ghost)
+        .on_press(Message::EnrichmentFolderDefaultsAdd)
+        .into(),
+    );
+    column(rows).spacing(spacing::XS).into()
+}
+
+/// A folder's path, its switches, confidence, and a remove button
+fn folder_defaults_row(rule: &FolderDefaults) -> Element<'_, Message> {
+    let toggle = |label: &'static str, checked: bool, set: fn(&mut FolderDefaults, bool)| {
+        let rule = rule.clone();
+        checkbox(label, checked)
+            .text_size(typography::SIZE_SMALL)
+            .on_toggle(move |on| {
+                let mut rule = rule.clone();
+                set(&mut rule, on);
+                Message::EnrichmentFolderDefaultsChanged(rule)
+            })
+    };
+    let confidence = {
+        let rule = rule.clone();
+        pick_list(
+            ConfidenceChoice::ALL,
+            Some(ConfidenceChoice(rule.min_confidence)),
+            move |choice| {
+                Message::EnrichmentFolderDefaultsChanged(FolderDefaults {
+                    min_confidence: choice.0,
+                    ..rule.clone()
+                })
+            },
+        )
+        .text_size(typography::SIZE_SMALL)
+        .padding(spacing::XS)
+    };
+
+    container(
+        row![
+            icon_sized(icons::FOLDER, typography::SIZE_SMALL).color(color::TEXT_MUTED),
+            text(&rule.folder)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_PRIMARY)
+                .width(Length::Fill),
+            toggle("Enabled", rule.enabled, |r, on| r.enabled = on),
+            toggle("Fill only", rule.fill_only, |r, on| r.fill_only = on),
+            toggle("Auto-write", rule.auto_write, |r, on| r.auto_write = on),
+            confidence,
+            button(icon_sized(icons::TRASH, typography::SIZE_SMALL))
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_ghost)
+                .on_press(Message::EnrichmentFolderDefaultsRemove(rule.folder.clone())),
+        ]
+        .spacing(spacing::SM)
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::XS, spacing::SM])
+    .style(|_| container::Style {
+        background: Some(color::SURFACE_ELEVATED.into()),
+        border: iced::Border {
+            color: color::BORDER,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
+
 /// A setting row with label, description, and control (horizontal layout)
 fn setting_row<'a>(
     label: &'a str,
@@ -132,6 +404,58 @@ fn api_key_input(s: &LoadedState) -> Element<'_, Message> {
     .into()
 }
 
+fn secrets_description(status: Status) -> &'static str {
+    match status {
+        Status::Plain => {
+            "Keep the keys in the config file behind a passphrase, for machines other people use. It's asked for once per session"
+        }
+        Status::Locked => "The keys are encrypted. Enter the passphrase to use them this session",
+        Status::Unlocked => "The keys are encrypted, and unlocked for this session",
+    }
+}
+
+/// Passphrase field with the button for what it does
+fn secrets_control(s: &LoadedState) -> Element<'_, Message> {
+    let enrichment = &s.enrichment;
+    let (label, action) = match enrichment.secrets {
+        Status::Plain => ("Encrypt", Message::SecretsEncrypt),
+        Status::Locked => ("Unlock", Message::SecretsUnlock),
+        Status::Unlocked => {
+            return button(text("Stop encrypting").size(typography::SIZE_SMALL))
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_ghost)
+                .on_press_maybe((!enrichment.secrets_busy).then_some(Message::SecretsDecrypt))
+                .into();
+        }
+    };
+    let ready = !enrichment.passphrase.is_empty() && !enrichment.secrets_busy;
+
+    row![
+        text_input("Passphrase...", &enrichment.passphrase)
+            .secure(true)
+            .on_input(Message::SecretsPassphraseChanged)
+            .on_submit_maybe(ready.then(|| action.clone()))
+            .padding(spacing::SM)
+            .size(typography::SIZE_BODY)
+            .width(Length::Fill)
+            .style(api_key_input_style),
+        Space::with_width(spacing::SM),
+        button(
+            text(if enrichment.secrets_busy {
+                "Checking..."
+            } else {
+                label
+            })
+            .size(typography::SIZE_SMALL)
+        )
+        .padding([spacing::XS, spacing::SM])
+        .style(theme::button_primary)
+        .on_press_maybe(ready.then_some(action)),
+    ]
+    .align_y(Alignment::Center)
+    .into()
+}
+
 /// Styled text input for API key
 fn api_key_input_style(_theme: &iced::Theme, status: text_input::Status) -> text_input::Style {
     let border_color = match status {
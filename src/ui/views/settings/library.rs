@@ -1,12 +1,17 @@
 //! Library settings section - watch paths, scan settings.
 
-use iced::widget::{Space, button, column, container, row, text};
+use iced::widget::{
+    Space, button, checkbox, column, container, pick_list, row, scrollable, text, text_input,
+};
 use iced::{Alignment, Element, Length};
 
+use crate::library::album_artists::AlbumView;
+use crate::metadata::ratings::KNOWN_POPM_PLAYERS;
 use crate::ui::icons::{self, icon_sized};
 use crate::ui::messages::Message;
-use crate::ui::state::LoadedState;
-use crate::ui::theme::{color, radius, spacing, typography};
+use crate::ui::state::{FolderStatus, LoadedState, PopmSourceChoice, WatchedFolder};
+use crate::ui::theme::{self, color, radius, spacing, typography};
+use crate::ui::views::helpers::error_badge;
 
 use super::{section_header, setting_description, setting_label};
 
@@ -15,26 +20,83 @@ pub fn library_section(s: &LoadedState) -> Element<'_, Message> {
     column![
         section_header(icons::MUSIC, "Library"),
         Space::with_height(spacing::SM),
+        // Profiles: separate database, config, and watch folders each
+        setting_row(
+            "Profile",
+            "Each profile is a separate library with its own database, settings, and watch folders. Switch from the sidebar or start with --profile",
+            profile_status(),
+        ),
+        new_profile_row(s),
+        Space::with_height(spacing::MD),
         // Watch paths display
         setting_row_vertical(
             "Watch Directories",
-            "Folders being monitored for music files",
+            "Library folders watched for new and changed music. Pause a folder to stop watching it without removing it from the library; an unplugged drive shows as offline and is picked up again when it returns",
             watch_paths_list(s),
         ),
         Space::with_height(spacing::MD),
         // Watcher status
         setting_row(
             "File Watcher",
-            "Automatically detect new and changed files",
+            "Automatically detect new and changed files. Files still being copied or downloaded are imported once they stop changing (library.watch_settle_secs)",
             watcher_status(s),
         ),
         Space::with_height(spacing::MD),
+        // Read-only lock (startup-only so it can't be switched off by accident)
+        setting_row(
+            "Read-only Mode",
+            "Refuse tag writes, moves, and deletes. Set library.read_only in the config file or start with --read-only",
+            read_only_status(),
+        ),
+        Space::with_height(spacing::MD),
         // Manual rescan button
         setting_row(
             "Rescan Library",
             "Force a full rescan of all watched directories",
             rescan_button(),
         ),
+        Space::with_height(spacing::MD),
+        // Which player's POPM rating to trust on scan
+        setting_row(
+            "Ratings From",
+            "Scans import ratings and play counts from the tags. MP3s can hold one POPM rating per player: pick whose to use. Preview with music-minder import-ratings",
+            rating_source_picker(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Kodi/Jellyfin sidecar files
+        setting_row(
+            "Media Center Files",
+            "Write folder.jpg, album.nfo and artist.nfo for Kodi and Jellyfin, from the library's tags. Templates are under [nfo] in the config file",
+            nfo_controls(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Downloaded cover art
+        setting_row(
+            "Cover Art Cache",
+            "Covers downloaded from the Cover Art Archive. Identical artwork is stored once; past the limit (library.cover_cache_mb) the least recently shown covers are dropped",
+            cover_cache_controls(s),
+        ),
+        Space::with_height(spacing::MD),
+        // One cover per album, fetched in a batch
+        setting_row(
+            "Album Covers",
+            "Fetch a cover once per album for albums missing one: their own art first, then the Cover Art Archive. Written as folder.jpg or embedded per [covers] in the config file",
+            cover_fetch_controls(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Distinct genres, merging, and the spelling rules
+        setting_row_vertical(
+            "Genres",
+            "Every genre in the library with its track count. Merging rewrites the tags and the stored genres, play history included; remembered merges become rules (tagging.genre_map) that map those spellings on future scans and tag writes",
+            genre_manager(s),
+        ),
+        Space::with_height(spacing::MD),
+        // Albums older scans split by track artist
+        setting_row_vertical(
+            "Album Artists",
+            "Finds albums split across artists and folders of one-track albums, and proposes the album artist they belong under. Applying writes the album and album artist tags and regroups the tracks; the last apply can be undone",
+            album_artist_wizard(s),
+        ),
     ]
     .spacing(spacing::XS)
     .into()
@@ -77,57 +139,129 @@ fn setting_row_vertical<'a>(
     .into()
 }
 
-/// Display list of watch paths
+/// Watched folders with their status, and controls to add, pause, and remove them
 fn watch_paths_list(s: &LoadedState) -> Element<'_, Message> {
-    if s.watcher_state.watch_paths.is_empty() {
-        return container(
-            text("No directories configured")
-                .size(typography::SIZE_SMALL)
-                .color(color::TEXT_MUTED),
-        )
-        .padding(spacing::SM)
-        .style(|_| container::Style {
-            background: Some(color::SURFACE_ELEVATED.into()),
-            border: iced::Border {
-                color: color::BORDER,
-                width: 1.0,
-                radius: radius::SM.into(),
-            },
-            ..Default::default()
-        })
+    let add = button(
+        row![
+            icon_sized(icons::PLUS, typography::SIZE_SMALL).color(color::TEXT_PRIMARY),
+            Space::with_width(spacing::XS),
+            text("Add Folder").size(typography::SIZE_BODY),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::MD])
+    .style(secondary_button_style)
+    .on_press(Message::WatchPathAdd);
+
+    if s.watcher_state.folders.is_empty() {
+        return column![
+            container(
+                text("No directories configured")
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_MUTED),
+            )
+            .padding(spacing::SM)
+            .width(Length::Fill)
+            .style(watch_path_style),
+            add,
+        ]
+        .spacing(spacing::XS)
         .into();
     }
 
-    let paths: Vec<Element<'_, Message>> = s
+    let mut paths: Vec<Element<'_, Message>> = s
         .watcher_state
-        .watch_paths
+        .folders
         .iter()
-        .map(|path| {
-            container(
+        .map(|folder| watch_path_row(s, folder))
+        .collect();
+    paths.push(add.into());
+
+    column(paths).spacing(spacing::XS).into()
+}
+
+/// One watched folder: path, status, activity, and its controls
+fn watch_path_row<'a>(s: &LoadedState, folder: &'a WatchedFolder) -> Element<'a, Message> {
+    let (icon, label, color_val) = if !folder.enabled {
+        (icons::PAUSE, "Paused".to_string(), color::TEXT_MUTED)
+    } else if !s.watcher_state.active {
+        (icons::CIRCLE, "Watcher off".to_string(), color::TEXT_MUTED)
+    } else {
+        match &folder.status {
+            FolderStatus::Starting => (
+                icons::CIRCLE_NOTCH,
+                "Starting".to_string(),
+                color::TEXT_MUTED,
+            ),
+            FolderStatus::Watching => (icons::CIRCLE_CHECK, "Watching".to_string(), color::SUCCESS),
+            FolderStatus::Offline(reason) => (
+                icons::WARNING,
+                format!("Offline: {}", reason),
+                color::WARNING,
+            ),
+        }
+    };
+
+    let activity = match folder.last_event {
+        Some(at) => format!(
+            "Last change {} · {} added, {} changed, {} removed",
+            at.format("%Y-%m-%d %H:%M"),
+            folder.created,
+            folder.modified,
+            folder.removed
+        ),
+        None => "No changes seen yet".to_string(),
+    };
+
+    let path = folder.path.clone();
+    container(
+        row![
+            icon_sized(icons::FOLDER, typography::SIZE_SMALL).color(color::TEXT_MUTED),
+            Space::with_width(spacing::SM),
+            column![
+                text(folder.path.display().to_string())
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_PRIMARY),
                 row![
-                    icon_sized(icons::FOLDER, typography::SIZE_SMALL).color(color::TEXT_MUTED),
+                    icon_sized(icon, typography::SIZE_SMALL).color(color_val),
+                    Space::with_width(spacing::XS),
+                    text(label).size(typography::SIZE_SMALL).color(color_val),
                     Space::with_width(spacing::SM),
-                    text(path.display().to_string())
+                    text(activity)
                         .size(typography::SIZE_SMALL)
-                        .color(color::TEXT_PRIMARY),
+                        .color(color::TEXT_MUTED),
                 ]
                 .align_y(Alignment::Center),
-            )
-            .padding([spacing::XS, spacing::SM])
-            .style(|_| container::Style {
-                background: Some(color::SURFACE_ELEVATED.into()),
-                border: iced::Border {
-                    color: color::BORDER,
-                    width: 1.0,
-                    radius: radius::SM.into(),
-                },
-                ..Default::default()
-            })
-            .into()
-        })
-        .collect();
+            ]
+            .spacing(2)
+            .width(Length::Fill),
+            checkbox("Watch", folder.enabled)
+                .on_toggle(move |_| Message::WatchPathToggle(path.clone()))
+                .size(14)
+                .text_size(typography::SIZE_SMALL),
+            Space::with_width(spacing::SM),
+            button(icon_sized(icons::TRASH, typography::SIZE_SMALL).color(color::TEXT_PRIMARY))
+                .padding([spacing::XS, spacing::SM])
+                .style(secondary_button_style)
+                .on_press(Message::WatchPathRemove(folder.path.clone())),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::XS, spacing::SM])
+    .style(watch_path_style)
+    .into()
+}
 
-    column(paths).spacing(spacing::XS).into()
+fn watch_path_style(_: &iced::Theme) -> container::Style {
+    container::Style {
+        background: Some(color::SURFACE_ELEVATED.into()),
+        border: iced::Border {
+            color: color::BORDER,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    }
 }
 
 /// Watcher status indicator
@@ -147,6 +281,63 @@ fn watcher_status(s: &LoadedState) -> Element<'_, Message> {
     .into()
 }
 
+/// Active profile name
+fn profile_status() -> Element<'static, Message> {
+    row![
+        icon_sized(icons::FOLDER, typography::SIZE_BODY).color(color::TEXT_SECONDARY),
+        Space::with_width(spacing::XS),
+        text(crate::profile::active())
+            .size(typography::SIZE_BODY)
+            .color(color::TEXT_PRIMARY),
+    ]
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Name field and button for creating (and switching to) a new profile
+fn new_profile_row(s: &LoadedState) -> Element<'_, Message> {
+    let name = s.new_profile_name.trim();
+    let create = button(text("Create & Switch").size(typography::SIZE_BODY))
+        .padding([spacing::SM, spacing::MD])
+        .style(secondary_button_style);
+    let create = if name.is_empty() || s.profiles.iter().any(|p| p == name) {
+        create
+    } else {
+        create.on_press(Message::CreateProfile)
+    };
+
+    row![
+        text_input("New profile name", &s.new_profile_name)
+            .on_input(Message::NewProfileNameChanged)
+            .on_submit(Message::CreateProfile)
+            .size(typography::SIZE_BODY)
+            .padding(spacing::SM)
+            .style(theme::text_input_style)
+            .width(Length::Fill),
+        create,
+    ]
+    .spacing(spacing::SM)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Read-only mode status
+fn read_only_status() -> Element<'static, Message> {
+    let (icon, label, color_val) = if crate::readonly::is_enabled() {
+        (icons::LOCK, "On", color::WARNING)
+    } else {
+        (icons::CIRCLE, "Off", color::TEXT_MUTED)
+    };
+
+    row![
+        icon_sized(icon, typography::SIZE_BODY).color(color_val),
+        Space::with_width(spacing::XS),
+        text(label).size(typography::SIZE_BODY).color(color_val),
+    ]
+    .align_y(Alignment::Center)
+    .into()
+}
+
 /// Rescan library button
 fn rescan_button() -> Element<'static, Message> {
     button(
@@ -163,6 +354,397 @@ fn rescan_button() -> Element<'static, Message> {
     .into()
 }
 
+/// After-organize toggle and button writing the files for the whole library
+fn nfo_controls(s: &LoadedState) -> Element<'_, Message> {
+    let write = button(
+        row![
+            icon_sized(icons::FILE_EXPORT, typography::SIZE_SMALL).color(color::TEXT_PRIMARY),
+            Space::with_width(spacing::XS),
+            text("Write Now").size(typography::SIZE_BODY),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::MD])
+    .style(secondary_button_style)
+    .on_press(Message::NfoExport(crate::nfo::Scope::Library));
+
+    column![
+        checkbox("After organize", s.nfo.after_organize)
+            .text_size(typography::SIZE_BODY)
+            .on_toggle(Message::NfoAfterOrganizeToggled),
+        write,
+    ]
+    .spacing(spacing::SM)
+    .align_x(Alignment::End)
+    .into()
+}
+
+/// Cache size readout and clear button
+fn cover_cache_controls(s: &LoadedState) -> Element<'_, Message> {
+    let mb = |bytes: u64| bytes as f64 / 1_000_000.0;
+    let readout = match &s.cover_cache {
+        Some(stats) if stats.limit > 0 => format!(
+            "{:.1} of {:.0} MB · {} covers for {} releases",
+            mb(stats.bytes),
+            mb(stats.limit),
+            stats.images,
+            stats.releases
+        ),
+        Some(stats) => format!(
+            "{:.1} MB · {} covers for {} releases",
+            mb(stats.bytes),
+            stats.images,
+            stats.releases
+        ),
+        None => "…".to_string(),
+    };
+    let clear = button(
+        row![
+            icon_sized(icons::TRASH, typography::SIZE_SMALL).color(color::TEXT_PRIMARY),
+            Space::with_width(spacing::XS),
+            text("Clear Cache").size(typography::SIZE_BODY),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::MD])
+    .style(secondary_button_style)
+    .on_press_maybe(
+        s.cover_cache
+            .is_some_and(|stats| stats.releases > 0)
+            .then_some(Message::CoverCacheClear),
+    );
+
+    column![
+        text(readout)
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_SECONDARY),
+        clear,
+    ]
+    .spacing(spacing::SM)
+    .align_x(Alignment::End)
+    .into()
+}
+
+/// Button fetching missing album covers, and the last batch's result
+fn cover_fetch_controls(s: &LoadedState) -> Element<'_, Message> {
+    let label = if s.cover_fetch_running {
+        "Fetching…"
+    } else {
+        "Fetch Missing"
+    };
+    let fetch = button(
+        row![
+            icon_sized(icons::RECORD_VINYL, typography::SIZE_SMALL).color(color::TEXT_PRIMARY),
+            Space::with_width(spacing::XS),
+            text(label).size(typography::SIZE_BODY),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::MD])
+    .style(secondary_button_style)
+    .on_press_maybe((!s.cover_fetch_running).then_some(Message::CoversFetch));
+
+    let mut controls = column![].spacing(spacing::SM).align_x(Alignment::End);
+    if let Some(report) = &s.cover_fetch_report {
+        controls = controls.push(
+            text(report.summary())
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+        );
+        let classes = report.failure_classes();
+        if !classes.is_empty() {
+            controls = controls.push(
+                row(classes.into_iter().map(|(class, n)| {
+                    row![
+                        error_badge(class),
+                        text(n).size(typography::SIZE_TINY).color(color::TEXT_MUTED),
+                    ]
+                    .spacing(spacing::XS)
+                    .align_y(Alignment::Center)
+                    .into()
+                }))
+                .spacing(spacing::SM),
+            );
+        }
+    }
+    controls.push(fetch).into()
+}
+
+/// Height of the genre list
+const GENRE_LIST_HEIGHT: f32 = 240.0;
+
+/// Genre list with counts, merge controls, and the rules
+fn genre_manager(s: &LoadedState) -> Element<'_, Message> {
+    let genres = &s.genres;
+    let load_label = match (&genres.counts, genres.loading) {
+        (_, true) => "Counting…",
+        (None, false) => "Count Genres",
+        (Some(_), false) => "Refresh",
+    };
+    let load = button(
+        row![
+            icon_sized(icons::SYNC, typography::SIZE_SMALL).color(color::TEXT_PRIMARY),
+            Space::with_width(spacing::XS),
+            text(load_label).size(typography::SIZE_BODY),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::MD])
+    .style(secondary_button_style)
+    .on_press_maybe((!genres.loading).then_some(Message::GenresLoad));
+
+    let mut manager = column![].spacing(spacing::SM);
+    match &genres.counts {
+        None => manager = manager.push(load),
+        Some(counts) => {
+            let filter = genres.filter.to_lowercase();
+            let list = counts
+                .iter()
+                .filter(|g| filter.is_empty() || g.name.to_lowercase().contains(&filter))
+                .fold(column![].spacing(2), |list, genre| {
+                    let name = genre.name.clone();
+                    list.push(
+                        row![
+                            checkbox(genre.name.as_str(), genres.selected.contains(&genre.name))
+                                .text_size(typography::SIZE_BODY)
+                                .on_toggle(move |on| Message::GenreToggled(name.clone(), on))
+                                .width(Length::Fill),
+                            text(genre.tracks.to_string())
+                                .size(typography::SIZE_SMALL)
+                                .color(color::TEXT_MUTED),
+                        ]
+                        .spacing(spacing::SM)
+                        .padding([0, spacing::SM])
+                        .align_y(Alignment::Center),
+                    )
+                });
+
+            let merge_label = match genres.selected.len() {
+                _ if genres.merging => "Merging…".to_string(),
+                1 => "Rename".to_string(),
+                n => format!("Merge {}", n),
+            };
+            let merge = button(text(merge_label).size(typography::SIZE_BODY))
+                .padding([spacing::SM, spacing::MD])
+                .style(secondary_button_style)
+                .on_press_maybe(
+                    (!genres.merging
+                        && !genres.selected.is_empty()
+                        && !genres.into.trim().is_empty())
+                    .then_some(Message::GenresMerge),
+                );
+
+            manager = manager
+                .push(
+                    row![
+                        text_input("Filter genres", &genres.filter)
+                            .on_input(Message::GenresFilterChanged)
+                            .size(typography::SIZE_BODY)
+                            .padding(spacing::SM)
+                            .style(theme::text_input_style)
+                            .width(Length::Fill),
+                        text(format!("{} genres", counts.len()))
+                            .size(typography::SIZE_SMALL)
+                            .color(color::TEXT_SECONDARY),
+                        load,
+                    ]
+                    .spacing(spacing::SM)
+                    .align_y(Alignment::Center),
+                )
+                .push(
+                    container(scrollable(list).height(Length::Fixed(GENRE_LIST_HEIGHT)))
+                        .padding(spacing::XS)
+                        .style(watch_path_style),
+                )
+                .push(
+                    row![
+                        text_input("Merge into", &genres.into)
+                            .on_input(Message::GenresIntoChanged)
+                            .on_submit(Message::GenresMerge)
+                            .size(typography::SIZE_BODY)
+                            .padding(spacing::SM)
+                            .style(theme::text_input_style)
+                            .width(Length::Fill),
+                        checkbox("Remember as a rule", genres.remember)
+                            .text_size(typography::SIZE_BODY)
+                            .on_toggle(Message::GenresRememberToggled),
+                        merge,
+                    ]
+                    .spacing(spacing::SM)
+                    .align_y(Alignment::Center),
+                );
+        }
+    }
+
+    if !genres.rules.is_empty() {
+        let rules = genres
+            .rules
+            .iter()
+            .fold(column![].spacing(2), |rules, (from, to)| {
+                rules.push(
+                    row![
+                        text(format!("{} → {}", from, to))
+                            .size(typography::SIZE_SMALL)
+                            .color(color::TEXT_SECONDARY)
+                            .width(Length::Fill),
+                        button(icon_sized(icons::TRASH, typography::SIZE_SMALL))
+                            .padding(spacing::XS)
+                            .style(secondary_button_style)
+                            .on_press(Message::GenreRuleRemove(from.clone())),
+                    ]
+                    .spacing(spacing::SM)
+                    .align_y(Alignment::Center),
+                )
+            });
+        manager = manager.push(setting_description("Rules")).push(rules);
+    }
+    manager.into()
+}
+
+/// Height of the album artist proposal list
+const FIX_LIST_HEIGHT: f32 = 280.0;
+
+/// Misfiled album proposals with their previews, apply and undo
+fn album_artist_wizard(s: &LoadedState) -> Element<'_, Message> {
+    let wizard = &s.album_artists;
+    let busy = wizard.scanning || wizard.applying;
+    let scan_label = match (&wizard.fixes, wizard.scanning) {
+        (_, true) => "Looking…",
+        (None, false) => "Find Misfiled Albums",
+        (Some(_), false) => "Look Again",
+    };
+    let scan = button(
+        row![
+            icon_sized(icons::SYNC, typography::SIZE_SMALL).color(color::TEXT_PRIMARY),
+            Space::with_width(spacing::XS),
+            text(scan_label).size(typography::SIZE_BODY),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::SM, spacing::MD])
+    .style(secondary_button_style)
+    .on_press_maybe((!busy).then_some(Message::AlbumArtistsScan));
+
+    let mut controls = row![scan].spacing(spacing::SM).align_y(Alignment::Center);
+    if wizard.can_undo {
+        controls = controls.push(
+            button(text("Undo Last Fix").size(typography::SIZE_BODY))
+                .padding([spacing::SM, spacing::MD])
+                .style(secondary_button_style)
+                .on_press_maybe((!busy).then_some(Message::AlbumArtistsUndo)),
+        );
+    }
+
+    let mut wizard_view = column![].spacing(spacing::SM);
+    let Some(fixes) = &wizard.fixes else {
+        return wizard_view.push(controls).into();
+    };
+    if fixes.is_empty() {
+        return wizard_view
+            .push(controls)
+            .push(setting_description("No misfiled albums found"))
+            .into();
+    }
+
+    let album_line = |sign: &str, album: &AlbumView| {
+        text(format!(
+            "{} {} — {} ({} tracks)",
+            sign, album.artist, album.title, album.tracks
+        ))
+        .size(typography::SIZE_SMALL)
+        .color(color::TEXT_SECONDARY)
+    };
+    let list = fixes
+        .iter()
+        .enumerate()
+        .fold(column![].spacing(spacing::XS), |list, (i, fix)| {
+            let previewing = wizard.preview == Some(i);
+            let mut entry = column![
+                row![
+                    checkbox(
+                        format!("{} — {}", fix.after.artist, fix.after.title),
+                        wizard.selected.contains(&i)
+                    )
+                    .text_size(typography::SIZE_BODY)
+                    .on_toggle(move |on| Message::AlbumArtistFixToggled(i, on))
+                    .width(Length::Fill),
+                    text(format!("{:.0}%", fix.confidence * 100.0))
+                        .size(typography::SIZE_SMALL)
+                        .color(color::TEXT_MUTED),
+                    button(
+                        text(if previewing { "Hide" } else { "Preview" })
+                            .size(typography::SIZE_SMALL)
+                    )
+                    .padding([spacing::XS, spacing::SM])
+                    .style(secondary_button_style)
+                    .on_press(Message::AlbumArtistFixPreview((!previewing).then_some(i))),
+                ]
+                .spacing(spacing::SM)
+                .align_y(Alignment::Center),
+                text(format!("{} · {}", fix.kind, fix.folder.display()))
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_MUTED),
+            ]
+            .spacing(2)
+            .padding([0, spacing::SM]);
+            if previewing {
+                entry = fix
+                    .before
+                    .iter()
+                    .fold(entry, |entry, album| entry.push(album_line("−", album)))
+                    .push(album_line("+", &fix.after));
+            }
+            list.push(entry)
+        });
+
+    let apply_label = match wizard.selected.len() {
+        _ if wizard.applying => "Applying…".to_string(),
+        n => format!("Apply {}", n),
+    };
+    let apply = button(text(apply_label).size(typography::SIZE_BODY))
+        .padding([spacing::SM, spacing::MD])
+        .style(secondary_button_style)
+        .on_press_maybe(
+            (!busy && !wizard.selected.is_empty()).then_some(Message::AlbumArtistsApply),
+        );
+
+    wizard_view = wizard_view
+        .push(
+            controls
+                .push(Space::with_width(Length::Fill))
+                .push(
+                    text(format!("{} proposal(s)", fixes.len()))
+                        .size(typography::SIZE_SMALL)
+                        .color(color::TEXT_SECONDARY),
+                )
+                .push(apply),
+        )
+        .push(
+            container(scrollable(list).height(Length::Fixed(FIX_LIST_HEIGHT)))
+                .padding(spacing::XS)
+                .style(watch_path_style),
+        );
+    wizard_view.into()
+}
+
+/// Picker for the POPM frame ratings are imported from
+fn rating_source_picker(s: &LoadedState) -> Element<'_, Message> {
+    let mut choices: Vec<PopmSourceChoice> = std::iter::once("")
+        .chain(KNOWN_POPM_PLAYERS.iter().map(|(_, email)| *email))
+        .map(|email| PopmSourceChoice(email.to_string()))
+        .collect();
+    let selected = PopmSourceChoice(s.popm_email.clone());
+    if !choices.contains(&selected) {
+        // Set by hand in the config file
+        choices.push(selected.clone());
+    }
+
+    pick_list(choices, Some(selected), Message::RatingSourceChanged)
+        .text_size(typography::SIZE_BODY)
+        .padding(spacing::SM)
+        .into()
+}
+
 /// Secondary button style
 fn secondary_button_style(_theme: &iced::Theme, status: button::Status) -> button::Style {
     let background = match status {
@@ -0,0 +1,117 @@
+//! Maintenance settings section - scheduled jobs, their next and last runs.
+
+use iced::widget::{Space, button, checkbox, column, row, text};
+use iced::{Alignment, Element, Length};
+
+use crate::scheduler::{self, Job, JobRun, Schedule};
+use crate::ui::icons;
+use crate::ui::messages::Message;
+use crate::ui::state::LoadedState;
+use crate::ui::theme::{self, color, spacing, typography};
+
+use super::{section_header, setting_description, setting_label};
+
+/// Maintenance settings section
+pub fn maintenance_section(s: &LoadedState) -> Element<'_, Message> {
+    let mut content = column![
+        section_header(icons::CLOCK, "Maintenance"),
+        Space::with_height(spacing::SM),
+    ]
+    .spacing(spacing::XS);
+
+    if !s.scheduler.config.enabled {
+        content = content.push(setting_description(
+            "Scheduled jobs are off. Set scheduler.enabled in the config file to turn them on; Run now still works",
+        ));
+    }
+    for job in Job::ALL {
+        content = content.push(job_row(s, job));
+    }
+
+    content
+        .push(Space::with_height(spacing::SM))
+        .push(setting_description(
+            "Rules are cron expressions (minute hour day month weekday) or @hourly, @daily, @weekly, @monthly, @idle. Change them under [scheduler] in the config file",
+        ))
+        .into()
+}
+
+/// One job: schedule, next and last run, enable toggle, run button, recent log
+fn job_row(s: &LoadedState, job: Job) -> Element<'_, Message> {
+    let sched = &s.scheduler;
+    let rule = job.schedule_rule(&sched.config);
+    let last = scheduler::last_run(&sched.runs, job);
+
+    let timing = match job.schedule(&sched.config) {
+        Ok(schedule) => {
+            let next = match &schedule {
+                Schedule::Idle => format!("when idle for {} min", sched.config.idle_minutes),
+                Schedule::Cron(_) => schedule
+                    .next_run(last.map(JobRun::started_local), sched.started)
+                    .map(|at| at.format("%a %d %b %H:%M").to_string())
+                    .unwrap_or_else(|| "never".to_string()),
+            };
+            text(format!("{}  ·  next: {}", rule, next))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY)
+        }
+        Err(e) => text(format!("Invalid schedule '{}': {}", rule, e))
+            .size(typography::SIZE_SMALL)
+            .color(color::ERROR),
+    };
+
+    let running = sched.running == Some(job);
+    let run_button =
+        button(text(if running { "Running…" } else { "Run now" }).size(typography::SIZE_BODY))
+            .padding([spacing::SM, spacing::MD])
+            .style(theme::button_secondary)
+            .on_press_maybe(
+                sched
+                    .running
+                    .is_none()
+                    .then_some(Message::SchedulerRunNow(job)),
+            );
+
+    let enabled = checkbox("Enabled", sched.config.job(job).enabled)
+        .text_size(typography::SIZE_BODY)
+        .on_toggle(move |_| Message::SchedulerToggleJob(job));
+
+    let mut info = column![
+        setting_label(job.label()),
+        setting_description(job.description()),
+        timing,
+    ]
+    .spacing(2)
+    .width(Length::Fill);
+
+    // Recent runs, newest first
+    for run in sched.runs.iter().filter(|r| r.job == job) {
+        info = info.push(
+            text(format!(
+                "{} {}  {}",
+                if run.success { "✓" } else { "✗" },
+                run.started_local().format("%Y-%m-%d %H:%M"),
+                run.summary
+            ))
+            .size(typography::SIZE_TINY)
+            .color(if run.success {
+                color::TEXT_MUTED
+            } else {
+                color::ERROR
+            }),
+        );
+    }
+    if last.is_none() {
+        info = info.push(
+            text("Not run yet")
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_MUTED),
+        );
+    }
+
+    row![info, enabled, run_button]
+        .spacing(spacing::MD)
+        .align_y(Alignment::Center)
+        .padding([spacing::SM, 0])
+        .into()
+}
@@ -4,7 +4,9 @@
 //! - Audio: Device selection, visualization mode
 //! - Library: Watch paths, scan settings  
 //! - Enrichment: AcoustID API key, fpcalc status
+//! - Maintenance: Scheduled jobs and their last/next runs
 //! - Appearance: Theme settings (future)
+//! - Transfer: Settings bundle export and selective import
 //! - About: Version, tagline, credits
 
 mod about;
@@ -12,13 +14,15 @@ mod appearance;
 mod audio;
 mod enrichment;
 mod library;
+mod maintenance;
+mod transfer;
 
 use iced::Element;
 use iced::widget::{Space, column, container, row, scrollable, text};
 
 use crate::ui::icons::icon_sized;
 use crate::ui::messages::Message;
-use crate::ui::state::LoadedState;
+use crate::ui::state::{ActivePane, LoadedState};
 use crate::ui::theme::{color, spacing, typography};
 
 pub use about::about_section;
@@ -26,6 +30,8 @@ pub use appearance::appearance_section;
 pub use audio::audio_section;
 pub use enrichment::enrichment_section;
 pub use library::library_section;
+pub use maintenance::maintenance_section;
+pub use transfer::transfer_section;
 
 /// Main settings pane with organized sections
 pub fn settings_pane(s: &LoadedState) -> Element<'_, Message> {
@@ -44,16 +50,25 @@ pub fn settings_pane(s: &LoadedState) -> Element<'_, Message> {
         // Enrichment section
         enrichment_section(s),
         section_divider(),
+        // Maintenance section
+        maintenance_section(s),
+        section_divider(),
         // Appearance section
         appearance_section(s),
         section_divider(),
+        // Transfer section
+        transfer_section(s),
+        section_divider(),
         // About section
-        about_section(),
+        about_section(s),
     ]
     .spacing(spacing::MD)
     .padding(spacing::LG);
 
-    scrollable(container(content).width(iced::Length::Fill)).into()
+    scrollable(container(content).width(iced::Length::Fill))
+        .id(ActivePane::Settings.scroll_id())
+        .on_scroll(|v| Message::PaneScrolled(ActivePane::Settings, v))
+        .into()
 }
 
 /// Visual divider between sections
@@ -0,0 +1,113 @@
+//! Transfer settings section - export a settings bundle, pick what to import.
+
+use iced::widget::{Space, button, checkbox, column, container, row, text};
+use iced::{Alignment, Element, Length};
+
+use crate::ui::icons;
+use crate::ui::messages::Message;
+use crate::ui::state::LoadedState;
+use crate::ui::theme::{self, color, radius, spacing, typography};
+
+use super::{section_header, setting_description, setting_label};
+
+/// Transfer settings section
+pub fn transfer_section(s: &LoadedState) -> Element<'_, Message> {
+    let transfer = &s.settings_transfer;
+    let idle = !transfer.busy && transfer.pending.is_none();
+
+    let buttons = row![
+        button(text("Export…").size(typography::SIZE_BODY))
+            .padding([spacing::SM, spacing::MD])
+            .style(theme::button_secondary)
+            .on_press_maybe(idle.then_some(Message::SettingsExport)),
+        button(text("Import…").size(typography::SIZE_BODY))
+            .padding([spacing::SM, spacing::MD])
+            .style(theme::button_secondary)
+            .on_press_maybe(idle.then_some(Message::SettingsImportPick)),
+    ]
+    .spacing(spacing::SM);
+
+    let mut content = column![
+        section_header(icons::FILE_EXPORT, "Transfer"),
+        Space::with_height(spacing::SM),
+        setting_label("Settings bundle"),
+        setting_description(
+            "One JSON file with every config section and your saved views, to set up another machine. API keys stay out of exports",
+        ),
+        Space::with_height(spacing::XS),
+        buttons,
+    ]
+    .spacing(spacing::XS);
+
+    if transfer.pending.is_some() {
+        content = content
+            .push(Space::with_height(spacing::SM))
+            .push(import_dialog(s));
+    }
+    content.into()
+}
+
+/// The bundle's sections to tick, with Import and Cancel
+fn import_dialog(s: &LoadedState) -> Element<'_, Message> {
+    let transfer = &s.settings_transfer;
+    let Some((path, bundle)) = &transfer.pending else {
+        return Space::with_height(0).into();
+    };
+
+    let mut sections = column![].spacing(spacing::XS);
+    for section in bundle.sections() {
+        sections = sections.push(
+            checkbox(section.label(), transfer.picked.contains(&section))
+                .text_size(typography::SIZE_BODY)
+                .on_toggle(move |_| Message::SettingsImportToggle(section)),
+        );
+    }
+
+    let actions = row![
+        Space::with_width(Length::Fill),
+        button(text("Cancel").size(typography::SIZE_BODY))
+            .padding([spacing::SM, spacing::MD])
+            .style(theme::button_ghost)
+            .on_press(Message::SettingsImportCancel),
+        button(text("Import selected").size(typography::SIZE_BODY))
+            .padding([spacing::SM, spacing::MD])
+            .style(theme::button_primary)
+            .on_press_maybe(
+                (!transfer.picked.is_empty()).then_some(Message::SettingsImportConfirm),
+            ),
+    ]
+    .spacing(spacing::SM)
+    .align_y(Alignment::Center);
+
+    container(
+        column![
+            setting_label("Import which settings?"),
+            text(format!(
+                "{} · exported {} by version {}",
+                path.file_name().unwrap_or_default().to_string_lossy(),
+                bundle.exported_at,
+                bundle.app_version
+            ))
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED),
+            Space::with_height(spacing::XS),
+            sections,
+            Space::with_height(spacing::XS),
+            setting_description("Ticked sections replace this machine's"),
+            actions,
+        ]
+        .spacing(spacing::XS),
+    )
+    .padding(spacing::MD)
+    .width(Length::Fill)
+    .style(|_| container::Style {
+        background: Some(color::SURFACE_ELEVATED.into()),
+        border: iced::Border {
+            color: color::BORDER,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
@@ -0,0 +1,478 @@
+//! Usage statistics pane - listening time, skipped tracks, identification
+//! success, scans, and the year in review.
+
+use iced::widget::{Space, button, column, container, row, scrollable, text};
+use iced::{Alignment, Element, Length};
+
+use chrono::Datelike;
+
+use crate::history::{MIN_PLAYS_FOR_RATE, SKIP_BEFORE, SkipRate};
+use crate::stats::wrapped::{self, TrackListening, Wrapped};
+use crate::stats::{Listening, ScanRun, UsageStats};
+use crate::ui::icons::{self, icon_sized, spinner_frame};
+use crate::ui::messages::Message;
+use crate::ui::state::{ActivePane, LoadedState, StatsState};
+use crate::ui::theme::{self, color, radius, spacing, typography};
+use crate::ui::views::helpers::filter_chip;
+
+/// Days shown in the per-day chart
+const CHART_DAYS: usize = 31;
+
+/// Scans shown in the scan chart
+const CHART_SCANS: usize = 20;
+
+/// Usage statistics pane
+pub fn stats_pane(s: &LoadedState) -> Element<'_, Message> {
+    let state = &s.stats;
+    if let Some(ref review) = state.wrapped {
+        return wrapped_pane(s, review);
+    }
+
+    let header = row![
+        column![
+            text("Stats")
+                .size(typography::SIZE_TITLE)
+                .color(color::TEXT_PRIMARY),
+            text("How you use Music Minder. Kept in the local database; nothing is sent anywhere")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(spacing::XS),
+        Space::with_width(Length::Fill),
+        header_button(
+            icons::GIFT,
+            "Year in review",
+            Message::StatsWrappedOpen(chrono::Local::now().year())
+        ),
+        header_button(icons::REFRESH, "Refresh", Message::StatsRefresh),
+        header_button(icons::FILE_EXPORT, "Export", Message::StatsExport),
+    ]
+    .spacing(spacing::SM)
+    .align_y(Alignment::Center);
+
+    let content = column![
+        range_chips(state),
+        summary(s),
+        card("Listening per day", day_chart(&state.stats.by_day)),
+        row![
+            card("Top artists", share_chart(&state.stats.by_artist)),
+            card("Top genres", share_chart(&state.stats.by_genre)),
+        ]
+        .spacing(spacing::MD),
+        card("Most skipped", skip_chart(&state.stats.most_skipped)),
+        card("Scans", scan_chart(&state.stats.scans)),
+    ]
+    .spacing(spacing::MD)
+    .padding([0, spacing::SM]);
+
+    column![
+        header,
+        Space::with_height(spacing::MD),
+        scrollable(content)
+            .id(ActivePane::Stats.scroll_id())
+            .on_scroll(|v| Message::PaneScrolled(ActivePane::Stats, v))
+            .height(Length::Fill),
+    ]
+    .into()
+}
+
+/// The year in review, in place of the period's figures
+fn wrapped_pane<'a>(s: &'a LoadedState, review: &'a Wrapped) -> Element<'a, Message> {
+    let state = &s.stats;
+
+    let header = row![
+        header_button(icons::CHEVRON_LEFT, "Stats", Message::StatsWrappedClose),
+        column![
+            text(format!("{} in Review", review.year))
+                .size(typography::SIZE_TITLE)
+                .color(color::TEXT_PRIMARY),
+            text("Your year from this computer's play history")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        ]
+        .spacing(spacing::XS),
+        Space::with_width(Length::Fill),
+        header_button(icons::FILE_EXPORT, "Export", Message::StatsWrappedExport),
+    ]
+    .spacing(spacing::MD)
+    .align_y(Alignment::Center);
+
+    let years: Vec<Element<Message>> = state
+        .wrapped_years
+        .iter()
+        .map(|&year| {
+            filter_chip(
+                year.to_string(),
+                year == review.year,
+                Message::StatsWrappedOpen(year),
+            )
+        })
+        .collect();
+    let mut year_row = row(years).spacing(spacing::XS).align_y(Alignment::Center);
+    if state.wrapped_loading {
+        year_row = year_row.push(
+            text(spinner_frame(s.animation_tick))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        );
+    }
+
+    let body: Element<Message> = if review.is_empty() {
+        text(format!("Nothing was played in {}", review.year))
+            .size(typography::SIZE_BODY)
+            .color(color::TEXT_MUTED)
+            .into()
+    } else {
+        let busiest = review.busiest_day.as_ref().map_or(String::new(), |day| {
+            format!(
+                "Busiest day {} ({})",
+                day.name,
+                wrapped::listening_time(day.seconds)
+            )
+        });
+        column![
+            row![
+                figure(
+                    "Listened",
+                    wrapped::listening_time(review.listened_secs),
+                    busiest
+                ),
+                figure(
+                    "Plays",
+                    review.plays.to_string(),
+                    format!("{} different tracks", review.tracks_played)
+                ),
+                figure(
+                    "New artists",
+                    review.discoveries.to_string(),
+                    "First heard this year".to_string()
+                ),
+                figure(
+                    "Library",
+                    format!("+{}", review.tracks_added),
+                    format!(
+                        "{} removed, {} tracks now",
+                        review.tracks_removed, review.library_tracks
+                    )
+                ),
+            ]
+            .spacing(spacing::MD),
+            row![
+                card("Top artists", share_chart(&review.top_artists)),
+                card("Top tracks", track_chart(&review.top_tracks)),
+            ]
+            .spacing(spacing::MD),
+            row![
+                card("Top genres", share_chart(&review.top_genres)),
+                card("New discoveries", share_chart(&review.top_discoveries)),
+            ]
+            .spacing(spacing::MD),
+        ]
+        .spacing(spacing::MD)
+        .into()
+    };
+
+    let content = column![year_row, body]
+        .spacing(spacing::MD)
+        .padding([0, spacing::SM]);
+
+    column![
+        header,
+        Space::with_height(spacing::MD),
+        scrollable(content)
+            .id(ActivePane::Stats.scroll_id())
+            .on_scroll(|v| Message::PaneScrolled(ActivePane::Stats, v))
+            .height(Length::Fill),
+    ]
+    .into()
+}
+
+fn header_button(icon: char, label: &'static str, on_press: Message) -> Element<'static, Message> {
+    button(
+        row![
+            icon_sized(icon, typography::SIZE_SMALL),
+            text(label).size(typography::SIZE_SMALL),
+        ]
+        .spacing(spacing::XS)
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::XS, spacing::MD])
+    .style(theme::button_secondary)
+    .on_press(on_press)
+    .into()
+}
+
+fn range_chips(state: &StatsState) -> Element<'_, Message> {
+    let ranges: [(&'static str, Option<u32>); 4] = [
+        ("7 days", Some(7)),
+        ("30 days", Some(30)),
+        ("Year", Some(365)),
+        ("All time", None),
+    ];
+    let chips: Vec<Element<Message>> = ranges
+        .into_iter()
+        .map(|(label, days)| {
+            filter_chip(label, state.days == days, Message::StatsRangeChanged(days))
+        })
+        .collect();
+    row(chips).spacing(spacing::XS).into()
+}
+
+/// Headline figures for the period
+fn summary(s: &LoadedState) -> Element<'_, Message> {
+    let state = &s.stats;
+    if state.loading {
+        return text(format!("{} Loading...", spinner_frame(s.animation_tick)))
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED)
+            .into();
+    }
+    let stats: &UsageStats = &state.stats;
+    let rates = &stats.identification;
+    let identified = match rates.success_rate() {
+        Some(rate) => format!("{:.0}%", rate * 100.0),
+        None => "—".to_string(),
+    };
+    let scan_average = match stats.scans.len() {
+        0 => "—".to_string(),
+        n => format_millis(stats.scans.iter().map(|s| s.duration_ms).sum::<i64>() / n as i64),
+    };
+
+    row![
+        figure(
+            "Listened",
+            format_secs(stats.listened_secs),
+            format!("{} plays", stats.plays)
+        ),
+        figure(
+            "Identified",
+            identified,
+            format!(
+                "{} of {} ({} no match, {} errors)",
+                rates.identified,
+                rates.total(),
+                rates.no_match,
+                rates.errors
+            ),
+        ),
+        figure(
+            "Average scan",
+            scan_average,
+            format!("{} scans", stats.scans.len())
+        ),
+    ]
+    .spacing(spacing::MD)
+    .into()
+}
+
+/// One headline figure with a caption
+fn figure(label: &'static str, value: String, caption: String) -> Element<'static, Message> {
+    panel(
+        column![
+            text(label)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+            text(value)
+                .size(typography::SIZE_TITLE)
+                .color(color::TEXT_PRIMARY),
+            text(caption)
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_SECONDARY),
+        ]
+        .spacing(spacing::XS)
+        .into(),
+    )
+}
+
+/// A titled panel
+fn card<'a>(title: &'static str, body: Element<'a, Message>) -> Element<'a, Message> {
+    panel(
+        column![
+            text(title)
+                .size(typography::SIZE_BODY)
+                .color(color::TEXT_PRIMARY),
+            body
+        ]
+        .spacing(spacing::SM)
+        .into(),
+    )
+}
+
+fn panel(content: Element<'_, Message>) -> Element<'_, Message> {
+    container(content)
+        .padding(spacing::MD)
+        .width(Length::Fill)
+        .style(|_| container::Style {
+            background: Some(color::SURFACE_ELEVATED.into()),
+            border: iced::Border {
+                color: color::BORDER,
+                width: 1.0,
+                radius: radius::MD.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+}
+
+/// The most recent days, one bar each
+fn day_chart(days: &[Listening]) -> Element<'_, Message> {
+    let shown = &days[days.len().saturating_sub(CHART_DAYS)..];
+    let max = shown.iter().map(|d| d.seconds).max().unwrap_or(0);
+    bars(
+        shown
+            .iter()
+            .map(|d| (d.name.clone(), d.seconds, format_secs(d.seconds)))
+            .collect(),
+        max,
+    )
+}
+
+/// Artists or genres, most listened first
+fn share_chart(shares: &[Listening]) -> Element<'_, Message> {
+    let max = shares.first().map_or(0, |s| s.seconds);
+    bars(
+        shares
+            .iter()
+            .map(|s| {
+                let value = format!("{} · {} plays", format_secs(s.seconds), s.plays);
+                (s.name.clone(), s.seconds, value)
+            })
+            .collect(),
+        max,
+    )
+}
+
+/// Tracks, most played first
+fn track_chart(tracks: &[TrackListening]) -> Element<'_, Message> {
+    let max = tracks.first().map_or(0, |t| t.plays);
+    bars(
+        tracks
+            .iter()
+            .map(|t| {
+                let value = format!("{} plays · {}", t.plays, format_secs(t.seconds));
+                (format!("{} - {}", t.title, t.artist), t.plays, value)
+            })
+            .collect(),
+        max,
+    )
+}
+
+/// Tracks, highest skip rate first
+fn skip_chart(tracks: &[SkipRate]) -> Element<'_, Message> {
+    let chart = bars(
+        tracks
+            .iter()
+            .map(|t| {
+                let share = (t.rate() * 1000.0).round() as i64;
+                let value = format!("{}% · {} of {} plays", share / 10, t.skips, t.plays);
+                (format!("{} - {}", t.title, t.artist), share, value)
+            })
+            .collect(),
+        tracks
+            .first()
+            .map_or(0, |t| (t.rate() * 1000.0).round() as i64),
+    );
+    column![
+        chart,
+        text(format!(
+            "Left before {}% of the track, among tracks played {} times or more",
+            (SKIP_BEFORE * 100.0) as u32,
+            MIN_PLAYS_FOR_RATE
+        ))
+        .size(typography::SIZE_TINY)
+        .color(color::TEXT_MUTED),
+    ]
+    .spacing(spacing::SM)
+    .into()
+}
+
+/// The most recent scans' durations, to spot a library getting slower
+fn scan_chart(scans: &[ScanRun]) -> Element<'_, Message> {
+    let shown = &scans[scans.len().saturating_sub(CHART_SCANS)..];
+    let max = shown.iter().map(|s| s.duration_ms).max().unwrap_or(0);
+    bars(
+        shown
+            .iter()
+            .map(|scan| {
+                let when = chrono::DateTime::parse_from_rfc3339(&scan.started_at)
+                    .map(|at| {
+                        at.with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|_| scan.started_at.clone());
+                let value = format!("{} · {} files", format_millis(scan.duration_ms), scan.files);
+                (when, scan.duration_ms, value)
+            })
+            .collect(),
+        max,
+    )
+}
+
+/// Labelled horizontal bars scaled to `max`
+fn bars(rows: Vec<(String, i64, String)>, max: i64) -> Element<'static, Message> {
+    if rows.is_empty() {
+        return text("Nothing recorded for this period")
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_MUTED)
+            .into();
+    }
+    let items: Vec<Element<'static, Message>> = rows
+        .into_iter()
+        .map(|(label, amount, value)| {
+            // Portions of 1000 so the longest bar fills the track
+            let filled = if max > 0 {
+                ((amount.max(0) * 1000) / max).clamp(1, 1000) as u16
+            } else {
+                1
+            };
+            let bar = row![
+                container(Space::with_height(8))
+                    .width(Length::FillPortion(filled))
+                    .style(|_| container::Style {
+                        background: Some(color::PRIMARY.into()),
+                        border: iced::Border {
+                            radius: radius::SM.into(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }),
+                Space::with_width(Length::FillPortion(1001 - filled)),
+            ];
+            row![
+                text(label)
+                    .size(typography::SIZE_SMALL)
+                    .color(color::TEXT_SECONDARY)
+                    .width(Length::Fixed(140.0)),
+                container(bar).width(Length::Fill),
+                text(value)
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_MUTED)
+                    .width(Length::Fixed(150.0)),
+            ]
+            .spacing(spacing::SM)
+            .align_y(Alignment::Center)
+            .into()
+        })
+        .collect();
+    column(items).spacing(spacing::XS).into()
+}
+
+/// "3h 12m", "12m", "45s"
+fn format_secs(secs: i64) -> String {
+    let (hours, minutes) = (secs / 3600, (secs % 3600) / 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{}s", secs.max(0)),
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/// "850 ms", "12.4 s", "3m 5s"
+fn format_millis(ms: i64) -> String {
+    match ms {
+        ..1000 => format!("{} ms", ms.max(0)),
+        1000..60_000 => format!("{:.1} s", ms as f64 / 1000.0),
+        _ => format!("{}m {}s", ms / 60_000, (ms % 60_000) / 1000),
+    }
+}
@@ -0,0 +1,279 @@
+//! Background tasks popover.
+//!
+//! The sidebar shows how many long-running operations are in progress;
+//! clicking it opens a panel listing each one's phase, progress and ETA with
+//! a cancel button, and below them the tag writes waiting for a file another
+//! program has open. The panel reads the task registry and the write queue
+//! on every frame, so it needs no messages of its own to stay current.
+
+use iced::widget::{
+    Space, button, column, container, mouse_area, progress_bar, row, text, tooltip,
+};
+use iced::{Alignment, Element, Length, Padding};
+
+use crate::tasks::{self, Progress};
+use crate::ui::icons::{self, icon_sized};
+use crate::ui::messages::Message;
+use crate::ui::state::LoadedState;
+use crate::ui::theme::{self, color, layout, radius, spacing, typography};
+use crate::write_queue::{self, Waiting};
+
+/// Popover width
+const PANEL_WIDTH: f32 = 320.0;
+
+/// Sidebar entry opening the popover (empty while nothing is running)
+pub fn background_tasks_indicator(s: &LoadedState, collapsed: bool) -> Element<'_, Message> {
+    let count = s.tasks.running().len();
+    let waiting = write_queue::len();
+    if count == 0 && waiting == 0 {
+        return Space::with_height(0).into();
+    }
+
+    let spinner = container(
+        text(icons::spinner_frame(s.animation_tick))
+            .size(typography::SIZE_TINY)
+            .color(color::PRIMARY),
+    )
+    .width(Length::Fixed(12.0))
+    .center_x(Length::Fixed(12.0));
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    let label = match (count, waiting) {
+        (0, _) => format!("{} write{} waiting", waiting, plural(waiting)),
+        (_, 0) => format!("{} background task{}", count, plural(count)),
+        _ => format!(
+            "{} background task{}, {} write{} waiting",
+            count,
+            plural(count),
+            waiting,
+            plural(waiting)
+        ),
+    };
+    let style = if s.tasks_popover_open {
+        theme::button_nav_active
+    } else {
+        theme::button_nav
+    };
+
+    if collapsed {
+        tooltip(
+            button(container(spinner).center_x(Length::Fill))
+                .padding(spacing::XS)
+                .width(Length::Fill)
+                .style(style)
+                .on_press(Message::TasksPopoverToggle),
+            text(label).size(typography::SIZE_SMALL),
+            tooltip::Position::Right,
+        )
+        .gap(spacing::SM as f32)
+        .into()
+    } else {
+        button(
+            row![
+                spinner,
+                text(label)
+                    .size(typography::SIZE_TINY)
+                    .color(color::TEXT_SECONDARY),
+            ]
+            .spacing(spacing::XS)
+            .align_y(Alignment::Center),
+        )
+        .padding([spacing::XS, 0])
+        .width(Length::Fill)
+        .style(style)
+        .on_press(Message::TasksPopoverToggle)
+        .into()
+    }
+}
+
+/// The open popover, if any, anchored above the player bar next to the sidebar
+pub fn background_tasks_popover(s: &LoadedState) -> Option<Element<'_, Message>> {
+    if !s.tasks_popover_open {
+        return None;
+    }
+
+    let running = s.tasks.running();
+    let mut list = column![
+        text("Background tasks")
+            .size(typography::SIZE_BODY)
+            .color(color::TEXT_PRIMARY),
+    ]
+    .spacing(spacing::MD);
+    if running.is_empty() {
+        list = list.push(
+            text("Nothing running")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        );
+    }
+    for task in running {
+        list = list.push(task_row(task));
+    }
+
+    let waiting = write_queue::waiting();
+    if !waiting.is_empty() {
+        list = list.push(
+            text("Waiting for files in use")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+        );
+        for write in waiting {
+            list = list.push(waiting_row(write));
+        }
+    }
+
+    let panel = container(list)
+        .width(Length::Fixed(PANEL_WIDTH))
+        .padding(spacing::MD)
+        .style(|_| container::Style {
+            background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+            border: iced::Border {
+                color: color::BORDER_SUBTLE,
+                width: 1.0,
+                radius: radius::MD.into(),
+            },
+            shadow: iced::Shadow {
+                color: iced::Color::from_rgba(0.0, 0.0, 0.0, 0.4),
+                offset: iced::Vector::new(0.0, 4.0),
+                blur_radius: 12.0,
+            },
+            ..Default::default()
+        });
+
+    let sidebar = if s.sidebar_collapsed {
+        layout::SIDEBAR_COLLAPSED
+    } else {
+        layout::SIDEBAR_WIDTH
+    };
+    // The panel swallows its own clicks, so only clicks outside it close
+    let positioned = container(mouse_area(panel).on_press(Message::Noop))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .align_y(iced::alignment::Vertical::Bottom)
+        .padding(Padding {
+            top: 0.0,
+            right: 0.0,
+            bottom: (layout::PLAYER_BAR_HEIGHT + spacing::SM) as f32,
+            left: (sidebar + spacing::SM) as f32,
+        });
+
+    Some(
+        mouse_area(positioned)
+            .on_press(Message::TasksPopoverClose)
+            .into(),
+    )
+}
+
+/// One task: label, phase, progress bar, counts and ETA, cancel button
+fn task_row(task: Progress) -> Element<'static, Message> {
+    let fraction = task.fraction();
+    let mut status = task.counts();
+    if let Some(eta) = task.eta() {
+        status.push_str(&format!(" · {} left", tasks::format_eta(eta)));
+    }
+
+    let cancel: Element<'static, Message> = if task.cancelled {
+        text("Cancelling…")
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED)
+            .into()
+    } else {
+        tooltip(
+            button(icon_sized(icons::XMARK, typography::SIZE_SMALL))
+                .padding([spacing::XS, spacing::SM])
+                .style(theme::button_ghost)
+                .on_press(Message::TaskCancel(task.id)),
+            text("Cancel").size(typography::SIZE_SMALL),
+            tooltip::Position::Left,
+        )
+        .into()
+    };
+
+    let mut info = column![
+        text(task.label)
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_PRIMARY),
+    ]
+    .spacing(2)
+    .width(Length::Fill);
+    if !task.phase.is_empty() {
+        info = info.push(
+            text(task.phase)
+                .size(typography::SIZE_TINY)
+                .color(color::TEXT_SECONDARY),
+        );
+    }
+    if let Some(fraction) = fraction {
+        info = info.push(
+            progress_bar(0.0..=1.0, fraction)
+                .height(Length::Fixed(4.0))
+                .style(theme::progress_bar_style),
+        );
+    }
+    info = info.push(
+        text(status)
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+    );
+
+    row![info, Space::with_width(spacing::SM), cancel]
+        .align_y(Alignment::Center)
+        .into()
+}
+
+/// One deferred write: file, what and when it's tried next, retry and
+/// discard buttons
+fn waiting_row(write: Waiting) -> Element<'static, Message> {
+    let name = write
+        .path
+        .file_name()
+        .unwrap_or(write.path.as_os_str())
+        .to_string_lossy()
+        .into_owned();
+    let status = if write.next_try.is_zero() {
+        format!("{} · trying again…", write.what)
+    } else {
+        format!(
+            "{} · {} attempt{}, next in {}",
+            write.what,
+            write.attempts,
+            if write.attempts == 1 { "" } else { "s" },
+            tasks::format_eta(write.next_try)
+        )
+    };
+
+    let info = column![
+        tooltip(
+            text(name)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_PRIMARY),
+            text(write.error).size(typography::SIZE_SMALL),
+            tooltip::Position::Top,
+        ),
+        text(status)
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+    ]
+    .spacing(2)
+    .width(Length::Fill);
+
+    let retry = tooltip(
+        button(icon_sized(icons::REFRESH, typography::SIZE_SMALL))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press(Message::WriteQueueRetry(write.id)),
+        text("Try now").size(typography::SIZE_SMALL),
+        tooltip::Position::Left,
+    );
+    let discard = tooltip(
+        button(icon_sized(icons::XMARK, typography::SIZE_SMALL))
+            .padding([spacing::XS, spacing::SM])
+            .style(theme::button_ghost)
+            .on_press(Message::WriteQueueDiscard(write.id)),
+        text("Don't write").size(typography::SIZE_SMALL),
+        tooltip::Position::Left,
+    );
+
+    row![info, Space::with_width(spacing::SM), retry, discard]
+        .align_y(Alignment::Center)
+        .into()
+}
@@ -1,18 +1,23 @@
 //! Track detail modal view.
 //!
 //! Shows detailed metadata for a single track, with ability to:
-//! - See all available metadata fields
+//! - See all available metadata fields, and where written ones came from
 //! - Identify which fields are missing/incomplete
 //! - Run fingerprint identification
 //! - See and apply enrichment results
+//! - See which tracks of the album are missing
+//! - See codec, encoder, true peak and tag sizes on the Technical tab
+//! - Set the album's cover from a dropped or pasted image, and undo it
 
-use iced::widget::{Space, button, column, container, row, scrollable, text};
+use iced::widget::{Space, button, column, container, row, scrollable, text, tooltip};
 use iced::{Alignment, Element, Length};
 
+use crate::metadata::{content, loudness};
 use crate::ui::icons::{self, icon_sized, spinner_frame};
 use crate::ui::messages::Message;
-use crate::ui::state::LoadedState;
+use crate::ui::state::{LibraryScope, LoadedState, TrackDetailTab};
 use crate::ui::theme::{self, color, radius, spacing, typography};
+use crate::ui::views::helpers::filter_chip;
 
 /// Track detail modal view
 pub fn track_detail_modal(s: &LoadedState) -> Option<Element<'_, Message>> {
@@ -22,21 +27,30 @@ pub fn track_detail_modal(s: &LoadedState) -> Option<Element<'_, Message>> {
     let content = column![
         // Header with close button
         modal_header(track),
-        Space::with_height(spacing::MD),
+        Space::with_height(spacing::SM),
+        tab_bar(s.track_detail.tab),
+        Space::with_height(spacing::SM),
         // Main content in scrollable area
-        scrollable(
-            column![
+        scrollable(match s.track_detail.tab {
+            TrackDetailTab::Overview => column![
                 // File info section
                 file_info_section(s, track),
                 Space::with_height(spacing::MD),
                 // Current metadata section
                 metadata_section(s, track),
                 Space::with_height(spacing::MD),
+                // Album completeness section
+                album_section(s),
+                Space::with_height(spacing::MD),
+                // Cover from a dropped or pasted image
+                cover_section(s, track),
+                Space::with_height(spacing::MD),
                 // Enrichment section
                 enrichment_section(s),
             ]
-            .spacing(spacing::SM)
-        )
+            .spacing(spacing::SM),
+            TrackDetailTab::Technical => column![technical_section(s)].spacing(spacing::SM),
+        })
         .height(Length::Fill),
         Space::with_height(spacing::MD),
         // Action buttons
@@ -86,6 +100,143 @@ fn modal_header(track: &crate::db::TrackWithMetadata) -> Element<'_, Message> {
     .into()
 }
 
+/// Overview / Technical tab chips
+fn tab_bar(active: TrackDetailTab) -> Element<'static, Message> {
+    row([
+        (TrackDetailTab::Overview, "Overview"),
+        (TrackDetailTab::Technical, "Technical"),
+    ]
+    .map(|(tab, label)| filter_chip(label, tab == active, Message::TrackDetailTab(tab))))
+    .spacing(spacing::XS)
+    .into()
+}
+
+/// Technical tab: codec, encoder, levels, lengths and tag layout
+fn technical_section(s: &LoadedState) -> Element<'_, Message> {
+    let detail = &s.track_detail;
+    let Some(info) = &detail.technical else {
+        let (message, message_color) = match &detail.technical_error {
+            Some(e) => (format!("Couldn't probe the file: {}", e), color::ERROR),
+            None => (
+                format!(
+                    "{} Decoding the file for true peak and length...",
+                    spinner_frame(s.animation_tick)
+                ),
+                color::TEXT_SECONDARY,
+            ),
+        };
+        return section_container(
+            "Technical",
+            icons::CHIP,
+            text(message)
+                .size(typography::SIZE_SMALL)
+                .color(message_color),
+        );
+    };
+
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "—".to_string());
+    let format = [
+        info.sample_rate.map(|sr| format!("{} Hz", sr)),
+        info.bit_depth.map(|b| format!("{}-bit", b)),
+        info.channels.map(|ch| format!("{} ch", ch)),
+        info.bitrate_kbps.map(|br| format!("{} kbps", br)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(", ");
+    let codec = column![
+        info_row_owned("Codec", info.codec.clone()),
+        info_row_owned("Profile", or_dash(info.profile.clone())),
+        info_row_owned("Encoder", or_dash(info.encoder.clone())),
+        info_row_owned("Settings", or_dash(info.encoder_settings.clone())),
+        info_row_owned("Format", or_dash((!format.is_empty()).then_some(format))),
+    ]
+    .spacing(spacing::XS);
+
+    let seconds = |ms: u64| format!("{}:{:06.3}", ms / 60_000, (ms % 60_000) as f64 / 1000.0);
+    let mut levels = column![
+        info_row_owned(
+            "True peak",
+            match (info.true_peak_db, info.stream_duration_ms) {
+                (Some(db), _) => format!("{:+.2} dBTP", db),
+                (None, Some(_)) => "Silent".to_string(),
+                (None, None) => "—".to_string(),
+            }
+        ),
+        info_row_owned("Stated", seconds(info.container_duration_ms)),
+        info_row_owned("Decoded", or_dash(info.stream_duration_ms.map(seconds))),
+    ]
+    .spacing(spacing::XS);
+    if info.true_peak_db.is_some_and(|db| db > 0.0) {
+        levels = levels.push(
+            text("Peaks above full scale: this file clips on playback")
+                .size(typography::SIZE_SMALL)
+                .color(color::WARNING),
+        );
+    }
+    if let Some(diff) = info.duration_mismatch_ms() {
+        levels = levels.push(
+            text(format!(
+                "The container's length is off by {:.1} s; seeking may land in the wrong place",
+                diff.unsigned_abs() as f64 / 1000.0
+            ))
+            .size(typography::SIZE_SMALL)
+            .color(color::WARNING),
+        );
+    }
+    if let Some(e) = &info.decode_error {
+        levels = levels.push(
+            text(format!("Couldn't decode: {}", e))
+                .size(typography::SIZE_SMALL)
+                .color(color::ERROR),
+        );
+    }
+
+    let mut tags = column![].spacing(spacing::XS);
+    for tag in &info.tags {
+        tags = tags.push(row![
+            text(tag.kind.clone())
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED)
+                .width(Length::Fixed(160.0)),
+            text(format_size(tag.bytes))
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_SECONDARY),
+        ]);
+    }
+    if info.tags.is_empty() {
+        tags = tags.push(
+            text("No tag blocks found")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        );
+    }
+    if info.pictures > 0 {
+        tags = tags.push(info_row_owned(
+            "Pictures",
+            format!("{} ({})", info.pictures, format_size(info.picture_bytes)),
+        ));
+    }
+
+    column![
+        section_container("Codec", icons::CHIP, codec),
+        section_container("Levels & Length", icons::CHART, levels),
+        section_container("Tag Blocks", icons::DATABASE, tags),
+    ]
+    .spacing(spacing::MD)
+    .into()
+}
+
+/// Byte count as B, KB or MB
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1_000 => format!("{} B", bytes),
+        1_000..1_000_000 => format!("{:.1} KB", bytes as f64 / 1_000.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_000_000.0),
+    }
+}
+
 /// File information section
 fn file_info_section<'a>(
     s: &LoadedState,
@@ -145,6 +296,17 @@ fn file_info_section<'a>(
             format!("{:.1} KB", full.file_size as f64 / 1_000.0)
         };
 
+        let track_gain_str = format!(
+            "{} / {}",
+            loudness::format_gain(full.loudness.track_gain),
+            loudness::format_peak(full.loudness.track_peak)
+        );
+        let album_gain_str = format!(
+            "{} / {}",
+            loudness::format_gain(full.loudness.album_gain),
+            loudness::format_peak(full.loudness.album_peak)
+        );
+
         let cover_art_str = if full.has_cover_art {
             "Yes ✓".to_string()
         } else {
@@ -163,8 +325,11 @@ fn file_info_section<'a>(
                 info_row_owned("Channels", channels_str),
                 info_row_owned("Bit Depth", bits_str),
                 info_row_owned("File Size", file_size_str),
+                info_row_owned("Track Gain / Peak", track_gain_str),
+                info_row_owned("Album Gain / Peak", album_gain_str),
                 info_row_owned("Cover Art", cover_art_str),
                 info_row("Path", &track.path),
+                file_actions(&track.path),
             ]
             .spacing(spacing::XS),
         )
@@ -211,6 +376,7 @@ fn file_info_section<'a>(
                 info_row_owned("Format", format_detail),
                 info_row_owned("Duration", duration_str),
                 info_row("Path", &track.path),
+                file_actions(&track.path),
             ]
             .spacing(spacing::XS),
         )
@@ -247,6 +413,11 @@ fn metadata_section(
             .unwrap_or_else(|| "—".to_string());
 
         let genre = full.genre.clone().unwrap_or_else(|| "—".to_string());
+        let language = full
+            .language
+            .as_deref()
+            .map_or_else(|| "—".to_string(), content::language_name);
+        let explicit = content::explicit_label(full.explicit).to_string();
         let composer = full.composer.clone().unwrap_or_else(|| "—".to_string());
         let comment = full.comment.clone().unwrap_or_else(|| "—".to_string());
 
@@ -289,6 +460,7 @@ fn metadata_section(
             .musicbrainz_track_id
             .clone()
             .unwrap_or_else(|| "—".to_string());
+        let acoustid = full.acoustid.id.clone().unwrap_or_else(|| "—".to_string());
 
         // Build the section with all fields
         section_container(
@@ -299,10 +471,12 @@ fn metadata_section(
                 text("Basic")
                     .size(typography::SIZE_SMALL)
                     .color(color::TEXT_MUTED),
-                metadata_row_owned("Title", title.clone(), full.title.is_none()),
-                metadata_row_owned("Artist", artist.clone(), full.artist.is_none()),
-                metadata_row_owned("Album", album.clone(), full.album.is_none()),
-                metadata_row_owned(
+                tagged_row(s, "title", "Title", title.clone(), full.title.is_none()),
+                tagged_row(s, "artist", "Artist", artist.clone(), full.artist.is_none()),
+                tagged_row(s, "album", "Album", album.clone(), full.album.is_none()),
+                tagged_row(
+                    s,
+                    "album_artist",
                     "Album Artist",
                     album_artist.clone(),
                     full.album_artist.is_none()
@@ -312,10 +486,24 @@ fn metadata_section(
                 text("Track Info")
                     .size(typography::SIZE_SMALL)
                     .color(color::TEXT_MUTED),
-                metadata_row_owned("Track #", track_str, full.track_number.is_none()),
-                metadata_row_owned("Disc #", disc_str, full.disc_number.is_none()),
-                metadata_row_owned("Year", year_str, full.year.is_none()),
-                metadata_row_owned("Genre", genre.clone(), full.genre.is_none()),
+                tagged_row(
+                    s,
+                    "track_number",
+                    "Track #",
+                    track_str,
+                    full.track_number.is_none()
+                ),
+                tagged_row(
+                    s,
+                    "disc_number",
+                    "Disc #",
+                    disc_str,
+                    full.disc_number.is_none()
+                ),
+                tagged_row(s, "year", "Year", year_str, full.year.is_none()),
+                tagged_row(s, "genre", "Genre", genre.clone(), full.genre.is_none()),
+                tagged_row(s, "language", "Language", language, full.language.is_none()),
+                tagged_row(s, "explicit", "Explicit", explicit, full.explicit.is_none()),
                 Space::with_height(spacing::XS),
                 // Additional info
                 text("Additional")
@@ -329,22 +517,30 @@ fn metadata_section(
                 text("MusicBrainz IDs")
                     .size(typography::SIZE_SMALL)
                     .color(color::TEXT_MUTED),
-                metadata_row_owned(
+                tagged_row(
+                    s,
+                    "musicbrainz_recording_id",
                     "Recording ID",
                     truncate_id(&mb_recording),
                     full.musicbrainz_recording_id.is_none()
                 ),
-                metadata_row_owned(
+                tagged_row(
+                    s,
+                    "musicbrainz_artist_id",
                     "Artist ID",
                     truncate_id(&mb_artist),
                     full.musicbrainz_artist_id.is_none()
                 ),
-                metadata_row_owned(
+                tagged_row(
+                    s,
+                    "musicbrainz_release_id",
                     "Release ID",
                     truncate_id(&mb_release),
                     full.musicbrainz_release_id.is_none()
                 ),
-                metadata_row_owned(
+                tagged_row(
+                    s,
+                    "musicbrainz_release_group_id",
                     "Rel. Group ID",
                     truncate_id(&mb_release_group),
                     full.musicbrainz_release_group_id.is_none()
@@ -354,6 +550,13 @@ fn metadata_section(
                     truncate_id(&mb_track),
                     full.musicbrainz_track_id.is_none()
                 ),
+                tagged_row(
+                    s,
+                    "acoustid_id",
+                    "AcoustID",
+                    truncate_id(&acoustid),
+                    full.acoustid.id.is_none()
+                ),
                 Space::with_height(spacing::XS),
                 // Quality
                 text("Quality")
@@ -376,9 +579,9 @@ fn metadata_section(
         } else {
             (
                 track.title.clone(),
-                track.artist_name.clone(),
-                track.album_name.clone(),
-                track.track_number.map(|n| n as u32),
+                track.artist_name.to_string(),
+                track.album_name.to_string(),
+                track.track_number,
             )
         };
 
@@ -423,6 +626,122 @@ fn truncate_id(id: &str) -> String {
     }
 }
 
+/// Album completeness: how many of the release's tracks are in the library
+fn album_section(s: &LoadedState) -> Element<'_, Message> {
+    let detail = &s.track_detail;
+    let mut content = column![].spacing(spacing::XS);
+
+    if detail.checking_album {
+        content = content.push(
+            text(format!(
+                "{} Checking album against MusicBrainz...",
+                spinner_frame(s.animation_tick)
+            ))
+            .size(typography::SIZE_SMALL)
+            .color(color::TEXT_SECONDARY),
+        );
+    } else if let Some(result) = &detail.completeness {
+        let summary_color = if result.is_complete() {
+            color::SUCCESS
+        } else {
+            color::WARNING
+        };
+        content = content.push(
+            text(result.summary())
+                .size(typography::SIZE_BODY)
+                .color(summary_color),
+        );
+        let multi_disc = result.multi_disc();
+        for track in &result.missing {
+            let position = if multi_disc {
+                format!("{}-{:02}", track.disc, track.position)
+            } else {
+                format!("{:02}", track.position)
+            };
+            content = content.push(info_row_owned(
+                "Missing",
+                format!("{} {}", position, track.title),
+            ));
+        }
+        content = content.push(
+            text(format!(
+                "Compared with {} on {}",
+                result.release_title,
+                result
+                    .checked_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d")
+            ))
+            .size(typography::SIZE_TINY)
+            .color(color::TEXT_MUTED),
+        );
+    } else {
+        content = content.push(
+            text("Not checked against MusicBrainz yet")
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        );
+    }
+
+    if let Some(note) = &detail.album_note {
+        content = content.push(
+            text(note)
+                .size(typography::SIZE_SMALL)
+                .color(color::TEXT_MUTED),
+        );
+    }
+
+    let label = if detail.completeness.is_some() {
+        "Check Again"
+    } else {
+        "Check Album"
+    };
+    content = content.push(
+        button(text(label).size(typography::SIZE_SMALL))
+            .padding([spacing::XS, spacing::MD])
+            .style(theme::button_secondary)
+            .on_press_maybe((!detail.checking_album).then_some(Message::TrackDetailCheckAlbum)),
+    );
+
+    section_container("Album", icons::COMPACT_DISC, content)
+}
+
+/// Where to drop or paste an image to set the album's cover, and undo the
+/// last one set
+fn cover_section<'a>(
+    s: &'a LoadedState,
+    track: &crate::db::TrackWithMetadata,
+) -> Element<'a, Message> {
+    let hint = if s.cover_setting {
+        text(format!(
+            "{} Setting the cover...",
+            spinner_frame(s.animation_tick)
+        ))
+        .color(color::TEXT_SECONDARY)
+    } else if s.file_hovering {
+        text("Drop the image to make it the album's cover").color(color::PRIMARY)
+    } else {
+        text("Drop an image file here, or copy one and press Ctrl+V, to make it the album's cover")
+            .color(color::TEXT_MUTED)
+    };
+    let mut content = column![hint.size(typography::SIZE_SMALL)].spacing(spacing::XS);
+
+    let scope = LibraryScope::album_of(track);
+    if s.cover_undo
+        .as_ref()
+        .is_some_and(|(album, _)| *album == scope)
+    {
+        content = content.push(
+            button(text("Put Back the Previous Cover").size(typography::SIZE_SMALL))
+                .padding([spacing::XS, spacing::MD])
+                .style(theme::button_secondary)
+                .on_press_maybe((!s.cover_setting).then_some(Message::CoverUndo)),
+        );
+    }
+
+    section_container("Cover", icons::PALETTE, content)
+}
+
 /// Enrichment results section
 fn enrichment_section(s: &LoadedState) -> Element<'_, Message> {
     let content: Element<'_, Message> = if s.track_detail.is_identifying {
@@ -513,6 +832,16 @@ fn enrichment_section(s: &LoadedState) -> Element<'_, Message> {
             diff_row_owned("Disc", disc_str),
             diff_row("Type", release_type),
             diff_row_owned("Genres", genres_str),
+            diff_row_owned(
+                "Language",
+                id.track.language.as_deref().map(content::language_name)
+            ),
+            diff_row_owned(
+                "Explicit",
+                id.track
+                    .explicit
+                    .map(|e| content::explicit_label(Some(e)).to_string())
+            ),
             // MusicBrainz IDs section
             if id.track.recording_id.is_some() {
                 Element::from(
@@ -584,7 +913,28 @@ fn action_buttons(s: &LoadedState) -> Element<'_, Message> {
 
     let can_write = s.track_detail.identification.is_some() && !s.track_detail.tags_written;
 
-    row![
+    let queue_row = match s.track_detail.track_index {
+        Some(idx) => row![
+            queue_button(icons::FORWARD, "Play Next", Message::PlayerPlayNext(idx)),
+            queue_button(icons::PLUS, "Add to Queue", Message::PlayerQueueTrack(idx)),
+            Space::with_width(spacing::MD),
+            queue_button(
+                icons::FORWARD,
+                "Play Album Next",
+                Message::PlayerPlayAlbumNext(idx)
+            ),
+            queue_button(
+                icons::PLUS,
+                "Add Album to Queue",
+                Message::PlayerQueueAlbum(idx)
+            ),
+        ]
+        .spacing(spacing::SM)
+        .align_y(Alignment::Center),
+        None => row![],
+    };
+
+    let main_row = row![
         // Identify button
         button(
             row![
@@ -626,7 +976,45 @@ fn action_buttons(s: &LoadedState) -> Element<'_, Message> {
             .style(theme::button_ghost)
             .on_press(Message::TrackDetailClose),
     ]
-    .align_y(Alignment::Center)
+    .align_y(Alignment::Center);
+
+    column![queue_row, main_row].spacing(spacing::SM).into()
+}
+
+/// Secondary button for the queue actions
+/// Show in folder / copy path buttons for the file information section
+fn file_actions(path: &str) -> Element<'static, Message> {
+    let path = std::path::PathBuf::from(path);
+    row![
+        queue_button(
+            icons::FOLDER_OPEN,
+            "Show in Folder",
+            Message::RevealInFolder(path.clone())
+        ),
+        queue_button(icons::COPY, "Copy Path", Message::CopyPath(path.clone())),
+        queue_button(
+            icons::COPY,
+            "Copy Relative Path",
+            Message::CopyRelativePath(path)
+        ),
+    ]
+    .spacing(spacing::SM)
+    .padding([spacing::XS, 0])
+    .into()
+}
+
+fn queue_button(icon: char, label: &'static str, msg: Message) -> Element<'static, Message> {
+    button(
+        row![
+            icon_sized(icon, typography::SIZE_SMALL).color(color::TEXT_SECONDARY),
+            Space::with_width(spacing::XS),
+            text(label).size(typography::SIZE_SMALL),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .padding([spacing::XS, spacing::MD])
+    .style(theme::button_secondary)
+    .on_press(msg)
     .into()
 }
 
@@ -721,6 +1109,45 @@ fn metadata_row_owned(
     .into()
 }
 
+/// A metadata row for a written tag field, with a tooltip saying where its
+/// value came from
+fn tagged_row(
+    s: &LoadedState,
+    field: &str,
+    label: &'static str,
+    value: String,
+    is_gap: bool,
+) -> Element<'static, Message> {
+    let row = metadata_row_owned(label, value, is_gap);
+    if is_gap {
+        return row;
+    }
+    let source = s
+        .track_detail
+        .provenance
+        .iter()
+        .find(|p| p.field == field)
+        .map(|p| p.describe())
+        .unwrap_or_else(|| "From the file's own tags".to_string());
+    tooltip(
+        row,
+        text(source).size(typography::SIZE_TINY),
+        tooltip::Position::Top,
+    )
+    .gap(spacing::XS)
+    .padding(spacing::XS)
+    .style(|_| container::Style {
+        background: Some(iced::Background::Color(color::SURFACE_ELEVATED)),
+        border: iced::Border {
+            color: color::BORDER_SUBTLE,
+            width: 1.0,
+            radius: radius::SM.into(),
+        },
+        ..Default::default()
+    })
+    .into()
+}
+
 /// A diff row showing new value from identification
 fn diff_row<'a>(label: &'a str, new_value: Option<&'a str>) -> Element<'a, Message> {
     let Some(value) = new_value else {
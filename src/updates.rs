@@ -0,0 +1,173 @@
+//! Update checks against GitHub releases.
+//!
+//! Asks the GitHub releases API for the latest release and compares its tag
+//! with this build's version. Nothing is downloaded or installed: the app
+//! shows the release notes and links to the release page. Checks run at
+//! startup when `network.check_for_updates` is on, or from Settings → About;
+//! `network.offline` refuses them like every other outgoing request.
+
+use serde::Deserialize;
+
+/// This build's version
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Latest published release (drafts and pre-releases are left out by GitHub)
+pub const RELEASES_URL: &str =
+    "https://api.github.com/repos/Hardcoreprawn/music-minder/releases/latest";
+
+/// GitHub rejects API requests without a user agent
+const USER_AGENT: &str = concat!("MusicMinder/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateError {
+    #[error("{0}")]
+    Offline(String),
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("GitHub answered {0}")]
+    Status(u16),
+    #[error("Release tag {0:?} isn't a version")]
+    BadTag(String),
+}
+
+/// The release as GitHub describes it
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    name: Option<String>,
+    body: Option<String>,
+    html_url: String,
+    published_at: Option<String>,
+}
+
+/// A published release
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// Version from the tag, without the leading "v"
+    pub version: String,
+    /// Release title, or the version when there's none
+    pub name: String,
+    /// Changelog (Markdown)
+    pub notes: String,
+    /// Release page with the downloads
+    pub url: String,
+    /// RFC 3339 publish time
+    pub published_at: Option<String>,
+}
+
+/// A version's numeric parts: "v1.2.3-beta" is [1, 2, 3]
+pub fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let core = version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['-', '+'])
+        .next()?;
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Whether `candidate` is a later version than `current`. Missing parts
+/// count as 0, so "1.2" and "1.2.0" are the same.
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    let (Some(mut candidate), Some(mut current)) =
+        (parse_version(candidate), parse_version(current))
+    else {
+        return false;
+    };
+    let len = candidate.len().max(current.len());
+    candidate.resize(len, 0);
+    current.resize(len, 0);
+    candidate > current
+}
+
+/// The latest release, newer or not
+pub async fn latest(url: &str) -> Result<Release, UpdateError> {
+    crate::enrichment::http::guard(url).map_err(|e| UpdateError::Offline(e.to_string()))?;
+    let response = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .build()?
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(UpdateError::Status(response.status().as_u16()));
+    }
+    let release: GithubRelease = response.json().await?;
+    let version = release.tag_name.trim_start_matches(['v', 'V']).to_string();
+    if parse_version(&version).is_none() {
+        return Err(UpdateError::BadTag(release.tag_name));
+    }
+    Ok(Release {
+        name: release
+            .name
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or_else(|| version.clone()),
+        version,
+        notes: release.body.unwrap_or_default(),
+        url: release.html_url,
+        published_at: release.published_at,
+    })
+}
+
+/// The latest release if it's newer than this build
+pub async fn check() -> Result<Option<Release>, UpdateError> {
+    check_at(RELEASES_URL, CURRENT_VERSION).await
+}
+
+/// [`check`] against another releases URL and version
+pub async fn check_at(url: &str, current: &str) -> Result<Option<Release>, UpdateError> {
+    let release = latest(url).await?;
+    tracing::info!(
+        latest = %release.version,
+        current,
+        "Checked for updates"
+    );
+    Ok(is_newer(&release.version, current).then_some(release))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{FixtureResponse, FixtureServer};
+
+    #[test]
+    fn test_version_comparison() {
+        assert_eq!(parse_version("v1.2.3-beta.1"), Some(vec![1, 2, 3]));
+        assert!(is_newer("v0.2.0", "0.1.7"));
+        assert!(is_newer("0.1.10", "0.1.7"));
+        assert!(!is_newer("0.1.7", "0.1.7"));
+        assert!(!is_newer("1.2", "1.2.0"));
+        assert!(!is_newer("0.1.6", "0.1.7"));
+        assert!(!is_newer("nightly", "0.1.7"));
+    }
+
+    #[tokio::test]
+    async fn test_check_reads_latest_release() {
+        let body = r#"{
+            "tag_name": "v0.2.0",
+            "name": "",
+            "body": "- Crossfade\n- Playlists",
+            "html_url": "https://github.com/Hardcoreprawn/music-minder/releases/tag/v0.2.0",
+            "published_at": "2026-09-01T12:00:00Z",
+            "draft": false,
+            "prerelease": false
+        }"#;
+        let server =
+            FixtureServer::start(vec![("/releases/latest", FixtureResponse::json(body))]).await;
+        let url = format!("{}/releases/latest", server.url());
+
+        let release = check_at(&url, "0.1.7").await.unwrap().unwrap();
+        assert_eq!(release.version, "0.2.0");
+        assert_eq!(release.name, "0.2.0");
+        assert!(release.notes.contains("Playlists"));
+        assert!(release.url.ends_with("/tag/v0.2.0"));
+
+        assert!(check_at(&url, "0.2.0").await.unwrap().is_none());
+
+        // Tests run offline, so the real API is never asked
+        assert!(matches!(
+            check().await.unwrap_err(),
+            UpdateError::Offline(_)
+        ));
+    }
+}
@@ -0,0 +1,315 @@
+//! Tag writes put off while their file is open in another program.
+//!
+//! A player holding a file open (on Windows, without sharing it for
+//! writing) makes a tag write fail with a sharing violation. Rather than
+//! reporting that OS error, [`write_or_defer`] keeps the write here when its
+//! failure is [`ErrorClass::LOCKED`] and [`retry_due`] tries it again: after
+//! [`FIRST_DELAY`], doubling up to [`MAX_DELAY`], or, when watching, as soon
+//! as the file can be opened for writing. Writes still blocked after
+//! [`GIVE_UP_AFTER`] fail for good.
+//!
+//! The queue lives in memory: writes still waiting when the app exits are
+//! dropped (and logged).
+
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use parking_lot::Mutex;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::enrichment::TrackIdentification;
+use crate::error::{Classify, ErrorClass, Failure};
+use crate::provenance::{self, ManualEditRules};
+use crate::{activity, metadata};
+
+/// Pause before the first retry
+pub const FIRST_DELAY: Duration = Duration::from_secs(5);
+
+/// Longest pause between retries
+pub const MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// How long a write may wait for its file before it fails
+pub const GIVE_UP_AFTER: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// A write and its bookkeeping (activity, provenance), run again on each
+/// retry; returns the number of fields written
+pub type Job = Box<dyn FnMut() -> BoxFuture<'static, anyhow::Result<usize>> + Send>;
+
+/// What became of a write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Written now, this many fields
+    Written(usize),
+    /// The file is in use; queued to try again
+    Deferred,
+}
+
+/// A queued write as listed in the UI
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waiting {
+    pub id: u64,
+    pub path: PathBuf,
+    /// What is being written ("Tags", ...)
+    pub what: &'static str,
+    /// Attempts so far, the first included
+    pub attempts: u32,
+    /// Until the next scheduled try; zero while it's being tried
+    pub next_try: Duration,
+    /// The last attempt's error
+    pub error: String,
+}
+
+/// A queued write that finished, one way or the other
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settled {
+    pub path: PathBuf,
+    pub what: &'static str,
+    pub result: Result<usize, Failure>,
+}
+
+struct Queued {
+    id: u64,
+    path: PathBuf,
+    what: &'static str,
+    /// Taken out while being tried
+    job: Option<Job>,
+    attempts: u32,
+    queued_at: Instant,
+    next_try: Instant,
+    error: String,
+}
+
+static QUEUE: Mutex<Vec<Queued>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Pause after `attempts` failed tries
+fn backoff(attempts: u32) -> Duration {
+    FIRST_DELAY
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_DELAY)
+}
+
+/// Run `job` and, when its file is in use, queue it to run again later
+/// instead of failing.
+pub async fn write_or_defer(
+    path: &Path,
+    what: &'static str,
+    mut job: impl FnMut() -> BoxFuture<'static, anyhow::Result<usize>> + Send + 'static,
+) -> anyhow::Result<Outcome> {
+    match job().await {
+        Ok(fields) => Ok(Outcome::Written(fields)),
+        Err(e) if e.class() == Some(ErrorClass::LOCKED) => {
+            tracing::info!(path = %path.display(), "File in use, writing later: {:#}", e);
+            let now = Instant::now();
+            QUEUE.lock().push(Queued {
+                id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+                path: path.to_path_buf(),
+                what,
+                job: Some(Box::new(job)),
+                attempts: 1,
+                queued_at: now,
+                next_try: now + backoff(1),
+                error: e.to_string(),
+            });
+            Ok(Outcome::Deferred)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Write a match's tags to `path`, leaving hand-edited fields alone, and
+/// record the write (activity, provenance); deferred while the file is in
+/// use
+pub async fn write_match(
+    pool: &SqlitePool,
+    path: &Path,
+    identification: &TrackIdentification,
+    options: metadata::WriteOptions2,
+    manual_edits: &ManualEditRules,
+) -> anyhow::Result<Outcome> {
+    let (pool, file) = (pool.clone(), path.to_path_buf());
+    let (identification, manual_edits) = (identification.clone(), manual_edits.clone());
+    write_or_defer(path, "Tags", move || {
+        let (pool, path) = (pool.clone(), file.clone());
+        let (identification, manual_edits) = (identification.clone(), manual_edits.clone());
+        let options = options.clone();
+        async move {
+            let (identified, _) =
+                provenance::guard_manual_edits(&pool, &path, &identification, &manual_edits).await;
+            let file = path.clone();
+            let written =
+                tokio::task::spawn_blocking(move || metadata::write(&file, &identified, &options))
+                    .await??;
+            activity::record_tags_written(&pool, &path, written.fields_updated).await;
+            provenance::record_identification(
+                &pool,
+                &path,
+                &written.fields_written,
+                &identification,
+            )
+            .await;
+            Ok(written.fields_updated)
+        }
+        .boxed()
+    })
+    .await
+}
+
+/// Whether another program holds `path` open so it can't be written
+pub fn is_locked(path: &Path) -> bool {
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(path)
+        .is_err_and(|e| e.class() == Some(ErrorClass::LOCKED))
+}
+
+/// Queued writes, oldest first
+pub fn waiting() -> Vec<Waiting> {
+    let now = Instant::now();
+    QUEUE
+        .lock()
+        .iter()
+        .map(|q| Waiting {
+            id: q.id,
+            path: q.path.clone(),
+            what: q.what,
+            attempts: q.attempts,
+            next_try: if q.job.is_some() {
+                q.next_try.saturating_duration_since(now)
+            } else {
+                Duration::ZERO
+            },
+            error: q.error.clone(),
+        })
+        .collect()
+}
+
+/// Number of queued writes
+pub fn len() -> usize {
+    QUEUE.lock().len()
+}
+
+/// Try a queued write at the next [`retry_due`]
+pub fn retry_now(id: u64) {
+    if let Some(q) = QUEUE.lock().iter_mut().find(|q| q.id == id) {
+        q.next_try = Instant::now();
+    }
+}
+
+/// Give up on a queued write; false if it's being tried or already gone
+pub fn discard(id: u64) -> bool {
+    let mut queue = QUEUE.lock();
+    let before = queue.len();
+    queue.retain(|q| q.id != id || q.job.is_none());
+    queue.len() < before
+}
+
+/// Try the queued writes that are due, and with `watch` those whose file
+/// was let go of since. Returns the writes that finished.
+pub async fn retry_due(watch: bool) -> Vec<Settled> {
+    let now = Instant::now();
+    let due: Vec<(u64, PathBuf, Job)> = {
+        let mut queue = QUEUE.lock();
+        queue
+            .iter_mut()
+            .filter(|q| q.job.is_some())
+            .filter(|q| q.next_try <= now || (watch && !is_locked(&q.path)))
+            .filter_map(|q| Some((q.id, q.path.clone(), q.job.take()?)))
+            .collect()
+    };
+
+    let mut settled = Vec::new();
+    for (id, path, mut job) in due {
+        let result = job().await;
+        let mut queue = QUEUE.lock();
+        let Some(pos) = queue.iter().position(|q| q.id == id) else {
+            continue;
+        };
+        let q = &mut queue[pos];
+        match result {
+            Err(e)
+                if e.class() == Some(ErrorClass::LOCKED)
+                    && q.queued_at.elapsed() < GIVE_UP_AFTER =>
+            {
+                q.attempts += 1;
+                q.next_try = Instant::now() + backoff(q.attempts);
+                q.error = e.to_string();
+                q.job = Some(job);
+            }
+            result => {
+                let q = queue.remove(pos);
+                if let Err(e) = &result {
+                    tracing::warn!(path = %path.display(), "Deferred write failed: {:#}", e);
+                }
+                settled.push(Settled {
+                    path,
+                    what: q.what,
+                    result: result.map_err(|e| Failure::of(&e)),
+                });
+            }
+        }
+    }
+    settled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicU32;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(1), FIRST_DELAY);
+        assert_eq!(backoff(2), FIRST_DELAY * 2);
+        assert_eq!(backoff(40), MAX_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_locked_writes_wait_and_others_fail() {
+        let path = PathBuf::from("/deferred/test.mp3");
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let outcome = write_or_defer(&path, "Tags", move || {
+            let counter = counter.clone();
+            async move {
+                // In use for the first two tries
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(anyhow::Error::from(std::io::Error::from(
+                        std::io::ErrorKind::ResourceBusy,
+                    )))
+                } else {
+                    Ok(3)
+                }
+            }
+            .boxed()
+        })
+        .await
+        .unwrap();
+        assert_eq!(outcome, Outcome::Deferred);
+        let id = waiting().iter().find(|w| w.path == path).unwrap().id;
+
+        retry_now(id);
+        assert!(retry_due(false).await.iter().all(|s| s.path != path));
+        let still = waiting().into_iter().find(|w| w.id == id).unwrap();
+        assert_eq!(still.attempts, 2);
+
+        retry_now(id);
+        let settled = retry_due(false).await;
+        let done = settled.iter().find(|s| s.path == path).unwrap();
+        assert_eq!(done.result, Ok(3));
+        assert!(waiting().iter().all(|w| w.id != id));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Not a lock: fails at once, nothing queued
+        let bad = PathBuf::from("/deferred/bad.mp3");
+        let failed = write_or_defer(&bad, "Tags", || {
+            async { Err(anyhow::anyhow!("unknown format")) }.boxed()
+        })
+        .await;
+        assert!(failed.is_err());
+        assert!(waiting().iter().all(|w| w.path != bad));
+    }
+}